@@ -0,0 +1,478 @@
+//! One time-ordered feed of what every workspace is doing.
+//!
+//! The event sink hands each backend event to [`observe`], which keeps turn starts and
+//! ends, tool calls, permission prompts and errors as [`ActivityEntry`]s and drops the
+//! rest (message deltas, usage updates). Entries are numbered, kept in memory for
+//! `get_activity`, broadcast as the `activity` event and written to the database on a
+//! background thread so the feed survives a restart.
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock};
+
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+
+use crate::backend::events::{AppServerEvent, SessionDisconnected};
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+use crate::types::{ActivityEntry, ActivityKind, ActivitySeverity};
+use crate::utils::now_ms;
+
+/// Entries kept in memory; the database keeps a few more.
+const CAPACITY: usize = 2000;
+const DEFAULT_LIMIT: usize = 200;
+const SUMMARY_MAX_CHARS: usize = 200;
+
+struct Feed {
+    entries: VecDeque<ActivityEntry>,
+    next_seq: u64,
+}
+
+static FEED: OnceLock<Mutex<Feed>> = OnceLock::new();
+static WRITER: OnceLock<Option<Sender<ActivityEntry>>> = OnceLock::new();
+
+fn feed() -> &'static Mutex<Feed> {
+    FEED.get_or_init(|| {
+        let entries: VecDeque<ActivityEntry> = storage::database_path()
+            .and_then(|path| match storage::read_activity(path, CAPACITY) {
+                Ok(entries) => Some(entries),
+                Err(err) => {
+                    tracing::warn!("failed to load the activity feed: {err}");
+                    None
+                }
+            })
+            .unwrap_or_default()
+            .into();
+        let next_seq = entries.back().map_or(1, |entry| entry.seq + 1);
+        Mutex::new(Feed { entries, next_seq })
+    })
+}
+
+fn persist(entry: ActivityEntry) {
+    let writer = WRITER.get_or_init(|| {
+        let path = storage::database_path()?.to_path_buf();
+        let (sender, receiver) = mpsc::channel::<ActivityEntry>();
+        std::thread::Builder::new()
+            .name("activity-writer".to_string())
+            .spawn(move || {
+                for entry in receiver {
+                    if let Err(err) = storage::append_activity(&path, &entry) {
+                        tracing::warn!("failed to record activity: {err}");
+                    }
+                }
+            })
+            .ok()?;
+        Some(sender)
+    });
+    if let Some(writer) = writer {
+        let _ = writer.send(entry);
+    }
+}
+
+fn string(params: &Value, key: &str) -> Option<String> {
+    params
+        .get(key)
+        .and_then(Value::as_str)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+fn shorten(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() <= SUMMARY_MAX_CHARS {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(SUMMARY_MAX_CHARS - 1).collect();
+    format!("{kept}…")
+}
+
+/// What a tool item is doing, from the `item` of `item/started` or `item/completed`;
+/// `None` for message and reasoning items.
+fn tool_label(item: &Value) -> Option<String> {
+    let kind = item.get("type").and_then(Value::as_str)?;
+    let label = match kind {
+        "commandExecution" => {
+            let tool = item
+                .get("command")
+                .and_then(Value::as_array)
+                .and_then(|command| command.first())
+                .and_then(Value::as_str)
+                .unwrap_or("tool");
+            let input = item.get("toolInput").unwrap_or(&Value::Null);
+            match input.get("command").and_then(Value::as_str) {
+                Some(command) => format!("{tool}: {command}"),
+                None => tool.to_string(),
+            }
+        }
+        "fileChange" => {
+            let paths: Vec<&str> = item
+                .get("changes")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|change| change.get("path").and_then(Value::as_str))
+                .collect();
+            format!("edit {}", paths.join(", "))
+        }
+        "mcpToolCall" => format!(
+            "{}/{}",
+            item.get("server").and_then(Value::as_str).unwrap_or(""),
+            item.get("tool").and_then(Value::as_str).unwrap_or("")
+        ),
+        "webSearch" => format!(
+            "web search: {}",
+            item.get("query").and_then(Value::as_str).unwrap_or("")
+        ),
+        "agentMessage" | "reasoning" | "userMessage" | "plan" => return None,
+        other => other.to_string(),
+    };
+    Some(label)
+}
+
+/// How the feed shows an event, or `None` for events it leaves out.
+pub(crate) fn describe(
+    method: &str,
+    params: &Value,
+) -> Option<(ActivityKind, ActivitySeverity, String)> {
+    use ActivityKind::{Error, Permission, Tool, Turn};
+    use ActivitySeverity::{Info, Warning};
+    let described = match method {
+        "turn/started" => (Turn, Info, "Turn started".to_string()),
+        "turn/completed" => {
+            if params.pointer("/turn/timedOut").and_then(Value::as_bool) == Some(true) {
+                (Turn, Warning, "Turn timed out".to_string())
+            } else {
+                (Turn, Info, "Turn completed".to_string())
+            }
+        }
+        "turn/retrying" => (
+            Turn,
+            Warning,
+            format!(
+                "Retrying the turn (attempt {})",
+                params.get("attempt").and_then(Value::as_u64).unwrap_or(1)
+            ),
+        ),
+        "turn/verification" => match params.get("status").and_then(Value::as_str) {
+            Some("passed") => (Turn, Info, "Verification passed".to_string()),
+            Some("failed") => (
+                Turn,
+                Warning,
+                format!(
+                    "Verification failed: {}",
+                    string(params, "command").unwrap_or_default()
+                ),
+            ),
+            _ => return None,
+        },
+        "turn/guardrailViolation" => {
+            let files = params
+                .get("files")
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len);
+            let reverted = params.get("reverted").and_then(Value::as_bool) == Some(true);
+            (
+                Error,
+                ActivitySeverity::Error,
+                format!(
+                    "Read-only guardrail violated: {files} file(s) changed{}",
+                    if reverted { " and reverted" } else { "" }
+                ),
+            )
+        }
+        "turn/permissionDenied" => {
+            let tools: Vec<&str> = params
+                .get("permissionDenials")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|denial| denial.get("toolName").and_then(Value::as_str))
+                .collect();
+            (
+                Permission,
+                Warning,
+                format!("Permission denied for {}", tools.join(", ")),
+            )
+        }
+        "item/started" => {
+            let label = tool_label(params.get("item")?)?;
+            (Tool, Info, label)
+        }
+        "item/completed" => {
+            let item = params.get("item")?;
+            let status = item.get("status").and_then(Value::as_str);
+            if !matches!(status, Some("failed" | "error")) {
+                return None;
+            }
+            (Tool, Warning, format!("{} failed", tool_label(item)?))
+        }
+        "item/permission/requestApproval" => (
+            Permission,
+            Warning,
+            format!(
+                "Waiting for permission to use {}",
+                string(params, "toolName").unwrap_or_default()
+            ),
+        ),
+        "item/permission/autoResolved" => {
+            let allowed = params.get("allowed").and_then(Value::as_bool) == Some(true);
+            (
+                Permission,
+                Info,
+                format!(
+                    "{} {} automatically",
+                    string(params, "toolName").unwrap_or_default(),
+                    if allowed { "allowed" } else { "denied" }
+                ),
+            )
+        }
+        "item/tool/requestUserInput" => (
+            Permission,
+            Warning,
+            "Waiting for an answer to a question".to_string(),
+        ),
+        "item/plan/pending" => (Permission, Warning, "Waiting for plan approval".to_string()),
+        "error" => (
+            Error,
+            ActivitySeverity::Error,
+            params
+                .pointer("/error/message")
+                .and_then(Value::as_str)
+                .unwrap_or("Unknown error")
+                .to_string(),
+        ),
+        "session/resourceWarning" => {
+            let megabytes = |key: &str| {
+                params
+                    .pointer(&format!("/warning/{key}"))
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0)
+                    / (1024 * 1024)
+            };
+            let restarting = params
+                .pointer("/warning/restarting")
+                .and_then(Value::as_bool)
+                == Some(true);
+            (
+                Error,
+                Warning,
+                format!(
+                    "Session using {} MB of its {} MB limit{}",
+                    megabytes("memoryBytes"),
+                    megabytes("limitBytes"),
+                    if restarting { "; restarting it" } else { "" }
+                ),
+            )
+        }
+        _ => return None,
+    };
+    Some(described)
+}
+
+fn push(
+    workspace_id: &str,
+    params: &Value,
+    method: &str,
+    (kind, severity, summary): (ActivityKind, ActivitySeverity, String),
+) -> ActivityEntry {
+    let turn_id = string(params, "turnId").or_else(|| {
+        params
+            .pointer("/turn/id")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    });
+    let mut feed = feed()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let entry = ActivityEntry {
+        seq: feed.next_seq,
+        timestamp: now_ms(),
+        workspace_id: workspace_id.to_string(),
+        thread_id: string(params, "threadId"),
+        turn_id,
+        kind,
+        severity,
+        summary: shorten(&summary),
+        method: method.to_string(),
+    };
+    feed.next_seq += 1;
+    if feed.entries.len() >= CAPACITY {
+        feed.entries.pop_front();
+    }
+    feed.entries.push_back(entry.clone());
+    drop(feed);
+    persist(entry.clone());
+    entry
+}
+
+/// Add an app-server event to the feed if it is one the feed shows.
+pub(crate) fn observe(event: &AppServerEvent) -> Option<ActivityEntry> {
+    let method = event.message.get("method").and_then(Value::as_str)?;
+    let params = event.message.get("params").unwrap_or(&Value::Null);
+    let described = describe(method, params)?;
+    Some(push(&event.workspace_id, params, method, described))
+}
+
+/// Add a thread's CLI process going away to the feed.
+pub(crate) fn observe_disconnect(event: &SessionDisconnected) -> ActivityEntry {
+    let (severity, summary) = if event.reconnected {
+        (ActivitySeverity::Warning, "Session restarted".to_string())
+    } else {
+        (
+            ActivitySeverity::Error,
+            match &event.error {
+                Some(error) => format!("Session disconnected: {error}"),
+                None => "Session disconnected".to_string(),
+            },
+        )
+    };
+    push(
+        &event.workspace_id,
+        &json!({ "threadId": event.thread_id }),
+        "session-disconnected",
+        (ActivityKind::Error, severity, summary),
+    )
+}
+
+/// Entries after `since`, or the newest ones without it, oldest first.
+fn query(
+    entries: &VecDeque<ActivityEntry>,
+    since: Option<u64>,
+    workspace_id: Option<&str>,
+    min_severity: Option<ActivitySeverity>,
+    limit: usize,
+) -> Vec<ActivityEntry> {
+    let matching = entries.iter().filter(|entry| {
+        since.is_none_or(|since| entry.seq > since)
+            && workspace_id.is_none_or(|id| entry.workspace_id == id)
+            && min_severity.is_none_or(|min| entry.severity >= min)
+    });
+    if since.is_some() {
+        return matching.take(limit).cloned().collect();
+    }
+    let mut newest: Vec<ActivityEntry> = matching.rev().take(limit).cloned().collect();
+    newest.reverse();
+    newest
+}
+
+/// The activity feed across workspaces, oldest first. With `since`, the entries after
+/// that `seq`, for polling; without it, the newest ones.
+#[tauri::command]
+pub(crate) async fn get_activity(
+    since: Option<u64>,
+    workspace_id: Option<String>,
+    min_severity: Option<ActivitySeverity>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ActivityEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_activity",
+            json!({
+                "since": since,
+                "workspaceId": workspace_id,
+                "minSeverity": min_severity,
+                "limit": limit,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, CAPACITY);
+    let feed = feed()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    Ok(query(
+        &feed.entries,
+        since,
+        workspace_id.as_deref(),
+        min_severity,
+        limit,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_the_events_it_keeps() {
+        let (kind, severity, summary) = describe(
+            "item/started",
+            &json!({ "item": {
+                "type": "commandExecution",
+                "command": ["Bash"],
+                "toolInput": { "command": "cargo test" },
+            } }),
+        )
+        .unwrap();
+        assert_eq!(kind, ActivityKind::Tool);
+        assert_eq!(severity, ActivitySeverity::Info);
+        assert_eq!(summary, "Bash: cargo test");
+
+        let (kind, severity, _) = describe(
+            "turn/completed",
+            &json!({ "turn": { "id": "turn-1", "timedOut": true } }),
+        )
+        .unwrap();
+        assert_eq!(
+            (kind, severity),
+            (ActivityKind::Turn, ActivitySeverity::Warning)
+        );
+        let (kind, severity, summary) =
+            describe("error", &json!({ "error": { "message": "boom" } })).unwrap();
+        assert_eq!(
+            (kind, severity),
+            (ActivityKind::Error, ActivitySeverity::Error)
+        );
+        assert_eq!(summary, "boom");
+
+        let message = json!({ "item": { "type": "agentMessage", "text": "" } });
+        assert!(describe("item/started", &message).is_none());
+        assert!(describe("item/agentMessage/delta", &json!({})).is_none());
+    }
+
+    #[test]
+    fn filters_by_cursor_workspace_and_severity() {
+        let entry = |seq: u64, workspace_id: &str, severity| ActivityEntry {
+            seq,
+            timestamp: seq as i64,
+            workspace_id: workspace_id.to_string(),
+            thread_id: None,
+            turn_id: None,
+            kind: ActivityKind::Turn,
+            severity,
+            summary: String::new(),
+            method: "turn/started".to_string(),
+        };
+        let entries: VecDeque<ActivityEntry> = VecDeque::from([
+            entry(1, "ws-1", ActivitySeverity::Info),
+            entry(2, "ws-2", ActivitySeverity::Error),
+            entry(3, "ws-1", ActivitySeverity::Warning),
+            entry(4, "ws-1", ActivitySeverity::Info),
+        ]);
+        let seqs =
+            |entries: Vec<ActivityEntry>| entries.iter().map(|entry| entry.seq).collect::<Vec<_>>();
+        assert_eq!(seqs(query(&entries, None, None, None, 2)), [3, 4]);
+        assert_eq!(seqs(query(&entries, Some(1), None, None, 2)), [2, 3]);
+        assert_eq!(
+            seqs(query(&entries, None, Some("ws-1"), None, 10)),
+            [1, 3, 4]
+        );
+        assert_eq!(
+            seqs(query(
+                &entries,
+                None,
+                None,
+                Some(ActivitySeverity::Warning),
+                10
+            )),
+            [2, 3]
+        );
+    }
+}
@@ -0,0 +1,493 @@
+//! Subagents: Markdown definitions under `<project>/.claude/agents` and
+//! `~/.claude/agents`, each a frontmatter block (`name`, `description`, `tools`,
+//! `model`, `color`) over the agent's system prompt.
+//!
+//! Agents are identified by their frontmatter `name`, falling back to the file name. A
+//! project agent hides a user agent of the same name, as in the CLI. Saving rewrites the
+//! whole file, so frontmatter keys the app does not know about are not kept.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+
+use crate::claude;
+use crate::claude_home::resolve_default_claude_home;
+use crate::remote_backend;
+use crate::slash_commands::parse_frontmatter;
+use crate::state::AppState;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum AgentScope {
+    Project,
+    User,
+}
+
+/// A subagent definition found on disk.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Subagent {
+    pub(crate) name: String,
+    pub(crate) scope: AgentScope,
+    pub(crate) path: String,
+    pub(crate) description: Option<String>,
+    /// `None` when `tools` is left out, which gives the agent every tool of the thread.
+    pub(crate) tools: Option<Vec<String>>,
+    /// `sonnet`, `opus`, `haiku`, `inherit` or a model id; `None` uses the CLI's default.
+    pub(crate) model: Option<String>,
+    pub(crate) color: Option<String>,
+    /// The system prompt below the frontmatter.
+    pub(crate) prompt: String,
+    /// A project agent of the same name is used instead.
+    pub(crate) shadowed: bool,
+}
+
+/// What `save_subagent` writes. `previous_name` names the agent being edited, so it can
+/// be renamed; without it a new agent is created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SubagentDraft {
+    pub(crate) name: String,
+    pub(crate) scope: AgentScope,
+    pub(crate) description: String,
+    #[serde(default)]
+    pub(crate) tools: Option<Vec<String>>,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) color: Option<String>,
+    pub(crate) prompt: String,
+    #[serde(default)]
+    pub(crate) previous_name: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct AgentDirs {
+    project: PathBuf,
+    user: Option<PathBuf>,
+}
+
+impl AgentDirs {
+    fn for_scope(&self, scope: AgentScope) -> Result<&Path, String> {
+        match scope {
+            AgentScope::Project => Ok(&self.project),
+            AgentScope::User => self
+                .user
+                .as_deref()
+                .ok_or_else(|| "could not find the user's Claude directory".to_string()),
+        }
+    }
+}
+
+/// Project agents first, then user agents, each by name.
+fn discover(dirs: &AgentDirs) -> Vec<Subagent> {
+    let mut project = scan_dir(&dirs.project, AgentScope::Project);
+    let mut user = dirs
+        .user
+        .as_deref()
+        .map(|dir| scan_dir(dir, AgentScope::User))
+        .unwrap_or_default();
+    project.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    user.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    for agent in &mut user {
+        agent.shadowed = project.iter().any(|other| other.name == agent.name);
+    }
+    project.extend(user);
+    project
+}
+
+fn scan_dir(dir: &Path, scope: AgentScope) -> Vec<Subagent> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut agents = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_md = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+        if !is_md || !path.is_file() {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let (frontmatter, body) = parse_frontmatter(&content);
+        let Some(name) = frontmatter.get("name").map(str::to_string).or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+        }) else {
+            continue;
+        };
+        agents.push(Subagent {
+            name,
+            scope,
+            path: path.to_string_lossy().to_string(),
+            description: frontmatter.get("description").map(str::to_string),
+            tools: frontmatter.get("tools").map(|_| frontmatter.list("tools")),
+            model: frontmatter.get("model").map(str::to_string),
+            color: frontmatter.get("color").map(str::to_string),
+            prompt: body.trim().to_string(),
+            shadowed: false,
+        });
+    }
+    agents
+}
+
+/// The CLI accepts lowercase letters, digits and hyphens.
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = name
+        .chars()
+        .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-');
+    if name.is_empty() || name.starts_with('-') || !valid {
+        return Err(format!(
+            "invalid agent name \"{name}\": use lowercase letters, digits and hyphens"
+        ));
+    }
+    Ok(())
+}
+
+fn render(draft: &SubagentDraft) -> String {
+    // Frontmatter values are single lines
+    let single_line = |value: &str| value.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut content = format!("---\nname: {}\n", draft.name);
+    content.push_str(&format!(
+        "description: {}\n",
+        single_line(&draft.description)
+    ));
+    if let Some(tools) = draft.tools.as_ref().filter(|tools| !tools.is_empty()) {
+        content.push_str(&format!("tools: {}\n", single_line(&tools.join(", "))));
+    }
+    for (key, value) in [("model", &draft.model), ("color", &draft.color)] {
+        if let Some(value) = value.as_deref().map(single_line).filter(|v| !v.is_empty()) {
+            content.push_str(&format!("{key}: {value}\n"));
+        }
+    }
+    content.push_str("---\n\n");
+    content.push_str(draft.prompt.trim());
+    content.push('\n');
+    content
+}
+
+fn save(dirs: &AgentDirs, draft: &SubagentDraft) -> Result<Subagent, String> {
+    validate_name(&draft.name)?;
+    if draft.description.trim().is_empty() {
+        return Err("an agent needs a description".to_string());
+    }
+    let dir = dirs.for_scope(draft.scope)?;
+    let existing = scan_dir(dir, draft.scope);
+    let editing = draft
+        .previous_name
+        .as_deref()
+        .map(|previous| {
+            existing
+                .iter()
+                .find(|agent| agent.name == previous)
+                .ok_or_else(|| format!("no agent named {previous}"))
+        })
+        .transpose()?;
+    if existing.iter().any(|agent| {
+        agent.name == draft.name && editing.is_none_or(|edited| edited.path != agent.path)
+    }) {
+        return Err(format!("an agent named {} already exists", draft.name));
+    }
+    let path = match editing {
+        Some(edited) if edited.name == draft.name => PathBuf::from(&edited.path),
+        _ => dir.join(format!("{}.md", draft.name)),
+    };
+    if path.exists() && editing.is_none_or(|edited| Path::new(&edited.path) != path) {
+        return Err(format!("{} already exists", path.display()));
+    }
+
+    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    let temp_path = path.with_extension("md.tmp");
+    fs::write(&temp_path, render(draft)).map_err(|err| err.to_string())?;
+    fs::rename(&temp_path, &path).map_err(|err| err.to_string())?;
+    if let Some(edited) = editing.filter(|edited| Path::new(&edited.path) != path) {
+        fs::remove_file(&edited.path).map_err(|err| err.to_string())?;
+    }
+    scan_dir(dir, draft.scope)
+        .into_iter()
+        .find(|agent| Path::new(&agent.path) == path)
+        .ok_or_else(|| format!("{} could not be read back", path.display()))
+}
+
+fn delete(dirs: &AgentDirs, scope: AgentScope, name: &str) -> Result<bool, String> {
+    let dir = dirs.for_scope(scope)?;
+    let Some(agent) = scan_dir(dir, scope)
+        .into_iter()
+        .find(|agent| agent.name == name)
+    else {
+        return Ok(false);
+    };
+    fs::remove_file(&agent.path).map_err(|err| err.to_string())?;
+    Ok(true)
+}
+
+async fn agent_dirs(state: &AppState, workspace_id: &str) -> Result<AgentDirs, String> {
+    let project = state
+        .workspaces
+        .lock()
+        .await
+        .get(workspace_id)
+        .map(|entry| PathBuf::from(&entry.path).join(".claude").join("agents"))
+        .ok_or("workspace not found")?;
+    Ok(AgentDirs {
+        project,
+        user: resolve_default_claude_home().map(|home| home.join("agents")),
+    })
+}
+
+async fn blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tokio::task::spawn_blocking(task)
+        .await
+        .map_err(|_| "subagent task failed".to_string())?
+}
+
+/// The workspace's subagents, project agents first.
+#[tauri::command]
+pub(crate) async fn list_subagents(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<Subagent>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_subagents",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let dirs = agent_dirs(&state, &workspace_id).await?;
+    blocking(move || Ok(discover(&dirs))).await
+}
+
+/// Create or edit a subagent definition file.
+#[tauri::command]
+pub(crate) async fn save_subagent(
+    workspace_id: String,
+    agent: SubagentDraft,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Subagent, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "save_subagent",
+            json!({ "workspaceId": workspace_id, "agent": agent }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let dirs = agent_dirs(&state, &workspace_id).await?;
+    blocking(move || save(&dirs, &agent)).await
+}
+
+#[tauri::command]
+pub(crate) async fn delete_subagent(
+    workspace_id: String,
+    scope: AgentScope,
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "delete_subagent",
+            json!({ "workspaceId": workspace_id, "scope": scope, "name": name }),
+        )
+        .await;
+    }
+    let dirs = agent_dirs(&state, &workspace_id).await?;
+    let deleted = blocking(move || delete(&dirs, scope, &name)).await?;
+    Ok(json!({ "deleted": deleted }))
+}
+
+/// Start a turn that hands `task` to the named subagent.
+#[tauri::command]
+pub(crate) async fn run_subagent(
+    workspace_id: String,
+    thread_id: String,
+    name: String,
+    task: String,
+    model: Option<String>,
+    access_mode: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "run_subagent",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "name": name,
+                "task": task,
+                "model": model,
+                "accessMode": access_mode,
+            }),
+        )
+        .await;
+    }
+
+    let task = task.trim().to_string();
+    if task.is_empty() {
+        return Err("describe the task for the subagent".to_string());
+    }
+    let dirs = agent_dirs(&state, &workspace_id).await?;
+    let known = blocking(move || Ok(discover(&dirs)))
+        .await?
+        .iter()
+        .any(|agent| agent.name == name);
+    if !known {
+        return Err(format!("no subagent named {name}"));
+    }
+    let text = format!("Use the {name} subagent for this task:\n\n{task}");
+    claude::send_user_message(
+        workspace_id,
+        thread_id,
+        text,
+        model,
+        None,
+        access_mode,
+        None,
+        None,
+        None,
+        None,
+        None,
+        state,
+        app,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dirs(name: &str) -> (PathBuf, AgentDirs) {
+        let base = std::env::temp_dir().join(format!("agents-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let dirs = AgentDirs {
+            project: base.join("project/.claude/agents"),
+            user: Some(base.join("home/.claude/agents")),
+        };
+        (base, dirs)
+    }
+
+    fn draft(name: &str) -> SubagentDraft {
+        SubagentDraft {
+            name: name.to_string(),
+            scope: AgentScope::Project,
+            description: "Reviews code.\nUse after edits.".to_string(),
+            tools: Some(vec!["Read".to_string(), "Bash(git diff:*)".to_string()]),
+            model: Some("sonnet".to_string()),
+            color: None,
+            prompt: "You are a careful reviewer.\n".to_string(),
+            previous_name: None,
+        }
+    }
+
+    #[test]
+    fn discovers_agents_and_their_configuration() {
+        let (base, dirs) = dirs("discover");
+        fs::create_dir_all(&dirs.project).unwrap();
+        fs::create_dir_all(dirs.user.as_ref().unwrap()).unwrap();
+        fs::write(
+            dirs.project.join("reviewer.md"),
+            "---\nname: code-reviewer\ndescription: Reviews code\ntools: Read, Grep\n\
+             model: opus\n---\n\nReview carefully.\n",
+        )
+        .unwrap();
+        fs::write(
+            dirs.user.as_ref().unwrap().join("code-reviewer.md"),
+            "---\nname: code-reviewer\ndescription: Mine\n---\nBe brief.",
+        )
+        .unwrap();
+        fs::write(
+            dirs.user.as_ref().unwrap().join("debugger.md"),
+            "Debug things.",
+        )
+        .unwrap();
+
+        let agents = discover(&dirs);
+        let summary: Vec<_> = agents
+            .iter()
+            .map(|agent| {
+                (
+                    agent.name.as_str(),
+                    agent.scope,
+                    agent.tools.clone(),
+                    agent.model.as_deref(),
+                    agent.shadowed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "code-reviewer",
+                    AgentScope::Project,
+                    Some(vec!["Read".to_string(), "Grep".to_string()]),
+                    Some("opus"),
+                    false
+                ),
+                ("code-reviewer", AgentScope::User, None, None, true),
+                ("debugger", AgentScope::User, None, None, false),
+            ]
+        );
+        assert_eq!(agents[0].prompt, "Review carefully.");
+
+        let _ = fs::remove_dir_all(base);
+    }
+
+    #[test]
+    fn saves_renames_and_deletes_agents() {
+        let (base, dirs) = dirs("save");
+
+        let created = save(&dirs, &draft("reviewer")).unwrap();
+        assert_eq!(
+            fs::read_to_string(&created.path).unwrap(),
+            "---\nname: reviewer\ndescription: Reviews code. Use after edits.\n\
+             tools: Read, Bash(git diff:*)\nmodel: sonnet\n---\n\nYou are a careful reviewer.\n"
+        );
+        assert_eq!(created.tools.as_deref().map(<[String]>::len), Some(2));
+        assert!(save(&dirs, &draft("reviewer"))
+            .unwrap_err()
+            .contains("already exists"));
+        assert!(save(&dirs, &draft("Bad Name")).is_err());
+
+        let renamed = save(
+            &dirs,
+            &SubagentDraft {
+                previous_name: Some("reviewer".to_string()),
+                tools: None,
+                ..draft("code-reviewer")
+            },
+        )
+        .unwrap();
+        assert!(renamed.path.ends_with("code-reviewer.md"));
+        assert_eq!(renamed.tools, None);
+        assert!(!Path::new(&created.path).exists());
+
+        assert!(delete(&dirs, AgentScope::Project, "code-reviewer").unwrap());
+        assert!(!delete(&dirs, AgentScope::Project, "code-reviewer").unwrap());
+
+        let _ = fs::remove_dir_all(base);
+    }
+}
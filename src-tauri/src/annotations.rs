@@ -0,0 +1,275 @@
+//! Review notes on agent output: a note, a star rating and a named bookmark per turn or
+//! message of a thread.
+//!
+//! Annotations are kept in the database rather than in the transcripts, which belong to
+//! the CLI. Resumed threads carry theirs as `annotations`, exports place them under the
+//! messages they were made on, and `search_annotations` finds them across workspaces.
+
+use std::collections::HashSet;
+
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::claude::emit_event;
+use crate::event_sink::TauriEventSink;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+use crate::types::{Annotation, AnnotationTarget};
+use crate::utils::now_ms;
+
+const MAX_NOTE_CHARS: usize = 10_000;
+const MAX_BOOKMARK_CHARS: usize = 80;
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 500;
+
+/// Trimmed text, or `None` when there is none.
+fn non_empty(
+    text: Option<String>,
+    field: &str,
+    max_chars: usize,
+) -> Result<Option<String>, String> {
+    let Some(text) = text.map(|text| text.trim().to_string()) else {
+        return Ok(None);
+    };
+    if text.chars().count() > max_chars {
+        return Err(format!(
+            "The {field} is longer than {max_chars} characters."
+        ));
+    }
+    Ok((!text.is_empty()).then_some(text))
+}
+
+/// Whether `annotation` passes the filters of `search_annotations`. Every term of the
+/// query has to appear in the note or the bookmark name, ignoring case.
+fn matches(
+    annotation: &Annotation,
+    terms: &[String],
+    min_rating: Option<u8>,
+    bookmarked: bool,
+) -> bool {
+    if bookmarked && annotation.bookmark.is_none() {
+        return false;
+    }
+    if let Some(min_rating) = min_rating {
+        if annotation.rating.unwrap_or(0) < min_rating {
+            return false;
+        }
+    }
+    let text = format!(
+        "{}\n{}",
+        annotation.bookmark.as_deref().unwrap_or_default(),
+        annotation.note.as_deref().unwrap_or_default()
+    )
+    .to_lowercase();
+    terms.iter().all(|term| text.contains(term.as_str()))
+}
+
+/// Set the note, rating and bookmark of a turn or message, replacing what it had.
+/// Clearing all three removes the annotation, and `null` is returned.
+#[tauri::command]
+pub(crate) async fn annotate(
+    workspace_id: String,
+    thread_id: String,
+    target: AnnotationTarget,
+    target_id: String,
+    note: Option<String>,
+    rating: Option<u8>,
+    bookmark: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<Annotation>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "annotate",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "target": target,
+                "targetId": target_id,
+                "note": note,
+                "rating": rating,
+                "bookmark": bookmark,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    if !state.workspaces.lock().await.contains_key(&workspace_id) {
+        return Err("workspace not found".to_string());
+    }
+    if target_id.trim().is_empty() {
+        return Err("An annotation needs the id of a turn or message.".to_string());
+    }
+    if rating.is_some_and(|rating| !(1..=5).contains(&rating)) {
+        return Err("Ratings go from 1 to 5 stars.".to_string());
+    }
+    let note = non_empty(note, "note", MAX_NOTE_CHARS)?;
+    let bookmark = non_empty(bookmark, "bookmark name", MAX_BOOKMARK_CHARS)?;
+
+    let annotation = if note.is_none() && rating.is_none() && bookmark.is_none() {
+        storage::delete_annotation(
+            &state.db_path,
+            &workspace_id,
+            &thread_id,
+            target,
+            &target_id,
+        )?;
+        None
+    } else {
+        let now = now_ms();
+        let created_at =
+            storage::read_annotations(&state.db_path, Some(&workspace_id), Some(&thread_id))?
+                .into_iter()
+                .find(|existing| existing.target == target && existing.target_id == target_id)
+                .map_or(now, |existing| existing.created_at);
+        let annotation = Annotation {
+            workspace_id: workspace_id.clone(),
+            thread_id: thread_id.clone(),
+            target,
+            target_id: target_id.clone(),
+            note,
+            rating,
+            bookmark,
+            created_at,
+            updated_at: now,
+        };
+        storage::save_annotation(&state.db_path, &annotation)?;
+        Some(annotation)
+    };
+    emit_event(
+        &TauriEventSink::new(app),
+        &workspace_id,
+        "thread/annotated",
+        json!({
+            "threadId": thread_id,
+            "target": target,
+            "targetId": target_id,
+            "annotation": annotation,
+        }),
+    );
+    Ok(annotation)
+}
+
+/// A workspace's annotations, or one thread's, most recently changed first.
+#[tauri::command]
+pub(crate) async fn list_annotations(
+    workspace_id: String,
+    thread_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<Annotation>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_annotations",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    storage::read_annotations(&state.db_path, Some(&workspace_id), thread_id.as_deref())
+}
+
+/// Annotations whose note or bookmark contains every word of `query`, optionally only
+/// bookmarks or those rated at least `min_rating`, most recently changed first.
+#[tauri::command]
+pub(crate) async fn search_annotations(
+    query: Option<String>,
+    workspace_ids: Option<Vec<String>>,
+    min_rating: Option<u8>,
+    bookmarked: Option<bool>,
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<Annotation>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "search_annotations",
+            json!({
+                "query": query,
+                "workspaceIds": workspace_ids,
+                "minRating": min_rating,
+                "bookmarked": bookmarked,
+                "limit": limit,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let terms: Vec<String> = query
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect();
+    let workspace_ids: Option<HashSet<String>> = workspace_ids.map(|ids| ids.into_iter().collect());
+    let limit = limit
+        .map_or(DEFAULT_LIMIT, |limit| limit as usize)
+        .clamp(1, MAX_LIMIT);
+    let bookmarked = bookmarked.unwrap_or(false);
+    Ok(storage::read_annotations(&state.db_path, None, None)?
+        .into_iter()
+        .filter(|annotation| {
+            workspace_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&annotation.workspace_id))
+        })
+        .filter(|annotation| matches(annotation, &terms, min_rating, bookmarked))
+        .take(limit)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotation(note: Option<&str>, rating: Option<u8>, bookmark: Option<&str>) -> Annotation {
+        Annotation {
+            workspace_id: "ws-1".to_string(),
+            thread_id: "thread-1".to_string(),
+            target: AnnotationTarget::Message,
+            target_id: "message-1".to_string(),
+            note: note.map(str::to_string),
+            rating,
+            bookmark: bookmark.map(str::to_string),
+            created_at: 1,
+            updated_at: 1,
+        }
+    }
+
+    #[test]
+    fn matches_terms_in_note_or_bookmark() {
+        let terms = |query: &str| -> Vec<String> {
+            query.split_whitespace().map(str::to_lowercase).collect()
+        };
+        let reviewed = annotation(Some("Missed the Windows path case"), Some(2), Some("paths"));
+        assert!(matches(&reviewed, &terms("windows PATHS"), None, false));
+        assert!(!matches(&reviewed, &terms("windows linux"), None, false));
+        assert!(matches(&reviewed, &[], Some(2), true));
+        assert!(!matches(&reviewed, &[], Some(3), false));
+
+        let note_only = annotation(Some("fine"), None, None);
+        assert!(!matches(&note_only, &[], None, true));
+        assert!(!matches(&note_only, &[], Some(1), false));
+    }
+
+    #[test]
+    fn trims_and_limits_text() {
+        assert_eq!(
+            non_empty(Some("  keep  ".to_string()), "note", 10),
+            Ok(Some("keep".to_string()))
+        );
+        assert_eq!(non_empty(Some("   ".to_string()), "note", 10), Ok(None));
+        assert_eq!(non_empty(None, "note", 10), Ok(None));
+        assert!(non_empty(Some("x".repeat(11)), "note", 10).is_err());
+    }
+}
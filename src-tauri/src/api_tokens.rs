@@ -0,0 +1,431 @@
+//! Tokens for the daemon's remote surfaces, and the audit log of what they did.
+//!
+//! Besides the shared `--token`, which acts as an operator, tokens can be issued per
+//! integration with a role: observers may watch (the event feed, metrics, the REST
+//! API's reads), operators may also act (start threads, send messages, interrupt
+//! turns). A token's secret is returned once when it is issued and only its SHA-256
+//! is kept, so a lost secret means issuing a new token. Every request on the event
+//! feed and the REST API, allowed or refused, is recorded in the audit log with the
+//! token that made it; metrics scrapes are not, since they come every few seconds.
+
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, State};
+use uuid::Uuid;
+
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+use crate::types::{ApiToken, AuditEntry, TokenRole};
+use crate::utils::now_ms;
+
+const SECRET_PREFIX: &str = "ccm_";
+const SHARED_TOKEN_NAME: &str = "daemon token";
+/// `lastUsedAt` is written back at most this often per token.
+const LAST_USED_GRANULARITY_MS: i64 = 60_000;
+const DEFAULT_AUDIT_LIMIT: usize = 200;
+const MAX_AUDIT_LIMIT: usize = 5_000;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Issued tokens by the hash of their secret, mirrored into the app database.
+#[derive(Default)]
+struct TokenStore {
+    path: Option<PathBuf>,
+    tokens: Vec<(String, ApiToken)>,
+}
+
+static TOKENS: OnceLock<Mutex<TokenStore>> = OnceLock::new();
+
+fn with_store<T>(f: impl FnOnce(&mut TokenStore) -> T) -> T {
+    let mut guard = TOKENS
+        .get_or_init(|| Mutex::new(TokenStore::default()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut guard)
+}
+
+/// Load issued tokens and persist changes to the database at `path`.
+pub(crate) fn load_tokens(path: PathBuf) {
+    let tokens = storage::read_api_tokens(&path).unwrap_or_else(|err| {
+        tracing::warn!("failed to load API tokens: {err}");
+        Vec::new()
+    });
+    with_store(|store| {
+        store.tokens = tokens;
+        store.path = Some(path);
+    });
+}
+
+fn persist(store: &TokenStore, hash: &str, token: &ApiToken) -> Result<(), String> {
+    match &store.path {
+        Some(path) => storage::save_api_token(path, hash, token),
+        None => Ok(()),
+    }
+}
+
+fn hash(secret: &str) -> String {
+    Sha256::digest(secret.trim().as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn is_live(token: &ApiToken, now: i64) -> bool {
+    token.revoked_at.is_none() && token.expires_at.is_none_or(|expires| expires > now)
+}
+
+/// Who made a request on a remote surface.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Caller {
+    /// `None` for the shared `--token`.
+    pub(crate) token_id: Option<String>,
+    pub(crate) name: String,
+    pub(crate) role: TokenRole,
+}
+
+/// Why a request was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Denial {
+    /// No token, or one that is unknown, revoked or expired.
+    Unauthenticated,
+    /// A valid token whose role does not allow the action.
+    Forbidden,
+}
+
+/// The caller presenting `secret`: the daemon's shared token, or a live issued one.
+pub(crate) fn authenticate(shared: Option<&str>, secret: &str, now: i64) -> Option<Caller> {
+    let secret = secret.trim();
+    if shared.is_some_and(|shared| constant_time_eq(shared, secret)) {
+        return Some(Caller {
+            token_id: None,
+            name: SHARED_TOKEN_NAME.to_string(),
+            role: TokenRole::Operator,
+        });
+    }
+    if !secret.starts_with(SECRET_PREFIX) {
+        return None;
+    }
+    let hash = hash(secret);
+    with_store(|store| {
+        let index = store
+            .tokens
+            .iter()
+            .position(|(stored, token)| constant_time_eq(stored, &hash) && is_live(token, now))?;
+        let (_, token) = &mut store.tokens[index];
+        let stale = token
+            .last_used_at
+            .is_none_or(|used| now - used >= LAST_USED_GRANULARITY_MS);
+        if stale {
+            token.last_used_at = Some(now);
+            if let Some(path) = store.path.clone() {
+                let (hash, token) = store.tokens[index].clone();
+                off_request_path(move || {
+                    if let Err(err) = storage::save_api_token(&path, &hash, &token) {
+                        tracing::warn!("failed to record use of API token {}: {err}", token.id);
+                    }
+                });
+            }
+        }
+        let (_, token) = &store.tokens[index];
+        Some(Caller {
+            token_id: Some(token.id.clone()),
+            name: token.name.clone(),
+            role: token.role,
+        })
+    })
+}
+
+/// Authenticate `secret` and check that its role allows `action`, recording the
+/// outcome in the audit log.
+pub(crate) fn authorize(
+    shared: Option<&str>,
+    secret: Option<&str>,
+    needed: TokenRole,
+    surface: &str,
+    action: &str,
+) -> Result<Caller, Denial> {
+    let now = now_ms();
+    let caller = secret.and_then(|secret| authenticate(shared, secret, now));
+    let outcome = match &caller {
+        None => Err(Denial::Unauthenticated),
+        Some(caller) if caller.role < needed => Err(Denial::Forbidden),
+        Some(caller) => Ok(caller.clone()),
+    };
+    record_audit(AuditEntry {
+        timestamp: now,
+        token_id: caller.as_ref().and_then(|caller| caller.token_id.clone()),
+        token_name: caller.as_ref().map(|caller| caller.name.clone()),
+        surface: surface.to_string(),
+        action: action.to_string(),
+        allowed: outcome.is_ok(),
+        reason: match outcome {
+            Ok(_) => None,
+            Err(Denial::Unauthenticated) => Some("missing or invalid token".to_string()),
+            Err(Denial::Forbidden) => Some(match needed {
+                TokenRole::Observer => "needs the observer role".to_string(),
+                TokenRole::Operator => "needs the operator role".to_string(),
+            }),
+        },
+    });
+    outcome
+}
+
+fn record_audit(entry: AuditEntry) {
+    if !entry.allowed {
+        tracing::info!(
+            "refused {} on {} for {}",
+            entry.action,
+            entry.surface,
+            entry.token_name.as_deref().unwrap_or("an unknown token")
+        );
+    }
+    let Some(path) = with_store(|store| store.path.clone()) else {
+        return;
+    };
+    off_request_path(move || {
+        if let Err(err) = storage::append_audit_entry(&path, &entry) {
+            tracing::warn!("failed to write the audit log: {err}");
+        }
+    });
+}
+
+/// Requests are authenticated on the runtime, so their database writes run on the
+/// blocking pool instead. Outside a runtime `write` runs right away.
+fn off_request_path(write: impl FnOnce() + Send + 'static) {
+    match tokio::runtime::Handle::try_current() {
+        Ok(runtime) => drop(runtime.spawn_blocking(write)),
+        Err(_) => write(),
+    }
+}
+
+/// Compare without short-circuiting so response timing does not leak the secret.
+pub(crate) fn constant_time_eq(expected: &str, provided: &str) -> bool {
+    let expected = expected.as_bytes();
+    let provided = provided.as_bytes();
+    if expected.is_empty() || expected.len() != provided.len() {
+        return false;
+    }
+    expected
+        .iter()
+        .zip(provided)
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+/// A newly issued token with its secret, which is never shown again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IssuedApiToken {
+    pub(crate) token: ApiToken,
+    pub(crate) secret: String,
+}
+
+fn issue(
+    name: &str,
+    role: TokenRole,
+    expires_in_days: Option<u32>,
+    now: i64,
+) -> Result<IssuedApiToken, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name the token after what will use it.".to_string());
+    }
+    let secret = format!(
+        "{SECRET_PREFIX}{}{}",
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    );
+    let token = ApiToken {
+        id: Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        role,
+        created_at: now,
+        expires_at: expires_in_days
+            .filter(|days| *days > 0)
+            .map(|days| now + i64::from(days) * DAY_MS),
+        last_used_at: None,
+        revoked_at: None,
+    };
+    let hash = hash(&secret);
+    with_store(|store| {
+        persist(store, &hash, &token)?;
+        store.tokens.push((hash, token.clone()));
+        Ok(IssuedApiToken { token, secret })
+    })
+}
+
+fn revoke(id: &str, now: i64) -> Result<ApiToken, String> {
+    with_store(|store| {
+        let index = store
+            .tokens
+            .iter()
+            .position(|(_, token)| token.id == id)
+            .ok_or_else(|| format!("API token not found: {id}"))?;
+        let mut revoked = store.tokens[index].1.clone();
+        revoked.revoked_at.get_or_insert(now);
+        persist(store, &store.tokens[index].0, &revoked)?;
+        store.tokens[index].1 = revoked.clone();
+        Ok(revoked)
+    })
+}
+
+fn list() -> Vec<ApiToken> {
+    with_store(|store| {
+        store
+            .tokens
+            .iter()
+            .map(|(_, token)| token.clone())
+            .collect()
+    })
+}
+
+/// Issued tokens, revoked and expired ones included, oldest first.
+#[tauri::command]
+pub(crate) async fn list_api_tokens(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ApiToken>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "list_api_tokens", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    Ok(list())
+}
+
+/// Issue a token for the daemon's remote surfaces. The response carries the secret,
+/// which is not stored and can't be shown again.
+#[tauri::command]
+pub(crate) async fn issue_api_token(
+    name: String,
+    role: TokenRole,
+    expires_in_days: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<IssuedApiToken, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "issue_api_token",
+            json!({ "name": name, "role": role, "expiresInDays": expires_in_days }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    issue(&name, role, expires_in_days, now_ms())
+}
+
+/// Revoke a token; requests presenting it are refused from now on.
+#[tauri::command]
+pub(crate) async fn revoke_api_token(
+    id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ApiToken, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "revoke_api_token", json!({ "id": id }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    revoke(&id, now_ms())
+}
+
+/// The newest audit entries, for one token or all of them, newest first.
+#[tauri::command]
+pub(crate) async fn list_api_audit_log(
+    token_id: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<AuditEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_api_audit_log",
+            json!({ "tokenId": token_id, "limit": limit }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let limit = limit
+        .unwrap_or(DEFAULT_AUDIT_LIMIT)
+        .clamp(1, MAX_AUDIT_LIMIT);
+    let path = state.db_path.clone();
+    tokio::task::spawn_blocking(move || storage::read_audit_log(&path, token_id.as_deref(), limit))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issues_checks_and_revokes_tokens() {
+        let now = 1_700_000_000_000;
+        let observer = issue("portal", TokenRole::Observer, Some(30), now).unwrap();
+        assert!(observer.secret.starts_with(SECRET_PREFIX));
+        assert_eq!(observer.token.expires_at, Some(now + 30 * DAY_MS));
+        assert!(issue("  ", TokenRole::Observer, None, now).is_err());
+
+        let caller = authenticate(None, &observer.secret, now).unwrap();
+        assert_eq!(caller.role, TokenRole::Observer);
+        assert_eq!(caller.token_id.as_deref(), Some(observer.token.id.as_str()));
+        let listed = list();
+        let stored = listed
+            .iter()
+            .find(|token| token.id == observer.token.id)
+            .unwrap();
+        assert_eq!(stored.last_used_at, Some(now));
+        assert!(authenticate(None, &observer.secret, now + 31 * DAY_MS).is_none());
+        assert!(authenticate(None, "ccm_guess", now).is_none());
+
+        let shared = authenticate(Some("s3cret"), "s3cret", now).unwrap();
+        assert_eq!(shared.role, TokenRole::Operator);
+        assert!(shared.token_id.is_none());
+
+        revoke(&observer.token.id, now + 1).unwrap();
+        assert!(authenticate(None, &observer.secret, now + 2).is_none());
+        assert!(revoke("missing", now).is_err());
+    }
+
+    #[test]
+    fn roles_gate_actions() {
+        let observer = issue("dashboard", TokenRole::Observer, None, now_ms()).unwrap();
+        let operator = issue("bot", TokenRole::Operator, None, now_ms()).unwrap();
+        let check = |secret: Option<&str>, needed| {
+            authorize(None, secret, needed, "rest", "send_user_message").map(|c| c.name)
+        };
+        assert_eq!(
+            check(Some(&observer.secret), TokenRole::Observer),
+            Ok("dashboard".to_string())
+        );
+        assert_eq!(
+            check(Some(&observer.secret), TokenRole::Operator),
+            Err(Denial::Forbidden)
+        );
+        assert_eq!(
+            check(Some(&operator.secret), TokenRole::Operator),
+            Ok("bot".to_string())
+        );
+        assert_eq!(
+            check(None, TokenRole::Observer),
+            Err(Denial::Unauthenticated)
+        );
+    }
+
+    #[test]
+    fn comparison_is_exact() {
+        assert!(constant_time_eq("abc", "abc"));
+        assert!(!constant_time_eq("abc", "abd"));
+        assert!(!constant_time_eq("abc", "abcd"));
+        assert!(!constant_time_eq("", ""));
+    }
+}
@@ -0,0 +1,354 @@
+//! Observe Claude sessions started outside the monitor.
+//!
+//! Finds `claude` processes in the process table, maps their working directory to a
+//! workspace, and pairs each with the transcript the CLI is writing. Attaching tails
+//! that transcript read-only; nothing is spawned and no input is sent.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::tail;
+use crate::transcripts::resolve_project_dir;
+use crate::types::WorkspaceEntry;
+
+/// A `claude` process found in the process table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClaudeProcess {
+    pub(crate) pid: u32,
+    pub(crate) command: String,
+    pub(crate) cwd: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExternalSession {
+    pub(crate) pid: u32,
+    pub(crate) workspace_id: String,
+    pub(crate) cwd: PathBuf,
+    /// The transcript the process is most likely writing, if one was found.
+    pub(crate) session_id: Option<String>,
+    pub(crate) transcript_path: Option<PathBuf>,
+    pub(crate) last_activity_at: Option<i64>,
+    pub(crate) attached: bool,
+}
+
+/// Whether a process command line is an interactive Claude CLI. Processes driven over
+/// stream-json (the monitor's own, or other SDK clients) are not something to observe.
+pub(crate) fn is_interactive_claude(command: &str) -> bool {
+    let mut args = command.split_whitespace();
+    let Some(program) = args.next() else {
+        return false;
+    };
+    let program_name = Path::new(program)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let is_claude = match program_name.as_str() {
+        "claude" | "claude.exe" => true,
+        // npm installs run the CLI as `node .../@anthropic-ai/claude-code/cli.js`
+        "node" | "node.exe" => args
+            .next()
+            .is_some_and(|script| script.contains("claude-code") && script.ends_with("cli.js")),
+        _ => false,
+    };
+    is_claude
+        && !command.contains("stream-json")
+        && !command
+            .split_whitespace()
+            .any(|arg| arg == "-p" || arg == "--print")
+}
+
+/// Parse `ps -axo pid=,args=` output.
+pub(crate) fn parse_process_table(output: &str) -> Vec<(u32, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            let (pid, command) = line.split_once(char::is_whitespace)?;
+            Some((pid.parse().ok()?, command.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn process_cwd(pid: u32) -> Option<PathBuf> {
+    if let Ok(path) = std::fs::read_link(format!("/proc/{pid}/cwd")) {
+        return Some(path);
+    }
+    // macOS has no procfs; lsof prints the cwd as an `n<path>` field
+    let output = std::process::Command::new("lsof")
+        .args(["-a", "-p", &pid.to_string(), "-d", "cwd", "-Fn"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix('n'))
+        .map(PathBuf::from)
+}
+
+#[cfg(unix)]
+pub(crate) fn list_claude_processes() -> Vec<ClaudeProcess> {
+    let Ok(output) = std::process::Command::new("ps")
+        .args(["-axo", "pid=,args="])
+        .output()
+    else {
+        return Vec::new();
+    };
+    parse_process_table(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .filter(|(_, command)| is_interactive_claude(command))
+        .map(|(pid, command)| ClaudeProcess {
+            pid,
+            command,
+            cwd: process_cwd(pid),
+        })
+        .collect()
+}
+
+/// Reading another process's working directory needs platform APIs the monitor does
+/// not use on Windows yet, so nothing is detected there.
+#[cfg(not(unix))]
+pub(crate) fn list_claude_processes() -> Vec<ClaudeProcess> {
+    Vec::new()
+}
+
+/// The workspace whose path most specifically contains `cwd`.
+pub(crate) fn match_workspace<'a>(
+    cwd: &Path,
+    workspaces: &'a [WorkspaceEntry],
+) -> Option<&'a WorkspaceEntry> {
+    workspaces
+        .iter()
+        .filter(|entry| cwd.starts_with(&entry.path))
+        .max_by_key(|entry| entry.path.len())
+}
+
+/// Transcripts in `project_dir`, most recently written first.
+fn recent_transcripts(project_dir: &Path) -> Vec<(String, PathBuf, i64)> {
+    let Ok(entries) = std::fs::read_dir(project_dir) else {
+        return Vec::new();
+    };
+    let mut transcripts: Vec<(String, PathBuf, i64)> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("jsonl"))
+        .filter_map(|path| {
+            let session_id = path.file_stem()?.to_string_lossy().to_string();
+            let modified = std::fs::metadata(&path)
+                .and_then(|meta| meta.modified())
+                .ok()?
+                .duration_since(UNIX_EPOCH)
+                .ok()?
+                .as_millis() as i64;
+            Some((session_id, path, modified))
+        })
+        .collect();
+    transcripts.sort_by(|a, b| b.2.cmp(&a.2));
+    transcripts
+}
+
+/// Pair processes with workspaces and transcripts. Several processes in one workspace
+/// are matched to that many of its most recently written transcripts, skipping
+/// sessions the monitor runs itself.
+pub(crate) fn find_external_sessions(
+    processes: &[ClaudeProcess],
+    workspaces: &[WorkspaceEntry],
+    monitor_sessions: &HashSet<String>,
+) -> Vec<ExternalSession> {
+    let mut by_workspace: HashMap<&str, Vec<(&ClaudeProcess, &Path)>> = HashMap::new();
+    for process in processes {
+        let Some(cwd) = process.cwd.as_deref() else {
+            continue;
+        };
+        if let Some(entry) = match_workspace(cwd, workspaces) {
+            by_workspace
+                .entry(entry.id.as_str())
+                .or_default()
+                .push((process, cwd));
+        }
+    }
+
+    let mut sessions = Vec::new();
+    for entry in workspaces {
+        let Some(found) = by_workspace.remove(entry.id.as_str()) else {
+            continue;
+        };
+        let mut transcripts = resolve_project_dir(entry)
+            .map(|dir| recent_transcripts(&dir))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(session_id, _, _)| !monitor_sessions.contains(session_id));
+        for (process, cwd) in found {
+            let transcript = transcripts.next();
+            sessions.push(ExternalSession {
+                pid: process.pid,
+                workspace_id: entry.id.clone(),
+                cwd: cwd.to_path_buf(),
+                session_id: transcript.as_ref().map(|(id, _, _)| id.clone()),
+                transcript_path: transcript.as_ref().map(|(_, path, _)| path.clone()),
+                last_activity_at: transcript.as_ref().map(|(_, _, modified)| *modified),
+                attached: false,
+            });
+        }
+    }
+    sessions
+}
+
+async fn discover(state: &AppState) -> Result<Vec<ExternalSession>, String> {
+    let workspaces: Vec<WorkspaceEntry> = state.workspaces.lock().await.values().cloned().collect();
+    let mut monitor_sessions = HashSet::new();
+    let sessions: Vec<_> = state.sessions.lock().await.values().cloned().collect();
+    for session in sessions {
+        monitor_sessions.extend(session.persistent_sessions.lock().await.keys().cloned());
+    }
+    let mut found = tokio::task::spawn_blocking(move || {
+        find_external_sessions(&list_claude_processes(), &workspaces, &monitor_sessions)
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+    for session in &mut found {
+        if let Some(session_id) = &session.session_id {
+            session.attached = tail::is_attached(state, &session.workspace_id, session_id).await;
+        }
+    }
+    Ok(found)
+}
+
+#[tauri::command]
+pub(crate) async fn list_external_sessions(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(&*state, app, "list_external_sessions", json!({}))
+            .await;
+    }
+
+    let sessions = discover(&state).await?;
+    serde_json::to_value(sessions).map_err(|err| err.to_string())
+}
+
+/// Follow an external session's transcript. The session id doubles as the thread id,
+/// the same as for sessions the workspace watcher discovers.
+#[tauri::command]
+pub(crate) async fn attach_external_session(
+    workspace_id: String,
+    session_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "attach_external_session",
+            json!({ "workspaceId": workspace_id, "sessionId": session_id }),
+        )
+        .await;
+    }
+
+    let running = state.sessions.lock().await.get(&workspace_id).cloned();
+    if let Some(session) = running {
+        if session.has_persistent_session(&session_id).await {
+            return Err("this session is running in the monitor".to_string());
+        }
+    }
+    tail::start_tail(&state, app, workspace_id, session_id, false, true).await
+}
+
+#[tauri::command]
+pub(crate) async fn detach_external_session(
+    workspace_id: String,
+    session_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "detach_external_session",
+            json!({ "workspaceId": workspace_id, "sessionId": session_id }),
+        )
+        .await;
+    }
+
+    let detached = tail::stop_tail(&state, &workspace_id, &session_id, true).await;
+    Ok(json!({ "detached": detached }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings};
+
+    fn workspace(id: &str, path: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: path.to_string(),
+            claude_bin: None,
+            model: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            archived_at: None,
+            pinned: false,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    #[test]
+    fn recognizes_interactive_claude_processes() {
+        assert!(is_interactive_claude("claude"));
+        assert!(is_interactive_claude(
+            "/opt/homebrew/bin/claude --resume abc"
+        ));
+        assert!(is_interactive_claude(
+            "node /usr/lib/node_modules/@anthropic-ai/claude-code/cli.js"
+        ));
+        assert!(!is_interactive_claude(
+            "claude --output-format stream-json --input-format stream-json"
+        ));
+        assert!(!is_interactive_claude("claude -p summarize"));
+        assert!(!is_interactive_claude(
+            "/Applications/Claude.app/Contents/MacOS/Claude"
+        ));
+        assert!(!is_interactive_claude("vim claude.md"));
+    }
+
+    #[test]
+    fn parses_ps_output() {
+        let rows = parse_process_table("  101 claude --continue\n 2002 /bin/zsh -l\nbogus\n");
+        assert_eq!(
+            rows,
+            vec![
+                (101, "claude --continue".to_string()),
+                (2002, "/bin/zsh -l".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_the_most_specific_workspace() {
+        let workspaces = vec![
+            workspace("repo", "/work/repo"),
+            workspace("api", "/work/repo/packages/api"),
+        ];
+        let matched = match_workspace(Path::new("/work/repo/packages/api/src"), &workspaces);
+        assert_eq!(matched.map(|entry| entry.id.as_str()), Some("api"));
+        let matched = match_workspace(Path::new("/work/repo/docs"), &workspaces);
+        assert_eq!(matched.map(|entry| entry.id.as_str()), Some("repo"));
+        assert!(match_workspace(Path::new("/work/other"), &workspaces).is_none());
+        // Prefix matching is per path component
+        assert!(match_workspace(Path::new("/work/repository"), &workspaces).is_none());
+    }
+}
@@ -0,0 +1,410 @@
+//! Files and images sent with a user message.
+//!
+//! Images (PNG, JPEG, GIF, WebP, recognized by their bytes rather than their name) go to
+//! the CLI as base64 content blocks. Other files are mentioned as `@path`, which the CLI
+//! expands itself, unless the message asks for them to be embedded; text files outside
+//! the workspace are always embedded, since the CLI may not be allowed to read them.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::types::ImageAttachment;
+
+/// The API's limit for one image.
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_IMAGES: usize = 20;
+/// Larger text files are mentioned by path instead of embedded.
+const MAX_EMBED_BYTES: u64 = 256 * 1024;
+/// How much of a file is read to tell its type.
+const SNIFF_BYTES: usize = 8 * 1024;
+
+/// Something attached to a message in the composer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum Attachment {
+    /// A file dropped or picked from disk; relative paths are in the workspace.
+    File {
+        path: String,
+        /// Put a text file's content in the message instead of mentioning its path.
+        #[serde(default)]
+        embed: bool,
+    },
+    /// Pasted or dragged image data, as a `data:` URL.
+    Data {
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(rename = "dataUrl")]
+        data_url: String,
+    },
+}
+
+/// The `images` of `send_user_message`: paths or `data:` URLs.
+pub(crate) fn from_images(images: Vec<String>) -> Vec<Attachment> {
+    images
+        .into_iter()
+        .map(|image| image.trim().to_string())
+        .filter(|image| !image.is_empty())
+        .map(|image| {
+            if image.starts_with("data:") {
+                Attachment::Data {
+                    name: None,
+                    data_url: image,
+                }
+            } else {
+                Attachment::File {
+                    path: image,
+                    embed: false,
+                }
+            }
+        })
+        .collect()
+}
+
+/// A user message with its attachments resolved.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct PreparedMessage {
+    pub(crate) text: String,
+    pub(crate) images: Vec<ImageAttachment>,
+}
+
+impl PreparedMessage {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.images.is_empty()
+    }
+}
+
+/// Resolve `attachments` against the workspace at `root`: images become content blocks,
+/// and file mentions and embedded files are appended to `text`.
+pub(crate) fn prepare(
+    text: &str,
+    attachments: Vec<Attachment>,
+    root: &Path,
+) -> Result<PreparedMessage, String> {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let mut images = Vec::new();
+    let mut mentions = Vec::new();
+    let mut embedded = Vec::new();
+    for attachment in attachments {
+        match attachment {
+            Attachment::Data { name, data_url } => {
+                let label = name.as_deref().unwrap_or("pasted image");
+                images.push(decode_data_url(&data_url).map_err(|err| format!("{label}: {err}"))?);
+            }
+            Attachment::File { path, embed } => match resolve_file(&root, &path, embed)? {
+                ResolvedFile::Image(image) => images.push(image),
+                ResolvedFile::Mention(mention) => mentions.push(mention),
+                ResolvedFile::Embedded(block) => embedded.push(block),
+            },
+        }
+    }
+    if images.len() > MAX_IMAGES {
+        return Err(format!(
+            "{} images attached; at most {MAX_IMAGES} can be sent at once",
+            images.len()
+        ));
+    }
+    let mut message = text.trim().to_string();
+    for section in [mentions.join(" "), embedded.join("\n\n")] {
+        if section.is_empty() {
+            continue;
+        }
+        if !message.is_empty() {
+            message.push_str("\n\n");
+        }
+        message.push_str(&section);
+    }
+    Ok(PreparedMessage {
+        text: message,
+        images,
+    })
+}
+
+enum ResolvedFile {
+    Image(ImageAttachment),
+    Mention(String),
+    Embedded(String),
+}
+
+fn resolve_file(root: &Path, path: &str, embed: bool) -> Result<ResolvedFile, String> {
+    // Image URLs from the composer are left for Claude to fetch
+    if path.starts_with("http://") || path.starts_with("https://") {
+        return Ok(ResolvedFile::Mention(path.to_string()));
+    }
+    let requested = PathBuf::from(path.trim());
+    let absolute = if requested.is_absolute() {
+        requested
+    } else {
+        root.join(requested)
+    };
+    let absolute = absolute
+        .canonicalize()
+        .map_err(|err| format!("can't attach {path}: {err}"))?;
+    let metadata = absolute
+        .metadata()
+        .map_err(|err| format!("can't attach {path}: {err}"))?;
+    if !metadata.is_file() {
+        return Err(format!("can't attach {path}: not a file"));
+    }
+    let head = read_head(&absolute).map_err(|err| format!("can't attach {path}: {err}"))?;
+    let kind = sniff(&head);
+
+    if let Some(media_type) = kind.image_type() {
+        if metadata.len() > MAX_IMAGE_BYTES {
+            return Err(format!(
+                "{path} is {}; images can be at most {}",
+                format_size(metadata.len()),
+                format_size(MAX_IMAGE_BYTES)
+            ));
+        }
+        let bytes =
+            std::fs::read(&absolute).map_err(|err| format!("can't attach {path}: {err}"))?;
+        return Ok(ResolvedFile::Image(ImageAttachment {
+            media_type: media_type.to_string(),
+            data: STANDARD.encode(bytes),
+        }));
+    }
+
+    let inside = absolute.strip_prefix(root).ok();
+    let small_text = kind == FileKind::Text && metadata.len() <= MAX_EMBED_BYTES;
+    if embed && !small_text {
+        return Err(if kind == FileKind::Text {
+            format!(
+                "{path} is {}; files can be embedded up to {}",
+                format_size(metadata.len()),
+                format_size(MAX_EMBED_BYTES)
+            )
+        } else {
+            format!("{path} is not a text file and can't be embedded")
+        });
+    }
+    if embed || (inside.is_none() && small_text) {
+        let content = std::fs::read_to_string(&absolute)
+            .map_err(|err| format!("can't attach {path}: {err}"))?;
+        let shown = inside.unwrap_or(&absolute);
+        return Ok(ResolvedFile::Embedded(embedded_file(shown, &content)));
+    }
+    Ok(ResolvedFile::Mention(mention(inside.unwrap_or(&absolute))))
+}
+
+fn read_head(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    File::open(path)?
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut head)?;
+    Ok(head)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+    Text,
+    Binary,
+}
+
+impl FileKind {
+    fn image_type(self) -> Option<&'static str> {
+        match self {
+            FileKind::Png => Some("image/png"),
+            FileKind::Jpeg => Some("image/jpeg"),
+            FileKind::Gif => Some("image/gif"),
+            FileKind::Webp => Some("image/webp"),
+            FileKind::Text | FileKind::Binary => None,
+        }
+    }
+}
+
+/// Tell a file's type from its first bytes.
+fn sniff(head: &[u8]) -> FileKind {
+    match head {
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => FileKind::Png,
+        [0xFF, 0xD8, 0xFF, ..] => FileKind::Jpeg,
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => FileKind::Gif,
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => FileKind::Webp,
+        _ if head.contains(&0) => FileKind::Binary,
+        _ => match std::str::from_utf8(head) {
+            Ok(_) => FileKind::Text,
+            // A character cut off by the end of the sample
+            Err(err) if err.error_len().is_none() => FileKind::Text,
+            Err(_) => FileKind::Binary,
+        },
+    }
+}
+
+fn decode_data_url(data_url: &str) -> Result<ImageAttachment, String> {
+    let (meta, data) = data_url
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(','))
+        .ok_or("not a data URL")?;
+    if !meta.ends_with(";base64") {
+        return Err("only base64 data URLs are supported".to_string());
+    }
+    let bytes = STANDARD
+        .decode(data.trim())
+        .map_err(|err| format!("invalid base64: {err}"))?;
+    if bytes.len() as u64 > MAX_IMAGE_BYTES {
+        return Err(format!(
+            "image is {}; images can be at most {}",
+            format_size(bytes.len() as u64),
+            format_size(MAX_IMAGE_BYTES)
+        ));
+    }
+    let media_type = sniff(&bytes)
+        .image_type()
+        .ok_or("not a PNG, JPEG, GIF or WebP image")?;
+    Ok(ImageAttachment {
+        media_type: media_type.to_string(),
+        data: STANDARD.encode(bytes),
+    })
+}
+
+/// `@path`, quoted when the path has spaces.
+fn mention(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    if path.contains(char::is_whitespace) {
+        format!("@\"{path}\"")
+    } else {
+        format!("@{path}")
+    }
+}
+
+fn embedded_file(path: &Path, content: &str) -> String {
+    fenced(&path.to_string_lossy().replace('\\', "/"), content)
+}
+
+/// `content` in a code fence under a `label:` line.
+pub(crate) fn fenced(label: &str, content: &str) -> String {
+    // A fence longer than any run of backticks in the content
+    let longest_run = content
+        .split(|ch| ch != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{label}:\n{fence}\n{}\n{fence}", content.trim_end())
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{} KB", bytes.div_ceil(1024))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+
+    fn temp_workspace(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("attachments-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        root
+    }
+
+    #[test]
+    fn sniffs_images_by_their_bytes() {
+        assert_eq!(sniff(PNG), FileKind::Png);
+        assert_eq!(sniff(&[0xFF, 0xD8, 0xFF, 0xE0]), FileKind::Jpeg);
+        assert_eq!(sniff(b"GIF89a...."), FileKind::Gif);
+        assert_eq!(sniff(b"RIFF\x10\0\0\0WEBPVP8 "), FileKind::Webp);
+        assert_eq!(sniff("fn main() {} // é".as_bytes()), FileKind::Text);
+        // A multi-byte character cut off at the end of the sample is still text
+        assert_eq!(sniff(&"é".as_bytes()[..1]), FileKind::Text);
+        assert_eq!(sniff(b"%PDF-1.7\n\0\x01"), FileKind::Binary);
+    }
+
+    #[test]
+    fn resolves_images_mentions_and_embedded_files() {
+        let root = temp_workspace("resolve");
+        let outside = temp_workspace("resolve-outside");
+        std::fs::write(root.join("shot.txt"), PNG).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(root.join("src/my notes.md"), "```\ncode\n```\n").unwrap();
+        std::fs::write(outside.join("spec.md"), "# Spec\n").unwrap();
+
+        let attachments = vec![
+            Attachment::File {
+                path: "shot.txt".to_string(),
+                embed: false,
+            },
+            Attachment::File {
+                path: "src/main.rs".to_string(),
+                embed: false,
+            },
+            Attachment::File {
+                path: root.join("src/my notes.md").to_string_lossy().into_owned(),
+                embed: false,
+            },
+            Attachment::File {
+                path: "src/my notes.md".to_string(),
+                embed: true,
+            },
+            Attachment::File {
+                path: outside.join("spec.md").to_string_lossy().into_owned(),
+                embed: false,
+            },
+        ];
+        let message = prepare("  Look at these  ", attachments, &root).unwrap();
+
+        assert_eq!(message.images.len(), 1);
+        assert_eq!(message.images[0].media_type, "image/png");
+        assert_eq!(STANDARD.decode(&message.images[0].data).unwrap(), PNG);
+        let spec = outside.canonicalize().unwrap().join("spec.md");
+        assert_eq!(
+            message.text,
+            format!(
+                "Look at these\n\n@src/main.rs @\"src/my notes.md\"\n\n\
+                 src/my notes.md:\n````\n```\ncode\n```\n````\n\n\
+                 {}:\n```\n# Spec\n```",
+                spec.to_string_lossy()
+            )
+        );
+
+        let _ = std::fs::remove_dir_all(root);
+        let _ = std::fs::remove_dir_all(outside);
+    }
+
+    #[test]
+    fn rejects_what_cannot_be_sent() {
+        let root = temp_workspace("reject");
+        std::fs::write(root.join("tool.bin"), [0u8, 1, 2, 3]).unwrap();
+        let embed_binary = vec![Attachment::File {
+            path: "tool.bin".to_string(),
+            embed: true,
+        }];
+        assert!(prepare("", embed_binary, &root)
+            .unwrap_err()
+            .contains("not a text file"));
+        let missing = vec![Attachment::File {
+            path: "nope.png".to_string(),
+            embed: false,
+        }];
+        assert!(prepare("", missing, &root).is_err());
+
+        let text_as_image = from_images(vec![format!(
+            "data:image/png;base64,{}",
+            STANDARD.encode("hello")
+        )]);
+        assert!(prepare("", text_as_image, &root).is_err());
+        let pasted = from_images(vec![format!(
+            "data:image/png;base64,{}",
+            STANDARD.encode(PNG)
+        )]);
+        let message = prepare("", pasted, &root).unwrap();
+        assert_eq!(message.images.len(), 1);
+        assert!(!message.is_empty());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
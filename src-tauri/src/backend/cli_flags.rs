@@ -0,0 +1,167 @@
+//! Per-workspace Claude CLI flags: validation and translation into arguments.
+
+use std::path::Path;
+
+use crate::types::ClaudeCliFlags;
+
+/// Values `--permission-mode` accepts.
+pub(crate) const CLI_PERMISSION_MODES: &[&str] = &[
+    "acceptEdits",
+    "bypassPermissions",
+    "default",
+    "delegate",
+    "dontAsk",
+    "plan",
+];
+
+/// A tool name such as `Edit` or `mcp__github__create_issue`, optionally followed by a
+/// parenthesized rule such as `Bash(npm run test:*)`.
+fn is_valid_tool(tool: &str) -> bool {
+    let (name, rule) = match tool.split_once('(') {
+        Some((name, rest)) => match rest.strip_suffix(')') {
+            Some(rule) => (name, Some(rule)),
+            None => return false,
+        },
+        None => (tool, None),
+    };
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+        && !matches!(rule, Some(rule) if rule.trim().is_empty())
+}
+
+/// Check the flags before they are saved. `workspace_path` resolves relative `addDirs`.
+pub(crate) fn validate_cli_flags(
+    flags: &ClaudeCliFlags,
+    workspace_path: &str,
+) -> Result<(), String> {
+    for tool in flags.allowed_tools.iter().chain(&flags.disallowed_tools) {
+        if !is_valid_tool(tool.trim()) {
+            return Err(format!("invalid tool name: {tool:?}"));
+        }
+    }
+    if let Some(tool) = flags
+        .allowed_tools
+        .iter()
+        .find(|tool| flags.disallowed_tools.contains(tool))
+    {
+        return Err(format!("{tool} is both allowed and disallowed"));
+    }
+    if let Some(mode) = &flags.permission_mode {
+        if !CLI_PERMISSION_MODES.contains(&mode.as_str()) {
+            return Err(format!(
+                "invalid permission mode {mode:?}; expected one of {}",
+                CLI_PERMISSION_MODES.join(", ")
+            ));
+        }
+    }
+    if flags.max_turns == Some(0) {
+        return Err("maxTurns must be at least 1".to_string());
+    }
+    for dir in &flags.add_dirs {
+        if dir.trim().is_empty() {
+            return Err("addDirs entries must not be empty".to_string());
+        }
+        if !Path::new(workspace_path).join(dir.trim()).is_dir() {
+            return Err(format!("{dir} is not a directory"));
+        }
+    }
+    Ok(())
+}
+
+/// The arguments for every flag but the permission mode, which callers combine with
+/// the access mode of the turn.
+pub(crate) fn cli_flag_args(flags: &ClaudeCliFlags) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut push_list = |flag: &str, values: &[String]| {
+        let values: Vec<String> = values
+            .iter()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect();
+        if !values.is_empty() {
+            args.push(flag.to_string());
+            args.extend(values);
+        }
+    };
+    push_list("--allowedTools", &flags.allowed_tools);
+    push_list("--disallowedTools", &flags.disallowed_tools);
+    push_list("--add-dir", &flags.add_dirs);
+    if let Some(max_turns) = flags.max_turns {
+        args.extend(["--max-turns".to_string(), max_turns.to_string()]);
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags() -> ClaudeCliFlags {
+        ClaudeCliFlags {
+            allowed_tools: vec!["Read".to_string(), "Bash(git log:*)".to_string()],
+            disallowed_tools: vec!["mcp__github__delete_repo".to_string()],
+            permission_mode: Some("acceptEdits".to_string()),
+            max_turns: Some(20),
+            add_dirs: vec![".".to_string()],
+        }
+    }
+
+    #[test]
+    fn validates_tools_modes_and_dirs() {
+        let workspace = std::env::temp_dir();
+        let workspace = workspace.to_str().unwrap();
+        assert!(validate_cli_flags(&flags(), workspace).is_ok());
+        assert!(validate_cli_flags(&ClaudeCliFlags::default(), workspace).is_ok());
+
+        let invalid = [
+            ClaudeCliFlags {
+                allowed_tools: vec!["Bash(git log".to_string()],
+                ..flags()
+            },
+            ClaudeCliFlags {
+                allowed_tools: vec!["rm -rf".to_string()],
+                ..flags()
+            },
+            ClaudeCliFlags {
+                disallowed_tools: vec!["Read".to_string()],
+                ..flags()
+            },
+            ClaudeCliFlags {
+                permission_mode: Some("yolo".to_string()),
+                ..flags()
+            },
+            ClaudeCliFlags {
+                max_turns: Some(0),
+                ..flags()
+            },
+            ClaudeCliFlags {
+                add_dirs: vec!["does-not-exist-7f3a".to_string()],
+                ..flags()
+            },
+        ];
+        for flags in invalid {
+            assert!(validate_cli_flags(&flags, workspace).is_err(), "{flags:?}");
+        }
+    }
+
+    #[test]
+    fn translates_flags_into_arguments() {
+        assert_eq!(
+            cli_flag_args(&flags()),
+            vec![
+                "--allowedTools",
+                "Read",
+                "Bash(git log:*)",
+                "--disallowedTools",
+                "mcp__github__delete_repo",
+                "--add-dir",
+                ".",
+                "--max-turns",
+                "20",
+            ]
+        );
+        assert!(cli_flag_args(&ClaudeCliFlags::default()).is_empty());
+    }
+}
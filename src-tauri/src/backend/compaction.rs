@@ -0,0 +1,247 @@
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::backend::usage_gauge::UsageGaugeReading;
+use crate::types::{AutoCompactPolicy, CompactionRecord, CompactionTrigger};
+
+const MIN_THRESHOLD_PERCENT: u8 = 1;
+const MAX_THRESHOLD_PERCENT: u8 = 99;
+
+pub(crate) fn validate_auto_compact(policy: &AutoCompactPolicy) -> Result<(), String> {
+    if !(MIN_THRESHOLD_PERCENT..=MAX_THRESHOLD_PERCENT).contains(&policy.threshold_percent) {
+        return Err(format!(
+            "autoCompact.thresholdPercent must be between {MIN_THRESHOLD_PERCENT} and \
+             {MAX_THRESHOLD_PERCENT}"
+        ));
+    }
+    Ok(())
+}
+
+/// The `/compact` message to send after a turn that ended with `reading`, if the policy
+/// asks for one.
+pub(crate) fn compact_command(
+    policy: Option<&AutoCompactPolicy>,
+    reading: &UsageGaugeReading,
+) -> Option<String> {
+    let policy = policy.filter(|policy| policy.enabled)?;
+    if reading.context_percent < f64::from(policy.threshold_percent) {
+        return None;
+    }
+    match policy.instructions.as_deref().map(str::trim) {
+        Some(instructions) if !instructions.is_empty() => Some(format!("/compact {instructions}")),
+        _ => Some("/compact".to_string()),
+    }
+}
+
+/// Follows the compactions of one thread, whoever started them: the monitor when a turn
+/// crosses the workspace's threshold, the user with `/compact`, or the CLI itself.
+///
+/// The CLI reports a finished compaction with a `compact_boundary` system event carrying
+/// the context size before it. How much it reclaimed shows in the prompt of the next
+/// message, so a record is updated once more when that message starts.
+#[derive(Debug)]
+pub(crate) struct CompactionTracker {
+    workspace_id: String,
+    thread_id: String,
+    /// Sent by the monitor and not finished yet.
+    pending: Option<CompactionRecord>,
+    /// Finished, waiting for the next message to measure what it reclaimed.
+    unmeasured: Option<CompactionRecord>,
+}
+
+impl CompactionTracker {
+    pub(crate) fn new(workspace_id: &str, thread_id: &str) -> Self {
+        Self {
+            workspace_id: workspace_id.to_string(),
+            thread_id: thread_id.to_string(),
+            pending: None,
+            unmeasured: None,
+        }
+    }
+
+    /// Whether the monitor is waiting for a compaction it sent.
+    pub(crate) fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Note that the monitor sent `/compact` after a turn ended with `reading`.
+    pub(crate) fn start(&mut self, reading: &UsageGaugeReading, now: i64) -> CompactionRecord {
+        let record = self.record(
+            CompactionTrigger::Threshold,
+            now,
+            Some(reading.context_tokens),
+        );
+        self.pending = Some(record.clone());
+        record
+    }
+
+    /// Drop a compaction the monitor could not send.
+    pub(crate) fn abandon(&mut self) {
+        self.pending = None;
+    }
+
+    /// Take in one stream event; returns the record when a compaction finished or failed.
+    pub(crate) fn observe(&mut self, event: &Value, now: i64) -> Option<CompactionRecord> {
+        let event_type = event.get("type").and_then(Value::as_str).unwrap_or("");
+        let subtype = event.get("subtype").and_then(Value::as_str).unwrap_or("");
+        match (event_type, subtype) {
+            ("system", "compact_boundary") => {
+                let metadata = event
+                    .get("compact_metadata")
+                    .or_else(|| event.get("compactMetadata"));
+                let pre_tokens = metadata
+                    .and_then(|metadata| {
+                        metadata
+                            .get("pre_tokens")
+                            .or_else(|| metadata.get("preTokens"))
+                    })
+                    .and_then(Value::as_u64);
+                let mut record = self.pending.take().unwrap_or_else(|| {
+                    let trigger = match metadata
+                        .and_then(|metadata| metadata.get("trigger"))
+                        .and_then(Value::as_str)
+                    {
+                        Some("auto") => CompactionTrigger::Cli,
+                        _ => CompactionTrigger::Manual,
+                    };
+                    self.record(trigger, now, None)
+                });
+                record.tokens_before = pre_tokens.or(record.tokens_before);
+                record.finished_at = Some(now);
+                self.unmeasured = Some(record.clone());
+                Some(record)
+            }
+            // A compaction that ends without a boundary did not happen, e.g. because the
+            // conversation was too short to summarize
+            ("result", _) => {
+                let mut record = self.pending.take()?;
+                record.finished_at = Some(now);
+                record.error = Some(
+                    event
+                        .get("result")
+                        .and_then(Value::as_str)
+                        .filter(|text| !text.trim().is_empty())
+                        .unwrap_or("the CLI did not compact the conversation")
+                        .to_string(),
+                );
+                Some(record)
+            }
+            _ => None,
+        }
+    }
+
+    /// Measure the last compaction against the first reading after it.
+    pub(crate) fn measure(&mut self, reading: &UsageGaugeReading) -> Option<CompactionRecord> {
+        let mut record = self.unmeasured.take()?;
+        record.tokens_after = Some(reading.context_tokens);
+        record.tokens_reclaimed = record
+            .tokens_before
+            .map(|before| before.saturating_sub(reading.context_tokens));
+        Some(record)
+    }
+
+    fn record(
+        &self,
+        trigger: CompactionTrigger,
+        now: i64,
+        tokens_before: Option<u64>,
+    ) -> CompactionRecord {
+        CompactionRecord {
+            id: Uuid::new_v4().to_string(),
+            workspace_id: self.workspace_id.clone(),
+            thread_id: self.thread_id.clone(),
+            trigger,
+            started_at: now,
+            finished_at: None,
+            tokens_before,
+            tokens_after: None,
+            tokens_reclaimed: None,
+            error: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn reading(context_tokens: u64, context_percent: f64) -> UsageGaugeReading {
+        UsageGaugeReading {
+            output_tokens: 0,
+            tokens_per_second: 0.0,
+            context_tokens,
+            context_window: 200_000,
+            context_percent,
+            done: true,
+        }
+    }
+
+    #[test]
+    fn compacts_past_the_threshold_only_when_enabled() {
+        let mut policy = AutoCompactPolicy {
+            enabled: true,
+            threshold_percent: 75,
+            instructions: Some("  keep the test plan ".to_string()),
+        };
+        assert_eq!(compact_command(Some(&policy), &reading(0, 74.9)), None);
+        assert_eq!(
+            compact_command(Some(&policy), &reading(0, 80.0)).as_deref(),
+            Some("/compact keep the test plan")
+        );
+        policy.instructions = None;
+        assert_eq!(
+            compact_command(Some(&policy), &reading(0, 75.0)).as_deref(),
+            Some("/compact")
+        );
+        policy.enabled = false;
+        assert_eq!(compact_command(Some(&policy), &reading(0, 99.0)), None);
+        assert_eq!(compact_command(None, &reading(0, 99.0)), None);
+
+        assert!(validate_auto_compact(&AutoCompactPolicy::default()).is_ok());
+        policy.threshold_percent = 100;
+        assert!(validate_auto_compact(&policy).is_err());
+    }
+
+    #[test]
+    fn records_what_a_compaction_reclaimed() {
+        let mut tracker = CompactionTracker::new("ws-1", "thread-1");
+        let started = tracker.start(&reading(160_000, 80.0), 1_000);
+        assert_eq!(started.trigger, CompactionTrigger::Threshold);
+        assert!(tracker.is_pending());
+
+        let boundary = json!({
+            "type": "system",
+            "subtype": "compact_boundary",
+            "compact_metadata": { "trigger": "manual", "pre_tokens": 161_000 }
+        });
+        let finished = tracker.observe(&boundary, 1_500).unwrap();
+        assert_eq!(finished.id, started.id);
+        assert_eq!(finished.trigger, CompactionTrigger::Threshold);
+        assert_eq!(finished.tokens_before, Some(161_000));
+        assert_eq!(finished.finished_at, Some(1_500));
+        assert_eq!(tracker.observe(&json!({ "type": "result" }), 1_600), None);
+
+        let measured = tracker.measure(&reading(21_000, 10.5)).unwrap();
+        assert_eq!(measured.tokens_after, Some(21_000));
+        assert_eq!(measured.tokens_reclaimed, Some(140_000));
+        assert_eq!(tracker.measure(&reading(22_000, 11.0)), None);
+
+        // The CLI's own compactions are recorded too
+        let auto = json!({
+            "type": "system",
+            "subtype": "compact_boundary",
+            "compact_metadata": { "trigger": "auto", "pre_tokens": 190_000 }
+        });
+        let cli = tracker.observe(&auto, 2_000).unwrap();
+        assert_eq!(cli.trigger, CompactionTrigger::Cli);
+        assert_eq!(cli.started_at, 2_000);
+
+        tracker.start(&reading(170_000, 85.0), 3_000);
+        let failed = tracker
+            .observe(&json!({ "type": "result", "result": "" }), 3_100)
+            .unwrap();
+        assert!(failed.error.is_some());
+        assert!(!tracker.is_pending());
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+/// A `can_use_tool` control request the CLI is blocked on until the UI answers it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PendingPermission {
+    pub(crate) request_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) tool_name: String,
+    pub(crate) tool_use_id: Option<String>,
+    pub(crate) input: Value,
+    /// Permission updates the CLI proposes for "always allow", applied as-is on request.
+    pub(crate) suggestions: Option<Value>,
+}
+
+/// The UI's answer to a permission request.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "behavior", rename_all = "camelCase")]
+pub(crate) enum PermissionDecision {
+    #[serde(rename_all = "camelCase")]
+    Allow {
+        /// Replacement tool input; the original input is sent back when omitted.
+        #[serde(default)]
+        updated_input: Option<Value>,
+        /// Also apply the CLI's suggested permission updates so it stops asking.
+        #[serde(default)]
+        always: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Deny {
+        #[serde(default)]
+        message: Option<String>,
+        /// Abort the whole turn instead of letting Claude continue without the tool.
+        #[serde(default)]
+        interrupt: bool,
+    },
+}
+
+/// Bookkeeping for the stdin control protocol of one workspace.
+///
+/// With `--permission-prompt-tool stdio` the CLI asks for tool permissions with
+/// `control_request` frames on stdout and waits for a matching `control_response` on
+/// stdin. Requests are tracked here from the moment the reader sees them until the UI
+/// answers or the thread's process goes away. Requests the monitor sends itself, such
+/// as interrupts and health pings, wait here for the CLI's `control_response`.
+#[derive(Debug, Default)]
+pub(crate) struct ControlChannel {
+    pending: HashMap<String, PendingPermission>,
+    outgoing: HashMap<String, oneshot::Sender<Value>>,
+}
+
+impl ControlChannel {
+    pub(crate) fn register(&mut self, permission: PendingPermission) {
+        self.pending
+            .insert(permission.request_id.clone(), permission);
+    }
+
+    pub(crate) fn take(&mut self, request_id: &str) -> Option<PendingPermission> {
+        self.pending.remove(request_id)
+    }
+
+    /// Forget every request of a thread whose process exited or was killed.
+    pub(crate) fn drain_thread(&mut self, thread_id: &str) -> Vec<PendingPermission> {
+        let request_ids: Vec<String> = self
+            .pending
+            .values()
+            .filter(|permission| permission.thread_id == thread_id)
+            .map(|permission| permission.request_id.clone())
+            .collect();
+        request_ids
+            .iter()
+            .filter_map(|request_id| self.pending.remove(request_id))
+            .collect()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.pending.clear();
+        self.outgoing.clear();
+    }
+
+    /// Track a request sent to the CLI; the receiver gets the body of its answer.
+    pub(crate) fn register_request(&mut self, request_id: String) -> oneshot::Receiver<Value> {
+        let (answered, receiver) = oneshot::channel();
+        self.outgoing.insert(request_id, answered);
+        receiver
+    }
+
+    /// Hand a `control_response` body to whoever sent the request. Returns whether
+    /// anyone was waiting for it.
+    pub(crate) fn resolve_request(&mut self, request_id: &str, response: Value) -> bool {
+        match self.outgoing.remove(request_id) {
+            Some(answered) => {
+                let _ = answered.send(response);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn cancel_request(&mut self, request_id: &str) {
+        self.outgoing.remove(request_id);
+    }
+}
+
+/// Build a read-only `control_request` used to check that the CLI still serves stdin.
+/// Any answer counts, including an error for a subtype the CLI does not know.
+pub(crate) fn ping_request(request_id: &str) -> Value {
+    outgoing_request(request_id, "mcp_status")
+}
+
+/// Build the `control_request` that stops the running turn without ending the process.
+pub(crate) fn interrupt_request(request_id: &str) -> Value {
+    outgoing_request(request_id, "interrupt")
+}
+
+/// Build the `control_request` that switches the process to another permission mode.
+pub(crate) fn set_permission_mode_request(request_id: &str, mode: &str) -> Value {
+    let mut request = outgoing_request(request_id, "set_permission_mode");
+    request["request"]["mode"] = Value::String(mode.to_string());
+    request
+}
+
+fn outgoing_request(request_id: &str, subtype: &str) -> Value {
+    json!({
+        "type": "control_request",
+        "request_id": request_id,
+        "request": { "subtype": subtype },
+    })
+}
+
+/// The error a `control_response` body reports, or `None` when it succeeded.
+pub(crate) fn response_error(response: &Value) -> Option<String> {
+    if response.get("subtype").and_then(|subtype| subtype.as_str()) == Some("success") {
+        return None;
+    }
+    Some(
+        response
+            .get("error")
+            .and_then(|error| error.as_str())
+            .unwrap_or("control request failed")
+            .to_string(),
+    )
+}
+
+/// Request id of a `control_response` frame the CLI wrote to stdout.
+pub(crate) fn response_request_id(frame: &Value) -> Option<&str> {
+    frame
+        .get("response")
+        .and_then(|response| response.get("request_id"))
+        .and_then(|id| id.as_str())
+}
+
+/// Build the `control_response` frame that answers a `can_use_tool` request.
+pub(crate) fn permission_response(
+    permission: &PendingPermission,
+    decision: PermissionDecision,
+) -> Value {
+    let body = match decision {
+        PermissionDecision::Allow {
+            updated_input,
+            always,
+        } => {
+            let mut body = json!({
+                "behavior": "allow",
+                "updatedInput": updated_input.unwrap_or_else(|| permission.input.clone()),
+            });
+            if always {
+                if let Some(suggestions) = permission.suggestions.clone() {
+                    body["updatedPermissions"] = suggestions;
+                }
+            }
+            body
+        }
+        PermissionDecision::Deny { message, interrupt } => json!({
+            "behavior": "deny",
+            "message": message
+                .filter(|message| !message.trim().is_empty())
+                .unwrap_or_else(|| "The user denied this tool use.".to_string()),
+            "interrupt": interrupt,
+        }),
+    };
+    json!({
+        "type": "control_response",
+        "response": {
+            "subtype": "success",
+            "request_id": permission.request_id,
+            "response": body,
+        },
+    })
+}
+
+/// Build an error `control_response`, used for requests the monitor cannot handle so the
+/// CLI does not wait on them forever.
+pub(crate) fn error_response(request_id: &str, error: &str) -> Value {
+    json!({
+        "type": "control_response",
+        "response": {
+            "subtype": "error",
+            "request_id": request_id,
+            "error": error,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(request_id: &str, thread_id: &str) -> PendingPermission {
+        PendingPermission {
+            request_id: request_id.to_string(),
+            thread_id: thread_id.to_string(),
+            tool_name: "Bash".to_string(),
+            tool_use_id: Some("toolu_1".to_string()),
+            input: json!({ "command": "ls" }),
+            suggestions: Some(json!([{ "type": "addRules", "destination": "session" }])),
+        }
+    }
+
+    #[test]
+    fn allow_echoes_input_and_applies_suggestions_when_asked() {
+        let permission = pending("req-1", "t1");
+        let decision: PermissionDecision =
+            serde_json::from_value(json!({ "behavior": "allow" })).unwrap();
+        let frame = permission_response(&permission, decision);
+        assert_eq!(frame["type"], "control_response");
+        assert_eq!(frame["response"]["request_id"], "req-1");
+        assert_eq!(
+            frame["response"]["response"]["updatedInput"]["command"],
+            "ls"
+        );
+        assert!(frame["response"]["response"]
+            .get("updatedPermissions")
+            .is_none());
+
+        let decision: PermissionDecision = serde_json::from_value(
+            json!({ "behavior": "allow", "always": true, "updatedInput": { "command": "ls -a" } }),
+        )
+        .unwrap();
+        let frame = permission_response(&permission, decision);
+        let body = &frame["response"]["response"];
+        assert_eq!(body["updatedInput"]["command"], "ls -a");
+        assert_eq!(body["updatedPermissions"][0]["type"], "addRules");
+    }
+
+    #[test]
+    fn deny_defaults_message() {
+        let frame = permission_response(
+            &pending("req-2", "t1"),
+            PermissionDecision::Deny {
+                message: None,
+                interrupt: true,
+            },
+        );
+        let body = &frame["response"]["response"];
+        assert_eq!(body["behavior"], "deny");
+        assert_eq!(body["message"], "The user denied this tool use.");
+        assert_eq!(body["interrupt"], true);
+    }
+
+    #[test]
+    fn channel_tracks_requests_per_thread() {
+        let mut channel = ControlChannel::default();
+        channel.register(pending("a", "t1"));
+        channel.register(pending("b", "t2"));
+        channel.register(pending("c", "t1"));
+
+        let mut drained: Vec<String> = channel
+            .drain_thread("t1")
+            .into_iter()
+            .map(|permission| permission.request_id)
+            .collect();
+        drained.sort();
+        assert_eq!(drained, vec!["a", "c"]);
+        assert!(channel.take("a").is_none());
+        assert_eq!(
+            channel.take("b").map(|p| p.thread_id).as_deref(),
+            Some("t2")
+        );
+    }
+
+    #[test]
+    fn outgoing_requests_resolve_once() {
+        let mut channel = ControlChannel::default();
+        let mut answered = channel.register_request("health-1".to_string());
+        let frame = json!({
+            "type": "control_response",
+            "response": { "subtype": "error", "request_id": "health-1", "error": "unknown" },
+        });
+        let request_id = response_request_id(&frame).unwrap();
+        assert!(channel.resolve_request(request_id, frame["response"].clone()));
+        let response = answered.try_recv().unwrap();
+        assert_eq!(response_error(&response).as_deref(), Some("unknown"));
+        assert!(!channel.resolve_request(request_id, Value::Null));
+        assert_eq!(ping_request("health-2")["request_id"], "health-2");
+    }
+
+    #[test]
+    fn interrupt_request_uses_interrupt_subtype() {
+        let frame = interrupt_request("interrupt-1");
+        assert_eq!(frame["type"], "control_request");
+        assert_eq!(frame["request"]["subtype"], "interrupt");
+        assert_eq!(
+            response_error(&json!({ "subtype": "success", "request_id": "interrupt-1" })),
+            None
+        );
+    }
+
+    #[test]
+    fn error_response_names_request() {
+        let frame = error_response("req-9", "unsupported");
+        assert_eq!(frame["response"]["subtype"], "error");
+        assert_eq!(frame["response"]["request_id"], "req-9");
+    }
+}
@@ -0,0 +1,252 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::backend::events::AppServerEvent;
+
+/// The streamed assistant text the batcher merges; every other event passes through.
+pub(crate) const TEXT_DELTA_METHOD: &str = "item/agentMessage/delta";
+
+/// Pending text past which a batch goes out without waiting for the interval.
+const MAX_BATCH_BYTES: usize = 64 * 1024;
+
+/// Counters behind `get_event_stats`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct BatchCounts {
+    pub(crate) deltas_received: u64,
+    /// Deltas appended to an earlier one instead of being sent on their own.
+    pub(crate) deltas_merged: u64,
+    /// Batches sent that held more than one delta.
+    pub(crate) batches_merged: u64,
+    /// Most deltas sent as one event.
+    pub(crate) largest_batch: u64,
+}
+
+struct PendingDelta {
+    workspace_id: String,
+    thread_id: String,
+    item_id: String,
+    params: Value,
+    text: String,
+    deltas: u64,
+}
+
+/// Merges text deltas of the same message that arrive within one interval, so a fast
+/// stream reaches the frontend as a few events instead of one per token.
+///
+/// Other events are never held back or dropped. Pending deltas are released ahead of
+/// them, so the frontend still sees a message's text before, say, its completion.
+pub(crate) struct DeltaBatcher {
+    pending: Vec<PendingDelta>,
+    pending_bytes: usize,
+    counts: BatchCounts,
+}
+
+/// `(thread id, item id, text)` of a delta the batcher can merge.
+fn text_delta(event: &AppServerEvent) -> Option<(&str, &str, &str)> {
+    if event.message.get("method").and_then(Value::as_str) != Some(TEXT_DELTA_METHOD) {
+        return None;
+    }
+    let params = event.message.get("params")?;
+    Some((
+        params.get("threadId")?.as_str()?,
+        params.get("itemId")?.as_str()?,
+        params.get("delta")?.as_str()?,
+    ))
+}
+
+impl DeltaBatcher {
+    pub(crate) const fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            pending_bytes: 0,
+            counts: BatchCounts {
+                deltas_received: 0,
+                deltas_merged: 0,
+                batches_merged: 0,
+                largest_batch: 0,
+            },
+        }
+    }
+
+    /// Take in an event and return what should be emitted now, in order. With a zero
+    /// interval nothing is held back.
+    pub(crate) fn push(
+        &mut self,
+        event: AppServerEvent,
+        interval: Duration,
+    ) -> Vec<AppServerEvent> {
+        let Some((thread_id, item_id, text)) = text_delta(&event) else {
+            let mut ready = self.flush();
+            ready.push(event);
+            return ready;
+        };
+        self.counts.deltas_received += 1;
+        if interval.is_zero() {
+            let mut ready = self.flush();
+            self.note_batch(1);
+            ready.push(event);
+            return ready;
+        }
+
+        self.pending_bytes += text.len();
+        let existing = self.pending.iter_mut().find(|pending| {
+            pending.workspace_id == event.workspace_id
+                && pending.thread_id == thread_id
+                && pending.item_id == item_id
+        });
+        match existing {
+            Some(pending) => {
+                pending.text.push_str(text);
+                pending.deltas += 1;
+                self.counts.deltas_merged += 1;
+            }
+            None => {
+                let pending = PendingDelta {
+                    workspace_id: event.workspace_id.clone(),
+                    thread_id: thread_id.to_string(),
+                    item_id: item_id.to_string(),
+                    params: event.message["params"].clone(),
+                    text: text.to_string(),
+                    deltas: 1,
+                };
+                self.pending.push(pending);
+            }
+        }
+        if self.pending_bytes >= MAX_BATCH_BYTES {
+            return self.flush();
+        }
+        Vec::new()
+    }
+
+    /// Release every pending delta, one event per message.
+    pub(crate) fn flush(&mut self) -> Vec<AppServerEvent> {
+        self.pending_bytes = 0;
+        let pending = std::mem::take(&mut self.pending);
+        pending
+            .into_iter()
+            .map(|pending| {
+                self.note_batch(pending.deltas);
+                let mut params = pending.params;
+                params["delta"] = Value::from(pending.text);
+                AppServerEvent {
+                    workspace_id: pending.workspace_id,
+                    message: serde_json::json!({
+                        "method": TEXT_DELTA_METHOD,
+                        "params": params,
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    /// Whether nothing is waiting for a flush.
+    pub(crate) fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Deltas waiting for a flush.
+    pub(crate) fn pending_deltas(&self) -> u64 {
+        self.pending.iter().map(|pending| pending.deltas).sum()
+    }
+
+    pub(crate) fn counts(&self) -> &BatchCounts {
+        &self.counts
+    }
+
+    fn note_batch(&mut self, deltas: u64) {
+        if deltas > 1 {
+            self.counts.batches_merged += 1;
+        }
+        self.counts.largest_batch = self.counts.largest_batch.max(deltas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const INTERVAL: Duration = Duration::from_millis(30);
+
+    fn delta(item_id: &str, text: &str) -> AppServerEvent {
+        AppServerEvent {
+            workspace_id: "ws-1".to_string(),
+            message: json!({
+                "method": TEXT_DELTA_METHOD,
+                "params": { "threadId": "thread-1", "itemId": item_id, "delta": text },
+            }),
+        }
+    }
+
+    fn completed(item_id: &str) -> AppServerEvent {
+        AppServerEvent {
+            workspace_id: "ws-1".to_string(),
+            message: json!({
+                "method": "item/completed",
+                "params": { "threadId": "thread-1", "item": { "id": item_id } },
+            }),
+        }
+    }
+
+    fn describe(events: &[AppServerEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| {
+                let params = &event.message["params"];
+                match params["delta"].as_str() {
+                    Some(text) => format!("{}:{text}", params["itemId"].as_str().unwrap()),
+                    None => event.message["method"].as_str().unwrap().to_string(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merges_deltas_per_message_until_flushed() {
+        let mut batcher = DeltaBatcher::new();
+        assert!(batcher.push(delta("a", "Hel"), INTERVAL).is_empty());
+        assert!(batcher.push(delta("b", "Other"), INTERVAL).is_empty());
+        assert!(batcher.push(delta("a", "lo"), INTERVAL).is_empty());
+        assert!(batcher.push(delta("a", "!"), INTERVAL).is_empty());
+        assert_eq!(batcher.pending_deltas(), 4);
+
+        let flushed = batcher.flush();
+        assert_eq!(describe(&flushed), ["a:Hello!", "b:Other"]);
+        assert!(batcher.is_idle());
+        assert_eq!(
+            batcher.counts(),
+            &BatchCounts {
+                deltas_received: 4,
+                deltas_merged: 2,
+                batches_merged: 1,
+                largest_batch: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn releases_pending_deltas_before_other_events() {
+        let mut batcher = DeltaBatcher::new();
+        batcher.push(delta("a", "Done"), INTERVAL);
+        let ready = batcher.push(completed("a"), INTERVAL);
+        assert_eq!(describe(&ready), ["a:Done", "item/completed"]);
+        assert!(batcher.is_idle());
+
+        let unbatched = batcher.push(delta("a", "now"), Duration::ZERO);
+        assert_eq!(describe(&unbatched), ["a:now"]);
+    }
+
+    #[test]
+    fn flushes_large_batches_early() {
+        let mut batcher = DeltaBatcher::new();
+        let chunk = "x".repeat(MAX_BATCH_BYTES / 2);
+        assert!(batcher.push(delta("a", &chunk), INTERVAL).is_empty());
+        let ready = batcher.push(delta("a", &chunk), INTERVAL);
+        assert_eq!(ready.len(), 1);
+        assert_eq!(
+            ready[0].message["params"]["delta"].as_str().map(str::len),
+            Some(MAX_BATCH_BYTES)
+        );
+    }
+}
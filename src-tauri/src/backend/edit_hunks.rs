@@ -0,0 +1,300 @@
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::types::{EditHunk, FileEdit, FileEditKind, TurnEdits};
+
+/// Files larger than this are not read to place hunks or to show what a `Write`
+/// replaced.
+const MAX_ORIGINAL_BYTES: u64 = 512 * 1024;
+
+/// Turn an `Edit`, `MultiEdit` or `Write` tool input into the hunks it applies. Line
+/// ranges are left unknown; see [`locate`].
+pub(crate) fn parse_file_edit(
+    tool_use_id: &str,
+    tool_name: &str,
+    input: &Value,
+) -> Option<FileEdit> {
+    let path = input
+        .get("file_path")
+        .or_else(|| input.get("filePath"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|path| !path.is_empty())?;
+    let (kind, hunks) = match tool_name {
+        "Write" => {
+            let content = input.get("content").and_then(Value::as_str).unwrap_or("");
+            (FileEditKind::Write, vec![hunk("", content, false)])
+        }
+        "Edit" => (FileEditKind::Edit, vec![replacement(input)?]),
+        "MultiEdit" => {
+            let hunks: Vec<EditHunk> = input
+                .get("edits")
+                .and_then(Value::as_array)?
+                .iter()
+                .filter_map(replacement)
+                .collect();
+            (FileEditKind::Edit, hunks)
+        }
+        _ => return None,
+    };
+    Some(FileEdit {
+        tool_use_id: tool_use_id.to_string(),
+        path: path.to_string(),
+        kind,
+        hunks,
+    })
+}
+
+fn replacement(edit: &Value) -> Option<EditHunk> {
+    let old_text = edit.get("old_string").and_then(Value::as_str)?;
+    let new_text = edit.get("new_string").and_then(Value::as_str).unwrap_or("");
+    let replace_all = edit
+        .get("replace_all")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    Some(hunk(old_text, new_text, replace_all))
+}
+
+fn hunk(old_text: &str, new_text: &str, replace_all: bool) -> EditHunk {
+    EditHunk {
+        old_text: old_text.to_string(),
+        new_text: new_text.to_string(),
+        replace_all,
+        old_start: None,
+        old_lines: line_count(old_text),
+        new_start: None,
+        new_lines: line_count(new_text),
+    }
+}
+
+fn line_count(text: &str) -> usize {
+    text.lines().count()
+}
+
+/// Place the hunks in `original`, the file as it was before the tool ran. Hunks apply
+/// one after another, so each is looked up in the text the previous ones left; a
+/// `replaceAll` hunk is placed at its first occurrence. A `Write` over an existing file
+/// shows the whole old content as replaced.
+pub(crate) fn locate(edit: &mut FileEdit, original: Option<&str>) {
+    if edit.kind == FileEditKind::Write {
+        if let Some(hunk) = edit.hunks.first_mut() {
+            let old_text = original.unwrap_or("");
+            hunk.old_text = old_text.to_string();
+            hunk.old_lines = line_count(old_text);
+            hunk.old_start = Some(1);
+            hunk.new_start = Some(1);
+        }
+        return;
+    }
+    let Some(original) = original else {
+        return;
+    };
+    let mut content = original.to_string();
+    for hunk in &mut edit.hunks {
+        if hunk.old_text.is_empty() {
+            continue;
+        }
+        let Some(offset) = content.find(&hunk.old_text) else {
+            continue;
+        };
+        let start = content[..offset].matches('\n').count() + 1;
+        hunk.old_start = Some(start);
+        hunk.new_start = Some(start);
+        content = if hunk.replace_all {
+            content.replace(&hunk.old_text, &hunk.new_text)
+        } else {
+            content.replacen(&hunk.old_text, &hunk.new_text, 1)
+        };
+    }
+}
+
+/// The edit as a unified diff, for views that render diff text. Hunks that could not be
+/// placed get a bare `@@` header.
+pub(crate) fn unified_diff(edit: &FileEdit) -> String {
+    let mut diff = String::new();
+    for hunk in &edit.hunks {
+        match (hunk.old_start, hunk.new_start) {
+            (Some(old_start), Some(new_start)) => diff.push_str(&format!(
+                "@@ -{},{} +{},{} @@\n",
+                old_start, hunk.old_lines, new_start, hunk.new_lines
+            )),
+            _ => diff.push_str("@@\n"),
+        }
+        for line in hunk.old_text.lines() {
+            diff.push('-');
+            diff.push_str(line);
+            diff.push('\n');
+        }
+        for line in hunk.new_text.lines() {
+            diff.push('+');
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+/// `path` relative to the workspace when it lies inside it.
+fn workspace_relative(path: &str, root: &Path) -> String {
+    Path::new(path)
+        .strip_prefix(root)
+        .map(|relative| relative.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+fn read_original(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_ORIGINAL_BYTES {
+        return None;
+    }
+    fs::read_to_string(path).ok()
+}
+
+/// Collects the file edits of a turn's `tool_use` blocks as the events stream in.
+///
+/// Each file is read when its tool call is announced, before the CLI runs it, so the
+/// hunks can be placed in the content they apply to.
+#[derive(Debug, Default)]
+pub(crate) struct EditRecorder {
+    edits: Vec<FileEdit>,
+}
+
+impl EditRecorder {
+    pub(crate) fn start(&mut self) {
+        self.edits.clear();
+    }
+
+    pub(crate) fn observe(&mut self, event: &Value, root: &Path) {
+        if event.get("type").and_then(Value::as_str) != Some("assistant") {
+            return;
+        }
+        let blocks = event
+            .get("message")
+            .and_then(|message| message.get("content"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|block| block.get("type").and_then(Value::as_str) == Some("tool_use"));
+        for block in blocks {
+            let id = block.get("id").and_then(Value::as_str).unwrap_or("");
+            if !id.is_empty() && self.edits.iter().any(|edit| edit.tool_use_id == id) {
+                continue;
+            }
+            let name = block.get("name").and_then(Value::as_str).unwrap_or("");
+            let input = block.get("input").unwrap_or(&Value::Null);
+            let Some(mut edit) = parse_file_edit(id, name, input) else {
+                continue;
+            };
+            let full_path = root.join(&edit.path);
+            locate(&mut edit, read_original(&full_path).as_deref());
+            edit.path = workspace_relative(&full_path.to_string_lossy(), root);
+            self.edits.push(edit);
+        }
+    }
+
+    /// The turn's edits, or `None` if it made none. Resets the recorder.
+    pub(crate) fn finish(
+        &mut self,
+        workspace_id: &str,
+        thread_id: &str,
+        turn_id: &str,
+        now: i64,
+    ) -> Option<TurnEdits> {
+        let edits = std::mem::take(&mut self.edits);
+        if edits.is_empty() {
+            return None;
+        }
+        Some(TurnEdits {
+            workspace_id: workspace_id.to_string(),
+            thread_id: thread_id.to_string(),
+            turn_id: turn_id.to_string(),
+            created_at: now,
+            edits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_multi_edit_hunks() {
+        let input = json!({
+            "file_path": "/repo/src/lib.rs",
+            "edits": [
+                { "old_string": "fn a() {}", "new_string": "fn a() {\n    b();\n}" },
+                { "old_string": "use x;", "new_string": "", "replace_all": true },
+            ],
+        });
+        let edit = parse_file_edit("toolu_1", "MultiEdit", &input).unwrap();
+        assert_eq!(edit.path, "/repo/src/lib.rs");
+        assert_eq!(edit.kind, FileEditKind::Edit);
+        assert_eq!(edit.hunks.len(), 2);
+        assert_eq!(edit.hunks[0].new_lines, 3);
+        assert!(edit.hunks[1].replace_all);
+        assert_eq!(edit.hunks[1].new_lines, 0);
+        assert!(parse_file_edit("toolu_2", "Read", &input).is_none());
+    }
+
+    #[test]
+    fn locates_hunks_in_sequence() {
+        let input = json!({
+            "file_path": "notes.txt",
+            "edits": [
+                { "old_string": "two", "new_string": "2\n2b" },
+                { "old_string": "three", "new_string": "3" },
+            ],
+        });
+        let mut edit = parse_file_edit("toolu_1", "MultiEdit", &input).unwrap();
+        locate(&mut edit, Some("one\ntwo\nthree\n"));
+        assert_eq!(edit.hunks[0].old_start, Some(2));
+        assert_eq!(edit.hunks[1].old_start, Some(4));
+        assert_eq!(
+            unified_diff(&edit),
+            "@@ -2,1 +2,2 @@\n-two\n+2\n+2b\n@@ -4,1 +4,1 @@\n-three\n+3\n"
+        );
+    }
+
+    #[test]
+    fn write_replaces_the_whole_file() {
+        let input = json!({ "file_path": "a.txt", "content": "new\n" });
+        let mut edit = parse_file_edit("toolu_1", "Write", &input).unwrap();
+        assert_eq!(unified_diff(&edit), "@@\n+new\n");
+        locate(&mut edit, Some("old\nlines\n"));
+        assert_eq!(unified_diff(&edit), "@@ -1,2 +1,1 @@\n-old\n-lines\n+new\n");
+    }
+
+    #[test]
+    fn records_edits_relative_to_the_workspace() {
+        let root = std::env::temp_dir().join(format!("edit-hunks-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("main.rs"), "fn main() {}\n").unwrap();
+        let event = json!({
+            "type": "assistant",
+            "message": { "content": [{
+                "type": "tool_use",
+                "id": "toolu_1",
+                "name": "Edit",
+                "input": {
+                    "file_path": root.join("main.rs"),
+                    "old_string": "fn main() {}",
+                    "new_string": "fn main() {\n    run();\n}",
+                },
+            }] },
+        });
+        let mut recorder = EditRecorder::default();
+        recorder.start();
+        recorder.observe(&event, &root);
+        recorder.observe(&event, &root);
+        let edits = recorder.finish("ws-1", "thread-1", "turn-1", 10).unwrap();
+        assert_eq!(edits.edits.len(), 1);
+        assert_eq!(edits.edits[0].path, "main.rs");
+        assert_eq!(edits.edits[0].hunks[0].old_start, Some(1));
+        assert!(recorder.finish("ws-1", "thread-1", "turn-2", 20).is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,6 +1,10 @@
 use serde::Serialize;
 use serde_json::Value;
 
+use crate::backend::batch_run::BatchRun;
+use crate::backend::pipeline_run::PipelineRun;
+use crate::backend::turn_queue::QueuedPromptInfo;
+
 #[derive(Serialize, Clone)]
 pub(crate) struct AppServerEvent {
     pub(crate) workspace_id: String,
@@ -16,7 +20,81 @@ pub(crate) struct TerminalOutput {
     pub(crate) data: String,
 }
 
+/// A workspace terminal whose shell exited on its own.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TerminalExit {
+    pub(crate) workspace_id: String,
+    pub(crate) terminal_id: String,
+    pub(crate) exit_code: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionDisconnected {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) reconnected: bool,
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnQueueUpdated {
+    pub(crate) workspace_id: String,
+    pub(crate) queue: Vec<QueuedPromptInfo>,
+}
+
+/// The whole batch after one of its workspaces changed state.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BatchProgress {
+    /// The workspace that just started or finished, if any.
+    pub(crate) workspace_id: Option<String>,
+    pub(crate) batch: BatchRun,
+}
+
+/// A pipeline after one of its steps started or ended.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PipelineProgress {
+    /// The workspace of the step that changed, if any.
+    pub(crate) workspace_id: Option<String>,
+    pub(crate) pipeline: PipelineRun,
+}
+
+/// What a Claude CLI update started with `update_claude_cli` is doing.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CliUpdateStage {
+    Started,
+    Output,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliUpdateProgress {
+    pub(crate) update_id: String,
+    /// The installation being updated.
+    pub(crate) path: String,
+    pub(crate) stage: CliUpdateStage,
+    /// A line of the installer's output.
+    pub(crate) line: Option<String>,
+    /// The version installed once finished.
+    pub(crate) version: Option<String>,
+    pub(crate) error: Option<String>,
+}
+
 pub(crate) trait EventSink: Clone + Send + Sync + 'static {
     fn emit_app_server_event(&self, event: AppServerEvent);
     fn emit_terminal_output(&self, event: TerminalOutput);
+    fn emit_terminal_exit(&self, event: TerminalExit);
+    fn emit_session_disconnected(&self, event: SessionDisconnected);
+    fn emit_turn_queue_updated(&self, event: TurnQueueUpdated);
+    fn emit_batch_progress(&self, event: BatchProgress);
+    fn emit_pipeline_progress(&self, event: PipelineProgress);
+    fn emit_cli_update_progress(&self, event: CliUpdateProgress);
 }
@@ -0,0 +1,403 @@
+//! Workspace guardrails that hold regardless of access mode or permission preset.
+//!
+//! A read-only ("observe only") workspace runs the CLI with every tool that can change
+//! files disallowed, denies any permission request for one that slips through, and has
+//! each turn checked against git afterwards; see `claude::spawn_turn_diff`.
+//!
+//! Path rules restrict the files Claude's file tools may touch. Denied paths become
+//! `Read(...)` and `Edit(...)` rules in `--disallowedTools`; the allowlist has no CLI
+//! equivalent, so file tools are taken out of `--allowedTools`, permission modes that
+//! edit without asking fall back to `default`, and every permission request is checked
+//! against both lists.
+
+use std::path::{Component, Path};
+
+use serde_json::Value;
+
+use crate::backend::control::PermissionDecision;
+use crate::types::{ClaudeCliFlags, PathRules, WorkspaceSettings};
+
+/// Tools a read-only workspace never gets. `Bash` is included because any shell command
+/// can write.
+pub(crate) const READ_ONLY_DISALLOWED_TOOLS: &[&str] =
+    &["Edit", "MultiEdit", "Write", "NotebookEdit", "Bash"];
+
+/// Tools whose input names the path they read or write.
+const FILE_TOOLS: &[&str] = &[
+    "Read",
+    "Edit",
+    "MultiEdit",
+    "Write",
+    "NotebookEdit",
+    "Grep",
+    "Glob",
+    "LS",
+];
+
+/// Input fields holding a file tool's path.
+const PATH_FIELDS: &[&str] = &["file_path", "filePath", "notebook_path", "path"];
+
+/// Modes, CLI or UI access modes, in which the CLI writes files without asking.
+const UNASKED_EDIT_MODES: &[&str] = &["acceptEdits", "bypassPermissions", "full-access"];
+
+/// The tool name of an `--allowedTools` entry such as `Bash(git log:*)`.
+fn tool_name(tool: &str) -> &str {
+    tool.split_once('(').map_or(tool, |(name, _)| name).trim()
+}
+
+/// Whether `tool`, possibly with a rule such as `Bash(git log:*)`, is one of the tools a
+/// read-only workspace disallows.
+fn is_mutating(tool: &str) -> bool {
+    READ_ONLY_DISALLOWED_TOOLS.contains(&tool_name(tool))
+}
+
+/// Apply the workspace's guardrails to the CLI flags it spawns with.
+pub(crate) fn apply_guardrails(settings: &WorkspaceSettings, flags: &mut ClaudeCliFlags) {
+    if settings.read_only {
+        apply_read_only(flags);
+    }
+    if let Some(rules) = &settings.path_rules {
+        apply_path_rules(rules, flags);
+    }
+}
+
+/// Restrict a read-only workspace's CLI flags: the mutating tools are disallowed and
+/// dropped from the allowed list, whatever the workspace configured.
+pub(crate) fn apply_read_only(flags: &mut ClaudeCliFlags) {
+    flags.allowed_tools.retain(|tool| !is_mutating(tool));
+    for tool in READ_ONLY_DISALLOWED_TOOLS {
+        push_unique(&mut flags.disallowed_tools, tool.to_string());
+    }
+}
+
+/// Disallow the denied paths and, with an allowlist, stop the CLI from running file
+/// tools without asking first, so every request reaches [`path_decision`].
+pub(crate) fn apply_path_rules(rules: &PathRules, flags: &mut ClaudeCliFlags) {
+    for pattern in rules.deny.iter().map(|pattern| pattern.trim()) {
+        if pattern.is_empty() {
+            continue;
+        }
+        push_unique(&mut flags.disallowed_tools, format!("Read({pattern})"));
+        push_unique(&mut flags.disallowed_tools, format!("Edit({pattern})"));
+    }
+    if has_allowlist(rules) {
+        flags
+            .allowed_tools
+            .retain(|tool| !FILE_TOOLS.contains(&tool_name(tool)));
+        if let Some(mode) = &mut flags.permission_mode {
+            if UNASKED_EDIT_MODES.contains(&mode.trim()) {
+                *mode = "default".to_string();
+            }
+        }
+    }
+}
+
+/// The permission or access mode a workspace's process may run in. The allowlist is only
+/// checked on permission requests, so with one, modes that write without asking become
+/// `default`.
+pub(crate) fn guard_permission_mode<'a>(settings: &WorkspaceSettings, mode: &'a str) -> &'a str {
+    let allowlisted = settings.path_rules.as_ref().is_some_and(has_allowlist);
+    if allowlisted && UNASKED_EDIT_MODES.contains(&mode.trim()) {
+        return "default";
+    }
+    mode
+}
+
+fn push_unique(tools: &mut Vec<String>, tool: String) {
+    if !tools.contains(&tool) {
+        tools.push(tool);
+    }
+}
+
+fn has_allowlist(rules: &PathRules) -> bool {
+    rules.allow.iter().any(|pattern| !pattern.trim().is_empty())
+}
+
+/// The answer a read-only workspace gives to a permission request for `tool_name`.
+pub(crate) fn read_only_decision(tool_name: &str) -> Option<PermissionDecision> {
+    is_mutating(tool_name).then(|| PermissionDecision::Deny {
+        message: Some(format!(
+            "The workspace is read-only; {tool_name} is not available."
+        )),
+        interrupt: false,
+    })
+}
+
+/// The denial for a file tool request that touches a path outside the workspace's path
+/// rules, or `None` when the request is within them.
+pub(crate) fn path_decision(
+    rules: &PathRules,
+    root: &Path,
+    tool_name: &str,
+    input: &Value,
+) -> Option<PermissionDecision> {
+    if !FILE_TOOLS.contains(&tool_name) {
+        return None;
+    }
+    let glob_base = (tool_name == "Glob").then(|| glob_base(input)).flatten();
+    let message = PATH_FIELDS
+        .iter()
+        .filter_map(|field| input.get(*field).and_then(Value::as_str))
+        .map(str::to_string)
+        .chain(glob_base)
+        .find_map(|path| path_violation(rules, root, &path))?;
+    Some(PermissionDecision::Deny {
+        message: Some(message),
+        interrupt: false,
+    })
+}
+
+/// The directory a `Glob` pattern searches from: the pattern's components before the
+/// first wildcard, under the tool's `path`. `None` when the pattern starts with one.
+fn glob_base(input: &Value) -> Option<String> {
+    let pattern = input.get("pattern").and_then(Value::as_str)?.trim();
+    let literal: Vec<&str> = pattern
+        .split('/')
+        .take_while(|part| !part.contains(['*', '?', '[', '{']))
+        .collect();
+    let prefix = literal.join("/");
+    if prefix.is_empty() {
+        return (pattern.starts_with('/')).then(|| "/".to_string());
+    }
+    match input.get("path").and_then(Value::as_str) {
+        Some(dir) if !Path::new(&prefix).is_absolute() => {
+            Some(format!("{}/{prefix}", dir.trim_end_matches('/')))
+        }
+        _ => Some(prefix),
+    }
+}
+
+/// Why `path` breaks the rules, if it does.
+fn path_violation(rules: &PathRules, root: &Path, path: &str) -> Option<String> {
+    let Some(relative) = workspace_path(path, root) else {
+        return has_allowlist(rules)
+            .then(|| format!("{path} is outside the workspace's allowed paths."));
+    };
+    if let Some(pattern) = rules
+        .deny
+        .iter()
+        .find(|pattern| glob_matches(pattern, &relative))
+    {
+        return Some(format!(
+            "{path} matches the workspace's denied path `{}`.",
+            pattern.trim()
+        ));
+    }
+    let allowed = !has_allowlist(rules)
+        || rules
+            .allow
+            .iter()
+            .any(|pattern| glob_matches(pattern, &relative));
+    (!allowed).then(|| format!("{path} is outside the workspace's allowed paths."))
+}
+
+/// `path` relative to `root` with `.` and `..` resolved, or `None` when it lies outside.
+fn workspace_path(path: &str, root: &Path) -> Option<String> {
+    let path = Path::new(path.trim());
+    let full = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+    let mut parts: Vec<String> = Vec::new();
+    for component in full.strip_prefix(root).ok()?.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            _ => {}
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Whether `pattern` matches the workspace-relative `path` or one of its directories.
+fn glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim();
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    let pattern = pattern.trim_matches('/');
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_suffix("/**").unwrap_or(pattern);
+    if pattern.is_empty() {
+        return false;
+    }
+    let pattern: Vec<char> = if anchored {
+        pattern.chars().collect()
+    } else {
+        "**/".chars().chain(pattern.chars()).collect()
+    };
+    let path: Vec<char> = path.chars().collect();
+    // A directory's rule covers everything inside it
+    (1..=path.len())
+        .filter(|end| *end == path.len() || path[*end] == '/')
+        .any(|end| glob_match(&pattern, &path[..end]))
+}
+
+fn glob_match(pattern: &[char], path: &[char]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // `**/` also matches no directory at all, but only starts at one
+            let (rest, whole_dirs) = match rest {
+                ['/', rest @ ..] => (rest, true),
+                rest => (rest, false),
+            };
+            (0..=path.len())
+                .filter(|start| !whole_dirs || *start == 0 || path[*start - 1] == '/')
+                .any(|start| glob_match(rest, &path[start..]))
+        }
+        ['*', rest @ ..] => (0..=path.len())
+            .take_while(|end| *end == 0 || path[*end - 1] != '/')
+            .any(|end| glob_match(rest, &path[end..])),
+        ['?', rest @ ..] => path.first().is_some_and(|c| *c != '/') && glob_match(rest, &path[1..]),
+        [c, rest @ ..] => path.first() == Some(c) && glob_match(rest, &path[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn read_only_overrides_configured_tools() {
+        let mut flags = ClaudeCliFlags {
+            allowed_tools: vec!["Read".to_string(), "Bash(git log:*)".to_string()],
+            disallowed_tools: vec!["Write".to_string()],
+            ..ClaudeCliFlags::default()
+        };
+        apply_read_only(&mut flags);
+        assert_eq!(flags.allowed_tools, ["Read"]);
+        assert_eq!(
+            flags.disallowed_tools,
+            ["Write", "Edit", "MultiEdit", "NotebookEdit", "Bash"]
+        );
+    }
+
+    #[test]
+    fn denies_mutating_tools_only() {
+        assert!(matches!(
+            read_only_decision("Edit"),
+            Some(PermissionDecision::Deny { .. })
+        ));
+        assert!(read_only_decision("Bash").is_some());
+        assert!(read_only_decision("Read").is_none());
+        assert!(read_only_decision("WebFetch").is_none());
+    }
+
+    #[test]
+    fn matches_globs() {
+        assert!(glob_matches("src/**", "src/lib.rs"));
+        assert!(glob_matches("src/**", "src/a/b/c.rs"));
+        assert!(glob_matches("src", "src/lib.rs"));
+        assert!(glob_matches("src/**", "src"));
+        assert!(!glob_matches("src/**", "lib/src/a.rs"));
+        assert!(!glob_matches("src/**", "srcs/lib.rs"));
+        assert!(glob_matches("src/*.rs", "src/lib.rs"));
+        assert!(!glob_matches("src/*.rs", "src/a/lib.rs"));
+        assert!(glob_matches("src/**/mod.rs", "src/mod.rs"));
+        assert!(glob_matches("src/**/mod.rs", "src/a/b/mod.rs"));
+        assert!(!glob_matches("src/**/mod.rs", "src/amod.rs"));
+        assert!(glob_matches("*.pem", "certs/server.pem"));
+        assert!(glob_matches(".env", ".env"));
+        assert!(glob_matches("./secrets/", "secrets/key"));
+        assert!(!glob_matches("?.rs", "ab.rs"));
+    }
+
+    #[test]
+    fn enforces_path_rules() {
+        let rules = PathRules {
+            allow: vec!["src/**".to_string()],
+            deny: vec!["src/secrets/**".to_string()],
+        };
+        let root = Path::new("/repo");
+        let decide = |tool: &str, path: &str| {
+            path_decision(&rules, root, tool, &json!({ "file_path": path })).is_some()
+        };
+        assert!(!decide("Edit", "/repo/src/lib.rs"));
+        assert!(!decide("Read", "src/main.rs"));
+        assert!(decide("Edit", "/repo/README.md"));
+        assert!(decide("Write", "/repo/src/secrets/key"));
+        assert!(decide("Read", "/repo/src/../Cargo.toml"));
+        assert!(decide("Read", "/etc/passwd"));
+        assert!(!decide("WebFetch", "/etc/passwd"));
+
+        let deny_only = PathRules {
+            deny: vec!["secrets".to_string()],
+            ..PathRules::default()
+        };
+        let input = json!({ "path": "/repo/secrets" });
+        assert!(path_decision(&deny_only, root, "Grep", &input).is_some());
+        let input = json!({ "file_path": "/etc/hosts" });
+        assert!(path_decision(&deny_only, root, "Read", &input).is_none());
+    }
+
+    #[test]
+    fn checks_where_glob_patterns_search() {
+        let rules = PathRules {
+            allow: vec!["src/**".to_string()],
+            deny: vec!["src/secrets/**".to_string()],
+        };
+        let root = Path::new("/repo");
+        let decide = |input: Value| path_decision(&rules, root, "Glob", &input).is_some();
+        assert!(!decide(json!({ "pattern": "src/**/*.rs" })));
+        assert!(!decide(
+            json!({ "pattern": "**/*.rs", "path": "/repo/src" })
+        ));
+        assert!(decide(json!({ "pattern": "../other/**" })));
+        assert!(decide(json!({ "pattern": "/etc/*.conf" })));
+        assert!(decide(json!({ "pattern": "/**/id_rsa" })));
+        assert!(decide(
+            json!({ "pattern": "secrets/*", "path": "/repo/src" })
+        ));
+        assert!(decide(json!({ "pattern": "*.rs", "path": "/repo/docs" })));
+        let grep = json!({ "pattern": "password", "path": "/repo/src/secrets" });
+        assert!(path_decision(&rules, root, "Grep", &grep).is_some());
+    }
+
+    #[test]
+    fn allowlists_keep_the_cli_asking_before_edits() {
+        let mut settings = WorkspaceSettings::default();
+        assert_eq!(
+            guard_permission_mode(&settings, "acceptEdits"),
+            "acceptEdits"
+        );
+        settings.path_rules = Some(PathRules {
+            allow: vec!["src/**".to_string()],
+            ..PathRules::default()
+        });
+        assert_eq!(guard_permission_mode(&settings, "acceptEdits"), "default");
+        assert_eq!(guard_permission_mode(&settings, "full-access"), "default");
+        assert_eq!(guard_permission_mode(&settings, "read-only"), "read-only");
+
+        let mut flags = ClaudeCliFlags {
+            permission_mode: Some("bypassPermissions".to_string()),
+            ..ClaudeCliFlags::default()
+        };
+        apply_path_rules(settings.path_rules.as_ref().unwrap(), &mut flags);
+        assert_eq!(flags.permission_mode.as_deref(), Some("default"));
+    }
+
+    #[test]
+    fn translates_path_rules_into_flags() {
+        let rules = PathRules {
+            allow: vec!["src/**".to_string()],
+            deny: vec!["secrets/**".to_string(), " ".to_string()],
+        };
+        let mut flags = ClaudeCliFlags {
+            allowed_tools: vec![
+                "Edit".to_string(),
+                "Edit(docs/**)".to_string(),
+                "Bash(npm test)".to_string(),
+            ],
+            ..ClaudeCliFlags::default()
+        };
+        apply_path_rules(&rules, &mut flags);
+        assert_eq!(flags.allowed_tools, ["Bash(npm test)"]);
+        assert_eq!(
+            flags.disallowed_tools,
+            ["Read(secrets/**)", "Edit(secrets/**)"]
+        );
+    }
+}
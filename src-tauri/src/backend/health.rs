@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// How long a persistent process gets to answer a control ping before it counts as degraded.
+pub(crate) const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Liveness of a thread's persistent CLI process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum HealthStatus {
+    /// Running, writable, and answering control requests.
+    Healthy,
+    /// Running but not answering, or its state could not be read.
+    Degraded,
+    /// Exited, missing, or its stdin pipe is broken.
+    Dead,
+    /// Stopped by focus mode; the next message or `resume_workspace_sessions` continues it.
+    Suspended,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionHealth {
+    pub(crate) thread_id: String,
+    pub(crate) status: HealthStatus,
+    pub(crate) pid: Option<u32>,
+    /// Round trip of the control ping, when it was answered.
+    pub(crate) latency_ms: Option<u64>,
+    /// Why the session is not healthy.
+    pub(crate) detail: Option<String>,
+    pub(crate) checked_at: i64,
+}
+
+impl SessionHealth {
+    pub(crate) fn healthy(thread_id: &str, pid: Option<u32>, latency: Duration) -> Self {
+        Self {
+            latency_ms: Some(latency.as_millis() as u64),
+            ..Self::new(thread_id, HealthStatus::Healthy, pid, None)
+        }
+    }
+
+    pub(crate) fn degraded(thread_id: &str, pid: Option<u32>, detail: impl Into<String>) -> Self {
+        Self::new(thread_id, HealthStatus::Degraded, pid, Some(detail.into()))
+    }
+
+    pub(crate) fn dead(thread_id: &str, pid: Option<u32>, detail: impl Into<String>) -> Self {
+        Self::new(thread_id, HealthStatus::Dead, pid, Some(detail.into()))
+    }
+
+    pub(crate) fn suspended(thread_id: &str, pid: Option<u32>) -> Self {
+        Self::new(
+            thread_id,
+            HealthStatus::Suspended,
+            pid,
+            Some("stopped by focus mode".to_string()),
+        )
+    }
+
+    fn new(
+        thread_id: &str,
+        status: HealthStatus,
+        pid: Option<u32>,
+        detail: Option<String>,
+    ) -> Self {
+        Self {
+            thread_id: thread_id.to_string(),
+            status,
+            pid,
+            latency_ms: None,
+            detail,
+            checked_at: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Everything a thread is waiting on the user for: permission prompts, questions and
+//! plans, keyed by tool use id with the time they were raised.
+//!
+//! With `interactionTimeouts` in a workspace's settings, requests nobody answers are
+//! reported stale after a while and, for unattended runs, answered with a default later
+//! on: permission requests are denied unless the tool is allowed by name, questions get
+//! their recommended option (else the first), and plans are approved only when the
+//! workspace opts in.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::backend::control::PermissionDecision;
+use crate::backend::user_questions::{Question, QuestionAnswer, QuestionAnswers};
+use crate::types::InteractionTimeouts;
+
+/// Free-text answer to a question that offers no options.
+const NO_ANSWER: &str = "Nobody was available to answer; use your best judgement.";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum InteractionKind {
+    Permission,
+    Question,
+    Plan,
+}
+
+/// A request waiting for the user.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PendingInteraction {
+    /// The tool use id, or the request id of a permission request without one.
+    pub(crate) key: String,
+    pub(crate) kind: InteractionKind,
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: String,
+    /// The control request to answer, for permission requests and plans.
+    pub(crate) request_id: Option<String>,
+    pub(crate) tool_name: String,
+    pub(crate) created_at: i64,
+    /// Reported as stale; it keeps waiting unless it is answered by default.
+    pub(crate) stale: bool,
+}
+
+/// What the interaction watchdog has to do about a request.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum InteractionDue {
+    Stale(PendingInteraction),
+    /// Answer with the default. The request has left the registry already, so it is
+    /// answered once.
+    AutoAnswer(PendingInteraction),
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct InteractionRegistry {
+    items: HashMap<String, PendingInteraction>,
+}
+
+impl InteractionRegistry {
+    pub(crate) fn open(&mut self, interaction: PendingInteraction) {
+        self.items.insert(interaction.key.clone(), interaction);
+    }
+
+    /// Close the request with this tool use id once it is answered.
+    pub(crate) fn close(&mut self, key: &str) -> Option<PendingInteraction> {
+        self.items.remove(key)
+    }
+
+    /// Close the request answered through this control request.
+    pub(crate) fn close_request(&mut self, request_id: &str) -> Option<PendingInteraction> {
+        let key = self
+            .items
+            .values()
+            .find(|item| item.request_id.as_deref() == Some(request_id))?
+            .key
+            .clone();
+        self.items.remove(&key)
+    }
+
+    /// Forget the requests of a thread whose process exited.
+    pub(crate) fn drain_thread(&mut self, thread_id: &str) -> Vec<PendingInteraction> {
+        let keys: Vec<String> = self
+            .items
+            .values()
+            .filter(|item| item.thread_id == thread_id)
+            .map(|item| item.key.clone())
+            .collect();
+        keys.iter()
+            .filter_map(|key| self.items.remove(key))
+            .collect()
+    }
+
+    /// Open requests, oldest first.
+    pub(crate) fn list(&self) -> Vec<PendingInteraction> {
+        let mut items: Vec<PendingInteraction> = self.items.values().cloned().collect();
+        items.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        items
+    }
+
+    /// Requests that went stale or are due for their default answer at `now_ms`. Stale
+    /// ones are reported once; due ones leave the registry.
+    pub(crate) fn check(
+        &mut self,
+        timeouts: &InteractionTimeouts,
+        now_ms: i64,
+    ) -> Vec<InteractionDue> {
+        let waited = |item: &PendingInteraction, seconds: Option<u32>| {
+            seconds.is_some_and(|seconds| {
+                seconds > 0 && now_ms - item.created_at >= i64::from(seconds) * 1000
+            })
+        };
+        let mut due = Vec::new();
+        for item in self.list() {
+            let answers_by_default =
+                item.kind != InteractionKind::Plan || timeouts.auto_approve_plans;
+            if answers_by_default && waited(&item, timeouts.auto_answer_after_seconds) {
+                self.items.remove(&item.key);
+                due.push(InteractionDue::AutoAnswer(item));
+            } else if !item.stale && waited(&item, timeouts.stale_after_seconds) {
+                if let Some(stored) = self.items.get_mut(&item.key) {
+                    stored.stale = true;
+                }
+                due.push(InteractionDue::Stale(PendingInteraction {
+                    stale: true,
+                    ..item
+                }));
+            }
+        }
+        due
+    }
+}
+
+/// The default answer to a permission request: allowed when the tool is listed in
+/// `autoAllowTools`, denied otherwise.
+pub(crate) fn default_permission(
+    timeouts: &InteractionTimeouts,
+    tool_name: &str,
+) -> PermissionDecision {
+    if timeouts
+        .auto_allow_tools
+        .iter()
+        .any(|tool| tool == tool_name)
+    {
+        return PermissionDecision::Allow {
+            updated_input: None,
+            always: false,
+        };
+    }
+    PermissionDecision::Deny {
+        message: Some(format!(
+            "Nobody answered this request for {tool_name} in time, so it was denied. \
+             Continue without it."
+        )),
+        interrupt: false,
+    }
+}
+
+/// The default answers to a set of questions: the options labelled as recommended, or
+/// the first option, or a note to go ahead without an answer.
+pub(crate) fn default_answers(questions: &[Question]) -> QuestionAnswers {
+    let answers = questions
+        .iter()
+        .map(|question| {
+            let recommended: Vec<String> = question
+                .options
+                .iter()
+                .filter(|option| option.label.to_lowercase().contains("recommended"))
+                .map(|option| option.label.clone())
+                .collect();
+            let mut chosen = match recommended.len() {
+                0 => question
+                    .options
+                    .first()
+                    .map(|option| vec![option.label.clone()])
+                    .unwrap_or_default(),
+                _ => recommended,
+            };
+            if !question.multi_select {
+                chosen.truncate(1);
+            }
+            if chosen.is_empty() {
+                chosen.push(NO_ANSWER.to_string());
+            }
+            (question.id.clone(), QuestionAnswer { answers: chosen })
+        })
+        .collect();
+    QuestionAnswers { answers }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::user_questions::{answer_text, parse_questions};
+    use serde_json::json;
+
+    fn interaction(key: &str, kind: InteractionKind, created_at: i64) -> PendingInteraction {
+        PendingInteraction {
+            key: key.to_string(),
+            kind,
+            thread_id: "thread-1".to_string(),
+            turn_id: "turn-1".to_string(),
+            request_id: Some(format!("req-{key}")),
+            tool_name: "Bash".to_string(),
+            created_at,
+            stale: false,
+        }
+    }
+
+    #[test]
+    fn reports_stale_requests_once_and_answers_due_ones() {
+        let timeouts = InteractionTimeouts {
+            stale_after_seconds: Some(60),
+            auto_answer_after_seconds: Some(300),
+            ..InteractionTimeouts::default()
+        };
+        let mut registry = InteractionRegistry::default();
+        registry.open(interaction("toolu_1", InteractionKind::Permission, 0));
+        registry.open(interaction("toolu_2", InteractionKind::Plan, 0));
+        registry.open(interaction("toolu_3", InteractionKind::Question, 250_000));
+
+        assert!(registry.check(&timeouts, 59_000).is_empty());
+        let due = registry.check(&timeouts, 60_000);
+        assert_eq!(due.len(), 2);
+        assert!(due
+            .iter()
+            .all(|due| matches!(due, InteractionDue::Stale(item) if item.stale)));
+        assert!(registry.check(&timeouts, 61_000).is_empty());
+
+        // Plans wait for the user unless the workspace approves them by default
+        let due = registry.check(&timeouts, 300_000);
+        assert_eq!(
+            due,
+            [InteractionDue::AutoAnswer(PendingInteraction {
+                stale: true,
+                ..interaction("toolu_1", InteractionKind::Permission, 0)
+            })]
+        );
+        assert_eq!(registry.list().len(), 2);
+        assert_eq!(
+            registry.close_request("req-toolu_2").map(|item| item.kind),
+            Some(InteractionKind::Plan)
+        );
+        assert_eq!(registry.drain_thread("thread-1").len(), 1);
+    }
+
+    #[test]
+    fn picks_default_answers() {
+        let timeouts = InteractionTimeouts {
+            auto_allow_tools: vec!["Read".to_string()],
+            ..InteractionTimeouts::default()
+        };
+        assert!(matches!(
+            default_permission(&timeouts, "Read"),
+            PermissionDecision::Allow { .. }
+        ));
+        assert!(matches!(
+            default_permission(&timeouts, "Bash"),
+            PermissionDecision::Deny { .. }
+        ));
+
+        let questions = parse_questions(
+            "toolu_1",
+            &json!({ "questions": [
+                { "question": "Which?", "options": [
+                    { "label": "Plain" },
+                    { "label": "Typed (Recommended)" }
+                ] },
+                { "question": "Any?", "multiSelect": true, "options": [
+                    { "label": "A" }, { "label": "B" }
+                ] },
+                { "question": "Name?" }
+            ]}),
+        );
+        let answers = default_answers(&questions);
+        assert_eq!(answers.answers["toolu_1"].answers, ["Typed (Recommended)"]);
+        assert_eq!(answers.answers["toolu_1-1"].answers, ["A"]);
+        assert_eq!(answers.answers["toolu_1-2"].answers, [NO_ANSWER]);
+        // The defaults pass the same checks as the user's answers
+        assert!(answer_text(&questions, &answers).is_ok());
+    }
+}
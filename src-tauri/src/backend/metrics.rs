@@ -0,0 +1,487 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+
+use serde_json::{json, Value};
+
+use crate::types::TurnUsage;
+
+/// Upper bounds of the turn duration histogram buckets, in seconds.
+const DURATION_BUCKETS: [f64; 10] = [
+    1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1200.0, 1800.0,
+];
+
+/// Per-workspace counters since the process started, as Prometheus expects of counters.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct WorkspaceCounters {
+    turns_started: u64,
+    turns_completed: u64,
+    turns_failed: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    cost_usd: f64,
+    /// Non-cumulative counts per bucket; the last slot is `+Inf`.
+    duration_buckets: [u64; DURATION_BUCKETS.len() + 1],
+    duration_sum_seconds: f64,
+}
+
+static REGISTRY: OnceLock<Mutex<BTreeMap<String, WorkspaceCounters>>> = OnceLock::new();
+
+fn with_registry<T>(f: impl FnOnce(&mut BTreeMap<String, WorkspaceCounters>) -> T) -> T {
+    let mut guard = REGISTRY
+        .get_or_init(|| Mutex::new(BTreeMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut guard)
+}
+
+pub(crate) fn record_turn_started(workspace_id: &str) {
+    with_registry(|registry| {
+        registry
+            .entry(workspace_id.to_string())
+            .or_default()
+            .turns_started += 1;
+    });
+}
+
+pub(crate) fn record_turn_finished(turn: &TurnUsage, failed: bool) {
+    with_registry(|registry| {
+        add_turn(
+            registry.entry(turn.workspace_id.clone()).or_default(),
+            turn,
+            failed,
+        )
+    });
+}
+
+fn add_turn(counters: &mut WorkspaceCounters, turn: &TurnUsage, failed: bool) {
+    if failed {
+        counters.turns_failed += 1;
+    } else {
+        counters.turns_completed += 1;
+    }
+    counters.input_tokens += turn.input_tokens;
+    counters.output_tokens += turn.output_tokens;
+    counters.cache_creation_tokens += turn.cache_creation_input_tokens;
+    counters.cache_read_tokens += turn.cache_read_input_tokens;
+    counters.cost_usd += turn.cost_usd;
+    // Wall time as the monitor saw it, falling back to the CLI's own figure
+    let duration_ms = turn
+        .timings
+        .as_ref()
+        .map(|timings| timings.wall_ms)
+        .or(turn.duration_ms);
+    if let Some(duration_ms) = duration_ms {
+        let seconds = duration_ms as f64 / 1000.0;
+        let bucket = DURATION_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(DURATION_BUCKETS.len());
+        counters.duration_buckets[bucket] += 1;
+        counters.duration_sum_seconds += seconds;
+    }
+}
+
+/// The Prometheus text exposition of every metric. `workspace_names` labels series with
+/// a readable name; `active_sessions` counts live CLI processes per workspace.
+pub(crate) fn render(
+    workspace_names: &HashMap<String, String>,
+    active_sessions: &HashMap<String, usize>,
+) -> String {
+    let counters = with_registry(|registry| registry.clone());
+    render_counters(&counters, workspace_names, active_sessions)
+}
+
+fn render_counters(
+    counters: &BTreeMap<String, WorkspaceCounters>,
+    workspace_names: &HashMap<String, String>,
+    active_sessions: &HashMap<String, usize>,
+) -> String {
+    let labels = |workspace_id: &str| {
+        let name = workspace_names
+            .get(workspace_id)
+            .map(String::as_str)
+            .unwrap_or("");
+        format!(
+            "workspace_id=\"{}\",workspace=\"{}\"",
+            escape_label(workspace_id),
+            escape_label(name)
+        )
+    };
+    let mut out = String::new();
+
+    header(
+        &mut out,
+        "claude_monitor_active_sessions",
+        "gauge",
+        "Claude CLI processes currently running.",
+    );
+    let mut active: Vec<(&String, &usize)> = active_sessions.iter().collect();
+    active.sort();
+    for (workspace_id, count) in active {
+        let _ = writeln!(
+            out,
+            "claude_monitor_active_sessions{{{}}} {count}",
+            labels(workspace_id)
+        );
+    }
+
+    let series: [(&str, &str, fn(&WorkspaceCounters) -> String); 4] = [
+        ("turns_started", "Turns sent to Claude.", |c| {
+            c.turns_started.to_string()
+        }),
+        (
+            "turns_completed",
+            "Turns that finished without an error.",
+            |c| c.turns_completed.to_string(),
+        ),
+        ("turns_failed", "Turns that finished with an error.", |c| {
+            c.turns_failed.to_string()
+        }),
+        (
+            "cost_usd",
+            "Cost reported by the CLI, in US dollars.",
+            |c| c.cost_usd.to_string(),
+        ),
+    ];
+    for (name, help, value) in series {
+        let name = format!("claude_monitor_{name}_total");
+        header(&mut out, &name, "counter", help);
+        for (workspace_id, counters) in counters {
+            let _ = writeln!(
+                out,
+                "{name}{{{}}} {}",
+                labels(workspace_id),
+                value(counters)
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "claude_monitor_tokens_total",
+        "counter",
+        "Tokens processed, by kind.",
+    );
+    for (workspace_id, counters) in counters {
+        for (kind, value) in [
+            ("input", counters.input_tokens),
+            ("output", counters.output_tokens),
+            ("cache_creation", counters.cache_creation_tokens),
+            ("cache_read", counters.cache_read_tokens),
+        ] {
+            let _ = writeln!(
+                out,
+                "claude_monitor_tokens_total{{{},kind=\"{kind}\"}} {value}",
+                labels(workspace_id)
+            );
+        }
+    }
+
+    let name = "claude_monitor_turn_duration_seconds";
+    header(&mut out, name, "histogram", "Wall-clock duration of turns.");
+    for (workspace_id, counters) in counters {
+        let labels = labels(workspace_id);
+        let mut cumulative = 0;
+        for (index, count) in counters.duration_buckets.iter().enumerate() {
+            cumulative += count;
+            let bound = DURATION_BUCKETS
+                .get(index)
+                .map(f64::to_string)
+                .unwrap_or_else(|| "+Inf".to_string());
+            let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {cumulative}");
+        }
+        let _ = writeln!(
+            out,
+            "{name}_sum{{{labels}}} {}",
+            counters.duration_sum_seconds
+        );
+        let _ = writeln!(out, "{name}_count{{{labels}}} {cumulative}");
+    }
+    out
+}
+
+/// The same metrics as OTLP JSON `metrics`, cumulative since `start_unix_nanos`, for
+/// the daemon's OpenTelemetry export.
+pub(crate) fn otlp_metrics(
+    workspace_names: &HashMap<String, String>,
+    active_sessions: &HashMap<String, usize>,
+    start_unix_nanos: u64,
+    now_unix_nanos: u64,
+) -> Vec<Value> {
+    let counters = with_registry(|registry| registry.clone());
+    otlp_counters(
+        &counters,
+        workspace_names,
+        active_sessions,
+        start_unix_nanos,
+        now_unix_nanos,
+    )
+}
+
+fn otlp_counters(
+    counters: &BTreeMap<String, WorkspaceCounters>,
+    workspace_names: &HashMap<String, String>,
+    active_sessions: &HashMap<String, usize>,
+    start_unix_nanos: u64,
+    now_unix_nanos: u64,
+) -> Vec<Value> {
+    let (start, now) = (start_unix_nanos.to_string(), now_unix_nanos.to_string());
+    let attributes = |workspace_id: &str, extra: Option<(&str, &str)>| {
+        let name = workspace_names
+            .get(workspace_id)
+            .map(String::as_str)
+            .unwrap_or("");
+        let mut attributes = vec![
+            json!({ "key": "workspace.id", "value": { "stringValue": workspace_id } }),
+            json!({ "key": "workspace.name", "value": { "stringValue": name } }),
+        ];
+        if let Some((key, value)) = extra {
+            attributes.push(json!({ "key": key, "value": { "stringValue": value } }));
+        }
+        attributes
+    };
+    let sum = |name: &str, description: &str, unit: &str, points: Vec<Value>| {
+        json!({
+            "name": name,
+            "description": description,
+            "unit": unit,
+            "sum": {
+                "aggregationTemporality": 2,
+                "isMonotonic": true,
+                "dataPoints": points,
+            },
+        })
+    };
+    let int_point = |workspace_id: &str, extra: Option<(&str, &str)>, value: u64| {
+        json!({
+            "attributes": attributes(workspace_id, extra),
+            "startTimeUnixNano": start,
+            "timeUnixNano": now,
+            "asInt": value.to_string(),
+        })
+    };
+    let mut metrics = Vec::new();
+
+    let mut active: Vec<(&String, &usize)> = active_sessions.iter().collect();
+    active.sort();
+    metrics.push(json!({
+        "name": "claude_monitor.active_sessions",
+        "description": "Claude CLI processes currently running.",
+        "unit": "{session}",
+        "gauge": {
+            "dataPoints": active
+                .into_iter()
+                .map(|(workspace_id, count)| json!({
+                    "attributes": attributes(workspace_id, None),
+                    "timeUnixNano": now,
+                    "asInt": count.to_string(),
+                }))
+                .collect::<Vec<_>>(),
+        },
+    }));
+
+    let turns: [(&str, &str, fn(&WorkspaceCounters) -> u64); 3] = [
+        ("started", "Turns sent to Claude.", |c| c.turns_started),
+        ("completed", "Turns that finished without an error.", |c| {
+            c.turns_completed
+        }),
+        ("failed", "Turns that finished with an error.", |c| {
+            c.turns_failed
+        }),
+    ];
+    for (name, description, value) in turns {
+        let points = counters
+            .iter()
+            .map(|(workspace_id, counters)| int_point(workspace_id, None, value(counters)))
+            .collect();
+        metrics.push(sum(
+            &format!("claude_monitor.turns.{name}"),
+            description,
+            "{turn}",
+            points,
+        ));
+    }
+
+    let points = counters
+        .iter()
+        .map(|(workspace_id, counters)| {
+            json!({
+                "attributes": attributes(workspace_id, None),
+                "startTimeUnixNano": start,
+                "timeUnixNano": now,
+                "asDouble": counters.cost_usd,
+            })
+        })
+        .collect();
+    metrics.push(sum(
+        "claude_monitor.cost",
+        "Cost reported by the CLI, in US dollars.",
+        "USD",
+        points,
+    ));
+
+    let points = counters
+        .iter()
+        .flat_map(|(workspace_id, counters)| {
+            [
+                ("input", counters.input_tokens),
+                ("output", counters.output_tokens),
+                ("cache_creation", counters.cache_creation_tokens),
+                ("cache_read", counters.cache_read_tokens),
+            ]
+            .map(|(kind, value)| int_point(workspace_id, Some(("kind", kind)), value))
+        })
+        .collect();
+    metrics.push(sum(
+        "claude_monitor.tokens",
+        "Tokens processed, by kind.",
+        "{token}",
+        points,
+    ));
+
+    let points: Vec<Value> = counters
+        .iter()
+        .map(|(workspace_id, counters)| {
+            json!({
+                "attributes": attributes(workspace_id, None),
+                "startTimeUnixNano": start,
+                "timeUnixNano": now,
+                "count": counters.duration_buckets.iter().sum::<u64>().to_string(),
+                "sum": counters.duration_sum_seconds,
+                "bucketCounts": counters
+                    .duration_buckets
+                    .iter()
+                    .map(u64::to_string)
+                    .collect::<Vec<_>>(),
+                "explicitBounds": DURATION_BUCKETS,
+            })
+        })
+        .collect();
+    metrics.push(json!({
+        "name": "claude_monitor.turn.duration",
+        "description": "Wall-clock duration of turns.",
+        "unit": "s",
+        "histogram": {
+            "aggregationTemporality": 2,
+            "dataPoints": points,
+        },
+    }));
+    metrics
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(duration_ms: u64, cost_usd: f64) -> TurnUsage {
+        TurnUsage {
+            workspace_id: "ws-1".to_string(),
+            session_id: "s1".to_string(),
+            turn_id: "t1".to_string(),
+            timestamp: 0,
+            model: None,
+            input_tokens: 100,
+            output_tokens: 20,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 5,
+            cost_usd,
+            duration_ms: Some(duration_ms),
+            timings: None,
+            is_error: false,
+        }
+    }
+
+    #[test]
+    fn renders_counters_and_histograms() {
+        let mut counters = WorkspaceCounters {
+            turns_started: 3,
+            ..WorkspaceCounters::default()
+        };
+        add_turn(&mut counters, &turn(4_000, 0.25), false);
+        add_turn(&mut counters, &turn(90_000, 0.5), true);
+        add_turn(&mut counters, &turn(3_600_000, 0.0), false);
+        let registry = BTreeMap::from([("ws-1".to_string(), counters)]);
+        let names = HashMap::from([("ws-1".to_string(), "my \"app\"".to_string())]);
+        let active = HashMap::from([("ws-1".to_string(), 2)]);
+
+        let text = render_counters(&registry, &names, &active);
+        let labels = r#"workspace_id="ws-1",workspace="my \"app\"""#;
+        for line in [
+            format!("claude_monitor_active_sessions{{{labels}}} 2"),
+            format!("claude_monitor_turns_started_total{{{labels}}} 3"),
+            format!("claude_monitor_turns_completed_total{{{labels}}} 2"),
+            format!("claude_monitor_turns_failed_total{{{labels}}} 1"),
+            format!("claude_monitor_cost_usd_total{{{labels}}} 0.75"),
+            format!("claude_monitor_tokens_total{{{labels},kind=\"input\"}} 300"),
+            format!("claude_monitor_turn_duration_seconds_bucket{{{labels},le=\"5\"}} 1"),
+            format!("claude_monitor_turn_duration_seconds_bucket{{{labels},le=\"120\"}} 2"),
+            format!("claude_monitor_turn_duration_seconds_bucket{{{labels},le=\"1800\"}} 2"),
+            format!("claude_monitor_turn_duration_seconds_bucket{{{labels},le=\"+Inf\"}} 3"),
+            format!("claude_monitor_turn_duration_seconds_count{{{labels}}} 3"),
+            "# TYPE claude_monitor_turn_duration_seconds histogram".to_string(),
+        ] {
+            assert!(
+                text.lines().any(|candidate| candidate == line),
+                "missing {line}"
+            );
+        }
+    }
+
+    #[test]
+    fn encodes_otlp_sums_and_histograms() {
+        let mut counters = WorkspaceCounters {
+            turns_started: 2,
+            ..WorkspaceCounters::default()
+        };
+        add_turn(&mut counters, &turn(4_000, 0.25), false);
+        add_turn(&mut counters, &turn(90_000, 0.5), true);
+        let registry = BTreeMap::from([("ws-1".to_string(), counters)]);
+        let names = HashMap::from([("ws-1".to_string(), "app".to_string())]);
+        let active = HashMap::from([("ws-1".to_string(), 1)]);
+
+        let metrics = otlp_counters(&registry, &names, &active, 10, 20);
+        let metric = |name: &str| {
+            metrics
+                .iter()
+                .find(|metric| metric["name"] == name)
+                .unwrap_or_else(|| panic!("missing {name}"))
+        };
+        assert_eq!(
+            metric("claude_monitor.active_sessions")["gauge"]["dataPoints"][0]["asInt"],
+            "1"
+        );
+        let failed = &metric("claude_monitor.turns.failed")["sum"];
+        assert_eq!(failed["isMonotonic"], true);
+        assert_eq!(failed["dataPoints"][0]["asInt"], "1");
+        assert_eq!(failed["dataPoints"][0]["startTimeUnixNano"], "10");
+        assert_eq!(
+            failed["dataPoints"][0]["attributes"][1],
+            json!({ "key": "workspace.name", "value": { "stringValue": "app" } })
+        );
+        let tokens = &metric("claude_monitor.tokens")["sum"]["dataPoints"];
+        assert_eq!(tokens.as_array().map(Vec::len), Some(4));
+        assert_eq!(tokens[1]["asInt"], "40");
+        let duration = &metric("claude_monitor.turn.duration")["histogram"]["dataPoints"][0];
+        assert_eq!(duration["count"], "2");
+        assert_eq!(duration["sum"], 94.0);
+        assert_eq!(
+            duration["bucketCounts"],
+            json!(["0", "1", "0", "0", "0", "1", "0", "0", "0", "0", "0"])
+        );
+    }
+}
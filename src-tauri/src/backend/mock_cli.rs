@@ -0,0 +1,429 @@
+//! A stand-in for the `claude` binary that replays canned stream-json, so the app and
+//! its tests can run turns without an Anthropic account.
+//!
+//! It accepts the flags the monitor passes to `claude` and speaks the same stdin
+//! protocol: each user message plays the next turn of the fixture in
+//! `CLAUDE_CODE_MONITOR_MOCK_FIXTURE`, cycling when it runs out. Without a fixture a
+//! built-in turn echoes the prompt. Fixtures are JSONL files of stream-json events:
+//!
+//! - a `result` event ends a turn;
+//! - `{"type":"mock_delay","ms":500}` pauses; other events wait
+//!   `CLAUDE_CODE_MONITOR_MOCK_DELAY_MS` (50 by default);
+//! - a `control_request` waits for the monitor's `control_response` before going on;
+//! - `{{prompt}}` and `{{session_id}}` are replaced in every line.
+//!
+//! Interrupts and health pings are acknowledged like the real CLI does.
+
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+pub(crate) const FIXTURE_ENV: &str = "CLAUDE_CODE_MONITOR_MOCK_FIXTURE";
+pub(crate) const DELAY_ENV: &str = "CLAUDE_CODE_MONITOR_MOCK_DELAY_MS";
+const DEFAULT_DELAY_MS: u64 = 50;
+const VERSION: &str = "0.0.0 (Mock Claude Code)";
+
+/// One turn of a fixture: raw event lines, placeholders not yet filled in.
+type Turn = Vec<String>;
+
+/// Split fixture lines into turns, each ending with its `result` event. Trailing
+/// events without a result form a last turn of their own.
+pub(crate) fn parse_fixture(text: &str) -> Result<Vec<Turn>, String> {
+    let mut turns = Vec::new();
+    let mut turn = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let event: Value = serde_json::from_str(line)
+            .map_err(|err| format!("fixture line {}: {err}", index + 1))?;
+        turn.push(line.to_string());
+        if event.get("type").and_then(Value::as_str) == Some("result") {
+            turns.push(std::mem::take(&mut turn));
+        }
+    }
+    if !turn.is_empty() {
+        turns.push(turn);
+    }
+    if turns.is_empty() {
+        return Err("fixture has no events".to_string());
+    }
+    Ok(turns)
+}
+
+/// The turn played when no fixture is configured.
+fn default_turn() -> Turn {
+    [
+        json!({"type": "system", "subtype": "init", "session_id": "{{session_id}}",
+               "model": "mock", "tools": ["Bash", "Read", "Edit"]}),
+        json!({"type": "assistant", "session_id": "{{session_id}}",
+               "message": {"role": "assistant", "model": "mock",
+                           "content": [{"type": "text", "text": "Mock reply to: {{prompt}}"}],
+                           "usage": {"input_tokens": 10, "output_tokens": 5}}}),
+        json!({"type": "result", "subtype": "success", "is_error": false,
+               "result": "Mock reply to: {{prompt}}", "session_id": "{{session_id}}",
+               "duration_ms": 1, "num_turns": 1, "total_cost_usd": 0.0,
+               "usage": {"input_tokens": 10, "output_tokens": 5}}),
+    ]
+    .iter()
+    .map(Value::to_string)
+    .collect()
+}
+
+/// Fill in `{{prompt}}` and `{{session_id}}`. Values are JSON-escaped so the line stays
+/// valid wherever the placeholder sits inside a string.
+pub(crate) fn fill_placeholders(line: &str, prompt: &str, session_id: &str) -> String {
+    let escape = |value: &str| {
+        let quoted = Value::from(value).to_string();
+        quoted[1..quoted.len() - 1].to_string()
+    };
+    line.replace("{{prompt}}", &escape(prompt))
+        .replace("{{session_id}}", &escape(session_id))
+}
+
+fn control_response(request_id: &str, subtype: &str) -> Value {
+    json!({
+        "type": "control_response",
+        "response": { "subtype": subtype, "request_id": request_id },
+    })
+}
+
+fn user_prompt(frame: &Value) -> String {
+    match frame.pointer("/message/content") {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Replays turns against frames arriving on stdin.
+pub(crate) struct MockSession<W: Write> {
+    turns: Vec<Turn>,
+    next_turn: usize,
+    session_id: String,
+    delay: Duration,
+    out: W,
+    /// User messages that arrived while a turn was playing
+    queued: VecDeque<String>,
+}
+
+/// How a wait for stdin ended.
+enum Wait {
+    Elapsed,
+    /// The awaited `control_response` arrived
+    Answered,
+    Interrupted,
+    Closed,
+}
+
+impl<W: Write> MockSession<W> {
+    pub(crate) fn new(turns: Vec<Turn>, session_id: String, delay: Duration, out: W) -> Self {
+        Self {
+            turns,
+            next_turn: 0,
+            session_id,
+            delay,
+            out,
+            queued: VecDeque::new(),
+        }
+    }
+
+    fn write(&mut self, event: &Value) -> io::Result<()> {
+        writeln!(self.out, "{event}")?;
+        self.out.flush()
+    }
+
+    /// Serve frames until stdin closes.
+    pub(crate) fn run(&mut self, frames: &Receiver<Value>) -> io::Result<()> {
+        loop {
+            let prompt = match self.queued.pop_front() {
+                Some(prompt) => prompt,
+                None => match frames.recv() {
+                    Ok(frame) => match frame.get("type").and_then(Value::as_str) {
+                        Some("user") => user_prompt(&frame),
+                        Some("control_request") => {
+                            // Nothing is running, so interrupts and pings just succeed
+                            let request_id = frame["request_id"].as_str().unwrap_or("");
+                            self.write(&control_response(request_id, "success"))?;
+                            continue;
+                        }
+                        _ => continue,
+                    },
+                    Err(_) => return Ok(()),
+                },
+            };
+            if !self.play_turn(&prompt, frames)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Play the next fixture turn. Returns `false` once stdin has closed.
+    fn play_turn(&mut self, prompt: &str, frames: &Receiver<Value>) -> io::Result<bool> {
+        let turn = self.turns[self.next_turn % self.turns.len()].clone();
+        self.next_turn += 1;
+        for line in turn {
+            let line = fill_placeholders(&line, prompt, &self.session_id);
+            let Ok(event) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+            let delay = match event.get("type").and_then(Value::as_str) {
+                Some("mock_delay") => {
+                    Duration::from_millis(event.get("ms").and_then(Value::as_u64).unwrap_or(0))
+                }
+                _ => self.delay,
+            };
+            match self.wait(frames, Instant::now() + delay, None)? {
+                Wait::Closed => return Ok(false),
+                Wait::Interrupted => return self.interrupted().map(|()| true),
+                Wait::Elapsed | Wait::Answered => {}
+            }
+            if event.get("type").and_then(Value::as_str) == Some("mock_delay") {
+                continue;
+            }
+            self.write(&event)?;
+            if event.get("type").and_then(Value::as_str) == Some("control_request") {
+                let request_id = event["request_id"].as_str().unwrap_or("").to_string();
+                match self.wait(frames, far_future(), Some(&request_id))? {
+                    Wait::Closed => return Ok(false),
+                    Wait::Interrupted => return self.interrupted().map(|()| true),
+                    Wait::Elapsed | Wait::Answered => {}
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn interrupted(&mut self) -> io::Result<()> {
+        self.write(&json!({
+            "type": "result",
+            "subtype": "error_during_execution",
+            "is_error": true,
+            "result": "Interrupted",
+            "session_id": self.session_id,
+            "total_cost_usd": 0.0,
+        }))
+    }
+
+    /// Handle frames until `deadline`, or until the `control_response` for `awaiting`.
+    fn wait(
+        &mut self,
+        frames: &Receiver<Value>,
+        deadline: Instant,
+        awaiting: Option<&str>,
+    ) -> io::Result<Wait> {
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(Wait::Elapsed);
+            }
+            let frame = match frames.recv_timeout(remaining) {
+                Ok(frame) => frame,
+                Err(RecvTimeoutError::Timeout) => return Ok(Wait::Elapsed),
+                Err(RecvTimeoutError::Disconnected) => return Ok(Wait::Closed),
+            };
+            match frame.get("type").and_then(Value::as_str) {
+                Some("user") => self.queued.push_back(user_prompt(&frame)),
+                Some("control_response") => {
+                    let answered = frame
+                        .pointer("/response/request_id")
+                        .and_then(Value::as_str);
+                    if awaiting.is_some() && answered == awaiting {
+                        return Ok(Wait::Answered);
+                    }
+                }
+                Some("control_request") => {
+                    let request_id = frame["request_id"].as_str().unwrap_or("").to_string();
+                    self.write(&control_response(&request_id, "success"))?;
+                    if frame.pointer("/request/subtype").and_then(Value::as_str)
+                        == Some("interrupt")
+                    {
+                        return Ok(Wait::Interrupted);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn far_future() -> Instant {
+    Instant::now() + Duration::from_secs(60 * 60 * 24)
+}
+
+fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
+
+/// Entry point of the `claude_code_monitor_mock_claude` binary.
+pub(crate) fn run() -> i32 {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--version" || arg == "-v") {
+        println!("{VERSION}");
+        return 0;
+    }
+    let turns = match env::var(FIXTURE_ENV) {
+        Ok(path) if !path.trim().is_empty() => {
+            match fs::read_to_string(&path)
+                .map_err(|err| format!("{path}: {err}"))
+                .and_then(|text| parse_fixture(&text))
+            {
+                Ok(turns) => turns,
+                Err(err) => {
+                    eprintln!("mock claude: {err}");
+                    return 1;
+                }
+            }
+        }
+        _ => vec![default_turn()],
+    };
+    let delay = env::var(DELAY_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_DELAY_MS);
+    let session_id = flag_value(&args, "--session-id")
+        .or_else(|| flag_value(&args, "--resume"))
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let (sender, frames) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Ok(frame) = serde_json::from_str::<Value>(&line) {
+                if sender.send(frame).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    let mut session = MockSession::new(
+        turns,
+        session_id,
+        Duration::from_millis(delay),
+        io::stdout(),
+    );
+    match session.run(&frames) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("mock claude: {err}");
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(out: &[u8]) -> Vec<Value> {
+        String::from_utf8_lossy(out)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn splits_fixtures_into_turns() {
+        let fixture = r#"
+            {"type":"assistant","message":{"content":[{"type":"text","text":"one"}]}}
+            {"type":"result","subtype":"success"}
+
+            {"type":"mock_delay","ms":10}
+            {"type":"result","subtype":"success"}
+            {"type":"assistant","message":{"content":[]}}
+        "#;
+        let turns = parse_fixture(fixture).unwrap();
+        assert_eq!(
+            turns.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        assert!(parse_fixture("not json")
+            .unwrap_err()
+            .starts_with("fixture line 1"));
+        assert!(parse_fixture("\n").is_err());
+    }
+
+    #[test]
+    fn escapes_placeholders() {
+        let line = r#"{"text":"You said: {{prompt}}","session_id":"{{session_id}}"}"#;
+        let filled = fill_placeholders(line, "say \"hi\"\n", "s-1");
+        let event: Value = serde_json::from_str(&filled).unwrap();
+        assert_eq!(event["text"], "You said: say \"hi\"\n");
+        assert_eq!(event["session_id"], "s-1");
+    }
+
+    #[test]
+    fn replays_turns_and_waits_for_permission_answers() {
+        let lines = [
+            json!({"type": "control_request", "request_id": "perm-1",
+                   "request": {"subtype": "can_use_tool", "tool_name": "Bash", "input": {}}}),
+            json!({"type": "result", "subtype": "success", "result": "{{prompt}}"}),
+        ];
+        let fixture = parse_fixture(&lines.map(|line| line.to_string()).join("\n")).unwrap();
+        let (sender, frames) = mpsc::channel();
+        sender
+            .send(json!({"type": "user", "message": {"role": "user", "content": "go"}}))
+            .unwrap();
+        sender
+            .send(json!({"type": "control_request", "request_id": "ping-1",
+                         "request": {"subtype": "mcp_status"}}))
+            .unwrap();
+        sender
+            .send(json!({"type": "control_response",
+                         "response": {"subtype": "success", "request_id": "perm-1"}}))
+            .unwrap();
+        drop(sender);
+
+        let mut session = MockSession::new(fixture, "s-1".to_string(), Duration::ZERO, Vec::new());
+        session.run(&frames).unwrap();
+        let events = events(&session.out);
+        let types: Vec<&str> = events
+            .iter()
+            .map(|event| event["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(types, vec!["control_request", "control_response", "result"]);
+        assert_eq!(events[1]["response"]["request_id"], "ping-1");
+        assert_eq!(events[2]["result"], "go");
+    }
+
+    #[test]
+    fn interrupts_end_the_turn() {
+        let fixture = parse_fixture(
+            r#"{"type":"mock_delay","ms":60000}
+               {"type":"result","subtype":"success"}"#,
+        )
+        .unwrap();
+        let (sender, frames) = mpsc::channel();
+        sender
+            .send(json!({"type": "user", "message": {"role": "user", "content": "go"}}))
+            .unwrap();
+        sender
+            .send(json!({"type": "control_request", "request_id": "int-1",
+                         "request": {"subtype": "interrupt"}}))
+            .unwrap();
+        drop(sender);
+
+        let mut session = MockSession::new(fixture, "s-1".to_string(), Duration::ZERO, Vec::new());
+        session.run(&frames).unwrap();
+        let events = events(&session.out);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["response"]["request_id"], "int-1");
+        assert_eq!(events[1]["is_error"], true);
+    }
+}
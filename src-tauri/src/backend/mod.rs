@@ -1,2 +1,33 @@
+pub(crate) mod agent;
+pub(crate) mod bash_policy;
+pub(crate) mod batch_run;
 pub(crate) mod claude_cli;
+pub(crate) mod cli_flags;
+pub(crate) mod compaction;
+pub(crate) mod control;
+pub(crate) mod delta_batcher;
+pub(crate) mod edit_hunks;
 pub(crate) mod events;
+pub(crate) mod guardrails;
+pub(crate) mod health;
+pub(crate) mod interactions;
+pub(crate) mod metrics;
+pub(crate) mod mock_cli;
+pub(crate) mod ownership;
+pub(crate) mod permission_presets;
+pub(crate) mod pipeline_run;
+pub(crate) mod plan_review;
+pub(crate) mod proc_stats;
+pub(crate) mod process;
+pub(crate) mod provider;
+pub(crate) mod rate_limits;
+pub(crate) mod recorder;
+pub(crate) mod retry;
+pub(crate) mod session_logs;
+pub(crate) mod stream_events;
+pub(crate) mod tool_timeline;
+pub(crate) mod turn_clock;
+pub(crate) mod turn_queue;
+pub(crate) mod turn_watchdog;
+pub(crate) mod usage_gauge;
+pub(crate) mod user_questions;
@@ -0,0 +1,145 @@
+//! Who drives each thread, for daemons shared by several people.
+//!
+//! The user who sends a message owns the thread from then on. While the thread has a
+//! turn running or queued, messages and interrupts from anyone else are refused until
+//! they take the thread over explicitly, so two people can't talk over each other's
+//! turn. The lock is advisory: it holds only against callers that say who they are,
+//! and an idle thread can be picked up by anyone.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+/// Stands in for the user when the environment names nobody.
+const UNKNOWN_USER: &str = "local";
+
+/// The user driving a thread.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadOwner {
+    pub(crate) thread_id: String,
+    pub(crate) user: String,
+    /// When this user started driving the thread.
+    pub(crate) since: i64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ThreadOwners {
+    owners: HashMap<String, ThreadOwner>,
+}
+
+impl ThreadOwners {
+    /// Refuse `user` when someone else owns the thread and it is `busy` with a turn.
+    pub(crate) fn check(&self, thread_id: &str, user: &str, busy: bool) -> Result<(), String> {
+        match self.owners.get(thread_id) {
+            Some(owner) if busy && owner.user != user => Err(format!(
+                "{} is driving this thread; take it over before sending or interrupting",
+                owner.user
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Record `user` as the thread's owner, keeping `since` when they already are.
+    pub(crate) fn claim(&mut self, thread_id: &str, user: &str, now: i64) {
+        let owner = self
+            .owners
+            .entry(thread_id.to_string())
+            .or_insert_with(|| ThreadOwner {
+                thread_id: thread_id.to_string(),
+                user: user.to_string(),
+                since: now,
+            });
+        if owner.user != user {
+            owner.user = user.to_string();
+            owner.since = now;
+        }
+    }
+
+    /// Hand the thread to `user` whoever holds it, returning the previous owner.
+    pub(crate) fn take_over(&mut self, thread_id: &str, user: &str, now: i64) -> Option<String> {
+        let previous = self
+            .owners
+            .get(thread_id)
+            .map(|owner| owner.user.clone())
+            .filter(|previous| previous != user);
+        self.claim(thread_id, user, now);
+        previous
+    }
+
+    pub(crate) fn get(&self, thread_id: &str) -> Option<&ThreadOwner> {
+        self.owners.get(thread_id)
+    }
+
+    /// Every owned thread, by thread id.
+    pub(crate) fn snapshot(&self) -> Vec<ThreadOwner> {
+        let mut owners: Vec<ThreadOwner> = self.owners.values().cloned().collect();
+        owners.sort_by(|a, b| a.thread_id.cmp(&b.thread_id));
+        owners
+    }
+}
+
+/// The user a request speaks for: the name it gives, else the local account.
+pub(crate) fn resolve_user(user: Option<String>) -> String {
+    user.map(|user| user.trim().to_string())
+        .filter(|user| !user.is_empty())
+        .unwrap_or_else(local_user)
+}
+
+/// The account the monitor runs as, sent along by apps in remote mode.
+pub(crate) fn local_user() -> String {
+    ["USER", "USERNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+        .map(|user| user.trim().to_string())
+        .filter(|user| !user.is_empty())
+        .unwrap_or_else(|| UNKNOWN_USER.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn busy_threads_refuse_other_users() {
+        let mut owners = ThreadOwners::default();
+        assert!(owners.check("thread-1", "alice", true).is_ok());
+        owners.claim("thread-1", "alice", 10);
+
+        assert!(owners.check("thread-1", "alice", true).is_ok());
+        let refused = owners.check("thread-1", "bob", true).unwrap_err();
+        assert!(refused.starts_with("alice is driving"));
+        assert!(owners.check("thread-1", "bob", false).is_ok());
+
+        owners.claim("thread-1", "alice", 20);
+        assert_eq!(owners.get("thread-1").unwrap().since, 10);
+    }
+
+    #[test]
+    fn takeover_hands_the_thread_over() {
+        let mut owners = ThreadOwners::default();
+        owners.claim("thread-1", "alice", 10);
+
+        assert_eq!(
+            owners.take_over("thread-1", "bob", 30),
+            Some("alice".to_string())
+        );
+        assert!(owners.check("thread-1", "bob", true).is_ok());
+        assert!(owners.check("thread-1", "alice", true).is_err());
+        assert_eq!(owners.take_over("thread-1", "bob", 40), None);
+        assert_eq!(
+            owners.snapshot(),
+            [ThreadOwner {
+                thread_id: "thread-1".to_string(),
+                user: "bob".to_string(),
+                since: 30,
+            }]
+        );
+    }
+
+    #[test]
+    fn named_users_win_over_the_local_account() {
+        assert_eq!(resolve_user(Some(" carol ".to_string())), "carol");
+        assert_eq!(resolve_user(Some("  ".to_string())), local_user());
+    }
+}
@@ -0,0 +1,68 @@
+//! What each `PermissionPreset` means for the CLI and for its permission requests.
+
+use crate::backend::control::PermissionDecision;
+use crate::types::PermissionPreset;
+
+/// Tools that change files.
+const EDIT_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+/// The `--permission-mode` a preset runs the CLI with.
+pub(crate) fn cli_permission_mode(preset: PermissionPreset) -> &'static str {
+    match preset {
+        PermissionPreset::Plan => "plan",
+        PermissionPreset::AcceptEdits => "acceptEdits",
+        PermissionPreset::AskEverything => "default",
+    }
+}
+
+/// The answer a preset gives to a `can_use_tool` request without asking the user, on
+/// top of what the CLI's permission mode already decides. `None` puts it to the user.
+pub(crate) fn preset_decision(
+    preset: PermissionPreset,
+    tool_name: &str,
+) -> Option<PermissionDecision> {
+    let edits = EDIT_TOOLS.contains(&tool_name);
+    match preset {
+        PermissionPreset::Plan if edits || tool_name == "Bash" => Some(PermissionDecision::Deny {
+            message: Some(format!(
+                "The workspace is in plan mode; {tool_name} is not available."
+            )),
+            interrupt: false,
+        }),
+        PermissionPreset::AcceptEdits if edits => Some(PermissionDecision::Allow {
+            updated_input: None,
+            always: false,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_decide_edits_and_commands() {
+        let decide = |preset, tool| preset_decision(preset, tool);
+        assert!(matches!(
+            decide(PermissionPreset::Plan, "Edit"),
+            Some(PermissionDecision::Deny { .. })
+        ));
+        assert!(matches!(
+            decide(PermissionPreset::Plan, "Bash"),
+            Some(PermissionDecision::Deny { .. })
+        ));
+        // Approving the plan is the user's call
+        assert!(decide(PermissionPreset::Plan, "ExitPlanMode").is_none());
+        assert!(matches!(
+            decide(PermissionPreset::AcceptEdits, "Write"),
+            Some(PermissionDecision::Allow { .. })
+        ));
+        assert!(decide(PermissionPreset::AcceptEdits, "Bash").is_none());
+        assert!(decide(PermissionPreset::AskEverything, "Edit").is_none());
+        assert_eq!(
+            cli_permission_mode(PermissionPreset::AskEverything),
+            "default"
+        );
+    }
+}
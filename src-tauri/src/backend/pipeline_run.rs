@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::stream_events::ResultEvent;
+use crate::prompt_library;
+
+/// The placeholder a step's prompt uses for the previous step's output.
+pub(crate) const PREVIOUS_PLACEHOLDER: &str = "previous";
+
+/// A step as requested: what to ask, and where.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PipelineStepSpec {
+    pub(crate) workspace_id: String,
+    pub(crate) prompt: String,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum PipelineStepStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    /// Not run because an earlier step failed.
+    Skipped,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum PipelineStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PipelineStep {
+    pub(crate) workspace_id: String,
+    pub(crate) workspace_name: String,
+    pub(crate) prompt: String,
+    pub(crate) model: Option<String>,
+    pub(crate) status: PipelineStepStatus,
+    /// The prompt actually sent, with the previous output filled in.
+    pub(crate) input: Option<String>,
+    pub(crate) output: Option<String>,
+    pub(crate) error: Option<String>,
+    pub(crate) cost_usd: Option<f64>,
+    pub(crate) started_at: Option<i64>,
+    pub(crate) finished_at: Option<i64>,
+}
+
+/// Steps run one after another, each fed the previous step's final message.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PipelineRun {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) status: PipelineStatus,
+    pub(crate) created_at: i64,
+    pub(crate) finished_at: Option<i64>,
+    pub(crate) current_step: Option<usize>,
+    pub(crate) total_cost_usd: f64,
+    pub(crate) steps: Vec<PipelineStep>,
+}
+
+/// The next step to run.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct NextStep {
+    pub(crate) index: usize,
+    pub(crate) workspace_id: String,
+    pub(crate) prompt: String,
+    pub(crate) model: Option<String>,
+}
+
+/// Check that every step's prompt can be filled: only `{previous}` is available, and
+/// not in the first step.
+pub(crate) fn validate_steps(steps: &[PipelineStepSpec]) -> Result<(), String> {
+    if steps.is_empty() {
+        return Err("A pipeline needs at least one step.".to_string());
+    }
+    for (index, step) in steps.iter().enumerate() {
+        if step.prompt.trim().is_empty() {
+            return Err(format!("Step {} has no prompt.", index + 1));
+        }
+        for name in prompt_library::placeholders(&step.prompt) {
+            if name != PREVIOUS_PLACEHOLDER {
+                return Err(format!(
+                    "Step {} uses {{{name}}}; only {{{PREVIOUS_PLACEHOLDER}}} is available.",
+                    index + 1
+                ));
+            }
+            if index == 0 {
+                return Err("The first step has no previous output to use.".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fill `{previous}` with the previous output, or append the output when the prompt
+/// does not say where it goes.
+pub(crate) fn compose_prompt(prompt: &str, previous: Option<&str>) -> String {
+    let Some(previous) = previous else {
+        return prompt.to_string();
+    };
+    if prompt_library::placeholders(prompt).is_empty() {
+        return format!("{prompt}\n\n{previous}");
+    }
+    let values = BTreeMap::from([(PREVIOUS_PLACEHOLDER.to_string(), previous.to_string())]);
+    // Steps are validated on creation, so `{previous}` is the only placeholder
+    prompt_library::render(prompt, &values).unwrap_or_else(|_| prompt.to_string())
+}
+
+impl PipelineRun {
+    /// `steps` pairs each requested step with its workspace's display name.
+    pub(crate) fn new(
+        id: String,
+        name: String,
+        steps: Vec<(PipelineStepSpec, String)>,
+        now: i64,
+    ) -> Self {
+        let steps = steps
+            .into_iter()
+            .map(|(spec, workspace_name)| PipelineStep {
+                workspace_id: spec.workspace_id,
+                workspace_name,
+                prompt: spec.prompt,
+                model: spec.model,
+                status: PipelineStepStatus::Pending,
+                input: None,
+                output: None,
+                error: None,
+                cost_usd: None,
+                started_at: None,
+                finished_at: None,
+            })
+            .collect();
+        Self {
+            id,
+            name,
+            status: PipelineStatus::Running,
+            created_at: now,
+            finished_at: None,
+            current_step: None,
+            total_cost_usd: 0.0,
+            steps,
+        }
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.status != PipelineStatus::Running
+    }
+
+    /// Mark the next pending step as running and build its prompt. `None` once the
+    /// pipeline is over.
+    pub(crate) fn start_next(&mut self, now: i64) -> Option<NextStep> {
+        let running = self
+            .steps
+            .iter()
+            .any(|step| step.status == PipelineStepStatus::Running);
+        if self.is_finished() || running {
+            return None;
+        }
+        let index = self
+            .steps
+            .iter()
+            .position(|step| step.status == PipelineStepStatus::Pending)?;
+        let previous = index
+            .checked_sub(1)
+            .and_then(|previous| self.steps[previous].output.clone());
+        let step = &mut self.steps[index];
+        let prompt = compose_prompt(&step.prompt, previous.as_deref());
+        step.status = PipelineStepStatus::Running;
+        step.input = Some(prompt.clone());
+        step.started_at = Some(now);
+        self.current_step = Some(index);
+        Some(NextStep {
+            index,
+            workspace_id: step.workspace_id.clone(),
+            prompt,
+            model: step.model.clone(),
+        })
+    }
+
+    /// Record a step's outcome. A failed step skips the rest of the pipeline.
+    pub(crate) fn finish_step(
+        &mut self,
+        index: usize,
+        outcome: Result<(String, Option<ResultEvent>), String>,
+        now: i64,
+    ) {
+        let is_last = index + 1 == self.steps.len();
+        let Some(step) = self.steps.get_mut(index) else {
+            return;
+        };
+        if step.status != PipelineStepStatus::Running {
+            return;
+        }
+        step.finished_at = Some(now);
+        let error = match outcome {
+            Ok((message, result)) => {
+                step.cost_usd = result.as_ref().and_then(|result| result.total_cost_usd);
+                match result.filter(|result| result.is_error) {
+                    Some(result) => Some(
+                        result
+                            .result
+                            .filter(|text| !text.trim().is_empty())
+                            .unwrap_or_else(|| "Claude reported an error.".to_string()),
+                    ),
+                    None if message.is_empty() && !is_last => {
+                        Some("The step finished without a reply to pass on.".to_string())
+                    }
+                    None => {
+                        step.output = Some(message);
+                        None
+                    }
+                }
+            }
+            Err(err) => Some(err),
+        };
+        match error {
+            Some(error) => {
+                step.status = PipelineStepStatus::Failed;
+                step.error = Some(error);
+                for later in &mut self.steps[index + 1..] {
+                    later.status = PipelineStepStatus::Skipped;
+                }
+                self.end(PipelineStatus::Failed, now);
+            }
+            None => {
+                step.status = PipelineStepStatus::Succeeded;
+                if is_last {
+                    self.end(PipelineStatus::Completed, now);
+                }
+            }
+        }
+        self.total_cost_usd = self.steps.iter().filter_map(|step| step.cost_usd).sum();
+    }
+
+    /// Stop the pipeline; the running step, if any, is abandoned.
+    pub(crate) fn cancel(&mut self, now: i64) {
+        if self.is_finished() {
+            return;
+        }
+        for step in &mut self.steps {
+            match step.status {
+                PipelineStepStatus::Running => {
+                    step.status = PipelineStepStatus::Cancelled;
+                    step.finished_at = Some(now);
+                }
+                PipelineStepStatus::Pending => step.status = PipelineStepStatus::Cancelled,
+                _ => {}
+            }
+        }
+        self.end(PipelineStatus::Cancelled, now);
+    }
+
+    fn end(&mut self, status: PipelineStatus, now: i64) {
+        self.status = status;
+        self.finished_at = Some(now);
+        self.current_step = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(workspace_id: &str, prompt: &str) -> PipelineStepSpec {
+        PipelineStepSpec {
+            workspace_id: workspace_id.to_string(),
+            prompt: prompt.to_string(),
+            model: None,
+        }
+    }
+
+    fn pipeline() -> PipelineRun {
+        PipelineRun::new(
+            "pipe-1".to_string(),
+            "plan then build".to_string(),
+            vec![
+                (
+                    spec("planner", "Plan the retry feature."),
+                    "planner".to_string(),
+                ),
+                (
+                    spec("builder", "Implement this plan:\n{previous}"),
+                    "builder".to_string(),
+                ),
+                (
+                    spec("reviewer", "Review the change."),
+                    "reviewer".to_string(),
+                ),
+            ],
+            0,
+        )
+    }
+
+    #[test]
+    fn validates_step_prompts() {
+        assert!(validate_steps(&[]).is_err());
+        assert!(validate_steps(&[spec("a", "Use {previous}")]).is_err());
+        assert!(validate_steps(&[spec("a", "Plan"), spec("b", "Fix {issue}")]).is_err());
+        assert!(validate_steps(&[spec("a", "Plan"), spec("b", "Do {previous}")]).is_ok());
+    }
+
+    #[test]
+    fn feeds_each_output_into_the_next_step() {
+        let mut run = pipeline();
+        let first = run.start_next(1).unwrap();
+        assert_eq!(first.prompt, "Plan the retry feature.");
+        assert!(run.start_next(1).is_none(), "one step at a time");
+        run.finish_step(0, Ok(("1. Add backoff".to_string(), None)), 2);
+        let second = run.start_next(2).unwrap();
+        assert_eq!(second.workspace_id, "builder");
+        assert_eq!(second.prompt, "Implement this plan:\n1. Add backoff");
+        run.finish_step(1, Ok(("Done".to_string(), None)), 3);
+        let third = run.start_next(3).unwrap();
+        assert_eq!(third.prompt, "Review the change.\n\nDone");
+        run.finish_step(2, Ok(("LGTM".to_string(), None)), 4);
+
+        assert_eq!(run.status, PipelineStatus::Completed);
+        assert_eq!(run.finished_at, Some(4));
+        assert!(run.start_next(5).is_none());
+    }
+
+    #[test]
+    fn a_failed_step_skips_the_rest_and_cancel_stops_everything() {
+        let mut run = pipeline();
+        run.start_next(1);
+        run.finish_step(0, Err("Claude CLI timed out".to_string()), 2);
+        assert_eq!(run.status, PipelineStatus::Failed);
+        assert_eq!(
+            run.steps.iter().map(|step| step.status).collect::<Vec<_>>(),
+            vec![
+                PipelineStepStatus::Failed,
+                PipelineStepStatus::Skipped,
+                PipelineStepStatus::Skipped,
+            ]
+        );
+
+        let mut run = pipeline();
+        run.start_next(1);
+        run.cancel(2);
+        assert_eq!(run.status, PipelineStatus::Cancelled);
+        // A late result for the abandoned step changes nothing
+        run.finish_step(0, Ok(("plan".to_string(), None)), 3);
+        assert_eq!(run.steps[0].status, PipelineStepStatus::Cancelled);
+        assert!(run.start_next(4).is_none());
+    }
+}
@@ -0,0 +1,182 @@
+//! Plan review for threads in plan mode.
+//!
+//! Claude ends planning with an `ExitPlanMode` tool call, which the CLI puts to the
+//! monitor as a `can_use_tool` request. Rather than a generic permission prompt it
+//! becomes the thread's [`PendingPlan`]: approving it lets the CLI leave plan mode and
+//! carry the plan out, rejecting it sends the user's feedback back so Claude revises the
+//! plan and proposes it again.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::backend::control::PermissionDecision;
+
+/// The tool Claude calls to present its plan.
+pub(crate) const EXIT_PLAN_TOOL: &str = "ExitPlanMode";
+
+/// A plan waiting for the user's review.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PendingPlan {
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: String,
+    /// The `can_use_tool` request the CLI is blocked on.
+    pub(crate) request_id: String,
+    pub(crate) tool_use_id: Option<String>,
+    /// The plan as Markdown.
+    pub(crate) plan: String,
+    pub(crate) proposed_at: i64,
+}
+
+/// Plans of a workspace's threads.
+#[derive(Debug, Default)]
+pub(crate) struct PlanReviews {
+    pending: HashMap<String, PendingPlan>,
+    /// Threads whose plan was approved: the plan preset no longer holds back their
+    /// edits and commands until their process goes away.
+    approved: HashSet<String>,
+}
+
+impl PlanReviews {
+    /// Record a proposed plan, replacing the thread's earlier one.
+    pub(crate) fn propose(&mut self, plan: PendingPlan) {
+        self.approved.remove(&plan.thread_id);
+        self.pending.insert(plan.thread_id.clone(), plan);
+    }
+
+    pub(crate) fn pending(&self, thread_id: &str) -> Option<&PendingPlan> {
+        self.pending.get(thread_id)
+    }
+
+    pub(crate) fn list(&self) -> Vec<PendingPlan> {
+        let mut plans: Vec<PendingPlan> = self.pending.values().cloned().collect();
+        plans.sort_by_key(|plan| plan.proposed_at);
+        plans
+    }
+
+    /// Drop the plan whose request was answered, whichever way. Returns it.
+    pub(crate) fn settle(&mut self, request_id: &str) -> Option<PendingPlan> {
+        let thread_id = self
+            .pending
+            .values()
+            .find(|plan| plan.request_id == request_id)?
+            .thread_id
+            .clone();
+        self.pending.remove(&thread_id)
+    }
+
+    pub(crate) fn approve(&mut self, thread_id: &str) {
+        self.approved.insert(thread_id.to_string());
+    }
+
+    pub(crate) fn revoke(&mut self, thread_id: &str) {
+        self.approved.remove(thread_id);
+    }
+
+    pub(crate) fn is_approved(&self, thread_id: &str) -> bool {
+        self.approved.contains(thread_id)
+    }
+
+    /// Forget a thread whose process exited; its pending plan, if any, is returned.
+    pub(crate) fn forget_thread(&mut self, thread_id: &str) -> Option<PendingPlan> {
+        self.approved.remove(thread_id);
+        self.pending.remove(thread_id)
+    }
+}
+
+/// The plan text of an `ExitPlanMode` tool input.
+pub(crate) fn plan_text(input: &Value) -> String {
+    input
+        .get("plan")
+        .and_then(|plan| plan.as_str())
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// The answer that lets Claude leave plan mode and start on the plan.
+pub(crate) fn approval() -> PermissionDecision {
+    PermissionDecision::Allow {
+        updated_input: None,
+        always: false,
+    }
+}
+
+/// The answer that keeps Claude planning, with the user's feedback as the reason.
+pub(crate) fn revision(feedback: &str) -> PermissionDecision {
+    let feedback = feedback.trim();
+    let message = if feedback.is_empty() {
+        "The user rejected this plan. Keep planning and propose a different one.".to_string()
+    } else {
+        format!(
+            "The user wants changes to this plan before you start:\n\n{feedback}\n\n\
+             Revise the plan and present it again."
+        )
+    };
+    PermissionDecision::Deny {
+        message: Some(message),
+        interrupt: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn plan(thread_id: &str, request_id: &str, proposed_at: i64) -> PendingPlan {
+        PendingPlan {
+            thread_id: thread_id.to_string(),
+            turn_id: "turn-1".to_string(),
+            request_id: request_id.to_string(),
+            tool_use_id: None,
+            plan: plan_text(&json!({ "plan": "  1. Add the command\n2. Test it\n" })),
+            proposed_at,
+        }
+    }
+
+    #[test]
+    fn tracks_plans_through_review() {
+        let mut reviews = PlanReviews::default();
+        reviews.propose(plan("thread-1", "req-1", 2));
+        reviews.propose(plan("thread-2", "req-2", 1));
+        assert_eq!(
+            reviews.pending("thread-1").unwrap().plan,
+            "1. Add the command\n2. Test it"
+        );
+        assert_eq!(
+            reviews
+                .list()
+                .iter()
+                .map(|plan| plan.request_id.as_str())
+                .collect::<Vec<_>>(),
+            ["req-2", "req-1"]
+        );
+
+        reviews.approve("thread-1");
+        assert_eq!(reviews.settle("req-1").unwrap().thread_id, "thread-1");
+        assert!(reviews.settle("req-1").is_none());
+        assert!(reviews.is_approved("thread-1"));
+        // A new plan in the same thread needs its own approval
+        reviews.propose(plan("thread-1", "req-3", 3));
+        assert!(!reviews.is_approved("thread-1"));
+
+        assert_eq!(
+            reviews.forget_thread("thread-2").unwrap().request_id,
+            "req-2"
+        );
+        assert_eq!(reviews.list().len(), 1);
+    }
+
+    #[test]
+    fn revision_carries_the_feedback() {
+        let PermissionDecision::Deny { message, interrupt } = revision("Keep the old API") else {
+            panic!("expected a denial");
+        };
+        assert!(message.unwrap().contains("Keep the old API"));
+        assert!(!interrupt);
+        assert!(matches!(approval(), PermissionDecision::Allow { .. }));
+    }
+}
@@ -0,0 +1,283 @@
+//! CPU and memory of each thread's CLI process, counting the processes it started
+//! (tool calls, MCP servers, language servers), so a runaway session shows up before it
+//! takes the machine down.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::types::WorkspaceSettings;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// The workspace's `resourceLimits`, with the memory limit in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct MemoryLimit {
+    pub(crate) max_bytes: Option<u64>,
+    pub(crate) auto_restart: bool,
+}
+
+/// The workspace's `resourceLimits.maxMemoryMb`; unset or 0 means no limit.
+pub(crate) fn memory_limit(settings: &WorkspaceSettings) -> MemoryLimit {
+    let Some(limits) = &settings.resource_limits else {
+        return MemoryLimit::default();
+    };
+    MemoryLimit {
+        max_bytes: limits
+            .max_memory_mb
+            .filter(|mb| *mb > 0)
+            .map(|mb| mb.saturating_mul(BYTES_PER_MB)),
+        auto_restart: limits.auto_restart,
+    }
+}
+
+/// One sample of a thread's process tree.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProcessStats {
+    pub(crate) thread_id: String,
+    pub(crate) pid: u32,
+    /// The CLI process and its descendants.
+    pub(crate) process_count: usize,
+    /// Summed over the tree; 100 is one core fully busy.
+    pub(crate) cpu_percent: f32,
+    /// Resident memory of the tree.
+    pub(crate) memory_bytes: u64,
+    pub(crate) sampled_at: i64,
+}
+
+/// A thread whose process tree went over the workspace's memory limit.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResourceWarning {
+    pub(crate) thread_id: String,
+    pub(crate) pid: u32,
+    pub(crate) memory_bytes: u64,
+    pub(crate) limit_bytes: u64,
+    /// The process is being stopped; the thread's next message starts a fresh one.
+    pub(crate) restarting: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ProcessUsage {
+    pid: u32,
+    parent: Option<u32>,
+    cpu_percent: f32,
+    memory_bytes: u64,
+}
+
+/// Reads process usage from the OS. CPU usage is measured between two refreshes, so
+/// one sampler is kept for the app's lifetime and the first sample reads 0% CPU.
+#[derive(Default)]
+pub(crate) struct ProcessSampler {
+    system: System,
+    processes: Vec<ProcessUsage>,
+}
+
+impl ProcessSampler {
+    /// Read every process's usage, for the `sample` calls that follow.
+    pub(crate) fn refresh(&mut self) {
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::new().with_cpu().with_memory(),
+        );
+        self.processes = self
+            .system
+            .processes()
+            .values()
+            // Linux lists threads as tasks of their process; they share its memory
+            .filter(|process| process.thread_kind().is_none())
+            .map(|process| ProcessUsage {
+                pid: process.pid().as_u32(),
+                parent: process.parent().map(Pid::as_u32),
+                cpu_percent: process.cpu_usage(),
+                memory_bytes: process.memory(),
+            })
+            .collect();
+    }
+
+    /// The process tree under each `(thread id, pid)` as of the last refresh; pids that
+    /// were gone are left out.
+    pub(crate) fn sample(&self, roots: &[(String, u32)], now: i64) -> Vec<ProcessStats> {
+        roots
+            .iter()
+            .filter_map(|(thread_id, pid)| tree_stats(thread_id, *pid, &self.processes, now))
+            .collect()
+    }
+}
+
+fn tree_stats(
+    thread_id: &str,
+    root: u32,
+    processes: &[ProcessUsage],
+    now: i64,
+) -> Option<ProcessStats> {
+    let by_pid: HashMap<u32, &ProcessUsage> = processes
+        .iter()
+        .map(|process| (process.pid, process))
+        .collect();
+    by_pid.get(&root)?;
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for process in processes {
+        if let Some(parent) = process.parent.filter(|parent| *parent != process.pid) {
+            children.entry(parent).or_default().push(process.pid);
+        }
+    }
+    let mut stats = ProcessStats {
+        thread_id: thread_id.to_string(),
+        pid: root,
+        process_count: 0,
+        cpu_percent: 0.0,
+        memory_bytes: 0,
+        sampled_at: now,
+    };
+    let mut seen = HashSet::new();
+    let mut pending = vec![root];
+    while let Some(pid) = pending.pop() {
+        if !seen.insert(pid) {
+            continue;
+        }
+        let Some(process) = by_pid.get(&pid) else {
+            continue;
+        };
+        stats.process_count += 1;
+        stats.cpu_percent += process.cpu_percent;
+        stats.memory_bytes += process.memory_bytes;
+        pending.extend(children.get(&pid).into_iter().flatten());
+    }
+    Some(stats)
+}
+
+/// The latest sample of a workspace's threads, and which of them are over the memory
+/// limit, so each crossing is reported once.
+#[derive(Debug, Default)]
+pub(crate) struct ResourceWatch {
+    latest: Vec<ProcessStats>,
+    over_limit: HashSet<String>,
+}
+
+impl ResourceWatch {
+    pub(crate) fn latest(&self) -> Vec<ProcessStats> {
+        self.latest.clone()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.latest.is_empty()
+    }
+
+    /// Take in a new sample of the workspace's threads. Threads that went over `limit`
+    /// are warned about once; with `autoRestart`, any that is over and not in `busy`
+    /// is to be restarted, including one warned about while its turn ran.
+    pub(crate) fn update(
+        &mut self,
+        stats: Vec<ProcessStats>,
+        limit: &MemoryLimit,
+        busy: &HashSet<String>,
+    ) -> Vec<ResourceWarning> {
+        let mut warnings = Vec::new();
+        let mut over_limit = HashSet::new();
+        if let Some(limit_bytes) = limit.max_bytes {
+            for sample in stats
+                .iter()
+                .filter(|sample| sample.memory_bytes > limit_bytes)
+            {
+                let restarting = limit.auto_restart && !busy.contains(&sample.thread_id);
+                if restarting || !self.over_limit.contains(&sample.thread_id) {
+                    warnings.push(ResourceWarning {
+                        thread_id: sample.thread_id.clone(),
+                        pid: sample.pid,
+                        memory_bytes: sample.memory_bytes,
+                        limit_bytes,
+                        restarting,
+                    });
+                }
+                over_limit.insert(sample.thread_id.clone());
+            }
+        }
+        self.over_limit = over_limit;
+        self.latest = stats;
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ResourceLimits;
+
+    fn usage(pid: u32, parent: Option<u32>, memory_mb: u64) -> ProcessUsage {
+        ProcessUsage {
+            pid,
+            parent,
+            cpu_percent: 10.0,
+            memory_bytes: memory_mb * BYTES_PER_MB,
+        }
+    }
+
+    fn sample(thread_id: &str, memory_mb: u64) -> ProcessStats {
+        ProcessStats {
+            thread_id: thread_id.to_string(),
+            pid: 1,
+            process_count: 1,
+            cpu_percent: 0.0,
+            memory_bytes: memory_mb * BYTES_PER_MB,
+            sampled_at: 0,
+        }
+    }
+
+    #[test]
+    fn sums_the_tree_under_a_process() {
+        let processes = [
+            usage(1, None, 1),
+            usage(100, Some(1), 200),
+            usage(101, Some(100), 50),
+            usage(102, Some(101), 25),
+            usage(200, Some(1), 500),
+        ];
+        let stats = tree_stats("thread-1", 100, &processes, 7).unwrap();
+        assert_eq!(stats.process_count, 3);
+        assert_eq!(stats.memory_bytes, 275 * BYTES_PER_MB);
+        assert_eq!(stats.cpu_percent, 30.0);
+        assert_eq!(stats.sampled_at, 7);
+        assert_eq!(tree_stats("thread-2", 999, &processes, 7), None);
+    }
+
+    #[test]
+    fn warns_once_and_restarts_idle_threads() {
+        let mut settings = WorkspaceSettings::default();
+        assert_eq!(memory_limit(&settings).max_bytes, None);
+        settings.resource_limits = Some(ResourceLimits {
+            max_memory_mb: Some(1_024),
+            auto_restart: false,
+        });
+        let limit = memory_limit(&settings);
+        let busy: HashSet<String> = HashSet::from(["thread-1".to_string()]);
+        let mut watch = ResourceWatch::default();
+
+        let warnings = watch.update(
+            vec![sample("thread-1", 2_048), sample("thread-2", 512)],
+            &limit,
+            &busy,
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].limit_bytes, 1_024 * BYTES_PER_MB);
+        assert!(!warnings[0].restarting);
+        assert!(watch
+            .update(vec![sample("thread-1", 2_048)], &limit, &busy)
+            .is_empty());
+        assert_eq!(watch.latest().len(), 1);
+
+        // With auto-restart, the thread is restarted as soon as its turn is over
+        let limit = MemoryLimit {
+            auto_restart: true,
+            ..limit
+        };
+        assert!(watch
+            .update(vec![sample("thread-1", 2_048)], &limit, &busy)
+            .is_empty());
+        let warnings = watch.update(vec![sample("thread-1", 2_048)], &limit, &HashSet::new());
+        assert!(warnings[0].restarting);
+    }
+}
@@ -0,0 +1,404 @@
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use tokio::process::{Child, Command};
+#[cfg(unix)]
+use tokio::time::timeout;
+
+/// Spawn the command as the leader of its own process group.
+///
+/// Claude CLI starts node, MCP servers and shell tools of its own. Isolating it in a
+/// fresh session (Unix) or, once [`track_process_group`] has run, a Job Object (Windows)
+/// lets us tear down the whole tree on interrupt instead of orphaning the grandchildren.
+#[cfg(unix)]
+pub(crate) fn isolate_process_group(command: &mut Command) {
+    // SAFETY: `setsid` is async-signal-safe and only affects the freshly forked child.
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn isolate_process_group(command: &mut Command) {
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    // `creation_flags` replaces rather than ORs, so keep the windowless flag set by
+    // `build_claude_command_with_bin`.
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    command.creation_flags(CREATE_NEW_PROCESS_GROUP | CREATE_NO_WINDOW);
+}
+
+/// Put a child spawned with [`isolate_process_group`] in a Job Object that kills every
+/// process in it when closed, so its tree goes with it even if the monitor crashes.
+/// Processes join the job at creation, including ones that detach from their parent;
+/// only those started before this call escape it. A no-op on Unix, where the session
+/// already holds the tree.
+#[cfg(unix)]
+pub(crate) fn track_process_group(_child: &Child) {}
+
+#[cfg(not(unix))]
+pub(crate) fn track_process_group(child: &Child) {
+    let (Some(pid), Some(process)) = (child.id(), child.raw_handle()) else {
+        return;
+    };
+    match job::Job::kill_on_close() {
+        Ok(job) => match job.assign(process) {
+            Ok(()) => {
+                jobs().insert(pid, job);
+            }
+            Err(err) => tracing::warn!("process {pid} could not join a job object: {err}"),
+        },
+        Err(err) => tracing::warn!("could not create a job object: {err}"),
+    }
+}
+
+#[cfg(not(unix))]
+static JOBS: std::sync::OnceLock<std::sync::Mutex<std::collections::HashMap<u32, job::Job>>> =
+    std::sync::OnceLock::new();
+
+#[cfg(not(unix))]
+fn jobs() -> std::sync::MutexGuard<'static, std::collections::HashMap<u32, job::Job>> {
+    JOBS.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The few kernel32 calls behind Job Objects.
+#[cfg(not(unix))]
+mod job {
+    use std::ffi::c_void;
+    use std::os::windows::io::RawHandle;
+
+    const JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE: u32 = 0x0000_2000;
+    const JOB_OBJECT_EXTENDED_LIMIT_INFORMATION: i32 = 9;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct BasicLimitInformation {
+        per_process_user_time_limit: i64,
+        per_job_user_time_limit: i64,
+        limit_flags: u32,
+        minimum_working_set_size: usize,
+        maximum_working_set_size: usize,
+        active_process_limit: u32,
+        affinity: usize,
+        priority_class: u32,
+        scheduling_class: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct IoCounters {
+        read_operation_count: u64,
+        write_operation_count: u64,
+        other_operation_count: u64,
+        read_transfer_count: u64,
+        write_transfer_count: u64,
+        other_transfer_count: u64,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ExtendedLimitInformation {
+        basic_limit_information: BasicLimitInformation,
+        io_info: IoCounters,
+        process_memory_limit: usize,
+        job_memory_limit: usize,
+        peak_process_memory_used: usize,
+        peak_job_memory_used: usize,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateJobObjectW(attributes: *mut c_void, name: *const u16) -> RawHandle;
+        fn SetInformationJobObject(
+            job: RawHandle,
+            class: i32,
+            information: *mut c_void,
+            length: u32,
+        ) -> i32;
+        fn AssignProcessToJobObject(job: RawHandle, process: RawHandle) -> i32;
+        fn TerminateJobObject(job: RawHandle, exit_code: u32) -> i32;
+        fn CloseHandle(handle: RawHandle) -> i32;
+    }
+
+    /// An owned job handle; closing it kills what is left in the job.
+    pub(super) struct Job(RawHandle);
+
+    // SAFETY: a job handle may be used and closed from any thread.
+    unsafe impl Send for Job {}
+
+    fn last_error() -> String {
+        std::io::Error::last_os_error().to_string()
+    }
+
+    impl Job {
+        pub(super) fn kill_on_close() -> Result<Self, String> {
+            // SAFETY: null attributes and name create an anonymous job with default security.
+            let handle = unsafe { CreateJobObjectW(std::ptr::null_mut(), std::ptr::null()) };
+            if handle.is_null() {
+                return Err(last_error());
+            }
+            let job = Job(handle);
+            let mut limits = ExtendedLimitInformation::default();
+            limits.basic_limit_information.limit_flags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            // SAFETY: `limits` is a valid JOBOBJECT_EXTENDED_LIMIT_INFORMATION of the given size.
+            let set = unsafe {
+                SetInformationJobObject(
+                    job.0,
+                    JOB_OBJECT_EXTENDED_LIMIT_INFORMATION,
+                    &mut limits as *mut ExtendedLimitInformation as *mut c_void,
+                    std::mem::size_of::<ExtendedLimitInformation>() as u32,
+                )
+            };
+            if set == 0 {
+                return Err(last_error());
+            }
+            Ok(job)
+        }
+
+        pub(super) fn assign(&self, process: RawHandle) -> Result<(), String> {
+            // SAFETY: both handles are open; the process handle is borrowed from its `Child`.
+            if unsafe { AssignProcessToJobObject(self.0, process) } == 0 {
+                return Err(last_error());
+            }
+            Ok(())
+        }
+
+        pub(super) fn terminate(&self) {
+            // SAFETY: the handle is open until `drop`.
+            unsafe {
+                TerminateJobObject(self.0, 1);
+            }
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            // SAFETY: the handle is owned and closed exactly once.
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+}
+
+/// Ask a child process to exit and escalate to a hard kill if it outlives `grace`.
+///
+/// On Unix the child's process group first receives SIGTERM so the Claude CLI can finish
+/// writing its transcript. Whatever is left of the group is SIGKILLed once the leader
+/// exits or the grace period elapses. Windows has no equivalent polite signal, so the
+/// process tree is killed immediately. Children that have already exited are treated as
+/// successfully terminated.
+pub(crate) async fn terminate_child(child: &mut Child, grace: Duration) -> Result<(), String> {
+    let pid = child.id();
+    if matches!(child.try_wait(), Ok(Some(_))) {
+        if let Some(pid) = pid {
+            kill_process_group(pid);
+        }
+        return Ok(());
+    }
+    #[cfg(unix)]
+    {
+        if let Some(pid) = pid {
+            let signalled = signal_group_or_process(pid, libc::SIGTERM);
+            // A group stopped by focus mode only acts on SIGTERM once continued
+            signal_group_or_process(pid, libc::SIGCONT);
+            if signalled && !grace.is_zero() {
+                if let Ok(result) = timeout(grace, child.wait()).await {
+                    // The leader exited; make sure nothing it spawned outlives it.
+                    kill_process_group(pid);
+                    return result.map(|_| ()).map_err(|err| err.to_string());
+                }
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = grace;
+    if let Some(pid) = pid {
+        kill_process_group(pid);
+    }
+    match child.kill().await {
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == ErrorKind::InvalidInput => Ok(()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Signal the process group led by `pid`, falling back to the process itself when it
+/// was not spawned as a group leader.
+#[cfg(unix)]
+fn signal_group_or_process(pid: u32, signal: libc::c_int) -> bool {
+    let pid = pid as libc::pid_t;
+    // SAFETY: `kill` has no memory-safety preconditions; a stale pid only yields ESRCH.
+    unsafe { libc::kill(-pid, signal) == 0 || libc::kill(pid, signal) == 0 }
+}
+
+/// Stop the process group led by `pid` with SIGSTOP; it keeps its memory and open
+/// pipes but gets no CPU until [`resume_process_group`].
+#[cfg(unix)]
+pub(crate) fn suspend_process_group(pid: u32) -> Result<(), String> {
+    if signal_group_or_process(pid, libc::SIGSTOP) {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+/// Continue a process group stopped by [`suspend_process_group`].
+#[cfg(unix)]
+pub(crate) fn resume_process_group(pid: u32) -> Result<(), String> {
+    if signal_group_or_process(pid, libc::SIGCONT) {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn suspend_process_group(_pid: u32) -> Result<(), String> {
+    Err("suspending processes is not supported on this platform".to_string())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn resume_process_group(_pid: u32) -> Result<(), String> {
+    Err("suspending processes is not supported on this platform".to_string())
+}
+
+/// SIGKILL everything left in the process group led by `pid`.
+#[cfg(unix)]
+pub(crate) fn kill_process_group(pid: u32) {
+    // SAFETY: see `signal_group_or_process`. Only the group is targeted so a recycled
+    // pid of an unrelated process is never hit.
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+/// Kill the Job Object `pid` was tracked in, or, for a process this run did not spawn
+/// (e.g. one adopted by recovery), its tree as far as `taskkill` can see it.
+#[cfg(not(unix))]
+pub(crate) fn kill_process_group(pid: u32) {
+    if let Some(job) = jobs().remove(&pid) {
+        job.terminate();
+        return;
+    }
+    let _ = std::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    async fn spawn_cat() -> Child {
+        let mut child = Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to spawn cat process for testing");
+        drop(child.stdin.take());
+        child
+    }
+
+    fn process_is_alive(pid: u32) -> bool {
+        let output = std::process::Command::new("ps")
+            .args(["-o", "stat=", "-p", &pid.to_string()])
+            .output()
+            .expect("Failed to run ps");
+        let stat = String::from_utf8_lossy(&output.stdout);
+        let stat = stat.trim();
+        !stat.is_empty() && !stat.starts_with('Z')
+    }
+
+    #[tokio::test]
+    async fn terminate_child_stops_process_that_honours_sigterm() {
+        let mut child = spawn_cat().await;
+
+        let result = terminate_child(&mut child, Duration::from_secs(5)).await;
+        assert!(
+            result.is_ok(),
+            "Expected graceful termination: {:?}",
+            result
+        );
+        assert!(matches!(child.try_wait(), Ok(Some(_))));
+    }
+
+    #[tokio::test]
+    async fn terminate_child_escalates_to_kill_after_grace_period() {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg("trap '' TERM; sleep 30")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        isolate_process_group(&mut command);
+        let mut child = command
+            .spawn()
+            .expect("Failed to spawn sh process for testing");
+        // Give the shell a moment to install its trap
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let started = std::time::Instant::now();
+        let result = terminate_child(&mut child, Duration::from_millis(200)).await;
+        assert!(result.is_ok(), "Expected forced termination: {:?}", result);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn terminate_child_succeeds_for_exited_process() {
+        let mut child = spawn_cat().await;
+        child.wait().await.expect("cat should exit on EOF");
+
+        let result = terminate_child(&mut child, Duration::from_millis(50)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn terminate_child_reaps_grandchildren_in_process_group() {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg("sleep 30 & echo $!; wait")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        isolate_process_group(&mut command);
+        let mut child = command
+            .spawn()
+            .expect("Failed to spawn sh process for testing");
+
+        let stdout = child.stdout.take().expect("Failed to get stdout");
+        let mut line = String::new();
+        BufReader::new(stdout)
+            .read_line(&mut line)
+            .await
+            .expect("Failed to read grandchild pid");
+        let grandchild: u32 = line.trim().parse().expect("grandchild pid");
+        assert!(process_is_alive(grandchild));
+
+        let result = terminate_child(&mut child, Duration::from_millis(500)).await;
+        assert!(result.is_ok(), "Expected termination: {:?}", result);
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while process_is_alive(grandchild) && std::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(
+            !process_is_alive(grandchild),
+            "grandchild {grandchild} survived"
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Per-workspace model provider: validation, the environment that selects it, and the
+//! endpoint the connectivity check probes.
+
+use regex::Regex;
+
+use crate::credentials::WorkspaceCredentials;
+use crate::types::{ModelProvider, ProviderSettings};
+
+/// Variables that select a provider or authenticate with one. A workspace with provider
+/// settings or credentials clears all of them, so nothing inherited from the app's
+/// environment mixes in.
+pub(crate) const PROVIDER_VARS: &[&str] = &[
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_AUTH_TOKEN",
+    "CLAUDE_CODE_USE_BEDROCK",
+    "CLAUDE_CODE_USE_VERTEX",
+    "AWS_ACCESS_KEY_ID",
+    "AWS_SECRET_ACCESS_KEY",
+    "AWS_SESSION_TOKEN",
+    "AWS_PROFILE",
+    "ANTHROPIC_VERTEX_PROJECT_ID",
+    "CLOUD_ML_REGION",
+    "GOOGLE_APPLICATION_CREDENTIALS",
+];
+
+fn provider_name(provider: ModelProvider) -> &'static str {
+    match provider {
+        ModelProvider::Anthropic => "the Anthropic API",
+        ModelProvider::Bedrock => "Bedrock",
+        ModelProvider::Vertex => "Vertex AI",
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn check_format(name: &str, value: &str, pattern: &str) -> Result<(), String> {
+    let regex = Regex::new(pattern).map_err(|err| err.to_string())?;
+    if regex.is_match(value) {
+        Ok(())
+    } else {
+        Err(format!("invalid {name}: {value:?}"))
+    }
+}
+
+/// Check provider settings before they are saved.
+pub(crate) fn validate_provider_settings(settings: &ProviderSettings) -> Result<(), String> {
+    let name = provider_name(settings.provider);
+    let region = non_empty(&settings.region);
+    let profile = non_empty(&settings.profile);
+    let project_id = non_empty(&settings.project_id);
+    match settings.provider {
+        ModelProvider::Anthropic => {
+            if region.is_some() || profile.is_some() || project_id.is_some() {
+                return Err(format!("{name} takes no region, profile or project"));
+            }
+        }
+        ModelProvider::Bedrock => {
+            let region = region.ok_or_else(|| format!("{name} needs a region"))?;
+            check_format("AWS region", region, r"^[a-z]{2}(-[a-z]+)+-\d+$")?;
+            if let Some(profile) = profile {
+                check_format("AWS profile", profile, r"^[A-Za-z0-9_.@+-]+$")?;
+            }
+            if project_id.is_some() {
+                return Err(format!("{name} takes no project"));
+            }
+        }
+        ModelProvider::Vertex => {
+            let region = region.ok_or_else(|| format!("{name} needs a region"))?;
+            check_format("Vertex region", region, r"^(global|[a-z]+-[a-z]+\d+)$")?;
+            let project_id = project_id.ok_or_else(|| format!("{name} needs a project"))?;
+            check_format(
+                "Google Cloud project",
+                project_id,
+                r"^[a-z][a-z0-9-]{4,28}[a-z0-9]$",
+            )?;
+            if profile.is_some() {
+                return Err(format!("{name} takes no profile"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn settings_env(settings: &ProviderSettings) -> Vec<(&'static str, String)> {
+    let mut env = Vec::new();
+    let region = non_empty(&settings.region).map(str::to_string);
+    match settings.provider {
+        ModelProvider::Anthropic => {}
+        ModelProvider::Bedrock => {
+            env.push(("CLAUDE_CODE_USE_BEDROCK", "1".to_string()));
+            env.extend(region.map(|region| ("AWS_REGION", region)));
+            if let Some(profile) = non_empty(&settings.profile) {
+                env.push(("AWS_PROFILE", profile.to_string()));
+            }
+        }
+        ModelProvider::Vertex => {
+            env.push(("CLAUDE_CODE_USE_VERTEX", "1".to_string()));
+            env.extend(region.map(|region| ("CLOUD_ML_REGION", region)));
+            if let Some(project_id) = non_empty(&settings.project_id) {
+                env.push(("ANTHROPIC_VERTEX_PROJECT_ID", project_id.to_string()));
+            }
+        }
+    }
+    env
+}
+
+/// The provider environment of a workspace's processes, or `None` to inherit the app's.
+/// Settings win over what the credentials repeat, such as the region.
+pub(crate) fn provider_env(
+    settings: Option<&ProviderSettings>,
+    credentials: Option<&WorkspaceCredentials>,
+) -> Result<Option<Vec<(&'static str, String)>>, String> {
+    if let (Some(settings), Some(credentials)) = (settings, credentials) {
+        if settings.provider != credentials.provider() {
+            return Err(format!(
+                "The workspace uses {} but its credentials are for {}.",
+                provider_name(settings.provider),
+                provider_name(credentials.provider())
+            ));
+        }
+    }
+    if settings.is_none() && credentials.is_none() {
+        return Ok(None);
+    }
+    let mut env = settings.map(settings_env).unwrap_or_default();
+    for (name, value) in credentials
+        .map(WorkspaceCredentials::env)
+        .unwrap_or_default()
+    {
+        if !env.iter().any(|(set, _)| *set == name) {
+            env.push((name, value));
+        }
+    }
+    Ok(Some(env))
+}
+
+/// The provider a process with `env` (or the app's environment) talks to, and the base
+/// URL of its API.
+pub(crate) fn provider_endpoint(
+    env: Option<&[(&'static str, String)]>,
+) -> Result<(ModelProvider, String), String> {
+    let lookup = |name: &str| {
+        match env {
+            Some(env) => env
+                .iter()
+                .find(|(set, _)| *set == name)
+                .map(|(_, value)| value.clone()),
+            None => std::env::var(name).ok(),
+        }
+        .filter(|value| !value.trim().is_empty())
+    };
+    let enabled = |name: &str| lookup(name).is_some_and(|value| value != "0");
+
+    if enabled("CLAUDE_CODE_USE_BEDROCK") {
+        let region = lookup("AWS_REGION")
+            .or_else(|| lookup("AWS_DEFAULT_REGION"))
+            .ok_or("Bedrock needs a region")?;
+        return Ok((
+            ModelProvider::Bedrock,
+            format!("https://bedrock-runtime.{region}.amazonaws.com"),
+        ));
+    }
+    if enabled("CLAUDE_CODE_USE_VERTEX") {
+        let region = lookup("CLOUD_ML_REGION").ok_or("Vertex AI needs a region")?;
+        let host = if region == "global" {
+            "aiplatform.googleapis.com".to_string()
+        } else {
+            format!("{region}-aiplatform.googleapis.com")
+        };
+        return Ok((ModelProvider::Vertex, format!("https://{host}")));
+    }
+    Ok((
+        ModelProvider::Anthropic,
+        "https://api.anthropic.com".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bedrock() -> ProviderSettings {
+        ProviderSettings {
+            provider: ModelProvider::Bedrock,
+            region: Some("us-west-2".to_string()),
+            profile: Some("corp-sso".to_string()),
+            project_id: None,
+        }
+    }
+
+    #[test]
+    fn validates_provider_settings() {
+        assert!(validate_provider_settings(&bedrock()).is_ok());
+        let vertex = ProviderSettings {
+            provider: ModelProvider::Vertex,
+            region: Some("us-east5".to_string()),
+            profile: None,
+            project_id: Some("acme-claude".to_string()),
+        };
+        assert!(validate_provider_settings(&vertex).is_ok());
+
+        let invalid = [
+            ProviderSettings {
+                region: None,
+                ..bedrock()
+            },
+            ProviderSettings {
+                region: Some("Oregon".to_string()),
+                ..bedrock()
+            },
+            ProviderSettings {
+                project_id: Some("acme-claude".to_string()),
+                ..bedrock()
+            },
+            ProviderSettings {
+                project_id: None,
+                ..vertex.clone()
+            },
+            ProviderSettings {
+                provider: ModelProvider::Anthropic,
+                ..bedrock()
+            },
+        ];
+        for settings in invalid {
+            assert!(
+                validate_provider_settings(&settings).is_err(),
+                "{settings:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn merges_settings_and_credentials() {
+        assert_eq!(provider_env(None, None), Ok(None));
+
+        let credentials = WorkspaceCredentials::Bedrock {
+            access_key_id: "AKIAEXAMPLE".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: None,
+            region: "eu-central-1".to_string(),
+        };
+        let env = provider_env(Some(&bedrock()), Some(&credentials))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            env,
+            vec![
+                ("CLAUDE_CODE_USE_BEDROCK", "1".to_string()),
+                ("AWS_REGION", "us-west-2".to_string()),
+                ("AWS_PROFILE", "corp-sso".to_string()),
+                ("AWS_ACCESS_KEY_ID", "AKIAEXAMPLE".to_string()),
+                ("AWS_SECRET_ACCESS_KEY", "secret".to_string()),
+            ]
+        );
+        assert_eq!(
+            provider_endpoint(Some(env.as_slice())),
+            Ok((
+                ModelProvider::Bedrock,
+                "https://bedrock-runtime.us-west-2.amazonaws.com".to_string()
+            ))
+        );
+
+        let anthropic = WorkspaceCredentials::Anthropic {
+            api_key: "sk-ant-test".to_string(),
+        };
+        assert!(provider_env(Some(&bedrock()), Some(&anthropic)).is_err());
+        assert_eq!(
+            provider_endpoint(Some(&[][..])),
+            Ok((
+                ModelProvider::Anthropic,
+                "https://api.anthropic.com".to_string()
+            ))
+        );
+    }
+}
@@ -0,0 +1,507 @@
+use chrono::DateTime;
+use serde::Serialize;
+use serde_json::Value;
+
+const MAX_BACKOFF_MS: u64 = 15 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum RateLimitKind {
+    /// HTTP 429: too many requests or tokens for the current window.
+    RateLimited,
+    /// HTTP 529: the API is overloaded; unrelated to the account's quota.
+    Overloaded,
+    /// The subscription's usage window is exhausted until it resets.
+    UsageLimit,
+    /// Still allowed, but close to a limit.
+    QuotaWarning,
+}
+
+/// Remaining quota as last reported in `anthropic-ratelimit-*` response headers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QuotaEstimate {
+    pub(crate) requests_remaining: Option<u64>,
+    pub(crate) requests_limit: Option<u64>,
+    pub(crate) tokens_remaining: Option<u64>,
+    pub(crate) tokens_limit: Option<u64>,
+    /// Share of the subscription window used, 0.0 to 1.0.
+    pub(crate) utilization: Option<f64>,
+    pub(crate) resets_at: Option<i64>,
+}
+
+impl QuotaEstimate {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Take every value `newer` reports, keeping older values it does not mention.
+    fn merge(&mut self, newer: &QuotaEstimate) {
+        fn take<T: Clone>(slot: &mut Option<T>, value: &Option<T>) {
+            if value.is_some() {
+                slot.clone_from(value);
+            }
+        }
+        take(&mut self.requests_remaining, &newer.requests_remaining);
+        take(&mut self.requests_limit, &newer.requests_limit);
+        take(&mut self.tokens_remaining, &newer.tokens_remaining);
+        take(&mut self.tokens_limit, &newer.tokens_limit);
+        take(&mut self.utilization, &newer.utilization);
+        take(&mut self.resets_at, &newer.resets_at);
+    }
+}
+
+/// A rate limit or overload the CLI ran into, parsed from one stream event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RateLimitSignal {
+    pub(crate) kind: RateLimitKind,
+    pub(crate) message: String,
+    pub(crate) status: Option<u16>,
+    /// From a `retry-after` header or the CLI's own retry delay.
+    pub(crate) retry_after_ms: Option<u64>,
+    pub(crate) resets_at: Option<i64>,
+    pub(crate) quota: QuotaEstimate,
+}
+
+/// What the UI is told when a signal is recorded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RateLimitWarning {
+    #[serde(flatten)]
+    pub(crate) signal: RateLimitSignal,
+    /// Consecutive signals without a successful turn in between.
+    pub(crate) attempt: u32,
+    pub(crate) backoff_ms: u64,
+    pub(crate) cooldown_until: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RateLimitStatus {
+    pub(crate) cooling_down: bool,
+    pub(crate) cooldown_until: Option<i64>,
+    pub(crate) consecutive: u32,
+    pub(crate) last: Option<RateLimitSignal>,
+    pub(crate) quota: QuotaEstimate,
+}
+
+/// Rate limit state of one workspace. Quota is account-wide, so every thread's events
+/// feed the same estimate.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimitTracker {
+    consecutive: u32,
+    cooldown_until: Option<i64>,
+    last: Option<RateLimitSignal>,
+    quota: QuotaEstimate,
+}
+
+impl RateLimitTracker {
+    pub(crate) fn observe_quota(&mut self, quota: &QuotaEstimate) {
+        self.quota.merge(quota);
+    }
+
+    pub(crate) fn record(&mut self, signal: RateLimitSignal, now_ms: i64) -> RateLimitWarning {
+        self.quota.merge(&signal.quota);
+        if signal.kind != RateLimitKind::QuotaWarning {
+            self.consecutive += 1;
+        }
+        let backoff_ms = suggested_backoff_ms(&signal, self.consecutive, now_ms);
+        let cooldown_until = now_ms + backoff_ms as i64;
+        if backoff_ms > 0 {
+            self.cooldown_until = Some(cooldown_until);
+        }
+        self.last = Some(signal.clone());
+        RateLimitWarning {
+            signal,
+            attempt: self.consecutive,
+            backoff_ms,
+            cooldown_until,
+        }
+    }
+
+    /// A turn went through. Returns whether a cooldown was in effect.
+    pub(crate) fn record_success(&mut self) -> bool {
+        let was_limited = self.consecutive > 0 || self.cooldown_until.is_some();
+        self.consecutive = 0;
+        self.cooldown_until = None;
+        was_limited
+    }
+
+    pub(crate) fn status(&self, now_ms: i64) -> RateLimitStatus {
+        RateLimitStatus {
+            cooling_down: self.cooldown_until.is_some_and(|until| until > now_ms),
+            cooldown_until: self.cooldown_until,
+            consecutive: self.consecutive,
+            last: self.last.clone(),
+            quota: self.quota.clone(),
+        }
+    }
+}
+
+/// How long to hold off before sending again: the server's answer when it gave one,
+/// otherwise exponential backoff from a per-kind base.
+pub(crate) fn suggested_backoff_ms(signal: &RateLimitSignal, attempt: u32, now_ms: i64) -> u64 {
+    if let Some(retry_after) = signal.retry_after_ms {
+        return retry_after;
+    }
+    if let Some(resets_at) = signal.resets_at {
+        if resets_at > now_ms {
+            return (resets_at - now_ms) as u64;
+        }
+    }
+    let base: u64 = match signal.kind {
+        RateLimitKind::Overloaded => 5_000,
+        RateLimitKind::RateLimited => 30_000,
+        RateLimitKind::UsageLimit => 5 * 60 * 1000,
+        RateLimitKind::QuotaWarning => return 0,
+    };
+    let exponent = attempt.saturating_sub(1).min(10);
+    (base << exponent).min(MAX_BACKOFF_MS)
+}
+
+/// Quota headers attached to an event, if the CLI reported any.
+pub(crate) fn quota_from_event(event: &Value) -> Option<QuotaEstimate> {
+    let quota = event_headers(event)
+        .map(quota_from_headers)
+        .unwrap_or_default();
+    (!quota.is_empty()).then_some(quota)
+}
+
+/// Recognize rate limit and overload errors in `result` and assistant error messages,
+/// the CLI's API retry notices, and its rate limit status events.
+pub(crate) fn detect(event: &Value) -> Option<RateLimitSignal> {
+    let event_type = event.get("type").and_then(Value::as_str).unwrap_or("");
+    let quota = quota_from_event(event).unwrap_or_default();
+    let mut signal = match event_type {
+        "rate_limit_event" => status_event_signal(event, quota),
+        "result" => {
+            if !event
+                .get("is_error")
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+                && event.get("subtype").and_then(Value::as_str) == Some("success")
+            {
+                return None;
+            }
+            let text = event.get("result").and_then(Value::as_str).unwrap_or("");
+            classify(text, None, quota)
+        }
+        "assistant" => {
+            let flagged = event
+                .get("isApiErrorMessage")
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+                || event.get("error").is_some();
+            if !flagged {
+                return None;
+            }
+            let text = event
+                .get("message")
+                .and_then(|message| message.get("content"))
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|block| block.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let hint = event.get("error").and_then(Value::as_str);
+            classify(&text, hint, quota)
+        }
+        "system" => {
+            let subtype = event.get("subtype").and_then(Value::as_str).unwrap_or("");
+            if !matches!(subtype, "api_retry" | "api_error" | "rate_limit") {
+                return None;
+            }
+            let error = event.get("error");
+            let status = event
+                .get("status")
+                .or_else(|| error.and_then(|error| error.get("status")))
+                .and_then(Value::as_u64)
+                .map(|status| status as u16);
+            let text = error
+                .map(|error| match error {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                })
+                .unwrap_or_default();
+            let mut signal = classify(&text, None, quota).or_else(|| {
+                status.and_then(|status| {
+                    classify(&format!("API Error: {status}"), None, Default::default())
+                })
+            })?;
+            signal.status = signal.status.or(status);
+            let retry_in = ["retry_in_ms", "retryInMs", "retry_delay_ms", "retryDelayMs"]
+                .iter()
+                .find_map(|key| event.get(*key).and_then(Value::as_u64));
+            signal.retry_after_ms = signal.retry_after_ms.or(retry_in);
+            Some(signal)
+        }
+        _ => None,
+    }?;
+    if signal.retry_after_ms.is_none() {
+        signal.retry_after_ms = event_headers(event)
+            .and_then(|headers| header_value(headers, "retry-after"))
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .map(|secs| (secs * 1000.0) as u64);
+    }
+    Some(signal)
+}
+
+fn status_event_signal(event: &Value, mut quota: QuotaEstimate) -> Option<RateLimitSignal> {
+    let info = event
+        .get("rate_limit_info")
+        .or_else(|| event.get("rateLimitInfo"))
+        .unwrap_or(event);
+    let status = info.get("status").and_then(Value::as_str).unwrap_or("");
+    let kind = match status {
+        "rejected" => RateLimitKind::UsageLimit,
+        "allowed_warning" => RateLimitKind::QuotaWarning,
+        _ => return None,
+    };
+    let resets_at = info
+        .get("resetsAt")
+        .or_else(|| info.get("resets_at"))
+        .and_then(timestamp_ms);
+    quota.utilization = quota
+        .utilization
+        .or_else(|| info.get("utilization").and_then(Value::as_f64));
+    quota.resets_at = quota.resets_at.or(resets_at);
+    let window = info
+        .get("rateLimitType")
+        .or_else(|| info.get("rate_limit_type"))
+        .and_then(Value::as_str);
+    let message = match (kind, window) {
+        (RateLimitKind::UsageLimit, Some(window)) => format!("{window} usage limit reached"),
+        (RateLimitKind::UsageLimit, None) => "Usage limit reached".to_string(),
+        (_, Some(window)) => format!("Approaching the {window} usage limit"),
+        (_, None) => "Approaching the usage limit".to_string(),
+    };
+    Some(RateLimitSignal {
+        kind,
+        message,
+        status: None,
+        retry_after_ms: None,
+        resets_at,
+        quota,
+    })
+}
+
+/// Classify an error text such as `API Error: 429 {"type":"error","error":{...}}` or
+/// `Claude AI usage limit reached|1760000000`.
+fn classify(text: &str, hint: Option<&str>, quota: QuotaEstimate) -> Option<RateLimitSignal> {
+    let status = api_error_status(text);
+    let body: Option<Value> = text
+        .find('{')
+        .and_then(|start| serde_json::from_str(&text[start..]).ok());
+    let error_type = body
+        .as_ref()
+        .and_then(|body| body.pointer("/error/type"))
+        .and_then(Value::as_str)
+        .or(hint)
+        .unwrap_or("");
+    let lower = text.to_lowercase();
+    let kind =
+        if status == Some(529) || error_type.contains("overloaded") || lower.contains("overloaded")
+        {
+            RateLimitKind::Overloaded
+        } else if lower.contains("usage limit") || lower.contains("limit reached") {
+            RateLimitKind::UsageLimit
+        } else if status == Some(429)
+            || error_type.contains("rate_limit")
+            || lower.contains("rate limit")
+        {
+            RateLimitKind::RateLimited
+        } else {
+            return None;
+        };
+    let message = body
+        .as_ref()
+        .and_then(|body| body.pointer("/error/message"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| text.split('|').next().unwrap_or(text).trim().to_string());
+    // The CLI appends the reset time as epoch seconds: `...limit reached|1760000000`
+    let resets_at = text
+        .rsplit_once('|')
+        .and_then(|(_, secs)| secs.trim().parse::<i64>().ok())
+        .map(|secs| secs * 1000)
+        .or(quota.resets_at);
+    Some(RateLimitSignal {
+        kind,
+        message,
+        status,
+        retry_after_ms: None,
+        resets_at,
+        quota,
+    })
+}
+
+fn api_error_status(text: &str) -> Option<u16> {
+    let (_, rest) = text.split_once("API Error: ")?;
+    let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+fn event_headers(event: &Value) -> Option<&serde_json::Map<String, Value>> {
+    event
+        .get("headers")
+        .or_else(|| event.pointer("/error/headers"))
+        .or_else(|| event.pointer("/response/headers"))
+        .and_then(Value::as_object)
+}
+
+fn header_value(headers: &serde_json::Map<String, Value>, name: &str) -> Option<String> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .and_then(|(_, value)| match value {
+            Value::String(text) => Some(text.clone()),
+            Value::Number(number) => Some(number.to_string()),
+            _ => None,
+        })
+}
+
+fn quota_from_headers(headers: &serde_json::Map<String, Value>) -> QuotaEstimate {
+    let header = |name: &str| header_value(headers, name);
+    let number = |name: &str| header(name).and_then(|value| value.trim().parse::<u64>().ok());
+    let resets_at = header("anthropic-ratelimit-unified-reset")
+        .and_then(|value| value.trim().parse::<i64>().ok().map(|secs| secs * 1000))
+        .or_else(|| {
+            [
+                "anthropic-ratelimit-tokens-reset",
+                "anthropic-ratelimit-requests-reset",
+            ]
+            .iter()
+            .find_map(|name| header(name))
+            .and_then(|value| DateTime::parse_from_rfc3339(value.trim()).ok())
+            .map(|time| time.timestamp_millis())
+        });
+    let utilization = header("anthropic-ratelimit-unified-utilization")
+        .and_then(|value| value.trim().parse::<f64>().ok());
+    QuotaEstimate {
+        requests_remaining: number("anthropic-ratelimit-requests-remaining"),
+        requests_limit: number("anthropic-ratelimit-requests-limit"),
+        tokens_remaining: number("anthropic-ratelimit-tokens-remaining"),
+        tokens_limit: number("anthropic-ratelimit-tokens-limit"),
+        utilization,
+        resets_at,
+    }
+}
+
+/// Reset times come as epoch seconds, epoch milliseconds or RFC 3339.
+fn timestamp_ms(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(number) => {
+            let raw = number.as_i64()?;
+            Some(if raw < 10_000_000_000 {
+                raw * 1000
+            } else {
+                raw
+            })
+        }
+        Value::String(text) => DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|time| time.timestamp_millis()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn detects_rate_limit_results_with_retry_after() {
+        let event = json!({
+            "type": "result",
+            "subtype": "error_during_execution",
+            "is_error": true,
+            "result": "API Error: 429 {\"type\":\"error\",\"error\":{\"type\":\"rate_limit_error\",\"message\":\"Number of request tokens has exceeded your per-minute rate limit\"}}",
+            "headers": {
+                "retry-after": "20",
+                "anthropic-ratelimit-tokens-remaining": "0",
+                "anthropic-ratelimit-tokens-limit": "80000",
+                "anthropic-ratelimit-tokens-reset": "2026-01-01T00:00:30Z"
+            }
+        });
+        let signal = detect(&event).expect("rate limit");
+        assert_eq!(signal.kind, RateLimitKind::RateLimited);
+        assert_eq!(signal.status, Some(429));
+        assert_eq!(signal.retry_after_ms, Some(20_000));
+        assert!(signal.message.starts_with("Number of request tokens"));
+        assert_eq!(signal.quota.tokens_remaining, Some(0));
+        assert_eq!(signal.quota.tokens_limit, Some(80_000));
+        assert!(signal.quota.resets_at.is_some());
+    }
+
+    #[test]
+    fn detects_overloaded_assistant_errors() {
+        let event = json!({
+            "type": "assistant",
+            "isApiErrorMessage": true,
+            "message": { "content": [{ "type": "text", "text": "API Error: 529 {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}" }] }
+        });
+        let signal = detect(&event).expect("overload");
+        assert_eq!(signal.kind, RateLimitKind::Overloaded);
+        assert_eq!(signal.message, "Overloaded");
+
+        let normal = json!({
+            "type": "assistant",
+            "message": { "content": [{ "type": "text", "text": "We hit a rate limit earlier." }] }
+        });
+        assert!(detect(&normal).is_none());
+    }
+
+    #[test]
+    fn usage_limits_carry_their_reset_time() {
+        let event = json!({
+            "type": "result",
+            "subtype": "success",
+            "is_error": true,
+            "result": "Claude AI usage limit reached|1760000000"
+        });
+        let signal = detect(&event).expect("usage limit");
+        assert_eq!(signal.kind, RateLimitKind::UsageLimit);
+        assert_eq!(signal.message, "Claude AI usage limit reached");
+        assert_eq!(signal.resets_at, Some(1_760_000_000_000));
+        assert_eq!(
+            suggested_backoff_ms(&signal, 1, 1_760_000_000_000 - 90_000),
+            90_000
+        );
+    }
+
+    #[test]
+    fn rate_limit_status_events_warn_before_rejecting() {
+        let warning = detect(&json!({
+            "type": "rate_limit_event",
+            "rate_limit_info": { "status": "allowed_warning", "rateLimitType": "five_hour", "utilization": 0.92, "resetsAt": 1760000000 }
+        }))
+        .expect("warning");
+        assert_eq!(warning.kind, RateLimitKind::QuotaWarning);
+        assert_eq!(warning.quota.utilization, Some(0.92));
+        assert!(detect(
+            &json!({ "type": "rate_limit_event", "rate_limit_info": { "status": "allowed" } })
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn tracker_backs_off_exponentially_until_a_turn_succeeds() {
+        let signal = detect(&json!({
+            "type": "system",
+            "subtype": "api_retry",
+            "error": { "type": "error", "error": { "type": "overloaded_error", "message": "Overloaded" } }
+        }))
+        .expect("retry notice");
+        let mut tracker = RateLimitTracker::default();
+        assert_eq!(tracker.record(signal.clone(), 0).backoff_ms, 5_000);
+        let second = tracker.record(signal, 0);
+        assert_eq!((second.attempt, second.backoff_ms), (2, 10_000));
+        assert!(tracker.status(1_000).cooling_down);
+        assert!(tracker.record_success());
+        assert!(!tracker.status(1_000).cooling_down);
+        assert!(!tracker.record_success());
+    }
+}
@@ -0,0 +1,190 @@
+//! Recordings of a thread's CLI traffic, for bug reports and demos.
+//!
+//! A recording is a JSONL file: a header naming the workspace, thread and agent, then
+//! every line written to the process's stdin or read from its stdout, stamped with the
+//! milliseconds since the process started. `replay_session` feeds the stdout lines back
+//! through the reader that produced the original events.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::redaction;
+use crate::types::AgentKind;
+
+const FORMAT_VERSION: u32 = 1;
+
+/// Set once at startup, next to the database.
+static RECORDINGS_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+pub(crate) fn set_recordings_dir(path: PathBuf) {
+    let _ = RECORDINGS_DIR.set(path);
+}
+
+/// Where every workspace's recordings are kept.
+pub(crate) fn recordings_dir() -> Option<&'static Path> {
+    RECORDINGS_DIR.get().map(PathBuf::as_path)
+}
+
+/// Where a workspace's recordings are kept.
+pub(crate) fn workspace_recordings_dir(workspace_id: &str) -> Option<PathBuf> {
+    RECORDINGS_DIR.get().map(|dir| dir.join(workspace_id))
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum RecordedStream {
+    Stdin,
+    Stdout,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecordingHeader {
+    pub(crate) version: u32,
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) agent: AgentKind,
+    /// Epoch milliseconds at which the process started.
+    pub(crate) started_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct RecordedLine {
+    /// Milliseconds since the process started.
+    pub(crate) t: u64,
+    pub(crate) stream: RecordedStream,
+    pub(crate) line: String,
+}
+
+/// Appends a thread's traffic to its recording file.
+#[derive(Debug)]
+pub(crate) struct Recorder {
+    file: File,
+    started: Instant,
+}
+
+impl Recorder {
+    /// Start a recording under `dir`, named after the thread and the start time.
+    pub(crate) fn start(
+        dir: &Path,
+        workspace_id: &str,
+        thread_id: &str,
+        agent: AgentKind,
+    ) -> Result<(Self, PathBuf), String> {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        let started_at = chrono::Utc::now().timestamp_millis();
+        let path = dir.join(format!("{thread_id}-{started_at}.jsonl"));
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|err| err.to_string())?;
+        let mut recorder = Self {
+            file,
+            started: Instant::now(),
+        };
+        let header = RecordingHeader {
+            version: FORMAT_VERSION,
+            workspace_id: workspace_id.to_string(),
+            thread_id: thread_id.to_string(),
+            agent,
+            started_at,
+        };
+        recorder.append(&serde_json::to_value(header).map_err(|err| err.to_string())?)?;
+        Ok((recorder, path))
+    }
+
+    /// Record one line; the trailing newline is dropped.
+    pub(crate) fn record(&mut self, stream: RecordedStream, line: &str) {
+        let entry = RecordedLine {
+            t: self.started.elapsed().as_millis() as u64,
+            stream,
+            line: redaction::redact_text(line.trim_end_matches(['\r', '\n'])).into_owned(),
+        };
+        let result = serde_json::to_value(entry)
+            .map_err(|err| err.to_string())
+            .and_then(|value| self.append(&value));
+        if let Err(err) = result {
+            tracing::warn!("failed to record a line: {err}");
+        }
+    }
+
+    fn append(&mut self, value: &serde_json::Value) -> Result<(), String> {
+        let mut line = value.to_string();
+        line.push('\n');
+        self.file
+            .write_all(line.as_bytes())
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Read a recording back. Lines that are not recorded traffic are skipped.
+pub(crate) fn read_recording(path: &Path) -> Result<(RecordingHeader, Vec<RecordedLine>), String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header: RecordingHeader = lines
+        .next()
+        .and_then(|line| serde_json::from_str(line).ok())
+        .ok_or_else(|| format!("{} is not a session recording", path.display()))?;
+    if header.version > FORMAT_VERSION {
+        return Err(format!(
+            "recording format {} is newer than this app supports",
+            header.version
+        ));
+    }
+    let entries = lines
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    Ok((header, entries))
+}
+
+/// When a line recorded at `t` milliseconds is replayed at `speed` times real time.
+pub(crate) fn replay_offset(t: u64, speed: f64) -> Duration {
+    Duration::from_secs_f64(t as f64 / 1000.0 / speed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recordings_round_trip() {
+        let dir = std::env::temp_dir().join(format!("recorder-test-{}", uuid::Uuid::new_v4()));
+        let (mut recorder, path) =
+            Recorder::start(&dir, "ws-1", "thread-1", AgentKind::Claude).unwrap();
+        recorder.record(RecordedStream::Stdin, "{\"type\":\"user\"}\n");
+        recorder.record(RecordedStream::Stdout, "{\"type\":\"result\"}");
+        drop(recorder);
+
+        let (header, entries) = read_recording(&path).unwrap();
+        assert_eq!(header.thread_id, "thread-1");
+        assert_eq!(header.agent, AgentKind::Claude);
+        let lines: Vec<(RecordedStream, &str)> = entries
+            .iter()
+            .map(|entry| (entry.stream, entry.line.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (RecordedStream::Stdin, "{\"type\":\"user\"}"),
+                (RecordedStream::Stdout, "{\"type\":\"result\"}"),
+            ]
+        );
+        assert!(entries[0].t <= entries[1].t);
+
+        fs::write(dir.join("other.jsonl"), "{\"type\":\"user\"}\n").unwrap();
+        assert!(read_recording(&dir.join("other.jsonl")).is_err());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn replay_speed_scales_offsets() {
+        assert_eq!(replay_offset(3000, 1.0), Duration::from_secs(3));
+        assert_eq!(replay_offset(3000, 4.0), Duration::from_millis(750));
+    }
+}
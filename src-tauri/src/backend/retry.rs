@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::backend::rate_limits::{self, RateLimitKind};
+use crate::types::WorkspaceSettings;
+
+/// Delay before retry number `attempt` (1-based) of a failed turn, or `None` when the
+/// workspace has not opted into retries or the attempts are used up.
+pub(crate) fn retry_delay(settings: &WorkspaceSettings, attempt: u32) -> Option<Duration> {
+    // Spread delays so threads that failed together do not retry together
+    let sample = (Uuid::new_v4().as_u128() % 2_001) as f64 / 1_000.0 - 1.0;
+    retry_delay_with(settings, attempt, sample)
+}
+
+/// `sample` in -1.0..=1.0 picks where in the jitter range the delay lands.
+fn retry_delay_with(settings: &WorkspaceSettings, attempt: u32, sample: f64) -> Option<Duration> {
+    let policy = settings
+        .retry_policy
+        .as_ref()
+        .filter(|policy| policy.enabled)?;
+    if attempt == 0 || attempt > policy.max_attempts {
+        return None;
+    }
+    let exponent = (attempt - 1).min(16);
+    let delay = policy
+        .base_delay_ms
+        .saturating_mul(1 << exponent)
+        .min(policy.max_delay_ms) as f64;
+    let jitter = policy.jitter.clamp(0.0, 1.0) * sample.clamp(-1.0, 1.0);
+    Some(Duration::from_millis(
+        (delay * (1.0 + jitter)).round() as u64
+    ))
+}
+
+/// Why a turn's event counts as a transient failure worth retrying: the API was
+/// overloaded, rate limited for a short window, or answered with a 5xx. Usage limits
+/// last hours and are left to the user.
+pub(crate) fn transient_failure(event: &Value) -> Option<String> {
+    if let Some(signal) = rate_limits::detect(event) {
+        return match signal.kind {
+            RateLimitKind::Overloaded | RateLimitKind::RateLimited => Some(signal.message),
+            RateLimitKind::UsageLimit | RateLimitKind::QuotaWarning => None,
+        };
+    }
+    let text = match event.get("type").and_then(Value::as_str) {
+        Some("result") if event.get("is_error").and_then(Value::as_bool) == Some(true) => event
+            .get("result")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string(),
+        Some("assistant")
+            if event.get("isApiErrorMessage").and_then(Value::as_bool) == Some(true) =>
+        {
+            event
+                .pointer("/message/content/0/text")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string()
+        }
+        _ => return None,
+    };
+    let status = text.split_once("API Error: ").and_then(|(_, rest)| {
+        rest.chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>()
+            .parse::<u16>()
+            .ok()
+    });
+    match status {
+        Some(status) if (500..600).contains(&status) => Some(format!("API Error: {status}")),
+        // Connection failures never got a status code
+        _ if text.contains("Connection error") || text.contains("ECONNRESET") => {
+            Some("Connection error".to_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RetryPolicy;
+    use serde_json::json;
+
+    fn settings(policy: RetryPolicy) -> WorkspaceSettings {
+        WorkspaceSettings {
+            retry_policy: Some(policy),
+            ..WorkspaceSettings::default()
+        }
+    }
+
+    #[test]
+    fn retries_are_opt_in() {
+        assert_eq!(retry_delay(&WorkspaceSettings::default(), 1), None);
+        assert_eq!(retry_delay(&settings(RetryPolicy::default()), 1), None);
+    }
+
+    #[test]
+    fn delays_grow_exponentially_with_jitter_and_stop_after_max_attempts() {
+        let settings = settings(RetryPolicy {
+            enabled: true,
+            max_attempts: 4,
+            base_delay_ms: 1_000,
+            max_delay_ms: 5_000,
+            jitter: 0.5,
+        });
+        let delay = |attempt, sample| retry_delay_with(&settings, attempt, sample);
+        assert_eq!(delay(1, 0.0), Some(Duration::from_millis(1_000)));
+        assert_eq!(delay(2, 0.0), Some(Duration::from_millis(2_000)));
+        assert_eq!(delay(3, 1.0), Some(Duration::from_millis(6_000)));
+        assert_eq!(delay(4, -1.0), Some(Duration::from_millis(2_500)));
+        assert_eq!(delay(5, 0.0), None);
+    }
+
+    #[test]
+    fn only_transient_failures_are_retried() {
+        let overloaded = json!({
+            "type": "result",
+            "is_error": true,
+            "result": "API Error: 529 {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}"
+        });
+        assert_eq!(
+            transient_failure(&overloaded).as_deref(),
+            Some("Overloaded")
+        );
+
+        let server_error = json!({
+            "type": "assistant",
+            "isApiErrorMessage": true,
+            "message": { "content": [{ "type": "text", "text": "API Error: 502 Bad Gateway" }] }
+        });
+        assert_eq!(
+            transient_failure(&server_error).as_deref(),
+            Some("API Error: 502")
+        );
+
+        let usage_limit = json!({
+            "type": "result",
+            "is_error": true,
+            "result": "Claude AI usage limit reached|1760000000"
+        });
+        assert_eq!(transient_failure(&usage_limit), None);
+
+        let bad_request = json!({
+            "type": "result",
+            "is_error": true,
+            "result": "API Error: 400 prompt is too long"
+        });
+        assert_eq!(transient_failure(&bad_request), None);
+    }
+}
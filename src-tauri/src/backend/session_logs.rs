@@ -0,0 +1,226 @@
+//! Raw stdout and stderr of a workspace's CLI processes, for debugging malformed
+//! stream-json or a crashing CLI without rerunning it in a terminal.
+//!
+//! The latest lines stay in memory. Once the buffer fills, its older half spills to
+//! `session-logs/<workspace id>.jsonl` next to the database, and that file is cut down to
+//! its newer half whenever it outgrows `MAX_SPILL_BYTES`.
+
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::redaction;
+
+const MAX_BUFFERED_LINES: usize = 2_000;
+/// Longer lines are cut, so one huge tool result cannot crowd out the rest.
+const MAX_LINE_CHARS: usize = 16_000;
+const MAX_SPILL_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Set once at startup, next to the database.
+static SESSION_LOGS_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+pub(crate) fn set_session_logs_dir(path: PathBuf) {
+    let _ = SESSION_LOGS_DIR.set(path);
+}
+
+/// Where every workspace's spilled log lines are kept.
+pub(crate) fn session_logs_dir() -> Option<&'static Path> {
+    SESSION_LOGS_DIR.get().map(PathBuf::as_path)
+}
+
+/// Where a workspace's spilled log lines are kept.
+pub(crate) fn workspace_log_file(workspace_id: &str) -> Option<PathBuf> {
+    SESSION_LOGS_DIR
+        .get()
+        .map(|dir| dir.join(format!("{workspace_id}.jsonl")))
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionLogLine {
+    pub(crate) timestamp: i64,
+    pub(crate) thread_id: String,
+    pub(crate) stream: LogStream,
+    pub(crate) text: String,
+    /// Set when the line was cut to `MAX_LINE_CHARS`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) truncated: bool,
+}
+
+/// The output of one workspace's processes, newest last.
+#[derive(Debug)]
+pub(crate) struct SessionLog {
+    spill: Option<PathBuf>,
+    lines: VecDeque<SessionLogLine>,
+    capacity: usize,
+}
+
+impl SessionLog {
+    pub(crate) fn new(spill: Option<PathBuf>) -> Self {
+        Self::with_capacity(spill, MAX_BUFFERED_LINES)
+    }
+
+    fn with_capacity(spill: Option<PathBuf>, capacity: usize) -> Self {
+        Self {
+            spill,
+            lines: VecDeque::new(),
+            capacity: capacity.max(2),
+        }
+    }
+
+    /// Keep one line a process wrote; secrets are redacted and the newline dropped.
+    pub(crate) fn push(&mut self, thread_id: &str, stream: LogStream, line: &str, now: i64) {
+        let line = redaction::redact_text(line.trim_end_matches(['\r', '\n']));
+        let truncated = line.chars().count() > MAX_LINE_CHARS;
+        let text = if truncated {
+            line.chars().take(MAX_LINE_CHARS).collect()
+        } else {
+            line.into_owned()
+        };
+        self.lines.push_back(SessionLogLine {
+            timestamp: now,
+            thread_id: thread_id.to_string(),
+            stream,
+            text,
+            truncated,
+        });
+        if self.lines.len() >= self.capacity {
+            let spilled: Vec<SessionLogLine> = self.lines.drain(..self.capacity / 2).collect();
+            self.spill(&spilled);
+        }
+    }
+
+    /// The last `count` lines, reaching into the spill file when memory holds fewer.
+    pub(crate) fn tail(&self, count: usize) -> Vec<SessionLogLine> {
+        let buffered = self.lines.len().min(count);
+        let mut lines = match &self.spill {
+            Some(path) if buffered < count => read_log_file(path, count - buffered),
+            _ => Vec::new(),
+        };
+        lines.extend(self.lines.iter().skip(self.lines.len() - buffered).cloned());
+        lines
+    }
+
+    fn spill(&self, lines: &[SessionLogLine]) {
+        let Some(path) = &self.spill else {
+            return;
+        };
+        if let Err(err) = append_log_file(path, lines) {
+            tracing::warn!("failed to spill to {}: {err}", path.display());
+        }
+    }
+}
+
+impl Drop for SessionLog {
+    /// A disconnected workspace keeps its output on disk.
+    fn drop(&mut self) {
+        let lines: Vec<SessionLogLine> = self.lines.drain(..).collect();
+        if !lines.is_empty() {
+            self.spill(&lines);
+        }
+    }
+}
+
+fn append_log_file(path: &Path, lines: &[SessionLogLine]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    let mut text = String::new();
+    for line in lines {
+        text.push_str(&serde_json::to_string(line).map_err(|err| err.to_string())?);
+        text.push('\n');
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| err.to_string())?;
+    file.write_all(text.as_bytes())
+        .map_err(|err| err.to_string())?;
+    let size = file.metadata().map_err(|err| err.to_string())?.len();
+    drop(file);
+    if size > MAX_SPILL_BYTES {
+        let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let kept: Vec<&str> = contents.lines().collect();
+        let mut text = kept[kept.len() / 2..].join("\n");
+        text.push('\n');
+        fs::write(path, text).map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+/// The last `count` lines of a spill file; unreadable lines are skipped.
+pub(crate) fn read_log_file(path: &Path, count: usize) -> Vec<SessionLogLine> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let mut lines: Vec<SessionLogLine> = contents
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(count)
+        .collect();
+    lines.reverse();
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lines: &[SessionLogLine]) -> Vec<&str> {
+        lines.iter().map(|line| line.text.as_str()).collect()
+    }
+
+    #[test]
+    fn spills_older_lines_and_reads_them_back() {
+        let dir = std::env::temp_dir().join(format!("session-logs-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("ws-1.jsonl");
+        let mut log = SessionLog::with_capacity(Some(path.clone()), 4);
+        for n in 0..6 {
+            log.push("thread-1", LogStream::Stdout, &format!("line {n}\n"), n);
+        }
+        log.push("thread-1", LogStream::Stderr, "boom", 6);
+
+        // Two lines went to disk when the buffer first filled, two more the second time
+        assert_eq!(
+            texts(&read_log_file(&path, 10)),
+            ["line 0", "line 1", "line 2", "line 3"]
+        );
+        assert_eq!(texts(&log.tail(2)), ["line 5", "boom"]);
+        assert_eq!(log.tail(1)[0].stream, LogStream::Stderr);
+        assert_eq!(
+            texts(&log.tail(5)),
+            ["line 2", "line 3", "line 4", "line 5", "boom"]
+        );
+
+        drop(log);
+        assert_eq!(read_log_file(&path, 100).len(), 7);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn cuts_overlong_lines() {
+        let mut log = SessionLog::with_capacity(None, 10);
+        log.push(
+            "thread-1",
+            LogStream::Stdout,
+            &"x".repeat(MAX_LINE_CHARS + 5),
+            0,
+        );
+        let line = &log.tail(1)[0];
+        assert!(line.truncated);
+        assert_eq!(line.text.len(), MAX_LINE_CHARS);
+    }
+}
@@ -0,0 +1,499 @@
+//! Typed model of the Claude CLI `--output-format stream-json` protocol.
+//!
+//! Every line the CLI writes to stdout is one JSON object tagged by `type`. Consumers
+//! should go through `parse_event_line` / `event_stream` instead of poking at raw
+//! `serde_json::Value`s so unknown or malformed lines are handled the same way everywhere.
+//! Fields nothing reads yet are still modelled, and allowed to be dead one by one.
+
+use futures_util::stream::{self, Stream};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::ChildStdout;
+
+/// A single stream-json event emitted by the Claude CLI.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ClaudeEvent {
+    System(SystemEvent),
+    Assistant(AssistantEvent),
+    User(UserEvent),
+    Result(ResultEvent),
+    /// Partial message deltas (`--include-partial-messages`).
+    StreamEvent(PartialMessageEvent),
+    /// Control messages are routed from the raw line, before typed parsing.
+    #[allow(dead_code)]
+    ControlRequest(ControlRequestEvent),
+    #[allow(dead_code)]
+    ControlResponse(ControlResponseEvent),
+    /// Any event type this version of the monitor does not know about.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ClaudeEvent {
+    #[allow(dead_code)]
+    pub(crate) fn session_id(&self) -> Option<&str> {
+        match self {
+            ClaudeEvent::System(event) => event.session_id.as_deref(),
+            ClaudeEvent::Assistant(event) => event.session_id.as_deref(),
+            ClaudeEvent::User(event) => event.session_id.as_deref(),
+            ClaudeEvent::Result(event) => event.session_id.as_deref(),
+            ClaudeEvent::StreamEvent(event) => event.session_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Subagent events carry the `tool_use_id` of the Task call that spawned them.
+    #[allow(dead_code)]
+    pub(crate) fn parent_tool_use_id(&self) -> Option<&str> {
+        match self {
+            ClaudeEvent::Assistant(event) => event.parent_tool_use_id.as_deref(),
+            ClaudeEvent::User(event) => event.parent_tool_use_id.as_deref(),
+            ClaudeEvent::StreamEvent(event) => event.parent_tool_use_id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct SystemEvent {
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) subtype: String,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) cwd: Option<String>,
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) tools: Vec<String>,
+    #[allow(dead_code)]
+    #[serde(default, alias = "permissionMode")]
+    pub(crate) permission_mode: Option<String>,
+    /// The session reads these from the raw init line instead.
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) mcp_servers: Vec<McpServerStatus>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct McpServerStatus {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) status: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct AssistantEvent {
+    pub(crate) message: AssistantMessage,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) uuid: Option<String>,
+    #[serde(default)]
+    pub(crate) parent_tool_use_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct AssistantMessage {
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) id: Option<String>,
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) content: Vec<ContentBlock>,
+    /// Turn totals come from the result event; per-message usage is informational.
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) usage: Option<Usage>,
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) stop_reason: Option<String>,
+}
+
+impl AssistantMessage {
+    /// Concatenated text of all `text` blocks.
+    pub(crate) fn text(&self) -> String {
+        content_text(&self.content)
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn tool_uses(&self) -> impl Iterator<Item = &ToolUse> {
+        self.content.iter().filter_map(|block| match block {
+            ContentBlock::ToolUse(tool_use) => Some(tool_use),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct UserEvent {
+    #[allow(dead_code)]
+    pub(crate) message: UserMessage,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) uuid: Option<String>,
+    #[serde(default)]
+    pub(crate) parent_tool_use_id: Option<String>,
+    #[allow(dead_code)]
+    #[serde(default, alias = "toolUseResult")]
+    pub(crate) tool_use_result: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct UserMessage {
+    #[serde(default)]
+    pub(crate) content: MessageContent,
+}
+
+impl UserMessage {
+    #[allow(dead_code)]
+    pub(crate) fn tool_results(&self) -> impl Iterator<Item = &ToolResult> {
+        self.content
+            .blocks()
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolResult(result) => Some(result),
+                _ => None,
+            })
+    }
+}
+
+/// User message content is either a bare string or a list of content blocks.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum MessageContent {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+impl Default for MessageContent {
+    fn default() -> Self {
+        MessageContent::Blocks(Vec::new())
+    }
+}
+
+impl MessageContent {
+    pub(crate) fn blocks(&self) -> &[ContentBlock] {
+        match self {
+            MessageContent::Text(_) => &[],
+            MessageContent::Blocks(blocks) => blocks,
+        }
+    }
+
+    pub(crate) fn text(&self) -> String {
+        match self {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Blocks(blocks) => content_text(blocks),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        #[serde(default)]
+        thinking: String,
+    },
+    ToolUse(ToolUse),
+    ToolResult(ToolResult),
+    Image {
+        #[allow(dead_code)]
+        #[serde(default)]
+        source: Value,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ToolUse {
+    #[serde(default)]
+    pub(crate) id: String,
+    #[serde(default)]
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) input: Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ToolResult {
+    #[serde(default, alias = "toolUseId")]
+    pub(crate) tool_use_id: String,
+    #[serde(default)]
+    pub(crate) content: Value,
+    #[serde(default)]
+    pub(crate) is_error: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ResultEvent {
+    #[serde(default)]
+    pub(crate) subtype: String,
+    #[serde(default)]
+    pub(crate) is_error: bool,
+    #[serde(default)]
+    pub(crate) duration_ms: Option<u64>,
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) duration_api_ms: Option<u64>,
+    #[serde(default)]
+    pub(crate) num_turns: Option<u32>,
+    #[serde(default)]
+    pub(crate) result: Option<String>,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[serde(default)]
+    pub(crate) total_cost_usd: Option<f64>,
+    #[serde(default)]
+    pub(crate) usage: Option<Usage>,
+    #[allow(dead_code)]
+    #[serde(default, rename = "modelUsage")]
+    pub(crate) model_usage: Option<Value>,
+    #[serde(default, alias = "permissionDenials")]
+    pub(crate) permission_denials: Vec<PermissionDenial>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct Usage {
+    #[serde(default)]
+    pub(crate) input_tokens: u64,
+    #[serde(default)]
+    pub(crate) output_tokens: u64,
+    #[serde(default)]
+    pub(crate) cache_creation_input_tokens: u64,
+    #[serde(default)]
+    pub(crate) cache_read_input_tokens: u64,
+}
+
+/// Only counted so far.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct PermissionDenial {
+    #[allow(dead_code)]
+    #[serde(default, alias = "toolName")]
+    pub(crate) tool_name: String,
+    #[allow(dead_code)]
+    #[serde(default, alias = "toolUseId")]
+    pub(crate) tool_use_id: String,
+    #[allow(dead_code)]
+    #[serde(default, alias = "toolInput")]
+    pub(crate) tool_input: Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct PartialMessageEvent {
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) event: Value,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[allow(dead_code)]
+    #[serde(default)]
+    pub(crate) uuid: Option<String>,
+    #[serde(default)]
+    pub(crate) parent_tool_use_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ControlRequestEvent {
+    pub(crate) request_id: String,
+    pub(crate) request: ControlRequest,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "subtype", rename_all = "snake_case")]
+pub(crate) enum ControlRequest {
+    CanUseTool {
+        tool_name: String,
+        #[serde(default)]
+        input: Value,
+        #[serde(default)]
+        tool_use_id: Option<String>,
+        #[serde(default)]
+        permission_suggestions: Option<Value>,
+    },
+    Interrupt,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ControlResponseEvent {
+    #[allow(dead_code)]
+    pub(crate) response: Value,
+}
+
+fn content_text(blocks: &[ContentBlock]) -> String {
+    let mut text = String::new();
+    for block in blocks {
+        if let ContentBlock::Text { text: piece } = block {
+            text.push_str(piece);
+        }
+    }
+    text
+}
+
+/// Parse a single stdout line. Blank and malformed lines yield `None`.
+#[tracing::instrument(level = "trace", name = "event_parse", skip_all, fields(bytes = line.len()))]
+pub(crate) fn parse_event_line(line: &str) -> Option<ClaudeEvent> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    serde_json::from_str(trimmed).ok()
+}
+
+/// Turn any line-oriented reader into a stream of parsed events. The stream ends at EOF
+/// or on the first read error; unparseable lines are skipped.
+pub(crate) fn event_stream<R>(reader: R) -> impl Stream<Item = ClaudeEvent>
+where
+    R: AsyncRead + Unpin,
+{
+    let lines = BufReader::new(reader).lines();
+    stream::unfold(lines, |mut lines| async move {
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => {
+                    if let Some(event) = parse_event_line(&line) {
+                        return Some((event, lines));
+                    }
+                }
+                Ok(None) | Err(_) => return None,
+            }
+        }
+    })
+}
+
+/// Stream the events of a spawned Claude CLI process.
+#[allow(dead_code)]
+pub(crate) fn stdout_events(stdout: ChildStdout) -> impl Stream<Item = ClaudeEvent> {
+    event_stream(stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[test]
+    fn parses_system_init_event() {
+        let event = parse_event_line(
+            r#"{"type":"system","subtype":"init","session_id":"s1","model":"claude-sonnet-4-5","tools":["Bash","Read"],"permissionMode":"plan","mcp_servers":[{"name":"github","status":"connected"}]}"#,
+        )
+        .expect("system event");
+        let ClaudeEvent::System(system) = event else {
+            panic!("expected system event");
+        };
+        assert_eq!(system.subtype, "init");
+        assert_eq!(system.session_id.as_deref(), Some("s1"));
+        assert_eq!(system.tools, vec!["Bash", "Read"]);
+        assert_eq!(system.permission_mode.as_deref(), Some("plan"));
+        assert_eq!(system.mcp_servers[0].name, "github");
+    }
+
+    #[test]
+    fn parses_assistant_text_thinking_and_tool_use() {
+        let event = parse_event_line(
+            r#"{"type":"assistant","session_id":"s1","message":{"model":"m","content":[{"type":"thinking","thinking":"hmm"},{"type":"text","text":"Hello "},{"type":"text","text":"world"},{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"ls"}}],"usage":{"input_tokens":10,"output_tokens":5}}}"#,
+        )
+        .expect("assistant event");
+        let ClaudeEvent::Assistant(assistant) = event else {
+            panic!("expected assistant event");
+        };
+        assert_eq!(assistant.message.text(), "Hello world");
+        let tools: Vec<_> = assistant.message.tool_uses().collect();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "Bash");
+        assert_eq!(tools[0].input["command"], "ls");
+        let usage = assistant.message.usage.expect("usage");
+        assert_eq!(usage.input_tokens, 10);
+        assert_eq!(usage.cache_read_input_tokens, 0);
+    }
+
+    #[test]
+    fn parses_user_tool_result_and_string_content() {
+        let event = parse_event_line(
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"ok","is_error":true}]},"toolUseResult":{"stdout":"ok"}}"#,
+        )
+        .expect("user event");
+        let ClaudeEvent::User(user) = event else {
+            panic!("expected user event");
+        };
+        let results: Vec<_> = user.message.tool_results().collect();
+        assert_eq!(results[0].tool_use_id, "toolu_1");
+        assert!(results[0].is_error);
+        assert!(user.tool_use_result.is_some());
+
+        let event = parse_event_line(r#"{"type":"user","message":{"role":"user","content":"hi"}}"#)
+            .expect("user event");
+        let ClaudeEvent::User(user) = event else {
+            panic!("expected user event");
+        };
+        assert_eq!(user.message.content.text(), "hi");
+        assert!(user.message.content.blocks().is_empty());
+    }
+
+    #[test]
+    fn parses_result_with_cost_and_denials() {
+        let event = parse_event_line(
+            r#"{"type":"result","subtype":"success","is_error":false,"duration_ms":1200,"num_turns":2,"session_id":"s1","total_cost_usd":0.0123,"usage":{"input_tokens":3,"output_tokens":4,"cache_read_input_tokens":7},"permission_denials":[{"tool_name":"Write","tool_use_id":"toolu_2","tool_input":{"file_path":"/tmp/x"}}]}"#,
+        )
+        .expect("result event");
+        let ClaudeEvent::Result(result) = event else {
+            panic!("expected result event");
+        };
+        assert_eq!(result.duration_ms, Some(1200));
+        assert_eq!(result.total_cost_usd, Some(0.0123));
+        assert_eq!(result.usage.expect("usage").cache_read_input_tokens, 7);
+        assert_eq!(result.permission_denials[0].tool_name, "Write");
+    }
+
+    #[test]
+    fn parses_control_request() {
+        let event = parse_event_line(
+            r#"{"type":"control_request","request_id":"req-1","request":{"subtype":"can_use_tool","tool_name":"Bash","input":{"command":"rm -rf /"}}}"#,
+        )
+        .expect("control event");
+        let ClaudeEvent::ControlRequest(control) = event else {
+            panic!("expected control request");
+        };
+        assert_eq!(control.request_id, "req-1");
+        assert!(matches!(
+            control.request,
+            ControlRequest::CanUseTool { ref tool_name, .. } if tool_name == "Bash"
+        ));
+    }
+
+    #[test]
+    fn unknown_and_malformed_lines_are_tolerated() {
+        assert!(matches!(
+            parse_event_line(r#"{"type":"brand_new_event","x":1}"#),
+            Some(ClaudeEvent::Unknown)
+        ));
+        assert!(parse_event_line("not json").is_none());
+        assert!(parse_event_line("   ").is_none());
+    }
+
+    #[tokio::test]
+    async fn event_stream_skips_noise_and_ends_at_eof() {
+        let input = b"{\"type\":\"system\",\"subtype\":\"init\"}\n\ngarbage\n{\"type\":\"result\",\"subtype\":\"success\"}\n";
+        let events: Vec<ClaudeEvent> = event_stream(&input[..]).collect().await;
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], ClaudeEvent::System(_)));
+        assert!(matches!(events[1], ClaudeEvent::Result(_)));
+    }
+}
@@ -0,0 +1,250 @@
+use serde_json::Value;
+
+use crate::types::{ToolCallRecord, ToolCallStatus, TurnTimeline};
+
+const MAX_SUMMARY_CHARS: usize = 200;
+const MAX_OUTPUT_CHARS: usize = 2000;
+
+/// Pairs a turn's `tool_use` blocks with their `tool_result`s as the events stream in.
+///
+/// Times are epoch milliseconds taken when the monitor reads each event, so a call's
+/// duration includes any permission prompt it waited on.
+#[derive(Debug, Default)]
+pub(crate) struct ToolTimeline {
+    started_at: Option<i64>,
+    calls: Vec<ToolCallRecord>,
+}
+
+impl ToolTimeline {
+    pub(crate) fn start(&mut self, now: i64) {
+        *self = Self {
+            started_at: Some(now),
+            calls: Vec::new(),
+        };
+    }
+
+    pub(crate) fn observe(&mut self, event: &Value, now: i64) {
+        let event_type = event.get("type").and_then(Value::as_str).unwrap_or("");
+        let blocks = event
+            .get("message")
+            .and_then(|message| message.get("content"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        for block in blocks {
+            match (event_type, block.get("type").and_then(Value::as_str)) {
+                ("assistant", Some("tool_use")) => self.open(block, now),
+                ("user", Some("tool_result")) => self.close(block, now),
+                _ => {}
+            }
+        }
+    }
+
+    /// The turn's timeline, or `None` if no turn was started. Calls still running are
+    /// marked unfinished. Resets the recorder.
+    pub(crate) fn finish(
+        &mut self,
+        workspace_id: &str,
+        thread_id: &str,
+        turn_id: &str,
+        now: i64,
+    ) -> Option<TurnTimeline> {
+        let mut timeline = std::mem::take(self);
+        let started_at = timeline.started_at?;
+        for call in &mut timeline.calls {
+            if call.status == ToolCallStatus::Running {
+                call.status = ToolCallStatus::Unfinished;
+            }
+        }
+        Some(TurnTimeline {
+            workspace_id: workspace_id.to_string(),
+            thread_id: thread_id.to_string(),
+            turn_id: turn_id.to_string(),
+            started_at,
+            finished_at: now,
+            tool_calls: timeline.calls,
+        })
+    }
+
+    fn open(&mut self, block: &Value, now: i64) {
+        let Some(id) = block
+            .get("id")
+            .and_then(Value::as_str)
+            .filter(|id| !id.is_empty())
+        else {
+            return;
+        };
+        if self.calls.iter().any(|call| call.id == id) {
+            return;
+        }
+        let name = block
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or("tool")
+            .to_string();
+        let input = block.get("input").unwrap_or(&Value::Null);
+        self.calls.push(ToolCallRecord {
+            id: id.to_string(),
+            input_summary: summarize_input(&name, input),
+            name,
+            started_at: now,
+            finished_at: None,
+            duration_ms: None,
+            status: ToolCallStatus::Running,
+            output: None,
+            output_truncated: false,
+        });
+    }
+
+    fn close(&mut self, block: &Value, now: i64) {
+        let Some(id) = block.get("tool_use_id").and_then(Value::as_str) else {
+            return;
+        };
+        let Some(call) = self
+            .calls
+            .iter_mut()
+            .find(|call| call.id == id && call.status == ToolCallStatus::Running)
+        else {
+            return;
+        };
+        let failed = block
+            .get("is_error")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        call.status = if failed {
+            ToolCallStatus::Failed
+        } else {
+            ToolCallStatus::Succeeded
+        };
+        call.finished_at = Some(now);
+        call.duration_ms = Some(now.saturating_sub(call.started_at).max(0) as u64);
+        let output = output_text(block.get("content").unwrap_or(&Value::Null));
+        if !output.is_empty() {
+            let (output, truncated) = truncate(&output, MAX_OUTPUT_CHARS);
+            call.output = Some(output);
+            call.output_truncated = truncated;
+        }
+    }
+}
+
+/// The input field that says what a call did, falling back to the whole input.
+pub(crate) fn summarize_input(name: &str, input: &Value) -> String {
+    let key = match name {
+        "Bash" => "command",
+        "Read" | "Write" | "Edit" | "MultiEdit" => "file_path",
+        "NotebookEdit" => "notebook_path",
+        "Grep" | "Glob" => "pattern",
+        "WebFetch" => "url",
+        "WebSearch" => "query",
+        "Task" => "description",
+        _ => "",
+    };
+    let summary = match input.get(key).and_then(Value::as_str) {
+        Some(value) => value.trim().to_string(),
+        None if input.is_null() => String::new(),
+        None => input.to_string(),
+    };
+    truncate(&summary, MAX_SUMMARY_CHARS).0
+}
+
+pub(crate) fn output_text(content: &Value) -> String {
+    match content {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> (String, bool) {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => (format!("{}…", &text[..end]), true),
+        None => (text.to_string(), false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_use(id: &str, name: &str, input: Value) -> Value {
+        json!({
+            "type": "assistant",
+            "message": { "content": [{ "type": "tool_use", "id": id, "name": name, "input": input }] }
+        })
+    }
+
+    fn tool_result(id: &str, content: Value, is_error: bool) -> Value {
+        json!({
+            "type": "user",
+            "message": { "content": [{
+                "type": "tool_result",
+                "tool_use_id": id,
+                "content": content,
+                "is_error": is_error
+            }] }
+        })
+    }
+
+    #[test]
+    fn pairs_tool_calls_with_their_results() {
+        let mut timeline = ToolTimeline::default();
+        timeline.start(1_000);
+        timeline.observe(
+            &tool_use("a", "Bash", json!({ "command": "cargo test" })),
+            1_100,
+        );
+        timeline.observe(
+            &tool_use("b", "Read", json!({ "file_path": "src/lib.rs" })),
+            1_200,
+        );
+        timeline.observe(
+            &tool_result(
+                "b",
+                json!([{ "type": "text", "text": "fn main() {}" }]),
+                false,
+            ),
+            1_250,
+        );
+        timeline.observe(&tool_result("a", json!("1 test failed"), true), 1_900);
+        timeline.observe(
+            &tool_use("c", "mcp__github__search", json!({ "q": "x" })),
+            2_000,
+        );
+
+        let turn = timeline.finish("ws", "thread", "turn", 2_100).unwrap();
+        assert_eq!((turn.started_at, turn.finished_at), (1_000, 2_100));
+        let calls = &turn.tool_calls;
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].input_summary, "cargo test");
+        assert_eq!(calls[0].status, ToolCallStatus::Failed);
+        assert_eq!(calls[0].duration_ms, Some(800));
+        assert_eq!(calls[0].output.as_deref(), Some("1 test failed"));
+        assert_eq!(calls[1].input_summary, "src/lib.rs");
+        assert_eq!(calls[1].status, ToolCallStatus::Succeeded);
+        assert_eq!(calls[1].output.as_deref(), Some("fn main() {}"));
+        assert_eq!(calls[2].input_summary, r#"{"q":"x"}"#);
+        assert_eq!(calls[2].status, ToolCallStatus::Unfinished);
+        assert!(timeline.finish("ws", "thread", "turn", 2_200).is_none());
+    }
+
+    #[test]
+    fn truncates_long_output() {
+        let mut timeline = ToolTimeline::default();
+        timeline.start(0);
+        timeline.observe(&tool_use("a", "Bash", json!({ "command": "cat big" })), 0);
+        timeline.observe(&tool_result("a", json!("é".repeat(3000)), false), 5);
+        let turn = timeline.finish("ws", "thread", "turn", 10).unwrap();
+        let call = &turn.tool_calls[0];
+        assert!(call.output_truncated);
+        assert_eq!(
+            call.output.as_ref().unwrap().chars().count(),
+            MAX_OUTPUT_CHARS + 1
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::types::TurnTimings;
+
+/// Timestamps a turn's stream events to split its wall time into tool and model time.
+///
+/// Tool time counts the spans where at least one `tool_use` has no `tool_result` yet, so
+/// parallel tool calls are not counted twice. Everything else is attributed to the model.
+#[derive(Debug, Default)]
+pub(crate) struct TurnClock {
+    started_at: Option<Instant>,
+    /// Whether `started_at` is when the prompt was written rather than the first event.
+    sent: bool,
+    first_output_at: Option<Instant>,
+    open_tools: HashSet<String>,
+    tools_since: Option<Instant>,
+    tool_time: Duration,
+}
+
+impl TurnClock {
+    /// Reset for a new turn. `sent_at` is when the monitor wrote the prompt, if it did.
+    pub(crate) fn start(&mut self, sent_at: Option<Instant>, now: Instant) {
+        *self = Self {
+            started_at: Some(sent_at.unwrap_or(now)),
+            sent: sent_at.is_some(),
+            ..Self::default()
+        };
+    }
+
+    pub(crate) fn observe(&mut self, event: &Value, now: Instant) {
+        let event_type = event.get("type").and_then(Value::as_str).unwrap_or("");
+        let content = event
+            .get("message")
+            .and_then(|message| message.get("content"))
+            .and_then(Value::as_array);
+        match event_type {
+            "assistant" => {
+                self.first_output_at.get_or_insert(now);
+                for id in content_ids(content, "tool_use", "id") {
+                    if self.open_tools.is_empty() {
+                        self.tools_since = Some(now);
+                    }
+                    self.open_tools.insert(id);
+                }
+            }
+            "user" => {
+                for id in content_ids(content, "tool_result", "tool_use_id") {
+                    if self.open_tools.remove(&id) && self.open_tools.is_empty() {
+                        self.close_tools(now);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The turn's timings, or `None` if no turn was started. Resets the clock.
+    pub(crate) fn finish(&mut self, now: Instant) -> Option<TurnTimings> {
+        let mut clock = std::mem::take(self);
+        let started_at = clock.started_at?;
+        if !clock.open_tools.is_empty() {
+            clock.close_tools(now);
+        }
+        let wall = now.saturating_duration_since(started_at);
+        let tool = clock.tool_time.min(wall);
+        Some(TurnTimings {
+            wall_ms: wall.as_millis() as u64,
+            time_to_first_token_ms: clock
+                .first_output_at
+                .filter(|_| clock.sent)
+                .map(|at| at.saturating_duration_since(started_at).as_millis() as u64),
+            tool_ms: tool.as_millis() as u64,
+            model_ms: (wall - tool).as_millis() as u64,
+        })
+    }
+
+    fn close_tools(&mut self, now: Instant) {
+        if let Some(since) = self.tools_since.take() {
+            self.tool_time += now.saturating_duration_since(since);
+        }
+    }
+}
+
+fn content_ids(content: Option<&Vec<Value>>, block_type: &str, id_key: &str) -> Vec<String> {
+    content
+        .into_iter()
+        .flatten()
+        .filter(|block| block.get("type").and_then(Value::as_str) == Some(block_type))
+        .filter_map(|block| block.get(id_key).and_then(Value::as_str))
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_use(ids: &[&str]) -> Value {
+        let content: Vec<Value> = ids
+            .iter()
+            .map(|id| json!({ "type": "tool_use", "id": id, "name": "Bash", "input": {} }))
+            .collect();
+        json!({ "type": "assistant", "message": { "content": content } })
+    }
+
+    fn tool_result(id: &str) -> Value {
+        json!({
+            "type": "user",
+            "message": { "content": [{ "type": "tool_result", "tool_use_id": id, "content": "ok" }] }
+        })
+    }
+
+    #[test]
+    fn splits_wall_time_into_tool_and_model_time() {
+        let sent = Instant::now();
+        let at = |ms: u64| sent + Duration::from_millis(ms);
+        let mut clock = TurnClock::default();
+        clock.start(Some(sent), at(300));
+        clock.observe(&tool_use(&["a", "b"]), at(300));
+        clock.observe(&tool_result("a"), at(500));
+        // Still counting: "b" is in flight
+        clock.observe(&tool_result("b"), at(900));
+        clock.observe(
+            &json!({ "type": "assistant", "message": { "content": [{ "type": "text", "text": "done" }] } }),
+            at(1_200),
+        );
+        let timings = clock.finish(at(1_250));
+        assert_eq!(
+            timings,
+            Some(TurnTimings {
+                wall_ms: 1_250,
+                time_to_first_token_ms: Some(300),
+                tool_ms: 600,
+                model_ms: 650,
+            })
+        );
+        assert_eq!(clock.finish(at(2_000)), None);
+    }
+
+    #[test]
+    fn turns_the_monitor_did_not_send_have_no_first_token_time() {
+        let now = Instant::now();
+        let mut clock = TurnClock::default();
+        clock.start(None, now);
+        clock.observe(&tool_use(&["a"]), now);
+        let timings = clock.finish(now + Duration::from_millis(40)).unwrap();
+        assert_eq!(timings.time_to_first_token_ms, None);
+        // Tools still running when the turn ends count up to the end
+        assert_eq!(timings.tool_ms, 40);
+        assert_eq!(timings.model_ms, 0);
+    }
+}
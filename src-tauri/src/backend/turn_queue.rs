@@ -0,0 +1,273 @@
+use std::collections::{HashSet, VecDeque};
+
+use serde::Serialize;
+
+use crate::types::ImageAttachment;
+
+const PREVIEW_MAX_CHARS: usize = 120;
+
+/// A prompt waiting for its thread to finish the turn that is currently running.
+#[derive(Debug, Clone)]
+pub(crate) struct QueuedPrompt {
+    pub(crate) id: String,
+    pub(crate) thread_id: String,
+    pub(crate) prompt: String,
+    pub(crate) images: Vec<ImageAttachment>,
+    pub(crate) model: Option<String>,
+    pub(crate) access_mode: Option<String>,
+    pub(crate) queued_at: i64,
+}
+
+/// What the UI sees of a queued prompt. `position` is 1-based across the workspace.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QueuedPromptInfo {
+    pub(crate) id: String,
+    pub(crate) thread_id: String,
+    pub(crate) preview: String,
+    pub(crate) position: usize,
+    pub(crate) queued_at: i64,
+}
+
+/// Serializes turns per thread for one workspace.
+///
+/// Each thread's CLI process handles one turn at a time, so prompts sent while a thread
+/// is busy wait here in workspace-wide order. Different threads still run in parallel:
+/// a queued prompt becomes ready as soon as its own thread is idle.
+#[derive(Debug, Default)]
+pub(crate) struct TurnQueue {
+    busy_threads: HashSet<String>,
+    pending: VecDeque<QueuedPrompt>,
+    dispatcher_running: bool,
+}
+
+impl TurnQueue {
+    /// Claim `thread_id` for a turn that starts right away. Fails while the thread is
+    /// running a turn or still has prompts waiting, so prompts never overtake each other.
+    pub(crate) fn try_start(&mut self, thread_id: &str) -> bool {
+        if self.is_busy(thread_id) {
+            return false;
+        }
+        self.busy_threads.insert(thread_id.to_string());
+        true
+    }
+
+    /// Mark the thread's running turn as finished. Returns whether it was busy.
+    pub(crate) fn finish(&mut self, thread_id: &str) -> bool {
+        self.busy_threads.remove(thread_id)
+    }
+
+    /// Whether the thread is running a turn or has prompts waiting for one.
+    pub(crate) fn is_busy(&self, thread_id: &str) -> bool {
+        self.busy_threads.contains(thread_id)
+            || self
+                .pending
+                .iter()
+                .any(|prompt| prompt.thread_id == thread_id)
+    }
+
+    /// Whether the thread is running a turn. Waiting prompts do not count.
+    pub(crate) fn is_running(&self, thread_id: &str) -> bool {
+        self.busy_threads.contains(thread_id)
+    }
+
+    /// Threads that are running a turn.
+    pub(crate) fn busy_thread_ids(&self) -> Vec<String> {
+        self.busy_threads.iter().cloned().collect()
+    }
+
+    /// Whether any thread is running a turn. Waiting prompts do not count.
+    pub(crate) fn has_running_turns(&self) -> bool {
+        !self.busy_threads.is_empty()
+    }
+
+    /// Append a prompt and return its 1-based position.
+    pub(crate) fn enqueue(&mut self, prompt: QueuedPrompt) -> usize {
+        self.pending.push_back(prompt);
+        self.pending.len()
+    }
+
+    /// Remove the first prompt whose thread is idle and claim that thread for it.
+    pub(crate) fn take_ready(&mut self) -> Option<QueuedPrompt> {
+        let index = self
+            .pending
+            .iter()
+            .position(|prompt| !self.busy_threads.contains(&prompt.thread_id))?;
+        let prompt = self.pending.remove(index)?;
+        self.busy_threads.insert(prompt.thread_id.clone());
+        Some(prompt)
+    }
+
+    pub(crate) fn cancel(&mut self, prompt_id: &str) -> Option<QueuedPrompt> {
+        let index = self
+            .pending
+            .iter()
+            .position(|prompt| prompt.id == prompt_id)?;
+        self.pending.remove(index)
+    }
+
+    /// Move a queued prompt to the 1-based `position`, clamped to the queue bounds.
+    pub(crate) fn move_to(&mut self, prompt_id: &str, position: usize) -> Result<(), String> {
+        let index = self
+            .pending
+            .iter()
+            .position(|prompt| prompt.id == prompt_id)
+            .ok_or("queued prompt not found")?;
+        let prompt = self
+            .pending
+            .remove(index)
+            .ok_or("queued prompt not found")?;
+        let target = position.saturating_sub(1).min(self.pending.len());
+        self.pending.insert(target, prompt);
+        Ok(())
+    }
+
+    /// Number of prompts waiting across the workspace.
+    pub(crate) fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<QueuedPromptInfo> {
+        self.pending
+            .iter()
+            .enumerate()
+            .map(|(index, prompt)| QueuedPromptInfo {
+                id: prompt.id.clone(),
+                thread_id: prompt.thread_id.clone(),
+                preview: preview(&prompt.prompt),
+                position: index + 1,
+                queued_at: prompt.queued_at,
+            })
+            .collect()
+    }
+
+    /// Claim the right to run the dispatcher task. Only one runs per workspace.
+    pub(crate) fn claim_dispatcher(&mut self) -> bool {
+        if self.dispatcher_running {
+            return false;
+        }
+        self.dispatcher_running = true;
+        true
+    }
+
+    /// Let the dispatcher exit once nothing is left to dispatch. Returns `true` if it
+    /// should stop.
+    pub(crate) fn release_dispatcher_if_idle(&mut self) -> bool {
+        if self.pending.is_empty() {
+            self.dispatcher_running = false;
+            return true;
+        }
+        false
+    }
+}
+
+fn preview(prompt: &str) -> String {
+    let line = prompt.trim().lines().next().unwrap_or("").trim();
+    match line.char_indices().nth(PREVIEW_MAX_CHARS) {
+        Some((index, _)) => format!("{}…", &line[..index]),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(id: &str, thread_id: &str) -> QueuedPrompt {
+        QueuedPrompt {
+            id: id.to_string(),
+            thread_id: thread_id.to_string(),
+            prompt: format!("prompt {id}\nsecond line"),
+            images: Vec::new(),
+            model: None,
+            access_mode: None,
+            queued_at: 0,
+        }
+    }
+
+    fn ids(queue: &TurnQueue) -> Vec<String> {
+        queue.snapshot().into_iter().map(|info| info.id).collect()
+    }
+
+    #[test]
+    fn busy_thread_queues_and_releases_in_order() {
+        let mut queue = TurnQueue::default();
+        assert!(queue.try_start("t1"));
+        assert!(!queue.try_start("t1"));
+        assert_eq!(queue.enqueue(prompt("a", "t1")), 1);
+        assert_eq!(queue.enqueue(prompt("b", "t1")), 2);
+
+        // Nothing is ready while the thread is busy
+        assert!(queue.take_ready().is_none());
+        assert!(queue.finish("t1"));
+        assert!(queue.is_busy("t1"));
+        // An idle thread with waiting prompts cannot be claimed directly
+        assert!(!queue.try_start("t1"));
+
+        assert_eq!(queue.take_ready().map(|p| p.id).as_deref(), Some("a"));
+        assert!(queue.take_ready().is_none());
+        queue.finish("t1");
+        assert_eq!(queue.take_ready().map(|p| p.id).as_deref(), Some("b"));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn running_turns_exclude_waiting_prompts() {
+        let mut queue = TurnQueue::default();
+        assert!(!queue.has_running_turns());
+        assert!(queue.try_start("t1"));
+        queue.enqueue(prompt("a", "t1"));
+        assert!(queue.has_running_turns());
+        queue.finish("t1");
+        assert!(!queue.has_running_turns());
+        queue.take_ready();
+        assert!(queue.has_running_turns());
+    }
+
+    #[test]
+    fn other_threads_are_not_blocked() {
+        let mut queue = TurnQueue::default();
+        assert!(queue.try_start("t1"));
+        queue.enqueue(prompt("a", "t1"));
+        queue.enqueue(prompt("b", "t2"));
+        assert_eq!(queue.take_ready().map(|p| p.id).as_deref(), Some("b"));
+        assert!(queue.try_start("t3"));
+    }
+
+    #[test]
+    fn cancel_and_reorder_update_positions() {
+        let mut queue = TurnQueue::default();
+        queue.enqueue(prompt("a", "t1"));
+        queue.enqueue(prompt("b", "t1"));
+        queue.enqueue(prompt("c", "t1"));
+
+        queue.move_to("c", 1).unwrap();
+        assert_eq!(ids(&queue), vec!["c", "a", "b"]);
+        queue.move_to("c", 99).unwrap();
+        assert_eq!(ids(&queue), vec!["a", "b", "c"]);
+        assert!(queue.move_to("missing", 1).is_err());
+
+        assert_eq!(queue.cancel("b").map(|p| p.id).as_deref(), Some("b"));
+        assert!(queue.cancel("b").is_none());
+        let snapshot = queue.snapshot();
+        assert_eq!(snapshot[1].id, "c");
+        assert_eq!(snapshot[1].position, 2);
+        assert_eq!(snapshot[1].preview, "prompt c");
+    }
+
+    #[test]
+    fn dispatcher_is_claimed_once_until_idle() {
+        let mut queue = TurnQueue::default();
+        assert!(queue.claim_dispatcher());
+        assert!(!queue.claim_dispatcher());
+        queue.enqueue(prompt("a", "t1"));
+        assert!(!queue.release_dispatcher_if_idle());
+        queue.cancel("a");
+        assert!(queue.release_dispatcher_if_idle());
+        assert!(queue.claim_dispatcher());
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::types::WorkspaceSettings;
+
+/// A workspace's limits on how long a turn, and any one tool call in it, may run.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct TurnLimits {
+    pub(crate) max_turn: Option<Duration>,
+    pub(crate) max_tool_run: Option<Duration>,
+}
+
+impl TurnLimits {
+    fn is_empty(&self) -> bool {
+        self.max_turn.is_none() && self.max_tool_run.is_none()
+    }
+}
+
+fn minutes(value: Option<u32>) -> Option<Duration> {
+    match value {
+        None | Some(0) => None,
+        Some(minutes) => Some(Duration::from_secs(u64::from(minutes) * 60)),
+    }
+}
+
+/// The workspace's `maxTurnMinutes` and `maxToolRunMinutes`; unset or 0 means no limit.
+pub(crate) fn turn_limits(settings: &WorkspaceSettings) -> TurnLimits {
+    TurnLimits {
+        max_turn: minutes(settings.max_turn_minutes),
+        max_tool_run: minutes(settings.max_tool_run_minutes),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TimeoutKind {
+    Turn,
+    ToolRun,
+}
+
+/// A turn the watchdog cancelled.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnTimeout {
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: String,
+    pub(crate) kind: TimeoutKind,
+    pub(crate) limit_seconds: u64,
+    pub(crate) elapsed_seconds: u64,
+    /// The tool call that ran too long, for `toolRun` timeouts.
+    pub(crate) tool_name: Option<String>,
+    pub(crate) tool_use_id: Option<String>,
+}
+
+/// What the watchdog has to do about an overdue turn.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum WatchdogAction {
+    /// Ask the CLI to stop the turn; it is killed if it does not.
+    Interrupt(TurnTimeout),
+    /// The turn outlived its interrupt by `kill_after`: kill the thread's process.
+    Kill { thread_id: String, turn_id: String },
+}
+
+#[derive(Debug)]
+struct RunningTurn {
+    turn_id: String,
+    started: Instant,
+    /// Tool calls without a result yet, by tool use id: name and start.
+    tools: HashMap<String, (String, Instant)>,
+    timed_out: Option<(TurnTimeout, Instant)>,
+    killed: bool,
+}
+
+/// The running turns of a workspace's threads, for the watchdog to check against the
+/// workspace's limits. The stdout reader reports turns and tool calls as they start
+/// and end.
+#[derive(Debug, Default)]
+pub(crate) struct TurnWatch {
+    turns: HashMap<String, RunningTurn>,
+}
+
+impl TurnWatch {
+    pub(crate) fn start(&mut self, thread_id: &str, turn_id: &str, now: Instant) {
+        self.turns.insert(
+            thread_id.to_string(),
+            RunningTurn {
+                turn_id: turn_id.to_string(),
+                started: now,
+                tools: HashMap::new(),
+                timed_out: None,
+                killed: false,
+            },
+        );
+    }
+
+    /// The id of the thread's running turn.
+    pub(crate) fn turn_id(&self, thread_id: &str) -> Option<&str> {
+        self.turns.get(thread_id).map(|turn| turn.turn_id.as_str())
+    }
+
+    /// Take in one stream event of the thread's turn: tool calls start with a `tool_use`
+    /// block of an assistant message and end with the `tool_result` block answering it.
+    pub(crate) fn observe(&mut self, thread_id: &str, event: &Value, now: Instant) {
+        let Some(turn) = self.turns.get_mut(thread_id) else {
+            return;
+        };
+        let event_type = event.get("type").and_then(Value::as_str).unwrap_or("");
+        let blocks = event
+            .get("message")
+            .and_then(|message| message.get("content"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        for block in blocks {
+            match (event_type, block.get("type").and_then(Value::as_str)) {
+                ("assistant", Some("tool_use")) => {
+                    let Some(id) = block.get("id").and_then(Value::as_str) else {
+                        continue;
+                    };
+                    let name = block.get("name").and_then(Value::as_str).unwrap_or("");
+                    turn.tools.insert(id.to_string(), (name.to_string(), now));
+                }
+                ("user", Some("tool_result")) => {
+                    if let Some(id) = block.get("tool_use_id").and_then(Value::as_str) {
+                        turn.tools.remove(id);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Stop watching the thread's turn; returns its timeout if the watchdog cancelled it.
+    pub(crate) fn finish(&mut self, thread_id: &str) -> Option<TurnTimeout> {
+        self.turns
+            .remove(thread_id)
+            .and_then(|turn| turn.timed_out)
+            .map(|(timeout, _)| timeout)
+    }
+
+    /// Turns past `limits` get interrupted once, and killed once they outlive the
+    /// interrupt by `kill_after`.
+    pub(crate) fn check(
+        &mut self,
+        limits: &TurnLimits,
+        kill_after: Duration,
+        now: Instant,
+    ) -> Vec<WatchdogAction> {
+        if limits.is_empty() {
+            return Vec::new();
+        }
+        let mut actions = Vec::new();
+        for (thread_id, turn) in &mut self.turns {
+            if let Some((_, fired_at)) = &turn.timed_out {
+                if !turn.killed && now.saturating_duration_since(*fired_at) >= kill_after {
+                    turn.killed = true;
+                    actions.push(WatchdogAction::Kill {
+                        thread_id: thread_id.clone(),
+                        turn_id: turn.turn_id.clone(),
+                    });
+                }
+                continue;
+            }
+            let Some(timeout) = overdue(thread_id, turn, limits, now) else {
+                continue;
+            };
+            turn.timed_out = Some((timeout.clone(), now));
+            actions.push(WatchdogAction::Interrupt(timeout));
+        }
+        actions
+    }
+}
+
+fn overdue(
+    thread_id: &str,
+    turn: &RunningTurn,
+    limits: &TurnLimits,
+    now: Instant,
+) -> Option<TurnTimeout> {
+    let timeout = |kind, limit: Duration, since: Instant| TurnTimeout {
+        thread_id: thread_id.to_string(),
+        turn_id: turn.turn_id.clone(),
+        kind,
+        limit_seconds: limit.as_secs(),
+        elapsed_seconds: now.saturating_duration_since(since).as_secs(),
+        tool_name: None,
+        tool_use_id: None,
+    };
+    if let Some(limit) = limits.max_turn {
+        if now.saturating_duration_since(turn.started) >= limit {
+            return Some(timeout(TimeoutKind::Turn, limit, turn.started));
+        }
+    }
+    let limit = limits.max_tool_run?;
+    let (id, (name, started)) = turn
+        .tools
+        .iter()
+        .filter(|(_, (_, started))| now.saturating_duration_since(*started) >= limit)
+        .min_by_key(|(_, (_, started))| *started)?;
+    Some(TurnTimeout {
+        tool_name: Some(name.clone()),
+        tool_use_id: Some(id.clone()),
+        ..timeout(TimeoutKind::ToolRun, limit, *started)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_use(id: &str, name: &str) -> Value {
+        json!({
+            "type": "assistant",
+            "message": { "content": [{ "type": "tool_use", "id": id, "name": name, "input": {} }] }
+        })
+    }
+
+    fn tool_result(id: &str) -> Value {
+        json!({
+            "type": "user",
+            "message": { "content": [{ "type": "tool_result", "tool_use_id": id }] }
+        })
+    }
+
+    #[test]
+    fn limits_come_from_workspace_settings() {
+        let mut settings = WorkspaceSettings::default();
+        assert_eq!(turn_limits(&settings), TurnLimits::default());
+        settings.max_turn_minutes = Some(30);
+        settings.max_tool_run_minutes = Some(0);
+        assert_eq!(
+            turn_limits(&settings),
+            TurnLimits {
+                max_turn: Some(Duration::from_secs(1_800)),
+                max_tool_run: None,
+            }
+        );
+    }
+
+    #[test]
+    fn interrupts_a_stuck_tool_then_kills() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let limits = TurnLimits {
+            max_turn: Some(Duration::from_secs(3_600)),
+            max_tool_run: Some(Duration::from_secs(600)),
+        };
+        let grace = Duration::from_secs(10);
+        let mut watch = TurnWatch::default();
+        watch.start("thread-1", "turn-1", at(0));
+        watch.observe("thread-1", &tool_use("toolu_1", "Bash"), at(10));
+        watch.observe("thread-1", &tool_use("toolu_2", "WebFetch"), at(20));
+        watch.observe("thread-1", &tool_result("toolu_1"), at(30));
+        assert!(watch.check(&limits, grace, at(500)).is_empty());
+
+        let actions = watch.check(&limits, grace, at(620));
+        let [WatchdogAction::Interrupt(timeout)] = actions.as_slice() else {
+            panic!("expected an interrupt: {actions:?}");
+        };
+        assert_eq!(timeout.kind, TimeoutKind::ToolRun);
+        assert_eq!(timeout.tool_name.as_deref(), Some("WebFetch"));
+        assert_eq!(timeout.elapsed_seconds, 600);
+
+        // Fired once; the kill follows when the interrupt did not end the turn
+        assert!(watch.check(&limits, grace, at(625)).is_empty());
+        assert_eq!(
+            watch.check(&limits, grace, at(630)),
+            vec![WatchdogAction::Kill {
+                thread_id: "thread-1".to_string(),
+                turn_id: "turn-1".to_string(),
+            }]
+        );
+        assert!(watch.check(&limits, grace, at(700)).is_empty());
+        assert_eq!(watch.finish("thread-1").unwrap().turn_id, "turn-1");
+        assert_eq!(watch.finish("thread-1"), None);
+    }
+
+    #[test]
+    fn whole_turns_time_out_too() {
+        let start = Instant::now();
+        let limits = TurnLimits {
+            max_turn: Some(Duration::from_secs(60)),
+            max_tool_run: None,
+        };
+        let mut watch = TurnWatch::default();
+        watch.start("thread-1", "turn-1", start);
+        watch.start("thread-2", "turn-2", start + Duration::from_secs(30));
+        let actions = watch.check(
+            &limits,
+            Duration::from_secs(5),
+            start + Duration::from_secs(61),
+        );
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(
+            &actions[0],
+            WatchdogAction::Interrupt(timeout)
+                if timeout.kind == TimeoutKind::Turn && timeout.thread_id == "thread-1"
+        ));
+        assert_eq!(watch.finish("thread-2"), None);
+    }
+}
@@ -0,0 +1,331 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Readings closer together than this are dropped, except the first of each message
+/// and the last of each turn.
+const READING_INTERVAL: Duration = Duration::from_millis(500);
+/// Streamed text per output token, for estimating output before the CLI reports it.
+const CHARS_PER_TOKEN: u64 = 4;
+const DEFAULT_CONTEXT_WINDOW: u64 = 200_000;
+/// Models run with the 1M-token context beta carry this suffix.
+const LONG_CONTEXT_SUFFIX: &str = "[1m]";
+const LONG_CONTEXT_WINDOW: u64 = 1_000_000;
+
+/// Live throughput and context-window fill of a thread's current turn.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsageGaugeReading {
+    /// Output tokens of the turn so far, estimated from streamed text until reported.
+    pub(crate) output_tokens: u64,
+    /// Output rate of the message being generated, or of the last one.
+    pub(crate) tokens_per_second: f64,
+    /// Prompt plus output of the message being generated: what it takes of the window.
+    pub(crate) context_tokens: u64,
+    pub(crate) context_window: u64,
+    pub(crate) context_percent: f64,
+    /// Set on the reading that ends the turn.
+    pub(crate) done: bool,
+}
+
+/// One assistant message: its prompt size and output as it streams.
+#[derive(Debug, Default)]
+struct MessageUsage {
+    id: Option<String>,
+    prompt_tokens: u64,
+    reported_output_tokens: u64,
+    streamed_chars: u64,
+    first_delta_at: Option<Instant>,
+    last_delta_at: Option<Instant>,
+}
+
+impl MessageUsage {
+    fn output_tokens(&self) -> u64 {
+        self.reported_output_tokens
+            .max(self.streamed_chars / CHARS_PER_TOKEN)
+    }
+
+    fn tokens_per_second(&self, now: Instant) -> f64 {
+        let Some(first) = self.first_delta_at else {
+            return 0.0;
+        };
+        let until = self.last_delta_at.unwrap_or(now).max(first);
+        let seconds = until.saturating_duration_since(first).as_secs_f64();
+        if seconds < 0.05 {
+            return 0.0;
+        }
+        self.output_tokens() as f64 / seconds
+    }
+}
+
+/// Follows the stream events of one thread, partial message deltas included, and turns
+/// them into gauge readings.
+///
+/// The context window comes from the `modelUsage` of the last `result`, or from the
+/// model name until a turn has finished.
+#[derive(Debug, Default)]
+pub(crate) struct UsageGauge {
+    model: Option<String>,
+    context_window: Option<u64>,
+    /// Output tokens of the turn's finished messages.
+    finished_output_tokens: u64,
+    message: Option<MessageUsage>,
+    last_reading_at: Option<Instant>,
+}
+
+impl UsageGauge {
+    /// Take in one stream event; returns a reading when one is due.
+    pub(crate) fn observe(&mut self, event: &Value, now: Instant) -> Option<UsageGaugeReading> {
+        match event.get("type").and_then(Value::as_str).unwrap_or("") {
+            "system" => {
+                if let Some(model) = event.get("model").and_then(Value::as_str) {
+                    self.model = Some(model.to_string());
+                }
+                None
+            }
+            "stream_event" => self.observe_partial(event.get("event")?, now),
+            "assistant" => {
+                let message = event.get("message")?;
+                let id = message.get("id").and_then(Value::as_str);
+                let streamed = self
+                    .message
+                    .as_ref()
+                    .is_some_and(|current| current.id.is_some() && current.id.as_deref() == id);
+                if streamed {
+                    return None;
+                }
+                // Without partial messages each assistant event is a whole message
+                let usage = message.get("usage")?;
+                self.start_message(id, message.get("model"), usage);
+                if let Some(current) = self.message.as_mut() {
+                    current.reported_output_tokens = token_count(usage, "output_tokens");
+                }
+                self.reading(now, true, false)
+            }
+            "result" => {
+                if let Some(window) = result_context_window(event) {
+                    self.context_window = Some(window);
+                }
+                let reading = self.reading(now, true, true);
+                self.finished_output_tokens = 0;
+                self.message = None;
+                self.last_reading_at = None;
+                reading
+            }
+            _ => None,
+        }
+    }
+
+    fn observe_partial(&mut self, event: &Value, now: Instant) -> Option<UsageGaugeReading> {
+        match event.get("type").and_then(Value::as_str).unwrap_or("") {
+            "message_start" => {
+                let message = event.get("message")?;
+                let usage = message.get("usage").cloned().unwrap_or(Value::Null);
+                self.start_message(
+                    message.get("id").and_then(Value::as_str),
+                    message.get("model"),
+                    &usage,
+                );
+                self.reading(now, true, false)
+            }
+            "content_block_delta" => {
+                let delta = event.get("delta")?;
+                let chars = ["text", "thinking", "partial_json"]
+                    .iter()
+                    .filter_map(|key| delta.get(*key).and_then(Value::as_str))
+                    .map(|text| text.chars().count() as u64)
+                    .sum::<u64>();
+                let current = self.message.get_or_insert_with(MessageUsage::default);
+                current.streamed_chars += chars;
+                current.first_delta_at.get_or_insert(now);
+                current.last_delta_at = Some(now);
+                self.reading(now, false, false)
+            }
+            "message_delta" => {
+                let reported = token_count(event.get("usage")?, "output_tokens");
+                let current = self.message.get_or_insert_with(MessageUsage::default);
+                current.reported_output_tokens = current.reported_output_tokens.max(reported);
+                self.reading(now, false, false)
+            }
+            _ => None,
+        }
+    }
+
+    fn start_message(&mut self, id: Option<&str>, model: Option<&Value>, usage: &Value) {
+        if let Some(finished) = self.message.take() {
+            self.finished_output_tokens += finished.output_tokens();
+        }
+        if let Some(model) = model.and_then(Value::as_str) {
+            self.model = Some(model.to_string());
+        }
+        self.message = Some(MessageUsage {
+            id: id.map(str::to_string),
+            prompt_tokens: token_count(usage, "input_tokens")
+                + token_count(usage, "cache_read_input_tokens")
+                + token_count(usage, "cache_creation_input_tokens"),
+            reported_output_tokens: token_count(usage, "output_tokens"),
+            ..MessageUsage::default()
+        });
+    }
+
+    fn reading(&mut self, now: Instant, force: bool, done: bool) -> Option<UsageGaugeReading> {
+        let current = self.message.as_ref()?;
+        let throttled = self
+            .last_reading_at
+            .is_some_and(|at| now.saturating_duration_since(at) < READING_INTERVAL);
+        if throttled && !force {
+            return None;
+        }
+        self.last_reading_at = Some(now);
+        let context_window = self.context_window();
+        let context_tokens = current.prompt_tokens + current.output_tokens();
+        Some(UsageGaugeReading {
+            output_tokens: self.finished_output_tokens + current.output_tokens(),
+            tokens_per_second: round(current.tokens_per_second(now)),
+            context_tokens,
+            context_window,
+            context_percent: round(context_tokens as f64 * 100.0 / context_window as f64),
+            done,
+        })
+    }
+
+    fn context_window(&self) -> u64 {
+        if let Some(window) = self.context_window {
+            return window;
+        }
+        match &self.model {
+            Some(model) if model.to_ascii_lowercase().ends_with(LONG_CONTEXT_SUFFIX) => {
+                LONG_CONTEXT_WINDOW
+            }
+            _ => DEFAULT_CONTEXT_WINDOW,
+        }
+    }
+}
+
+fn token_count(usage: &Value, key: &str) -> u64 {
+    usage.get(key).and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// The largest `contextWindow` in a result's `modelUsage`.
+fn result_context_window(event: &Value) -> Option<u64> {
+    event
+        .get("modelUsage")?
+        .as_object()?
+        .values()
+        .filter_map(|usage| usage.get("contextWindow").and_then(Value::as_u64))
+        .filter(|window| *window > 0)
+        .max()
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn partial(event: Value) -> Value {
+        json!({ "type": "stream_event", "event": event })
+    }
+
+    fn text_delta(text: &str) -> Value {
+        partial(json!({
+            "type": "content_block_delta",
+            "index": 0,
+            "delta": { "type": "text_delta", "text": text }
+        }))
+    }
+
+    #[test]
+    fn streams_throughput_and_context_fill() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut gauge = UsageGauge::default();
+        let opening = gauge
+            .observe(
+                &partial(json!({
+                    "type": "message_start",
+                    "message": {
+                        "id": "msg_1",
+                        "model": "claude-sonnet-4-5",
+                        "usage": {
+                            "input_tokens": 1_000,
+                            "cache_read_input_tokens": 49_000,
+                            "output_tokens": 1
+                        }
+                    }
+                })),
+                at(0),
+            )
+            .unwrap();
+        assert_eq!(opening.context_tokens, 50_001);
+        assert_eq!(opening.context_window, 200_000);
+        assert_eq!(opening.context_percent, 25.0);
+
+        assert!(gauge
+            .observe(&text_delta(&"a".repeat(40)), at(600))
+            .is_some());
+        // Throttled: too soon after the last reading
+        assert!(gauge
+            .observe(&text_delta(&"a".repeat(40)), at(800))
+            .is_none());
+        let reading = gauge
+            .observe(&text_delta(&"a".repeat(120)), at(1_600))
+            .unwrap();
+        assert_eq!(reading.output_tokens, 50);
+        assert_eq!(reading.tokens_per_second, 50.0);
+
+        // The CLI's own count wins once reported; the assistant event is not counted again
+        gauge.observe(
+            &partial(json!({ "type": "message_delta", "usage": { "output_tokens": 60 } })),
+            at(1_700),
+        );
+        let assistant = json!({
+            "type": "assistant",
+            "message": { "id": "msg_1", "content": [], "usage": { "output_tokens": 60 } }
+        });
+        assert!(gauge.observe(&assistant, at(1_750)).is_none());
+
+        let result = json!({
+            "type": "result",
+            "modelUsage": { "claude-sonnet-4-5": { "contextWindow": 1_000_000 } }
+        });
+        let last = gauge.observe(&result, at(1_800)).unwrap();
+        assert!(last.done);
+        assert_eq!(last.output_tokens, 60);
+        assert_eq!(last.context_window, 1_000_000);
+        // The window is kept for the next turn, the counts are not
+        assert!(gauge.observe(&result, at(1_900)).is_none());
+        assert_eq!(gauge.context_window(), 1_000_000);
+    }
+
+    #[test]
+    fn whole_assistant_messages_count_without_partial_events() {
+        let now = Instant::now();
+        let mut gauge = UsageGauge::default();
+        gauge.observe(
+            &json!({ "type": "system", "subtype": "init", "model": "claude-opus-4-5[1m]" }),
+            now,
+        );
+        let message = |id: &str, input: u64| {
+            json!({
+                "type": "assistant",
+                "message": {
+                    "id": id,
+                    "content": [],
+                    "usage": { "input_tokens": input, "output_tokens": 10 }
+                }
+            })
+        };
+        gauge.observe(&message("msg_1", 100_000), now);
+        let reading = gauge.observe(&message("msg_2", 250_000), now).unwrap();
+        assert_eq!(reading.output_tokens, 20);
+        assert_eq!(reading.context_tokens, 250_010);
+        assert_eq!(reading.context_window, LONG_CONTEXT_WINDOW);
+        assert_eq!(reading.context_percent, 25.0);
+        assert_eq!(reading.tokens_per_second, 0.0);
+    }
+}
@@ -0,0 +1,296 @@
+//! Questions Claude asks with the `AskUserQuestion` tool, and the user's answers.
+//!
+//! Each question offers options, one of which may be picked (several with
+//! `multiSelect`), and always takes a free-text answer instead. Questions are kept per
+//! tool use until answered, so the UI's answers are checked against them before anything
+//! is written to stdin, and reach Claude worded the way the CLI words them.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub(crate) const ASK_USER_QUESTION_TOOL: &str = "AskUserQuestion";
+
+const DEFAULT_HEADER: &str = "Claude needs your input";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QuestionOption {
+    pub(crate) label: String,
+    #[serde(default)]
+    pub(crate) description: String,
+}
+
+/// One question of an `AskUserQuestion` call, as sent to the UI.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Question {
+    /// The tool use id for the first question, `<tool use id>-<index>` for the others.
+    pub(crate) id: String,
+    pub(crate) header: String,
+    pub(crate) question: String,
+    pub(crate) options: Vec<QuestionOption>,
+    pub(crate) multi_select: bool,
+}
+
+/// The UI's answers: question id to the chosen option labels or free text.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct QuestionAnswers {
+    pub(crate) answers: HashMap<String, QuestionAnswer>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct QuestionAnswer {
+    pub(crate) answers: Vec<String>,
+}
+
+/// The questions of an `AskUserQuestion` tool input. Inputs with a single `question`
+/// string, from older CLIs, become one free-text question.
+pub(crate) fn parse_questions(tool_use_id: &str, input: &Value) -> Vec<Question> {
+    let text = |value: &Value, key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let Some(questions) = input.get("questions").and_then(|v| v.as_array()) else {
+        return vec![Question {
+            id: tool_use_id.to_string(),
+            header: DEFAULT_HEADER.to_string(),
+            question: text(input, "question"),
+            options: Vec::new(),
+            multi_select: false,
+        }];
+    };
+    questions
+        .iter()
+        .enumerate()
+        .map(|(index, question)| Question {
+            id: if index == 0 {
+                tool_use_id.to_string()
+            } else {
+                format!("{tool_use_id}-{index}")
+            },
+            header: Some(text(question, "header"))
+                .filter(|header| !header.is_empty())
+                .unwrap_or_else(|| DEFAULT_HEADER.to_string()),
+            question: text(question, "question"),
+            options: question
+                .get("options")
+                .cloned()
+                .and_then(|options| serde_json::from_value(options).ok())
+                .unwrap_or_default(),
+            multi_select: question
+                .get("multiSelect")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        })
+        .collect()
+}
+
+/// Check `answers` against `questions` and word them for Claude. Every question needs
+/// an answer: one option or free text, or with `multiSelect` any number of distinct
+/// options plus at most one free-text entry.
+pub(crate) fn answer_text(
+    questions: &[Question],
+    answers: &QuestionAnswers,
+) -> Result<String, String> {
+    if let Some(unknown) = answers
+        .answers
+        .keys()
+        .find(|id| !questions.iter().any(|question| &question.id == *id))
+    {
+        return Err(format!("no question with id {unknown}"));
+    }
+    let mut parts = Vec::with_capacity(questions.len());
+    for question in questions {
+        let chosen: Vec<&str> = answers
+            .answers
+            .get(&question.id)
+            .map(|answer| answer.answers.iter().map(|choice| choice.trim()).collect())
+            .unwrap_or_default();
+        let name = &question.question;
+        if chosen.is_empty() {
+            return Err(format!("\"{name}\" has no answer"));
+        }
+        if chosen.iter().any(|choice| choice.is_empty()) {
+            return Err(format!("\"{name}\" has an empty answer"));
+        }
+        let free_text = chosen
+            .iter()
+            .filter(|choice| {
+                !question
+                    .options
+                    .iter()
+                    .any(|option| option.label == **choice)
+            })
+            .count();
+        if !question.multi_select && chosen.len() > 1 {
+            return Err(format!("\"{name}\" takes a single answer"));
+        }
+        if free_text > 1 {
+            return Err(format!("\"{name}\" takes one free-text answer at most"));
+        }
+        if (1..chosen.len()).any(|index| chosen[..index].contains(&chosen[index])) {
+            return Err(format!("\"{name}\" has the same answer twice"));
+        }
+        parts.push(format!("\"{name}\"=\"{}\"", chosen.join(", ")));
+    }
+    Ok(format!(
+        "User has answered your questions: {}. You can now continue with the user's answers \
+         in mind.",
+        parts.join(", ")
+    ))
+}
+
+/// `AskUserQuestion` calls waiting for the user, by tool use id.
+#[derive(Debug, Default)]
+pub(crate) struct OpenQuestions {
+    asked: HashMap<String, (String, Vec<Question>)>,
+}
+
+impl OpenQuestions {
+    pub(crate) fn ask(&mut self, thread_id: &str, tool_use_id: &str, questions: Vec<Question>) {
+        self.asked
+            .insert(tool_use_id.to_string(), (thread_id.to_string(), questions));
+    }
+
+    /// The questions of a tool use, if it was asked in `thread_id`.
+    pub(crate) fn get(&self, thread_id: &str, tool_use_id: &str) -> Option<&[Question]> {
+        self.asked
+            .get(tool_use_id)
+            .filter(|(asked_in, _)| asked_in == thread_id)
+            .map(|(_, questions)| questions.as_slice())
+    }
+
+    pub(crate) fn answered(&mut self, tool_use_id: &str) {
+        self.asked.remove(tool_use_id);
+    }
+
+    /// Forget the questions of a thread whose process exited.
+    pub(crate) fn drain_thread(&mut self, thread_id: &str) {
+        self.asked.retain(|_, (asked_in, _)| asked_in != thread_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn questions() -> Vec<Question> {
+        parse_questions(
+            "toolu_1",
+            &json!({ "questions": [
+                {
+                    "question": "Which library?",
+                    "header": "Library",
+                    "options": [
+                        { "label": "date-fns", "description": "Small" },
+                        { "label": "dayjs", "description": "Smaller" }
+                    ],
+                    "multiSelect": false
+                },
+                {
+                    "question": "Which features?",
+                    "options": [{ "label": "Parsing" }, { "label": "Formatting" }],
+                    "multiSelect": true
+                }
+            ]}),
+        )
+    }
+
+    fn answers(entries: &[(&str, &[&str])]) -> QuestionAnswers {
+        QuestionAnswers {
+            answers: entries
+                .iter()
+                .map(|(id, chosen)| {
+                    let answers = chosen.iter().map(|choice| choice.to_string()).collect();
+                    (id.to_string(), QuestionAnswer { answers })
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parses_questions_and_legacy_input() {
+        let parsed = questions();
+        assert_eq!(parsed[0].id, "toolu_1");
+        assert_eq!(parsed[1].id, "toolu_1-1");
+        assert_eq!(parsed[1].header, DEFAULT_HEADER);
+        assert_eq!(parsed[1].options[0].description, "");
+        assert!(parsed[1].multi_select);
+
+        let legacy = parse_questions("toolu_2", &json!({ "question": "Proceed?" }));
+        assert_eq!(legacy.len(), 1);
+        assert_eq!(legacy[0].question, "Proceed?");
+        assert!(legacy[0].options.is_empty());
+    }
+
+    #[test]
+    fn validates_and_words_answers() {
+        let questions = questions();
+        let text = answer_text(
+            &questions,
+            &answers(&[
+                ("toolu_1", &["date-fns"]),
+                ("toolu_1-1", &["Parsing", " time zones "]),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            text,
+            "User has answered your questions: \"Which library?\"=\"date-fns\", \
+             \"Which features?\"=\"Parsing, time zones\". You can now continue with the \
+             user's answers in mind."
+        );
+
+        let error = |entries: &[(&str, &[&str])]| answer_text(&questions, &answers(entries));
+        let both = &["Parsing"][..];
+        assert!(error(&[("toolu_1", &["date-fns"])])
+            .unwrap_err()
+            .contains("has no answer"));
+        assert!(
+            error(&[("toolu_1", &["date-fns", "dayjs"]), ("toolu_1-1", both)])
+                .unwrap_err()
+                .contains("single answer")
+        );
+        assert!(error(&[("toolu_1", &["  "]), ("toolu_1-1", both)])
+            .unwrap_err()
+            .contains("empty answer"));
+        assert!(error(&[("toolu_1", &["x"]), ("toolu_1-1", &["a", "b"])])
+            .unwrap_err()
+            .contains("one free-text answer"));
+        assert!(
+            error(&[("toolu_1", &["x"]), ("toolu_1-1", &["Parsing", "Parsing"])])
+                .unwrap_err()
+                .contains("same answer twice")
+        );
+        assert!(
+            error(&[("toolu_1", &["x"]), ("toolu_1-1", both), ("toolu_9", both)])
+                .unwrap_err()
+                .contains("no question with id toolu_9")
+        );
+        assert!(serde_json::from_value::<QuestionAnswers>(
+            json!({ "answers": { "toolu_1": { "answers": "date-fns" } } })
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn keeps_questions_per_thread() {
+        let mut open = OpenQuestions::default();
+        open.ask("thread-1", "toolu_1", questions());
+        assert!(open.get("thread-2", "toolu_1").is_none());
+        assert_eq!(
+            open.get("thread-1", "toolu_1").map(<[Question]>::len),
+            Some(2)
+        );
+        open.drain_thread("thread-1");
+        assert!(open.get("thread-1", "toolu_1").is_none());
+    }
+}
@@ -0,0 +1,262 @@
+//! Run one prompt across many workspaces.
+//!
+//! Each workspace gets a one-shot `claude -p` turn, like a scheduled run, with at most
+//! `concurrency` of them running at once. Every start and finish is broadcast as a
+//! `batch-progress` event carrying the whole batch, and recent batches can be fetched
+//! until the app restarts. Cancelling skips the workspaces that have not started and
+//! kills the runs in flight.
+
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use futures_util::stream::{self, StreamExt};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::backend::batch_run::BatchRun;
+use crate::backend::events::{BatchProgress, EventSink};
+use crate::budget;
+use crate::claude;
+use crate::event_sink::TauriEventSink;
+use crate::operations;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::WorkspaceEntry;
+use crate::utils::now_ms;
+
+const DEFAULT_CONCURRENCY: usize = 3;
+const MAX_CONCURRENCY: usize = 8;
+const RUN_TIME_LIMIT: Duration = Duration::from_secs(30 * 60);
+const MAX_KEPT_BATCHES: usize = 20;
+
+struct TrackedBatch {
+    run: BatchRun,
+    cancel: CancellationToken,
+}
+
+static BATCHES: OnceLock<Mutex<Vec<TrackedBatch>>> = OnceLock::new();
+
+fn batches() -> MutexGuard<'static, Vec<TrackedBatch>> {
+    BATCHES
+        .get_or_init(|| Mutex::new(Vec::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Keep a new batch, forgetting the oldest finished ones past the limit.
+fn remember(run: BatchRun, cancel: CancellationToken) {
+    let mut batches = batches();
+    batches.insert(0, TrackedBatch { run, cancel });
+    while batches.len() > MAX_KEPT_BATCHES {
+        match batches
+            .iter()
+            .rposition(|tracked| tracked.run.is_finished())
+        {
+            Some(index) => {
+                batches.remove(index);
+            }
+            None => break,
+        }
+    }
+}
+
+/// Apply `change` to a batch and return a snapshot of it afterwards.
+fn update<R>(batch_id: &str, change: impl FnOnce(&mut BatchRun) -> R) -> Option<(R, BatchRun)> {
+    let mut batches = batches();
+    let tracked = batches
+        .iter_mut()
+        .find(|tracked| tracked.run.id == batch_id)?;
+    let result = change(&mut tracked.run);
+    Some((result, tracked.run.clone()))
+}
+
+fn emit_progress(event_sink: &TauriEventSink, workspace_id: Option<&str>, batch: BatchRun) {
+    event_sink.emit_batch_progress(BatchProgress {
+        workspace_id: workspace_id.map(str::to_string),
+        batch,
+    });
+}
+
+async fn run_workspace(
+    app: &AppHandle,
+    batch_id: &str,
+    cancel: &CancellationToken,
+    entry: WorkspaceEntry,
+    prompt: &str,
+    model: Option<&str>,
+    permission_mode: &str,
+) {
+    let event_sink = TauriEventSink::new(app.clone());
+    match update(batch_id, |run| run.start(&entry.id, now_ms())) {
+        Some((true, snapshot)) => emit_progress(&event_sink, Some(&entry.id), snapshot),
+        _ => return,
+    }
+    let state = app.state::<AppState>();
+    let outcome = match budget::ensure_turn_allowed(&state, &entry.id).await {
+        // Dropping the turn on cancellation kills the CLI
+        Ok(()) => cancel
+            .run_until_cancelled(claude::run_claude_prompt_once_with_result(
+                &entry,
+                claude::workspace_claude_bin(&state, &entry).await,
+                prompt.to_string(),
+                Some(permission_mode.to_string()),
+                model.map(str::to_string).or(entry.model.clone()),
+                &claude::workspace_cli_args(&entry),
+                RUN_TIME_LIMIT,
+            ))
+            .await
+            .unwrap_or_else(|| Err(operations::CANCELLED.to_string()))
+            .map(|output| (output.message, output.result)),
+        Err(err) => Err(err),
+    };
+    let finished = update(batch_id, |run| {
+        if outcome.is_err() && cancel.is_cancelled() {
+            run.stop(&entry.id, now_ms());
+        } else {
+            run.finish(&entry.id, outcome, now_ms());
+        }
+    });
+    if let Some(((), snapshot)) = finished {
+        emit_progress(&event_sink, Some(&entry.id), snapshot);
+    }
+}
+
+/// Start `prompt` in every listed workspace and return the batch right away; results
+/// arrive through `batch-progress` events.
+#[tauri::command]
+pub(crate) async fn batch_run(
+    prompt: String,
+    workspace_ids: Vec<String>,
+    concurrency: Option<usize>,
+    model: Option<String>,
+    permission_mode: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "batch_run",
+            json!({
+                "prompt": prompt,
+                "workspaceIds": workspace_ids,
+                "concurrency": concurrency,
+                "model": model,
+                "permissionMode": permission_mode,
+            }),
+        )
+        .await;
+    }
+
+    if prompt.trim().is_empty() {
+        return Err("Prompt is required.".to_string());
+    }
+    let entries: Vec<WorkspaceEntry> = {
+        let workspaces = state.workspaces.lock().await;
+        let mut seen = HashSet::new();
+        workspace_ids
+            .iter()
+            .filter(|id| seen.insert(id.as_str()))
+            .map(|id| {
+                workspaces
+                    .get(id)
+                    .cloned()
+                    .ok_or_else(|| format!("workspace not found: {id}"))
+            })
+            .collect::<Result<_, _>>()?
+    };
+    if entries.is_empty() {
+        return Err("Pick at least one workspace.".to_string());
+    }
+    let concurrency = concurrency
+        .unwrap_or(DEFAULT_CONCURRENCY)
+        .clamp(1, MAX_CONCURRENCY);
+    let run = BatchRun::new(
+        Uuid::new_v4().to_string(),
+        prompt.clone(),
+        concurrency,
+        entries
+            .iter()
+            .map(|entry| (entry.id.clone(), entry.name.clone()))
+            .collect(),
+        now_ms(),
+    );
+    let batch_id = run.id.clone();
+    let cancel = operations::root().child_token();
+    remember(run.clone(), cancel.clone());
+    emit_progress(&TauriEventSink::new(app.clone()), None, run.clone());
+
+    let model = model.filter(|value| !value.trim().is_empty());
+    let permission_mode = permission_mode
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| claude::UNATTENDED_PERMISSION_MODE.to_string());
+    tauri::async_runtime::spawn(async move {
+        stream::iter(entries)
+            .for_each_concurrent(concurrency, |entry| {
+                run_workspace(
+                    &app,
+                    &batch_id,
+                    &cancel,
+                    entry,
+                    &prompt,
+                    model.as_deref(),
+                    &permission_mode,
+                )
+            })
+            .await;
+    });
+    serde_json::to_value(run).map_err(|err| err.to_string())
+}
+
+/// Recent batches, newest first.
+#[tauri::command]
+pub(crate) async fn batches_list(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(&*state, app, "batches_list", json!({})).await;
+    }
+
+    let batches: Vec<BatchRun> = batches()
+        .iter()
+        .map(|tracked| tracked.run.clone())
+        .collect();
+    serde_json::to_value(batches).map_err(|err| err.to_string())
+}
+
+/// Stop a batch: workspaces that have not started are skipped and running turns are
+/// killed.
+#[tauri::command]
+pub(crate) async fn batch_cancel(
+    batch_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "batch_cancel",
+            json!({ "batchId": batch_id }),
+        )
+        .await;
+    }
+
+    let snapshot = {
+        let mut batches = batches();
+        let tracked = batches
+            .iter_mut()
+            .find(|tracked| tracked.run.id == batch_id)
+            .ok_or("batch not found")?;
+        tracked.run.cancel(now_ms());
+        tracked.cancel.cancel();
+        tracked.run.clone()
+    };
+    emit_progress(&TauriEventSink::new(app), None, snapshot.clone());
+    serde_json::to_value(snapshot).map_err(|err| err.to_string())
+}
@@ -0,0 +1,6 @@
+fn main() {
+    if let Err(err) = fix_path_env::fix() {
+        eprintln!("Failed to sync PATH from shell: {err}");
+    }
+    claude_code_monitor_lib::run_daemon()
+}
@@ -0,0 +1,3 @@
+fn main() {
+    claude_code_monitor_lib::run_mock_claude()
+}
@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate, TimeZone};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+
+use crate::claude::emit_event;
+use crate::event_sink::TauriEventSink;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::CostBudget;
+use crate::usage;
+
+const DEFAULT_WARN_RATIO: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum BudgetPeriod {
+    Daily,
+    Monthly,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum BudgetLevel {
+    Ok,
+    Warning,
+    Exceeded,
+}
+
+/// Spend against one limit. `workspace_id` is `None` for the global budget.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BudgetStatus {
+    pub(crate) workspace_id: Option<String>,
+    pub(crate) period: BudgetPeriod,
+    pub(crate) limit_usd: f64,
+    /// Spend at which warnings start.
+    pub(crate) warn_usd: f64,
+    pub(crate) spent_usd: f64,
+    pub(crate) level: BudgetLevel,
+    /// Epoch milliseconds at which the period resets.
+    pub(crate) resets_at: i64,
+    /// The hard limit was lifted with `override_budget` for the rest of the period.
+    pub(crate) overridden: bool,
+}
+
+/// Overrides of exhausted budgets, keyed by workspace id (`""` for the global budget),
+/// valid until the stored epoch milliseconds. Kept in memory, so a restart restores
+/// the limit.
+static OVERRIDES: OnceLock<Mutex<HashMap<String, i64>>> = OnceLock::new();
+
+fn with_overrides<T>(f: impl FnOnce(&mut HashMap<String, i64>) -> T) -> T {
+    let mut guard = OVERRIDES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut guard)
+}
+
+fn override_key(workspace_id: Option<&str>) -> String {
+    workspace_id.unwrap_or_default().to_string()
+}
+
+/// Start and end of the period containing `now`, in epoch milliseconds.
+pub(crate) fn period_bounds(period: BudgetPeriod, now: DateTime<Local>) -> (i64, i64) {
+    let today = now.date_naive();
+    let (start, end) = match period {
+        BudgetPeriod::Daily => (today, today.succ_opt().unwrap_or(today)),
+        BudgetPeriod::Monthly => {
+            let first = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap_or(today);
+            (first, first + Months::new(1))
+        }
+    };
+    let midnight = |date: NaiveDate| {
+        date.and_hms_opt(0, 0, 0)
+            .and_then(|time| Local.from_local_datetime(&time).earliest())
+            .map(|time| time.timestamp_millis())
+            .unwrap_or_else(|| now.timestamp_millis())
+    };
+    (midnight(start), midnight(end))
+}
+
+pub(crate) fn level(spent_usd: f64, limit_usd: f64, warn_usd: f64) -> BudgetLevel {
+    if spent_usd >= limit_usd {
+        BudgetLevel::Exceeded
+    } else if spent_usd >= warn_usd {
+        BudgetLevel::Warning
+    } else {
+        BudgetLevel::Ok
+    }
+}
+
+/// One status per limit set in `budget`. `spent_since` returns the dollars spent in
+/// this scope since an epoch-millisecond timestamp.
+pub(crate) fn evaluate(
+    budget: &CostBudget,
+    workspace_id: Option<&str>,
+    now: DateTime<Local>,
+    spent_since: impl Fn(i64) -> f64,
+) -> Vec<BudgetStatus> {
+    let warn_ratio = budget
+        .warn_ratio
+        .filter(|ratio| *ratio > 0.0 && *ratio <= 1.0)
+        .unwrap_or(DEFAULT_WARN_RATIO);
+    let overridden_until = with_overrides(|overrides| {
+        overrides
+            .get(&override_key(workspace_id))
+            .copied()
+            .unwrap_or(0)
+    });
+    [
+        (BudgetPeriod::Daily, budget.daily_usd),
+        (BudgetPeriod::Monthly, budget.monthly_usd),
+    ]
+    .into_iter()
+    .filter_map(|(period, limit)| Some((period, limit.filter(|limit| *limit > 0.0)?)))
+    .map(|(period, limit_usd)| {
+        let (start, resets_at) = period_bounds(period, now);
+        let spent_usd = spent_since(start);
+        let warn_usd = limit_usd * warn_ratio;
+        BudgetStatus {
+            workspace_id: workspace_id.map(str::to_string),
+            period,
+            limit_usd,
+            warn_usd,
+            spent_usd,
+            level: level(spent_usd, limit_usd, warn_usd),
+            resets_at,
+            overridden: overridden_until > now.timestamp_millis(),
+        }
+    })
+    .collect()
+}
+
+/// Statuses of the workspace's own budget followed by the global one. Settings are
+/// read fresh, so edits apply to the next turn.
+pub(crate) async fn budget_statuses(
+    state: &AppState,
+    workspace_id: Option<&str>,
+) -> Vec<BudgetStatus> {
+    let now = Local::now();
+    let mut statuses = Vec::new();
+    if let Some(workspace_id) = workspace_id {
+        let budget = state
+            .workspaces
+            .lock()
+            .await
+            .get(workspace_id)
+            .and_then(|entry| entry.settings.cost_budget.clone());
+        if let Some(budget) = budget {
+            statuses.extend(evaluate(&budget, Some(workspace_id), now, |since| {
+                usage::spent_since(Some(workspace_id), since)
+            }));
+        }
+    }
+    let global = state.app_settings.lock().await.cost_budget.clone();
+    if let Some(budget) = global {
+        statuses.extend(evaluate(&budget, None, now, |since| {
+            usage::spent_since(None, since)
+        }));
+    }
+    statuses
+}
+
+/// Refuse to start a turn while a budget covering the workspace is used up, unless it
+/// was overridden.
+pub(crate) async fn ensure_turn_allowed(
+    state: &AppState,
+    workspace_id: &str,
+) -> Result<(), String> {
+    let blocked = budget_statuses(state, Some(workspace_id))
+        .await
+        .into_iter()
+        .find(|status| status.level == BudgetLevel::Exceeded && !status.overridden);
+    match blocked {
+        Some(status) => Err(format!(
+            "{} {} budget of ${:.2} is used up (${:.2} spent); override it to start new turns.",
+            match status.period {
+                BudgetPeriod::Daily => "The daily",
+                BudgetPeriod::Monthly => "The monthly",
+            },
+            if status.workspace_id.is_some() {
+                "workspace"
+            } else {
+                "global"
+            },
+            status.limit_usd,
+            status.spent_usd,
+        )),
+        None => Ok(()),
+    }
+}
+
+/// After a turn's cost was recorded, emit `budget/warning` or `budget/exceeded` for
+/// every limit the turn pushed into a higher level.
+pub(crate) fn notify_turn_cost(event_sink: &TauriEventSink, workspace_id: &str, cost_usd: f64) {
+    if cost_usd <= 0.0 {
+        return;
+    }
+    let app = event_sink.app_handle().clone();
+    let event_sink = event_sink.clone();
+    let workspace_id = workspace_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<AppState>() else {
+            return;
+        };
+        for status in budget_statuses(&state, Some(&workspace_id)).await {
+            let before = level(
+                status.spent_usd - cost_usd,
+                status.limit_usd,
+                status.warn_usd,
+            );
+            let method = match status.level {
+                BudgetLevel::Exceeded if before != BudgetLevel::Exceeded => "budget/exceeded",
+                BudgetLevel::Warning if before == BudgetLevel::Ok => "budget/warning",
+                _ => continue,
+            };
+            emit_event(
+                &event_sink,
+                &workspace_id,
+                method,
+                json!({ "status": status }),
+            );
+        }
+    });
+}
+
+#[tauri::command]
+pub(crate) async fn get_budget_status(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "get_budget_status",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+    let statuses = budget_statuses(&state, workspace_id.as_deref()).await;
+    serde_json::to_value(statuses).map_err(|err| err.to_string())
+}
+
+/// Lift the hard limit of a used-up budget (the global one when `workspace_id` is
+/// omitted) until its period resets. Warnings keep coming.
+#[tauri::command]
+pub(crate) async fn override_budget(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "override_budget",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+    let statuses = budget_statuses(&state, workspace_id.as_deref()).await;
+    let until = statuses
+        .iter()
+        .filter(|status| status.workspace_id == workspace_id)
+        .filter(|status| status.level == BudgetLevel::Exceeded)
+        .map(|status| status.resets_at)
+        .max()
+        .ok_or("This budget is not used up.")?;
+    with_overrides(|overrides| {
+        overrides.insert(override_key(workspace_id.as_deref()), until);
+    });
+    let statuses = budget_statuses(&state, workspace_id.as_deref()).await;
+    serde_json::to_value(statuses).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(year, month, day, hour, 0, 0)
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn periods_run_from_local_midnight() {
+        let now = local(2026, 12, 31, 15);
+        assert_eq!(
+            period_bounds(BudgetPeriod::Daily, now),
+            (
+                local(2026, 12, 31, 0).timestamp_millis(),
+                local(2027, 1, 1, 0).timestamp_millis()
+            )
+        );
+        assert_eq!(
+            period_bounds(BudgetPeriod::Monthly, now),
+            (
+                local(2026, 12, 1, 0).timestamp_millis(),
+                local(2027, 1, 1, 0).timestamp_millis()
+            )
+        );
+    }
+
+    #[test]
+    fn levels_follow_the_warn_ratio() {
+        let budget = CostBudget {
+            daily_usd: Some(10.0),
+            monthly_usd: Some(100.0),
+            warn_ratio: Some(0.5),
+        };
+        let now = local(2026, 3, 15, 12);
+        let (day_start, _) = period_bounds(BudgetPeriod::Daily, now);
+        // $6 today, $104 this month
+        let statuses = evaluate(&budget, Some("ws-budget-test"), now, |since| {
+            if since == day_start {
+                6.0
+            } else {
+                104.0
+            }
+        });
+        let levels: Vec<(BudgetPeriod, BudgetLevel)> = statuses
+            .iter()
+            .map(|status| (status.period, status.level))
+            .collect();
+        assert_eq!(
+            levels,
+            vec![
+                (BudgetPeriod::Daily, BudgetLevel::Warning),
+                (BudgetPeriod::Monthly, BudgetLevel::Exceeded),
+            ]
+        );
+        assert!(statuses.iter().all(|status| !status.overridden));
+        assert_eq!(statuses[0].warn_usd, 5.0);
+        assert_eq!(level(1.0, 10.0, 8.0), BudgetLevel::Ok);
+
+        // Unset and zero limits are ignored
+        let budget = CostBudget {
+            daily_usd: Some(0.0),
+            ..CostBudget::default()
+        };
+        assert!(evaluate(&budget, None, now, |_| 1.0).is_empty());
+    }
+}
@@ -1,35 +1,118 @@
 use chrono::DateTime;
+use futures_util::future::join_all;
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::{json, Map, Value};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::env;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use tauri::{AppHandle, State};
-use tokio::io::{AsyncBufReadExt, BufReader as AsyncBufReader};
+use tauri::{AppHandle, Manager, State};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader as AsyncBufReader};
 use tokio::process::Command;
-use tokio::sync::watch;
 use tokio::time::{interval, sleep, timeout};
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 use uuid::Uuid;
 
-
-
+use crate::attachments::{self, Attachment};
+use crate::backend::bash_policy::{self, BASH_TOOL};
 pub(crate) use crate::backend::claude_cli::WorkspaceSession;
 use crate::backend::claude_cli::{
-    build_claude_command_with_bin, build_claude_path_env, check_claude_installation,
-    spawn_workspace_session as spawn_workspace_session_inner,
+    build_claude_command_with_bin, build_claude_path_env, idle_timeout,
+    spawn_workspace_session as spawn_workspace_session_inner, InterruptOutcome,
+    PersistentSessionReaders, PersistentSessionStatus, SessionLaunch, SuspendOutcome,
+};
+use crate::backend::cli_flags::cli_flag_args;
+use crate::backend::compaction::{compact_command, CompactionTracker};
+use crate::backend::control::{response_request_id, PendingPermission, PermissionDecision};
+use crate::backend::edit_hunks::{self, EditRecorder};
+use crate::backend::events::{AppServerEvent, EventSink, SessionDisconnected, TurnQueueUpdated};
+use crate::backend::guardrails::{self, path_decision, read_only_decision};
+use crate::backend::health::SessionHealth;
+use crate::backend::interactions::{
+    default_answers, default_permission, InteractionDue, InteractionKind, PendingInteraction,
+};
+use crate::backend::metrics;
+use crate::backend::ownership::{self, ThreadOwner};
+use crate::backend::permission_presets::preset_decision;
+use crate::backend::plan_review::{plan_text, PendingPlan, EXIT_PLAN_TOOL};
+use crate::backend::proc_stats::{memory_limit, ProcessSampler};
+use crate::backend::provider::{provider_endpoint, provider_env};
+use crate::backend::rate_limits;
+use crate::backend::retry;
+use crate::backend::session_logs::{self, LogStream};
+use crate::backend::stream_events::{
+    parse_event_line, ClaudeEvent, ControlRequest, ControlRequestEvent, McpServerStatus,
+    ResultEvent,
 };
-use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::tool_timeline::ToolTimeline;
+use crate::backend::turn_clock::TurnClock;
+use crate::backend::turn_queue::QueuedPrompt;
+use crate::backend::turn_watchdog::{turn_limits, TurnTimeout, WatchdogAction};
+use crate::backend::usage_gauge::{UsageGauge, UsageGaugeReading};
+use crate::backend::user_questions::{parse_questions, ASK_USER_QUESTION_TOOL};
+use crate::budget;
 use crate::claude_home::{resolve_default_claude_home, resolve_workspace_claude_home};
+use crate::claude_settings::{read_settings_file, write_settings_file};
+use crate::cli_manager::check_claude_installation;
+use crate::context_pack;
+use crate::credentials;
 use crate::event_sink::TauriEventSink;
+use crate::git::autocommit;
+use crate::git::diff::{self as git_diff, SnapshotDiff, WorktreeSnapshot};
+use crate::git_utils::resolve_git_root;
+use crate::notifications::{self, Notice};
+use crate::operations;
+use crate::outbox;
+use crate::proxy;
+use crate::redaction;
 use crate::remote_backend;
+use crate::shutdown;
 use crate::state::{AppState, WorkspaceWatcher};
-use crate::types::WorkspaceEntry;
+use crate::storage::{self, SessionFork, ThreadSessionStatus};
+use crate::transcripts::{fork_transcript, list_sessions, resolve_project_dir};
+use crate::types::{
+    AgentKind, BashDecision, CompactionRecord, InteractionTimeouts, PermissionPreset, TurnDiff,
+    WorkspaceEntry,
+};
+use crate::usage;
+use crate::utils::now_ms;
+use crate::verification;
+use crate::webhooks::{self, WebhookEvent};
+use crate::workspaces::remove_worktree_entry;
+
+/// Decisions `list_bash_decisions` returns when not asked for a count.
+const DEFAULT_BASH_DECISIONS: usize = 100;
+/// Most decisions `list_bash_decisions` returns.
+const MAX_BASH_DECISIONS: usize = 1000;
+/// Lines `get_session_logs` returns when not asked for a count.
+const DEFAULT_SESSION_LOG_LINES: usize = 200;
+/// Most lines `get_session_logs` returns.
+const MAX_SESSION_LOG_LINES: usize = 10_000;
+/// How often the session supervisor checks whether its CLI process is still alive.
+const SESSION_SUPERVISOR_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Consecutive unexpected exits after which the supervisor stops respawning.
+const MAX_SESSION_RECONNECTS: u32 = 3;
+/// A process that stays up this long is considered healthy again.
+const SESSION_STABLE_AFTER: Duration = Duration::from_secs(30);
+/// How often the reaper looks for thread processes that have sat idle too long.
+const IDLE_REAPER_INTERVAL: Duration = Duration::from_secs(60);
+/// How often the turn watchdog checks running turns against their workspace's limits.
+const TURN_WATCHDOG_INTERVAL: Duration = Duration::from_secs(5);
+/// How often unanswered permission requests, questions and plans are checked against
+/// their workspace's timeouts.
+const INTERACTION_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// How long a turn the watchdog interrupted may keep running before its process is killed.
+const TIMED_OUT_TURN_KILL_AFTER: Duration = Duration::from_secs(30);
+/// How often the CPU and memory of every thread's process tree is sampled.
+const RESOURCE_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+/// How often every persistent process gets a health check.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -83,13 +166,13 @@ pub(crate) async fn ensure_workspace_thread_watcher(
         if existing.workspace_path == entry.path {
             return;
         }
-        let _ = existing.shutdown.send(true);
+        existing.shutdown.cancel();
     }
-    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let shutdown = operations::root().child_token();
     watchers.insert(
         workspace_id.to_string(),
         WorkspaceWatcher {
-            shutdown: shutdown_tx,
+            shutdown: shutdown.clone(),
             workspace_path: entry.path.clone(),
         },
     );
@@ -98,17 +181,430 @@ pub(crate) async fn ensure_workspace_thread_watcher(
         workspace_id.to_string(),
         entry,
         event_sink,
-        shutdown_rx,
+        shutdown,
     ));
 }
 
-pub(crate) async fn stop_workspace_thread_watcher(
+/// Background task that stops thread processes idle for longer than their workspace's
+/// `idleTimeoutMinutes` and emits `session/hibernated` for each. The transcript stays
+/// on disk, so the next message simply resumes it.
+pub(crate) fn spawn_idle_session_reaper(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let event_sink = TauriEventSink::new(app.clone());
+        let mut ticker = interval(IDLE_REAPER_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let Some(state) = app.try_state::<AppState>() else {
+                continue;
+            };
+            for (workspace_id, session) in connected_sessions(&state).await {
+                // Read settings fresh; the session keeps the entry it was spawned with
+                let timeout = {
+                    let workspaces = state.workspaces.lock().await;
+                    workspaces
+                        .get(&workspace_id)
+                        .and_then(|entry| idle_timeout(&entry.settings))
+                };
+                let Some(timeout) = timeout else {
+                    continue;
+                };
+                for thread_id in session.hibernate_idle_sessions(timeout).await {
+                    emit_event(
+                        &event_sink,
+                        &workspace_id,
+                        "session/hibernated",
+                        json!({
+                            "threadId": thread_id,
+                            "idleMinutes": timeout.as_secs() / 60,
+                        }),
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Background task that health-checks every persistent process and emits
+/// `session/health` whenever a thread's status changes, so a broken pipe shows up
+/// before the next message fails on it.
+pub(crate) fn spawn_session_health_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let event_sink = TauriEventSink::new(app.clone());
+        let mut ticker = interval(HEALTH_CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let Some(state) = app.try_state::<AppState>() else {
+                continue;
+            };
+            for (workspace_id, session) in connected_sessions(&state).await {
+                for report in check_session_health(&session, None).await {
+                    if session.record_health(&report).await {
+                        emit_event(
+                            &event_sink,
+                            &workspace_id,
+                            "session/health",
+                            json!({ "threadId": report.thread_id, "health": report }),
+                        );
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Background task that cancels turns running longer than their workspace's
+/// `maxTurnMinutes`, or with a tool call running longer than `maxToolRunMinutes`. The
+/// turn is interrupted like a user would, and its process killed if the turn is still
+/// running `TIMED_OUT_TURN_KILL_AFTER` later. Emits `turn/timedOut` when it fires.
+pub(crate) fn spawn_turn_watchdog(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let event_sink = TauriEventSink::new(app.clone());
+        let mut ticker = interval(TURN_WATCHDOG_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let Some(state) = app.try_state::<AppState>() else {
+                continue;
+            };
+            for (workspace_id, session) in connected_sessions(&state).await {
+                // Read settings fresh; the session keeps the entry it was spawned with
+                let limits = {
+                    let workspaces = state.workspaces.lock().await;
+                    workspaces
+                        .get(&workspace_id)
+                        .map(|entry| turn_limits(&entry.settings))
+                        .unwrap_or_default()
+                };
+                let actions = session.turn_watch.lock().await.check(
+                    &limits,
+                    TIMED_OUT_TURN_KILL_AFTER,
+                    Instant::now(),
+                );
+                for action in actions {
+                    tokio::spawn(enforce_turn_timeout(
+                        Arc::clone(&session),
+                        event_sink.clone(),
+                        workspace_id.clone(),
+                        action,
+                    ));
+                }
+            }
+        }
+    });
+}
+
+async fn enforce_turn_timeout(
+    session: Arc<WorkspaceSession>,
+    event_sink: TauriEventSink,
+    workspace_id: String,
+    action: WatchdogAction,
+) {
+    let (thread_id, result) = match action {
+        WatchdogAction::Interrupt(timeout) => {
+            tracing::warn!(
+                "turn {} of thread {} ran past its {:?} limit of {}s; interrupting it",
+                timeout.turn_id,
+                timeout.thread_id,
+                timeout.kind,
+                timeout.limit_seconds
+            );
+            emit_event(
+                &event_sink,
+                &workspace_id,
+                "turn/timedOut",
+                json!({ "threadId": timeout.thread_id, "timeout": timeout }),
+            );
+            let outcome = session
+                .interrupt_turn(&timeout.thread_id, &timeout.turn_id)
+                .await;
+            (timeout.thread_id, outcome)
+        }
+        WatchdogAction::Kill { thread_id, turn_id } => {
+            tracing::warn!(
+                "timed out turn {turn_id} of thread {thread_id} did not stop; killing it"
+            );
+            let outcome = session
+                .kill_persistent_session(&thread_id)
+                .await
+                .map(|()| InterruptOutcome::Terminated);
+            (thread_id, outcome)
+        }
+    };
+    match result {
+        // An acknowledged interrupt ends the turn through the CLI's `result` event
+        Ok(InterruptOutcome::Acknowledged | InterruptOutcome::NotRunning) => {}
+        Ok(InterruptOutcome::Terminated) => session.finish_turn(&thread_id).await,
+        Err(err) => tracing::warn!("failed to stop timed out turn of thread {thread_id}: {err}"),
+    }
+}
+
+/// Background task that samples the CPU and memory of every thread's process tree and
+/// emits `session/resources`. Threads over their workspace's memory limit get a
+/// `session/resourceWarning`, and with `autoRestart` are stopped once idle.
+pub(crate) fn spawn_resource_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let event_sink = TauriEventSink::new(app.clone());
+        let mut sampler = ProcessSampler::default();
+        let mut ticker = interval(RESOURCE_SAMPLE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let Some(state) = app.try_state::<AppState>() else {
+                continue;
+            };
+            let mut sessions = Vec::new();
+            for (workspace_id, session) in connected_sessions(&state).await {
+                let threads = session.thread_processes().await;
+                sessions.push((workspace_id, session, threads));
+            }
+            // One refresh of the process table serves every workspace
+            let any_alive = sessions
+                .iter()
+                .any(|(_, _, threads)| threads.iter().any(|thread| thread.alive));
+            if any_alive {
+                sampler.refresh();
+            }
+            let now = now_ms();
+            for (workspace_id, session, threads) in sessions {
+                let roots: Vec<(String, u32)> = threads
+                    .iter()
+                    .filter(|thread| thread.alive)
+                    .filter_map(|thread| Some((thread.thread_id.clone(), thread.pid?)))
+                    .collect();
+                let busy: HashSet<String> = threads
+                    .iter()
+                    .filter(|thread| thread.running)
+                    .map(|thread| thread.thread_id.clone())
+                    .collect();
+                // Read settings fresh; the session keeps the entry it was spawned with
+                let limit = {
+                    let workspaces = state.workspaces.lock().await;
+                    workspaces
+                        .get(&workspace_id)
+                        .map(|entry| memory_limit(&entry.settings))
+                        .unwrap_or_default()
+                };
+                let stats = sampler.sample(&roots, now);
+                let warnings = {
+                    let mut resources = session.resources.lock().await;
+                    if !stats.is_empty() || !resources.is_empty() {
+                        emit_event(
+                            &event_sink,
+                            &workspace_id,
+                            "session/resources",
+                            json!({ "processes": stats }),
+                        );
+                    }
+                    resources.update(stats, &limit, &busy)
+                };
+                for warning in warnings {
+                    tracing::warn!(
+                        "thread {} uses {} MB, over its limit of {} MB{}",
+                        warning.thread_id,
+                        warning.memory_bytes / (1024 * 1024),
+                        warning.limit_bytes / (1024 * 1024),
+                        if warning.restarting {
+                            "; restarting it"
+                        } else {
+                            ""
+                        }
+                    );
+                    emit_event(
+                        &event_sink,
+                        &workspace_id,
+                        "session/resourceWarning",
+                        json!({ "threadId": warning.thread_id, "warning": warning }),
+                    );
+                    if !warning.restarting {
+                        continue;
+                    }
+                    if let Err(err) = session.kill_persistent_session(&warning.thread_id).await {
+                        tracing::warn!("failed to restart thread {}: {err}", warning.thread_id);
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Report requests nobody answered as stale, and answer overdue ones with their
+/// defaults, as each workspace's `interactionTimeouts` say.
+pub(crate) fn spawn_interaction_watchdog(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let event_sink = TauriEventSink::new(app.clone());
+        let mut ticker = interval(INTERACTION_CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let Some(state) = app.try_state::<AppState>() else {
+                continue;
+            };
+            let now = now_ms();
+            for (workspace_id, session) in connected_sessions(&state).await {
+                // Read settings fresh; the session keeps the entry it was spawned with
+                let timeouts = {
+                    let workspaces = state.workspaces.lock().await;
+                    workspaces
+                        .get(&workspace_id)
+                        .and_then(|entry| entry.settings.interaction_timeouts.clone())
+                };
+                let Some(timeouts) = timeouts else {
+                    continue;
+                };
+                let due = session.interactions.lock().await.check(&timeouts, now);
+                for due in due {
+                    match due {
+                        InteractionDue::Stale(interaction) => emit_event(
+                            &event_sink,
+                            &workspace_id,
+                            "interaction/stale",
+                            json!({
+                                "threadId": interaction.thread_id,
+                                "interaction": interaction,
+                            }),
+                        ),
+                        InteractionDue::AutoAnswer(interaction) => {
+                            let sink = &event_sink;
+                            auto_answer(&session, sink, &workspace_id, &timeouts, interaction).await
+                        }
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn auto_answer(
+    session: &WorkspaceSession,
+    event_sink: &TauriEventSink,
     workspace_id: &str,
-    state: &AppState,
+    timeouts: &InteractionTimeouts,
+    interaction: PendingInteraction,
+) {
+    let thread_id = interaction.thread_id.as_str();
+    let result = match (interaction.kind, interaction.request_id.as_deref()) {
+        (InteractionKind::Permission, Some(request_id)) => {
+            let decision = default_permission(timeouts, &interaction.tool_name);
+            let result = session
+                .respond_to_permission(thread_id, request_id, decision)
+                .await;
+            if result.is_ok() {
+                emit_event(
+                    event_sink,
+                    workspace_id,
+                    "item/permission/resolved",
+                    json!({ "threadId": thread_id, "requestId": request_id }),
+                );
+            }
+            result
+        }
+        (InteractionKind::Permission, None) => Err("no request to answer".to_string()),
+        (InteractionKind::Plan, _) => session
+            .approve_plan(thread_id, None)
+            .await
+            .map(|plan| emit_plan_resolved(event_sink, workspace_id, &plan, true)),
+        (InteractionKind::Question, _) => {
+            let questions = session
+                .questions
+                .lock()
+                .await
+                .get(thread_id, &interaction.key)
+                .map(<[_]>::to_vec);
+            match questions {
+                Some(questions) => {
+                    let answers = serde_json::to_value(default_answers(&questions))
+                        .map_err(|err| err.to_string());
+                    match answers {
+                        Ok(answers) => {
+                            session
+                                .answer_questions(thread_id, &interaction.key, answers)
+                                .await
+                        }
+                        Err(err) => Err(err),
+                    }
+                }
+                None => Err("question already answered".to_string()),
+            }
+        }
+    };
+    match result {
+        Ok(()) => emit_event(
+            event_sink,
+            workspace_id,
+            "interaction/autoAnswered",
+            json!({ "threadId": thread_id, "interaction": interaction }),
+        ),
+        Err(err) => tracing::warn!(
+            "could not give {} in thread {thread_id} its default answer: {err}",
+            interaction.key
+        ),
+    }
+}
+
+/// Track a request the thread now waits on the user for, under its tool use id or,
+/// without one, its request id.
+async fn open_interaction(
+    session: &WorkspaceSession,
+    kind: InteractionKind,
+    thread_id: &str,
+    turn_id: &str,
+    request_id: Option<&str>,
+    tool_use_id: Option<&str>,
+    tool_name: &str,
 ) {
+    let Some(key) = tool_use_id.or(request_id).filter(|key| !key.is_empty()) else {
+        return;
+    };
+    session.interactions.lock().await.open(PendingInteraction {
+        key: key.to_string(),
+        kind,
+        thread_id: thread_id.to_string(),
+        turn_id: turn_id.to_string(),
+        request_id: request_id.map(str::to_string),
+        tool_name: tool_name.to_string(),
+        created_at: now_ms(),
+        stale: false,
+    });
+}
+
+async fn connected_sessions(state: &AppState) -> Vec<(String, Arc<WorkspaceSession>)> {
+    state
+        .sessions
+        .lock()
+        .await
+        .iter()
+        .map(|(id, session)| (id.clone(), Arc::clone(session)))
+        .collect()
+}
+
+/// Live CLI processes per connected workspace.
+pub(crate) async fn active_session_counts(state: &AppState) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for (workspace_id, session) in connected_sessions(state).await {
+        counts.insert(workspace_id, session.persistent_thread_ids().await.len());
+    }
+    counts
+}
+
+/// Check one thread, or every thread with a live process, concurrently.
+async fn check_session_health(
+    session: &WorkspaceSession,
+    thread_id: Option<String>,
+) -> Vec<SessionHealth> {
+    let thread_ids = match thread_id {
+        Some(thread_id) => vec![thread_id],
+        None => session.persistent_thread_ids().await,
+    };
+    join_all(
+        thread_ids
+            .iter()
+            .map(|thread_id| session.check_health(thread_id)),
+    )
+    .await
+}
+
+pub(crate) async fn stop_workspace_thread_watcher(workspace_id: &str, state: &AppState) {
     if let Some(existing) = state.thread_watchers.lock().await.remove(workspace_id) {
-        let _ = existing.shutdown.send(true);
+        existing.shutdown.cancel();
     }
+    crate::tail::stop_workspace_tails(workspace_id, state).await;
 }
 
 #[tauri::command]
@@ -134,6 +630,47 @@ pub(crate) async fn claude_doctor(
     }))
 }
 
+/// Probe the API a workspace's processes would talk to, with its provider settings and
+/// credentials, through its proxy settings. Any HTTP response counts as reachable: this
+/// checks DNS, proxies and TLS, not whether the credentials are accepted.
+#[tauri::command]
+pub(crate) async fn check_provider_connectivity(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "check_provider_connectivity",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+    let entry = state
+        .workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not found")?;
+    let credentials = credentials::for_workspace(&entry).await?;
+    let env = provider_env(entry.settings.provider.as_ref(), credentials.as_ref())?;
+    let (provider, endpoint) = provider_endpoint(env.as_deref())?;
+    let proxy = proxy::effective(entry.settings.proxy.as_ref());
+    let result = proxy::probe(&endpoint, &proxy).await?;
+    Ok(json!({
+        "ok": result.ok,
+        "provider": provider,
+        "endpoint": endpoint,
+        "status": result.status,
+        "latencyMs": result.latency_ms,
+        "error": result.error,
+        "hasCredentials": credentials.is_some(),
+    }))
+}
+
 #[tauri::command]
 pub(crate) async fn start_thread(
     workspace_id: String,
@@ -196,15 +733,103 @@ pub(crate) async fn resume_thread(
     };
 
     let thread_id_clone = thread_id.clone();
-    let thread = tokio::task::spawn_blocking(move || {
-        build_thread_from_session(&entry, &thread_id_clone)
-    })
-    .await
-    .map_err(|err| err.to_string())??;
+    let thread =
+        tokio::task::spawn_blocking(move || build_thread_from_session(&entry, &thread_id_clone))
+            .await
+            .map_err(|err| err.to_string())??;
 
     Ok(json!({ "thread": thread }))
 }
 
+/// Branch a session: copy its transcript up to `message_index` into a new session, which
+/// resumes from there the first time it is sent a message. The original is untouched;
+/// the fork's parent is recorded for `list_session_forks` and `list_threads`.
+#[tauri::command]
+pub(crate) async fn fork_session(
+    workspace_id: String,
+    session_id: String,
+    message_index: u32,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "fork_session",
+            json!({
+                "workspaceId": workspace_id,
+                "sessionId": session_id,
+                "messageIndex": message_index,
+            }),
+        )
+        .await;
+    }
+
+    let entry = state
+        .workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not found")?;
+    let source = resolve_session_path(&entry, &session_id)
+        .ok_or_else(|| "Session file not found".to_string())?;
+    let fork_id = Uuid::new_v4().to_string();
+    let dest = source.with_file_name(format!("{fork_id}.jsonl"));
+    let (thread, forked) = {
+        let entry = entry.clone();
+        let fork_id = fork_id.clone();
+        tokio::task::spawn_blocking(move || {
+            let forked = fork_transcript(&source, &dest, &fork_id, message_index)?;
+            Ok::<_, String>((build_thread_from_session(&entry, &fork_id)?, forked))
+        })
+        .await
+        .map_err(|err| err.to_string())??
+    };
+    let fork = SessionFork {
+        workspace_id: workspace_id.clone(),
+        session_id: fork_id,
+        parent_session_id: session_id,
+        message_index,
+        kept_messages: forked.kept_messages,
+        created_at: now_ms(),
+    };
+    storage::insert_session_fork(&state.db_path, &fork)?;
+    emit_event(
+        &TauriEventSink::new(app),
+        &workspace_id,
+        "thread/forked",
+        json!({
+            "threadId": fork.session_id,
+            "parentThreadId": fork.parent_session_id,
+            "keptMessages": fork.kept_messages,
+        }),
+    );
+    Ok(json!({ "thread": thread, "fork": fork }))
+}
+
+/// Every fork made in the workspace, oldest first.
+#[tauri::command]
+pub(crate) async fn list_session_forks(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_session_forks",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let forks = storage::read_session_forks(&state.db_path, &workspace_id)?;
+    Ok(json!({ "forks": forks }))
+}
+
 #[tauri::command]
 pub(crate) async fn list_threads(
     workspace_id: String,
@@ -234,14 +859,17 @@ pub(crate) async fn list_threads(
 
     let workspace_path = workspace_entry.path.clone();
     let entries = load_sessions_index(&workspace_entry);
-    let archived_ids = archived_threads_path(&state)
-        .ok()
-        .and_then(|path| read_archived_threads(&path).ok())
-        .and_then(|archived| archived.get(&workspace_id).cloned())
-        .unwrap_or_default();
+    let archived_ids =
+        storage::read_archived_threads(&state.db_path, &workspace_id).unwrap_or_default();
     let archived_set = archived_ids
         .into_iter()
         .collect::<std::collections::HashSet<_>>();
+    let parents: HashMap<String, String> =
+        storage::read_session_forks(&state.db_path, &workspace_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|fork| (fork.session_id, fork.parent_session_id))
+            .collect();
     let mut sorted = entries
         .into_iter()
         .filter(|entry| !archived_set.contains(&entry.session_id))
@@ -260,7 +888,11 @@ pub(crate) async fn list_threads(
         None
     };
 
-    let page_entries = sorted.into_iter().skip(offset).take(limit).collect::<Vec<_>>();
+    let page_entries = sorted
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .collect::<Vec<_>>();
     let mut threads = Vec::new();
     for entry in page_entries {
         let session_id = entry.session_id.clone();
@@ -282,6 +914,7 @@ pub(crate) async fn list_threads(
             "updatedAt": updated_at,
             "cwd": cwd,
             "gitBranch": entry.git_branch,
+            "forkedFrom": parents.get(&session_id),
         }));
         threads.extend(list_subagent_threads(&workspace_entry, &session_id, &cwd));
     }
@@ -296,6 +929,7 @@ pub(crate) async fn list_threads(
 pub(crate) async fn archive_thread(
     workspace_id: String,
     thread_id: String,
+    remove_worktree: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Value, String> {
@@ -304,19 +938,32 @@ pub(crate) async fn archive_thread(
             &*state,
             app,
             "archive_thread",
-            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "removeWorktree": remove_worktree,
+            }),
         )
         .await;
     }
 
-    let path = archived_threads_path(&state)?;
-    let mut archived = read_archived_threads(&path)?;
-    let entry = archived.entry(workspace_id).or_default();
-    if !entry.contains(&thread_id) {
-        entry.push(thread_id);
-        write_archived_threads(&path, &archived)?;
+    storage::archive_thread(&state.db_path, &workspace_id, &thread_id)?;
+
+    // Deleting the session of a worktree agent can take its checkout with it
+    let mut worktree_removed = false;
+    if remove_worktree.unwrap_or(false) {
+        let is_worktree = state
+            .workspaces
+            .lock()
+            .await
+            .get(&workspace_id)
+            .is_some_and(|entry| entry.kind.is_worktree());
+        if is_worktree {
+            remove_worktree_entry(&workspace_id, &state).await?;
+            worktree_removed = true;
+        }
     }
-    Ok(json!({ "ok": true }))
+    Ok(json!({ "ok": true, "worktreeRemoved": worktree_removed }))
 }
 
 #[tauri::command]
@@ -328,7 +975,10 @@ pub(crate) async fn send_user_message(
     effort: Option<String>,
     access_mode: Option<String>,
     images: Option<Vec<String>>,
+    attachments: Option<Vec<Attachment>>,
+    context_pack: Option<String>,
     _collaboration_mode: Option<Value>,
+    user: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Value, String> {
@@ -345,49 +995,466 @@ pub(crate) async fn send_user_message(
                 "effort": effort,
                 "accessMode": access_mode,
                 "images": images,
+                "attachments": attachments,
+                "contextPack": context_pack,
+                "user": user.unwrap_or_else(ownership::local_user),
             }),
         )
         .await;
     }
+    let user = ownership::resolve_user(user);
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
+    // Sessions attached from outside the monitor are observed, never driven.
+    if crate::tail::is_attached(&state, &workspace_id, &thread_id).await {
+        return Err("this session was started outside the monitor and is read-only".to_string());
+    }
+
+    let (root, archived) = state
+        .workspaces
+        .lock()
+        .await
         .get(&workspace_id)
-        .ok_or("workspace not connected")?
-        .clone();
-    drop(sessions);
+        .map(|entry| (PathBuf::from(&entry.path), entry.archived_at.is_some()))
+        .ok_or("workspace not found")?;
+    let mut attached = attachments::from_images(images.unwrap_or_default());
+    attached.extend(attachments.unwrap_or_default());
+    let mut message = attachments::prepare(&text, attached, &root)?;
+    if let Some(name) = context_pack.filter(|name| !name.trim().is_empty()) {
+        let expanded = context_pack::expand_named(&state, &workspace_id, &name).await?;
+        message.text = context_pack::prepend(&expanded, &message.text);
+    }
+    if message.is_empty() {
+        return Err("empty user message".to_string());
+    }
+    let prompt = QueuedPrompt {
+        id: Uuid::new_v4().to_string(),
+        thread_id: thread_id.clone(),
+        prompt: message.text,
+        images: message.images,
+        model: resolve_turn_model(&state, &workspace_id, model).await,
+        access_mode,
+        queued_at: now_ms(),
+    };
+    let event_sink = TauriEventSink::new(app.clone());
 
-    ensure_workspace_thread_watcher(&workspace_id, session.entry.clone(), &state, app.clone()).await;
+    let session = state.sessions.lock().await.get(&workspace_id).cloned();
+    let Some(session) = session else {
+        // Keep the message for when the workspace connects again
+        if archived {
+            return Err("workspace not connected".to_string());
+        }
+        return outbox::park(
+            &event_sink,
+            outbox::pending_message(&workspace_id, prompt, None),
+        )
+        .await;
+    };
 
-    let prompt = build_prompt_with_images(text, images);
-    if prompt.trim().is_empty() {
-        return Err("empty user message".to_string());
+    // Someone else's running or queued turn is theirs until the thread is taken over
+    let busy = session.turn_queue.lock().await.is_busy(&thread_id);
+    {
+        let mut owners = session.owners.lock().await;
+        owners.check(&thread_id, &user, busy)?;
+        owners.claim(&thread_id, &user, now_ms());
     }
 
-    let event_sink = TauriEventSink::new(app.clone());
+    ensure_workspace_thread_watcher(&workspace_id, session.entry.clone(), &state, app.clone())
+        .await;
+
+    // Messages still waiting in the outbox go first
+    if outbox::has_pending(&workspace_id, &thread_id) {
+        let message = outbox::pending_message(&workspace_id, prompt, None);
+        let parked = outbox::park(&event_sink, message).await;
+        outbox::flush(&session, &event_sink, &workspace_id, false).await;
+        return parked;
+    }
+
+    // Queue the prompt if this thread is still busy with an earlier turn
+    let mut queue = session.turn_queue.lock().await;
+    if !queue.try_start(&thread_id) {
+        let prompt_id = prompt.id.clone();
+        let position = queue.enqueue(prompt);
+        drop(queue);
+        emit_turn_queue_updated(&event_sink, &workspace_id, &session).await;
+        ensure_turn_queue_dispatcher(&workspace_id, &session, event_sink).await;
+        return Ok(json!({
+            "result": {
+                "queued": { "id": prompt_id, "threadId": thread_id, "position": position }
+            }
+        }));
+    }
+    drop(queue);
+
+    let turn_id = match start_turn(&workspace_id, &session, &prompt, event_sink.clone()).await {
+        Ok(turn_id) => turn_id,
+        Err(TurnStartError::Refused(message)) => {
+            session.finish_turn(&thread_id).await;
+            return Err(message);
+        }
+        Err(TurnStartError::SessionDown(error)) => {
+            session.finish_turn(&thread_id).await;
+            tracing::warn!("parking message for thread {thread_id}: {error}");
+            let message = outbox::pending_message(&workspace_id, prompt, Some(error));
+            return outbox::park(&event_sink, message).await;
+        }
+    };
+
+    Ok(json!({
+        "result": {
+            "turn": { "id": turn_id, "threadId": thread_id }
+        }
+    }))
+}
+
+/// The model for a turn: the per-turn override if given, otherwise the workspace's
+/// configured model. `None` leaves the choice to the CLI's own settings.
+async fn resolve_turn_model(
+    state: &AppState,
+    workspace_id: &str,
+    requested: Option<String>,
+) -> Option<String> {
+    let requested = requested
+        .map(|model| model.trim().to_string())
+        .filter(|model| !model.is_empty());
+    if requested.is_some() {
+        return requested;
+    }
+    state
+        .workspaces
+        .lock()
+        .await
+        .get(workspace_id)
+        .and_then(|entry| entry.model.clone())
+}
+
+/// Why a turn did not start.
+enum TurnStartError {
+    /// The app is shutting down or the workspace is over its budget.
+    Refused(String),
+    /// The thread's process could not be started or written to; the prompt can wait in
+    /// the outbox until it can.
+    SessionDown(String),
+}
+
+impl TurnStartError {
+    fn into_message(self) -> String {
+        match self {
+            Self::Refused(message) | Self::SessionDown(message) => message,
+        }
+    }
+}
+
+/// Sends a prompt to the thread's persistent process. The caller must have claimed the
+/// thread in the turn queue.
+async fn start_turn(
+    workspace_id: &str,
+    session: &Arc<WorkspaceSession>,
+    prompt: &QueuedPrompt,
+    event_sink: TauriEventSink,
+) -> Result<String, TurnStartError> {
+    let thread_id = prompt.thread_id.as_str();
+    shutdown::ensure_accepting_turns().map_err(TurnStartError::Refused)?;
+    if let Some(state) = event_sink.app_handle().try_state::<AppState>() {
+        budget::ensure_turn_allowed(&state, workspace_id)
+            .await
+            .map_err(TurnStartError::Refused)?;
+    }
 
     // Ensure persistent session exists and get turn_id
     let turn_id = ensure_persistent_session(
-        &workspace_id,
-        &session,
-        &thread_id,
-        model.as_deref(),
-        access_mode.as_deref(),
+        workspace_id,
+        session,
+        thread_id,
+        prompt.model.as_deref(),
+        prompt.access_mode.as_deref(),
         None, // max_thinking_tokens - use default
         event_sink,
-    ).await?;
+    )
+    .await
+    .map_err(TurnStartError::SessionDown)?;
+
+    snapshot_read_only_turn(session, thread_id).await;
 
     // Set the pending turn ID so the reader knows which turn_id to use
-    session.set_pending_turn_id(&thread_id, turn_id.clone()).await;
+    session
+        .set_pending_turn_id(thread_id, turn_id.clone())
+        .await;
+
+    let turn = register_turn_operation(workspace_id, session, thread_id, &turn_id).await;
 
     // Send the user message via stdin
-    session.send_message(&thread_id, &prompt).await?;
+    if let Err(err) = session
+        .send_message_with_images(thread_id, &prompt.prompt, &prompt.images)
+        .await
+    {
+        session.turn_snapshots.lock().await.remove(thread_id);
+        return Err(TurnStartError::SessionDown(err));
+    }
+    metrics::record_turn_started(workspace_id);
+    watch_turn_operation(turn, session, thread_id, &turn_id);
 
-    Ok(json!({
-        "result": {
-            "turn": { "id": turn_id, "threadId": thread_id }
+    Ok(turn_id)
+}
+
+/// A turn registered as an operation under its turn id, so `cancel_operation`
+/// interrupts it the way `turn_interrupt` does. The operation ends with the turn.
+struct TurnOperation {
+    operation: operations::Operation,
+    ended: CancellationToken,
+}
+
+/// Register a turn before its prompt is sent, so that a turn which ends right away
+/// still ends its operation.
+async fn register_turn_operation(
+    workspace_id: &str,
+    session: &WorkspaceSession,
+    thread_id: &str,
+    turn_id: &str,
+) -> Option<TurnOperation> {
+    let operation = match operations::start_turn(turn_id, workspace_id, thread_id) {
+        Ok(operation) => operation,
+        Err(err) => {
+            tracing::warn!("turn {turn_id} cannot be cancelled as an operation: {err}");
+            return None;
         }
-    }))
+    };
+    let ended = session.track_turn_end(thread_id).await;
+    Some(TurnOperation { operation, ended })
+}
+
+/// Interrupt the turn once its operation is cancelled; called after the prompt is
+/// sent, so a cancellation that came in before is acted on right away.
+fn watch_turn_operation(
+    turn: Option<TurnOperation>,
+    session: &Arc<WorkspaceSession>,
+    thread_id: &str,
+    turn_id: &str,
+) {
+    let Some(TurnOperation { operation, ended }) = turn else {
+        return;
+    };
+    let session = Arc::downgrade(session);
+    let thread_id = thread_id.to_string();
+    let turn_id = turn_id.to_string();
+    tokio::spawn(async move {
+        let finished = operation
+            .token()
+            .run_until_cancelled(ended.cancelled())
+            .await;
+        drop(operation);
+        // Shutdown stops the processes itself
+        if finished.is_some() || operations::root().is_cancelled() {
+            return;
+        }
+        let Some(session) = session.upgrade() else {
+            return;
+        };
+        match session.interrupt_turn(&thread_id, &turn_id).await {
+            // An acknowledged interrupt ends the turn through the CLI's `result` event
+            Ok(InterruptOutcome::Acknowledged | InterruptOutcome::NotRunning) => {}
+            Ok(InterruptOutcome::Terminated) => session.finish_turn(&thread_id).await,
+            Err(err) => tracing::warn!("failed to cancel turn {turn_id}: {err}"),
+        }
+    });
+}
+
+/// Append prompts to the workspace's turn queue in order and make sure they get sent,
+/// including to threads that are idle.
+pub(crate) async fn enqueue_prompts(
+    workspace_id: &str,
+    session: &Arc<WorkspaceSession>,
+    prompts: Vec<QueuedPrompt>,
+    event_sink: TauriEventSink,
+) {
+    {
+        let mut queue = session.turn_queue.lock().await;
+        for prompt in prompts {
+            queue.enqueue(prompt);
+        }
+    }
+    session.turn_queue_notify.notify_one();
+    emit_turn_queue_updated(&event_sink, workspace_id, session).await;
+    ensure_turn_queue_dispatcher(workspace_id, session, event_sink).await;
+}
+
+/// Starts the workspace's queue dispatcher unless one is already running.
+async fn ensure_turn_queue_dispatcher(
+    workspace_id: &str,
+    session: &Arc<WorkspaceSession>,
+    event_sink: TauriEventSink,
+) {
+    if !session.turn_queue.lock().await.claim_dispatcher() {
+        return;
+    }
+    tokio::spawn(run_turn_queue(
+        workspace_id.to_string(),
+        Arc::clone(session),
+        event_sink,
+    ));
+}
+
+/// Background task that starts queued prompts as their threads become idle. Exits once
+/// the queue is empty; the next enqueue starts a new one.
+async fn run_turn_queue(
+    workspace_id: String,
+    session: Arc<WorkspaceSession>,
+    event_sink: TauriEventSink,
+) {
+    loop {
+        let next = {
+            let mut queue = session.turn_queue.lock().await;
+            match queue.take_ready() {
+                Some(prompt) => Some(prompt),
+                None if queue.release_dispatcher_if_idle() => return,
+                None => None,
+            }
+        };
+        let Some(prompt) = next else {
+            session.turn_queue_notify.notified().await;
+            continue;
+        };
+        emit_turn_queue_updated(&event_sink, &workspace_id, &session).await;
+        let result = start_turn(&workspace_id, &session, &prompt, event_sink.clone()).await;
+        let from_outbox = session.outbox_claims.lock().await.contains(&prompt.id);
+        let err = match result {
+            Ok(_) => {
+                if from_outbox {
+                    outbox::delivered(&session, &event_sink, &workspace_id, &prompt.id).await;
+                }
+                continue;
+            }
+            Err(err) => err,
+        };
+        session.finish_turn(&prompt.thread_id).await;
+        match err {
+            err if from_outbox => {
+                let error = err.into_message();
+                outbox::failed(&session, &event_sink, &workspace_id, &prompt.id, error).await;
+            }
+            TurnStartError::SessionDown(error) => {
+                tracing::warn!(
+                    "parking queued message for thread {}: {error}",
+                    prompt.thread_id
+                );
+                let message = outbox::pending_message(&workspace_id, prompt, Some(error));
+                if let Err(err) = outbox::park(&event_sink, message).await {
+                    tracing::warn!("failed to park queued message: {err}");
+                }
+            }
+            TurnStartError::Refused(message) => emit_event(
+                &event_sink,
+                &workspace_id,
+                "error",
+                json!({
+                    "threadId": prompt.thread_id,
+                    "error": { "message": format!("Failed to send queued message: {message}") },
+                    "willRetry": false,
+                }),
+            ),
+        }
+    }
+}
+
+pub(crate) async fn emit_turn_queue_updated(
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    session: &WorkspaceSession,
+) {
+    let queue = session.turn_queue.lock().await.snapshot();
+    event_sink.emit_turn_queue_updated(TurnQueueUpdated {
+        workspace_id: workspace_id.to_string(),
+        queue,
+    });
+}
+
+async fn workspace_session(
+    state: &AppState,
+    workspace_id: &str,
+) -> Result<Arc<WorkspaceSession>, String> {
+    state
+        .sessions
+        .lock()
+        .await
+        .get(workspace_id)
+        .cloned()
+        .ok_or_else(|| "workspace not connected".to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn list_turn_queue(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_turn_queue",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    let queue = session.turn_queue.lock().await.snapshot();
+    Ok(json!({ "queue": queue }))
+}
+
+#[tauri::command]
+pub(crate) async fn cancel_queued_prompt(
+    workspace_id: String,
+    prompt_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "cancel_queued_prompt",
+            json!({ "workspaceId": workspace_id, "promptId": prompt_id }),
+        )
+        .await;
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    session
+        .turn_queue
+        .lock()
+        .await
+        .cancel(&prompt_id)
+        .ok_or("queued prompt not found")?;
+    emit_turn_queue_updated(&TauriEventSink::new(app), &workspace_id, &session).await;
+    Ok(json!({ "ok": true }))
+}
+
+#[tauri::command]
+pub(crate) async fn reorder_queued_prompt(
+    workspace_id: String,
+    prompt_id: String,
+    position: u32,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "reorder_queued_prompt",
+            json!({ "workspaceId": workspace_id, "promptId": prompt_id, "position": position }),
+        )
+        .await;
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    session
+        .turn_queue
+        .lock()
+        .await
+        .move_to(&prompt_id, position as usize)?;
+    emit_turn_queue_updated(&TauriEventSink::new(app), &workspace_id, &session).await;
+    Ok(json!({ "ok": true }))
 }
 
 #[tauri::command]
@@ -413,6 +1480,7 @@ pub(crate) async fn turn_interrupt(
     workspace_id: String,
     thread_id: String,
     turn_id: String,
+    user: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Value, String> {
@@ -421,16 +1489,27 @@ pub(crate) async fn turn_interrupt(
             &*state,
             app,
             "turn_interrupt",
-            json!({ "workspaceId": workspace_id, "threadId": thread_id, "turnId": turn_id }),
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "turnId": turn_id,
+                "user": user.unwrap_or_else(ownership::local_user),
+            }),
         )
         .await;
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
-    session.interrupt_turn(&thread_id, &turn_id).await?;
+    let session = workspace_session(&state, &workspace_id).await?;
+    session
+        .owners
+        .lock()
+        .await
+        .check(&thread_id, &ownership::resolve_user(user), true)?;
+    let outcome = session.interrupt_turn(&thread_id, &turn_id).await?;
+    // An acknowledged interrupt ends the turn through the CLI's `result` event
+    if outcome == InterruptOutcome::Terminated {
+        session.finish_turn(&thread_id).await;
+    }
     Ok(json!({ "ok": true }))
 }
 
@@ -477,13 +1556,24 @@ pub(crate) async fn start_review(
         None, // access_mode - use default
         None, // max_thinking_tokens - use default
         event_sink,
-    ).await?;
+    )
+    .await?;
+
+    snapshot_read_only_turn(&session, &thread_id).await;
 
     // Set the pending turn ID so the reader knows which turn_id to use
-    session.set_pending_turn_id(&thread_id, turn_id.clone()).await;
+    session
+        .set_pending_turn_id(&thread_id, turn_id.clone())
+        .await;
+
+    let turn = register_turn_operation(&workspace_id, &session, &thread_id, &turn_id).await;
 
     // Send the review prompt via stdin
-    session.send_message(&thread_id, &prompt).await?;
+    if let Err(err) = session.send_message(&thread_id, &prompt).await {
+        session.turn_snapshots.lock().await.remove(&thread_id);
+        return Err(err);
+    }
+    watch_turn_operation(turn, &session, &thread_id, &turn_id);
 
     Ok(json!({
         "result": {
@@ -508,7 +1598,7 @@ pub(crate) async fn model_list(
         .await;
     }
 
-    let data = vec![
+    let mut data = vec![
         json!({
             "id": "claude-opus-4-5-20251101",
             "model": "claude-opus-4-5-20251101",
@@ -527,8 +1617,39 @@ pub(crate) async fn model_list(
             "defaultReasoningEffort": "",
             "isDefault": false,
         }),
+        json!({
+            "id": "claude-haiku-4-5-20251001",
+            "model": "claude-haiku-4-5-20251001",
+            "displayName": "Claude Haiku 4.5",
+            "description": "Fastest model for quick tasks.",
+            "supportedReasoningEfforts": [],
+            "defaultReasoningEffort": "",
+            "isDefault": false,
+        }),
     ];
 
+    // A workspace model outside the built-in list (e.g. an alias like `opus`) is still
+    // offered so the composer can select it
+    let workspace_model = state
+        .workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .and_then(|entry| entry.model.clone());
+    if let Some(model) = workspace_model {
+        if !data.iter().any(|item| item["model"] == model.as_str()) {
+            data.push(json!({
+                "id": model,
+                "model": model,
+                "displayName": model,
+                "description": "Workspace model.",
+                "supportedReasoningEfforts": [],
+                "defaultReasoningEffort": "",
+                "isDefault": false,
+            }));
+        }
+    }
+
     Ok(json!({ "data": data }))
 }
 
@@ -554,7 +1675,9 @@ pub(crate) async fn global_rate_limits() -> Result<Value, String> {
         let w = usage.get(key)?;
         let pct = w.get("utilization")?.as_f64()?;
         let resets = w.get("resets_at").and_then(|v| v.as_str()).and_then(|s| {
-            DateTime::parse_from_rfc3339(s).ok().map(|t| t.timestamp_millis())
+            DateTime::parse_from_rfc3339(s)
+                .ok()
+                .map(|t| t.timestamp_millis())
         });
         Some(json!({ "usedPercent": pct, "resetsAt": resets }))
     };
@@ -571,7 +1694,14 @@ pub(crate) async fn global_rate_limits() -> Result<Value, String> {
 async fn read_oauth_token() -> Option<String> {
     let user = env::var("USER").unwrap_or_default();
     let output = Command::new("security")
-        .args(["find-generic-password", "-a", &user, "-s", "Claude Code-credentials", "-w"])
+        .args([
+            "find-generic-password",
+            "-a",
+            &user,
+            "-s",
+            "Claude Code-credentials",
+            "-w",
+        ])
         .output()
         .await
         .ok()?;
@@ -585,8 +1715,9 @@ async fn read_oauth_token() -> Option<String> {
 
 #[cfg(not(target_os = "macos"))]
 async fn read_oauth_token() -> Option<String> {
-    let home = env::var("HOME").or_else(|_| env::var("USERPROFILE")).ok()?;
-    let path = PathBuf::from(home).join(".claude").join(".credentials.json");
+    let path = crate::claude_home::resolve_home_dir()?
+        .join(".claude")
+        .join(".credentials.json");
     let raw = fs::read_to_string(&path).ok()?;
     let creds: ClaudeCredentials = serde_json::from_str(&raw).ok()?;
     Some(creds.claude_ai_oauth?.access_token)
@@ -612,11 +1743,11 @@ pub(crate) async fn skills_list(
 }
 
 #[tauri::command]
-pub(crate) async fn respond_to_server_request(
+pub(crate) async fn respond_to_permission_request(
     workspace_id: String,
     thread_id: String,
-    tool_use_id: String,
-    result: Value,
+    request_id: String,
+    decision: Value,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), String> {
@@ -624,52 +1755,306 @@ pub(crate) async fn respond_to_server_request(
         remote_backend::call_remote(
             &*state,
             app,
-            "respond_to_server_request",
-            json!({ "workspaceId": workspace_id, "threadId": thread_id, "toolUseId": tool_use_id, "result": result }),
+            "respond_to_permission_request",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "requestId": request_id,
+                "decision": decision,
+            }),
         )
         .await?;
         return Ok(());
     }
 
-    let sessions = state.sessions.lock().await;
-    let session = sessions
-        .get(&workspace_id)
-        .ok_or("workspace not connected")?;
-
-    session.send_response(&thread_id, tool_use_id, result).await
+    let decision: PermissionDecision =
+        serde_json::from_value(decision).map_err(|err| format!("Invalid decision: {err}"))?;
+    let session = workspace_session(&state, &workspace_id).await?;
+    session
+        .respond_to_permission(&thread_id, &request_id, decision)
+        .await
 }
 
-/// Gets the diff content for commit message generation
+/// Plans waiting for review in a workspace's threads, oldest first.
 #[tauri::command]
-pub(crate) async fn get_commit_message_prompt(
+pub(crate) async fn list_pending_plans(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    let diff = crate::git::get_workspace_diff(&workspace_id, &state).await?;
-
-    if diff.trim().is_empty() {
-        return Err("No changes to generate commit message for".to_string());
+    app: AppHandle,
+) -> Result<Vec<PendingPlan>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_pending_plans",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    let prompt = format!(
-        "Generate a concise git commit message for the following changes. \
-Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
-Focus on the 'why' rather than the 'what'. Keep the summary line under 72 characters. \
-Only output the commit message, nothing else.\n\n\
-Changes:\n{diff}"
-    );
-
-    Ok(prompt)
+    let session = workspace_session(&state, &workspace_id).await?;
+    let plans = session.plans.lock().await.list();
+    Ok(plans)
 }
 
+/// Permission requests, questions and plans waiting for the user, oldest first.
 #[tauri::command]
-pub(crate) async fn remember_approval_rule(
+pub(crate) async fn list_pending_interactions(
     workspace_id: String,
-    rule: String,
     state: State<'_, AppState>,
-) -> Result<Value, String> {
-    let rule = rule.trim();
-    if rule.is_empty() {
+    app: AppHandle,
+) -> Result<Vec<PendingInteraction>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_pending_interactions",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    let interactions = session.interactions.lock().await.list();
+    Ok(interactions)
+}
+
+/// Who drives each of a workspace's threads.
+#[tauri::command]
+pub(crate) async fn list_thread_owners(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ThreadOwner>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_thread_owners",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    let owners = session.owners.lock().await.snapshot();
+    Ok(owners)
+}
+
+/// Take a thread over from whoever drives it, so `user` can message or interrupt its
+/// running turn. The previous owner hears about it through `thread/ownerChanged`.
+#[tauri::command]
+pub(crate) async fn take_over_thread(
+    workspace_id: String,
+    thread_id: String,
+    user: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadOwner, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "take_over_thread",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "user": user.unwrap_or_else(ownership::local_user),
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let user = ownership::resolve_user(user);
+    let session = workspace_session(&state, &workspace_id).await?;
+    let (owner, previous) = {
+        let mut owners = session.owners.lock().await;
+        let previous = owners.take_over(&thread_id, &user, now_ms());
+        let owner = owners.get(&thread_id).cloned().ok_or("thread not found")?;
+        (owner, previous)
+    };
+    if let Some(previous) = &previous {
+        tracing::info!("{user} took thread {thread_id} over from {previous}");
+    }
+    emit_event(
+        &TauriEventSink::new(app),
+        &workspace_id,
+        "thread/ownerChanged",
+        json!({ "threadId": thread_id, "user": owner.user, "previousUser": previous }),
+    );
+    Ok(owner)
+}
+
+/// Approve a thread's plan so Claude carries it out. `mode` optionally switches the
+/// thread's permission mode for the execution, e.g. to `acceptEdits`.
+#[tauri::command]
+pub(crate) async fn approve_plan(
+    workspace_id: String,
+    thread_id: String,
+    mode: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "approve_plan",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id, "mode": mode }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    let plan = session.approve_plan(&thread_id, mode.as_deref()).await?;
+    emit_plan_resolved(&TauriEventSink::new(app), &workspace_id, &plan, true);
+    Ok(())
+}
+
+/// Reject a thread's plan; Claude keeps planning with `feedback` in mind.
+#[tauri::command]
+pub(crate) async fn reject_plan(
+    workspace_id: String,
+    thread_id: String,
+    feedback: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "reject_plan",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id, "feedback": feedback }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    let plan = session.reject_plan(&thread_id, &feedback).await?;
+    emit_plan_resolved(&TauriEventSink::new(app), &workspace_id, &plan, false);
+    Ok(())
+}
+
+/// Switch a workspace's permission preset, or clear it with `None`. The preset is saved
+/// with the workspace and applies at once to the threads it connects; see
+/// [`WorkspaceSession::apply_permission_preset`].
+#[tauri::command]
+pub(crate) async fn set_permission_preset(
+    workspace_id: String,
+    preset: Option<PermissionPreset>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "set_permission_preset",
+            json!({ "workspaceId": workspace_id, "preset": preset }),
+        )
+        .await;
+    }
+
+    let list: Vec<WorkspaceEntry> = {
+        let mut workspaces = state.workspaces.lock().await;
+        let entry = workspaces
+            .get_mut(&workspace_id)
+            .ok_or("workspace not found")?;
+        entry.settings.permission_preset = preset;
+        workspaces.values().cloned().collect()
+    };
+    storage::write_workspaces(&state.db_path, &list)?;
+
+    let session = state.sessions.lock().await.get(&workspace_id).cloned();
+    let (switched, stopped) = match session {
+        Some(session) => session.apply_permission_preset(preset).await,
+        None => (Vec::new(), Vec::new()),
+    };
+    Ok(json!({
+        "preset": preset,
+        "switchedThreads": switched,
+        "stoppedThreads": stopped,
+    }))
+}
+
+#[tauri::command]
+pub(crate) async fn respond_to_server_request(
+    workspace_id: String,
+    thread_id: String,
+    tool_use_id: String,
+    result: Value,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "respond_to_server_request",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id, "toolUseId": tool_use_id, "result": result }),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let sessions = state.sessions.lock().await;
+    let session = sessions
+        .get(&workspace_id)
+        .ok_or("workspace not connected")?;
+
+    // AskUserQuestion answers are checked against the questions before they are sent
+    let asked = session
+        .questions
+        .lock()
+        .await
+        .get(&thread_id, &tool_use_id)
+        .is_some();
+    if asked {
+        return session
+            .answer_questions(&thread_id, &tool_use_id, result)
+            .await;
+    }
+    session.send_response(&thread_id, tool_use_id, result).await
+}
+
+/// Gets the diff content for commit message generation
+#[tauri::command]
+pub(crate) async fn get_commit_message_prompt(
+    workspace_id: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let diff = crate::git::get_workspace_diff(&workspace_id, &state).await?;
+
+    if diff.trim().is_empty() {
+        return Err("No changes to generate commit message for".to_string());
+    }
+
+    let prompt = format!(
+        "Generate a concise git commit message for the following changes. \
+Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
+Focus on the 'why' rather than the 'what'. Keep the summary line under 72 characters. \
+Only output the commit message, nothing else.\n\n\
+Changes:\n{diff}"
+    );
+
+    Ok(prompt)
+}
+
+#[tauri::command]
+pub(crate) async fn remember_approval_rule(
+    workspace_id: String,
+    rule: String,
+    state: State<'_, AppState>,
+) -> Result<Value, String> {
+    let rule = rule.trim();
+    if rule.is_empty() {
         return Err("empty rule".to_string());
     }
 
@@ -688,7 +2073,7 @@ pub(crate) async fn remember_approval_rule(
     };
 
     let settings_path = resolve_permissions_path(&entry, parent_path.as_deref())?;
-    let mut settings = read_settings_json(&settings_path)?;
+    let mut settings = read_settings_file(&settings_path)?;
     let permissions = settings
         .entry("permissions")
         .or_insert_with(|| json!({}))
@@ -702,7 +2087,7 @@ pub(crate) async fn remember_approval_rule(
     if !allow.iter().any(|item| item.as_str() == Some(rule)) {
         allow.push(Value::String(rule.to_string()));
     }
-    write_settings_json(&settings_path, &settings)?;
+    write_settings_file(&settings_path, &settings)?;
 
     Ok(json!({
         "ok": true,
@@ -745,11 +2130,13 @@ Changes:\n{diff}"
     };
 
     let response = run_claude_prompt_once(
-        &entry.path,
+        &entry,
         default_bin,
         prompt,
         Some("dontAsk".to_string()),
         Some("haiku".to_string()),
+        &[],
+        Duration::from_secs(60),
     )
     .await?;
 
@@ -791,11 +2178,13 @@ User's task description:\n{prompt}"
     );
 
     let response = run_claude_prompt_once(
-        &entry.path,
+        &entry,
         default_bin,
         system_prompt,
         Some("dontAsk".to_string()),
         Some("haiku".to_string()),
+        &[],
+        Duration::from_secs(60),
     )
     .await?;
 
@@ -824,37 +2213,76 @@ User's task description:\n{prompt}"
     }
 }
 
-fn build_prompt_with_images(text: String, images: Option<Vec<String>>) -> String {
-    let mut prompt = text.trim().to_string();
-    if let Some(images) = images {
-        let mut image_lines = Vec::new();
-        for image in images {
-            let trimmed = image.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-            image_lines.push(format!("[image] {trimmed}"));
-        }
-        if !image_lines.is_empty() {
-            if !prompt.is_empty() {
-                prompt.push_str("\n\n");
-            }
-            prompt.push_str("Attached images:\n");
-            prompt.push_str(&image_lines.join("\n"));
-        }
-    }
-    prompt
+/// Permission mode for `claude -p` runs nobody is watching: tools the settings allow
+/// still run, anything that would prompt is denied instead of hanging.
+pub(crate) const UNATTENDED_PERMISSION_MODE: &str = "dontAsk";
+
+/// The CLI a workspace runs: its own override, else the app-wide setting.
+pub(crate) async fn workspace_claude_bin(
+    state: &AppState,
+    entry: &WorkspaceEntry,
+) -> Option<String> {
+    let default_bin = state.app_settings.lock().await.claude_bin.clone();
+    entry
+        .claude_bin
+        .clone()
+        .filter(|value| !value.trim().is_empty())
+        .or(default_bin)
 }
 
-async fn run_claude_prompt_once(
-    cwd: &str,
+/// Arguments for a workspace's CLI flags in unattended runs, which pick their own
+/// permission mode.
+pub(crate) fn workspace_cli_args(entry: &WorkspaceEntry) -> Vec<String> {
+    let mut flags = entry.settings.cli_flags.clone().unwrap_or_default();
+    guardrails::apply_guardrails(&entry.settings, &mut flags);
+    cli_flag_args(&flags)
+}
+
+/// Run one prompt through `claude -p` without keeping a session, returning the final
+/// assistant message. It runs in the workspace's folder with its credentials; `cli_args`
+/// are appended, e.g. [`workspace_cli_args`].
+pub(crate) async fn run_claude_prompt_once(
+    entry: &WorkspaceEntry,
     claude_bin: Option<String>,
     prompt: String,
     permission_mode: Option<String>,
     model: Option<String>,
+    cli_args: &[String],
+    time_limit: Duration,
 ) -> Result<String, String> {
+    run_claude_prompt_once_with_result(
+        entry,
+        claude_bin,
+        prompt,
+        permission_mode,
+        model,
+        cli_args,
+        time_limit,
+    )
+    .await
+    .map(|output| output.message)
+}
+
+/// What a one-shot `claude -p` run produced.
+pub(crate) struct PromptOnceOutput {
+    pub(crate) message: String,
+    /// The closing `result` event, with cost and usage, when the CLI sent one.
+    pub(crate) result: Option<ResultEvent>,
+}
+
+pub(crate) async fn run_claude_prompt_once_with_result(
+    entry: &WorkspaceEntry,
+    claude_bin: Option<String>,
+    prompt: String,
+    permission_mode: Option<String>,
+    model: Option<String>,
+    cli_args: &[String],
+    time_limit: Duration,
+) -> Result<PromptOnceOutput, String> {
     let mut command = build_claude_command_with_bin(claude_bin);
-    command.current_dir(cwd);
+    command.current_dir(&entry.path);
+    credentials::apply_provider_env(entry, &mut command).await?;
+    proxy::apply(&mut command, entry.settings.proxy.as_ref());
     command.arg("-p").arg(prompt);
     command.arg("--output-format").arg("stream-json");
     command.arg("--verbose");
@@ -865,10 +2293,12 @@ async fn run_claude_prompt_once(
     if let Some(m) = model {
         command.arg("--model").arg(m);
     }
+    command.args(cli_args);
     command.stdout(std::process::Stdio::piped());
     command.stderr(std::process::Stdio::piped());
+    command.kill_on_drop(true);
 
-    let output = timeout(Duration::from_secs(60), command.output())
+    let output = timeout(time_limit, command.output())
         .await
         .map_err(|_| "Claude CLI timed out".to_string())?
         .map_err(|err| err.to_string())?;
@@ -881,37 +2311,593 @@ async fn run_claude_prompt_once(
         });
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut message = String::new();
-    for line in stdout.lines() {
-        if let Ok(value) = serde_json::from_str::<Value>(line) {
-            if value.get("type").and_then(|v| v.as_str()) == Some("assistant") {
-                if let Some(text) = value
-                    .get("message")
-                    .and_then(|m| m.get("content"))
-                    .and_then(|content| content.as_array())
-                    .map(|content| extract_text_from_content(content))
-                {
-                    if !text.is_empty() {
-                        message = text;
-                    }
-                }
-            }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut message = String::new();
+    let mut result = None;
+    for line in stdout.lines() {
+        match parse_event_line(line) {
+            Some(ClaudeEvent::Assistant(event)) => {
+                let text = event.message.text();
+                if !text.is_empty() {
+                    message = text;
+                }
+            }
+            Some(ClaudeEvent::Result(event)) => result = Some(event),
+            _ => {}
+        }
+    }
+    Ok(PromptOnceOutput {
+        message: message.trim().to_string(),
+        result,
+    })
+}
+
+/// Reattach a workspace to a previous conversation so it can continue with full context.
+///
+/// With a `session_id` this behaves like `claude --resume <id>`; without one the most
+/// recently updated transcript is picked, matching `claude --continue`.
+#[tauri::command]
+pub(crate) async fn resume_session(
+    workspace_id: String,
+    session_id: Option<String>,
+    model: Option<String>,
+    access_mode: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "resume_session",
+            json!({
+                "workspaceId": workspace_id,
+                "sessionId": session_id,
+                "model": model,
+                "accessMode": access_mode,
+            }),
+        )
+        .await;
+    }
+
+    let session = {
+        let sessions = state.sessions.lock().await;
+        sessions
+            .get(&workspace_id)
+            .ok_or("workspace not connected")?
+            .clone()
+    };
+    let session_id = match session_id.filter(|value| !value.trim().is_empty()) {
+        Some(session_id) => session_id.trim().to_string(),
+        None => {
+            let entry = session.entry.clone();
+            tokio::task::spawn_blocking(move || {
+                resolve_project_dir(&entry)
+                    .and_then(|dir| list_sessions(&dir).into_iter().next())
+                    .map(|summary| summary.session_id)
+            })
+            .await
+            .map_err(|err| err.to_string())?
+            .ok_or("no previous session to continue")?
+        }
+    };
+
+    let model = resolve_turn_model(&state, &workspace_id, model).await;
+    let event_sink = TauriEventSink::new(app);
+    let pid = {
+        let init_lock = session.session_init_lock(&session_id).await;
+        let _init_guard = init_lock.lock().await;
+        let readers = session
+            .resume_session(&session_id, model.as_deref(), access_mode.as_deref(), None)
+            .await?;
+        let (_, pid) = attach_persistent_readers(
+            &workspace_id,
+            &session,
+            &session_id,
+            readers,
+            event_sink.clone(),
+        );
+        pid
+    };
+    if let Some(pid) = pid {
+        tokio::spawn(supervise_persistent_session(
+            workspace_id.clone(),
+            session_id.clone(),
+            pid,
+            Arc::clone(&session),
+            event_sink,
+        ));
+    }
+
+    storage::remember_thread_session(
+        &workspace_id,
+        &session_id,
+        &session_id,
+        model,
+        ThreadSessionStatus::Idle,
+    );
+
+    let entry = session.entry.clone();
+    let thread_id = session_id.clone();
+    let thread = tokio::task::spawn_blocking(move || build_thread_from_session(&entry, &thread_id))
+        .await
+        .map_err(|err| err.to_string())??;
+
+    Ok(json!({ "threadId": session_id, "thread": thread }))
+}
+
+/// Threads of a workspace with the Claude session behind each and its last known state,
+/// most recently active first. Survives restarts, unlike the in-memory session map.
+#[tauri::command]
+pub(crate) async fn list_thread_sessions(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_thread_sessions",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+    let path = state.db_path.clone();
+    let sessions =
+        tokio::task::spawn_blocking(move || storage::read_thread_sessions(&path, &workspace_id))
+            .await
+            .map_err(|err| err.to_string())??;
+    serde_json::to_value(sessions).map_err(|err| err.to_string())
+}
+
+/// The tool calls a finished turn made, with durations and truncated output, or `null`
+/// when the turn was never recorded.
+#[tauri::command]
+pub(crate) async fn get_turn_timeline(
+    workspace_id: String,
+    turn_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "get_turn_timeline",
+            json!({ "workspaceId": workspace_id, "turnId": turn_id }),
+        )
+        .await;
+    }
+    let path = state.db_path.clone();
+    let timeline = tokio::task::spawn_blocking(move || {
+        storage::read_turn_timeline(&path, &workspace_id, &turn_id)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    serde_json::to_value(timeline).map_err(|err| err.to_string())
+}
+
+/// The last `lines` lines (default 200) the workspace's CLI processes wrote to stdout
+/// and stderr, oldest first. A workspace without a session returns what was spilled to
+/// disk.
+#[tauri::command]
+pub(crate) async fn get_session_logs(
+    workspace_id: String,
+    lines: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "get_session_logs",
+            json!({ "workspaceId": workspace_id, "lines": lines }),
+        )
+        .await;
+    }
+    let count = lines
+        .unwrap_or(DEFAULT_SESSION_LOG_LINES)
+        .min(MAX_SESSION_LOG_LINES);
+    let session = state.sessions.lock().await.get(&workspace_id).cloned();
+    let logs = match session {
+        Some(session) => session.logs.lock().await.tail(count),
+        None => session_logs::workspace_log_file(&workspace_id)
+            .map(|path| session_logs::read_log_file(&path, count))
+            .unwrap_or_default(),
+    };
+    serde_json::to_value(logs).map_err(|err| err.to_string())
+}
+
+/// The latest CPU and memory sample of each of a workspace's thread processes, counting
+/// the processes they started.
+#[tauri::command]
+pub(crate) async fn get_session_resources(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "get_session_resources",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+    let session = state.sessions.lock().await.get(&workspace_id).cloned();
+    let stats = match session {
+        Some(session) => session.resources.lock().await.latest(),
+        None => Vec::new(),
+    };
+    serde_json::to_value(stats).map_err(|err| err.to_string())
+}
+
+/// Compactions of a workspace's threads, or of one thread, newest first.
+#[tauri::command]
+pub(crate) async fn list_compactions(
+    workspace_id: String,
+    thread_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_compactions",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await;
+    }
+    let path = state.db_path.clone();
+    let compactions = tokio::task::spawn_blocking(move || {
+        storage::read_compactions(&path, &workspace_id, thread_id.as_deref())
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    serde_json::to_value(compactions).map_err(|err| err.to_string())
+}
+
+/// What a finished turn changed in its workspace, or `null` when no diff was recorded
+/// (the workspace is not a git repository, or the turn is too old).
+#[tauri::command]
+pub(crate) async fn get_turn_diff(
+    workspace_id: String,
+    turn_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "get_turn_diff",
+            json!({ "workspaceId": workspace_id, "turnId": turn_id }),
+        )
+        .await;
+    }
+    let path = state.db_path.clone();
+    let diff = tokio::task::spawn_blocking(move || {
+        storage::read_turn_diff(&path, &workspace_id, &turn_id)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    serde_json::to_value(diff).map_err(|err| err.to_string())
+}
+
+/// The `Edit`, `MultiEdit` and `Write` calls of a finished turn as hunks, or `null`
+/// when the turn edited no files.
+#[tauri::command]
+pub(crate) async fn get_turn_edits(
+    workspace_id: String,
+    turn_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "get_turn_edits",
+            json!({ "workspaceId": workspace_id, "turnId": turn_id }),
+        )
+        .await;
+    }
+    let path = state.db_path.clone();
+    let edits = tokio::task::spawn_blocking(move || {
+        storage::read_turn_edits(&path, &workspace_id, &turn_id)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    serde_json::to_value(edits).map_err(|err| err.to_string())
+}
+
+/// A workspace's newest Bash policy decisions, newest first.
+#[tauri::command]
+pub(crate) async fn list_bash_decisions(
+    workspace_id: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<BashDecision>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_bash_decisions",
+            json!({ "workspaceId": workspace_id, "limit": limit }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let limit = limit
+        .unwrap_or(DEFAULT_BASH_DECISIONS)
+        .clamp(1, MAX_BASH_DECISIONS);
+    let path = state.db_path.clone();
+    tokio::task::spawn_blocking(move || storage::read_bash_decisions(&path, &workspace_id, limit))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// Health of a thread's persistent process, or of every live process in the workspace
+/// when `thread_id` is omitted.
+#[tauri::command]
+pub(crate) async fn session_health(
+    workspace_id: String,
+    thread_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "session_health",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await;
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    let reports = check_session_health(&session, thread_id).await;
+    for report in &reports {
+        session.record_health(report).await;
+    }
+    serde_json::to_value(reports).map_err(|err| err.to_string())
+}
+
+/// Stop (or continue) a workspace's idle CLI processes for focus mode, reporting the
+/// threads that changed as `session/suspended` or `session/resumed` and their new
+/// health as `session/health`.
+async fn set_workspace_suspended(
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    session: &WorkspaceSession,
+    suspend: bool,
+) -> Result<SuspendOutcome, String> {
+    let outcome = if suspend {
+        session.suspend_sessions().await
+    } else {
+        session.resume_sessions().await
+    };
+    let (method, changed) = if suspend {
+        ("session/suspended", &outcome.suspended)
+    } else {
+        ("session/resumed", &outcome.resumed)
+    };
+    if changed.is_empty() {
+        return Ok(outcome);
+    }
+    emit_event(
+        event_sink,
+        workspace_id,
+        method,
+        json!({ "threadIds": changed }),
+    );
+    for thread_id in changed {
+        let report = session.check_health(thread_id).await;
+        if session.record_health(&report).await {
+            emit_event(
+                event_sink,
+                workspace_id,
+                "session/health",
+                json!({ "threadId": report.thread_id, "health": report }),
+            );
+        }
+    }
+    Ok(outcome)
+}
+
+/// Stop the workspace's persistent CLI processes with SIGSTOP so they stop using CPU
+/// while their sessions stay intact. Threads with a turn running or queued are skipped;
+/// a stopped thread continues when it is next sent a message.
+#[tauri::command]
+pub(crate) async fn suspend_workspace_sessions(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<SuspendOutcome, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "suspend_workspace_sessions",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    let event_sink = TauriEventSink::new(app);
+    set_workspace_suspended(&event_sink, &workspace_id, &session, true).await
+}
+
+/// Continue the workspace's processes stopped by `suspend_workspace_sessions`.
+#[tauri::command]
+pub(crate) async fn resume_workspace_sessions(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<SuspendOutcome, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "resume_workspace_sessions",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    let event_sink = TauriEventSink::new(app);
+    set_workspace_suspended(&event_sink, &workspace_id, &session, false).await
+}
+
+/// Focus mode: resume the focused workspace and suspend every other connected one.
+/// Without a workspace every connected workspace is resumed.
+#[tauri::command]
+pub(crate) async fn focus_workspace(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "focus_workspace",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let event_sink = TauriEventSink::new(app);
+    let mut sessions = connected_sessions(&state).await;
+    sessions.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut workspaces = Vec::new();
+    for (id, session) in sessions {
+        let suspend = workspace_id.as_ref().is_some_and(|focused| *focused != id);
+        let outcome = set_workspace_suspended(&event_sink, &id, &session, suspend).await?;
+        workspaces.push(json!({
+            "workspaceId": id,
+            "suspended": outcome.suspended,
+            "skipped": outcome.skipped,
+            "resumed": outcome.resumed,
+            "failed": outcome.failed,
+        }));
+    }
+    Ok(json!({ "workspaces": workspaces }))
+}
+
+/// The workspace's thread registry: every thread with a persistent process or a turn
+/// running or queued, with what its turns have cost so far.
+#[tauri::command]
+pub(crate) async fn list_workspace_threads(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_workspace_threads",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    let mut threads = session.thread_processes().await;
+    let mut totals = usage::session_totals(&workspace_id);
+    for thread in &mut threads {
+        thread.usage = totals.remove(&thread.thread_id).unwrap_or_default();
+    }
+    Ok(json!({ "threads": threads }))
+}
+
+/// Rate limit cooldown and the last quota estimate for a workspace.
+#[tauri::command]
+pub(crate) async fn get_rate_limit_status(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "get_rate_limit_status",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let session = workspace_session(&state, &workspace_id).await?;
+    let status = session.rate_limits.lock().await.status(now_ms());
+    serde_json::to_value(status).map_err(|err| err.to_string())
+}
+
+/// Feed quota headers and rate limit errors from a stream event into the workspace's
+/// tracker, and warn the UI with a suggested cooldown instead of a bare failure.
+async fn track_rate_limits(
+    session: &WorkspaceSession,
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    thread_id: &str,
+    value: &Value,
+) {
+    let Some(signal) = rate_limits::detect(value) else {
+        if let Some(quota) = rate_limits::quota_from_event(value) {
+            session.rate_limits.lock().await.observe_quota(&quota);
         }
+        return;
+    };
+    let warning = session.rate_limits.lock().await.record(signal, now_ms());
+    let mut params = serde_json::to_value(&warning).unwrap_or_else(|_| json!({}));
+    params["threadId"] = json!(thread_id);
+    emit_event(event_sink, workspace_id, "rateLimit/warning", params);
+}
+
+/// `turn/completed` params; a turn the watchdog cancelled carries `timedOut` and why.
+fn turn_completed_params(thread_id: &str, turn_id: &str, timeout: Option<&TurnTimeout>) -> Value {
+    let mut params = json!({
+        "threadId": thread_id,
+        "turn": { "id": turn_id, "threadId": thread_id },
+    });
+    if let Some(timeout) = timeout {
+        params["turn"]["timedOut"] = json!(true);
+        params["timeout"] = json!(timeout);
     }
-    Ok(message.trim().to_string())
+    params
 }
 
-/// Container for the stdout and stderr readers from a spawned persistent Claude CLI session.
-pub(crate) struct PersistentSessionReaders {
-    pub stdout: AsyncBufReader<tokio::process::ChildStdout>,
-    pub stderr: AsyncBufReader<tokio::process::ChildStderr>,
+/// Store a compaction of a thread, unless replaying, and show it in the UI.
+fn report_compaction(
+    session: &WorkspaceSession,
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    record: &CompactionRecord,
+) {
+    if !session.replaying {
+        storage::record_compaction(record);
+    }
+    emit_event(
+        event_sink,
+        workspace_id,
+        "thread/compaction",
+        json!({ "threadId": record.thread_id, "compaction": record }),
+    );
 }
 
-/// Spawns a persistent Claude CLI session with bidirectional streaming.
-///
-/// This function spawns Claude CLI with streaming JSON input/output format,
-/// allowing for continuous interaction without spawning new processes for each turn.
+/// Spawns a persistent Claude CLI session with bidirectional streaming, resuming the
+/// thread's transcript when one already exists on disk.
 ///
 /// # Arguments
 /// * `session` - The workspace session containing entry and claude_bin information
@@ -929,94 +2915,15 @@ pub(crate) async fn spawn_persistent_claude_session(
     access_mode: Option<&str>,
     max_thinking_tokens: Option<u32>,
 ) -> Result<PersistentSessionReaders, String> {
-    let mut command = build_claude_command_with_bin(session.claude_bin.clone());
-    command.current_dir(&session.entry.path);
-
-    // Set up streaming JSON input/output format
-    command.arg("--print");
-    command.arg("--input-format").arg("stream-json");
-    command.arg("--output-format").arg("stream-json");
-    command.arg("--include-partial-messages");
-    command.arg("--verbose");
-
-    // Set model if specified
-    if let Some(model) = model {
-        if !model.trim().is_empty() {
-            command.arg("--model").arg(model);
-        }
-    }
-
-    // Set permission mode if specified
-    // Map UI access modes to valid Claude CLI permission modes:
-    // - "read-only" → "plan" (requires plan approval, safest)
-    // - "current" → skip (use CLI default)
-    // - "full-access" → "bypassPermissions" (bypass all permission checks)
-    // Also accept direct CLI modes: acceptEdits, bypassPermissions, default, delegate, dontAsk, plan
-    if let Some(mode) = access_mode {
-        let mode_trimmed = mode.trim();
-        let mapped_mode = match mode_trimmed {
-            "read-only" => Some("plan"),
-            "full-access" => Some("bypassPermissions"),
-            "current" => None, // Use CLI default
-            // Direct CLI modes pass through
-            "acceptEdits" | "bypassPermissions" | "default" | "delegate" | "dontAsk" | "plan" => Some(mode_trimmed),
-            _ => None, // Unknown modes are ignored
-        };
-        if let Some(cli_mode) = mapped_mode {
-            command.arg("--permission-mode").arg(cli_mode);
-        }
-    }
-
-    // Set max thinking tokens (default to 31999, Claude's default)
-    let thinking_tokens = max_thinking_tokens.unwrap_or(31999);
-    command.arg("--max-thinking-tokens").arg(thinking_tokens.to_string());
-
     // Use --resume if session exists, otherwise --session-id
-    if session_exists(&session.entry, thread_id) {
-        command.arg("--resume").arg(thread_id);
+    let launch = if session_exists(&session.entry, thread_id) {
+        SessionLaunch::Resume
     } else {
-        command.arg("--session-id").arg(thread_id);
-    }
-
-    // Configure stdio for bidirectional communication
-    command.stdin(std::process::Stdio::piped());
-    command.stdout(std::process::Stdio::piped());
-    command.stderr(std::process::Stdio::piped());
-
-    // Spawn the process
-    let mut child = command.spawn().map_err(|err| {
-        format!("Failed to spawn Claude CLI: {}", err)
-    })?;
-
-    // Take stdin for bidirectional communication
-    let stdin = child.stdin.take().ok_or("Failed to capture stdin")?;
-
-    // Take stdout for reading responses
-    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
-    let stdout_reader = AsyncBufReader::new(stdout);
-
-    // Take stderr for reading error messages
-    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
-    let stderr_reader = AsyncBufReader::new(stderr);
-
-    // Store the persistent session for this thread (stdin + child + permission_mode + model)
-    // Convert access_mode to the CLI permission mode for storage
-    let stored_permission_mode = access_mode.map(|mode| {
-        match mode {
-            "read-only" => "plan".to_string(),
-            "full-access" => "bypassPermissions".to_string(),
-            "current" => "default".to_string(),
-            other => other.to_string(),
-        }
-    });
-    // Store the model for detecting changes
-    let stored_model = model.map(|m| m.to_string());
-    session.set_persistent_session(thread_id.to_string(), stdin, child, stored_permission_mode, stored_model).await;
-
-    Ok(PersistentSessionReaders {
-        stdout: stdout_reader,
-        stderr: stderr_reader,
-    })
+        SessionLaunch::New
+    };
+    session
+        .spawn_persistent_process(thread_id, launch, model, access_mode, max_thinking_tokens)
+        .await
 }
 
 /// Ensures a persistent session exists for the given workspace and thread.
@@ -1036,17 +2943,20 @@ async fn ensure_persistent_session(
     max_thinking_tokens: Option<u32>,
     event_sink: TauriEventSink,
 ) -> Result<String, String> {
-    // Acquire the session initialization lock to prevent race conditions
-    let _init_guard = session.session_init_lock.lock().await;
-
+    // Acquire the thread's initialization lock to prevent race conditions; other
+    // threads of the workspace start their processes independently
+    let init_lock = session.session_init_lock(thread_id).await;
+    let _init_guard = init_lock.lock().await;
+
+    // Compared as spawned, after the workspace's guardrails
+    let access_mode =
+        access_mode.map(|mode| guardrails::guard_permission_mode(&session.entry.settings, mode));
     // Convert requested access_mode to CLI permission mode for comparison
-    let requested_permission_mode = access_mode.map(|mode| {
-        match mode {
-            "read-only" => "plan".to_string(),
-            "full-access" => "bypassPermissions".to_string(),
-            "current" => "default".to_string(),
-            other => other.to_string(),
-        }
+    let requested_permission_mode = access_mode.map(|mode| match mode {
+        "read-only" => "plan".to_string(),
+        "full-access" => "bypassPermissions".to_string(),
+        "current" => "default".to_string(),
+        other => other.to_string(),
     });
 
     // Convert requested model for comparison (normalize empty strings to None)
@@ -1057,7 +2967,9 @@ async fn ensure_persistent_session(
     // Check if a persistent session already exists for THIS thread
     if session.has_persistent_session(thread_id).await {
         // Check if permission mode changed - if so, we need to restart the session
-        let current_permission_mode = session.get_persistent_session_permission_mode(thread_id).await;
+        let current_permission_mode = session
+            .get_persistent_session_permission_mode(thread_id)
+            .await;
         let current_model = session.get_persistent_session_model(thread_id).await;
 
         // Only restart if the requested mode is different from the current mode
@@ -1072,18 +2984,22 @@ async fn ensure_persistent_session(
             // Permission mode changed - kill the old session and spawn a new one
             // This follows Claude CLI behavior: permission mode is per-process,
             // so changing it requires starting a new process with --resume
-            eprintln!(
-                "[ensure_persistent_session] Permission mode changed from '{}' to '{}' for thread {}, restarting session",
-                current_mode, requested_mode, thread_id
+            tracing::info!(
+                "Permission mode changed from '{}' to '{}' for thread {}, restarting session",
+                current_mode,
+                requested_mode,
+                thread_id
             );
             session.kill_persistent_session(thread_id).await?;
         } else if model_changed {
             // Model changed - kill the old session and spawn a new one
             // This follows Claude CLI behavior: model is per-process,
             // so changing it requires starting a new process with --resume --model
-            eprintln!(
-                "[ensure_persistent_session] Model changed from '{:?}' to '{:?}' for thread {}, restarting session",
-                current_model, requested_model, thread_id
+            tracing::info!(
+                "Model changed from '{:?}' to '{:?}' for thread {}, restarting session",
+                current_model,
+                requested_model,
+                thread_id
             );
             session.kill_persistent_session(thread_id).await?;
         } else {
@@ -1092,10 +3008,96 @@ async fn ensure_persistent_session(
         }
     }
 
-    let turn_id = Uuid::new_v4().to_string();
+    let (turn_id, pid) = start_persistent_session(
+        workspace_id,
+        session,
+        thread_id,
+        model,
+        access_mode,
+        max_thinking_tokens,
+        event_sink.clone(),
+    )
+    .await?;
+
+    // Spawn the supervisor that respawns the process if it dies unexpectedly
+    if let Some(pid) = pid {
+        tokio::spawn(supervise_persistent_session(
+            workspace_id.to_string(),
+            thread_id.to_string(),
+            pid,
+            Arc::clone(session),
+            event_sink,
+        ));
+    }
+
+    Ok(turn_id)
+}
+
+/// Start a thread's persistent process ahead of its next message, with the workspace's
+/// model and default permission mode, so the first turn skips the CLI's startup.
+pub(crate) async fn warm_persistent_session(
+    workspace_id: &str,
+    session: &Arc<WorkspaceSession>,
+    thread_id: &str,
+    event_sink: TauriEventSink,
+) -> Result<(), String> {
+    let model = session.entry.model.clone();
+    ensure_persistent_session(
+        workspace_id,
+        session,
+        thread_id,
+        model.as_deref(),
+        None,
+        None,
+        event_sink,
+    )
+    .await
+    .map(|_| ())
+}
 
+/// Spawns the persistent CLI process for a thread together with its stdout/stderr
+/// readers. Callers must hold the thread's `session_init_lock`.
+///
+/// Returns the turn_id for the current turn and the pid of the spawned process.
+async fn start_persistent_session(
+    workspace_id: &str,
+    session: &Arc<WorkspaceSession>,
+    thread_id: &str,
+    model: Option<&str>,
+    access_mode: Option<&str>,
+    max_thinking_tokens: Option<u32>,
+    event_sink: TauriEventSink,
+) -> Result<(String, Option<u32>), String> {
     // Spawn a new persistent session for this thread
-    let readers = spawn_persistent_claude_session(session, thread_id, model, access_mode, max_thinking_tokens).await?;
+    let readers = spawn_persistent_claude_session(
+        session,
+        thread_id,
+        model,
+        access_mode,
+        max_thinking_tokens,
+    )
+    .await?;
+    Ok(attach_persistent_readers(
+        workspace_id,
+        session,
+        thread_id,
+        readers,
+        event_sink,
+    ))
+}
+
+/// Starts the background stdout/stderr readers for a freshly spawned persistent process.
+///
+/// Returns the turn_id for the current turn and the pid of the process.
+fn attach_persistent_readers(
+    workspace_id: &str,
+    session: &Arc<WorkspaceSession>,
+    thread_id: &str,
+    readers: PersistentSessionReaders,
+    event_sink: TauriEventSink,
+) -> (String, Option<u32>) {
+    let turn_id = Uuid::new_v4().to_string();
+    let pid = readers.pid;
 
     // Spawn background task to read stdout and emit events
     let workspace_id_owned = workspace_id.to_string();
@@ -1103,44 +3105,343 @@ async fn ensure_persistent_session(
     let turn_id_clone = turn_id.clone();
     let event_sink_clone = event_sink.clone();
     let session_clone = Arc::clone(session);
-    tokio::spawn(async move {
-        read_persistent_stdout(
-            readers.stdout,
-            workspace_id_owned,
-            thread_id_owned,
-            turn_id_clone,
-            session_clone,
-            event_sink_clone,
-        ).await;
-    });
+    let session_span = tracing::info_span!("session", workspace_id, thread_id, pid);
+    tokio::spawn(
+        async move {
+            read_persistent_stdout(
+                readers.stdout,
+                workspace_id_owned,
+                thread_id_owned,
+                turn_id_clone,
+                session_clone,
+                event_sink_clone,
+            )
+            .await;
+        }
+        .instrument(session_span.clone()),
+    );
 
     // Spawn background task to read stderr and emit error events
     let workspace_id_for_stderr = workspace_id.to_string();
     let thread_id_for_stderr = thread_id.to_string();
     let session_for_stderr = Arc::clone(session);
-    tokio::spawn(async move {
-        read_persistent_stderr(
-            readers.stderr,
-            workspace_id_for_stderr,
-            thread_id_for_stderr,
-            session_for_stderr,
-            event_sink,
-        ).await;
+    tokio::spawn(
+        async move {
+            read_persistent_stderr(
+                readers.stderr,
+                workspace_id_for_stderr,
+                thread_id_for_stderr,
+                session_for_stderr,
+                event_sink,
+            )
+            .await;
+        }
+        .instrument(session_span),
+    );
+
+    (turn_id, pid)
+}
+
+/// Background task that watches a thread's persistent CLI process.
+///
+/// Intentional kills remove the session before terminating it, so the supervisor only
+/// acts when the process it watches exits while still registered. In that case it
+/// respawns the process (which resumes the conversation via `--resume <session_id>`),
+/// emits `session-disconnected`, and keeps supervising the replacement. Crash loops are
+/// capped by `MAX_SESSION_RECONNECTS`; a process that stays up for `SESSION_STABLE_AFTER`
+/// resets the attempt counter.
+async fn supervise_persistent_session(
+    workspace_id: String,
+    thread_id: String,
+    mut pid: u32,
+    session: Arc<WorkspaceSession>,
+    event_sink: TauriEventSink,
+) {
+    let mut attempt: u32 = 0;
+    loop {
+        let started = Instant::now();
+        let mut ticker = interval(SESSION_SUPERVISOR_POLL_INTERVAL);
+        let exited = loop {
+            ticker.tick().await;
+            match session.poll_persistent_session(&thread_id, pid).await {
+                PersistentSessionStatus::Running => continue,
+                PersistentSessionStatus::Gone => return,
+                PersistentSessionStatus::Exited(exited) => break exited,
+            }
+        };
+
+        attempt = if started.elapsed() >= SESSION_STABLE_AFTER {
+            1
+        } else {
+            attempt + 1
+        };
+        tracing::warn!(
+            "Claude CLI for thread {} exited with {:?}, reconnect attempt {}",
+            thread_id,
+            exited.exit_code,
+            attempt
+        );
+
+        let result = if attempt > MAX_SESSION_RECONNECTS {
+            session.finish_turn(&thread_id).await;
+            Err(format!(
+                "Claude CLI exited {} times in a row; not reconnecting",
+                attempt - 1
+            ))
+        } else {
+            let init_lock = session.session_init_lock(&thread_id).await;
+            let _init_guard = init_lock.lock().await;
+            if session.has_persistent_session(&thread_id).await {
+                // A new message already respawned the session (with its own supervisor)
+                Ok(None)
+            } else {
+                // Whatever turn the process was running died with it
+                session.finish_turn(&thread_id).await;
+                start_persistent_session(
+                    &workspace_id,
+                    &session,
+                    &thread_id,
+                    exited.model.as_deref(),
+                    exited.permission_mode.as_deref(),
+                    None,
+                    event_sink.clone(),
+                )
+                .await
+                .map(|(_, pid)| pid)
+            }
+        };
+
+        event_sink.emit_session_disconnected(SessionDisconnected {
+            workspace_id: workspace_id.clone(),
+            thread_id: thread_id.clone(),
+            exit_code: exited.exit_code,
+            reconnected: result.is_ok(),
+            error: result.as_ref().err().cloned(),
+        });
+
+        match result {
+            Ok(Some(new_pid)) => {
+                pid = new_pid;
+                outbox::flush(&session, &event_sink, &workspace_id, true).await;
+            }
+            _ => return,
+        }
+    }
+}
+
+/// The git state a turn is diffed against.
+enum TurnSnapshot {
+    /// Taken before the prompt was written, so nothing the turn does can land in it.
+    Taken(PathBuf, WorktreeSnapshot),
+    /// Still being taken in the background while the model starts working.
+    Pending(tokio::task::JoinHandle<Option<(PathBuf, WorktreeSnapshot)>>),
+}
+
+impl TurnSnapshot {
+    async fn wait(self) -> Option<(PathBuf, WorktreeSnapshot)> {
+        match self {
+            Self::Taken(repo_root, snapshot) => Some((repo_root, snapshot)),
+            Self::Pending(snapshot) => snapshot.await.ok().flatten(),
+        }
+    }
+}
+
+/// Snapshot the workspace's git state in the background while the model starts working.
+/// Workspaces outside git get no snapshot.
+fn start_turn_snapshot(entry: &WorkspaceEntry) -> Option<TurnSnapshot> {
+    let repo_root = resolve_git_root(entry).ok()?;
+    Some(TurnSnapshot::Pending(tokio::task::spawn_blocking(
+        move || {
+            let snapshot = git_diff::snapshot(&repo_root).ok()?;
+            Some((repo_root, snapshot))
+        },
+    )))
+}
+
+/// Snapshot the workspace's git state and wait for it.
+async fn take_turn_snapshot(entry: &WorkspaceEntry) -> Option<(PathBuf, WorktreeSnapshot)> {
+    start_turn_snapshot(entry)?.wait().await
+}
+
+/// A read-only turn must not get to edit anything before its snapshot is taken, so the
+/// snapshot is taken here, ahead of the prompt, and picked up by the reader when the
+/// turn starts. Other turns are snapshotted by the reader in the background.
+async fn snapshot_read_only_turn(session: &WorkspaceSession, thread_id: &str) {
+    if !session.entry.settings.read_only || session.replaying {
+        return;
+    }
+    if let Some(snapshot) = take_turn_snapshot(&session.entry).await {
+        session
+            .turn_snapshots
+            .lock()
+            .await
+            .insert(thread_id.to_string(), snapshot);
+    }
+}
+
+/// Diff the working tree against `before`, reverting every changed path when `revert`
+/// is set. Blocks on git.
+fn diff_turn(
+    repo_root: &Path,
+    before: &WorktreeSnapshot,
+    revert: bool,
+) -> Result<(SnapshotDiff, Option<Result<Vec<String>, String>>), String> {
+    let after = git_diff::snapshot(repo_root)?;
+    let diff = git_diff::diff_snapshots(repo_root, before, &after)?;
+    let restore = (revert && !diff.files.is_empty()).then(|| {
+        let paths: Vec<String> = diff.files.iter().map(|file| file.path.clone()).collect();
+        git_diff::restore_paths(repo_root, before, &paths)
+    });
+    Ok((diff, restore))
+}
+
+/// Diff the working tree against the turn's starting snapshot, store the result for
+/// `get_turn_diff` and announce the changed files with `turn/diff`. In a read-only
+/// workspace any change is reverted and reported with `turn/guardrailViolation`. The
+/// diff cannot tell who made a change, so edits the user makes to the workspace during
+/// a read-only turn are reverted too.
+fn spawn_turn_diff(
+    event_sink: TauriEventSink,
+    before: TurnSnapshot,
+    workspace_id: String,
+    thread_id: String,
+    turn_id: String,
+    read_only: bool,
+) {
+    tauri::async_runtime::spawn(async move {
+        let Some((repo_root, before)) = before.wait().await else {
+            return;
+        };
+        let diff =
+            tokio::task::spawn_blocking(move || diff_turn(&repo_root, &before, read_only)).await;
+        let (diff, restore) = match diff {
+            Ok(Ok(diff)) => diff,
+            Ok(Err(err)) => {
+                tracing::warn!("failed to diff turn {turn_id}: {err}");
+                return;
+            }
+            Err(_) => return,
+        };
+        let turn_diff = TurnDiff {
+            workspace_id: workspace_id.clone(),
+            thread_id: thread_id.clone(),
+            turn_id: turn_id.clone(),
+            created_at: now_ms(),
+            files: diff.files,
+            diff: diff.diff,
+            diff_truncated: diff.truncated,
+        };
+        storage::record_turn_diff(&turn_diff);
+        emit_event(
+            &event_sink,
+            &workspace_id,
+            "turn/diff",
+            json!({ "threadId": thread_id, "turnId": turn_id, "files": turn_diff.files }),
+        );
+        let Some(restore) = restore else {
+            return;
+        };
+        let paths: Vec<&str> = turn_diff
+            .files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        let (failed, error) = match restore {
+            Ok(failed) => (failed, None),
+            Err(err) => (Vec::new(), Some(err)),
+        };
+        let reverted = error.is_none() && failed.is_empty();
+        tracing::warn!(
+            "read-only workspace {workspace_id} changed during turn {turn_id}: {paths:?}"
+        );
+        emit_event(
+            &event_sink,
+            &workspace_id,
+            "turn/guardrailViolation",
+            json!({
+                "threadId": thread_id,
+                "turnId": turn_id,
+                "guardrail": "readOnly",
+                "files": paths,
+                "reverted": reverted,
+                "failed": failed,
+                "error": error,
+            }),
+        );
+        notifications::notify(
+            event_sink.app_handle(),
+            &workspace_id,
+            Notice::GuardrailViolated {
+                files: paths.len(),
+                reverted,
+            },
+        );
     });
+}
 
-    Ok(turn_id)
+/// Commit the working tree to the workspace's auto-commit branch after a successful
+/// turn, if it opted in, and announce the commit with `turn/autoCommitted`.
+fn spawn_auto_commit(
+    event_sink: &TauriEventSink,
+    entry: &WorkspaceEntry,
+    thread_id: &str,
+    turn_id: &str,
+    summary: &str,
+) {
+    let Some(branch) = autocommit::target_branch(entry.settings.auto_commit.as_ref()) else {
+        return;
+    };
+    let Ok(repo_root) = resolve_git_root(entry) else {
+        return;
+    };
+    let message = autocommit::commit_message(summary, thread_id, turn_id);
+    let event_sink = event_sink.clone();
+    let workspace_id = entry.id.clone();
+    let thread_id = thread_id.to_string();
+    let turn_id = turn_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let commit_branch = branch.clone();
+        let commit = tokio::task::spawn_blocking(move || {
+            autocommit::commit_worktree(&repo_root, &commit_branch, &message)
+        })
+        .await;
+        let commit = match commit {
+            Ok(Ok(Some(commit))) => commit,
+            Ok(Ok(None)) | Err(_) => return,
+            Ok(Err(err)) => {
+                tracing::warn!("auto-commit after turn {turn_id} failed: {err}");
+                return;
+            }
+        };
+        emit_event(
+            &event_sink,
+            &workspace_id,
+            "turn/autoCommitted",
+            json!({
+                "threadId": thread_id,
+                "turnId": turn_id,
+                "branch": branch,
+                "commit": commit.to_string(),
+            }),
+        );
+    });
 }
 
 /// Background task that reads stdout from the persistent Claude CLI session
 /// and emits events to the frontend.
-async fn read_persistent_stdout(
-    mut reader: AsyncBufReader<tokio::process::ChildStdout>,
+///
+/// Replays drive the same reader with recorded output; their sessions are marked as
+/// replaying so nothing is persisted, committed or sent out a second time.
+pub(crate) async fn read_persistent_stdout<R>(
+    mut reader: R,
     workspace_id: String,
     thread_id: String,
     initial_turn_id: String,
     session: Arc<WorkspaceSession>,
     event_sink: TauriEventSink,
-) {
+) where
+    R: AsyncBufRead + Unpin,
+{
     let mut current_turn_id = initial_turn_id;
     let mut item_id = format!("{current_turn_id}-assistant");
     let mut full_text = String::new();
@@ -1155,23 +3456,87 @@ async fn read_persistent_stdout(
     let mut request_id_counter: u64 = 0;
     let mut permission_denial_ids: HashSet<String> = HashSet::new();
     let mut turn_active = false;
+    // Cumulative `total_cost_usd` last reported by this process
+    let mut reported_total_cost: f64 = 0.0;
+    // Claude session backing this thread, as last reported by `system:init`
+    let mut claude_session_id = thread_id.clone();
+    let mut turn_clock = TurnClock::default();
+    let mut tool_timeline = ToolTimeline::default();
+    let mut edit_recorder = EditRecorder::default();
+    let mut usage_gauge = UsageGauge::default();
+    // Open while a turn runs, so its close reports how long the turn took
+    let mut turn_span: Option<tracing::Span> = None;
+    // Last gauge reading, which on a result is the one the turn ended with
+    let mut last_reading: Option<UsageGaugeReading> = None;
+    let mut compactions = CompactionTracker::new(&workspace_id, &thread_id);
+    // Set while the process runs a `/compact` the monitor sent, which never triggers another
+    let mut compacting = false;
+    // Working tree as the current turn found it, for diffing once it finishes
+    let mut turn_snapshot: Option<TurnSnapshot> = None;
+    // Retries of the current turn so far, and a transient API error it reported
+    let mut retry_attempt: u32 = 0;
+    let mut turn_api_failure: Option<String> = None;
+    // Agents other than Claude have their output translated into stream-json first
+    let mut translator = session.agent.translator();
+    let mut translated: VecDeque<String> = VecDeque::new();
 
     let mut line = String::new();
 
     loop {
         line.clear();
-        match reader.read_line(&mut line).await {
+        let read = match translated.pop_front() {
+            Some(event) => {
+                line.push_str(&event);
+                Ok(line.len())
+            }
+            None => match reader.read_line(&mut line).await {
+                Ok(read) if read > 0 => {
+                    session.record_output(&thread_id, &line).await;
+                    session
+                        .log_output(&thread_id, LogStream::Stdout, &line)
+                        .await;
+                    match translator.as_mut() {
+                        Some(translator) => {
+                            let events = translator.translate(line.trim());
+                            translated.extend(events.iter().map(Value::to_string));
+                            continue;
+                        }
+                        None => Ok(read),
+                    }
+                }
+                other => other,
+            },
+        };
+        match read {
             Ok(0) => {
                 // EOF - process ended
+                clear_permission_requests(&session, &event_sink, &workspace_id, &thread_id).await;
+                session.take_interrupted(&thread_id).await;
+                session.mcp_servers.lock().await.remove(&thread_id);
+                if !session.replaying {
+                    storage::remember_thread_session(
+                        &workspace_id,
+                        &thread_id,
+                        &claude_session_id,
+                        None,
+                        if turn_active {
+                            ThreadSessionStatus::Interrupted
+                        } else {
+                            ThreadSessionStatus::Idle
+                        },
+                    );
+                }
+                if compacting && !turn_active {
+                    session.finish_turn(&thread_id).await;
+                }
+                let timed_out = session.turn_watch.lock().await.finish(&thread_id);
                 if turn_active {
+                    session.finish_turn(&thread_id).await;
                     emit_event(
                         &event_sink,
                         &workspace_id,
                         "turn/completed",
-                        json!({
-                            "threadId": thread_id,
-                            "turn": { "id": current_turn_id, "threadId": thread_id },
-                        }),
+                        turn_completed_params(&thread_id, &current_turn_id, timed_out.as_ref()),
                     );
                 }
                 break;
@@ -1182,19 +3547,83 @@ async fn read_persistent_stdout(
                     continue;
                 }
 
-                let value: Value = match serde_json::from_str(trimmed) {
+                let parsed = tracing::trace_span!("event_parse", bytes = trimmed.len())
+                    .in_scope(|| serde_json::from_str::<Value>(trimmed));
+                let value = match parsed {
                     Ok(v) => v,
-                    Err(_) => continue,
+                    Err(err) => {
+                        tracing::debug!("skipping malformed stdout line: {err}");
+                        continue;
+                    }
                 };
 
+                // Subagents share the account's limits, so check before skipping them
+                track_rate_limits(&session, &event_sink, &workspace_id, &thread_id, &value).await;
+
                 // Skip subagent events - they have parent_tool_use_id set
-                if value.get("parent_tool_use_id").and_then(|v| v.as_str()).is_some() {
+                if value
+                    .get("parent_tool_use_id")
+                    .and_then(|v| v.as_str())
+                    .is_some()
+                {
                     continue;
                 }
 
+                // Partial message deltas stream in before the turn's first assistant event
+                if let Some(reading) = usage_gauge.observe(&value, Instant::now()) {
+                    let mut params = serde_json::to_value(&reading).unwrap_or_else(|_| json!({}));
+                    params["threadId"] = json!(thread_id);
+                    emit_event(
+                        &event_sink,
+                        &workspace_id,
+                        "thread/usageGauge/updated",
+                        params,
+                    );
+                    if !reading.done {
+                        if let Some(record) = compactions.measure(&reading) {
+                            report_compaction(&session, &event_sink, &workspace_id, &record);
+                        }
+                    }
+                    last_reading = Some(reading);
+                }
+                if let Some(record) = compactions.observe(&value, now_ms()) {
+                    report_compaction(&session, &event_sink, &workspace_id, &record);
+                }
+
                 let event_type = value.get("type").and_then(|v| v.as_str()).unwrap_or("");
                 let subtype = value.get("subtype").and_then(|v| v.as_str()).unwrap_or("");
 
+                // The CLI blocks on control requests until they are answered over stdin
+                if event_type == "control_request" {
+                    let prompted = handle_control_request(
+                        &session,
+                        &event_sink,
+                        &workspace_id,
+                        &thread_id,
+                        &current_turn_id,
+                        value,
+                    )
+                    .await;
+                    // The user already decided on this tool call; don't report it again
+                    // as a permission denial when the turn ends
+                    if let Some(tool_use_id) = prompted {
+                        permission_denial_ids.insert(tool_use_id);
+                    }
+                    continue;
+                }
+                // Answers to control requests the monitor sent: interrupts and health pings
+                if event_type == "control_response" {
+                    if let Some(request_id) = response_request_id(&value) {
+                        let response = value.get("response").cloned().unwrap_or(Value::Null);
+                        session
+                            .control
+                            .lock()
+                            .await
+                            .resolve_request(request_id, response);
+                    }
+                    continue;
+                }
+
                 // Handle system init event
                 if event_type == "system" {
                     if subtype == "init" {
@@ -1207,10 +3636,31 @@ async fn read_persistent_stdout(
                             .get("model")
                             .and_then(|v| v.as_str())
                             .map(|s| s.to_string());
-                        let tools = value
-                            .get("tools")
-                            .and_then(|v| v.as_array())
-                            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect::<Vec<_>>());
+                        let tools = value.get("tools").and_then(|v| v.as_array()).map(|arr| {
+                            arr.iter()
+                                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                .collect::<Vec<_>>()
+                        });
+                        let mcp_servers: Vec<McpServerStatus> = value
+                            .get("mcp_servers")
+                            .cloned()
+                            .and_then(|servers| serde_json::from_value(servers).ok())
+                            .unwrap_or_default();
+                        session
+                            .mcp_servers
+                            .lock()
+                            .await
+                            .insert(thread_id.clone(), mcp_servers);
+                        claude_session_id = session_id.to_string();
+                        if !session.replaying {
+                            storage::remember_thread_session(
+                                &workspace_id,
+                                &thread_id,
+                                session_id,
+                                model.clone(),
+                                ThreadSessionStatus::Running,
+                            );
+                        }
 
                         // Emit session initialized event
                         emit_event(
@@ -1248,6 +3698,23 @@ async fn read_persistent_stdout(
                     tool_counter = 0;
                     thinking_counter = 0;
                     permission_denial_ids.clear();
+                    turn_clock.start(session.take_turn_sent_at(&thread_id).await, Instant::now());
+                    tool_timeline.start(now_ms());
+                    edit_recorder.start();
+                    turn_snapshot = match session.turn_snapshots.lock().await.remove(&thread_id) {
+                        Some((repo_root, snapshot)) => {
+                            Some(TurnSnapshot::Taken(repo_root, snapshot))
+                        }
+                        None if session.replaying => None,
+                        None => start_turn_snapshot(&session.entry),
+                    };
+                    turn_api_failure = None;
+                    turn_span = Some(tracing::info_span!("turn", turn_id = %current_turn_id));
+                    session.turn_watch.lock().await.start(
+                        &thread_id,
+                        &current_turn_id,
+                        Instant::now(),
+                    );
 
                     emit_event(
                         &event_sink,
@@ -1258,6 +3725,14 @@ async fn read_persistent_stdout(
                             "turn": { "id": current_turn_id, "threadId": thread_id },
                         }),
                     );
+                    if !session.replaying {
+                        webhooks::dispatch(
+                            event_sink.app_handle(),
+                            &workspace_id,
+                            WebhookEvent::TurnStarted,
+                            json!({ "threadId": thread_id, "turnId": current_turn_id }),
+                        );
+                    }
                     emit_event(
                         &event_sink,
                         &workspace_id,
@@ -1269,6 +3744,22 @@ async fn read_persistent_stdout(
                     );
                 }
 
+                if turn_active {
+                    turn_clock.observe(&value, Instant::now());
+                    tool_timeline.observe(&value, now_ms());
+                    edit_recorder.observe(&value, Path::new(&session.entry.path));
+                    session
+                        .turn_watch
+                        .lock()
+                        .await
+                        .observe(&thread_id, &value, Instant::now());
+                    if event_type == "assistant" {
+                        if let Some(reason) = retry::transient_failure(&value) {
+                            turn_api_failure = Some(reason);
+                        }
+                    }
+                }
+
                 if event_type == "assistant" {
                     if let Some(uuid) = value.get("uuid").and_then(|v| v.as_str()) {
                         if !uuid.is_empty() {
@@ -1283,13 +3774,17 @@ async fn read_persistent_stdout(
                         }
                         if let Some(content) = message.get("content").and_then(|v| v.as_array()) {
                             for entry in content {
-                                let entry_type = entry.get("type").and_then(|v| v.as_str()).unwrap_or("");
+                                let entry_type =
+                                    entry.get("type").and_then(|v| v.as_str()).unwrap_or("");
                                 if entry_type == "thinking" {
-                                    if let Some(thinking) = entry.get("thinking").and_then(|v| v.as_str()) {
+                                    if let Some(thinking) =
+                                        entry.get("thinking").and_then(|v| v.as_str())
+                                    {
                                         let trimmed = thinking.trim();
                                         if !trimmed.is_empty() {
                                             thinking_counter += 1;
-                                            let thinking_id = format!("{item_id}-thinking-{thinking_counter}");
+                                            let thinking_id =
+                                                format!("{item_id}-thinking-{thinking_counter}");
                                             emit_event(
                                                 &event_sink,
                                                 &workspace_id,
@@ -1311,10 +3806,8 @@ async fn read_persistent_stdout(
                                 if entry_type != "tool_use" {
                                     continue;
                                 }
-                                let tool_id = entry
-                                    .get("id")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("");
+                                let tool_id =
+                                    entry.get("id").and_then(|v| v.as_str()).unwrap_or("");
                                 let tool_name = entry
                                     .get("name")
                                     .and_then(|v| v.as_str())
@@ -1332,40 +3825,26 @@ async fn read_persistent_stdout(
                                     tool_id.to_string()
                                 };
                                 // Check for AskUserQuestion - emit request_user_input event
-                                if tool_name == "AskUserQuestion" {
+                                if tool_name == ASK_USER_QUESTION_TOOL {
                                     request_id_counter += 1;
-                                    // Extract questions array from tool_input
-                                    // Claude's AskUserQuestion tool has structure:
-                                    // { "questions": [{ "question": "...", "header": "...", "options": [{ "label": "...", "description": "..." }] }] }
-                                    let questions_raw = tool_input.get("questions").and_then(|v| v.as_array());
-                                    let questions = if let Some(q_array) = questions_raw {
-                                        // Map each question, adding the tool_id as the id for each
-                                        q_array.iter().enumerate().map(|(idx, q)| {
-                                            let id = if idx == 0 {
-                                                tool_id.to_string()
-                                            } else {
-                                                format!("{}-{}", tool_id, idx)
-                                            };
-                                            json!({
-                                                "id": id,
-                                                "header": q.get("header").and_then(|v| v.as_str()).unwrap_or("Claude needs your input"),
-                                                "question": q.get("question").and_then(|v| v.as_str()).unwrap_or(""),
-                                                "options": q.get("options").cloned().unwrap_or(Value::Null),
-                                            })
-                                        }).collect::<Vec<_>>()
-                                    } else {
-                                        // Fallback for legacy single question format
-                                        let question_text = tool_input
-                                            .get("question")
-                                            .and_then(|v| v.as_str())
-                                            .unwrap_or("")
-                                            .to_string();
-                                        vec![json!({
-                                            "id": tool_id,
-                                            "header": "Claude needs your input",
-                                            "question": question_text,
-                                        })]
-                                    };
+                                    let questions = parse_questions(tool_id, &tool_input);
+                                    if !tool_id.is_empty() {
+                                        session.questions.lock().await.ask(
+                                            &thread_id,
+                                            tool_id,
+                                            questions.clone(),
+                                        );
+                                        open_interaction(
+                                            &session,
+                                            InteractionKind::Question,
+                                            &thread_id,
+                                            &current_turn_id,
+                                            None,
+                                            Some(tool_id),
+                                            &tool_name,
+                                        )
+                                        .await;
+                                    }
                                     emit_event_with_id(
                                         &event_sink,
                                         &workspace_id,
@@ -1429,7 +3908,8 @@ async fn read_persistent_stdout(
                     if let Some(message) = value.get("message") {
                         if let Some(content) = message.get("content").and_then(|v| v.as_array()) {
                             for (index, entry) in content.iter().enumerate() {
-                                if entry.get("type").and_then(|v| v.as_str()) != Some("tool_result") {
+                                if entry.get("type").and_then(|v| v.as_str()) != Some("tool_result")
+                                {
                                     continue;
                                 }
                                 let tool_use_id = entry
@@ -1437,7 +3917,8 @@ async fn read_persistent_stdout(
                                     .or_else(|| entry.get("toolUseId"))
                                     .and_then(|v| v.as_str())
                                     .unwrap_or("");
-                                let content_value = entry.get("content").cloned().unwrap_or(Value::Null);
+                                let content_value =
+                                    entry.get("content").cloned().unwrap_or(Value::Null);
                                 let mut output = tool_result_output(&content_value);
                                 let is_error = entry
                                     .get("is_error")
@@ -1463,10 +3944,8 @@ async fn read_persistent_stdout(
                                     .get(tool_use_id)
                                     .cloned()
                                     .unwrap_or_else(|| "Tool".to_string());
-                                let tool_input = tool_inputs
-                                    .get(tool_use_id)
-                                    .cloned()
-                                    .unwrap_or(Value::Null);
+                                let tool_input =
+                                    tool_inputs.get(tool_use_id).cloned().unwrap_or(Value::Null);
                                 if is_permission_denial {
                                     let denial_id = if tool_use_id.is_empty() {
                                         format!("{thread_id}-{command}-{index}")
@@ -1490,7 +3969,8 @@ async fn read_persistent_stdout(
                                         );
                                     }
                                 }
-                                output = collapse_subagent_output(output, &command, &tool_input, &value);
+                                output =
+                                    collapse_subagent_output(output, &command, &tool_input, &value);
                                 let item_id_result = if tool_use_id.is_empty() {
                                     tool_counter += 1;
                                     format!("{current_turn_id}-tool-result-{tool_counter}")
@@ -1517,6 +3997,141 @@ async fn read_persistent_stdout(
                         }
                     }
                 } else if event_type == "result" {
+                    // A transient API failure is re-sent after a backoff instead of ending
+                    // the turn, if the workspace opted in
+                    let api_failure = turn_api_failure.take();
+                    let interrupted = session
+                        .interrupted_threads
+                        .lock()
+                        .await
+                        .contains(&thread_id);
+                    let retry = match retry::transient_failure(&value).or(api_failure) {
+                        Some(reason) if turn_active && !interrupted && !session.replaying => {
+                            retry::retry_delay(&session.entry.settings, retry_attempt + 1)
+                                .map(|delay| (delay, reason))
+                        }
+                        _ => None,
+                    };
+                    // A retried turn is still running; any other result ends it
+                    let timed_out = match retry {
+                        Some(_) => None,
+                        None => session.turn_watch.lock().await.finish(&thread_id),
+                    };
+                    if let Some(ClaudeEvent::Result(result)) = parse_event_line(trimmed) {
+                        let mut turn_usage = usage::turn_usage_from_result(
+                            &workspace_id,
+                            &thread_id,
+                            &current_turn_id,
+                            last_model.clone(),
+                            &result,
+                            reported_total_cost,
+                        );
+                        turn_usage.timings = turn_clock.finish(Instant::now());
+                        let turn_cost = turn_usage.cost_usd;
+                        if !session.replaying {
+                            metrics::record_turn_finished(&turn_usage, result.is_error);
+                            usage::record_turn(turn_usage);
+                            budget::notify_turn_cost(&event_sink, &workspace_id, turn_cost);
+                            if let Some(timeline) = tool_timeline.finish(
+                                &workspace_id,
+                                &thread_id,
+                                &current_turn_id,
+                                now_ms(),
+                            ) {
+                                storage::record_turn_timeline(&timeline);
+                            }
+                            if let Some(edits) = edit_recorder.finish(
+                                &workspace_id,
+                                &thread_id,
+                                &current_turn_id,
+                                now_ms(),
+                            ) {
+                                storage::record_turn_edits(&edits);
+                            }
+                            if let Some(before) = turn_snapshot.take() {
+                                spawn_turn_diff(
+                                    event_sink.clone(),
+                                    before,
+                                    workspace_id.clone(),
+                                    thread_id.clone(),
+                                    current_turn_id.clone(),
+                                    session.entry.settings.read_only,
+                                );
+                            }
+                            if !result.is_error && !interrupted && retry.is_none() {
+                                let summary = result
+                                    .result
+                                    .clone()
+                                    .filter(|text| !text.trim().is_empty())
+                                    .unwrap_or_else(|| full_text.clone());
+                                spawn_auto_commit(
+                                    &event_sink,
+                                    &session.entry,
+                                    &thread_id,
+                                    &current_turn_id,
+                                    &summary,
+                                );
+                                verification::spawn_verification(
+                                    &event_sink,
+                                    &session,
+                                    &thread_id,
+                                    &current_turn_id,
+                                );
+                            }
+                            if retry.is_none() {
+                                let event = if result.is_error {
+                                    WebhookEvent::TurnFailed
+                                } else {
+                                    WebhookEvent::TurnCompleted
+                                };
+                                webhooks::dispatch(
+                                    event_sink.app_handle(),
+                                    &workspace_id,
+                                    event,
+                                    json!({
+                                        "threadId": thread_id,
+                                        "turnId": current_turn_id,
+                                        "subtype": result.subtype,
+                                        "interrupted": interrupted,
+                                        "timedOut": timed_out.is_some(),
+                                        "durationMs": result.duration_ms,
+                                        "costUsd": turn_cost,
+                                        "result": result.result,
+                                    }),
+                                );
+                            }
+                        }
+                        if !result.is_error && session.rate_limits.lock().await.record_success() {
+                            emit_event(
+                                &event_sink,
+                                &workspace_id,
+                                "rateLimit/cleared",
+                                json!({ "threadId": thread_id }),
+                            );
+                        }
+                        reported_total_cost = result.total_cost_usd.unwrap_or(reported_total_cost);
+                        if !session.replaying {
+                            storage::remember_thread_session(
+                                &workspace_id,
+                                &thread_id,
+                                &claude_session_id,
+                                last_model.clone(),
+                                ThreadSessionStatus::Idle,
+                            );
+                        }
+                        // Nobody needs a notification for a turn they stopped themselves, or
+                        // for one that is about to be retried
+                        if retry.is_none()
+                            && !session.take_interrupted(&thread_id).await
+                            && !session.replaying
+                        {
+                            notifications::notify(
+                                event_sink.app_handle(),
+                                &workspace_id,
+                                Notice::from_result(&result),
+                            );
+                        }
+                    }
                     if let Some(usage) = value.get("usage") {
                         last_usage = Some(usage.clone());
                     }
@@ -1578,9 +4193,89 @@ async fn read_persistent_stdout(
                         );
                     }
 
+                    if let Some((delay, reason)) = retry {
+                        retry_attempt += 1;
+                        emit_event(
+                            &event_sink,
+                            &workspace_id,
+                            "item/completed",
+                            json!({
+                                "threadId": thread_id,
+                                "item": {
+                                    "id": item_id,
+                                    "type": "agentMessage",
+                                    "text": full_text,
+                                    "model": last_model,
+                                },
+                            }),
+                        );
+                        emit_event(
+                            &event_sink,
+                            &workspace_id,
+                            "turn/retrying",
+                            json!({
+                                "threadId": thread_id,
+                                "turnId": current_turn_id,
+                                "attempt": retry_attempt,
+                                "maxAttempts": session
+                                    .entry
+                                    .settings
+                                    .retry_policy
+                                    .as_ref()
+                                    .map(|policy| policy.max_attempts),
+                                "delayMs": delay.as_millis() as u64,
+                                "reason": reason,
+                            }),
+                        );
+                        // The thread stays claimed in the turn queue until the retry ends
+                        tokio::spawn(retry_turn(
+                            Arc::clone(&session),
+                            event_sink.clone(),
+                            workspace_id.clone(),
+                            thread_id.clone(),
+                            current_turn_id.clone(),
+                            delay,
+                        ));
+                        turn_active = false;
+                        continue;
+                    }
+                    retry_attempt = 0;
+
+                    // Past the workspace's threshold, compact before the next prompt; the
+                    // thread stays claimed in the turn queue until the compaction's own result
+                    let compacted = std::mem::take(&mut compacting);
+                    let compact = last_reading
+                        .take()
+                        .filter(|reading| reading.done && turn_active && !session.replaying)
+                        .filter(|_| !compacted)
+                        .filter(|_| matches!(session.agent.kind(), AgentKind::Claude))
+                        .and_then(|reading| {
+                            let policy = session.entry.settings.auto_compact.as_ref();
+                            compact_command(policy, &reading).map(|command| (command, reading))
+                        });
+                    if let Some((command, reading)) = compact {
+                        let record = compactions.start(&reading, now_ms());
+                        match session.send_message(&thread_id, &command).await {
+                            Ok(()) => {
+                                report_compaction(&session, &event_sink, &workspace_id, &record);
+                                compacting = true;
+                            }
+                            Err(err) => {
+                                compactions.abandon();
+                                tracing::warn!("failed to compact thread {thread_id}: {err}");
+                            }
+                        }
+                    }
+
                     // Result event signals end of turn
+                    if !compacting {
+                        session.finish_turn(&thread_id).await;
+                    }
                     if turn_active {
-                        if let Some(usage) = last_usage.take().and_then(|u| format_token_usage(u, last_model_usage.as_ref())) {
+                        if let Some(usage) = last_usage
+                            .take()
+                            .and_then(|u| format_token_usage(u, last_model_usage.as_ref()))
+                        {
                             emit_event(
                                 &event_sink,
                                 &workspace_id,
@@ -1610,27 +4305,27 @@ async fn read_persistent_stdout(
                             &event_sink,
                             &workspace_id,
                             "turn/completed",
-                            json!({
-                                "threadId": thread_id,
-                                "turn": { "id": current_turn_id, "threadId": thread_id },
-                            }),
+                            turn_completed_params(&thread_id, &current_turn_id, timed_out.as_ref()),
                         );
 
                         turn_active = false;
+                        turn_span = None;
                     }
                 }
             }
             Err(_) => {
                 // Error reading - process likely ended
+                if compacting && !turn_active {
+                    session.finish_turn(&thread_id).await;
+                }
+                let timed_out = session.turn_watch.lock().await.finish(&thread_id);
                 if turn_active {
+                    session.finish_turn(&thread_id).await;
                     emit_event(
                         &event_sink,
                         &workspace_id,
                         "turn/completed",
-                        json!({
-                            "threadId": thread_id,
-                            "turn": { "id": current_turn_id, "threadId": thread_id },
-                        }),
+                        turn_completed_params(&thread_id, &current_turn_id, timed_out.as_ref()),
                     );
                 }
                 break;
@@ -1639,8 +4334,62 @@ async fn read_persistent_stdout(
     }
 }
 
+/// Re-send a thread's last prompt after `delay`, continuing `turn_id`. An interrupt
+/// during the wait cancels the retry and ends the turn.
+async fn retry_turn(
+    session: Arc<WorkspaceSession>,
+    event_sink: TauriEventSink,
+    workspace_id: String,
+    thread_id: String,
+    turn_id: String,
+    delay: Duration,
+) {
+    tokio::time::sleep(delay).await;
+    let failure = if session.take_interrupted(&thread_id).await {
+        None
+    } else {
+        match session.last_prompt(&thread_id).await {
+            Some(prompt) => {
+                session
+                    .set_pending_turn_id(&thread_id, turn_id.clone())
+                    .await;
+                match session.send_message(&thread_id, &prompt).await {
+                    Ok(()) => return,
+                    Err(err) => Some(err),
+                }
+            }
+            None => Some("the thread's Claude process is gone".to_string()),
+        }
+    };
+    session.finish_turn(&thread_id).await;
+    if let Some(err) = failure {
+        emit_event(
+            &event_sink,
+            &workspace_id,
+            "error",
+            json!({
+                "threadId": thread_id,
+                "turnId": turn_id,
+                "error": { "message": format!("Retry failed: {err}") },
+                "willRetry": false,
+            }),
+        );
+    }
+    emit_event(
+        &event_sink,
+        &workspace_id,
+        "turn/completed",
+        json!({
+            "threadId": thread_id,
+            "turn": { "id": turn_id, "threadId": thread_id },
+        }),
+    );
+}
+
 /// Background task that reads stderr from the persistent Claude CLI session
 /// and emits error events to the frontend.
+///
+/// Process exit is handled by `supervise_persistent_session`, which owns cleanup.
 async fn read_persistent_stderr(
     mut reader: AsyncBufReader<tokio::process::ChildStderr>,
     workspace_id: String,
@@ -1654,8 +4403,7 @@ async fn read_persistent_stderr(
         line.clear();
         match reader.read_line(&mut line).await {
             Ok(0) => {
-                // EOF - process ended, cleanup the session for this thread
-                let _ = session.kill_persistent_session(&thread_id).await;
+                // EOF - process ended
                 break;
             }
             Ok(_) => {
@@ -1663,6 +4411,9 @@ async fn read_persistent_stderr(
                 if trimmed.is_empty() {
                     continue;
                 }
+                session
+                    .log_output(&thread_id, LogStream::Stderr, &line)
+                    .await;
 
                 // Emit stderr message to frontend
                 emit_event(
@@ -1673,15 +4424,285 @@ async fn read_persistent_stderr(
                 );
             }
             Err(_) => {
-                // Error reading - process likely ended, cleanup
-                let _ = session.kill_persistent_session(&thread_id).await;
+                // Error reading - process likely ended
                 break;
             }
         }
     }
 }
 
-fn emit_event(event_sink: &TauriEventSink, workspace_id: &str, method: &str, params: Value) {
+/// Surface a `can_use_tool` control request to the UI and remember it until answered,
+/// unless the workspace's permission preset answers it. Other control requests are
+/// rejected so the CLI does not wait on them. Returns the tool use id that was decided.
+async fn handle_control_request(
+    session: &WorkspaceSession,
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    thread_id: &str,
+    turn_id: &str,
+    value: Value,
+) -> Option<String> {
+    let request_id = value
+        .get("request_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    let control: ControlRequestEvent = match serde_json::from_value(value) {
+        Ok(control) => control,
+        Err(err) => {
+            tracing::warn!("malformed control request: {err}");
+            if !request_id.is_empty() {
+                let _ = session
+                    .reject_control_request(thread_id, &request_id, "malformed control request")
+                    .await;
+            }
+            return None;
+        }
+    };
+    match control.request {
+        ControlRequest::CanUseTool {
+            tool_name,
+            input,
+            tool_use_id,
+            permission_suggestions,
+        } => {
+            if tool_name == EXIT_PLAN_TOOL {
+                let plan = PendingPlan {
+                    thread_id: thread_id.to_string(),
+                    turn_id: turn_id.to_string(),
+                    request_id: control.request_id.clone(),
+                    tool_use_id: tool_use_id.clone(),
+                    plan: plan_text(&input),
+                    proposed_at: now_ms(),
+                };
+                open_interaction(
+                    session,
+                    InteractionKind::Plan,
+                    thread_id,
+                    turn_id,
+                    Some(&control.request_id),
+                    tool_use_id.as_deref(),
+                    &tool_name,
+                )
+                .await;
+                // Register before emitting so an immediate answer finds the request
+                session.control.lock().await.register(PendingPermission {
+                    request_id: control.request_id,
+                    thread_id: thread_id.to_string(),
+                    tool_name,
+                    tool_use_id: tool_use_id.clone(),
+                    input,
+                    suggestions: None,
+                });
+                session.plans.lock().await.propose(plan.clone());
+                let params = json!({ "threadId": thread_id, "turnId": turn_id, "plan": plan });
+                if !session.replaying {
+                    webhooks::dispatch(
+                        event_sink.app_handle(),
+                        workspace_id,
+                        WebhookEvent::PlanPending,
+                        params.clone(),
+                    );
+                }
+                emit_event(event_sink, workspace_id, "item/plan/pending", params);
+                if !session.replaying {
+                    notifications::notify(event_sink.app_handle(), workspace_id, Notice::PlanReady);
+                }
+                return tool_use_id;
+            }
+            let preset = *session.permission_preset.lock().await;
+            // Once its plan is approved, plan mode no longer holds the thread back
+            let plan_approved = session.plans.lock().await.is_approved(thread_id);
+            let preset =
+                preset.filter(|preset| !(plan_approved && *preset == PermissionPreset::Plan));
+            // Guardrails refuse what they cover whatever the preset says
+            let settings = &session.entry.settings;
+            let read_only = settings
+                .read_only
+                .then(|| read_only_decision(&tool_name))
+                .flatten();
+            let path_rules = settings.path_rules.as_ref().and_then(|rules| {
+                path_decision(rules, Path::new(&session.entry.path), &tool_name, &input)
+            });
+            let bash = match (&read_only, &settings.bash_policy) {
+                (None, Some(policy)) if tool_name == BASH_TOOL => {
+                    let command = input.get("command").and_then(Value::as_str).unwrap_or("");
+                    let (action, rule) = bash_policy::evaluate(policy, command);
+                    if !session.replaying {
+                        storage::record_bash_decision(&BashDecision {
+                            timestamp: now_ms(),
+                            workspace_id: workspace_id.to_string(),
+                            thread_id: thread_id.to_string(),
+                            turn_id: turn_id.to_string(),
+                            tool_use_id: tool_use_id.clone(),
+                            command: command.to_string(),
+                            action,
+                            rule: rule.clone(),
+                        });
+                    }
+                    bash_policy::permission(action, rule.as_deref())
+                        .map(|decision| (decision, rule))
+                }
+                _ => None,
+            };
+            let (guardrail, rule) = if read_only.is_some() {
+                (Some("readOnly"), None)
+            } else if path_rules.is_some() {
+                (Some("pathRules"), None)
+            } else if let Some((_, rule)) = &bash {
+                (Some("bashPolicy"), rule.clone())
+            } else {
+                (None, None)
+            };
+            let decision = read_only
+                .or(path_rules)
+                .or(bash.map(|(decision, _)| decision))
+                .or_else(|| preset.and_then(|preset| preset_decision(preset, &tool_name)));
+            if let Some(decision) = decision {
+                let allowed = matches!(decision, PermissionDecision::Allow { .. });
+                let params = json!({
+                    "threadId": thread_id,
+                    "turnId": turn_id,
+                    "requestId": control.request_id,
+                    "toolName": tool_name,
+                    "toolUseId": tool_use_id,
+                    "allowed": allowed,
+                    "preset": preset,
+                    "guardrail": guardrail,
+                    "rule": rule,
+                });
+                session.control.lock().await.register(PendingPermission {
+                    request_id: control.request_id.clone(),
+                    thread_id: thread_id.to_string(),
+                    tool_name,
+                    tool_use_id: tool_use_id.clone(),
+                    input,
+                    suggestions: None,
+                });
+                if let Err(err) = session
+                    .respond_to_permission(thread_id, &control.request_id, decision)
+                    .await
+                {
+                    tracing::warn!("failed to answer by preset: {err}");
+                }
+                emit_event(
+                    event_sink,
+                    workspace_id,
+                    "item/permission/autoResolved",
+                    params,
+                );
+                return tool_use_id;
+            }
+            let params = json!({
+                "threadId": thread_id,
+                "turnId": turn_id,
+                "requestId": control.request_id,
+                "toolName": tool_name,
+                "toolUseId": tool_use_id,
+                "toolInput": input,
+                "canRemember": permission_suggestions.is_some(),
+            });
+            let notice = Notice::PermissionRequested {
+                tool_name: tool_name.clone(),
+            };
+            open_interaction(
+                session,
+                InteractionKind::Permission,
+                thread_id,
+                turn_id,
+                Some(&control.request_id),
+                tool_use_id.as_deref(),
+                &tool_name,
+            )
+            .await;
+            // Register before emitting so an immediate answer finds the request
+            session.control.lock().await.register(PendingPermission {
+                request_id: control.request_id,
+                thread_id: thread_id.to_string(),
+                tool_name,
+                tool_use_id: tool_use_id.clone(),
+                input,
+                suggestions: permission_suggestions,
+            });
+            if !session.replaying {
+                webhooks::dispatch(
+                    event_sink.app_handle(),
+                    workspace_id,
+                    WebhookEvent::PermissionPending,
+                    params.clone(),
+                );
+            }
+            emit_event(
+                event_sink,
+                workspace_id,
+                "item/permission/requestApproval",
+                params,
+            );
+            if !session.replaying {
+                notifications::notify(event_sink.app_handle(), workspace_id, notice);
+            }
+            tool_use_id
+        }
+        _ => {
+            let _ = session
+                .reject_control_request(
+                    thread_id,
+                    &control.request_id,
+                    "unsupported control request",
+                )
+                .await;
+            None
+        }
+    }
+}
+
+/// Drop the thread's unanswered permission requests once its process is gone.
+async fn clear_permission_requests(
+    session: &WorkspaceSession,
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    thread_id: &str,
+) {
+    let dropped = session.control.lock().await.drain_thread(thread_id);
+    for permission in dropped {
+        emit_event(
+            event_sink,
+            workspace_id,
+            "item/permission/resolved",
+            json!({ "threadId": thread_id, "requestId": permission.request_id }),
+        );
+    }
+    session.questions.lock().await.drain_thread(thread_id);
+    session.interactions.lock().await.drain_thread(thread_id);
+    if let Some(plan) = session.plans.lock().await.forget_thread(thread_id) {
+        emit_plan_resolved(event_sink, workspace_id, &plan, false);
+    }
+}
+
+fn emit_plan_resolved(
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    plan: &PendingPlan,
+    approved: bool,
+) {
+    emit_event(
+        event_sink,
+        workspace_id,
+        "item/plan/resolved",
+        json!({
+            "threadId": plan.thread_id,
+            "requestId": plan.request_id,
+            "approved": approved,
+        }),
+    );
+}
+
+pub(crate) fn emit_event(
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    method: &str,
+    params: Value,
+) {
     event_sink.emit_app_server_event(AppServerEvent {
         workspace_id: workspace_id.to_string(),
         message: json!({
@@ -1691,7 +4712,13 @@ fn emit_event(event_sink: &TauriEventSink, workspace_id: &str, method: &str, par
     });
 }
 
-fn emit_event_with_id(event_sink: &TauriEventSink, workspace_id: &str, method: &str, id: u64, params: Value) {
+fn emit_event_with_id(
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    method: &str,
+    id: u64,
+    params: Value,
+) {
     event_sink.emit_app_server_event(AppServerEvent {
         workspace_id: workspace_id.to_string(),
         message: json!({
@@ -1703,9 +4730,7 @@ fn emit_event_with_id(event_sink: &TauriEventSink, workspace_id: &str, method: &
 }
 
 fn build_thread_from_session(entry: &WorkspaceEntry, thread_id: &str) -> Result<Value, String> {
-    let session_path = if let Some((parent_id, agent_id)) =
-        parse_subagent_thread_id(thread_id)
-    {
+    let session_path = if let Some((parent_id, agent_id)) = parse_subagent_thread_id(thread_id) {
         resolve_subagent_path(entry, &parent_id, &agent_id)
     } else {
         resolve_session_path(entry, thread_id)
@@ -1791,10 +4816,7 @@ fn build_thread_from_session(entry: &WorkspaceEntry, thread_id: &str) -> Result<
                     .get(tool_use_id)
                     .cloned()
                     .unwrap_or_else(|| "Tool".to_string());
-                let tool_input = tool_inputs
-                    .get(tool_use_id)
-                    .cloned()
-                    .unwrap_or(Value::Null);
+                let tool_input = tool_inputs.get(tool_use_id).cloned().unwrap_or(Value::Null);
                 // Only skip nested subagent tool results (those with agentId)
                 // Task tool results should be shown - they don't have agentId
                 if extract_subagent_id(&value).is_some() {
@@ -1833,9 +4855,7 @@ fn build_thread_from_session(entry: &WorkspaceEntry, thread_id: &str) -> Result<
                         }
                     }
                     Some("thinking") => {
-                        if let Some(thinking) =
-                            entry.get("thinking").and_then(|v| v.as_str())
-                        {
+                        if let Some(thinking) = entry.get("thinking").and_then(|v| v.as_str()) {
                             let trimmed = thinking.trim();
                             if !trimmed.is_empty() {
                                 let message_id = value
@@ -1854,10 +4874,7 @@ fn build_thread_from_session(entry: &WorkspaceEntry, thread_id: &str) -> Result<
                         }
                     }
                     Some("tool_use") => {
-                        let tool_id = entry
-                            .get("id")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("");
+                        let tool_id = entry.get("id").and_then(|v| v.as_str()).unwrap_or("");
                         let tool_name = entry
                             .get("name")
                             .and_then(|v| v.as_str())
@@ -1874,25 +4891,19 @@ fn build_thread_from_session(entry: &WorkspaceEntry, thread_id: &str) -> Result<
                         }
                         // Don't skip Task tools - we want to show them
                         // (subagent_tool_ids tracking above is still needed for collapsing output)
-                    let id = if tool_id.is_empty() {
-                        format!("{thread_id}-tool-{}", items.len())
-                    } else {
-                        tool_id.to_string()
-                    };
-                    let item_id = id.clone();
-                    let item = build_tool_item(
-                        &id,
-                        &tool_name,
-                        &tool_input,
-                        "running",
-                        None,
-                        None,
-                    );
-                    if let Some(index) = tool_item_indices.get(&item_id) {
-                        items[*index] = item;
-                    } else {
-                        tool_item_indices.insert(item_id, items.len());
-                        items.push(item);
+                        let id = if tool_id.is_empty() {
+                            format!("{thread_id}-tool-{}", items.len())
+                        } else {
+                            tool_id.to_string()
+                        };
+                        let item_id = id.clone();
+                        let item =
+                            build_tool_item(&id, &tool_name, &tool_input, "running", None, None);
+                        if let Some(index) = tool_item_indices.get(&item_id) {
+                            items[*index] = item;
+                        } else {
+                            tool_item_indices.insert(item_id, items.len());
+                            items.push(item);
                         }
                     }
                     _ => {}
@@ -1929,8 +4940,11 @@ fn build_thread_from_session(entry: &WorkspaceEntry, thread_id: &str) -> Result<
         .and_then(|entry| entry.first_prompt)
         .or(preview)
         .unwrap_or_default();
+    let annotations = storage::database_path()
+        .and_then(|path| storage::read_annotations(path, Some(&entry.id), Some(thread_id)).ok())
+        .unwrap_or_default();
 
-    Ok(json!({
+    let mut thread = json!({
         "id": thread_id,
         "preview": preview,
         "createdAt": created_at,
@@ -1942,7 +4956,10 @@ fn build_thread_from_session(entry: &WorkspaceEntry, thread_id: &str) -> Result<
                 "items": items,
             }
         ],
-    }))
+        "annotations": annotations,
+    });
+    redaction::redact_value(&mut thread);
+    Ok(thread)
 }
 
 fn load_sessions_index(entry: &WorkspaceEntry) -> Vec<ClaudeSessionEntry> {
@@ -2038,8 +5055,7 @@ fn scan_project_sessions(entry: &WorkspaceEntry) -> Vec<ClaudeSessionEntry> {
             .and_then(|meta| meta.modified().ok())
             .and_then(|timestamp| timestamp.duration_since(UNIX_EPOCH).ok())
             .map(|duration| duration.as_millis() as i64);
-        let (first_prompt, message_count, git_branch) =
-            scan_session_metadata(&path);
+        let (first_prompt, message_count, git_branch) = scan_session_metadata(&path);
         entries.push(ClaudeSessionEntry {
             session_id,
             file_mtime,
@@ -2166,10 +5182,7 @@ fn resolve_subagent_path(
     }
 }
 
-fn list_subagent_files(
-    entry: &WorkspaceEntry,
-    parent_id: &str,
-) -> Vec<(String, PathBuf, i64)> {
+fn list_subagent_files(entry: &WorkspaceEntry, parent_id: &str) -> Vec<(String, PathBuf, i64)> {
     let mut files = Vec::new();
     let project_dir = match resolve_project_dir(entry) {
         Some(dir) => dir,
@@ -2297,10 +5310,7 @@ fn process_subagent_line(
                 .get(tool_use_id)
                 .cloned()
                 .unwrap_or_else(|| "Tool".to_string());
-            let tool_input = tool_inputs
-                .get(tool_use_id)
-                .cloned()
-                .unwrap_or(Value::Null);
+            let tool_input = tool_inputs.get(tool_use_id).cloned().unwrap_or(Value::Null);
             output = collapse_subagent_output(output, &command, &tool_input, value);
             let item_id = if tool_use_id.is_empty() {
                 *tool_counter += 1;
@@ -2332,10 +5342,7 @@ fn process_subagent_line(
     for entry in content.iter() {
         match entry.get("type").and_then(|v| v.as_str()) {
             Some("tool_use") => {
-                let tool_id = entry
-                    .get("id")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
+                let tool_id = entry.get("id").and_then(|v| v.as_str()).unwrap_or("");
                 let tool_name = entry
                     .get("name")
                     .and_then(|v| v.as_str())
@@ -2431,7 +5438,7 @@ async fn tail_subagent_thread(
     thread_id: String,
     path: PathBuf,
     event_sink: TauriEventSink,
-    shutdown: watch::Receiver<bool>,
+    shutdown: CancellationToken,
 ) {
     let turn_id = Uuid::new_v4().to_string();
     emit_event(
@@ -2466,7 +5473,7 @@ async fn tail_subagent_thread(
     let mut tool_counter: usize = 0;
 
     loop {
-        if *shutdown.borrow() {
+        if shutdown.is_cancelled() {
             break;
         }
         line.clear();
@@ -2513,7 +5520,7 @@ async fn watch_workspace_threads(
     workspace_id: String,
     entry: WorkspaceEntry,
     event_sink: TauriEventSink,
-    shutdown: watch::Receiver<bool>,
+    shutdown: CancellationToken,
 ) {
     let mut known_sessions: HashSet<String> = HashSet::new();
     let mut known_subagents: HashSet<String> = HashSet::new();
@@ -2533,7 +5540,7 @@ async fn watch_workspace_threads(
 
     let mut ticker = interval(Duration::from_millis(1000));
     loop {
-        if *shutdown.borrow() {
+        if shutdown.is_cancelled() {
             break;
         }
         ticker.tick().await;
@@ -2608,16 +5615,17 @@ fn parse_iso_timestamp(value: Option<&str>) -> Option<i64> {
 fn value_to_millis(value: &Value) -> Option<i64> {
     match value {
         Value::String(value) => parse_iso_timestamp(Some(value)),
-        Value::Number(value) => value.as_i64().map(|raw| if raw < 1_000_000_000_000 { raw * 1000 } else { raw }),
+        Value::Number(value) => value.as_i64().map(|raw| {
+            if raw < 1_000_000_000_000 {
+                raw * 1000
+            } else {
+                raw
+            }
+        }),
         _ => None,
     }
 }
 
-fn resolve_project_dir(entry: &WorkspaceEntry) -> Option<PathBuf> {
-    let projects_root = resolve_default_claude_home()?.join("projects");
-    Some(projects_root.join(encode_project_path(&entry.path)))
-}
-
 fn resolve_sessions_index_path(entry: &WorkspaceEntry) -> Option<PathBuf> {
     let project_dir = resolve_project_dir(entry)?;
     let index_path = project_dir.join("sessions-index.json");
@@ -2628,7 +5636,7 @@ fn resolve_sessions_index_path(entry: &WorkspaceEntry) -> Option<PathBuf> {
     }
 }
 
-fn resolve_session_path(entry: &WorkspaceEntry, thread_id: &str) -> Option<PathBuf> {
+pub(crate) fn resolve_session_path(entry: &WorkspaceEntry, thread_id: &str) -> Option<PathBuf> {
     if let Some(index_path) = resolve_sessions_index_path(entry) {
         if let Ok(data) = std::fs::read_to_string(index_path) {
             if let Ok(value) = serde_json::from_str::<Value>(&data) {
@@ -2658,15 +5666,6 @@ fn session_exists(entry: &WorkspaceEntry, thread_id: &str) -> bool {
     resolve_session_path(entry, thread_id).is_some()
 }
 
-fn encode_project_path(path: &str) -> String {
-    let normalized = path.replace('\\', "/");
-    if normalized.starts_with('/') {
-        format!("-{}", normalized.trim_start_matches('/').replace('/', "-"))
-    } else {
-        normalized.replace('/', "-")
-    }
-}
-
 fn extract_text_from_message(message: &Value) -> String {
     let content = normalize_message_content(message);
     extract_text_from_content(&content)
@@ -2722,7 +5721,10 @@ fn tool_result_output(value: &Value) -> String {
             .iter()
             .filter_map(|entry| {
                 if entry.get("type").and_then(|v| v.as_str()) == Some("text") {
-                    entry.get("text").and_then(|v| v.as_str()).map(|v| v.to_string())
+                    entry
+                        .get("text")
+                        .and_then(|v| v.as_str())
+                        .map(|v| v.to_string())
                 } else {
                     None
                 }
@@ -2902,7 +5904,10 @@ fn build_tool_item(
         });
         if let Value::Object(ref mut map) = item {
             if let Some(output) = output {
-                map.insert("aggregatedOutput".to_string(), Value::String(output.to_string()));
+                map.insert(
+                    "aggregatedOutput".to_string(),
+                    Value::String(output.to_string()),
+                );
             }
         }
         return item;
@@ -2912,12 +5917,23 @@ fn build_tool_item(
         normalized.as_str(),
         "write" | "edit" | "multiedit" | "notebookedit"
     ) {
-        let paths = extract_file_paths(tool_input);
-        let kind = if normalized == "write" { "add" } else { "modify" };
-        let changes = paths
-            .into_iter()
-            .map(|path| json!({ "path": path, "kind": kind }))
-            .collect::<Vec<_>>();
+        let kind = if normalized == "write" {
+            "add"
+        } else {
+            "modify"
+        };
+        let changes = match edit_hunks::parse_file_edit(id, tool_name.trim(), tool_input) {
+            Some(edit) => vec![json!({
+                "path": edit.path,
+                "kind": kind,
+                "diff": edit_hunks::unified_diff(&edit),
+                "hunks": edit.hunks,
+            })],
+            None => extract_file_paths(tool_input)
+                .into_iter()
+                .map(|path| json!({ "path": path, "kind": kind }))
+                .collect::<Vec<_>>(),
+        };
         let mut item = json!({
             "id": id,
             "type": "fileChange",
@@ -2926,7 +5942,10 @@ fn build_tool_item(
         });
         if let Value::Object(ref mut map) = item {
             if let Some(output) = output {
-                map.insert("aggregatedOutput".to_string(), Value::String(output.to_string()));
+                map.insert(
+                    "aggregatedOutput".to_string(),
+                    Value::String(output.to_string()),
+                );
             }
             if !tool_input.is_null() {
                 map.insert("toolInput".to_string(), tool_input.clone());
@@ -2944,7 +5963,10 @@ fn build_tool_item(
     });
     if let Value::Object(ref mut map) = item {
         if let Some(output) = output {
-            map.insert("aggregatedOutput".to_string(), Value::String(output.to_string()));
+            map.insert(
+                "aggregatedOutput".to_string(),
+                Value::String(output.to_string()),
+            );
         }
     }
     item
@@ -3005,8 +6027,10 @@ fn format_token_usage(raw: Value, model_usage: Option<&Value>) -> Option<Value>
     let input_tokens = usage_number(&map, &["input_tokens", "inputTokens"]);
     let output_tokens = usage_number(&map, &["output_tokens", "outputTokens"]);
     let cached_read = usage_number(&map, &["cache_read_input_tokens", "cacheReadInputTokens"]);
-    let cached_create =
-        usage_number(&map, &["cache_creation_input_tokens", "cacheCreationInputTokens"]);
+    let cached_create = usage_number(
+        &map,
+        &["cache_creation_input_tokens", "cacheCreationInputTokens"],
+    );
     let cached_input_tokens = cached_read + cached_create;
     let reasoning_output_tokens =
         usage_number(&map, &["reasoning_output_tokens", "reasoningOutputTokens"]);
@@ -3054,7 +6078,6 @@ fn usage_number(map: &Map<String, Value>, keys: &[&str]) -> i64 {
     0
 }
 
-
 async fn build_review_prompt(
     workspace_id: &str,
     target: &Value,
@@ -3115,49 +6138,46 @@ fn resolve_permissions_path(
         .ok_or_else(|| "Unable to resolve Claude settings path".to_string())
 }
 
-fn read_settings_json(path: &Path) -> Result<Map<String, Value>, String> {
-    if !path.exists() {
-        return Ok(Map::new());
-    }
-    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
-    let value: Value = serde_json::from_str(&contents).map_err(|err| err.to_string())?;
-    match value {
-        Value::Object(map) => Ok(map),
-        _ => Ok(Map::new()),
-    }
-}
-
-fn write_settings_json(path: &Path, settings: &Map<String, Value>) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
-    }
-    let contents = serde_json::to_string_pretty(settings).map_err(|err| err.to_string())?;
-    std::fs::write(path, contents).map_err(|err| err.to_string())
-}
-
-fn archived_threads_path(state: &State<'_, AppState>) -> Result<PathBuf, String> {
-    state
-        .settings_path
-        .parent()
-        .map(|path| path.join("archived_threads.json"))
-        .ok_or_else(|| "Unable to resolve app data dir.".to_string())
-}
-
-fn read_archived_threads(path: &Path) -> Result<HashMap<String, Vec<String>>, String> {
-    if !path.exists() {
-        return Ok(HashMap::new());
-    }
-    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
-    serde_json::from_str(&contents).map_err(|err| err.to_string())
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings};
+
+    #[tokio::test]
+    async fn read_only_turn_reverts_edit_made_as_the_turn_starts() {
+        let root = env::temp_dir().join(format!("claude-code-monitor-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("create temp repo root");
+        git2::Repository::init(&root).expect("init repo");
+        fs::write(root.join("notes.txt"), "before\n").expect("write file");
+        let entry = WorkspaceEntry {
+            id: Uuid::new_v4().to_string(),
+            name: "test-workspace".to_string(),
+            path: root.to_string_lossy().to_string(),
+            claude_bin: None,
+            model: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            archived_at: None,
+            pinned: false,
+            settings: WorkspaceSettings {
+                read_only: true,
+                ..WorkspaceSettings::default()
+            },
+        };
 
-fn write_archived_threads(
-    path: &Path,
-    data: &HashMap<String, Vec<String>>,
-) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        let (repo_root, before) = take_turn_snapshot(&entry).await.expect("snapshot");
+        // The turn's first tool call, before the reader has seen any of its events
+        fs::write(root.join("notes.txt"), "after\n").expect("edit file");
+        let (diff, restore) = diff_turn(&repo_root, &before, true).expect("diff turn");
+
+        assert_eq!(diff.files.len(), 1);
+        assert_eq!(diff.files[0].path, "notes.txt");
+        assert_eq!(restore, Some(Ok(Vec::new())));
+        assert_eq!(
+            fs::read_to_string(root.join("notes.txt")).expect("read file"),
+            "before\n"
+        );
+        let _ = fs::remove_dir_all(&root);
     }
-    let contents = serde_json::to_string_pretty(data).map_err(|err| err.to_string())?;
-    std::fs::write(path, contents).map_err(|err| err.to_string())
 }
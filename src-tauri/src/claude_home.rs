@@ -36,15 +36,21 @@ pub(crate) fn resolve_default_claude_home() -> Option<PathBuf> {
     resolve_home_dir().map(|home| home.join(".claude"))
 }
 
-fn resolve_home_dir() -> Option<PathBuf> {
-    if let Ok(value) = env::var("HOME") {
-        if !value.trim().is_empty() {
-            return Some(PathBuf::from(value));
-        }
-    }
-    if let Ok(value) = env::var("USERPROFILE") {
-        if !value.trim().is_empty() {
-            return Some(PathBuf::from(value));
+/// Resolve the user's home directory.
+///
+/// Windows shells such as Git Bash export a POSIX-style `HOME` that native processes
+/// cannot use, so `USERPROFILE` wins there; everywhere else `HOME` is authoritative.
+pub(crate) fn resolve_home_dir() -> Option<PathBuf> {
+    let keys: [&str; 2] = if cfg!(windows) {
+        ["USERPROFILE", "HOME"]
+    } else {
+        ["HOME", "USERPROFILE"]
+    };
+    for key in keys {
+        if let Ok(value) = env::var(key) {
+            if !value.trim().is_empty() {
+                return Some(PathBuf::from(value));
+            }
         }
     }
     None
@@ -0,0 +1,481 @@
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use ignore::WalkBuilder;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+
+use crate::claude_home::resolve_default_claude_home;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::WorkspaceEntry;
+
+const MEMORY_FILE: &str = "CLAUDE.md";
+const LOCAL_MEMORY_FILE: &str = "CLAUDE.local.md";
+/// Nested memory files deeper than this are unlikely and not worth the walk.
+const NESTED_MAX_DEPTH: usize = 8;
+const BACKUPS_KEPT: usize = 20;
+
+/// Why a memory file applies to the workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ClaudeMdKind {
+    /// `~/.claude/CLAUDE.md`, loaded in every project.
+    User,
+    /// `CLAUDE.md` or `.claude/CLAUDE.md` at the workspace root.
+    Project,
+    /// `CLAUDE.local.md` at the workspace root.
+    Local,
+    /// A directory above the workspace; the CLI loads these on startup too.
+    Ancestor,
+    /// A subdirectory; loaded when Claude reads files under it.
+    Nested,
+    /// The main checkout a worktree was created from.
+    WorktreeParent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClaudeMdFile {
+    pub(crate) path: PathBuf,
+    pub(crate) kind: ClaudeMdKind,
+    /// Path relative to the workspace when the file lives inside it.
+    pub(crate) relative_path: Option<String>,
+    /// Root-level candidates are listed even when missing, so they can be created.
+    pub(crate) exists: bool,
+    pub(crate) size_bytes: u64,
+    pub(crate) line_count: usize,
+    pub(crate) modified_at: Option<i64>,
+}
+
+fn should_skip_dir(name: &str) -> bool {
+    matches!(
+        name,
+        ".git" | "node_modules" | "dist" | "target" | "release-artifacts"
+    )
+}
+
+/// Every memory file the CLI would consider for a session started in `workspace`.
+pub(crate) fn discover_claude_md(
+    workspace: &Path,
+    worktree_parent: Option<&Path>,
+    claude_home: Option<&Path>,
+) -> Vec<ClaudeMdFile> {
+    let mut files = Vec::new();
+    if let Some(claude_home) = claude_home {
+        files.push(describe(
+            claude_home.join(MEMORY_FILE),
+            ClaudeMdKind::User,
+            workspace,
+        ));
+    }
+    for (path, kind) in [
+        (workspace.join(MEMORY_FILE), ClaudeMdKind::Project),
+        (
+            workspace.join(".claude").join(MEMORY_FILE),
+            ClaudeMdKind::Project,
+        ),
+        (workspace.join(LOCAL_MEMORY_FILE), ClaudeMdKind::Local),
+    ] {
+        files.push(describe(path, kind, workspace));
+    }
+    for ancestor in workspace.ancestors().skip(1) {
+        // Like the CLI, stop short of the filesystem root
+        if ancestor.parent().is_none() {
+            break;
+        }
+        for name in [MEMORY_FILE, LOCAL_MEMORY_FILE] {
+            let path = ancestor.join(name);
+            if path.is_file() {
+                files.push(describe(path, ClaudeMdKind::Ancestor, workspace));
+            }
+        }
+    }
+    if let Some(parent) = worktree_parent {
+        for path in [
+            parent.join(MEMORY_FILE),
+            parent.join(".claude").join(MEMORY_FILE),
+            parent.join(LOCAL_MEMORY_FILE),
+        ] {
+            if path.is_file() {
+                files.push(describe(path, ClaudeMdKind::WorktreeParent, workspace));
+            }
+        }
+    }
+    files.extend(
+        find_nested(workspace)
+            .into_iter()
+            .map(|path| describe(path, ClaudeMdKind::Nested, workspace)),
+    );
+    files
+}
+
+fn find_nested(root: &Path) -> Vec<PathBuf> {
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .follow_links(false)
+        .require_git(false)
+        .max_depth(Some(NESTED_MAX_DEPTH))
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                let name = entry.file_name().to_string_lossy();
+                return !should_skip_dir(&name);
+            }
+            true
+        })
+        .build();
+    let mut nested: Vec<PathBuf> = walker
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            name == MEMORY_FILE || name == LOCAL_MEMORY_FILE
+        })
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            // Root-level files are already listed as project memory
+            let parent = path.parent();
+            parent != Some(root) && parent != Some(root.join(".claude").as_path())
+        })
+        .collect();
+    nested.sort();
+    nested
+}
+
+fn describe(path: PathBuf, kind: ClaudeMdKind, workspace: &Path) -> ClaudeMdFile {
+    let metadata = std::fs::metadata(&path).ok().filter(|meta| meta.is_file());
+    let line_count = metadata
+        .as_ref()
+        .and_then(|_| std::fs::read_to_string(&path).ok())
+        .map(|contents| contents.lines().count())
+        .unwrap_or(0);
+    ClaudeMdFile {
+        relative_path: path
+            .strip_prefix(workspace)
+            .ok()
+            .map(|relative| relative.to_string_lossy().replace('\\', "/")),
+        kind,
+        exists: metadata.is_some(),
+        size_bytes: metadata.as_ref().map(|meta| meta.len()).unwrap_or(0),
+        line_count,
+        modified_at: metadata.as_ref().and_then(modified_millis),
+        path,
+    }
+}
+
+fn modified_millis(metadata: &std::fs::Metadata) -> Option<i64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as i64)
+}
+
+/// Replace `path` with `content`, copying the previous version into `backup_dir` first.
+/// `expected_modified_at` guards against overwriting an edit made outside the monitor
+/// since the file was read.
+pub(crate) fn write_claude_md_file(
+    path: &Path,
+    content: &str,
+    expected_modified_at: Option<i64>,
+    backup_dir: &Path,
+) -> Result<ClaudeMdFile, String> {
+    let current = std::fs::metadata(path).ok();
+    if let Some(expected) = expected_modified_at {
+        let actual = current.as_ref().and_then(modified_millis);
+        if actual != Some(expected) {
+            return Err(format!(
+                "{} changed on disk since it was loaded",
+                path.display()
+            ));
+        }
+    }
+    if current.is_some() {
+        backup(path, backup_dir)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let temp_path = path.with_extension("md.tmp");
+    std::fs::write(&temp_path, content).map_err(|err| err.to_string())?;
+    std::fs::rename(&temp_path, path).map_err(|err| err.to_string())?;
+    let metadata = std::fs::metadata(path).map_err(|err| err.to_string())?;
+    Ok(ClaudeMdFile {
+        path: path.to_path_buf(),
+        kind: ClaudeMdKind::Project,
+        relative_path: None,
+        exists: true,
+        size_bytes: metadata.len(),
+        line_count: content.lines().count(),
+        modified_at: modified_millis(&metadata),
+    })
+}
+
+fn backup(path: &Path, backup_dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(backup_dir).map_err(|err| err.to_string())?;
+    let label: String = path
+        .to_string_lossy()
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '.' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%3f");
+    std::fs::copy(
+        path,
+        backup_dir.join(format!("{stamp}-{}", label.trim_matches('_'))),
+    )
+    .map_err(|err| err.to_string())?;
+
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(backup_dir)
+        .map_err(|err| err.to_string())?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .collect();
+    backups.sort();
+    let excess = backups.len().saturating_sub(BACKUPS_KEPT);
+    for old in backups.into_iter().take(excess) {
+        let _ = std::fs::remove_file(old);
+    }
+    Ok(())
+}
+
+struct MemoryContext {
+    workspace: PathBuf,
+    worktree_parent: Option<PathBuf>,
+    backup_dir: PathBuf,
+}
+
+impl MemoryContext {
+    fn discover(&self) -> Vec<ClaudeMdFile> {
+        discover_claude_md(
+            &self.workspace,
+            self.worktree_parent.as_deref(),
+            resolve_default_claude_home().as_deref(),
+        )
+    }
+
+    /// Only files discovery would list may be read or written through these commands.
+    fn resolve(&self, path: &str) -> Result<ClaudeMdFile, String> {
+        let requested = PathBuf::from(path);
+        self.discover()
+            .into_iter()
+            .find(|file| file.path == requested)
+            .ok_or_else(|| format!("{path} is not a memory file for this workspace"))
+    }
+}
+
+async fn memory_context(state: &AppState, workspace_id: &str) -> Result<MemoryContext, String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry: &WorkspaceEntry = workspaces.get(workspace_id).ok_or("workspace not found")?;
+    let worktree_parent = entry
+        .parent_id
+        .as_ref()
+        .filter(|_| entry.kind.is_worktree())
+        .and_then(|parent_id| workspaces.get(parent_id))
+        .map(|parent| PathBuf::from(&parent.path));
+    let backup_dir = state
+        .db_path
+        .parent()
+        .ok_or_else(|| "Unable to resolve app data dir.".to_string())?
+        .join("workspaces")
+        .join(&entry.id)
+        .join("claude-md-backups");
+    Ok(MemoryContext {
+        workspace: PathBuf::from(&entry.path),
+        worktree_parent,
+        backup_dir,
+    })
+}
+
+#[tauri::command]
+pub(crate) async fn list_claude_md_files(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_claude_md_files",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let context = memory_context(&state, &workspace_id).await?;
+    let files = tokio::task::spawn_blocking(move || context.discover())
+        .await
+        .map_err(|err| err.to_string())?;
+    serde_json::to_value(files).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn read_claude_md(
+    workspace_id: String,
+    path: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "read_claude_md",
+            json!({ "workspaceId": workspace_id, "path": path }),
+        )
+        .await;
+    }
+
+    let context = memory_context(&state, &workspace_id).await?;
+    tokio::task::spawn_blocking(move || {
+        let file = context.resolve(&path)?;
+        let content = if file.exists {
+            std::fs::read_to_string(&file.path).map_err(|err| err.to_string())?
+        } else {
+            String::new()
+        };
+        Ok(json!({ "file": file, "content": content }))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub(crate) async fn write_claude_md(
+    workspace_id: String,
+    path: String,
+    content: String,
+    expected_modified_at: Option<i64>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "write_claude_md",
+            json!({
+                "workspaceId": workspace_id,
+                "path": path,
+                "content": content,
+                "expectedModifiedAt": expected_modified_at,
+            }),
+        )
+        .await;
+    }
+
+    let context = memory_context(&state, &workspace_id).await?;
+    tokio::task::spawn_blocking(move || {
+        let file = context.resolve(&path)?;
+        let written = write_claude_md_file(
+            &file.path,
+            &content,
+            expected_modified_at,
+            &context.backup_dir,
+        )?;
+        serde_json::to_value(ClaudeMdFile {
+            kind: file.kind,
+            relative_path: file.relative_path,
+            ..written
+        })
+        .map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn temp_root() -> PathBuf {
+        std::env::temp_dir().join(format!("claude-md-{}", Uuid::new_v4()))
+    }
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn discovers_memory_files_around_a_worktree() {
+        let root = temp_root();
+        let home = root.join("home").join(".claude");
+        let parent = root.join("repo");
+        let workspace = root.join("worktrees").join("feature");
+        write(&home.join(MEMORY_FILE), "# Global\n");
+        write(&parent.join(MEMORY_FILE), "# Repo\n");
+        write(&root.join(MEMORY_FILE), "# Above\n");
+        write(&workspace.join(MEMORY_FILE), "# Project\nUse pnpm.\n");
+        write(&workspace.join("packages/api").join(MEMORY_FILE), "# API\n");
+        write(
+            &workspace.join("node_modules/dep").join(MEMORY_FILE),
+            "# Dep\n",
+        );
+
+        let files = discover_claude_md(&workspace, Some(&parent), Some(&home));
+        let kinds: Vec<_> = files
+            .iter()
+            .filter(|file| file.exists)
+            .map(|file| (file.kind, file.path.clone()))
+            .collect();
+        assert!(kinds.contains(&(ClaudeMdKind::User, home.join(MEMORY_FILE))));
+        assert!(kinds.contains(&(ClaudeMdKind::Project, workspace.join(MEMORY_FILE))));
+        assert!(kinds.contains(&(ClaudeMdKind::Ancestor, root.join(MEMORY_FILE))));
+        assert!(kinds.contains(&(ClaudeMdKind::WorktreeParent, parent.join(MEMORY_FILE))));
+        assert!(kinds.contains(&(
+            ClaudeMdKind::Nested,
+            workspace.join("packages/api").join(MEMORY_FILE)
+        )));
+        assert!(!kinds
+            .iter()
+            .any(|(_, path)| path.starts_with(workspace.join("node_modules"))));
+
+        let project = files
+            .iter()
+            .find(|file| file.path == workspace.join(MEMORY_FILE))
+            .unwrap();
+        assert_eq!(project.line_count, 2);
+        assert_eq!(project.relative_path.as_deref(), Some("CLAUDE.md"));
+        let local = files
+            .iter()
+            .find(|file| file.kind == ClaudeMdKind::Local)
+            .unwrap();
+        assert!(!local.exists);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn writes_with_backup_and_detects_conflicts() {
+        let root = temp_root();
+        let path = root.join(MEMORY_FILE);
+        let backups = root.join("backups");
+        write(&path, "old\n");
+        let loaded = modified_millis(&std::fs::metadata(&path).unwrap());
+
+        let written = write_claude_md_file(&path, "new\n", loaded, &backups).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new\n");
+        let saved: Vec<_> = std::fs::read_dir(&backups).unwrap().collect();
+        assert_eq!(saved.len(), 1);
+        let saved = saved.into_iter().next().unwrap().unwrap().path();
+        assert_eq!(std::fs::read_to_string(saved).unwrap(), "old\n");
+
+        let stale = written.modified_at.map(|modified| modified - 1_000);
+        assert!(write_claude_md_file(&path, "newer\n", stale, &backups).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new\n");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,491 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, State};
+
+use crate::claude_home::{resolve_default_claude_home, resolve_workspace_claude_home};
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::WorkspaceEntry;
+
+const SETTINGS_FILE: &str = "settings.json";
+const LOCAL_SETTINGS_FILE: &str = "settings.local.json";
+
+/// Where a Claude settings file lives, lowest precedence first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SettingsScope {
+    /// `~/.claude/settings.json`, shared by every project.
+    User,
+    /// `<project>/.claude/settings.json`, usually checked in.
+    Project,
+    /// `<project>/.claude/settings.local.json`, personal and git-ignored.
+    Local,
+}
+
+impl SettingsScope {
+    const ALL: [SettingsScope; 3] = [Self::User, Self::Project, Self::Local];
+}
+
+/// The subset of settings the monitor edits, read from merged or single-scope JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClaudeSettings {
+    #[serde(default)]
+    pub(crate) permissions: PermissionSettings,
+    #[serde(default)]
+    pub(crate) env: BTreeMap<String, String>,
+    /// Hook configuration keyed by event name, kept as the CLI's JSON shape.
+    #[serde(default)]
+    pub(crate) hooks: Value,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PermissionSettings {
+    #[serde(default)]
+    pub(crate) allow: Vec<String>,
+    #[serde(default)]
+    pub(crate) deny: Vec<String>,
+    #[serde(default)]
+    pub(crate) ask: Vec<String>,
+    #[serde(default)]
+    pub(crate) default_mode: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScopeSettings {
+    pub(crate) scope: SettingsScope,
+    pub(crate) path: PathBuf,
+    pub(crate) exists: bool,
+    pub(crate) settings: ClaudeSettings,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClaudeSettingsSnapshot {
+    /// What the CLI sees after merging every scope.
+    pub(crate) effective: ClaudeSettings,
+    pub(crate) scopes: Vec<ScopeSettings>,
+}
+
+/// One typed change to a single scope's file. Other keys in the file are preserved.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "key", content = "value", rename_all = "camelCase")]
+pub(crate) enum ClaudeSettingsUpdate {
+    Permissions(PermissionSettings),
+    Env(BTreeMap<String, String>),
+    Hooks(Value),
+    Model(Option<String>),
+}
+
+/// Paths of the three settings files for a project whose `.claude` dir is `claude_dir`.
+pub(crate) fn scope_path(scope: SettingsScope, claude_dir: &Path) -> Option<PathBuf> {
+    match scope {
+        SettingsScope::User => resolve_default_claude_home().map(|home| home.join(SETTINGS_FILE)),
+        SettingsScope::Project => Some(claude_dir.join(SETTINGS_FILE)),
+        SettingsScope::Local => Some(claude_dir.join(LOCAL_SETTINGS_FILE)),
+    }
+}
+
+/// The `.claude` dir the CLI uses for a workspace; worktrees share their parent's.
+pub(crate) fn workspace_claude_dir(entry: &WorkspaceEntry, parent_path: Option<&str>) -> PathBuf {
+    resolve_workspace_claude_home(entry, parent_path)
+        .unwrap_or_else(|| PathBuf::from(&entry.path).join(".claude"))
+}
+
+/// Read a settings file. A missing file is empty; malformed JSON is an error so a later
+/// write never clobbers a file the user is halfway through editing.
+pub(crate) fn read_settings_file(path: &Path) -> Result<Map<String, Value>, String> {
+    if !path.exists() {
+        return Ok(Map::new());
+    }
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    if contents.trim().is_empty() {
+        return Ok(Map::new());
+    }
+    match serde_json::from_str(&contents) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(format!("{} is not a JSON object", path.display())),
+        Err(err) => Err(format!("{} is not valid JSON: {err}", path.display())),
+    }
+}
+
+/// Write through a temp file and rename so the CLI never reads a half-written file.
+pub(crate) fn write_settings_file(
+    path: &Path,
+    settings: &Map<String, Value>,
+) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let mut contents = serde_json::to_string_pretty(settings).map_err(|err| err.to_string())?;
+    contents.push('\n');
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, contents).map_err(|err| err.to_string())?;
+    std::fs::rename(&temp_path, path).map_err(|err| err.to_string())
+}
+
+/// Merge scopes the way the CLI does: later scopes win for scalars, objects merge key by
+/// key, and arrays (permission rules, for one) accumulate without duplicates.
+pub(crate) fn merge_settings(layers: &[Map<String, Value>]) -> Map<String, Value> {
+    let mut merged = Map::new();
+    for layer in layers {
+        merge_into(&mut merged, layer);
+    }
+    merged
+}
+
+fn merge_into(target: &mut Map<String, Value>, layer: &Map<String, Value>) {
+    for (key, value) in layer {
+        match (target.get_mut(key), value) {
+            (Some(Value::Object(existing)), Value::Object(incoming)) => {
+                merge_into(existing, incoming);
+            }
+            (Some(Value::Array(existing)), Value::Array(incoming)) => {
+                for item in incoming {
+                    if !existing.contains(item) {
+                        existing.push(item.clone());
+                    }
+                }
+            }
+            _ => {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Typed view of a settings map; fields with an unexpected shape read as defaults.
+pub(crate) fn typed_settings(settings: &Map<String, Value>) -> ClaudeSettings {
+    let field = |key: &str| settings.get(key).cloned().unwrap_or(Value::Null);
+    ClaudeSettings {
+        permissions: serde_json::from_value(field("permissions")).unwrap_or_default(),
+        env: settings
+            .get("env")
+            .and_then(|env| env.as_object())
+            .map(|env| {
+                env.iter()
+                    .filter_map(|(key, value)| match value {
+                        Value::String(text) => Some((key.clone(), text.clone())),
+                        Value::Number(_) | Value::Bool(_) => Some((key.clone(), value.to_string())),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        hooks: field("hooks"),
+        model: settings
+            .get("model")
+            .and_then(|model| model.as_str())
+            .map(str::to_string),
+    }
+}
+
+/// Apply `update` to one scope's settings map, leaving unrelated keys alone.
+pub(crate) fn apply_update(
+    settings: &mut Map<String, Value>,
+    update: ClaudeSettingsUpdate,
+) -> Result<(), String> {
+    match update {
+        ClaudeSettingsUpdate::Permissions(permissions) => {
+            let entry = settings.entry("permissions").or_insert_with(|| json!({}));
+            let Some(existing) = entry.as_object_mut() else {
+                return Err("`permissions` is not an object".to_string());
+            };
+            for (key, rules) in [
+                ("allow", permissions.allow),
+                ("deny", permissions.deny),
+                ("ask", permissions.ask),
+            ] {
+                let rules = normalize_rules(rules);
+                if rules.is_empty() {
+                    existing.remove(key);
+                } else {
+                    existing.insert(key.to_string(), json!(rules));
+                }
+            }
+            match permissions
+                .default_mode
+                .filter(|mode| !mode.trim().is_empty())
+            {
+                Some(mode) => existing.insert("defaultMode".to_string(), json!(mode.trim())),
+                None => existing.remove("defaultMode"),
+            };
+            if existing.is_empty() {
+                settings.remove("permissions");
+            }
+        }
+        ClaudeSettingsUpdate::Env(env) => {
+            if env.keys().any(|key| key.trim().is_empty()) {
+                return Err("environment variable names must not be empty".to_string());
+            }
+            if env.is_empty() {
+                settings.remove("env");
+            } else {
+                settings.insert("env".to_string(), json!(env));
+            }
+        }
+        ClaudeSettingsUpdate::Hooks(hooks) => match hooks {
+            Value::Null => {
+                settings.remove("hooks");
+            }
+            Value::Object(_) => {
+                settings.insert("hooks".to_string(), hooks);
+            }
+            _ => return Err("`hooks` must be an object keyed by event".to_string()),
+        },
+        ClaudeSettingsUpdate::Model(model) => {
+            match model
+                .map(|model| model.trim().to_string())
+                .filter(|model| !model.is_empty())
+            {
+                Some(model) => settings.insert("model".to_string(), json!(model)),
+                None => settings.remove("model"),
+            };
+        }
+    }
+    Ok(())
+}
+
+/// Trim rules and drop blanks and duplicates, keeping the user's order.
+fn normalize_rules(rules: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for rule in rules {
+        let rule = rule.trim().to_string();
+        if !rule.is_empty() && !normalized.contains(&rule) {
+            normalized.push(rule);
+        }
+    }
+    normalized
+}
+
+pub(crate) fn read_snapshot(claude_dir: &Path) -> Result<ClaudeSettingsSnapshot, String> {
+    let mut layers = Vec::new();
+    let mut scopes = Vec::new();
+    for scope in SettingsScope::ALL {
+        let Some(path) = scope_path(scope, claude_dir) else {
+            continue;
+        };
+        let settings = read_settings_file(&path)?;
+        scopes.push(ScopeSettings {
+            scope,
+            exists: path.is_file(),
+            settings: typed_settings(&settings),
+            path,
+        });
+        layers.push(settings);
+    }
+    Ok(ClaudeSettingsSnapshot {
+        effective: typed_settings(&merge_settings(&layers)),
+        scopes,
+    })
+}
+
+pub(crate) fn update_scope(
+    claude_dir: &Path,
+    scope: SettingsScope,
+    update: ClaudeSettingsUpdate,
+) -> Result<ScopeSettings, String> {
+    let path = scope_path(scope, claude_dir)
+        .ok_or_else(|| "Unable to resolve Claude settings path".to_string())?;
+    let mut settings = read_settings_file(&path)?;
+    apply_update(&mut settings, update)?;
+    write_settings_file(&path, &settings)?;
+    Ok(ScopeSettings {
+        scope,
+        exists: true,
+        settings: typed_settings(&settings),
+        path,
+    })
+}
+
+/// A workspace's checkout path and the `.claude` dir its settings live in.
+pub(crate) async fn resolve_workspace_dirs(
+    state: &AppState,
+    workspace_id: &str,
+) -> Result<(PathBuf, PathBuf), String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces.get(workspace_id).ok_or("workspace not found")?;
+    let parent_path = entry
+        .parent_id
+        .as_ref()
+        .and_then(|parent_id| workspaces.get(parent_id))
+        .map(|parent| parent.path.clone());
+    Ok((
+        PathBuf::from(&entry.path),
+        workspace_claude_dir(entry, parent_path.as_deref()),
+    ))
+}
+
+#[tauri::command]
+pub(crate) async fn get_claude_settings(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "get_claude_settings",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let (_, claude_dir) = resolve_workspace_dirs(&state, &workspace_id).await?;
+    let snapshot = tokio::task::spawn_blocking(move || read_snapshot(&claude_dir))
+        .await
+        .map_err(|err| err.to_string())??;
+    serde_json::to_value(snapshot).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn update_claude_settings(
+    workspace_id: String,
+    scope: SettingsScope,
+    update: Value,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "update_claude_settings",
+            json!({ "workspaceId": workspace_id, "scope": scope, "update": update }),
+        )
+        .await;
+    }
+
+    let update: ClaudeSettingsUpdate =
+        serde_json::from_value(update).map_err(|err| format!("invalid settings update: {err}"))?;
+    let (_, claude_dir) = resolve_workspace_dirs(&state, &workspace_id).await?;
+    let updated = tokio::task::spawn_blocking(move || update_scope(&claude_dir, scope, update))
+        .await
+        .map_err(|err| err.to_string())??;
+    serde_json::to_value(updated).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn map(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("expected an object"),
+        }
+    }
+
+    #[test]
+    fn merges_scopes_like_the_cli() {
+        let user = map(json!({
+            "model": "claude-sonnet-4-5",
+            "env": { "A": "user", "B": "user" },
+            "permissions": { "allow": ["Bash(ls:*)"], "deny": ["Read(.env)"] },
+        }));
+        let project = map(json!({
+            "env": { "B": "project" },
+            "permissions": { "allow": ["Bash(npm test:*)", "Bash(ls:*)"] },
+        }));
+        let local = map(json!({ "model": "claude-opus-4-5", "env": { "C": 1 } }));
+
+        let effective = typed_settings(&merge_settings(&[user, project, local]));
+        assert_eq!(effective.model.as_deref(), Some("claude-opus-4-5"));
+        assert_eq!(effective.env["A"], "user");
+        assert_eq!(effective.env["B"], "project");
+        assert_eq!(effective.env["C"], "1");
+        assert_eq!(
+            effective.permissions.allow,
+            vec!["Bash(ls:*)".to_string(), "Bash(npm test:*)".to_string()]
+        );
+        assert_eq!(effective.permissions.deny, vec!["Read(.env)".to_string()]);
+    }
+
+    #[test]
+    fn updates_preserve_unrelated_keys() {
+        let mut settings = map(json!({
+            "includeCoAuthoredBy": false,
+            "permissions": { "allow": ["Bash(ls:*)"], "additionalDirectories": ["../shared"] },
+        }));
+        apply_update(
+            &mut settings,
+            ClaudeSettingsUpdate::Permissions(PermissionSettings {
+                allow: vec![" Edit ".to_string(), "Edit".to_string(), "".to_string()],
+                deny: Vec::new(),
+                ask: vec!["Bash(git push:*)".to_string()],
+                default_mode: Some("acceptEdits".to_string()),
+            }),
+        )
+        .unwrap();
+        apply_update(
+            &mut settings,
+            ClaudeSettingsUpdate::Model(Some(" ".to_string())),
+        )
+        .unwrap();
+
+        assert_eq!(settings["includeCoAuthoredBy"], false);
+        let permissions = &settings["permissions"];
+        assert_eq!(permissions["allow"], json!(["Edit"]));
+        assert!(permissions.get("deny").is_none());
+        assert_eq!(permissions["ask"], json!(["Bash(git push:*)"]));
+        assert_eq!(permissions["defaultMode"], "acceptEdits");
+        assert_eq!(permissions["additionalDirectories"], json!(["../shared"]));
+        assert!(settings.get("model").is_none());
+
+        assert!(apply_update(&mut settings, ClaudeSettingsUpdate::Hooks(json!([]))).is_err());
+    }
+
+    #[test]
+    fn update_scope_round_trips_and_refuses_malformed_files() {
+        let claude_dir = std::env::temp_dir().join(format!("claude-settings-{}", Uuid::new_v4()));
+        let updated = update_scope(
+            &claude_dir,
+            SettingsScope::Local,
+            ClaudeSettingsUpdate::Env(BTreeMap::from([("DEBUG".to_string(), "1".to_string())])),
+        )
+        .unwrap();
+        assert_eq!(updated.path, claude_dir.join(LOCAL_SETTINGS_FILE));
+        let stored = read_settings_file(&updated.path).unwrap();
+        assert_eq!(stored["env"]["DEBUG"], "1");
+
+        let project = claude_dir.join(SETTINGS_FILE);
+        std::fs::write(&project, "{ \"model\": ").unwrap();
+        assert!(update_scope(
+            &claude_dir,
+            SettingsScope::Project,
+            ClaudeSettingsUpdate::Model(Some("claude-opus-4-5".to_string())),
+        )
+        .is_err());
+        assert_eq!(std::fs::read_to_string(&project).unwrap(), "{ \"model\": ");
+
+        let _ = std::fs::remove_dir_all(&claude_dir);
+    }
+
+    #[test]
+    fn parses_tagged_updates() {
+        let update: ClaudeSettingsUpdate = serde_json::from_value(json!({
+            "key": "permissions",
+            "value": { "allow": ["Read"], "defaultMode": "plan" },
+        }))
+        .unwrap();
+        match update {
+            ClaudeSettingsUpdate::Permissions(permissions) => {
+                assert_eq!(permissions.allow, vec!["Read".to_string()]);
+                assert_eq!(permissions.default_mode.as_deref(), Some("plan"));
+            }
+            other => panic!("unexpected update {other:?}"),
+        }
+        let clear: ClaudeSettingsUpdate =
+            serde_json::from_value(json!({ "key": "model", "value": null })).unwrap();
+        assert!(matches!(clear, ClaudeSettingsUpdate::Model(None)));
+    }
+}
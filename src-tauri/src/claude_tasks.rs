@@ -45,7 +45,7 @@ fn get_tasks_dir(session_id: &str) -> Option<PathBuf> {
 #[tauri::command]
 pub async fn get_claude_tasks(session_id: String) -> Result<ClaudeTasksResponse, String> {
     let session_id_clone = session_id.clone();
-    
+
     tokio::task::spawn_blocking(move || {
         let tasks_dir = match get_tasks_dir(&session_id_clone) {
             Some(dir) => dir,
@@ -65,16 +65,14 @@ pub async fn get_claude_tasks(session_id: String) -> Result<ClaudeTasksResponse,
             let path = entry.path();
             if path.extension().is_some_and(|ext| ext == "json") {
                 match fs::read_to_string(&path) {
-                    Ok(content) => {
-                        match serde_json::from_str::<ClaudeTask>(&content) {
-                            Ok(task) => tasks.push(task),
-                            Err(e) => {
-                                eprintln!("Failed to parse task file {:?}: {}", path, e);
-                            }
+                    Ok(content) => match serde_json::from_str::<ClaudeTask>(&content) {
+                        Ok(task) => tasks.push(task),
+                        Err(e) => {
+                            tracing::warn!("Failed to parse task file {:?}: {}", path, e);
                         }
-                    }
+                    },
                     Err(e) => {
-                        eprintln!("Failed to read task file {:?}: {}", path, e);
+                        tracing::warn!("Failed to read task file {:?}: {}", path, e);
                     }
                 }
             }
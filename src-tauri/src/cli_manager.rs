@@ -0,0 +1,599 @@
+//! Installed Claude CLI versions and their updates.
+//!
+//! Installs are found where npm, bun, Homebrew and the native installer put them, plus
+//! the CLI path configured in the app settings and anything else named `claude` on the
+//! CLI's `PATH`. Each install reports its version and which workspaces run it; a
+//! workspace picks its install through its `claudeBin`. The latest version comes from
+//! the npm registry, and `update_claude_cli` updates an install with the tool that
+//! installed it, streaming the installer's output as `cli-update-progress` events.
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use futures_util::future::join_all;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+use crate::backend::claude_cli::{build_claude_command_with_bin, build_claude_path_env};
+use crate::backend::events::{CliUpdateProgress, CliUpdateStage, EventSink};
+use crate::claude_home::resolve_home_dir;
+use crate::event_sink::TauriEventSink;
+use crate::proxy;
+use crate::remote_backend;
+use crate::state::AppState;
+
+const NPM_PACKAGE: &str = "@anthropic-ai/claude-code";
+const HOMEBREW_CASK: &str = "claude-code";
+const UPDATE_TIME_LIMIT: Duration = Duration::from_secs(10 * 60);
+
+#[cfg(windows)]
+pub(crate) const CLI_NAMES: &[&str] = &["claude.exe", "claude.cmd"];
+#[cfg(not(windows))]
+pub(crate) const CLI_NAMES: &[&str] = &["claude"];
+
+/// Installs with an update running, so the same one isn't updated twice at once.
+static UPDATING: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+
+fn updating() -> MutexGuard<'static, HashSet<PathBuf>> {
+    UPDATING
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// What installed a copy of the CLI, which decides how it is updated.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum InstallSource {
+    Npm,
+    Bun,
+    Homebrew,
+    /// The native installer, or `claude migrate-installer`.
+    Local,
+    Other,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliInstallation {
+    /// The path to set as a workspace's `claudeBin` to run this install.
+    pub(crate) path: String,
+    /// Where `path` leads once symlinks are followed.
+    pub(crate) resolved_path: String,
+    pub(crate) source: InstallSource,
+    /// As `claude --version` prints it, e.g. `1.0.51 (Claude Code)`.
+    pub(crate) version: Option<String>,
+    pub(crate) error: Option<String>,
+    /// Whether workspaces without their own `claudeBin` run this install.
+    pub(crate) is_default: bool,
+    pub(crate) workspace_ids: Vec<String>,
+    /// Only known after `check_claude_cli_updates`.
+    pub(crate) update_available: bool,
+}
+
+pub(crate) async fn check_claude_installation(
+    claude_bin: Option<String>,
+) -> Result<Option<String>, String> {
+    let mut command = build_claude_command_with_bin(claude_bin);
+    command.arg("--version");
+    command.stdout(std::process::Stdio::piped());
+    command.stderr(std::process::Stdio::piped());
+
+    let output = match timeout(Duration::from_secs(5), command.output()).await {
+        Ok(result) => result.map_err(|e| {
+            if e.kind() == ErrorKind::NotFound {
+                "Claude Code CLI not found. Install Claude Code and ensure `claude` is on your PATH."
+                    .to_string()
+            } else {
+                e.to_string()
+            }
+        })?,
+        Err(_) => {
+            return Err(
+                "Timed out while checking Claude Code CLI. Make sure `claude --version` runs in Terminal."
+                    .to_string(),
+            );
+        }
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        if detail.is_empty() {
+            return Err(
+                "Claude Code CLI failed to start. Try running `claude --version` in Terminal."
+                    .to_string(),
+            );
+        }
+        return Err(format!(
+            "Claude Code CLI failed to start: {detail}. Try running `claude --version` in Terminal."
+        ));
+    }
+
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(if version.is_empty() {
+        None
+    } else {
+        Some(version)
+    })
+}
+/// The CLI's directories, in the order a spawned CLI searches them.
+pub(crate) fn search_dirs() -> Vec<PathBuf> {
+    build_claude_path_env(None)
+        .map(|value| env::split_paths(&value).collect())
+        .unwrap_or_default()
+}
+
+/// Where `bin` (or `claude` without one) leads, the way `build_claude_command_with_bin`
+/// would find it.
+pub(crate) fn resolve_bin(bin: Option<&str>, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let bin = bin.map(str::trim).filter(|bin| !bin.is_empty());
+    let found = match bin {
+        Some(bin) if Path::new(bin).components().count() > 1 => {
+            Some(PathBuf::from(bin)).filter(|path| path.is_file())
+        }
+        Some(bin) => dirs.iter().find_map(|dir| {
+            let mut names = vec![bin.to_string()];
+            names.extend(CLI_NAMES.iter().map(|name| name.replacen("claude", bin, 1)));
+            names
+                .into_iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.is_file())
+        }),
+        None => dirs.iter().find_map(|dir| {
+            CLI_NAMES
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.is_file())
+        }),
+    }?;
+    Some(fs::canonicalize(&found).unwrap_or(found))
+}
+
+fn candidate_paths(configured: Option<&str>, dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut extra_dirs = Vec::new();
+    if let Some(home) = resolve_home_dir() {
+        extra_dirs.push(home.join(".claude").join("local"));
+        extra_dirs.push(home.join(".npm-global").join("bin"));
+    }
+    if let Some(prefix) = env::var_os("NPM_CONFIG_PREFIX").map(PathBuf::from) {
+        extra_dirs.push(if cfg!(windows) {
+            prefix
+        } else {
+            prefix.join("bin")
+        });
+    }
+    if let Some(bun) = env::var_os("BUN_INSTALL").map(PathBuf::from) {
+        extra_dirs.push(bun.join("bin"));
+    }
+    if let Some(app_data) = env::var_os("APPDATA").map(PathBuf::from) {
+        extra_dirs.push(app_data.join("npm"));
+    }
+    let mut candidates: Vec<PathBuf> = configured
+        .map(str::trim)
+        .filter(|bin| Path::new(bin).components().count() > 1)
+        .map(PathBuf::from)
+        .into_iter()
+        .collect();
+    for dir in dirs.iter().chain(&extra_dirs) {
+        candidates.extend(CLI_NAMES.iter().map(|name| dir.join(name)));
+    }
+    candidates
+}
+
+/// Tell what installed `path` from where it lives once symlinks are followed.
+fn classify(path: &Path, resolved: &Path) -> InstallSource {
+    let resolved_str = resolved.to_string_lossy().replace('\\', "/").to_lowercase();
+    let package_dir = Path::new("node_modules").join(NPM_PACKAGE);
+    if resolved_str.contains("/.bun/") {
+        InstallSource::Bun
+    } else if ["/cellar/", "/caskroom/", "/homebrew/", "/linuxbrew/"]
+        .iter()
+        .any(|part| resolved_str.contains(part))
+    {
+        InstallSource::Homebrew
+    } else if resolved_str.contains("/node_modules/")
+        || path
+            .parent()
+            .is_some_and(|dir| dir.join(&package_dir).is_dir())
+    {
+        InstallSource::Npm
+    } else if ["/.claude/local/", "/.local/share/claude/", "/.local/bin/"]
+        .iter()
+        .any(|part| resolved_str.contains(part))
+    {
+        InstallSource::Local
+    } else {
+        InstallSource::Other
+    }
+}
+
+/// The leading `major.minor.patch` of a version string, for comparing.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version.split_whitespace().find_map(|word| {
+        let core = word.trim_start_matches('v').split(['-', '+']).next()?;
+        let parts = core
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u64>>>()?;
+        Some(parts).filter(|parts| parts.len() >= 2)
+    })
+}
+
+fn is_older(installed: &str, latest: &str) -> bool {
+    match (parse_version(installed), parse_version(latest)) {
+        (Some(installed), Some(latest)) => installed < latest,
+        _ => false,
+    }
+}
+
+async fn detect_installations(state: &AppState) -> Vec<CliInstallation> {
+    let configured = state.app_settings.lock().await.claude_bin.clone();
+    let workspace_bins: Vec<(String, Option<String>)> = state
+        .workspaces
+        .lock()
+        .await
+        .values()
+        .map(|entry| (entry.id.clone(), entry.claude_bin.clone()))
+        .collect();
+    let dirs = search_dirs();
+    let default = resolve_bin(configured.as_deref(), &dirs);
+
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+    for path in candidate_paths(configured.as_deref(), &dirs) {
+        if !path.is_file() {
+            continue;
+        }
+        let resolved = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if seen.insert(resolved.clone()) {
+            found.push((path, resolved));
+        }
+    }
+    let workspace_resolved: Vec<(String, Option<PathBuf>)> = workspace_bins
+        .into_iter()
+        .map(|(id, bin)| {
+            let resolved = match bin.filter(|bin| !bin.trim().is_empty()) {
+                Some(bin) => resolve_bin(Some(&bin), &dirs),
+                None => default.clone(),
+            };
+            (id, resolved)
+        })
+        .collect();
+
+    let versions = join_all(
+        found
+            .iter()
+            .map(|(path, _)| check_claude_installation(Some(path.to_string_lossy().to_string()))),
+    )
+    .await;
+    found
+        .into_iter()
+        .zip(versions)
+        .map(|((path, resolved), version)| {
+            let mut workspace_ids: Vec<String> = workspace_resolved
+                .iter()
+                .filter(|(_, used)| used.as_ref() == Some(&resolved))
+                .map(|(id, _)| id.clone())
+                .collect();
+            workspace_ids.sort();
+            let (version, error) = match version {
+                Ok(version) => (version, None),
+                Err(err) => (None, Some(err)),
+            };
+            CliInstallation {
+                source: classify(&path, &resolved),
+                path: path.to_string_lossy().to_string(),
+                resolved_path: resolved.to_string_lossy().to_string(),
+                version,
+                error,
+                is_default: default.as_ref() == Some(&resolved),
+                workspace_ids,
+                update_available: false,
+            }
+        })
+        .collect()
+}
+
+async fn latest_version() -> Result<String, String> {
+    let url = Url::parse(&format!("https://registry.npmjs.org/{NPM_PACKAGE}/latest"))
+        .map_err(|err| err.to_string())?;
+    let manifest: Value = proxy::http_client(&url)?
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| format!("Failed to check for CLI updates: {err}"))?
+        .json()
+        .await
+        .map_err(|err| format!("Failed to check for CLI updates: {err}"))?;
+    manifest
+        .get("version")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| "The npm registry returned no version.".to_string())
+}
+
+/// Every Claude CLI install found, with its version and the workspaces that run it.
+#[tauri::command]
+pub(crate) async fn list_claude_cli_installations(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_claude_cli_installations",
+            json!({}),
+        )
+        .await;
+    }
+    let installations = detect_installations(&state).await;
+    serde_json::to_value(installations).map_err(|err| err.to_string())
+}
+
+/// The installs together with the latest released version and which of them are older.
+#[tauri::command]
+pub(crate) async fn check_claude_cli_updates(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(&*state, app, "check_claude_cli_updates", json!({}))
+            .await;
+    }
+    let (mut installations, latest) = tokio::join!(detect_installations(&state), latest_version());
+    if let Ok(latest) = &latest {
+        for installation in &mut installations {
+            installation.update_available = installation
+                .version
+                .as_deref()
+                .is_some_and(|version| is_older(version, latest));
+        }
+    }
+    Ok(json!({
+        "installations": installations,
+        "latestVersion": latest.as_ref().ok(),
+        "error": latest.err(),
+    }))
+}
+
+/// The command that updates an install, run with the tools next to it where possible so
+/// e.g. an nvm-managed npm updates its own global packages.
+fn update_command(path: &Path, source: InstallSource) -> Result<Command, String> {
+    let sibling = |name: &str| {
+        let dir = path.parent()?;
+        let names = if cfg!(windows) {
+            vec![format!("{name}.cmd"), format!("{name}.exe")]
+        } else {
+            vec![name.to_string()]
+        };
+        names
+            .into_iter()
+            .map(|name| dir.join(name))
+            .find(|candidate| candidate.is_file())
+            .map(|candidate| candidate.to_string_lossy().to_string())
+    };
+    let package = format!("{NPM_PACKAGE}@latest");
+    let mut command = match source {
+        InstallSource::Npm => {
+            let mut command = build_claude_command_with_bin(sibling("npm").or(Some("npm".into())));
+            command.args(["install", "-g", package.as_str()]);
+            command
+        }
+        InstallSource::Bun => {
+            let mut command = build_claude_command_with_bin(sibling("bun").or(Some("bun".into())));
+            command.args(["add", "-g", package.as_str()]);
+            command
+        }
+        InstallSource::Homebrew => {
+            let mut command = build_claude_command_with_bin(Some("brew".into()));
+            command.args(["upgrade", HOMEBREW_CASK]);
+            command
+        }
+        InstallSource::Local => {
+            let mut command =
+                build_claude_command_with_bin(Some(path.to_string_lossy().to_string()));
+            command.arg("update");
+            command
+        }
+        InstallSource::Other => {
+            return Err(format!(
+                "{} was not installed with npm, bun, Homebrew or the native installer; \
+                 update it the way it was installed.",
+                path.display()
+            ));
+        }
+    };
+    command.stdin(Stdio::null());
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+    command.kill_on_drop(true);
+    Ok(command)
+}
+
+fn installer_name(source: InstallSource) -> &'static str {
+    match source {
+        InstallSource::Npm => "npm",
+        InstallSource::Bun => "bun",
+        InstallSource::Homebrew => "Homebrew",
+        InstallSource::Local | InstallSource::Other => "The Claude CLI",
+    }
+}
+
+fn forward_lines<R: AsyncRead + Unpin + Send + 'static>(
+    reader: Option<R>,
+    lines: mpsc::UnboundedSender<String>,
+) {
+    let Some(reader) = reader else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = reader.next_line().await {
+            if lines.send(line).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+async fn run_update(
+    event_sink: &TauriEventSink,
+    update_id: &str,
+    path: &Path,
+    source: InstallSource,
+) -> Result<Option<String>, String> {
+    let emit_line = |line: String| {
+        event_sink.emit_cli_update_progress(CliUpdateProgress {
+            update_id: update_id.to_string(),
+            path: path.to_string_lossy().to_string(),
+            stage: CliUpdateStage::Output,
+            line: Some(line),
+            version: None,
+            error: None,
+        });
+    };
+    let mut child = update_command(path, source)?
+        .spawn()
+        .map_err(|err| match err.kind() {
+            ErrorKind::NotFound => format!("{} was not found.", installer_name(source)),
+            _ => err.to_string(),
+        })?;
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    forward_lines(child.stdout.take(), sender.clone());
+    forward_lines(child.stderr.take(), sender);
+    let finished = timeout(UPDATE_TIME_LIMIT, async {
+        while let Some(line) = receiver.recv().await {
+            emit_line(line);
+        }
+        child.wait().await
+    })
+    .await;
+    match finished {
+        Ok(Ok(status)) if status.success() => {}
+        Ok(Ok(status)) => return Err(format!("The update failed ({status}).")),
+        Ok(Err(err)) => return Err(err.to_string()),
+        Err(_) => return Err("The update timed out.".to_string()),
+    }
+    check_claude_installation(Some(path.to_string_lossy().to_string())).await
+}
+
+/// Update the install at `path` to the latest version and return right away with an
+/// `updateId`; the installer's output and the result arrive as `cli-update-progress`
+/// events. Running threads keep their CLI until they are restarted.
+#[tauri::command]
+pub(crate) async fn update_claude_cli(
+    path: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "update_claude_cli",
+            json!({ "path": path }),
+        )
+        .await;
+    }
+    let installation = detect_installations(&state)
+        .await
+        .into_iter()
+        .find(|installation| installation.path == path || installation.resolved_path == path)
+        .ok_or_else(|| format!("No Claude CLI install at {path}."))?;
+    let resolved = PathBuf::from(&installation.resolved_path);
+    if !updating().insert(resolved.clone()) {
+        return Err(format!("{path} is already being updated."));
+    }
+
+    let update_id = Uuid::new_v4().to_string();
+    let source = installation.source;
+    let started = CliUpdateProgress {
+        update_id: update_id.clone(),
+        path: installation.path.clone(),
+        stage: CliUpdateStage::Started,
+        line: None,
+        version: None,
+        error: None,
+    };
+    let event_sink = TauriEventSink::new(app);
+    event_sink.emit_cli_update_progress(started.clone());
+    tokio::spawn(async move {
+        let path = PathBuf::from(&started.path);
+        let outcome = run_update(&event_sink, &started.update_id, &path, source).await;
+        updating().remove(&resolved);
+        let (stage, version, error) = match outcome {
+            Ok(version) => (CliUpdateStage::Finished, version, None),
+            Err(err) => (CliUpdateStage::Failed, None, Some(err)),
+        };
+        event_sink.emit_cli_update_progress(CliUpdateProgress {
+            stage,
+            version,
+            error,
+            ..started
+        });
+    });
+    Ok(json!({ "updateId": update_id, "source": source }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_cli_versions() {
+        assert_eq!(parse_version("1.0.51 (Claude Code)"), Some(vec![1, 0, 51]));
+        assert_eq!(parse_version("v2.1.0-beta.1"), Some(vec![2, 1, 0]));
+        assert_eq!(parse_version("Claude Code"), None);
+        assert!(is_older("1.0.51 (Claude Code)", "1.0.100"));
+        assert!(!is_older("1.0.51 (Claude Code)", "1.0.51"));
+        assert!(!is_older("unknown", "1.0.51"));
+    }
+
+    #[test]
+    fn tells_install_sources_apart() {
+        let cases = [
+            (
+                "/opt/homebrew/Caskroom/claude-code/1.0.51/claude",
+                InstallSource::Homebrew,
+            ),
+            (
+                "/home/me/.bun/install/global/node_modules/@anthropic-ai/claude-code/cli.js",
+                InstallSource::Bun,
+            ),
+            (
+                "/usr/local/lib/node_modules/@anthropic-ai/claude-code/cli.js",
+                InstallSource::Npm,
+            ),
+            (
+                "/home/me/.local/share/claude/versions/1.0.51",
+                InstallSource::Local,
+            ),
+            ("/usr/bin/claude", InstallSource::Other),
+        ];
+        for (resolved, source) in cases {
+            let resolved = Path::new(resolved);
+            assert_eq!(classify(resolved, resolved), source, "{resolved:?}");
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! Portable bundles of the monitor's configuration, for moving to another machine or
+//! sharing a team baseline.
+//!
+//! A bundle is one JSON document with the app settings, the workspaces (tags and groups
+//! included), the prompt library and the schedules. Secrets are left out: the remote
+//! backend token, webhook signing secrets and proxy credentials are dropped on export,
+//! and provider keys never leave the keychain. So is what only holds on this machine:
+//! worktrees, CLI binary paths and the remote backend connection.
+//!
+//! Importing merges into the current configuration. Workspaces are matched by id, then
+//! by path; unmatched ones are added when their folder exists here and skipped
+//! otherwise. Local secrets and machine settings are kept.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::prompt_library::{library_file, read_library, write_library, PromptTemplate};
+use crate::proxy::without_credentials;
+use crate::remote_backend;
+use crate::scheduler::{self, ScheduledPrompt};
+use crate::settings::store_app_settings;
+use crate::state::AppState;
+use crate::storage::write_workspaces;
+use crate::types::{AppSettings, ProxySettings, WorkspaceEntry};
+use crate::utils::now_ms;
+use crate::workspace_tags::normalize_tags;
+
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigBundle {
+    pub(crate) version: u32,
+    pub(crate) exported_at: i64,
+    pub(crate) settings: AppSettings,
+    #[serde(default)]
+    pub(crate) workspaces: Vec<WorkspaceEntry>,
+    /// The global prompt library.
+    #[serde(default)]
+    pub(crate) templates: Vec<PromptTemplate>,
+    /// Workspace prompt libraries by workspace id.
+    #[serde(default)]
+    pub(crate) workspace_templates: BTreeMap<String, Vec<PromptTemplate>>,
+    #[serde(default)]
+    pub(crate) schedules: Vec<ScheduledPrompt>,
+}
+
+/// What an import changed.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImportReport {
+    pub(crate) workspaces_added: usize,
+    pub(crate) workspaces_updated: usize,
+    /// Names of bundled workspaces whose folder does not exist on this machine; their
+    /// templates and schedules are skipped too.
+    pub(crate) workspaces_skipped: Vec<String>,
+    pub(crate) templates_imported: usize,
+    pub(crate) schedules_imported: usize,
+}
+
+/// Export the configuration, also writing it to `path` when given.
+#[tauri::command]
+pub(crate) async fn export_config(
+    path: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ConfigBundle, String> {
+    let bundle = if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "export_config", json!({})).await?;
+        serde_json::from_value(response).map_err(|err| err.to_string())?
+    } else {
+        build_bundle(&state).await?
+    };
+    if let Some(path) = path {
+        let data = serde_json::to_string_pretty(&bundle).map_err(|err| err.to_string())?;
+        std::fs::write(&path, data).map_err(|err| format!("{path}: {err}"))?;
+    }
+    Ok(bundle)
+}
+
+/// Merge a bundle, given inline or as the file at `path`, into the configuration.
+#[tauri::command]
+pub(crate) async fn import_config(
+    bundle: Option<ConfigBundle>,
+    path: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ImportReport, String> {
+    let bundle = match (bundle, path) {
+        (Some(bundle), _) => bundle,
+        (None, Some(path)) => {
+            let raw = std::fs::read_to_string(&path).map_err(|err| format!("{path}: {err}"))?;
+            serde_json::from_str(&raw)
+                .map_err(|err| format!("{path} is not a configuration bundle: {err}"))?
+        }
+        (None, None) => return Err("A bundle or a bundle path is required.".to_string()),
+    };
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "This bundle has format version {}; this monitor reads up to {BUNDLE_VERSION}.",
+            bundle.version
+        ));
+    }
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "import_config", json!({ "bundle": bundle }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    apply_bundle(&state, bundle).await
+}
+
+async fn build_bundle(state: &AppState) -> Result<ConfigBundle, String> {
+    let data_dir = data_dir(state)?;
+    let mut workspaces: Vec<WorkspaceEntry> = state
+        .workspaces
+        .lock()
+        .await
+        .values()
+        .filter(|entry| !entry.kind.is_worktree())
+        .cloned()
+        .map(portable_workspace)
+        .collect();
+    workspaces.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+
+    let mut workspace_templates = BTreeMap::new();
+    for entry in &workspaces {
+        let templates = read_library(&library_file(data_dir, Some(&entry.id)))?;
+        if !templates.is_empty() {
+            workspace_templates.insert(entry.id.clone(), templates);
+        }
+    }
+    let ids: HashSet<&str> = workspaces.iter().map(|entry| entry.id.as_str()).collect();
+    let schedules = scheduler::exported_schedules(state)?
+        .into_iter()
+        .filter(|schedule| ids.contains(schedule.workspace_id.as_str()))
+        .collect();
+    let settings = portable_settings(state.app_settings.lock().await.clone());
+    Ok(ConfigBundle {
+        version: BUNDLE_VERSION,
+        exported_at: now_ms(),
+        settings,
+        templates: read_library(&library_file(data_dir, None))?,
+        workspace_templates,
+        schedules,
+        workspaces,
+    })
+}
+
+async fn apply_bundle(state: &AppState, bundle: ConfigBundle) -> Result<ImportReport, String> {
+    let data_dir = data_dir(state)?;
+    for entry in &bundle.workspaces {
+        normalize_tags(&entry.settings.tags)
+            .map_err(|err| format!("workspace \"{}\": {err}", entry.name))?;
+    }
+    let local_settings = state.app_settings.lock().await.clone();
+    store_app_settings(state, merge_settings(bundle.settings, &local_settings)).await?;
+
+    let mut report = ImportReport::default();
+    let id_map = {
+        let mut workspaces = state.workspaces.lock().await;
+        let id_map = merge_workspaces(&mut workspaces, bundle.workspaces, &mut report);
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        write_workspaces(&state.db_path, &list)?;
+        id_map
+    };
+
+    let global_path = library_file(data_dir, None);
+    let mut global = read_library(&global_path)?;
+    report.templates_imported += merge_templates(&mut global, bundle.templates);
+    write_library(&global_path, &global)?;
+    for (bundled_id, templates) in bundle.workspace_templates {
+        let Some(local_id) = id_map.get(&bundled_id) else {
+            continue;
+        };
+        let path = library_file(data_dir, Some(local_id));
+        let mut library = read_library(&path)?;
+        report.templates_imported += merge_templates(&mut library, templates);
+        write_library(&path, &library)?;
+    }
+
+    let schedules: Vec<ScheduledPrompt> = bundle
+        .schedules
+        .into_iter()
+        .filter_map(|schedule| {
+            let workspace_id = id_map.get(&schedule.workspace_id)?.clone();
+            Some(ScheduledPrompt {
+                workspace_id,
+                ..schedule
+            })
+        })
+        .collect();
+    report.schedules_imported = scheduler::import_schedules(state, schedules)?;
+    Ok(report)
+}
+
+/// App settings without secrets or this machine's CLI and remote backend settings.
+fn portable_settings(mut settings: AppSettings) -> AppSettings {
+    let defaults = AppSettings::default();
+    settings.claude_bin = None;
+    settings.backend_mode = defaults.backend_mode;
+    settings.remote_backend_host = defaults.remote_backend_host;
+    settings.remote_backend_token = None;
+    for webhook in &mut settings.webhooks {
+        webhook.secret = None;
+    }
+    strip_proxy_credentials(&mut settings.proxy);
+    settings
+}
+
+fn portable_workspace(mut entry: WorkspaceEntry) -> WorkspaceEntry {
+    entry.claude_bin = None;
+    entry.settings.agent_bin = None;
+    strip_proxy_credentials(&mut entry.settings.proxy);
+    entry
+}
+
+fn strip_proxy_credentials(proxy: &mut Option<ProxySettings>) {
+    if let Some(proxy) = proxy {
+        for value in [&mut proxy.http_proxy, &mut proxy.https_proxy]
+            .into_iter()
+            .flatten()
+        {
+            *value = without_credentials(value);
+        }
+    }
+}
+
+/// Put back the credentials of local proxy URLs the bundle has without them.
+fn restore_proxy_credentials(proxy: &mut Option<ProxySettings>, local: Option<&ProxySettings>) {
+    let (Some(proxy), Some(local)) = (proxy.as_mut(), local) else {
+        return;
+    };
+    let fields = [
+        (&mut proxy.http_proxy, &local.http_proxy),
+        (&mut proxy.https_proxy, &local.https_proxy),
+    ];
+    for (value, local_value) in fields {
+        if let Some(local_value) = local_value {
+            if value.as_deref() == Some(without_credentials(local_value).as_str()) {
+                *value = Some(local_value.clone());
+            }
+        }
+    }
+}
+
+/// Bundled settings with this machine's CLI, remote backend and secrets kept.
+fn merge_settings(mut imported: AppSettings, local: &AppSettings) -> AppSettings {
+    imported.claude_bin = local.claude_bin.clone();
+    imported.backend_mode = local.backend_mode.clone();
+    imported.remote_backend_host = local.remote_backend_host.clone();
+    imported.remote_backend_token = local.remote_backend_token.clone();
+    for webhook in &mut imported.webhooks {
+        if webhook.secret.is_none() {
+            webhook.secret = local
+                .webhooks
+                .iter()
+                .find(|known| known.url == webhook.url)
+                .and_then(|known| known.secret.clone());
+        }
+    }
+    restore_proxy_credentials(&mut imported.proxy, local.proxy.as_ref());
+    imported
+}
+
+/// Merge bundled workspaces into `local`, returning the local id of every bundled
+/// workspace that was matched or added.
+fn merge_workspaces(
+    local: &mut HashMap<String, WorkspaceEntry>,
+    imported: Vec<WorkspaceEntry>,
+    report: &mut ImportReport,
+) -> HashMap<String, String> {
+    let mut id_map = HashMap::new();
+    for mut entry in imported {
+        if entry.kind.is_worktree() {
+            continue;
+        }
+        entry.settings.tags = normalize_tags(&entry.settings.tags).unwrap_or_default();
+        let matched = local
+            .get(&entry.id)
+            .map(|known| known.id.clone())
+            .or_else(|| {
+                local
+                    .values()
+                    .find(|known| !known.kind.is_worktree() && known.path == entry.path)
+                    .map(|known| known.id.clone())
+            });
+        match matched.and_then(|id| local.get_mut(&id)) {
+            Some(known) => {
+                let mut settings = entry.settings;
+                settings.agent_bin = known.settings.agent_bin.take();
+                settings.git_root = known.settings.git_root.take();
+                restore_proxy_credentials(&mut settings.proxy, known.settings.proxy.as_ref());
+                known.name = entry.name;
+                known.model = entry.model;
+                known.settings = settings;
+                id_map.insert(entry.id, known.id.clone());
+                report.workspaces_updated += 1;
+            }
+            None if Path::new(&entry.path).is_dir() => {
+                entry.claude_bin = None;
+                entry.parent_id = None;
+                entry.worktree = None;
+                id_map.insert(entry.id.clone(), entry.id.clone());
+                local.insert(entry.id.clone(), entry);
+                report.workspaces_added += 1;
+            }
+            None => report.workspaces_skipped.push(entry.name),
+        }
+    }
+    id_map
+}
+
+/// Add `imported` templates to `library`, replacing ones with the same name.
+fn merge_templates(library: &mut Vec<PromptTemplate>, imported: Vec<PromptTemplate>) -> usize {
+    let count = imported.len();
+    for template in imported {
+        match library.iter_mut().find(|known| known.name == template.name) {
+            Some(known) => *known = template,
+            None => library.push(template),
+        }
+    }
+    count
+}
+
+fn data_dir(state: &AppState) -> Result<&Path, String> {
+    state
+        .db_path
+        .parent()
+        .ok_or_else(|| "Unable to resolve app data dir.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WebhookConfig, WorkspaceKind, WorkspaceSettings};
+    use uuid::Uuid;
+
+    fn proxy(url: &str) -> Option<ProxySettings> {
+        Some(ProxySettings {
+            https_proxy: Some(url.to_string()),
+            ..ProxySettings::default()
+        })
+    }
+
+    fn workspace(id: &str, path: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: path.to_string(),
+            claude_bin: Some("/opt/claude".to_string()),
+            model: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            archived_at: None,
+            pinned: false,
+            settings: WorkspaceSettings {
+                proxy: proxy("http://me:pw@proxy.corp:3128"),
+                ..WorkspaceSettings::default()
+            },
+        }
+    }
+
+    #[test]
+    fn secrets_are_dropped_on_export_and_kept_on_import() {
+        let local = AppSettings {
+            remote_backend_token: Some("token".to_string()),
+            webhooks: vec![WebhookConfig {
+                url: "https://hooks.example/turns".to_string(),
+                secret: Some("signing-key".to_string()),
+                events: Vec::new(),
+                enabled: true,
+            }],
+            proxy: proxy("http://me:pw@proxy.corp:3128"),
+            ..AppSettings::default()
+        };
+
+        let exported = portable_settings(local.clone());
+        assert_eq!(exported.remote_backend_token, None);
+        assert_eq!(exported.webhooks[0].secret, None);
+        let exported_proxy = exported.proxy.clone().unwrap().https_proxy;
+        assert_eq!(exported_proxy.as_deref(), Some("http://proxy.corp:3128/"));
+        let entry = portable_workspace(workspace("ws-1", "/tmp/ws-1"));
+        assert_eq!(entry.claude_bin, None);
+        assert!(!format!("{:?}", entry.settings.proxy).contains("pw"));
+
+        let merged = merge_settings(exported, &local);
+        assert_eq!(merged.remote_backend_token.as_deref(), Some("token"));
+        assert_eq!(merged.webhooks[0].secret.as_deref(), Some("signing-key"));
+        assert_eq!(merged.proxy, local.proxy);
+    }
+
+    #[test]
+    fn workspaces_merge_by_id_then_path_and_skip_missing_folders() {
+        let dir = std::env::temp_dir().join(format!("config-bundle-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir_path = dir.to_string_lossy().to_string();
+        let mut local = HashMap::from([
+            ("ws-1".to_string(), workspace("ws-1", "/tmp/one")),
+            ("local-2".to_string(), workspace("local-2", "/tmp/two")),
+        ]);
+
+        let mut renamed = portable_workspace(workspace("ws-1", "/elsewhere/one"));
+        renamed.name = "One".to_string();
+        renamed.settings.tags = vec!["client-a".to_string(), "Client-A".to_string()];
+        let imported = vec![
+            renamed,
+            portable_workspace(workspace("remote-2", "/tmp/two")),
+            portable_workspace(workspace("ws-3", &dir_path)),
+            portable_workspace(workspace("ws-4", "/does/not/exist")),
+        ];
+        let mut report = ImportReport::default();
+        let id_map = merge_workspaces(&mut local, imported, &mut report);
+
+        assert_eq!(report.workspaces_updated, 2);
+        assert_eq!(report.workspaces_added, 1);
+        assert_eq!(report.workspaces_skipped, vec!["ws-4".to_string()]);
+        assert_eq!(id_map["remote-2"], "local-2");
+        assert_eq!(id_map["ws-3"], "ws-3");
+        assert!(!id_map.contains_key("ws-4"));
+        let updated = &local["ws-1"];
+        assert_eq!(updated.name, "One");
+        assert_eq!(updated.path, "/tmp/one");
+        assert_eq!(updated.claude_bin.as_deref(), Some("/opt/claude"));
+        assert_eq!(updated.settings.tags, vec!["client-a".to_string()]);
+        assert_eq!(
+            updated.settings.proxy,
+            proxy("http://me:pw@proxy.corp:3128")
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
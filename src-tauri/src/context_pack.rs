@@ -0,0 +1,536 @@
+//! Context packs: files and snippets a workspace sends along with its messages.
+//!
+//! Packs live in `context-packs.json` under `workspaces/<id>/` in the app data dir. When
+//! a message names a pack, its items are read fresh and put in front of the message text
+//! as fenced blocks. A pack has a token budget, counted with `token_estimate`; when the
+//! items add up to more, `sequential` truncation keeps items in order and cuts the one
+//! that crosses the budget, dropping the rest, while `proportional` cuts every item to
+//! its share of the budget. Truncated content keeps its first lines.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+
+use crate::attachments;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::token_estimate::count_tokens;
+use crate::utils::now_ms;
+
+const PACKS_FILE: &str = "context-packs.json";
+const DEFAULT_MAX_TOKENS: usize = 20_000;
+/// Files larger than this can't be put in a pack.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// An item cut to fewer tokens than this is dropped instead.
+const MIN_TRUNCATED_TOKENS: usize = 64;
+/// Characters kept of a first line that is over budget on its own, per token.
+const CHARS_PER_TOKEN: usize = 3;
+/// Room kept for the note saying how much was cut.
+const CUT_NOTE_TOKENS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum ContextItem {
+    /// A file, relative to the workspace unless absolute, optionally limited to a range
+    /// of 1-based, inclusive lines.
+    #[serde(rename_all = "camelCase")]
+    File {
+        path: String,
+        #[serde(default)]
+        start_line: Option<usize>,
+        #[serde(default)]
+        end_line: Option<usize>,
+    },
+    /// Text kept in the pack itself.
+    Snippet {
+        #[serde(default)]
+        label: Option<String>,
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TruncationStrategy {
+    #[default]
+    Sequential,
+    Proportional,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ContextPack {
+    pub(crate) name: String,
+    pub(crate) items: Vec<ContextItem>,
+    #[serde(default)]
+    pub(crate) max_tokens: Option<usize>,
+    #[serde(default)]
+    pub(crate) truncation: TruncationStrategy,
+    #[serde(default)]
+    pub(crate) updated_at: i64,
+}
+
+/// How one item fared when its pack was expanded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExpandedItem {
+    pub(crate) label: String,
+    /// Estimated tokens of the item in full
+    pub(crate) tokens: usize,
+    /// Estimated tokens sent
+    pub(crate) sent_tokens: usize,
+    pub(crate) truncated: bool,
+    pub(crate) omitted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExpandedPack {
+    pub(crate) text: String,
+    pub(crate) tokens: usize,
+    pub(crate) max_tokens: usize,
+    pub(crate) items: Vec<ExpandedItem>,
+}
+
+/// The first lines of `text` that fit in `max_tokens`, with a note saying how many lines
+/// were left out.
+fn truncate(text: &str, max_tokens: usize) -> String {
+    let max_tokens = max_tokens.saturating_sub(CUT_NOTE_TOKENS);
+    let total_lines = text.lines().count();
+    let mut kept = String::new();
+    let mut kept_tokens = 0;
+    let mut kept_lines = 0;
+    for line in text.lines() {
+        let line_tokens = count_tokens(line) + 1;
+        if kept_tokens + line_tokens > max_tokens {
+            break;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+        kept_tokens += line_tokens;
+        kept_lines += 1;
+    }
+    if kept_lines == 0 {
+        // The first line alone is over budget; cut it
+        kept = text.chars().take(max_tokens * CHARS_PER_TOKEN).collect();
+        kept.push('\n');
+    }
+    format!(
+        "{kept}[… {} more lines cut to fit the context budget]",
+        total_lines.saturating_sub(kept_lines)
+    )
+}
+
+/// An item's label and content.
+fn read_item(item: &ContextItem, root: &Path) -> Result<(String, String), String> {
+    match item {
+        ContextItem::Snippet { label, text } => Ok((
+            label
+                .clone()
+                .filter(|label| !label.trim().is_empty())
+                .unwrap_or_else(|| "snippet".to_string()),
+            text.clone(),
+        )),
+        ContextItem::File {
+            path,
+            start_line,
+            end_line,
+        } => {
+            let requested = PathBuf::from(path.trim());
+            let absolute = if requested.is_absolute() {
+                requested
+            } else {
+                root.join(requested)
+            };
+            let metadata =
+                std::fs::metadata(&absolute).map_err(|err| format!("can't read {path}: {err}"))?;
+            if !metadata.is_file() {
+                return Err(format!("can't read {path}: not a file"));
+            }
+            if metadata.len() > MAX_FILE_BYTES {
+                return Err(format!(
+                    "{path} is too large for a context pack (over 1 MB)"
+                ));
+            }
+            let content = std::fs::read_to_string(&absolute)
+                .map_err(|err| format!("can't read {path}: {err}"))?;
+            if start_line.is_none() && end_line.is_none() {
+                return Ok((path.clone(), content));
+            }
+            let start = start_line.unwrap_or(1).max(1);
+            let end = end_line.unwrap_or(usize::MAX);
+            let lines: Vec<&str> = content
+                .lines()
+                .skip(start - 1)
+                .take(end.saturating_sub(start - 1))
+                .collect();
+            let last = start + lines.len().saturating_sub(1);
+            Ok((format!("{path}:{start}-{last}"), lines.join("\n")))
+        }
+    }
+}
+
+/// Read the pack's items and fit them into its budget.
+pub(crate) fn expand(pack: &ContextPack, root: &Path) -> Result<ExpandedPack, String> {
+    let max_tokens = pack.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+    let mut blocks = Vec::new();
+    for item in &pack.items {
+        let (label, content) = read_item(item, root)?;
+        let tokens = count_tokens(&attachments::fenced(&label, &content));
+        blocks.push((label, content, tokens));
+    }
+    let total: usize = blocks.iter().map(|(_, _, tokens)| tokens).sum();
+    let mut remaining = max_tokens;
+    let mut sent = Vec::new();
+    let mut items = Vec::new();
+    for (label, content, tokens) in blocks {
+        let allowance = if total <= max_tokens {
+            tokens
+        } else {
+            match pack.truncation {
+                TruncationStrategy::Sequential => remaining,
+                TruncationStrategy::Proportional => max_tokens * tokens / total.max(1),
+            }
+        };
+        // The fence, the label and the blank line before the next block
+        let overhead = count_tokens(&attachments::fenced(&label, "")) + 1;
+        let (block, truncated, omitted) = if tokens <= allowance {
+            (Some(attachments::fenced(&label, &content)), false, false)
+        } else if allowance >= overhead + CUT_NOTE_TOKENS + MIN_TRUNCATED_TOKENS {
+            let cut = truncate(&content, allowance - overhead);
+            (Some(attachments::fenced(&label, &cut)), true, false)
+        } else {
+            (None, false, true)
+        };
+        let sent_tokens = block.as_deref().map(count_tokens).unwrap_or(0);
+        if sent_tokens > 0 {
+            remaining = remaining.saturating_sub(sent_tokens + 1);
+        }
+        sent.extend(block);
+        items.push(ExpandedItem {
+            label,
+            tokens,
+            sent_tokens,
+            truncated,
+            omitted,
+        });
+    }
+    let text = sent.join("\n\n");
+    Ok(ExpandedPack {
+        tokens: count_tokens(&text),
+        text,
+        max_tokens,
+        items,
+    })
+}
+
+/// `message` with the expanded pack in front of it.
+pub(crate) fn prepend(expanded: &ExpandedPack, message: &str) -> String {
+    if expanded.text.is_empty() {
+        return message.to_string();
+    }
+    if message.trim().is_empty() {
+        return expanded.text.clone();
+    }
+    format!("{}\n\n{message}", expanded.text)
+}
+
+pub(crate) fn read_packs(path: &Path) -> Result<Vec<ContextPack>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(raw) => serde_json::from_str(&raw)
+            .map_err(|err| format!("invalid context packs {}: {err}", path.display())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+pub(crate) fn write_packs(path: &Path, packs: &[ContextPack]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(packs).map_err(|err| err.to_string())?;
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, data).map_err(|err| err.to_string())?;
+    std::fs::rename(&temp_path, path).map_err(|err| err.to_string())
+}
+
+async fn packs_path(state: &AppState, workspace_id: &str) -> Result<PathBuf, String> {
+    let data_dir = state
+        .db_path
+        .parent()
+        .ok_or_else(|| "Unable to resolve app data dir.".to_string())?;
+    if !state.workspaces.lock().await.contains_key(workspace_id) {
+        return Err("workspace not found".to_string());
+    }
+    Ok(data_dir
+        .join("workspaces")
+        .join(workspace_id)
+        .join(PACKS_FILE))
+}
+
+async fn workspace_root(state: &AppState, workspace_id: &str) -> Result<PathBuf, String> {
+    state
+        .workspaces
+        .lock()
+        .await
+        .get(workspace_id)
+        .map(|entry| PathBuf::from(&entry.path))
+        .ok_or_else(|| "workspace not found".to_string())
+}
+
+/// Expand the workspace's pack called `name`.
+pub(crate) async fn expand_named(
+    state: &AppState,
+    workspace_id: &str,
+    name: &str,
+) -> Result<ExpandedPack, String> {
+    let pack = read_packs(&packs_path(state, workspace_id).await?)?
+        .into_iter()
+        .find(|pack| pack.name == name)
+        .ok_or_else(|| format!("no context pack named {name}"))?;
+    let root = workspace_root(state, workspace_id).await?;
+    tokio::task::spawn_blocking(move || expand(&pack, &root))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub(crate) async fn context_packs_list(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "context_packs_list",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let mut packs = read_packs(&packs_path(&state, &workspace_id).await?)?;
+    packs.sort_by(|a, b| a.name.cmp(&b.name));
+    serde_json::to_value(packs).map_err(|err| err.to_string())
+}
+
+/// Create or replace the workspace's pack called `name`.
+#[tauri::command]
+pub(crate) async fn context_pack_save(
+    workspace_id: String,
+    name: String,
+    items: Vec<ContextItem>,
+    max_tokens: Option<usize>,
+    truncation: Option<TruncationStrategy>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "context_pack_save",
+            json!({
+                "workspaceId": workspace_id,
+                "name": name,
+                "items": items,
+                "maxTokens": max_tokens,
+                "truncation": truncation,
+            }),
+        )
+        .await;
+    }
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Context pack name is required.".to_string());
+    }
+    let path = packs_path(&state, &workspace_id).await?;
+    let mut packs = read_packs(&path)?;
+    let pack = ContextPack {
+        name: name.clone(),
+        items,
+        max_tokens: max_tokens.filter(|tokens| *tokens > 0),
+        truncation: truncation.unwrap_or_default(),
+        updated_at: now_ms(),
+    };
+    match packs.iter_mut().find(|existing| existing.name == name) {
+        Some(existing) => *existing = pack.clone(),
+        None => packs.push(pack.clone()),
+    }
+    write_packs(&path, &packs)?;
+    serde_json::to_value(pack).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn context_pack_delete(
+    workspace_id: String,
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "context_pack_delete",
+            json!({ "workspaceId": workspace_id, "name": name }),
+        )
+        .await;
+    }
+
+    let path = packs_path(&state, &workspace_id).await?;
+    let mut packs = read_packs(&path)?;
+    let before = packs.len();
+    packs.retain(|pack| pack.name != name);
+    let deleted = packs.len() != before;
+    if deleted {
+        write_packs(&path, &packs)?;
+    }
+    Ok(json!({ "deleted": deleted }))
+}
+
+/// The text a pack expands to right now, with its estimated size and what was cut.
+#[tauri::command]
+pub(crate) async fn context_pack_preview(
+    workspace_id: String,
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "context_pack_preview",
+            json!({ "workspaceId": workspace_id, "name": name }),
+        )
+        .await;
+    }
+
+    let expanded = expand_named(&state, &workspace_id, &name).await?;
+    serde_json::to_value(expanded).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(label: &str, text: &str) -> ContextItem {
+        ContextItem::Snippet {
+            label: Some(label.to_string()),
+            text: text.to_string(),
+        }
+    }
+
+    fn pack(
+        items: Vec<ContextItem>,
+        max_tokens: usize,
+        truncation: TruncationStrategy,
+    ) -> ContextPack {
+        ContextPack {
+            name: "pack".to_string(),
+            items,
+            max_tokens: Some(max_tokens),
+            truncation,
+            updated_at: 0,
+        }
+    }
+
+    fn lines(count: usize) -> String {
+        (0..count)
+            .map(|index| format!("line {index:04} of the file"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn reads_files_and_line_ranges() {
+        let root = std::env::temp_dir().join(format!("context-pack-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "one\ntwo\nthree\nfour\n").unwrap();
+        let items = vec![
+            ContextItem::File {
+                path: "src/lib.rs".to_string(),
+                start_line: Some(2),
+                end_line: Some(3),
+            },
+            snippet("notes", "Keep the API stable."),
+        ];
+        let expanded = expand(&pack(items, 1000, TruncationStrategy::Sequential), &root).unwrap();
+        assert_eq!(
+            expanded.text,
+            "src/lib.rs:2-3:\n```\ntwo\nthree\n```\n\nnotes:\n```\nKeep the API stable.\n```"
+        );
+        assert!(expanded
+            .items
+            .iter()
+            .all(|item| !item.truncated && !item.omitted));
+        assert_eq!(
+            prepend(&expanded, "Refactor this."),
+            format!("{}\n\nRefactor this.", expanded.text)
+        );
+
+        let missing = vec![ContextItem::File {
+            path: "nope.rs".to_string(),
+            start_line: None,
+            end_line: None,
+        }];
+        assert!(expand(&pack(missing, 1000, TruncationStrategy::Sequential), &root).is_err());
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[test]
+    fn sequential_truncation_cuts_the_item_over_budget_and_drops_the_rest() {
+        let items = vec![
+            snippet("a", &lines(10)),
+            snippet("b", &lines(100)),
+            snippet("c", &lines(10)),
+        ];
+        let expanded = expand(
+            &pack(items, 300, TruncationStrategy::Sequential),
+            Path::new("/"),
+        )
+        .unwrap();
+        let outcome: Vec<_> = expanded
+            .items
+            .iter()
+            .map(|item| (item.truncated, item.omitted))
+            .collect();
+        assert_eq!(outcome, [(false, false), (true, false), (false, true)]);
+        assert!(expanded.tokens <= 300);
+        assert!(expanded
+            .text
+            .contains("more lines cut to fit the context budget"));
+    }
+
+    #[test]
+    fn proportional_truncation_cuts_every_item() {
+        let items = vec![snippet("a", &lines(100)), snippet("b", &lines(100))];
+        let expanded = expand(
+            &pack(items, 600, TruncationStrategy::Proportional),
+            Path::new("/"),
+        )
+        .unwrap();
+        assert!(expanded.items.iter().all(|item| item.truncated));
+        assert!(expanded.tokens <= 600);
+    }
+
+    #[test]
+    fn pack_files_round_trip() {
+        let dir = std::env::temp_dir().join(format!("context-packs-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(PACKS_FILE);
+        assert!(read_packs(&path).unwrap().is_empty());
+        let packs = vec![pack(
+            vec![snippet("a", "text")],
+            100,
+            TruncationStrategy::Proportional,
+        )];
+        write_packs(&path, &packs).unwrap();
+        assert_eq!(read_packs(&path).unwrap(), packs);
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
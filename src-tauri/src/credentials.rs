@@ -0,0 +1,323 @@
+//! Per-workspace API credentials, kept in the OS keychain.
+//!
+//! A workspace can run Claude against its own Anthropic API key, Amazon Bedrock or
+//! Google Vertex AI account. The credentials are stored as one keychain item per
+//! workspace (Keychain on macOS, Credential Manager on Windows, the Secret Service on
+//! Linux), never in the database or settings, and are read when a CLI process is
+//! spawned to set its environment, together with the workspace's provider settings.
+//! Worktrees use their parent workspace's credentials.
+//! The frontend can set and clear them but only ever reads back which provider is
+//! configured.
+
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+use tokio::process::Command;
+
+use crate::backend::provider::{provider_env, PROVIDER_VARS};
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage_encryption::KEYCHAIN_SERVICE;
+use crate::types::{ModelProvider, WorkspaceEntry};
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "provider", rename_all = "camelCase")]
+pub(crate) enum WorkspaceCredentials {
+    #[serde(rename_all = "camelCase")]
+    Anthropic { api_key: String },
+    #[serde(rename_all = "camelCase")]
+    Bedrock {
+        access_key_id: String,
+        secret_access_key: String,
+        #[serde(default)]
+        session_token: Option<String>,
+        region: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Vertex {
+        project_id: String,
+        region: String,
+        /// Path of a service account key; application default credentials otherwise.
+        #[serde(default)]
+        credentials_file: Option<String>,
+    },
+}
+
+/// Never print the secrets, e.g. in an error or a log line.
+impl std::fmt::Debug for WorkspaceCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WorkspaceCredentials({:?})", self.provider())
+    }
+}
+
+impl WorkspaceCredentials {
+    pub(crate) fn provider(&self) -> ModelProvider {
+        match self {
+            Self::Anthropic { .. } => ModelProvider::Anthropic,
+            Self::Bedrock { .. } => ModelProvider::Bedrock,
+            Self::Vertex { .. } => ModelProvider::Vertex,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let required: Vec<(&str, &str)> = match self {
+            Self::Anthropic { api_key } => vec![("apiKey", api_key.as_str())],
+            Self::Bedrock {
+                access_key_id,
+                secret_access_key,
+                region,
+                ..
+            } => vec![
+                ("accessKeyId", access_key_id.as_str()),
+                ("secretAccessKey", secret_access_key.as_str()),
+                ("region", region.as_str()),
+            ],
+            Self::Vertex {
+                project_id, region, ..
+            } => vec![
+                ("projectId", project_id.as_str()),
+                ("region", region.as_str()),
+            ],
+        };
+        match required.iter().find(|(_, value)| value.trim().is_empty()) {
+            Some((name, _)) => Err(format!("{name} is required")),
+            None => Ok(()),
+        }
+    }
+
+    /// The environment the Claude CLI reads these credentials from.
+    pub(crate) fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = Vec::new();
+        match self {
+            Self::Anthropic { api_key } => {
+                env.push(("ANTHROPIC_API_KEY", api_key.trim().to_string()));
+            }
+            Self::Bedrock {
+                access_key_id,
+                secret_access_key,
+                session_token,
+                region,
+            } => {
+                env.push(("CLAUDE_CODE_USE_BEDROCK", "1".to_string()));
+                env.push(("AWS_ACCESS_KEY_ID", access_key_id.trim().to_string()));
+                env.push((
+                    "AWS_SECRET_ACCESS_KEY",
+                    secret_access_key.trim().to_string(),
+                ));
+                if let Some(token) = session_token.as_deref().map(str::trim) {
+                    if !token.is_empty() {
+                        env.push(("AWS_SESSION_TOKEN", token.to_string()));
+                    }
+                }
+                env.push(("AWS_REGION", region.trim().to_string()));
+            }
+            Self::Vertex {
+                project_id,
+                region,
+                credentials_file,
+            } => {
+                env.push(("CLAUDE_CODE_USE_VERTEX", "1".to_string()));
+                env.push(("ANTHROPIC_VERTEX_PROJECT_ID", project_id.trim().to_string()));
+                env.push(("CLOUD_ML_REGION", region.trim().to_string()));
+                if let Some(path) = credentials_file.as_deref().map(str::trim) {
+                    if !path.is_empty() {
+                        env.push(("GOOGLE_APPLICATION_CREDENTIALS", path.to_string()));
+                    }
+                }
+            }
+        }
+        env
+    }
+}
+
+/// Worktrees share their parent's credentials.
+fn owner_id(entry: &WorkspaceEntry) -> &str {
+    entry.parent_id.as_deref().unwrap_or(&entry.id)
+}
+
+fn keychain_entry(workspace_id: &str) -> Result<Entry, String> {
+    Entry::new(KEYCHAIN_SERVICE, &format!("workspace:{workspace_id}"))
+        .map_err(|err| format!("The OS keychain is unavailable: {err}"))
+}
+
+fn read_credentials(workspace_id: &str) -> Result<Option<WorkspaceCredentials>, String> {
+    match keychain_entry(workspace_id)?.get_password() {
+        Ok(raw) => serde_json::from_str(&raw)
+            .map(Some)
+            .map_err(|err| format!("Stored credentials are unreadable: {err}")),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(format!("Failed to read workspace credentials: {err}")),
+    }
+}
+
+fn write_credentials(workspace_id: &str, credentials: &WorkspaceCredentials) -> Result<(), String> {
+    let raw = serde_json::to_string(credentials).map_err(|err| err.to_string())?;
+    keychain_entry(workspace_id)?
+        .set_password(&raw)
+        .map_err(|err| format!("Failed to store workspace credentials: {err}"))
+}
+
+pub(crate) fn delete_credentials(workspace_id: &str) -> Result<(), String> {
+    match keychain_entry(workspace_id)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(format!("Failed to remove workspace credentials: {err}")),
+    }
+}
+
+/// The credentials a workspace's processes run with, if it has any. Reading the keychain
+/// may block on an unlock prompt, so it happens off the async runtime.
+pub(crate) async fn for_workspace(
+    entry: &WorkspaceEntry,
+) -> Result<Option<WorkspaceCredentials>, String> {
+    let workspace_id = owner_id(entry).to_string();
+    tokio::task::spawn_blocking(move || read_credentials(&workspace_id))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// Set the provider environment of a process the workspace starts, from its provider
+/// settings and credentials.
+pub(crate) async fn apply_provider_env(
+    entry: &WorkspaceEntry,
+    command: &mut Command,
+) -> Result<(), String> {
+    let credentials = for_workspace(entry).await?;
+    let env = provider_env(entry.settings.provider.as_ref(), credentials.as_ref())?;
+    if let Some(env) = env {
+        for name in PROVIDER_VARS {
+            command.env_remove(name);
+        }
+        command.envs(env);
+    }
+    Ok(())
+}
+
+async fn workspace_entry(state: &AppState, workspace_id: &str) -> Result<WorkspaceEntry, String> {
+    state
+        .workspaces
+        .lock()
+        .await
+        .get(workspace_id)
+        .cloned()
+        .ok_or_else(|| "workspace not found".to_string())
+}
+
+fn summary(credentials: Option<&WorkspaceCredentials>) -> Value {
+    json!({ "provider": credentials.map(WorkspaceCredentials::provider) })
+}
+
+/// Which provider a workspace's credentials are for; `provider` is null without any.
+/// The secrets themselves are never returned.
+#[tauri::command]
+pub(crate) async fn get_workspace_credentials(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "get_workspace_credentials",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+    let entry = workspace_entry(&state, &workspace_id).await?;
+    Ok(summary(for_workspace(&entry).await?.as_ref()))
+}
+
+/// Store a workspace's credentials, or remove them with `null`. Processes started from
+/// then on use them; running threads keep theirs until they are restarted.
+#[tauri::command]
+pub(crate) async fn set_workspace_credentials(
+    workspace_id: String,
+    credentials: Option<WorkspaceCredentials>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "set_workspace_credentials",
+            json!({ "workspaceId": workspace_id, "credentials": credentials }),
+        )
+        .await;
+    }
+    let entry = workspace_entry(&state, &workspace_id).await?;
+    if entry.kind.is_worktree() {
+        return Err("Worktrees use their parent workspace's credentials.".to_string());
+    }
+    if let Some(credentials) = &credentials {
+        credentials.validate()?;
+    }
+    let stored = credentials.clone();
+    tokio::task::spawn_blocking(move || match &stored {
+        Some(credentials) => write_credentials(&workspace_id, credentials),
+        None => delete_credentials(&workspace_id),
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    Ok(summary(credentials.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_become_provider_environment() {
+        let bedrock: WorkspaceCredentials = serde_json::from_value(json!({
+            "provider": "bedrock",
+            "accessKeyId": "AKIAEXAMPLE",
+            "secretAccessKey": " secret ",
+            "sessionToken": "",
+            "region": "us-east-1",
+        }))
+        .unwrap();
+        assert_eq!(
+            bedrock.env(),
+            vec![
+                ("CLAUDE_CODE_USE_BEDROCK", "1".to_string()),
+                ("AWS_ACCESS_KEY_ID", "AKIAEXAMPLE".to_string()),
+                ("AWS_SECRET_ACCESS_KEY", "secret".to_string()),
+                ("AWS_REGION", "us-east-1".to_string()),
+            ]
+        );
+        assert_eq!(format!("{bedrock:?}"), "WorkspaceCredentials(Bedrock)");
+
+        let vertex = WorkspaceCredentials::Vertex {
+            project_id: "proj".to_string(),
+            region: "us-east5".to_string(),
+            credentials_file: Some("/keys/sa.json".to_string()),
+        };
+        assert_eq!(
+            vertex.env(),
+            vec![
+                ("CLAUDE_CODE_USE_VERTEX", "1".to_string()),
+                ("ANTHROPIC_VERTEX_PROJECT_ID", "proj".to_string()),
+                ("CLOUD_ML_REGION", "us-east5".to_string()),
+                (
+                    "GOOGLE_APPLICATION_CREDENTIALS",
+                    "/keys/sa.json".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_missing_fields() {
+        let anthropic = WorkspaceCredentials::Anthropic {
+            api_key: "  ".to_string(),
+        };
+        assert_eq!(anthropic.validate(), Err("apiKey is required".to_string()));
+        let vertex = WorkspaceCredentials::Vertex {
+            project_id: "proj".to_string(),
+            region: String::new(),
+            credentials_file: None,
+        };
+        assert_eq!(vertex.validate(), Err("region is required".to_string()));
+    }
+}
@@ -0,0 +1,1940 @@
+//! Headless control surface for `claude_code_monitor_daemon`.
+//!
+//! The daemon runs the same backend as the app without a window and accepts
+//! newline-delimited JSON-RPC 2.0 on a Unix domain socket (a named pipe on Windows).
+//! Methods mirror the Tauri commands and take the same camelCase parameters; events
+//! the app would receive are streamed to clients that call `subscribe`. With
+//! `--listen` the same events are also served read-only over an authenticated
+//! WebSocket (see [`websocket`]) for watching sessions from another device.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::state::AppState;
+use crate::{
+    activity, agents, annotations, api_tokens, attach, autostart, batch, budget, claude, claude_md,
+    claude_settings, cli_manager, config_bundle, context_pack, credentials, disk_usage, doctor,
+    event_sink, export, file_index, github, hooks, issues, mcp, operations, outbox, pipeline,
+    prompt_library, proxy, recovery, redaction, replay, retention, scheduler, search,
+    slash_commands, storage_encryption, tail, terminal, token_estimate, transcripts, usage,
+    workspace_discovery, workspace_tags, workspaces,
+};
+
+pub(crate) mod ci;
+mod exporter;
+mod otlp;
+mod rest;
+mod rpc;
+pub(crate) mod service;
+pub(crate) mod slack;
+mod websocket;
+
+use rpc::{optional, required, to_result, RpcError, Subscription};
+
+const SOCKET_ENV: &str = "CLAUDE_CODE_MONITOR_SOCKET";
+const LISTEN_ENV: &str = "CLAUDE_CODE_MONITOR_LISTEN";
+const TOKEN_ENV: &str = "CLAUDE_CODE_MONITOR_TOKEN";
+const METRICS_ENV: &str = "CLAUDE_CODE_MONITOR_METRICS";
+const REST_ENV: &str = "CLAUDE_CODE_MONITOR_REST";
+const SLACK_ENV: &str = "CLAUDE_CODE_MONITOR_SLACK";
+const OTLP_ENV: &str = "CLAUDE_CODE_MONITOR_OTLP";
+/// The standard OpenTelemetry variable, honoured when `OTLP_ENV` is unset.
+const OTEL_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+#[cfg(unix)]
+const DEFAULT_SOCKET_NAME: &str = "daemon.sock";
+#[cfg(windows)]
+const DEFAULT_PIPE_NAME: &str = r"\\.\pipe\claude-code-monitor";
+const EVENT_BUFFER: usize = 1024;
+
+/// Methods served by the daemon besides `subscribe`/`unsubscribe`.
+const METHODS: &[&str] = &[
+    "ping",
+    "list_workspaces",
+    "archive_workspace",
+    "unarchive_workspace",
+    "set_workspace_pinned",
+    "aggregate_workspaces",
+    "connect_workspace",
+    "add_workspace",
+    "is_workspace_path_dir",
+    "discover_workspaces",
+    "rename_worktree",
+    "rename_worktree_upstream",
+    "list_workspace_files",
+    "read_workspace_file",
+    "start_thread",
+    "list_threads",
+    "resume_thread",
+    "archive_thread",
+    "resume_session",
+    "list_workspace_sessions",
+    "fork_session",
+    "list_session_forks",
+    "list_thread_sessions",
+    "send_user_message",
+    "start_review",
+    "turn_interrupt",
+    "list_thread_owners",
+    "take_over_thread",
+    "list_turn_queue",
+    "cancel_queued_prompt",
+    "reorder_queued_prompt",
+    "list_pending_messages",
+    "cancel_pending_message",
+    "flush_pending_messages",
+    "session_health",
+    "suspend_workspace_sessions",
+    "resume_workspace_sessions",
+    "focus_workspace",
+    "list_workspace_threads",
+    "complete_paths",
+    "model_list",
+    "collaboration_mode_list",
+    "skills_list",
+    "respond_to_permission_request",
+    "respond_to_server_request",
+    "list_pending_interactions",
+    "list_pending_plans",
+    "approve_plan",
+    "reject_plan",
+    "set_permission_preset",
+    "get_claude_settings",
+    "update_claude_settings",
+    "list_mcp_servers",
+    "get_mcp_status",
+    "list_hooks",
+    "add_hook_definition",
+    "update_hook_definition",
+    "remove_hook_definition",
+    "test_hook",
+    "list_claude_md_files",
+    "read_claude_md",
+    "write_claude_md",
+    "start_transcript_tail",
+    "stop_transcript_tail",
+    "list_external_sessions",
+    "attach_external_session",
+    "detach_external_session",
+    "get_turn_metrics",
+    "get_usage_report",
+    "export_usage",
+    "usage_turns",
+    "usage_daily",
+    "usage_workspaces",
+    "get_turn_timeline",
+    "list_compactions",
+    "get_session_logs",
+    "get_session_resources",
+    "get_turn_diff",
+    "get_turn_edits",
+    "list_bash_decisions",
+    "get_activity",
+    "cancel_operation",
+    "list_operations",
+    "get_event_stats",
+    "export_session",
+    "create_pr_from_turn",
+    "start_issue_turn",
+    "list_issue_links",
+    "post_issue_comment",
+    "search_transcripts",
+    "annotate",
+    "list_annotations",
+    "search_annotations",
+    "get_budget_status",
+    "override_budget",
+    "list_recordings",
+    "replay_session",
+    "list_orphaned_sessions",
+    "adopt_orphaned_session",
+    "kill_orphaned_session",
+    "reveal_redacted_secret",
+    "get_storage_encryption",
+    "set_storage_encryption",
+    "get_workspace_credentials",
+    "set_workspace_credentials",
+    "check_provider_connectivity",
+    "test_proxy_connectivity",
+    "list_claude_cli_installations",
+    "check_claude_cli_updates",
+    "update_claude_cli",
+    "run_doctor",
+    "apply_retention_policy",
+    "get_disk_usage",
+    "run_disk_cleanup",
+    "export_config",
+    "import_config",
+    "get_rate_limit_status",
+    "prompt_templates_list",
+    "prompt_template_save",
+    "prompt_template_delete",
+    "prompt_template_render",
+    "prompt_template_send",
+    "context_packs_list",
+    "context_pack_save",
+    "context_pack_delete",
+    "context_pack_preview",
+    "estimate_tokens",
+    "list_slash_commands",
+    "run_slash_command",
+    "list_subagents",
+    "save_subagent",
+    "delete_subagent",
+    "run_subagent",
+    "schedules_list",
+    "schedule_save",
+    "schedule_delete",
+    "schedule_set_enabled",
+    "schedule_run_now",
+    "schedule_runs_list",
+    "batch_run",
+    "batches_list",
+    "batch_cancel",
+    "create_pipeline",
+    "list_pipelines",
+    "cancel_pipeline",
+    "list_api_tokens",
+    "issue_api_token",
+    "revoke_api_token",
+    "list_api_audit_log",
+    "terminal_open",
+    "terminal_write",
+    "terminal_resize",
+    "terminal_kill",
+    "terminal_close",
+];
+
+#[derive(Debug, Clone)]
+pub(crate) struct DaemonEvent {
+    method: &'static str,
+    params: Value,
+}
+
+/// Managed only by the daemon; the desktop app never registers it, so publishing is a
+/// no-op there.
+pub(crate) struct DaemonState {
+    events: broadcast::Sender<DaemonEvent>,
+    socket_path: PathBuf,
+}
+
+/// Forward a backend event to subscribed daemon clients. `method` matches the Tauri event
+/// name, e.g. `app-server-event`.
+pub(crate) fn publish<T: Serialize>(app: &AppHandle, method: &'static str, payload: &T) {
+    let Some(daemon) = app.try_state::<DaemonState>() else {
+        return;
+    };
+    if daemon.events.receiver_count() == 0 {
+        return;
+    }
+    if let Ok(params) = serde_json::to_value(payload) {
+        let _ = daemon.events.send(DaemonEvent { method, params });
+    }
+}
+
+/// Command-line configuration of the daemon.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct DaemonOptions {
+    /// Control socket path, or pipe name on Windows.
+    pub(crate) socket: Option<PathBuf>,
+    /// `host:port` for the WebSocket event feed; disabled when unset.
+    pub(crate) listen: Option<String>,
+    /// Shared secret remote clients may present as an operator, besides issued API
+    /// tokens.
+    pub(crate) token: Option<String>,
+    /// `host:port` for the Prometheus metrics endpoint; disabled when unset.
+    pub(crate) metrics: Option<String>,
+    /// `host:port` for the REST API; disabled when unset.
+    pub(crate) rest: Option<String>,
+    /// `host:port` for Slack's slash command and button requests; disabled when unset.
+    pub(crate) slack: Option<String>,
+    /// OTLP/HTTP collector URL to push traces and metrics to; disabled when unset.
+    pub(crate) otlp: Option<String>,
+}
+
+const USAGE: &str =
+    "usage: claude_code_monitor_daemon [--socket <path>] [--listen <host:port>] [--token <token>] \
+     [--metrics <host:port>] [--rest <host:port>] [--slack <host:port>] [--otlp <url>]";
+
+/// Read options from the command line, falling back to environment variables.
+pub(crate) fn options_from_env() -> Result<DaemonOptions, String> {
+    let mut options = parse_args(std::env::args().skip(1))?;
+    let env = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    };
+    options.socket = options
+        .socket
+        .or_else(|| env(SOCKET_ENV).map(PathBuf::from));
+    options.listen = options.listen.or_else(|| env(LISTEN_ENV));
+    options.token = options.token.or_else(|| env(TOKEN_ENV));
+    options.metrics = options.metrics.or_else(|| env(METRICS_ENV));
+    options.rest = options.rest.or_else(|| env(REST_ENV));
+    options.slack = options.slack.or_else(|| env(SLACK_ENV));
+    options.otlp = options
+        .otlp
+        .or_else(|| env(OTLP_ENV))
+        .or_else(|| env(OTEL_ENDPOINT_ENV));
+    Ok(options)
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<DaemonOptions, String> {
+    let mut options = DaemonOptions::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        if !matches!(
+            flag.as_str(),
+            "--socket" | "--listen" | "--token" | "--metrics" | "--rest" | "--slack" | "--otlp"
+        ) {
+            return Err(format!("unknown argument `{arg}`\n{USAGE}"));
+        }
+        let value = match inline.or_else(|| args.next()) {
+            Some(value) if !value.trim().is_empty() => value,
+            _ => return Err(format!("{flag} needs a value\n{USAGE}")),
+        };
+        match flag.as_str() {
+            "--socket" => options.socket = Some(PathBuf::from(value)),
+            "--listen" => options.listen = Some(value),
+            "--metrics" => options.metrics = Some(value),
+            "--rest" => options.rest = Some(value),
+            "--slack" => options.slack = Some(value),
+            "--otlp" => options.otlp = Some(value),
+            _ => options.token = Some(value),
+        }
+    }
+    Ok(options)
+}
+
+/// Bind the control socket (and the WebSocket feed when configured), connect saved
+/// workspaces and start serving clients.
+pub(crate) fn start(app: &AppHandle, options: DaemonOptions) -> Result<(), String> {
+    let socket_path = match options.socket {
+        Some(path) => path,
+        None => default_socket_path(app)?,
+    };
+    let listener = bind(&socket_path)?;
+    let metrics = match &options.metrics {
+        Some(address) => Some(bind_metrics(address)?),
+        None => None,
+    };
+    let rest = match &options.rest {
+        Some(address) => Some(bind_rest(address)?),
+        None => None,
+    };
+    let websocket = match &options.listen {
+        Some(listen) => Some(bind_websocket(listen)?),
+        None => None,
+    };
+    let slack_bridge = slack::SlackBridge::from_env(options.slack.is_some())?;
+    let slack = match &options.slack {
+        Some(address) => Some(bind_slack(address)?),
+        None => None,
+    };
+    if let Some(bridge) = slack_bridge {
+        app.manage(bridge);
+    }
+    let otlp = match &options.otlp {
+        Some(endpoint) => Some(otlp::OtlpConfig::from_env(endpoint)?),
+        None => None,
+    };
+    let (events, _) = broadcast::channel(EVENT_BUFFER);
+    app.manage(DaemonState {
+        events,
+        socket_path: socket_path.clone(),
+    });
+    tracing::info!("listening on {}", socket_path.display());
+
+    let app_for_workspaces = app.clone();
+    tauri::async_runtime::spawn(async move {
+        connect_saved_workspaces(app_for_workspaces.clone()).await;
+        autostart::start_pinned(app_for_workspaces).await;
+    });
+    if let Some(listener) = websocket {
+        let app = app.clone();
+        let token = options.token.clone();
+        tauri::async_runtime::spawn(async move {
+            websocket::serve(app, listener, token).await;
+        });
+    }
+    if let Some(listener) = rest {
+        let app = app.clone();
+        let token = options.token.clone();
+        tauri::async_runtime::spawn(async move {
+            rest::serve(app, listener, token).await;
+        });
+    }
+    if let Some(listener) = slack {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            slack::serve(app, listener).await;
+        });
+    }
+    if let Some(config) = otlp {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            otlp::run(app, config).await;
+        });
+    }
+    if let Some(listener) = metrics {
+        let app = app.clone();
+        let token = options.token;
+        tauri::async_runtime::spawn(async move {
+            exporter::serve(app, listener, token).await;
+        });
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        accept_loop(app, listener).await;
+    });
+    Ok(())
+}
+
+fn bind_metrics(address: &str) -> Result<std::net::TcpListener, String> {
+    let listener = std::net::TcpListener::bind(address)
+        .map_err(|err| format!("Failed to serve metrics on {address}: {err}"))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|err| err.to_string())?;
+    tracing::info!("serving metrics on http://{address}/metrics");
+    Ok(listener)
+}
+
+fn bind_rest(address: &str) -> Result<std::net::TcpListener, String> {
+    let listener = std::net::TcpListener::bind(address)
+        .map_err(|err| format!("Failed to serve the REST API on {address}: {err}"))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|err| err.to_string())?;
+    tracing::info!("serving the REST API on http://{address}/v1 (spec at /openapi.json)");
+    Ok(listener)
+}
+
+fn bind_slack(address: &str) -> Result<std::net::TcpListener, String> {
+    let listener = std::net::TcpListener::bind(address)
+        .map_err(|err| format!("Failed to serve Slack requests on {address}: {err}"))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|err| err.to_string())?;
+    tracing::info!("serving Slack requests on http://{address}/slack");
+    Ok(listener)
+}
+
+fn bind_websocket(listen: &str) -> Result<std::net::TcpListener, String> {
+    let listener = std::net::TcpListener::bind(listen)
+        .map_err(|err| format!("Failed to listen on {listen}: {err}"))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|err| err.to_string())?;
+    tracing::info!("streaming events on ws://{listen}/events");
+    Ok(listener)
+}
+
+/// Remove the socket file on exit so the next start does not trip over it.
+pub(crate) fn shutdown(app: &AppHandle) {
+    #[cfg(unix)]
+    if let Some(daemon) = app.try_state::<DaemonState>() {
+        let _ = std::fs::remove_file(&daemon.socket_path);
+    }
+    #[cfg(not(unix))]
+    let _ = app;
+}
+
+#[cfg(unix)]
+fn default_socket_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(DEFAULT_SOCKET_NAME))
+        .map_err(|err| format!("Failed to resolve app data dir: {err}"))
+}
+
+#[cfg(windows)]
+fn default_socket_path(_app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(PathBuf::from(DEFAULT_PIPE_NAME))
+}
+
+#[cfg(unix)]
+type Listener = std::os::unix::net::UnixListener;
+
+/// The socket is the only access control, so it is created owner-only. A stale socket
+/// left by a crashed daemon is replaced; a live one is an error.
+#[cfg(unix)]
+fn bind(path: &std::path::Path) -> Result<Listener, String> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(format!(
+                "Another daemon is already listening on {}",
+                path.display()
+            ));
+        }
+        std::fs::remove_file(path)
+            .map_err(|err| format!("Failed to remove stale socket: {err}"))?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create socket directory: {err}"))?;
+    }
+    let listener = UnixListener::bind(path)
+        .map_err(|err| format!("Failed to bind {}: {err}", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|err| format!("Failed to restrict socket permissions: {err}"))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|err| err.to_string())?;
+    Ok(listener)
+}
+
+#[cfg(windows)]
+type Listener = tokio::net::windows::named_pipe::NamedPipeServer;
+
+#[cfg(windows)]
+fn bind(path: &std::path::Path) -> Result<Listener, String> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let name = path.as_os_str().to_owned();
+    tauri::async_runtime::block_on(async move {
+        ServerOptions::new()
+            .first_pipe_instance(true)
+            .create(&name)
+            .map_err(|err| format!("Failed to create pipe {}: {err}", name.to_string_lossy()))
+    })
+}
+
+#[cfg(unix)]
+async fn accept_loop(app: AppHandle, listener: Listener) {
+    let listener = match tokio::net::UnixListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::warn!("failed to listen: {err}");
+            return;
+        }
+    };
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(serve_connection(app.clone(), stream));
+            }
+            Err(err) => {
+                tracing::warn!("accept failed: {err}");
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+/// Named pipes serve one client per instance, so a fresh instance is created before
+/// handing the connected one off.
+#[cfg(windows)]
+async fn accept_loop(app: AppHandle, mut server: Listener) {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let name = app.state::<DaemonState>().socket_path.clone();
+    loop {
+        if let Err(err) = server.connect().await {
+            tracing::warn!("accept failed: {err}");
+            continue;
+        }
+        let next = match ServerOptions::new().create(&name) {
+            Ok(next) => next,
+            Err(err) => {
+                tracing::warn!("failed to create pipe: {err}");
+                return;
+            }
+        };
+        let connected = std::mem::replace(&mut server, next);
+        tokio::spawn(serve_connection(app.clone(), connected));
+    }
+}
+
+/// Without a window nothing else connects workspaces, so the daemon does it on start.
+async fn connect_saved_workspaces(app: AppHandle) {
+    let ids: Vec<String> = {
+        let state = app.state::<AppState>();
+        let workspaces = state.workspaces.lock().await;
+        workspaces.keys().cloned().collect()
+    };
+    for id in ids {
+        if let Err(err) =
+            workspaces::connect_workspace(id.clone(), app.state::<AppState>(), app.clone()).await
+        {
+            tracing::warn!("failed to connect workspace {id}: {err}");
+        }
+    }
+}
+
+async fn serve_connection<S>(app: AppHandle, stream: S)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
+    tokio::spawn(async move {
+        while let Some(message) = out_rx.recv().await {
+            if writer.write_all(message.as_bytes()).await.is_err()
+                || writer.write_all(b"\n").await.is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    let mut subscription: Option<JoinHandle<()>> = None;
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let request = match rpc::parse_request(trimmed) {
+            Ok(request) => request,
+            Err(error) => {
+                let _ = out_tx.send(rpc::response(Value::Null, Err(error)));
+                continue;
+            }
+        };
+        match request.method.as_str() {
+            "subscribe" => {
+                let result = Subscription::from_params(&request.params).map(|filter| {
+                    if let Some(task) = subscription.take() {
+                        task.abort();
+                    }
+                    subscription = Some(forward_events(&app, filter, out_tx.clone()));
+                    json!({ "ok": true })
+                });
+                reply(&out_tx, request.id, result);
+            }
+            "unsubscribe" => {
+                if let Some(task) = subscription.take() {
+                    task.abort();
+                }
+                reply(&out_tx, request.id, Ok(json!({ "ok": true })));
+            }
+            _ => {
+                let app = app.clone();
+                let out_tx = out_tx.clone();
+                tokio::spawn(async move {
+                    let result = dispatch(&app, &request.method, &request.params).await;
+                    reply(&out_tx, request.id, result);
+                });
+            }
+        }
+    }
+    if let Some(task) = subscription {
+        task.abort();
+    }
+}
+
+fn reply(
+    out_tx: &mpsc::UnboundedSender<String>,
+    id: Option<Value>,
+    result: Result<Value, RpcError>,
+) {
+    if let Some(id) = id {
+        let _ = out_tx.send(rpc::response(id, result));
+    }
+}
+
+fn forward_events(
+    app: &AppHandle,
+    filter: Subscription,
+    out_tx: mpsc::UnboundedSender<String>,
+) -> JoinHandle<()> {
+    let mut events = app.state::<DaemonState>().events.subscribe();
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if !filter.matches(event.method, &event.params) {
+                        continue;
+                    }
+                    if out_tx
+                        .send(rpc::notification(event.method, &event.params))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+                // Slow clients miss events rather than stall the backend
+                Err(RecvError::Lagged(skipped)) => {
+                    crate::event_sink::note_dropped_events(skipped);
+                    let params = json!({ "skipped": skipped });
+                    if out_tx
+                        .send(rpc::notification("daemon/eventsDropped", &params))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+async fn dispatch(app: &AppHandle, method: &str, params: &Value) -> Result<Value, RpcError> {
+    let state = || app.state::<AppState>();
+    match method {
+        "ping" => Ok(json!({ "ok": true, "methods": METHODS })),
+        "list_workspaces" => to_result(
+            workspaces::list_workspaces(optional(params, "includeArchived")?, state(), app.clone())
+                .await,
+        ),
+        "archive_workspace" => to_result(
+            workspaces::archive_workspace(required(params, "id")?, state(), app.clone()).await,
+        ),
+        "unarchive_workspace" => to_result(
+            workspaces::unarchive_workspace(required(params, "id")?, state(), app.clone()).await,
+        ),
+        "set_workspace_pinned" => to_result(
+            workspaces::set_workspace_pinned(
+                required(params, "id")?,
+                required(params, "pinned")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "aggregate_workspaces" => to_result(
+            workspace_tags::aggregate_workspaces(
+                required(params, "by")?,
+                optional(params, "key")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "connect_workspace" => to_result(
+            workspaces::connect_workspace(required(params, "id")?, state(), app.clone()).await,
+        ),
+        "add_workspace" => to_result(
+            workspaces::add_workspace(
+                required(params, "path")?,
+                optional(params, "claude_bin")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "is_workspace_path_dir" => to_result(
+            workspaces::is_workspace_path_dir(required(params, "path")?, state(), app.clone())
+                .await,
+        ),
+        "discover_workspaces" => to_result(
+            workspace_discovery::discover_workspaces(
+                optional(params, "roots")?,
+                optional(params, "maxDepth")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "rename_worktree" => to_result(
+            workspaces::rename_worktree(
+                required(params, "id")?,
+                required(params, "branch")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "rename_worktree_upstream" => to_result(
+            workspaces::rename_worktree_upstream(
+                required(params, "id")?,
+                required(params, "oldBranch")?,
+                required(params, "newBranch")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_workspace_files" => to_result(
+            workspaces::list_workspace_files(
+                required(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "read_workspace_file" => to_result(
+            workspaces::read_workspace_file(
+                required(params, "workspaceId")?,
+                required(params, "path")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "start_thread" => to_result(
+            claude::start_thread(required(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "list_threads" => to_result(
+            claude::list_threads(
+                required(params, "workspaceId")?,
+                optional(params, "cursor")?,
+                optional(params, "limit")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "resume_thread" => to_result(
+            claude::resume_thread(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "archive_thread" => to_result(
+            claude::archive_thread(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                optional(params, "removeWorktree")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "resume_session" => to_result(
+            claude::resume_session(
+                required(params, "workspaceId")?,
+                optional(params, "sessionId")?,
+                optional(params, "model")?,
+                optional(params, "accessMode")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_workspace_sessions" => to_result(
+            transcripts::list_workspace_sessions(
+                required(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "fork_session" => to_result(
+            claude::fork_session(
+                required(params, "workspaceId")?,
+                required(params, "sessionId")?,
+                required(params, "messageIndex")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_session_forks" => to_result(
+            claude::list_session_forks(required(params, "workspaceId")?, state(), app.clone())
+                .await,
+        ),
+        "list_thread_sessions" => to_result(
+            claude::list_thread_sessions(required(params, "workspaceId")?, state(), app.clone())
+                .await,
+        ),
+        "send_user_message" => to_result(
+            claude::send_user_message(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                required(params, "text")?,
+                optional(params, "model")?,
+                optional(params, "effort")?,
+                optional(params, "accessMode")?,
+                optional(params, "images")?,
+                optional(params, "attachments")?,
+                optional(params, "contextPack")?,
+                optional(params, "collaborationMode")?,
+                optional(params, "user")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "start_review" => to_result(
+            claude::start_review(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                required(params, "target")?,
+                optional(params, "delivery")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "turn_interrupt" => to_result(
+            claude::turn_interrupt(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                required(params, "turnId")?,
+                optional(params, "user")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_thread_owners" => to_result(
+            claude::list_thread_owners(required(params, "workspaceId")?, state(), app.clone())
+                .await,
+        ),
+        "take_over_thread" => to_result(
+            claude::take_over_thread(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                optional(params, "user")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_turn_queue" => to_result(
+            claude::list_turn_queue(required(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "cancel_queued_prompt" => to_result(
+            claude::cancel_queued_prompt(
+                required(params, "workspaceId")?,
+                required(params, "promptId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "reorder_queued_prompt" => to_result(
+            claude::reorder_queued_prompt(
+                required(params, "workspaceId")?,
+                required(params, "promptId")?,
+                required(params, "position")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_pending_messages" => to_result(
+            outbox::list_pending_messages(required(params, "workspaceId")?, state(), app.clone())
+                .await,
+        ),
+        "cancel_pending_message" => to_result(
+            outbox::cancel_pending_message(
+                required(params, "workspaceId")?,
+                required(params, "messageId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "flush_pending_messages" => to_result(
+            outbox::flush_pending_messages(required(params, "workspaceId")?, state(), app.clone())
+                .await,
+        ),
+        "session_health" => to_result(
+            claude::session_health(
+                required(params, "workspaceId")?,
+                optional(params, "threadId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "suspend_workspace_sessions" => to_result(
+            claude::suspend_workspace_sessions(
+                required(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "resume_workspace_sessions" => to_result(
+            claude::resume_workspace_sessions(
+                required(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "focus_workspace" => to_result(
+            claude::focus_workspace(optional(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "list_workspace_threads" => to_result(
+            claude::list_workspace_threads(required(params, "workspaceId")?, state(), app.clone())
+                .await,
+        ),
+        "complete_paths" => to_result(
+            file_index::complete_paths(
+                required(params, "workspaceId")?,
+                required(params, "prefix")?,
+                optional(params, "limit")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "model_list" => to_result(
+            claude::model_list(required(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "collaboration_mode_list" => to_result(
+            claude::collaboration_mode_list(required(params, "workspaceId")?, state(), app.clone())
+                .await,
+        ),
+        "skills_list" => to_result(
+            claude::skills_list(required(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "respond_to_permission_request" => to_result(
+            claude::respond_to_permission_request(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                required(params, "requestId")?,
+                required(params, "decision")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "respond_to_server_request" => to_result(
+            claude::respond_to_server_request(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                required(params, "toolUseId")?,
+                required(params, "result")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_pending_interactions" => to_result(
+            claude::list_pending_interactions(
+                required(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_pending_plans" => to_result(
+            claude::list_pending_plans(required(params, "workspaceId")?, state(), app.clone())
+                .await,
+        ),
+        "approve_plan" => to_result(
+            claude::approve_plan(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                optional(params, "mode")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "reject_plan" => to_result(
+            claude::reject_plan(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                optional(params, "feedback")?.unwrap_or_default(),
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "set_permission_preset" => to_result(
+            claude::set_permission_preset(
+                required(params, "workspaceId")?,
+                optional(params, "preset")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "get_claude_settings" => to_result(
+            claude_settings::get_claude_settings(
+                required(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "update_claude_settings" => to_result(
+            claude_settings::update_claude_settings(
+                required(params, "workspaceId")?,
+                required(params, "scope")?,
+                required(params, "update")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_mcp_servers" => to_result(
+            mcp::list_mcp_servers(required(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "get_mcp_status" => to_result(
+            mcp::get_mcp_status(
+                required(params, "workspaceId")?,
+                optional(params, "threadId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_hooks" => to_result(
+            hooks::list_hooks(required(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "add_hook_definition" => to_result(
+            hooks::add_hook_definition(
+                required(params, "workspaceId")?,
+                required(params, "scope")?,
+                required(params, "hook")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "update_hook_definition" => to_result(
+            hooks::update_hook_definition(
+                required(params, "workspaceId")?,
+                required(params, "scope")?,
+                required(params, "id")?,
+                required(params, "hook")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "remove_hook_definition" => to_result(
+            hooks::remove_hook_definition(
+                required(params, "workspaceId")?,
+                required(params, "scope")?,
+                required(params, "id")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "test_hook" => to_result(
+            hooks::test_hook(
+                required(params, "workspaceId")?,
+                required(params, "hook")?,
+                optional(params, "payload")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_claude_md_files" => to_result(
+            claude_md::list_claude_md_files(required(params, "workspaceId")?, state(), app.clone())
+                .await,
+        ),
+        "read_claude_md" => to_result(
+            claude_md::read_claude_md(
+                required(params, "workspaceId")?,
+                required(params, "path")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "write_claude_md" => to_result(
+            claude_md::write_claude_md(
+                required(params, "workspaceId")?,
+                required(params, "path")?,
+                required(params, "content")?,
+                optional(params, "expectedModifiedAt")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "start_transcript_tail" => to_result(
+            tail::start_transcript_tail(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                optional(params, "fromStart")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "stop_transcript_tail" => to_result(
+            tail::stop_transcript_tail(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_external_sessions" => {
+            to_result(attach::list_external_sessions(state(), app.clone()).await)
+        }
+        "attach_external_session" => to_result(
+            attach::attach_external_session(
+                required(params, "workspaceId")?,
+                required(params, "sessionId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "detach_external_session" => to_result(
+            attach::detach_external_session(
+                required(params, "workspaceId")?,
+                required(params, "sessionId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "get_usage_report" => to_result(
+            usage::get_usage_report(
+                optional(params, "range")?,
+                optional(params, "groupBy")?,
+                optional(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "export_usage" => to_result(
+            usage::export_usage(
+                optional(params, "since")?,
+                optional(params, "until")?,
+                required(params, "format")?,
+                optional(params, "path")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "usage_turns" => to_result(
+            usage::usage_turns(
+                optional(params, "workspaceId")?,
+                optional(params, "sessionId")?,
+                optional(params, "limit")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "usage_daily" => to_result(
+            usage::usage_daily(
+                optional(params, "days")?,
+                optional(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "usage_workspaces" => to_result(
+            usage::usage_workspaces(optional(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "get_turn_metrics" => to_result(
+            usage::get_turn_metrics(
+                optional(params, "workspaceId")?,
+                optional(params, "limit")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "get_turn_timeline" => to_result(
+            claude::get_turn_timeline(
+                required(params, "workspaceId")?,
+                required(params, "turnId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_compactions" => to_result(
+            claude::list_compactions(
+                required(params, "workspaceId")?,
+                optional(params, "threadId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "get_session_logs" => to_result(
+            claude::get_session_logs(
+                required(params, "workspaceId")?,
+                optional(params, "lines")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "get_session_resources" => to_result(
+            claude::get_session_resources(required(params, "workspaceId")?, state(), app.clone())
+                .await,
+        ),
+        "get_turn_diff" => to_result(
+            claude::get_turn_diff(
+                required(params, "workspaceId")?,
+                required(params, "turnId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "get_turn_edits" => to_result(
+            claude::get_turn_edits(
+                required(params, "workspaceId")?,
+                required(params, "turnId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_bash_decisions" => to_result(
+            claude::list_bash_decisions(
+                required(params, "workspaceId")?,
+                optional(params, "limit")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "get_activity" => to_result(
+            activity::get_activity(
+                optional(params, "since")?,
+                optional(params, "workspaceId")?,
+                optional(params, "minSeverity")?,
+                optional(params, "limit")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "cancel_operation" => to_result(
+            operations::cancel_operation(
+                required(params, "operationId")?,
+                optional(params, "user")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_operations" => to_result(operations::list_operations(state(), app.clone()).await),
+        "get_event_stats" => to_result(event_sink::get_event_stats(state(), app.clone()).await),
+        "export_session" => to_result(
+            export::export_session(
+                required(params, "workspaceId")?,
+                required(params, "sessionId")?,
+                required(params, "format")?,
+                optional(params, "path")?,
+                optional(params, "operationId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "create_pr_from_turn" => to_result(
+            github::create_pr_from_turn(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                required(params, "turnId")?,
+                optional(params, "branch")?,
+                optional(params, "base")?,
+                optional(params, "draft")?,
+                optional(params, "shareTranscript")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "start_issue_turn" => to_result(
+            issues::start_issue_turn(
+                required(params, "issue")?,
+                optional(params, "workspaceId")?,
+                optional(params, "template")?,
+                optional(params, "model")?,
+                optional(params, "accessMode")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_issue_links" => to_result(
+            issues::list_issue_links(required(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "post_issue_comment" => to_result(
+            issues::post_issue_comment(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                optional(params, "body")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "search_transcripts" => to_result(
+            search::search_transcripts(
+                required(params, "query")?,
+                optional(params, "workspaceIds")?,
+                optional(params, "since")?,
+                optional(params, "until")?,
+                optional(params, "roles")?,
+                optional(params, "limit")?,
+                optional(params, "operationId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "annotate" => to_result(
+            annotations::annotate(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                required(params, "target")?,
+                required(params, "targetId")?,
+                optional(params, "note")?,
+                optional(params, "rating")?,
+                optional(params, "bookmark")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_annotations" => to_result(
+            annotations::list_annotations(
+                required(params, "workspaceId")?,
+                optional(params, "threadId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "search_annotations" => to_result(
+            annotations::search_annotations(
+                optional(params, "query")?,
+                optional(params, "workspaceIds")?,
+                optional(params, "minRating")?,
+                optional(params, "bookmarked")?,
+                optional(params, "limit")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "get_budget_status" => to_result(
+            budget::get_budget_status(optional(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "override_budget" => to_result(
+            budget::override_budget(optional(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "list_recordings" => to_result(
+            replay::list_recordings(required(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "replay_session" => to_result(
+            replay::replay_session(
+                required(params, "workspaceId")?,
+                required(params, "path")?,
+                optional(params, "speed")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_orphaned_sessions" => {
+            to_result(recovery::list_orphaned_sessions(state(), app.clone()).await)
+        }
+        "adopt_orphaned_session" => to_result(
+            recovery::adopt_orphaned_session(
+                required(params, "workspaceId")?,
+                required(params, "pid")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "kill_orphaned_session" => to_result(
+            recovery::kill_orphaned_session(
+                required(params, "workspaceId")?,
+                required(params, "pid")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "reveal_redacted_secret" => to_result(
+            redaction::reveal_redacted_secret(required(params, "id")?, state(), app.clone()).await,
+        ),
+        "get_storage_encryption" => {
+            to_result(storage_encryption::get_storage_encryption(state(), app.clone()).await)
+        }
+        "set_storage_encryption" => to_result(
+            storage_encryption::set_storage_encryption(
+                required(params, "enabled")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "get_workspace_credentials" => to_result(
+            credentials::get_workspace_credentials(
+                required(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "set_workspace_credentials" => to_result(
+            credentials::set_workspace_credentials(
+                required(params, "workspaceId")?,
+                optional(params, "credentials")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "check_provider_connectivity" => to_result(
+            claude::check_provider_connectivity(
+                required(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "test_proxy_connectivity" => to_result(
+            proxy::test_proxy_connectivity(
+                optional(params, "workspaceId")?,
+                optional(params, "url")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_claude_cli_installations" => {
+            to_result(cli_manager::list_claude_cli_installations(state(), app.clone()).await)
+        }
+        "check_claude_cli_updates" => {
+            to_result(cli_manager::check_claude_cli_updates(state(), app.clone()).await)
+        }
+        "update_claude_cli" => to_result(
+            cli_manager::update_claude_cli(required(params, "path")?, state(), app.clone()).await,
+        ),
+        "run_doctor" => to_result(
+            doctor::run_doctor(optional(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "apply_retention_policy" => {
+            to_result(retention::apply_retention_policy(state(), app.clone()).await)
+        }
+        "get_disk_usage" => to_result(
+            disk_usage::get_disk_usage(optional(params, "olderThanDays")?, state(), app.clone())
+                .await,
+        ),
+        "run_disk_cleanup" => to_result(
+            disk_usage::run_disk_cleanup(required(params, "action")?, state(), app.clone()).await,
+        ),
+        "export_config" => to_result(
+            config_bundle::export_config(optional(params, "path")?, state(), app.clone()).await,
+        ),
+        "import_config" => to_result(
+            config_bundle::import_config(
+                optional(params, "bundle")?,
+                optional(params, "path")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "get_rate_limit_status" => to_result(
+            claude::get_rate_limit_status(required(params, "workspaceId")?, state(), app.clone())
+                .await,
+        ),
+        "prompt_templates_list" => to_result(
+            prompt_library::prompt_templates_list(
+                required(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "prompt_template_save" => to_result(
+            prompt_library::prompt_template_save(
+                required(params, "workspaceId")?,
+                required(params, "scope")?,
+                required(params, "name")?,
+                optional(params, "description")?,
+                required(params, "body")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "prompt_template_delete" => to_result(
+            prompt_library::prompt_template_delete(
+                required(params, "workspaceId")?,
+                required(params, "scope")?,
+                required(params, "name")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "prompt_template_render" => to_result(
+            prompt_library::prompt_template_render(
+                required(params, "workspaceId")?,
+                required(params, "name")?,
+                optional(params, "values")?.unwrap_or_default(),
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "prompt_template_send" => to_result(
+            prompt_library::prompt_template_send(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                required(params, "name")?,
+                optional(params, "values")?.unwrap_or_default(),
+                optional(params, "model")?,
+                optional(params, "effort")?,
+                optional(params, "accessMode")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "context_packs_list" => to_result(
+            context_pack::context_packs_list(
+                required(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "context_pack_save" => to_result(
+            context_pack::context_pack_save(
+                required(params, "workspaceId")?,
+                required(params, "name")?,
+                required(params, "items")?,
+                optional(params, "maxTokens")?,
+                optional(params, "truncation")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "context_pack_delete" => to_result(
+            context_pack::context_pack_delete(
+                required(params, "workspaceId")?,
+                required(params, "name")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "context_pack_preview" => to_result(
+            context_pack::context_pack_preview(
+                required(params, "workspaceId")?,
+                required(params, "name")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "estimate_tokens" => to_result(
+            token_estimate::estimate_tokens(
+                required(params, "workspaceId")?,
+                required(params, "text")?,
+                optional(params, "images")?,
+                optional(params, "attachments")?,
+                optional(params, "contextPack")?,
+                optional(params, "models")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_slash_commands" => to_result(
+            slash_commands::list_slash_commands(
+                required(params, "workspaceId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "run_slash_command" => to_result(
+            slash_commands::run_slash_command(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                required(params, "name")?,
+                optional(params, "arguments")?,
+                optional(params, "model")?,
+                optional(params, "accessMode")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_subagents" => to_result(
+            agents::list_subagents(required(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "save_subagent" => to_result(
+            agents::save_subagent(
+                required(params, "workspaceId")?,
+                required(params, "agent")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "delete_subagent" => to_result(
+            agents::delete_subagent(
+                required(params, "workspaceId")?,
+                required(params, "scope")?,
+                required(params, "name")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "run_subagent" => to_result(
+            agents::run_subagent(
+                required(params, "workspaceId")?,
+                required(params, "threadId")?,
+                required(params, "name")?,
+                required(params, "task")?,
+                optional(params, "model")?,
+                optional(params, "accessMode")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "schedules_list" => to_result(
+            scheduler::schedules_list(optional(params, "workspaceId")?, state(), app.clone()).await,
+        ),
+        "schedule_save" => to_result(
+            scheduler::schedule_save(
+                required(params, "workspaceId")?,
+                optional(params, "id")?,
+                required(params, "name")?,
+                optional(params, "prompt")?,
+                optional(params, "template")?,
+                optional(params, "values")?,
+                required(params, "schedule")?,
+                optional(params, "enabled")?,
+                optional(params, "model")?,
+                optional(params, "permissionMode")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "schedule_delete" => to_result(
+            scheduler::schedule_delete(required(params, "scheduleId")?, state(), app.clone()).await,
+        ),
+        "schedule_set_enabled" => to_result(
+            scheduler::schedule_set_enabled(
+                required(params, "scheduleId")?,
+                required(params, "enabled")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "schedule_run_now" => to_result(
+            scheduler::schedule_run_now(required(params, "scheduleId")?, state(), app.clone())
+                .await,
+        ),
+        "schedule_runs_list" => to_result(
+            scheduler::schedule_runs_list(
+                required(params, "workspaceId")?,
+                optional(params, "scheduleId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "batch_run" => to_result(
+            batch::batch_run(
+                required(params, "prompt")?,
+                required(params, "workspaceIds")?,
+                optional(params, "concurrency")?,
+                optional(params, "model")?,
+                optional(params, "permissionMode")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "batches_list" => to_result(batch::batches_list(state(), app.clone()).await),
+        "batch_cancel" => {
+            to_result(batch::batch_cancel(required(params, "batchId")?, state(), app.clone()).await)
+        }
+        "create_pipeline" => to_result(
+            pipeline::create_pipeline(
+                optional(params, "name")?.unwrap_or_default(),
+                required(params, "steps")?,
+                optional(params, "permissionMode")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "list_pipelines" => to_result(pipeline::list_pipelines(state(), app.clone()).await),
+        "cancel_pipeline" => to_result(
+            pipeline::cancel_pipeline(required(params, "pipelineId")?, state(), app.clone()).await,
+        ),
+        "list_api_tokens" => to_result(api_tokens::list_api_tokens(state(), app.clone()).await),
+        "issue_api_token" => to_result(
+            api_tokens::issue_api_token(
+                required(params, "name")?,
+                required(params, "role")?,
+                optional(params, "expiresInDays")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "revoke_api_token" => to_result(
+            api_tokens::revoke_api_token(required(params, "id")?, state(), app.clone()).await,
+        ),
+        "list_api_audit_log" => to_result(
+            api_tokens::list_api_audit_log(
+                optional(params, "tokenId")?,
+                optional(params, "limit")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "terminal_open" => to_result(
+            terminal::terminal_open(
+                required(params, "workspaceId")?,
+                required(params, "terminalId")?,
+                required(params, "cols")?,
+                required(params, "rows")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "terminal_write" => to_result(
+            terminal::terminal_write(
+                required(params, "workspaceId")?,
+                required(params, "terminalId")?,
+                required(params, "data")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "terminal_resize" => to_result(
+            terminal::terminal_resize(
+                required(params, "workspaceId")?,
+                required(params, "terminalId")?,
+                required(params, "cols")?,
+                required(params, "rows")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "terminal_kill" => to_result(
+            terminal::terminal_kill(
+                required(params, "workspaceId")?,
+                required(params, "terminalId")?,
+                optional(params, "signal")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        "terminal_close" => to_result(
+            terminal::terminal_close(
+                required(params, "workspaceId")?,
+                required(params, "terminalId")?,
+                state(),
+                app.clone(),
+            )
+            .await,
+        ),
+        _ => Err(RpcError::method_not_found(method)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn rust_sources(dir: &Path, found: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                rust_sources(&path, found);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                found.push(path);
+            }
+        }
+    }
+
+    #[test]
+    fn serves_every_method_commands_forward_in_remote_mode() {
+        let mut sources = Vec::new();
+        rust_sources(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut sources,
+        );
+        let mut missing = Vec::new();
+        for path in sources {
+            let source = fs::read_to_string(&path).unwrap();
+            for call in source.split("remote_backend::call_remote(").skip(1) {
+                let method = call.split('"').nth(1).unwrap_or_default();
+                if !METHODS.contains(&method) {
+                    missing.push(format!("{method} ({})", path.display()));
+                }
+            }
+        }
+        assert!(missing.is_empty(), "not served by the daemon: {missing:?}");
+    }
+
+    #[test]
+    fn parses_arguments() {
+        assert_eq!(parse_args(args(&[])).unwrap(), DaemonOptions::default());
+        assert_eq!(
+            parse_args(args(&[
+                "--socket",
+                "/tmp/ccm.sock",
+                "--listen=0.0.0.0:4733",
+                "--token",
+                "s3cret",
+                "--metrics=127.0.0.1:9464",
+                "--rest",
+                "127.0.0.1:4734",
+                "--slack=127.0.0.1:4735",
+                "--otlp",
+                "http://localhost:4318",
+            ]))
+            .unwrap(),
+            DaemonOptions {
+                socket: Some(PathBuf::from("/tmp/ccm.sock")),
+                listen: Some("0.0.0.0:4733".to_string()),
+                token: Some("s3cret".to_string()),
+                metrics: Some("127.0.0.1:9464".to_string()),
+                rest: Some("127.0.0.1:4734".to_string()),
+                slack: Some("127.0.0.1:4735".to_string()),
+                otlp: Some("http://localhost:4318".to_string()),
+            }
+        );
+        assert!(parse_args(args(&["--socket"])).is_err());
+        assert!(parse_args(args(&["--token="])).is_err());
+        assert!(parse_args(args(&["--verbose"])).is_err());
+    }
+}
@@ -0,0 +1,542 @@
+//! `claude_code_monitor_daemon run`: one non-interactive turn for CI pipelines.
+//!
+//! The prompt runs as a one-shot `claude -p` turn in the workspace at `--workspace`
+//! (a saved workspace's settings apply when the path matches one), under the budgets
+//! and a time limit. The outcome, with a summary of what the turn changed in the git
+//! working tree, is written to stdout as JSON or text, and the exit code tells a
+//! pipeline what happened without parsing it.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::time::timeout;
+
+use crate::backend::stream_events::ResultEvent;
+use crate::git::diff as git_diff;
+use crate::git_utils::resolve_git_root;
+use crate::state::AppState;
+use crate::types::{GitFileStatus, WorkspaceEntry};
+use crate::{budget, claude, usage};
+
+/// The turn finished without error.
+pub(crate) const EXIT_SUCCEEDED: i32 = 0;
+/// Claude failed to run or ended the turn with an error.
+pub(crate) const EXIT_FAILED: i32 = 1;
+/// Bad arguments, or the prompt or workspace could not be read.
+pub(crate) const EXIT_USAGE: i32 = 2;
+/// A budget covering the workspace is used up, so the turn was not started.
+pub(crate) const EXIT_BUDGET_EXCEEDED: i32 = 3;
+/// The turn ran past its time limit and was killed.
+pub(crate) const EXIT_TIMED_OUT: i32 = 4;
+
+/// Time limit of turns in workspaces without `maxTurnMinutes`.
+const DEFAULT_TIMEOUT_MINUTES: u64 = 30;
+
+pub(crate) const RUN_USAGE: &str =
+    "usage: claude_code_monitor_daemon run --workspace <path> (--prompt-file <file> | --prompt \
+     <text>) [--output json|text] [--model <model>] [--permission-mode <mode>] \
+     [--timeout <minutes>]";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    #[default]
+    Json,
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PromptSource {
+    /// A file to read, or stdin for `-`.
+    File(PathBuf),
+    Text(String),
+}
+
+/// Command-line configuration of `run`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RunOptions {
+    pub(crate) workspace: PathBuf,
+    pub(crate) prompt: PromptSource,
+    pub(crate) output: OutputFormat,
+    pub(crate) model: Option<String>,
+    pub(crate) permission_mode: Option<String>,
+    /// Overrides the workspace's `maxTurnMinutes`.
+    pub(crate) timeout_minutes: Option<u64>,
+}
+
+/// Parse the arguments following `run`.
+pub(crate) fn parse_run_args(args: impl IntoIterator<Item = String>) -> Result<RunOptions, String> {
+    let mut workspace = None;
+    let mut prompt = None;
+    let mut output = OutputFormat::default();
+    let mut model = None;
+    let mut permission_mode = None;
+    let mut timeout_minutes = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        let known = [
+            "--workspace",
+            "--prompt-file",
+            "--prompt",
+            "--output",
+            "--model",
+            "--permission-mode",
+            "--timeout",
+        ];
+        if !known.contains(&flag.as_str()) {
+            return Err(format!("unknown argument `{arg}`\n{RUN_USAGE}"));
+        }
+        let value = match inline.or_else(|| args.next()) {
+            Some(value) if !value.trim().is_empty() => value,
+            _ => return Err(format!("{flag} needs a value\n{RUN_USAGE}")),
+        };
+        match flag.as_str() {
+            "--workspace" => workspace = Some(PathBuf::from(value)),
+            "--prompt-file" | "--prompt" if prompt.is_some() => {
+                return Err(format!(
+                    "pass either --prompt-file or --prompt, once\n{RUN_USAGE}"
+                ));
+            }
+            "--prompt-file" => prompt = Some(PromptSource::File(PathBuf::from(value))),
+            "--prompt" => prompt = Some(PromptSource::Text(value)),
+            "--output" => {
+                output = match value.as_str() {
+                    "json" => OutputFormat::Json,
+                    "text" => OutputFormat::Text,
+                    _ => return Err(format!("--output is json or text\n{RUN_USAGE}")),
+                }
+            }
+            "--model" => model = Some(value),
+            "--permission-mode" => permission_mode = Some(value),
+            _ => {
+                timeout_minutes = match value.parse::<u64>() {
+                    Ok(minutes) if minutes > 0 => Some(minutes),
+                    _ => {
+                        return Err(format!(
+                            "--timeout is a whole number of minutes\n{RUN_USAGE}"
+                        ))
+                    }
+                }
+            }
+        }
+    }
+    let workspace = workspace.ok_or_else(|| format!("--workspace is required\n{RUN_USAGE}"))?;
+    let prompt =
+        prompt.ok_or_else(|| format!("--prompt-file or --prompt is required\n{RUN_USAGE}"))?;
+    Ok(RunOptions {
+        workspace,
+        prompt,
+        output,
+        model,
+        permission_mode,
+        timeout_minutes,
+    })
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum RunStatus {
+    Succeeded,
+    Failed,
+    /// The prompt or workspace could not be read.
+    Invalid,
+    BudgetExceeded,
+    TimedOut,
+}
+
+impl RunStatus {
+    pub(crate) fn exit_code(self) -> i32 {
+        match self {
+            RunStatus::Succeeded => EXIT_SUCCEEDED,
+            RunStatus::Failed => EXIT_FAILED,
+            RunStatus::Invalid => EXIT_USAGE,
+            RunStatus::BudgetExceeded => EXIT_BUDGET_EXCEEDED,
+            RunStatus::TimedOut => EXIT_TIMED_OUT,
+        }
+    }
+}
+
+/// What the turn changed in the working tree.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiffSummary {
+    pub(crate) files: Vec<GitFileStatus>,
+    pub(crate) additions: i64,
+    pub(crate) deletions: i64,
+}
+
+impl DiffSummary {
+    fn from_files(files: Vec<GitFileStatus>) -> Self {
+        DiffSummary {
+            additions: files.iter().map(|file| file.additions).sum(),
+            deletions: files.iter().map(|file| file.deletions).sum(),
+            files,
+        }
+    }
+}
+
+/// The outcome of a run, as written to stdout.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunReport {
+    pub(crate) status: RunStatus,
+    pub(crate) workspace: String,
+    /// Claude's final message.
+    pub(crate) message: String,
+    pub(crate) error: Option<String>,
+    pub(crate) session_id: Option<String>,
+    pub(crate) cost_usd: Option<f64>,
+    pub(crate) duration_ms: Option<u64>,
+    pub(crate) num_turns: Option<u32>,
+    pub(crate) input_tokens: Option<u64>,
+    pub(crate) output_tokens: Option<u64>,
+    pub(crate) permission_denials: usize,
+    /// `None` when the workspace is not a git repository.
+    pub(crate) diff: Option<DiffSummary>,
+}
+
+impl RunReport {
+    fn new(status: RunStatus, workspace: &Path) -> Self {
+        RunReport {
+            status,
+            workspace: workspace.display().to_string(),
+            message: String::new(),
+            error: None,
+            session_id: None,
+            cost_usd: None,
+            duration_ms: None,
+            num_turns: None,
+            input_tokens: None,
+            output_tokens: None,
+            permission_denials: 0,
+            diff: None,
+        }
+    }
+
+    fn failed(status: RunStatus, workspace: &Path, error: String) -> Self {
+        RunReport {
+            error: Some(error),
+            ..RunReport::new(status, workspace)
+        }
+    }
+
+    /// Fill in what the CLI's closing `result` event reports; an error result fails
+    /// the run.
+    fn apply_result(&mut self, result: &ResultEvent) {
+        if result.is_error {
+            self.status = RunStatus::Failed;
+            self.error = Some(match result.result.as_deref() {
+                Some(text) if !text.trim().is_empty() => text.trim().to_string(),
+                _ => format!("Claude ended the turn with an error ({})", result.subtype),
+            });
+        }
+        self.session_id = result.session_id.clone();
+        self.cost_usd = result.total_cost_usd;
+        self.duration_ms = result.duration_ms;
+        self.num_turns = result.num_turns;
+        self.input_tokens = result.usage.as_ref().map(|usage| usage.input_tokens);
+        self.output_tokens = result.usage.as_ref().map(|usage| usage.output_tokens);
+        self.permission_denials = result.permission_denials.len();
+    }
+
+    pub(crate) fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(self).unwrap_or_else(|err| err.to_string())
+            }
+            OutputFormat::Text => self.render_text(),
+        }
+    }
+
+    fn render_text(&self) -> String {
+        let mut lines = Vec::new();
+        if !self.message.is_empty() {
+            lines.push(self.message.clone());
+            lines.push(String::new());
+        }
+        let status = match self.status {
+            RunStatus::Succeeded => "succeeded",
+            RunStatus::Failed => "failed",
+            RunStatus::Invalid => "invalid",
+            RunStatus::BudgetExceeded => "budget exceeded",
+            RunStatus::TimedOut => "timed out",
+        };
+        lines.push(format!("status: {status}"));
+        if let Some(error) = &self.error {
+            lines.push(format!("error: {error}"));
+        }
+        if let Some(cost) = self.cost_usd {
+            lines.push(format!("cost: ${cost:.4}"));
+        }
+        if let Some(duration) = self.duration_ms {
+            lines.push(format!("duration: {:.1}s", duration as f64 / 1000.0));
+        }
+        if self.permission_denials > 0 {
+            lines.push(format!("permission denials: {}", self.permission_denials));
+        }
+        if let Some(diff) = &self.diff {
+            lines.push(format!(
+                "changed files: {} (+{} -{})",
+                diff.files.len(),
+                diff.additions,
+                diff.deletions
+            ));
+            for file in &diff.files {
+                lines.push(format!(
+                    "  {} {} (+{} -{})",
+                    file.status, file.path, file.additions, file.deletions
+                ));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+fn read_prompt(source: &PromptSource) -> Result<String, String> {
+    let prompt = match source {
+        PromptSource::Text(text) => text.clone(),
+        PromptSource::File(path) if path.as_os_str() == "-" => {
+            std::io::read_to_string(std::io::stdin())
+                .map_err(|err| format!("Failed to read the prompt from stdin: {err}"))?
+        }
+        PromptSource::File(path) => std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?,
+    };
+    if prompt.trim().is_empty() {
+        return Err("The prompt is empty.".to_string());
+    }
+    Ok(prompt)
+}
+
+/// The saved workspace at `path`, or an unsaved one with default settings.
+async fn resolve_workspace(
+    state: &AppState,
+    path: &Path,
+) -> Result<(WorkspaceEntry, bool), String> {
+    let path = std::fs::canonicalize(path)
+        .map_err(|err| format!("Workspace {} not found: {err}", path.display()))?;
+    if !path.is_dir() {
+        return Err(format!("Workspace {} is not a directory", path.display()));
+    }
+    let saved = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .values()
+            .filter(|entry| entry.archived_at.is_none())
+            .find(|entry| std::fs::canonicalize(&entry.path).is_ok_and(|saved| saved == path))
+            .cloned()
+    };
+    if let Some(entry) = saved {
+        return Ok((entry, true));
+    }
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    let entry = WorkspaceEntry {
+        id: format!("ci:{}", path.display()),
+        name,
+        path: path.display().to_string(),
+        claude_bin: None,
+        model: None,
+        kind: Default::default(),
+        parent_id: None,
+        worktree: None,
+        archived_at: None,
+        pinned: false,
+        settings: Default::default(),
+    };
+    Ok((entry, false))
+}
+
+/// Run the turn and report it on stdout. Returns the process exit code.
+pub(crate) async fn run(app: &AppHandle, options: RunOptions) -> i32 {
+    let report = run_turn(app, &options).await;
+    println!("{}", report.render(options.output));
+    report.status.exit_code()
+}
+
+async fn run_turn(app: &AppHandle, options: &RunOptions) -> RunReport {
+    let invalid = |error: String| RunReport::failed(RunStatus::Invalid, &options.workspace, error);
+    let prompt = match read_prompt(&options.prompt) {
+        Ok(prompt) => prompt,
+        Err(err) => return invalid(err),
+    };
+    let state = app.state::<AppState>();
+    let (entry, saved) = match resolve_workspace(&state, &options.workspace).await {
+        Ok(found) => found,
+        Err(err) => return invalid(err),
+    };
+    let workspace = PathBuf::from(&entry.path);
+    if let Err(err) = budget::ensure_turn_allowed(&state, &entry.id).await {
+        return RunReport::failed(RunStatus::BudgetExceeded, &workspace, err);
+    }
+
+    let repo_root = resolve_git_root(&entry).ok();
+    let before = match repo_root.clone() {
+        Some(root) => tokio::task::spawn_blocking(move || git_diff::snapshot(&root).ok())
+            .await
+            .ok()
+            .flatten(),
+        None => None,
+    };
+
+    let limit = Duration::from_secs(
+        60 * options.timeout_minutes.unwrap_or_else(|| {
+            entry
+                .settings
+                .max_turn_minutes
+                .map(u64::from)
+                .unwrap_or(DEFAULT_TIMEOUT_MINUTES)
+        }),
+    );
+    let model = options.model.clone().or(entry.model.clone());
+    let permission_mode = options
+        .permission_mode
+        .clone()
+        .unwrap_or_else(|| claude::UNATTENDED_PERMISSION_MODE.to_string());
+    // The process is killed on drop, so the outer limit is the one that holds
+    let turn = claude::run_claude_prompt_once_with_result(
+        &entry,
+        claude::workspace_claude_bin(&state, &entry).await,
+        prompt,
+        Some(permission_mode),
+        model.clone(),
+        &claude::workspace_cli_args(&entry),
+        limit + Duration::from_secs(5),
+    );
+    let mut report = match timeout(limit, turn).await {
+        Err(_) => RunReport::failed(
+            RunStatus::TimedOut,
+            &workspace,
+            format!("The turn ran longer than {} minutes", limit.as_secs() / 60),
+        ),
+        Ok(Err(err)) => RunReport::failed(RunStatus::Failed, &workspace, err),
+        Ok(Ok(output)) => {
+            let mut report = RunReport::new(RunStatus::Succeeded, &workspace);
+            report.message = output.message;
+            if let Some(result) = &output.result {
+                report.apply_result(result);
+                // Spend in saved workspaces counts toward their budgets
+                if saved {
+                    let session_id = result.session_id.clone().unwrap_or_default();
+                    usage::record_turn(usage::turn_usage_from_result(
+                        &entry.id,
+                        &session_id,
+                        &session_id,
+                        model,
+                        result,
+                        0.0,
+                    ));
+                }
+            }
+            report
+        }
+    };
+
+    if let (Some(root), Some(before)) = (repo_root, before) {
+        let diff = tokio::task::spawn_blocking(move || {
+            let after = git_diff::snapshot(&root)?;
+            git_diff::diff_snapshots(&root, &before, &after)
+        })
+        .await;
+        match diff {
+            Ok(Ok(diff)) => report.diff = Some(DiffSummary::from_files(diff.files)),
+            Ok(Err(err)) => tracing::warn!("failed to diff the run: {err}"),
+            Err(_) => {}
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_run_arguments() {
+        assert_eq!(
+            parse_run_args(args(&[
+                "--workspace",
+                "/src/app",
+                "--prompt-file=p.md",
+                "--output",
+                "text",
+                "--timeout",
+                "10",
+            ]))
+            .unwrap(),
+            RunOptions {
+                workspace: PathBuf::from("/src/app"),
+                prompt: PromptSource::File(PathBuf::from("p.md")),
+                output: OutputFormat::Text,
+                model: None,
+                permission_mode: None,
+                timeout_minutes: Some(10),
+            }
+        );
+        assert!(parse_run_args(args(&["--prompt", "hi"])).is_err());
+        assert!(parse_run_args(args(&["--workspace", "."])).is_err());
+        let both = [
+            "--workspace",
+            ".",
+            "--prompt",
+            "hi",
+            "--prompt-file",
+            "p.md",
+        ];
+        assert!(parse_run_args(args(&both)).is_err());
+        let format = ["--workspace", ".", "--prompt", "hi", "--output", "yaml"];
+        assert!(parse_run_args(args(&format)).is_err());
+        let minutes = ["--workspace", ".", "--prompt", "hi", "--timeout", "0"];
+        assert!(parse_run_args(args(&minutes)).is_err());
+    }
+
+    #[test]
+    fn reports_results_and_exit_codes() {
+        let workspace = Path::new("/src/app");
+        let result: ResultEvent = serde_json::from_value(json!({
+            "subtype": "error_max_turns",
+            "is_error": true,
+            "session_id": "s-1",
+            "total_cost_usd": 0.25,
+            "usage": { "input_tokens": 10, "output_tokens": 5 },
+        }))
+        .unwrap();
+        let mut report = RunReport::new(RunStatus::Succeeded, workspace);
+        report.apply_result(&result);
+        report.diff = Some(DiffSummary::from_files(vec![GitFileStatus {
+            path: "src/lib.rs".to_string(),
+            status: "M".to_string(),
+            additions: 3,
+            deletions: 1,
+        }]));
+        assert_eq!(report.status.exit_code(), EXIT_FAILED);
+        assert_eq!(
+            report.error.as_deref(),
+            Some("Claude ended the turn with an error (error_max_turns)")
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&report.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json["status"], "failed");
+        assert_eq!(json["costUsd"], 0.25);
+        assert_eq!(json["diff"]["additions"], 3);
+        let text = report.render(OutputFormat::Text);
+        assert!(text.contains("status: failed"));
+        assert!(text.contains("  M src/lib.rs (+3 -1)"));
+
+        assert_eq!(RunStatus::TimedOut.exit_code(), EXIT_TIMED_OUT);
+        assert_eq!(RunStatus::BudgetExceeded.exit_code(), EXIT_BUDGET_EXCEEDED);
+    }
+}
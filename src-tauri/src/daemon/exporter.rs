@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::api_tokens;
+use crate::backend::metrics;
+use crate::claude;
+use crate::state::AppState;
+
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Prometheus scrape endpoint at `http://<metrics>/metrics`.
+///
+/// When the daemon has a token, scrapers must send it, or an issued API token of any
+/// role, as `Authorization: Bearer <token>` (Prometheus' `authorization` or
+/// `bearer_token` settings).
+pub(crate) async fn serve(app: AppHandle, listener: std::net::TcpListener, token: Option<String>) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::warn!("failed to serve metrics: {err}");
+            return;
+        }
+    };
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(serve_client(app.clone(), stream, token.clone()));
+            }
+            Err(err) => {
+                tracing::warn!("metrics accept failed: {err}");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+async fn serve_client(app: AppHandle, mut stream: TcpStream, token: Option<String>) {
+    let Ok(Some(head)) = tokio::time::timeout(REQUEST_TIMEOUT, read_head(&mut stream)).await else {
+        return;
+    };
+    let response = match route(&head, token.as_deref()) {
+        Ok(()) => {
+            let body = render(&app).await;
+            response("200 OK", CONTENT_TYPE, &body)
+        }
+        Err(status) => response(status, "text/plain; charset=utf-8", status),
+    };
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Read up to the blank line that ends the request head. Bodies are never needed.
+async fn read_head(stream: &mut TcpStream) -> Option<String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+        if buffer.len() > MAX_REQUEST_BYTES {
+            return None;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    String::from_utf8(buffer).ok()
+}
+
+/// Check the request line and credentials; the error is the response status.
+fn route(head: &str, token: Option<&str>) -> Result<(), &'static str> {
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or("").split_whitespace();
+    let (method, target) = (request_line.next(), request_line.next());
+    let path = target.map(|target| target.split('?').next().unwrap_or(target));
+    if path != Some("/metrics") {
+        return Err("404 Not Found");
+    }
+    if method != Some("GET") {
+        return Err("405 Method Not Allowed");
+    }
+    let Some(token) = token else {
+        return Ok(());
+    };
+    let bearer = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+        .and_then(|(_, value)| value.trim().strip_prefix("Bearer "))
+        .map(str::trim);
+    match bearer {
+        Some(provided)
+            if api_tokens::authenticate(Some(token), provided, crate::utils::now_ms())
+                .is_some() =>
+        {
+            Ok(())
+        }
+        _ => Err("401 Unauthorized"),
+    }
+}
+
+fn response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+async fn render(app: &AppHandle) -> String {
+    let (names, active) = workspace_labels(app).await;
+    metrics::render(&names, &active)
+}
+
+/// Workspace names by id, and live CLI processes per workspace, for labelling metrics.
+pub(super) async fn workspace_labels(
+    app: &AppHandle,
+) -> (HashMap<String, String>, HashMap<String, usize>) {
+    let state = app.state::<AppState>();
+    let names: HashMap<String, String> = state
+        .workspaces
+        .lock()
+        .await
+        .values()
+        .map(|entry| (entry.id.clone(), entry.name.clone()))
+        .collect();
+    let active = claude::active_session_counts(&state).await;
+    (names, active)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serves_only_authorized_metrics_scrapes() {
+        let get = |path: &str, authorization: Option<&str>| {
+            let mut head = format!("GET {path} HTTP/1.1\r\nHost: localhost:9464\r\n");
+            if let Some(value) = authorization {
+                head.push_str(&format!("authorization: {value}\r\n"));
+            }
+            head + "\r\n"
+        };
+        assert_eq!(route(&get("/metrics", None), None), Ok(()));
+        assert_eq!(
+            route(&get("/metrics", Some("Bearer s3cret")), Some("s3cret")),
+            Ok(())
+        );
+        assert_eq!(
+            route(&get("/metrics", Some("Bearer nope")), Some("s3cret")),
+            Err("401 Unauthorized")
+        );
+        assert_eq!(
+            route(&get("/metrics", None), Some("s3cret")),
+            Err("401 Unauthorized")
+        );
+        assert_eq!(route(&get("/events", None), None), Err("404 Not Found"));
+        assert_eq!(
+            route("POST /metrics HTTP/1.1\r\n\r\n", None),
+            Err("405 Method Not Allowed")
+        );
+    }
+}
@@ -0,0 +1,328 @@
+//! OpenTelemetry export for the daemon.
+//!
+//! With `--otlp <url>`, the backend's closed spans (sessions, turns, CLI spawns and
+//! stdin writes) and the metrics of the Prometheus endpoint are pushed every
+//! `EXPORT_INTERVAL` to an OTLP/HTTP collector as JSON, at `<url>/v1/traces` and
+//! `<url>/v1/metrics`. Request headers, e.g. a vendor's API key, come from
+//! `OTEL_EXPORTER_OTLP_HEADERS` (`key=value` pairs separated by commas, values
+//! percent-encoded) and the service name from `OTEL_SERVICE_NAME`.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Client;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::backend::metrics;
+use crate::telemetry::{self, FinishedSpan};
+use crate::utils::now_unix_nanos;
+
+use super::exporter;
+
+const HEADERS_ENV: &str = "OTEL_EXPORTER_OTLP_HEADERS";
+const SERVICE_NAME_ENV: &str = "OTEL_SERVICE_NAME";
+const DEFAULT_SERVICE_NAME: &str = "claude-code-monitor";
+const SCOPE_NAME: &str = "claude_code_monitor";
+const EXPORT_INTERVAL: Duration = Duration::from_secs(10);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Spans per trace request, to stay under collectors' request size limits.
+const MAX_SPANS_PER_REQUEST: usize = 512;
+/// `SPAN_KIND_INTERNAL`: every backend span is in-process work.
+const SPAN_KIND_INTERNAL: u8 = 1;
+const STATUS_CODE_ERROR: u8 = 2;
+
+/// Where and how to export, from `--otlp` and the standard OpenTelemetry variables.
+#[derive(Debug, Clone)]
+pub(crate) struct OtlpConfig {
+    /// Collector base URL without a trailing slash, e.g. `http://localhost:4318`.
+    endpoint: String,
+    headers: HeaderMap,
+    service_name: String,
+}
+
+impl OtlpConfig {
+    pub(crate) fn from_env(endpoint: &str) -> Result<Self, String> {
+        let env = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Self::new(endpoint, env(HEADERS_ENV).as_deref(), env(SERVICE_NAME_ENV))
+    }
+
+    fn new(
+        endpoint: &str,
+        headers: Option<&str>,
+        service_name: Option<String>,
+    ) -> Result<Self, String> {
+        let endpoint = endpoint.trim().trim_end_matches('/');
+        if !(endpoint.starts_with("http://") || endpoint.starts_with("https://")) {
+            return Err(format!(
+                "--otlp needs an http:// or https:// collector URL, not `{endpoint}`"
+            ));
+        }
+        Ok(Self {
+            endpoint: endpoint.to_string(),
+            headers: parse_headers(headers.unwrap_or(""))?,
+            service_name: service_name.unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string()),
+        })
+    }
+}
+
+/// Headers in the `OTEL_EXPORTER_OTLP_HEADERS` format: `key=value,key2=value2`.
+fn parse_headers(text: &str) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    for pair in text.split(',').filter(|pair| !pair.trim().is_empty()) {
+        let (name, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("{HEADERS_ENV} entries look like key=value, not `{pair}`"))?;
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|err| format!("invalid header name in {HEADERS_ENV}: {err}"))?;
+        let value = HeaderValue::from_str(&percent_decode(value.trim()))
+            .map_err(|err| format!("invalid value for {name} in {HEADERS_ENV}: {err}"))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+/// Undo `%XX` escapes, leaving malformed ones as they are.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = match bytes.get(index..index + 3) {
+            Some([b'%', high, low]) if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => {
+                std::str::from_utf8(&bytes[index + 1..index + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            }
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// An OTLP `AnyValue`; 64-bit integers are strings in OTLP JSON.
+fn any_value(value: &Value) -> Value {
+    match value {
+        Value::Bool(value) => json!({ "boolValue": value }),
+        Value::Number(number) if number.is_f64() => json!({ "doubleValue": number }),
+        Value::Number(number) => json!({ "intValue": number.to_string() }),
+        Value::String(text) => json!({ "stringValue": text }),
+        other => json!({ "stringValue": other.to_string() }),
+    }
+}
+
+fn resource(config: &OtlpConfig) -> Value {
+    json!({
+        "attributes": [
+            { "key": "service.name", "value": { "stringValue": config.service_name } },
+            {
+                "key": "service.version",
+                "value": { "stringValue": env!("CARGO_PKG_VERSION") },
+            },
+        ],
+    })
+}
+
+fn encode_span(span: &FinishedSpan) -> Value {
+    let mut attributes: Vec<Value> = span
+        .attributes
+        .iter()
+        .map(|(key, value)| json!({ "key": key, "value": any_value(value) }))
+        .collect();
+    attributes.push(json!({ "key": "code.namespace", "value": { "stringValue": span.target } }));
+    let mut encoded = json!({
+        "traceId": hex(&span.trace_id),
+        "spanId": hex(&span.span_id),
+        "name": span.name,
+        "kind": SPAN_KIND_INTERNAL,
+        "startTimeUnixNano": span.start_unix_nanos.to_string(),
+        "endTimeUnixNano": span.end_unix_nanos.to_string(),
+        "attributes": attributes,
+    });
+    if let Some(parent) = span.parent_span_id {
+        encoded["parentSpanId"] = Value::from(hex(&parent));
+    }
+    if let Some(error) = &span.error {
+        encoded["status"] = json!({ "code": STATUS_CODE_ERROR, "message": error });
+    }
+    encoded
+}
+
+/// An `ExportTraceServiceRequest`.
+fn traces_request(config: &OtlpConfig, spans: &[FinishedSpan]) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": resource(config),
+            "scopeSpans": [{
+                "scope": { "name": SCOPE_NAME },
+                "spans": spans.iter().map(encode_span).collect::<Vec<_>>(),
+            }],
+        }],
+    })
+}
+
+/// An `ExportMetricsServiceRequest`.
+fn metrics_request(config: &OtlpConfig, metrics: Vec<Value>) -> Value {
+    json!({
+        "resourceMetrics": [{
+            "resource": resource(config),
+            "scopeMetrics": [{
+                "scope": { "name": SCOPE_NAME },
+                "metrics": metrics,
+            }],
+        }],
+    })
+}
+
+async fn post(config: &OtlpConfig, signal: &str, body: &Value) -> Result<(), String> {
+    let url = format!("{}/v1/{signal}", config.endpoint);
+    let response = client()
+        .post(&url)
+        .headers(config.headers.clone())
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .map_err(|err| format!("{url}: {err}"))?;
+    if !response.status().is_success() {
+        return Err(format!("{url}: {}", response.status()));
+    }
+    Ok(())
+}
+
+async fn export(app: &AppHandle, config: &OtlpConfig, start_unix_nanos: u64) -> Result<(), String> {
+    let (spans, dropped) = telemetry::take_spans();
+    if dropped > 0 {
+        tracing::warn!("dropped {dropped} spans the OTLP export could not keep up with");
+    }
+    for chunk in spans.chunks(MAX_SPANS_PER_REQUEST) {
+        post(config, "traces", &traces_request(config, chunk)).await?;
+    }
+    let (names, active) = exporter::workspace_labels(app).await;
+    let metrics = metrics::otlp_metrics(&names, &active, start_unix_nanos, now_unix_nanos());
+    post(config, "metrics", &metrics_request(config, metrics)).await
+}
+
+/// Collect spans and push them with the metrics every `EXPORT_INTERVAL`. A failing
+/// collector is logged once until it recovers; spans closed meanwhile are lost.
+pub(crate) async fn run(app: AppHandle, config: OtlpConfig) {
+    tracing::info!(
+        "exporting traces and metrics over OTLP to {}",
+        config.endpoint
+    );
+    telemetry::collect_spans(true);
+    let start_unix_nanos = now_unix_nanos();
+    let mut interval = tokio::time::interval(EXPORT_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut failing = false;
+    loop {
+        interval.tick().await;
+        match export(&app, &config, start_unix_nanos).await {
+            Ok(()) if failing => {
+                failing = false;
+                tracing::info!("OTLP export to {} recovered", config.endpoint);
+            }
+            Ok(()) => {}
+            Err(err) if !failing => {
+                failing = true;
+                tracing::warn!("OTLP export failed: {err}");
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_endpoint_and_headers() {
+        let config = OtlpConfig::new(
+            "https://otlp.example.com/ ",
+            Some("x-api-key=abc123, Authorization=Basic%20dXNlcjpwdw%3D%3D"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(config.endpoint, "https://otlp.example.com");
+        assert_eq!(config.service_name, DEFAULT_SERVICE_NAME);
+        assert_eq!(config.headers["x-api-key"], "abc123");
+        assert_eq!(config.headers["authorization"], "Basic dXNlcjpwdw==");
+
+        assert!(OtlpConfig::new("localhost:4318", None, None).is_err());
+        assert!(OtlpConfig::new("http://localhost:4318", Some("no-value"), None).is_err());
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    #[test]
+    fn encodes_spans_as_otlp_json() {
+        let config =
+            OtlpConfig::new("http://localhost:4318", None, Some("ci".to_string())).unwrap();
+        let span = FinishedSpan {
+            trace_id: [0xab; 16],
+            span_id: [1, 2, 3, 4, 5, 6, 7, 8],
+            parent_span_id: Some([0xff; 8]),
+            name: "turn",
+            target: "claude_code_monitor_lib::claude",
+            start_unix_nanos: 1_000,
+            end_unix_nanos: 2_000,
+            attributes: vec![
+                ("turn_id".to_string(), Value::from("turn-1")),
+                ("pid".to_string(), Value::from(42u64)),
+                ("ratio".to_string(), Value::from(0.5)),
+            ],
+            error: Some("boom".to_string()),
+        };
+        let request = traces_request(&config, &[span]);
+        let resource = &request["resourceSpans"][0]["resource"]["attributes"][0];
+        assert_eq!(resource["value"]["stringValue"], "ci");
+        let encoded = &request["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+        assert_eq!(encoded["traceId"], "ab".repeat(16));
+        assert_eq!(encoded["spanId"], "0102030405060708");
+        assert_eq!(encoded["parentSpanId"], "ffffffffffffffff");
+        assert_eq!(encoded["startTimeUnixNano"], "1000");
+        assert_eq!(encoded["status"], json!({ "code": 2, "message": "boom" }));
+        assert_eq!(
+            encoded["attributes"],
+            json!([
+                { "key": "turn_id", "value": { "stringValue": "turn-1" } },
+                { "key": "pid", "value": { "intValue": "42" } },
+                { "key": "ratio", "value": { "doubleValue": 0.5 } },
+                {
+                    "key": "code.namespace",
+                    "value": { "stringValue": "claude_code_monitor_lib::claude" },
+                },
+            ])
+        );
+    }
+}
@@ -0,0 +1,642 @@
+//! HTTP REST API over the daemon's methods, for integrations that don't speak
+//! JSON-RPC.
+//!
+//! Every route maps onto one JSON-RPC method: path and query parameters plus the JSON
+//! body become that method's params, so the two surfaces can't drift apart. Requests
+//! need the daemon's token or an issued API token as `Authorization: Bearer <token>`:
+//! `GET` routes take any role, `POST` routes an operator. The OpenAPI document at
+//! `/openapi.json`, built from the same route table, is served without one. Requests
+//! with an issued token act as the user named by the token when a thread's ownership is
+//! checked.
+
+use std::collections::HashMap;
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, on, MethodFilter};
+use axum::{Json, Router};
+use serde_json::{json, Map, Value};
+use tauri::AppHandle;
+use tokio::net::TcpListener;
+
+use super::rpc::{RpcError, INVALID_PARAMS, METHOD_NOT_FOUND};
+use crate::api_tokens::{self, Caller, Denial};
+use crate::types::TokenRole;
+
+const API_VERSION: &str = "1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verb {
+    Get,
+    Post,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamKind {
+    String,
+    Integer,
+    Boolean,
+}
+
+/// An optional query parameter; path parameters are always strings.
+#[derive(Debug, Clone, Copy)]
+struct QueryParam {
+    name: &'static str,
+    kind: ParamKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Route {
+    verb: Verb,
+    /// Path with `{param}` placeholders named after the method's params.
+    path: &'static str,
+    method: &'static str,
+    tag: &'static str,
+    summary: &'static str,
+    query: &'static [QueryParam],
+    /// String properties of the JSON body, and whether each is required.
+    body: &'static [(&'static str, bool)],
+}
+
+const fn query(name: &'static str, kind: ParamKind) -> QueryParam {
+    QueryParam { name, kind }
+}
+
+const ROUTES: &[Route] = &[
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces",
+        method: "list_workspaces",
+        tag: "workspaces",
+        summary: "List workspaces",
+        query: &[query("includeArchived", ParamKind::Boolean)],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Post,
+        path: "/v1/workspaces/{id}/connect",
+        method: "connect_workspace",
+        tag: "workspaces",
+        summary: "Start a workspace's Claude session",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/health",
+        method: "session_health",
+        tag: "sessions",
+        summary: "Health of a workspace's sessions",
+        query: &[query("threadId", ParamKind::String)],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Post,
+        path: "/v1/workspaces/{workspaceId}/suspend",
+        method: "suspend_workspace_sessions",
+        tag: "sessions",
+        summary: "Stop a workspace's idle CLI processes for focus mode",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Post,
+        path: "/v1/workspaces/{workspaceId}/resume",
+        method: "resume_workspace_sessions",
+        tag: "sessions",
+        summary: "Continue a workspace's suspended CLI processes",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Post,
+        path: "/v1/focus",
+        method: "focus_workspace",
+        tag: "sessions",
+        summary: "Suspend every workspace but the focused one",
+        query: &[],
+        body: &[("workspaceId", false)],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/sessions",
+        method: "list_thread_sessions",
+        tag: "sessions",
+        summary: "List the Claude sessions behind a workspace's threads",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/threads",
+        method: "list_threads",
+        tag: "sessions",
+        summary: "List a workspace's threads, newest first",
+        query: &[
+            query("cursor", ParamKind::String),
+            query("limit", ParamKind::Integer),
+        ],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Post,
+        path: "/v1/workspaces/{workspaceId}/threads",
+        method: "start_thread",
+        tag: "sessions",
+        summary: "Start a thread",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/threads/{threadId}/messages",
+        method: "resume_thread",
+        tag: "messages",
+        summary: "A thread's conversation",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Post,
+        path: "/v1/workspaces/{workspaceId}/sessions/{sessionId}/fork",
+        method: "fork_session",
+        tag: "sessions",
+        summary: "Copy a session up to a message into a new session",
+        query: &[query("messageIndex", ParamKind::Integer)],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/annotations",
+        method: "list_annotations",
+        tag: "sessions",
+        summary: "Notes, ratings and bookmarks on turns and messages",
+        query: &[query("threadId", ParamKind::String)],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/forks",
+        method: "list_session_forks",
+        tag: "sessions",
+        summary: "Forked sessions and their parents",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Post,
+        path: "/v1/workspaces/{workspaceId}/threads/{threadId}/messages",
+        method: "send_user_message",
+        tag: "messages",
+        summary: "Send a message, starting a turn",
+        query: &[],
+        body: &[
+            ("text", true),
+            ("model", false),
+            ("effort", false),
+            ("accessMode", false),
+            ("user", false),
+        ],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/pending-messages",
+        method: "list_pending_messages",
+        tag: "messages",
+        summary: "Messages waiting for their workspace to come back",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/queue",
+        method: "list_turn_queue",
+        tag: "turns",
+        summary: "Turns queued behind running ones",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Post,
+        path: "/v1/workspaces/{workspaceId}/threads/{threadId}/turns/{turnId}/interrupt",
+        method: "turn_interrupt",
+        tag: "turns",
+        summary: "Interrupt a running turn",
+        query: &[],
+        body: &[("user", false)],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/owners",
+        method: "list_thread_owners",
+        tag: "sessions",
+        summary: "Who drives each of a workspace's threads",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Post,
+        path: "/v1/workspaces/{workspaceId}/threads/{threadId}/takeover",
+        method: "take_over_thread",
+        tag: "sessions",
+        summary: "Take a thread over from whoever drives it",
+        query: &[],
+        body: &[("user", false)],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/turns/{turnId}/timeline",
+        method: "get_turn_timeline",
+        tag: "turns",
+        summary: "A turn's tool calls over time",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/turns/{turnId}/diff",
+        method: "get_turn_diff",
+        tag: "turns",
+        summary: "What a turn changed in the working tree",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/turns/{turnId}/edits",
+        method: "get_turn_edits",
+        tag: "turns",
+        summary: "A turn's file edits as hunks",
+        query: &[],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/workspaces/{workspaceId}/bash-decisions",
+        method: "list_bash_decisions",
+        tag: "workspaces",
+        summary: "Bash commands the workspace's policy allowed, denied or passed on",
+        query: &[query("limit", ParamKind::Integer)],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/activity",
+        method: "get_activity",
+        tag: "activity",
+        summary: "Turns, tool calls, permission prompts and errors across workspaces",
+        query: &[
+            query("since", ParamKind::Integer),
+            query("workspaceId", ParamKind::String),
+            query("minSeverity", ParamKind::String),
+            query("limit", ParamKind::Integer),
+        ],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/usage/turns",
+        method: "get_turn_metrics",
+        tag: "usage",
+        summary: "Recent turns with tokens, cost and timings",
+        query: &[
+            query("workspaceId", ParamKind::String),
+            query("limit", ParamKind::Integer),
+        ],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/usage/export",
+        method: "export_usage",
+        tag: "usage",
+        summary: "Spend per workspace and model as CSV or JSON, returned as content",
+        query: &[
+            query("since", ParamKind::Integer),
+            query("until", ParamKind::Integer),
+            query("format", ParamKind::String),
+        ],
+        body: &[],
+    },
+    Route {
+        verb: Verb::Get,
+        path: "/v1/usage/budgets",
+        method: "get_budget_status",
+        tag: "usage",
+        summary: "Spend against the daily and monthly budgets",
+        query: &[query("workspaceId", ParamKind::String)],
+        body: &[],
+    },
+];
+
+/// Serve the API until the daemon exits.
+pub(crate) async fn serve(app: AppHandle, listener: std::net::TcpListener, token: Option<String>) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::warn!("failed to serve the REST API: {err}");
+            return;
+        }
+    };
+    if let Err(err) = axum::serve(listener, router(app, token)).await {
+        tracing::warn!("REST API stopped: {err}");
+    }
+}
+
+#[derive(Clone)]
+struct ApiState {
+    app: AppHandle,
+    token: Option<String>,
+}
+
+fn router(app: AppHandle, token: Option<String>) -> Router {
+    let mut router = Router::new().route("/openapi.json", get(|| async { Json(openapi()) }));
+    for route in ROUTES {
+        let filter = match route.verb {
+            Verb::Get => MethodFilter::GET,
+            Verb::Post => MethodFilter::POST,
+        };
+        let handler = move |State(api): State<ApiState>,
+                            headers: HeaderMap,
+                            path: Option<Path<HashMap<String, String>>>,
+                            Query(query): Query<HashMap<String, String>>,
+                            body: Option<Json<Value>>| async move {
+            let caller = match authorize(&headers, api.token.as_deref(), route) {
+                Ok(caller) => caller,
+                Err(Denial::Unauthenticated) => {
+                    return error(StatusCode::UNAUTHORIZED, "missing or wrong bearer token")
+                }
+                Err(Denial::Forbidden) => {
+                    return error(StatusCode::FORBIDDEN, "this token may only read")
+                }
+            };
+            let path = path.map(|Path(path)| path).unwrap_or_default();
+            let mut params = match params(route, path, &query, body.map(|Json(body)| body)) {
+                Ok(params) => params,
+                Err(err) => return rpc_error(err),
+            };
+            // Issued tokens act as the user they were issued to; only the shared
+            // token may say who it speaks for
+            if caller.token_id.is_some() {
+                params["user"] = Value::String(caller.name);
+            }
+            match super::dispatch(&api.app, route.method, &params).await {
+                Ok(result) => Json(result).into_response(),
+                Err(err) => rpc_error(err),
+            }
+        };
+        router = router.route(route.path, on(filter, handler));
+    }
+    router.with_state(ApiState { app, token })
+}
+
+fn authorize(headers: &HeaderMap, token: Option<&str>, route: &Route) -> Result<Caller, Denial> {
+    let provided = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().strip_prefix("Bearer "));
+    api_tokens::authorize(token, provided, needed_role(route), "rest", route.method)
+}
+
+fn needed_role(route: &Route) -> TokenRole {
+    match route.verb {
+        Verb::Get => TokenRole::Observer,
+        Verb::Post => TokenRole::Operator,
+    }
+}
+
+/// The JSON-RPC params of a request: the body's fields, then query and path
+/// parameters on top.
+fn params(
+    route: &Route,
+    path: HashMap<String, String>,
+    query: &HashMap<String, String>,
+    body: Option<Value>,
+) -> Result<Value, RpcError> {
+    let mut params = match body {
+        None | Some(Value::Null) => Map::new(),
+        Some(Value::Object(body)) => body,
+        Some(_) => return Err(RpcError::invalid_params("the body must be a JSON object")),
+    };
+    for (name, value) in query {
+        let Some(param) = route.query.iter().find(|param| param.name == name) else {
+            return Err(RpcError::invalid_params(format!(
+                "unknown query parameter `{name}`"
+            )));
+        };
+        let value = match param.kind {
+            ParamKind::String => Value::String(value.clone()),
+            ParamKind::Integer => value.parse::<u64>().map(Value::from).map_err(|_| {
+                RpcError::invalid_params(format!("`{name}` must be a whole number"))
+            })?,
+            ParamKind::Boolean => value
+                .parse::<bool>()
+                .map(Value::from)
+                .map_err(|_| RpcError::invalid_params(format!("`{name}` must be true or false")))?,
+        };
+        params.insert(name.clone(), value);
+    }
+    for (name, value) in path {
+        params.insert(name, Value::String(value));
+    }
+    Ok(Value::Object(params))
+}
+
+fn rpc_error(err: RpcError) -> Response {
+    let status = match err.code {
+        INVALID_PARAMS => StatusCode::BAD_REQUEST,
+        METHOD_NOT_FOUND => StatusCode::NOT_FOUND,
+        _ if err.message.to_lowercase().contains("not found") => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    error(status, &err.message)
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+fn path_params(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter_map(|segment| {
+        segment
+            .strip_prefix('{')
+            .and_then(|segment| segment.strip_suffix('}'))
+    })
+}
+
+/// OpenAPI 3.1 description of [`ROUTES`].
+fn openapi() -> Value {
+    let mut paths = Map::new();
+    for route in ROUTES {
+        let mut parameters: Vec<Value> = path_params(route.path)
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                })
+            })
+            .collect();
+        parameters.extend(route.query.iter().map(|param| {
+            let kind = match param.kind {
+                ParamKind::String => "string",
+                ParamKind::Integer => "integer",
+                ParamKind::Boolean => "boolean",
+            };
+            json!({
+                "name": param.name,
+                "in": "query",
+                "required": false,
+                "schema": { "type": kind },
+            })
+        }));
+        let mut operation = json!({
+            "operationId": route.method,
+            "summary": route.summary,
+            "tags": [route.tag],
+            "parameters": parameters,
+            "responses": {
+                "200": {
+                    "description": format!("The result of `{}`", route.method),
+                    "content": { "application/json": { "schema": {} } },
+                },
+                "400": { "$ref": "#/components/responses/Error" },
+                "401": { "$ref": "#/components/responses/Error" },
+                "403": { "$ref": "#/components/responses/Error" },
+                "404": { "$ref": "#/components/responses/Error" },
+                "500": { "$ref": "#/components/responses/Error" },
+            },
+        });
+        if !route.body.is_empty() {
+            let properties: Map<String, Value> = route
+                .body
+                .iter()
+                .map(|(name, _)| (name.to_string(), json!({ "type": "string" })))
+                .collect();
+            let required: Vec<&str> = route
+                .body
+                .iter()
+                .filter(|(_, required)| *required)
+                .map(|(name, _)| *name)
+                .collect();
+            operation["requestBody"] = json!({
+                "required": !required.is_empty(),
+                "content": { "application/json": { "schema": {
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                } } },
+            });
+        }
+        let verb = match route.verb {
+            Verb::Get => "get",
+            Verb::Post => "post",
+        };
+        let item = paths
+            .entry(route.path.to_string())
+            .or_insert_with(|| json!({}));
+        item[verb] = operation;
+    }
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Claude Code Monitor daemon",
+            "version": API_VERSION,
+            "description": "REST access to the daemon's workspaces, sessions, turns, \
+                messages and usage. Each operation mirrors the JSON-RPC method named by \
+                its operationId.",
+        },
+        "paths": paths,
+        "security": [{ "bearer": [] }],
+        "components": {
+            "securitySchemes": { "bearer": { "type": "http", "scheme": "bearer" } },
+            "responses": {
+                "Error": {
+                    "description": "The request failed",
+                    "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "properties": { "error": { "type": "string" } },
+                        "required": ["error"],
+                    } } },
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(method: &str) -> &'static Route {
+        ROUTES.iter().find(|route| route.method == method).unwrap()
+    }
+
+    #[test]
+    fn builds_params_from_path_query_and_body() {
+        let path = HashMap::from([
+            ("workspaceId".to_string(), "ws-1".to_string()),
+            ("threadId".to_string(), "thread-1".to_string()),
+        ]);
+        let sent = params(
+            route("send_user_message"),
+            path.clone(),
+            &HashMap::new(),
+            Some(json!({ "text": "Fix the build", "workspaceId": "ws-2" })),
+        )
+        .unwrap();
+        assert_eq!(
+            sent,
+            json!({ "workspaceId": "ws-1", "threadId": "thread-1", "text": "Fix the build" })
+        );
+
+        let query = HashMap::from([("limit".to_string(), "20".to_string())]);
+        let listed = params(route("list_threads"), path.clone(), &query, None).unwrap();
+        assert_eq!(listed["limit"], 20);
+
+        let bad = HashMap::from([("limit".to_string(), "many".to_string())]);
+        assert!(params(route("list_threads"), path.clone(), &bad, None).is_err());
+        let unknown = HashMap::from([("verbose".to_string(), "1".to_string())]);
+        assert!(params(route("list_threads"), path.clone(), &unknown, None).is_err());
+        assert!(params(
+            route("start_thread"),
+            path,
+            &HashMap::new(),
+            Some(json!([]))
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn writes_need_an_operator() {
+        assert_eq!(needed_role(route("list_threads")), TokenRole::Observer);
+        assert_eq!(needed_role(route("send_user_message")), TokenRole::Operator);
+        assert_eq!(needed_role(route("turn_interrupt")), TokenRole::Operator);
+    }
+
+    #[test]
+    fn describes_every_route() {
+        let spec = openapi();
+        for route in ROUTES {
+            let verb = match route.verb {
+                Verb::Get => "get",
+                Verb::Post => "post",
+            };
+            let operation = &spec["paths"][route.path][verb];
+            assert_eq!(operation["operationId"], route.method);
+            assert!(super::super::METHODS.contains(&route.method));
+            let path_count = path_params(route.path).count();
+            assert_eq!(
+                operation["parameters"].as_array().unwrap().len(),
+                path_count + route.query.len()
+            );
+        }
+        assert_eq!(
+            spec["paths"]["/v1/workspaces/{workspaceId}/threads/{threadId}/messages"]["post"]
+                ["requestBody"]["content"]["application/json"]["schema"]["required"],
+            json!(["text"])
+        );
+    }
+}
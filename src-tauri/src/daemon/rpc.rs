@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+pub(super) const METHOD_NOT_FOUND: i64 = -32601;
+pub(super) const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+/// One line of client input. `id` is absent for notifications, which get no response.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Request {
+    pub(crate) id: Option<Value>,
+    pub(crate) method: String,
+    pub(crate) params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RpcError {
+    pub(crate) code: i64,
+    pub(crate) message: String,
+}
+
+impl RpcError {
+    pub(crate) fn method_not_found(method: &str) -> Self {
+        Self {
+            code: METHOD_NOT_FOUND,
+            message: format!("unknown method: {method}"),
+        }
+    }
+
+    pub(crate) fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.into(),
+        }
+    }
+}
+
+/// Command errors are plain strings; they surface as generic server errors.
+impl From<String> for RpcError {
+    fn from(message: String) -> Self {
+        Self {
+            code: SERVER_ERROR,
+            message,
+        }
+    }
+}
+
+pub(crate) fn parse_request(line: &str) -> Result<Request, RpcError> {
+    let value: Value = serde_json::from_str(line).map_err(|err| RpcError {
+        code: PARSE_ERROR,
+        message: format!("invalid JSON: {err}"),
+    })?;
+    let method = value
+        .get("method")
+        .and_then(|method| method.as_str())
+        .filter(|method| !method.is_empty())
+        .ok_or_else(|| RpcError {
+            code: INVALID_REQUEST,
+            message: "request is missing a method".to_string(),
+        })?;
+    Ok(Request {
+        id: value.get("id").filter(|id| !id.is_null()).cloned(),
+        method: method.to_string(),
+        params: value.get("params").cloned().unwrap_or(Value::Null),
+    })
+}
+
+/// Read a required parameter. Names follow the Tauri commands, e.g. `workspaceId`.
+pub(crate) fn required<T: DeserializeOwned>(params: &Value, key: &str) -> Result<T, RpcError> {
+    optional(params, key)?.ok_or_else(|| RpcError::invalid_params(format!("missing `{key}`")))
+}
+
+pub(crate) fn optional<T: DeserializeOwned>(
+    params: &Value,
+    key: &str,
+) -> Result<Option<T>, RpcError> {
+    match params.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|err| RpcError::invalid_params(format!("invalid `{key}`: {err}"))),
+    }
+}
+
+/// Convert a command result into a JSON-RPC result.
+pub(crate) fn to_result<T: Serialize>(result: Result<T, String>) -> Result<Value, RpcError> {
+    let value = result?;
+    serde_json::to_value(value).map_err(|err| RpcError::from(err.to_string()))
+}
+
+pub(crate) fn response(id: Value, result: Result<Value, RpcError>) -> String {
+    let message = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    };
+    message.to_string()
+}
+
+pub(crate) fn notification(method: &str, params: &Value) -> String {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params }).to_string()
+}
+
+/// Which backend events a client wants. Missing lists mean "everything".
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Subscription {
+    #[serde(default)]
+    pub(crate) workspace_ids: Option<HashSet<String>>,
+    #[serde(default)]
+    pub(crate) events: Option<HashSet<String>>,
+}
+
+impl Subscription {
+    pub(crate) fn from_params(params: &Value) -> Result<Self, RpcError> {
+        if params.is_null() {
+            return Ok(Self::default());
+        }
+        serde_json::from_value(params.clone())
+            .map_err(|err| RpcError::invalid_params(format!("invalid subscription: {err}")))
+    }
+
+    pub(crate) fn matches(&self, method: &str, params: &Value) -> bool {
+        if let Some(events) = &self.events {
+            if !events.contains(method) {
+                return false;
+            }
+        }
+        let Some(workspace_ids) = &self.workspace_ids else {
+            return true;
+        };
+        params
+            .get("workspace_id")
+            .or_else(|| params.get("workspaceId"))
+            .and_then(|id| id.as_str())
+            .is_some_and(|id| workspace_ids.contains(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_requests_and_notifications() {
+        let request =
+            parse_request(r#"{"jsonrpc":"2.0","id":7,"method":"list_workspaces"}"#).unwrap();
+        assert_eq!(request.id, Some(json!(7)));
+        assert_eq!(request.method, "list_workspaces");
+        assert!(request.params.is_null());
+
+        let notification =
+            parse_request(r#"{"method":"turn_interrupt","params":{"workspaceId":"ws"}}"#).unwrap();
+        assert!(notification.id.is_none());
+        assert_eq!(notification.params["workspaceId"], "ws");
+
+        assert_eq!(parse_request("{").unwrap_err().code, PARSE_ERROR);
+        assert_eq!(
+            parse_request(r#"{"id":1}"#).unwrap_err().code,
+            INVALID_REQUEST
+        );
+    }
+
+    #[test]
+    fn reads_camel_case_params() {
+        let params = json!({ "workspaceId": "ws-1", "limit": 20, "cursor": null });
+        assert_eq!(required::<String>(&params, "workspaceId").unwrap(), "ws-1");
+        assert_eq!(optional::<u32>(&params, "limit").unwrap(), Some(20));
+        assert_eq!(optional::<String>(&params, "cursor").unwrap(), None);
+        assert_eq!(
+            required::<String>(&params, "threadId").unwrap_err().code,
+            INVALID_PARAMS
+        );
+        assert!(required::<String>(&params, "limit").is_err());
+    }
+
+    #[test]
+    fn builds_responses() {
+        let ok: Value =
+            serde_json::from_str(&response(json!(1), Ok(json!({ "ok": true })))).unwrap();
+        assert_eq!(ok["result"]["ok"], true);
+        assert!(ok.get("error").is_none());
+
+        let failed: Value = serde_json::from_str(&response(
+            json!("a"),
+            Err(RpcError::from("workspace not connected".to_string())),
+        ))
+        .unwrap();
+        assert_eq!(failed["id"], "a");
+        assert_eq!(failed["error"]["code"], SERVER_ERROR);
+        assert_eq!(failed["error"]["message"], "workspace not connected");
+    }
+
+    #[test]
+    fn subscription_filters_by_workspace_and_event() {
+        let event = json!({ "workspace_id": "ws-1", "message": {} });
+        assert!(Subscription::from_params(&Value::Null)
+            .unwrap()
+            .matches("app-server-event", &event));
+
+        let scoped = Subscription::from_params(&json!({ "workspaceIds": ["ws-2"] })).unwrap();
+        assert!(!scoped.matches("app-server-event", &event));
+        assert!(scoped.matches("turn-queue-updated", &json!({ "workspaceId": "ws-2" })));
+
+        let events = Subscription::from_params(&json!({ "events": ["app-server-event"] })).unwrap();
+        assert!(events.matches("app-server-event", &event));
+        assert!(!events.matches("terminal-output", &event));
+
+        assert!(Subscription::from_params(&json!({ "workspaceIds": "ws-1" })).is_err());
+    }
+}
@@ -0,0 +1,405 @@
+//! `claude_code_monitor_daemon install-service` / `uninstall-service`: run the daemon
+//! as a per-user service so it keeps watching sessions while the app is closed.
+//!
+//! macOS gets a launchd agent in `~/Library/LaunchAgents`, Linux a systemd user unit in
+//! `~/.config/systemd/user`. The service runs this executable with the daemon flags
+//! given to `install-service`, the current `PATH` (so the Claude CLI is found) and its
+//! output appended to `daemon.log` / `daemon.err.log` in the platform's log directory.
+//! It starts at login unless `--no-autostart` is given, and is restarted if it exits.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::claude_home::resolve_home_dir;
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const LAUNCHD_LABEL: &str = "com.claudecodemonitor.daemon";
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const SYSTEMD_UNIT: &str = "claude-code-monitor-daemon.service";
+#[cfg(not(target_os = "macos"))]
+const LOG_DIR_NAME: &str = "claude-code-monitor";
+#[cfg(target_os = "macos")]
+const MACOS_LOG_DIR_NAME: &str = "ClaudeCodeMonitor";
+
+pub(crate) const SERVICE_USAGE: &str =
+    "usage: claude_code_monitor_daemon install-service [--no-autostart] [--socket <path>] \
+     [--listen <host:port>] [--token <token>] [--metrics <host:port>] [--rest <host:port>] \
+     [--slack <host:port>]\n       \
+     claude_code_monitor_daemon uninstall-service";
+
+/// Command-line configuration of `install-service`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ServiceOptions {
+    /// Start at login, not just now.
+    pub(crate) autostart: bool,
+    /// Daemon flags the service runs with, as given.
+    pub(crate) daemon_args: Vec<String>,
+}
+
+/// What the service runs and where its output goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ServiceSpec {
+    program: PathBuf,
+    args: Vec<String>,
+    path_env: Option<String>,
+    log_dir: PathBuf,
+    autostart: bool,
+}
+
+/// Parse the arguments following `install-service`. Daemon flags are checked the same
+/// way the daemon checks them, so a typo fails now rather than at login.
+pub(crate) fn parse_service_args(
+    args: impl IntoIterator<Item = String>,
+) -> Result<ServiceOptions, String> {
+    let mut autostart = true;
+    let mut daemon_args = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--no-autostart" => autostart = false,
+            "-h" | "--help" => return Err(SERVICE_USAGE.to_string()),
+            _ => daemon_args.push(arg),
+        }
+    }
+    super::parse_args(daemon_args.clone())
+        .map_err(|err| format!("{}\n{SERVICE_USAGE}", err.lines().next().unwrap_or("")))?;
+    Ok(ServiceOptions {
+        autostart,
+        daemon_args,
+    })
+}
+
+/// Write the service definition and load it. Returns what was done, for the user.
+pub(crate) fn install(options: ServiceOptions) -> Result<String, String> {
+    let program = std::env::current_exe()
+        .map_err(|err| format!("Failed to locate the daemon executable: {err}"))?;
+    let spec = ServiceSpec {
+        program,
+        args: options.daemon_args,
+        path_env: std::env::var("PATH").ok().filter(|path| !path.is_empty()),
+        log_dir: log_dir()?,
+        autostart: options.autostart,
+    };
+    std::fs::create_dir_all(&spec.log_dir).map_err(|err| {
+        format!(
+            "Failed to create log directory {}: {err}",
+            spec.log_dir.display()
+        )
+    })?;
+    platform_install(&spec)
+}
+
+/// Stop the service and remove its definition.
+pub(crate) fn uninstall() -> Result<String, String> {
+    platform_uninstall()
+}
+
+#[cfg(target_os = "macos")]
+fn platform_install(spec: &ServiceSpec) -> Result<String, String> {
+    let path = launchd_plist_path()?;
+    if path.exists() {
+        // Reinstalling replaces the running agent with the new definition
+        let _ = run("launchctl", &["unload", &path.to_string_lossy()]);
+    }
+    write_private(&path, &launchd_plist(spec))?;
+    run("launchctl", &["load", "-w", &path.to_string_lossy()])?;
+    Ok(format!(
+        "Installed {} and started it{}.\nLogs: {}",
+        path.display(),
+        if spec.autostart {
+            "; it starts at login"
+        } else {
+            ""
+        },
+        spec.log_dir.display()
+    ))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_uninstall() -> Result<String, String> {
+    let path = launchd_plist_path()?;
+    if !path.exists() {
+        return Ok(format!("No service installed at {}.", path.display()));
+    }
+    let _ = run("launchctl", &["unload", "-w", &path.to_string_lossy()]);
+    std::fs::remove_file(&path)
+        .map_err(|err| format!("Failed to remove {}: {err}", path.display()))?;
+    Ok(format!("Stopped and removed {}.", path.display()))
+}
+
+#[cfg(target_os = "linux")]
+fn platform_install(spec: &ServiceSpec) -> Result<String, String> {
+    let path = systemd_unit_path()?;
+    write_private(&path, &systemd_unit(spec))?;
+    run("systemctl", &["--user", "daemon-reload"])?;
+    if spec.autostart {
+        run("systemctl", &["--user", "enable", SYSTEMD_UNIT])?;
+    } else {
+        let _ = run("systemctl", &["--user", "disable", SYSTEMD_UNIT]);
+    }
+    run("systemctl", &["--user", "restart", SYSTEMD_UNIT])?;
+    Ok(format!(
+        "Installed {} and started it{}.\nLogs: {}",
+        path.display(),
+        if spec.autostart {
+            "; it starts at login (run `loginctl enable-linger` to keep it running after \
+             logout)"
+        } else {
+            ""
+        },
+        spec.log_dir.display()
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn platform_uninstall() -> Result<String, String> {
+    let path = systemd_unit_path()?;
+    if !path.exists() {
+        return Ok(format!("No service installed at {}.", path.display()));
+    }
+    let _ = run("systemctl", &["--user", "disable", "--now", SYSTEMD_UNIT]);
+    std::fs::remove_file(&path)
+        .map_err(|err| format!("Failed to remove {}: {err}", path.display()))?;
+    let _ = run("systemctl", &["--user", "daemon-reload"]);
+    Ok(format!("Stopped and removed {}.", path.display()))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn platform_install(_spec: &ServiceSpec) -> Result<String, String> {
+    Err("Installing the daemon as a service is supported on macOS and Linux".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn platform_uninstall() -> Result<String, String> {
+    Err("Installing the daemon as a service is supported on macOS and Linux".to_string())
+}
+
+fn home() -> Result<PathBuf, String> {
+    resolve_home_dir().ok_or_else(|| "Failed to resolve the home directory".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn launchd_plist_path() -> Result<PathBuf, String> {
+    Ok(home()?
+        .join("Library/LaunchAgents")
+        .join(format!("{LAUNCHD_LABEL}.plist")))
+}
+
+#[cfg(target_os = "linux")]
+fn systemd_unit_path() -> Result<PathBuf, String> {
+    let config = std::env::var("XDG_CONFIG_HOME")
+        .ok()
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+        .map_or_else(|| home().map(|home| home.join(".config")), Ok)?;
+    Ok(config.join("systemd/user").join(SYSTEMD_UNIT))
+}
+
+#[cfg(target_os = "macos")]
+fn log_dir() -> Result<PathBuf, String> {
+    Ok(home()?.join("Library/Logs").join(MACOS_LOG_DIR_NAME))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn log_dir() -> Result<PathBuf, String> {
+    let state = std::env::var("XDG_STATE_HOME")
+        .ok()
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+        .map_or_else(|| home().map(|home| home.join(".local/state")), Ok)?;
+    Ok(state.join(LOG_DIR_NAME))
+}
+
+/// Owner-only, since the daemon flags may include `--token`.
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), allow(dead_code))]
+fn write_private(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    std::fs::write(path, contents)
+        .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|err| format!("Failed to restrict {}: {err}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), allow(dead_code))]
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| format!("Failed to run {program}: {err}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(format!(
+        "`{program} {}` failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn launchd_plist(spec: &ServiceSpec) -> String {
+    let mut arguments = format!(
+        "        <string>{}</string>\n",
+        xml_escape(&spec.program.to_string_lossy())
+    );
+    for arg in &spec.args {
+        arguments.push_str(&format!("        <string>{}</string>\n", xml_escape(arg)));
+    }
+    let environment = spec
+        .path_env
+        .as_deref()
+        .map(|path| {
+            format!(
+                "    <key>EnvironmentVariables</key>\n    <dict>\n        <key>PATH</key>\n        \
+                 <string>{}</string>\n    </dict>\n",
+                xml_escape(path)
+            )
+        })
+        .unwrap_or_default();
+    let log = |name: &str| xml_escape(&spec.log_dir.join(name).to_string_lossy());
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+         \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n    \
+         <key>Label</key>\n    <string>{LAUNCHD_LABEL}</string>\n    \
+         <key>ProgramArguments</key>\n    <array>\n{arguments}    </array>\n\
+         {environment}    \
+         <key>RunAtLoad</key>\n    <{run_at_load}/>\n    \
+         <key>KeepAlive</key>\n    <dict>\n        \
+         <key>SuccessfulExit</key>\n        <false/>\n    </dict>\n    \
+         <key>StandardOutPath</key>\n    <string>{stdout}</string>\n    \
+         <key>StandardErrorPath</key>\n    <string>{stderr}</string>\n\
+         </dict>\n\
+         </plist>\n",
+        run_at_load = if spec.autostart { "true" } else { "false" },
+        stdout = log("daemon.log"),
+        stderr = log("daemon.err.log"),
+    )
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn systemd_unit(spec: &ServiceSpec) -> String {
+    let exec = std::iter::once(spec.program.to_string_lossy().into_owned())
+        .chain(spec.args.iter().cloned())
+        .map(|arg| systemd_quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let environment = spec
+        .path_env
+        .as_deref()
+        .map(|path| format!("Environment={}\n", systemd_quote(&format!("PATH={path}"))))
+        .unwrap_or_default();
+    let log = |name: &str| spec.log_dir.join(name).to_string_lossy().replace('%', "%%");
+    format!(
+        "[Unit]\n\
+         Description=Claude Code Monitor daemon\n\
+         \n\
+         [Service]\n\
+         ExecStart={exec}\n\
+         {environment}\
+         Restart=on-failure\n\
+         RestartSec=5\n\
+         StandardOutput=append:{stdout}\n\
+         StandardError=append:{stderr}\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        stdout = log("daemon.log"),
+        stderr = log("daemon.err.log"),
+    )
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Double-quote a word for `ExecStart=`/`Environment=`, escaping what systemd expands.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn systemd_quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%")
+        .replace('$', "$$");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(autostart: bool) -> ServiceSpec {
+        ServiceSpec {
+            program: PathBuf::from("/opt/Claude Code Monitor/claude_code_monitor_daemon"),
+            args: vec!["--listen".to_string(), "0.0.0.0:4733".to_string()],
+            path_env: Some("/usr/bin:/home/dev/.local/bin".to_string()),
+            log_dir: PathBuf::from("/home/dev/.local/state/claude-code-monitor"),
+            autostart,
+        }
+    }
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_daemon_flags_and_autostart() {
+        let options =
+            parse_service_args(args(&["--no-autostart", "--rest", "127.0.0.1:4734"])).unwrap();
+        assert!(!options.autostart);
+        assert_eq!(options.daemon_args, ["--rest", "127.0.0.1:4734"]);
+
+        assert!(parse_service_args(args(&[])).unwrap().autostart);
+        assert!(parse_service_args(args(&["--verbose"])).is_err());
+        assert!(parse_service_args(args(&["--listen"])).is_err());
+    }
+
+    #[test]
+    fn writes_a_launchd_agent() {
+        let plist = launchd_plist(&spec(true));
+        assert!(plist.contains("<string>com.claudecodemonitor.daemon</string>"));
+        assert!(
+            plist.contains("<string>/opt/Claude Code Monitor/claude_code_monitor_daemon</string>")
+        );
+        assert!(plist.contains("<string>0.0.0.0:4733</string>"));
+        assert!(plist.contains("<key>RunAtLoad</key>\n    <true/>"));
+        assert!(plist.contains(
+            "<string>/home/dev/.local/state/claude-code-monitor/daemon.err.log</string>"
+        ));
+        assert!(launchd_plist(&spec(false)).contains("<key>RunAtLoad</key>\n    <false/>"));
+    }
+
+    #[test]
+    fn writes_a_systemd_unit() {
+        let unit = systemd_unit(&spec(true));
+        assert!(unit.contains(
+            "ExecStart=\"/opt/Claude Code Monitor/claude_code_monitor_daemon\" \"--listen\" \
+             \"0.0.0.0:4733\"\n"
+        ));
+        assert!(unit.contains("Environment=\"PATH=/usr/bin:/home/dev/.local/bin\"\n"));
+        assert!(unit.contains(
+            "StandardOutput=append:/home/dev/.local/state/claude-code-monitor/daemon.log\n"
+        ));
+        assert!(unit.contains("WantedBy=default.target"));
+    }
+
+    #[test]
+    fn escapes_what_the_service_managers_expand() {
+        assert_eq!(systemd_quote("50%$HOME\"x"), "\"50%%$$HOME\\\"x\"");
+        assert_eq!(xml_escape("a&b<c>"), "a&amp;b&lt;c&gt;");
+    }
+}
@@ -0,0 +1,603 @@
+//! Slack bridge for the daemon.
+//!
+//! With `CLAUDE_CODE_MONITOR_SLACK_BOT_TOKEN` and `CLAUDE_CODE_MONITOR_SLACK_CHANNEL`
+//! set, finished and failed turns, pending plans and permission prompts are posted to
+//! the channel. `--slack <host:port>` also serves the request URLs of a Slack app,
+//! checked against `CLAUDE_CODE_MONITOR_SLACK_SIGNING_SECRET`: the slash command
+//! (`/cc send <workspace> <prompt>`) at `/slack/commands`, and the Allow / Deny buttons
+//! of permission prompts at `/slack/interactions`. Both go through the same daemon
+//! methods as every other client, acting as the Slack user who sent them.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tauri::{AppHandle, Manager};
+use tokio::net::TcpListener;
+
+use crate::api_tokens;
+use crate::backend::tool_timeline::summarize_input;
+use crate::state::AppState;
+use crate::storage;
+use crate::utils::now_ms;
+use crate::webhooks::WebhookEvent;
+
+const BOT_TOKEN_ENV: &str = "CLAUDE_CODE_MONITOR_SLACK_BOT_TOKEN";
+const SIGNING_SECRET_ENV: &str = "CLAUDE_CODE_MONITOR_SLACK_SIGNING_SECRET";
+const CHANNEL_ENV: &str = "CLAUDE_CODE_MONITOR_SLACK_CHANNEL";
+const POST_MESSAGE_URL: &str = "https://slack.com/api/chat.postMessage";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Requests signed longer ago than this are refused as possible replays.
+const MAX_REQUEST_AGE_SECS: i64 = 5 * 60;
+/// Slack truncates section text at 3000 characters.
+const MAX_TEXT_CHARS: usize = 2800;
+const ALLOW_ACTION: &str = "allow";
+const DENY_ACTION: &str = "deny";
+const USAGE: &str = "Usage: `/cc send <workspace> <prompt>` sends a prompt to the workspace's \
+                     latest thread (or a new one).";
+
+/// Managed by the daemon when a bot token is configured.
+pub(crate) struct SlackBridge {
+    bot_token: String,
+    channel: Option<String>,
+    signing_secret: Option<String>,
+    /// Whether the request URLs are served, so prompts can carry buttons.
+    interactive: bool,
+}
+
+impl SlackBridge {
+    /// The bridge configured in the environment, if any. `interactive` is set when
+    /// `--slack` was given, which needs the bot token and signing secret.
+    pub(crate) fn from_env(interactive: bool) -> Result<Option<Self>, String> {
+        let env = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let bot_token = env(BOT_TOKEN_ENV);
+        let signing_secret = env(SIGNING_SECRET_ENV);
+        if interactive && (bot_token.is_none() || signing_secret.is_none()) {
+            return Err(format!(
+                "--slack needs {BOT_TOKEN_ENV} and {SIGNING_SECRET_ENV} to be set"
+            ));
+        }
+        Ok(bot_token.map(|bot_token| Self {
+            bot_token,
+            channel: env(CHANNEL_ENV),
+            signing_secret,
+            interactive,
+        }))
+    }
+}
+
+fn client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_TEXT_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Post a turn lifecycle event to the channel in the background. A no-op outside the
+/// daemon or without a channel. `data` is the (already redacted) webhook payload.
+pub(crate) fn post_event(app: &AppHandle, workspace_id: &str, event: WebhookEvent, data: &Value) {
+    let Some(bridge) = app.try_state::<SlackBridge>() else {
+        return;
+    };
+    let Some(channel) = bridge.channel.clone() else {
+        return;
+    };
+    if event == WebhookEvent::TurnStarted {
+        return;
+    }
+    let bot_token = bridge.bot_token.clone();
+    let interactive = bridge.interactive;
+    let app = app.clone();
+    let workspace_id = workspace_id.to_string();
+    let data = data.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<AppState>() else {
+            return;
+        };
+        let workspace_name = state
+            .workspaces
+            .lock()
+            .await
+            .get(&workspace_id)
+            .map(|entry| entry.name.clone())
+            .unwrap_or_else(|| workspace_id.clone());
+        let mut message = event_message(&workspace_name, event, &data);
+        if event == WebhookEvent::PermissionPending && interactive {
+            message["blocks"]
+                .as_array_mut()
+                .expect("event messages have blocks")
+                .push(permission_buttons(&workspace_id, &data));
+        }
+        message["channel"] = Value::String(channel);
+        post_message(&bot_token, &message).await;
+    });
+}
+
+/// `text` (the notification fallback) and `blocks` of an event's message.
+fn event_message(workspace: &str, event: WebhookEvent, data: &Value) -> Value {
+    let text_of = |key: &str| data.get(key).and_then(Value::as_str).unwrap_or_default();
+    let text = match event {
+        WebhookEvent::TurnStarted => format!("*{workspace}* started a turn."),
+        WebhookEvent::TurnCompleted => {
+            let mut details = Vec::new();
+            if let Some(ms) = data.get("durationMs").and_then(Value::as_u64) {
+                details.push(format!("{:.0}s", ms as f64 / 1000.0));
+            }
+            if let Some(cost) = data.get("costUsd").and_then(Value::as_f64) {
+                details.push(format!("${cost:.2}"));
+            }
+            let details = if details.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", details.join(", "))
+            };
+            let reply = text_of("result").trim();
+            if reply.is_empty() {
+                format!("*{workspace}* finished a turn{details}.")
+            } else {
+                format!(
+                    "*{workspace}* finished a turn{details}:\n{}",
+                    truncate(reply)
+                )
+            }
+        }
+        WebhookEvent::TurnFailed => {
+            let reason = match text_of("result").trim() {
+                "" => text_of("subtype"),
+                reason => reason,
+            };
+            format!("*{workspace}* turn failed: {}", truncate(reason))
+        }
+        WebhookEvent::PermissionPending => {
+            let tool = text_of("toolName");
+            let input = data.get("toolInput").cloned().unwrap_or(Value::Null);
+            format!(
+                "*{workspace}* wants to use `{tool}`:\n```{}```",
+                truncate(&summarize_input(tool, &input))
+            )
+        }
+        WebhookEvent::PlanPending => {
+            let plan = data
+                .pointer("/plan/plan")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            format!(
+                "*{workspace}* has a plan ready for review:\n{}",
+                truncate(plan)
+            )
+        }
+    };
+    json!({
+        "text": text,
+        "blocks": [{ "type": "section", "text": { "type": "mrkdwn", "text": text } }],
+    })
+}
+
+/// Allow / Deny buttons whose value names the request they answer.
+fn permission_buttons(workspace_id: &str, data: &Value) -> Value {
+    let value = json!({
+        "workspaceId": workspace_id,
+        "threadId": data.get("threadId"),
+        "requestId": data.get("requestId"),
+    })
+    .to_string();
+    json!({
+        "type": "actions",
+        "elements": [
+            {
+                "type": "button",
+                "action_id": ALLOW_ACTION,
+                "style": "primary",
+                "text": { "type": "plain_text", "text": "Allow" },
+                "value": value,
+            },
+            {
+                "type": "button",
+                "action_id": DENY_ACTION,
+                "style": "danger",
+                "text": { "type": "plain_text", "text": "Deny" },
+                "value": value,
+            },
+        ],
+    })
+}
+
+async fn post_message(bot_token: &str, message: &Value) {
+    let response = client()
+        .post(POST_MESSAGE_URL)
+        .bearer_auth(bot_token)
+        .json(message)
+        .send()
+        .await;
+    match response {
+        Ok(response) => {
+            // Slack answers 200 with `ok: false` for most failures
+            let body: Value = response.json().await.unwrap_or_default();
+            if body.get("ok").and_then(Value::as_bool) != Some(true) {
+                tracing::warn!("Slack refused a message: {}", body["error"]);
+            }
+        }
+        Err(err) => tracing::warn!("failed to post to Slack: {err}"),
+    }
+}
+
+/// Answer through a command's or action's `response_url`.
+async fn respond(response_url: &str, message: Value) {
+    if let Err(err) = client().post(response_url).json(&message).send().await {
+        tracing::warn!("failed to answer Slack: {err}");
+    }
+}
+
+/// Serve the slash command and interactivity request URLs.
+pub(crate) async fn serve(app: AppHandle, listener: std::net::TcpListener) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::warn!("failed to serve Slack requests: {err}");
+            return;
+        }
+    };
+    let router = Router::new()
+        .route("/slack/commands", post(command))
+        .route("/slack/interactions", post(interaction))
+        .with_state(app);
+    if let Err(err) = axum::serve(listener, router).await {
+        tracing::warn!("Slack bridge stopped: {err}");
+    }
+}
+
+/// Whether the request carries a valid `v0` signature made within the last minutes.
+fn verify(secret: &str, headers: &HeaderMap, body: &[u8], now: i64) -> bool {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let (Some(timestamp), Some(signature)) = (
+        header("x-slack-request-timestamp"),
+        header("x-slack-signature"),
+    ) else {
+        return false;
+    };
+    let Ok(signed_at) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if (now - signed_at).abs() > MAX_REQUEST_AGE_SECS {
+        return false;
+    }
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("v0:{timestamp}:").as_bytes());
+    mac.update(body);
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    api_tokens::constant_time_eq(&format!("v0={hex}"), signature)
+}
+
+fn authorized(app: &AppHandle, headers: &HeaderMap, body: &[u8]) -> bool {
+    app.try_state::<SlackBridge>()
+        .and_then(|bridge| bridge.signing_secret.clone())
+        .is_some_and(|secret| verify(&secret, headers, body, now_ms() / 1000))
+}
+
+/// Decode an `application/x-www-form-urlencoded` body.
+fn form_field(body: &[u8], name: &str) -> Option<String> {
+    String::from_utf8_lossy(body).split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        (percent_decode(key) == name).then(|| percent_decode(value))
+    })
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let hex = |byte: u8| (byte as char).to_digit(16).map(|digit| digit as u8);
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' => match (
+                bytes.get(index + 1).copied().and_then(hex),
+                bytes.get(index + 2).copied().and_then(hex),
+            ) {
+                (Some(high), Some(low)) => {
+                    decoded.push((high << 4) | low);
+                    index += 2;
+                }
+                _ => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[derive(Debug, PartialEq)]
+enum SlackCommand {
+    Send { workspace: String, prompt: String },
+    Help,
+}
+
+fn parse_command(text: &str) -> SlackCommand {
+    let text = text.trim();
+    let (verb, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    if verb != "send" {
+        return SlackCommand::Help;
+    }
+    let rest = rest.trim_start();
+    let (workspace, prompt) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let prompt = prompt.trim();
+    if workspace.is_empty() || prompt.is_empty() {
+        return SlackCommand::Help;
+    }
+    SlackCommand::Send {
+        workspace: workspace.to_string(),
+        prompt: prompt.to_string(),
+    }
+}
+
+fn ephemeral(text: impl Into<String>) -> Value {
+    json!({ "response_type": "ephemeral", "text": text.into() })
+}
+
+async fn command(State(app): State<AppHandle>, headers: HeaderMap, body: Bytes) -> Response {
+    if !authorized(&app, &headers, &body) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let text = form_field(&body, "text").unwrap_or_default();
+    let SlackCommand::Send { workspace, prompt } = parse_command(&text) else {
+        return Json(ephemeral(USAGE)).into_response();
+    };
+    let user = form_field(&body, "user_name").unwrap_or_default();
+    let response_url = form_field(&body, "response_url");
+    // Slack wants an answer within three seconds; starting the turn can take longer
+    let acknowledgement = format!("Sending to *{workspace}*…");
+    tauri::async_runtime::spawn(async move {
+        let outcome = send_prompt(&app, &workspace, prompt, user).await;
+        if let Some(response_url) = response_url {
+            let text = outcome.unwrap_or_else(|err| format!("Could not send: {err}"));
+            respond(&response_url, ephemeral(text)).await;
+        }
+    });
+    Json(ephemeral(acknowledgement)).into_response()
+}
+
+/// Send `prompt` to the workspace's most recent thread, starting one if it has none.
+async fn send_prompt(
+    app: &AppHandle,
+    workspace: &str,
+    prompt: String,
+    user: String,
+) -> Result<String, String> {
+    let state = app.state::<AppState>();
+    let entry = state
+        .workspaces
+        .lock()
+        .await
+        .values()
+        .find(|entry| entry.id == workspace || entry.name.eq_ignore_ascii_case(workspace))
+        .cloned()
+        .ok_or_else(|| format!("no workspace named {workspace}"))?;
+    let path = state.db_path.clone();
+    let workspace_id = entry.id.clone();
+    let latest =
+        tokio::task::spawn_blocking(move || storage::read_thread_sessions(&path, &workspace_id))
+            .await
+            .map_err(|err| err.to_string())??
+            .into_iter()
+            .next()
+            .map(|session| session.thread_id);
+    let thread_id = match latest {
+        Some(thread_id) => thread_id,
+        None => super::dispatch(app, "start_thread", &json!({ "workspaceId": entry.id }))
+            .await
+            .map_err(|err| err.message)?
+            .pointer("/thread/id")
+            .and_then(Value::as_str)
+            .ok_or("no thread was started")?
+            .to_string(),
+    };
+    let params = json!({
+        "workspaceId": entry.id,
+        "threadId": thread_id,
+        "text": prompt,
+        "user": user,
+    });
+    let sent = super::dispatch(app, "send_user_message", &params)
+        .await
+        .map_err(|err| err.message)?;
+    Ok(
+        match sent
+            .pointer("/result/queued/position")
+            .and_then(Value::as_u64)
+        {
+            Some(position) => format!("Queued for *{}* at position {position}.", entry.name),
+            None if sent.pointer("/result/turn/id").is_some() => {
+                format!("Started a turn in *{}*.", entry.name)
+            }
+            None => format!("Saved for when *{}* reconnects.", entry.name),
+        },
+    )
+}
+
+async fn interaction(State(app): State<AppHandle>, headers: HeaderMap, body: Bytes) -> Response {
+    if !authorized(&app, &headers, &body) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let Some(payload) = form_field(&body, "payload")
+        .and_then(|payload| serde_json::from_str::<Value>(&payload).ok())
+    else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let Some(action) = payload.pointer("/actions/0") else {
+        return StatusCode::OK.into_response();
+    };
+    let action_id = action
+        .get("action_id")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let Some(mut params) = action
+        .get("value")
+        .and_then(Value::as_str)
+        .and_then(|value| serde_json::from_str::<Value>(value).ok())
+    else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let user = payload
+        .pointer("/user/username")
+        .or_else(|| payload.pointer("/user/name"))
+        .and_then(Value::as_str)
+        .unwrap_or("someone")
+        .to_string();
+    params["decision"] = match action_id {
+        ALLOW_ACTION => json!({ "behavior": "allow" }),
+        DENY_ACTION => {
+            json!({ "behavior": "deny", "message": format!("Denied by {user} in Slack") })
+        }
+        _ => return StatusCode::OK.into_response(),
+    };
+    let response_url = payload
+        .get("response_url")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    tauri::async_runtime::spawn(async move {
+        let answered = super::dispatch(&app, "respond_to_permission_request", &params).await;
+        let Some(response_url) = response_url else {
+            return;
+        };
+        let text = match (answered, action_id_verb(&params)) {
+            (Ok(_), verb) => format!("{verb} by {user}."),
+            (Err(err), _) => format!("Could not answer the prompt: {}", err.message),
+        };
+        respond(
+            &response_url,
+            json!({ "replace_original": true, "text": text }),
+        )
+        .await;
+    });
+    StatusCode::OK.into_response()
+}
+
+fn action_id_verb(params: &Value) -> &'static str {
+    match params.pointer("/decision/behavior").and_then(Value::as_str) {
+        Some("allow") => "Allowed",
+        _ => "Denied",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn signed_headers(secret: &str, timestamp: i64, body: &[u8]) -> HeaderMap {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("v0:{timestamp}:").as_bytes());
+        mac.update(body);
+        let hex: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-slack-request-timestamp",
+            HeaderValue::from_str(&timestamp.to_string()).unwrap(),
+        );
+        headers.insert(
+            "x-slack-signature",
+            HeaderValue::from_str(&format!("v0={hex}")).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn verifies_slack_signatures() {
+        let body = b"command=%2Fcc&text=send+web+fix+it";
+        let headers = signed_headers("shh", 1_000, body);
+        assert!(verify("shh", &headers, body, 1_010));
+        assert!(!verify("other", &headers, body, 1_010));
+        assert!(!verify("shh", &headers, b"command=%2Fcc", 1_010));
+        assert!(!verify(
+            "shh",
+            &headers,
+            body,
+            1_000 + MAX_REQUEST_AGE_SECS + 1
+        ));
+        assert!(!verify("shh", &HeaderMap::new(), body, 1_000));
+    }
+
+    #[test]
+    fn reads_form_fields() {
+        let body = b"user_name=ann&text=send+web+Fix+the+%2Flogin+page%21&x=%zz";
+        assert_eq!(form_field(body, "user_name").as_deref(), Some("ann"));
+        assert_eq!(
+            form_field(body, "text").as_deref(),
+            Some("send web Fix the /login page!")
+        );
+        assert_eq!(form_field(body, "x").as_deref(), Some("%zz"));
+        assert_eq!(form_field(body, "missing"), None);
+    }
+
+    #[test]
+    fn parses_slash_commands() {
+        assert_eq!(
+            parse_command(" send  web   fix the build\nplease "),
+            SlackCommand::Send {
+                workspace: "web".to_string(),
+                prompt: "fix the build\nplease".to_string(),
+            }
+        );
+        assert_eq!(parse_command("send web"), SlackCommand::Help);
+        assert_eq!(parse_command("help"), SlackCommand::Help);
+        assert_eq!(parse_command(""), SlackCommand::Help);
+    }
+
+    #[test]
+    fn describes_events() {
+        let message = event_message(
+            "web",
+            WebhookEvent::TurnCompleted,
+            &json!({ "durationMs": 12_400, "costUsd": 0.031, "result": "Fixed the build." }),
+        );
+        assert_eq!(
+            message["text"],
+            "*web* finished a turn (12s, $0.03):\nFixed the build."
+        );
+        let prompt = event_message(
+            "web",
+            WebhookEvent::PermissionPending,
+            &json!({ "toolName": "Bash", "toolInput": { "command": "rm -rf target" } }),
+        );
+        assert_eq!(
+            prompt["text"],
+            "*web* wants to use `Bash`:\n```rm -rf target```"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::rpc::{self, Subscription};
+use super::DaemonState;
+use crate::api_tokens::{self, Denial};
+use crate::types::TokenRole;
+
+/// Read-only event feed for remote UIs.
+///
+/// Clients connect to `ws://<listen>/events` and authenticate with the daemon token or
+/// an issued API token of any role, either as `Authorization: Bearer <token>` or as a
+/// `token` query parameter for browsers that cannot set headers. `workspaceId` and
+/// `event` query parameters (repeatable) narrow the feed the same way `subscribe` does on
+/// the control socket.
+pub(crate) async fn serve(app: AppHandle, listener: std::net::TcpListener, token: Option<String>) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::warn!("failed to listen for websockets: {err}");
+            return;
+        }
+    };
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(serve_client(app.clone(), stream, token.clone()));
+            }
+            Err(err) => {
+                tracing::warn!("websocket accept failed: {err}");
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+async fn serve_client(app: AppHandle, stream: TcpStream, token: Option<String>) {
+    let mut filter = None;
+    let handshake =
+        tokio_tungstenite::accept_hdr_async(stream, |request: &Request, response: Response| {
+            authorize(request, token.as_deref()).map(|subscription| {
+                filter = Some(subscription);
+                response
+            })
+        })
+        .await;
+    let (Ok(socket), Some(filter)) = (handshake, filter) else {
+        return;
+    };
+    let (mut sink, mut incoming) = socket.split();
+    let mut events = app.state::<DaemonState>().events.subscribe();
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let text = match event {
+                    Ok(event) if filter.matches(event.method, &event.params) => {
+                        rpc::notification(event.method, &event.params)
+                    }
+                    Ok(_) => continue,
+                    // A phone on a flaky link must not hold the backend up
+                    Err(RecvError::Lagged(skipped)) => {
+                        crate::event_sink::note_dropped_events(skipped);
+                        rpc::notification("daemon/eventsDropped", &json!({ "skipped": skipped }))
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if sink.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            // Reading drives ping/pong and close handling; client messages are ignored
+            message = incoming.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    let _ = sink.close().await;
+}
+
+fn authorize(request: &Request, token: Option<&str>) -> Result<Subscription, ErrorResponse> {
+    if request.uri().path() != "/events" {
+        return Err(reject(StatusCode::NOT_FOUND, "unknown path"));
+    }
+    let query = query_pairs(request.uri().query().unwrap_or(""));
+    let bearer = request
+        .headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+    let provided = bearer.or_else(|| {
+        query
+            .iter()
+            .find(|(key, _)| key == "token")
+            .map(|(_, value)| value.clone())
+    });
+    let authorized = api_tokens::authorize(
+        token,
+        provided.as_deref(),
+        TokenRole::Observer,
+        "websocket",
+        "subscribe",
+    );
+    match authorized {
+        Ok(_) => Ok(subscription_from_query(&query)),
+        Err(Denial::Unauthenticated) => Err(reject(StatusCode::UNAUTHORIZED, "invalid token")),
+        Err(Denial::Forbidden) => Err(reject(StatusCode::FORBIDDEN, "token not allowed")),
+    }
+}
+
+fn reject(status: StatusCode, message: &str) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(message.to_string()));
+    *response.status_mut() = status;
+    response
+}
+
+fn subscription_from_query(query: &[(String, String)]) -> Subscription {
+    let values = |name: &str| {
+        let values: std::collections::HashSet<String> = query
+            .iter()
+            .filter(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .collect();
+        (!values.is_empty()).then_some(values)
+    };
+    Subscription {
+        workspace_ids: values("workspaceId"),
+        events: values("event"),
+    }
+}
+
+fn query_pairs(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' if index + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        index += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str, authorization: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri(uri);
+        if let Some(value) = authorization {
+            builder = builder.header("Authorization", value);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn accepts_bearer_or_query_token() {
+        assert!(authorize(&request("/events", Some("Bearer s3cret")), Some("s3cret")).is_ok());
+        assert!(authorize(&request("/events?token=s3cret", None), Some("s3cret")).is_ok());
+
+        let denied = authorize(&request("/events?token=nope", None), Some("s3cret")).unwrap_err();
+        assert_eq!(denied.status(), StatusCode::UNAUTHORIZED);
+        let missing = authorize(&request("/events", None), Some("s3cret")).unwrap_err();
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+        let wrong_path =
+            authorize(&request("/other?token=s3cret", None), Some("s3cret")).unwrap_err();
+        assert_eq!(wrong_path.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn query_narrows_the_feed() {
+        let subscription = authorize(
+            &request(
+                "/events?token=t&workspaceId=ws-1&workspaceId=ws%202&event=app-server-event",
+                None,
+            ),
+            "t",
+        )
+        .unwrap();
+        let workspace_ids = subscription.workspace_ids.clone().unwrap();
+        assert!(workspace_ids.contains("ws-1"));
+        assert!(workspace_ids.contains("ws 2"));
+        assert!(subscription.matches("app-server-event", &json!({ "workspace_id": "ws-1" })));
+        assert!(!subscription.matches("terminal-output", &json!({ "workspace_id": "ws-1" })));
+
+        let everything = authorize(&request("/events?token=t", None), Some("t")).unwrap();
+        assert_eq!(everything, Subscription::default());
+    }
+
+    #[test]
+    fn percent_decoding_tolerates_bad_escapes() {
+        assert_eq!(percent_decode("a%20b+c"), "a b c");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+}
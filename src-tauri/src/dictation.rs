@@ -226,14 +226,14 @@ fn model_info(model_id: &str) -> Option<&'static DictationModelInfo> {
 }
 
 fn model_path(app: &AppHandle, model_id: &str) -> Result<PathBuf, String> {
-    let info = model_info(model_id)
-        .ok_or_else(|| format!("Unknown dictation model: {model_id}"))?;
+    let info =
+        model_info(model_id).ok_or_else(|| format!("Unknown dictation model: {model_id}"))?;
     Ok(model_dir(app).join(info.filename))
 }
 
 fn model_temp_path(app: &AppHandle, model_id: &str) -> Result<PathBuf, String> {
-    let info = model_info(model_id)
-        .ok_or_else(|| format!("Unknown dictation model: {model_id}"))?;
+    let info =
+        model_info(model_id).ok_or_else(|| format!("Unknown dictation model: {model_id}"))?;
     Ok(model_dir(app).join(format!("{}.partial", info.filename)))
 }
 
@@ -265,10 +265,7 @@ fn emit_event(app: &AppHandle, event: DictationEvent) {
     let _ = app.emit("dictation-event", event);
 }
 
-async fn clear_processing_cancel(
-    app: &AppHandle,
-    cancel_flag: &Arc<AtomicBool>,
-) -> bool {
+async fn clear_processing_cancel(app: &AppHandle, cancel_flag: &Arc<AtomicBool>) -> bool {
     let state_handle = app.state::<AppState>();
     let mut dictation = state_handle.dictation.lock().await;
     if dictation
@@ -282,11 +279,7 @@ async fn clear_processing_cancel(
     false
 }
 
-async fn update_status(
-    app: &AppHandle,
-    state: &State<'_, AppState>,
-    status: DictationModelStatus,
-) {
+async fn update_status(app: &AppHandle, state: &State<'_, AppState>, status: DictationModelStatus) {
     {
         let mut dictation = state.dictation.lock().await;
         dictation.model_status = status.clone();
@@ -300,10 +293,7 @@ async fn clear_download_state(state: &State<'_, AppState>) {
     dictation.download_task = None;
 }
 
-async fn resolve_model_id(
-    state: &State<'_, AppState>,
-    model_id: Option<String>,
-) -> String {
+async fn resolve_model_id(state: &State<'_, AppState>, model_id: Option<String>) -> String {
     let candidate = if let Some(model_id) = model_id {
         model_id
     } else {
@@ -376,9 +366,7 @@ pub(crate) async fn dictation_download_model(
     if current.state == DictationModelState::Ready {
         return Ok(current);
     }
-    if current.state == DictationModelState::Downloading
-        && current.model_id == model_id
-    {
+    if current.state == DictationModelState::Downloading && current.model_id == model_id {
         return Ok(current);
     }
 
@@ -737,14 +725,24 @@ pub(crate) async fn dictation_start(
     let model_status = refresh_status(&app, &state, &model_id).await;
     if model_status.state != DictationModelState::Ready {
         let message = "Dictation model is not downloaded yet.".to_string();
-        emit_event(&app, DictationEvent::Error { message: message.clone() });
+        emit_event(
+            &app,
+            DictationEvent::Error {
+                message: message.clone(),
+            },
+        );
         return Err(message);
     }
     {
         let dictation = state.dictation.lock().await;
         if dictation.session_state != DictationSessionState::Idle {
             let message = "Dictation is already active.".to_string();
-            emit_event(&app, DictationEvent::Error { message: message.clone() });
+            emit_event(
+                &app,
+                DictationEvent::Error {
+                    message: message.clone(),
+                },
+            );
             return Err(message);
         }
     }
@@ -756,11 +754,21 @@ pub(crate) async fn dictation_start(
         }
         Ok(false) => {
             let message = "Microphone access was denied. Please grant microphone permission in System Settings > Privacy & Security > Microphone.".to_string();
-            emit_event(&app, DictationEvent::Error { message: message.clone() });
+            emit_event(
+                &app,
+                DictationEvent::Error {
+                    message: message.clone(),
+                },
+            );
             return Err(message);
         }
         Err(error) => {
-            emit_event(&app, DictationEvent::Error { message: error.clone() });
+            emit_event(
+                &app,
+                DictationEvent::Error {
+                    message: error.clone(),
+                },
+            );
             return Err(error);
         }
     }
@@ -788,12 +796,22 @@ pub(crate) async fn dictation_start(
     let sample_rate = match ready_rx.await {
         Ok(Ok(rate)) => rate,
         Ok(Err(message)) => {
-            emit_event(&app, DictationEvent::Error { message: message.clone() });
+            emit_event(
+                &app,
+                DictationEvent::Error {
+                    message: message.clone(),
+                },
+            );
             return Err(message);
         }
         Err(_) => {
             let message = "Failed to start microphone capture.".to_string();
-            emit_event(&app, DictationEvent::Error { message: message.clone() });
+            emit_event(
+                &app,
+                DictationEvent::Error {
+                    message: message.clone(),
+                },
+            );
             return Err(message);
         }
     };
@@ -831,7 +849,12 @@ pub(crate) async fn dictation_stop(
         let mut dictation = state.dictation.lock().await;
         if dictation.session_state != DictationSessionState::Listening {
             let message = "Dictation is not currently listening.".to_string();
-            emit_event(&app, DictationEvent::Error { message: message.clone() });
+            emit_event(
+                &app,
+                DictationEvent::Error {
+                    message: message.clone(),
+                },
+            );
             return Err(message);
         }
         dictation.session_state = DictationSessionState::Processing;
@@ -971,17 +994,11 @@ pub(crate) async fn dictation_stop(
         match outcome {
             Ok(text) => {
                 if !text.trim().is_empty() {
-                    emit_event(
-                        &app_handle,
-                        DictationEvent::Transcript { text },
-                    );
+                    emit_event(&app_handle, DictationEvent::Transcript { text });
                 }
             }
             Err(message) => {
-                emit_event(
-                    &app_handle,
-                    DictationEvent::Error { message },
-                );
+                emit_event(&app_handle, DictationEvent::Error { message });
             }
         }
 
@@ -1031,7 +1048,12 @@ pub(crate) async fn dictation_cancel(
         let mut dictation = state.dictation.lock().await;
         if dictation.session_state != DictationSessionState::Listening {
             let message = "Dictation is not currently listening.".to_string();
-            emit_event(&app, DictationEvent::Error { message: message.clone() });
+            emit_event(
+                &app,
+                DictationEvent::Error {
+                    message: message.clone(),
+                },
+            );
             return Err(message);
         }
         dictation.session_state = DictationSessionState::Idle;
@@ -1202,9 +1224,11 @@ fn start_capture_thread(
         }
     });
 
-    eprintln!(
-        "dictation: capture started (rate={}Hz, channels={}, format={:?})",
-        sample_rate, channels, sample_format
+    tracing::debug!(
+        "capture started (rate={}Hz, channels={}, format={:?})",
+        sample_rate,
+        channels,
+        sample_format
     );
     let _ = ready_tx.send(Ok(sample_rate));
     let _ = stop_rx.recv();
@@ -1304,14 +1328,18 @@ fn transcribe_audio(
     }
     let rms = (sum / samples.len() as f32).sqrt();
     let duration = samples.len() as f32 / sample_rate as f32;
-    let gain = if max > 0.0 { (0.6 / max).min(10.0) } else { 1.0 };
+    let gain = if max > 0.0 {
+        (0.6 / max).min(10.0)
+    } else {
+        1.0
+    };
     if gain != 1.0 {
         for value in &mut normalized {
             *value = (*value * gain).clamp(-1.0, 1.0);
         }
     }
-    eprintln!(
-        "dictation: captured {} samples ({:.2}s), max={:.4}, rms={:.4}, gain={:.2}",
+    tracing::debug!(
+        "captured {} samples ({:.2}s), max={:.4}, rms={:.4}, gain={:.2}",
         samples.len(),
         duration,
         max,
@@ -1377,7 +1405,7 @@ fn transcribe_audio(
     let segments = state
         .full_n_segments()
         .map_err(|error| format!("Failed to read segments: {error}"))?;
-    eprintln!("dictation: whisper segments={}", segments);
+    tracing::debug!("whisper segments={}", segments);
     let mut transcript = String::new();
     for index in 0..segments {
         let segment = state
@@ -1387,9 +1415,12 @@ fn transcribe_audio(
     }
     let cleaned = transcript.trim().to_string();
     if cleaned.is_empty() {
-        eprintln!(
-            "dictation: no speech detected (rms={:.4}, max={:.4}, duration={:.2}s, segments={})",
-            rms, max, duration, segments
+        tracing::debug!(
+            "no speech detected (rms={:.4}, max={:.4}, duration={:.2}s, segments={})",
+            rms,
+            max,
+            duration,
+            segments
         );
         return Ok(String::new());
     }
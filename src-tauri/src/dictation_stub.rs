@@ -153,7 +153,12 @@ pub(crate) async fn dictation_start(
         dictation.session_state = DictationSessionState::Idle;
     }
     let message = UNSUPPORTED_MESSAGE.to_string();
-    emit_event(&app, DictationEvent::Error { message: message.clone() });
+    emit_event(
+        &app,
+        DictationEvent::Error {
+            message: message.clone(),
+        },
+    );
     Err(message)
 }
 
@@ -167,7 +172,12 @@ pub(crate) async fn dictation_stop(
         dictation.session_state = DictationSessionState::Idle;
     }
     let message = UNSUPPORTED_MESSAGE.to_string();
-    emit_event(&app, DictationEvent::Error { message: message.clone() });
+    emit_event(
+        &app,
+        DictationEvent::Error {
+            message: message.clone(),
+        },
+    );
     Err(message)
 }
 
@@ -188,4 +198,3 @@ pub(crate) async fn dictation_cancel(
     );
     Ok(DictationSessionState::Idle)
 }
-
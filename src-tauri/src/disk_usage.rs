@@ -0,0 +1,512 @@
+//! Disk space taken by the CLI's data under `~/.claude` and by the monitor's own storage,
+//! per workspace where it can be told apart, and the cleanups that would free some of it.
+//!
+//! A workspace's share is its project directory under `~/.claude/projects`, the todo
+//! lists of the sessions in it, its recordings and its spilled session logs. Shell
+//! snapshots and the database are not tied to one workspace.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::backend::recorder::{recordings_dir, workspace_recordings_dir};
+use crate::backend::session_logs::{session_logs_dir, workspace_log_file};
+use crate::claude_home::resolve_default_claude_home;
+use crate::remote_backend;
+use crate::retention::{self, stale_files, RetentionReport};
+use crate::state::AppState;
+use crate::storage;
+use crate::telemetry::LOG_DIR;
+use crate::transcripts::encode_project_path;
+use crate::types::{RetentionPolicy, WorkspaceEntry};
+use crate::utils::now_ms;
+
+/// Sessions untouched for this long are offered for deletion unless asked otherwise.
+const DEFAULT_STALE_DAYS: u32 = 30;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Files under a directory and their total size.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DirUsage {
+    pub(crate) bytes: u64,
+    pub(crate) files: usize,
+}
+
+impl DirUsage {
+    fn add(&mut self, other: DirUsage) {
+        self.bytes += other.bytes;
+        self.files += other.files;
+    }
+
+    fn minus(self, other: DirUsage) -> DirUsage {
+        DirUsage {
+            bytes: self.bytes.saturating_sub(other.bytes),
+            files: self.files.saturating_sub(other.files),
+        }
+    }
+}
+
+/// `~/.claude`, or wherever `CLAUDE_HOME` points.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClaudeHomeUsage {
+    pub(crate) path: Option<String>,
+    /// Session transcripts, one directory per project.
+    pub(crate) projects: DirUsage,
+    pub(crate) todos: DirUsage,
+    pub(crate) shell_snapshots: DirUsage,
+    /// Settings, plugins, statsig caches and anything else.
+    pub(crate) other: DirUsage,
+    pub(crate) total: DirUsage,
+}
+
+/// The app data directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppStorageUsage {
+    pub(crate) path: String,
+    pub(crate) database: DirUsage,
+    /// Part of the database file a vacuum would give back.
+    pub(crate) database_free_bytes: u64,
+    pub(crate) recordings: DirUsage,
+    pub(crate) session_logs: DirUsage,
+    pub(crate) logs: DirUsage,
+    pub(crate) other: DirUsage,
+    pub(crate) total: DirUsage,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceDiskUsage {
+    pub(crate) workspace_id: String,
+    pub(crate) name: String,
+    pub(crate) transcripts: DirUsage,
+    pub(crate) todos: DirUsage,
+    pub(crate) recordings: DirUsage,
+    pub(crate) session_logs: DirUsage,
+    /// Transcripts and recordings old enough for `deleteOldSessions`.
+    pub(crate) stale_sessions: DirUsage,
+    pub(crate) total_bytes: u64,
+}
+
+/// Something `run_disk_cleanup` can do.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum CleanupAction {
+    /// Delete transcripts and recordings untouched for `olderThanDays`, with their turn
+    /// history, as the retention sweep does. Threads with a live process are kept.
+    DeleteOldSessions {
+        #[serde(rename = "olderThanDays")]
+        older_than_days: u32,
+    },
+    /// Give the database's free pages back to the file system.
+    VacuumDatabase,
+}
+
+/// A cleanup worth offering, with what it would free.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CleanupSuggestion {
+    pub(crate) action: CleanupAction,
+    pub(crate) bytes: u64,
+    pub(crate) files: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiskUsageReport {
+    pub(crate) claude_home: ClaudeHomeUsage,
+    pub(crate) app: AppStorageUsage,
+    pub(crate) workspaces: Vec<WorkspaceDiskUsage>,
+    /// Project directories of no registered workspace, e.g. of sessions run elsewhere.
+    pub(crate) unassigned_projects: DirUsage,
+    pub(crate) suggestions: Vec<CleanupSuggestion>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CleanupResult {
+    pub(crate) bytes_freed: u64,
+    pub(crate) files_removed: usize,
+}
+
+/// How much disk `~/.claude` and the app's storage take, per workspace, and which
+/// cleanups would help; sessions count as old after `olderThanDays` (default 30).
+#[tauri::command]
+pub(crate) async fn get_disk_usage(
+    older_than_days: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<DiskUsageReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_disk_usage",
+            json!({ "olderThanDays": older_than_days }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let older_than_days = older_than_days.unwrap_or(DEFAULT_STALE_DAYS).max(1);
+    let mut entries: Vec<WorkspaceEntry> =
+        state.workspaces.lock().await.values().cloned().collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    let db_path = state.db_path.clone();
+    tokio::task::spawn_blocking(move || {
+        let home = resolve_default_claude_home();
+        let cutoff = now_ms() - i64::from(older_than_days) * DAY_MS;
+        let mut report = scan(home.as_deref(), &db_path, &entries, cutoff, older_than_days);
+        report.app.database_free_bytes = storage::database_free_bytes(&db_path).unwrap_or(0);
+        if report.app.database_free_bytes > 0 {
+            report.suggestions.push(CleanupSuggestion {
+                action: CleanupAction::VacuumDatabase,
+                bytes: report.app.database_free_bytes,
+                files: 0,
+            });
+        }
+        report
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Run one of the cleanups `get_disk_usage` suggests.
+#[tauri::command]
+pub(crate) async fn run_disk_cleanup(
+    action: CleanupAction,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<CleanupResult, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "run_disk_cleanup",
+            json!({ "action": action }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    match action {
+        CleanupAction::DeleteOldSessions { older_than_days } => {
+            let policy = RetentionPolicy {
+                transcript_days: Some(older_than_days),
+                usage_days: None,
+            };
+            retention::validate_retention_policy(&policy)?;
+            let report: RetentionReport = retention::sweep(&state, &policy).await?;
+            Ok(CleanupResult {
+                bytes_freed: report.bytes_freed,
+                files_removed: report.transcripts_removed + report.recordings_removed,
+            })
+        }
+        CleanupAction::VacuumDatabase => {
+            let db_path = state.db_path.clone();
+            tokio::task::spawn_blocking(move || {
+                let before = database_usage(&db_path).bytes;
+                storage::vacuum_database(&db_path)?;
+                Ok(CleanupResult {
+                    bytes_freed: before.saturating_sub(database_usage(&db_path).bytes),
+                    files_removed: 0,
+                })
+            })
+            .await
+            .map_err(|err| err.to_string())?
+        }
+    }
+}
+
+fn scan(
+    home: Option<&Path>,
+    db_path: &Path,
+    entries: &[WorkspaceEntry],
+    cutoff: i64,
+    older_than_days: u32,
+) -> DiskUsageReport {
+    let mut report = DiskUsageReport::default();
+    let mut stale = DirUsage::default();
+    // Session ids of each workspace's transcripts, to attribute todo lists
+    let mut session_owner: HashMap<String, usize> = HashMap::new();
+    let mut claimed_projects = HashSet::new();
+    for entry in entries {
+        let mut usage = WorkspaceDiskUsage {
+            workspace_id: entry.id.clone(),
+            name: entry.name.clone(),
+            ..WorkspaceDiskUsage::default()
+        };
+        let project_dir =
+            home.map(|home| home.join("projects").join(encode_project_path(&entry.path)));
+        // Workspaces on the same path share a project directory; the first one counts it
+        if let Some(dir) = project_dir.filter(|dir| claimed_projects.insert(dir.clone())) {
+            usage.transcripts = dir_usage(&dir);
+            for session_id in jsonl_stems(&dir) {
+                session_owner.insert(session_id, report.workspaces.len());
+            }
+            usage
+                .stale_sessions
+                .add(files_usage(&stale_files(&dir, cutoff, |_| false)));
+        }
+        if let Some(dir) = workspace_recordings_dir(&entry.id) {
+            usage.recordings = dir_usage(&dir);
+            usage
+                .stale_sessions
+                .add(files_usage(&stale_files(&dir, cutoff, |_| false)));
+        }
+        if let Some(file) = workspace_log_file(&entry.id) {
+            usage.session_logs = file_usage(&file);
+        }
+        stale.add(usage.stale_sessions);
+        report.workspaces.push(usage);
+    }
+
+    if let Some(home) = home {
+        let claude = &mut report.claude_home;
+        claude.path = Some(home.display().to_string());
+        claude.total = dir_usage(home);
+        claude.projects = dir_usage(&home.join("projects"));
+        claude.shell_snapshots = dir_usage(&home.join("shell-snapshots"));
+        // Todo lists are `<session id>-agent-<agent id>.json`
+        for (path, size) in dir_files(&home.join("todos")) {
+            claude.todos.add(DirUsage {
+                bytes: size,
+                files: 1,
+            });
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("");
+            let session_id = name.split("-agent-").next().unwrap_or(name);
+            if let Some(index) = session_owner.get(session_id) {
+                report.workspaces[*index].todos.add(DirUsage {
+                    bytes: size,
+                    files: 1,
+                });
+            }
+        }
+        claude.other = claude
+            .total
+            .minus(claude.projects)
+            .minus(claude.todos)
+            .minus(claude.shell_snapshots);
+        if let Ok(dir_entries) = fs::read_dir(home.join("projects")) {
+            for dir_entry in dir_entries.flatten() {
+                let path = dir_entry.path();
+                if path.is_dir() && !claimed_projects.contains(&path) {
+                    report.unassigned_projects.add(dir_usage(&path));
+                }
+            }
+        }
+    }
+
+    let app = &mut report.app;
+    let data_dir = db_path.parent().unwrap_or(Path::new("."));
+    app.path = data_dir.display().to_string();
+    app.total = dir_usage(data_dir);
+    app.database = database_usage(db_path);
+    app.recordings = recordings_dir().map(dir_usage).unwrap_or_default();
+    app.session_logs = session_logs_dir().map(dir_usage).unwrap_or_default();
+    app.logs = dir_usage(&data_dir.join(LOG_DIR));
+    app.other = app
+        .total
+        .minus(app.database)
+        .minus(app.recordings)
+        .minus(app.session_logs)
+        .minus(app.logs);
+
+    for usage in &mut report.workspaces {
+        usage.total_bytes = usage.transcripts.bytes
+            + usage.todos.bytes
+            + usage.recordings.bytes
+            + usage.session_logs.bytes;
+    }
+    report
+        .workspaces
+        .sort_by(|a, b| b.total_bytes.cmp(&a.total_bytes));
+    if stale.files > 0 {
+        report.suggestions.push(CleanupSuggestion {
+            action: CleanupAction::DeleteOldSessions { older_than_days },
+            bytes: stale.bytes,
+            files: stale.files,
+        });
+    }
+    report
+}
+
+/// The database file with its journal.
+fn database_usage(db_path: &Path) -> DirUsage {
+    let mut usage = file_usage(db_path);
+    for suffix in ["-journal", "-wal", "-shm"] {
+        let mut path = db_path.as_os_str().to_owned();
+        path.push(suffix);
+        usage.add(file_usage(Path::new(&path)));
+    }
+    usage
+}
+
+fn file_usage(path: &Path) -> DirUsage {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_file() => DirUsage {
+            bytes: meta.len(),
+            files: 1,
+        },
+        _ => DirUsage::default(),
+    }
+}
+
+fn files_usage(files: &[(PathBuf, u64)]) -> DirUsage {
+    DirUsage {
+        bytes: files.iter().map(|(_, size)| size).sum(),
+        files: files.len(),
+    }
+}
+
+/// Everything under `dir`; symlinks are not followed.
+fn dir_usage(dir: &Path) -> DirUsage {
+    let mut usage = DirUsage::default();
+    let Ok(dir_entries) = fs::read_dir(dir) else {
+        return usage;
+    };
+    for dir_entry in dir_entries.flatten() {
+        let Ok(meta) = dir_entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            usage.add(dir_usage(&dir_entry.path()));
+        } else if meta.is_file() {
+            usage.add(DirUsage {
+                bytes: meta.len(),
+                files: 1,
+            });
+        }
+    }
+    usage
+}
+
+/// Files directly in `dir`, with their size.
+fn dir_files(dir: &Path) -> Vec<(PathBuf, u64)> {
+    let Ok(dir_entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    dir_entries
+        .flatten()
+        .filter_map(|dir_entry| {
+            let meta = dir_entry.metadata().ok().filter(|meta| meta.is_file())?;
+            Some((dir_entry.path(), meta.len()))
+        })
+        .collect()
+}
+
+fn jsonl_stems(dir: &Path) -> Vec<String> {
+    dir_files(dir)
+        .into_iter()
+        .filter(|(path, _)| path.extension().and_then(|ext| ext.to_str()) == Some("jsonl"))
+        .filter_map(|(path, _)| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings};
+    use uuid::Uuid;
+
+    fn workspace(id: &str, path: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: path.to_string(),
+            claude_bin: None,
+            model: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            archived_at: None,
+            pinned: false,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    fn write(path: &Path, bytes: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "x".repeat(bytes)).unwrap();
+    }
+
+    #[test]
+    fn breaks_usage_down_by_area_and_workspace() {
+        let root = std::env::temp_dir().join(format!("disk-usage-{}", Uuid::new_v4()));
+        let home = root.join(".claude");
+        let projects = home.join("projects");
+        write(&projects.join("-work-api").join("s1.jsonl"), 100);
+        write(&projects.join("-work-api").join("s2.jsonl"), 50);
+        write(&projects.join("-elsewhere").join("s9.jsonl"), 30);
+        write(&home.join("todos").join("s1-agent-s1.json"), 7);
+        write(&home.join("todos").join("s9-agent-s9.json"), 3);
+        write(&home.join("shell-snapshots").join("snapshot-zsh-1.sh"), 20);
+        write(&home.join("settings.json"), 5);
+        let db_path = root.join("data").join(storage::DATABASE_FILE);
+        write(&db_path, 400);
+        write(&root.join("data").join(LOG_DIR).join("backend.log"), 60);
+
+        let entries = [workspace("api", "/work/api"), workspace("web", "/work/web")];
+        // Everything counts as old with a cutoff in the future
+        let report = scan(Some(&home), &db_path, &entries, now_ms() + DAY_MS, 30);
+
+        let claude = &report.claude_home;
+        assert_eq!(
+            claude.projects,
+            DirUsage {
+                bytes: 180,
+                files: 3
+            }
+        );
+        assert_eq!(claude.todos.bytes, 10);
+        assert_eq!(claude.shell_snapshots.bytes, 20);
+        assert_eq!(claude.other, DirUsage { bytes: 5, files: 1 });
+        assert_eq!(report.unassigned_projects.bytes, 30);
+
+        let api = &report.workspaces[0];
+        assert_eq!(api.workspace_id, "api");
+        assert_eq!(api.transcripts.bytes, 150);
+        assert_eq!(api.todos, DirUsage { bytes: 7, files: 1 });
+        assert_eq!(api.stale_sessions.files, 2);
+        assert_eq!(api.total_bytes, 157);
+        assert_eq!(report.workspaces[1].total_bytes, 0);
+
+        assert_eq!(report.app.database.bytes, 400);
+        assert_eq!(report.app.logs.bytes, 60);
+        assert_eq!(
+            report.suggestions,
+            vec![CleanupSuggestion {
+                action: CleanupAction::DeleteOldSessions {
+                    older_than_days: 30
+                },
+                bytes: 150,
+                files: 2,
+            }]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn cleanup_actions_use_camel_case_tags() {
+        let action: CleanupAction =
+            serde_json::from_value(json!({ "kind": "deleteOldSessions", "olderThanDays": 14 }))
+                .unwrap();
+        assert_eq!(
+            action,
+            CleanupAction::DeleteOldSessions {
+                older_than_days: 14
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(CleanupAction::VacuumDatabase).unwrap(),
+            json!({ "kind": "vacuumDatabase" })
+        );
+    }
+}
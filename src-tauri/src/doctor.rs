@@ -0,0 +1,583 @@
+//! Environment diagnostics behind `run_doctor`.
+//!
+//! Most problems teammates hit are in the environment rather than the app: a CLI that
+//! isn't on the `PATH` a GUI app sees, a settings file with a stray comma, a proxy that
+//! blocks the API. Each check reports pass, warn or fail with what it found and, unless
+//! it passed, how to fix it. Given a workspace, the checks use its CLI, settings, MCP
+//! servers, provider and proxy; otherwise the app's, and the MCP check is skipped.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use futures_util::future::join_all;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+use tokio::process::Command;
+use tokio::time::timeout;
+
+use crate::backend::claude_cli::build_claude_path_env;
+use crate::backend::provider::{provider_endpoint, provider_env};
+use crate::claude;
+use crate::claude_home::{resolve_default_claude_home, resolve_home_dir};
+use crate::claude_settings::{
+    read_settings_file, resolve_workspace_dirs, scope_path, SettingsScope,
+};
+use crate::cli_manager::{self, check_claude_installation, CLI_NAMES};
+use crate::credentials;
+use crate::git_utils::resolve_git_root;
+use crate::mcp::{list_configured_servers, McpApproval, McpServerConfig};
+use crate::proxy;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::WorkspaceEntry;
+
+const GIT_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Below this much free space transcripts may fail to write.
+const MIN_FREE_BYTES: u64 = 500 * 1024 * 1024;
+/// Below this much free space a long session can fill the disk.
+const LOW_FREE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    /// Not applicable, e.g. the MCP check without a workspace.
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DoctorCheck {
+    /// Stable identifier, e.g. `cli` or `mcp`.
+    pub(crate) id: &'static str,
+    pub(crate) label: &'static str,
+    pub(crate) status: CheckStatus,
+    pub(crate) detail: String,
+    /// What to do about a warning or failure.
+    pub(crate) fix: Option<String>,
+}
+
+impl DoctorCheck {
+    fn new(id: &'static str, label: &'static str, status: CheckStatus, detail: String) -> Self {
+        Self {
+            id,
+            label,
+            status,
+            detail,
+            fix: None,
+        }
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DoctorReport {
+    /// False when any check failed; warnings don't count.
+    pub(crate) ok: bool,
+    pub(crate) workspace_id: Option<String>,
+    pub(crate) checks: Vec<DoctorCheck>,
+}
+
+async fn check_cli(claude_bin: Option<String>) -> DoctorCheck {
+    let (id, label) = ("cli", "Claude CLI");
+    match check_claude_installation(claude_bin).await {
+        Ok(Some(version)) => DoctorCheck::new(id, label, CheckStatus::Pass, version),
+        Ok(None) => DoctorCheck::new(
+            id,
+            label,
+            CheckStatus::Warn,
+            "`claude --version` printed nothing.".to_string(),
+        )
+        .fix("Reinstall the CLI or pick another install in Settings."),
+        Err(err) => DoctorCheck::new(id, label, CheckStatus::Fail, err).fix(
+            "Install Claude Code (`npm install -g @anthropic-ai/claude-code`), or set the \
+             path of an existing install in Settings.",
+        ),
+    }
+}
+
+fn check_path(claude_bin: Option<&str>) -> DoctorCheck {
+    let (id, label) = ("path", "PATH resolution");
+    let dirs = cli_manager::search_dirs();
+    let configured = claude_bin.map(str::trim).filter(|bin| !bin.is_empty());
+    let Some(resolved) = cli_manager::resolve_bin(configured, &dirs) else {
+        let detail = match configured {
+            Some(bin) => format!("The configured CLI `{bin}` doesn't exist."),
+            None => "No `claude` on the PATH the app's processes get.".to_string(),
+        };
+        return DoctorCheck::new(id, label, CheckStatus::Fail, detail).fix(
+            "Set the CLI path in Settings to an existing install, or install the CLI where \
+             GUI apps can see it, e.g. /usr/local/bin.",
+        );
+    };
+    let mut on_path: Vec<PathBuf> = Vec::new();
+    for dir in &dirs {
+        for name in CLI_NAMES {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                let candidate = std::fs::canonicalize(&candidate).unwrap_or(candidate);
+                if !on_path.contains(&candidate) {
+                    on_path.push(candidate);
+                }
+            }
+        }
+    }
+    let detail = format!("Runs {}.", resolved.display());
+    if configured.is_none() && on_path.len() > 1 {
+        let others: Vec<String> = on_path
+            .iter()
+            .filter(|path| **path != resolved)
+            .map(|path| path.display().to_string())
+            .collect();
+        return DoctorCheck::new(
+            id,
+            label,
+            CheckStatus::Warn,
+            format!("{detail} Other installs on PATH: {}.", others.join(", ")),
+        )
+        .fix("Uninstall the ones you don't use, or pin an install in Settings.");
+    }
+    DoctorCheck::new(id, label, CheckStatus::Pass, detail)
+}
+
+fn check_claude_home(workspace_claude_dir: Option<&Path>) -> DoctorCheck {
+    let (id, label) = ("claudeHome", "Claude home");
+    let Some(home) = resolve_default_claude_home() else {
+        return DoctorCheck::new(
+            id,
+            label,
+            CheckStatus::Fail,
+            "Neither HOME nor CLAUDE_HOME is set.".to_string(),
+        )
+        .fix("Set HOME for the app, or CLAUDE_HOME to the CLI's config directory.");
+    };
+    if !home.is_dir() {
+        return DoctorCheck::new(
+            id,
+            label,
+            CheckStatus::Warn,
+            format!("{} doesn't exist yet.", home.display()),
+        )
+        .fix("Run `claude` once in a terminal to log in and create it.");
+    }
+    let mut detail = format!("Using {}.", home.display());
+    if let Some(dir) = workspace_claude_dir.filter(|dir| dir.is_dir()) {
+        detail.push_str(&format!(" Workspace settings in {}.", dir.display()));
+    }
+    DoctorCheck::new(id, label, CheckStatus::Pass, detail)
+}
+
+fn check_settings(workspace_claude_dir: Option<&Path>) -> DoctorCheck {
+    let (id, label) = ("settings", "Settings files");
+    let scopes: &[SettingsScope] = match workspace_claude_dir {
+        Some(_) => &[
+            SettingsScope::User,
+            SettingsScope::Project,
+            SettingsScope::Local,
+        ],
+        None => &[SettingsScope::User],
+    };
+    let claude_dir = workspace_claude_dir.unwrap_or(Path::new(""));
+    let mut paths: Vec<PathBuf> = scopes
+        .iter()
+        .filter_map(|scope| scope_path(*scope, claude_dir))
+        .collect();
+    paths.extend(resolve_home_dir().map(|home| home.join(".claude.json")));
+    let errors: Vec<String> = paths
+        .iter()
+        .filter_map(|path| read_settings_file(path).err())
+        .collect();
+    if errors.is_empty() {
+        let present = paths.iter().filter(|path| path.is_file()).count();
+        return DoctorCheck::new(
+            id,
+            label,
+            CheckStatus::Pass,
+            format!("{present} of {} files present, all valid.", paths.len()),
+        );
+    }
+    DoctorCheck::new(id, label, CheckStatus::Fail, errors.join(" "))
+        .fix("Fix the JSON in the file named above; the CLI ignores or rejects it as is.")
+}
+
+/// Whether a stdio server's command can be found, the way the CLI would look for it.
+fn command_exists(command: &str, dirs: &[PathBuf]) -> bool {
+    let path = Path::new(command);
+    if path.components().count() > 1 {
+        return path.is_file();
+    }
+    let extensions: &[&str] = if cfg!(windows) {
+        &["", ".exe", ".cmd", ".bat"]
+    } else {
+        &[""]
+    };
+    dirs.iter().any(|dir| {
+        extensions
+            .iter()
+            .any(|extension| dir.join(format!("{command}{extension}")).is_file())
+    })
+}
+
+async fn check_mcp_server(server: &McpServerConfig, entry: &WorkspaceEntry) -> Option<String> {
+    match (&server.command, &server.url) {
+        (Some(command), _) => {
+            let dirs = cli_manager::search_dirs();
+            (!command_exists(command, &dirs))
+                .then(|| format!("{}: `{command}` not found", server.name))
+        }
+        (None, Some(url)) => {
+            let settings = proxy::effective(entry.settings.proxy.as_ref());
+            match proxy::probe(url, &settings).await {
+                Ok(result) if result.ok => None,
+                Ok(result) => Some(format!(
+                    "{}: {}",
+                    server.name,
+                    result.error.unwrap_or_default()
+                )),
+                Err(err) => Some(format!("{}: {err}", server.name)),
+            }
+        }
+        (None, None) => Some(format!("{}: no command or URL", server.name)),
+    }
+}
+
+async fn check_mcp(
+    entry: Option<&WorkspaceEntry>,
+    dirs: Option<&(PathBuf, PathBuf)>,
+) -> DoctorCheck {
+    let (id, label) = ("mcp", "MCP servers");
+    let (Some(entry), Some((project_path, claude_dir))) = (entry, dirs) else {
+        return DoctorCheck::new(
+            id,
+            label,
+            CheckStatus::Skip,
+            "Pick a workspace to check its MCP servers.".to_string(),
+        );
+    };
+    let (project_path, claude_dir) = (project_path.clone(), claude_dir.clone());
+    let servers = tokio::task::spawn_blocking(move || {
+        list_configured_servers(&project_path, &claude_dir, resolve_home_dir().as_deref())
+    })
+    .await
+    .map_err(|err| err.to_string())
+    .and_then(|result| result);
+    let servers = match servers {
+        Ok(servers) => servers,
+        Err(err) => {
+            return DoctorCheck::new(id, label, CheckStatus::Fail, err)
+                .fix("Fix the MCP configuration file named above.");
+        }
+    };
+    let enabled: Vec<&McpServerConfig> = servers
+        .iter()
+        .filter(|server| server.approval != McpApproval::Disabled)
+        .collect();
+    if enabled.is_empty() {
+        return DoctorCheck::new(
+            id,
+            label,
+            CheckStatus::Pass,
+            "No MCP servers configured.".to_string(),
+        );
+    }
+    let problems: Vec<String> =
+        join_all(enabled.iter().map(|server| check_mcp_server(server, entry)))
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+    if problems.is_empty() {
+        return DoctorCheck::new(
+            id,
+            label,
+            CheckStatus::Pass,
+            format!("{} servers reachable.", enabled.len()),
+        );
+    }
+    DoctorCheck::new(
+        id,
+        label,
+        CheckStatus::Warn,
+        format!(
+            "{} of {} servers unreachable: {}.",
+            problems.len(),
+            enabled.len(),
+            problems.join("; ")
+        ),
+    )
+    .fix("Install the missing commands or start the servers; threads run without them.")
+}
+
+async fn check_git(entry: Option<&WorkspaceEntry>) -> DoctorCheck {
+    let (id, label) = ("git", "Git");
+    let mut command = Command::new("git");
+    if let Some(path_env) = build_claude_path_env(None) {
+        command.env("PATH", path_env);
+    }
+    command.arg("--version");
+    command.stdin(Stdio::null());
+    let version = match timeout(GIT_CHECK_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+        Ok(Ok(output)) => {
+            return DoctorCheck::new(
+                id,
+                label,
+                CheckStatus::Fail,
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            )
+            .fix("Reinstall git.");
+        }
+        Ok(Err(err)) => {
+            return DoctorCheck::new(id, label, CheckStatus::Fail, format!("git: {err}")).fix(
+                "Install git, e.g. with `xcode-select --install` on macOS or your package \
+                 manager on Linux.",
+            );
+        }
+        Err(_) => {
+            return DoctorCheck::new(
+                id,
+                label,
+                CheckStatus::Fail,
+                "`git --version` timed out.".to_string(),
+            )
+            .fix("Run `git --version` in a terminal to see what it waits for.");
+        }
+    };
+    let Some(entry) = entry else {
+        return DoctorCheck::new(id, label, CheckStatus::Pass, version);
+    };
+    let repo = resolve_git_root(entry).and_then(|root| {
+        git2::Repository::discover(&root)
+            .map(|_| ())
+            .map_err(|_| format!("{} is not in a git repository", root.display()))
+    });
+    match repo {
+        Ok(()) => DoctorCheck::new(id, label, CheckStatus::Pass, version),
+        Err(err) => DoctorCheck::new(id, label, CheckStatus::Warn, format!("{version}; {err}."))
+            .fix("Diffs, auto-commit and worktrees need a git repository; run `git init`."),
+    }
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+fn free_space(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: `statvfs` only writes to the struct it is given, and `path` is a valid
+    // NUL-terminated string for the duration of the call.
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+    Some(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+    const MB: f64 = 1024.0 * 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= GB {
+        format!("{:.1} GB", bytes / GB)
+    } else {
+        format!("{:.0} MB", bytes / MB)
+    }
+}
+
+fn check_disk_space() -> DoctorCheck {
+    let (id, label) = ("diskSpace", "Disk space for transcripts");
+    let Some(projects) = resolve_default_claude_home().map(|home| home.join("projects")) else {
+        return DoctorCheck::new(id, label, CheckStatus::Skip, "No Claude home.".to_string());
+    };
+    let existing = projects.ancestors().find(|path| path.exists());
+    let Some(free) = existing.and_then(free_space) else {
+        return DoctorCheck::new(
+            id,
+            label,
+            CheckStatus::Skip,
+            "Free space can't be read on this platform.".to_string(),
+        );
+    };
+    let detail = format!("{} free for {}.", format_bytes(free), projects.display());
+    let fix = "Free up space; the CLI stops writing transcripts when the disk is full.";
+    if free < MIN_FREE_BYTES {
+        DoctorCheck::new(id, label, CheckStatus::Fail, detail).fix(fix)
+    } else if free < LOW_FREE_BYTES {
+        DoctorCheck::new(id, label, CheckStatus::Warn, detail).fix(fix)
+    } else {
+        DoctorCheck::new(id, label, CheckStatus::Pass, detail)
+    }
+}
+
+async fn check_api(entry: Option<&WorkspaceEntry>) -> DoctorCheck {
+    let (id, label) = ("api", "API connectivity");
+    let endpoint = async {
+        let credentials = match entry {
+            Some(entry) => credentials::for_workspace(entry).await?,
+            None => None,
+        };
+        let settings = entry.and_then(|entry| entry.settings.provider.as_ref());
+        let env = provider_env(settings, credentials.as_ref())?;
+        provider_endpoint(env.as_deref()).map(|(_, endpoint)| endpoint)
+    };
+    let endpoint = match endpoint.await {
+        Ok(endpoint) => endpoint,
+        Err(err) => {
+            return DoctorCheck::new(id, label, CheckStatus::Fail, err)
+                .fix("Check the workspace's provider settings and credentials.");
+        }
+    };
+    let settings = proxy::effective(entry.and_then(|entry| entry.settings.proxy.as_ref()));
+    let result = match proxy::probe(&endpoint, &settings).await {
+        Ok(result) => result,
+        Err(err) => {
+            return DoctorCheck::new(id, label, CheckStatus::Fail, err)
+                .fix("Fix the proxy or CA certificate settings.");
+        }
+    };
+    let via = result
+        .proxy
+        .as_deref()
+        .map(|proxy| format!(" via {proxy}"))
+        .unwrap_or_default();
+    match (result.status, result.error) {
+        (Some(status), _) => DoctorCheck::new(
+            id,
+            label,
+            CheckStatus::Pass,
+            format!(
+                "{endpoint}{via} answered {status} in {} ms.",
+                result.latency_ms
+            ),
+        ),
+        (None, error) => DoctorCheck::new(
+            id,
+            label,
+            CheckStatus::Fail,
+            format!("{endpoint}{via}: {}", error.unwrap_or_default()),
+        )
+        .fix(
+            "Behind a corporate proxy, set it under Settings > Proxy, along with the CA \
+             certificate if it inspects TLS.",
+        ),
+    }
+}
+
+/// Check the environment Claude runs in and report what's wrong and how to fix it.
+#[tauri::command]
+pub(crate) async fn run_doctor(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "run_doctor",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+    let entry = match &workspace_id {
+        Some(workspace_id) => Some(
+            state
+                .workspaces
+                .lock()
+                .await
+                .get(workspace_id)
+                .cloned()
+                .ok_or("workspace not found")?,
+        ),
+        None => None,
+    };
+    let dirs = match &workspace_id {
+        Some(workspace_id) => Some(resolve_workspace_dirs(&state, workspace_id).await?),
+        None => None,
+    };
+    let claude_bin = match &entry {
+        Some(entry) => claude::workspace_claude_bin(&state, entry).await,
+        None => state.app_settings.lock().await.claude_bin.clone(),
+    };
+    let claude_dir = dirs.as_ref().map(|(_, claude_dir)| claude_dir.as_path());
+
+    let (cli, mcp, git, api) = tokio::join!(
+        check_cli(claude_bin.clone()),
+        check_mcp(entry.as_ref(), dirs.as_ref()),
+        check_git(entry.as_ref()),
+        check_api(entry.as_ref()),
+    );
+    let checks = vec![
+        cli,
+        check_path(claude_bin.as_deref()),
+        check_claude_home(claude_dir),
+        check_settings(claude_dir),
+        mcp,
+        git,
+        check_disk_space(),
+        api,
+    ];
+    let report = DoctorReport {
+        ok: checks.iter().all(|check| check.status != CheckStatus::Fail),
+        workspace_id,
+        checks,
+    };
+    serde_json::to_value(report).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_stdio_commands_on_the_search_path() {
+        let dir = std::env::temp_dir().join(format!("doctor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("mcp-server"), "").unwrap();
+        let dirs = vec![dir.clone()];
+
+        assert!(command_exists("mcp-server", &dirs));
+        assert!(command_exists(
+            &dir.join("mcp-server").to_string_lossy(),
+            &[]
+        ));
+        assert!(!command_exists("missing-server", &dirs));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reports_invalid_settings_files() {
+        let dir = std::env::temp_dir().join(format!("doctor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("settings.local.json"), "{ \"model\": ").unwrap();
+
+        let check = check_settings(Some(&dir));
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(
+            check.detail.contains("settings.local.json"),
+            "{}",
+            check.detail
+        );
+        assert!(check.fix.is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
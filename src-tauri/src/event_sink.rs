@@ -1,6 +1,91 @@
-use tauri::{AppHandle, Emitter};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
 
-use crate::backend::events::{AppServerEvent, EventSink, TerminalOutput};
+use serde_json::json;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::activity;
+use crate::backend::delta_batcher::DeltaBatcher;
+use crate::backend::events::{
+    AppServerEvent, BatchProgress, CliUpdateProgress, EventSink, PipelineProgress,
+    SessionDisconnected, TerminalExit, TerminalOutput, TurnQueueUpdated,
+};
+use crate::daemon;
+use crate::redaction;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::{ActivityEntry, EventStats};
+
+const DEFAULT_DELTA_BATCH_MS: u64 = 30;
+/// Longest interval accepted in settings; more would make streaming look stuck.
+pub(crate) const MAX_DELTA_BATCH_MS: u64 = 1000;
+
+static DELTA_BATCH_MS: AtomicU64 = AtomicU64::new(DEFAULT_DELTA_BATCH_MS);
+static EVENTS_EMITTED: AtomicU64 = AtomicU64::new(0);
+static EMIT_ERRORS: AtomicU64 = AtomicU64::new(0);
+static EVENTS_DROPPED: AtomicU64 = AtomicU64::new(0);
+static BATCHER: Mutex<DeltaBatcher> = Mutex::new(DeltaBatcher::new());
+/// Events the batcher released, in order, waiting to be emitted.
+static OUTGOING: Mutex<VecDeque<AppServerEvent>> = Mutex::new(VecDeque::new());
+static DELIVERING: AtomicBool = AtomicBool::new(false);
+
+fn batcher() -> MutexGuard<'static, DeltaBatcher> {
+    BATCHER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn outgoing() -> MutexGuard<'static, VecDeque<AppServerEvent>> {
+    OUTGOING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Apply the `deltaBatchMs` setting.
+pub(crate) fn configure_delta_batching(batch_ms: Option<u64>) {
+    let batch_ms = batch_ms
+        .unwrap_or(DEFAULT_DELTA_BATCH_MS)
+        .min(MAX_DELTA_BATCH_MS);
+    DELTA_BATCH_MS.store(batch_ms, Ordering::Relaxed);
+}
+
+/// Count events a daemon client missed because it fell behind.
+pub(crate) fn note_dropped_events(skipped: u64) {
+    EVENTS_DROPPED.fetch_add(skipped, Ordering::Relaxed);
+}
+
+fn event_stats() -> EventStats {
+    let batcher = batcher();
+    let counts = batcher.counts();
+    EventStats {
+        delta_batch_ms: DELTA_BATCH_MS.load(Ordering::Relaxed),
+        events_emitted: EVENTS_EMITTED.load(Ordering::Relaxed),
+        deltas_received: counts.deltas_received,
+        deltas_merged: counts.deltas_merged,
+        batches_merged: counts.batches_merged,
+        largest_batch: counts.largest_batch,
+        pending_deltas: batcher.pending_deltas(),
+        emit_errors: EMIT_ERRORS.load(Ordering::Relaxed),
+        events_dropped: EVENTS_DROPPED.load(Ordering::Relaxed),
+    }
+}
+
+/// How app-server events, streamed text in particular, reached the frontend.
+#[tauri::command]
+pub(crate) async fn get_event_stats(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<EventStats, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "get_event_stats", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(event_stats())
+}
 
 #[derive(Clone)]
 pub(crate) struct TauriEventSink {
@@ -11,14 +96,115 @@ impl TauriEventSink {
     pub(crate) fn new(app: AppHandle) -> Self {
         Self { app }
     }
+
+    pub(crate) fn app_handle(&self) -> &AppHandle {
+        &self.app
+    }
+
+    fn emit_activity(&self, entry: ActivityEntry) {
+        daemon::publish(&self.app, "activity", &entry);
+        let _ = self.app.emit("activity", entry);
+    }
+
+    /// Emit the events the batcher released. Whichever caller finds no delivery
+    /// running drains the queue, so events go out in the order they were released
+    /// without any lock held while they are emitted.
+    fn drain(&self) {
+        while !DELIVERING.swap(true, Ordering::Acquire) {
+            loop {
+                let next = outgoing().pop_front();
+                let Some(event) = next else {
+                    break;
+                };
+                self.deliver(event);
+            }
+            DELIVERING.store(false, Ordering::Release);
+            if outgoing().is_empty() {
+                return;
+            }
+        }
+    }
+
+    /// Redacted here rather than on the way in, so a secret split across merged
+    /// deltas is caught in the merged text.
+    fn deliver(&self, mut event: AppServerEvent) {
+        redaction::redact_value(&mut event.message);
+        let entry = activity::observe(&event);
+        daemon::publish(&self.app, "app-server-event", &event);
+        EVENTS_EMITTED.fetch_add(1, Ordering::Relaxed);
+        if self.app.emit("app-server-event", event).is_err() {
+            EMIT_ERRORS.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(entry) = entry {
+            self.emit_activity(entry);
+        }
+    }
+
+    /// Emit the streamed text gathered so far.
+    fn flush_deltas(&self) {
+        {
+            let mut batcher = batcher();
+            outgoing().extend(batcher.flush());
+        }
+        self.drain();
+    }
 }
 
 impl EventSink for TauriEventSink {
     fn emit_app_server_event(&self, event: AppServerEvent) {
-        let _ = self.app.emit("app-server-event", event);
+        let interval = Duration::from_millis(DELTA_BATCH_MS.load(Ordering::Relaxed));
+        let started_batch = {
+            // Queued under the lock so a flush cannot overtake, or fall behind, the
+            // events pushed after it.
+            let mut batcher = batcher();
+            let was_idle = batcher.is_idle();
+            outgoing().extend(batcher.push(event, interval));
+            was_idle && !batcher.is_idle()
+        };
+        if started_batch {
+            let sink = self.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(interval).await;
+                sink.flush_deltas();
+            });
+        }
+        self.drain();
     }
 
     fn emit_terminal_output(&self, event: TerminalOutput) {
+        daemon::publish(&self.app, "terminal-output", &event);
         let _ = self.app.emit("terminal-output", event);
     }
+
+    fn emit_terminal_exit(&self, event: TerminalExit) {
+        daemon::publish(&self.app, "terminal-exit", &event);
+        let _ = self.app.emit("terminal-exit", event);
+    }
+
+    fn emit_session_disconnected(&self, event: SessionDisconnected) {
+        let entry = activity::observe_disconnect(&event);
+        daemon::publish(&self.app, "session-disconnected", &event);
+        let _ = self.app.emit("session-disconnected", event);
+        self.emit_activity(entry);
+    }
+
+    fn emit_turn_queue_updated(&self, event: TurnQueueUpdated) {
+        daemon::publish(&self.app, "turn-queue-updated", &event);
+        let _ = self.app.emit("turn-queue-updated", event);
+    }
+
+    fn emit_batch_progress(&self, event: BatchProgress) {
+        daemon::publish(&self.app, "batch-progress", &event);
+        let _ = self.app.emit("batch-progress", event);
+    }
+
+    fn emit_pipeline_progress(&self, event: PipelineProgress) {
+        daemon::publish(&self.app, "pipeline-progress", &event);
+        let _ = self.app.emit("pipeline-progress", event);
+    }
+
+    fn emit_cli_update_progress(&self, event: CliUpdateProgress) {
+        daemon::publish(&self.app, "cli-update-progress", &event);
+        let _ = self.app.emit("cli-update-progress", event);
+    }
 }
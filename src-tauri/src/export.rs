@@ -0,0 +1,733 @@
+//! Export a Claude session transcript for sharing.
+//!
+//! The CLI's JSONL is first normalized into messages of text and tool calls, with each
+//! `tool_result` folded into the call it answers. That form is written out as JSON,
+//! as Markdown with every tool call in a collapsible `<details>` block, or as a
+//! standalone HTML page. Thinking blocks are only kept in the JSON export. Annotations
+//! are placed under the message they were made on; those on whole turns are listed
+//! after the header.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+
+use crate::backend::stream_events::{ContentBlock, MessageContent};
+use crate::backend::tool_timeline::{output_text, summarize_input};
+use crate::claude::resolve_session_path;
+use crate::operations;
+use crate::redaction;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+use crate::types::{Annotation, AnnotationTarget};
+
+const MAX_TOOL_OUTPUT_CHARS: usize = 4000;
+const TITLE_MAX_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ExportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl ExportFormat {
+    fn render(self, session: &ExportedSession) -> Result<String, String> {
+        match self {
+            Self::Markdown => Ok(render_markdown(session)),
+            Self::Html => Ok(render_html(session)),
+            Self::Json => serde_json::to_string_pretty(session).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Role {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum ExportedBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolCall {
+        id: String,
+        name: String,
+        input: Value,
+        output: Option<String>,
+        is_error: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExportedMessage {
+    pub(crate) role: Role,
+    pub(crate) timestamp: Option<i64>,
+    pub(crate) blocks: Vec<ExportedBlock>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) annotations: Vec<Annotation>,
+    /// Uuids of the transcript lines the message was made from.
+    #[serde(skip)]
+    pub(crate) line_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExportedSession {
+    pub(crate) session_id: String,
+    pub(crate) title: String,
+    pub(crate) started_at: Option<i64>,
+    pub(crate) updated_at: Option<i64>,
+    pub(crate) messages: Vec<ExportedMessage>,
+    /// Annotations on turns, and on messages the export does not contain.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) annotations: Vec<Annotation>,
+}
+
+/// The parts of a transcript line an export needs.
+#[derive(Debug, Default, Deserialize)]
+struct TranscriptLine {
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    uuid: Option<String>,
+    #[serde(default)]
+    timestamp: Option<String>,
+    #[serde(default, rename = "isMeta")]
+    is_meta: bool,
+    #[serde(default, rename = "isSidechain")]
+    is_sidechain: bool,
+    #[serde(default)]
+    message: Option<TranscriptMessage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TranscriptMessage {
+    #[serde(default)]
+    content: MessageContent,
+}
+
+/// Normalize a transcript. Consecutive assistant lines (the CLI writes one per content
+/// block) become one message, and user lines carrying only tool results disappear into
+/// the calls they answer.
+pub(crate) fn load_session(path: &Path, session_id: &str) -> Result<ExportedSession, String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    let mut messages: Vec<ExportedMessage> = Vec::new();
+    // Tool use id -> (message index, block index)
+    let mut calls: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut started_at = None;
+    let mut updated_at = None;
+
+    for line in BufReader::new(file).lines() {
+        let Ok(line) = line else {
+            continue;
+        };
+        let Ok(parsed) = serde_json::from_str::<TranscriptLine>(&line) else {
+            continue;
+        };
+        let role = match parsed.kind.as_str() {
+            "user" => Role::User,
+            "assistant" => Role::Assistant,
+            _ => continue,
+        };
+        if parsed.is_meta || parsed.is_sidechain {
+            continue;
+        }
+        let timestamp = parsed
+            .timestamp
+            .as_deref()
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .map(|value| value.timestamp_millis());
+        if timestamp.is_some() {
+            started_at = started_at.or(timestamp);
+            updated_at = timestamp;
+        }
+        let content = parsed
+            .message
+            .map(|message| message.content)
+            .unwrap_or_default();
+
+        let mut blocks = Vec::new();
+        if let MessageContent::Text(text) = &content {
+            push_text(&mut blocks, text);
+        }
+        for block in content.blocks() {
+            match block {
+                ContentBlock::Text { text } => push_text(&mut blocks, text),
+                ContentBlock::Thinking { thinking } if !thinking.trim().is_empty() => {
+                    blocks.push(ExportedBlock::Thinking {
+                        text: thinking.trim().to_string(),
+                    });
+                }
+                ContentBlock::ToolUse(tool_use) => blocks.push(ExportedBlock::ToolCall {
+                    id: tool_use.id.clone(),
+                    name: tool_use.name.clone(),
+                    input: tool_use.input.clone(),
+                    output: None,
+                    is_error: false,
+                }),
+                ContentBlock::ToolResult(result) => {
+                    let Some(&(message, index)) = calls.get(&result.tool_use_id) else {
+                        continue;
+                    };
+                    if let Some(ExportedBlock::ToolCall {
+                        output, is_error, ..
+                    }) = messages[message].blocks.get_mut(index)
+                    {
+                        *output = Some(output_text(&result.content));
+                        *is_error = result.is_error;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if blocks.is_empty() {
+            continue;
+        }
+
+        let merge = role == Role::Assistant
+            && messages
+                .last()
+                .is_some_and(|last| last.role == Role::Assistant);
+        if !merge {
+            messages.push(ExportedMessage {
+                role,
+                timestamp,
+                blocks: Vec::new(),
+                annotations: Vec::new(),
+                line_ids: Vec::new(),
+            });
+        }
+        let message_index = messages.len() - 1;
+        let message = &mut messages[message_index];
+        message.line_ids.extend(parsed.uuid);
+        for block in blocks {
+            if let ExportedBlock::ToolCall { id, .. } = &block {
+                calls.insert(id.clone(), (message_index, message.blocks.len()));
+            }
+            message.blocks.push(block);
+        }
+    }
+
+    let title = messages
+        .iter()
+        .filter(|message| message.role == Role::User)
+        .flat_map(|message| &message.blocks)
+        .find_map(|block| match block {
+            ExportedBlock::Text { text } => text.lines().next().map(str::trim),
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .map(|line| truncate(line, TITLE_MAX_CHARS).0)
+        .unwrap_or_else(|| "Claude session".to_string());
+    Ok(ExportedSession {
+        session_id: session_id.to_string(),
+        title,
+        started_at,
+        updated_at,
+        messages,
+        annotations: Vec::new(),
+    })
+}
+
+/// Place each annotation under the message it was made on: one built from the annotated
+/// transcript line, or holding the annotated tool call.
+pub(crate) fn attach_annotations(session: &mut ExportedSession, annotations: Vec<Annotation>) {
+    for annotation in annotations {
+        let message = match annotation.target {
+            AnnotationTarget::Message => session.messages.iter_mut().find(|message| {
+                message.line_ids.contains(&annotation.target_id)
+                    || message.blocks.iter().any(|block| {
+                        matches!(block, ExportedBlock::ToolCall { id, .. } if *id == annotation.target_id)
+                    })
+            }),
+            AnnotationTarget::Turn => None,
+        };
+        match message {
+            Some(message) => message.annotations.push(annotation),
+            None => session.annotations.push(annotation),
+        }
+    }
+}
+
+fn push_text(blocks: &mut Vec<ExportedBlock>, text: &str) {
+    let text = text.trim();
+    if !text.is_empty() {
+        blocks.push(ExportedBlock::Text {
+            text: text.to_string(),
+        });
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> (String, bool) {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => (format!("{}…", &text[..end]), true),
+        None => (text.to_string(), false),
+    }
+}
+
+fn role_label(role: Role) -> &'static str {
+    match role {
+        Role::User => "User",
+        Role::Assistant => "Claude",
+    }
+}
+
+fn format_time(timestamp_ms: i64) -> String {
+    Local
+        .timestamp_millis_opt(timestamp_ms)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// One line describing a tool call, such as `Bash: cargo test`.
+fn tool_summary(name: &str, input: &Value) -> String {
+    let summary = summarize_input(name, input);
+    if summary.is_empty() {
+        name.to_string()
+    } else {
+        format!("{name}: {summary}")
+    }
+}
+
+/// Stars and bookmark name of an annotation, such as `★★★★☆ · Bookmark: flaky test`.
+fn annotation_heading(annotation: &Annotation) -> String {
+    let mut parts = Vec::new();
+    if let Some(rating) = annotation.rating {
+        let rating = usize::from(rating.min(5));
+        parts.push(format!("{}{}", "★".repeat(rating), "☆".repeat(5 - rating)));
+    }
+    if let Some(bookmark) = &annotation.bookmark {
+        parts.push(format!("Bookmark: {bookmark}"));
+    }
+    if parts.is_empty() {
+        "Note".to_string()
+    } else {
+        parts.join(" · ")
+    }
+}
+
+fn push_markdown_annotation(out: &mut String, annotation: &Annotation) {
+    out.push_str(&format!("> **{}**\n", annotation_heading(annotation)));
+    if let Some(note) = &annotation.note {
+        out.push_str(">\n");
+        for line in note.lines() {
+            if line.trim().is_empty() {
+                out.push_str(">\n");
+            } else {
+                out.push_str(&format!("> {line}\n"));
+            }
+        }
+    }
+    out.push('\n');
+}
+
+fn push_html_annotation(out: &mut String, annotation: &Annotation) {
+    out.push_str(&format!(
+        "<aside class=\"annotation\">\n<strong>{}</strong>\n",
+        escape_html(&annotation_heading(annotation))
+    ));
+    if let Some(note) = &annotation.note {
+        out.push_str(&format!(
+            "<div class=\"text\">{}</div>\n",
+            escape_html(note)
+        ));
+    }
+    out.push_str("</aside>\n");
+}
+
+fn pretty_input(input: &Value) -> String {
+    serde_json::to_string_pretty(input).unwrap_or_else(|_| input.to_string())
+}
+
+/// A code fence longer than any backtick run inside `text`, so the block cannot end
+/// early.
+fn fence(text: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for ch in text.chars() {
+        if ch == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn push_code_block(out: &mut String, language: &str, text: &str) {
+    let fence = fence(text);
+    out.push_str(&format!("{fence}{language}\n{text}\n{fence}\n"));
+}
+
+pub(crate) fn render_markdown(session: &ExportedSession) -> String {
+    let mut out = format!("# {}\n\n", session.title);
+    out.push_str(&format!("Session `{}`", session.session_id));
+    if let Some(started_at) = session.started_at {
+        out.push_str(&format!(", started {}", format_time(started_at)));
+    }
+    out.push_str("\n\n");
+    for annotation in &session.annotations {
+        push_markdown_annotation(&mut out, annotation);
+    }
+
+    for message in &session.messages {
+        out.push_str(&format!("## {}\n\n", role_label(message.role)));
+        for block in &message.blocks {
+            match block {
+                ExportedBlock::Text { text } => {
+                    out.push_str(text);
+                    out.push_str("\n\n");
+                }
+                ExportedBlock::Thinking { .. } => {}
+                ExportedBlock::ToolCall {
+                    name,
+                    input,
+                    output,
+                    is_error,
+                    ..
+                } => {
+                    let status = if *is_error { " (failed)" } else { "" };
+                    out.push_str(&format!(
+                        "<details>\n<summary>{}{status}</summary>\n\n",
+                        escape_html(&tool_summary(name, input))
+                    ));
+                    push_code_block(&mut out, "json", &pretty_input(input));
+                    if let Some(output) = output.as_deref().filter(|text| !text.is_empty()) {
+                        out.push('\n');
+                        let (output, truncated) = truncate(output, MAX_TOOL_OUTPUT_CHARS);
+                        push_code_block(&mut out, "", &output);
+                        if truncated {
+                            out.push_str("\n_Output truncated._\n");
+                        }
+                    }
+                    out.push_str("\n</details>\n\n");
+                }
+            }
+        }
+        for annotation in &message.annotations {
+            push_markdown_annotation(&mut out, annotation);
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = "body{font:15px/1.5 -apple-system,BlinkMacSystemFont,\"Segoe UI\",sans-serif;max-width:860px;margin:2rem auto;padding:0 1rem;color:#1f2328}
+header p{color:#59636e}
+section{border-top:1px solid #d1d9e0;padding:.75rem 0}
+h2{font-size:.85rem;text-transform:uppercase;letter-spacing:.04em;color:#59636e;margin:.25rem 0}
+section.user h2{color:#0969da}
+.text{white-space:pre-wrap;margin:.5rem 0}
+details{margin:.5rem 0;border:1px solid #d1d9e0;border-radius:6px;padding:.25rem .75rem}
+details.failed{border-color:#cf222e}
+summary{cursor:pointer;font-family:ui-monospace,SFMono-Regular,Menlo,monospace;font-size:.85rem}
+pre{background:#f6f8fa;border-radius:6px;padding:.75rem;overflow:auto;font-size:.8rem}
+aside.annotation{border-left:3px solid #bf8700;background:#fff8c5;padding:.25rem .75rem;margin:.5rem 0}";
+
+pub(crate) fn render_html(session: &ExportedSession) -> String {
+    let title = escape_html(&session.title);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n\
+         <header>\n<h1>{title}</h1>\n<p>Session <code>{}</code>{}</p>\n</header>\n",
+        escape_html(&session.session_id),
+        session
+            .started_at
+            .map(|started_at| format!(", started {}", format_time(started_at)))
+            .unwrap_or_default(),
+    );
+    for annotation in &session.annotations {
+        push_html_annotation(&mut out, annotation);
+    }
+    for message in &session.messages {
+        let class = match message.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        out.push_str(&format!(
+            "<section class=\"{class}\">\n<h2>{}</h2>\n",
+            role_label(message.role)
+        ));
+        for block in &message.blocks {
+            match block {
+                ExportedBlock::Text { text } => {
+                    out.push_str(&format!(
+                        "<div class=\"text\">{}</div>\n",
+                        escape_html(text)
+                    ));
+                }
+                ExportedBlock::Thinking { .. } => {}
+                ExportedBlock::ToolCall {
+                    name,
+                    input,
+                    output,
+                    is_error,
+                    ..
+                } => {
+                    let class = if *is_error { " class=\"failed\"" } else { "" };
+                    out.push_str(&format!(
+                        "<details{class}>\n<summary>{}</summary>\n<pre>{}</pre>\n",
+                        escape_html(&tool_summary(name, input)),
+                        escape_html(&pretty_input(input))
+                    ));
+                    if let Some(output) = output.as_deref().filter(|text| !text.is_empty()) {
+                        let (output, _) = truncate(output, MAX_TOOL_OUTPUT_CHARS);
+                        out.push_str(&format!("<pre>{}</pre>\n", escape_html(&output)));
+                    }
+                    out.push_str("</details>\n");
+                }
+            }
+        }
+        for annotation in &message.annotations {
+            push_html_annotation(&mut out, annotation);
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Export a session as Markdown, HTML or JSON. With a `path` the export is written there;
+/// without one it is returned as `content`. Cancelling `operation_id` before the file is
+/// written leaves no file behind.
+#[tauri::command]
+pub(crate) async fn export_session(
+    workspace_id: String,
+    session_id: String,
+    format: ExportFormat,
+    path: Option<String>,
+    operation_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    let path = path.filter(|path| !path.trim().is_empty());
+    if remote_backend::is_remote_mode(&*state).await {
+        // The daemon renders; the file is written on this machine
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "export_session",
+            json!({
+                "workspaceId": workspace_id,
+                "sessionId": session_id,
+                "format": format,
+                "operationId": operation_id,
+            }),
+        )
+        .await?;
+        let Some(path) = path else {
+            return Ok(response);
+        };
+        let content = response
+            .get("content")
+            .and_then(Value::as_str)
+            .ok_or("Remote export returned no content.")?;
+        fs::write(&path, content).map_err(|err| err.to_string())?;
+        return Ok(json!({ "path": path, "bytes": content.len() }));
+    }
+
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let operation = operations::start(operation_id, "export_session", Some(&workspace_id))?;
+    let token = operation.token().clone();
+    let db_path = state.db_path.clone();
+    let export = tokio::task::spawn_blocking(move || {
+        let session_path =
+            resolve_session_path(&entry, &session_id).ok_or("Session file not found")?;
+        let mut session = load_session(&session_path, &session_id)?;
+        if token.is_cancelled() {
+            return Err(operations::CANCELLED.to_string());
+        }
+        let mut annotations =
+            storage::read_annotations(&db_path, Some(&workspace_id), Some(&session_id))?;
+        annotations.sort_by_key(|annotation| annotation.created_at);
+        attach_annotations(&mut session, annotations);
+        let content = format.render(&session)?;
+        let content = redaction::redact_text(&content).into_owned();
+        if token.is_cancelled() {
+            return Err(operations::CANCELLED.to_string());
+        }
+        match path {
+            Some(path) => {
+                fs::write(&path, &content).map_err(|err| err.to_string())?;
+                Ok(json!({ "path": path, "bytes": content.len() }))
+            }
+            None => Ok(json!({ "content": content })),
+        }
+    });
+    operation
+        .run(async { export.await.map_err(|err| err.to_string())? })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn write_transcript(lines: &[Value]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("claude-export-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        let path = dir.join("session.jsonl");
+        let body: Vec<String> = lines.iter().map(Value::to_string).collect();
+        fs::write(&path, body.join("\n")).expect("write transcript");
+        path
+    }
+
+    fn sample_session() -> ExportedSession {
+        let path = write_transcript(&[
+            json!({ "type": "summary", "summary": "Fix tests" }),
+            json!({
+                "type": "user",
+                "timestamp": "2026-01-05T10:00:00Z",
+                "message": { "content": "Fix the failing test\nin retry.rs" }
+            }),
+            json!({
+                "type": "assistant",
+                "message": { "content": [{ "type": "thinking", "thinking": "Run it first." }] }
+            }),
+            json!({
+                "type": "assistant",
+                "message": { "content": [{
+                    "type": "tool_use",
+                    "id": "tool-1",
+                    "name": "Bash",
+                    "input": { "command": "cargo test retry" }
+                }] }
+            }),
+            json!({
+                "type": "user",
+                "message": { "content": [{
+                    "type": "tool_result",
+                    "tool_use_id": "tool-1",
+                    "content": "```\n1 failed <jitter>\n```",
+                    "is_error": true
+                }] }
+            }),
+            json!({ "type": "user", "isMeta": true, "message": { "content": "Caveat" } }),
+            json!({
+                "type": "assistant",
+                "timestamp": "2026-01-05T10:02:00Z",
+                "message": { "content": [{ "type": "text", "text": "Fixed the jitter bound." }] }
+            }),
+        ]);
+        load_session(&path, "session-1").expect("load session")
+    }
+
+    #[test]
+    fn normalizes_transcript_lines_into_messages() {
+        let session = sample_session();
+        assert_eq!(session.title, "Fix the failing test");
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.messages[0].role, Role::User);
+        // Thinking, the tool call with its result and the reply form one assistant message
+        assert_eq!(session.messages[1].blocks.len(), 3);
+        assert_eq!(
+            session.messages[1].blocks[1],
+            ExportedBlock::ToolCall {
+                id: "tool-1".to_string(),
+                name: "Bash".to_string(),
+                input: json!({ "command": "cargo test retry" }),
+                output: Some("```\n1 failed <jitter>\n```".to_string()),
+                is_error: true,
+            }
+        );
+        assert_eq!(session.updated_at, Some(1_767_607_320_000));
+    }
+
+    #[test]
+    fn renders_markdown_and_html() {
+        let session = sample_session();
+        let markdown = render_markdown(&session);
+        assert!(markdown.starts_with("# Fix the failing test\n"));
+        assert!(markdown.contains("<summary>Bash: cargo test retry (failed)</summary>"));
+        // Output containing a fence is wrapped in a longer one
+        assert!(markdown.contains("````\n```\n1 failed <jitter>\n```\n````"));
+        assert!(!markdown.contains("Run it first."));
+
+        let html = render_html(&session);
+        assert!(html.contains("<details class=\"failed\">"));
+        assert!(html.contains("1 failed &lt;jitter&gt;"));
+        assert!(html.contains("<div class=\"text\">Fixed the jitter bound.</div>"));
+
+        let json = ExportFormat::Json.render(&session).unwrap();
+        assert!(json.contains("\"type\": \"thinking\""));
+    }
+
+    #[test]
+    fn places_annotations_under_their_messages() {
+        let annotation = |target, target_id: &str| Annotation {
+            workspace_id: "ws-1".to_string(),
+            thread_id: "session-1".to_string(),
+            target,
+            target_id: target_id.to_string(),
+            note: Some("Ran the right test.\n\nKeep this.".to_string()),
+            rating: Some(4),
+            bookmark: Some("retry fix".to_string()),
+            created_at: 1,
+            updated_at: 1,
+        };
+        let mut session = sample_session();
+        attach_annotations(
+            &mut session,
+            vec![
+                annotation(AnnotationTarget::Message, "tool-1"),
+                annotation(AnnotationTarget::Turn, "turn-1"),
+            ],
+        );
+        assert!(session.messages[0].annotations.is_empty());
+        assert_eq!(session.messages[1].annotations.len(), 1);
+        assert_eq!(session.annotations.len(), 1);
+
+        let markdown = render_markdown(&session);
+        assert!(markdown.contains(
+            "> **★★★★☆ · Bookmark: retry fix**\n>\n> Ran the right test.\n>\n> Keep this.\n"
+        ));
+        let html = render_html(&session);
+        assert!(html.contains(
+            "<aside class=\"annotation\">\n<strong>★★★★☆ · Bookmark: retry fix</strong>"
+        ));
+        let json = ExportFormat::Json.render(&session).unwrap();
+        assert!(json.contains("\"bookmark\": \"retry fix\""));
+    }
+}
@@ -0,0 +1,321 @@
+//! Per-workspace index of files and directories for `@path` completion in the composer.
+//!
+//! Like the CLI's own `@` completion, it follows `.gitignore` (and `.ignore`) rules,
+//! includes hidden files, and offers directories as well as files. Indexes are built on a
+//! blocking thread when a workspace connects or is first completed in, and rebuilt in the
+//! background once they are `REFRESH_AFTER` old; queries meanwhile use the older one.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::utils::normalize_git_path;
+
+const REFRESH_AFTER: Duration = Duration::from_secs(30);
+/// Entries beyond this are left out of the index.
+const MAX_ENTRIES: usize = 200_000;
+const DEFAULT_LIMIT: usize = 50;
+
+/// One completion for an `@` mention; directories end in `/`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PathCompletion {
+    pub(crate) path: String,
+    pub(crate) is_dir: bool,
+}
+
+#[derive(Debug, Default)]
+struct FileIndex {
+    root: PathBuf,
+    /// Workspace-relative, `/`-separated, directories with a trailing `/`.
+    entries: Vec<PathCompletion>,
+    built_at: Option<Instant>,
+    building: bool,
+}
+
+static INDEXES: OnceLock<Mutex<HashMap<String, FileIndex>>> = OnceLock::new();
+
+fn with_indexes<T>(f: impl FnOnce(&mut HashMap<String, FileIndex>) -> T) -> T {
+    let mut guard = INDEXES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut guard)
+}
+
+/// Start indexing a workspace in the background, unless its index is fresh.
+pub(crate) fn warm(workspace_id: &str, root: &Path) {
+    if claim_rebuild(workspace_id, root) {
+        let workspace_id = workspace_id.to_string();
+        let root = root.to_path_buf();
+        tauri::async_runtime::spawn(async move { rebuild(workspace_id, root).await });
+    }
+}
+
+/// Drop a workspace's index, e.g. when it is removed.
+pub(crate) fn forget(workspace_id: &str) {
+    with_indexes(|indexes| indexes.remove(workspace_id));
+}
+
+/// Whether the caller should rebuild the index: it is missing, stale or for another
+/// root, and no rebuild is running.
+fn claim_rebuild(workspace_id: &str, root: &Path) -> bool {
+    with_indexes(|indexes| {
+        let index = indexes.entry(workspace_id.to_string()).or_default();
+        if index.root != root {
+            *index = FileIndex {
+                root: root.to_path_buf(),
+                ..FileIndex::default()
+            };
+        }
+        let fresh = index
+            .built_at
+            .is_some_and(|built_at| built_at.elapsed() < REFRESH_AFTER);
+        if fresh || index.building {
+            return false;
+        }
+        index.building = true;
+        true
+    })
+}
+
+async fn rebuild(workspace_id: String, root: PathBuf) {
+    let walk_root = root.clone();
+    let entries = tauri::async_runtime::spawn_blocking(move || index_entries(&walk_root))
+        .await
+        .unwrap_or_else(|err| {
+            tracing::warn!("indexing {} failed: {err}", root.display());
+            Vec::new()
+        });
+    with_indexes(|indexes| {
+        if let Some(index) = indexes
+            .get_mut(&workspace_id)
+            .filter(|index| index.root == root)
+        {
+            index.entries = entries;
+            index.built_at = Some(Instant::now());
+            index.building = false;
+        }
+    });
+}
+
+fn index_entries(root: &Path) -> Vec<PathCompletion> {
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .follow_links(false)
+        // Ignore rules apply in folders that are not git repositories too, as in the CLI
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    let mut entries = Vec::new();
+    for entry in walker.flatten() {
+        if entry.depth() == 0 {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let is_dir = entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_dir());
+        let mut path = normalize_git_path(&relative.to_string_lossy());
+        if is_dir {
+            path.push('/');
+        }
+        entries.push(PathCompletion { path, is_dir });
+        if entries.len() >= MAX_ENTRIES {
+            tracing::info!(
+                "stopped indexing {} at {MAX_ENTRIES} entries",
+                root.display()
+            );
+            break;
+        }
+    }
+    entries
+}
+
+/// Rank `entries` for what was typed after `@`: paths starting with it first, then
+/// those whose name starts with it, then those containing it, ignoring case. Shallower
+/// and shorter paths come first within each. With nothing typed, the top level is
+/// offered.
+fn complete(entries: &[PathCompletion], prefix: &str, limit: usize) -> Vec<PathCompletion> {
+    let prefix = prefix.trim().trim_start_matches("./").replace('\\', "/");
+    let needle = prefix.to_lowercase();
+    let mut ranked: Vec<(u8, usize, usize, &PathCompletion)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let path = entry.path.to_lowercase();
+            let trimmed = path.trim_end_matches('/');
+            let depth = trimmed.matches('/').count();
+            let name = trimmed.rsplit('/').next().unwrap_or(trimmed);
+            let tier = if needle.is_empty() {
+                (depth == 0).then_some(0)?
+            } else if path.starts_with(&needle) {
+                // Typing a directory offers what is in it, not everything below it
+                let rest = path[needle.len()..].trim_end_matches('/');
+                if needle.ends_with('/') && (rest.is_empty() || rest.contains('/')) {
+                    return None;
+                }
+                0
+            } else if name.starts_with(&needle) {
+                1
+            } else if path.contains(&needle) {
+                2
+            } else {
+                return None;
+            };
+            Some((tier, depth, entry.path.len(), entry))
+        })
+        .collect();
+    ranked.sort_by_key(|&(tier, depth, len, entry)| {
+        (tier, depth, len, Reverse(entry.is_dir), entry.path.as_str())
+    });
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, _, _, entry)| entry.clone())
+        .collect()
+}
+
+/// Paths in the workspace matching what was typed after `@`, best first.
+#[tauri::command]
+pub(crate) async fn complete_paths(
+    workspace_id: String,
+    prefix: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<PathCompletion>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "complete_paths",
+            json!({ "workspaceId": workspace_id, "prefix": prefix, "limit": limit }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let root = state
+        .workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .map(|entry| PathBuf::from(&entry.path))
+        .ok_or("workspace not found")?;
+    if claim_rebuild(&workspace_id, &root) {
+        let never_built = with_indexes(|indexes| {
+            indexes
+                .get(&workspace_id)
+                .is_none_or(|index| index.built_at.is_none())
+        });
+        if never_built {
+            rebuild(workspace_id.clone(), root.clone()).await;
+        } else {
+            let workspace_id = workspace_id.clone();
+            let root = root.clone();
+            tauri::async_runtime::spawn(async move { rebuild(workspace_id, root).await });
+        }
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    Ok(with_indexes(|indexes| {
+        indexes
+            .get(&workspace_id)
+            .map(|index| complete(&index.entries, &prefix, limit))
+            .unwrap_or_default()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str) -> PathCompletion {
+        PathCompletion {
+            path: path.to_string(),
+            is_dir: path.ends_with('/'),
+        }
+    }
+
+    fn paths(completions: Vec<PathCompletion>) -> Vec<String> {
+        completions.into_iter().map(|entry| entry.path).collect()
+    }
+
+    #[test]
+    fn indexes_what_git_would_track() {
+        let root = std::env::temp_dir().join(format!("file-index-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src/nested")).unwrap();
+        std::fs::create_dir_all(root.join("target/debug")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        std::fs::write(root.join(".env.example"), "").unwrap();
+        std::fs::write(root.join("build.log"), "").unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        std::fs::write(root.join("src/nested/mod.rs"), "").unwrap();
+        std::fs::write(root.join("target/debug/app"), "").unwrap();
+        std::fs::write(root.join(".git/HEAD"), "").unwrap();
+
+        let mut indexed = paths(index_entries(&root));
+        indexed.sort();
+        assert_eq!(
+            indexed,
+            [
+                ".env.example",
+                ".gitignore",
+                "src/",
+                "src/main.rs",
+                "src/nested/",
+                "src/nested/mod.rs"
+            ]
+        );
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn ranks_like_the_cli() {
+        let entries = [
+            "README.md",
+            "src/",
+            "src/main.rs",
+            "src/components/",
+            "src/components/Main.tsx",
+            "src/components/Sidebar.tsx",
+            "docs/main-flow.md",
+        ]
+        .map(entry);
+
+        assert_eq!(paths(complete(&entries, "", 10)), ["src/", "README.md"]);
+        assert_eq!(
+            paths(complete(&entries, "src/", 10)),
+            ["src/main.rs", "src/components/"]
+        );
+        assert_eq!(
+            paths(complete(&entries, "main", 10)),
+            [
+                "src/main.rs",
+                "docs/main-flow.md",
+                "src/components/Main.tsx"
+            ]
+        );
+        assert_eq!(
+            paths(complete(&entries, "./src/comp", 10)),
+            [
+                "src/components/",
+                "src/components/Main.tsx",
+                "src/components/Sidebar.tsx"
+            ]
+        );
+        assert_eq!(complete(&entries, "bar", 1).len(), 1);
+    }
+}
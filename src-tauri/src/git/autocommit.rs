@@ -0,0 +1,189 @@
+use std::path::Path;
+
+use git2::{IndexAddOption, Oid, Repository, Signature};
+
+use crate::types::AutoCommitPolicy;
+
+pub(crate) const DEFAULT_BRANCH: &str = "monitor/autocommit";
+const SUBJECT_MAX_CHARS: usize = 72;
+const BODY_MAX_LINES: usize = 20;
+
+/// The branch to commit to, or `None` when the policy is off.
+pub(crate) fn target_branch(policy: Option<&AutoCommitPolicy>) -> Option<String> {
+    let policy = policy.filter(|policy| policy.enabled)?;
+    let branch = policy
+        .branch
+        .as_deref()
+        .map(str::trim)
+        .filter(|branch| !branch.is_empty())
+        .unwrap_or(DEFAULT_BRANCH);
+    Some(branch.to_string())
+}
+
+/// Build a commit message from the turn's final reply: its first line as the subject,
+/// the next lines as the body, and trailers pointing back at the turn.
+pub(crate) fn commit_message(summary: &str, thread_id: &str, turn_id: &str) -> String {
+    let mut lines = summary
+        .lines()
+        .map(str::trim_end)
+        .skip_while(|line| line.trim().is_empty());
+    let subject = lines
+        .next()
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.char_indices().nth(SUBJECT_MAX_CHARS) {
+            Some((end, _)) => format!("{}…", &line[..end]),
+            None => line.to_string(),
+        })
+        .unwrap_or_else(|| format!("Claude turn {turn_id}"));
+    let body: Vec<&str> = lines.take(BODY_MAX_LINES).collect();
+    let body = body.join("\n");
+    let body = body.trim();
+
+    let mut message = subject;
+    if !body.is_empty() {
+        message.push_str("\n\n");
+        message.push_str(body);
+    }
+    message.push_str(&format!("\n\nThread: {thread_id}\nTurn: {turn_id}\n"));
+    message
+}
+
+/// Commit everything in the working tree, untracked files included, onto `branch`.
+///
+/// The tree is built from the index as loaded in memory and never written back, so
+/// `HEAD`, `.git/index` and the checkout are left alone. A new branch starts from
+/// `HEAD`. Returns `None` when the working tree matches the branch's last commit.
+pub(crate) fn commit_worktree(
+    repo_root: &Path,
+    branch: &str,
+    message: &str,
+) -> Result<Option<Oid>, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let refname = format!("refs/heads/{branch}");
+    if !git2::Reference::is_valid_name(&refname) {
+        return Err(format!("Invalid auto-commit branch: {branch}"));
+    }
+    let head = repo.head().ok();
+    if head.as_ref().and_then(|head| head.name()) == Some(refname.as_str()) {
+        return Err(format!(
+            "Auto-commit branch {branch} is checked out; pick another branch."
+        ));
+    }
+
+    // Changes to this in-memory index are never written back to `.git/index`
+    let mut index = repo.index().map_err(|e| e.to_string())?;
+    index
+        .add_all(["*"], IndexAddOption::DEFAULT, None)
+        .map_err(|e| e.to_string())?;
+    index.update_all(["*"], None).map_err(|e| e.to_string())?;
+    let tree_id = index.write_tree().map_err(|e| e.to_string())?;
+    let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+
+    let parent = match repo.find_reference(&refname) {
+        Ok(reference) => Some(reference.peel_to_commit().map_err(|e| e.to_string())?),
+        Err(_) => head.and_then(|head| head.peel_to_commit().ok()),
+    };
+    if parent
+        .as_ref()
+        .is_some_and(|parent| parent.tree_id() == tree_id)
+    {
+        return Ok(None);
+    }
+
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("ClaudeCodeMonitor", "monitor@localhost"))
+        .map_err(|e| e.to_string())?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let commit_id = repo
+        .commit(None, &signature, &signature, message, &tree, &parents)
+        .map_err(|e| e.to_string())?;
+    repo.reference(&refname, commit_id, true, "auto-commit after turn")
+        .map_err(|e| e.to_string())?;
+    Ok(Some(commit_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn create_temp_repo() -> (std::path::PathBuf, Repository) {
+        let root =
+            std::env::temp_dir().join(format!("claude-code-monitor-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("create temp repo root");
+        let repo = Repository::init(&root).expect("init repo");
+        fs::write(root.join("a.txt"), "one\n").expect("write file");
+        let mut index = repo.index().expect("index");
+        index.add_path(Path::new("a.txt")).expect("add path");
+        index.write().expect("write index");
+        let tree = repo
+            .find_tree(index.write_tree().expect("write tree"))
+            .expect("find tree");
+        let sig = Signature::now("Test", "test@example.com").expect("signature");
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .expect("commit");
+        (root, repo)
+    }
+
+    #[test]
+    fn commits_the_worktree_without_touching_head_or_index() {
+        let (root, repo) = create_temp_repo();
+        let head_before = repo.head().unwrap().target();
+        fs::write(root.join("a.txt"), "two\n").unwrap();
+        fs::write(root.join("new.txt"), "new\n").unwrap();
+
+        let commit = commit_worktree(&root, DEFAULT_BRANCH, "Edit a")
+            .unwrap()
+            .expect("a commit");
+        let branch = repo
+            .find_branch(DEFAULT_BRANCH, git2::BranchType::Local)
+            .unwrap();
+        assert_eq!(branch.get().target(), Some(commit));
+        let tree = repo.find_commit(commit).unwrap().tree().unwrap();
+        assert!(tree.get_path(Path::new("new.txt")).is_ok());
+
+        assert_eq!(repo.head().unwrap().target(), head_before);
+        let status = repo.status_file(Path::new("new.txt")).unwrap();
+        assert!(status.contains(git2::Status::WT_NEW));
+
+        // Nothing new to record
+        assert_eq!(
+            commit_worktree(&root, DEFAULT_BRANCH, "again").unwrap(),
+            None
+        );
+        fs::remove_file(root.join("new.txt")).unwrap();
+        let second = commit_worktree(&root, DEFAULT_BRANCH, "Remove new")
+            .unwrap()
+            .expect("a second commit");
+        let second = repo.find_commit(second).unwrap();
+        assert_eq!(second.parent_id(0).unwrap(), commit);
+        assert!(second
+            .tree()
+            .unwrap()
+            .get_path(Path::new("new.txt"))
+            .is_err());
+    }
+
+    #[test]
+    fn builds_messages_from_the_turn_reply() {
+        assert_eq!(
+            commit_message(
+                "\n## Fixed the flaky retry test\nThe delay was not jittered.\n",
+                "t1",
+                "turn-1"
+            ),
+            "Fixed the flaky retry test\n\nThe delay was not jittered.\n\nThread: t1\nTurn: turn-1\n"
+        );
+        assert!(commit_message("", "t1", "turn-2").starts_with("Claude turn turn-2\n\n"));
+        assert_eq!(
+            target_branch(Some(&AutoCommitPolicy {
+                enabled: true,
+                branch: None
+            })),
+            Some(DEFAULT_BRANCH.to_string())
+        );
+        assert_eq!(target_branch(Some(&AutoCommitPolicy::default())), None);
+    }
+}
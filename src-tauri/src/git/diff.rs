@@ -0,0 +1,353 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use git2::{DiffOptions, ObjectType, Oid, Patch, Repository, StatusOptions, Tree};
+
+use crate::git_utils::diff_patch_to_string;
+use crate::types::GitFileStatus;
+
+/// Files larger than this are hashed but not copied into the object database, so their
+/// changes are listed without a patch.
+const MAX_SNAPSHOT_FILE_BYTES: u64 = 2 * 1024 * 1024;
+const MAX_DIFF_CHARS: usize = 256 * 1024;
+
+/// The state of a working tree at one moment: its `HEAD` tree plus the content of
+/// every path `git status` reports as differing from it.
+///
+/// Contents are written as loose blobs, which leaves the index and refs untouched;
+/// objects nothing refers to are pruned by the next `git gc`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WorktreeSnapshot {
+    head_tree: Option<Oid>,
+    /// `None` when the path is missing from the working tree.
+    files: BTreeMap<String, Option<Oid>>,
+}
+
+/// What changed between two snapshots.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SnapshotDiff {
+    pub(crate) files: Vec<GitFileStatus>,
+    /// Unified diff of every changed text file, cut at [`MAX_DIFF_CHARS`].
+    pub(crate) diff: String,
+    pub(crate) truncated: bool,
+}
+
+pub(crate) fn snapshot(repo_root: &Path) -> Result<WorktreeSnapshot, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let workdir = repo
+        .workdir()
+        .ok_or("Repository has no working tree.")?
+        .to_path_buf();
+    let head_tree = repo
+        .head()
+        .ok()
+        .and_then(|head| head.peel_to_tree().ok())
+        .map(|tree| tree.id());
+
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| e.to_string())?;
+
+    let mut files = BTreeMap::new();
+    for entry in statuses.iter() {
+        let Some(path) = entry.path() else {
+            continue;
+        };
+        let full_path = workdir.join(path);
+        let blob = match fs::metadata(&full_path) {
+            Ok(meta) if meta.is_file() && meta.len() > MAX_SNAPSHOT_FILE_BYTES => {
+                Some(Oid::hash_file(ObjectType::Blob, &full_path).map_err(|e| e.to_string())?)
+            }
+            Ok(meta) if meta.is_file() => {
+                Some(repo.blob_path(&full_path).map_err(|e| e.to_string())?)
+            }
+            _ => None,
+        };
+        files.insert(path.to_string(), blob);
+    }
+    Ok(WorktreeSnapshot { head_tree, files })
+}
+
+/// Diff two snapshots of the same repository. Commits made in between are included,
+/// since both sides are compared by content.
+pub(crate) fn diff_snapshots(
+    repo_root: &Path,
+    before: &WorktreeSnapshot,
+    after: &WorktreeSnapshot,
+) -> Result<SnapshotDiff, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let find_tree = |oid: Option<Oid>| oid.and_then(|oid| repo.find_tree(oid).ok());
+    let before_tree = find_tree(before.head_tree);
+    let after_tree = find_tree(after.head_tree);
+
+    let mut paths: BTreeSet<String> = before.files.keys().cloned().collect();
+    paths.extend(after.files.keys().cloned());
+    if before.head_tree != after.head_tree {
+        let diff = repo
+            .diff_tree_to_tree(before_tree.as_ref(), after_tree.as_ref(), None)
+            .map_err(|e| e.to_string())?;
+        for delta in diff.deltas() {
+            for file in [delta.old_file(), delta.new_file()] {
+                if let Some(path) = file.path().and_then(Path::to_str) {
+                    paths.insert(path.to_string());
+                }
+            }
+        }
+    }
+
+    let mut result = SnapshotDiff::default();
+    for path in paths {
+        let old = content_at(before, before_tree.as_ref(), &path);
+        let new = content_at(after, after_tree.as_ref(), &path);
+        if old == new {
+            continue;
+        }
+        let status = match (old, new) {
+            (None, _) => "A",
+            (_, None) => "D",
+            _ => "M",
+        };
+        let (additions, deletions, patch) = patch_for(&repo, &path, old, new);
+        if let Some(patch) = patch.filter(|patch| !patch.trim().is_empty()) {
+            append_patch(&mut result, &patch);
+        }
+        result.files.push(GitFileStatus {
+            path,
+            status: status.to_string(),
+            additions,
+            deletions,
+        });
+    }
+    Ok(result)
+}
+
+/// Put `paths` back the way they were in `before`: rewrite or recreate files it had,
+/// delete files it did not. Commits made since are left alone. Returns the paths that
+/// could not be restored, such as files too large to have had their content snapshotted.
+pub(crate) fn restore_paths(
+    repo_root: &Path,
+    before: &WorktreeSnapshot,
+    paths: &[String],
+) -> Result<Vec<String>, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let workdir = repo
+        .workdir()
+        .ok_or("Repository has no working tree.")?
+        .to_path_buf();
+    let before_tree = before.head_tree.and_then(|oid| repo.find_tree(oid).ok());
+    let mut failed = Vec::new();
+    for path in paths {
+        let full_path = workdir.join(path);
+        let restored = match content_at(before, before_tree.as_ref(), path) {
+            Some(oid) => repo.find_blob(oid).ok().is_some_and(|blob| {
+                full_path
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::write(&full_path, blob.content()))
+                    .is_ok()
+            }),
+            None => match fs::remove_file(&full_path) {
+                Ok(()) => true,
+                Err(err) => err.kind() == std::io::ErrorKind::NotFound,
+            },
+        };
+        if !restored {
+            failed.push(path.clone());
+        }
+    }
+    Ok(failed)
+}
+
+/// The blob a path held in a snapshot: its recorded content when `git status` listed
+/// it, otherwise whatever `HEAD` had.
+fn content_at(snapshot: &WorktreeSnapshot, head_tree: Option<&Tree>, path: &str) -> Option<Oid> {
+    match snapshot.files.get(path) {
+        Some(blob) => *blob,
+        None => head_tree
+            .and_then(|tree| tree.get_path(Path::new(path)).ok())
+            .filter(|entry| entry.kind() == Some(ObjectType::Blob))
+            .map(|entry| entry.id()),
+    }
+}
+
+/// Line counts and patch text for one path. Files whose content was only hashed have
+/// neither.
+fn patch_for(
+    repo: &Repository,
+    path: &str,
+    old: Option<Oid>,
+    new: Option<Oid>,
+) -> (i64, i64, Option<String>) {
+    let find_blob = |oid: Option<Oid>| match oid {
+        Some(oid) => repo.find_blob(oid).map(Some),
+        None => Ok(None),
+    };
+    let (Ok(old_blob), Ok(new_blob)) = (find_blob(old), find_blob(new)) else {
+        return (0, 0, None);
+    };
+    let mut options = DiffOptions::new();
+    let patch = Patch::from_blobs(
+        old_blob.as_ref(),
+        Some(Path::new(path)),
+        new_blob.as_ref(),
+        Some(Path::new(path)),
+        Some(&mut options),
+    );
+    let Ok(mut patch) = patch else {
+        return (0, 0, None);
+    };
+    let (additions, deletions) = patch
+        .line_stats()
+        .map(|(_, additions, deletions)| (additions as i64, deletions as i64))
+        .unwrap_or((0, 0));
+    (additions, deletions, diff_patch_to_string(&mut patch).ok())
+}
+
+fn append_patch(result: &mut SnapshotDiff, patch: &str) {
+    if result.truncated {
+        return;
+    }
+    let room = MAX_DIFF_CHARS.saturating_sub(result.diff.chars().count());
+    match patch.char_indices().nth(room) {
+        Some((end, _)) => {
+            result.diff.push_str(&patch[..end]);
+            result.truncated = true;
+        }
+        None => result.diff.push_str(patch),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_temp_repo() -> (std::path::PathBuf, Repository) {
+        let root =
+            std::env::temp_dir().join(format!("claude-code-monitor-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("create temp repo root");
+        let repo = Repository::init(&root).expect("init repo");
+        (root, repo)
+    }
+
+    fn commit_all(repo: &Repository, message: &str) {
+        let mut index = repo.index().expect("index");
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .expect("add all");
+        index.write().expect("write index");
+        let tree = repo
+            .find_tree(index.write_tree().expect("write tree"))
+            .expect("find tree");
+        let sig = git2::Signature::now("Test", "test@example.com").expect("signature");
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .expect("commit");
+    }
+
+    #[test]
+    fn reports_only_what_changed_between_snapshots() {
+        let (root, repo) = create_temp_repo();
+        fs::write(root.join("kept.txt"), "one\n").unwrap();
+        fs::write(root.join("edited.txt"), "one\n").unwrap();
+        fs::write(root.join("removed.txt"), "gone\n").unwrap();
+        commit_all(&repo, "init");
+        // Already dirty before the turn, and left alone by it
+        fs::write(root.join("kept.txt"), "one\ntwo\n").unwrap();
+
+        let before = snapshot(&root).unwrap();
+        fs::write(root.join("edited.txt"), "one\nthree\n").unwrap();
+        fs::remove_file(root.join("removed.txt")).unwrap();
+        fs::write(root.join("added.txt"), "new\n").unwrap();
+        let after = snapshot(&root).unwrap();
+
+        let diff = diff_snapshots(&root, &before, &after).unwrap();
+        let files: Vec<(&str, &str, i64, i64)> = diff
+            .files
+            .iter()
+            .map(|file| {
+                (
+                    file.path.as_str(),
+                    file.status.as_str(),
+                    file.additions,
+                    file.deletions,
+                )
+            })
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                ("added.txt", "A", 1, 0),
+                ("edited.txt", "M", 1, 0),
+                ("removed.txt", "D", 0, 1),
+            ]
+        );
+        assert!(diff.diff.contains("+three"));
+        assert!(!diff.diff.contains("kept.txt"));
+        assert!(!diff.truncated);
+    }
+
+    #[test]
+    fn restores_paths_to_their_snapshot() {
+        let (root, repo) = create_temp_repo();
+        fs::write(root.join("edited.txt"), "one\n").unwrap();
+        fs::write(root.join("removed.txt"), "gone\n").unwrap();
+        commit_all(&repo, "init");
+        fs::write(root.join("dirty.txt"), "before\n").unwrap();
+
+        let before = snapshot(&root).unwrap();
+        fs::write(root.join("edited.txt"), "two\n").unwrap();
+        fs::remove_file(root.join("removed.txt")).unwrap();
+        fs::write(root.join("dirty.txt"), "after\n").unwrap();
+        fs::write(root.join("added.txt"), "new\n").unwrap();
+        let after = snapshot(&root).unwrap();
+        let paths: Vec<String> = diff_snapshots(&root, &before, &after)
+            .unwrap()
+            .files
+            .into_iter()
+            .map(|file| file.path)
+            .collect();
+
+        assert!(restore_paths(&root, &before, &paths).unwrap().is_empty());
+        assert_eq!(
+            fs::read_to_string(root.join("edited.txt")).unwrap(),
+            "one\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("removed.txt")).unwrap(),
+            "gone\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("dirty.txt")).unwrap(),
+            "before\n"
+        );
+        assert!(!root.join("added.txt").exists());
+        let restored = snapshot(&root).unwrap();
+        assert!(diff_snapshots(&root, &before, &restored)
+            .unwrap()
+            .files
+            .is_empty());
+    }
+
+    #[test]
+    fn includes_changes_the_turn_committed() {
+        let (root, repo) = create_temp_repo();
+        fs::write(root.join("a.txt"), "one\n").unwrap();
+        commit_all(&repo, "init");
+
+        let before = snapshot(&root).unwrap();
+        fs::write(root.join("a.txt"), "two\n").unwrap();
+        commit_all(&repo, "edit");
+        let after = snapshot(&root).unwrap();
+
+        let diff = diff_snapshots(&root, &before, &after).unwrap();
+        assert_eq!(diff.files.len(), 1);
+        assert_eq!((diff.files[0].additions, diff.files[0].deletions), (1, 1));
+    }
+}
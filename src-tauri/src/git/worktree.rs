@@ -0,0 +1,222 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::run_git_command;
+
+/// Untracked Claude config a fresh checkout would otherwise lose. Symlinking it from the
+/// parent keeps the worktree's session on the same local settings and memory.
+const CLAUDE_DIR: &str = ".claude";
+const CLAUDE_LOCAL_SETTINGS: &str = ".claude/settings.local.json";
+const CLAUDE_LOCAL_MEMORY: &str = "CLAUDE.local.md";
+
+/// Check out `branch` into `worktree_path`. New branches start from `base_ref`, or the
+/// parent's `HEAD` when none is given.
+pub(crate) async fn add_checkout(
+    repo_root: &Path,
+    worktree_path: &Path,
+    branch: &str,
+    branch_exists: bool,
+    base_ref: Option<&str>,
+) -> Result<(), String> {
+    let path = worktree_path.to_string_lossy().to_string();
+    let mut args = vec!["worktree", "add"];
+    if branch_exists {
+        args.extend([path.as_str(), branch]);
+    } else {
+        args.extend(["-b", branch, path.as_str()]);
+        if let Some(base_ref) = base_ref.map(str::trim).filter(|base| !base.is_empty()) {
+            args.push(base_ref);
+        }
+    }
+    run_git_command(repo_root, &args).await?;
+    link_claude_config(repo_root, worktree_path);
+    Ok(())
+}
+
+/// Remove the checkout at `worktree_path` and prune its metadata from the parent repo.
+/// Claude config symlinks are unlinked first so the parent's files are never touched.
+pub(crate) async fn remove_checkout(repo_root: &Path, worktree_path: &Path) -> Result<(), String> {
+    unlink_claude_config(worktree_path);
+    if worktree_path.exists() {
+        let path = worktree_path.to_string_lossy().to_string();
+        if let Err(error) =
+            run_git_command(repo_root, &["worktree", "remove", "--force", &path]).await
+        {
+            if !is_missing_worktree_error(&error) {
+                return Err(error);
+            }
+            if worktree_path.exists() {
+                fs::remove_dir_all(worktree_path)
+                    .map_err(|err| format!("Failed to remove worktree folder: {err}"))?;
+            }
+        }
+    }
+    let _ = run_git_command(repo_root, &["worktree", "prune", "--expire", "now"]).await;
+    Ok(())
+}
+
+pub(crate) fn is_missing_worktree_error(error: &str) -> bool {
+    error.contains("is not a working tree")
+}
+
+/// Symlink untracked Claude config from `parent` into `worktree`. When `.claude` is not
+/// tracked at all the whole directory is linked; otherwise only the local settings file.
+/// Existing paths in the worktree are left alone. Returns the links that were created.
+pub(crate) fn link_claude_config(parent: &Path, worktree: &Path) -> Vec<PathBuf> {
+    let mut linked = Vec::new();
+    let parent_dir = parent.join(CLAUDE_DIR);
+    let worktree_dir = worktree.join(CLAUDE_DIR);
+    if parent_dir.is_dir() && !path_present(&worktree_dir) {
+        if symlink_dir(&parent_dir, &worktree_dir).is_ok() {
+            linked.push(worktree_dir);
+        }
+    } else if worktree_dir.is_dir() {
+        linked.extend(link_file(parent, worktree, CLAUDE_LOCAL_SETTINGS));
+    }
+    linked.extend(link_file(parent, worktree, CLAUDE_LOCAL_MEMORY));
+    linked
+}
+
+/// Remove the symlinks created by [`link_claude_config`]. Real files are kept.
+pub(crate) fn unlink_claude_config(worktree: &Path) {
+    for relative in [CLAUDE_LOCAL_SETTINGS, CLAUDE_LOCAL_MEMORY, CLAUDE_DIR] {
+        let path = worktree.join(relative);
+        let is_link = fs::symlink_metadata(&path)
+            .map(|meta| meta.file_type().is_symlink())
+            .unwrap_or(false);
+        if is_link {
+            if let Err(err) = remove_link(&path) {
+                tracing::warn!("failed to remove {}: {err}", path.display());
+            }
+        }
+    }
+}
+
+fn link_file(parent: &Path, worktree: &Path, relative: &str) -> Option<PathBuf> {
+    let source = parent.join(relative);
+    let target = worktree.join(relative);
+    if !source.is_file() || path_present(&target) {
+        return None;
+    }
+    symlink_file(&source, &target).ok()?;
+    Some(target)
+}
+
+/// Like `exists`, but also true for dangling symlinks.
+fn path_present(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+#[cfg(unix)]
+fn symlink_dir(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(source, target)
+}
+
+#[cfg(unix)]
+fn symlink_file(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(source, target)
+}
+
+#[cfg(windows)]
+fn symlink_dir(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_dir(source, target)
+}
+
+#[cfg(windows)]
+fn symlink_file(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(source, target)
+}
+
+fn remove_link(path: &Path) -> io::Result<()> {
+    // Windows directory symlinks must be removed as directories
+    #[cfg(windows)]
+    if fs::metadata(path)
+        .map(|meta| meta.is_dir())
+        .unwrap_or(false)
+    {
+        return fs::remove_dir(path);
+    }
+    fs::remove_file(path)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("worktree-{label}-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn links_whole_claude_dir_when_untracked() {
+        let parent = temp_dir("parent");
+        let worktree = temp_dir("checkout");
+        fs::create_dir_all(parent.join(".claude")).unwrap();
+        fs::write(parent.join(CLAUDE_LOCAL_SETTINGS), "{}").unwrap();
+        fs::write(parent.join(CLAUDE_LOCAL_MEMORY), "notes").unwrap();
+
+        let linked = link_claude_config(&parent, &worktree);
+        assert_eq!(
+            linked,
+            vec![
+                worktree.join(CLAUDE_DIR),
+                worktree.join(CLAUDE_LOCAL_MEMORY)
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(worktree.join(CLAUDE_LOCAL_SETTINGS)).unwrap(),
+            "{}"
+        );
+
+        unlink_claude_config(&worktree);
+        assert!(!path_present(&worktree.join(CLAUDE_DIR)));
+        assert!(!path_present(&worktree.join(CLAUDE_LOCAL_MEMORY)));
+        // The parent's files survive the cleanup
+        assert!(parent.join(CLAUDE_LOCAL_SETTINGS).is_file());
+        assert!(parent.join(CLAUDE_LOCAL_MEMORY).is_file());
+
+        let _ = fs::remove_dir_all(&parent);
+        let _ = fs::remove_dir_all(&worktree);
+    }
+
+    #[test]
+    fn links_only_local_settings_into_tracked_claude_dir() {
+        let parent = temp_dir("parent");
+        let worktree = temp_dir("checkout");
+        fs::create_dir_all(parent.join(".claude")).unwrap();
+        fs::write(parent.join(CLAUDE_LOCAL_SETTINGS), "{}").unwrap();
+        fs::create_dir_all(worktree.join(".claude")).unwrap();
+        fs::write(worktree.join(".claude/settings.json"), "{}").unwrap();
+
+        let linked = link_claude_config(&parent, &worktree);
+        assert_eq!(linked, vec![worktree.join(CLAUDE_LOCAL_SETTINGS)]);
+
+        unlink_claude_config(&worktree);
+        assert!(!path_present(&worktree.join(CLAUDE_LOCAL_SETTINGS)));
+        assert!(worktree.join(".claude/settings.json").is_file());
+
+        let _ = fs::remove_dir_all(&parent);
+        let _ = fs::remove_dir_all(&worktree);
+    }
+
+    #[test]
+    fn unlink_keeps_real_files() {
+        let worktree = temp_dir("checkout");
+        fs::write(worktree.join(CLAUDE_LOCAL_MEMORY), "mine").unwrap();
+        unlink_claude_config(&worktree);
+        assert!(worktree.join(CLAUDE_LOCAL_MEMORY).is_file());
+        let _ = fs::remove_dir_all(&worktree);
+    }
+
+    #[test]
+    fn detects_missing_worktree_errors() {
+        assert!(is_missing_worktree_error(
+            "fatal: '/tmp/x' is not a working tree"
+        ));
+        assert!(!is_missing_worktree_error("fatal: permission denied"));
+    }
+}
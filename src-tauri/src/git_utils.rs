@@ -79,13 +79,18 @@ pub(crate) fn parse_github_repo(remote_url: &str) -> Option<String> {
     let mut path = if trimmed.starts_with("git@github.com:") {
         trimmed.trim_start_matches("git@github.com:").to_string()
     } else if trimmed.starts_with("ssh://git@github.com/") {
-        trimmed.trim_start_matches("ssh://git@github.com/").to_string()
+        trimmed
+            .trim_start_matches("ssh://git@github.com/")
+            .to_string()
     } else if let Some(index) = trimmed.find("github.com/") {
         trimmed[index + "github.com/".len()..].to_string()
     } else {
         return None;
     };
-    path = path.trim_end_matches(".git").trim_end_matches('/').to_string();
+    path = path
+        .trim_end_matches(".git")
+        .trim_end_matches('/')
+        .to_string();
     if path.is_empty() {
         None
     } else {
@@ -123,11 +128,7 @@ fn should_skip_dir(name: &str) -> bool {
     )
 }
 
-pub(crate) fn list_git_roots(
-    root: &Path,
-    max_depth: usize,
-    max_results: usize,
-) -> Vec<String> {
+pub(crate) fn list_git_roots(root: &Path, max_depth: usize, max_results: usize) -> Vec<String> {
     if !root.is_dir() {
         return Vec::new();
     }
@@ -0,0 +1,453 @@
+//! Open GitHub pull requests from what a turn changed.
+//!
+//! The turn's changes are taken from the workspace's auto-commit branch when it has
+//! one, and are otherwise committed onto a fresh `monitor/turn-…` branch the same way
+//! auto-commit does, without touching the checkout. The branch is pushed and the PR
+//! opened with the `gh` CLI, using the turn's final reply as title and description.
+//! The redacted transcript is uploaded as a secret gist and linked from the PR.
+
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, State};
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::claude::resolve_session_path;
+use crate::export::{self, ExportedBlock, ExportedSession, Role};
+use crate::git::autocommit;
+use crate::git_utils::{parse_github_repo, resolve_git_root};
+use crate::redaction;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+use crate::types::{GitFileStatus, TurnTimeline, WorkspaceEntry};
+
+const TURN_BRANCH_PREFIX: &str = "monitor/turn-";
+/// Changed files listed in the description before the rest are summed up.
+const MAX_LISTED_FILES: usize = 50;
+
+/// The pull request opened for a turn.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CreatedPullRequest {
+    pub(crate) url: String,
+    pub(crate) branch: String,
+    pub(crate) title: String,
+    pub(crate) transcript_url: Option<String>,
+}
+
+/// Push the branch holding a turn's changes and open a pull request for it.
+///
+/// `branch` overrides the branch to push; it must already exist. `base` defaults to the
+/// repository's default branch. With `share_transcript` off no gist is created.
+#[tauri::command]
+pub(crate) async fn create_pr_from_turn(
+    workspace_id: String,
+    thread_id: String,
+    turn_id: String,
+    branch: Option<String>,
+    base: Option<String>,
+    draft: Option<bool>,
+    share_transcript: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<CreatedPullRequest, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "create_pr_from_turn",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "turnId": turn_id,
+                "branch": branch,
+                "base": base,
+                "draft": draft,
+                "shareTranscript": share_transcript,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    let (remote, repo_name) = github_remote(&repo_root)?;
+
+    let db_path = state.db_path.clone();
+    let turn = {
+        let entry = entry.clone();
+        let thread_id = thread_id.clone();
+        let turn_id = turn_id.clone();
+        tokio::task::spawn_blocking(move || load_turn(&db_path, &entry, &thread_id, &turn_id))
+            .await
+            .map_err(|err| err.to_string())??
+    };
+
+    let branch = match branch.map(|branch| branch.trim().to_string()) {
+        Some(branch) if !branch.is_empty() => branch,
+        _ => {
+            let repo_root = repo_root.clone();
+            let policy = entry.settings.auto_commit.clone();
+            let message = autocommit::commit_message(&turn.summary, &thread_id, &turn_id);
+            let turn_branch = turn_branch(&turn_id);
+            tokio::task::spawn_blocking(move || {
+                if let Some(branch) = autocommit::target_branch(policy.as_ref()) {
+                    return Ok(branch);
+                }
+                autocommit::commit_worktree(&repo_root, &turn_branch, &message)?
+                    .ok_or("The working tree has no changes to open a pull request with.")?;
+                Ok::<_, String>(turn_branch)
+            })
+            .await
+            .map_err(|err| err.to_string())??
+        }
+    };
+
+    run_cli(
+        &repo_root,
+        "git",
+        &[
+            "push",
+            "--set-upstream",
+            &remote,
+            &format!("{branch}:{branch}"),
+        ],
+    )
+    .await?;
+
+    let transcript_url = if share_transcript.unwrap_or(true) {
+        match &turn.transcript {
+            Some(transcript) => Some(share_transcript_gist(&repo_root, transcript).await?),
+            None => None,
+        }
+    } else {
+        None
+    };
+
+    let (title, body) = pull_request_text(
+        &turn.summary,
+        &turn.files,
+        transcript_url.as_deref(),
+        &thread_id,
+        &turn_id,
+    );
+    let body_path = std::env::temp_dir().join(format!("claude-pr-{}.md", Uuid::new_v4()));
+    std::fs::write(&body_path, &body).map_err(|err| err.to_string())?;
+    let body_file = body_path.to_string_lossy().to_string();
+    let mut args = vec![
+        "pr",
+        "create",
+        "--repo",
+        repo_name.as_str(),
+        "--head",
+        branch.as_str(),
+        "--title",
+        title.as_str(),
+        "--body-file",
+        body_file.as_str(),
+    ];
+    if let Some(base) = base
+        .as_deref()
+        .map(str::trim)
+        .filter(|base| !base.is_empty())
+    {
+        args.extend(["--base", base]);
+    }
+    if draft.unwrap_or(false) {
+        args.push("--draft");
+    }
+    let created = run_cli(&repo_root, "gh", &args).await;
+    let _ = std::fs::remove_file(&body_path);
+    let url = last_url(&created?).ok_or("GitHub CLI did not return the pull request URL.")?;
+
+    Ok(CreatedPullRequest {
+        url,
+        branch,
+        title,
+        transcript_url,
+    })
+}
+
+/// What a PR needs to know about a turn.
+pub(crate) struct TurnSource {
+    pub(crate) summary: String,
+    pub(crate) files: Vec<GitFileStatus>,
+    pub(crate) transcript: Option<ExportedSession>,
+}
+
+pub(crate) fn load_turn(
+    db_path: &Path,
+    entry: &WorkspaceEntry,
+    thread_id: &str,
+    turn_id: &str,
+) -> Result<TurnSource, String> {
+    let files = storage::read_turn_diff(db_path, &entry.id, turn_id)?
+        .map(|diff| diff.files)
+        .unwrap_or_default();
+    let timeline = storage::read_turn_timeline(db_path, &entry.id, turn_id)?;
+    let transcript = match resolve_session_path(entry, thread_id) {
+        Some(path) => Some(export::load_session(&path, thread_id)?),
+        None => None,
+    };
+    let summary = transcript
+        .as_ref()
+        .and_then(|session| turn_summary(session, timeline.as_ref()))
+        .unwrap_or_default();
+    Ok(TurnSource {
+        summary,
+        files,
+        transcript,
+    })
+}
+
+/// The last thing Claude said during the turn, or in the session when the turn's timing
+/// wasn't recorded.
+fn turn_summary(session: &ExportedSession, timeline: Option<&TurnTimeline>) -> Option<String> {
+    session
+        .messages
+        .iter()
+        .rev()
+        .filter(|message| message.role == Role::Assistant)
+        .filter(|message| match (timeline, message.timestamp) {
+            (Some(timeline), Some(timestamp)) => {
+                timestamp >= timeline.started_at && timestamp <= timeline.finished_at
+            }
+            _ => true,
+        })
+        .find_map(|message| {
+            message.blocks.iter().rev().find_map(|block| match block {
+                ExportedBlock::Text { text } if !text.trim().is_empty() => Some(text.clone()),
+                _ => None,
+            })
+        })
+}
+
+fn turn_branch(turn_id: &str) -> String {
+    let short: String = turn_id
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .take(8)
+        .collect();
+    format!("{TURN_BRANCH_PREFIX}{short}")
+}
+
+/// Title and description for a turn's PR: the reply's first line, then the rest of the
+/// reply, the changed files and a footer pointing back at the transcript and turn.
+fn pull_request_text(
+    summary: &str,
+    files: &[GitFileStatus],
+    transcript_url: Option<&str>,
+    thread_id: &str,
+    turn_id: &str,
+) -> (String, String) {
+    let message = autocommit::commit_message(summary, thread_id, turn_id);
+    let title = message.lines().next().unwrap_or_default().to_string();
+    let rest: Vec<&str> = summary
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .skip(1)
+        .collect();
+
+    let mut body = String::new();
+    let rest = rest.join("\n");
+    if !rest.trim().is_empty() {
+        body.push_str(rest.trim());
+        body.push_str("\n\n");
+    }
+    if !files.is_empty() {
+        body.push_str("### Changed files\n\n");
+        for file in files.iter().take(MAX_LISTED_FILES) {
+            body.push_str(&format!(
+                "- `{}` (+{} −{})\n",
+                file.path, file.additions, file.deletions
+            ));
+        }
+        if files.len() > MAX_LISTED_FILES {
+            body.push_str(&format!("- …and {} more\n", files.len() - MAX_LISTED_FILES));
+        }
+        body.push('\n');
+    }
+    body.push_str("---\n\n");
+    if let Some(url) = transcript_url {
+        body.push_str(&format!("[Session transcript]({url}) · "));
+    }
+    body.push_str(&format!("Thread `{thread_id}` · turn `{turn_id}`\n"));
+    (title, body)
+}
+
+/// Upload the transcript as Markdown to a secret gist and return its URL.
+async fn share_transcript_gist(
+    repo_root: &Path,
+    transcript: &ExportedSession,
+) -> Result<String, String> {
+    let content = export::render_markdown(transcript);
+    let content = redaction::redact_text(&content).into_owned();
+    let dir = std::env::temp_dir().join(format!("claude-transcript-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    let path: PathBuf = dir.join(format!("{}.md", transcript.session_id));
+    std::fs::write(&path, content).map_err(|err| err.to_string())?;
+    let file = path.to_string_lossy().to_string();
+    let created = run_cli(
+        repo_root,
+        "gh",
+        &["gist", "create", "--desc", &transcript.title, &file],
+    )
+    .await;
+    let _ = std::fs::remove_dir_all(&dir);
+    last_url(&created?).ok_or_else(|| "GitHub CLI did not return the gist URL.".to_string())
+}
+
+/// The remote to push to (`origin` when there is one) and the GitHub repository it
+/// points at.
+fn github_remote(repo_root: &Path) -> Result<(String, String), String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let remotes = repo.remotes().map_err(|e| e.to_string())?;
+    let name = if remotes.iter().any(|remote| remote == Some("origin")) {
+        "origin".to_string()
+    } else {
+        remotes.iter().flatten().next().unwrap_or("").to_string()
+    };
+    if name.is_empty() {
+        return Err("No git remote configured.".to_string());
+    }
+    let remote = repo.find_remote(&name).map_err(|e| e.to_string())?;
+    let remote_url = remote.url().ok_or("Remote has no URL configured.")?;
+    let repo_name = parse_github_repo(remote_url).ok_or("Remote is not a GitHub repository.")?;
+    Ok((name, repo_name))
+}
+
+/// Run `program` in the repository and return its stdout, or the most telling output
+/// as the error.
+pub(crate) async fn run_cli(
+    repo_root: &Path,
+    program: &str,
+    args: &[&str],
+) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(repo_root)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {program}: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if output.status.success() {
+        return Ok(stdout);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let detail = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    if detail.is_empty() {
+        return Err(format!("{program} failed."));
+    }
+    Err(detail.to_string())
+}
+
+/// `gh` prints the created resource's URL as its last line of output.
+pub(crate) fn last_url(output: &str) -> Option<String> {
+    output
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.starts_with("https://"))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ExportedMessage;
+
+    fn file(path: &str, additions: i64, deletions: i64) -> GitFileStatus {
+        GitFileStatus {
+            path: path.to_string(),
+            status: "M".to_string(),
+            additions,
+            deletions,
+        }
+    }
+
+    fn assistant(timestamp: i64, text: &str) -> ExportedMessage {
+        ExportedMessage {
+            role: Role::Assistant,
+            timestamp: Some(timestamp),
+            blocks: vec![ExportedBlock::Text {
+                text: text.to_string(),
+            }],
+            annotations: Vec::new(),
+            line_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn builds_title_and_description_from_the_reply() {
+        let (title, body) = pull_request_text(
+            "\n## Fix the login redirect\n\nThe callback now keeps the `next` parameter.",
+            &[file("src/auth.rs", 12, 3)],
+            Some("https://gist.github.com/abc"),
+            "thread-1",
+            "turn-1",
+        );
+        assert_eq!(title, "Fix the login redirect");
+        assert_eq!(
+            body,
+            "The callback now keeps the `next` parameter.\n\n\
+             ### Changed files\n\n- `src/auth.rs` (+12 −3)\n\n---\n\n\
+             [Session transcript](https://gist.github.com/abc) · Thread `thread-1` · turn `turn-1`\n"
+        );
+    }
+
+    #[test]
+    fn summary_comes_from_the_turn_window() {
+        let session = ExportedSession {
+            session_id: "thread-1".to_string(),
+            title: "Fix login".to_string(),
+            started_at: Some(100),
+            updated_at: Some(900),
+            messages: vec![
+                assistant(150, "first turn done"),
+                assistant(450, "second turn done"),
+                assistant(900, "third turn done"),
+            ],
+            annotations: Vec::new(),
+        };
+        let timeline = TurnTimeline {
+            workspace_id: "ws-1".to_string(),
+            thread_id: "thread-1".to_string(),
+            turn_id: "turn-2".to_string(),
+            started_at: 400,
+            finished_at: 500,
+            tool_calls: Vec::new(),
+        };
+        assert_eq!(
+            turn_summary(&session, Some(&timeline)).as_deref(),
+            Some("second turn done")
+        );
+        assert_eq!(
+            turn_summary(&session, None).as_deref(),
+            Some("third turn done")
+        );
+    }
+
+    #[test]
+    fn finds_the_created_url() {
+        assert_eq!(
+            last_url("Creating pull request\nhttps://github.com/o/r/pull/7\n").as_deref(),
+            Some("https://github.com/o/r/pull/7")
+        );
+        assert_eq!(turn_branch("1a2b-3c4d-5e6f"), "monitor/turn-1a2b3c4d");
+    }
+}
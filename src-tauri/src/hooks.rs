@@ -0,0 +1,714 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, State};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+use crate::claude_settings::{
+    read_settings_file, resolve_workspace_dirs, scope_path, write_settings_file, SettingsScope,
+};
+use crate::remote_backend;
+use crate::state::AppState;
+
+/// Hook events the CLI fires, in the order they appear in its docs.
+pub(crate) const HOOK_EVENTS: &[&str] = &[
+    "PreToolUse",
+    "PostToolUse",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "SubagentStop",
+    "PreCompact",
+    "SessionStart",
+    "SessionEnd",
+];
+/// Matches the CLI's own default when a hook sets no timeout.
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 60;
+
+/// A command hook as the UI edits it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HookInput {
+    pub(crate) event: String,
+    #[serde(default)]
+    pub(crate) matcher: Option<String>,
+    pub(crate) command: String,
+    #[serde(default)]
+    pub(crate) timeout: Option<u64>,
+}
+
+/// A command hook found in one settings file. `id` addresses it as
+/// `<event>/<matcher group>/<hook>` for later edits.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HookEntry {
+    pub(crate) id: String,
+    pub(crate) scope: SettingsScope,
+    pub(crate) path: PathBuf,
+    pub(crate) event: String,
+    pub(crate) matcher: Option<String>,
+    pub(crate) command: String,
+    pub(crate) timeout: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HookTestResult {
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    pub(crate) duration_ms: u64,
+    pub(crate) timed_out: bool,
+    /// Exit code 2: the CLI would block the action and hand stderr to Claude.
+    pub(crate) blocking: bool,
+    /// Stdout parsed as JSON, for hooks that answer with a structured decision.
+    pub(crate) output: Option<Value>,
+    pub(crate) payload: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HookAddress {
+    group: usize,
+    hook: usize,
+}
+
+/// Check a hook before it is written and return it with blank fields dropped.
+pub(crate) fn validate_hook(input: HookInput) -> Result<HookInput, String> {
+    let event = input.event.trim().to_string();
+    if !HOOK_EVENTS.contains(&event.as_str()) {
+        return Err(format!("unknown hook event `{event}`"));
+    }
+    let command = input.command.trim().to_string();
+    if command.is_empty() {
+        return Err("hook command must not be empty".to_string());
+    }
+    if input.timeout == Some(0) {
+        return Err("hook timeout must be at least one second".to_string());
+    }
+    let matcher = input
+        .matcher
+        .map(|matcher| matcher.trim().to_string())
+        .filter(|matcher| !matcher.is_empty());
+    if let Some(matcher) = matcher.as_deref() {
+        validate_matcher(&event, matcher)?;
+    }
+    Ok(HookInput {
+        event,
+        matcher,
+        command,
+        timeout: input.timeout,
+    })
+}
+
+/// Tool events match tool names by regex; `PreCompact` and `SessionStart` match a fixed
+/// set of triggers; everything else ignores the matcher, so one is almost certainly a
+/// mistake.
+fn validate_matcher(event: &str, matcher: &str) -> Result<(), String> {
+    let allowed: &[&str] = match event {
+        "PreToolUse" | "PostToolUse" => {
+            if matcher == "*" {
+                return Ok(());
+            }
+            return Regex::new(&format!("^(?:{matcher})$"))
+                .map(|_| ())
+                .map_err(|err| format!("invalid matcher `{matcher}`: {err}"));
+        }
+        "PreCompact" => &["manual", "auto"],
+        "SessionStart" => &["startup", "resume", "clear", "compact"],
+        _ => return Err(format!("{event} hooks do not take a matcher")),
+    };
+    if allowed.contains(&matcher) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{event} matcher must be one of: {}",
+            allowed.join(", ")
+        ))
+    }
+}
+
+/// Command hooks defined in one settings file. Hooks of other types are skipped but
+/// keep their positions, so ids stay stable.
+pub(crate) fn list_scope_hooks(
+    scope: SettingsScope,
+    path: &Path,
+    settings: &Map<String, Value>,
+) -> Vec<HookEntry> {
+    let Some(events) = settings.get("hooks").and_then(|hooks| hooks.as_object()) else {
+        return Vec::new();
+    };
+    let mut entries = Vec::new();
+    for (event, groups) in events {
+        let Some(groups) = groups.as_array() else {
+            continue;
+        };
+        for (group_index, group) in groups.iter().enumerate() {
+            let matcher = group
+                .get("matcher")
+                .and_then(|matcher| matcher.as_str())
+                .filter(|matcher| !matcher.is_empty())
+                .map(str::to_string);
+            let Some(hooks) = group.get("hooks").and_then(|hooks| hooks.as_array()) else {
+                continue;
+            };
+            for (hook_index, hook) in hooks.iter().enumerate() {
+                if hook.get("type").and_then(|kind| kind.as_str()) != Some("command") {
+                    continue;
+                }
+                let Some(command) = hook.get("command").and_then(|command| command.as_str()) else {
+                    continue;
+                };
+                entries.push(HookEntry {
+                    id: format!("{event}/{group_index}/{hook_index}"),
+                    scope,
+                    path: path.to_path_buf(),
+                    event: event.clone(),
+                    matcher: matcher.clone(),
+                    command: command.to_string(),
+                    timeout: hook.get("timeout").and_then(|timeout| timeout.as_u64()),
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// Append a hook, joining an existing group with the same matcher when there is one.
+pub(crate) fn add_hook(
+    settings: &mut Map<String, Value>,
+    input: HookInput,
+) -> Result<String, String> {
+    let input = validate_hook(input)?;
+    let groups = event_groups(settings, &input.event)?;
+    let group_index = groups
+        .iter()
+        .position(|group| group_matcher(group) == input.matcher.as_deref())
+        .unwrap_or_else(|| {
+            let mut group = Map::new();
+            if let Some(matcher) = &input.matcher {
+                group.insert("matcher".to_string(), json!(matcher));
+            }
+            group.insert("hooks".to_string(), json!([]));
+            groups.push(Value::Object(group));
+            groups.len() - 1
+        });
+    let hooks = groups[group_index]
+        .get_mut("hooks")
+        .and_then(|hooks| hooks.as_array_mut())
+        .ok_or_else(|| format!("{} hooks are malformed", input.event))?;
+    hooks.push(hook_value(&input));
+    Ok(format!("{}/{group_index}/{}", input.event, hooks.len() - 1))
+}
+
+/// Replace the hook at `id`. Changing its event or matcher moves it to the right group.
+pub(crate) fn update_hook(
+    settings: &mut Map<String, Value>,
+    id: &str,
+    input: HookInput,
+) -> Result<String, String> {
+    let input = validate_hook(input)?;
+    let (event, address) = parse_hook_id(id)?;
+    let groups = event_groups(settings, &event)?;
+    let same_group = event == input.event
+        && groups
+            .get(address.group)
+            .is_some_and(|group| group_matcher(group) == input.matcher.as_deref());
+    if same_group {
+        let hook = hook_at(groups, address).ok_or_else(|| format!("hook `{id}` not found"))?;
+        let mut updated = hook_value(&input);
+        // Keep fields this editor does not know about
+        if let (Some(existing), Some(updated)) = (hook.as_object(), updated.as_object_mut()) {
+            for (key, value) in existing {
+                if !matches!(key.as_str(), "command" | "timeout") {
+                    updated.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+            }
+        }
+        *hook = updated;
+        return Ok(id.to_string());
+    }
+    remove_hook(settings, id)?;
+    add_hook(settings, input)
+}
+
+/// Remove the hook at `id`, pruning groups and events it leaves empty.
+pub(crate) fn remove_hook(settings: &mut Map<String, Value>, id: &str) -> Result<(), String> {
+    let (event, address) = parse_hook_id(id)?;
+    let groups = event_groups(settings, &event)?;
+    hook_at(groups, address).ok_or_else(|| format!("hook `{id}` not found"))?;
+    let group = &mut groups[address.group];
+    let hooks = group
+        .get_mut("hooks")
+        .and_then(|hooks| hooks.as_array_mut())
+        .expect("hook_at found the group");
+    hooks.remove(address.hook);
+    if hooks.is_empty() {
+        groups.remove(address.group);
+    }
+    let empty_event = groups.is_empty();
+    let hooks = settings
+        .get_mut("hooks")
+        .and_then(|hooks| hooks.as_object_mut())
+        .expect("event_groups created the hooks object");
+    if empty_event {
+        hooks.remove(&event);
+    }
+    if hooks.is_empty() {
+        settings.remove("hooks");
+    }
+    Ok(())
+}
+
+fn event_groups<'a>(
+    settings: &'a mut Map<String, Value>,
+    event: &str,
+) -> Result<&'a mut Vec<Value>, String> {
+    settings
+        .entry("hooks")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or("`hooks` is not an object")?
+        .entry(event)
+        .or_insert_with(|| json!([]))
+        .as_array_mut()
+        .ok_or_else(|| format!("`hooks.{event}` is not an array"))
+}
+
+fn group_matcher(group: &Value) -> Option<&str> {
+    group
+        .get("matcher")
+        .and_then(|matcher| matcher.as_str())
+        .filter(|matcher| !matcher.is_empty())
+}
+
+fn hook_at(groups: &mut [Value], address: HookAddress) -> Option<&mut Value> {
+    groups
+        .get_mut(address.group)?
+        .get_mut("hooks")?
+        .as_array_mut()?
+        .get_mut(address.hook)
+}
+
+fn hook_value(input: &HookInput) -> Value {
+    let mut hook = json!({ "type": "command", "command": input.command });
+    if let Some(timeout) = input.timeout {
+        hook["timeout"] = json!(timeout);
+    }
+    hook
+}
+
+fn parse_hook_id(id: &str) -> Result<(String, HookAddress), String> {
+    let invalid = || format!("invalid hook id `{id}`");
+    let mut parts = id.split('/');
+    let (Some(event), Some(group), Some(hook), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+    let group = group.parse().map_err(|_| invalid())?;
+    let hook = hook.parse().map_err(|_| invalid())?;
+    Ok((event.to_string(), HookAddress { group, hook }))
+}
+
+/// A representative payload for `event`, shaped like what the CLI pipes to hooks.
+pub(crate) fn sample_payload(event: &str, matcher: Option<&str>, cwd: &Path) -> Value {
+    let mut payload = json!({
+        "session_id": "hook-dry-run",
+        "transcript_path": "",
+        "cwd": cwd,
+        "hook_event_name": event,
+    });
+    let tool_name = matcher
+        .and_then(|matcher| matcher.split('|').next())
+        .filter(|name| name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|name| !name.is_empty())
+        .unwrap_or("Bash");
+    let tool_input = match tool_name {
+        "Bash" => json!({ "command": "echo hook dry run", "description": "Dry run" }),
+        "Read" | "Write" | "Edit" | "MultiEdit" => {
+            json!({ "file_path": cwd.join("README.md") })
+        }
+        _ => json!({}),
+    };
+    let extra = match event {
+        "PreToolUse" => json!({ "tool_name": tool_name, "tool_input": tool_input }),
+        "PostToolUse" => json!({
+            "tool_name": tool_name,
+            "tool_input": tool_input,
+            "tool_response": { "success": true },
+        }),
+        "Notification" => json!({ "message": "Claude needs your permission to use Bash" }),
+        "UserPromptSubmit" => json!({ "prompt": "Run the test suite" }),
+        "Stop" | "SubagentStop" => json!({ "stop_hook_active": false }),
+        "PreCompact" => json!({
+            "trigger": matcher.unwrap_or("manual"),
+            "custom_instructions": "",
+        }),
+        "SessionStart" => json!({ "source": matcher.unwrap_or("startup") }),
+        "SessionEnd" => json!({ "reason": "other" }),
+        _ => json!({}),
+    };
+    if let (Some(payload), Value::Object(extra)) = (payload.as_object_mut(), extra) {
+        payload.extend(extra);
+    }
+    payload
+}
+
+/// Run a hook command once with `payload` on stdin, the way the CLI would, without
+/// involving a Claude session.
+pub(crate) async fn run_hook_test(
+    command: &str,
+    cwd: &Path,
+    payload: Value,
+    limit: Duration,
+) -> Result<HookTestResult, String> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .current_dir(cwd)
+        .env("CLAUDE_PROJECT_DIR", cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let started = Instant::now();
+    let mut child = shell
+        .spawn()
+        .map_err(|err| format!("failed to run hook: {err}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let mut input = payload.to_string();
+        input.push('\n');
+        // A hook may exit without reading its input; that is not a test failure
+        let _ = stdin.write_all(input.as_bytes()).await;
+    }
+    let (exit_code, stdout, stderr, timed_out) =
+        match timeout(limit, child.wait_with_output()).await {
+            Ok(output) => {
+                let output = output.map_err(|err| err.to_string())?;
+                (
+                    output.status.code(),
+                    String::from_utf8_lossy(&output.stdout).into_owned(),
+                    String::from_utf8_lossy(&output.stderr).into_owned(),
+                    false,
+                )
+            }
+            Err(_) => (None, String::new(), String::new(), true),
+        };
+    Ok(HookTestResult {
+        blocking: exit_code == Some(2),
+        output: serde_json::from_str(stdout.trim()).ok(),
+        exit_code,
+        stdout,
+        stderr,
+        duration_ms: started.elapsed().as_millis() as u64,
+        timed_out,
+        payload,
+    })
+}
+
+fn scope_settings_path(scope: SettingsScope, claude_dir: &Path) -> Result<PathBuf, String> {
+    scope_path(scope, claude_dir)
+        .ok_or_else(|| "Unable to resolve Claude settings path".to_string())
+}
+
+/// Read, change and write back one scope's settings file.
+async fn edit_scope_hooks<T, F>(
+    state: &AppState,
+    workspace_id: &str,
+    scope: SettingsScope,
+    edit: F,
+) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Map<String, Value>) -> Result<T, String> + Send + 'static,
+{
+    let (_, claude_dir) = resolve_workspace_dirs(state, workspace_id).await?;
+    tokio::task::spawn_blocking(move || {
+        let path = scope_settings_path(scope, &claude_dir)?;
+        let mut settings = read_settings_file(&path)?;
+        let result = edit(&mut settings)?;
+        write_settings_file(&path, &settings)?;
+        Ok(result)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub(crate) async fn list_hooks(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_hooks",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let (_, claude_dir) = resolve_workspace_dirs(&state, &workspace_id).await?;
+    let hooks = tokio::task::spawn_blocking(move || {
+        let mut hooks = Vec::new();
+        for scope in [
+            SettingsScope::User,
+            SettingsScope::Project,
+            SettingsScope::Local,
+        ] {
+            let Some(path) = scope_path(scope, &claude_dir) else {
+                continue;
+            };
+            let settings = read_settings_file(&path)?;
+            hooks.extend(list_scope_hooks(scope, &path, &settings));
+        }
+        Ok::<_, String>(hooks)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    serde_json::to_value(hooks).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn add_hook_definition(
+    workspace_id: String,
+    scope: SettingsScope,
+    hook: HookInput,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "add_hook_definition",
+            json!({ "workspaceId": workspace_id, "scope": scope, "hook": hook }),
+        )
+        .await;
+    }
+
+    let id = edit_scope_hooks(&state, &workspace_id, scope, move |settings| {
+        add_hook(settings, hook)
+    })
+    .await?;
+    Ok(json!({ "id": id }))
+}
+
+#[tauri::command]
+pub(crate) async fn update_hook_definition(
+    workspace_id: String,
+    scope: SettingsScope,
+    id: String,
+    hook: HookInput,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "update_hook_definition",
+            json!({ "workspaceId": workspace_id, "scope": scope, "id": id, "hook": hook }),
+        )
+        .await;
+    }
+
+    let id = edit_scope_hooks(&state, &workspace_id, scope, move |settings| {
+        update_hook(settings, &id, hook)
+    })
+    .await?;
+    Ok(json!({ "id": id }))
+}
+
+#[tauri::command]
+pub(crate) async fn remove_hook_definition(
+    workspace_id: String,
+    scope: SettingsScope,
+    id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "remove_hook_definition",
+            json!({ "workspaceId": workspace_id, "scope": scope, "id": id }),
+        )
+        .await;
+    }
+
+    edit_scope_hooks(&state, &workspace_id, scope, move |settings| {
+        remove_hook(settings, &id)
+    })
+    .await?;
+    Ok(json!({ "ok": true }))
+}
+
+#[tauri::command]
+pub(crate) async fn test_hook(
+    workspace_id: String,
+    hook: HookInput,
+    payload: Option<Value>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "test_hook",
+            json!({ "workspaceId": workspace_id, "hook": hook, "payload": payload }),
+        )
+        .await;
+    }
+
+    let hook = validate_hook(hook)?;
+    let (project_path, _) = resolve_workspace_dirs(&state, &workspace_id).await?;
+    let payload = payload
+        .unwrap_or_else(|| sample_payload(&hook.event, hook.matcher.as_deref(), &project_path));
+    let limit = Duration::from_secs(hook.timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS));
+    let result = run_hook_test(&hook.command, &project_path, payload, limit).await?;
+    serde_json::to_value(result).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(event: &str, matcher: Option<&str>, command: &str) -> HookInput {
+        HookInput {
+            event: event.to_string(),
+            matcher: matcher.map(str::to_string),
+            command: command.to_string(),
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn validates_events_and_matchers() {
+        assert!(validate_hook(hook("PreToolUse", Some("Edit|Write"), "fmt")).is_ok());
+        assert!(validate_hook(hook("PreToolUse", Some("*"), "fmt")).is_ok());
+        assert!(validate_hook(hook("PreToolUse", Some("mcp__github__.*"), "fmt")).is_ok());
+        assert!(validate_hook(hook("PreToolUse", Some("Edit|("), "fmt")).is_err());
+        assert!(validate_hook(hook("SessionStart", Some("resume"), "fmt")).is_ok());
+        assert!(validate_hook(hook("SessionStart", Some("boot"), "fmt")).is_err());
+        assert!(validate_hook(hook("Stop", Some("Bash"), "fmt")).is_err());
+        assert!(validate_hook(hook("AfterEverything", None, "fmt")).is_err());
+        assert!(validate_hook(hook("Stop", Some("  "), "  ")).is_err());
+
+        let normalized = validate_hook(hook(" Stop ", Some(" "), " notify ")).unwrap();
+        assert_eq!(normalized, hook("Stop", None, "notify"));
+    }
+
+    #[test]
+    fn adds_updates_and_removes_hooks() {
+        let mut settings = Map::new();
+        settings.insert("model".to_string(), json!("claude-opus-4-5"));
+
+        let first = add_hook(&mut settings, hook("PreToolUse", Some("Bash"), "guard.sh")).unwrap();
+        let second = add_hook(&mut settings, hook("PreToolUse", Some("Bash"), "log.sh")).unwrap();
+        let other = add_hook(&mut settings, hook("PreToolUse", Some("Edit"), "fmt.sh")).unwrap();
+        assert_eq!(first, "PreToolUse/0/0");
+        assert_eq!(second, "PreToolUse/0/1");
+        assert_eq!(other, "PreToolUse/1/0");
+
+        settings["hooks"]["PreToolUse"][0]["hooks"][1]["statusMessage"] = json!("Logging");
+        let kept = update_hook(
+            &mut settings,
+            &second,
+            HookInput {
+                timeout: Some(5),
+                ..hook("PreToolUse", Some("Bash"), "log.sh --verbose")
+            },
+        )
+        .unwrap();
+        assert_eq!(kept, second);
+        let updated = &settings["hooks"]["PreToolUse"][0]["hooks"][1];
+        assert_eq!(updated["command"], "log.sh --verbose");
+        assert_eq!(updated["timeout"], 5);
+        assert_eq!(updated["statusMessage"], "Logging");
+
+        let moved = update_hook(&mut settings, &first, hook("Stop", None, "guard.sh")).unwrap();
+        assert_eq!(moved, "Stop/0/0");
+        let listed = list_scope_hooks(
+            SettingsScope::Project,
+            Path::new("settings.json"),
+            &settings,
+        );
+        assert_eq!(listed.len(), 3);
+
+        remove_hook(&mut settings, "PreToolUse/0/0").unwrap();
+        remove_hook(&mut settings, "PreToolUse/0/0").unwrap();
+        remove_hook(&mut settings, &moved).unwrap();
+        assert!(settings.get("hooks").is_none());
+        assert_eq!(settings["model"], "claude-opus-4-5");
+        assert!(remove_hook(&mut settings, "PreToolUse/0/0").is_err());
+        assert!(remove_hook(&mut settings, "PreToolUse").is_err());
+    }
+
+    #[test]
+    fn builds_event_specific_payloads() {
+        let cwd = Path::new("/repo");
+        let payload = sample_payload("PreToolUse", Some("Edit|Write"), cwd);
+        assert_eq!(payload["hook_event_name"], "PreToolUse");
+        assert_eq!(payload["tool_name"], "Edit");
+        assert_eq!(payload["cwd"], "/repo");
+
+        let payload = sample_payload("PreToolUse", Some(".*"), cwd);
+        assert_eq!(payload["tool_name"], "Bash");
+
+        let payload = sample_payload("SessionStart", Some("resume"), cwd);
+        assert_eq!(payload["source"], "resume");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn dry_run_pipes_payload_and_reports_blocking_exit() {
+        let cwd = std::env::temp_dir();
+        let payload = sample_payload("PreToolUse", Some("Bash"), &cwd);
+        let result = run_hook_test(
+            "grep -q '\"tool_name\":\"Bash\"' && echo blocked >&2 && exit 2",
+            &cwd,
+            payload,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.exit_code, Some(2));
+        assert!(result.blocking);
+        assert_eq!(result.stderr.trim(), "blocked");
+
+        let result = run_hook_test(
+            "echo '{\"decision\":\"approve\"}'",
+            &cwd,
+            json!({}),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.exit_code, Some(0));
+        assert_eq!(result.output, Some(json!({ "decision": "approve" })));
+
+        let result = run_hook_test("sleep 5", &cwd, json!({}), Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert!(result.timed_out);
+    }
+}
@@ -0,0 +1,716 @@
+//! Start turns from GitHub and GitLab issues and post their results back.
+//!
+//! An issue is named by its URL, `owner/repo#12`, `gitlab:group/project#12`, or just its
+//! number within a workspace's own repository. Its title, body and comments are
+//! fetched with `gh` or `glab`, rendered into a prompt template (a named one from the
+//! prompt library, or [`DEFAULT_TEMPLATE`]) and sent as the first message of a new
+//! thread in the workspace whose remote points at the issue's repository. The thread
+//! keeps a link to the issue, which `post_issue_comment` uses to comment there.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use git2::Repository;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+use uuid::Uuid;
+
+use crate::claude;
+use crate::git_utils::resolve_git_root;
+use crate::github;
+use crate::prompt_library;
+use crate::redaction;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+use crate::types::{IssueLink, IssueProvider, IssueRef, WorkspaceEntry};
+use crate::utils::now_ms;
+use crate::workspaces;
+
+const DEFAULT_GITHUB_HOST: &str = "github.com";
+const DEFAULT_GITLAB_HOST: &str = "gitlab.com";
+/// Used when no template is named. Templates get `title`, `body`, `comments`, `url`,
+/// `number` and `repo`.
+const DEFAULT_TEMPLATE: &str =
+    "Resolve this issue from {repo}: {url}\n\n# {title}\n\n{body}\n{comments}";
+
+/// How an issue was named before its workspace is known.
+#[derive(Debug, PartialEq)]
+enum IssueSpec {
+    Full(IssueRef),
+    /// A bare number, resolved against the workspace's remote.
+    Number(u64),
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct IssueComment {
+    author: String,
+    created_at: String,
+    body: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct IssueDetails {
+    title: String,
+    body: String,
+    comments: Vec<IssueComment>,
+}
+
+fn issue_url(provider: IssueProvider, host: &str, repo: &str, number: u64) -> String {
+    match provider {
+        IssueProvider::GitHub => format!("https://{host}/{repo}/issues/{number}"),
+        IssueProvider::GitLab => format!("https://{host}/{repo}/-/issues/{number}"),
+    }
+}
+
+fn issue_ref(provider: IssueProvider, host: &str, repo: &str, number: u64) -> IssueRef {
+    IssueRef {
+        provider,
+        host: host.to_string(),
+        repo: repo.to_string(),
+        number,
+        url: issue_url(provider, host, repo, number),
+    }
+}
+
+/// Leading digits of `text`, ignoring anything after them (`12#note_3`, `12?foo`).
+fn leading_number(text: &str) -> Option<u64> {
+    let digits: String = text.chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+fn parse_issue_spec(input: &str) -> Result<IssueSpec, String> {
+    let input = input.trim();
+    let invalid = || format!("not an issue reference: {input}");
+    if let Some(number) = input.strip_prefix('#').unwrap_or(input).parse().ok() {
+        return Ok(IssueSpec::Number(number));
+    }
+    if let Some(rest) = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))
+    {
+        let (host, path) = rest.split_once('/').ok_or_else(invalid)?;
+        if let Some((repo, number)) = path.split_once("/-/issues/") {
+            let number = leading_number(number).ok_or_else(invalid)?;
+            return Ok(IssueSpec::Full(issue_ref(
+                IssueProvider::GitLab,
+                host,
+                repo,
+                number,
+            )));
+        }
+        let segments: Vec<&str> = path.split('/').collect();
+        if let [owner, repo, "issues", number, ..] = segments.as_slice() {
+            let number = leading_number(number).ok_or_else(invalid)?;
+            let repo = format!("{owner}/{repo}");
+            return Ok(IssueSpec::Full(issue_ref(
+                IssueProvider::GitHub,
+                host,
+                &repo,
+                number,
+            )));
+        }
+        return Err(invalid());
+    }
+    let (provider, host, rest) = match input.strip_prefix("gitlab:") {
+        Some(rest) => (IssueProvider::GitLab, DEFAULT_GITLAB_HOST, rest),
+        None => (IssueProvider::GitHub, DEFAULT_GITHUB_HOST, input),
+    };
+    let (repo, number) = rest.rsplit_once('#').ok_or_else(invalid)?;
+    let number = number.parse().map_err(|_| invalid())?;
+    if repo.split('/').filter(|part| !part.is_empty()).count() < 2 {
+        return Err(invalid());
+    }
+    Ok(IssueSpec::Full(issue_ref(provider, host, repo, number)))
+}
+
+/// Host and repository path of a git remote URL, for HTTPS, `ssh://` and `git@host:`
+/// remotes.
+fn remote_slug(url: &str) -> Option<(String, String)> {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let (host, path) = match url.split_once("://") {
+        Some((_, rest)) => {
+            let (authority, path) = rest.split_once('/')?;
+            let host = authority.rsplit('@').next()?;
+            (host.split(':').next()?, path)
+        }
+        None => {
+            let (authority, path) = url.split_once(':')?;
+            (authority.rsplit('@').next()?, path)
+        }
+    };
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some((
+        host.to_lowercase(),
+        path.trim_start_matches('/').to_string(),
+    ))
+}
+
+/// The workspace repository's remotes as `(host, path)`, `origin` first.
+fn workspace_remotes(entry: &WorkspaceEntry) -> Vec<(String, String)> {
+    let Ok(repo) = resolve_git_root(entry)
+        .and_then(|root| Repository::open(root).map_err(|err| err.to_string()))
+    else {
+        return Vec::new();
+    };
+    let Ok(names) = repo.remotes() else {
+        return Vec::new();
+    };
+    let mut names: Vec<&str> = names.iter().flatten().collect();
+    names.sort_by_key(|name| *name != "origin");
+    names
+        .into_iter()
+        .filter_map(|name| repo.find_remote(name).ok())
+        .filter_map(|remote| remote.url().and_then(remote_slug))
+        .collect()
+}
+
+/// The issue a spec names, with bare numbers taken from the workspace's remote.
+fn resolve_issue(spec: IssueSpec, entry: Option<&WorkspaceEntry>) -> Result<IssueRef, String> {
+    match spec {
+        IssueSpec::Full(issue) => Ok(issue),
+        IssueSpec::Number(number) => {
+            let entry = entry.ok_or("name the workspace to look an issue number up in")?;
+            let (host, repo) = workspace_remotes(entry)
+                .into_iter()
+                .next()
+                .ok_or("No git remote configured.")?;
+            let provider = if host.contains("gitlab") {
+                IssueProvider::GitLab
+            } else {
+                IssueProvider::GitHub
+            };
+            Ok(issue_ref(provider, &host, &repo, number))
+        }
+    }
+}
+
+/// The first unarchived workspace whose remote points at the issue's repository.
+fn mapped_workspace<'a>(
+    issue: &IssueRef,
+    entries: impl Iterator<Item = &'a WorkspaceEntry>,
+) -> Option<WorkspaceEntry> {
+    let mut entries: Vec<&WorkspaceEntry> = entries
+        .filter(|entry| entry.archived_at.is_none())
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries
+        .into_iter()
+        .find(|entry| {
+            workspace_remotes(entry).iter().any(|(host, repo)| {
+                host.eq_ignore_ascii_case(&issue.host) && repo.eq_ignore_ascii_case(&issue.repo)
+            })
+        })
+        .cloned()
+}
+
+/// `gh --repo` takes `owner/repo`, prefixed with the host outside github.com.
+fn gh_repo(issue: &IssueRef) -> String {
+    if issue.host == DEFAULT_GITHUB_HOST {
+        issue.repo.clone()
+    } else {
+        format!("{}/{}", issue.host, issue.repo)
+    }
+}
+
+fn gitlab_issue_path(issue: &IssueRef) -> String {
+    format!(
+        "projects/{}/issues/{}",
+        issue.repo.replace('/', "%2F"),
+        issue.number
+    )
+}
+
+fn text_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn parse_github_issue(value: &Value) -> IssueDetails {
+    let comments = value
+        .get("comments")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|comment| IssueComment {
+            author: comment
+                .pointer("/author/login")
+                .and_then(Value::as_str)
+                .unwrap_or("ghost")
+                .to_string(),
+            created_at: text_field(comment, "createdAt"),
+            body: text_field(comment, "body"),
+        })
+        .collect();
+    IssueDetails {
+        title: text_field(value, "title"),
+        body: text_field(value, "body"),
+        comments,
+    }
+}
+
+fn parse_gitlab_issue(issue: &Value, notes: &Value) -> IssueDetails {
+    let comments = notes
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|note| !note.get("system").and_then(Value::as_bool).unwrap_or(false))
+        .map(|note| IssueComment {
+            author: note
+                .pointer("/author/username")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown")
+                .to_string(),
+            created_at: text_field(note, "created_at"),
+            body: text_field(note, "body"),
+        })
+        .collect();
+    IssueDetails {
+        title: text_field(issue, "title"),
+        body: text_field(issue, "description"),
+        comments,
+    }
+}
+
+fn parse_json(output: &str) -> Result<Value, String> {
+    serde_json::from_str(output).map_err(|err| err.to_string())
+}
+
+async fn fetch_issue(repo_root: &Path, issue: &IssueRef) -> Result<IssueDetails, String> {
+    match issue.provider {
+        IssueProvider::GitHub => {
+            let number = issue.number.to_string();
+            let output = github::run_cli(
+                repo_root,
+                "gh",
+                &[
+                    "issue",
+                    "view",
+                    &number,
+                    "--repo",
+                    &gh_repo(issue),
+                    "--json",
+                    "title,body,comments",
+                ],
+            )
+            .await?;
+            Ok(parse_github_issue(&parse_json(&output)?))
+        }
+        IssueProvider::GitLab => {
+            let path = gitlab_issue_path(issue);
+            let notes_path = format!("{path}/notes?sort=asc&per_page=100");
+            let details = github::run_cli(
+                repo_root,
+                "glab",
+                &["api", "--hostname", &issue.host, &path],
+            )
+            .await?;
+            let notes = github::run_cli(
+                repo_root,
+                "glab",
+                &["api", "--hostname", &issue.host, &notes_path],
+            )
+            .await?;
+            Ok(parse_gitlab_issue(
+                &parse_json(&details)?,
+                &parse_json(&notes)?,
+            ))
+        }
+    }
+}
+
+/// Comment on the issue and return the comment's URL.
+async fn comment_on_issue(
+    repo_root: &Path,
+    issue: &IssueRef,
+    body: &str,
+) -> Result<String, String> {
+    match issue.provider {
+        IssueProvider::GitHub => {
+            let body_path =
+                std::env::temp_dir().join(format!("claude-issue-comment-{}.md", Uuid::new_v4()));
+            std::fs::write(&body_path, body).map_err(|err| err.to_string())?;
+            let number = issue.number.to_string();
+            let body_file = body_path.to_string_lossy().to_string();
+            let output = github::run_cli(
+                repo_root,
+                "gh",
+                &[
+                    "issue",
+                    "comment",
+                    &number,
+                    "--repo",
+                    &gh_repo(issue),
+                    "--body-file",
+                    &body_file,
+                ],
+            )
+            .await;
+            let _ = std::fs::remove_file(&body_path);
+            Ok(github::last_url(&output?).unwrap_or_else(|| issue.url.clone()))
+        }
+        IssueProvider::GitLab => {
+            let notes_path = format!("{}/notes", gitlab_issue_path(issue));
+            let field = format!("body={body}");
+            let output = github::run_cli(
+                repo_root,
+                "glab",
+                &[
+                    "api",
+                    "--hostname",
+                    &issue.host,
+                    "--method",
+                    "POST",
+                    &notes_path,
+                    "--raw-field",
+                    &field,
+                ],
+            )
+            .await?;
+            let note = parse_json(&output)?;
+            Ok(match note.get("id").and_then(Value::as_u64) {
+                Some(id) => format!("{}#note_{id}", issue.url),
+                None => issue.url.clone(),
+            })
+        }
+    }
+}
+
+/// Template values for an issue.
+fn template_values(issue: &IssueRef, details: &IssueDetails) -> BTreeMap<String, String> {
+    let mut comments = String::new();
+    if !details.comments.is_empty() {
+        comments.push_str("\n## Comments\n");
+        for comment in &details.comments {
+            comments.push_str(&format!(
+                "\n**{}** ({}):\n\n{}\n",
+                comment.author,
+                comment.created_at,
+                comment.body.trim()
+            ));
+        }
+    }
+    let body = match details.body.trim() {
+        "" => "(no description)".to_string(),
+        body => body.to_string(),
+    };
+    BTreeMap::from([
+        ("title".to_string(), details.title.trim().to_string()),
+        ("body".to_string(), body),
+        ("comments".to_string(), comments),
+        ("url".to_string(), issue.url.clone()),
+        ("number".to_string(), issue.number.to_string()),
+        ("repo".to_string(), issue.repo.clone()),
+    ])
+}
+
+/// Fetch an issue and send it as the first message of a new thread.
+///
+/// Without `workspace_id` the issue goes to the workspace whose remote points at its
+/// repository. `template` names a prompt library template to render the issue into.
+#[tauri::command]
+pub(crate) async fn start_issue_turn(
+    issue: String,
+    workspace_id: Option<String>,
+    template: Option<String>,
+    model: Option<String>,
+    access_mode: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<IssueLink, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "start_issue_turn",
+            json!({
+                "issue": issue,
+                "workspaceId": workspace_id,
+                "template": template,
+                "model": model,
+                "accessMode": access_mode,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let spec = parse_issue_spec(&issue)?;
+    let entries: Vec<WorkspaceEntry> = state.workspaces.lock().await.values().cloned().collect();
+    let (entry, issue) = tokio::task::spawn_blocking(move || {
+        let named = match &workspace_id {
+            Some(id) => Some(
+                entries
+                    .iter()
+                    .find(|entry| &entry.id == id)
+                    .cloned()
+                    .ok_or("workspace not found")?,
+            ),
+            None => None,
+        };
+        let issue = resolve_issue(spec, named.as_ref())?;
+        let entry = match named {
+            Some(entry) => entry,
+            None => mapped_workspace(&issue, entries.iter()).ok_or_else(|| {
+                format!(
+                    "No workspace has a remote for {}; name the workspace to use.",
+                    issue.repo
+                )
+            })?,
+        };
+        Ok::<_, String>((entry, issue))
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+
+    let repo_root = resolve_git_root(&entry)?;
+    let details = fetch_issue(&repo_root, &issue).await?;
+    let values = template_values(&issue, &details);
+    let text = match template.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => {
+            prompt_library::render_named(&state, &entry.id, name, &values).await?
+        }
+        _ => prompt_library::render(DEFAULT_TEMPLATE, &values)?,
+    };
+
+    let connected = state.sessions.lock().await.contains_key(&entry.id);
+    if !connected {
+        workspaces::connect_workspace(entry.id.clone(), app.state::<AppState>(), app.clone())
+            .await?;
+    }
+    let thread =
+        claude::start_thread(entry.id.clone(), app.state::<AppState>(), app.clone()).await?;
+    let thread_id = thread
+        .pointer("/thread/id")
+        .and_then(Value::as_str)
+        .ok_or("no thread was started")?
+        .to_string();
+    let sent = claude::send_user_message(
+        entry.id.clone(),
+        thread_id.clone(),
+        text,
+        model,
+        None,
+        access_mode,
+        None,
+        None,
+        None,
+        None,
+        None,
+        app.state::<AppState>(),
+        app.clone(),
+    )
+    .await?;
+
+    let link = IssueLink {
+        workspace_id: entry.id,
+        thread_id,
+        turn_id: sent
+            .pointer("/result/turn/id")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        issue,
+        created_at: now_ms(),
+        commented_at: None,
+        comment_url: None,
+    };
+    let path = state.db_path.clone();
+    let saved = link.clone();
+    tokio::task::spawn_blocking(move || storage::save_issue_link(&path, &saved))
+        .await
+        .map_err(|err| err.to_string())??;
+    Ok(link)
+}
+
+/// Threads of a workspace that were started from issues, newest first.
+#[tauri::command]
+pub(crate) async fn list_issue_links(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<IssueLink>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_issue_links",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let path = state.db_path.clone();
+    tokio::task::spawn_blocking(move || storage::read_issue_links(&path, &workspace_id, None))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// Comment on the issue a thread was started from, with `body` or the reply of the turn
+/// the issue started.
+#[tauri::command]
+pub(crate) async fn post_issue_comment(
+    workspace_id: String,
+    thread_id: String,
+    body: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<IssueLink, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "post_issue_comment",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id, "body": body }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let entry = state
+        .workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not found")?;
+    let path = state.db_path.clone();
+    let (mut link, reply) = tokio::task::spawn_blocking(move || {
+        let link = storage::read_issue_links(&path, &entry.id, Some(&thread_id))?
+            .into_iter()
+            .next()
+            .ok_or("this thread was not started from an issue")?;
+        let reply = match body.filter(|body| !body.trim().is_empty()) {
+            Some(body) => body,
+            None => {
+                let turn_id = link.turn_id.clone().unwrap_or_default();
+                github::load_turn(&path, &entry, &thread_id, &turn_id)?.summary
+            }
+        };
+        Ok::<_, String>((link, reply))
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    if reply.trim().is_empty() {
+        return Err("the thread has no reply to post yet".to_string());
+    }
+
+    let entry = state
+        .workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not found")?;
+    let repo_root = resolve_git_root(&entry)?;
+    let reply = redaction::redact_text(&reply).into_owned();
+    let url = comment_on_issue(&repo_root, &link.issue, &reply).await?;
+    link.commented_at = Some(now_ms());
+    link.comment_url = Some(url);
+    let path = state.db_path.clone();
+    let saved = link.clone();
+    tokio::task::spawn_blocking(move || storage::save_issue_link(&path, &saved))
+        .await
+        .map_err(|err| err.to_string())??;
+    Ok(link)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full(provider: IssueProvider, host: &str, repo: &str, number: u64) -> IssueSpec {
+        IssueSpec::Full(issue_ref(provider, host, repo, number))
+    }
+
+    #[test]
+    fn parses_issue_references() {
+        assert_eq!(parse_issue_spec("#12"), Ok(IssueSpec::Number(12)));
+        assert_eq!(
+            parse_issue_spec("https://github.com/acme/web/issues/12#issuecomment-1"),
+            Ok(full(IssueProvider::GitHub, "github.com", "acme/web", 12))
+        );
+        assert_eq!(
+            parse_issue_spec("https://gitlab.example.com/group/sub/app/-/issues/7"),
+            Ok(full(
+                IssueProvider::GitLab,
+                "gitlab.example.com",
+                "group/sub/app",
+                7
+            ))
+        );
+        assert_eq!(
+            parse_issue_spec("acme/web#3"),
+            Ok(full(IssueProvider::GitHub, "github.com", "acme/web", 3))
+        );
+        assert_eq!(
+            parse_issue_spec("gitlab:group/app#4"),
+            Ok(full(IssueProvider::GitLab, "gitlab.com", "group/app", 4))
+        );
+        assert!(parse_issue_spec("web#3").is_err());
+        assert!(parse_issue_spec("https://github.com/acme/web/pull/3").is_err());
+    }
+
+    #[test]
+    fn reads_remote_urls() {
+        let slug = |host: &str, path: &str| Some((host.to_string(), path.to_string()));
+        assert_eq!(
+            remote_slug("git@github.com:acme/web.git"),
+            slug("github.com", "acme/web")
+        );
+        assert_eq!(
+            remote_slug("https://token@GitLab.example.com/group/app.git/"),
+            slug("gitlab.example.com", "group/app")
+        );
+        assert_eq!(
+            remote_slug("ssh://git@gitlab.com:2222/group/sub/app"),
+            slug("gitlab.com", "group/sub/app")
+        );
+        assert_eq!(remote_slug("/srv/repos/app"), None);
+    }
+
+    #[test]
+    fn renders_the_issue_into_the_default_template() {
+        let issue = issue_ref(IssueProvider::GitHub, "github.com", "acme/web", 12);
+        let details = parse_github_issue(&json!({
+            "title": "Login loops",
+            "body": "Redirects forever.",
+            "comments": [
+                { "author": { "login": "bob" }, "createdAt": "2026-01-02", "body": "Same here\n" }
+            ],
+        }));
+        let text =
+            prompt_library::render(DEFAULT_TEMPLATE, &template_values(&issue, &details)).unwrap();
+        assert_eq!(
+            text,
+            "Resolve this issue from acme/web: https://github.com/acme/web/issues/12\n\n\
+             # Login loops\n\nRedirects forever.\n\n## Comments\n\n\
+             **bob** (2026-01-02):\n\nSame here\n"
+        );
+    }
+
+    #[test]
+    fn skips_gitlab_system_notes() {
+        let details = parse_gitlab_issue(
+            &json!({ "title": "Crash", "description": null }),
+            &json!([
+                { "body": "changed the description", "system": true, "author": { "username": "bot" } },
+                { "body": "Repro attached", "system": false, "author": { "username": "ann" }, "created_at": "t" }
+            ]),
+        );
+        assert_eq!(details.body, "");
+        assert_eq!(
+            details.comments,
+            [IssueComment {
+                author: "ann".to_string(),
+                created_at: "t".to_string(),
+                body: "Repro attached".to_string(),
+            }]
+        );
+    }
+}
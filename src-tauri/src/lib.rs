@@ -1,32 +1,80 @@
 use tauri::Manager;
 
+mod activity;
+mod agents;
+mod annotations;
+mod api_tokens;
+mod attach;
+mod attachments;
+mod autostart;
 mod backend;
+mod batch;
+mod budget;
 mod claude;
-mod claude_tasks;
-mod claude_home;
 mod claude_config;
-mod task_manager;
+mod claude_home;
+mod claude_md;
+mod claude_settings;
+mod claude_tasks;
+mod cli_manager;
+mod config_bundle;
+mod context_pack;
+mod credentials;
+mod daemon;
 #[cfg(not(target_os = "windows"))]
 #[path = "dictation.rs"]
 mod dictation;
 #[cfg(target_os = "windows")]
 #[path = "dictation_stub.rs"]
 mod dictation;
+mod disk_usage;
+mod doctor;
 mod event_sink;
+mod export;
+mod file_index;
 mod git;
 mod git_utils;
+mod github;
+mod hooks;
+mod issues;
 mod local_usage;
+mod mcp;
 mod menu;
+mod notifications;
+mod operations;
+mod outbox;
+mod pipeline;
+mod prompt_library;
 mod prompts;
+mod proxy;
+mod recovery;
+mod redaction;
 mod remote_backend;
+mod replay;
+mod retention;
+mod scheduler;
+mod search;
 mod settings;
+mod shutdown;
+mod slash_commands;
 mod state;
-mod terminal;
-mod window;
 mod storage;
+mod storage_encryption;
+mod tail;
+mod task_manager;
 mod task_watcher;
+mod telemetry;
+mod terminal;
+mod token_estimate;
+mod transcripts;
 mod types;
+mod usage;
 mod utils;
+mod verification;
+mod webhooks;
+mod window;
+mod workspace_discovery;
+mod workspace_tags;
 mod workspaces;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -48,6 +96,15 @@ pub fn run() {
             let state = state::AppState::load(&app.handle());
             app.manage(state);
             app.manage(task_watcher::TaskWatcherState::default());
+            claude::spawn_idle_session_reaper(app.handle().clone());
+            claude::spawn_session_health_monitor(app.handle().clone());
+            claude::spawn_turn_watchdog(app.handle().clone());
+            claude::spawn_interaction_watchdog(app.handle().clone());
+            claude::spawn_resource_monitor(app.handle().clone());
+            outbox::spawn_outbox_flusher(app.handle().clone());
+            scheduler::spawn_scheduler(app.handle().clone());
+            retention::spawn_retention_sweeper(app.handle().clone());
+            autostart::spawn_pinned_autostart(app.handle().clone());
             #[cfg(desktop)]
             {
                 app.handle()
@@ -64,34 +121,121 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             settings::get_app_settings,
             settings::update_app_settings,
+            retention::apply_retention_policy,
+            disk_usage::get_disk_usage,
+            disk_usage::run_disk_cleanup,
+            config_bundle::export_config,
+            config_bundle::import_config,
             menu::menu_set_accelerators,
             claude::claude_doctor,
+            doctor::run_doctor,
+            claude::check_provider_connectivity,
+            cli_manager::list_claude_cli_installations,
+            cli_manager::check_claude_cli_updates,
+            cli_manager::update_claude_cli,
             workspaces::list_workspaces,
             workspaces::is_workspace_path_dir,
+            workspace_discovery::discover_workspaces,
             workspaces::add_workspace,
             workspaces::add_clone,
             workspaces::add_worktree,
             workspaces::remove_workspace,
+            workspaces::archive_workspace,
+            workspaces::unarchive_workspace,
+            workspaces::set_workspace_pinned,
             workspaces::remove_worktree,
             workspaces::rename_worktree,
             workspaces::rename_worktree_upstream,
             workspaces::apply_worktree_changes,
             workspaces::update_workspace_settings,
             workspaces::update_workspace_claude_bin,
+            workspaces::update_workspace_model,
+            workspace_tags::aggregate_workspaces,
             claude::start_thread,
             claude::send_user_message,
             claude::turn_interrupt,
+            claude::list_thread_owners,
+            claude::take_over_thread,
+            claude::list_turn_queue,
+            claude::session_health,
+            claude::suspend_workspace_sessions,
+            claude::resume_workspace_sessions,
+            claude::focus_workspace,
+            claude::list_workspace_threads,
+            claude::cancel_queued_prompt,
+            claude::reorder_queued_prompt,
+            outbox::list_pending_messages,
+            outbox::cancel_pending_message,
+            outbox::flush_pending_messages,
             claude::start_review,
             claude::respond_to_server_request,
+            claude::respond_to_permission_request,
+            claude::list_pending_interactions,
+            claude::list_pending_plans,
+            claude::approve_plan,
+            claude::reject_plan,
+            claude::set_permission_preset,
             claude::remember_approval_rule,
+            claude_settings::get_claude_settings,
+            claude_settings::update_claude_settings,
+            mcp::list_mcp_servers,
+            mcp::get_mcp_status,
+            hooks::list_hooks,
+            hooks::add_hook_definition,
+            hooks::update_hook_definition,
+            hooks::remove_hook_definition,
+            hooks::test_hook,
+            claude_md::list_claude_md_files,
+            claude_md::read_claude_md,
+            claude_md::write_claude_md,
+            tail::start_transcript_tail,
+            tail::stop_transcript_tail,
+            attach::list_external_sessions,
+            attach::attach_external_session,
+            attach::detach_external_session,
             claude::get_commit_message_prompt,
             claude::generate_commit_message,
             claude::generate_run_metadata,
             claude::resume_thread,
+            claude::fork_session,
+            claude::list_session_forks,
+            claude::resume_session,
+            claude::list_thread_sessions,
+            claude::get_turn_timeline,
+            claude::list_compactions,
+            claude::get_session_logs,
+            claude::get_session_resources,
+            claude::get_turn_diff,
+            claude::get_turn_edits,
+            claude::list_bash_decisions,
+            activity::get_activity,
+            operations::cancel_operation,
+            operations::list_operations,
+            event_sink::get_event_stats,
+            export::export_session,
+            search::search_transcripts,
+            annotations::annotate,
+            annotations::list_annotations,
+            annotations::search_annotations,
+            budget::get_budget_status,
+            budget::override_budget,
+            replay::list_recordings,
+            replay::replay_session,
+            recovery::list_orphaned_sessions,
+            recovery::adopt_orphaned_session,
+            recovery::kill_orphaned_session,
+            redaction::reveal_redacted_secret,
+            storage_encryption::get_storage_encryption,
+            storage_encryption::set_storage_encryption,
+            credentials::get_workspace_credentials,
+            credentials::set_workspace_credentials,
+            proxy::test_proxy_connectivity,
             claude::list_threads,
+            transcripts::list_workspace_sessions,
             claude::archive_thread,
             claude::collaboration_mode_list,
             workspaces::connect_workspace,
@@ -114,7 +258,12 @@ pub fn run() {
             git::get_github_pull_requests,
             git::get_github_pull_request_diff,
             git::get_github_pull_request_comments,
+            github::create_pr_from_turn,
+            issues::start_issue_turn,
+            issues::list_issue_links,
+            issues::post_issue_comment,
             workspaces::list_workspace_files,
+            file_index::complete_paths,
             workspaces::read_workspace_file,
             workspaces::open_workspace_in,
             git::list_git_branches,
@@ -122,6 +271,7 @@ pub fn run() {
             git::create_git_branch,
             claude::model_list,
             claude::global_rate_limits,
+            claude::get_rate_limit_status,
             claude::skills_list,
             prompts::prompts_list,
             prompts::prompts_create,
@@ -133,6 +283,7 @@ pub fn run() {
             terminal::terminal_open,
             terminal::terminal_write,
             terminal::terminal_resize,
+            terminal::terminal_kill,
             terminal::terminal_close,
             dictation::dictation_model_status,
             dictation::dictation_download_model,
@@ -142,6 +293,44 @@ pub fn run() {
             dictation::dictation_stop,
             dictation::dictation_cancel,
             local_usage::local_usage_snapshot,
+            usage::usage_turns,
+            usage::usage_daily,
+            usage::usage_workspaces,
+            usage::get_turn_metrics,
+            usage::get_usage_report,
+            usage::export_usage,
+            prompt_library::prompt_templates_list,
+            prompt_library::prompt_template_save,
+            prompt_library::prompt_template_delete,
+            prompt_library::prompt_template_render,
+            prompt_library::prompt_template_send,
+            context_pack::context_packs_list,
+            context_pack::context_pack_save,
+            context_pack::context_pack_delete,
+            context_pack::context_pack_preview,
+            token_estimate::estimate_tokens,
+            slash_commands::list_slash_commands,
+            slash_commands::run_slash_command,
+            agents::list_subagents,
+            agents::save_subagent,
+            agents::delete_subagent,
+            agents::run_subagent,
+            scheduler::schedules_list,
+            scheduler::schedule_save,
+            scheduler::schedule_delete,
+            scheduler::schedule_set_enabled,
+            scheduler::schedule_run_now,
+            scheduler::schedule_runs_list,
+            batch::batch_run,
+            batch::batches_list,
+            batch::batch_cancel,
+            pipeline::create_pipeline,
+            pipeline::list_pipelines,
+            pipeline::cancel_pipeline,
+            api_tokens::list_api_tokens,
+            api_tokens::issue_api_token,
+            api_tokens::revoke_api_token,
+            api_tokens::list_api_audit_log,
             claude_tasks::get_claude_tasks,
             task_watcher::task_watcher_start,
             task_watcher::task_watcher_stop,
@@ -152,6 +341,142 @@ pub fn run() {
             task_manager::task_delete,
             task_manager::task_lists_available
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(context())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                if let Some(state) = app.try_state::<state::AppState>() {
+                    tauri::async_runtime::block_on(shutdown::shutdown(&state));
+                }
+            }
+        });
+}
+
+/// Entry point of `claude_code_monitor_mock_claude`, a fake `claude` that replays
+/// fixtures; see [`backend::mock_cli`].
+pub fn run_mock_claude() {
+    std::process::exit(backend::mock_cli::run());
+}
+
+/// Entry point of `claude_code_monitor_daemon`: the same backend without a window,
+/// driven over the JSON-RPC control socket in [`daemon`], running a single turn with
+/// `run` (see [`daemon::ci`]), or installing itself as a login service with
+/// `install-service` (see [`daemon::service`]).
+pub fn run_daemon() {
+    let mut args = std::env::args().skip(1).peekable();
+    match args.peek().map(String::as_str) {
+        Some("run") => {
+            args.next();
+            run_ci_turn(args);
+            return;
+        }
+        Some("install-service") => {
+            args.next();
+            let outcome =
+                daemon::service::parse_service_args(args).and_then(daemon::service::install);
+            finish_service_command(outcome);
+        }
+        Some("uninstall-service") => finish_service_command(daemon::service::uninstall()),
+        _ => {}
+    }
+    let options = match daemon::options_from_env() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut context = context();
+    context.config_mut().app.windows.clear();
+
+    tauri::Builder::default()
+        .setup(move |app| {
+            let state = state::AppState::load(&app.handle());
+            app.manage(state);
+            app.manage(task_watcher::TaskWatcherState::default());
+            claude::spawn_idle_session_reaper(app.handle().clone());
+            claude::spawn_session_health_monitor(app.handle().clone());
+            claude::spawn_turn_watchdog(app.handle().clone());
+            claude::spawn_interaction_watchdog(app.handle().clone());
+            claude::spawn_resource_monitor(app.handle().clone());
+            outbox::spawn_outbox_flusher(app.handle().clone());
+            scheduler::spawn_scheduler(app.handle().clone());
+            retention::spawn_retention_sweeper(app.handle().clone());
+            daemon::start(app.handle(), options)?;
+            Ok(())
+        })
+        .build(context)
+        .expect("error while building claude_code_monitor_daemon")
+        .run(|app, event| match event {
+            // With no windows Tauri would exit right away; only explicit exits count
+            tauri::RunEvent::ExitRequested { api, code, .. } if code.is_none() => {
+                api.prevent_exit();
+            }
+            tauri::RunEvent::Exit => {
+                if let Some(state) = app.try_state::<state::AppState>() {
+                    tauri::async_runtime::block_on(shutdown::shutdown(&state));
+                }
+                daemon::shutdown(app);
+            }
+            _ => {}
+        });
+}
+
+fn finish_service_command(outcome: Result<String, String>) -> ! {
+    match outcome {
+        Ok(message) => {
+            println!("{message}");
+            std::process::exit(0);
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `claude_code_monitor_daemon run`: one turn with the saved settings and budgets,
+/// its report on stdout and its outcome as the exit code.
+fn run_ci_turn(args: impl Iterator<Item = String>) {
+    let options = match daemon::ci::parse_run_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(daemon::ci::EXIT_USAGE);
+        }
+    };
+
+    let mut context = context();
+    context.config_mut().app.windows.clear();
+
+    tauri::Builder::default()
+        .setup(move |app| {
+            let state = state::AppState::load(&app.handle());
+            app.manage(state);
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let code = daemon::ci::run(&handle, options).await;
+                handle.exit(code);
+            });
+            Ok(())
+        })
+        .build(context)
+        .expect("error while building claude_code_monitor_daemon")
+        .run(|app, event| match event {
+            // Without windows Tauri would exit right away; wait for the turn's exit code
+            tauri::RunEvent::ExitRequested { api, code, .. } if code.is_none() => {
+                api.prevent_exit();
+            }
+            tauri::RunEvent::Exit => {
+                if let Some(state) = app.try_state::<state::AppState>() {
+                    tauri::async_runtime::block_on(shutdown::shutdown(&state));
+                }
+            }
+            _ => {}
+        });
+}
+
+fn context() -> tauri::Context<tauri::Wry> {
+    tauri::generate_context!()
 }
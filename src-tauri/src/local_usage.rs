@@ -58,15 +58,17 @@ pub(crate) async fn local_usage_snapshot(
             Some(PathBuf::from(trimmed))
         }
     });
-    let snapshot = tokio::task::spawn_blocking(move || {
-        scan_local_usage(days, workspace_path.as_deref())
-    })
-    .await
-    .map_err(|err| err.to_string())??;
+    let snapshot =
+        tokio::task::spawn_blocking(move || scan_local_usage(days, workspace_path.as_deref()))
+            .await
+            .map_err(|err| err.to_string())??;
     Ok(snapshot)
 }
 
-fn scan_local_usage(days: u32, workspace_path: Option<&Path>) -> Result<LocalUsageSnapshot, String> {
+fn scan_local_usage(
+    days: u32,
+    workspace_path: Option<&Path>,
+) -> Result<LocalUsageSnapshot, String> {
     let updated_at = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -81,7 +83,9 @@ fn scan_local_usage(days: u32, workspace_path: Option<&Path>) -> Result<LocalUsa
 
     let workspace_key = workspace_path.map(|path| path.to_string_lossy().to_string());
     let cache = LOCAL_USAGE_CACHE.get_or_init(|| Mutex::new(LocalUsageCache::default()));
-    let mut cache = cache.lock().map_err(|_| "local usage cache lock poisoned")?;
+    let mut cache = cache
+        .lock()
+        .map_err(|_| "local usage cache lock poisoned")?;
     if cache.days != days || cache.workspace_key != workspace_key {
         cache.days = days;
         cache.workspace_key = workspace_key;
@@ -415,10 +419,11 @@ fn scan_file(
                 continue;
             }
 
-            let info = payload.and_then(|payload| payload.get("info")).and_then(|v| v.as_object());
+            let info = payload
+                .and_then(|payload| payload.get("info"))
+                .and_then(|v| v.as_object());
             let (input, cached, output, used_total) = if let Some(info) = info {
-                if let Some(total) =
-                    find_usage_map(info, &["total_token_usage", "totalTokenUsage"])
+                if let Some(total) = find_usage_map(info, &["total_token_usage", "totalTokenUsage"])
                 {
                     (
                         read_i64(total, &["input_tokens", "inputTokens"]),
@@ -471,7 +476,11 @@ fn scan_file(
                     cached: (cached - prev.cached).max(0),
                     output: (output - prev.output).max(0),
                 };
-                previous_totals = Some(UsageTotals { input, cached, output });
+                previous_totals = Some(UsageTotals {
+                    input,
+                    cached,
+                    output,
+                });
             } else {
                 // Some streams emit `last_token_usage` deltas between `total_token_usage` snapshots.
                 // Treat those as already-counted to avoid double-counting when the next total arrives.
@@ -576,7 +585,11 @@ fn find_usage_map<'a>(
 fn read_i64(map: &serde_json::Map<String, Value>, keys: &[&str]) -> i64 {
     keys.iter()
         .find_map(|key| map.get(*key))
-        .and_then(|value| value.as_i64().or_else(|| value.as_f64().map(|value| value as i64)))
+        .and_then(|value| {
+            value
+                .as_i64()
+                .or_else(|| value.as_f64().map(|value| value as i64))
+        })
         .unwrap_or(0)
 }
 
@@ -0,0 +1,404 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, State};
+
+use crate::backend::stream_events::McpServerStatus;
+use crate::claude_home::resolve_home_dir;
+use crate::claude_settings::{
+    merge_settings, read_settings_file, resolve_workspace_dirs, scope_path, SettingsScope,
+};
+use crate::remote_backend;
+use crate::state::AppState;
+
+const MCP_JSON_FILE: &str = ".mcp.json";
+const USER_CONFIG_FILE: &str = ".claude.json";
+
+/// Where a server definition came from, highest precedence first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum McpSource {
+    /// `projects.<path>.mcpServers` in `~/.claude.json` (`claude mcp add --scope local`).
+    Local,
+    /// The project's checked-in `.mcp.json`.
+    McpJson,
+    /// Top-level `mcpServers` in `~/.claude.json` (`claude mcp add --scope user`).
+    User,
+    /// `mcpServers` in one of the `settings.json` scopes.
+    LocalSettings,
+    ProjectSettings,
+    UserSettings,
+}
+
+/// Whether the CLI will start a server. Only `.mcp.json` servers need approval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum McpApproval {
+    Approved,
+    Pending,
+    Disabled,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct McpServerConfig {
+    pub(crate) name: String,
+    pub(crate) source: McpSource,
+    pub(crate) path: PathBuf,
+    /// `stdio`, `sse` or `http`.
+    pub(crate) transport: String,
+    pub(crate) command: Option<String>,
+    pub(crate) args: Vec<String>,
+    pub(crate) url: Option<String>,
+    pub(crate) approval: McpApproval,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct McpServerState {
+    pub(crate) name: String,
+    pub(crate) configured: bool,
+    /// What the session reported in `system:init`, or `None` when a configured
+    /// server never showed up there.
+    pub(crate) status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadMcpStatus {
+    pub(crate) thread_id: String,
+    pub(crate) servers: Vec<McpServerState>,
+}
+
+/// Every MCP server the CLI would see for `project_path`, one entry per name.
+pub(crate) fn list_configured_servers(
+    project_path: &Path,
+    claude_dir: &Path,
+    home_dir: Option<&Path>,
+) -> Result<Vec<McpServerConfig>, String> {
+    let mut layers = Vec::new();
+    let mut settings_servers = Vec::new();
+    for (scope, source) in [
+        (SettingsScope::User, McpSource::UserSettings),
+        (SettingsScope::Project, McpSource::ProjectSettings),
+        (SettingsScope::Local, McpSource::LocalSettings),
+    ] {
+        let Some(path) = scope_path(scope, claude_dir) else {
+            continue;
+        };
+        let settings = read_settings_file(&path)?;
+        settings_servers.push((source, path, settings.get("mcpServers").cloned()));
+        layers.push(settings);
+    }
+    let approvals = merge_settings(&layers);
+
+    let mut definitions = Vec::new();
+    let mcp_json_path = project_path.join(MCP_JSON_FILE);
+    let mcp_json = read_settings_file(&mcp_json_path)?;
+    definitions.push((
+        McpSource::McpJson,
+        mcp_json_path,
+        mcp_json.get("mcpServers").cloned(),
+    ));
+    if let Some(home_dir) = home_dir {
+        let user_path = home_dir.join(USER_CONFIG_FILE);
+        let user_config = read_settings_file(&user_path)?;
+        let local = user_config
+            .get("projects")
+            .and_then(|projects| projects.get(project_path.to_string_lossy().as_ref()))
+            .and_then(|project| project.get("mcpServers"))
+            .cloned();
+        definitions.push((McpSource::Local, user_path.clone(), local));
+        definitions.push((
+            McpSource::User,
+            user_path,
+            user_config.get("mcpServers").cloned(),
+        ));
+    }
+    definitions.extend(settings_servers);
+
+    let mut servers: BTreeMap<String, McpServerConfig> = BTreeMap::new();
+    for (source, path, value) in definitions {
+        let Some(Value::Object(entries)) = value else {
+            continue;
+        };
+        for (name, definition) in entries {
+            let server = parse_server(&name, source, &path, &definition, &approvals);
+            match servers.get(&name) {
+                Some(existing) if existing.source <= source => {}
+                _ => {
+                    servers.insert(name, server);
+                }
+            }
+        }
+    }
+    Ok(servers.into_values().collect())
+}
+
+fn parse_server(
+    name: &str,
+    source: McpSource,
+    path: &Path,
+    definition: &Value,
+    approvals: &Map<String, Value>,
+) -> McpServerConfig {
+    let text = |key: &str| {
+        definition
+            .get(key)
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+    };
+    let command = text("command");
+    let url = text("url");
+    let transport = text("type").unwrap_or_else(|| {
+        if command.is_none() && url.is_some() {
+            "http".to_string()
+        } else {
+            "stdio".to_string()
+        }
+    });
+    let args = definition
+        .get("args")
+        .and_then(|args| args.as_array())
+        .map(|args| {
+            args.iter()
+                .filter_map(|arg| arg.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    let approval = if source == McpSource::McpJson {
+        mcp_json_approval(name, approvals)
+    } else {
+        McpApproval::Approved
+    };
+    McpServerConfig {
+        name: name.to_string(),
+        source,
+        path: path.to_path_buf(),
+        transport,
+        command,
+        args,
+        url,
+        approval,
+    }
+}
+
+/// The CLI asks before starting servers from a checked-in `.mcp.json`; settings record
+/// the answer per server or for the whole project.
+fn mcp_json_approval(name: &str, settings: &Map<String, Value>) -> McpApproval {
+    let listed = |key: &str| {
+        settings
+            .get(key)
+            .and_then(|names| names.as_array())
+            .is_some_and(|names| names.iter().any(|item| item.as_str() == Some(name)))
+    };
+    if listed("disabledMcpjsonServers") {
+        return McpApproval::Disabled;
+    }
+    let enable_all = settings
+        .get("enableAllProjectMcpServers")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    if enable_all || listed("enabledMcpjsonServers") {
+        McpApproval::Approved
+    } else {
+        McpApproval::Pending
+    }
+}
+
+/// Line up what a session reported against the servers it should have started.
+pub(crate) fn compare_with_session(
+    configured: &[McpServerConfig],
+    reported: &[McpServerStatus],
+) -> Vec<McpServerState> {
+    let mut servers: Vec<McpServerState> = configured
+        .iter()
+        .filter(|server| server.approval == McpApproval::Approved)
+        .map(|server| McpServerState {
+            name: server.name.clone(),
+            configured: true,
+            status: reported
+                .iter()
+                .find(|status| status.name == server.name)
+                .map(|status| status.status.clone()),
+        })
+        .collect();
+    for status in reported {
+        if !servers.iter().any(|server| server.name == status.name) {
+            servers.push(McpServerState {
+                name: status.name.clone(),
+                configured: false,
+                status: Some(status.status.clone()),
+            });
+        }
+    }
+    servers
+}
+
+async fn configured_servers(
+    state: &AppState,
+    workspace_id: &str,
+) -> Result<Vec<McpServerConfig>, String> {
+    let (project_path, claude_dir) = resolve_workspace_dirs(state, workspace_id).await?;
+    tokio::task::spawn_blocking(move || {
+        list_configured_servers(&project_path, &claude_dir, resolve_home_dir().as_deref())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub(crate) async fn list_mcp_servers(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_mcp_servers",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let servers = configured_servers(&state, &workspace_id).await?;
+    serde_json::to_value(servers).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn get_mcp_status(
+    workspace_id: String,
+    thread_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "get_mcp_status",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await;
+    }
+
+    let configured = configured_servers(&state, &workspace_id).await?;
+    let session = state.sessions.lock().await.get(&workspace_id).cloned();
+    let Some(session) = session else {
+        return Ok(json!([]));
+    };
+    let thread_ids = match thread_id {
+        Some(thread_id) => vec![thread_id],
+        None => session.persistent_thread_ids().await,
+    };
+    let reported = session.mcp_servers.lock().await;
+    let statuses: Vec<ThreadMcpStatus> = thread_ids
+        .into_iter()
+        .filter_map(|thread_id| {
+            let servers = reported.get(&thread_id)?;
+            Some(ThreadMcpStatus {
+                servers: compare_with_session(&configured, servers),
+                thread_id,
+            })
+        })
+        .collect();
+    serde_json::to_value(statuses).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn write(path: &Path, value: Value) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, value.to_string()).unwrap();
+    }
+
+    #[test]
+    fn lists_servers_from_every_source() {
+        let root = std::env::temp_dir().join(format!("mcp-inventory-{}", Uuid::new_v4()));
+        let project = root.join("project");
+        let home = root.join("home");
+        let claude_dir = project.join(".claude");
+        write(
+            &project.join(MCP_JSON_FILE),
+            json!({ "mcpServers": {
+                "github": { "command": "gh-mcp", "args": ["--stdio"] },
+                "sentry": { "type": "sse", "url": "https://mcp.sentry.dev/sse" },
+                "docs": { "url": "https://docs.example.com/mcp" },
+            }}),
+        );
+        write(
+            &claude_dir.join("settings.local.json"),
+            json!({ "enabledMcpjsonServers": ["github"], "disabledMcpjsonServers": ["docs"] }),
+        );
+        write(
+            &home.join(USER_CONFIG_FILE),
+            json!({
+                "mcpServers": { "memory": { "command": "mcp-memory" } },
+                "projects": { project.to_string_lossy(): {
+                    "mcpServers": { "github": { "command": "gh-mcp-dev" } },
+                }},
+            }),
+        );
+
+        let servers = list_configured_servers(&project, &claude_dir, Some(&home)).unwrap();
+        let names: Vec<_> = servers.iter().map(|server| server.name.as_str()).collect();
+        assert_eq!(names, vec!["docs", "github", "memory", "sentry"]);
+
+        let github = &servers[1];
+        assert_eq!(github.source, McpSource::Local);
+        assert_eq!(github.command.as_deref(), Some("gh-mcp-dev"));
+        assert_eq!(github.approval, McpApproval::Approved);
+        assert_eq!(servers[0].transport, "http");
+        assert_eq!(servers[0].approval, McpApproval::Disabled);
+        assert_eq!(servers[2].source, McpSource::User);
+        assert_eq!(servers[3].transport, "sse");
+        assert_eq!(servers[3].approval, McpApproval::Pending);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn compares_configuration_with_init_report() {
+        let configured = vec![
+            McpServerConfig {
+                name: "github".to_string(),
+                source: McpSource::McpJson,
+                path: PathBuf::from(".mcp.json"),
+                transport: "stdio".to_string(),
+                command: Some("gh-mcp".to_string()),
+                args: Vec::new(),
+                url: None,
+                approval: McpApproval::Approved,
+            },
+            McpServerConfig {
+                name: "memory".to_string(),
+                source: McpSource::User,
+                path: PathBuf::from(".claude.json"),
+                transport: "stdio".to_string(),
+                command: Some("mcp-memory".to_string()),
+                args: Vec::new(),
+                url: None,
+                approval: McpApproval::Approved,
+            },
+        ];
+        let reported: Vec<McpServerStatus> = serde_json::from_value(json!([
+            { "name": "github", "status": "failed" },
+            { "name": "claude.ai Gmail", "status": "connected" },
+        ]))
+        .unwrap();
+
+        let states = compare_with_session(&configured, &reported);
+        assert_eq!(states.len(), 3);
+        assert_eq!(states[0].status.as_deref(), Some("failed"));
+        assert_eq!(states[1].name, "memory");
+        assert_eq!(states[1].status, None);
+        assert!(!states[2].configured);
+    }
+}
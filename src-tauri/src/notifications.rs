@@ -0,0 +1,246 @@
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::{Notification, NotificationExt};
+
+use crate::backend::stream_events::ResultEvent;
+use crate::state::AppState;
+
+const BODY_MAX_CHARS: usize = 160;
+
+/// Moments worth pulling the user back to a workspace.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Notice {
+    TurnCompleted {
+        duration_ms: Option<u64>,
+        summary: Option<String>,
+    },
+    TurnFailed {
+        message: String,
+    },
+    PermissionRequested {
+        tool_name: String,
+    },
+    PlanReady,
+    /// A read-only workspace's files changed during a turn.
+    GuardrailViolated {
+        files: usize,
+        reverted: bool,
+    },
+    ScheduledRunFinished {
+        name: String,
+        summary: Option<String>,
+        error: Option<String>,
+    },
+}
+
+impl Notice {
+    pub(crate) fn from_result(result: &ResultEvent) -> Self {
+        let text = result
+            .result
+            .as_deref()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string);
+        if result.is_error {
+            let message = text.unwrap_or_else(|| match result.subtype.as_str() {
+                "error_max_turns" => "Reached the maximum number of turns.".to_string(),
+                "error_during_execution" => "Claude hit an error while running.".to_string(),
+                _ => "Claude reported an error.".to_string(),
+            });
+            return Self::TurnFailed { message };
+        }
+        Self::TurnCompleted {
+            duration_ms: result.duration_ms,
+            summary: text,
+        }
+    }
+}
+
+/// Show a native notification for `workspace_id` unless the workspace is muted or
+/// one of the app windows already has focus. The headless daemon does not load the
+/// notification plugin, so this is a no-op there.
+pub(crate) fn notify(app: &AppHandle, workspace_id: &str, notice: Notice) {
+    if app.try_state::<Notification<tauri::Wry>>().is_none() {
+        return;
+    }
+    let app = app.clone();
+    let workspace_id = workspace_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<AppState>() else {
+            return;
+        };
+        let workspace_name = {
+            let workspaces = state.workspaces.lock().await;
+            match workspaces.get(&workspace_id) {
+                Some(entry) if !entry.settings.notifications_muted => entry.name.clone(),
+                _ => return,
+            }
+        };
+        if app_has_focus(&app) {
+            return;
+        }
+        let (title, body) = render(&workspace_name, &notice);
+        if let Err(err) = app.notification().builder().title(title).body(body).show() {
+            tracing::warn!("failed to show notification: {err}");
+        }
+    });
+}
+
+fn app_has_focus(app: &AppHandle) -> bool {
+    app.webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false))
+}
+
+fn render(workspace_name: &str, notice: &Notice) -> (String, String) {
+    match notice {
+        Notice::TurnCompleted {
+            duration_ms,
+            summary,
+        } => {
+            let title = match duration_ms {
+                Some(duration_ms) => format!(
+                    "{workspace_name}: turn finished after {}",
+                    format_duration(*duration_ms)
+                ),
+                None => format!("{workspace_name}: turn finished"),
+            };
+            let body = summary
+                .as_deref()
+                .map(first_line)
+                .unwrap_or_else(|| "Claude is waiting for your next message.".to_string());
+            (title, body)
+        }
+        Notice::TurnFailed { message } => (
+            format!("{workspace_name}: turn failed"),
+            first_line(message),
+        ),
+        Notice::PermissionRequested { tool_name } => (
+            format!("{workspace_name}: permission needed"),
+            format!("Claude wants to use {tool_name}."),
+        ),
+        Notice::PlanReady => (
+            format!("{workspace_name}: plan ready for review"),
+            "Claude is waiting for you to approve or revise its plan.".to_string(),
+        ),
+        Notice::GuardrailViolated { files, reverted } => {
+            let changed = match files {
+                1 => "1 file".to_string(),
+                files => format!("{files} files"),
+            };
+            let body = if *reverted {
+                format!("{changed} changed during a turn and were reverted.")
+            } else {
+                format!("{changed} changed during a turn and could not all be reverted.")
+            };
+            (
+                format!("{workspace_name}: read-only workspace modified"),
+                body,
+            )
+        }
+        Notice::ScheduledRunFinished {
+            name,
+            summary,
+            error,
+        } => match error {
+            Some(error) => (
+                format!("{workspace_name}: scheduled \"{name}\" failed"),
+                first_line(error),
+            ),
+            None => (
+                format!("{workspace_name}: scheduled \"{name}\" finished"),
+                summary
+                    .as_deref()
+                    .map(first_line)
+                    .unwrap_or_else(|| "The run produced no output.".to_string()),
+            ),
+        },
+    }
+}
+
+fn format_duration(duration_ms: u64) -> String {
+    let seconds = duration_ms / 1000;
+    match seconds {
+        0..=59 => format!("{seconds}s"),
+        60..=3599 => format!("{}m {}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {}m", seconds / 3600, (seconds % 3600) / 60),
+    }
+}
+
+fn first_line(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+    if line.chars().count() <= BODY_MAX_CHARS {
+        return line.to_string();
+    }
+    let mut truncated: String = line.chars().take(BODY_MAX_CHARS - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn result_event(value: serde_json::Value) -> ResultEvent {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn classifies_result_events() {
+        let done = result_event(json!({
+            "subtype": "success",
+            "duration_ms": 252_000,
+            "result": "\nRefactored the parser.\nAll tests pass.",
+        }));
+        assert_eq!(
+            Notice::from_result(&done),
+            Notice::TurnCompleted {
+                duration_ms: Some(252_000),
+                summary: Some("Refactored the parser.\nAll tests pass.".to_string()),
+            }
+        );
+
+        let failed = result_event(json!({ "subtype": "error_max_turns", "is_error": true }));
+        assert_eq!(
+            Notice::from_result(&failed),
+            Notice::TurnFailed {
+                message: "Reached the maximum number of turns.".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn renders_titles_and_bodies() {
+        let (title, body) = render(
+            "api",
+            &Notice::TurnCompleted {
+                duration_ms: Some(252_000),
+                summary: Some("Refactored the parser.\nAll tests pass.".to_string()),
+            },
+        );
+        assert_eq!(title, "api: turn finished after 4m 12s");
+        assert_eq!(body, "Refactored the parser.");
+
+        let (title, body) = render(
+            "api",
+            &Notice::PermissionRequested {
+                tool_name: "Bash".to_string(),
+            },
+        );
+        assert_eq!(title, "api: permission needed");
+        assert_eq!(body, "Claude wants to use Bash.");
+    }
+
+    #[test]
+    fn truncates_long_bodies() {
+        let body = first_line(&"x".repeat(500));
+        assert_eq!(body.chars().count(), BODY_MAX_CHARS);
+        assert!(body.ends_with('…'));
+        assert_eq!(format_duration(42_000), "42s");
+        assert_eq!(format_duration(7_380_000), "2h 3m");
+    }
+}
@@ -0,0 +1,264 @@
+//! Cancelling long-running work from the UI.
+//!
+//! Commands that can take a while (session exports, transcript searches) accept an
+//! `operationId` chosen by the caller. While such a command runs, its
+//! [`CancellationToken`] is registered under that id, and `cancel_operation` stops it:
+//! the command returns `Cancelled.` and drops its work, which kills the processes and
+//! closes the files it owned. Every turn is registered under its turn id until it
+//! finishes, and cancelling it interrupts the turn the way `turn_interrupt` does.
+//! Batches, pipelines and transcript tails hold tokens of their own. Every token
+//! descends from the root token, which shutdown cancels, so no background work
+//! outlives the app.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use serde_json::json;
+use tauri::{AppHandle, State};
+use tokio_util::sync::CancellationToken;
+
+use crate::backend::ownership;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::OperationInfo;
+use crate::utils::now_ms;
+
+/// Error returned by work that stopped because its operation was cancelled.
+pub(crate) const CANCELLED: &str = "Cancelled.";
+
+struct RunningOperation {
+    info: OperationInfo,
+    /// The thread a turn operation belongs to, whose owner alone may cancel it.
+    thread_id: Option<String>,
+    token: CancellationToken,
+}
+
+static ROOT: OnceLock<CancellationToken> = OnceLock::new();
+static RUNNING: OnceLock<Mutex<HashMap<String, RunningOperation>>> = OnceLock::new();
+
+fn running() -> MutexGuard<'static, HashMap<String, RunningOperation>> {
+    RUNNING
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The token every other one descends from.
+pub(crate) fn root() -> &'static CancellationToken {
+    ROOT.get_or_init(CancellationToken::new)
+}
+
+/// Stop all cancellable work, e.g. when the app exits.
+pub(crate) fn cancel_all() {
+    root().cancel();
+}
+
+/// A registered operation; dropping it unregisters the id.
+pub(crate) struct Operation {
+    id: Option<String>,
+    token: CancellationToken,
+}
+
+impl Operation {
+    pub(crate) fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Run `work` until it finishes or the operation is cancelled, in which case `work`
+    /// is dropped, killing the processes and closing the files it owns.
+    pub(crate) async fn run<T>(
+        &self,
+        work: impl Future<Output = Result<T, String>>,
+    ) -> Result<T, String> {
+        self.token
+            .run_until_cancelled(work)
+            .await
+            .unwrap_or_else(|| Err(CANCELLED.to_string()))
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        if let Some(id) = &self.id {
+            running().remove(id);
+        }
+    }
+}
+
+/// Register a `kind` command under `operation_id`. Without an id the operation can
+/// still be stopped by shutdown, just not by `cancel_operation`.
+pub(crate) fn start(
+    operation_id: Option<String>,
+    kind: &str,
+    workspace_id: Option<&str>,
+) -> Result<Operation, String> {
+    register(operation_id, kind, workspace_id, None)
+}
+
+/// Register a thread's turn under its turn id.
+pub(crate) fn start_turn(
+    turn_id: &str,
+    workspace_id: &str,
+    thread_id: &str,
+) -> Result<Operation, String> {
+    register(
+        Some(turn_id.to_string()),
+        "turn",
+        Some(workspace_id),
+        Some(thread_id),
+    )
+}
+
+fn register(
+    operation_id: Option<String>,
+    kind: &str,
+    workspace_id: Option<&str>,
+    thread_id: Option<&str>,
+) -> Result<Operation, String> {
+    let token = root().child_token();
+    let id = operation_id.filter(|id| !id.trim().is_empty());
+    if let Some(id) = &id {
+        let mut running = running();
+        if running.contains_key(id) {
+            return Err(format!("Operation {id} is already running."));
+        }
+        running.insert(
+            id.clone(),
+            RunningOperation {
+                info: OperationInfo {
+                    id: id.clone(),
+                    kind: kind.to_string(),
+                    workspace_id: workspace_id.map(str::to_string),
+                    started_at: now_ms(),
+                },
+                thread_id: thread_id.map(str::to_string),
+                token: token.clone(),
+            },
+        );
+    }
+    Ok(Operation { id, token })
+}
+
+/// The workspace and thread of a running turn operation.
+fn turn_thread(operation_id: &str) -> Option<(String, String)> {
+    let running = running();
+    let operation = running.get(operation_id)?;
+    Some((
+        operation.info.workspace_id.clone()?,
+        operation.thread_id.clone()?,
+    ))
+}
+
+fn cancel(operation_id: &str) -> bool {
+    match running().get(operation_id) {
+        Some(operation) => {
+            operation.token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Cancel a running operation. Returns false when it is not running, e.g. because it
+/// already finished. A turn is only cancelled for the user `turn_interrupt` would let
+/// interrupt it.
+#[tauri::command]
+pub(crate) async fn cancel_operation(
+    operation_id: String,
+    user: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<bool, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "cancel_operation",
+            json!({
+                "operationId": operation_id,
+                "user": user.unwrap_or_else(ownership::local_user),
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    if let Some((workspace_id, thread_id)) = turn_thread(&operation_id) {
+        let session = state.sessions.lock().await.get(&workspace_id).cloned();
+        if let Some(session) = session {
+            session
+                .owners
+                .lock()
+                .await
+                .check(&thread_id, &ownership::resolve_user(user), true)?;
+        }
+    }
+    Ok(cancel(&operation_id))
+}
+
+/// Operations running now, oldest first.
+#[tauri::command]
+pub(crate) async fn list_operations(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<OperationInfo>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "list_operations", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let mut operations: Vec<OperationInfo> = running()
+        .values()
+        .map(|operation| operation.info.clone())
+        .collect();
+    operations.sort_by(|a, b| a.started_at.cmp(&b.started_at).then(a.id.cmp(&b.id)));
+    Ok(operations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers_operations_until_they_are_dropped() {
+        let operation = start(Some("op-registry".to_string()), "export_session", None).unwrap();
+        assert!(start(Some("op-registry".to_string()), "export_session", None).is_err());
+        assert!(cancel("op-registry"));
+        assert!(operation.token().is_cancelled());
+        drop(operation);
+        assert!(!cancel("op-registry"));
+
+        let anonymous = start(None, "search_transcripts", None).unwrap();
+        assert!(!anonymous.token().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn cancelled_operations_drop_their_work() {
+        let operation = start(Some("op-run".to_string()), "export_session", None).unwrap();
+        assert_eq!(operation.run(async { Ok(1) }).await, Ok(1));
+        assert!(cancel("op-run"));
+        let stopped: Result<(), String> = operation.run(std::future::pending()).await;
+        assert_eq!(stopped, Err(CANCELLED.to_string()));
+    }
+
+    #[test]
+    fn records_the_thread_of_turn_operations() {
+        let turn = start_turn("op-turn", "ws-1", "thread-1").unwrap();
+        assert_eq!(
+            turn_thread("op-turn"),
+            Some(("ws-1".to_string(), "thread-1".to_string()))
+        );
+        drop(turn);
+        assert_eq!(turn_thread("op-turn"), None);
+
+        let _export = start(
+            Some("op-export".to_string()),
+            "export_session",
+            Some("ws-1"),
+        )
+        .unwrap();
+        assert_eq!(turn_thread("op-export"), None);
+    }
+}
@@ -0,0 +1,426 @@
+//! Messages sent while their thread had no process to take them: the workspace was not
+//! connected, or the thread's CLI could not be started or written to.
+//!
+//! They are parked in the database, shown as pending, and go out through the workspace's
+//! turn queue once it can take them, in the order they were written. A thread with
+//! messages parked parks new ones behind them. Flushes happen when a workspace connects,
+//! when a thread's process is respawned, and every `FLUSH_INTERVAL`; a message that
+//! failed again waits `RETRY_AFTER_MS` before the periodic flush tries it once more.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+use tokio::time::interval;
+
+use crate::backend::turn_queue::QueuedPrompt;
+use crate::claude::{self, emit_event, WorkspaceSession};
+use crate::event_sink::TauriEventSink;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+use crate::types::PendingMessage;
+use crate::utils::now_ms;
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+const RETRY_AFTER_MS: i64 = 30_000;
+
+fn database() -> Result<&'static std::path::Path, String> {
+    storage::database_path().ok_or_else(|| "database is not open".to_string())
+}
+
+/// A prompt for the outbox; `error` is why sending it just failed, if it was tried.
+pub(crate) fn pending_message(
+    workspace_id: &str,
+    prompt: QueuedPrompt,
+    error: Option<String>,
+) -> PendingMessage {
+    let now = now_ms();
+    PendingMessage {
+        id: prompt.id,
+        workspace_id: workspace_id.to_string(),
+        thread_id: prompt.thread_id,
+        prompt: prompt.prompt,
+        images: prompt.images,
+        model: prompt.model,
+        access_mode: prompt.access_mode,
+        queued_at: prompt.queued_at,
+        attempts: u32::from(error.is_some()),
+        last_attempt_at: error.as_ref().map(|_| now),
+        error,
+    }
+}
+
+fn queued_prompt(message: &PendingMessage) -> QueuedPrompt {
+    QueuedPrompt {
+        id: message.id.clone(),
+        thread_id: message.thread_id.clone(),
+        prompt: message.prompt.clone(),
+        images: message.images.clone(),
+        model: message.model.clone(),
+        access_mode: message.access_mode.clone(),
+        queued_at: message.queued_at,
+    }
+}
+
+/// Park a message and answer `send_user_message` with it as pending.
+pub(crate) async fn park(
+    event_sink: &TauriEventSink,
+    message: PendingMessage,
+) -> Result<Value, String> {
+    let path = database()?;
+    storage::save_pending_message(path, &message)?;
+    let position = storage::read_pending_messages(path, Some(&message.workspace_id))?
+        .iter()
+        .position(|pending| pending.id == message.id)
+        .map_or(0, |index| index + 1);
+    emit_outbox_updated(event_sink, &message.workspace_id);
+    Ok(json!({
+        "result": {
+            "pending": {
+                "id": message.id,
+                "threadId": message.thread_id,
+                "position": position,
+                "error": message.error,
+            }
+        }
+    }))
+}
+
+/// Whether the thread has messages parked, which a new message must not overtake.
+pub(crate) fn has_pending(workspace_id: &str, thread_id: &str) -> bool {
+    database()
+        .and_then(|path| storage::read_pending_messages(path, Some(workspace_id)))
+        .map(|messages| {
+            messages
+                .iter()
+                .any(|message| message.thread_id == thread_id)
+        })
+        .unwrap_or(false)
+}
+
+/// Hand the workspace's parked messages to its turn queue. Without `force`, a thread
+/// whose oldest message failed less than `RETRY_AFTER_MS` ago is left alone.
+pub(crate) async fn flush(
+    session: &Arc<WorkspaceSession>,
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    force: bool,
+) {
+    let messages = match database()
+        .and_then(|path| storage::read_pending_messages(path, Some(workspace_id)))
+    {
+        Ok(messages) => messages,
+        Err(err) => {
+            tracing::warn!("failed to read the outbox: {err}");
+            return;
+        }
+    };
+    let prompts: Vec<QueuedPrompt> = {
+        let mut claims = session.outbox_claims.lock().await;
+        let ready = ready_to_flush(&messages, &claims, now_ms(), force);
+        ready
+            .into_iter()
+            .map(|message| {
+                claims.insert(message.id.clone());
+                queued_prompt(message)
+            })
+            .collect()
+    };
+    if prompts.is_empty() {
+        return;
+    }
+    tracing::info!(
+        "sending {} parked message(s) of workspace {workspace_id}",
+        prompts.len()
+    );
+    claude::enqueue_prompts(workspace_id, session, prompts, event_sink.clone()).await;
+}
+
+/// The messages to send now, in order. Each thread's messages go in order, so a thread
+/// stops at its first message that is waiting out a retry.
+fn ready_to_flush<'a>(
+    messages: &'a [PendingMessage],
+    claimed: &HashSet<String>,
+    now: i64,
+    force: bool,
+) -> Vec<&'a PendingMessage> {
+    let mut held_threads = HashSet::new();
+    let mut ready = Vec::new();
+    for message in messages {
+        if claimed.contains(&message.id) || held_threads.contains(&message.thread_id) {
+            continue;
+        }
+        let retry_due = message
+            .last_attempt_at
+            .is_none_or(|at| now - at >= RETRY_AFTER_MS);
+        if force || retry_due {
+            ready.push(message);
+        } else {
+            held_threads.insert(message.thread_id.clone());
+        }
+    }
+    ready
+}
+
+/// A parked message started its turn.
+pub(crate) async fn delivered(
+    session: &WorkspaceSession,
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    message_id: &str,
+) {
+    session.outbox_claims.lock().await.remove(message_id);
+    if let Err(err) = database().and_then(|path| storage::delete_pending_message(path, message_id))
+    {
+        tracing::warn!("failed to drop sent message {message_id} from the outbox: {err}");
+    }
+    emit_outbox_updated(event_sink, workspace_id);
+}
+
+/// A parked message failed again. It stays parked, and its thread's later messages come
+/// back out of the turn queue so they do not overtake it.
+pub(crate) async fn failed(
+    session: &Arc<WorkspaceSession>,
+    event_sink: &TauriEventSink,
+    workspace_id: &str,
+    message_id: &str,
+    error: String,
+) {
+    tracing::warn!("parked message {message_id} could not be sent: {error}");
+    let messages = database()
+        .and_then(|path| storage::read_pending_messages(path, Some(workspace_id)))
+        .unwrap_or_default();
+    let Some(mut message) = messages
+        .into_iter()
+        .find(|message| message.id == message_id)
+    else {
+        session.outbox_claims.lock().await.remove(message_id);
+        return;
+    };
+    message.attempts += 1;
+    message.last_attempt_at = Some(now_ms());
+    message.error = Some(error);
+    if let Err(err) = database().and_then(|path| storage::save_pending_message(path, &message)) {
+        tracing::warn!("failed to update parked message {message_id}: {err}");
+    }
+    {
+        let mut claims = session.outbox_claims.lock().await;
+        claims.remove(message_id);
+        let mut queue = session.turn_queue.lock().await;
+        let later: Vec<String> = queue
+            .snapshot()
+            .into_iter()
+            .filter(|queued| queued.thread_id == message.thread_id && claims.contains(&queued.id))
+            .map(|queued| queued.id)
+            .collect();
+        for id in later {
+            queue.cancel(&id);
+            claims.remove(&id);
+        }
+    }
+    claude::emit_turn_queue_updated(event_sink, workspace_id, session).await;
+    emit_outbox_updated(event_sink, workspace_id);
+}
+
+fn emit_outbox_updated(event_sink: &TauriEventSink, workspace_id: &str) {
+    let messages = database()
+        .and_then(|path| storage::read_pending_messages(path, Some(workspace_id)))
+        .unwrap_or_default();
+    emit_event(
+        event_sink,
+        workspace_id,
+        "outbox/updated",
+        json!({ "messages": messages }),
+    );
+}
+
+/// Background task that flushes every connected workspace's outbox, for messages
+/// parked after a failed send and workspaces connected without `connect_workspace`.
+pub(crate) fn spawn_outbox_flusher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let event_sink = TauriEventSink::new(app.clone());
+        let mut ticker = interval(FLUSH_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let Some(state) = app.try_state::<AppState>() else {
+                continue;
+            };
+            let sessions: Vec<(String, Arc<WorkspaceSession>)> = state
+                .sessions
+                .lock()
+                .await
+                .iter()
+                .map(|(id, session)| (id.clone(), Arc::clone(session)))
+                .collect();
+            for (workspace_id, session) in sessions {
+                flush(&session, &event_sink, &workspace_id, false).await;
+            }
+        }
+    });
+}
+
+/// A workspace's parked messages, oldest first.
+#[tauri::command]
+pub(crate) async fn list_pending_messages(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<PendingMessage>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_pending_messages",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    storage::read_pending_messages(&state.db_path, Some(&workspace_id))
+}
+
+/// Drop a parked message. One already handed to the turn queue is taken out of it; one
+/// whose turn is starting can no longer be cancelled.
+#[tauri::command]
+pub(crate) async fn cancel_pending_message(
+    workspace_id: String,
+    message_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "cancel_pending_message",
+            json!({ "workspaceId": workspace_id, "messageId": message_id }),
+        )
+        .await?;
+        return Ok(());
+    }
+    let event_sink = TauriEventSink::new(app);
+    let session = state.sessions.lock().await.get(&workspace_id).cloned();
+    if let Some(session) = &session {
+        let mut claims = session.outbox_claims.lock().await;
+        if claims.contains(&message_id) {
+            if session
+                .turn_queue
+                .lock()
+                .await
+                .cancel(&message_id)
+                .is_none()
+            {
+                return Err("the message is being sent".to_string());
+            }
+            claims.remove(&message_id);
+        }
+    }
+    if !storage::delete_pending_message(&state.db_path, &message_id)? {
+        return Err("pending message not found".to_string());
+    }
+    if let Some(session) = &session {
+        claude::emit_turn_queue_updated(&event_sink, &workspace_id, session).await;
+    }
+    emit_outbox_updated(&event_sink, &workspace_id);
+    Ok(())
+}
+
+/// Try the workspace's parked messages now, without waiting out their retry delay.
+#[tauri::command]
+pub(crate) async fn flush_pending_messages(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "flush_pending_messages",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return Ok(());
+    }
+    let session = state
+        .sessions
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not connected")?;
+    flush(&session, &TauriEventSink::new(app), &workspace_id, true).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(id: &str, thread_id: &str) -> QueuedPrompt {
+        QueuedPrompt {
+            id: id.to_string(),
+            thread_id: thread_id.to_string(),
+            prompt: format!("prompt {id}"),
+            images: Vec::new(),
+            model: None,
+            access_mode: None,
+            queued_at: 1_000,
+        }
+    }
+
+    fn message(id: &str, thread_id: &str, last_attempt_at: Option<i64>) -> PendingMessage {
+        PendingMessage {
+            last_attempt_at,
+            ..pending_message("ws-1", prompt(id, thread_id), None)
+        }
+    }
+
+    #[test]
+    fn flushes_in_order_and_holds_threads_waiting_out_a_retry() {
+        let now = 100_000;
+        let messages = [
+            message("a1", "thread-a", Some(now - 5_000)),
+            message("b1", "thread-b", None),
+            message("a2", "thread-a", None),
+            message("b2", "thread-b", Some(now - RETRY_AFTER_MS)),
+            message("c1", "thread-c", None),
+        ];
+        let claimed = HashSet::from(["c1".to_string()]);
+        let ids = |ready: Vec<&PendingMessage>| {
+            ready
+                .into_iter()
+                .map(|message| message.id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        // a1 failed 5s ago, so a2 waits behind it; c1 is already in the turn queue
+        assert_eq!(
+            ids(ready_to_flush(&messages, &claimed, now, false)),
+            ["b1", "b2"]
+        );
+        assert_eq!(
+            ids(ready_to_flush(&messages, &claimed, now, true)),
+            ["a1", "b1", "a2", "b2"]
+        );
+    }
+
+    #[test]
+    fn parked_prompts_keep_their_place_and_count_failed_sends() {
+        let fresh = pending_message("ws-1", prompt("p1", "thread-1"), None);
+        assert_eq!((fresh.attempts, fresh.last_attempt_at), (0, None));
+        assert_eq!(fresh.queued_at, 1_000);
+        let failed = pending_message(
+            "ws-1",
+            prompt("p2", "thread-1"),
+            Some("broken pipe".to_string()),
+        );
+        assert_eq!(failed.attempts, 1);
+        assert!(failed.last_attempt_at.is_some());
+        let requeued = queued_prompt(&failed);
+        assert_eq!((requeued.id.as_str(), requeued.queued_at), ("p2", 1_000));
+    }
+}
@@ -0,0 +1,231 @@
+//! Chain turns across workspaces.
+//!
+//! A pipeline is a list of steps, each a prompt for one workspace. Steps run in order
+//! as one-shot `claude -p` turns, and each step's final message is passed to the next
+//! through `{previous}` (or appended when the prompt has no placeholder), so a planner
+//! worktree can hand its plan to an implementer worktree. Progress is broadcast as
+//! `pipeline-progress` events; cancelling kills the running step.
+
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::backend::events::{EventSink, PipelineProgress};
+use crate::backend::pipeline_run::{validate_steps, PipelineRun, PipelineStepSpec};
+use crate::budget;
+use crate::claude;
+use crate::event_sink::TauriEventSink;
+use crate::operations;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::utils::now_ms;
+
+const STEP_TIME_LIMIT: Duration = Duration::from_secs(30 * 60);
+const MAX_KEPT_PIPELINES: usize = 20;
+
+struct TrackedPipeline {
+    run: PipelineRun,
+    cancel: CancellationToken,
+}
+
+static PIPELINES: OnceLock<Mutex<Vec<TrackedPipeline>>> = OnceLock::new();
+
+fn pipelines() -> MutexGuard<'static, Vec<TrackedPipeline>> {
+    PIPELINES
+        .get_or_init(|| Mutex::new(Vec::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Keep a new pipeline, forgetting the oldest finished ones past the limit.
+fn remember(run: PipelineRun, cancel: CancellationToken) {
+    let mut pipelines = pipelines();
+    pipelines.insert(0, TrackedPipeline { run, cancel });
+    while pipelines.len() > MAX_KEPT_PIPELINES {
+        match pipelines
+            .iter()
+            .rposition(|tracked| tracked.run.is_finished())
+        {
+            Some(index) => {
+                pipelines.remove(index);
+            }
+            None => break,
+        }
+    }
+}
+
+/// Apply `change` to a pipeline and return a snapshot of it afterwards.
+fn update<R>(
+    pipeline_id: &str,
+    change: impl FnOnce(&mut PipelineRun) -> R,
+) -> Option<(R, PipelineRun)> {
+    let mut pipelines = pipelines();
+    let tracked = pipelines
+        .iter_mut()
+        .find(|tracked| tracked.run.id == pipeline_id)?;
+    let result = change(&mut tracked.run);
+    Some((result, tracked.run.clone()))
+}
+
+fn emit_progress(event_sink: &TauriEventSink, workspace_id: Option<&str>, pipeline: PipelineRun) {
+    event_sink.emit_pipeline_progress(PipelineProgress {
+        workspace_id: workspace_id.map(str::to_string),
+        pipeline,
+    });
+}
+
+/// Run the steps one by one until the pipeline ends or is cancelled.
+async fn drive(
+    app: AppHandle,
+    pipeline_id: String,
+    permission_mode: String,
+    cancel: CancellationToken,
+) {
+    let event_sink = TauriEventSink::new(app.clone());
+    let state = app.state::<AppState>();
+    loop {
+        let Some((Some(step), snapshot)) = update(&pipeline_id, |run| run.start_next(now_ms()))
+        else {
+            return;
+        };
+        emit_progress(&event_sink, Some(&step.workspace_id), snapshot);
+
+        let entry = state
+            .workspaces
+            .lock()
+            .await
+            .get(&step.workspace_id)
+            .cloned();
+        let allowed = budget::ensure_turn_allowed(&state, &step.workspace_id).await;
+        let outcome = match (entry, allowed) {
+            (_, Err(err)) => Err(err),
+            (Some(entry), Ok(())) => {
+                let turn = claude::run_claude_prompt_once_with_result(
+                    &entry,
+                    claude::workspace_claude_bin(&state, &entry).await,
+                    step.prompt,
+                    Some(permission_mode.clone()),
+                    step.model.or(entry.model.clone()),
+                    &claude::workspace_cli_args(&entry),
+                    STEP_TIME_LIMIT,
+                );
+                tokio::select! {
+                    outcome = turn => outcome.map(|output| (output.message, output.result)),
+                    // Dropping the turn kills the CLI; the cancel is already recorded
+                    _ = cancel.cancelled() => return,
+                }
+            }
+            (None, Ok(())) => Err("workspace not found".to_string()),
+        };
+        let finished = update(&pipeline_id, |run| {
+            run.finish_step(step.index, outcome, now_ms())
+        });
+        if let Some(((), snapshot)) = finished {
+            emit_progress(&event_sink, Some(&step.workspace_id), snapshot);
+        }
+    }
+}
+
+/// Validate the steps, start the first one, and return the pipeline right away.
+#[tauri::command]
+pub(crate) async fn create_pipeline(
+    name: String,
+    steps: Vec<PipelineStepSpec>,
+    permission_mode: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "create_pipeline",
+            json!({
+                "name": name,
+                "steps": steps,
+                "permissionMode": permission_mode,
+            }),
+        )
+        .await;
+    }
+
+    validate_steps(&steps)?;
+    let steps = {
+        let workspaces = state.workspaces.lock().await;
+        steps
+            .into_iter()
+            .map(|step| {
+                let workspace_name = workspaces
+                    .get(&step.workspace_id)
+                    .map(|entry| entry.name.clone())
+                    .ok_or_else(|| format!("workspace not found: {}", step.workspace_id))?;
+                Ok((step, workspace_name))
+            })
+            .collect::<Result<Vec<_>, String>>()?
+    };
+    let name = match name.trim() {
+        "" => "Pipeline".to_string(),
+        name => name.to_string(),
+    };
+    let run = PipelineRun::new(Uuid::new_v4().to_string(), name, steps, now_ms());
+    let cancel = operations::root().child_token();
+    remember(run.clone(), cancel.clone());
+    emit_progress(&TauriEventSink::new(app.clone()), None, run.clone());
+
+    let permission_mode = permission_mode
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| claude::UNATTENDED_PERMISSION_MODE.to_string());
+    tauri::async_runtime::spawn(drive(app, run.id.clone(), permission_mode, cancel));
+    serde_json::to_value(run).map_err(|err| err.to_string())
+}
+
+/// Recent pipelines, newest first.
+#[tauri::command]
+pub(crate) async fn list_pipelines(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(&*state, app, "list_pipelines", json!({})).await;
+    }
+
+    let runs: Vec<PipelineRun> = pipelines()
+        .iter()
+        .map(|tracked| tracked.run.clone())
+        .collect();
+    serde_json::to_value(runs).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn cancel_pipeline(
+    pipeline_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "cancel_pipeline",
+            json!({ "pipelineId": pipeline_id }),
+        )
+        .await;
+    }
+
+    let snapshot = {
+        let mut pipelines = pipelines();
+        let tracked = pipelines
+            .iter_mut()
+            .find(|tracked| tracked.run.id == pipeline_id)
+            .ok_or("pipeline not found")?;
+        tracked.run.cancel(now_ms());
+        tracked.cancel.cancel();
+        tracked.run.clone()
+    };
+    emit_progress(&TauriEventSink::new(app), None, snapshot.clone());
+    serde_json::to_value(snapshot).map_err(|err| err.to_string())
+}
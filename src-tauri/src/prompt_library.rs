@@ -0,0 +1,474 @@
+//! Named prompt templates with `{placeholder}` slots.
+//!
+//! Templates live in `prompt-library.json` files: one in the app data dir shared by
+//! every workspace, and one per workspace under `workspaces/<id>/`. A workspace template
+//! shadows a global one with the same name. Write `{{` and `}}` for literal braces;
+//! braces around anything that is not a placeholder name (JSON, code) are left alone.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+
+use crate::claude;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::utils::now_ms;
+
+const LIBRARY_FILE: &str = "prompt-library.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TemplateScope {
+    Workspace,
+    Global,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PromptTemplate {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+    pub(crate) body: String,
+    #[serde(default)]
+    pub(crate) updated_at: i64,
+}
+
+/// A template as listed to the UI, with its scope and the values it asks for.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PromptTemplateEntry {
+    #[serde(flatten)]
+    pub(crate) template: PromptTemplate,
+    pub(crate) scope: TemplateScope,
+    pub(crate) placeholders: Vec<String>,
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+}
+
+fn parse_template(body: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = body;
+    while let Some(index) = rest.find(['{', '}']) {
+        let (text, tail) = rest.split_at(index);
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            segments.push(Segment::Text(&tail[..1]));
+            rest = &tail[2..];
+            continue;
+        }
+        if tail.starts_with('{') {
+            if let Some(end) = tail.find('}') {
+                let name = &tail[1..end];
+                if is_placeholder_name(name) {
+                    segments.push(Segment::Placeholder(name));
+                    rest = &tail[end + 1..];
+                    continue;
+                }
+            }
+        }
+        segments.push(Segment::Text(&tail[..1]));
+        rest = &tail[1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    segments
+}
+
+/// Placeholder names in order of first use.
+pub(crate) fn placeholders(body: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    parse_template(body)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Placeholder(name) if seen.insert(name) => Some(name.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Fill every placeholder; fails naming the ones without a value.
+pub(crate) fn render(body: &str, values: &BTreeMap<String, String>) -> Result<String, String> {
+    let missing: Vec<String> = placeholders(body)
+        .into_iter()
+        .filter(|name| !values.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("missing values for {}", missing.join(", ")));
+    }
+    Ok(parse_template(body)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text,
+            Segment::Placeholder(name) => values[name].as_str(),
+        })
+        .collect())
+}
+
+pub(crate) fn read_library(path: &Path) -> Result<Vec<PromptTemplate>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(raw) => serde_json::from_str(&raw)
+            .map_err(|err| format!("invalid prompt library {}: {err}", path.display())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+pub(crate) fn write_library(path: &Path, templates: &[PromptTemplate]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(templates).map_err(|err| err.to_string())?;
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, data).map_err(|err| err.to_string())?;
+    std::fs::rename(&temp_path, path).map_err(|err| err.to_string())
+}
+
+/// Workspace templates first, then global ones not shadowed by name.
+fn merge_libraries(
+    workspace: Vec<PromptTemplate>,
+    global: Vec<PromptTemplate>,
+) -> Vec<PromptTemplateEntry> {
+    let names: HashSet<String> = workspace.iter().map(|t| t.name.clone()).collect();
+    let entry = |scope| {
+        move |template: PromptTemplate| PromptTemplateEntry {
+            placeholders: placeholders(&template.body),
+            template,
+            scope,
+        }
+    };
+    let mut workspace: Vec<_> = workspace
+        .into_iter()
+        .map(entry(TemplateScope::Workspace))
+        .collect();
+    let mut global: Vec<_> = global
+        .into_iter()
+        .filter(|template| !names.contains(&template.name))
+        .map(entry(TemplateScope::Global))
+        .collect();
+    workspace.sort_by(|a, b| a.template.name.cmp(&b.template.name));
+    global.sort_by(|a, b| a.template.name.cmp(&b.template.name));
+    workspace.extend(global);
+    workspace
+}
+
+async fn library_path(
+    state: &AppState,
+    workspace_id: &str,
+    scope: TemplateScope,
+) -> Result<PathBuf, String> {
+    let data_dir = state
+        .db_path
+        .parent()
+        .ok_or_else(|| "Unable to resolve app data dir.".to_string())?;
+    match scope {
+        TemplateScope::Global => Ok(library_file(data_dir, None)),
+        TemplateScope::Workspace => {
+            if !state.workspaces.lock().await.contains_key(workspace_id) {
+                return Err("workspace not found".to_string());
+            }
+            Ok(library_file(data_dir, Some(workspace_id)))
+        }
+    }
+}
+
+/// The global library without a workspace id, the workspace's own otherwise.
+pub(crate) fn library_file(data_dir: &Path, workspace_id: Option<&str>) -> PathBuf {
+    match workspace_id {
+        Some(workspace_id) => data_dir
+            .join("workspaces")
+            .join(workspace_id)
+            .join(LIBRARY_FILE),
+        None => data_dir.join(LIBRARY_FILE),
+    }
+}
+
+async fn list_templates(
+    state: &AppState,
+    workspace_id: &str,
+) -> Result<Vec<PromptTemplateEntry>, String> {
+    let workspace =
+        read_library(&library_path(state, workspace_id, TemplateScope::Workspace).await?)?;
+    let global = read_library(&library_path(state, workspace_id, TemplateScope::Global).await?)?;
+    Ok(merge_libraries(workspace, global))
+}
+
+pub(crate) async fn render_named(
+    state: &AppState,
+    workspace_id: &str,
+    name: &str,
+    values: &BTreeMap<String, String>,
+) -> Result<String, String> {
+    let template = list_templates(state, workspace_id)
+        .await?
+        .into_iter()
+        .find(|entry| entry.template.name == name)
+        .ok_or_else(|| format!("no prompt template named {name}"))?;
+    render(&template.template.body, values)
+}
+
+#[tauri::command]
+pub(crate) async fn prompt_templates_list(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "prompt_templates_list",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let templates = list_templates(&state, &workspace_id).await?;
+    serde_json::to_value(templates).map_err(|err| err.to_string())
+}
+
+/// Create or replace the template called `name` in one scope.
+#[tauri::command]
+pub(crate) async fn prompt_template_save(
+    workspace_id: String,
+    scope: TemplateScope,
+    name: String,
+    description: Option<String>,
+    body: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "prompt_template_save",
+            json!({
+                "workspaceId": workspace_id,
+                "scope": scope,
+                "name": name,
+                "description": description,
+                "body": body,
+            }),
+        )
+        .await;
+    }
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Template name is required.".to_string());
+    }
+    let path = library_path(&state, &workspace_id, scope).await?;
+    let mut templates = read_library(&path)?;
+    let template = PromptTemplate {
+        name: name.clone(),
+        description: description.filter(|value| !value.trim().is_empty()),
+        body,
+        updated_at: now_ms(),
+    };
+    match templates.iter_mut().find(|existing| existing.name == name) {
+        Some(existing) => *existing = template.clone(),
+        None => templates.push(template.clone()),
+    }
+    write_library(&path, &templates)?;
+    serde_json::to_value(PromptTemplateEntry {
+        placeholders: placeholders(&template.body),
+        template,
+        scope,
+    })
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn prompt_template_delete(
+    workspace_id: String,
+    scope: TemplateScope,
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "prompt_template_delete",
+            json!({ "workspaceId": workspace_id, "scope": scope, "name": name }),
+        )
+        .await;
+    }
+
+    let path = library_path(&state, &workspace_id, scope).await?;
+    let mut templates = read_library(&path)?;
+    let before = templates.len();
+    templates.retain(|template| template.name != name);
+    let deleted = templates.len() != before;
+    if deleted {
+        write_library(&path, &templates)?;
+    }
+    Ok(json!({ "deleted": deleted }))
+}
+
+#[tauri::command]
+pub(crate) async fn prompt_template_render(
+    workspace_id: String,
+    name: String,
+    values: BTreeMap<String, String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "prompt_template_render",
+            json!({ "workspaceId": workspace_id, "name": name, "values": values }),
+        )
+        .await;
+    }
+
+    let text = render_named(&state, &workspace_id, &name, &values).await?;
+    Ok(json!({ "text": text }))
+}
+
+/// Render a template and send it to a thread like a typed message.
+#[tauri::command]
+pub(crate) async fn prompt_template_send(
+    workspace_id: String,
+    thread_id: String,
+    name: String,
+    values: BTreeMap<String, String>,
+    model: Option<String>,
+    effort: Option<String>,
+    access_mode: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "prompt_template_send",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "name": name,
+                "values": values,
+                "model": model,
+                "effort": effort,
+                "accessMode": access_mode,
+            }),
+        )
+        .await;
+    }
+
+    let text = render_named(&state, &workspace_id, &name, &values).await?;
+    claude::send_user_message(
+        workspace_id,
+        thread_id,
+        text,
+        model,
+        effort,
+        access_mode,
+        None,
+        None,
+        None,
+        None,
+        None,
+        state,
+        app,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn template(name: &str, body: &str) -> PromptTemplate {
+        PromptTemplate {
+            name: name.to_string(),
+            description: None,
+            body: body.to_string(),
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn renders_placeholders_and_leaves_other_braces_alone() {
+        let body = "Review PR #{pr} on {branch}. Reply as {\"ok\": true}, use {{pr}} literally, then recheck {pr}.";
+        assert_eq!(placeholders(body), vec!["pr", "branch"]);
+        assert_eq!(
+            render(body, &values(&[("pr", "42"), ("branch", "main")])).unwrap(),
+            "Review PR #42 on main. Reply as {\"ok\": true}, use {pr} literally, then recheck 42."
+        );
+    }
+
+    #[test]
+    fn render_names_missing_values() {
+        let err = render(
+            "Write tests for {target} in {file}",
+            &values(&[("file", "a.rs")]),
+        )
+        .unwrap_err();
+        assert_eq!(err, "missing values for target");
+    }
+
+    #[test]
+    fn workspace_templates_shadow_global_ones() {
+        let merged = merge_libraries(
+            vec![template("review", "workspace review")],
+            vec![
+                template("tests", "write tests"),
+                template("review", "global review"),
+            ],
+        );
+        let listed: Vec<_> = merged
+            .iter()
+            .map(|entry| (entry.template.name.as_str(), entry.scope))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("review", TemplateScope::Workspace),
+                ("tests", TemplateScope::Global),
+            ]
+        );
+    }
+
+    #[test]
+    fn library_files_round_trip() {
+        let dir = std::env::temp_dir().join(format!("prompt-library-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(LIBRARY_FILE);
+        assert!(read_library(&path).unwrap().is_empty());
+        write_library(&path, &[template("review", "Review {pr}")]).unwrap();
+        assert_eq!(
+            read_library(&path).unwrap(),
+            vec![template("review", "Review {pr}")]
+        );
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -2,8 +2,8 @@ use serde::Serialize;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use tokio::task;
 use tauri::State;
+use tokio::task;
 
 use crate::claude_home::resolve_default_claude_home;
 use crate::state::AppState;
@@ -41,7 +41,7 @@ fn require_workspace_entry(
 
 fn app_data_dir(state: &State<'_, AppState>) -> Result<PathBuf, String> {
     state
-        .settings_path
+        .db_path
         .parent()
         .map(|path| path.to_path_buf())
         .ok_or_else(|| "Unable to resolve app data dir.".to_string())
@@ -52,10 +52,7 @@ fn workspace_prompts_dir(
     entry: &WorkspaceEntry,
 ) -> Result<PathBuf, String> {
     let data_dir = app_data_dir(state)?;
-    Ok(data_dir
-        .join("workspaces")
-        .join(&entry.id)
-        .join("prompts"))
+    Ok(data_dir.join("workspaces").join(&entry.id).join("prompts"))
 }
 
 fn prompt_roots_for_workspace(
@@ -172,7 +169,9 @@ fn build_prompt_contents(
     argument_hint: Option<String>,
     content: String,
 ) -> String {
-    let has_meta = description.as_ref().is_some_and(|value| !value.trim().is_empty())
+    let has_meta = description
+        .as_ref()
+        .is_some_and(|value| !value.trim().is_empty())
         || argument_hint
             .as_ref()
             .is_some_and(|value| !value.trim().is_empty());
@@ -183,7 +182,10 @@ fn build_prompt_contents(
     if let Some(description) = description {
         let trimmed = description.trim();
         if !trimmed.is_empty() {
-            output.push_str(&format!("description: \"{}\"\n", trimmed.replace('"', "\\\"")));
+            output.push_str(&format!(
+                "description: \"{}\"\n",
+                trimmed.replace('"', "\\\"")
+            ));
         }
     }
     if let Some(argument_hint) = argument_hint {
@@ -332,7 +334,8 @@ pub(crate) async fn prompts_create(
                 (dir, "workspace")
             }
             "global" => {
-                let dir = default_prompts_dir().ok_or("Unable to resolve CLAUDE_HOME".to_string())?;
+                let dir =
+                    default_prompts_dir().ok_or("Unable to resolve CLAUDE_HOME".to_string())?;
                 (dir, "global")
             }
             _ => return Err("Invalid scope.".to_string()),
@@ -0,0 +1,249 @@
+//! Find CLI processes a previous run of the monitor left behind.
+//!
+//! Every process the monitor spawns is recorded with its pid and arguments under the id
+//! of the current run, and a clean shutdown forgets them again. Records of other runs
+//! therefore point at processes that may have outlived a crash. Those still running
+//! with the recorded arguments are orphans: they can be adopted, which tails the
+//! thread's transcript read-only, or killed along with their process group.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+use uuid::Uuid;
+
+use crate::backend::process::kill_process_group;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage::{self, SessionProcessRecord};
+use crate::tail;
+
+static RUN_ID: OnceLock<String> = OnceLock::new();
+
+fn run_id() -> &'static str {
+    RUN_ID.get_or_init(|| Uuid::new_v4().to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OrphanedSession {
+    pub(crate) pid: u32,
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) started_at: i64,
+    /// Whether the thread's transcript is being tailed.
+    pub(crate) attached: bool,
+}
+
+/// Record a process spawned for a thread. Failures are logged: a process that can't be
+/// recovered after a crash is no reason to fail the turn.
+pub(crate) fn remember_spawn(workspace_id: &str, thread_id: &str, pid: u32, args: &[String]) {
+    let Some(path) = storage::database_path() else {
+        return;
+    };
+    let record = SessionProcessRecord {
+        run_id: run_id().to_string(),
+        pid,
+        workspace_id: workspace_id.to_string(),
+        thread_id: thread_id.to_string(),
+        command: args.join(" "),
+        started_at: chrono::Utc::now().timestamp_millis(),
+    };
+    if let Err(err) = storage::insert_session_process(path, &record) {
+        tracing::warn!("failed to record process {pid}: {err}");
+    }
+}
+
+/// Forget this run's processes after shutdown has stopped them.
+pub(crate) fn forget_run(db_path: &Path) {
+    if let Err(err) = storage::delete_run_session_processes(db_path, run_id()) {
+        tracing::warn!("failed to forget this run's processes: {err}");
+    }
+}
+
+/// Whether a running command line belongs to the recorded process. The program itself
+/// may show up as an interpreter (`node …/cli.js`), so only the arguments are compared.
+pub(crate) fn is_recorded_process(recorded_args: &str, command_line: &str) -> bool {
+    let recorded_args = recorded_args.trim();
+    !recorded_args.is_empty() && command_line.trim().ends_with(recorded_args)
+}
+
+#[cfg(unix)]
+fn process_command_line(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-ww", "-o", "args=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Reading another process's command line needs platform APIs the monitor does not use
+/// on Windows yet, so no orphans are found there.
+#[cfg(not(unix))]
+fn process_command_line(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Processes of earlier runs that are still alive. Records of processes that are gone
+/// are deleted along the way.
+fn find_orphans(db_path: &Path) -> Result<Vec<SessionProcessRecord>, String> {
+    let mut orphans = Vec::new();
+    for record in storage::read_session_processes(db_path)? {
+        if record.run_id == run_id() {
+            continue;
+        }
+        let alive = process_command_line(record.pid)
+            .is_some_and(|command_line| is_recorded_process(&record.command, &command_line));
+        if alive {
+            orphans.push(record);
+        } else {
+            storage::delete_session_process(db_path, &record.run_id, record.pid)?;
+        }
+    }
+    Ok(orphans)
+}
+
+fn kill_orphan(db_path: &Path, orphan: &SessionProcessRecord) -> Result<(), String> {
+    kill_process_group(orphan.pid);
+    storage::delete_session_process(db_path, &orphan.run_id, orphan.pid)
+}
+
+/// Stop the orphans of a thread before the monitor starts a new process for it, so two
+/// processes never write the same transcript.
+pub(crate) async fn reclaim_thread(workspace_id: &str, thread_id: &str) {
+    let Some(path) = storage::database_path() else {
+        return;
+    };
+    let workspace_id = workspace_id.to_string();
+    let thread_id = thread_id.to_string();
+    let result = tokio::task::spawn_blocking(move || {
+        for orphan in find_orphans(path)? {
+            if orphan.workspace_id == workspace_id && orphan.thread_id == thread_id {
+                kill_orphan(path, &orphan)?;
+            }
+        }
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|err| err.to_string())
+    .and_then(|result| result);
+    if let Err(err) = result {
+        tracing::warn!("failed to reclaim thread: {err}");
+    }
+}
+
+async fn find_orphan(
+    state: &AppState,
+    workspace_id: &str,
+    pid: u32,
+) -> Result<SessionProcessRecord, String> {
+    let db_path = state.db_path.clone();
+    tokio::task::spawn_blocking(move || find_orphans(&db_path))
+        .await
+        .map_err(|err| err.to_string())??
+        .into_iter()
+        .find(|orphan| orphan.workspace_id == workspace_id && orphan.pid == pid)
+        .ok_or_else(|| "orphaned process not found".to_string())
+}
+
+/// CLI processes left running by an earlier run of the monitor, newest first.
+#[tauri::command]
+pub(crate) async fn list_orphaned_sessions(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(&*state, app, "list_orphaned_sessions", json!({}))
+            .await;
+    }
+    let db_path = state.db_path.clone();
+    let found = tokio::task::spawn_blocking(move || find_orphans(&db_path))
+        .await
+        .map_err(|err| err.to_string())??;
+    let mut orphans = Vec::with_capacity(found.len());
+    for record in found {
+        orphans.push(OrphanedSession {
+            attached: tail::is_attached(&state, &record.workspace_id, &record.thread_id).await,
+            pid: record.pid,
+            workspace_id: record.workspace_id,
+            thread_id: record.thread_id,
+            started_at: record.started_at,
+        });
+    }
+    serde_json::to_value(orphans).map_err(|err| err.to_string())
+}
+
+/// Keep an orphan running and follow its thread's transcript. Sending the thread a
+/// message later stops the orphan and resumes the conversation in a new process.
+#[tauri::command]
+pub(crate) async fn adopt_orphaned_session(
+    workspace_id: String,
+    pid: u32,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "adopt_orphaned_session",
+            json!({ "workspaceId": workspace_id, "pid": pid }),
+        )
+        .await;
+    }
+    let orphan = find_orphan(&state, &workspace_id, pid).await?;
+    tail::start_tail(&state, app, workspace_id, orphan.thread_id, false, true).await
+}
+
+/// Kill an orphan and its process group.
+#[tauri::command]
+pub(crate) async fn kill_orphaned_session(
+    workspace_id: String,
+    pid: u32,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "kill_orphaned_session",
+            json!({ "workspaceId": workspace_id, "pid": pid }),
+        )
+        .await;
+    }
+    let orphan = find_orphan(&state, &workspace_id, pid).await?;
+    kill_orphan(&state.db_path, &orphan)?;
+    tail::stop_tail(&state, &workspace_id, &orphan.thread_id, true).await;
+    Ok(json!({ "killed": true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_the_recorded_process() {
+        let args = "--output-format stream-json --session-id thread-1";
+        assert!(is_recorded_process(
+            args,
+            "/usr/local/bin/claude --output-format stream-json --session-id thread-1"
+        ));
+        assert!(is_recorded_process(
+            args,
+            "node /usr/lib/node_modules/@anthropic-ai/claude-code/cli.js --output-format stream-json --session-id thread-1\n"
+        ));
+        assert!(!is_recorded_process(
+            args,
+            "/usr/local/bin/claude --output-format stream-json --session-id thread-2"
+        ));
+        assert!(!is_recorded_process(args, "/bin/zsh -l"));
+        assert!(!is_recorded_process(args, ""));
+        assert!(!is_recorded_process("", "/bin/zsh -l"));
+    }
+}
@@ -49,8 +49,7 @@ impl RemoteBackend {
             return Err(DISCONNECTED_MESSAGE.to_string());
         }
 
-        rx.await
-            .map_err(|_| DISCONNECTED_MESSAGE.to_string())?
+        rx.await.map_err(|_| DISCONNECTED_MESSAGE.to_string())?
     }
 }
 
@@ -217,6 +216,21 @@ async fn read_loop(
             "terminal-output" => {
                 let _ = app.emit("terminal-output", params);
             }
+            "terminal-exit" => {
+                let _ = app.emit("terminal-exit", params);
+            }
+            "activity" => {
+                let _ = app.emit("activity", params);
+            }
+            "batch-progress" => {
+                let _ = app.emit("batch-progress", params);
+            }
+            "pipeline-progress" => {
+                let _ = app.emit("pipeline-progress", params);
+            }
+            "cli-update-progress" => {
+                let _ = app.emit("cli-update-progress", params);
+            }
             _ => {}
         }
     }
@@ -0,0 +1,180 @@
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+use tokio::io::{AsyncWriteExt, BufReader as AsyncBufReader};
+use tokio::time::{sleep_until, Instant};
+use uuid::Uuid;
+
+use crate::backend::claude_cli::replay_workspace_session;
+use crate::backend::recorder::{
+    read_recording, replay_offset, workspace_recordings_dir, RecordedStream, RecordingHeader,
+};
+use crate::claude::{emit_event, read_persistent_stdout};
+use crate::event_sink::TauriEventSink;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::AgentKind;
+
+const MAX_SPEED: f64 = 1000.0;
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecordingSummary {
+    pub(crate) path: String,
+    pub(crate) thread_id: String,
+    pub(crate) agent: AgentKind,
+    pub(crate) started_at: i64,
+    pub(crate) size_bytes: u64,
+}
+
+fn read_header(path: &Path) -> Option<RecordingHeader> {
+    let file = File::open(path).ok()?;
+    let mut first = String::new();
+    BufReader::new(file).read_line(&mut first).ok()?;
+    serde_json::from_str(&first).ok()
+}
+
+/// Recordings of a workspace's threads, newest first.
+#[tauri::command]
+pub(crate) async fn list_recordings(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_recordings",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+    let mut recordings: Vec<RecordingSummary> = workspace_recordings_dir(&workspace_id)
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|item| item.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("jsonl"))
+        .filter_map(|path| {
+            let header = read_header(&path)?;
+            Some(RecordingSummary {
+                size_bytes: fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0),
+                path: path.to_string_lossy().into_owned(),
+                thread_id: header.thread_id,
+                agent: header.agent,
+                started_at: header.started_at,
+            })
+        })
+        .collect();
+    recordings.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    serde_json::to_value(recordings).map_err(|err| err.to_string())
+}
+
+/// Play a recording back into a workspace as a new `replay-…` thread. Its stdout goes
+/// through the normal reader, so the UI sees the same events as the original run;
+/// stdin lines are emitted as `replay/input`. `speed` scales the recorded timing (1 is
+/// real time). Returns the thread id right away; `replay/completed` marks the end.
+#[tauri::command]
+pub(crate) async fn replay_session(
+    workspace_id: String,
+    path: String,
+    speed: Option<f64>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "replay_session",
+            json!({ "workspaceId": workspace_id, "path": path, "speed": speed }),
+        )
+        .await;
+    }
+    let speed = speed.unwrap_or(1.0);
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err("speed must be a positive number".to_string());
+    }
+    let speed = speed.min(MAX_SPEED);
+    let (header, lines) = read_recording(Path::new(&path))?;
+    let entry = state
+        .workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not found")?;
+
+    let thread_id = format!("replay-{}", Uuid::new_v4());
+    let session = replay_workspace_session(entry, header.agent);
+    let event_sink = TauriEventSink::new(app);
+    emit_event(
+        &event_sink,
+        &workspace_id,
+        "replay/started",
+        json!({
+            "threadId": thread_id,
+            "recordedThreadId": header.thread_id,
+            "recordedAt": header.started_at,
+            "speed": speed,
+        }),
+    );
+
+    let (mut writer, reader) = tokio::io::duplex(PIPE_CAPACITY);
+    let feeder_sink = event_sink.clone();
+    let feeder_workspace = workspace_id.clone();
+    let feeder_thread = thread_id.clone();
+    tokio::spawn(async move {
+        let started = Instant::now();
+        for recorded in lines {
+            sleep_until(started + replay_offset(recorded.t, speed)).await;
+            match recorded.stream {
+                RecordedStream::Stdout => {
+                    let line = recorded.line + "\n";
+                    if writer.write_all(line.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+                RecordedStream::Stdin => {
+                    let frame = serde_json::from_str(&recorded.line)
+                        .unwrap_or(Value::String(recorded.line));
+                    emit_event(
+                        &feeder_sink,
+                        &feeder_workspace,
+                        "replay/input",
+                        json!({ "threadId": feeder_thread, "frame": frame }),
+                    );
+                }
+            }
+        }
+        // Dropping the writer ends the reader like a process exit would
+    });
+
+    let reader_thread = thread_id.clone();
+    tokio::spawn(async move {
+        read_persistent_stdout(
+            AsyncBufReader::new(reader),
+            workspace_id.clone(),
+            reader_thread.clone(),
+            Uuid::new_v4().to_string(),
+            session,
+            event_sink.clone(),
+        )
+        .await;
+        emit_event(
+            &event_sink,
+            &workspace_id,
+            "replay/completed",
+            json!({ "threadId": reader_thread }),
+        );
+    });
+
+    Ok(json!({ "threadId": thread_id }))
+}
@@ -0,0 +1,243 @@
+//! Pruning of old session data according to the app's `retention` settings.
+//!
+//! Transcripts are the CLI's `<session-id>.jsonl` files under `~/.claude/projects` for
+//! every registered workspace, archived ones included. A transcript, recording, turn
+//! timeline or turn diff is old once it has not changed for `transcriptDays`; usage
+//! records once their turn is `usageDays` old. Threads with a live process are never
+//! touched. The sweep runs in the background and on demand via `apply_retention_policy`.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager, State};
+use tokio::time::interval;
+
+use crate::backend::recorder::workspace_recordings_dir;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+use crate::transcripts::resolve_project_dir;
+use crate::types::{RetentionPolicy, WorkspaceEntry};
+use crate::usage;
+use crate::utils::now_ms;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// What one sweep removed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RetentionReport {
+    pub(crate) transcripts_removed: usize,
+    pub(crate) recordings_removed: usize,
+    /// Size of the removed transcript and recording files.
+    pub(crate) bytes_freed: u64,
+    /// Turn timelines, turn diffs and thread session records.
+    pub(crate) history_rows_removed: usize,
+    pub(crate) usage_records_removed: usize,
+}
+
+pub(crate) fn validate_retention_policy(policy: &RetentionPolicy) -> Result<(), String> {
+    let limits = [
+        ("transcriptDays", policy.transcript_days),
+        ("usageDays", policy.usage_days),
+    ];
+    for (name, days) in limits {
+        if days == Some(0) {
+            return Err(format!("retention.{name} must be at least 1 day"));
+        }
+    }
+    Ok(())
+}
+
+/// Apply the current retention policy now.
+#[tauri::command]
+pub(crate) async fn apply_retention_policy(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<RetentionReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "apply_retention_policy", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let policy = state.app_settings.lock().await.retention.clone();
+    sweep(&state, &policy).await
+}
+
+/// Sweep every `SWEEP_INTERVAL`, starting right after launch.
+pub(crate) fn spawn_retention_sweeper(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(SWEEP_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let Some(state) = app.try_state::<AppState>() else {
+                continue;
+            };
+            if remote_backend::is_remote_mode(&state).await {
+                continue;
+            }
+            let policy = state.app_settings.lock().await.retention.clone();
+            if policy == RetentionPolicy::default() {
+                continue;
+            }
+            match sweep(&state, &policy).await {
+                Ok(report) if report != RetentionReport::default() => {
+                    tracing::info!("pruned old data: {report:?}");
+                }
+                Ok(_) => {}
+                Err(err) => tracing::warn!("{err}"),
+            }
+        }
+    });
+}
+
+pub(crate) async fn sweep(
+    state: &AppState,
+    policy: &RetentionPolicy,
+) -> Result<RetentionReport, String> {
+    let now = now_ms();
+    let mut report = RetentionReport::default();
+    if let Some(cutoff) = cutoff(policy.transcript_days, now) {
+        let entries: Vec<WorkspaceEntry> =
+            state.workspaces.lock().await.values().cloned().collect();
+        let sessions = state.sessions.lock().await.clone();
+        let mut targets = Vec::new();
+        for entry in entries {
+            let live = match sessions.get(&entry.id) {
+                Some(session) => session.persistent_thread_ids().await.into_iter().collect(),
+                None => HashSet::new(),
+            };
+            targets.push((entry, live));
+        }
+        let db_path = state.db_path.clone();
+        report = tokio::task::spawn_blocking(move || prune_files(&db_path, &targets, cutoff))
+            .await
+            .map_err(|err| err.to_string())?;
+        report.history_rows_removed = storage::prune_turn_history(&state.db_path, cutoff)?;
+    }
+    if let Some(cutoff) = cutoff(policy.usage_days, now) {
+        report.usage_records_removed = usage::prune_before(cutoff)?;
+    }
+    Ok(report)
+}
+
+fn prune_files(
+    db_path: &Path,
+    targets: &[(WorkspaceEntry, HashSet<String>)],
+    cutoff: i64,
+) -> RetentionReport {
+    let mut report = RetentionReport::default();
+    for (entry, live) in targets {
+        if let Some(dir) = resolve_project_dir(entry) {
+            for (path, size) in stale_files(&dir, cutoff, |stem| live.contains(stem)) {
+                if fs::remove_file(&path).is_err() {
+                    continue;
+                }
+                report.transcripts_removed += 1;
+                report.bytes_freed += size;
+                if let (Some(file), Some(session_id)) = (
+                    path.to_str(),
+                    path.file_stem().and_then(|stem| stem.to_str()),
+                ) {
+                    if let Err(err) =
+                        storage::forget_transcript(db_path, &entry.id, session_id, file)
+                    {
+                        tracing::warn!("failed to drop {file} from the index: {err}");
+                    }
+                }
+            }
+        }
+        // Recordings are named `<thread-id>-<started-at>.jsonl`
+        let recording_of_live_thread = |stem: &str| {
+            live.iter()
+                .any(|thread_id| stem.starts_with(&format!("{thread_id}-")))
+        };
+        if let Some(dir) = workspace_recordings_dir(&entry.id) {
+            for (path, size) in stale_files(&dir, cutoff, recording_of_live_thread) {
+                if fs::remove_file(&path).is_ok() {
+                    report.recordings_removed += 1;
+                    report.bytes_freed += size;
+                }
+            }
+        }
+    }
+    report
+}
+
+/// `.jsonl` files directly in `dir` last modified before `cutoff`, with their size. Files
+/// whose stem `keep` accepts are left out.
+pub(crate) fn stale_files(
+    dir: &Path,
+    cutoff: i64,
+    keep: impl Fn(&str) -> bool,
+) -> Vec<(PathBuf, u64)> {
+    let Ok(dir_entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    dir_entries
+        .flatten()
+        .filter_map(|dir_entry| {
+            let path = dir_entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+                return None;
+            }
+            let stem = path.file_stem().and_then(|stem| stem.to_str())?;
+            if keep(stem) {
+                return None;
+            }
+            let meta = dir_entry.metadata().ok().filter(|meta| meta.is_file())?;
+            let modified = meta
+                .modified()
+                .ok()?
+                .duration_since(UNIX_EPOCH)
+                .ok()?
+                .as_millis() as i64;
+            (modified < cutoff).then_some((path, meta.len()))
+        })
+        .collect()
+}
+
+fn cutoff(days: Option<u32>, now: i64) -> Option<i64> {
+    days.map(|days| now - i64::from(days) * DAY_MS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn stale_files_skips_recent_kept_and_foreign_files() {
+        let dir = std::env::temp_dir().join(format!("retention-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["old.jsonl", "live.jsonl", "notes.txt"] {
+            fs::write(dir.join(name), "{}\n").unwrap();
+        }
+
+        let future = now_ms() + DAY_MS;
+        let stale = stale_files(&dir, future, |stem| stem == "live");
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].0, dir.join("old.jsonl"));
+        assert_eq!(stale[0].1, 3);
+
+        assert!(stale_files(&dir, now_ms() - DAY_MS, |_| false).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn zero_day_limits_are_rejected() {
+        assert!(validate_retention_policy(&RetentionPolicy::default()).is_ok());
+        let policy = RetentionPolicy {
+            transcript_days: Some(30),
+            usage_days: Some(0),
+        };
+        assert!(validate_retention_policy(&policy).is_err());
+        assert_eq!(cutoff(Some(1), DAY_MS * 3), Some(DAY_MS * 2));
+        assert_eq!(cutoff(None, DAY_MS), None);
+    }
+}
@@ -0,0 +1,864 @@
+//! Saved prompts that run on their own in a workspace.
+//!
+//! A schedule fires on a fixed interval or on a five-field cron expression evaluated in
+//! local time. Each run is a one-shot `claude -p` turn with no session kept, so it never
+//! touches the workspace's threads. Schedules live in `schedules.json` in the app data
+//! dir; the latest runs of each workspace go to `workspaces/<id>/schedule-runs.json`.
+//! A slot missed while the app was closed runs once at the next start.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use chrono::{
+    DateTime, Datelike, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Timelike,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+use tokio::time::interval;
+use uuid::Uuid;
+
+use crate::budget;
+use crate::claude;
+use crate::event_sink::TauriEventSink;
+use crate::notifications::{self, Notice};
+use crate::prompt_library;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::utils::now_ms;
+
+const SCHEDULES_FILE: &str = "schedules.json";
+const RUNS_FILE: &str = "schedule-runs.json";
+const MAX_RUNS_PER_WORKSPACE: usize = 50;
+const SCHEDULER_TICK: Duration = Duration::from_secs(30);
+const RUN_TIME_LIMIT: Duration = Duration::from_secs(30 * 60);
+
+static STORE_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+static RUNNING: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum Schedule {
+    Interval { minutes: u32 },
+    Cron { expression: String },
+}
+
+impl Schedule {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Interval { minutes: 0 } => {
+                Err("Interval must be at least one minute.".to_string())
+            }
+            Self::Interval { .. } => Ok(()),
+            Self::Cron { expression } => CronExpr::parse(expression).map(|_| ()),
+        }
+    }
+
+    /// The first time strictly after `after` this schedule fires.
+    pub(crate) fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            Self::Interval { minutes } => {
+                Some(after + chrono::Duration::minutes(i64::from(*minutes)))
+            }
+            Self::Cron { expression } => {
+                let cron = CronExpr::parse(expression).ok()?;
+                let mut naive = after.naive_local();
+                loop {
+                    naive = cron.next_after(naive)?;
+                    match Local.from_local_datetime(&naive) {
+                        LocalResult::Single(time) => return Some(time),
+                        LocalResult::Ambiguous(earliest, _) => return Some(earliest),
+                        // The slot fell into a daylight saving gap
+                        LocalResult::None => continue,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A parsed `minute hour day-of-month month day-of-week` expression. Fields accept `*`,
+/// numbers, ranges, lists and `/step`; day-of-week counts from Sunday as 0 (or 7).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let invalid = || format!("invalid cron field `{field}`");
+    let number = |value: &str| value.parse::<u32>().map_err(|_| invalid());
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, Some(number(step)?)),
+            None => (part, None),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (number(start)?, number(end)?)
+        } else {
+            let start = number(range)?;
+            // `5/15` means every 15 starting at 5
+            (start, if step.is_some() { max } else { start })
+        };
+        let step = step.unwrap_or(1);
+        if step == 0 || start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+impl CronExpr {
+    pub(crate) fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(
+                "cron expressions need five fields: minute hour day month weekday".to_string(),
+            );
+        };
+        let mut weekdays = parse_cron_field(weekday, 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_cron_field(minute, 0, 59)?,
+            hours: parse_cron_field(hour, 0, 23)?,
+            days: parse_cron_field(day, 1, 31)?,
+            months: parse_cron_field(month, 1, 12)?,
+            weekdays,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+
+    /// Like cron, a restricted day-of-month and day-of-week match when either does.
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+
+    pub(crate) fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        // Every valid expression fires within a few years (Feb 29 is the rarest day)
+        let limit = after + chrono::Duration::days(366 * 8);
+        while time <= limit {
+            let date = time.date();
+            if self.months & (1 << date.month()) == 0 {
+                let (year, month) = match date.month() {
+                    12 => (date.year() + 1, 1),
+                    month => (date.year(), month + 1),
+                };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.matches_date(date) {
+                time = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << time.hour()) == 0 {
+                time = date.and_hms_opt(time.hour(), 0, 0)? + chrono::Duration::hours(1);
+            } else if self.minutes & (1 << time.minute()) == 0 {
+                time += chrono::Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ScheduleRunStatus {
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ScheduleRunTrigger {
+    Schedule,
+    Manual,
+}
+
+/// A saved prompt, or a prompt library template with its values, and when to run it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScheduledPrompt {
+    pub(crate) id: String,
+    pub(crate) workspace_id: String,
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) prompt: Option<String>,
+    #[serde(default)]
+    pub(crate) template: Option<String>,
+    #[serde(default)]
+    pub(crate) values: BTreeMap<String, String>,
+    pub(crate) schedule: Schedule,
+    pub(crate) enabled: bool,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) permission_mode: Option<String>,
+    pub(crate) created_at: i64,
+    #[serde(default)]
+    pub(crate) last_run_at: Option<i64>,
+    #[serde(default)]
+    pub(crate) last_status: Option<ScheduleRunStatus>,
+    #[serde(default)]
+    pub(crate) next_run_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScheduleRun {
+    pub(crate) id: String,
+    pub(crate) schedule_id: String,
+    pub(crate) schedule_name: String,
+    pub(crate) trigger: ScheduleRunTrigger,
+    pub(crate) status: ScheduleRunStatus,
+    pub(crate) started_at: i64,
+    pub(crate) finished_at: i64,
+    #[serde(default)]
+    pub(crate) output: Option<String>,
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+}
+
+fn store_lock() -> MutexGuard<'static, ()> {
+    STORE_LOCK
+        .get_or_init(|| Mutex::new(()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn running() -> MutexGuard<'static, HashSet<String>> {
+    RUNNING
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn read_list<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(raw) => {
+            serde_json::from_str(&raw).map_err(|err| format!("invalid {}: {err}", path.display()))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn write_list<T: Serialize>(path: &Path, items: &[T]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(items).map_err(|err| err.to_string())?;
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, data).map_err(|err| err.to_string())?;
+    std::fs::rename(&temp_path, path).map_err(|err| err.to_string())
+}
+
+fn data_dir(state: &AppState) -> Result<&Path, String> {
+    state
+        .db_path
+        .parent()
+        .ok_or_else(|| "Unable to resolve app data dir.".to_string())
+}
+
+fn schedules_path(state: &AppState) -> Result<PathBuf, String> {
+    Ok(data_dir(state)?.join(SCHEDULES_FILE))
+}
+
+fn runs_path(state: &AppState, workspace_id: &str) -> Result<PathBuf, String> {
+    Ok(data_dir(state)?
+        .join("workspaces")
+        .join(workspace_id)
+        .join(RUNS_FILE))
+}
+
+/// Apply `change` to the stored schedules and write them back.
+fn update_schedules<R>(
+    state: &AppState,
+    change: impl FnOnce(&mut Vec<ScheduledPrompt>) -> Result<R, String>,
+) -> Result<R, String> {
+    let path = schedules_path(state)?;
+    let _guard = store_lock();
+    let mut schedules = read_list(&path)?;
+    let result = change(&mut schedules)?;
+    write_list(&path, &schedules)?;
+    Ok(result)
+}
+
+fn load_schedules(state: &AppState) -> Result<Vec<ScheduledPrompt>, String> {
+    let path = schedules_path(state)?;
+    let _guard = store_lock();
+    read_list(&path)
+}
+
+/// Stored schedules without their run state, for a configuration bundle.
+pub(crate) fn exported_schedules(state: &AppState) -> Result<Vec<ScheduledPrompt>, String> {
+    Ok(load_schedules(state)?
+        .into_iter()
+        .map(|schedule| ScheduledPrompt {
+            last_run_at: None,
+            last_status: None,
+            next_run_at: None,
+            ..schedule
+        })
+        .collect())
+}
+
+/// Add bundled schedules, replacing stored ones with the same id. Each is validated and
+/// gets its next slot from now; run state is kept for replaced ones.
+pub(crate) fn import_schedules(
+    state: &AppState,
+    imported: Vec<ScheduledPrompt>,
+) -> Result<usize, String> {
+    for schedule in &imported {
+        schedule
+            .schedule
+            .validate()
+            .map_err(|err| format!("schedule \"{}\": {err}", schedule.name))?;
+    }
+    let now = Local::now();
+    update_schedules(state, |schedules| {
+        let count = imported.len();
+        for mut schedule in imported {
+            schedule.next_run_at = next_run_at(&schedule, now);
+            match schedules.iter_mut().find(|stored| stored.id == schedule.id) {
+                Some(stored) => {
+                    schedule.last_run_at = stored.last_run_at;
+                    schedule.last_status = stored.last_status;
+                    *stored = schedule;
+                }
+                None => schedules.push(schedule),
+            }
+        }
+        Ok(count)
+    })
+}
+
+fn record_run(state: &AppState, workspace_id: &str, run: &ScheduleRun) -> Result<(), String> {
+    let path = runs_path(state, workspace_id)?;
+    let _guard = store_lock();
+    let mut runs: Vec<ScheduleRun> = read_list(&path)?;
+    runs.insert(0, run.clone());
+    runs.truncate(MAX_RUNS_PER_WORKSPACE);
+    write_list(&path, &runs)
+}
+
+fn next_run_at(schedule: &ScheduledPrompt, after: DateTime<Local>) -> Option<i64> {
+    if !schedule.enabled {
+        return None;
+    }
+    schedule
+        .schedule
+        .next_after(after)
+        .map(|time| time.timestamp_millis())
+}
+
+/// Claim the schedules whose time has come, moving each to its next slot.
+fn take_due(
+    schedules: &mut [ScheduledPrompt],
+    workspaces: &HashSet<String>,
+    now: DateTime<Local>,
+) -> Vec<String> {
+    let now_ms = now.timestamp_millis();
+    let mut due = Vec::new();
+    for schedule in schedules.iter_mut() {
+        if !schedule.enabled || !workspaces.contains(&schedule.workspace_id) {
+            continue;
+        }
+        if schedule.next_run_at.is_some_and(|at| at <= now_ms) {
+            schedule.next_run_at = next_run_at(schedule, now);
+            due.push(schedule.id.clone());
+        }
+    }
+    due
+}
+
+async fn resolve_prompt(state: &AppState, schedule: &ScheduledPrompt) -> Result<String, String> {
+    let prompt = match &schedule.template {
+        Some(name) => {
+            prompt_library::render_named(state, &schedule.workspace_id, name, &schedule.values)
+                .await?
+        }
+        None => schedule.prompt.clone().unwrap_or_default(),
+    };
+    if prompt.trim().is_empty() {
+        return Err("Scheduled prompt is empty.".to_string());
+    }
+    Ok(prompt)
+}
+
+async fn run_prompt(state: &AppState, schedule: &ScheduledPrompt) -> Result<String, String> {
+    let entry = state
+        .workspaces
+        .lock()
+        .await
+        .get(&schedule.workspace_id)
+        .cloned()
+        .ok_or("workspace not found")?;
+    budget::ensure_turn_allowed(state, &entry.id).await?;
+    let prompt = resolve_prompt(state, schedule).await?;
+    let permission_mode = schedule
+        .permission_mode
+        .clone()
+        .unwrap_or_else(|| claude::UNATTENDED_PERMISSION_MODE.to_string());
+    claude::run_claude_prompt_once(
+        &entry,
+        claude::workspace_claude_bin(state, &entry).await,
+        prompt,
+        Some(permission_mode),
+        schedule.model.clone().or(entry.model.clone()),
+        &claude::workspace_cli_args(&entry),
+        RUN_TIME_LIMIT,
+    )
+    .await
+}
+
+/// Run a schedule to completion, store the run, and tell the UI and the user.
+async fn execute(
+    app: AppHandle,
+    schedule_id: String,
+    trigger: ScheduleRunTrigger,
+) -> Result<ScheduleRun, String> {
+    let state = app.state::<AppState>();
+    let schedule = load_schedules(&state)?
+        .into_iter()
+        .find(|schedule| schedule.id == schedule_id)
+        .ok_or("schedule not found")?;
+    if !running().insert(schedule_id.clone()) {
+        return Err("This schedule is already running.".to_string());
+    }
+    let started_at = now_ms();
+    let outcome = run_prompt(&state, &schedule).await;
+    running().remove(&schedule_id);
+
+    let (status, output, error) = match outcome {
+        Ok(output) => (ScheduleRunStatus::Succeeded, Some(output), None),
+        Err(err) => (ScheduleRunStatus::Failed, None, Some(err)),
+    };
+    let run = ScheduleRun {
+        id: Uuid::new_v4().to_string(),
+        schedule_id: schedule_id.clone(),
+        schedule_name: schedule.name.clone(),
+        trigger,
+        status,
+        started_at,
+        finished_at: now_ms(),
+        output,
+        error,
+    };
+    record_run(&state, &schedule.workspace_id, &run)?;
+    update_schedules(&state, |schedules| {
+        if let Some(stored) = schedules.iter_mut().find(|stored| stored.id == schedule_id) {
+            stored.last_run_at = Some(started_at);
+            stored.last_status = Some(status);
+        }
+        Ok(())
+    })?;
+
+    claude::emit_event(
+        &TauriEventSink::new(app.clone()),
+        &schedule.workspace_id,
+        "schedule/completed",
+        json!({ "scheduleId": schedule_id, "run": run }),
+    );
+    notifications::notify(
+        &app,
+        &schedule.workspace_id,
+        Notice::ScheduledRunFinished {
+            name: schedule.name,
+            summary: run.output.clone(),
+            error: run.error.clone(),
+        },
+    );
+    Ok(run)
+}
+
+fn spawn_run(app: AppHandle, schedule_id: String, trigger: ScheduleRunTrigger) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = execute(app, schedule_id.clone(), trigger).await {
+            tracing::warn!("schedule {schedule_id} failed to run: {err}");
+        }
+    });
+}
+
+async fn run_due_schedules(app: &AppHandle, state: &AppState) -> Result<(), String> {
+    let workspaces: HashSet<String> = state.workspaces.lock().await.keys().cloned().collect();
+    let path = schedules_path(state)?;
+    let due = {
+        let _guard = store_lock();
+        let mut schedules = read_list(&path)?;
+        let due = take_due(&mut schedules, &workspaces, Local::now());
+        if !due.is_empty() {
+            write_list(&path, &schedules)?;
+        }
+        due
+    };
+    for schedule_id in due {
+        spawn_run(app.clone(), schedule_id, ScheduleRunTrigger::Schedule);
+    }
+    Ok(())
+}
+
+/// Check for due schedules every half minute. In remote mode the daemon runs them.
+pub(crate) fn spawn_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(SCHEDULER_TICK);
+        loop {
+            ticker.tick().await;
+            let Some(state) = app.try_state::<AppState>() else {
+                continue;
+            };
+            if remote_backend::is_remote_mode(&state).await {
+                continue;
+            }
+            if let Err(err) = run_due_schedules(&app, &state).await {
+                tracing::warn!("{err}");
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub(crate) async fn schedules_list(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "schedules_list",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let mut schedules: Vec<ScheduledPrompt> = load_schedules(&state)?
+        .into_iter()
+        .filter(|schedule| {
+            workspace_id
+                .as_deref()
+                .is_none_or(|id| schedule.workspace_id == id)
+        })
+        .collect();
+    schedules.sort_by(|a, b| a.name.cmp(&b.name));
+    serde_json::to_value(schedules).map_err(|err| err.to_string())
+}
+
+/// Create a schedule, or replace the one with `id`. Run history is kept on edits.
+#[tauri::command]
+pub(crate) async fn schedule_save(
+    workspace_id: String,
+    id: Option<String>,
+    name: String,
+    prompt: Option<String>,
+    template: Option<String>,
+    values: Option<BTreeMap<String, String>>,
+    schedule: Schedule,
+    enabled: Option<bool>,
+    model: Option<String>,
+    permission_mode: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "schedule_save",
+            json!({
+                "workspaceId": workspace_id,
+                "id": id,
+                "name": name,
+                "prompt": prompt,
+                "template": template,
+                "values": values,
+                "schedule": schedule,
+                "enabled": enabled,
+                "model": model,
+                "permissionMode": permission_mode,
+            }),
+        )
+        .await;
+    }
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Schedule name is required.".to_string());
+    }
+    let prompt = prompt.filter(|value| !value.trim().is_empty());
+    let template = template.filter(|value| !value.trim().is_empty());
+    if prompt.is_none() && template.is_none() {
+        return Err("A schedule needs a prompt or a template.".to_string());
+    }
+    schedule.validate()?;
+    if !state.workspaces.lock().await.contains_key(&workspace_id) {
+        return Err("workspace not found".to_string());
+    }
+    let mut saved = ScheduledPrompt {
+        id: id.clone().unwrap_or_else(|| Uuid::new_v4().to_string()),
+        workspace_id,
+        name,
+        prompt,
+        template,
+        values: values.unwrap_or_default(),
+        schedule,
+        enabled: enabled.unwrap_or(true),
+        model: model.filter(|value| !value.trim().is_empty()),
+        permission_mode: permission_mode.filter(|value| !value.trim().is_empty()),
+        created_at: now_ms(),
+        last_run_at: None,
+        last_status: None,
+        next_run_at: None,
+    };
+    saved.next_run_at = next_run_at(&saved, Local::now());
+    let saved = update_schedules(&state, |schedules| {
+        match id {
+            Some(id) => {
+                let existing = schedules
+                    .iter_mut()
+                    .find(|existing| existing.id == id)
+                    .ok_or("schedule not found")?;
+                saved.created_at = existing.created_at;
+                saved.last_run_at = existing.last_run_at;
+                saved.last_status = existing.last_status;
+                *existing = saved.clone();
+            }
+            None => schedules.push(saved.clone()),
+        }
+        Ok(saved)
+    })?;
+    serde_json::to_value(saved).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn schedule_delete(
+    schedule_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "schedule_delete",
+            json!({ "scheduleId": schedule_id }),
+        )
+        .await;
+    }
+
+    let deleted = update_schedules(&state, |schedules| {
+        let before = schedules.len();
+        schedules.retain(|schedule| schedule.id != schedule_id);
+        Ok(schedules.len() != before)
+    })?;
+    Ok(json!({ "deleted": deleted }))
+}
+
+/// Enabling restarts the clock from now rather than catching up on missed slots.
+#[tauri::command]
+pub(crate) async fn schedule_set_enabled(
+    schedule_id: String,
+    enabled: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "schedule_set_enabled",
+            json!({ "scheduleId": schedule_id, "enabled": enabled }),
+        )
+        .await;
+    }
+
+    let updated = update_schedules(&state, |schedules| {
+        let schedule = schedules
+            .iter_mut()
+            .find(|schedule| schedule.id == schedule_id)
+            .ok_or("schedule not found")?;
+        schedule.enabled = enabled;
+        schedule.next_run_at = next_run_at(schedule, Local::now());
+        Ok(schedule.clone())
+    })?;
+    serde_json::to_value(updated).map_err(|err| err.to_string())
+}
+
+/// Start a run in the background; `schedule/completed` reports the result. Does not
+/// move the schedule's next slot.
+#[tauri::command]
+pub(crate) async fn schedule_run_now(
+    schedule_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "schedule_run_now",
+            json!({ "scheduleId": schedule_id }),
+        )
+        .await;
+    }
+
+    if !load_schedules(&state)?
+        .iter()
+        .any(|schedule| schedule.id == schedule_id)
+    {
+        return Err("schedule not found".to_string());
+    }
+    if running().contains(&schedule_id) {
+        return Err("This schedule is already running.".to_string());
+    }
+    spawn_run(app, schedule_id, ScheduleRunTrigger::Manual);
+    Ok(json!({ "started": true }))
+}
+
+/// Recent runs in a workspace, newest first, optionally for one schedule.
+#[tauri::command]
+pub(crate) async fn schedule_runs_list(
+    workspace_id: String,
+    schedule_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "schedule_runs_list",
+            json!({ "workspaceId": workspace_id, "scheduleId": schedule_id }),
+        )
+        .await;
+    }
+
+    let path = runs_path(&state, &workspace_id)?;
+    let runs: Vec<ScheduleRun> = {
+        let _guard = store_lock();
+        read_list(&path)?
+    };
+    let runs: Vec<ScheduleRun> = runs
+        .into_iter()
+        .filter(|run| {
+            schedule_id
+                .as_deref()
+                .is_none_or(|id| run.schedule_id == id)
+        })
+        .collect();
+    serde_json::to_value(runs).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(expression: &str, after: &str) -> String {
+        CronExpr::parse(expression)
+            .unwrap()
+            .next_after(at(after))
+            .unwrap()
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+
+    #[test]
+    fn finds_the_next_cron_slot() {
+        assert_eq!(next("0 2 * * *", "2026-03-10 01:59"), "2026-03-10 02:00");
+        assert_eq!(next("0 2 * * *", "2026-03-10 02:00"), "2026-03-11 02:00");
+        assert_eq!(next("*/15 * * * *", "2026-03-10 10:07"), "2026-03-10 10:15");
+        assert_eq!(next("30 9 * * 1-5", "2026-03-13 10:00"), "2026-03-16 09:30");
+        assert_eq!(next("0 0 1 */3 *", "2026-02-14 12:00"), "2026-04-01 00:00");
+        assert_eq!(next("0 0 29 2 *", "2026-03-01 00:00"), "2028-02-29 00:00");
+        assert_eq!(next("@weekly", "2026-03-10 12:00"), "2026-03-15 00:00");
+        // Sunday as 7, and day-of-month OR day-of-week when both are restricted
+        assert_eq!(next("0 12 * * 7", "2026-03-10 12:00"), "2026-03-15 12:00");
+        assert_eq!(next("0 12 13 * 0", "2026-03-10 12:00"), "2026-03-13 12:00");
+    }
+
+    #[test]
+    fn rejects_malformed_cron_expressions() {
+        for expression in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(CronExpr::parse(expression).is_err(), "{expression}");
+        }
+        assert!(Schedule::Interval { minutes: 0 }.validate().is_err());
+    }
+
+    #[test]
+    fn claims_due_schedules_and_advances_them() {
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        let schedule =
+            |id: &str, workspace_id: &str, enabled: bool, next_run_at: i64| ScheduledPrompt {
+                id: id.to_string(),
+                workspace_id: workspace_id.to_string(),
+                name: id.to_string(),
+                prompt: Some("summarize open TODOs".to_string()),
+                template: None,
+                values: BTreeMap::new(),
+                schedule: Schedule::Interval { minutes: 60 },
+                enabled,
+                model: None,
+                permission_mode: None,
+                created_at: 0,
+                last_run_at: None,
+                last_status: None,
+                next_run_at: Some(next_run_at),
+            };
+        let past = now.timestamp_millis() - 1_000;
+        let mut schedules = vec![
+            schedule("due", "ws", true, past),
+            schedule("later", "ws", true, now.timestamp_millis() + 60_000),
+            schedule("disabled", "ws", false, past),
+            schedule("removed-workspace", "gone", true, past),
+        ];
+        let workspaces = HashSet::from(["ws".to_string()]);
+        assert_eq!(take_due(&mut schedules, &workspaces, now), vec!["due"]);
+        assert_eq!(
+            schedules[0].next_run_at,
+            Some((now + chrono::Duration::hours(1)).timestamp_millis())
+        );
+        assert!(take_due(&mut schedules, &workspaces, now).is_empty());
+    }
+}
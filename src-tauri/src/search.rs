@@ -0,0 +1,361 @@
+//! Full-text search over the Claude transcripts of every workspace.
+//!
+//! Transcripts are indexed lazily: each search first reindexes the session files that
+//! changed since the last one, so the index never needs a watcher.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+
+use crate::export::{load_session, ExportedBlock, Role};
+use crate::operations;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage::{
+    self, TranscriptDocument, TranscriptFileStamp, TranscriptQuery, HIGHLIGHT_END, HIGHLIGHT_START,
+};
+use crate::transcripts::resolve_project_dir;
+use crate::types::WorkspaceEntry;
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+/// Tool results can be whole files; only their start is indexed.
+const MAX_RESULT_CHARS: usize = 20_000;
+const ROLES: [&str; 3] = ["user", "assistant", "tool"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SnippetSegment {
+    pub(crate) text: String,
+    pub(crate) highlighted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TranscriptSearchHit {
+    pub(crate) workspace_id: String,
+    pub(crate) session_id: String,
+    /// `user`, `assistant` or `tool`.
+    pub(crate) role: String,
+    /// `text`, `tool_input` or `tool_result`.
+    pub(crate) kind: String,
+    pub(crate) timestamp: i64,
+    pub(crate) snippet: Vec<SnippetSegment>,
+}
+
+/// The searchable pieces of a session. Thinking is left out.
+pub(crate) fn session_documents(
+    path: &Path,
+    session_id: &str,
+) -> Result<Vec<TranscriptDocument>, String> {
+    let session = load_session(path, session_id)?;
+    let fallback = session.updated_at.or(session.started_at).unwrap_or(0);
+    let mut documents = Vec::new();
+    for message in &session.messages {
+        let timestamp = message.timestamp.unwrap_or(fallback);
+        let mut push = |role: &str, kind: &str, text: String| {
+            if !text.trim().is_empty() {
+                documents.push(TranscriptDocument {
+                    role: role.to_string(),
+                    kind: kind.to_string(),
+                    timestamp,
+                    text,
+                });
+            }
+        };
+        for block in &message.blocks {
+            match block {
+                ExportedBlock::Text { text } => {
+                    let role = match message.role {
+                        Role::User => "user",
+                        Role::Assistant => "assistant",
+                    };
+                    push(role, "text", text.clone());
+                }
+                ExportedBlock::Thinking { .. } => {}
+                ExportedBlock::ToolCall {
+                    name,
+                    input,
+                    output,
+                    ..
+                } => {
+                    push("tool", "tool_input", format!("{name} {input}"));
+                    if let Some(output) = output {
+                        let output = match output.char_indices().nth(MAX_RESULT_CHARS) {
+                            Some((end, _)) => output[..end].to_string(),
+                            None => output.clone(),
+                        };
+                        push("tool", "tool_result", output);
+                    }
+                }
+            }
+        }
+    }
+    Ok(documents)
+}
+
+/// Bring a workspace's part of the index up to date with its transcript directory.
+pub(crate) fn sync_workspace(db_path: &Path, entry: &WorkspaceEntry) -> Result<(), String> {
+    let mut indexed = storage::indexed_transcripts(db_path, &entry.id)?;
+    let files = resolve_project_dir(entry)
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|item| item.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("jsonl"));
+    for path in files {
+        let (Some(file), Some(session_id)) = (
+            path.to_str().map(str::to_string),
+            path.file_stem().and_then(|stem| stem.to_str()),
+        ) else {
+            continue;
+        };
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+        let stamp = TranscriptFileStamp {
+            size: meta.len() as i64,
+            modified: meta
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_millis() as i64)
+                .unwrap_or(0),
+        };
+        if indexed.remove(&file) == Some(stamp) {
+            continue;
+        }
+        let documents = match session_documents(&path, session_id) {
+            Ok(documents) => documents,
+            Err(err) => {
+                tracing::warn!("failed to index {file}: {err}");
+                continue;
+            }
+        };
+        storage::index_transcript(db_path, &entry.id, session_id, &file, stamp, &documents)?;
+    }
+    // Whatever is left was deleted since it was indexed
+    for file in indexed.into_keys() {
+        let session_id = Path::new(&file)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default()
+            .to_string();
+        storage::forget_transcript(db_path, &entry.id, &session_id, &file)?;
+    }
+    Ok(())
+}
+
+/// Turn free text into an FTS5 expression matching every term, so operators and
+/// punctuation typed by the user are searched for literally.
+pub(crate) fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Split a snippet from the index into plain and highlighted segments.
+pub(crate) fn parse_snippet(snippet: &str) -> Vec<SnippetSegment> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut highlighted = false;
+    for ch in snippet.chars() {
+        if ch == HIGHLIGHT_START || ch == HIGHLIGHT_END {
+            if !current.is_empty() {
+                segments.push(SnippetSegment {
+                    text: std::mem::take(&mut current),
+                    highlighted,
+                });
+            }
+            highlighted = ch == HIGHLIGHT_START;
+        } else {
+            current.push(ch);
+        }
+    }
+    if !current.is_empty() {
+        segments.push(SnippetSegment {
+            text: current,
+            highlighted,
+        });
+    }
+    segments
+}
+
+#[tauri::command]
+pub(crate) async fn search_transcripts(
+    query: String,
+    workspace_ids: Option<Vec<String>>,
+    since: Option<i64>,
+    until: Option<i64>,
+    roles: Option<Vec<String>>,
+    limit: Option<u32>,
+    operation_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "search_transcripts",
+            json!({
+                "query": query,
+                "workspaceIds": workspace_ids,
+                "since": since,
+                "until": until,
+                "roles": roles,
+                "limit": limit,
+                "operationId": operation_id,
+            }),
+        )
+        .await;
+    }
+
+    let Some(expression) = match_expression(&query) else {
+        return Ok(json!([]));
+    };
+    let roles = roles.unwrap_or_default();
+    if let Some(role) = roles.iter().find(|role| !ROLES.contains(&role.as_str())) {
+        return Err(format!("Unknown role: {role}"));
+    }
+    let entries: Vec<WorkspaceEntry> = {
+        let workspaces = state.workspaces.lock().await;
+        match &workspace_ids {
+            Some(ids) => {
+                let ids: HashSet<&String> = ids.iter().collect();
+                workspaces
+                    .values()
+                    .filter(|entry| ids.contains(&entry.id))
+                    .cloned()
+                    .collect()
+            }
+            None => workspaces.values().cloned().collect(),
+        }
+    };
+    let query = TranscriptQuery {
+        expression,
+        workspace_ids: entries.iter().map(|entry| entry.id.clone()).collect(),
+        roles,
+        since,
+        until,
+        limit: limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize,
+    };
+    if query.workspace_ids.is_empty() {
+        return Ok(json!([]));
+    }
+    let operation = operations::start(operation_id, "search_transcripts", None)?;
+    let token = operation.token().clone();
+    let db_path = state.db_path.clone();
+    let search = tokio::task::spawn_blocking(move || {
+        // Indexing is the slow part; stop between workspaces when cancelled
+        for entry in &entries {
+            if token.is_cancelled() {
+                return Err(operations::CANCELLED.to_string());
+            }
+            sync_workspace(&db_path, entry)?;
+        }
+        if token.is_cancelled() {
+            return Err(operations::CANCELLED.to_string());
+        }
+        storage::search_transcripts(&db_path, &query)
+    });
+    let hits = operation
+        .run(async { search.await.map_err(|err| err.to_string())? })
+        .await?;
+
+    let hits: Vec<TranscriptSearchHit> = hits
+        .into_iter()
+        .map(|hit| TranscriptSearchHit {
+            snippet: parse_snippet(&hit.snippet),
+            workspace_id: hit.workspace_id,
+            session_id: hit.session_id,
+            role: hit.role,
+            kind: hit.kind,
+            timestamp: hit.timestamp,
+        })
+        .collect();
+    serde_json::to_value(hits).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_every_term() {
+        assert_eq!(
+            match_expression("  fix OR \"race\"  "),
+            Some(r#""fix" "OR" """race""""#.to_string())
+        );
+        assert_eq!(match_expression("   "), None);
+    }
+
+    #[test]
+    fn splits_snippets_on_highlight_markers() {
+        let snippet = format!(
+            "…the {HIGHLIGHT_START}race{HIGHLIGHT_END} in {HIGHLIGHT_START}it{HIGHLIGHT_END}"
+        );
+        let segment = |text: &str, highlighted| SnippetSegment {
+            text: text.to_string(),
+            highlighted,
+        };
+        assert_eq!(
+            parse_snippet(&snippet),
+            vec![
+                segment("…the ", false),
+                segment("race", true),
+                segment(" in ", false),
+                segment("it", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn indexes_text_and_tool_calls_but_not_thinking() {
+        let dir = std::env::temp_dir().join(format!("search-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("s1.jsonl");
+        let lines = [
+            json!({"type": "user", "timestamp": "2026-01-01T00:00:00Z",
+                   "message": {"role": "user", "content": "Why is the build slow?"}}),
+            json!({"type": "assistant", "timestamp": "2026-01-01T00:00:05Z",
+                   "message": {"role": "assistant", "content": [
+                       {"type": "thinking", "thinking": "secret plan"},
+                       {"type": "tool_use", "id": "t1", "name": "Bash",
+                        "input": {"command": "cargo build --timings"}}]}}),
+            json!({"type": "user", "timestamp": "2026-01-01T00:00:09Z",
+                   "message": {"role": "user", "content": [
+                       {"type": "tool_result", "tool_use_id": "t1",
+                        "content": "Finished in 90s"}]}}),
+        ];
+        let text: Vec<String> = lines.iter().map(Value::to_string).collect();
+        fs::write(&path, text.join("\n")).unwrap();
+
+        let documents = session_documents(&path, "s1").unwrap();
+        let summary: Vec<(&str, &str)> = documents
+            .iter()
+            .map(|document| (document.role.as_str(), document.kind.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("user", "text"),
+                ("tool", "tool_input"),
+                ("tool", "tool_result")
+            ]
+        );
+        assert!(documents[1].text.contains("cargo build --timings"));
+        assert_eq!(documents[2].text, "Finished in 90s");
+        assert!(documents
+            .iter()
+            .all(|document| !document.text.contains("secret")));
+    }
+}
@@ -1,8 +1,13 @@
 use tauri::{State, Window};
 
 use crate::claude_config;
+use crate::event_sink::{self, MAX_DELTA_BATCH_MS};
+use crate::proxy::{self, validate_proxy_settings};
+use crate::redaction;
+use crate::retention::validate_retention_policy;
 use crate::state::AppState;
-use crate::storage::write_settings;
+use crate::storage::{clear_redacted_secrets, write_settings};
+use crate::telemetry::{self, validate_log_level};
 use crate::types::AppSettings;
 use crate::window;
 
@@ -31,12 +36,45 @@ pub(crate) async fn update_app_settings(
     state: State<'_, AppState>,
     window: Window,
 ) -> Result<AppSettings, String> {
+    let settings = store_app_settings(&state, settings).await?;
+    let _ = window::apply_window_appearance(&window, settings.theme.as_str());
+    Ok(settings)
+}
+
+/// Validate, persist and apply new app settings.
+pub(crate) async fn store_app_settings(
+    state: &AppState,
+    settings: AppSettings,
+) -> Result<AppSettings, String> {
+    if let Some(proxy) = &settings.proxy {
+        validate_proxy_settings(proxy)?;
+    }
+    validate_retention_policy(&settings.retention)?;
+    if let Some(level) = &settings.log_level {
+        validate_log_level(level)?;
+    }
+    if settings
+        .delta_batch_ms
+        .is_some_and(|batch_ms| batch_ms > MAX_DELTA_BATCH_MS)
+    {
+        return Err(format!(
+            "Streamed text can be batched for at most {MAX_DELTA_BATCH_MS} ms."
+        ));
+    }
+    redaction::configure(&settings.redaction)?;
     let _ = claude_config::write_collab_enabled(settings.experimental_collab_enabled);
     let _ = claude_config::write_steer_enabled(settings.experimental_steer_enabled);
     let _ = claude_config::write_unified_exec_enabled(settings.experimental_unified_exec_enabled);
-    write_settings(&state.settings_path, &settings)?;
+    write_settings(&state.db_path, &settings)?;
     let mut current = state.app_settings.lock().await;
+    if current.redaction.keep_originals && !settings.redaction.keep_originals {
+        if let Err(err) = clear_redacted_secrets(&state.db_path) {
+            tracing::warn!("failed to drop kept originals: {err}");
+        }
+    }
+    proxy::configure(settings.proxy.as_ref());
+    telemetry::set_log_level(settings.log_level.as_deref());
+    event_sink::configure_delta_batching(settings.delta_batch_ms);
     *current = settings.clone();
-    let _ = window::apply_window_appearance(&window, settings.theme.as_str());
     Ok(settings)
 }
@@ -0,0 +1,81 @@
+//! Orderly teardown when the app or daemon exits, so no CLI process outlives it.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::join_all;
+use tokio::time::{sleep, Instant};
+
+use crate::backend::claude_cli::WorkspaceSession;
+use crate::operations;
+use crate::recovery;
+use crate::state::AppState;
+use crate::storage;
+
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Refuse new turns once shutdown has begun, so draining can finish.
+pub(crate) fn ensure_accepting_turns() -> Result<(), String> {
+    if SHUTTING_DOWN.load(Ordering::SeqCst) {
+        return Err("The app is shutting down.".to_string());
+    }
+    Ok(())
+}
+
+/// Stop every workspace's CLI processes and persist what the readers will not get to.
+///
+/// Cancellable work (exports, searches, batches, pipelines, watchers and tails) stops
+/// first. In-flight turns get up to `shutdownDrainSeconds` (none by default) to finish.
+/// Each process then has its stdin flushed and its process group terminated, all
+/// workspaces at once. Threads still marked running are recorded as interrupted, since
+/// their readers may not run again before the process exits. Only the first call does
+/// anything.
+pub(crate) async fn shutdown(state: &AppState) {
+    if SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    operations::cancel_all();
+    let sessions: Vec<Arc<WorkspaceSession>> =
+        state.sessions.lock().await.values().cloned().collect();
+    let drain = state
+        .app_settings
+        .lock()
+        .await
+        .shutdown_drain_seconds
+        .unwrap_or(0);
+    if drain > 0 && !wait_for_turns(&sessions, Duration::from_secs(drain)).await {
+        tracing::info!("turns still running after {drain}s; stopping them");
+    }
+
+    join_all(sessions.iter().map(|session| session.terminate_all())).await;
+    recovery::forget_run(&state.db_path);
+
+    if let Err(err) = storage::mark_running_sessions_interrupted(&state.db_path) {
+        tracing::warn!("failed to record interrupted threads: {err}");
+    }
+    let workspaces: Vec<_> = state.workspaces.lock().await.values().cloned().collect();
+    if let Err(err) = storage::write_workspaces(&state.db_path, &workspaces) {
+        tracing::warn!("failed to save workspaces: {err}");
+    }
+}
+
+/// Wait until no session runs a turn. Returns whether they all finished in time.
+async fn wait_for_turns(sessions: &[Arc<WorkspaceSession>], timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let mut running = false;
+        for session in sessions {
+            running |= session.turn_queue.lock().await.has_running_turns();
+        }
+        if !running {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        sleep(DRAIN_POLL_INTERVAL).await;
+    }
+}
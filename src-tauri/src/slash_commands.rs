@@ -0,0 +1,446 @@
+//! Custom slash commands: Markdown files under `<project>/.claude/commands` and
+//! `~/.claude/commands`.
+//!
+//! Running one sends `/name arguments` to the thread like a typed message, so the CLI
+//! expands it (arguments, `!` commands, `@` files, `allowed-tools`) exactly as it would
+//! in a terminal. Files in subdirectories keep their plain name and show the directory
+//! as a namespace; a project command hides a user command of the same name.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+
+use crate::claude;
+use crate::claude_home::resolve_default_claude_home;
+use crate::remote_backend;
+use crate::state::AppState;
+
+/// How deep subdirectories of a commands directory are searched.
+const MAX_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SlashCommandScope {
+    Project,
+    User,
+}
+
+/// A custom slash command found on disk.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SlashCommand {
+    /// Typed as `/name`.
+    pub(crate) name: String,
+    pub(crate) scope: SlashCommandScope,
+    /// Subdirectory of the commands directory, `/`-separated.
+    pub(crate) namespace: Option<String>,
+    pub(crate) path: String,
+    /// From the frontmatter, else the first line of the body.
+    pub(crate) description: Option<String>,
+    pub(crate) argument_hint: Option<String>,
+    /// Tools the command may use without asking, as written in `allowed-tools`.
+    pub(crate) allowed_tools: Vec<String>,
+    pub(crate) model: Option<String>,
+    /// A project command of the same name is used instead.
+    pub(crate) shadowed: bool,
+}
+
+/// The `---` block of a command or agent file. Keys are lowercased with `_` read as
+/// `-`; a YAML `- item` list is kept as its items joined with `, `.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Frontmatter {
+    fields: Vec<(String, String)>,
+}
+
+impl Frontmatter {
+    pub(crate) fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .rev()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+            .filter(|value| !value.is_empty())
+    }
+
+    pub(crate) fn list(&self, key: &str) -> Vec<String> {
+        self.get(key).map(split_list).unwrap_or_default()
+    }
+}
+
+fn user_commands_dir() -> Option<PathBuf> {
+    resolve_default_claude_home().map(|home| home.join("commands"))
+}
+
+/// Project commands first, then user commands, each by name.
+fn discover(project_dir: Option<&Path>, user_dir: Option<&Path>) -> Vec<SlashCommand> {
+    let mut project = Vec::new();
+    if let Some(dir) = project_dir {
+        scan_dir(dir, dir, SlashCommandScope::Project, 0, &mut project);
+    }
+    let mut user = Vec::new();
+    if let Some(dir) = user_dir {
+        scan_dir(dir, dir, SlashCommandScope::User, 0, &mut user);
+    }
+    project.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    user.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    for command in &mut user {
+        command.shadowed = project.iter().any(|other| other.name == command.name);
+    }
+    project.extend(user);
+    project
+}
+
+fn scan_dir(
+    root: &Path,
+    dir: &Path,
+    scope: SlashCommandScope,
+    depth: usize,
+    out: &mut Vec<SlashCommand>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if depth < MAX_DEPTH {
+                scan_dir(root, &path, scope, depth + 1, out);
+            }
+            continue;
+        }
+        let is_md = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if !is_md || name.is_empty() || name.contains(char::is_whitespace) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let (frontmatter, body) = parse_frontmatter(&content);
+        let namespace = path
+            .parent()
+            .and_then(|parent| parent.strip_prefix(root).ok())
+            .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+            .filter(|relative| !relative.is_empty());
+        let description = frontmatter
+            .get("description")
+            .map(str::to_string)
+            .or_else(|| {
+                body.lines()
+                    .map(str::trim)
+                    .find(|line| !line.is_empty())
+                    .map(|line| line.trim_start_matches('#').trim().to_string())
+            });
+        out.push(SlashCommand {
+            name: name.to_string(),
+            scope,
+            namespace,
+            path: path.to_string_lossy().to_string(),
+            description,
+            argument_hint: frontmatter.get("argument-hint").map(str::to_string),
+            allowed_tools: frontmatter.list("allowed-tools"),
+            model: frontmatter.get("model").map(str::to_string),
+            shadowed: false,
+        });
+    }
+}
+
+/// Split a command or agent file into its frontmatter and body. A file without a
+/// closed `---` block is all body.
+pub(crate) fn parse_frontmatter(content: &str) -> (Frontmatter, &str) {
+    let mut frontmatter = Frontmatter::default();
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (frontmatter, content);
+    };
+    let mut offset = content.len() - rest.len();
+    let mut in_list = false;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let trimmed = line.trim();
+        if trimmed == "---" {
+            return (frontmatter, &content[offset..]);
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        // `- item` under a key with no value of its own
+        if let Some(item) = trimmed.strip_prefix("- ").filter(|_| in_list) {
+            if let Some((_, value)) = frontmatter.fields.last_mut() {
+                if !value.is_empty() {
+                    value.push_str(", ");
+                }
+                value.push_str(&unquote(item));
+            }
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else {
+            in_list = false;
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase().replace('_', "-");
+        in_list = value.trim().is_empty();
+        frontmatter.fields.push((key, unquote(value)));
+    }
+    (Frontmatter::default(), content)
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner.to_string();
+        }
+    }
+    value.to_string()
+}
+
+/// `Read, Bash(git add:*)` or `[Read, Grep]`; commas inside parentheses belong to the
+/// tool's pattern.
+fn split_list(value: &str) -> Vec<String> {
+    let value = value
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .unwrap_or(value);
+    let mut tools = Vec::new();
+    let mut depth = 0usize;
+    let mut current = String::new();
+    for ch in value.chars() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                tools.push(unquote(&current));
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(ch);
+    }
+    tools.push(unquote(&current));
+    tools.retain(|tool| !tool.is_empty());
+    tools
+}
+
+async fn workspace_commands(
+    state: &AppState,
+    workspace_id: &str,
+) -> Result<Vec<SlashCommand>, String> {
+    let project_dir = state
+        .workspaces
+        .lock()
+        .await
+        .get(workspace_id)
+        .map(|entry| PathBuf::from(&entry.path).join(".claude").join("commands"))
+        .ok_or("workspace not found")?;
+    let user_dir = user_commands_dir();
+    tokio::task::spawn_blocking(move || discover(Some(&project_dir), user_dir.as_deref()))
+        .await
+        .map_err(|_| "slash command discovery failed".to_string())
+}
+
+/// The workspace's custom slash commands, project commands first.
+#[tauri::command]
+pub(crate) async fn list_slash_commands(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<SlashCommand>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_slash_commands",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    workspace_commands(&state, &workspace_id).await
+}
+
+/// Send `/name arguments` to a thread as a turn.
+#[tauri::command]
+pub(crate) async fn run_slash_command(
+    workspace_id: String,
+    thread_id: String,
+    name: String,
+    arguments: Option<String>,
+    model: Option<String>,
+    access_mode: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "run_slash_command",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "name": name,
+                "arguments": arguments,
+                "model": model,
+                "accessMode": access_mode,
+            }),
+        )
+        .await;
+    }
+
+    let name = name.trim().trim_start_matches('/').to_string();
+    let command = workspace_commands(&state, &workspace_id)
+        .await?
+        .into_iter()
+        .find(|command| command.name == name && !command.shadowed)
+        .ok_or_else(|| format!("no slash command named /{name}"))?;
+    let text = match arguments.as_deref().map(str::trim) {
+        Some(arguments) if !arguments.is_empty() => format!("/{} {arguments}", command.name),
+        _ => format!("/{}", command.name),
+    };
+    // The command's own `model` applies unless the caller picked one
+    let model = model.or(command.model);
+    claude::send_user_message(
+        workspace_id,
+        thread_id,
+        text,
+        model,
+        None,
+        access_mode,
+        None,
+        None,
+        None,
+        None,
+        None,
+        state,
+        app,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_frontmatter_in_its_usual_forms() {
+        let (frontmatter, body) = parse_frontmatter(
+            "---\n\
+             description: \"Create a git commit\"\n\
+             argument-hint: [message]\n\
+             allowed-tools: Bash(git add:*), Bash(git status:*), Read\n\
+             model: claude-3-5-haiku-latest\n\
+             ---\n\
+             Commit with message: $ARGUMENTS\n",
+        );
+        assert_eq!(frontmatter.get("description"), Some("Create a git commit"));
+        assert_eq!(frontmatter.get("argument-hint"), Some("[message]"));
+        assert_eq!(
+            frontmatter.list("allowed-tools"),
+            ["Bash(git add:*)", "Bash(git status:*)", "Read"]
+        );
+        assert_eq!(frontmatter.get("model"), Some("claude-3-5-haiku-latest"));
+        assert_eq!(body, "Commit with message: $ARGUMENTS\n");
+
+        let (frontmatter, _) =
+            parse_frontmatter("---\nallowed-tools:\n  - Read\n  - 'Grep'\ndescription: x\n---\n");
+        assert_eq!(frontmatter.list("allowed-tools"), ["Read", "Grep"]);
+        assert_eq!(frontmatter.get("description"), Some("x"));
+        let (_, body) = parse_frontmatter("---\ndescription: never closed\n");
+        assert_eq!(body, "---\ndescription: never closed\n");
+        assert_eq!(
+            split_list("[Read, Bash(npm run test, lint)]"),
+            ["Read", "Bash(npm run test, lint)"]
+        );
+    }
+
+    #[test]
+    fn discovers_project_and_user_commands() {
+        let base = std::env::temp_dir().join(format!("slash-commands-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let project = base.join("project/.claude/commands");
+        let user = base.join("home/.claude/commands");
+        fs::create_dir_all(project.join("frontend")).unwrap();
+        fs::create_dir_all(&user).unwrap();
+        fs::write(
+            project.join("review.md"),
+            "# Review the diff\n\nLook at $ARGUMENTS",
+        )
+        .unwrap();
+        fs::write(
+            project.join("frontend/component.md"),
+            "---\ndescription: New component\n---\nCreate $1",
+        )
+        .unwrap();
+        fs::write(project.join("notes.txt"), "not a command").unwrap();
+        fs::write(user.join("review.md"), "My review").unwrap();
+        fs::write(user.join("standup.md"), "Summarize yesterday").unwrap();
+
+        let commands = discover(Some(&project), Some(&user));
+        let summary: Vec<_> = commands
+            .iter()
+            .map(|command| {
+                (
+                    command.name.as_str(),
+                    command.scope,
+                    command.namespace.as_deref(),
+                    command.description.as_deref(),
+                    command.shadowed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "component",
+                    SlashCommandScope::Project,
+                    Some("frontend"),
+                    Some("New component"),
+                    false
+                ),
+                (
+                    "review",
+                    SlashCommandScope::Project,
+                    None,
+                    Some("Review the diff"),
+                    false
+                ),
+                (
+                    "review",
+                    SlashCommandScope::User,
+                    None,
+                    Some("My review"),
+                    true
+                ),
+                (
+                    "standup",
+                    SlashCommandScope::User,
+                    None,
+                    Some("Summarize yesterday"),
+                    false
+                ),
+            ]
+        );
+
+        let _ = fs::remove_dir_all(base);
+    }
+}
@@ -3,27 +3,32 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use tauri::{AppHandle, Manager};
-use tokio::sync::{watch, Mutex};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 
 use crate::dictation::DictationState;
-use crate::storage::{read_settings, read_workspaces};
-use crate::types::{AppSettings, WorkspaceEntry};
+use crate::storage::{
+    mark_running_sessions_interrupted, read_settings, read_workspaces, set_database_path,
+    DATABASE_FILE,
+};
+use crate::types::{AppSettings, RedactionSettings, WorkspaceEntry};
 
 pub(crate) struct AppState {
     pub(crate) workspaces: Mutex<HashMap<String, WorkspaceEntry>>,
     pub(crate) sessions: Mutex<HashMap<String, Arc<crate::claude::WorkspaceSession>>>,
     pub(crate) thread_watchers: Mutex<HashMap<String, WorkspaceWatcher>>,
-    pub(crate) terminal_sessions:
-        Mutex<HashMap<String, Arc<crate::terminal::TerminalSession>>>,
+    /// Live transcript tails keyed by `<workspace-id>:<thread-id>`.
+    pub(crate) transcript_tails: Mutex<HashMap<String, crate::tail::TranscriptTail>>,
+    pub(crate) terminal_sessions: Mutex<HashMap<String, Arc<crate::terminal::TerminalSession>>>,
     pub(crate) remote_backend: Mutex<Option<crate::remote_backend::RemoteBackend>>,
-    pub(crate) storage_path: PathBuf,
-    pub(crate) settings_path: PathBuf,
+    /// SQLite database holding workspaces, settings, sessions and usage.
+    pub(crate) db_path: PathBuf,
     pub(crate) app_settings: Mutex<AppSettings>,
     pub(crate) dictation: Mutex<DictationState>,
 }
 
 pub(crate) struct WorkspaceWatcher {
-    pub(crate) shutdown: watch::Sender<bool>,
+    pub(crate) shutdown: CancellationToken,
     pub(crate) workspace_path: String,
 }
 
@@ -33,18 +38,34 @@ impl AppState {
             .path()
             .app_data_dir()
             .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| ".".into()));
-        let storage_path = data_dir.join("workspaces.json");
-        let settings_path = data_dir.join("settings.json");
-        let workspaces = read_workspaces(&storage_path).unwrap_or_default();
-        let app_settings = read_settings(&settings_path).unwrap_or_default();
+        crate::telemetry::init(&data_dir);
+        let db_path = data_dir.join(DATABASE_FILE);
+        set_database_path(db_path.clone());
+        crate::storage_encryption::load_key(&db_path);
+        crate::backend::recorder::set_recordings_dir(data_dir.join("recordings"));
+        crate::backend::session_logs::set_session_logs_dir(data_dir.join("session-logs"));
+        if let Err(err) = mark_running_sessions_interrupted(&db_path) {
+            tracing::warn!("failed to open {}: {err}", db_path.display());
+        }
+        crate::usage::load_ledger(db_path.clone());
+        crate::api_tokens::load_tokens(db_path.clone());
+        let workspaces = read_workspaces(&db_path).unwrap_or_default();
+        let app_settings = read_settings(&db_path).unwrap_or_default();
+        if let Err(err) = crate::redaction::configure(&app_settings.redaction) {
+            tracing::warn!("redaction patterns: {err}; using the built-in patterns only");
+            let _ = crate::redaction::configure(&RedactionSettings::default());
+        }
+        crate::proxy::configure(app_settings.proxy.as_ref());
+        crate::telemetry::set_log_level(app_settings.log_level.as_deref());
+        crate::event_sink::configure_delta_batching(app_settings.delta_batch_ms);
         Self {
             workspaces: Mutex::new(workspaces),
             sessions: Mutex::new(HashMap::new()),
             thread_watchers: Mutex::new(HashMap::new()),
+            transcript_tails: Mutex::new(HashMap::new()),
             terminal_sessions: Mutex::new(HashMap::new()),
             remote_backend: Mutex::new(None),
-            storage_path,
-            settings_path,
+            db_path,
             app_settings: Mutex::new(app_settings),
             dictation: Mutex::new(DictationState::default()),
         }
@@ -1,80 +1,2385 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::fs;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock, RwLockReadGuard};
 
-use crate::types::{AppSettings, WorkspaceEntry};
+use rusqlite::{params, Connection, DatabaseName, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
 
-pub(crate) fn read_workspaces(path: &PathBuf) -> Result<HashMap<String, WorkspaceEntry>, String> {
-    if !path.exists() {
-        return Ok(HashMap::new());
+use crate::redaction;
+use crate::types::{
+    ActivityEntry, Annotation, AnnotationTarget, ApiToken, AppSettings, AuditEntry, BashDecision,
+    CompactionRecord, IssueLink, PendingMessage, TurnDiff, TurnEdits, TurnTimeline, TurnUsage,
+    WorkspaceEntry,
+};
+use crate::utils::now_ms;
+
+/// Everything the monitor remembers between launches lives in one SQLite database in the
+/// app data dir. Rows that are only ever read back whole (workspace entries, settings,
+/// turn usage) are stored as JSON so new serde fields need no migration.
+pub(crate) const DATABASE_FILE: &str = "monitor.db";
+
+const SCHEMA_VERSION: i64 = 15;
+const MAX_TIMELINES_PER_WORKSPACE: i64 = 500;
+const MAX_DIFFS_PER_WORKSPACE: i64 = 200;
+const MAX_COMPACTIONS_PER_WORKSPACE: i64 = 500;
+const MAX_AUDIT_ENTRIES: i64 = 10_000;
+const MAX_BASH_DECISIONS_PER_WORKSPACE: i64 = 10_000;
+const MAX_ACTIVITY_ENTRIES: i64 = 5000;
+const SETTINGS_KEY: &str = "app_settings";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS workspaces (
+    id TEXT PRIMARY KEY,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS kv (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS thread_sessions (
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    model TEXT,
+    status TEXT NOT NULL,
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (workspace_id, thread_id)
+);
+CREATE TABLE IF NOT EXISTS session_processes (
+    run_id TEXT NOT NULL,
+    pid INTEGER NOT NULL,
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    command TEXT NOT NULL,
+    started_at INTEGER NOT NULL,
+    PRIMARY KEY (run_id, pid)
+);
+CREATE TABLE IF NOT EXISTS redacted_secrets (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    nonce BLOB NOT NULL,
+    ciphertext BLOB NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS turns (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    workspace_id TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS turns_by_workspace ON turns (workspace_id, timestamp);
+CREATE TABLE IF NOT EXISTS archived_threads (
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    PRIMARY KEY (workspace_id, thread_id)
+);
+CREATE TABLE IF NOT EXISTS turn_timelines (
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    turn_id TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (workspace_id, turn_id)
+);
+CREATE TABLE IF NOT EXISTS turn_diffs (
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    turn_id TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (workspace_id, turn_id)
+);
+CREATE TABLE IF NOT EXISTS turn_edits (
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    turn_id TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (workspace_id, turn_id)
+);
+CREATE INDEX IF NOT EXISTS turn_timelines_by_thread ON turn_timelines (workspace_id, thread_id, timestamp);
+CREATE TABLE IF NOT EXISTS compactions (
+    id TEXT PRIMARY KEY,
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS compactions_by_thread ON compactions (workspace_id, thread_id, timestamp);
+CREATE TABLE IF NOT EXISTS outbox (
+    id TEXT PRIMARY KEY,
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    queued_at INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS outbox_by_workspace ON outbox (workspace_id, queued_at);
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id TEXT PRIMARY KEY,
+    hash TEXT NOT NULL UNIQUE,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    token_id TEXT,
+    timestamp INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS audit_log_by_token ON audit_log (token_id, id);
+CREATE TABLE IF NOT EXISTS bash_decisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    workspace_id TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS bash_decisions_by_workspace ON bash_decisions (workspace_id, id);
+CREATE TABLE IF NOT EXISTS activity (
+    seq INTEGER PRIMARY KEY,
+    workspace_id TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS session_forks (
+    workspace_id TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    parent_session_id TEXT NOT NULL,
+    message_index INTEGER NOT NULL,
+    kept_messages INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (workspace_id, session_id)
+);
+CREATE TABLE IF NOT EXISTS annotations (
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    target TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    updated_at INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (workspace_id, thread_id, target, target_id)
+);
+CREATE TABLE IF NOT EXISTS issue_links (
+    workspace_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (workspace_id, thread_id)
+);
+CREATE TABLE IF NOT EXISTS transcript_files (
+    file TEXT PRIMARY KEY,
+    workspace_id TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    modified INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS transcript_documents (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    workspace_id TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    role TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    text TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS transcript_documents_by_session ON transcript_documents (workspace_id, session_id);
+CREATE VIRTUAL TABLE IF NOT EXISTS transcript_search USING fts5(
+    text,
+    content = 'transcript_documents',
+    content_rowid = 'id',
+    tokenize = 'porter unicode61'
+);
+CREATE TRIGGER IF NOT EXISTS transcript_documents_insert AFTER INSERT ON transcript_documents BEGIN
+    INSERT INTO transcript_search (rowid, text) VALUES (new.id, new.text);
+END;
+CREATE TRIGGER IF NOT EXISTS transcript_documents_delete AFTER DELETE ON transcript_documents BEGIN
+    INSERT INTO transcript_search (transcript_search, rowid, text) VALUES ('delete', old.id, old.text);
+END;
+";
+
+/// Rewrite the database under `key`, or in plain text without one. The copy is made next
+/// to the database and then moved over it.
+pub(crate) fn rekey_database(path: &Path, key: Option<String>) -> Result<(), String> {
+    let mut current = DATABASE_KEY.write().unwrap_or_else(|err| err.into_inner());
+    if *current == key {
+        return Ok(());
     }
-    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let list: Vec<WorkspaceEntry> = serde_json::from_str(&data).map_err(|e| e.to_string())?;
-    Ok(list
-        .into_iter()
-        .map(|entry| (entry.id.clone(), entry))
-        .collect())
+    if path.exists() {
+        export_database(path, current.as_deref(), key.as_deref())?;
+    }
+    *current = key;
+    Ok(())
 }
 
-pub(crate) fn write_workspaces(path: &PathBuf, entries: &[WorkspaceEntry]) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+fn export_database(path: &Path, from: Option<&str>, to: Option<&str>) -> Result<(), String> {
+    let copy = path.with_extension("db.rekey");
+    let _ = fs::remove_file(&copy);
+    let conn = open_with_key(path, from)?;
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let target_key = to.map(|key| format!("x'{key}'")).unwrap_or_default();
+    conn.execute(
+        "ATTACH DATABASE ?1 AS target KEY ?2",
+        params![copy.to_string_lossy(), target_key],
+    )
+    .map_err(|e| e.to_string())?;
+    let exported = conn
+        .query_row("SELECT sqlcipher_export('target')", [], |_| Ok(()))
+        .and_then(|_| {
+            conn.pragma_update(
+                Some(DatabaseName::Attached("target")),
+                "user_version",
+                version,
+            )
+        })
+        .and_then(|_| conn.execute_batch("DETACH DATABASE target"));
+    drop(conn);
+    if let Err(err) = exported {
+        let _ = fs::remove_file(&copy);
+        return Err(err.to_string());
+    }
+    fs::rename(&copy, path).map_err(|e| e.to_string())
+}
+
+/// Set once at startup for writers that run without access to `AppState`, such as the
+/// stdout readers of Claude processes.
+static DATABASE_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+pub(crate) fn set_database_path(path: PathBuf) {
+    let _ = DATABASE_PATH.set(path);
+}
+
+pub(crate) fn database_path() -> Option<&'static Path> {
+    DATABASE_PATH.get().map(PathBuf::as_path)
+}
+
+/// Last known state of a thread's Claude process.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ThreadSessionStatus {
+    Running,
+    Idle,
+    /// Was running when the monitor last exited; the turn never reported a result.
+    Interrupted,
+}
+
+impl ThreadSessionStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Idle => "idle",
+            Self::Interrupted => "interrupted",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "running" => Self::Running,
+            "interrupted" => Self::Interrupted,
+            _ => Self::Idle,
+        }
+    }
+}
+
+/// Which Claude session backs a thread of a workspace, and what it was doing last.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadSessionRecord {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) session_id: String,
+    pub(crate) model: Option<String>,
+    pub(crate) status: ThreadSessionStatus,
+    pub(crate) updated_at: i64,
+}
+
+/// A session copied from an earlier point of another one by `fork_session`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionFork {
+    pub(crate) workspace_id: String,
+    pub(crate) session_id: String,
+    pub(crate) parent_session_id: String,
+    /// The message the fork was requested at
+    pub(crate) message_index: u32,
+    /// Messages copied from the parent, which runs to the end of a turn
+    pub(crate) kept_messages: u32,
+    pub(crate) created_at: i64,
+}
+
+/// A CLI process the monitor spawned, kept until the run that spawned it exits cleanly
+/// so a crashed run's processes can be found again.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionProcessRecord {
+    /// Identifies the monitor run that spawned the process.
+    pub(crate) run_id: String,
+    pub(crate) pid: u32,
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    /// The arguments the process was started with, to tell it from a recycled pid.
+    pub(crate) command: String,
+    pub(crate) started_at: i64,
+}
+
+/// Hex-encoded SQLCipher key of the database, when it is encrypted at rest. Every open
+/// connection holds it for reading, so a re-encryption waits until they have all closed
+/// and no new one opens until the swapped file is in place.
+static DATABASE_KEY: RwLock<Option<String>> = RwLock::new(None);
+
+pub(crate) fn set_database_key(key: Option<String>) {
+    *DATABASE_KEY.write().unwrap_or_else(|err| err.into_inner()) = key;
+}
+
+pub(crate) fn database_encrypted() -> bool {
+    DATABASE_KEY
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .is_some()
+}
+
+/// Whether the database exists and opens without a key.
+pub(crate) fn is_plaintext_database(path: &Path) -> bool {
+    path.exists() && open_with_key(path, None).is_ok()
+}
+
+/// A connection that keeps the database key read-locked for as long as it is open.
+struct Db {
+    conn: Connection,
+    _key: RwLockReadGuard<'static, Option<String>>,
+}
+
+impl Deref for Db {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn
     }
-    let data = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
-    std::fs::write(path, data).map_err(|e| e.to_string())
 }
 
-pub(crate) fn read_settings(path: &PathBuf) -> Result<AppSettings, String> {
-    if !path.exists() {
-        return Ok(AppSettings::default());
+impl DerefMut for Db {
+    fn deref_mut(&mut self) -> &mut Connection {
+        &mut self.conn
     }
-    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    serde_json::from_str(&data).map_err(|e| e.to_string())
 }
 
-pub(crate) fn write_settings(path: &PathBuf, settings: &AppSettings) -> Result<(), String> {
+fn open(path: &Path) -> Result<Db, String> {
+    let key = DATABASE_KEY.read().unwrap_or_else(|err| err.into_inner());
+    let conn = open_with_key(path, key.as_deref())?;
+    Ok(Db { conn, _key: key })
+}
+
+fn open_with_key(path: &Path, key: Option<&str>) -> Result<Connection, String> {
     if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut conn = Connection::open(path).map_err(|e| e.to_string())?;
+    if let Some(key) = key {
+        conn.execute_batch(&format!("PRAGMA key = \"x'{key}'\";"))
+            .map_err(|e| e.to_string())?;
+    }
+    conn.busy_timeout(std::time::Duration::from_secs(5))
+        .map_err(|e| e.to_string())?;
+    migrate(&mut conn, path)?;
+    Ok(conn)
+}
+
+fn migrate(conn: &mut Connection, path: &Path) -> Result<(), String> {
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if version >= SCHEMA_VERSION {
+        return Ok(());
+    }
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+    if version == 0 {
+        if let Some(dir) = path.parent() {
+            import_legacy_files(&tx, dir)?;
+        }
+    }
+    tx.pragma_update(None, "user_version", SCHEMA_VERSION)
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// One-time import of the JSON files earlier versions kept next to the database. The
+/// files are left in place so downgrading still finds them.
+fn import_legacy_files(tx: &Transaction, dir: &Path) -> Result<(), String> {
+    if let Ok(data) = fs::read_to_string(dir.join("workspaces.json")) {
+        match serde_json::from_str::<Vec<WorkspaceEntry>>(&data) {
+            Ok(entries) => insert_workspaces(tx, &entries)?,
+            Err(err) => tracing::warn!("skipping unreadable workspaces.json: {err}"),
+        }
+    }
+    if let Ok(data) = fs::read_to_string(dir.join("settings.json")) {
+        match serde_json::from_str::<AppSettings>(&data) {
+            Ok(settings) => put_json(tx, SETTINGS_KEY, &settings)?,
+            Err(err) => tracing::warn!("skipping unreadable settings.json: {err}"),
+        }
+    }
+    if let Ok(data) = fs::read_to_string(dir.join("usage.jsonl")) {
+        for turn in data
+            .lines()
+            .filter_map(|line| serde_json::from_str::<TurnUsage>(line).ok())
+        {
+            insert_turn(tx, &turn)?;
+        }
+    }
+    if let Ok(data) = fs::read_to_string(dir.join("archived_threads.json")) {
+        let archived: HashMap<String, Vec<String>> =
+            serde_json::from_str(&data).unwrap_or_default();
+        for (workspace_id, thread_ids) in archived {
+            for thread_id in thread_ids {
+                insert_archived_thread(tx, &workspace_id, &thread_id)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn read_workspaces(path: &Path) -> Result<HashMap<String, WorkspaceEntry>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare("SELECT data FROM workspaces ORDER BY position")
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    let mut entries = HashMap::new();
+    for data in rows {
+        let data = data.map_err(|e| e.to_string())?;
+        let entry: WorkspaceEntry = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+        entries.insert(entry.id.clone(), entry);
+    }
+    Ok(entries)
+}
+
+pub(crate) fn write_workspaces(path: &Path, entries: &[WorkspaceEntry]) -> Result<(), String> {
+    let mut conn = open(path)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM workspaces", [])
+        .map_err(|e| e.to_string())?;
+    insert_workspaces(&tx, entries)?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+fn insert_workspaces(conn: &Connection, entries: &[WorkspaceEntry]) -> Result<(), String> {
+    for (position, entry) in entries.iter().enumerate() {
+        let data = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO workspaces (id, position, data) VALUES (?1, ?2, ?3)",
+            params![entry.id, position as i64, data],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_settings(path: &Path) -> Result<AppSettings, String> {
+    let conn = open(path)?;
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM kv WHERE key = ?1",
+            params![SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match value {
+        Some(value) => serde_json::from_str(&value).map_err(|e| e.to_string()),
+        None => Ok(AppSettings::default()),
+    }
+}
+
+pub(crate) fn write_settings(path: &Path, settings: &AppSettings) -> Result<(), String> {
+    let conn = open(path)?;
+    put_json(&conn, SETTINGS_KEY, settings)
+}
+
+fn put_json<T: Serialize>(conn: &Connection, key: &str, value: &T) -> Result<(), String> {
+    let data = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO kv (key, value) VALUES (?1, ?2)",
+        params![key, data],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// All recorded turns, oldest first.
+pub(crate) fn read_turns(path: &Path) -> Result<Vec<TurnUsage>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare("SELECT data FROM turns ORDER BY id")
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    Ok(rows
+        .filter_map(Result::ok)
+        .filter_map(|data| serde_json::from_str(&data).ok())
+        .collect())
+}
+
+pub(crate) fn append_turn(path: &Path, turn: &TurnUsage) -> Result<(), String> {
+    let conn = open(path)?;
+    insert_turn(&conn, turn)
+}
+
+fn insert_turn(conn: &Connection, turn: &TurnUsage) -> Result<(), String> {
+    let data = serde_json::to_string(turn).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO turns (workspace_id, session_id, timestamp, data) VALUES (?1, ?2, ?3, ?4)",
+        params![turn.workspace_id, turn.session_id, turn.timestamp, data],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Drop the usage records of turns that finished before `cutoff`. Returns how many were
+/// removed.
+pub(crate) fn prune_turns(path: &Path, cutoff: i64) -> Result<usize, String> {
+    let conn = open(path)?;
+    conn.execute("DELETE FROM turns WHERE timestamp < ?1", params![cutoff])
+        .map_err(|e| e.to_string())
+}
+
+/// Drop turn timelines and diffs recorded before `cutoff`, and thread sessions that have
+/// not changed since and are not running. Returns how many rows were removed.
+pub(crate) fn prune_turn_history(path: &Path, cutoff: i64) -> Result<usize, String> {
+    let mut conn = open(path)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut removed = tx
+        .execute(
+            "DELETE FROM turn_timelines WHERE timestamp < ?1",
+            params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+    removed += tx
+        .execute(
+            "DELETE FROM turn_diffs WHERE timestamp < ?1",
+            params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+    removed += tx
+        .execute(
+            "DELETE FROM turn_edits WHERE timestamp < ?1",
+            params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+    removed += tx
+        .execute(
+            "DELETE FROM compactions WHERE timestamp < ?1",
+            params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+    removed += tx
+        .execute(
+            "DELETE FROM thread_sessions WHERE updated_at < ?1 AND status != ?2",
+            params![cutoff, ThreadSessionStatus::Running.as_str()],
+        )
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(removed)
+}
+
+/// Bytes of the database file held by free pages, which `vacuum_database` returns to the
+/// file system.
+pub(crate) fn database_free_bytes(path: &Path) -> Result<u64, String> {
+    let conn = open(path)?;
+    let page_size: i64 = conn
+        .query_row("PRAGMA page_size", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let free_pages: i64 = conn
+        .query_row("PRAGMA freelist_count", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    Ok((page_size * free_pages).max(0) as u64)
+}
+
+/// Rebuild the database file without its free pages, e.g. after a large prune.
+pub(crate) fn vacuum_database(path: &Path) -> Result<(), String> {
+    open(path)?
+        .execute_batch("VACUUM")
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) fn read_archived_threads(
+    path: &Path,
+    workspace_id: &str,
+) -> Result<Vec<String>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare("SELECT thread_id FROM archived_threads WHERE workspace_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![workspace_id], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) fn archive_thread(
+    path: &Path,
+    workspace_id: &str,
+    thread_id: &str,
+) -> Result<(), String> {
+    let conn = open(path)?;
+    insert_archived_thread(&conn, workspace_id, thread_id)
+}
+
+fn insert_archived_thread(
+    conn: &Connection,
+    workspace_id: &str,
+    thread_id: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO archived_threads (workspace_id, thread_id) VALUES (?1, ?2)",
+        params![workspace_id, thread_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Store a turn's tool-call timeline, keeping only a workspace's most recent ones.
+pub(crate) fn save_turn_timeline(path: &Path, timeline: &TurnTimeline) -> Result<(), String> {
+    let conn = open(path)?;
+    let data = serde_json::to_string(timeline).map_err(|e| e.to_string())?;
+    let data = redaction::redact_text(&data);
+    conn.execute(
+        "INSERT OR REPLACE INTO turn_timelines (workspace_id, thread_id, turn_id, timestamp, data)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            timeline.workspace_id,
+            timeline.thread_id,
+            timeline.turn_id,
+            timeline.finished_at,
+            data,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM turn_timelines WHERE workspace_id = ?1 AND rowid NOT IN (
+             SELECT rowid FROM turn_timelines WHERE workspace_id = ?1
+             ORDER BY timestamp DESC LIMIT ?2
+         )",
+        params![timeline.workspace_id, MAX_TIMELINES_PER_WORKSPACE],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub(crate) fn read_turn_timeline(
+    path: &Path,
+    workspace_id: &str,
+    turn_id: &str,
+) -> Result<Option<TurnTimeline>, String> {
+    let conn = open(path)?;
+    let data: Option<String> = conn
+        .query_row(
+            "SELECT data FROM turn_timelines WHERE workspace_id = ?1 AND turn_id = ?2",
+            params![workspace_id, turn_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    data.map(|data| serde_json::from_str(&data).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+/// Record a turn's timeline from the stdout reader. Failures are logged, like
+/// [`remember_thread_session`].
+pub(crate) fn record_turn_timeline(timeline: &TurnTimeline) {
+    let Some(path) = DATABASE_PATH.get() else {
+        return;
+    };
+    if let Err(err) = save_turn_timeline(path, timeline) {
+        tracing::warn!(
+            "failed to record timeline for turn {}: {err}",
+            timeline.turn_id
+        );
+    }
+}
+
+/// Store what a turn changed, keeping only a workspace's most recent diffs.
+pub(crate) fn save_turn_diff(path: &Path, diff: &TurnDiff) -> Result<(), String> {
+    let conn = open(path)?;
+    let data = serde_json::to_string(diff).map_err(|e| e.to_string())?;
+    let data = redaction::redact_text(&data);
+    conn.execute(
+        "INSERT OR REPLACE INTO turn_diffs (workspace_id, thread_id, turn_id, timestamp, data)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            diff.workspace_id,
+            diff.thread_id,
+            diff.turn_id,
+            diff.created_at,
+            data,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM turn_diffs WHERE workspace_id = ?1 AND rowid NOT IN (
+             SELECT rowid FROM turn_diffs WHERE workspace_id = ?1
+             ORDER BY timestamp DESC LIMIT ?2
+         )",
+        params![diff.workspace_id, MAX_DIFFS_PER_WORKSPACE],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub(crate) fn read_turn_diff(
+    path: &Path,
+    workspace_id: &str,
+    turn_id: &str,
+) -> Result<Option<TurnDiff>, String> {
+    let conn = open(path)?;
+    let data: Option<String> = conn
+        .query_row(
+            "SELECT data FROM turn_diffs WHERE workspace_id = ?1 AND turn_id = ?2",
+            params![workspace_id, turn_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    data.map(|data| serde_json::from_str(&data).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+/// Record a turn's diff from code without `AppState`. Failures are logged.
+pub(crate) fn record_turn_diff(diff: &TurnDiff) {
+    let Some(path) = DATABASE_PATH.get() else {
+        return;
+    };
+    if let Err(err) = save_turn_diff(path, diff) {
+        tracing::warn!("failed to record diff for turn {}: {err}", diff.turn_id);
+    }
+}
+
+/// Store a turn's file edits, keeping only a workspace's most recent turns.
+pub(crate) fn save_turn_edits(path: &Path, edits: &TurnEdits) -> Result<(), String> {
+    let conn = open(path)?;
+    let data = serde_json::to_string(edits).map_err(|e| e.to_string())?;
+    let data = redaction::redact_text(&data);
+    conn.execute(
+        "INSERT OR REPLACE INTO turn_edits (workspace_id, thread_id, turn_id, timestamp, data)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            edits.workspace_id,
+            edits.thread_id,
+            edits.turn_id,
+            edits.created_at,
+            data,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM turn_edits WHERE workspace_id = ?1 AND rowid NOT IN (
+             SELECT rowid FROM turn_edits WHERE workspace_id = ?1
+             ORDER BY timestamp DESC LIMIT ?2
+         )",
+        params![edits.workspace_id, MAX_DIFFS_PER_WORKSPACE],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub(crate) fn read_turn_edits(
+    path: &Path,
+    workspace_id: &str,
+    turn_id: &str,
+) -> Result<Option<TurnEdits>, String> {
+    let conn = open(path)?;
+    let data: Option<String> = conn
+        .query_row(
+            "SELECT data FROM turn_edits WHERE workspace_id = ?1 AND turn_id = ?2",
+            params![workspace_id, turn_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    data.map(|data| serde_json::from_str(&data).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+/// Record a turn's file edits from the stdout reader. Failures are logged.
+pub(crate) fn record_turn_edits(edits: &TurnEdits) {
+    let Some(path) = DATABASE_PATH.get() else {
+        return;
+    };
+    if let Err(err) = save_turn_edits(path, edits) {
+        tracing::warn!("failed to record edits for turn {}: {err}", edits.turn_id);
+    }
+}
+
+/// Store a compaction, replacing an earlier version of the same record, and keep only a
+/// workspace's most recent ones.
+pub(crate) fn save_compaction(path: &Path, record: &CompactionRecord) -> Result<(), String> {
+    let conn = open(path)?;
+    let data = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO compactions (id, workspace_id, thread_id, timestamp, data)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            record.id,
+            record.workspace_id,
+            record.thread_id,
+            record.started_at,
+            data,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM compactions WHERE workspace_id = ?1 AND rowid NOT IN (
+             SELECT rowid FROM compactions WHERE workspace_id = ?1
+             ORDER BY timestamp DESC LIMIT ?2
+         )",
+        params![record.workspace_id, MAX_COMPACTIONS_PER_WORKSPACE],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// A workspace's compactions, or one thread's, newest first.
+pub(crate) fn read_compactions(
+    path: &Path,
+    workspace_id: &str,
+    thread_id: Option<&str>,
+) -> Result<Vec<CompactionRecord>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare(
+            "SELECT data FROM compactions
+             WHERE workspace_id = ?1 AND (?2 IS NULL OR thread_id = ?2)
+             ORDER BY timestamp DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![workspace_id, thread_id], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?;
+    let mut records = Vec::new();
+    for data in rows {
+        let data = data.map_err(|e| e.to_string())?;
+        records.push(serde_json::from_str(&data).map_err(|e| e.to_string())?);
+    }
+    Ok(records)
+}
+
+/// Record a compaction from the stdout reader. Failures are logged.
+pub(crate) fn record_compaction(record: &CompactionRecord) {
+    let Some(path) = DATABASE_PATH.get() else {
+        return;
+    };
+    if let Err(err) = save_compaction(path, record) {
+        tracing::warn!(
+            "failed to record compaction of thread {}: {err}",
+            record.thread_id
+        );
+    }
+}
+
+/// Park a message in the outbox, or update one already there in place.
+pub(crate) fn save_pending_message(path: &Path, message: &PendingMessage) -> Result<(), String> {
+    let conn = open(path)?;
+    let data = serde_json::to_string(message).map_err(|e| e.to_string())?;
+    // An update keeps the row, and with it the message's place among equal timestamps
+    let updated = conn
+        .execute(
+            "UPDATE outbox SET data = ?2 WHERE id = ?1",
+            params![message.id, data],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        conn.execute(
+            "INSERT INTO outbox (id, workspace_id, thread_id, queued_at, data)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                message.id,
+                message.workspace_id,
+                message.thread_id,
+                message.queued_at,
+                data,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// A workspace's parked messages, or every workspace's, oldest first.
+pub(crate) fn read_pending_messages(
+    path: &Path,
+    workspace_id: Option<&str>,
+) -> Result<Vec<PendingMessage>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare(
+            "SELECT data FROM outbox WHERE ?1 IS NULL OR workspace_id = ?1
+             ORDER BY queued_at, rowid",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![workspace_id], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    let mut messages = Vec::new();
+    for data in rows {
+        let data = data.map_err(|e| e.to_string())?;
+        messages.push(serde_json::from_str(&data).map_err(|e| e.to_string())?);
+    }
+    Ok(messages)
+}
+
+/// Drop a message from the outbox once it is sent or cancelled. Returns whether it was
+/// there.
+pub(crate) fn delete_pending_message(path: &Path, id: &str) -> Result<bool, String> {
+    let conn = open(path)?;
+    conn.execute("DELETE FROM outbox WHERE id = ?1", params![id])
+        .map(|removed| removed > 0)
+        .map_err(|e| e.to_string())
+}
+
+/// Remember the issue a thread was started from, replacing the thread's earlier link.
+pub(crate) fn save_issue_link(path: &Path, link: &IssueLink) -> Result<(), String> {
+    let conn = open(path)?;
+    let data = serde_json::to_string(link).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO issue_links (workspace_id, thread_id, created_at, data)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![link.workspace_id, link.thread_id, link.created_at, data],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The workspace's issue links, or the one of `thread_id`, newest first.
+pub(crate) fn read_issue_links(
+    path: &Path,
+    workspace_id: &str,
+    thread_id: Option<&str>,
+) -> Result<Vec<IssueLink>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare(
+            "SELECT data FROM issue_links WHERE workspace_id = ?1 AND (?2 IS NULL OR thread_id = ?2)
+             ORDER BY created_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![workspace_id, thread_id], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?;
+    let mut links = Vec::new();
+    for data in rows {
+        let data = data.map_err(|e| e.to_string())?;
+        links.push(serde_json::from_str(&data).map_err(|e| e.to_string())?);
+    }
+    Ok(links)
+}
+
+/// Store an annotation, replacing the one already on its turn or message.
+pub(crate) fn save_annotation(path: &Path, annotation: &Annotation) -> Result<(), String> {
+    let conn = open(path)?;
+    let data = serde_json::to_string(annotation).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO annotations (workspace_id, thread_id, target, target_id, updated_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            annotation.workspace_id,
+            annotation.thread_id,
+            annotation.target.as_str(),
+            annotation.target_id,
+            annotation.updated_at,
+            data,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Remove the annotation on a turn or message. Returns whether there was one.
+pub(crate) fn delete_annotation(
+    path: &Path,
+    workspace_id: &str,
+    thread_id: &str,
+    target: AnnotationTarget,
+    target_id: &str,
+) -> Result<bool, String> {
+    let conn = open(path)?;
+    conn.execute(
+        "DELETE FROM annotations
+         WHERE workspace_id = ?1 AND thread_id = ?2 AND target = ?3 AND target_id = ?4",
+        params![workspace_id, thread_id, target.as_str(), target_id],
+    )
+    .map(|removed| removed > 0)
+    .map_err(|e| e.to_string())
+}
+
+/// Annotations of every workspace, of one, or of one thread, most recently changed
+/// first.
+pub(crate) fn read_annotations(
+    path: &Path,
+    workspace_id: Option<&str>,
+    thread_id: Option<&str>,
+) -> Result<Vec<Annotation>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare(
+            "SELECT data FROM annotations
+             WHERE (?1 IS NULL OR workspace_id = ?1) AND (?2 IS NULL OR thread_id = ?2)
+             ORDER BY updated_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![workspace_id, thread_id], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?;
+    let mut annotations = Vec::new();
+    for data in rows {
+        let data = data.map_err(|e| e.to_string())?;
+        annotations.push(serde_json::from_str(&data).map_err(|e| e.to_string())?);
+    }
+    Ok(annotations)
+}
+
+/// Store an API token under the SHA-256 of its secret, replacing its earlier version.
+pub(crate) fn save_api_token(path: &Path, hash: &str, token: &ApiToken) -> Result<(), String> {
+    let conn = open(path)?;
+    let data = serde_json::to_string(token).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO api_tokens (id, hash, data) VALUES (?1, ?2, ?3)
+         ON CONFLICT(id) DO UPDATE SET data = excluded.data",
+        params![token.id, hash, data],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Every API token with the hash of its secret, revoked ones included.
+pub(crate) fn read_api_tokens(path: &Path) -> Result<Vec<(String, ApiToken)>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare("SELECT hash, data FROM api_tokens ORDER BY rowid")
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut tokens = Vec::new();
+    for row in rows {
+        let (hash, data) = row.map_err(|e| e.to_string())?;
+        tokens.push((
+            hash,
+            serde_json::from_str(&data).map_err(|e| e.to_string())?,
+        ));
+    }
+    Ok(tokens)
+}
+
+/// Append to the audit log, dropping the oldest entries past [`MAX_AUDIT_ENTRIES`].
+pub(crate) fn append_audit_entry(path: &Path, entry: &AuditEntry) -> Result<(), String> {
+    let conn = open(path)?;
+    let data = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO audit_log (token_id, timestamp, data) VALUES (?1, ?2, ?3)",
+        params![entry.token_id, entry.timestamp, data],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM audit_log WHERE id <= (SELECT MAX(id) FROM audit_log) - ?1",
+        params![MAX_AUDIT_ENTRIES],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The newest audit entries, for one token or all of them, newest first.
+pub(crate) fn read_audit_log(
+    path: &Path,
+    token_id: Option<&str>,
+    limit: usize,
+) -> Result<Vec<AuditEntry>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare(
+            "SELECT data FROM audit_log WHERE ?1 IS NULL OR token_id = ?1
+             ORDER BY id DESC LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![token_id, limit as i64], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?;
+    let mut entries = Vec::new();
+    for data in rows {
+        let data = data.map_err(|e| e.to_string())?;
+        entries.push(serde_json::from_str(&data).map_err(|e| e.to_string())?);
+    }
+    Ok(entries)
+}
+
+/// Append to a workspace's Bash policy log, dropping its oldest entries past
+/// [`MAX_BASH_DECISIONS_PER_WORKSPACE`].
+pub(crate) fn append_bash_decision(path: &Path, decision: &BashDecision) -> Result<(), String> {
+    let conn = open(path)?;
+    let data = serde_json::to_string(decision).map_err(|e| e.to_string())?;
+    let data = redaction::redact_text(&data);
+    conn.execute(
+        "INSERT INTO bash_decisions (workspace_id, timestamp, data) VALUES (?1, ?2, ?3)",
+        params![decision.workspace_id, decision.timestamp, data],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM bash_decisions WHERE workspace_id = ?1 AND id NOT IN (
+             SELECT id FROM bash_decisions WHERE workspace_id = ?1
+             ORDER BY id DESC LIMIT ?2
+         )",
+        params![decision.workspace_id, MAX_BASH_DECISIONS_PER_WORKSPACE],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// A workspace's newest Bash policy decisions, newest first.
+pub(crate) fn read_bash_decisions(
+    path: &Path,
+    workspace_id: &str,
+    limit: usize,
+) -> Result<Vec<BashDecision>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare(
+            "SELECT data FROM bash_decisions WHERE workspace_id = ?1
+             ORDER BY id DESC LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![workspace_id, limit as i64], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?;
+    let mut decisions = Vec::new();
+    for data in rows {
+        let data = data.map_err(|e| e.to_string())?;
+        decisions.push(serde_json::from_str(&data).map_err(|e| e.to_string())?);
+    }
+    Ok(decisions)
+}
+
+/// Record a Bash policy decision from the permission handler. Failures are logged.
+pub(crate) fn record_bash_decision(decision: &BashDecision) {
+    let Some(path) = DATABASE_PATH.get() else {
+        return;
+    };
+    if let Err(err) = append_bash_decision(path, decision) {
+        tracing::warn!("failed to record a Bash policy decision: {err}");
+    }
+}
+
+/// Append to the activity feed, dropping entries past [`MAX_ACTIVITY_ENTRIES`].
+pub(crate) fn append_activity(path: &Path, entry: &ActivityEntry) -> Result<(), String> {
+    let conn = open(path)?;
+    let data = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    let data = redaction::redact_text(&data);
+    conn.execute(
+        "INSERT OR REPLACE INTO activity (seq, workspace_id, timestamp, data)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![entry.seq as i64, entry.workspace_id, entry.timestamp, data],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM activity WHERE seq <= (SELECT MAX(seq) FROM activity) - ?1",
+        params![MAX_ACTIVITY_ENTRIES],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The newest `limit` activity entries, oldest first.
+pub(crate) fn read_activity(path: &Path, limit: usize) -> Result<Vec<ActivityEntry>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare("SELECT data FROM activity ORDER BY seq DESC LIMIT ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![limit as i64], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    let mut entries = Vec::new();
+    for data in rows {
+        let data = data.map_err(|e| e.to_string())?;
+        entries.push(serde_json::from_str(&data).map_err(|e| e.to_string())?);
+    }
+    entries.reverse();
+    Ok(entries)
+}
+
+/// One searchable piece of a transcript: a message's text, a tool call's input or its
+/// result.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TranscriptDocument {
+    pub(crate) role: String,
+    pub(crate) kind: String,
+    pub(crate) timestamp: i64,
+    pub(crate) text: String,
+}
+
+/// Size and modification time of a transcript file when it was last indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TranscriptFileStamp {
+    pub(crate) size: i64,
+    pub(crate) modified: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TranscriptQuery {
+    /// An FTS5 match expression.
+    pub(crate) expression: String,
+    pub(crate) workspace_ids: Vec<String>,
+    pub(crate) roles: Vec<String>,
+    pub(crate) since: Option<i64>,
+    pub(crate) until: Option<i64>,
+    pub(crate) limit: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TranscriptHit {
+    pub(crate) workspace_id: String,
+    pub(crate) session_id: String,
+    pub(crate) role: String,
+    pub(crate) kind: String,
+    pub(crate) timestamp: i64,
+    /// Excerpt around the match, with matched terms between `HIGHLIGHT_START` and
+    /// `HIGHLIGHT_END`.
+    pub(crate) snippet: String,
+}
+
+pub(crate) const HIGHLIGHT_START: char = '\u{2}';
+pub(crate) const HIGHLIGHT_END: char = '\u{3}';
+
+/// Transcript files indexed for a workspace, by file path.
+pub(crate) fn indexed_transcripts(
+    path: &Path,
+    workspace_id: &str,
+) -> Result<HashMap<String, TranscriptFileStamp>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare("SELECT file, size, modified FROM transcript_files WHERE workspace_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![workspace_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                TranscriptFileStamp {
+                    size: row.get(1)?,
+                    modified: row.get(2)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Replace everything indexed for a session with `documents`, in one transaction.
+pub(crate) fn index_transcript(
+    path: &Path,
+    workspace_id: &str,
+    session_id: &str,
+    file: &str,
+    stamp: TranscriptFileStamp,
+    documents: &[TranscriptDocument],
+) -> Result<(), String> {
+    // Before the transaction: keeping originals writes to the database as well
+    let texts: Vec<_> = documents
+        .iter()
+        .map(|document| redaction::redact_text(&document.text))
+        .collect();
+    let mut conn = open(path)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM transcript_documents WHERE workspace_id = ?1 AND session_id = ?2",
+        params![workspace_id, session_id],
+    )
+    .map_err(|e| e.to_string())?;
+    {
+        let mut insert = tx
+            .prepare(
+                "INSERT INTO transcript_documents (workspace_id, session_id, role, kind, timestamp, text)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .map_err(|e| e.to_string())?;
+        for (document, text) in documents.iter().zip(&texts) {
+            insert
+                .execute(params![
+                    workspace_id,
+                    session_id,
+                    document.role,
+                    document.kind,
+                    document.timestamp,
+                    text,
+                ])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO transcript_files (file, workspace_id, session_id, size, modified)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![file, workspace_id, session_id, stamp.size, stamp.modified],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Drop a transcript file that no longer exists from the index.
+pub(crate) fn forget_transcript(
+    path: &Path,
+    workspace_id: &str,
+    session_id: &str,
+    file: &str,
+) -> Result<(), String> {
+    let mut conn = open(path)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM transcript_documents WHERE workspace_id = ?1 AND session_id = ?2",
+        params![workspace_id, session_id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM transcript_files WHERE file = ?1",
+        params![file],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Best matches first, as ranked by BM25.
+pub(crate) fn search_transcripts(
+    path: &Path,
+    query: &TranscriptQuery,
+) -> Result<Vec<TranscriptHit>, String> {
+    let conn = open(path)?;
+    let mut sql = String::from(
+        "SELECT d.workspace_id, d.session_id, d.role, d.kind, d.timestamp,
+                snippet(transcript_search, 0, ?1, ?2, '…', 24)
+         FROM transcript_search JOIN transcript_documents d ON d.id = transcript_search.rowid
+         WHERE transcript_search MATCH ?3",
+    );
+    let mut values: Vec<rusqlite::types::Value> = vec![
+        HIGHLIGHT_START.to_string().into(),
+        HIGHLIGHT_END.to_string().into(),
+        query.expression.clone().into(),
+    ];
+    let mut filter_in = |sql: &mut String, column: &str, items: &[String]| {
+        if items.is_empty() {
+            return;
+        }
+        let placeholders: Vec<String> = items
+            .iter()
+            .map(|item| {
+                values.push(item.clone().into());
+                format!("?{}", values.len())
+            })
+            .collect();
+        sql.push_str(&format!(" AND d.{column} IN ({})", placeholders.join(", ")));
+    };
+    filter_in(&mut sql, "workspace_id", &query.workspace_ids);
+    filter_in(&mut sql, "role", &query.roles);
+    if let Some(since) = query.since {
+        values.push(since.into());
+        sql.push_str(&format!(" AND d.timestamp >= ?{}", values.len()));
+    }
+    if let Some(until) = query.until {
+        values.push(until.into());
+        sql.push_str(&format!(" AND d.timestamp <= ?{}", values.len()));
+    }
+    values.push((query.limit as i64).into());
+    sql.push_str(&format!(
+        " ORDER BY bm25(transcript_search), d.timestamp DESC LIMIT ?{}",
+        values.len()
+    ));
+
+    let mut statement = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(rusqlite::params_from_iter(values), |row| {
+            Ok(TranscriptHit {
+                workspace_id: row.get(0)?,
+                session_id: row.get(1)?,
+                role: row.get(2)?,
+                kind: row.get(3)?,
+                timestamp: row.get(4)?,
+                snippet: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Insert or update a thread's session. A `None` model keeps the one already stored.
+pub(crate) fn upsert_thread_session(
+    path: &Path,
+    record: &ThreadSessionRecord,
+) -> Result<(), String> {
+    let conn = open(path)?;
+    conn.execute(
+        "INSERT INTO thread_sessions (workspace_id, thread_id, session_id, model, status, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT (workspace_id, thread_id) DO UPDATE SET
+             session_id = excluded.session_id,
+             model = COALESCE(excluded.model, thread_sessions.model),
+             status = excluded.status,
+             updated_at = excluded.updated_at",
+        params![
+            record.workspace_id,
+            record.thread_id,
+            record.session_id,
+            record.model,
+            record.status.as_str(),
+            record.updated_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// A workspace's thread sessions, most recently active first.
+pub(crate) fn read_thread_sessions(
+    path: &Path,
+    workspace_id: &str,
+) -> Result<Vec<ThreadSessionRecord>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare(
+            "SELECT workspace_id, thread_id, session_id, model, status, updated_at
+             FROM thread_sessions WHERE workspace_id = ?1 ORDER BY updated_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![workspace_id], |row| {
+            Ok(ThreadSessionRecord {
+                workspace_id: row.get(0)?,
+                thread_id: row.get(1)?,
+                session_id: row.get(2)?,
+                model: row.get(3)?,
+                status: ThreadSessionStatus::parse(&row.get::<_, String>(4)?),
+                updated_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) fn insert_session_fork(path: &Path, fork: &SessionFork) -> Result<(), String> {
+    let conn = open(path)?;
+    conn.execute(
+        "INSERT INTO session_forks
+             (workspace_id, session_id, parent_session_id, message_index, kept_messages, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            fork.workspace_id,
+            fork.session_id,
+            fork.parent_session_id,
+            fork.message_index,
+            fork.kept_messages,
+            fork.created_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// A workspace's forked sessions, oldest first, so parents come before their forks.
+pub(crate) fn read_session_forks(
+    path: &Path,
+    workspace_id: &str,
+) -> Result<Vec<SessionFork>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare(
+            "SELECT workspace_id, session_id, parent_session_id, message_index, kept_messages,
+                    created_at
+             FROM session_forks WHERE workspace_id = ?1 ORDER BY created_at, session_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![workspace_id], |row| {
+            Ok(SessionFork {
+                workspace_id: row.get(0)?,
+                session_id: row.get(1)?,
+                parent_session_id: row.get(2)?,
+                message_index: row.get(3)?,
+                kept_messages: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Called at startup: processes from the previous run are gone, so anything still marked
+/// running was cut off mid-turn.
+pub(crate) fn mark_running_sessions_interrupted(path: &Path) -> Result<usize, String> {
+    let conn = open(path)?;
+    conn.execute(
+        "UPDATE thread_sessions SET status = ?1 WHERE status = ?2",
+        params![
+            ThreadSessionStatus::Interrupted.as_str(),
+            ThreadSessionStatus::Running.as_str(),
+        ],
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Record a thread's session state from code without `AppState`. Failures are logged:
+/// losing this bookkeeping must never break a running turn.
+pub(crate) fn remember_thread_session(
+    workspace_id: &str,
+    thread_id: &str,
+    session_id: &str,
+    model: Option<String>,
+    status: ThreadSessionStatus,
+) {
+    let Some(path) = DATABASE_PATH.get() else {
+        return;
+    };
+    let record = ThreadSessionRecord {
+        workspace_id: workspace_id.to_string(),
+        thread_id: thread_id.to_string(),
+        session_id: session_id.to_string(),
+        model,
+        status,
+        updated_at: now_ms(),
+    };
+    if let Err(err) = upsert_thread_session(path, &record) {
+        tracing::warn!("failed to record session for thread {thread_id}: {err}");
     }
-    let data = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
-    std::fs::write(path, data).map_err(|e| e.to_string())
+}
+
+pub(crate) fn insert_session_process(
+    path: &Path,
+    record: &SessionProcessRecord,
+) -> Result<(), String> {
+    let conn = open(path)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO session_processes (run_id, pid, workspace_id, thread_id, command, started_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            record.run_id,
+            record.pid,
+            record.workspace_id,
+            record.thread_id,
+            record.command,
+            record.started_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Every recorded process, newest first.
+pub(crate) fn read_session_processes(path: &Path) -> Result<Vec<SessionProcessRecord>, String> {
+    let conn = open(path)?;
+    let mut statement = conn
+        .prepare(
+            "SELECT run_id, pid, workspace_id, thread_id, command, started_at
+             FROM session_processes ORDER BY started_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok(SessionProcessRecord {
+                run_id: row.get(0)?,
+                pid: row.get(1)?,
+                workspace_id: row.get(2)?,
+                thread_id: row.get(3)?,
+                command: row.get(4)?,
+                started_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) fn delete_session_process(path: &Path, run_id: &str, pid: u32) -> Result<(), String> {
+    let conn = open(path)?;
+    conn.execute(
+        "DELETE FROM session_processes WHERE run_id = ?1 AND pid = ?2",
+        params![run_id, pid],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Forget the processes of a run, once it has stopped them all.
+pub(crate) fn delete_run_session_processes(path: &Path, run_id: &str) -> Result<usize, String> {
+    let conn = open(path)?;
+    conn.execute(
+        "DELETE FROM session_processes WHERE run_id = ?1",
+        params![run_id],
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// An encrypted original of a redacted value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RedactedSecretRecord {
+    pub(crate) id: String,
+    pub(crate) kind: String,
+    pub(crate) nonce: Vec<u8>,
+    pub(crate) ciphertext: Vec<u8>,
+    pub(crate) created_at: i64,
+}
+
+/// Store an encrypted original. The id derives from the value, so storing it again is a
+/// no-op.
+pub(crate) fn insert_redacted_secret(
+    path: &Path,
+    record: &RedactedSecretRecord,
+) -> Result<(), String> {
+    let conn = open(path)?;
+    conn.execute(
+        "INSERT OR IGNORE INTO redacted_secrets (id, kind, nonce, ciphertext, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            record.id,
+            record.kind,
+            record.nonce,
+            record.ciphertext,
+            record.created_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub(crate) fn read_redacted_secret(
+    path: &Path,
+    id: &str,
+) -> Result<Option<RedactedSecretRecord>, String> {
+    let conn = open(path)?;
+    conn.query_row(
+        "SELECT id, kind, nonce, ciphertext, created_at FROM redacted_secrets WHERE id = ?1",
+        params![id],
+        |row| {
+            Ok(RedactedSecretRecord {
+                id: row.get(0)?,
+                kind: row.get(1)?,
+                nonce: row.get(2)?,
+                ciphertext: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Drop every stored original, e.g. once the user stops keeping them.
+pub(crate) fn clear_redacted_secrets(path: &Path) -> Result<usize, String> {
+    let conn = open(path)?;
+    conn.execute("DELETE FROM redacted_secrets", [])
+        .map_err(|e| e.to_string())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{read_workspaces, write_workspaces};
-    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use super::*;
+    use crate::types::{IssueProvider, IssueRef, WorkspaceKind, WorkspaceSettings};
     use uuid::Uuid;
 
-    #[test]
-    fn write_read_workspaces_persists_sort_and_group() {
-        let temp_dir = std::env::temp_dir()
-            .join(format!("claude-code-monitor-test-{}", Uuid::new_v4()));
+    fn temp_dir() -> PathBuf {
+        let temp_dir =
+            std::env::temp_dir().join(format!("claude-code-monitor-test-{}", Uuid::new_v4()));
         std::fs::create_dir_all(&temp_dir).expect("create temp dir");
-        let path = temp_dir.join("workspaces.json");
-
-        let mut settings = WorkspaceSettings::default();
-        settings.sort_order = Some(5);
-        settings.group_id = Some("group-42".to_string());
-        settings.sidebar_collapsed = true;
-        settings.git_root = Some("/tmp".to_string());
+        temp_dir
+    }
 
-        let entry = WorkspaceEntry {
-            id: "w1".to_string(),
+    fn workspace(id: &str, settings: WorkspaceSettings) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
             name: "Workspace".to_string(),
             path: "/tmp".to_string(),
             claude_bin: None,
+            model: None,
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
-            settings: settings.clone(),
-        };
+            archived_at: None,
+            pinned: false,
+            settings,
+        }
+    }
+
+    fn turn(workspace_id: &str, session_id: &str, timestamp: i64) -> TurnUsage {
+        TurnUsage {
+            workspace_id: workspace_id.to_string(),
+            session_id: session_id.to_string(),
+            turn_id: format!("turn-{timestamp}"),
+            timestamp,
+            model: None,
+            input_tokens: 10,
+            output_tokens: 5,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+            cost_usd: 0.01,
+            duration_ms: None,
+            timings: None,
+            is_error: false,
+        }
+    }
+
+    #[test]
+    fn write_read_workspaces_persists_sort_and_group() {
+        let path = temp_dir().join(DATABASE_FILE);
 
-        write_workspaces(&path, &[entry]).expect("write workspaces");
+        let mut settings = WorkspaceSettings::default();
+        settings.sort_order = Some(5);
+        settings.group_id = Some("group-42".to_string());
+        settings.sidebar_collapsed = true;
+        settings.git_root = Some("/tmp".to_string());
+
+        write_workspaces(&path, &[workspace("w1", settings.clone())]).expect("write workspaces");
         let read = read_workspaces(&path).expect("read workspaces");
         let stored = read.get("w1").expect("stored workspace");
         assert_eq!(stored.settings.sort_order, Some(5));
         assert_eq!(stored.settings.group_id.as_deref(), Some("group-42"));
         assert!(stored.settings.sidebar_collapsed);
         assert_eq!(stored.settings.git_root.as_deref(), Some("/tmp"));
+
+        // Writing replaces the whole list
+        write_workspaces(&path, &[workspace("w2", WorkspaceSettings::default())])
+            .expect("rewrite workspaces");
+        let read = read_workspaces(&path).expect("read workspaces");
+        assert_eq!(read.keys().collect::<Vec<_>>(), vec!["w2"]);
+    }
+
+    #[test]
+    fn imports_legacy_json_files_once() {
+        let dir = temp_dir();
+        let legacy = vec![workspace("legacy", WorkspaceSettings::default())];
+        fs::write(
+            dir.join("workspaces.json"),
+            serde_json::to_string(&legacy).unwrap(),
+        )
+        .unwrap();
+        fs::write(dir.join("settings.json"), r#"{"claudeBin":"/opt/claude"}"#).unwrap();
+        fs::write(
+            dir.join("usage.jsonl"),
+            format!(
+                "{}\nnot json\n",
+                serde_json::to_string(&turn("legacy", "s1", 1)).unwrap()
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("archived_threads.json"), r#"{"legacy":["t-old"]}"#).unwrap();
+
+        let path = dir.join(DATABASE_FILE);
+        assert!(read_workspaces(&path).unwrap().contains_key("legacy"));
+        assert_eq!(
+            read_settings(&path).unwrap().claude_bin.as_deref(),
+            Some("/opt/claude")
+        );
+        assert_eq!(read_turns(&path).unwrap().len(), 1);
+        assert_eq!(
+            read_archived_threads(&path, "legacy").unwrap(),
+            vec!["t-old"]
+        );
+
+        // Later edits win over the legacy files, which are never re-imported
+        write_workspaces(&path, &[]).unwrap();
+        assert!(read_workspaces(&path).unwrap().is_empty());
+        assert!(dir.join("workspaces.json").exists());
+    }
+
+    #[test]
+    fn settings_default_until_written() {
+        let path = temp_dir().join(DATABASE_FILE);
+        assert!(read_settings(&path).unwrap().claude_bin.is_none());
+        let mut settings = AppSettings::default();
+        settings.claude_bin = Some("claude-dev".to_string());
+        write_settings(&path, &settings).unwrap();
+        assert_eq!(
+            read_settings(&path).unwrap().claude_bin.as_deref(),
+            Some("claude-dev")
+        );
+    }
+
+    #[test]
+    fn turns_and_archived_threads_round_trip() {
+        let path = temp_dir().join(DATABASE_FILE);
+        append_turn(&path, &turn("ws-1", "a", 1)).unwrap();
+        append_turn(&path, &turn("ws-1", "b", 2)).unwrap();
+        let turns = read_turns(&path).unwrap();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[1].session_id, "b");
+
+        archive_thread(&path, "ws-1", "t1").unwrap();
+        archive_thread(&path, "ws-1", "t1").unwrap();
+        assert_eq!(read_archived_threads(&path, "ws-1").unwrap(), vec!["t1"]);
+        assert!(read_archived_threads(&path, "ws-2").unwrap().is_empty());
+    }
+
+    #[test]
+    fn prune_drops_only_old_rows() {
+        let path = temp_dir().join(DATABASE_FILE);
+        append_turn(&path, &turn("w1", "s1", 100)).unwrap();
+        append_turn(&path, &turn("w1", "s1", 300)).unwrap();
+        for (thread_id, status, updated_at) in [
+            ("old", ThreadSessionStatus::Idle, 100),
+            ("running", ThreadSessionStatus::Running, 100),
+            ("recent", ThreadSessionStatus::Idle, 300),
+        ] {
+            upsert_thread_session(
+                &path,
+                &ThreadSessionRecord {
+                    workspace_id: "w1".to_string(),
+                    thread_id: thread_id.to_string(),
+                    session_id: thread_id.to_string(),
+                    model: None,
+                    status,
+                    updated_at,
+                },
+            )
+            .unwrap();
+        }
+
+        assert_eq!(prune_turns(&path, 200).unwrap(), 1);
+        assert_eq!(read_turns(&path).unwrap()[0].timestamp, 300);
+        assert_eq!(prune_turn_history(&path, 200).unwrap(), 1);
+        let threads: Vec<_> = read_thread_sessions(&path, "w1")
+            .unwrap()
+            .into_iter()
+            .map(|record| record.thread_id)
+            .collect();
+        assert_eq!(threads, vec!["recent", "running"]);
+    }
+
+    #[test]
+    fn thread_sessions_track_latest_state() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let record =
+            |thread_id: &str, model: Option<&str>, status, updated_at| ThreadSessionRecord {
+                workspace_id: "ws-1".to_string(),
+                thread_id: thread_id.to_string(),
+                session_id: thread_id.to_string(),
+                model: model.map(str::to_string),
+                status,
+                updated_at,
+            };
+        upsert_thread_session(
+            &path,
+            &record(
+                "t1",
+                Some("claude-opus-4-5-20251101"),
+                ThreadSessionStatus::Running,
+                1,
+            ),
+        )
+        .unwrap();
+        upsert_thread_session(&path, &record("t2", None, ThreadSessionStatus::Running, 2)).unwrap();
+        upsert_thread_session(&path, &record("t1", None, ThreadSessionStatus::Idle, 3)).unwrap();
+
+        assert_eq!(mark_running_sessions_interrupted(&path).unwrap(), 1);
+        let sessions = read_thread_sessions(&path, "ws-1").unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].thread_id, "t1");
+        assert_eq!(sessions[0].status, ThreadSessionStatus::Idle);
+        // The model survives updates that don't know it
+        assert_eq!(
+            sessions[0].model.as_deref(),
+            Some("claude-opus-4-5-20251101")
+        );
+        assert_eq!(sessions[1].status, ThreadSessionStatus::Interrupted);
+        assert!(read_thread_sessions(&path, "ws-2").unwrap().is_empty());
+    }
+
+    #[test]
+    fn session_forks_record_lineage() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let fork = |session_id: &str, parent: &str, created_at| SessionFork {
+            workspace_id: "ws-1".to_string(),
+            session_id: session_id.to_string(),
+            parent_session_id: parent.to_string(),
+            message_index: 4,
+            kept_messages: 6,
+            created_at,
+        };
+        insert_session_fork(&path, &fork("b", "a", 2)).unwrap();
+        insert_session_fork(&path, &fork("c", "b", 3)).unwrap();
+        assert!(insert_session_fork(&path, &fork("b", "a", 4)).is_err());
+
+        let forks = read_session_forks(&path, "ws-1").unwrap();
+        assert_eq!(forks, vec![fork("b", "a", 2), fork("c", "b", 3)]);
+        assert!(read_session_forks(&path, "ws-2").unwrap().is_empty());
+    }
+
+    #[test]
+    fn annotations_replace_per_target() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let annotation = |thread_id: &str, target_id: &str, rating, updated_at| Annotation {
+            workspace_id: "ws-1".to_string(),
+            thread_id: thread_id.to_string(),
+            target: AnnotationTarget::Message,
+            target_id: target_id.to_string(),
+            note: Some("Good fix".to_string()),
+            rating: Some(rating),
+            bookmark: None,
+            created_at: 1,
+            updated_at,
+        };
+        save_annotation(&path, &annotation("t1", "m1", 2, 1)).unwrap();
+        save_annotation(&path, &annotation("t1", "m1", 5, 2)).unwrap();
+        save_annotation(&path, &annotation("t2", "m1", 3, 3)).unwrap();
+
+        assert_eq!(
+            read_annotations(&path, Some("ws-1"), Some("t1")).unwrap(),
+            vec![annotation("t1", "m1", 5, 2)]
+        );
+        assert_eq!(read_annotations(&path, None, None).unwrap().len(), 2);
+        assert!(delete_annotation(&path, "ws-1", "t2", AnnotationTarget::Message, "m1").unwrap());
+        assert!(!delete_annotation(&path, "ws-1", "t2", AnnotationTarget::Turn, "m1").unwrap());
+        assert!(read_annotations(&path, Some("ws-2"), None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn session_processes_are_kept_per_run() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let record = |run_id: &str, pid: u32, started_at| SessionProcessRecord {
+            run_id: run_id.to_string(),
+            pid,
+            workspace_id: "ws-1".to_string(),
+            thread_id: format!("thread-{pid}"),
+            command: format!("--session-id thread-{pid}"),
+            started_at,
+        };
+        insert_session_process(&path, &record("old", 10, 1)).unwrap();
+        insert_session_process(&path, &record("old", 11, 2)).unwrap();
+        insert_session_process(&path, &record("new", 10, 3)).unwrap();
+
+        let pids = |path: &Path| -> Vec<(String, u32)> {
+            read_session_processes(path)
+                .unwrap()
+                .into_iter()
+                .map(|record| (record.run_id, record.pid))
+                .collect()
+        };
+        assert_eq!(
+            pids(&path),
+            vec![
+                ("new".to_string(), 10),
+                ("old".to_string(), 11),
+                ("old".to_string(), 10),
+            ]
+        );
+        assert_eq!(delete_run_session_processes(&path, "new").unwrap(), 1);
+        delete_session_process(&path, "old", 11).unwrap();
+        assert_eq!(pids(&path), vec![("old".to_string(), 10)]);
+    }
+
+    #[test]
+    fn database_can_be_encrypted_and_decrypted() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let settings = AppSettings {
+            theme: "dark".to_string(),
+            ..AppSettings::default()
+        };
+        write_settings(&path, &settings).unwrap();
+        let key = "5a".repeat(32);
+
+        export_database(&path, None, Some(&key)).unwrap();
+        assert!(open_with_key(&path, None).is_err());
+        assert!(open_with_key(&path, Some(&"00".repeat(32))).is_err());
+        let conn = open_with_key(&path, Some(&key)).unwrap();
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+        drop(conn);
+
+        export_database(&path, Some(&key), None).unwrap();
+        assert_eq!(read_settings(&path).unwrap().theme, "dark");
+    }
+
+    #[test]
+    fn redacted_secrets_are_stored_once() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let record = |ciphertext: &[u8]| RedactedSecretRecord {
+            id: "abc".to_string(),
+            kind: "github-token".to_string(),
+            nonce: vec![1; 12],
+            ciphertext: ciphertext.to_vec(),
+            created_at: 1,
+        };
+        insert_redacted_secret(&path, &record(b"first")).unwrap();
+        insert_redacted_secret(&path, &record(b"second")).unwrap();
+        assert_eq!(
+            read_redacted_secret(&path, "abc").unwrap(),
+            Some(record(b"first"))
+        );
+        assert_eq!(read_redacted_secret(&path, "missing").unwrap(), None);
+        assert_eq!(clear_redacted_secrets(&path).unwrap(), 1);
+        assert_eq!(read_redacted_secret(&path, "abc").unwrap(), None);
+    }
+
+    #[test]
+    fn turn_timelines_round_trip_per_workspace() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let timeline = |workspace_id: &str, turn_id: &str| TurnTimeline {
+            workspace_id: workspace_id.to_string(),
+            thread_id: "t1".to_string(),
+            turn_id: turn_id.to_string(),
+            started_at: 1,
+            finished_at: 2,
+            tool_calls: Vec::new(),
+        };
+        save_turn_timeline(&path, &timeline("ws-1", "turn-1")).unwrap();
+        save_turn_timeline(&path, &timeline("ws-1", "turn-1")).unwrap();
+        assert_eq!(
+            read_turn_timeline(&path, "ws-1", "turn-1").unwrap(),
+            Some(timeline("ws-1", "turn-1"))
+        );
+        assert!(read_turn_timeline(&path, "ws-2", "turn-1")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn turn_diffs_round_trip() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let diff = TurnDiff {
+            workspace_id: "ws-1".to_string(),
+            thread_id: "t1".to_string(),
+            turn_id: "turn-1".to_string(),
+            created_at: 5,
+            files: vec![crate::types::GitFileStatus {
+                path: "src/lib.rs".to_string(),
+                status: "M".to_string(),
+                additions: 2,
+                deletions: 1,
+            }],
+            diff: "+fn added() {}".to_string(),
+            diff_truncated: false,
+        };
+        save_turn_diff(&path, &diff).unwrap();
+        assert_eq!(read_turn_diff(&path, "ws-1", "turn-1").unwrap(), Some(diff));
+        assert!(read_turn_diff(&path, "ws-1", "turn-2").unwrap().is_none());
+    }
+
+    #[test]
+    fn turn_edits_round_trip() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let edits = TurnEdits {
+            workspace_id: "ws-1".to_string(),
+            thread_id: "t1".to_string(),
+            turn_id: "turn-1".to_string(),
+            created_at: 5,
+            edits: vec![crate::types::FileEdit {
+                tool_use_id: "toolu_1".to_string(),
+                path: "src/lib.rs".to_string(),
+                kind: crate::types::FileEditKind::Edit,
+                hunks: vec![crate::types::EditHunk {
+                    old_text: "fn a() {}".to_string(),
+                    new_text: "fn b() {}".to_string(),
+                    replace_all: false,
+                    old_start: Some(3),
+                    old_lines: 1,
+                    new_start: Some(3),
+                    new_lines: 1,
+                }],
+            }],
+        };
+        save_turn_edits(&path, &edits).unwrap();
+        assert_eq!(
+            read_turn_edits(&path, "ws-1", "turn-1").unwrap(),
+            Some(edits)
+        );
+        assert!(read_turn_edits(&path, "ws-1", "turn-2").unwrap().is_none());
+    }
+
+    #[test]
+    fn transcript_search_filters_and_reindexes() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let document = |role: &str, timestamp, text: &str| TranscriptDocument {
+            role: role.to_string(),
+            kind: "text".to_string(),
+            timestamp,
+            text: text.to_string(),
+        };
+        let stamp = TranscriptFileStamp {
+            size: 1,
+            modified: 1,
+        };
+        index_transcript(
+            &path,
+            "ws-1",
+            "s1",
+            "/p/s1.jsonl",
+            stamp,
+            &[
+                document("user", 10, "Fix the race condition in the watcher"),
+                document("assistant", 20, "The watcher races with the reader."),
+            ],
+        )
+        .unwrap();
+        index_transcript(
+            &path,
+            "ws-2",
+            "s2",
+            "/p/s2.jsonl",
+            stamp,
+            &[document("user", 30, "Another race condition")],
+        )
+        .unwrap();
+
+        let query = |workspace_ids: &[&str], roles: &[&str], since| TranscriptQuery {
+            expression: "\"race\"".to_string(),
+            workspace_ids: workspace_ids.iter().map(|id| id.to_string()).collect(),
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+            since,
+            until: None,
+            limit: 10,
+        };
+        // Porter stemming matches "races" too
+        assert_eq!(
+            search_transcripts(&path, &query(&[], &[], None))
+                .unwrap()
+                .len(),
+            3
+        );
+        let hits = search_transcripts(&path, &query(&["ws-1"], &["user"], None)).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].snippet,
+            format!("Fix the {HIGHLIGHT_START}race{HIGHLIGHT_END} condition in the watcher")
+        );
+        assert_eq!(
+            search_transcripts(&path, &query(&[], &[], Some(25)))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Reindexing replaces a session's documents; forgetting removes them
+        index_transcript(&path, "ws-1", "s1", "/p/s1.jsonl", stamp, &[]).unwrap();
+        assert_eq!(
+            search_transcripts(&path, &query(&["ws-1"], &[], None))
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_eq!(
+            indexed_transcripts(&path, "ws-1").unwrap()["/p/s1.jsonl"],
+            stamp
+        );
+        forget_transcript(&path, "ws-2", "s2", "/p/s2.jsonl").unwrap();
+        assert!(search_transcripts(&path, &query(&[], &[], None))
+            .unwrap()
+            .is_empty());
+        assert!(indexed_transcripts(&path, "ws-2").unwrap().is_empty());
+    }
+
+    #[test]
+    fn vacuum_returns_free_pages() {
+        let path = temp_dir().join(DATABASE_FILE);
+        for timestamp in 0..500 {
+            append_turn(&path, &turn("ws-1", &"s".repeat(200), timestamp)).unwrap();
+        }
+        assert_eq!(database_free_bytes(&path).unwrap(), 0);
+        prune_turns(&path, 500).unwrap();
+        assert!(database_free_bytes(&path).unwrap() > 0);
+
+        let before = std::fs::metadata(&path).unwrap().len();
+        vacuum_database(&path).unwrap();
+        assert_eq!(database_free_bytes(&path).unwrap(), 0);
+        assert!(std::fs::metadata(&path).unwrap().len() < before);
+    }
+
+    #[test]
+    fn pending_messages_keep_their_order() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let message = |id: &str, workspace_id: &str, queued_at: i64| PendingMessage {
+            id: id.to_string(),
+            workspace_id: workspace_id.to_string(),
+            thread_id: "thread-1".to_string(),
+            prompt: format!("prompt {id}"),
+            images: Vec::new(),
+            model: None,
+            access_mode: None,
+            queued_at,
+            attempts: 0,
+            last_attempt_at: None,
+            error: None,
+        };
+        save_pending_message(&path, &message("b", "ws-1", 5)).unwrap();
+        save_pending_message(&path, &message("c", "ws-1", 5)).unwrap();
+        save_pending_message(&path, &message("a", "ws-1", 1)).unwrap();
+        save_pending_message(&path, &message("x", "ws-2", 3)).unwrap();
+        let mut retried = message("b", "ws-1", 5);
+        retried.attempts = 1;
+        retried.error = Some("broken pipe".to_string());
+        save_pending_message(&path, &retried).unwrap();
+
+        let ids = |messages: Vec<PendingMessage>| {
+            messages
+                .into_iter()
+                .map(|message| message.id)
+                .collect::<Vec<_>>()
+        };
+        let pending = read_pending_messages(&path, Some("ws-1")).unwrap();
+        assert_eq!(pending[1].attempts, 1);
+        assert_eq!(ids(pending), ["a", "b", "c"]);
+        assert_eq!(read_pending_messages(&path, None).unwrap().len(), 4);
+
+        assert!(delete_pending_message(&path, "b").unwrap());
+        assert!(!delete_pending_message(&path, "b").unwrap());
+        assert_eq!(
+            ids(read_pending_messages(&path, Some("ws-1")).unwrap()),
+            ["a", "c"]
+        );
+    }
+
+    #[test]
+    fn keeps_api_tokens_and_their_audit_log() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let mut token = ApiToken {
+            id: "tok-1".to_string(),
+            name: "portal".to_string(),
+            role: crate::types::TokenRole::Observer,
+            created_at: 1,
+            expires_at: None,
+            last_used_at: None,
+            revoked_at: None,
+        };
+        save_api_token(&path, "hash-1", &token).unwrap();
+        token.revoked_at = Some(2);
+        save_api_token(&path, "hash-1", &token).unwrap();
+        assert_eq!(
+            read_api_tokens(&path).unwrap(),
+            [("hash-1".to_string(), token)]
+        );
+
+        let entry = |token_id: Option<&str>, action: &str| AuditEntry {
+            timestamp: 3,
+            token_id: token_id.map(str::to_string),
+            token_name: None,
+            surface: "rest".to_string(),
+            action: action.to_string(),
+            allowed: true,
+            reason: None,
+        };
+        append_audit_entry(&path, &entry(Some("tok-1"), "list_workspaces")).unwrap();
+        append_audit_entry(&path, &entry(None, "subscribe")).unwrap();
+        append_audit_entry(&path, &entry(Some("tok-1"), "send_user_message")).unwrap();
+        let actions = |entries: Vec<AuditEntry>| {
+            entries
+                .into_iter()
+                .map(|entry| entry.action)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            actions(read_audit_log(&path, Some("tok-1"), 10).unwrap()),
+            ["send_user_message", "list_workspaces"]
+        );
+        assert_eq!(
+            actions(read_audit_log(&path, None, 1).unwrap()),
+            ["send_user_message"]
+        );
+    }
+
+    #[test]
+    fn bash_decisions_are_kept_per_workspace() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let decision = |workspace_id: &str, command: &str, action| BashDecision {
+            timestamp: 4,
+            workspace_id: workspace_id.to_string(),
+            thread_id: "t1".to_string(),
+            turn_id: "turn-1".to_string(),
+            tool_use_id: None,
+            command: command.to_string(),
+            action,
+            rule: None,
+        };
+        let allowed = decision("ws-1", "git status", crate::types::BashAction::Allow);
+        let denied = decision("ws-1", "rm -rf target", crate::types::BashAction::Deny);
+        append_bash_decision(&path, &allowed).unwrap();
+        append_bash_decision(
+            &path,
+            &decision("ws-2", "ls", crate::types::BashAction::Ask),
+        )
+        .unwrap();
+        append_bash_decision(&path, &denied).unwrap();
+        assert_eq!(
+            read_bash_decisions(&path, "ws-1", 10).unwrap(),
+            [denied.clone(), allowed]
+        );
+        assert_eq!(read_bash_decisions(&path, "ws-1", 1).unwrap(), [denied]);
+    }
+
+    #[test]
+    fn activity_keeps_the_newest_entries_in_order() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let entry = |seq: u64| ActivityEntry {
+            seq,
+            timestamp: seq as i64 * 10,
+            workspace_id: "ws-1".to_string(),
+            thread_id: None,
+            turn_id: None,
+            kind: crate::types::ActivityKind::Turn,
+            severity: crate::types::ActivitySeverity::Info,
+            summary: format!("entry {seq}"),
+            method: "turn/started".to_string(),
+        };
+        for seq in 1..=3 {
+            append_activity(&path, &entry(seq)).unwrap();
+        }
+        assert_eq!(read_activity(&path, 2).unwrap(), [entry(2), entry(3)]);
+        assert_eq!(read_activity(&path, 10).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn issue_links_are_kept_per_thread() {
+        let path = temp_dir().join(DATABASE_FILE);
+        let link = |thread_id: &str, created_at: i64| IssueLink {
+            workspace_id: "ws-1".to_string(),
+            thread_id: thread_id.to_string(),
+            turn_id: Some(format!("turn-{thread_id}")),
+            issue: IssueRef {
+                provider: IssueProvider::GitHub,
+                host: "github.com".to_string(),
+                repo: "acme/web".to_string(),
+                number: 12,
+                url: "https://github.com/acme/web/issues/12".to_string(),
+            },
+            created_at,
+            commented_at: None,
+            comment_url: None,
+        };
+        save_issue_link(&path, &link("t1", 1)).unwrap();
+        save_issue_link(&path, &link("t2", 2)).unwrap();
+        let mut commented = link("t1", 1);
+        commented.commented_at = Some(5);
+        save_issue_link(&path, &commented).unwrap();
+
+        let links = read_issue_links(&path, "ws-1", None).unwrap();
+        assert_eq!(links, [link("t2", 2), commented.clone()]);
+        assert_eq!(
+            read_issue_links(&path, "ws-1", Some("t1")).unwrap(),
+            [commented]
+        );
+        assert!(read_issue_links(&path, "ws-2", None).unwrap().is_empty());
     }
 }
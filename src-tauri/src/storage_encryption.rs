@@ -0,0 +1,146 @@
+//! Optional encryption of the monitor's database at rest.
+//!
+//! The database is SQLCipher. With encryption on, its 256-bit key lives in the OS
+//! keychain (Keychain on macOS, Credential Manager on Windows, the Secret Service on
+//! Linux) and never touches the disk, so settings, workspaces, the transcript search
+//! index, timelines, diffs and usage can't be read from a copy of the data directory.
+//! Recordings and exports are separate files and are not encrypted.
+
+use std::path::Path;
+
+use aes_gcm::aead::{KeyInit, OsRng};
+use aes_gcm::Aes256Gcm;
+use keyring::Entry;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, State};
+
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+
+pub(crate) const KEYCHAIN_SERVICE: &str = "com.claudecodemonitor.app";
+const KEYCHAIN_ACCOUNT: &str = "database-key";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StorageEncryptionStatus {
+    pub(crate) enabled: bool,
+}
+
+fn keychain_entry() -> Result<Entry, String> {
+    Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
+        .map_err(|err| format!("The OS keychain is unavailable: {err}"))
+}
+
+fn read_keychain_key() -> Result<Option<String>, String> {
+    match keychain_entry()?.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(format!("Failed to read the database key: {err}")),
+    }
+}
+
+fn delete_keychain_key() -> Result<(), String> {
+    match keychain_entry()?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(format!("Failed to remove the database key: {err}")),
+    }
+}
+
+fn new_key() -> String {
+    Aes256Gcm::generate_key(&mut OsRng)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Pick up the key of an encrypted database at startup, before anything opens it. A key
+/// left behind by an interrupted switch to plain text is dropped.
+pub(crate) fn load_key(db_path: &Path) {
+    let key = match read_keychain_key() {
+        Ok(Some(key)) => key,
+        Ok(None) => return,
+        Err(err) => {
+            tracing::warn!("{err}");
+            return;
+        }
+    };
+    if storage::is_plaintext_database(db_path) {
+        if let Err(err) = delete_keychain_key() {
+            tracing::warn!("{err}");
+        }
+        return;
+    }
+    storage::set_database_key(Some(key));
+}
+
+fn enable(db_path: &Path) -> Result<(), String> {
+    if storage::database_encrypted() {
+        return Ok(());
+    }
+    let key = new_key();
+    keychain_entry()?
+        .set_password(&key)
+        .map_err(|err| format!("Failed to store the database key: {err}"))?;
+    if let Err(err) = storage::rekey_database(db_path, Some(key)) {
+        let _ = delete_keychain_key();
+        return Err(err);
+    }
+    Ok(())
+}
+
+fn disable(db_path: &Path) -> Result<(), String> {
+    if !storage::database_encrypted() {
+        return Ok(());
+    }
+    storage::rekey_database(db_path, None)?;
+    delete_keychain_key()
+}
+
+#[tauri::command]
+pub(crate) async fn get_storage_encryption(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(&*state, app, "get_storage_encryption", json!({}))
+            .await;
+    }
+    let status = StorageEncryptionStatus {
+        enabled: storage::database_encrypted(),
+    };
+    serde_json::to_value(status).map_err(|err| err.to_string())
+}
+
+/// Encrypt the database with a new key kept in the OS keychain, or decrypt it again.
+#[tauri::command]
+pub(crate) async fn set_storage_encryption(
+    enabled: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "set_storage_encryption",
+            json!({ "enabled": enabled }),
+        )
+        .await;
+    }
+    let db_path = state.db_path.clone();
+    tokio::task::spawn_blocking(move || {
+        if enabled {
+            enable(&db_path)
+        } else {
+            disable(&db_path)
+        }
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    let status = StorageEncryptionStatus {
+        enabled: storage::database_encrypted(),
+    };
+    serde_json::to_value(status).map_err(|err| err.to_string())
+}
@@ -0,0 +1,406 @@
+//! Live tailing of Claude session transcripts.
+//!
+//! Watches a thread's `<session-id>.jsonl` under `~/.claude/projects` and emits
+//! `transcript/appended` with every complete line appended after tailing started, so
+//! turns driven from a terminal show up in the monitor as they happen.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::RecursiveMode;
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::mpsc;
+use tokio::time::interval;
+use tokio_util::sync::CancellationToken;
+
+use crate::claude::emit_event;
+use crate::event_sink::TauriEventSink;
+use crate::operations;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+use crate::transcripts::resolve_project_dir;
+
+/// Debounce window for file system events on the transcript directory.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+/// Re-read on a timer too; some file systems (network mounts) drop change events.
+const FALLBACK_POLL: Duration = Duration::from_secs(5);
+
+pub(crate) struct TranscriptTail {
+    shutdown: CancellationToken,
+    pub(crate) path: PathBuf,
+    /// Observing a session another process drives; see `attach`. Only detaching stops
+    /// these, and the monitor refuses to send messages to the thread meanwhile.
+    pub(crate) attached: bool,
+}
+
+/// Reads whatever was appended to a JSONL file since the last call.
+#[derive(Debug)]
+pub(crate) struct TranscriptCursor {
+    path: PathBuf,
+    offset: u64,
+    /// Bytes of a line the CLI has not finished writing yet.
+    pending: Vec<u8>,
+}
+
+impl TranscriptCursor {
+    /// Start at the current end of the file, or at the beginning with `from_start`.
+    pub(crate) fn open(path: PathBuf, from_start: bool) -> Self {
+        let offset = if from_start {
+            0
+        } else {
+            std::fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0)
+        };
+        Self {
+            path,
+            offset,
+            pending: Vec::new(),
+        }
+    }
+
+    pub(crate) fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Complete JSON lines appended since the last read. A file that shrank was
+    /// rewritten, so reading restarts from the top.
+    pub(crate) fn read_appended(&mut self) -> Result<Vec<Value>, String> {
+        let mut file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.to_string()),
+        };
+        let len = file.metadata().map_err(|err| err.to_string())?.len();
+        if len < self.offset {
+            self.offset = 0;
+            self.pending.clear();
+        }
+        if len == self.offset {
+            return Ok(Vec::new());
+        }
+        file.seek(SeekFrom::Start(self.offset))
+            .map_err(|err| err.to_string())?;
+        let mut appended = Vec::new();
+        file.take(len - self.offset)
+            .read_to_end(&mut appended)
+            .map_err(|err| err.to_string())?;
+        self.offset += appended.len() as u64;
+        self.pending.extend_from_slice(&appended);
+
+        let Some(last_newline) = self.pending.iter().rposition(|byte| *byte == b'\n') else {
+            return Ok(Vec::new());
+        };
+        let complete: Vec<u8> = self.pending.drain(..=last_newline).collect();
+        Ok(String::from_utf8_lossy(&complete)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+fn tail_key(workspace_id: &str, thread_id: &str) -> String {
+    format!("{workspace_id}:{thread_id}")
+}
+
+/// The transcript a thread writes to: its last known Claude session, which differs
+/// from the thread id once a session has been forked or replaced.
+fn transcript_path(
+    db_path: &Path,
+    project_dir: &Path,
+    workspace_id: &str,
+    thread_id: &str,
+) -> PathBuf {
+    let session_id = storage::read_thread_sessions(db_path, workspace_id)
+        .ok()
+        .and_then(|records| {
+            records
+                .into_iter()
+                .find(|record| record.thread_id == thread_id)
+                .map(|record| record.session_id)
+        })
+        .unwrap_or_else(|| thread_id.to_string());
+    project_dir.join(format!("{session_id}.jsonl"))
+}
+
+async fn run_tail(
+    app: AppHandle,
+    workspace_id: String,
+    thread_id: String,
+    mut cursor: TranscriptCursor,
+    shutdown: CancellationToken,
+) {
+    let event_sink = TauriEventSink::new(app.clone());
+    let (tx, mut rx) = mpsc::unbounded_channel::<DebounceEventResult>();
+    let debouncer = new_debouncer(DEBOUNCE, move |result| {
+        let _ = tx.send(result);
+    });
+    // Watch the directory: the transcript may not exist until the first turn starts
+    let debouncer = match debouncer {
+        Ok(mut debouncer) => {
+            let watch_dir = cursor.path.parent().map(Path::to_path_buf);
+            if let Some(dir) = watch_dir.filter(|dir| dir.is_dir()) {
+                if let Err(err) = debouncer.watcher().watch(&dir, RecursiveMode::NonRecursive) {
+                    tracing::warn!("failed to watch {}: {err}", dir.display());
+                }
+            }
+            Some(debouncer)
+        }
+        Err(err) => {
+            tracing::warn!("failed to create watcher: {err}");
+            None
+        }
+    };
+    let mut watching = debouncer.is_some();
+    let mut poll = interval(FALLBACK_POLL);
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            event = rx.recv(), if watching => {
+                let touched = match event {
+                    Some(Ok(events)) => events.iter().any(|event| event.path == cursor.path),
+                    Some(Err(err)) => {
+                        tracing::warn!("watcher error: {err:?}");
+                        false
+                    }
+                    None => {
+                        watching = false;
+                        false
+                    }
+                };
+                if !touched {
+                    continue;
+                }
+            }
+            _ = poll.tick() => {}
+        }
+        if shutdown.is_cancelled() {
+            break;
+        }
+        let entries = match cursor.read_appended() {
+            Ok(entries) => entries,
+            Err(err) => {
+                tracing::warn!("failed to read {}: {err}", cursor.path.display());
+                continue;
+            }
+        };
+        if entries.is_empty() {
+            continue;
+        }
+        let external = !driven_by_monitor(&app, &workspace_id, &thread_id).await;
+        emit_event(
+            &event_sink,
+            &workspace_id,
+            "transcript/appended",
+            json!({ "threadId": thread_id, "entries": entries, "external": external }),
+        );
+    }
+}
+
+/// Whether the monitor itself is running a turn on the thread; the UI already renders
+/// those from the live stream.
+async fn driven_by_monitor(app: &AppHandle, workspace_id: &str, thread_id: &str) -> bool {
+    let Some(state) = app.try_state::<AppState>() else {
+        return false;
+    };
+    let session = state.sessions.lock().await.get(workspace_id).cloned();
+    match session {
+        Some(session) => session.turn_queue.lock().await.is_busy(thread_id),
+        None => false,
+    }
+}
+
+/// Start tailing a thread's transcript unless it is already tailed. `attached` marks
+/// the tail as observing an external session.
+pub(crate) async fn start_tail(
+    state: &AppState,
+    app: AppHandle,
+    workspace_id: String,
+    thread_id: String,
+    from_start: bool,
+    attached: bool,
+) -> Result<Value, String> {
+    let key = tail_key(&workspace_id, &thread_id);
+    if let Some(existing) = state.transcript_tails.lock().await.get_mut(&key) {
+        existing.attached |= attached;
+        return Ok(json!({ "path": existing.path, "alreadyTailing": true }));
+    }
+    let project_dir = {
+        let workspaces = state.workspaces.lock().await;
+        let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
+        resolve_project_dir(entry).ok_or("Unable to resolve Claude projects directory")?
+    };
+    let path = {
+        let db_path = state.db_path.clone();
+        let workspace_id = workspace_id.clone();
+        let thread_id = thread_id.clone();
+        tokio::task::spawn_blocking(move || {
+            transcript_path(&db_path, &project_dir, &workspace_id, &thread_id)
+        })
+        .await
+        .map_err(|err| err.to_string())?
+    };
+    let cursor = TranscriptCursor::open(path.clone(), from_start);
+    let offset = cursor.offset();
+
+    let mut tails = state.transcript_tails.lock().await;
+    if let Some(existing) = tails.get_mut(&key) {
+        existing.attached |= attached;
+        return Ok(json!({ "path": existing.path, "alreadyTailing": true }));
+    }
+    let shutdown = operations::root().child_token();
+    tails.insert(
+        key,
+        TranscriptTail {
+            shutdown: shutdown.clone(),
+            path: path.clone(),
+            attached,
+        },
+    );
+    tokio::spawn(run_tail(app, workspace_id, thread_id, cursor, shutdown));
+    Ok(json!({ "path": path, "offset": offset, "alreadyTailing": false }))
+}
+
+/// Stop a thread's tail. Attached tails survive unless `detach` is set.
+pub(crate) async fn stop_tail(
+    state: &AppState,
+    workspace_id: &str,
+    thread_id: &str,
+    detach: bool,
+) -> bool {
+    let mut tails = state.transcript_tails.lock().await;
+    let key = tail_key(workspace_id, thread_id);
+    match tails.get(&key) {
+        Some(tail) if detach || !tail.attached => {
+            tail.shutdown.cancel();
+            tails.remove(&key);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub(crate) async fn is_attached(state: &AppState, workspace_id: &str, thread_id: &str) -> bool {
+    state
+        .transcript_tails
+        .lock()
+        .await
+        .get(&tail_key(workspace_id, thread_id))
+        .is_some_and(|tail| tail.attached)
+}
+
+/// Stop every tail belonging to a workspace, e.g. when it is removed.
+pub(crate) async fn stop_workspace_tails(workspace_id: &str, state: &AppState) {
+    let prefix = format!("{workspace_id}:");
+    state.transcript_tails.lock().await.retain(|key, tail| {
+        if key.starts_with(&prefix) {
+            tail.shutdown.cancel();
+            false
+        } else {
+            true
+        }
+    });
+}
+
+#[tauri::command]
+pub(crate) async fn start_transcript_tail(
+    workspace_id: String,
+    thread_id: String,
+    from_start: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "start_transcript_tail",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id, "fromStart": from_start }),
+        )
+        .await;
+    }
+
+    start_tail(
+        &state,
+        app,
+        workspace_id,
+        thread_id,
+        from_start.unwrap_or(false),
+        false,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn stop_transcript_tail(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "stop_transcript_tail",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await;
+    }
+
+    let stopped = stop_tail(&state, &workspace_id, &thread_id, false).await;
+    Ok(json!({ "stopped": stopped }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use uuid::Uuid;
+
+    #[test]
+    fn reads_only_complete_appended_lines() {
+        let path = std::env::temp_dir().join(format!("tail-{}.jsonl", Uuid::new_v4()));
+        std::fs::write(&path, "{\"type\":\"user\",\"n\":0}\n").unwrap();
+
+        let mut cursor = TranscriptCursor::open(path.clone(), false);
+        assert!(cursor.read_appended().unwrap().is_empty());
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        write!(
+            file,
+            "{{\"type\":\"assistant\",\"n\":1}}\nnot json\n{{\"type\":\"as"
+        )
+        .unwrap();
+        let entries = cursor.read_appended().unwrap();
+        assert_eq!(entries, vec![json!({ "type": "assistant", "n": 1 })]);
+
+        write!(file, "sistant\",\"n\":2}}\n").unwrap();
+        let entries = cursor.read_appended().unwrap();
+        assert_eq!(entries, vec![json!({ "type": "assistant", "n": 2 })]);
+
+        // A rewritten, shorter file is read again from the top
+        std::fs::write(&path, "{\"n\":3}\n").unwrap();
+        assert_eq!(cursor.read_appended().unwrap(), vec![json!({ "n": 3 })]);
+
+        let mut from_start = TranscriptCursor::open(path.clone(), true);
+        assert_eq!(from_start.read_appended().unwrap().len(), 1);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn missing_transcript_reads_as_empty() {
+        let path = std::env::temp_dir().join(format!("tail-{}.jsonl", Uuid::new_v4()));
+        let mut cursor = TranscriptCursor::open(path, false);
+        assert_eq!(cursor.offset(), 0);
+        assert!(cursor.read_appended().unwrap().is_empty());
+    }
+}
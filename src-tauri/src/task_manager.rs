@@ -98,7 +98,8 @@ impl FileLock {
 
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(|e| format!("Failed to create lock directory: {}", e))?;
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create lock directory: {}", e))?;
         }
 
         // Try to create lock file (simple approach - not bulletproof but sufficient for most cases)
@@ -177,7 +178,8 @@ pub fn create_task(
     let _lock = FileLock::acquire(list_id)?;
 
     let list_dir = get_task_list_dir(list_id)?;
-    fs::create_dir_all(&list_dir).map_err(|e| format!("Failed to create task list directory: {}", e))?;
+    fs::create_dir_all(&list_dir)
+        .map_err(|e| format!("Failed to create task list directory: {}", e))?;
 
     let task_id = next_task_id(list_id)?;
 
@@ -208,11 +210,10 @@ pub fn read_task(list_id: &str, task_id: &str) -> Result<Task, String> {
         return Err(format!("Task {} not found in list {}", task_id, list_id));
     }
 
-    let content = fs::read_to_string(&task_path)
-        .map_err(|e| format!("Failed to read task file: {}", e))?;
+    let content =
+        fs::read_to_string(&task_path).map_err(|e| format!("Failed to read task file: {}", e))?;
 
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse task file: {}", e))
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse task file: {}", e))
 }
 
 /// Read all tasks in a list
@@ -231,16 +232,14 @@ pub fn read_task_list(list_id: &str) -> Result<Vec<Task>, String> {
         let path = entry.path();
         if path.extension().is_some_and(|ext| ext == "json") {
             match fs::read_to_string(&path) {
-                Ok(content) => {
-                    match serde_json::from_str::<Task>(&content) {
-                        Ok(task) => tasks.push(task),
-                        Err(e) => {
-                            eprintln!("Failed to parse task file {:?}: {}", path, e);
-                        }
+                Ok(content) => match serde_json::from_str::<Task>(&content) {
+                    Ok(task) => tasks.push(task),
+                    Err(e) => {
+                        tracing::warn!("Failed to parse task file {:?}: {}", path, e);
                     }
-                }
+                },
                 Err(e) => {
-                    eprintln!("Failed to read task file {:?}: {}", path, e);
+                    tracing::warn!("Failed to read task file {:?}: {}", path, e);
                 }
             }
         }
@@ -296,7 +295,9 @@ pub fn update_task(list_id: &str, task_id: &str, updates: TaskUpdate) -> Result<
         // Merge metadata - if existing metadata exists, merge the new keys
         match (&mut task.metadata, metadata) {
             (Some(existing), new_metadata) => {
-                if let (Some(existing_obj), Some(new_obj)) = (existing.as_object_mut(), new_metadata.as_object()) {
+                if let (Some(existing_obj), Some(new_obj)) =
+                    (existing.as_object_mut(), new_metadata.as_object())
+                {
                     for (key, value) in new_obj {
                         if value.is_null() {
                             existing_obj.remove(key);
@@ -385,21 +386,17 @@ pub async fn task_create(
     description: String,
     active_form: Option<String>,
 ) -> Result<Task, String> {
-    tokio::task::spawn_blocking(move || {
-        create_task(&list_id, subject, description, active_form)
-    })
-    .await
-    .map_err(|e| e.to_string())?
+    tokio::task::spawn_blocking(move || create_task(&list_id, subject, description, active_form))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 /// Read a single task from a list
 #[tauri::command]
 pub async fn task_read(list_id: String, task_id: String) -> Result<Task, String> {
-    tokio::task::spawn_blocking(move || {
-        read_task(&list_id, &task_id)
-    })
-    .await
-    .map_err(|e| e.to_string())?
+    tokio::task::spawn_blocking(move || read_task(&list_id, &task_id))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 /// Read all tasks in a list
@@ -424,21 +421,17 @@ pub async fn task_update(
     task_id: String,
     updates: TaskUpdate,
 ) -> Result<Task, String> {
-    tokio::task::spawn_blocking(move || {
-        update_task(&list_id, &task_id, updates)
-    })
-    .await
-    .map_err(|e| e.to_string())?
+    tokio::task::spawn_blocking(move || update_task(&list_id, &task_id, updates))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 /// Delete a task from a list
 #[tauri::command]
 pub async fn task_delete(list_id: String, task_id: String) -> Result<(), String> {
-    tokio::task::spawn_blocking(move || {
-        delete_task(&list_id, &task_id)
-    })
-    .await
-    .map_err(|e| e.to_string())?
+    tokio::task::spawn_blocking(move || delete_task(&list_id, &task_id))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 /// List all available task lists
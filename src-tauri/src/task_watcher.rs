@@ -58,15 +58,13 @@ pub async fn task_watcher_start(list_id: String, app_handle: AppHandle) -> Resul
         return Ok(()); // Already watching
     }
 
-    let tasks_dir = get_tasks_dir(&list_id).ok_or_else(|| {
-        "Could not resolve Claude home directory".to_string()
-    })?;
+    let tasks_dir = get_tasks_dir(&list_id)
+        .ok_or_else(|| "Could not resolve Claude home directory".to_string())?;
 
     // Create the directory if it doesn't exist
     if !tasks_dir.exists() {
-        std::fs::create_dir_all(&tasks_dir).map_err(|e| {
-            format!("Failed to create tasks directory: {}", e)
-        })?;
+        std::fs::create_dir_all(&tasks_dir)
+            .map_err(|e| format!("Failed to create tasks directory: {}", e))?;
     }
 
     let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
@@ -85,24 +83,31 @@ pub async fn task_watcher_start(list_id: String, app_handle: AppHandle) -> Resul
         let mut debouncer = match new_debouncer(Duration::from_millis(100), tx) {
             Ok(d) => d,
             Err(e) => {
-                eprintln!("Failed to create task watcher debouncer: {}", e);
+                tracing::warn!("Failed to create task watcher debouncer: {}", e);
                 return;
             }
         };
 
         // Start watching the directory
-        if let Err(e) = debouncer.watcher().watch(&tasks_dir_clone, RecursiveMode::NonRecursive) {
-            eprintln!("Failed to watch tasks directory {:?}: {}", tasks_dir_clone, e);
+        if let Err(e) = debouncer
+            .watcher()
+            .watch(&tasks_dir_clone, RecursiveMode::NonRecursive)
+        {
+            tracing::warn!(
+                "Failed to watch tasks directory {:?}: {}",
+                tasks_dir_clone,
+                e
+            );
             return;
         }
 
-        println!("Started watching tasks directory: {:?}", tasks_dir_clone);
+        tracing::info!("Started watching tasks directory: {:?}", tasks_dir_clone);
 
         // Process events in a loop
         loop {
             tokio::select! {
                 _ = shutdown_rx.recv() => {
-                    println!("Stopping task watcher for list: {}", list_id_clone);
+                    tracing::info!("Stopping task watcher for list: {}", list_id_clone);
                     break;
                 }
                 _ = tokio::time::sleep(Duration::from_millis(50)) => {
@@ -117,20 +122,20 @@ pub async fn task_watcher_start(list_id: String, app_handle: AppHandle) -> Resul
                             });
 
                             if has_json_change {
-                                println!("Task list changed: {}", list_id_clone);
+                                tracing::debug!("Task list changed: {}", list_id_clone);
                                 if let Err(e) = app_handle_clone.emit(&event_name, ()) {
-                                    eprintln!("Failed to emit task-list-changed event: {}", e);
+                                    tracing::warn!("Failed to emit task-list-changed event: {}", e);
                                 }
                             }
                         }
                         Ok(Err(error)) => {
-                            eprintln!("Task watcher error: {:?}", error);
+                            tracing::warn!("Task watcher error: {:?}", error);
                         }
                         Err(std::sync::mpsc::TryRecvError::Empty) => {
                             // No events, continue
                         }
                         Err(std::sync::mpsc::TryRecvError::Disconnected) => {
-                            println!("Task watcher channel disconnected for list: {}", list_id_clone);
+                            tracing::info!("Task watcher channel disconnected for list: {}", list_id_clone);
                             break;
                         }
                     }
@@ -140,12 +145,7 @@ pub async fn task_watcher_start(list_id: String, app_handle: AppHandle) -> Resul
     });
 
     // Store the watcher
-    watchers.insert(
-        list_id.clone(),
-        TaskWatcher {
-            shutdown_tx,
-        },
-    );
+    watchers.insert(list_id.clone(), TaskWatcher { shutdown_tx });
 
     Ok(())
 }
@@ -158,7 +158,7 @@ pub async fn task_watcher_stop(list_id: String, app_handle: AppHandle) -> Result
 
     if let Some(watcher) = watchers.remove(&list_id) {
         watcher.stop().await;
-        println!("Stopped task watcher for list: {}", list_id);
+        tracing::info!("Stopped task watcher for list: {}", list_id);
     }
 
     Ok(())
@@ -172,7 +172,7 @@ pub async fn stop_all_watchers(app_handle: &AppHandle) {
 
     for (list_id, watcher) in watchers.drain() {
         let _ = watcher.shutdown_tx.send(()).await;
-        println!("Stopped task watcher for list: {}", list_id);
+        tracing::info!("Stopped task watcher for list: {}", list_id);
     }
 }
 
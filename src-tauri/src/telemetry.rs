@@ -0,0 +1,117 @@
+//! Structured logging for the backend.
+//!
+//! Events and closed spans go to stderr and to a daily log file under `logs/` in the app
+//! data directory, of which the last `MAX_LOG_FILES` are kept. The level filter is the
+//! app settings' `logLevel`, or `RUST_LOG` without one, and changes without a restart.
+//! Closed spans can also be kept for export (see [`spans`]).
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+mod spans;
+
+pub(crate) use spans::{collect_spans, take_spans, FinishedSpan};
+
+const DEFAULT_LOG_LEVEL: &str = "info";
+pub(crate) const LOG_DIR: &str = "logs";
+const LOG_FILE_PREFIX: &str = "backend";
+const MAX_LOG_FILES: usize = 7;
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+/// Flushes the file writer's buffer when the process exits.
+static FILE_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+/// Check a `logLevel` setting before it is saved: a level such as `debug`, or filter
+/// directives such as `info,claude_code_monitor_lib::claude=trace`.
+pub(crate) fn validate_log_level(level: &str) -> Result<(), String> {
+    EnvFilter::try_new(level)
+        .map(|_| ())
+        .map_err(|err| format!("invalid logLevel {level:?}: {err}"))
+}
+
+fn filter(level: Option<&str>) -> EnvFilter {
+    let configured = level.map(str::trim).filter(|level| !level.is_empty());
+    let filter = match configured {
+        Some(level) => EnvFilter::try_new(level),
+        None => EnvFilter::try_from_default_env(),
+    };
+    filter.unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_LEVEL))
+}
+
+/// Install the subscriber, logging to stderr and under `data_dir`. Only the first call
+/// does anything.
+pub(crate) fn init(data_dir: &Path) {
+    if FILTER.get().is_some() {
+        return;
+    }
+    let (filter_layer, handle) = reload::Layer::new(filter(None));
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(data_dir.join(LOG_DIR));
+    let file_layer = match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = FILE_GUARD.set(guard);
+            Some(
+                fmt::layer()
+                    .with_writer(writer)
+                    .with_ansi(false)
+                    .with_span_events(FmtSpan::CLOSE),
+            )
+        }
+        Err(err) => {
+            eprintln!("[telemetry] failed to open the log directory: {err}");
+            None
+        }
+    };
+    let installed = tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(
+            fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_span_events(FmtSpan::CLOSE),
+        )
+        .with(file_layer)
+        .with(spans::SpanCollector)
+        .try_init();
+    if installed.is_ok() {
+        let _ = FILTER.set(handle);
+    }
+}
+
+/// Apply the `logLevel` setting, e.g. at startup and whenever app settings are saved.
+pub(crate) fn set_log_level(level: Option<&str>) {
+    let Some(handle) = FILTER.get() else {
+        return;
+    };
+    if let Err(err) = handle.reload(filter(level)) {
+        tracing::warn!("failed to change the log level: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_levels_and_directives() {
+        for level in [
+            "debug",
+            "warn",
+            "info,claude_code_monitor_lib::claude=trace",
+        ] {
+            assert!(validate_log_level(level).is_ok(), "{level}");
+        }
+        assert!(validate_log_level("claude_code_monitor_lib=loud").is_err());
+    }
+}
@@ -0,0 +1,269 @@
+//! Closed backend spans kept for export, e.g. over OTLP by the daemon.
+//!
+//! The collector is part of every subscriber but does nothing until [`collect_spans`]
+//! turns it on. Only this crate's spans are kept, so the HTTP client shipping them does
+//! not feed its own spans back in; a span joins its parent's trace, or starts one.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use serde_json::Value;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+use uuid::Uuid;
+
+use crate::utils::now_unix_nanos;
+
+/// Closed spans held between exports; the oldest go first when it is full.
+const MAX_BUFFERED_SPANS: usize = 4096;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+static FINISHED: Mutex<VecDeque<FinishedSpan>> = Mutex::new(VecDeque::new());
+
+/// A closed span with what was recorded on it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FinishedSpan {
+    pub(crate) trace_id: [u8; 16],
+    pub(crate) span_id: [u8; 8],
+    pub(crate) parent_span_id: Option<[u8; 8]>,
+    pub(crate) name: &'static str,
+    /// Module the span was opened in.
+    pub(crate) target: &'static str,
+    pub(crate) start_unix_nanos: u64,
+    pub(crate) end_unix_nanos: u64,
+    pub(crate) attributes: Vec<(String, Value)>,
+    /// Message of the last error event inside the span, e.g. from `instrument(err)`.
+    pub(crate) error: Option<String>,
+}
+
+/// Span data while the span is open, kept in the registry's extensions.
+struct OpenSpan {
+    span: FinishedSpan,
+}
+
+/// Start or stop keeping closed spans. Stopping discards the ones not yet taken.
+pub(crate) fn collect_spans(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        buffer().clear();
+    }
+}
+
+/// The spans closed since the last call, oldest first, and how many were dropped
+/// because the buffer was full.
+pub(crate) fn take_spans() -> (Vec<FinishedSpan>, u64) {
+    let spans = buffer().drain(..).collect();
+    (spans, DROPPED.swap(0, Ordering::Relaxed))
+}
+
+fn buffer() -> MutexGuard<'static, VecDeque<FinishedSpan>> {
+    FINISHED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn random_span_id() -> [u8; 8] {
+    let mut id = [0u8; 8];
+    id.copy_from_slice(&Uuid::new_v4().as_bytes()[..8]);
+    id
+}
+
+/// Records span fields as JSON values.
+struct Fields<'a>(&'a mut Vec<(String, Value)>);
+
+impl Fields<'_> {
+    fn set(&mut self, field: &Field, value: Value) {
+        match self.0.iter_mut().find(|(name, _)| name == field.name()) {
+            Some((_, existing)) => *existing = value,
+            None => self.0.push((field.name().to_string(), value)),
+        }
+    }
+}
+
+impl Visit for Fields<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.set(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.set(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.set(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.set(field, Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.set(field, Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.set(field, Value::from(format!("{value:?}")));
+    }
+}
+
+/// The `error` field of an event, or its message.
+#[derive(Default)]
+struct ErrorMessage {
+    error: Option<String>,
+    message: Option<String>,
+}
+
+impl Visit for ErrorMessage {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "error" => self.error = Some(value.to_string()),
+            "message" => self.message = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "error" => self.error = Some(format!("{value:?}")),
+            "message" => self.message = Some(format!("{value:?}")),
+            _ => {}
+        }
+    }
+}
+
+/// Layer that keeps this crate's closed spans while collection is on.
+pub(crate) struct SpanCollector;
+
+impl<S> Layer<S> for SpanCollector
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+        let metadata = attrs.metadata();
+        if !metadata.target().starts_with(env!("CARGO_CRATE_NAME")) {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let parent = span.parent().and_then(|parent| {
+            let extensions = parent.extensions();
+            let open = extensions.get::<OpenSpan>()?;
+            Some((open.span.trace_id, open.span.span_id))
+        });
+        let (trace_id, parent_span_id) = match parent {
+            Some((trace_id, parent_span_id)) => (trace_id, Some(parent_span_id)),
+            None => (*Uuid::new_v4().as_bytes(), None),
+        };
+        let mut attributes = Vec::new();
+        attrs.record(&mut Fields(&mut attributes));
+        span.extensions_mut().insert(OpenSpan {
+            span: FinishedSpan {
+                trace_id,
+                span_id: random_span_id(),
+                parent_span_id,
+                name: metadata.name(),
+                target: metadata.target(),
+                start_unix_nanos: now_unix_nanos(),
+                end_unix_nanos: 0,
+                attributes,
+                error: None,
+            },
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(open) = span.extensions_mut().get_mut::<OpenSpan>() {
+            values.record(&mut Fields(&mut open.span.attributes));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        let Some(span) = ctx.event_span(event) else {
+            return;
+        };
+        if let Some(open) = span.extensions_mut().get_mut::<OpenSpan>() {
+            let mut message = ErrorMessage::default();
+            event.record(&mut message);
+            open.span.error = Some(message.error.or(message.message).unwrap_or_default());
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span_ref) = ctx.span(&id) else {
+            return;
+        };
+        let Some(OpenSpan { mut span }) = span_ref.extensions_mut().remove::<OpenSpan>() else {
+            return;
+        };
+        if !ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+        span.end_unix_nanos = now_unix_nanos();
+        let mut buffer = buffer();
+        if buffer.len() >= MAX_BUFFERED_SPANS {
+            buffer.pop_front();
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+        buffer.push_back(span);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn keeps_closed_spans_in_their_parents_trace() {
+        let subscriber = tracing_subscriber::registry().with(SpanCollector);
+        collect_spans(true);
+        tracing::subscriber::with_default(subscriber, || {
+            let session = tracing::info_span!("session", workspace_id = "ws-1", pid = 42u64);
+            let _entered = session.enter();
+            let turn = tracing::info_span!("turn", turn_id = tracing::field::Empty);
+            turn.record("turn_id", "turn-1");
+            turn.in_scope(|| tracing::error!(error = "boom", "turn failed"));
+            drop(turn);
+            tracing::info_span!(target: "hyper", "connect").in_scope(|| {});
+        });
+        let (spans, dropped) = take_spans();
+        collect_spans(false);
+
+        assert_eq!(dropped, 0);
+        let names: Vec<&str> = spans.iter().map(|span| span.name).collect();
+        assert_eq!(names, ["turn", "session"]);
+        let (turn, session) = (&spans[0], &spans[1]);
+        assert_eq!(turn.trace_id, session.trace_id);
+        assert_eq!(turn.parent_span_id, Some(session.span_id));
+        assert_eq!(session.parent_span_id, None);
+        assert_eq!(turn.error.as_deref(), Some("boom"));
+        assert_eq!(
+            turn.attributes,
+            [("turn_id".to_string(), Value::from("turn-1"))]
+        );
+        assert_eq!(
+            session.attributes,
+            [
+                ("workspace_id".to_string(), Value::from("ws-1")),
+                ("pid".to_string(), Value::from(42))
+            ]
+        );
+        assert!(turn.end_unix_nanos >= turn.start_unix_nanos);
+    }
+}
@@ -1,16 +1,26 @@
 use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
-use serde::Serialize;
-use tauri::{AppHandle, State};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager, State};
 use tokio::sync::Mutex;
 
-use crate::backend::events::{EventSink, TerminalOutput};
+use crate::backend::claude_cli::build_claude_path_env;
+use crate::backend::events::{EventSink, TerminalExit, TerminalOutput};
+use crate::claude;
 use crate::event_sink::TauriEventSink;
+use crate::remote_backend;
 use crate::state::AppState;
 
+/// How many times, and how often, to check for the shell's exit status once its output
+/// ends.
+const EXIT_POLLS: u32 = 20;
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 pub(crate) struct TerminalSession {
     pub(crate) id: String,
     pub(crate) master: Mutex<Box<dyn portable_pty::MasterPty + Send>>,
@@ -18,11 +28,22 @@ pub(crate) struct TerminalSession {
     pub(crate) child: Mutex<Box<dyn portable_pty::Child + Send>>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct TerminalSessionInfo {
     id: String,
 }
 
+/// What `terminal_kill` sends to the terminal's foreground job.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TerminalSignal {
+    /// Like pressing Ctrl-C.
+    #[default]
+    Interrupt,
+    Terminate,
+    Kill,
+}
+
 fn terminal_key(workspace_id: &str, terminal_id: &str) -> String {
     format!("{workspace_id}:{terminal_id}")
 }
@@ -31,10 +52,13 @@ fn shell_path() -> String {
     std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string())
 }
 
+/// Stream the pty's output until the shell goes away. A session that ended on its own,
+/// rather than through `terminal_close`, is dropped and reported with `terminal-exit`.
 fn spawn_terminal_reader(
-    event_sink: impl EventSink,
+    event_sink: TauriEventSink,
     workspace_id: String,
     terminal_id: String,
+    session: Arc<TerminalSession>,
     mut reader: Box<dyn Read + Send>,
 ) {
     std::thread::spawn(move || {
@@ -54,18 +78,82 @@ fn spawn_terminal_reader(
                 Err(_) => break,
             }
         }
+        let key = terminal_key(&workspace_id, &terminal_id);
+        let state = event_sink.app_handle().state::<AppState>();
+        let mut sessions = state.terminal_sessions.blocking_lock();
+        let still_open = sessions
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &session));
+        if !still_open {
+            return;
+        }
+        sessions.remove(&key);
+        drop(sessions);
+        event_sink.emit_terminal_exit(TerminalExit {
+            workspace_id,
+            terminal_id,
+            exit_code: wait_for_exit(&session),
+        });
     });
 }
 
-async fn get_workspace_path(
-    workspace_id: &str,
-    state: &State<'_, AppState>,
-) -> Result<PathBuf, String> {
-    let workspaces = state.workspaces.lock().await;
-    let entry = workspaces
-        .get(workspace_id)
-        .ok_or_else(|| "Unknown workspace".to_string())?;
-    Ok(PathBuf::from(&entry.path))
+/// The shell's exit code, if it has been reaped by the time its output ended.
+fn wait_for_exit(session: &TerminalSession) -> Option<i32> {
+    for _ in 0..EXIT_POLLS {
+        let status = session.child.blocking_lock().try_wait();
+        match status {
+            Ok(Some(status)) => return i32::try_from(status.exit_code()).ok(),
+            Ok(None) => std::thread::sleep(EXIT_POLL_INTERVAL),
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+/// Signal the foreground job of the terminal: the command it is running, or the shell
+/// itself when idle.
+#[cfg(unix)]
+async fn signal_terminal(session: &TerminalSession, signal: TerminalSignal) -> Result<(), String> {
+    let signal = match signal {
+        TerminalSignal::Interrupt => libc::SIGINT,
+        TerminalSignal::Terminate => libc::SIGTERM,
+        TerminalSignal::Kill => libc::SIGKILL,
+    };
+    let group = session.master.lock().await.process_group_leader();
+    let shell = session
+        .child
+        .lock()
+        .await
+        .process_id()
+        .and_then(|pid| libc::pid_t::try_from(pid).ok());
+    // SAFETY: `kill` has no memory-safety preconditions; a stale pid only yields ESRCH.
+    let delivered = group.is_some_and(|group| unsafe { libc::kill(-group, signal) } == 0)
+        || shell.is_some_and(|shell| unsafe { libc::kill(shell, signal) } == 0);
+    if delivered {
+        Ok(())
+    } else {
+        Err("Failed to signal terminal".to_string())
+    }
+}
+
+/// Windows has no signals for a console: interrupting types Ctrl-C, anything stronger
+/// ends the shell.
+#[cfg(not(unix))]
+async fn signal_terminal(session: &TerminalSession, signal: TerminalSignal) -> Result<(), String> {
+    if signal == TerminalSignal::Interrupt {
+        let mut writer = session.writer.lock().await;
+        writer
+            .write_all(b"\x03")
+            .and_then(|_| writer.flush())
+            .map_err(|e| format!("Failed to write to pty: {e}"))
+    } else {
+        session
+            .child
+            .lock()
+            .await
+            .kill()
+            .map_err(|e| format!("Failed to kill terminal: {e}"))
+    }
 }
 
 #[tauri::command]
@@ -77,6 +165,21 @@ pub(crate) async fn terminal_open(
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<TerminalSessionInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "terminal_open",
+            json!({
+                "workspaceId": workspace_id,
+                "terminalId": terminal_id,
+                "cols": cols,
+                "rows": rows,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
     if terminal_id.is_empty() {
         return Err("Terminal id is required".to_string());
     }
@@ -90,7 +193,14 @@ pub(crate) async fn terminal_open(
         }
     }
 
-    let cwd = get_workspace_path(&workspace_id, &state).await?;
+    let entry = state
+        .workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or_else(|| "Unknown workspace".to_string())?;
+    let claude_bin = claude::workspace_claude_bin(&state, &entry).await;
     let pty_system = native_pty_system();
     let size = PtySize {
         rows: rows.max(2),
@@ -103,9 +213,12 @@ pub(crate) async fn terminal_open(
         .map_err(|e| format!("Failed to open pty: {e}"))?;
 
     let mut cmd = CommandBuilder::new(shell_path());
-    cmd.cwd(cwd);
+    cmd.cwd(PathBuf::from(&entry.path));
     cmd.arg("-i");
     cmd.env("TERM", "xterm-256color");
+    if let Some(path) = build_claude_path_env(claude_bin.as_deref()) {
+        cmd.env("PATH", path);
+    }
 
     let child = pair
         .slave
@@ -137,14 +250,12 @@ pub(crate) async fn terminal_open(
                 id: existing.id.clone(),
             });
         }
-        sessions.insert(key, session);
+        sessions.insert(key, Arc::clone(&session));
     }
     let event_sink = TauriEventSink::new(app);
-    spawn_terminal_reader(event_sink, workspace_id, terminal_id, reader);
+    spawn_terminal_reader(event_sink, workspace_id, terminal_id, session, reader);
 
-    Ok(TerminalSessionInfo {
-        id: session_id,
-    })
+    Ok(TerminalSessionInfo { id: session_id })
 }
 
 #[tauri::command]
@@ -153,7 +264,18 @@ pub(crate) async fn terminal_write(
     terminal_id: String,
     data: String,
     state: State<'_, AppState>,
+    app: AppHandle,
 ) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "terminal_write",
+            json!({ "workspaceId": workspace_id, "terminalId": terminal_id, "data": data }),
+        )
+        .await?;
+        return Ok(());
+    }
     let key = terminal_key(&workspace_id, &terminal_id);
     let sessions = state.terminal_sessions.lock().await;
     let session = sessions
@@ -176,7 +298,23 @@ pub(crate) async fn terminal_resize(
     cols: u16,
     rows: u16,
     state: State<'_, AppState>,
+    app: AppHandle,
 ) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "terminal_resize",
+            json!({
+                "workspaceId": workspace_id,
+                "terminalId": terminal_id,
+                "cols": cols,
+                "rows": rows,
+            }),
+        )
+        .await?;
+        return Ok(());
+    }
     let key = terminal_key(&workspace_id, &terminal_id);
     let sessions = state.terminal_sessions.lock().await;
     let session = sessions
@@ -200,7 +338,18 @@ pub(crate) async fn terminal_close(
     workspace_id: String,
     terminal_id: String,
     state: State<'_, AppState>,
+    app: AppHandle,
 ) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "terminal_close",
+            json!({ "workspaceId": workspace_id, "terminalId": terminal_id }),
+        )
+        .await?;
+        return Ok(());
+    }
     let key = terminal_key(&workspace_id, &terminal_id);
     let mut sessions = state.terminal_sessions.lock().await;
     let session = sessions
@@ -210,3 +359,33 @@ pub(crate) async fn terminal_close(
     let _ = child.kill();
     Ok(())
 }
+
+/// Signal what the terminal is running without closing it. Defaults to an interrupt.
+#[tauri::command]
+pub(crate) async fn terminal_kill(
+    workspace_id: String,
+    terminal_id: String,
+    signal: Option<TerminalSignal>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "terminal_kill",
+            json!({ "workspaceId": workspace_id, "terminalId": terminal_id, "signal": signal }),
+        )
+        .await?;
+        return Ok(());
+    }
+    let key = terminal_key(&workspace_id, &terminal_id);
+    let session = state
+        .terminal_sessions
+        .lock()
+        .await
+        .get(&key)
+        .cloned()
+        .ok_or_else(|| "Terminal session not found".to_string())?;
+    signal_terminal(&session, signal.unwrap_or_default()).await
+}
@@ -0,0 +1,366 @@
+//! Estimate the tokens and input cost of a drafted message before it is sent.
+//!
+//! Claude's tokenizer is not published, so text is counted locally by splitting it the
+//! way BPE tokenizers pre-split (words with their leading space, digit groups, runs of
+//! punctuation and whitespace) and charging each piece by length. That is close enough
+//! to decide whether a draft needs trimming, not to reconcile a bill. Images are charged
+//! at the API's `width * height / 750` after it scales them down to 1568 px and 1.15
+//! megapixels.
+
+use std::path::PathBuf;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::attachments::{self, Attachment};
+use crate::context_pack;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::ImageAttachment;
+
+/// What an image costs when its size can't be read: the most any image costs.
+const UNKNOWN_IMAGE_TOKENS: usize = 1600;
+const MAX_IMAGE_EDGE: f64 = 1568.0;
+const MAX_IMAGE_PIXELS: f64 = 1_150_000.0;
+const PIXELS_PER_TOKEN: f64 = 750.0;
+
+/// Models estimated when none is selected and the workspace has no model.
+const DEFAULT_MODELS: &[&str] = &[
+    "claude-opus-4-5-20251101",
+    "claude-sonnet-4-5-20250929",
+    "claude-haiku-4-5-20251001",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Letter,
+    Digit,
+    Space,
+    Newline,
+    /// CJK and other scripts tokenized about a character at a time, and emoji
+    Wide,
+    Punct,
+}
+
+fn classify(ch: char) -> CharClass {
+    match ch {
+        '\n' | '\r' => CharClass::Newline,
+        _ if ch.is_whitespace() => CharClass::Space,
+        _ if ch.is_ascii_digit() => CharClass::Digit,
+        _ if ch.is_ascii_alphabetic() || ch == '_' => CharClass::Letter,
+        // Latin, Greek and Cyrillic letters merge into words like ASCII ones
+        _ if ch.is_alphabetic() && (ch as u32) < 0x2E80 => CharClass::Letter,
+        _ if ch.is_ascii() => CharClass::Punct,
+        _ => CharClass::Wide,
+    }
+}
+
+/// Estimated tokens of `text`.
+pub(crate) fn count_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(first) = chars.next() {
+        let class = classify(first);
+        let mut len = 1;
+        if class != CharClass::Wide {
+            while chars.peek().is_some_and(|next| classify(*next) == class) {
+                chars.next();
+                len += 1;
+            }
+        }
+        tokens += match class {
+            // A single space belongs to the piece after it
+            CharClass::Space
+                if len == 1 && chars.peek().is_some_and(|next| !next.is_whitespace()) =>
+            {
+                0
+            }
+            CharClass::Space => len.div_ceil(4),
+            CharClass::Newline => len.div_ceil(2),
+            CharClass::Letter if len <= 8 => 1,
+            CharClass::Letter => len.div_ceil(5),
+            CharClass::Digit => len.div_ceil(3),
+            CharClass::Punct => len.div_ceil(3),
+            CharClass::Wide => 1,
+        };
+    }
+    tokens
+}
+
+/// Width and height from an image's header.
+fn image_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |at: usize| Some(u16::from_be_bytes([*bytes.get(at)?, *bytes.get(at + 1)?]) as u32);
+    let le16 = |at: usize| Some(u16::from_le_bytes([*bytes.get(at)?, *bytes.get(at + 1)?]) as u32);
+    let le24 = |at: usize| {
+        Some(u32::from_le_bytes([
+            *bytes.get(at)?,
+            *bytes.get(at + 1)?,
+            *bytes.get(at + 2)?,
+            0,
+        ]))
+    };
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => {
+            let be32 =
+                |at: usize| Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+            Some((be32(16)?, be32(20)?))
+        }
+        [b'G', b'I', b'F', ..] => Some((le16(6)?, le16(8)?)),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => {
+            match bytes.get(12..16)? {
+                b"VP8 " => Some((le16(26)? & 0x3fff, le16(28)? & 0x3fff)),
+                b"VP8L" => {
+                    let bits = u32::from_le_bytes(bytes.get(21..25)?.try_into().ok()?);
+                    Some((1 + (bits & 0x3fff), 1 + ((bits >> 14) & 0x3fff)))
+                }
+                b"VP8X" => Some((1 + le24(24)?, 1 + le24(27)?)),
+                _ => None,
+            }
+        }
+        [0xFF, 0xD8, ..] => {
+            let mut at = 2;
+            while at + 9 < bytes.len() {
+                if bytes[at] != 0xFF {
+                    at += 1;
+                    continue;
+                }
+                let marker = bytes[at + 1];
+                // Start of frame markers, which hold the size; C4, C8 and CC are not
+                if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+                    return Some((be16(at + 7)?, be16(at + 5)?));
+                }
+                at += 2 + be16(at + 2)? as usize;
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Tokens of an image `width` by `height` pixels once the API has scaled it.
+fn image_tokens_for_size(width: u32, height: u32) -> usize {
+    let (mut width, mut height) = (width as f64, height as f64);
+    let long_edge = width.max(height);
+    if long_edge > MAX_IMAGE_EDGE {
+        let scale = MAX_IMAGE_EDGE / long_edge;
+        width *= scale;
+        height *= scale;
+    }
+    if width * height > MAX_IMAGE_PIXELS {
+        let scale = (MAX_IMAGE_PIXELS / (width * height)).sqrt();
+        width *= scale;
+        height *= scale;
+    }
+    ((width * height) / PIXELS_PER_TOKEN).ceil() as usize
+}
+
+pub(crate) fn image_tokens(image: &ImageAttachment) -> usize {
+    STANDARD
+        .decode(&image.data)
+        .ok()
+        .and_then(|bytes| image_size(&bytes))
+        .map(|(width, height)| image_tokens_for_size(width, height))
+        .unwrap_or(UNKNOWN_IMAGE_TOKENS)
+}
+
+/// Input price in US dollars per million tokens, from the model's family.
+fn input_price(model: &str) -> Option<f64> {
+    let model = model.to_ascii_lowercase();
+    if model.contains("opus") {
+        // Opus 4.5 costs a third of the earlier Opus models
+        let older = ["opus-4-1", "opus-4-2025", "opus-3", "3-opus"]
+            .iter()
+            .any(|id| model.contains(id))
+            || model == "claude-opus-4";
+        Some(if older { 15.0 } else { 5.0 })
+    } else if model.contains("sonnet") {
+        Some(3.0)
+    } else if model.contains("haiku") {
+        let older = model.contains("3-5") || model.contains("3-haiku");
+        Some(if older { 0.8 } else { 1.0 })
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ModelEstimate {
+    pub(crate) model: String,
+    /// `None` for models without a known price
+    pub(crate) input_cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TokenEstimate {
+    /// The typed text
+    pub(crate) text_tokens: usize,
+    /// File mentions and embedded files
+    pub(crate) attachment_tokens: usize,
+    pub(crate) context_pack_tokens: usize,
+    pub(crate) image_tokens: usize,
+    pub(crate) total_tokens: usize,
+    pub(crate) models: Vec<ModelEstimate>,
+}
+
+fn model_estimates(models: Vec<String>, total_tokens: usize) -> Vec<ModelEstimate> {
+    models
+        .into_iter()
+        .map(|model| ModelEstimate {
+            input_cost_usd: input_price(&model)
+                .map(|price| price * total_tokens as f64 / 1_000_000.0),
+            model,
+        })
+        .collect()
+}
+
+/// Estimate the message `send_user_message` would send with the same text, images,
+/// attachments and context pack, for each of `models` (the workspace's model, or the
+/// built-in ones, when empty).
+#[tauri::command]
+pub(crate) async fn estimate_tokens(
+    workspace_id: String,
+    text: String,
+    images: Option<Vec<String>>,
+    attachments: Option<Vec<Attachment>>,
+    context_pack: Option<String>,
+    models: Option<Vec<String>>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TokenEstimate, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "estimate_tokens",
+            json!({
+                "workspaceId": workspace_id,
+                "text": text,
+                "images": images,
+                "attachments": attachments,
+                "contextPack": context_pack,
+                "models": models,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let (root, workspace_model) = state
+        .workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .map(|entry| (PathBuf::from(&entry.path), entry.model.clone()))
+        .ok_or("workspace not found")?;
+    let mut attached = attachments::from_images(images.unwrap_or_default());
+    attached.extend(attachments.unwrap_or_default());
+    let message = attachments::prepare(&text, attached, &root)?;
+
+    let text_tokens = count_tokens(text.trim());
+    let attachment_tokens = count_tokens(&message.text).saturating_sub(text_tokens);
+    let context_pack_tokens = match context_pack.filter(|name| !name.trim().is_empty()) {
+        Some(name) => {
+            context_pack::expand_named(&state, &workspace_id, &name)
+                .await?
+                .tokens
+        }
+        None => 0,
+    };
+    let image_tokens = message.images.iter().map(image_tokens).sum();
+    let total_tokens = text_tokens + attachment_tokens + context_pack_tokens + image_tokens;
+
+    let models: Vec<String> = models
+        .unwrap_or_default()
+        .into_iter()
+        .map(|model| model.trim().to_string())
+        .filter(|model| !model.is_empty())
+        .collect();
+    let models = if !models.is_empty() {
+        models
+    } else if let Some(model) = workspace_model {
+        vec![model]
+    } else {
+        DEFAULT_MODELS
+            .iter()
+            .map(|model| model.to_string())
+            .collect()
+    };
+    Ok(TokenEstimate {
+        text_tokens,
+        attachment_tokens,
+        context_pack_tokens,
+        image_tokens,
+        total_tokens,
+        models: model_estimates(models, total_tokens),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_words_numbers_and_punctuation() {
+        assert_eq!(count_tokens(""), 0);
+        assert_eq!(count_tokens("Hello world"), 2);
+        assert_eq!(count_tokens("fn main() {}"), 4);
+        assert_eq!(count_tokens("1234567"), 3);
+        assert_eq!(count_tokens("internationalization"), 4);
+        assert_eq!(count_tokens("    indented\n\n"), 3);
+        assert_eq!(count_tokens("日本語"), 3);
+        let prose = "The quick brown fox jumps over the lazy dog. ".repeat(20);
+        let tokens = count_tokens(&prose);
+        assert!((200..=240).contains(&tokens), "{tokens}");
+    }
+
+    #[test]
+    fn reads_image_sizes() {
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+        png.extend(b"IHDR");
+        png.extend(800u32.to_be_bytes());
+        png.extend(600u32.to_be_bytes());
+        assert_eq!(image_size(&png), Some((800, 600)));
+        let gif = [b'G', b'I', b'F', b'8', b'9', b'a', 0x20, 0x03, 0x58, 0x02];
+        assert_eq!(image_size(&gif), Some((800, 600)));
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x02,
+            0x58, 0x03, 0x20, 0x03,
+        ];
+        assert_eq!(image_size(&jpeg), Some((800, 600)));
+        assert_eq!(image_size(b"not an image"), None);
+    }
+
+    #[test]
+    fn charges_images_after_scaling() {
+        assert_eq!(image_tokens_for_size(750, 1), 1);
+        assert_eq!(image_tokens_for_size(800, 600), 640);
+        // Scaled to fit 1.15 megapixels
+        assert_eq!(image_tokens_for_size(4000, 3000), 1534);
+        let unreadable = ImageAttachment {
+            media_type: "image/png".to_string(),
+            data: STANDARD.encode("not an image"),
+        };
+        assert_eq!(image_tokens(&unreadable), UNKNOWN_IMAGE_TOKENS);
+    }
+
+    #[test]
+    fn prices_by_model_family() {
+        let estimates = model_estimates(
+            vec![
+                "claude-opus-4-5-20251101".to_string(),
+                "claude-opus-4-1-20250805".to_string(),
+                "sonnet".to_string(),
+                "claude-haiku-4-5-20251001".to_string(),
+                "custom-model".to_string(),
+            ],
+            1_000_000,
+        );
+        let costs: Vec<_> = estimates.iter().map(|e| e.input_cost_usd).collect();
+        assert_eq!(costs, [Some(5.0), Some(15.0), Some(3.0), Some(1.0), None]);
+    }
+}
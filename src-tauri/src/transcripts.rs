@@ -0,0 +1,424 @@
+use chrono::DateTime;
+use serde::Deserialize;
+use serde_json::json;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, State};
+
+use crate::backend::stream_events::{ContentBlock, MessageContent};
+use crate::claude_home::resolve_default_claude_home;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::{WorkspaceEntry, WorkspaceSessionSummary};
+
+const FIRST_MESSAGE_MAX_CHARS: usize = 200;
+
+/// The subset of a transcript line needed to summarize a session.
+#[derive(Debug, Default, Deserialize)]
+struct TranscriptLine {
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    timestamp: Option<String>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default, rename = "gitBranch")]
+    git_branch: Option<String>,
+    #[serde(default, rename = "isMeta")]
+    is_meta: bool,
+    #[serde(default, rename = "isSidechain")]
+    is_sidechain: bool,
+    #[serde(default)]
+    message: Option<TranscriptMessage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TranscriptMessage {
+    #[serde(default)]
+    content: MessageContent,
+}
+
+#[tauri::command]
+pub(crate) async fn list_workspace_sessions(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<WorkspaceSessionSummary>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_workspace_sessions",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    tokio::task::spawn_blocking(move || {
+        let Some(project_dir) = resolve_project_dir(&entry) else {
+            return Vec::new();
+        };
+        list_sessions(&project_dir)
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Directory under `~/.claude/projects` where the CLI stores this workspace's transcripts.
+pub(crate) fn resolve_project_dir(entry: &WorkspaceEntry) -> Option<PathBuf> {
+    let projects_root = resolve_default_claude_home()?.join("projects");
+    Some(projects_root.join(encode_project_path(&entry.path)))
+}
+
+/// Mirror the CLI's directory naming: every character other than an ASCII letter or
+/// digit becomes `-`, so `/Users/me/my.app` maps to `-Users-me-my-app` and
+/// `C:\work` maps to `C--work`.
+pub(crate) fn encode_project_path(path: &str) -> String {
+    path.chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '-' })
+        .collect()
+}
+
+/// Summarize every `<session-id>.jsonl` in `project_dir`, most recently updated first.
+pub(crate) fn list_sessions(project_dir: &Path) -> Vec<WorkspaceSessionSummary> {
+    let Ok(dir_entries) = fs::read_dir(project_dir) else {
+        return Vec::new();
+    };
+    let mut sessions = Vec::new();
+    for dir_entry in dir_entries.flatten() {
+        let path = dir_entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let session_id = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) if !stem.is_empty() => stem.to_string(),
+            _ => continue,
+        };
+        let file_mtime = dir_entry
+            .metadata()
+            .ok()
+            .and_then(|meta| meta.modified().ok())
+            .and_then(|timestamp| timestamp.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or(0);
+        if let Some(summary) = summarize_session_file(&path, session_id, file_mtime) {
+            sessions.push(summary);
+        }
+    }
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    sessions
+}
+
+fn summarize_session_file(
+    path: &Path,
+    session_id: String,
+    file_mtime: i64,
+) -> Option<WorkspaceSessionSummary> {
+    let file = File::open(path).ok()?;
+    let mut created_at: Option<i64> = None;
+    let mut last_timestamp: Option<i64> = None;
+    let mut summary: Option<String> = None;
+    let mut first_message: Option<String> = None;
+    let mut git_branch: Option<String> = None;
+    let mut message_count: u32 = 0;
+    for line in BufReader::new(file).lines() {
+        let Ok(line) = line else {
+            continue;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(parsed) = serde_json::from_str::<TranscriptLine>(&line) else {
+            continue;
+        };
+        if let Some(timestamp) = parse_timestamp(parsed.timestamp.as_deref()) {
+            created_at.get_or_insert(timestamp);
+            last_timestamp = Some(timestamp);
+        }
+        if parsed.kind == "summary" {
+            if let Some(text) = parsed.summary.filter(|text| !text.trim().is_empty()) {
+                summary = Some(text.trim().to_string());
+            }
+            continue;
+        }
+        if parsed.kind != "user" && parsed.kind != "assistant" {
+            continue;
+        }
+        if parsed.is_sidechain {
+            continue;
+        }
+        if git_branch.is_none() {
+            git_branch = parsed.git_branch.filter(|branch| !branch.is_empty());
+        }
+        message_count += 1;
+        if first_message.is_none() && parsed.kind == "user" && !parsed.is_meta {
+            let text = parsed
+                .message
+                .map(|message| message.content.text())
+                .unwrap_or_default();
+            if is_prompt_text(&text) {
+                first_message = Some(truncate_chars(text.trim(), FIRST_MESSAGE_MAX_CHARS));
+            }
+        }
+    }
+    if message_count == 0 && summary.is_none() {
+        return None;
+    }
+    Some(WorkspaceSessionSummary {
+        session_id,
+        path: path.to_string_lossy().to_string(),
+        created_at,
+        updated_at: last_timestamp.map_or(file_mtime, |last| last.max(file_mtime)),
+        summary,
+        first_message,
+        message_count,
+        git_branch,
+    })
+}
+
+/// What `fork_transcript` copied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ForkedTranscript {
+    /// Messages in the copy, counted like `messageCount`
+    pub(crate) kept_messages: u32,
+    pub(crate) total_messages: u32,
+}
+
+/// Whether a transcript line is a user message with text or images, as opposed to one
+/// carrying only tool results, so a transcript can end right before it.
+fn starts_turn(line: &TranscriptLine) -> bool {
+    if line.kind != "user" || line.is_sidechain {
+        return false;
+    }
+    match line.message.as_ref().map(|message| &message.content) {
+        Some(MessageContent::Text(text)) => !text.trim().is_empty(),
+        Some(MessageContent::Blocks(blocks)) => blocks.iter().any(|block| {
+            matches!(
+                block,
+                ContentBlock::Text { .. } | ContentBlock::Image { .. }
+            )
+        }),
+        None => false,
+    }
+}
+
+/// Copy the transcript at `source` to `dest` under `session_id`, keeping the messages
+/// before `message_index` (0-based, counted like `messageCount`). A cut inside a turn
+/// moves to the end of that turn, so the copy never ends between a tool call and its
+/// result. Summaries are left out; they may describe messages that were cut.
+pub(crate) fn fork_transcript(
+    source: &Path,
+    dest: &Path,
+    session_id: &str,
+    message_index: u32,
+) -> Result<ForkedTranscript, String> {
+    let content = fs::read_to_string(source).map_err(|err| err.to_string())?;
+    let mut lines = Vec::new();
+    let mut messages = 0;
+    let mut cut: Option<(usize, u32)> = None;
+    for line in content.lines() {
+        let Ok(mut value) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        let parsed: TranscriptLine = serde_json::from_value(value.clone()).unwrap_or_default();
+        if parsed.kind == "summary" {
+            continue;
+        }
+        let is_message =
+            (parsed.kind == "user" || parsed.kind == "assistant") && !parsed.is_sidechain;
+        if cut.is_none() && is_message && messages >= message_index && starts_turn(&parsed) {
+            cut = Some((lines.len(), messages));
+        }
+        if is_message {
+            messages += 1;
+        }
+        if let Some(object) = value.as_object_mut() {
+            if object.contains_key("sessionId") {
+                object.insert("sessionId".to_string(), json!(session_id));
+            }
+        }
+        lines.push(value.to_string());
+    }
+    if message_index >= messages {
+        return Err(format!(
+            "message {message_index} is out of range; the session has {messages} messages"
+        ));
+    }
+    let (end, kept_messages) = cut.unwrap_or((lines.len(), messages));
+    if kept_messages == 0 {
+        return Err("a fork needs at least one message from the session".to_string());
+    }
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(dest)
+        .map_err(|err| err.to_string())?;
+    for line in &lines[..end] {
+        writeln!(file, "{line}").map_err(|err| err.to_string())?;
+    }
+    Ok(ForkedTranscript {
+        kept_messages,
+        total_messages: messages,
+    })
+}
+
+/// Slash-command wrappers and caveats injected by the CLI are not real prompts.
+fn is_prompt_text(text: &str) -> bool {
+    let trimmed = text.trim();
+    !trimmed.is_empty()
+        && !trimmed.starts_with("<command-")
+        && !trimmed.starts_with("<local-command-")
+        && !trimmed.starts_with("Caveat:")
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+fn parse_timestamp(value: Option<&str>) -> Option<i64> {
+    DateTime::parse_from_rfc3339(value?)
+        .map(|dt| dt.timestamp_millis())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn temp_project_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("claude-transcripts-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn encode_project_path_matches_cli_naming() {
+        assert_eq!(
+            encode_project_path("/Users/me/project"),
+            "-Users-me-project"
+        );
+        assert_eq!(
+            encode_project_path("/Users/me/my.app_v2"),
+            "-Users-me-my-app-v2"
+        );
+        assert_eq!(encode_project_path("C:\\work\\repo"), "C--work-repo");
+    }
+
+    #[test]
+    fn list_sessions_summarizes_and_sorts_by_recency() {
+        let dir = temp_project_dir();
+        fs::write(
+            dir.join("older.jsonl"),
+            [
+                r#"{"type":"user","timestamp":"2025-01-01T10:00:00Z","gitBranch":"main","isMeta":true,"message":{"role":"user","content":"Caveat: ignore"}}"#,
+                r#"{"type":"user","timestamp":"2025-01-01T10:00:01Z","message":{"role":"user","content":"<command-name>/clear</command-name>"}}"#,
+                r#"{"type":"user","timestamp":"2025-01-01T10:00:02Z","message":{"role":"user","content":[{"type":"text","text":"Fix the build"}]}}"#,
+                r#"{"type":"assistant","timestamp":"2025-01-01T10:00:05Z","message":{"content":[{"type":"text","text":"Done"}]}}"#,
+                r#"{"type":"summary","summary":"Build fix","leafUuid":"x"}"#,
+                "not json",
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("newer.jsonl"),
+            r#"{"type":"user","timestamp":"2030-01-01T00:00:00Z","message":{"role":"user","content":"Hello"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("empty.jsonl"), "").unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let sessions = list_sessions(&dir);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session_id, "newer");
+        let older = &sessions[1];
+        assert_eq!(older.session_id, "older");
+        assert_eq!(older.first_message.as_deref(), Some("Fix the build"));
+        assert_eq!(older.summary.as_deref(), Some("Build fix"));
+        assert_eq!(older.message_count, 4);
+        assert_eq!(older.git_branch.as_deref(), Some("main"));
+        assert_eq!(
+            older.created_at,
+            parse_timestamp(Some("2025-01-01T10:00:00Z"))
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn list_sessions_handles_missing_directory() {
+        let dir = std::env::temp_dir().join(format!("claude-missing-{}", Uuid::new_v4()));
+        assert!(list_sessions(&dir).is_empty());
+    }
+
+    #[test]
+    fn fork_transcript_cuts_at_turn_boundaries() {
+        let dir = temp_project_dir();
+        let source = dir.join("parent.jsonl");
+        fs::write(
+            &source,
+            [
+                r#"{"type":"user","sessionId":"parent","uuid":"u1","message":{"role":"user","content":"Add a parser"}}"#,
+                r#"{"type":"assistant","sessionId":"parent","uuid":"a1","message":{"content":[{"type":"tool_use","id":"t1","name":"Write","input":{}}]}}"#,
+                r#"{"type":"user","sessionId":"parent","uuid":"u2","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}}"#,
+                r#"{"type":"assistant","sessionId":"parent","uuid":"a2","message":{"content":[{"type":"text","text":"Added"}]}}"#,
+                r#"{"type":"summary","summary":"Parser","leafUuid":"a3"}"#,
+                r#"{"type":"user","sessionId":"parent","uuid":"u3","message":{"role":"user","content":[{"type":"text","text":"Now test it"}]}}"#,
+                r#"{"type":"assistant","sessionId":"parent","uuid":"a3","message":{"content":[{"type":"text","text":"Tested"}]}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+
+        // Message 2 is a tool result; the copy runs to the end of its turn
+        let dest = dir.join("fork.jsonl");
+        let forked = fork_transcript(&source, &dest, "fork", 2).unwrap();
+        assert_eq!(
+            forked,
+            ForkedTranscript {
+                kept_messages: 4,
+                total_messages: 6
+            }
+        );
+        let copied = fs::read_to_string(&dest).unwrap();
+        assert_eq!(copied.lines().count(), 4);
+        assert!(copied.contains(r#""sessionId":"fork""#));
+        assert!(!copied.contains("parent"));
+        assert!(!copied.contains("Now test it"));
+
+        // Forking at a prompt leaves it out, so it can be asked differently
+        let at_prompt = dir.join("at-prompt.jsonl");
+        assert_eq!(
+            fork_transcript(&source, &at_prompt, "p", 4)
+                .unwrap()
+                .kept_messages,
+            4
+        );
+        assert!(fork_transcript(&source, &dir.join("a.jsonl"), "a", 0).is_err());
+        assert!(fork_transcript(&source, &dir.join("b.jsonl"), "b", 6).is_err());
+        // An existing session is never overwritten
+        assert!(fork_transcript(&source, &dest, "fork", 2).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn truncate_chars_respects_char_boundaries() {
+        assert_eq!(truncate_chars("héllo", 2), "hé…");
+        assert_eq!(truncate_chars("hi", 5), "hi");
+    }
+}
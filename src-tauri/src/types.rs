@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub(crate) struct GitFileStatus {
     pub(crate) path: String,
     pub(crate) status: String,
@@ -155,6 +155,577 @@ pub(crate) struct LocalUsageSnapshot {
     pub(crate) top_models: Vec<LocalUsageModel>,
 }
 
+/// Tokens and spend of a single completed turn, taken from the CLI's `result` event.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnUsage {
+    pub(crate) workspace_id: String,
+    pub(crate) session_id: String,
+    pub(crate) turn_id: String,
+    pub(crate) timestamp: i64,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    pub(crate) input_tokens: u64,
+    pub(crate) output_tokens: u64,
+    pub(crate) cache_creation_input_tokens: u64,
+    pub(crate) cache_read_input_tokens: u64,
+    pub(crate) cost_usd: f64,
+    #[serde(default)]
+    pub(crate) duration_ms: Option<u64>,
+    /// Missing for turns recorded before timings were tracked.
+    #[serde(default)]
+    pub(crate) timings: Option<TurnTimings>,
+    /// The turn ended in an error. Turns recorded before this was tracked count as
+    /// successful.
+    #[serde(default)]
+    pub(crate) is_error: bool,
+}
+
+/// Where a turn's wall-clock time went, measured by timestamping the CLI's events as
+/// the monitor reads them.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnTimings {
+    /// From writing the prompt (or the first event, if the monitor did not send it) to
+    /// the `result` event.
+    pub(crate) wall_ms: u64,
+    /// From writing the prompt to the first assistant message; unknown when the turn was
+    /// not started by the monitor.
+    pub(crate) time_to_first_token_ms: Option<u64>,
+    /// Time with at least one tool call in flight, including permission prompts.
+    pub(crate) tool_ms: u64,
+    /// The rest of the wall time: waiting on and streaming from the model.
+    pub(crate) model_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ToolCallStatus {
+    Running,
+    Succeeded,
+    Failed,
+    /// The turn ended before the tool reported a result.
+    Unfinished,
+}
+
+/// One `tool_use` of a turn paired with its `tool_result`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ToolCallRecord {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    /// The most telling input field (command, path, pattern, ...) or the input as JSON.
+    pub(crate) input_summary: String,
+    pub(crate) started_at: i64,
+    pub(crate) finished_at: Option<i64>,
+    pub(crate) duration_ms: Option<u64>,
+    pub(crate) status: ToolCallStatus,
+    pub(crate) output: Option<String>,
+    #[serde(default)]
+    pub(crate) output_truncated: bool,
+}
+
+/// The tool calls of a completed turn, in the order they were made.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnTimeline {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: String,
+    pub(crate) started_at: i64,
+    pub(crate) finished_at: i64,
+    pub(crate) tool_calls: Vec<ToolCallRecord>,
+}
+
+/// What a turn changed in its workspace, from git snapshots taken when the turn started
+/// and when it finished. Other threads working in the same checkout at the same time
+/// show up here too.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnDiff {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: String,
+    pub(crate) created_at: i64,
+    pub(crate) files: Vec<GitFileStatus>,
+    /// Unified diff of the changed files.
+    pub(crate) diff: String,
+    #[serde(default)]
+    pub(crate) diff_truncated: bool,
+}
+
+/// How a file-editing tool call changes its file.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum FileEditKind {
+    /// `Write`: the whole file is replaced, or created.
+    Write,
+    /// `Edit` or `MultiEdit`: strings in the file are replaced.
+    Edit,
+}
+
+/// One replacement made by a file-editing tool call. Line numbers are 1-based and
+/// unset when the old text could not be found in the file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EditHunk {
+    pub(crate) old_text: String,
+    pub(crate) new_text: String,
+    /// Every occurrence of `old_text` is replaced, not just the first.
+    #[serde(default)]
+    pub(crate) replace_all: bool,
+    #[serde(default)]
+    pub(crate) old_start: Option<usize>,
+    pub(crate) old_lines: usize,
+    #[serde(default)]
+    pub(crate) new_start: Option<usize>,
+    pub(crate) new_lines: usize,
+}
+
+/// The hunks of one `Edit`, `MultiEdit` or `Write` call.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileEdit {
+    pub(crate) tool_use_id: String,
+    /// Relative to the workspace when the file is inside it.
+    pub(crate) path: String,
+    pub(crate) kind: FileEditKind,
+    pub(crate) hunks: Vec<EditHunk>,
+}
+
+/// The file edits a turn's tool calls made, in order. Unlike [`TurnDiff`] this covers
+/// only what the thread's own tools wrote, and needs no git repository.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnEdits {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: String,
+    pub(crate) created_at: i64,
+    pub(crate) edits: Vec<FileEdit>,
+}
+
+/// What started a compaction of a thread's conversation.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CompactionTrigger {
+    /// The workspace's `autoCompact` threshold.
+    Threshold,
+    /// `/compact` sent by the user.
+    Manual,
+    /// The CLI's own auto-compaction, close to the end of the window.
+    Cli,
+}
+
+/// One compaction of a thread's conversation.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CompactionRecord {
+    pub(crate) id: String,
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) trigger: CompactionTrigger,
+    pub(crate) started_at: i64,
+    #[serde(default)]
+    pub(crate) finished_at: Option<i64>,
+    /// Context the conversation took before compacting.
+    #[serde(default)]
+    pub(crate) tokens_before: Option<u64>,
+    /// Context of the first message after compacting, once there is one.
+    #[serde(default)]
+    pub(crate) tokens_after: Option<u64>,
+    #[serde(default)]
+    pub(crate) tokens_reclaimed: Option<u64>,
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+}
+
+/// An image sent with a user message, base64-encoded.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImageAttachment {
+    /// `image/png`, `image/jpeg`, `image/gif` or `image/webp`.
+    pub(crate) media_type: String,
+    pub(crate) data: String,
+}
+
+impl ImageAttachment {
+    pub(crate) fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.media_type, self.data)
+    }
+}
+
+/// A message whose thread had no process to take it, kept until one is back.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PendingMessage {
+    pub(crate) id: String,
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) prompt: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) images: Vec<ImageAttachment>,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) access_mode: Option<String>,
+    pub(crate) queued_at: i64,
+    /// Failed attempts to send it since it was parked.
+    #[serde(default)]
+    pub(crate) attempts: u32,
+    #[serde(default)]
+    pub(crate) last_attempt_at: Option<i64>,
+    /// Why the message could not be sent, most recently.
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+}
+
+/// What a daemon API token may do on the remote surfaces. Roles are ordered: an
+/// operator can do everything an observer can.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TokenRole {
+    /// Watch: the event feed, metrics and the REST API's reads.
+    Observer,
+    /// Also act: start threads, send messages, interrupt turns.
+    Operator,
+}
+
+/// A token issued for the daemon's remote surfaces. Its secret is shown once, when it
+/// is issued; only its SHA-256 is stored.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ApiToken {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) role: TokenRole,
+    pub(crate) created_at: i64,
+    #[serde(default)]
+    pub(crate) expires_at: Option<i64>,
+    /// Updated at most once a minute.
+    #[serde(default)]
+    pub(crate) last_used_at: Option<i64>,
+    #[serde(default)]
+    pub(crate) revoked_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ActivitySeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ActivityKind {
+    Turn,
+    Tool,
+    Permission,
+    Error,
+}
+
+/// A running command that can be cancelled with `cancel_operation`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OperationInfo {
+    /// Chosen by the caller and passed to the command as `operationId`.
+    pub(crate) id: String,
+    /// The command running, e.g. `export_session`.
+    pub(crate) kind: String,
+    #[serde(default)]
+    pub(crate) workspace_id: Option<String>,
+    pub(crate) started_at: i64,
+}
+
+/// How app-server events have been delivered since the backend started.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EventStats {
+    /// Current batching interval for streamed text; 0 when batching is off.
+    pub(crate) delta_batch_ms: u64,
+    pub(crate) events_emitted: u64,
+    pub(crate) deltas_received: u64,
+    /// Deltas merged into an earlier one instead of being emitted on their own.
+    pub(crate) deltas_merged: u64,
+    /// Emitted events that carried more than one delta.
+    pub(crate) batches_merged: u64,
+    pub(crate) largest_batch: u64,
+    /// Deltas waiting for the current interval to end.
+    pub(crate) pending_deltas: u64,
+    /// Events the window did not accept.
+    pub(crate) emit_errors: u64,
+    /// Events skipped for daemon clients that fell behind.
+    pub(crate) events_dropped: u64,
+}
+
+/// One line of the cross-workspace activity feed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ActivityEntry {
+    /// Increases by one per entry, across restarts; use it as the `since` cursor.
+    pub(crate) seq: u64,
+    pub(crate) timestamp: i64,
+    pub(crate) workspace_id: String,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+    #[serde(default)]
+    pub(crate) turn_id: Option<String>,
+    pub(crate) kind: ActivityKind,
+    pub(crate) severity: ActivitySeverity,
+    pub(crate) summary: String,
+    /// The event the entry was made from, e.g. `turn/completed`.
+    pub(crate) method: String,
+}
+
+/// One request on a remote surface, allowed or not.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditEntry {
+    pub(crate) timestamp: i64,
+    /// `None` for the daemon's shared `--token` and for tokens nobody issued.
+    #[serde(default)]
+    pub(crate) token_id: Option<String>,
+    #[serde(default)]
+    pub(crate) token_name: Option<String>,
+    /// `rest` or `websocket`.
+    pub(crate) surface: String,
+    /// The method called, or `subscribe` for the event feed.
+    pub(crate) action: String,
+    pub(crate) allowed: bool,
+    /// Why the request was refused.
+    #[serde(default)]
+    pub(crate) reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum IssueProvider {
+    GitHub,
+    GitLab,
+}
+
+/// An issue on GitHub or a GitLab instance.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IssueRef {
+    pub(crate) provider: IssueProvider,
+    pub(crate) host: String,
+    /// `owner/repo` on GitHub, the project's full path on GitLab.
+    pub(crate) repo: String,
+    pub(crate) number: u64,
+    pub(crate) url: String,
+}
+
+/// A thread started from an issue, so its result can be posted back there.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IssueLink {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    /// The turn the issue started; `None` when the message was parked for later.
+    #[serde(default)]
+    pub(crate) turn_id: Option<String>,
+    pub(crate) issue: IssueRef,
+    pub(crate) created_at: i64,
+    #[serde(default)]
+    pub(crate) commented_at: Option<i64>,
+    #[serde(default)]
+    pub(crate) comment_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum AnnotationTarget {
+    Turn,
+    Message,
+}
+
+impl AnnotationTarget {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Turn => "turn",
+            Self::Message => "message",
+        }
+    }
+}
+
+/// A reviewer's note, star rating and named bookmark on a turn or on one message of a
+/// thread. Messages are identified by their item id, turns by their turn id.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Annotation {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) target: AnnotationTarget,
+    pub(crate) target_id: String,
+    #[serde(default)]
+    pub(crate) note: Option<String>,
+    /// One to five stars.
+    #[serde(default)]
+    pub(crate) rating: Option<u8>,
+    #[serde(default)]
+    pub(crate) bookmark: Option<String>,
+    pub(crate) created_at: i64,
+    pub(crate) updated_at: i64,
+}
+
+/// Timing statistics over a workspace's timed turns.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceTurnStats {
+    pub(crate) workspace_id: String,
+    pub(crate) turns: u64,
+    pub(crate) avg_wall_ms: u64,
+    pub(crate) p50_wall_ms: u64,
+    pub(crate) p90_wall_ms: u64,
+    pub(crate) max_wall_ms: u64,
+    pub(crate) avg_time_to_first_token_ms: Option<u64>,
+    pub(crate) avg_tool_ms: u64,
+    pub(crate) avg_model_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnMetrics {
+    /// Timed turns, newest first.
+    pub(crate) turns: Vec<TurnUsage>,
+    /// Slowest workspaces (by average wall time) first.
+    pub(crate) workspaces: Vec<WorkspaceTurnStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsageTotals {
+    pub(crate) turns: u64,
+    pub(crate) input_tokens: u64,
+    pub(crate) output_tokens: u64,
+    pub(crate) cache_creation_input_tokens: u64,
+    pub(crate) cache_read_input_tokens: u64,
+    pub(crate) cost_usd: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum UsageInterval {
+    #[default]
+    Day,
+    /// Weeks start on Monday.
+    Week,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum UsageGroupBy {
+    Workspace,
+    Model,
+}
+
+/// The period a usage report covers, in epoch milliseconds. Without `since` it covers
+/// the last 30 days, or the last 12 weeks with weekly buckets.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsageRange {
+    #[serde(default)]
+    pub(crate) since: Option<i64>,
+    #[serde(default)]
+    pub(crate) until: Option<i64>,
+    #[serde(default)]
+    pub(crate) interval: UsageInterval,
+}
+
+/// Totals of a set of turns with their success rate and average duration.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsageRollup {
+    #[serde(flatten)]
+    pub(crate) totals: UsageTotals,
+    pub(crate) failed_turns: u64,
+    /// Share of turns that did not end in an error; `None` without turns.
+    pub(crate) success_rate: Option<f64>,
+    /// Over the turns whose duration is known.
+    pub(crate) avg_duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsageBucket {
+    /// Local date the bucket starts on, `YYYY-MM-DD`.
+    pub(crate) start: String,
+    #[serde(flatten)]
+    pub(crate) rollup: UsageRollup,
+}
+
+/// One workspace's or model's rollups, with a bucket for every bucket of the report.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsageSeries {
+    /// The workspace id or model; `None` collects turns with no model recorded.
+    pub(crate) key: Option<String>,
+    pub(crate) buckets: Vec<UsageBucket>,
+    pub(crate) totals: UsageRollup,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsageReport {
+    pub(crate) since: i64,
+    pub(crate) until: i64,
+    pub(crate) interval: UsageInterval,
+    pub(crate) group_by: Option<UsageGroupBy>,
+    /// Every turn in the range, bucketed; empty buckets are included so charts line up.
+    pub(crate) buckets: Vec<UsageBucket>,
+    /// Most expensive first. Empty without `group_by`.
+    pub(crate) series: Vec<UsageSeries>,
+    pub(crate) totals: UsageRollup,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DailyUsage {
+    pub(crate) day: String,
+    pub(crate) totals: UsageTotals,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionUsage {
+    pub(crate) session_id: String,
+    pub(crate) totals: UsageTotals,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceUsage {
+    pub(crate) workspace_id: String,
+    pub(crate) totals: UsageTotals,
+    pub(crate) sessions: Vec<SessionUsage>,
+}
+
+/// A folder found by workspace discovery that is not registered yet.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiscoveredWorkspace {
+    pub(crate) entry: WorkspaceEntry,
+    pub(crate) git_repo: bool,
+    pub(crate) claude_project: bool,
+}
+
+/// A past conversation found in the workspace's `~/.claude/projects` directory.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceSessionSummary {
+    pub(crate) session_id: String,
+    pub(crate) path: String,
+    pub(crate) created_at: Option<i64>,
+    pub(crate) updated_at: i64,
+    pub(crate) summary: Option<String>,
+    pub(crate) first_message: Option<String>,
+    pub(crate) message_count: u32,
+    pub(crate) git_branch: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct BranchInfo {
     pub(crate) name: String,
@@ -168,12 +739,22 @@ pub(crate) struct WorkspaceEntry {
     pub(crate) path: String,
     #[serde(default)]
     pub(crate) claude_bin: Option<String>,
+    /// Model used for turns that don't pick one, e.g. `claude-opus-4-5-20251101`.
+    #[serde(default)]
+    pub(crate) model: Option<String>,
     #[serde(default)]
     pub(crate) kind: WorkspaceKind,
     #[serde(default, rename = "parentId")]
     pub(crate) parent_id: Option<String>,
     #[serde(default)]
     pub(crate) worktree: Option<WorktreeInfo>,
+    /// When the workspace was archived: hidden from active views, with its transcripts
+    /// and stats kept.
+    #[serde(default, rename = "archivedAt")]
+    pub(crate) archived_at: Option<i64>,
+    /// Connected at launch, with a process already running for its latest thread.
+    #[serde(default)]
+    pub(crate) pinned: bool,
     #[serde(default)]
     pub(crate) settings: WorkspaceSettings,
 }
@@ -186,12 +767,22 @@ pub(crate) struct WorkspaceInfo {
     pub(crate) connected: bool,
     #[serde(default)]
     pub(crate) claude_bin: Option<String>,
+    /// Model used for turns that don't pick one, e.g. `claude-opus-4-5-20251101`.
+    #[serde(default)]
+    pub(crate) model: Option<String>,
     #[serde(default)]
     pub(crate) kind: WorkspaceKind,
     #[serde(default, rename = "parentId")]
     pub(crate) parent_id: Option<String>,
     #[serde(default)]
     pub(crate) worktree: Option<WorktreeInfo>,
+    /// When the workspace was archived: hidden from active views, with its transcripts
+    /// and stats kept.
+    #[serde(default, rename = "archivedAt")]
+    pub(crate) archived_at: Option<i64>,
+    /// Connected at launch, with a process already running for its latest thread.
+    #[serde(default)]
+    pub(crate) pinned: bool,
     #[serde(default)]
     pub(crate) settings: WorkspaceSettings,
 }
@@ -240,6 +831,365 @@ pub(crate) struct WorkspaceSettings {
     pub(crate) group_id: Option<String>,
     #[serde(default, rename = "gitRoot")]
     pub(crate) git_root: Option<String>,
+    #[serde(default, rename = "interruptGraceMs")]
+    pub(crate) interrupt_grace_ms: Option<u64>,
+    /// Suppresses desktop notifications for turns and permission prompts.
+    #[serde(default, rename = "notificationsMuted")]
+    pub(crate) notifications_muted: bool,
+    /// Minutes a thread's CLI process may sit idle before it is stopped; 0 disables.
+    #[serde(default, rename = "idleTimeoutMinutes")]
+    pub(crate) idle_timeout_minutes: Option<u32>,
+    /// Minutes a turn may run before the watchdog cancels it; unset or 0 disables.
+    #[serde(default, rename = "maxTurnMinutes")]
+    pub(crate) max_turn_minutes: Option<u32>,
+    /// Minutes any one tool call may run before the watchdog cancels its turn; unset or
+    /// 0 disables.
+    #[serde(default, rename = "maxToolRunMinutes")]
+    pub(crate) max_tool_run_minutes: Option<u32>,
+    /// Memory a thread's CLI process tree may use before the workspace is warned, and
+    /// whether the process is then restarted.
+    #[serde(default, rename = "resourceLimits")]
+    pub(crate) resource_limits: Option<ResourceLimits>,
+    /// When unanswered permission prompts, questions and plans go stale and get a
+    /// default answer; they wait for the user unless configured.
+    #[serde(default, rename = "interactionTimeouts")]
+    pub(crate) interaction_timeouts: Option<InteractionTimeouts>,
+    /// Re-send turns that fail with a transient API error; off unless configured.
+    #[serde(default, rename = "retryPolicy")]
+    pub(crate) retry_policy: Option<RetryPolicy>,
+    /// Commit the working tree to a side branch after every successful turn.
+    #[serde(default, rename = "autoCommit")]
+    pub(crate) auto_commit: Option<AutoCommitPolicy>,
+    /// Observe only: tools that change files are unavailable, and a turn that changes
+    /// the git working tree anyway is reported and reverted.
+    #[serde(default, rename = "readOnly")]
+    pub(crate) read_only: bool,
+    /// Glob rules for the paths Claude's file tools may touch.
+    #[serde(default, rename = "pathRules")]
+    pub(crate) path_rules: Option<PathRules>,
+    /// Regex rules that answer `Bash` permission requests without asking.
+    #[serde(default, rename = "bashPolicy")]
+    pub(crate) bash_policy: Option<BashPolicy>,
+    /// Commands such as `cargo test` run after every successful turn.
+    #[serde(default)]
+    pub(crate) verification: Option<VerificationPolicy>,
+    /// Send `/compact` after turns that leave a thread's context window too full.
+    #[serde(default, rename = "autoCompact")]
+    pub(crate) auto_compact: Option<AutoCompactPolicy>,
+    /// Spending limits for this workspace's turns.
+    #[serde(default, rename = "costBudget")]
+    pub(crate) cost_budget: Option<CostBudget>,
+    /// Agent CLI the workspace's threads run on; Claude Code when unset.
+    #[serde(default)]
+    pub(crate) agent: Option<AgentKind>,
+    /// Binary of a non-Claude agent; `claudeBin` still applies to Claude.
+    #[serde(default, rename = "agentBin")]
+    pub(crate) agent_bin: Option<String>,
+    /// Write each thread process's stdin and stdout to a replayable recording.
+    #[serde(default, rename = "recordSessions")]
+    pub(crate) record_sessions: bool,
+    /// Claude CLI flags that constrain the workspace's threads and unattended runs.
+    #[serde(default, rename = "cliFlags")]
+    pub(crate) cli_flags: Option<ClaudeCliFlags>,
+    /// Permission mode of turns that keep the current access mode; overrides
+    /// `cliFlags.permissionMode`.
+    #[serde(default, rename = "permissionPreset")]
+    pub(crate) permission_preset: Option<PermissionPreset>,
+    /// API the workspace's Claude processes talk to; whatever the environment selects
+    /// when unset.
+    #[serde(default)]
+    pub(crate) provider: Option<ProviderSettings>,
+    /// Overrides the app's proxy settings field by field.
+    #[serde(default)]
+    pub(crate) proxy: Option<ProxySettings>,
+    /// Free-form labels such as `client-a`, for filtering and aggregate views.
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+}
+
+/// Network settings for spawned CLI processes; unset fields leave the inherited
+/// environment alone.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProxySettings {
+    /// `HTTP_PROXY`, e.g. `http://proxy.corp:3128`.
+    #[serde(default)]
+    pub(crate) http_proxy: Option<String>,
+    /// `HTTPS_PROXY`, which the CLI uses for the API.
+    #[serde(default)]
+    pub(crate) https_proxy: Option<String>,
+    /// `NO_PROXY`: comma-separated hosts that bypass the proxy.
+    #[serde(default)]
+    pub(crate) no_proxy: Option<String>,
+    /// `NODE_EXTRA_CA_CERTS`: a PEM file of extra certificate authorities, e.g. the one a
+    /// TLS-inspecting proxy signs with.
+    #[serde(default)]
+    pub(crate) ca_certs_path: Option<String>,
+}
+
+/// Where Claude models are served from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ModelProvider {
+    Anthropic,
+    Bedrock,
+    Vertex,
+}
+
+/// Non-secret provider configuration; keys and tokens live in the keychain, see
+/// `credentials`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProviderSettings {
+    pub(crate) provider: ModelProvider,
+    /// `AWS_REGION` for Bedrock, `CLOUD_ML_REGION` for Vertex.
+    #[serde(default)]
+    pub(crate) region: Option<String>,
+    /// `AWS_PROFILE` for Bedrock.
+    #[serde(default)]
+    pub(crate) profile: Option<String>,
+    /// `ANTHROPIC_VERTEX_PROJECT_ID` for Vertex.
+    #[serde(default)]
+    pub(crate) project_id: Option<String>,
+}
+
+/// How much a workspace's threads may do without asking.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum PermissionPreset {
+    /// Read and plan only: edits and shell commands are refused without asking.
+    Plan,
+    /// File edits go through without asking; everything else still asks.
+    AcceptEdits,
+    /// Every tool use that needs permission is put to the user.
+    AskEverything,
+}
+
+/// Typed Claude CLI flags; see `backend::cli_flags` for how they become arguments.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClaudeCliFlags {
+    /// `--allowedTools`: tools that run without asking, e.g. `Read` or `Bash(git log:*)`.
+    #[serde(default)]
+    pub(crate) allowed_tools: Vec<String>,
+    /// `--disallowedTools`: tools Claude may not use at all.
+    #[serde(default)]
+    pub(crate) disallowed_tools: Vec<String>,
+    /// `--permission-mode` for turns that keep the current access mode.
+    #[serde(default)]
+    pub(crate) permission_mode: Option<String>,
+    /// `--max-turns`: agentic turns per message before Claude stops.
+    #[serde(default)]
+    pub(crate) max_turns: Option<u32>,
+    /// `--add-dir`: directories outside the workspace Claude may access. Relative paths
+    /// are relative to the workspace.
+    #[serde(default)]
+    pub(crate) add_dirs: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum AgentKind {
+    #[default]
+    Claude,
+    Codex,
+    /// Replays fixtures instead of calling a model; for development and tests.
+    Mock,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CostBudget {
+    #[serde(default)]
+    pub(crate) daily_usd: Option<f64>,
+    #[serde(default)]
+    pub(crate) monthly_usd: Option<f64>,
+    /// Share of a limit at which warnings start; 0.8 when unset.
+    #[serde(default)]
+    pub(crate) warn_ratio: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AutoCommitPolicy {
+    #[serde(default)]
+    pub(crate) enabled: bool,
+    /// Branch the commits go to; `monitor/autocommit` when unset.
+    #[serde(default)]
+    pub(crate) branch: Option<String>,
+}
+
+/// Workspace-relative globs such as `src/**` or `secrets/**`. `*` stays within one
+/// directory, `**` spans any number, and a pattern without a `/` matches at any depth.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PathRules {
+    /// When non-empty, file tools may only touch paths matching one of these.
+    #[serde(default)]
+    pub(crate) allow: Vec<String>,
+    /// Paths file tools may never touch; these win over `allow`.
+    #[serde(default)]
+    pub(crate) deny: Vec<String>,
+}
+
+/// Regexes checked against the command of a `Bash` permission request; see
+/// `backend::bash_policy`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BashPolicy {
+    /// A command runs without asking when every part of it matches one of these whole.
+    #[serde(default)]
+    pub(crate) allow: Vec<String>,
+    /// A command matching any of these anywhere is refused; these win over `allow`.
+    #[serde(default)]
+    pub(crate) deny: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum BashAction {
+    Allow,
+    Deny,
+    /// No rule matched; the request went on to the permission preset or the user.
+    Ask,
+}
+
+/// One `Bash` permission request the workspace's policy looked at.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BashDecision {
+    pub(crate) timestamp: i64,
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: String,
+    #[serde(default)]
+    pub(crate) tool_use_id: Option<String>,
+    pub(crate) command: String,
+    pub(crate) action: BashAction,
+    /// The rule that decided, for `allow` and `deny`.
+    #[serde(default)]
+    pub(crate) rule: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct VerificationPolicy {
+    /// Shell commands run in order in the workspace directory; the first failure stops
+    /// the rest.
+    #[serde(default)]
+    pub(crate) commands: Vec<String>,
+    /// Send a failing command's output back to the thread as a follow-up message.
+    #[serde(default)]
+    pub(crate) feed_back_failures: bool,
+    /// Follow-ups in a row before the loop gives up; 3 when unset.
+    #[serde(default)]
+    pub(crate) max_fix_attempts: Option<u32>,
+    /// Seconds each command may run; 600 when unset or 0.
+    #[serde(default)]
+    pub(crate) timeout_seconds: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResourceLimits {
+    /// Resident memory of a thread's process and its descendants, in megabytes; unset
+    /// or 0 means no limit.
+    #[serde(default)]
+    pub(crate) max_memory_mb: Option<u64>,
+    /// Stop a thread's process once it is over the limit and idle; its next message
+    /// resumes the thread in a fresh process.
+    #[serde(default)]
+    pub(crate) auto_restart: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InteractionTimeouts {
+    /// Seconds before an unanswered request is reported stale; unset or 0 never.
+    #[serde(default)]
+    pub(crate) stale_after_seconds: Option<u32>,
+    /// Seconds before an unanswered request gets its default answer; unset or 0 leaves
+    /// it to the user.
+    #[serde(default)]
+    pub(crate) auto_answer_after_seconds: Option<u32>,
+    /// Tools whose permission requests are allowed by default; others are denied.
+    #[serde(default)]
+    pub(crate) auto_allow_tools: Vec<String>,
+    /// Approve plans by default too, instead of waiting for the user.
+    #[serde(default)]
+    pub(crate) auto_approve_plans: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AutoCompactPolicy {
+    #[serde(default)]
+    pub(crate) enabled: bool,
+    /// Percent of the context window a turn may leave filled, 1 to 99.
+    #[serde(default = "default_compact_threshold_percent")]
+    pub(crate) threshold_percent: u8,
+    /// What the summary should focus on, passed along as `/compact <instructions>`.
+    #[serde(default)]
+    pub(crate) instructions: Option<String>,
+}
+
+impl Default for AutoCompactPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_percent: default_compact_threshold_percent(),
+            instructions: None,
+        }
+    }
+}
+
+fn default_compact_threshold_percent() -> u8 {
+    80
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RetryPolicy {
+    #[serde(default)]
+    pub(crate) enabled: bool,
+    /// Retries after the first failure, not counting the original attempt.
+    #[serde(default = "default_retry_max_attempts")]
+    pub(crate) max_attempts: u32,
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub(crate) base_delay_ms: u64,
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub(crate) max_delay_ms: u64,
+    /// Fraction of each delay randomized up or down, 0.0 to 1.0.
+    #[serde(default = "default_retry_jitter")]
+    pub(crate) jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_attempts: default_retry_max_attempts(),
+            base_delay_ms: default_retry_base_delay_ms(),
+            max_delay_ms: default_retry_max_delay_ms(),
+            jitter: default_retry_jitter(),
+        }
+    }
+}
+
+fn default_retry_max_attempts() -> u32 {
+    3
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    2_000
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    60_000
+}
+
+fn default_retry_jitter() -> f64 {
+    0.2
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -357,33 +1307,51 @@ pub(crate) struct AppSettings {
     pub(crate) experimental_unified_exec_enabled: bool,
     #[serde(default = "default_dictation_enabled", rename = "dictationEnabled")]
     pub(crate) dictation_enabled: bool,
-    #[serde(
-        default = "default_dictation_model_id",
-        rename = "dictationModelId"
-    )]
+    #[serde(default = "default_dictation_model_id", rename = "dictationModelId")]
     pub(crate) dictation_model_id: String,
     #[serde(default, rename = "dictationPreferredLanguage")]
     pub(crate) dictation_preferred_language: Option<String>,
+    #[serde(default = "default_dictation_hold_key", rename = "dictationHoldKey")]
+    pub(crate) dictation_hold_key: String,
     #[serde(
-        default = "default_dictation_hold_key",
-        rename = "dictationHoldKey"
+        default = "default_composer_editor_preset",
+        rename = "composerEditorPreset"
     )]
-    pub(crate) dictation_hold_key: String,
-    #[serde(default = "default_composer_editor_preset", rename = "composerEditorPreset")]
     pub(crate) composer_editor_preset: String,
-    #[serde(default = "default_composer_fence_expand_on_space", rename = "composerFenceExpandOnSpace")]
+    #[serde(
+        default = "default_composer_fence_expand_on_space",
+        rename = "composerFenceExpandOnSpace"
+    )]
     pub(crate) composer_fence_expand_on_space: bool,
-    #[serde(default = "default_composer_fence_expand_on_enter", rename = "composerFenceExpandOnEnter")]
+    #[serde(
+        default = "default_composer_fence_expand_on_enter",
+        rename = "composerFenceExpandOnEnter"
+    )]
     pub(crate) composer_fence_expand_on_enter: bool,
-    #[serde(default = "default_composer_fence_language_tags", rename = "composerFenceLanguageTags")]
+    #[serde(
+        default = "default_composer_fence_language_tags",
+        rename = "composerFenceLanguageTags"
+    )]
     pub(crate) composer_fence_language_tags: bool,
-    #[serde(default = "default_composer_fence_wrap_selection", rename = "composerFenceWrapSelection")]
+    #[serde(
+        default = "default_composer_fence_wrap_selection",
+        rename = "composerFenceWrapSelection"
+    )]
     pub(crate) composer_fence_wrap_selection: bool,
-    #[serde(default = "default_composer_fence_auto_wrap_paste_multiline", rename = "composerFenceAutoWrapPasteMultiline")]
+    #[serde(
+        default = "default_composer_fence_auto_wrap_paste_multiline",
+        rename = "composerFenceAutoWrapPasteMultiline"
+    )]
     pub(crate) composer_fence_auto_wrap_paste_multiline: bool,
-    #[serde(default = "default_composer_fence_auto_wrap_paste_code_like", rename = "composerFenceAutoWrapPasteCodeLike")]
+    #[serde(
+        default = "default_composer_fence_auto_wrap_paste_code_like",
+        rename = "composerFenceAutoWrapPasteCodeLike"
+    )]
     pub(crate) composer_fence_auto_wrap_paste_code_like: bool,
-    #[serde(default = "default_composer_list_continuation", rename = "composerListContinuation")]
+    #[serde(
+        default = "default_composer_list_continuation",
+        rename = "composerListContinuation"
+    )]
     pub(crate) composer_list_continuation: bool,
     #[serde(
         default = "default_composer_code_block_copy_use_modifier",
@@ -392,6 +1360,95 @@ pub(crate) struct AppSettings {
     pub(crate) composer_code_block_copy_use_modifier: bool,
     #[serde(default = "default_workspace_groups", rename = "workspaceGroups")]
     pub(crate) workspace_groups: Vec<WorkspaceGroup>,
+    /// Folders scanned by `discover_workspaces`, e.g. `~/code`.
+    #[serde(default, rename = "workspaceDiscoveryRoots")]
+    pub(crate) workspace_discovery_roots: Vec<String>,
+    /// Spending limits across every workspace.
+    #[serde(default, rename = "costBudget")]
+    pub(crate) cost_budget: Option<CostBudget>,
+    #[serde(default)]
+    pub(crate) webhooks: Vec<WebhookConfig>,
+    /// How long to let running turns finish when the app quits before stopping them.
+    #[serde(default, rename = "shutdownDrainSeconds")]
+    pub(crate) shutdown_drain_seconds: Option<u64>,
+    /// How long streamed assistant text is gathered before it is sent to the frontend;
+    /// 30 ms when unset, and 0 sends every delta on its own.
+    #[serde(default, rename = "deltaBatchMs")]
+    pub(crate) delta_batch_ms: Option<u64>,
+    /// Masking of secrets in events and in what the monitor stores.
+    #[serde(default)]
+    pub(crate) redaction: RedactionSettings,
+    /// Proxy and certificate settings for every CLI process.
+    #[serde(default)]
+    pub(crate) proxy: Option<ProxySettings>,
+    /// How long old sessions and usage records are kept.
+    #[serde(default)]
+    pub(crate) retention: RetentionPolicy,
+    /// Backend log filter, e.g. `debug` or `info,claude_code_monitor_lib::claude=trace`;
+    /// `RUST_LOG` applies when unset.
+    #[serde(default, rename = "logLevel")]
+    pub(crate) log_level: Option<String>,
+}
+
+/// Secrets such as API keys are replaced with `[REDACTED:<kind>]` placeholders before
+/// events reach the frontend and before transcripts, timelines and recordings are stored.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RedactionSettings {
+    #[serde(default = "default_redaction_enabled")]
+    pub(crate) enabled: bool,
+    /// Extra regular expressions to redact. When a pattern has a capture group, only the
+    /// first group is replaced, e.g. `password: (\S+)`.
+    #[serde(default)]
+    pub(crate) patterns: Vec<String>,
+    /// Keep each redacted value encrypted so it can be revealed later.
+    #[serde(default)]
+    pub(crate) keep_originals: bool,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            patterns: Vec::new(),
+            keep_originals: false,
+        }
+    }
+}
+
+fn default_redaction_enabled() -> bool {
+    true
+}
+
+/// Age limits for stored data, counted from when it last changed; unset keeps it forever.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RetentionPolicy {
+    /// Days to keep session transcripts, recordings, turn timelines and turn diffs.
+    #[serde(default)]
+    pub(crate) transcript_days: Option<u32>,
+    /// Days to keep per-turn usage and cost records.
+    #[serde(default)]
+    pub(crate) usage_days: Option<u32>,
+}
+
+/// An HTTP endpoint that receives turn lifecycle events.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WebhookConfig {
+    pub(crate) url: String,
+    /// Key for the `X-Monitor-Signature` HMAC; requests are unsigned without one.
+    #[serde(default)]
+    pub(crate) secret: Option<String>,
+    /// Event names such as `turn.failed`; empty means all of them.
+    #[serde(default)]
+    pub(crate) events: Vec<String>,
+    #[serde(default = "default_webhook_enabled")]
+    pub(crate) enabled: bool,
+}
+
+fn default_webhook_enabled() -> bool {
+    true
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -424,8 +1481,7 @@ fn default_theme() -> String {
 }
 
 fn default_ui_font_family() -> String {
-    "\"SF Pro Text\", \"SF Pro Display\", -apple-system, \"Helvetica Neue\", sans-serif"
-        .to_string()
+    "\"SF Pro Text\", \"SF Pro Display\", -apple-system, \"Helvetica Neue\", sans-serif".to_string()
 }
 
 fn default_code_font_family() -> String {
@@ -602,11 +1658,22 @@ impl Default for AppSettings {
             composer_fence_expand_on_enter: default_composer_fence_expand_on_enter(),
             composer_fence_language_tags: default_composer_fence_language_tags(),
             composer_fence_wrap_selection: default_composer_fence_wrap_selection(),
-            composer_fence_auto_wrap_paste_multiline: default_composer_fence_auto_wrap_paste_multiline(),
-            composer_fence_auto_wrap_paste_code_like: default_composer_fence_auto_wrap_paste_code_like(),
+            composer_fence_auto_wrap_paste_multiline:
+                default_composer_fence_auto_wrap_paste_multiline(),
+            composer_fence_auto_wrap_paste_code_like:
+                default_composer_fence_auto_wrap_paste_code_like(),
             composer_list_continuation: default_composer_list_continuation(),
             composer_code_block_copy_use_modifier: default_composer_code_block_copy_use_modifier(),
             workspace_groups: default_workspace_groups(),
+            workspace_discovery_roots: Vec::new(),
+            cost_budget: None,
+            webhooks: Vec::new(),
+            shutdown_drain_seconds: None,
+            delta_batch_ms: None,
+            redaction: RedactionSettings::default(),
+            proxy: None,
+            retention: RetentionPolicy::default(),
+            log_level: None,
         }
     }
 }
@@ -684,6 +1751,7 @@ mod tests {
         assert!(!settings.composer_list_continuation);
         assert!(!settings.composer_code_block_copy_use_modifier);
         assert!(settings.workspace_groups.is_empty());
+        assert!(settings.workspace_discovery_roots.is_empty());
     }
 
     #[test]
@@ -715,13 +1783,13 @@ mod tests {
 
     #[test]
     fn workspace_entry_defaults_from_minimal_json() {
-        let entry: WorkspaceEntry = serde_json::from_str(
-            r#"{"id":"1","name":"Test","path":"/tmp","claudeBin":null}"#,
-        )
-        .expect("workspace deserialize");
+        let entry: WorkspaceEntry =
+            serde_json::from_str(r#"{"id":"1","name":"Test","path":"/tmp","claudeBin":null}"#)
+                .expect("workspace deserialize");
         assert!(matches!(entry.kind, WorkspaceKind::Main));
         assert!(entry.parent_id.is_none());
         assert!(entry.worktree.is_none());
+        assert!(entry.model.is_none());
         assert!(entry.settings.sort_order.is_none());
         assert!(entry.settings.group_id.is_none());
     }
@@ -733,5 +1801,6 @@ mod tests {
         assert!(settings.sort_order.is_none());
         assert!(settings.group_id.is_none());
         assert!(settings.git_root.is_none());
+        assert!(settings.interrupt_grace_ms.is_none());
     }
 }
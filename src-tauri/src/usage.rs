@@ -0,0 +1,1196 @@
+use chrono::{Datelike, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, State};
+
+use crate::backend::stream_events::ResultEvent;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::storage;
+use crate::types::{
+    DailyUsage, SessionUsage, TurnMetrics, TurnUsage, UsageBucket, UsageGroupBy, UsageInterval,
+    UsageRange, UsageReport, UsageRollup, UsageSeries, UsageTotals, WorkspaceTurnStats,
+    WorkspaceUsage,
+};
+use crate::utils::now_ms;
+
+/// Every completed turn seen by this app, mirrored into the `turns` table of the app
+/// database so spend survives restarts.
+#[derive(Default)]
+struct UsageLedger {
+    path: Option<PathBuf>,
+    turns: Vec<TurnUsage>,
+}
+
+static USAGE_LEDGER: OnceLock<Mutex<UsageLedger>> = OnceLock::new();
+
+const DEFAULT_TURN_LIMIT: u32 = 100;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+/// Keeps a report from covering years in daily buckets.
+const MAX_REPORT_BUCKETS: usize = 400;
+
+fn ledger() -> &'static Mutex<UsageLedger> {
+    USAGE_LEDGER.get_or_init(|| Mutex::new(UsageLedger::default()))
+}
+
+fn with_ledger<T>(f: impl FnOnce(&mut UsageLedger) -> T) -> T {
+    let mut guard = ledger()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut guard)
+}
+
+/// Load previously recorded turns and persist new ones to the database at `path`.
+pub(crate) fn load_ledger(path: PathBuf) {
+    let turns = storage::read_turns(&path).unwrap_or_else(|err| {
+        tracing::warn!("failed to load turn usage: {err}");
+        Vec::new()
+    });
+    with_ledger(|ledger| {
+        ledger.turns = turns;
+        ledger.path = Some(path);
+    });
+}
+
+pub(crate) fn record_turn(turn: TurnUsage) {
+    with_ledger(|ledger| {
+        if let Some(path) = &ledger.path {
+            if let Err(err) = storage::append_turn(path, &turn) {
+                tracing::warn!("failed to persist turn usage: {err}");
+            }
+        }
+        ledger.turns.push(turn);
+    });
+}
+
+/// Forget turns that finished before `cutoff`, in memory and in the database. Returns how
+/// many were dropped.
+pub(crate) fn prune_before(cutoff: i64) -> Result<usize, String> {
+    with_ledger(|ledger| {
+        let before = ledger.turns.len();
+        ledger.turns.retain(|turn| turn.timestamp >= cutoff);
+        if let Some(path) = &ledger.path {
+            storage::prune_turns(path, cutoff)?;
+        }
+        Ok(before - ledger.turns.len())
+    })
+}
+
+/// Dollars spent since `since_ms`, in one workspace or across all of them.
+pub(crate) fn spent_since(workspace_id: Option<&str>, since_ms: i64) -> f64 {
+    with_ledger(|ledger| cost_since(&ledger.turns, workspace_id, since_ms))
+}
+
+/// A workspace's usage totals and when its last turn finished.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WorkspaceActivity {
+    pub(crate) totals: UsageTotals,
+    pub(crate) last_turn_at: Option<i64>,
+}
+
+/// Usage totals and last activity of every workspace with recorded turns.
+pub(crate) fn workspace_activity() -> HashMap<String, WorkspaceActivity> {
+    with_ledger(|ledger| activity_by_workspace(&ledger.turns))
+}
+
+fn activity_by_workspace(turns: &[TurnUsage]) -> HashMap<String, WorkspaceActivity> {
+    let mut workspaces: HashMap<String, WorkspaceActivity> = HashMap::new();
+    for turn in turns {
+        let activity = workspaces.entry(turn.workspace_id.clone()).or_default();
+        add_turn(&mut activity.totals, turn);
+        activity.last_turn_at = activity.last_turn_at.max(Some(turn.timestamp));
+    }
+    workspaces
+}
+
+/// Usage totals per session (thread) of one workspace.
+pub(crate) fn session_totals(workspace_id: &str) -> HashMap<String, UsageTotals> {
+    with_ledger(|ledger| totals_by_session(&ledger.turns, workspace_id))
+}
+
+fn totals_by_session(turns: &[TurnUsage], workspace_id: &str) -> HashMap<String, UsageTotals> {
+    let mut sessions: HashMap<String, UsageTotals> = HashMap::new();
+    for turn in turns
+        .iter()
+        .filter(|turn| turn.workspace_id == workspace_id)
+    {
+        add_turn(sessions.entry(turn.session_id.clone()).or_default(), turn);
+    }
+    sessions
+}
+
+fn cost_since(turns: &[TurnUsage], workspace_id: Option<&str>, since_ms: i64) -> f64 {
+    turns
+        .iter()
+        .filter(|turn| turn.timestamp >= since_ms)
+        .filter(|turn| matches_filter(workspace_id, &turn.workspace_id))
+        .map(|turn| turn.cost_usd)
+        .sum()
+}
+
+/// Build the ledger entry for a turn from its `result` event.
+///
+/// The CLI reports `total_cost_usd` cumulatively for the lifetime of the process, so the
+/// turn's own cost is the difference to the previous report from the same process.
+pub(crate) fn turn_usage_from_result(
+    workspace_id: &str,
+    session_id: &str,
+    turn_id: &str,
+    model: Option<String>,
+    result: &ResultEvent,
+    previous_total_cost: f64,
+) -> TurnUsage {
+    let usage = result.usage.clone().unwrap_or_default();
+    let reported_total = result.total_cost_usd.unwrap_or(previous_total_cost);
+    let cost_usd = if reported_total >= previous_total_cost {
+        reported_total - previous_total_cost
+    } else {
+        reported_total
+    };
+    TurnUsage {
+        workspace_id: workspace_id.to_string(),
+        session_id: result
+            .session_id
+            .clone()
+            .unwrap_or_else(|| session_id.to_string()),
+        turn_id: turn_id.to_string(),
+        timestamp: now_ms(),
+        model,
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        cache_creation_input_tokens: usage.cache_creation_input_tokens,
+        cache_read_input_tokens: usage.cache_read_input_tokens,
+        cost_usd,
+        duration_ms: result.duration_ms,
+        timings: None,
+        is_error: result.is_error || result.subtype.starts_with("error"),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn usage_turns(
+    workspace_id: Option<String>,
+    session_id: Option<String>,
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<TurnUsage>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "usage_turns",
+            json!({ "workspaceId": workspace_id, "sessionId": session_id, "limit": limit }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let limit = limit.unwrap_or(DEFAULT_TURN_LIMIT).max(1) as usize;
+    Ok(with_ledger(|ledger| {
+        recent_turns(
+            &ledger.turns,
+            workspace_id.as_deref(),
+            session_id.as_deref(),
+            limit,
+        )
+    }))
+}
+
+#[tauri::command]
+pub(crate) async fn usage_daily(
+    days: Option<u32>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<DailyUsage>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "usage_daily",
+            json!({ "days": days, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let days = days.unwrap_or(30).clamp(1, 365);
+    let today = Local::now().date_naive();
+    let day_keys = (0..days)
+        .rev()
+        .map(|offset| {
+            (today - chrono::Duration::days(offset as i64))
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+    Ok(with_ledger(|ledger| {
+        daily_usage(&ledger.turns, &day_keys, workspace_id.as_deref())
+    }))
+}
+
+#[tauri::command]
+pub(crate) async fn usage_workspaces(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<WorkspaceUsage>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "usage_workspaces",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    Ok(with_ledger(|ledger| {
+        workspace_usage(&ledger.turns, workspace_id.as_deref())
+    }))
+}
+
+/// Per-turn timings plus per-workspace timing statistics, to see where turns are slow.
+#[tauri::command]
+pub(crate) async fn get_turn_metrics(
+    workspace_id: Option<String>,
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TurnMetrics, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_turn_metrics",
+            json!({ "workspaceId": workspace_id, "limit": limit }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let limit = limit.unwrap_or(DEFAULT_TURN_LIMIT).max(1) as usize;
+    Ok(with_ledger(|ledger| {
+        turn_metrics(&ledger.turns, workspace_id.as_deref(), limit)
+    }))
+}
+
+/// Turns, tokens, cost, success rate and average duration per day or week, overall and
+/// per workspace or model, for the usage charts.
+#[tauri::command]
+pub(crate) async fn get_usage_report(
+    range: Option<UsageRange>,
+    group_by: Option<UsageGroupBy>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<UsageReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_usage_report",
+            json!({ "range": range, "groupBy": group_by, "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let range = range.unwrap_or_default();
+    let until = range.until.unwrap_or_else(now_ms);
+    let since = range.since.unwrap_or_else(|| {
+        until
+            - match range.interval {
+                UsageInterval::Day => 30 * DAY_MS,
+                UsageInterval::Week => 12 * 7 * DAY_MS,
+            }
+    });
+    with_ledger(|ledger| {
+        usage_report(
+            &ledger.turns,
+            since,
+            until,
+            range.interval,
+            group_by,
+            workspace_id.as_deref(),
+        )
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum UsageExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelUsageExport {
+    /// `None` for turns with no model recorded.
+    model: Option<String>,
+    #[serde(flatten)]
+    rollup: UsageRollup,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceUsageExport {
+    workspace_id: String,
+    /// `None` once the workspace has been removed.
+    workspace_name: Option<String>,
+    #[serde(flatten)]
+    rollup: UsageRollup,
+    models: Vec<ModelUsageExport>,
+}
+
+/// Spend over a period, per workspace, per model and per model within each workspace.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageExport {
+    since: i64,
+    until: i64,
+    /// Local dates of `since` and `until`.
+    period_start: String,
+    period_end: String,
+    totals: UsageRollup,
+    workspaces: Vec<WorkspaceUsageExport>,
+    models: Vec<ModelUsageExport>,
+}
+
+impl UsageExportFormat {
+    fn render(self, export: &UsageExport) -> Result<String, String> {
+        match self {
+            Self::Csv => Ok(render_usage_csv(export)),
+            Self::Json => serde_json::to_string_pretty(export).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+fn usage_export(
+    turns: &[TurnUsage],
+    since: i64,
+    until: i64,
+    workspace_names: &HashMap<String, String>,
+) -> UsageExport {
+    let mut total = RollupSums::default();
+    let mut workspaces: BTreeMap<&str, (RollupSums, BTreeMap<Option<&str>, RollupSums>)> =
+        BTreeMap::new();
+    let mut models: BTreeMap<Option<&str>, RollupSums> = BTreeMap::new();
+    for turn in turns
+        .iter()
+        .filter(|turn| turn.timestamp >= since && turn.timestamp <= until)
+    {
+        total.add(turn);
+        let (workspace, by_model) = workspaces.entry(turn.workspace_id.as_str()).or_default();
+        workspace.add(turn);
+        by_model.entry(turn.model.as_deref()).or_default().add(turn);
+        models.entry(turn.model.as_deref()).or_default().add(turn);
+    }
+    let model_rows = |models: BTreeMap<Option<&str>, RollupSums>| {
+        models
+            .into_iter()
+            .map(|(model, sums)| ModelUsageExport {
+                model: model.map(str::to_string),
+                rollup: sums.rollup(),
+            })
+            .collect::<Vec<_>>()
+    };
+    let date = |timestamp_ms| {
+        local_date(timestamp_ms)
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    };
+    UsageExport {
+        since,
+        until,
+        period_start: date(since),
+        period_end: date(until),
+        totals: total.rollup(),
+        workspaces: workspaces
+            .into_iter()
+            .map(|(workspace_id, (sums, by_model))| WorkspaceUsageExport {
+                workspace_id: workspace_id.to_string(),
+                workspace_name: workspace_names.get(workspace_id).cloned(),
+                rollup: sums.rollup(),
+                models: model_rows(by_model),
+            })
+            .collect(),
+        models: model_rows(models),
+    }
+}
+
+const USAGE_CSV_HEADER: &str = "scope,period_start,period_end,workspace_id,workspace_name,model,\
+turns,failed_turns,input_tokens,output_tokens,cache_creation_input_tokens,\
+cache_read_input_tokens,cost_usd,success_rate,avg_duration_ms";
+
+/// A CSV field, quoted when needed. Text starting like a formula gets a leading `'` so
+/// spreadsheets show it instead of evaluating it.
+fn csv_field(text: &str) -> String {
+    let text = if text.starts_with(['=', '+', '-', '@']) {
+        format!("'{text}")
+    } else {
+        text.to_string()
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// One row per workspace followed by its models, then one per model and a total. The
+/// `scope` column tells them apart, so filtering on it gives each breakdown.
+fn render_usage_csv(export: &UsageExport) -> String {
+    let mut out = format!("{USAGE_CSV_HEADER}\n");
+    let mut row = |scope: &str,
+                   workspace: Option<&WorkspaceUsageExport>,
+                   model: Option<&str>,
+                   rollup: &UsageRollup| {
+        let totals = &rollup.totals;
+        let fields = [
+            scope.to_string(),
+            export.period_start.clone(),
+            export.period_end.clone(),
+            csv_field(workspace.map_or("", |workspace| workspace.workspace_id.as_str())),
+            csv_field(
+                workspace
+                    .and_then(|workspace| workspace.workspace_name.as_deref())
+                    .unwrap_or_default(),
+            ),
+            csv_field(model.unwrap_or_default()),
+            totals.turns.to_string(),
+            rollup.failed_turns.to_string(),
+            totals.input_tokens.to_string(),
+            totals.output_tokens.to_string(),
+            totals.cache_creation_input_tokens.to_string(),
+            totals.cache_read_input_tokens.to_string(),
+            format!("{:.6}", totals.cost_usd),
+            rollup
+                .success_rate
+                .map(|rate| format!("{rate:.4}"))
+                .unwrap_or_default(),
+            rollup
+                .avg_duration_ms
+                .map(|ms| ms.to_string())
+                .unwrap_or_default(),
+        ];
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    };
+    for workspace in &export.workspaces {
+        row("workspace", Some(workspace), None, &workspace.rollup);
+        for model in &workspace.models {
+            row(
+                "workspace_model",
+                Some(workspace),
+                model.model.as_deref(),
+                &model.rollup,
+            );
+        }
+    }
+    for model in &export.models {
+        row("model", None, model.model.as_deref(), &model.rollup);
+    }
+    row("total", None, None, &export.totals);
+    out
+}
+
+/// Midnight at the start of the current month, local time.
+fn start_of_month_ms() -> i64 {
+    let today = Local::now().date_naive();
+    today
+        .with_day(1)
+        .and_then(|first| first.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map_or(0, |start| start.timestamp_millis())
+}
+
+/// Export spend between `since` and `until` (this month so far by default) as CSV or
+/// JSON for expense reports. With a `path` the export is written there; without one it
+/// is returned as `content`.
+#[tauri::command]
+pub(crate) async fn export_usage(
+    since: Option<i64>,
+    until: Option<i64>,
+    format: UsageExportFormat,
+    path: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    let path = path.filter(|path| !path.trim().is_empty());
+    if remote_backend::is_remote_mode(&*state).await {
+        // The daemon renders; the file is written on this machine
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "export_usage",
+            json!({ "since": since, "until": until, "format": format }),
+        )
+        .await?;
+        let Some(path) = path else {
+            return Ok(response);
+        };
+        let content = response
+            .get("content")
+            .and_then(Value::as_str)
+            .ok_or("Remote export returned no content.")?;
+        fs::write(&path, content).map_err(|err| err.to_string())?;
+        return Ok(json!({ "path": path, "bytes": content.len() }));
+    }
+
+    let until = until.unwrap_or_else(now_ms);
+    let since = since.unwrap_or_else(start_of_month_ms);
+    if since > until {
+        return Err("The export range ends before it starts.".to_string());
+    }
+    let workspace_names: HashMap<String, String> = state
+        .workspaces
+        .lock()
+        .await
+        .values()
+        .map(|entry| (entry.id.clone(), entry.name.clone()))
+        .collect();
+    let export = with_ledger(|ledger| usage_export(&ledger.turns, since, until, &workspace_names));
+    let content = format.render(&export)?;
+    match path {
+        Some(path) => {
+            fs::write(&path, &content).map_err(|err| err.to_string())?;
+            Ok(json!({ "path": path, "bytes": content.len() }))
+        }
+        None => Ok(json!({ "content": content })),
+    }
+}
+
+fn recent_turns(
+    turns: &[TurnUsage],
+    workspace_id: Option<&str>,
+    session_id: Option<&str>,
+    limit: usize,
+) -> Vec<TurnUsage> {
+    turns
+        .iter()
+        .rev()
+        .filter(|turn| matches_filter(workspace_id, &turn.workspace_id))
+        .filter(|turn| matches_filter(session_id, &turn.session_id))
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+fn daily_usage(
+    turns: &[TurnUsage],
+    day_keys: &[String],
+    workspace_id: Option<&str>,
+) -> Vec<DailyUsage> {
+    let mut by_day: HashMap<String, UsageTotals> = day_keys
+        .iter()
+        .map(|day| (day.clone(), UsageTotals::default()))
+        .collect();
+    for turn in turns {
+        if !matches_filter(workspace_id, &turn.workspace_id) {
+            continue;
+        }
+        let Some(day) = day_key_for_timestamp_ms(turn.timestamp) else {
+            continue;
+        };
+        if let Some(totals) = by_day.get_mut(&day) {
+            add_turn(totals, turn);
+        }
+    }
+    day_keys
+        .iter()
+        .map(|day| DailyUsage {
+            day: day.clone(),
+            totals: by_day.remove(day).unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn workspace_usage(turns: &[TurnUsage], workspace_id: Option<&str>) -> Vec<WorkspaceUsage> {
+    let mut workspaces: BTreeMap<&str, (UsageTotals, BTreeMap<&str, UsageTotals>)> =
+        BTreeMap::new();
+    for turn in turns {
+        if !matches_filter(workspace_id, &turn.workspace_id) {
+            continue;
+        }
+        let (totals, sessions) = workspaces.entry(turn.workspace_id.as_str()).or_default();
+        add_turn(totals, turn);
+        add_turn(sessions.entry(turn.session_id.as_str()).or_default(), turn);
+    }
+    workspaces
+        .into_iter()
+        .map(|(workspace_id, (totals, sessions))| WorkspaceUsage {
+            workspace_id: workspace_id.to_string(),
+            totals,
+            sessions: sessions
+                .into_iter()
+                .map(|(session_id, totals)| SessionUsage {
+                    session_id: session_id.to_string(),
+                    totals,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn turn_metrics(turns: &[TurnUsage], workspace_id: Option<&str>, limit: usize) -> TurnMetrics {
+    let timed = turns
+        .iter()
+        .filter(|turn| turn.timings.is_some())
+        .filter(|turn| matches_filter(workspace_id, &turn.workspace_id));
+    let mut by_workspace: BTreeMap<&str, Vec<&TurnUsage>> = BTreeMap::new();
+    for turn in timed.clone() {
+        by_workspace
+            .entry(turn.workspace_id.as_str())
+            .or_default()
+            .push(turn);
+    }
+    let mut workspaces: Vec<WorkspaceTurnStats> = by_workspace
+        .into_iter()
+        .map(|(workspace_id, turns)| workspace_turn_stats(workspace_id, &turns))
+        .collect();
+    workspaces.sort_by(|a, b| b.avg_wall_ms.cmp(&a.avg_wall_ms));
+    TurnMetrics {
+        turns: timed.rev().take(limit).cloned().collect(),
+        workspaces,
+    }
+}
+
+fn workspace_turn_stats(workspace_id: &str, turns: &[&TurnUsage]) -> WorkspaceTurnStats {
+    let timings: Vec<_> = turns
+        .iter()
+        .filter_map(|turn| turn.timings.as_ref())
+        .collect();
+    let count = timings.len() as u64;
+    let average = |values: Vec<u64>| {
+        let len = values.len() as u64;
+        (len > 0).then(|| values.iter().sum::<u64>() / len)
+    };
+    let mut wall: Vec<u64> = timings.iter().map(|t| t.wall_ms).collect();
+    wall.sort_unstable();
+    let percentile = |p: usize| {
+        wall.get((wall.len().saturating_sub(1) * p).div_ceil(100))
+            .copied()
+            .unwrap_or_default()
+    };
+    WorkspaceTurnStats {
+        workspace_id: workspace_id.to_string(),
+        turns: count,
+        avg_wall_ms: average(wall.clone()).unwrap_or_default(),
+        p50_wall_ms: percentile(50),
+        p90_wall_ms: percentile(90),
+        max_wall_ms: wall.last().copied().unwrap_or_default(),
+        avg_time_to_first_token_ms: average(
+            timings
+                .iter()
+                .filter_map(|t| t.time_to_first_token_ms)
+                .collect(),
+        ),
+        avg_tool_ms: average(timings.iter().map(|t| t.tool_ms).collect()).unwrap_or_default(),
+        avg_model_ms: average(timings.iter().map(|t| t.model_ms).collect()).unwrap_or_default(),
+    }
+}
+
+/// Running sums behind a `UsageRollup`.
+#[derive(Debug, Clone, Default)]
+struct RollupSums {
+    totals: UsageTotals,
+    failed_turns: u64,
+    duration_ms: u64,
+    timed_turns: u64,
+}
+
+impl RollupSums {
+    fn add(&mut self, turn: &TurnUsage) {
+        add_turn(&mut self.totals, turn);
+        if turn.is_error {
+            self.failed_turns += 1;
+        }
+        let duration = turn
+            .duration_ms
+            .or(turn.timings.as_ref().map(|timings| timings.wall_ms));
+        if let Some(duration) = duration {
+            self.duration_ms += duration;
+            self.timed_turns += 1;
+        }
+    }
+
+    fn rollup(&self) -> UsageRollup {
+        let turns = self.totals.turns;
+        UsageRollup {
+            totals: self.totals.clone(),
+            failed_turns: self.failed_turns,
+            success_rate: (turns > 0).then(|| (turns - self.failed_turns) as f64 / turns as f64),
+            avg_duration_ms: (self.timed_turns > 0).then(|| self.duration_ms / self.timed_turns),
+        }
+    }
+}
+
+/// Sums per bucket, in the order of the report's buckets.
+fn bucket_rollups(starts: &[NaiveDate], sums: &[RollupSums]) -> Vec<UsageBucket> {
+    starts
+        .iter()
+        .zip(sums)
+        .map(|(start, sums)| UsageBucket {
+            start: start.format("%Y-%m-%d").to_string(),
+            rollup: sums.rollup(),
+        })
+        .collect()
+}
+
+fn local_date(timestamp_ms: i64) -> Option<NaiveDate> {
+    let utc = Utc.timestamp_millis_opt(timestamp_ms).single()?;
+    Some(utc.with_timezone(&Local).date_naive())
+}
+
+/// The first day of the day or week `date` falls in.
+fn bucket_start(date: NaiveDate, interval: UsageInterval) -> NaiveDate {
+    match interval {
+        UsageInterval::Day => date,
+        UsageInterval::Week => {
+            date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+        }
+    }
+}
+
+fn usage_report(
+    turns: &[TurnUsage],
+    since: i64,
+    until: i64,
+    interval: UsageInterval,
+    group_by: Option<UsageGroupBy>,
+    workspace_id: Option<&str>,
+) -> Result<UsageReport, String> {
+    if since > until {
+        return Err("The report range ends before it starts.".to_string());
+    }
+    let (Some(first), Some(last)) = (local_date(since), local_date(until)) else {
+        return Err("The report range is out of bounds.".to_string());
+    };
+    let step = match interval {
+        UsageInterval::Day => 1,
+        UsageInterval::Week => 7,
+    };
+    let last = bucket_start(last, interval);
+    let mut starts = vec![bucket_start(first, interval)];
+    while let Some(&previous) = starts.last().filter(|start| **start < last) {
+        if starts.len() >= MAX_REPORT_BUCKETS {
+            return Err(format!(
+                "The report range spans more than {MAX_REPORT_BUCKETS} buckets; use weekly buckets or a shorter range."
+            ));
+        }
+        starts.push(previous + chrono::Duration::days(step));
+    }
+    let index: HashMap<NaiveDate, usize> = starts
+        .iter()
+        .enumerate()
+        .map(|(index, start)| (*start, index))
+        .collect();
+
+    let mut overall = vec![RollupSums::default(); starts.len()];
+    let mut groups: HashMap<Option<String>, Vec<RollupSums>> = HashMap::new();
+    let in_range = turns
+        .iter()
+        .filter(|turn| turn.timestamp >= since && turn.timestamp <= until)
+        .filter(|turn| matches_filter(workspace_id, &turn.workspace_id));
+    for turn in in_range {
+        let Some(&bucket) = local_date(turn.timestamp)
+            .map(|date| bucket_start(date, interval))
+            .and_then(|start| index.get(&start))
+        else {
+            continue;
+        };
+        overall[bucket].add(turn);
+        let key = match group_by {
+            Some(UsageGroupBy::Workspace) => Some(turn.workspace_id.clone()),
+            Some(UsageGroupBy::Model) => turn.model.clone(),
+            None => continue,
+        };
+        groups
+            .entry(key)
+            .or_insert_with(|| vec![RollupSums::default(); starts.len()])[bucket]
+            .add(turn);
+    }
+
+    let total = |sums: &[RollupSums]| {
+        let mut total = RollupSums::default();
+        for bucket in sums {
+            add_totals(&mut total.totals, &bucket.totals);
+            total.failed_turns += bucket.failed_turns;
+            total.duration_ms += bucket.duration_ms;
+            total.timed_turns += bucket.timed_turns;
+        }
+        total.rollup()
+    };
+    let mut series: Vec<UsageSeries> = groups
+        .into_iter()
+        .map(|(key, sums)| UsageSeries {
+            key,
+            buckets: bucket_rollups(&starts, &sums),
+            totals: total(&sums),
+        })
+        .collect();
+    series.sort_by(|a, b| {
+        b.totals
+            .totals
+            .cost_usd
+            .total_cmp(&a.totals.totals.cost_usd)
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    Ok(UsageReport {
+        since,
+        until,
+        interval,
+        group_by,
+        buckets: bucket_rollups(&starts, &overall),
+        series,
+        totals: total(&overall),
+    })
+}
+
+fn add_totals(totals: &mut UsageTotals, other: &UsageTotals) {
+    totals.turns += other.turns;
+    totals.input_tokens += other.input_tokens;
+    totals.output_tokens += other.output_tokens;
+    totals.cache_creation_input_tokens += other.cache_creation_input_tokens;
+    totals.cache_read_input_tokens += other.cache_read_input_tokens;
+    totals.cost_usd += other.cost_usd;
+}
+
+fn matches_filter(filter: Option<&str>, value: &str) -> bool {
+    filter.is_none() || filter == Some(value)
+}
+
+fn add_turn(totals: &mut UsageTotals, turn: &TurnUsage) {
+    totals.turns += 1;
+    totals.input_tokens += turn.input_tokens;
+    totals.output_tokens += turn.output_tokens;
+    totals.cache_creation_input_tokens += turn.cache_creation_input_tokens;
+    totals.cache_read_input_tokens += turn.cache_read_input_tokens;
+    totals.cost_usd += turn.cost_usd;
+}
+
+fn day_key_for_timestamp_ms(timestamp_ms: i64) -> Option<String> {
+    Some(local_date(timestamp_ms)?.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::stream_events::{parse_event_line, ClaudeEvent};
+    use crate::types::TurnTimings;
+    use uuid::Uuid;
+
+    fn turn(workspace_id: &str, session_id: &str, timestamp: i64, cost_usd: f64) -> TurnUsage {
+        TurnUsage {
+            workspace_id: workspace_id.to_string(),
+            session_id: session_id.to_string(),
+            turn_id: Uuid::new_v4().to_string(),
+            timestamp,
+            model: None,
+            input_tokens: 10,
+            output_tokens: 5,
+            cache_creation_input_tokens: 1,
+            cache_read_input_tokens: 2,
+            cost_usd,
+            duration_ms: None,
+            timings: None,
+            is_error: false,
+        }
+    }
+
+    fn result_event(line: &str) -> ResultEvent {
+        match parse_event_line(line) {
+            Some(ClaudeEvent::Result(result)) => result,
+            _ => panic!("expected result event"),
+        }
+    }
+
+    #[test]
+    fn turn_cost_is_delta_of_cumulative_total() {
+        let result = result_event(
+            r#"{"type":"result","subtype":"success","session_id":"s1","total_cost_usd":0.75,"usage":{"input_tokens":100,"output_tokens":20}}"#,
+        );
+        let usage = turn_usage_from_result("ws", "thread", "turn", None, &result, 0.5);
+        assert!((usage.cost_usd - 0.25).abs() < 1e-9);
+        assert_eq!(usage.session_id, "s1");
+        assert_eq!(usage.input_tokens, 100);
+
+        // A respawned process starts counting from zero again
+        let usage = turn_usage_from_result("ws", "thread", "turn", None, &result, 2.0);
+        assert!((usage.cost_usd - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn workspace_usage_groups_by_workspace_and_session() {
+        let turns = vec![
+            turn("ws-1", "a", 0, 0.1),
+            turn("ws-1", "a", 0, 0.2),
+            turn("ws-1", "b", 0, 0.3),
+            turn("ws-2", "c", 0, 1.0),
+        ];
+        let usage = workspace_usage(&turns, None);
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].workspace_id, "ws-1");
+        assert_eq!(usage[0].totals.turns, 3);
+        assert_eq!(usage[0].totals.input_tokens, 30);
+        assert!((usage[0].totals.cost_usd - 0.6).abs() < 1e-9);
+        assert_eq!(usage[0].sessions.len(), 2);
+        assert_eq!(usage[0].sessions[0].totals.turns, 2);
+
+        let filtered = workspace_usage(&turns, Some("ws-2"));
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].sessions[0].session_id, "c");
+    }
+
+    #[test]
+    fn totals_by_session_only_counts_the_workspace() {
+        let turns = vec![
+            turn("ws-1", "a", 0, 0.1),
+            turn("ws-1", "a", 0, 0.2),
+            turn("ws-1", "b", 0, 0.3),
+            turn("ws-2", "a", 0, 1.0),
+        ];
+        let sessions = totals_by_session(&turns, "ws-1");
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions["a"].turns, 2);
+        assert!((sessions["a"].cost_usd - 0.3).abs() < 1e-9);
+        assert_eq!(sessions["b"].output_tokens, 5);
+    }
+
+    #[test]
+    fn activity_by_workspace_tracks_the_latest_turn() {
+        let turns = vec![
+            turn("ws-1", "a", 300, 0.1),
+            turn("ws-1", "b", 100, 0.2),
+            turn("ws-2", "c", 50, 1.0),
+        ];
+        let activity = activity_by_workspace(&turns);
+        assert_eq!(activity["ws-1"].totals.turns, 2);
+        assert_eq!(activity["ws-1"].last_turn_at, Some(300));
+        assert_eq!(activity["ws-2"].last_turn_at, Some(50));
+    }
+
+    #[test]
+    fn daily_usage_buckets_turns_into_requested_days() {
+        let now = now_ms();
+        let today = day_key_for_timestamp_ms(now).unwrap();
+        let turns = vec![
+            turn("ws-1", "a", now, 0.5),
+            turn("ws-2", "b", now, 0.25),
+            turn("ws-1", "a", 0, 9.0),
+        ];
+        let days = vec!["1999-01-01".to_string(), today.clone()];
+        let daily = daily_usage(&turns, &days, Some("ws-1"));
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[0].totals, UsageTotals::default());
+        assert_eq!(daily[1].day, today);
+        assert_eq!(daily[1].totals.turns, 1);
+        assert!((daily[1].totals.cost_usd - 0.5).abs() < 1e-9);
+    }
+
+    fn timed_turn(workspace_id: &str, wall_ms: u64, ttft_ms: Option<u64>) -> TurnUsage {
+        TurnUsage {
+            timings: Some(TurnTimings {
+                wall_ms,
+                time_to_first_token_ms: ttft_ms,
+                tool_ms: wall_ms / 4,
+                model_ms: wall_ms - wall_ms / 4,
+            }),
+            ..turn(workspace_id, "a", 0, 0.0)
+        }
+    }
+
+    #[test]
+    fn turn_metrics_rank_slowest_workspaces_first() {
+        let turns = vec![
+            timed_turn("fast", 1_000, Some(200)),
+            timed_turn("slow", 8_000, None),
+            timed_turn("slow", 4_000, Some(900)),
+            timed_turn("slow", 12_000, Some(1_100)),
+            turn("slow", "a", 0, 0.0),
+        ];
+        let metrics = turn_metrics(&turns, None, 2);
+        assert_eq!(metrics.turns.len(), 2);
+        assert_eq!(metrics.turns[0].timings.as_ref().unwrap().wall_ms, 12_000);
+
+        let slow = &metrics.workspaces[0];
+        assert_eq!(slow.workspace_id, "slow");
+        assert_eq!(slow.turns, 3);
+        assert_eq!(slow.avg_wall_ms, 8_000);
+        assert_eq!(slow.p50_wall_ms, 8_000);
+        assert_eq!(slow.p90_wall_ms, 12_000);
+        assert_eq!(slow.max_wall_ms, 12_000);
+        assert_eq!(slow.avg_time_to_first_token_ms, Some(1_000));
+        assert_eq!(slow.avg_tool_ms, 2_000);
+        assert_eq!(metrics.workspaces[1].workspace_id, "fast");
+
+        let filtered = turn_metrics(&turns, Some("fast"), 10);
+        assert_eq!(filtered.turns.len(), 1);
+        assert_eq!(filtered.workspaces.len(), 1);
+    }
+
+    #[test]
+    fn recent_turns_returns_newest_first_with_filters() {
+        let turns = vec![
+            turn("ws-1", "a", 1, 0.0),
+            turn("ws-1", "b", 2, 0.0),
+            turn("ws-1", "a", 3, 0.0),
+        ];
+        let recent = recent_turns(&turns, Some("ws-1"), Some("a"), 10);
+        assert_eq!(
+            recent.iter().map(|t| t.timestamp).collect::<Vec<_>>(),
+            vec![3, 1]
+        );
+        assert_eq!(recent_turns(&turns, None, None, 1)[0].timestamp, 3);
+    }
+
+    #[test]
+    fn usage_report_buckets_turns_per_week_and_model() {
+        let at = |date: &str, hour: u32| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+                .and_local_timezone(Local)
+                .unwrap()
+                .timestamp_millis()
+        };
+        let with = |model: Option<&str>, is_error, duration_ms, turn: TurnUsage| TurnUsage {
+            model: model.map(str::to_string),
+            is_error,
+            duration_ms,
+            ..turn
+        };
+        // 2026-03-02 is a Monday
+        let turns = vec![
+            with(
+                Some("opus"),
+                false,
+                Some(1_000),
+                turn("ws-1", "a", at("2026-03-02", 9), 1.0),
+            ),
+            with(
+                Some("opus"),
+                true,
+                Some(3_000),
+                turn("ws-1", "a", at("2026-03-08", 9), 1.0),
+            ),
+            with(
+                Some("haiku"),
+                false,
+                None,
+                turn("ws-2", "b", at("2026-03-10", 9), 0.5),
+            ),
+            with(
+                None,
+                false,
+                None,
+                turn("ws-1", "a", at("2026-03-20", 9), 0.1),
+            ),
+            turn("ws-1", "a", at("2026-02-01", 9), 5.0),
+        ];
+        let report = usage_report(
+            &turns,
+            at("2026-03-04", 0),
+            at("2026-03-20", 12),
+            UsageInterval::Week,
+            Some(UsageGroupBy::Model),
+            None,
+        )
+        .unwrap();
+
+        let starts: Vec<&str> = report.buckets.iter().map(|b| b.start.as_str()).collect();
+        assert_eq!(starts, vec!["2026-03-02", "2026-03-09", "2026-03-16"]);
+        // The turn on Monday the 2nd is before `since`
+        assert_eq!(report.buckets[0].rollup.totals.turns, 1);
+        assert_eq!(report.buckets[0].rollup.success_rate, Some(0.0));
+        assert_eq!(report.buckets[0].rollup.avg_duration_ms, Some(3_000));
+        assert_eq!(report.totals.totals.turns, 3);
+        assert_eq!(report.totals.failed_turns, 1);
+
+        let keys: Vec<Option<&str>> = report.series.iter().map(|s| s.key.as_deref()).collect();
+        assert_eq!(keys, vec![Some("opus"), Some("haiku"), None]);
+        assert_eq!(report.series[1].buckets.len(), 3);
+        assert_eq!(report.series[1].buckets[1].rollup.totals.turns, 1);
+        assert_eq!(report.series[1].totals.success_rate, Some(1.0));
+        assert_eq!(report.series[1].totals.avg_duration_ms, None);
+
+        let by_workspace = usage_report(
+            &turns,
+            at("2026-03-01", 0),
+            at("2026-03-10", 12),
+            UsageInterval::Day,
+            None,
+            Some("ws-2"),
+        )
+        .unwrap();
+        assert_eq!(by_workspace.buckets.len(), 10);
+        assert_eq!(by_workspace.totals.totals.turns, 1);
+        assert!(by_workspace.series.is_empty());
+
+        assert!(usage_report(&turns, 10, 0, UsageInterval::Day, None, None).is_err());
+        assert!(usage_report(&turns, 0, 500 * DAY_MS, UsageInterval::Day, None, None).is_err());
+    }
+
+    #[test]
+    fn usage_export_breaks_spend_down_by_workspace_and_model() {
+        let with_model = |model: Option<&str>, turn: TurnUsage| TurnUsage {
+            model: model.map(str::to_string),
+            ..turn
+        };
+        let turns = vec![
+            with_model(Some("opus"), turn("ws-1", "a", 100, 1.0)),
+            with_model(Some("haiku"), turn("ws-1", "a", 200, 0.25)),
+            with_model(Some("opus"), turn("ws-2", "b", 300, 2.0)),
+            with_model(None, turn("ws-2", "b", 400, 0.5)),
+            with_model(Some("opus"), turn("ws-1", "a", 900, 7.0)),
+        ];
+        let names = HashMap::from([("ws-1".to_string(), "Billing, \"core\"".to_string())]);
+        let export = usage_export(&turns, 0, 500, &names);
+
+        assert_eq!(export.totals.totals.turns, 4);
+        assert!((export.totals.totals.cost_usd - 3.75).abs() < 1e-9);
+        assert_eq!(export.workspaces.len(), 2);
+        assert_eq!(export.workspaces[0].models.len(), 2);
+        assert_eq!(export.workspaces[1].workspace_name, None);
+        let models: Vec<Option<&str>> = export.models.iter().map(|m| m.model.as_deref()).collect();
+        assert_eq!(models, vec![None, Some("haiku"), Some("opus")]);
+        assert_eq!(export.models[2].rollup.totals.turns, 2);
+
+        let csv = UsageExportFormat::Csv.render(&export).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], USAGE_CSV_HEADER);
+        // 2 workspaces with 2 models each, 3 models and the total
+        assert_eq!(lines.len(), 1 + 6 + 3 + 1);
+        assert!(lines[1].starts_with("workspace,"));
+        assert!(lines[1].contains(",ws-1,\"Billing, \"\"core\"\"\",,2,0,20,10,"));
+        assert!(lines[2].starts_with("workspace_model,"));
+        assert!(lines[10].starts_with("total,"));
+        assert!(lines[10].contains(",3.750000,1.0000,"));
+
+        let json = UsageExportFormat::Json.render(&export).unwrap();
+        assert!(json.contains("\"workspaceName\": \"Billing, \\\"core\\\"\""));
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_and_defused() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("=SUM(A1)"), "'=SUM(A1)");
+        assert_eq!(csv_field("-x,\"y\""), "\"'-x,\"\"y\"\"\"");
+    }
+
+    #[test]
+    fn sums_cost_since_a_timestamp() {
+        let turns = vec![
+            turn("ws-1", "s1", 1_000, 0.5),
+            turn("ws-1", "s1", 2_000, 0.25),
+            turn("ws-2", "s2", 3_000, 1.0),
+        ];
+        assert!((cost_since(&turns, Some("ws-1"), 1_500) - 0.25).abs() < 1e-9);
+        assert!((cost_since(&turns, None, 0) - 1.75).abs() < 1e-9);
+    }
+}
@@ -1,7 +1,25 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 pub(crate) fn normalize_git_path(path: &str) -> String {
     path.replace('\\', "/")
 }
 
+/// Milliseconds since the Unix epoch, the unit every stored timestamp uses.
+pub(crate) fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// Nanoseconds since the Unix epoch, the unit OTLP timestamps use.
+pub(crate) fn now_unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
 #[cfg(test)]
 mod tests {
     use super::normalize_git_path;
@@ -0,0 +1,311 @@
+//! Verification commands run after a turn.
+//!
+//! A workspace can list commands such as `cargo test` or `npm test` that run, one after
+//! another in the workspace directory, once a turn ends successfully. The first failing
+//! command stops the run. Each step is reported as `turn/verification`. When the
+//! workspace opts in, the failing output goes back to the thread as a follow-up message,
+//! up to `maxFixAttempts` in a row; the follow-up names its attempt so the next run can
+//! tell a fix turn from one the user started.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tokio::process::Command;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+use crate::backend::claude_cli::build_claude_path_env;
+use crate::backend::process::{isolate_process_group, kill_process_group, track_process_group};
+use crate::backend::turn_queue::QueuedPrompt;
+use crate::claude::{self, WorkspaceSession};
+use crate::event_sink::TauriEventSink;
+use crate::types::VerificationPolicy;
+use crate::utils::now_ms;
+
+const DEFAULT_MAX_FIX_ATTEMPTS: u32 = 3;
+const DEFAULT_TIMEOUT_SECONDS: u64 = 600;
+/// Output kept from a failing command, from its end.
+const OUTPUT_MAX_CHARS: usize = 8000;
+const FIX_PROMPT_PREFIX: &str = "Verification failed after your last turn (fix attempt ";
+
+/// How one verification command ended.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CommandOutcome {
+    pub(crate) command: String,
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) timed_out: bool,
+    /// Combined stdout and stderr, cut to its last [`OUTPUT_MAX_CHARS`].
+    pub(crate) output: String,
+    pub(crate) duration_ms: u64,
+}
+
+impl CommandOutcome {
+    fn passed(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+}
+
+/// The commands to run, or `None` when the workspace has none.
+pub(crate) fn commands(policy: Option<&VerificationPolicy>) -> Option<Vec<String>> {
+    let commands: Vec<String> = policy?
+        .commands
+        .iter()
+        .map(|command| command.trim())
+        .filter(|command| !command.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!commands.is_empty()).then_some(commands)
+}
+
+/// Which fix attempt `prompt` was, if it is a follow-up this module sent.
+pub(crate) fn fix_attempt(prompt: &str) -> Option<u32> {
+    let rest = prompt.strip_prefix(FIX_PROMPT_PREFIX)?;
+    let (attempt, _) = rest.split_once(" of ")?;
+    attempt.parse().ok()
+}
+
+/// The follow-up message asking the thread to fix a failing command.
+pub(crate) fn fix_prompt(outcome: &CommandOutcome, attempt: u32, max_attempts: u32) -> String {
+    let ending = match (outcome.timed_out, outcome.exit_code) {
+        (true, _) => "timed out".to_string(),
+        (false, Some(code)) => format!("exited with code {code}"),
+        (false, None) => "was killed by a signal".to_string(),
+    };
+    format!(
+        "{FIX_PROMPT_PREFIX}{attempt} of {max_attempts}).\n\n`{}` {ending}:\n\n\
+         ```\n{}\n```\n\nFix the cause of this failure.",
+        outcome.command,
+        outcome.output.trim_end(),
+    )
+}
+
+/// The last `max_chars` characters of `text`, marked as cut when anything was dropped.
+fn tail(text: &str, max_chars: usize) -> String {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().skip(count - max_chars).collect();
+    format!(
+        "[… {} earlier characters omitted]\n{kept}",
+        count - max_chars
+    )
+}
+
+/// Run one command through the platform shell in `cwd`.
+async fn run_command(
+    command: &str,
+    cwd: &Path,
+    path_env: Option<&str>,
+    limit: Duration,
+) -> CommandOutcome {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(path) = path_env {
+        shell.env("PATH", path);
+    }
+    isolate_process_group(&mut shell);
+    let started = Instant::now();
+    let outcome = |exit_code, timed_out, output: String| CommandOutcome {
+        command: command.to_string(),
+        exit_code,
+        timed_out,
+        output: tail(&output, OUTPUT_MAX_CHARS),
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    let child = match shell.spawn() {
+        Ok(child) => child,
+        Err(err) => return outcome(None, false, format!("failed to run command: {err}")),
+    };
+    track_process_group(&child);
+    let pid = child.id();
+    match timeout(limit, child.wait_with_output()).await {
+        Ok(Ok(output)) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+            // Nothing the command left running in the background outlives it
+            if let Some(pid) = pid {
+                kill_process_group(pid);
+            }
+            outcome(output.status.code(), false, text)
+        }
+        Ok(Err(err)) => outcome(None, false, err.to_string()),
+        Err(_) => {
+            if let Some(pid) = pid {
+                kill_process_group(pid);
+            }
+            outcome(None, true, String::new())
+        }
+    }
+}
+
+/// Run the workspace's verification commands after a turn, if it has any, and send the
+/// first failure back to the thread when the workspace asked for that.
+pub(crate) fn spawn_verification(
+    event_sink: &TauriEventSink,
+    session: &Arc<WorkspaceSession>,
+    thread_id: &str,
+    turn_id: &str,
+) {
+    let policy = session.entry.settings.verification.clone();
+    let Some(commands) = commands(policy.as_ref()) else {
+        return;
+    };
+    let policy = policy.unwrap_or_default();
+    let event_sink = event_sink.clone();
+    let session = Arc::clone(session);
+    let thread_id = thread_id.to_string();
+    let turn_id = turn_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let workspace_id = session.entry.id.clone();
+        let cwd = PathBuf::from(&session.entry.path);
+        let path_env = build_claude_path_env(session.claude_bin.as_deref());
+        let limit = Duration::from_secs(
+            policy
+                .timeout_seconds
+                .filter(|seconds| *seconds > 0)
+                .unwrap_or(DEFAULT_TIMEOUT_SECONDS),
+        );
+        let report = |status: &str, outcome: Option<&CommandOutcome>, extra: Value| {
+            let mut params = json!({
+                "threadId": thread_id,
+                "turnId": turn_id,
+                "status": status,
+            });
+            if let Some(outcome) = outcome {
+                params["command"] = json!(outcome.command);
+                params["exitCode"] = json!(outcome.exit_code);
+                params["timedOut"] = json!(outcome.timed_out);
+                params["durationMs"] = json!(outcome.duration_ms);
+                params["output"] = json!(outcome.output);
+            }
+            if let (Some(params), Some(extra)) = (params.as_object_mut(), extra.as_object()) {
+                params.extend(extra.clone());
+            }
+            claude::emit_event(&event_sink, &workspace_id, "turn/verification", params);
+        };
+
+        report("running", None, json!({ "commands": commands }));
+        let mut failure = None;
+        for command in &commands {
+            let outcome = run_command(command, &cwd, path_env.as_deref(), limit).await;
+            if !outcome.passed() {
+                failure = Some(outcome);
+                break;
+            }
+        }
+        let Some(failure) = failure else {
+            report("passed", None, json!({}));
+            return;
+        };
+
+        let max_attempts = policy.max_fix_attempts.unwrap_or(DEFAULT_MAX_FIX_ATTEMPTS);
+        let attempt = session
+            .last_prompt(&thread_id)
+            .await
+            .as_deref()
+            .and_then(fix_attempt)
+            .unwrap_or(0)
+            + 1;
+        let follow_up = policy.feed_back_failures && attempt <= max_attempts;
+        report(
+            "failed",
+            Some(&failure),
+            json!({
+                "followUp": follow_up,
+                "attempt": follow_up.then_some(attempt),
+                "maxAttempts": max_attempts,
+            }),
+        );
+        if !follow_up {
+            return;
+        }
+        let prompt = QueuedPrompt {
+            id: Uuid::new_v4().to_string(),
+            thread_id: thread_id.clone(),
+            prompt: fix_prompt(&failure, attempt, max_attempts),
+            images: Vec::new(),
+            model: None,
+            access_mode: None,
+            queued_at: now_ms(),
+        };
+        claude::enqueue_prompts(&workspace_id, &session, vec![prompt], event_sink.clone()).await;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(exit_code: Option<i32>, timed_out: bool) -> CommandOutcome {
+        CommandOutcome {
+            command: "cargo test".to_string(),
+            exit_code,
+            timed_out,
+            output: "test parser::empty ... FAILED\n".to_string(),
+            duration_ms: 1200,
+        }
+    }
+
+    #[test]
+    fn skips_blank_commands() {
+        let policy = VerificationPolicy {
+            commands: vec!["  ".to_string(), " cargo test ".to_string()],
+            ..VerificationPolicy::default()
+        };
+        assert_eq!(
+            commands(Some(&policy)),
+            Some(vec!["cargo test".to_string()])
+        );
+        assert_eq!(commands(Some(&VerificationPolicy::default())), None);
+        assert_eq!(commands(None), None);
+    }
+
+    #[test]
+    fn fix_prompt_round_trips_its_attempt() {
+        let prompt = fix_prompt(&failure(Some(101), false), 2, 3);
+        assert!(prompt.contains("`cargo test` exited with code 101"));
+        assert!(prompt.contains("test parser::empty ... FAILED"));
+        assert_eq!(fix_attempt(&prompt), Some(2));
+        assert_eq!(fix_attempt("Please run the tests"), None);
+        assert!(fix_prompt(&failure(None, true), 1, 3).contains("`cargo test` timed out"));
+    }
+
+    #[test]
+    fn keeps_the_end_of_long_output() {
+        let output = tail(&"ab".repeat(10), 4);
+        assert_eq!(output, "[… 16 earlier characters omitted]\nabab");
+        assert_eq!(tail("short", 10), "short");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn reports_failing_commands() {
+        let dir = std::env::temp_dir();
+        let limit = Duration::from_secs(10);
+        let passed = run_command("echo ok", &dir, None, limit).await;
+        assert!(passed.passed());
+        assert_eq!(passed.output.trim(), "ok");
+        let failed = run_command("echo broken >&2; exit 3", &dir, None, limit).await;
+        assert!(!failed.passed());
+        assert_eq!(failed.exit_code, Some(3));
+        assert_eq!(failed.output.trim(), "broken");
+    }
+}
@@ -0,0 +1,207 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+use crate::daemon;
+use crate::redaction;
+use crate::state::AppState;
+use crate::types::WebhookConfig;
+use crate::utils::now_ms;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Waits before the second, third and fourth attempt.
+const RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+    Duration::from_secs(30),
+];
+
+/// Turn lifecycle moments a webhook can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WebhookEvent {
+    TurnStarted,
+    TurnCompleted,
+    TurnFailed,
+    PermissionPending,
+    PlanPending,
+}
+
+impl WebhookEvent {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::TurnStarted => "turn.started",
+            Self::TurnCompleted => "turn.completed",
+            Self::TurnFailed => "turn.failed",
+            Self::PermissionPending => "permission.pending",
+            Self::PlanPending => "plan.pending",
+        }
+    }
+}
+
+fn client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// Whether `webhook` wants `event`. An empty filter means every event.
+pub(crate) fn subscribed(webhook: &WebhookConfig, event: WebhookEvent) -> bool {
+    webhook.enabled
+        && !webhook.url.trim().is_empty()
+        && (webhook.events.is_empty()
+            || webhook
+                .events
+                .iter()
+                .any(|name| name == event.name() || name == "*"))
+}
+
+/// `sha256=<hex>` HMAC of the exact request body, sent as `X-Monitor-Signature`.
+pub(crate) fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("sha256={hex}")
+}
+
+/// POST `event` to every subscribed webhook in the background. `data` carries the
+/// event's details, e.g. `threadId` and `turnId`.
+pub(crate) fn dispatch(app: &AppHandle, workspace_id: &str, event: WebhookEvent, data: Value) {
+    let mut data = data;
+    redaction::redact_value(&mut data);
+    daemon::slack::post_event(app, workspace_id, event, &data);
+    let app = app.clone();
+    let workspace_id = workspace_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<AppState>() else {
+            return;
+        };
+        let webhooks: Vec<WebhookConfig> = {
+            let settings = state.app_settings.lock().await;
+            settings
+                .webhooks
+                .iter()
+                .filter(|webhook| subscribed(webhook, event))
+                .cloned()
+                .collect()
+        };
+        if webhooks.is_empty() {
+            return;
+        }
+        let workspace_name = state
+            .workspaces
+            .lock()
+            .await
+            .get(&workspace_id)
+            .map(|entry| entry.name.clone());
+        let body = json!({
+            "event": event.name(),
+            "workspaceId": workspace_id,
+            "workspaceName": workspace_name,
+            "timestamp": now_ms(),
+            "data": data,
+        })
+        .to_string();
+        for webhook in webhooks {
+            tauri::async_runtime::spawn(deliver(webhook, event, body.clone()));
+        }
+    });
+}
+
+/// Send one payload, retrying connection errors, `429` and `5xx` responses.
+async fn deliver(webhook: WebhookConfig, event: WebhookEvent, body: String) {
+    let delivery_id = Uuid::new_v4().to_string();
+    let signature = webhook
+        .secret
+        .as_deref()
+        .filter(|secret| !secret.is_empty())
+        .map(|secret| signature(secret, body.as_bytes()));
+    let mut attempt = 0;
+    loop {
+        let mut request = client()
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", "ClaudeCodeMonitor-Webhook")
+            .header("X-Monitor-Event", event.name())
+            .header("X-Monitor-Delivery", &delivery_id)
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header("X-Monitor-Signature", signature);
+        }
+        let error = match request.send().await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => {
+                let status = response.status();
+                if !(status.is_server_error() || status.as_u16() == 429) {
+                    tracing::warn!("{} rejected {}: {status}", webhook.url, event.name());
+                    return;
+                }
+                status.to_string()
+            }
+            Err(err) => err.to_string(),
+        };
+        let Some(delay) = RETRY_DELAYS.get(attempt) else {
+            tracing::warn!(
+                "giving up on {} for {} after {} attempts: {error}",
+                webhook.url,
+                event.name(),
+                attempt + 1
+            );
+            return;
+        };
+        attempt += 1;
+        tokio::time::sleep(*delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn webhook(events: &[&str]) -> WebhookConfig {
+        WebhookConfig {
+            url: "https://hooks.example.com/monitor".to_string(),
+            secret: None,
+            events: events.iter().map(|event| event.to_string()).collect(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn filters_events() {
+        assert!(subscribed(&webhook(&[]), WebhookEvent::TurnFailed));
+        assert!(subscribed(
+            &webhook(&["turn.failed", "permission.pending"]),
+            WebhookEvent::PermissionPending
+        ));
+        assert!(!subscribed(
+            &webhook(&["turn.failed"]),
+            WebhookEvent::TurnStarted
+        ));
+        let disabled = WebhookConfig {
+            enabled: false,
+            ..webhook(&[])
+        };
+        assert!(!subscribed(&disabled, WebhookEvent::TurnStarted));
+    }
+
+    #[test]
+    fn signs_bodies_with_hmac_sha256() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -21,13 +21,11 @@ pub(crate) fn set_window_appearance_override(handler: Option<WindowAppearanceOve
 #[cfg(target_os = "macos")]
 fn apply_macos_window_appearance(window: &Window, theme: &str) -> Result<(), String> {
     use objc2_app_kit::{
-        NSAppearance, NSAppearanceCustomization, NSAppearanceNameAqua,
-        NSAppearanceNameDarkAqua, NSWindow,
+        NSAppearance, NSAppearanceCustomization, NSAppearanceNameAqua, NSAppearanceNameDarkAqua,
+        NSWindow,
     };
 
-    let ns_window = window
-        .ns_window()
-        .map_err(|error| error.to_string())?;
+    let ns_window = window.ns_window().map_err(|error| error.to_string())?;
     let ns_window: &NSWindow = unsafe { &*ns_window.cast() };
 
     if theme == "system" {
@@ -0,0 +1,233 @@
+use serde_json::json;
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+use uuid::Uuid;
+
+use crate::claude_home::resolve_home_dir;
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::{DiscoveredWorkspace, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+
+const DEFAULT_MAX_DEPTH: u32 = 3;
+const MAX_DEPTH_LIMIT: u32 = 6;
+const MAX_CANDIDATES: usize = 500;
+
+/// Directories that never contain projects worth proposing and are expensive to walk.
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "vendor",
+    "Library",
+    "Applications",
+];
+
+#[tauri::command]
+pub(crate) async fn discover_workspaces(
+    roots: Option<Vec<String>>,
+    max_depth: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<DiscoveredWorkspace>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "discover_workspaces",
+            json!({ "roots": roots, "maxDepth": max_depth }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let roots = match roots {
+        Some(roots) => roots,
+        None => state
+            .app_settings
+            .lock()
+            .await
+            .workspace_discovery_roots
+            .clone(),
+    };
+    if roots.iter().all(|root| root.trim().is_empty()) {
+        return Err("No workspace discovery folders configured.".to_string());
+    }
+    let known: HashSet<PathBuf> = state
+        .workspaces
+        .lock()
+        .await
+        .values()
+        .map(|entry| normalize_path(Path::new(&entry.path)))
+        .collect();
+    let max_depth = max_depth.unwrap_or(DEFAULT_MAX_DEPTH).min(MAX_DEPTH_LIMIT);
+
+    tokio::task::spawn_blocking(move || {
+        let roots: Vec<PathBuf> = roots.iter().filter_map(|root| expand_root(root)).collect();
+        scan_roots(&roots, max_depth, &known)
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Walk each root breadth-first up to `max_depth` levels and collect folders that are
+/// git repositories or Claude projects. Matches are not descended into, hidden folders
+/// and common build outputs are skipped, and symlinks are never followed.
+fn scan_roots(
+    roots: &[PathBuf],
+    max_depth: u32,
+    known: &HashSet<PathBuf>,
+) -> Vec<DiscoveredWorkspace> {
+    let claude_home = resolve_home_dir().map(|home| normalize_path(&home));
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    let mut candidates = Vec::new();
+    for root in roots {
+        let mut queue = VecDeque::from([(root.clone(), 0u32)]);
+        while let Some((dir, depth)) = queue.pop_front() {
+            if candidates.len() >= MAX_CANDIDATES {
+                return candidates;
+            }
+            let normalized = normalize_path(&dir);
+            if !seen.insert(normalized.clone()) {
+                continue;
+            }
+            let git_repo = dir.join(".git").exists();
+            // `~/.claude` is the CLI's global config, not a project marker for $HOME
+            let claude_project =
+                dir.join(".claude").is_dir() && claude_home.as_ref() != Some(&normalized);
+            if git_repo || claude_project {
+                if !known.contains(&normalized) {
+                    candidates.push(DiscoveredWorkspace {
+                        entry: candidate_entry(&dir),
+                        git_repo,
+                        claude_project,
+                    });
+                }
+                continue;
+            }
+            if depth >= max_depth {
+                continue;
+            }
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut children: Vec<PathBuf> = entries
+                .flatten()
+                .filter(|entry| entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false))
+                .filter(|entry| {
+                    let name = entry.file_name();
+                    let name = name.to_string_lossy();
+                    !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref())
+                })
+                .map(|entry| entry.path())
+                .collect();
+            children.sort();
+            queue.extend(children.into_iter().map(|child| (child, depth + 1)));
+        }
+    }
+    candidates
+}
+
+fn candidate_entry(path: &Path) -> WorkspaceEntry {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("Workspace")
+        .to_string();
+    WorkspaceEntry {
+        id: Uuid::new_v4().to_string(),
+        name,
+        path: path.to_string_lossy().to_string(),
+        claude_bin: None,
+        model: None,
+        kind: WorkspaceKind::Main,
+        parent_id: None,
+        worktree: None,
+        archived_at: None,
+        pinned: false,
+        settings: WorkspaceSettings::default(),
+    }
+}
+
+fn expand_root(root: &str) -> Option<PathBuf> {
+    let trimmed = root.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    if trimmed == "~" {
+        return resolve_home_dir();
+    }
+    if let Some(rest) = trimmed
+        .strip_prefix("~/")
+        .or_else(|| trimmed.strip_prefix("~\\"))
+    {
+        return resolve_home_dir().map(|home| home.join(rest));
+    }
+    Some(PathBuf::from(trimmed))
+}
+
+fn normalize_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("workspace-discovery-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("create temp root");
+        root
+    }
+
+    fn names(candidates: &[DiscoveredWorkspace]) -> Vec<String> {
+        candidates.iter().map(|c| c.entry.name.clone()).collect()
+    }
+
+    #[test]
+    fn finds_git_repos_and_claude_projects() {
+        let root = temp_root();
+        fs::create_dir_all(root.join("repo/.git")).unwrap();
+        fs::create_dir_all(root.join("repo/nested/.git")).unwrap();
+        fs::create_dir_all(root.join("notes/.claude")).unwrap();
+        fs::create_dir_all(root.join("group/deep/.git")).unwrap();
+        fs::create_dir_all(root.join("plain/src")).unwrap();
+        fs::create_dir_all(root.join("node_modules/pkg/.git")).unwrap();
+        fs::create_dir_all(root.join(".hidden/.git")).unwrap();
+
+        let candidates = scan_roots(&[root.clone()], 3, &HashSet::new());
+        assert_eq!(names(&candidates), vec!["notes", "repo", "deep"]);
+        let repo = candidates.iter().find(|c| c.entry.name == "repo").unwrap();
+        assert!(repo.git_repo);
+        assert!(!repo.claude_project);
+        let notes = candidates.iter().find(|c| c.entry.name == "notes").unwrap();
+        assert!(notes.claude_project);
+        assert!(matches!(notes.entry.kind, WorkspaceKind::Main));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn respects_depth_and_known_workspaces() {
+        let root = temp_root();
+        fs::create_dir_all(root.join("a/b/c/.git")).unwrap();
+        fs::create_dir_all(root.join("known/.git")).unwrap();
+
+        let known = HashSet::from([normalize_path(&root.join("known"))]);
+        assert!(scan_roots(&[root.clone()], 2, &known).is_empty());
+        assert_eq!(names(&scan_roots(&[root.clone()], 3, &known)), vec!["c"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn expand_root_handles_home_and_blank_values() {
+        assert!(expand_root("   ").is_none());
+        assert_eq!(expand_root("/tmp/code"), Some(PathBuf::from("/tmp/code")));
+        if let Some(home) = resolve_home_dir() {
+            assert_eq!(expand_root("~/code"), Some(home.join("code")));
+        }
+    }
+}
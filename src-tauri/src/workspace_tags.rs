@@ -0,0 +1,357 @@
+//! Workspace tags and the aggregate views behind `aggregate_workspaces`.
+//!
+//! A workspace belongs to at most one group (`settings.groupId`, one of the app's
+//! `workspaceGroups`) but can carry any number of tags, e.g. `client-a` or
+//! `experiments`. Aggregates roll up status, spend and activity per tag or per group so
+//! a long workspace list can be read at a glance. A workspace with several tags counts
+//! towards each of them.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::remote_backend;
+use crate::state::AppState;
+use crate::types::{UsageTotals, WorkspaceEntry, WorkspaceGroup};
+use crate::usage::{self, WorkspaceActivity};
+
+const MAX_TAG_CHARS: usize = 40;
+const MAX_TAGS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum AggregateBy {
+    Tag,
+    Group,
+}
+
+/// Status, spend and activity of the workspaces sharing a tag or a group.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceAggregate {
+    /// The tag or group id; `None` collects untagged or ungrouped workspaces.
+    pub(crate) key: Option<String>,
+    pub(crate) label: String,
+    pub(crate) workspace_ids: Vec<String>,
+    /// Workspaces with a running session.
+    pub(crate) connected: usize,
+    pub(crate) running_turns: usize,
+    pub(crate) queued_prompts: usize,
+    pub(crate) totals: UsageTotals,
+    /// When the most recent turn in any of the workspaces finished.
+    pub(crate) last_activity_at: Option<i64>,
+}
+
+/// What a connected workspace is doing right now.
+#[derive(Debug, Clone, Copy)]
+struct WorkspaceStatus {
+    running_turns: usize,
+    queued_prompts: usize,
+}
+
+/// Trim tags, drop empty ones and duplicates that differ only in case, keeping the first
+/// spelling.
+pub(crate) fn normalize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_CHARS {
+            return Err(format!(
+                "Tag \"{tag}\" is longer than {MAX_TAG_CHARS} characters."
+            ));
+        }
+        if tag.contains(',') {
+            return Err(format!("Tag \"{tag}\" must not contain commas."));
+        }
+        if !normalized
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(tag))
+        {
+            normalized.push(tag.to_string());
+        }
+    }
+    if normalized.len() > MAX_TAGS {
+        return Err(format!("A workspace can have at most {MAX_TAGS} tags."));
+    }
+    Ok(normalized)
+}
+
+/// Roll up workspaces per tag or group. With `key`, only that tag (matched ignoring case)
+/// or group is returned.
+#[tauri::command]
+pub(crate) async fn aggregate_workspaces(
+    by: AggregateBy,
+    key: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<WorkspaceAggregate>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "aggregate_workspaces",
+            json!({ "by": by, "key": key }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    // Archived workspaces are out of active views, aggregates included
+    let mut entries: Vec<WorkspaceEntry> = state
+        .workspaces
+        .lock()
+        .await
+        .values()
+        .filter(|entry| entry.archived_at.is_none())
+        .cloned()
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    let groups = state.app_settings.lock().await.workspace_groups.clone();
+    let sessions: Vec<_> = state
+        .sessions
+        .lock()
+        .await
+        .iter()
+        .map(|(id, session)| (id.clone(), session.clone()))
+        .collect();
+    let mut status = HashMap::new();
+    for (id, session) in sessions {
+        let queue = session.turn_queue.lock().await;
+        status.insert(
+            id,
+            WorkspaceStatus {
+                running_turns: queue.busy_thread_ids().len(),
+                queued_prompts: queue.len(),
+            },
+        );
+    }
+    let activity = usage::workspace_activity();
+    Ok(aggregate(
+        &entries,
+        &groups,
+        &status,
+        &activity,
+        by,
+        key.as_deref(),
+    ))
+}
+
+fn aggregate(
+    entries: &[WorkspaceEntry],
+    groups: &[WorkspaceGroup],
+    status: &HashMap<String, WorkspaceStatus>,
+    activity: &HashMap<String, WorkspaceActivity>,
+    by: AggregateBy,
+    key: Option<&str>,
+) -> Vec<WorkspaceAggregate> {
+    let mut buckets: BTreeMap<Option<String>, WorkspaceAggregate> = BTreeMap::new();
+    for entry in entries {
+        for (bucket_key, label) in bucket_keys(entry, groups, by) {
+            if let Some(key) = key {
+                if !bucket_key
+                    .as_deref()
+                    .is_some_and(|k| k.eq_ignore_ascii_case(key))
+                {
+                    continue;
+                }
+            }
+            let bucket = buckets
+                .entry(bucket_key.clone())
+                .or_insert_with(|| WorkspaceAggregate {
+                    key: bucket_key,
+                    label,
+                    workspace_ids: Vec::new(),
+                    connected: 0,
+                    running_turns: 0,
+                    queued_prompts: 0,
+                    totals: UsageTotals::default(),
+                    last_activity_at: None,
+                });
+            add_workspace(bucket, entry, status, activity);
+        }
+    }
+    let (mut keyed, unkeyed): (Vec<_>, Vec<_>) = buckets
+        .into_values()
+        .partition(|bucket| bucket.key.is_some());
+    keyed.sort_by_key(|bucket| bucket.label.to_lowercase());
+    keyed.extend(unkeyed);
+    keyed
+}
+
+/// The buckets a workspace falls into. Tags are bucketed case-insensitively under the
+/// first spelling seen; groups that no longer exist count as ungrouped.
+fn bucket_keys(
+    entry: &WorkspaceEntry,
+    groups: &[WorkspaceGroup],
+    by: AggregateBy,
+) -> Vec<(Option<String>, String)> {
+    match by {
+        AggregateBy::Tag if entry.settings.tags.is_empty() => {
+            vec![(None, "Untagged".to_string())]
+        }
+        AggregateBy::Tag => entry
+            .settings
+            .tags
+            .iter()
+            .map(|tag| (Some(tag.to_lowercase()), tag.clone()))
+            .collect(),
+        AggregateBy::Group => {
+            let group = entry
+                .settings
+                .group_id
+                .as_deref()
+                .and_then(|id| groups.iter().find(|group| group.id == id));
+            match group {
+                Some(group) => vec![(Some(group.id.clone()), group.name.clone())],
+                None => vec![(None, "Ungrouped".to_string())],
+            }
+        }
+    }
+}
+
+fn add_workspace(
+    bucket: &mut WorkspaceAggregate,
+    entry: &WorkspaceEntry,
+    status: &HashMap<String, WorkspaceStatus>,
+    activity: &HashMap<String, WorkspaceActivity>,
+) {
+    bucket.workspace_ids.push(entry.id.clone());
+    if let Some(status) = status.get(&entry.id) {
+        bucket.connected += 1;
+        bucket.running_turns += status.running_turns;
+        bucket.queued_prompts += status.queued_prompts;
+    }
+    if let Some(activity) = activity.get(&entry.id) {
+        let totals = &mut bucket.totals;
+        totals.turns += activity.totals.turns;
+        totals.input_tokens += activity.totals.input_tokens;
+        totals.output_tokens += activity.totals.output_tokens;
+        totals.cache_creation_input_tokens += activity.totals.cache_creation_input_tokens;
+        totals.cache_read_input_tokens += activity.totals.cache_read_input_tokens;
+        totals.cost_usd += activity.totals.cost_usd;
+        bucket.last_activity_at = bucket.last_activity_at.max(activity.last_turn_at);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{WorkspaceKind, WorkspaceSettings};
+
+    fn workspace(id: &str, tags: &[&str], group_id: Option<&str>) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: format!("/tmp/{id}"),
+            claude_bin: None,
+            model: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            archived_at: None,
+            pinned: false,
+            settings: WorkspaceSettings {
+                tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                group_id: group_id.map(str::to_string),
+                ..WorkspaceSettings::default()
+            },
+        }
+    }
+
+    fn activity(cost_usd: f64, last_turn_at: i64) -> WorkspaceActivity {
+        WorkspaceActivity {
+            totals: UsageTotals {
+                turns: 1,
+                cost_usd,
+                ..UsageTotals::default()
+            },
+            last_turn_at: Some(last_turn_at),
+        }
+    }
+
+    #[test]
+    fn normalize_tags_trims_and_dedupes_ignoring_case() {
+        let tags = vec![
+            " client-A ".to_string(),
+            "".to_string(),
+            "Client-a".to_string(),
+            "experiments".to_string(),
+        ];
+        assert_eq!(
+            normalize_tags(&tags).unwrap(),
+            vec!["client-A".to_string(), "experiments".to_string()]
+        );
+        assert!(normalize_tags(&["a,b".to_string()]).is_err());
+        assert!(normalize_tags(&["x".repeat(MAX_TAG_CHARS + 1)]).is_err());
+    }
+
+    #[test]
+    fn aggregates_by_tag_and_group() {
+        let entries = vec![
+            workspace("ws-1", &["client-a", "experiments"], Some("g-1")),
+            workspace("ws-2", &["Client-A"], Some("missing")),
+            workspace("ws-3", &[], None),
+        ];
+        let groups = vec![WorkspaceGroup {
+            id: "g-1".to_string(),
+            name: "Clients".to_string(),
+            sort_order: None,
+            copies_folder: None,
+        }];
+        let status = HashMap::from([(
+            "ws-2".to_string(),
+            WorkspaceStatus {
+                running_turns: 2,
+                queued_prompts: 1,
+            },
+        )]);
+        let activity = HashMap::from([
+            ("ws-1".to_string(), activity(0.5, 100)),
+            ("ws-2".to_string(), activity(0.25, 200)),
+        ]);
+
+        let by_tag = aggregate(
+            &entries,
+            &groups,
+            &status,
+            &activity,
+            AggregateBy::Tag,
+            None,
+        );
+        let labels: Vec<_> = by_tag.iter().map(|bucket| bucket.label.as_str()).collect();
+        assert_eq!(labels, vec!["client-a", "experiments", "Untagged"]);
+        assert_eq!(by_tag[0].workspace_ids, vec!["ws-1", "ws-2"]);
+        assert_eq!(by_tag[0].connected, 1);
+        assert_eq!(by_tag[0].running_turns, 2);
+        assert!((by_tag[0].totals.cost_usd - 0.75).abs() < 1e-9);
+        assert_eq!(by_tag[0].last_activity_at, Some(200));
+
+        let filtered = aggregate(
+            &entries,
+            &groups,
+            &status,
+            &activity,
+            AggregateBy::Tag,
+            Some("EXPERIMENTS"),
+        );
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].workspace_ids, vec!["ws-1"]);
+
+        let by_group = aggregate(
+            &entries,
+            &groups,
+            &status,
+            &activity,
+            AggregateBy::Group,
+            None,
+        );
+        assert_eq!(by_group[0].label, "Clients");
+        assert_eq!(by_group[1].key, None);
+        assert_eq!(by_group[1].workspace_ids, vec!["ws-2", "ws-3"]);
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
 use ignore::WalkBuilder;
@@ -12,19 +12,25 @@ use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 use uuid::Uuid;
 
+use crate::backend::cli_flags::validate_cli_flags;
+use crate::backend::compaction::validate_auto_compact;
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::provider::validate_provider_settings;
 use crate::claude::{
     ensure_workspace_thread_watcher, spawn_workspace_session, stop_workspace_thread_watcher,
 };
-use crate::backend::events::{AppServerEvent, EventSink};
+use crate::credentials;
 use crate::event_sink::TauriEventSink;
+use crate::file_index;
+use crate::git::worktree;
+use crate::git_utils::resolve_git_root;
+use crate::proxy::validate_proxy_settings;
 use crate::remote_backend;
 use crate::state::AppState;
-use crate::git_utils::resolve_git_root;
 use crate::storage::write_workspaces;
-use crate::types::{
-    WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo,
-};
+use crate::types::{WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo};
 use crate::utils::normalize_git_path;
+use crate::workspace_tags::normalize_tags;
 
 fn should_skip_dir(name: &str) -> bool {
     matches!(
@@ -139,8 +145,7 @@ fn read_workspace_file_inner(
         return Err("Path is not a file".to_string());
     }
 
-    let file =
-        File::open(&canonical_path).map_err(|err| format!("Failed to open file: {err}"))?;
+    let file = File::open(&canonical_path).map_err(|err| format!("Failed to open file: {err}"))?;
     let mut buffer = Vec::new();
     file.take(MAX_WORKSPACE_FILE_BYTES + 1)
         .read_to_end(&mut buffer)
@@ -151,8 +156,7 @@ fn read_workspace_file_inner(
         buffer.truncate(MAX_WORKSPACE_FILE_BYTES as usize);
     }
 
-    let content =
-        String::from_utf8(buffer).map_err(|_| "File is not valid UTF-8".to_string())?;
+    let content = String::from_utf8(buffer).map_err(|_| "File is not valid UTF-8".to_string())?;
     Ok(WorkspaceFileResponse { content, truncated })
 }
 
@@ -175,9 +179,7 @@ pub(crate) async fn read_workspace_file(
     }
 
     let workspaces = state.workspaces.lock().await;
-    let entry = workspaces
-        .get(&workspace_id)
-        .ok_or("workspace not found")?;
+    let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
     let root = PathBuf::from(&entry.path);
     read_workspace_file_inner(&root, &path)
 }
@@ -196,10 +198,23 @@ fn sort_workspaces(list: &mut Vec<WorkspaceInfo>) {
 fn apply_workspace_settings_update(
     workspaces: &mut HashMap<String, WorkspaceEntry>,
     id: &str,
-    settings: WorkspaceSettings,
+    mut settings: WorkspaceSettings,
 ) -> Result<WorkspaceEntry, String> {
     match workspaces.get_mut(id) {
         Some(entry) => {
+            if let Some(flags) = &settings.cli_flags {
+                validate_cli_flags(flags, &entry.path)?;
+            }
+            if let Some(provider) = &settings.provider {
+                validate_provider_settings(provider)?;
+            }
+            if let Some(proxy) = &settings.proxy {
+                validate_proxy_settings(proxy)?;
+            }
+            if let Some(policy) = &settings.auto_compact {
+                validate_auto_compact(policy)?;
+            }
+            settings.tags = normalize_tags(&settings.tags)?;
             entry.settings = settings.clone();
             Ok(entry.clone())
         }
@@ -232,10 +247,6 @@ async fn run_git_command(repo_path: &PathBuf, args: &[&str]) -> Result<String, S
     }
 }
 
-fn is_missing_worktree_error(error: &str) -> bool {
-    error.contains("is not a working tree")
-}
-
 async fn run_git_command_bytes(repo_path: &PathBuf, args: &[&str]) -> Result<Vec<u8>, String> {
     let output = Command::new("git")
         .args(args)
@@ -464,27 +475,41 @@ fn null_device_path() -> &'static str {
 
 #[tauri::command]
 pub(crate) async fn list_workspaces(
+    include_archived: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Vec<WorkspaceInfo>, String> {
     if remote_backend::is_remote_mode(&*state).await {
-        let response = remote_backend::call_remote(&*state, app, "list_workspaces", json!({})).await?;
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_workspaces",
+            json!({ "includeArchived": include_archived }),
+        )
+        .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
+    let include_archived = include_archived.unwrap_or(false);
     let workspaces = state.workspaces.lock().await;
     let sessions = state.sessions.lock().await;
     let mut result = Vec::new();
     for entry in workspaces.values() {
+        if entry.archived_at.is_some() && !include_archived {
+            continue;
+        }
         result.push(WorkspaceInfo {
             id: entry.id.clone(),
             name: entry.name.clone(),
             path: entry.path.clone(),
             claude_bin: entry.claude_bin.clone(),
+            model: entry.model.clone(),
             connected: sessions.contains_key(&entry.id),
             kind: entry.kind.clone(),
             parent_id: entry.parent_id.clone(),
             worktree: entry.worktree.clone(),
+            archived_at: entry.archived_at,
+            pinned: entry.pinned,
             settings: entry.settings.clone(),
         });
     }
@@ -543,9 +568,12 @@ pub(crate) async fn add_workspace(
         name: name.clone(),
         path: path.clone(),
         claude_bin,
+        model: None,
         kind: WorkspaceKind::Main,
         parent_id: None,
         worktree: None,
+        archived_at: None,
+        pinned: false,
         settings: WorkspaceSettings::default(),
     };
 
@@ -559,7 +587,7 @@ pub(crate) async fn add_workspace(
         let mut workspaces = state.workspaces.lock().await;
         workspaces.insert(entry.id.clone(), entry.clone());
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(&state.storage_path, &list)
+        write_workspaces(&state.db_path, &list)
     } {
         {
             let mut workspaces = state.workspaces.lock().await;
@@ -581,10 +609,13 @@ pub(crate) async fn add_workspace(
         name: entry.name,
         path: entry.path,
         claude_bin: entry.claude_bin,
+        model: entry.model,
         connected: true,
         kind: entry.kind,
         parent_id: entry.parent_id,
         worktree: entry.worktree,
+        archived_at: entry.archived_at,
+        pinned: entry.pinned,
         settings: entry.settings,
     })
 }
@@ -657,11 +688,17 @@ pub(crate) async fn add_clone(
         name: copy_name.clone(),
         path: destination_path_string,
         claude_bin: source_entry.claude_bin.clone(),
+        model: source_entry.model.clone(),
         kind: WorkspaceKind::Main,
         parent_id: None,
         worktree: None,
+        archived_at: None,
+        pinned: false,
         settings: WorkspaceSettings {
             group_id: inherited_group_id,
+            provider: source_entry.settings.provider.clone(),
+            proxy: source_entry.settings.proxy.clone(),
+            tags: source_entry.settings.tags.clone(),
             ..WorkspaceSettings::default()
         },
     };
@@ -682,7 +719,7 @@ pub(crate) async fn add_clone(
         let mut workspaces = state.workspaces.lock().await;
         workspaces.insert(entry.id.clone(), entry.clone());
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(&state.storage_path, &list)
+        write_workspaces(&state.db_path, &list)
     } {
         {
             let mut workspaces = state.workspaces.lock().await;
@@ -705,10 +742,13 @@ pub(crate) async fn add_clone(
         name: entry.name,
         path: entry.path,
         claude_bin: entry.claude_bin,
+        model: entry.model,
         connected: true,
         kind: entry.kind,
         parent_id: entry.parent_id,
         worktree: entry.worktree,
+        archived_at: entry.archived_at,
+        pinned: entry.pinned,
         settings: entry.settings,
     })
 }
@@ -717,6 +757,7 @@ pub(crate) async fn add_clone(
 pub(crate) async fn add_worktree(
     parent_id: String,
     branch: String,
+    base_ref: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<WorkspaceInfo, String> {
@@ -750,32 +791,38 @@ pub(crate) async fn add_worktree(
     let worktree_path = unique_worktree_path(&worktree_root, &safe_name);
     let worktree_path_string = worktree_path.to_string_lossy().to_string();
 
-    let branch_exists = git_branch_exists(&PathBuf::from(&parent_entry.path), branch).await?;
-    if branch_exists {
-        run_git_command(
-            &PathBuf::from(&parent_entry.path),
-            &["worktree", "add", &worktree_path_string, branch],
-        )
-        .await?;
-    } else {
-        run_git_command(
-            &PathBuf::from(&parent_entry.path),
-            &["worktree", "add", "-b", branch, &worktree_path_string],
-        )
-        .await?;
-    }
+    let parent_path = PathBuf::from(&parent_entry.path);
+    let branch_exists = git_branch_exists(&parent_path, branch).await?;
+    worktree::add_checkout(
+        &parent_path,
+        &worktree_path,
+        branch,
+        branch_exists,
+        base_ref.as_deref(),
+    )
+    .await?;
 
     let entry = WorkspaceEntry {
         id: Uuid::new_v4().to_string(),
         name: branch.to_string(),
         path: worktree_path_string,
         claude_bin: parent_entry.claude_bin.clone(),
+        model: parent_entry.model.clone(),
         kind: WorkspaceKind::Worktree,
         parent_id: Some(parent_entry.id.clone()),
         worktree: Some(WorktreeInfo {
             branch: branch.to_string(),
         }),
-        settings: WorkspaceSettings::default(),
+        // Worktrees run on their parent's provider and proxy, as they do on its credentials,
+        // and carry its tags so they show up in the same aggregates
+        archived_at: None,
+        pinned: false,
+        settings: WorkspaceSettings {
+            provider: parent_entry.settings.provider.clone(),
+            proxy: parent_entry.settings.proxy.clone(),
+            tags: parent_entry.settings.tags.clone(),
+            ..WorkspaceSettings::default()
+        },
     };
 
     let default_bin = {
@@ -787,7 +834,7 @@ pub(crate) async fn add_worktree(
         let mut workspaces = state.workspaces.lock().await;
         workspaces.insert(entry.id.clone(), entry.clone());
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(&state.storage_path, &list)?;
+        write_workspaces(&state.db_path, &list)?;
     }
     state
         .sessions
@@ -802,25 +849,22 @@ pub(crate) async fn add_worktree(
         name: entry.name,
         path: entry.path,
         claude_bin: entry.claude_bin,
+        model: entry.model,
         connected: true,
         kind: entry.kind,
         parent_id: entry.parent_id,
         worktree: entry.worktree,
+        archived_at: entry.archived_at,
+        pinned: entry.pinned,
         settings: entry.settings,
     })
 }
 
 #[tauri::command]
-pub(crate) async fn remove_workspace(
-    id: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
+pub(crate) async fn remove_workspace(id: String, state: State<'_, AppState>) -> Result<(), String> {
     let (entry, child_worktrees) = {
         let workspaces = state.workspaces.lock().await;
-        let entry = workspaces
-            .get(&id)
-            .cloned()
-            .ok_or("workspace not found")?;
+        let entry = workspaces.get(&id).cloned().ok_or("workspace not found")?;
         if entry.kind.is_worktree() {
             return Err("Use remove_worktree for worktree agents.".to_string());
         }
@@ -842,27 +886,8 @@ pub(crate) async fn remove_workspace(
         if let Some(session) = state.sessions.lock().await.remove(&child.id) {
             let _ = session.kill_all_persistent_sessions().await;
         }
-        let child_path = PathBuf::from(&child.path);
-        if child_path.exists() {
-            if let Err(error) = run_git_command(
-                &parent_path,
-                &["worktree", "remove", "--force", &child.path],
-            )
-            .await
-            {
-                if is_missing_worktree_error(&error) {
-                    if child_path.exists() {
-                        std::fs::remove_dir_all(&child_path).map_err(|err| {
-                            format!("Failed to remove worktree folder: {err}")
-                        })?;
-                    }
-                } else {
-                    return Err(error);
-                }
-            }
-        }
+        worktree::remove_checkout(&parent_path, &PathBuf::from(&child.path)).await?;
     }
-    let _ = run_git_command(&parent_path, &["worktree", "prune", "--expire", "now"]).await;
 
     if let Some(session) = state.sessions.lock().await.remove(&id) {
         let _ = session.kill_all_persistent_sessions().await;
@@ -871,34 +896,183 @@ pub(crate) async fn remove_workspace(
     {
         let mut workspaces = state.workspaces.lock().await;
         workspaces.remove(&id);
+        file_index::forget(&id);
         for child in child_worktrees {
             workspaces.remove(&child.id);
+            file_index::forget(&child.id);
         }
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(&state.storage_path, &list)?;
+        write_workspaces(&state.db_path, &list)?;
+    }
+
+    // Credentials live in the keychain, outside the database
+    let removed = tokio::task::spawn_blocking(move || credentials::delete_credentials(&id)).await;
+    if let Ok(Err(err)) = removed {
+        tracing::warn!("{err}");
     }
 
     Ok(())
 }
 
+/// Hide a workspace and its worktrees from active views. Their processes are stopped;
+/// transcripts, usage and settings are kept until the retention policy prunes them.
 #[tauri::command]
-pub(crate) async fn remove_worktree(
+pub(crate) async fn archive_workspace(
     id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "archive_workspace", json!({ "id": id }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let archived_at = chrono::Utc::now().timestamp_millis();
+    let ids = set_workspace_archived(&state, &id, Some(archived_at)).await?;
+    for workspace_id in &ids {
+        stop_workspace_thread_watcher(workspace_id, &state).await;
+        let session = state.sessions.lock().await.remove(workspace_id);
+        if let Some(session) = session {
+            let _ = session.kill_all_persistent_sessions().await;
+        }
+    }
+    workspace_info(&state, &id).await
+}
+
+/// Bring an archived workspace and its worktrees back into active views.
+#[tauri::command]
+pub(crate) async fn unarchive_workspace(
+    id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "unarchive_workspace", json!({ "id": id }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    set_workspace_archived(&state, &id, None).await?;
+    workspace_info(&state, &id).await
+}
+
+/// Pin a workspace so it connects at launch with its latest thread's process running,
+/// or unpin it. Archived workspaces are never started, pinned or not.
+#[tauri::command]
+pub(crate) async fn set_workspace_pinned(
+    id: String,
+    pinned: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_workspace_pinned",
+            json!({ "id": id, "pinned": pinned }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    {
+        let mut workspaces = state.workspaces.lock().await;
+        workspaces.get_mut(&id).ok_or("workspace not found")?.pinned = pinned;
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        write_workspaces(&state.db_path, &list)?;
+    }
+    workspace_info(&state, &id).await
+}
+
+/// Set `archived_at` on a workspace and its worktrees and persist the list. Returns the ids
+/// that changed.
+async fn set_workspace_archived(
+    state: &AppState,
+    id: &str,
+    archived_at: Option<i64>,
+) -> Result<Vec<String>, String> {
+    let mut workspaces = state.workspaces.lock().await;
+    let ids = archive_targets(&workspaces, id, archived_at.is_some())?;
+    for workspace_id in &ids {
+        if let Some(entry) = workspaces.get_mut(workspace_id) {
+            entry.archived_at = archived_at;
+        }
+    }
+    let list: Vec<_> = workspaces.values().cloned().collect();
+    write_workspaces(&state.db_path, &list)?;
+    Ok(ids)
+}
+
+/// The workspace and its worktrees. A worktree cannot come back while its parent is
+/// archived.
+fn archive_targets(
+    workspaces: &HashMap<String, WorkspaceEntry>,
+    id: &str,
+    archiving: bool,
+) -> Result<Vec<String>, String> {
+    let entry = workspaces.get(id).ok_or("workspace not found")?;
+    if !archiving {
+        let parent = entry
+            .parent_id
+            .as_deref()
+            .and_then(|parent| workspaces.get(parent));
+        if parent.is_some_and(|parent| parent.archived_at.is_some()) {
+            return Err("Unarchive the parent workspace first.".to_string());
+        }
+    }
+    let mut ids = vec![id.to_string()];
+    ids.extend(
+        workspaces
+            .values()
+            .filter(|workspace| workspace.parent_id.as_deref() == Some(id))
+            .map(|workspace| workspace.id.clone()),
+    );
+    Ok(ids)
+}
+
+async fn workspace_info(state: &AppState, id: &str) -> Result<WorkspaceInfo, String> {
+    let entry = state
+        .workspaces
+        .lock()
+        .await
+        .get(id)
+        .cloned()
+        .ok_or("workspace not found")?;
+    let connected = state.sessions.lock().await.contains_key(id);
+    Ok(WorkspaceInfo {
+        id: entry.id,
+        name: entry.name,
+        path: entry.path,
+        claude_bin: entry.claude_bin,
+        model: entry.model,
+        connected,
+        kind: entry.kind,
+        parent_id: entry.parent_id,
+        worktree: entry.worktree,
+        archived_at: entry.archived_at,
+        pinned: entry.pinned,
+        settings: entry.settings,
+    })
+}
+
+#[tauri::command]
+pub(crate) async fn remove_worktree(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    remove_worktree_entry(&id, &state).await
+}
+
+/// Stop the worktree's sessions, delete its checkout and unregister it.
+pub(crate) async fn remove_worktree_entry(id: &str, state: &AppState) -> Result<(), String> {
     let (entry, parent) = {
         let workspaces = state.workspaces.lock().await;
-        let entry = workspaces
-            .get(&id)
-            .cloned()
-            .ok_or("workspace not found")?;
+        let entry = workspaces.get(id).cloned().ok_or("workspace not found")?;
         if !entry.kind.is_worktree() {
             return Err("Not a worktree workspace.".to_string());
         }
-        let parent_id = entry
-            .parent_id
-            .clone()
-            .ok_or("worktree parent not found")?;
+        let parent_id = entry.parent_id.clone().ok_or("worktree parent not found")?;
         let parent = workspaces
             .get(&parent_id)
             .cloned()
@@ -906,39 +1080,20 @@ pub(crate) async fn remove_worktree(
         (entry, parent)
     };
 
-    stop_workspace_thread_watcher(&entry.id, &state).await;
+    stop_workspace_thread_watcher(&entry.id, state).await;
 
     if let Some(session) = state.sessions.lock().await.remove(&entry.id) {
         let _ = session.kill_all_persistent_sessions().await;
     }
 
-    let parent_path = PathBuf::from(&parent.path);
-    let entry_path = PathBuf::from(&entry.path);
-    if entry_path.exists() {
-        if let Err(error) = run_git_command(
-            &parent_path,
-            &["worktree", "remove", "--force", &entry.path],
-        )
-        .await
-        {
-            if is_missing_worktree_error(&error) {
-                if entry_path.exists() {
-                    std::fs::remove_dir_all(&entry_path).map_err(|err| {
-                        format!("Failed to remove worktree folder: {err}")
-                    })?;
-                }
-            } else {
-                return Err(error);
-            }
-        }
-    }
-    let _ = run_git_command(&parent_path, &["worktree", "prune", "--expire", "now"]).await;
+    worktree::remove_checkout(&PathBuf::from(&parent.path), &PathBuf::from(&entry.path)).await?;
 
     {
         let mut workspaces = state.workspaces.lock().await;
         workspaces.remove(&entry.id);
+        file_index::forget(&entry.id);
         let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(&state.storage_path, &list)?;
+        write_workspaces(&state.db_path, &list)?;
     }
 
     Ok(())
@@ -969,17 +1124,11 @@ pub(crate) async fn rename_worktree(
 
     let (entry, parent) = {
         let workspaces = state.workspaces.lock().await;
-        let entry = workspaces
-            .get(&id)
-            .cloned()
-            .ok_or("workspace not found")?;
+        let entry = workspaces.get(&id).cloned().ok_or("workspace not found")?;
         if !entry.kind.is_worktree() {
             return Err("Not a worktree workspace.".to_string());
         }
-        let parent_id = entry
-            .parent_id
-            .clone()
-            .ok_or("worktree parent not found")?;
+        let parent_id = entry.parent_id.clone().ok_or("worktree parent not found")?;
         let parent = workspaces
             .get(&parent_id)
             .cloned()
@@ -997,17 +1146,12 @@ pub(crate) async fn rename_worktree(
     }
 
     let parent_root = resolve_git_root(&parent)?;
-    let (final_branch, _was_suffixed) =
-        unique_branch_name(&parent_root, trimmed, None).await?;
+    let (final_branch, _was_suffixed) = unique_branch_name(&parent_root, trimmed, None).await?;
     if final_branch == old_branch {
         return Err("Branch name is unchanged.".to_string());
     }
 
-    run_git_command(
-        &parent_root,
-        &["branch", "-m", &old_branch, &final_branch],
-    )
-    .await?;
+    run_git_command(&parent_root, &["branch", "-m", &old_branch, &final_branch]).await?;
 
     let worktree_root = app
         .path()
@@ -1020,8 +1164,7 @@ pub(crate) async fn rename_worktree(
 
     let safe_name = sanitize_worktree_name(&final_branch);
     let current_path = PathBuf::from(&entry.path);
-    let next_path =
-        unique_worktree_path_for_rename(&worktree_root, &safe_name, &current_path)?;
+    let next_path = unique_worktree_path_for_rename(&worktree_root, &safe_name, &current_path)?;
     let next_path_string = next_path.to_string_lossy().to_string();
     if next_path_string != entry.path {
         if let Err(error) = run_git_command(
@@ -1030,11 +1173,8 @@ pub(crate) async fn rename_worktree(
         )
         .await
         {
-            let _ = run_git_command(
-                &parent_root,
-                &["branch", "-m", &final_branch, &old_branch],
-            )
-            .await;
+            let _ =
+                run_git_command(&parent_root, &["branch", "-m", &final_branch, &old_branch]).await;
             return Err(error);
         }
     }
@@ -1061,7 +1201,7 @@ pub(crate) async fn rename_worktree(
         let list: Vec<_> = workspaces.values().cloned().collect();
         (snapshot, list)
     };
-    write_workspaces(&state.storage_path, &list)?;
+    write_workspaces(&state.db_path, &list)?;
 
     let was_connected = state.sessions.lock().await.contains_key(&entry_snapshot.id);
     if was_connected {
@@ -1100,8 +1240,8 @@ pub(crate) async fn rename_worktree(
                 .await;
             }
             Err(error) => {
-                eprintln!(
-                    "rename_worktree: respawn failed for {} after rename: {error}",
+                tracing::warn!(
+                    "respawn failed for {} after rename: {error}",
                     entry_snapshot.id
                 );
             }
@@ -1114,10 +1254,13 @@ pub(crate) async fn rename_worktree(
         name: entry_snapshot.name,
         path: entry_snapshot.path,
         claude_bin: entry_snapshot.claude_bin,
+        model: entry_snapshot.model,
         connected,
         kind: entry_snapshot.kind,
         parent_id: entry_snapshot.parent_id,
         worktree: entry_snapshot.worktree,
+        archived_at: entry_snapshot.archived_at,
+        pinned: entry_snapshot.pinned,
         settings: entry_snapshot.settings,
     })
 }
@@ -1152,17 +1295,11 @@ pub(crate) async fn rename_worktree_upstream(
 
     let (_entry, parent) = {
         let workspaces = state.workspaces.lock().await;
-        let entry = workspaces
-            .get(&id)
-            .cloned()
-            .ok_or("workspace not found")?;
+        let entry = workspaces.get(&id).cloned().ok_or("workspace not found")?;
         if !entry.kind.is_worktree() {
             return Err("Not a worktree workspace.".to_string());
         }
-        let parent_id = entry
-            .parent_id
-            .clone()
-            .ok_or("worktree parent not found")?;
+        let parent_id = entry.parent_id.clone().ok_or("worktree parent not found")?;
         let parent = workspaces
             .get(&parent_id)
             .cloned()
@@ -1194,11 +1331,7 @@ pub(crate) async fn rename_worktree_upstream(
     if remote_for_old.is_some() {
         run_git_command(
             &parent_root,
-            &[
-                "push",
-                &remote_name,
-                &format!("{new_branch}:{new_branch}"),
-            ],
+            &["push", &remote_name, &format!("{new_branch}:{new_branch}")],
         )
         .await?;
         run_git_command(
@@ -1238,10 +1371,7 @@ pub(crate) async fn apply_worktree_changes(
         if !entry.kind.is_worktree() {
             return Err("Not a worktree workspace.".to_string());
         }
-        let parent_id = entry
-            .parent_id
-            .clone()
-            .ok_or("worktree parent not found")?;
+        let parent_id = entry.parent_id.clone().ok_or("worktree parent not found")?;
         let parent = workspaces
             .get(&parent_id)
             .cloned()
@@ -1252,8 +1382,7 @@ pub(crate) async fn apply_worktree_changes(
     let worktree_root = resolve_git_root(&entry)?;
     let parent_root = resolve_git_root(&parent)?;
 
-    let parent_status =
-        run_git_command_bytes(&parent_root, &["status", "--porcelain"]).await?;
+    let parent_status = run_git_command_bytes(&parent_root, &["status", "--porcelain"]).await?;
     if !String::from_utf8_lossy(&parent_status).trim().is_empty() {
         return Err(
             "Your current branch has uncommitted changes. Please commit, stash, or discard them before applying worktree changes."
@@ -1262,11 +1391,13 @@ pub(crate) async fn apply_worktree_changes(
     }
 
     let mut patch: Vec<u8> = Vec::new();
-    let staged_patch =
-        run_git_diff(&worktree_root, &["diff", "--binary", "--no-color", "--cached"]).await?;
+    let staged_patch = run_git_diff(
+        &worktree_root,
+        &["diff", "--binary", "--no-color", "--cached"],
+    )
+    .await?;
     patch.extend_from_slice(&staged_patch);
-    let unstaged_patch =
-        run_git_diff(&worktree_root, &["diff", "--binary", "--no-color"]).await?;
+    let unstaged_patch = run_git_diff(&worktree_root, &["diff", "--binary", "--no-color"]).await?;
     patch.extend_from_slice(&unstaged_patch);
 
     let untracked_output = run_git_command_bytes(
@@ -1363,18 +1494,28 @@ pub(crate) async fn update_workspace_settings(
         let list: Vec<_> = workspaces.values().cloned().collect();
         (entry_snapshot, list)
     };
-    write_workspaces(&state.storage_path, &list)?;
-
-    let connected = state.sessions.lock().await.contains_key(&id);
+    write_workspaces(&state.db_path, &list)?;
+
+    let session = state.sessions.lock().await.get(&id).cloned();
+    let connected = session.is_some();
+    if let Some(session) = session {
+        let preset = entry_snapshot.settings.permission_preset;
+        if *session.permission_preset.lock().await != preset {
+            session.apply_permission_preset(preset).await;
+        }
+    }
     Ok(WorkspaceInfo {
         id: entry_snapshot.id,
         name: entry_snapshot.name,
         path: entry_snapshot.path,
         claude_bin: entry_snapshot.claude_bin,
+        model: entry_snapshot.model,
         connected,
         kind: entry_snapshot.kind,
         parent_id: entry_snapshot.parent_id,
         worktree: entry_snapshot.worktree,
+        archived_at: entry_snapshot.archived_at,
+        pinned: entry_snapshot.pinned,
         settings: entry_snapshot.settings,
     })
 }
@@ -1397,7 +1538,47 @@ pub(crate) async fn update_workspace_claude_bin(
         let list: Vec<_> = workspaces.values().cloned().collect();
         (entry_snapshot, list)
     };
-    write_workspaces(&state.storage_path, &list)?;
+    write_workspaces(&state.db_path, &list)?;
+
+    let connected = state.sessions.lock().await.contains_key(&id);
+    Ok(WorkspaceInfo {
+        id: entry_snapshot.id,
+        name: entry_snapshot.name,
+        path: entry_snapshot.path,
+        claude_bin: entry_snapshot.claude_bin,
+        model: entry_snapshot.model,
+        connected,
+        kind: entry_snapshot.kind,
+        parent_id: entry_snapshot.parent_id,
+        worktree: entry_snapshot.worktree,
+        archived_at: entry_snapshot.archived_at,
+        pinned: entry_snapshot.pinned,
+        settings: entry_snapshot.settings,
+    })
+}
+
+#[tauri::command]
+pub(crate) async fn update_workspace_model(
+    id: String,
+    model: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<WorkspaceInfo, String> {
+    let model = model
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let (entry_snapshot, list) = {
+        let mut workspaces = state.workspaces.lock().await;
+        let entry_snapshot = match workspaces.get_mut(&id) {
+            Some(entry) => {
+                entry.model = model;
+                entry.clone()
+            }
+            None => return Err("workspace not found".to_string()),
+        };
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        (entry_snapshot, list)
+    };
+    write_workspaces(&state.db_path, &list)?;
 
     let connected = state.sessions.lock().await.contains_key(&id);
     Ok(WorkspaceInfo {
@@ -1405,10 +1586,13 @@ pub(crate) async fn update_workspace_claude_bin(
         name: entry_snapshot.name,
         path: entry_snapshot.path,
         claude_bin: entry_snapshot.claude_bin,
+        model: entry_snapshot.model,
         connected,
         kind: entry_snapshot.kind,
         parent_id: entry_snapshot.parent_id,
         worktree: entry_snapshot.worktree,
+        archived_at: entry_snapshot.archived_at,
+        pinned: entry_snapshot.pinned,
         settings: entry_snapshot.settings,
     })
 }
@@ -1420,31 +1604,37 @@ pub(crate) async fn connect_workspace(
     app: AppHandle,
 ) -> Result<(), String> {
     if remote_backend::is_remote_mode(&*state).await {
-        remote_backend::call_remote(&*state, app, "connect_workspace", json!({ "id": id }))
-            .await?;
+        remote_backend::call_remote(&*state, app, "connect_workspace", json!({ "id": id })).await?;
         return Ok(());
     }
 
     let entry = {
         let workspaces = state.workspaces.lock().await;
-        workspaces
-            .get(&id)
-            .cloned()
-            .ok_or("workspace not found")?
+        workspaces.get(&id).cloned().ok_or("workspace not found")?
     };
+    if entry.archived_at.is_some() {
+        return Err("Workspace is archived. Unarchive it to connect.".to_string());
+    }
 
     let default_bin = {
         let settings = state.app_settings.lock().await;
         settings.claude_bin.clone()
     };
     let session = spawn_workspace_session(entry.clone(), default_bin).await?;
-    state.sessions.lock().await.insert(entry.id.clone(), session);
+    state
+        .sessions
+        .lock()
+        .await
+        .insert(entry.id.clone(), session.clone());
+    file_index::warm(&entry.id, Path::new(&entry.path));
     ensure_workspace_thread_watcher(&entry.id, entry.clone(), &state, app.clone()).await;
     let event_sink = TauriEventSink::new(app.clone());
     event_sink.emit_app_server_event(AppServerEvent {
-        workspace_id: entry.id,
+        workspace_id: entry.id.clone(),
         message: json!({ "method": "claude/connected", "params": {} }),
     });
+    // Messages written while the workspace was down go out now, in order
+    crate::outbox::flush(&session, &event_sink, &entry.id, true).await;
     Ok(())
 }
 
@@ -1466,18 +1656,13 @@ pub(crate) async fn list_workspace_files(
     }
 
     let workspaces = state.workspaces.lock().await;
-    let entry = workspaces
-        .get(&workspace_id)
-        .ok_or("workspace not found")?;
+    let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
     let root = PathBuf::from(&entry.path);
     Ok(list_workspace_files_inner(&root, usize::MAX))
 }
 
 #[tauri::command]
-pub(crate) async fn open_workspace_in(
-    path: String,
-    app: String,
-) -> Result<(), String> {
+pub(crate) async fn open_workspace_in(path: String, app: String) -> Result<(), String> {
     let status = std::process::Command::new("open")
         .arg("-a")
         .arg(app)
@@ -1501,7 +1686,10 @@ mod tests {
         sanitize_worktree_name, sort_workspaces,
     };
     use crate::storage::{read_workspaces, write_workspaces};
-    use crate::types::{WorktreeInfo, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings};
+    use crate::types::{
+        ClaudeCliFlags, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings,
+        WorktreeInfo,
+    };
     use uuid::Uuid;
 
     fn workspace(name: &str, sort_order: Option<u32>) -> WorkspaceInfo {
@@ -1530,21 +1718,26 @@ mod tests {
             path: "/tmp".to_string(),
             connected: false,
             claude_bin: None,
+            model: None,
             kind,
             parent_id,
             worktree,
+            archived_at: None,
+            pinned: false,
             settings: WorkspaceSettings {
                 sidebar_collapsed: false,
                 sort_order,
-                group_id: None,
-                git_root: None,
+                ..WorkspaceSettings::default()
             },
         }
     }
 
     #[test]
     fn sanitize_worktree_name_rewrites_specials() {
-        assert_eq!(sanitize_worktree_name("feature/new-thing"), "feature-new-thing");
+        assert_eq!(
+            sanitize_worktree_name("feature/new-thing"),
+            "feature-new-thing"
+        );
         assert_eq!(sanitize_worktree_name("///"), "worktree");
         assert_eq!(sanitize_worktree_name("--branch--"), "branch");
     }
@@ -1557,7 +1750,10 @@ mod tests {
 
     #[test]
     fn sanitize_clone_dir_name_rewrites_specials() {
-        assert_eq!(sanitize_clone_dir_name("feature/new-thing"), "feature-new-thing");
+        assert_eq!(
+            sanitize_clone_dir_name("feature/new-thing"),
+            "feature-new-thing"
+        );
         assert_eq!(sanitize_clone_dir_name("///"), "copy");
         assert_eq!(sanitize_clone_dir_name("--name--"), "name");
     }
@@ -1570,8 +1766,8 @@ mod tests {
 
     #[test]
     fn build_clone_destination_path_sanitizes_and_uniquifies() {
-        let temp_dir = std::env::temp_dir()
-            .join(format!("claude-code-monitor-test-{}", Uuid::new_v4()));
+        let temp_dir =
+            std::env::temp_dir().join(format!("claude-code-monitor-test-{}", Uuid::new_v4()));
         let copies_folder = temp_dir.join("copies");
         std::fs::create_dir_all(&copies_folder).expect("create copies folder");
 
@@ -1676,9 +1872,12 @@ mod tests {
             name: "Workspace".to_string(),
             path: "/tmp".to_string(),
             claude_bin: None,
+            model: None,
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
+            archived_at: None,
+            pinned: false,
             settings: WorkspaceSettings::default(),
         };
         let mut workspaces = HashMap::from([(id.clone(), entry)]);
@@ -1689,15 +1888,15 @@ mod tests {
         settings.sidebar_collapsed = true;
         settings.git_root = Some("/tmp".to_string());
 
-        let updated =
-            apply_workspace_settings_update(&mut workspaces, &id, settings.clone()).expect("update");
+        let updated = apply_workspace_settings_update(&mut workspaces, &id, settings.clone())
+            .expect("update");
         assert_eq!(updated.settings.sort_order, Some(3));
         assert_eq!(updated.settings.group_id.as_deref(), Some("group-1"));
         assert!(updated.settings.sidebar_collapsed);
         assert_eq!(updated.settings.git_root.as_deref(), Some("/tmp"));
 
-        let temp_dir = std::env::temp_dir()
-            .join(format!("claude-code-monitor-test-{}", Uuid::new_v4()));
+        let temp_dir =
+            std::env::temp_dir().join(format!("claude-code-monitor-test-{}", Uuid::new_v4()));
         std::fs::create_dir_all(&temp_dir).expect("create temp dir");
         let path = PathBuf::from(temp_dir.join("workspaces.json"));
         let list: Vec<_> = workspaces.values().cloned().collect();
@@ -1710,4 +1909,71 @@ mod tests {
         assert!(stored.settings.sidebar_collapsed);
         assert_eq!(stored.settings.git_root.as_deref(), Some("/tmp"));
     }
+
+    #[test]
+    fn update_workspace_settings_rejects_invalid_cli_flags() {
+        let id = "workspace-1".to_string();
+        let entry = WorkspaceEntry {
+            id: id.clone(),
+            name: "Workspace".to_string(),
+            path: "/tmp".to_string(),
+            claude_bin: None,
+            model: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            archived_at: None,
+            pinned: false,
+            settings: WorkspaceSettings::default(),
+        };
+        let mut workspaces = HashMap::from([(id.clone(), entry)]);
+
+        let mut settings = WorkspaceSettings::default();
+        settings.cli_flags = Some(ClaudeCliFlags {
+            max_turns: Some(0),
+            ..ClaudeCliFlags::default()
+        });
+        assert!(apply_workspace_settings_update(&mut workspaces, &id, settings).is_err());
+        assert!(workspaces[&id].settings.cli_flags.is_none());
+    }
+
+    #[test]
+    fn archive_targets_include_worktrees_and_guard_unarchive() {
+        let entry = |id: &str, parent_id: Option<&str>| WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: format!("/tmp/{id}"),
+            claude_bin: None,
+            model: None,
+            kind: if parent_id.is_some() {
+                WorkspaceKind::Worktree
+            } else {
+                WorkspaceKind::Main
+            },
+            parent_id: parent_id.map(str::to_string),
+            worktree: None,
+            archived_at: None,
+            pinned: false,
+            settings: WorkspaceSettings::default(),
+        };
+        let mut workspaces = HashMap::from([
+            ("main".to_string(), entry("main", None)),
+            ("wt".to_string(), entry("wt", Some("main"))),
+            ("other".to_string(), entry("other", None)),
+        ]);
+
+        assert_eq!(
+            archive_targets(&workspaces, "main", true).unwrap(),
+            vec!["main".to_string(), "wt".to_string()]
+        );
+        assert_eq!(
+            archive_targets(&workspaces, "wt", true).unwrap(),
+            vec!["wt"]
+        );
+
+        workspaces.get_mut("main").unwrap().archived_at = Some(1);
+        assert!(archive_targets(&workspaces, "wt", false).is_err());
+        assert!(archive_targets(&workspaces, "main", false).is_ok());
+        assert!(archive_targets(&workspaces, "missing", true).is_err());
+    }
 }
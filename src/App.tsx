@@ -173,6 +173,7 @@ function MainApp() {
     markWorkspaceConnected,
     updateWorkspaceSettings,
     updateWorkspaceClaudeBin,
+    updateWorkspaceModel,
     createWorkspaceGroup,
     renameWorkspaceGroup,
     moveWorkspaceGroup,
@@ -556,6 +557,7 @@ function MainApp() {
     activeThreadId,
     activeItems,
     permissionDenials,
+    permissionRequests,
     userInputRequests,
     threadsByWorkspace,
     threadParentById,
@@ -584,6 +586,7 @@ function MainApp() {
     handlePermissionRemember,
     handlePermissionRetry,
     handlePermissionDismiss,
+    handlePermissionDecision,
     handleUserInputSubmit,
   } = useThreads({
     activeWorkspace,
@@ -1337,10 +1340,12 @@ function MainApp() {
     activeRateLimits,
     codeBlockCopyUseModifier: appSettings.composerCodeBlockCopyUseModifier,
     permissionDenials,
+    permissionRequests,
     userInputRequests,
     handlePermissionRemember,
     handlePermissionRetry,
     handlePermissionDismiss,
+    handlePermissionDecision,
     handleUserInputSubmit,
     onOpenSettings: () => openSettings(),
     onOpenDictationSettings: () => openSettings("dictation"),
@@ -1795,6 +1800,19 @@ function MainApp() {
           onUpdateWorkspaceClaudeBin: async (id, claudeBin) => {
             await updateWorkspaceClaudeBin(id, claudeBin);
           },
+          onUpdateWorkspaceModel: async (id, model) => {
+            await updateWorkspaceModel(id, model);
+          },
+          onToggleWorkspaceNotifications: async (id, muted) => {
+            const workspace = workspaces.find((entry) => entry.id === id);
+            if (!workspace) {
+              return;
+            }
+            await updateWorkspaceSettings(id, {
+              ...workspace.settings,
+              notificationsMuted: muted,
+            });
+          },
           scaleShortcutTitle,
           scaleShortcutText,
           onTestNotificationSound: handleTestNotificationSound,
@@ -1,14 +1,24 @@
 import { useMemo } from "react";
-import type { PermissionDenial, WorkspaceInfo } from "../../../types";
+import type {
+  PermissionDecision,
+  PermissionDenial,
+  PermissionRequest,
+  WorkspaceInfo,
+} from "../../../types";
 import type { ApprovalRuleInfo } from "../../../utils/approvalRules";
 import { getApprovalRuleInfo } from "../../../utils/approvalRules";
 
 type ApprovalToastsProps = {
   permissionDenials?: PermissionDenial[];
+  permissionRequests?: PermissionRequest[];
   workspaces: WorkspaceInfo[];
   onPermissionRemember?: (denial: PermissionDenial, ruleInfo: ApprovalRuleInfo) => void;
   onPermissionRetry?: (denial: PermissionDenial, ruleInfo: ApprovalRuleInfo) => void;
   onPermissionDismiss?: (denial: PermissionDenial) => void;
+  onPermissionDecision?: (
+    request: PermissionRequest,
+    decision: PermissionDecision,
+  ) => void;
 };
 
 export function ApprovalToasts({
@@ -17,6 +27,8 @@ export function ApprovalToasts({
   onPermissionRemember,
   onPermissionRetry,
   onPermissionDismiss,
+  permissionRequests,
+  onPermissionDecision,
 }: ApprovalToastsProps) {
   const workspaceLabels = useMemo(
     () => new Map(workspaces.map((workspace) => [workspace.id, workspace.name])),
@@ -24,8 +36,9 @@ export function ApprovalToasts({
   );
 
   const denials = permissionDenials ?? [];
+  const requests = permissionRequests ?? [];
 
-  if (!denials.length) {
+  if (!denials.length && !requests.length) {
     return null;
   }
 
@@ -51,15 +64,77 @@ export function ApprovalToasts({
     return { text: JSON.stringify(value, null, 2), isCode: true };
   };
 
+  const renderInputDetails = (toolInput: PermissionDenial["tool_input"]) =>
+    toolInput && typeof toolInput === "object"
+      ? Object.entries(toolInput).map(([key, value]) => {
+          const rendered = renderParamValue(value);
+          return (
+            <div key={key} className="approval-toast-detail">
+              <div className="approval-toast-detail-label">{formatLabel(key)}</div>
+              {rendered.isCode ? (
+                <pre className="approval-toast-detail-code">{rendered.text}</pre>
+              ) : (
+                <div className="approval-toast-detail-value">{rendered.text}</div>
+              )}
+            </div>
+          );
+        })
+      : null;
+
   return (
     <div className="approval-toasts" role="region" aria-live="assertive">
+      {requests.map((request) => {
+        const workspaceName = workspaceLabels.get(request.workspace_id);
+        return (
+          <div key={request.id} className="approval-toast" role="alert">
+            <div className="approval-toast-header">
+              <div className="approval-toast-title">Approval required</div>
+              {workspaceName ? (
+                <div className="approval-toast-workspace">{workspaceName}</div>
+              ) : null}
+            </div>
+            <div className="approval-toast-method">{request.tool_name}</div>
+            <div className="approval-toast-details">
+              {renderInputDetails(request.tool_input)}
+            </div>
+            <div className="approval-toast-actions">
+              <button
+                className="secondary"
+                onClick={() =>
+                  onPermissionDecision?.(request, { behavior: "deny" })
+                }
+              >
+                Deny
+              </button>
+              {request.can_remember ? (
+                <button
+                  className="ghost approval-toast-remember"
+                  onClick={() =>
+                    onPermissionDecision?.(request, {
+                      behavior: "allow",
+                      always: true,
+                    })
+                  }
+                  title={`Stop asking about ${request.tool_name} in this session`}
+                >
+                  Always allow
+                </button>
+              ) : null}
+              <button
+                className="primary"
+                onClick={() =>
+                  onPermissionDecision?.(request, { behavior: "allow" })
+                }
+              >
+                Allow
+              </button>
+            </div>
+          </div>
+        );
+      })}
       {denials.map((denial) => {
         const workspaceName = workspaceLabels.get(denial.workspace_id);
         const toolInput = denial.tool_input ?? {};
-        const inputEntries =
-          toolInput && typeof toolInput === "object"
-            ? Object.entries(toolInput as Record<string, unknown>)
-            : [];
         const ruleInfo = getApprovalRuleInfo({
           ...(toolInput && typeof toolInput === "object"
             ? (toolInput as Record<string, unknown>)
@@ -82,27 +157,7 @@ export function ApprovalToasts({
                   Add to settings.local.json to allow.
                 </div>
               </div>
-              {inputEntries.length
-                ? inputEntries.map(([key, value]) => {
-                    const rendered = renderParamValue(value);
-                    return (
-                      <div key={key} className="approval-toast-detail">
-                        <div className="approval-toast-detail-label">
-                          {formatLabel(key)}
-                        </div>
-                        {rendered.isCode ? (
-                          <pre className="approval-toast-detail-code">
-                            {rendered.text}
-                          </pre>
-                        ) : (
-                          <div className="approval-toast-detail-value">
-                            {rendered.text}
-                          </div>
-                        )}
-                      </div>
-                    );
-                  })
-                : null}
+              {renderInputDetails(denial.tool_input)}
             </div>
             <div className="approval-toast-actions">
               <button
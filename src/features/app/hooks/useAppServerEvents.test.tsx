@@ -154,6 +154,511 @@ describe("useAppServerEvents", () => {
     expect(unlisten).toHaveBeenCalledTimes(1);
   });
 
+  it("routes permission requests and their resolution", async () => {
+    const handlers: Handlers = {
+      onPermissionRequest: vi.fn(),
+      onPermissionResolved: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    act(() => {
+      listener?.({
+        workspace_id: "ws-4",
+        message: {
+          method: "item/permission/requestApproval",
+          params: {
+            threadId: "thread-4",
+            turnId: "turn-4",
+            requestId: "req-4",
+            toolName: "Bash",
+            toolUseId: "toolu_4",
+            toolInput: { command: "npm test" },
+            canRemember: true,
+          },
+        },
+      });
+      listener?.({
+        workspace_id: "ws-4",
+        message: {
+          method: "item/permission/resolved",
+          params: { threadId: "thread-4", requestId: "req-4" },
+        },
+      });
+    });
+
+    expect(handlers.onPermissionRequest).toHaveBeenCalledWith({
+      id: "req-4",
+      workspace_id: "ws-4",
+      thread_id: "thread-4",
+      turn_id: "turn-4",
+      tool_name: "Bash",
+      tool_use_id: "toolu_4",
+      tool_input: { command: "npm test" },
+      can_remember: true,
+    });
+    expect(handlers.onPermissionResolved).toHaveBeenCalledWith("ws-4", "req-4");
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes the session model from system init", async () => {
+    const handlers: Handlers = {
+      onSessionInitialized: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    act(() => {
+      listener?.({
+        workspace_id: "ws-5",
+        message: {
+          method: "session/initialized",
+          params: {
+            threadId: "thread-5",
+            sessionId: "session-5",
+            model: "claude-sonnet-4-5-20250929",
+            tools: ["Bash"],
+          },
+        },
+      });
+    });
+
+    expect(handlers.onSessionInitialized).toHaveBeenCalledWith("ws-5", "thread-5", {
+      sessionId: "session-5",
+      model: "claude-sonnet-4-5-20250929",
+    });
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes idle session hibernation", async () => {
+    const handlers: Handlers = {
+      onSessionHibernated: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    act(() => {
+      listener?.({
+        workspace_id: "ws-6",
+        message: {
+          method: "session/hibernated",
+          params: { threadId: "thread-6", idleMinutes: 30 },
+        },
+      });
+    });
+
+    expect(handlers.onSessionHibernated).toHaveBeenCalledWith("ws-6", "thread-6", 30);
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes transcript appends with their origin", async () => {
+    const handlers: Handlers = {
+      onTranscriptAppended: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    act(() => {
+      listener?.({
+        workspace_id: "ws-7",
+        message: {
+          method: "transcript/appended",
+          params: {
+            threadId: "thread-7",
+            entries: [{ type: "assistant" }],
+            external: true,
+          },
+        },
+      });
+      listener?.({
+        workspace_id: "ws-7",
+        message: {
+          method: "transcript/appended",
+          params: { threadId: "thread-7", entries: [] },
+        },
+      });
+    });
+
+    expect(handlers.onTranscriptAppended).toHaveBeenCalledTimes(1);
+    expect(handlers.onTranscriptAppended).toHaveBeenCalledWith("ws-7", "thread-7", {
+      entries: [{ type: "assistant" }],
+      external: true,
+    });
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes rate limit warnings and clears", async () => {
+    const handlers: Handlers = {
+      onRateLimitWarning: vi.fn(),
+      onRateLimitCleared: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    act(() => {
+      listener?.({
+        workspace_id: "ws-8",
+        message: {
+          method: "rateLimit/warning",
+          params: {
+            threadId: "thread-8",
+            kind: "overloaded",
+            message: "Overloaded",
+            backoffMs: 5000,
+            cooldownUntil: 1000,
+          },
+        },
+      });
+      listener?.({
+        workspace_id: "ws-8",
+        message: { method: "rateLimit/cleared", params: { threadId: "thread-8" } },
+      });
+    });
+
+    expect(handlers.onRateLimitWarning).toHaveBeenCalledWith(
+      "ws-8",
+      "thread-8",
+      expect.objectContaining({ kind: "overloaded", backoffMs: 5000 }),
+    );
+    expect(handlers.onRateLimitCleared).toHaveBeenCalledWith("ws-8", "thread-8");
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes live usage gauge readings", async () => {
+    const handlers: Handlers = {
+      onThreadUsageGauge: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    act(() => {
+      listener?.({
+        workspace_id: "ws-8",
+        message: {
+          method: "thread/usageGauge/updated",
+          params: {
+            threadId: "thread-8",
+            outputTokens: 120,
+            tokensPerSecond: 48.5,
+            contextTokens: 150000,
+            contextWindow: 200000,
+            contextPercent: 75,
+            done: false,
+          },
+        },
+      });
+    });
+
+    expect(handlers.onThreadUsageGauge).toHaveBeenCalledWith(
+      "ws-8",
+      "thread-8",
+      expect.objectContaining({ tokensPerSecond: 48.5, contextPercent: 75 }),
+    );
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes compactions", async () => {
+    const handlers: Handlers = {
+      onThreadCompaction: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    const compaction = {
+      id: "compaction-1",
+      workspaceId: "ws-8",
+      threadId: "thread-8",
+      trigger: "threshold",
+      startedAt: 1000,
+      finishedAt: 1500,
+      tokensBefore: 161000,
+      tokensAfter: null,
+      tokensReclaimed: null,
+      error: null,
+    };
+    act(() => {
+      listener?.({
+        workspace_id: "ws-8",
+        message: {
+          method: "thread/compaction",
+          params: { threadId: "thread-8", compaction },
+        },
+      });
+    });
+
+    expect(handlers.onThreadCompaction).toHaveBeenCalledWith(
+      "ws-8",
+      "thread-8",
+      compaction,
+    );
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes turn retries", async () => {
+    const handlers: Handlers = {
+      onTurnRetrying: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    act(() => {
+      listener?.({
+        workspace_id: "ws-9",
+        message: {
+          method: "turn/retrying",
+          params: {
+            threadId: "thread-9",
+            turnId: "turn-9",
+            attempt: 1,
+            maxAttempts: 3,
+            delayMs: 2000,
+            reason: "Overloaded",
+          },
+        },
+      });
+    });
+
+    expect(handlers.onTurnRetrying).toHaveBeenCalledWith("ws-9", "thread-9", {
+      turnId: "turn-9",
+      attempt: 1,
+      maxAttempts: 3,
+      delayMs: 2000,
+      reason: "Overloaded",
+    });
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes turn timeouts", async () => {
+    const handlers: Handlers = {
+      onTurnTimedOut: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    const timeout = {
+      threadId: "thread-9",
+      turnId: "turn-9",
+      kind: "toolRun",
+      limitSeconds: 600,
+      elapsedSeconds: 605,
+      toolName: "WebFetch",
+      toolUseId: "toolu_1",
+    };
+    act(() => {
+      listener?.({
+        workspace_id: "ws-9",
+        message: {
+          method: "turn/timedOut",
+          params: { threadId: "thread-9", timeout },
+        },
+      });
+    });
+
+    expect(handlers.onTurnTimedOut).toHaveBeenCalledWith(
+      "ws-9",
+      "thread-9",
+      timeout,
+    );
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes session resource samples and warnings", async () => {
+    const handlers: Handlers = {
+      onSessionResources: vi.fn(),
+      onSessionResourceWarning: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    const processes = [
+      {
+        threadId: "thread-9",
+        pid: 4242,
+        processCount: 3,
+        cpuPercent: 12.5,
+        memoryBytes: 3_221_225_472,
+        sampledAt: 1_700_000_000_000,
+      },
+    ];
+    const warning = {
+      threadId: "thread-9",
+      pid: 4242,
+      memoryBytes: 3_221_225_472,
+      limitBytes: 2_147_483_648,
+      restarting: true,
+    };
+    act(() => {
+      listener?.({
+        workspace_id: "ws-9",
+        message: { method: "session/resources", params: { processes } },
+      });
+      listener?.({
+        workspace_id: "ws-9",
+        message: {
+          method: "session/resourceWarning",
+          params: { threadId: "thread-9", warning },
+        },
+      });
+    });
+
+    expect(handlers.onSessionResources).toHaveBeenCalledWith(
+      "ws-9",
+      processes,
+    );
+    expect(handlers.onSessionResourceWarning).toHaveBeenCalledWith(
+      "ws-9",
+      "thread-9",
+      warning,
+    );
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes outbox updates", async () => {
+    const handlers: Handlers = {
+      onPendingMessages: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    const messages = [
+      {
+        id: "msg-1",
+        workspaceId: "ws-3",
+        threadId: "thread-3",
+        prompt: "run the tests",
+        model: null,
+        accessMode: null,
+        queuedAt: 1_700_000_000_000,
+        attempts: 1,
+        lastAttemptAt: 1_700_000_000_000,
+        error: "workspace not connected",
+      },
+    ];
+    act(() => {
+      listener?.({
+        workspace_id: "ws-3",
+        message: { method: "outbox/updated", params: { messages } },
+      });
+    });
+
+    expect(handlers.onPendingMessages).toHaveBeenCalledWith("ws-3", messages);
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes pending and resolved plans", async () => {
+    const handlers: Handlers = {
+      onPlanPending: vi.fn(),
+      onPlanResolved: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    const plan = {
+      threadId: "thread-4",
+      turnId: "turn-4",
+      requestId: "req-4",
+      toolUseId: "toolu_4",
+      plan: "1. Add the command",
+      proposedAt: 1_700_000_000_000,
+    };
+    act(() => {
+      listener?.({
+        workspace_id: "ws-4",
+        message: {
+          method: "item/plan/pending",
+          params: { threadId: "thread-4", turnId: "turn-4", plan },
+        },
+      });
+      listener?.({
+        workspace_id: "ws-4",
+        message: {
+          method: "item/plan/resolved",
+          params: { threadId: "thread-4", requestId: "req-4", approved: true },
+        },
+      });
+    });
+
+    expect(handlers.onPlanPending).toHaveBeenCalledWith("ws-4", plan);
+    expect(handlers.onPlanResolved).toHaveBeenCalledWith(
+      "ws-4",
+      "thread-4",
+      true,
+    );
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
+  it("routes stale and auto-answered interactions", async () => {
+    const handlers: Handlers = {
+      onInteractionStale: vi.fn(),
+      onInteractionAutoAnswered: vi.fn(),
+    };
+    const { root } = await mount(handlers);
+
+    const interaction = {
+      key: "toolu_5",
+      kind: "permission" as const,
+      threadId: "thread-5",
+      turnId: "turn-5",
+      requestId: "req-5",
+      toolName: "Bash",
+      createdAt: 1_700_000_000_000,
+      stale: true,
+    };
+    act(() => {
+      listener?.({
+        workspace_id: "ws-5",
+        message: {
+          method: "interaction/stale",
+          params: { threadId: "thread-5", interaction },
+        },
+      });
+      listener?.({
+        workspace_id: "ws-5",
+        message: {
+          method: "interaction/autoAnswered",
+          params: { threadId: "thread-5", interaction },
+        },
+      });
+    });
+
+    expect(handlers.onInteractionStale).toHaveBeenCalledWith(
+      "ws-5",
+      interaction,
+    );
+    expect(handlers.onInteractionAutoAnswered).toHaveBeenCalledWith(
+      "ws-5",
+      interaction,
+    );
+
+    await act(async () => {
+      root.unmount();
+    });
+  });
+
   it("normalizes request user input questions and options", async () => {
     const handlers: Handlers = {
       onRequestUserInput: vi.fn(),
@@ -190,6 +695,13 @@ describe("useAppServerEvents", () => {
                   { label: "", description: "No label" },
                 ],
               },
+              {
+                id: "q-2",
+                header: "Features",
+                question: "Pick any",
+                options: [{ label: "Parsing", description: "" }],
+                multiSelect: true,
+              },
             ],
           },
         },
@@ -214,6 +726,13 @@ describe("useAppServerEvents", () => {
               { label: "", description: "No label" },
             ],
           },
+          {
+            id: "q-2",
+            header: "Features",
+            question: "Pick any",
+            options: [{ label: "Parsing", description: "" }],
+            multiSelect: true,
+          },
         ],
       },
     });
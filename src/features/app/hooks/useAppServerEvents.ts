@@ -1,5 +1,19 @@
 import { useEffect } from "react";
-import type { AppServerEvent, PermissionDenial, RequestUserInputRequest } from "../../../types";
+import type {
+  AppServerEvent,
+  CompactionRecord,
+  PendingInteraction,
+  PendingMessage,
+  PendingPlan,
+  PermissionDenial,
+  PermissionRequest,
+  ProcessStats,
+  RateLimitWarning,
+  RequestUserInputRequest,
+  ResourceWarning,
+  TurnTimeout,
+  UsageGaugeReading,
+} from "../../../types";
 import { subscribeAppServerEvents } from "../../../services/events";
 
 type AgentDelta = {
@@ -26,6 +40,43 @@ type AppServerEventHandlers = {
     denials: PermissionDenial[];
   }) => void;
   onRequestUserInput?: (request: RequestUserInputRequest) => void;
+  onPermissionRequest?: (request: PermissionRequest) => void;
+  onPermissionResolved?: (workspaceId: string, requestId: string) => void;
+  onPlanPending?: (workspaceId: string, plan: PendingPlan) => void;
+  onPlanResolved?: (
+    workspaceId: string,
+    threadId: string,
+    approved: boolean,
+  ) => void;
+  onInteractionStale?: (
+    workspaceId: string,
+    interaction: PendingInteraction,
+  ) => void;
+  onInteractionAutoAnswered?: (
+    workspaceId: string,
+    interaction: PendingInteraction,
+  ) => void;
+  onSessionInitialized?: (
+    workspaceId: string,
+    threadId: string,
+    session: { sessionId: string; model: string | null },
+  ) => void;
+  onSessionHibernated?: (
+    workspaceId: string,
+    threadId: string,
+    idleMinutes: number,
+  ) => void;
+  onTranscriptAppended?: (
+    workspaceId: string,
+    threadId: string,
+    update: { entries: Record<string, unknown>[]; external: boolean },
+  ) => void;
+  onRateLimitWarning?: (
+    workspaceId: string,
+    threadId: string,
+    warning: RateLimitWarning,
+  ) => void;
+  onRateLimitCleared?: (workspaceId: string, threadId: string) => void;
   onAgentMessageDelta?: (event: AgentDelta) => void;
   onAgentMessageStarted?: (event: {
     workspaceId: string;
@@ -43,6 +94,35 @@ type AppServerEventHandlers = {
     turnId: string,
     payload: { message: string; willRetry: boolean },
   ) => void;
+  onTurnRetrying?: (
+    workspaceId: string,
+    threadId: string,
+    retry: {
+      turnId: string;
+      attempt: number;
+      maxAttempts: number | null;
+      delayMs: number;
+      reason: string;
+    },
+  ) => void;
+  onTurnTimedOut?: (
+    workspaceId: string,
+    threadId: string,
+    timeout: TurnTimeout,
+  ) => void;
+  onSessionResources?: (
+    workspaceId: string,
+    processes: ProcessStats[],
+  ) => void;
+  onSessionResourceWarning?: (
+    workspaceId: string,
+    threadId: string,
+    warning: ResourceWarning,
+  ) => void;
+  onPendingMessages?: (
+    workspaceId: string,
+    messages: PendingMessage[],
+  ) => void;
   onTurnPlanUpdated?: (
     workspaceId: string,
     threadId: string,
@@ -68,6 +148,16 @@ type AppServerEventHandlers = {
     threadId: string,
     tokenUsage: Record<string, unknown>,
   ) => void;
+  onThreadUsageGauge?: (
+    workspaceId: string,
+    threadId: string,
+    reading: UsageGaugeReading,
+  ) => void;
+  onThreadCompaction?: (
+    workspaceId: string,
+    threadId: string,
+    compaction: CompactionRecord,
+  ) => void;
   };
 
 export function useAppServerEvents(handlers: AppServerEventHandlers) {
@@ -112,6 +202,7 @@ export function useAppServerEvents(handlers: AppServerEventHandlers) {
               header: String(question.header ?? ""),
               question: String(question.question ?? ""),
               options: options.length ? options : undefined,
+              ...(question.multiSelect === true ? { multiSelect: true } : {}),
             };
           })
           .filter((question) => question.id);
@@ -129,6 +220,141 @@ export function useAppServerEvents(handlers: AppServerEventHandlers) {
         return;
       }
 
+      if (method === "item/permission/requestApproval") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const requestId = String(params.requestId ?? params.request_id ?? "");
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        const toolName = String(params.toolName ?? params.tool_name ?? "").trim();
+        if (requestId && threadId && toolName) {
+          const toolUseId = params.toolUseId ?? params.tool_use_id;
+          const toolInput = params.toolInput ?? params.tool_input;
+          handlers.onPermissionRequest?.({
+            id: requestId,
+            workspace_id,
+            thread_id: threadId,
+            turn_id: String(params.turnId ?? params.turn_id ?? ""),
+            tool_name: toolName,
+            tool_use_id: typeof toolUseId === "string" ? toolUseId : null,
+            tool_input:
+              toolInput && typeof toolInput === "object"
+                ? (toolInput as Record<string, unknown>)
+                : null,
+            can_remember: Boolean(params.canRemember ?? params.can_remember),
+          });
+        }
+        return;
+      }
+
+      if (method === "item/permission/resolved") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const requestId = String(params.requestId ?? params.request_id ?? "");
+        if (requestId) {
+          handlers.onPermissionResolved?.(workspace_id, requestId);
+        }
+        return;
+      }
+
+      if (method === "item/plan/pending") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const plan = params.plan as PendingPlan | undefined;
+        if (plan?.threadId && plan.requestId) {
+          handlers.onPlanPending?.(workspace_id, plan);
+        }
+        return;
+      }
+
+      if (method === "item/plan/resolved") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        if (threadId) {
+          handlers.onPlanResolved?.(
+            workspace_id,
+            threadId,
+            Boolean(params.approved),
+          );
+        }
+        return;
+      }
+
+      if (
+        method === "interaction/stale" ||
+        method === "interaction/autoAnswered"
+      ) {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const interaction = params.interaction as
+          | PendingInteraction
+          | undefined;
+        if (interaction?.threadId && interaction.key) {
+          const handler =
+            method === "interaction/stale"
+              ? handlers.onInteractionStale
+              : handlers.onInteractionAutoAnswered;
+          handler?.(workspace_id, interaction);
+        }
+        return;
+      }
+
+      if (method === "session/initialized") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        const sessionId = String(params.sessionId ?? params.session_id ?? "");
+        const model = typeof params.model === "string" ? params.model : null;
+        if (threadId) {
+          handlers.onSessionInitialized?.(workspace_id, threadId, {
+            sessionId,
+            model,
+          });
+        }
+        return;
+      }
+
+      if (method === "session/hibernated") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        const idleMinutes = Number(params.idleMinutes ?? params.idle_minutes ?? 0);
+        if (threadId) {
+          handlers.onSessionHibernated?.(workspace_id, threadId, idleMinutes);
+        }
+        return;
+      }
+
+      if (method === "transcript/appended") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        const entries = Array.isArray(params.entries)
+          ? (params.entries as Record<string, unknown>[])
+          : [];
+        if (threadId && entries.length > 0) {
+          handlers.onTranscriptAppended?.(workspace_id, threadId, {
+            entries,
+            external: Boolean(params.external),
+          });
+        }
+        return;
+      }
+
+      if (method === "rateLimit/warning") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        if (threadId) {
+          handlers.onRateLimitWarning?.(
+            workspace_id,
+            threadId,
+            params as unknown as RateLimitWarning,
+          );
+        }
+        return;
+      }
+
+      if (method === "rateLimit/cleared") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        if (threadId) {
+          handlers.onRateLimitCleared?.(workspace_id, threadId);
+        }
+        return;
+      }
+
       if (method === "item/agentMessage/delta") {
         const params = message.params as Record<string, unknown>;
         const threadId = String(params.threadId ?? params.thread_id ?? "");
@@ -174,6 +400,60 @@ export function useAppServerEvents(handlers: AppServerEventHandlers) {
         return;
       }
 
+      if (method === "turn/retrying") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        if (threadId) {
+          const maxAttempts = params.maxAttempts ?? params.max_attempts;
+          handlers.onTurnRetrying?.(workspace_id, threadId, {
+            turnId: String(params.turnId ?? params.turn_id ?? ""),
+            attempt: Number(params.attempt ?? 0),
+            maxAttempts: maxAttempts == null ? null : Number(maxAttempts),
+            delayMs: Number(params.delayMs ?? params.delay_ms ?? 0),
+            reason: String(params.reason ?? ""),
+          });
+        }
+        return;
+      }
+
+      if (method === "turn/timedOut") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        const timeout = params.timeout as TurnTimeout | undefined;
+        if (threadId && timeout) {
+          handlers.onTurnTimedOut?.(workspace_id, threadId, timeout);
+        }
+        return;
+      }
+
+      if (method === "session/resources") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const processes = Array.isArray(params.processes)
+          ? (params.processes as ProcessStats[])
+          : [];
+        handlers.onSessionResources?.(workspace_id, processes);
+        return;
+      }
+
+      if (method === "session/resourceWarning") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        const warning = params.warning as ResourceWarning | undefined;
+        if (threadId && warning) {
+          handlers.onSessionResourceWarning?.(workspace_id, threadId, warning);
+        }
+        return;
+      }
+
+      if (method === "outbox/updated") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const messages = Array.isArray(params.messages)
+          ? (params.messages as PendingMessage[])
+          : [];
+        handlers.onPendingMessages?.(workspace_id, messages);
+        return;
+      }
+
       if (method === "turn/completed") {
         const params = message.params as Record<string, unknown>;
         const turn = params.turn as Record<string, unknown> | undefined;
@@ -289,6 +569,29 @@ export function useAppServerEvents(handlers: AppServerEventHandlers) {
         return;
       }
 
+      if (method === "thread/usageGauge/updated") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        if (threadId) {
+          handlers.onThreadUsageGauge?.(
+            workspace_id,
+            threadId,
+            params as unknown as UsageGaugeReading,
+          );
+        }
+        return;
+      }
+
+      if (method === "thread/compaction") {
+        const params = (message.params as Record<string, unknown>) ?? {};
+        const threadId = String(params.threadId ?? params.thread_id ?? "");
+        const compaction = params.compaction as CompactionRecord | undefined;
+        if (threadId && compaction) {
+          handlers.onThreadCompaction?.(workspace_id, threadId, compaction);
+        }
+        return;
+      }
+
       if (method === "item/completed") {
         const params = message.params as Record<string, unknown>;
         const threadId = String(params.threadId ?? params.thread_id ?? "");
@@ -1,6 +1,8 @@
 import { convertFileSrc } from "@tauri-apps/api/core";
+import FileText from "lucide-react/dist/esm/icons/file-text";
 import Image from "lucide-react/dist/esm/icons/image";
 import X from "lucide-react/dist/esm/icons/x";
+import { isImageAttachment } from "../../../utils/attachments";
 
 type ComposerAttachmentsProps = {
   attachments: string[];
@@ -48,7 +50,8 @@ export function ComposerAttachments({
       {attachments.map((path) => {
         const title = fileTitle(path);
         const titleAttr = path.startsWith("data:") ? "Pasted image" : path;
-        const previewSrc = attachmentPreviewSrc(path);
+        const isImage = isImageAttachment(path);
+        const previewSrc = isImage ? attachmentPreviewSrc(path) : "";
         return (
           <div
             key={path}
@@ -66,7 +69,7 @@ export function ComposerAttachments({
               </span>
             ) : (
               <span className="composer-icon" aria-hidden>
-                <Image size={14} />
+                {isImage ? <Image size={14} /> : <FileText size={14} />}
               </span>
             )}
             <span className="composer-attachment-name">{title}</span>
@@ -175,7 +175,7 @@ describe("useComposerImageDrop", () => {
     restoreFileReader();
   });
 
-  it("filters tauri drag-drop paths and respects drop target", async () => {
+  it("trims tauri drag-drop paths and respects drop target", async () => {
     const onAttachImages = vi.fn();
     const hook = renderImageDropHook({ disabled: false, onAttachImages });
 
@@ -219,7 +219,10 @@ describe("useComposerImageDrop", () => {
       });
     });
 
-    expect(onAttachImages).toHaveBeenCalledWith(["/tmp/photo.png"]);
+    expect(onAttachImages).toHaveBeenCalledWith([
+      "/tmp/photo.png",
+      "/tmp/note.txt",
+    ]);
 
     hook.unmount();
   });
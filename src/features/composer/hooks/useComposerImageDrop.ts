@@ -1,22 +1,6 @@
 import { useEffect, useRef, useState } from "react";
 import { subscribeWindowDragDrop } from "../../../services/dragDrop";
 
-const imageExtensions = [
-  ".png",
-  ".jpg",
-  ".jpeg",
-  ".gif",
-  ".webp",
-  ".bmp",
-  ".tiff",
-  ".tif",
-];
-
-function isImagePath(path: string) {
-  const lower = path.toLowerCase();
-  return imageExtensions.some((ext) => lower.endsWith(ext));
-}
-
 function isDragFileTransfer(types: readonly string[] | undefined) {
   if (!types || types.length === 0) {
     return false;
@@ -112,12 +96,12 @@ export function useComposerImageDrop({
         if (!isInside) {
           return;
         }
-        const imagePaths = (event.payload.paths ?? [])
+        // Images are sent as images, other files as mentions or content
+        const paths = (event.payload.paths ?? [])
           .map((path) => path.trim())
-          .filter(Boolean)
-          .filter(isImagePath);
-        if (imagePaths.length > 0) {
-          onAttachImages?.(imagePaths);
+          .filter(Boolean);
+        if (paths.length > 0) {
+          onAttachImages?.(paths);
         }
       }
     });
@@ -166,9 +150,8 @@ export function useComposerImageDrop({
     const filePaths = [...files, ...itemFiles]
       .map((file) => (file as File & { path?: string }).path ?? "")
       .filter(Boolean);
-    const imagePaths = filePaths.filter(isImagePath);
-    if (imagePaths.length > 0) {
-      onAttachImages?.(imagePaths);
+    if (filePaths.length > 0) {
+      onAttachImages?.(filePaths);
       return;
     }
     const fileImages = [...files, ...itemFiles].filter((file) =>
@@ -37,7 +37,9 @@ import type {
   GitLogEntry,
   LocalUsageSnapshot,
   ModelOption,
+  PermissionDecision,
   PermissionDenial,
+  PermissionRequest,
   QueuedMessage,
   RateLimitSnapshot,
   RequestUserInputRequest,
@@ -108,6 +110,7 @@ type LayoutNodesOptions = {
   activeRateLimits: RateLimitSnapshot | null;
   codeBlockCopyUseModifier: boolean;
   permissionDenials: PermissionDenial[];
+  permissionRequests: PermissionRequest[];
   userInputRequests: RequestUserInputRequest[];
   handlePermissionRemember: (
     denial: PermissionDenial,
@@ -118,6 +121,10 @@ type LayoutNodesOptions = {
     ruleInfo: ApprovalRuleInfo,
   ) => void;
   handlePermissionDismiss: (denial: PermissionDenial) => void;
+  handlePermissionDecision: (
+    request: PermissionRequest,
+    decision: PermissionDecision,
+  ) => void;
   handleUserInputSubmit: (
     request: RequestUserInputRequest,
     response: RequestUserInputResponse,
@@ -540,10 +547,12 @@ export function useLayoutNodes(options: LayoutNodesOptions): LayoutNodesResult {
   const approvalToastsNode = (
     <ApprovalToasts
       permissionDenials={options.permissionDenials}
+      permissionRequests={options.permissionRequests}
       workspaces={options.workspaces}
       onPermissionRemember={options.handlePermissionRemember}
       onPermissionRetry={options.handlePermissionRetry}
       onPermissionDismiss={options.handlePermissionDismiss}
+      onPermissionDecision={options.handlePermissionDecision}
     />
   );
 
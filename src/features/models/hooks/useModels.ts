@@ -15,6 +15,22 @@ const pickDefaultModel = (models: ModelOption[]) =>
   models[0] ??
   null;
 
+// A workspace model override beats the app-wide preference.
+const pickPreferredModel = (
+  models: ModelOption[],
+  workspaceModel: string | null,
+  preferredModelId: string | null,
+) =>
+  (workspaceModel
+    ? models.find(
+        (model) => model.model === workspaceModel || model.id === workspaceModel,
+      )
+    : null) ??
+  (preferredModelId
+    ? models.find((model) => model.id === preferredModelId)
+    : null) ??
+  null;
+
 export function useModels({
   activeWorkspace,
   onDebug,
@@ -32,6 +48,7 @@ export function useModels({
 
   const workspaceId = activeWorkspace?.id ?? null;
   const isConnected = Boolean(activeWorkspace?.connected);
+  const workspaceModel = activeWorkspace?.model ?? null;
 
   useEffect(() => {
     if (workspaceId === lastWorkspaceId.current) {
@@ -138,9 +155,11 @@ export function useModels({
       if (selectedModelId && !existingSelection) {
         hasUserSelectedModel.current = false;
       }
-      const preferredSelection = preferredModelId
-        ? data.find((model) => model.id === preferredModelId) ?? null
-        : null;
+      const preferredSelection = pickPreferredModel(
+        data,
+        workspaceModel,
+        preferredModelId,
+      );
       const shouldKeepExisting =
         hasUserSelectedModel.current && existingSelection !== null;
       const nextSelection =
@@ -179,6 +198,7 @@ export function useModels({
     selectedModelId,
     resolveEffort,
     workspaceId,
+    workspaceModel,
   ]);
 
   useEffect(() => {
@@ -211,9 +231,11 @@ export function useModels({
     if (!models.length) {
       return;
     }
-    const preferredSelection = preferredModelId
-      ? models.find((model) => model.id === preferredModelId) ?? null
-      : null;
+    const preferredSelection = pickPreferredModel(
+      models,
+      workspaceModel,
+      preferredModelId,
+    );
     const defaultModel = pickDefaultModel(models);
     const existingSelection = selectedModelId
       ? models.find((model) => model.id === selectedModelId) ?? null
@@ -238,7 +260,14 @@ export function useModels({
     if (nextEffort !== selectedEffort) {
       setSelectedEffortState(nextEffort);
     }
-  }, [models, preferredModelId, selectedEffort, selectedModelId, resolveEffort]);
+  }, [
+    models,
+    preferredModelId,
+    selectedEffort,
+    selectedModelId,
+    resolveEffort,
+    workspaceModel,
+  ]);
 
   return {
     models,
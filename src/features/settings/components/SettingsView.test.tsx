@@ -9,7 +9,7 @@ import {
 } from "@testing-library/react";
 import type { ComponentProps } from "react";
 import { describe, expect, it, vi } from "vitest";
-import type { AppSettings } from "../../../types";
+import type { AppSettings, WorkspaceInfo } from "../../../types";
 import { SettingsView } from "./SettingsView";
 
 vi.mock("@tauri-apps/plugin-dialog", () => ({
@@ -64,6 +64,7 @@ const baseSettings: AppSettings = {
   composerListContinuation: false,
   composerCodeBlockCopyUseModifier: false,
   workspaceGroups: [],
+  workspaceDiscoveryRoots: [],
 };
 
 const createDoctorResult = () => ({
@@ -79,19 +80,28 @@ const renderDisplaySection = (
     reduceTransparency?: boolean;
     onUpdateAppSettings?: ComponentProps<typeof SettingsView>["onUpdateAppSettings"];
     onToggleTransparency?: ComponentProps<typeof SettingsView>["onToggleTransparency"];
+    workspaces?: WorkspaceInfo[];
+    onToggleWorkspaceNotifications?: ComponentProps<
+      typeof SettingsView
+    >["onToggleWorkspaceNotifications"];
   } = {},
 ) => {
   cleanup();
   const onUpdateAppSettings =
     options.onUpdateAppSettings ?? vi.fn().mockResolvedValue(undefined);
   const onToggleTransparency = options.onToggleTransparency ?? vi.fn();
+  const onToggleWorkspaceNotifications =
+    options.onToggleWorkspaceNotifications ?? vi.fn().mockResolvedValue(undefined);
+  const workspaces = options.workspaces ?? [];
   const props: ComponentProps<typeof SettingsView> = {
     reduceTransparency: options.reduceTransparency ?? false,
     onToggleTransparency,
     appSettings: { ...baseSettings, ...options.appSettings },
     onUpdateAppSettings,
     workspaceGroups: [],
-    groupedWorkspaces: [],
+    groupedWorkspaces: workspaces.length
+      ? [{ id: null, name: "Ungrouped", workspaces }]
+      : [],
     ungroupedLabel: "Ungrouped",
     onClose: vi.fn(),
     onMoveWorkspace: vi.fn(),
@@ -103,6 +113,8 @@ const renderDisplaySection = (
     onAssignWorkspaceGroup: vi.fn().mockResolvedValue(null),
     onRunDoctor: vi.fn().mockResolvedValue(createDoctorResult()),
     onUpdateWorkspaceClaudeBin: vi.fn().mockResolvedValue(undefined),
+    onUpdateWorkspaceModel: vi.fn().mockResolvedValue(undefined),
+    onToggleWorkspaceNotifications,
     scaleShortcutTitle: "Scale shortcut",
     scaleShortcutText: "Use Command +/-",
     onTestNotificationSound: vi.fn(),
@@ -257,6 +269,39 @@ describe("SettingsView Display", () => {
       );
     });
   });
+
+  it("mutes desktop notifications per workspace", async () => {
+    const onToggleWorkspaceNotifications = vi.fn().mockResolvedValue(undefined);
+    renderDisplaySection({
+      onToggleWorkspaceNotifications,
+      workspaces: [
+        {
+          id: "ws-1",
+          name: "api",
+          path: "/tmp/api",
+          connected: true,
+          settings: { sidebarCollapsed: false },
+        },
+        {
+          id: "ws-2",
+          name: "web",
+          path: "/tmp/web",
+          connected: true,
+          settings: { sidebarCollapsed: false, notificationsMuted: true },
+        },
+      ],
+    });
+
+    const web = screen.getByRole("button", { name: "Notifications for web" });
+    expect(web.getAttribute("aria-pressed")).toBe("false");
+    fireEvent.click(screen.getByRole("button", { name: "Notifications for api" }));
+    fireEvent.click(web);
+
+    await waitFor(() => {
+      expect(onToggleWorkspaceNotifications).toHaveBeenCalledWith("ws-1", true);
+      expect(onToggleWorkspaceNotifications).toHaveBeenCalledWith("ws-2", false);
+    });
+  });
 });
 
 describe("SettingsView Shortcuts", () => {
@@ -281,6 +326,8 @@ describe("SettingsView Shortcuts", () => {
         onUpdateAppSettings={vi.fn().mockResolvedValue(undefined)}
         onRunDoctor={vi.fn().mockResolvedValue(createDoctorResult())}
         onUpdateWorkspaceClaudeBin={vi.fn().mockResolvedValue(undefined)}
+        onUpdateWorkspaceModel={vi.fn().mockResolvedValue(undefined)}
+        onToggleWorkspaceNotifications={vi.fn().mockResolvedValue(undefined)}
         scaleShortcutTitle="Scale shortcut"
         scaleShortcutText="Use Command +/-"
         onTestNotificationSound={vi.fn()}
@@ -319,6 +366,8 @@ describe("SettingsView Shortcuts", () => {
         onUpdateAppSettings={vi.fn().mockResolvedValue(undefined)}
         onRunDoctor={vi.fn().mockResolvedValue(createDoctorResult())}
         onUpdateWorkspaceClaudeBin={vi.fn().mockResolvedValue(undefined)}
+        onUpdateWorkspaceModel={vi.fn().mockResolvedValue(undefined)}
+        onToggleWorkspaceNotifications={vi.fn().mockResolvedValue(undefined)}
         scaleShortcutTitle="Scale shortcut"
         scaleShortcutText="Use Command +/-"
         onTestNotificationSound={vi.fn()}
@@ -118,6 +118,8 @@ export type SettingsViewProps = {
   onUpdateAppSettings: (next: AppSettings) => Promise<void>;
   onRunDoctor: (claudeBin: string | null) => Promise<ClaudeDoctorResult>;
   onUpdateWorkspaceClaudeBin: (id: string, claudeBin: string | null) => Promise<void>;
+  onUpdateWorkspaceModel: (id: string, model: string | null) => Promise<void>;
+  onToggleWorkspaceNotifications: (id: string, muted: boolean) => Promise<void>;
   scaleShortcutTitle: string;
   scaleShortcutText: string;
   onTestNotificationSound: () => void;
@@ -196,6 +198,8 @@ export function SettingsView({
   onUpdateAppSettings,
   onRunDoctor,
   onUpdateWorkspaceClaudeBin,
+  onUpdateWorkspaceModel,
+  onToggleWorkspaceNotifications,
   scaleShortcutTitle,
   scaleShortcutText,
   onTestNotificationSound,
@@ -218,6 +222,7 @@ export function SettingsView({
     appSettings.codeFontSize,
   );
   const [overrideDrafts, setOverrideDrafts] = useState<Record<string, string>>({});
+  const [modelDrafts, setModelDrafts] = useState<Record<string, string>>({});
   const [groupDrafts, setGroupDrafts] = useState<Record<string, string>>({});
   const [newGroupName, setNewGroupName] = useState("");
   const [groupError, setGroupError] = useState<string | null>(null);
@@ -355,6 +360,13 @@ export function SettingsView({
       });
       return next;
     });
+    setModelDrafts((prev) => {
+      const next: Record<string, string> = {};
+      projects.forEach((workspace) => {
+        next[workspace.id] = prev[workspace.id] ?? workspace.model ?? "";
+      });
+      return next;
+    });
   }, [projects]);
 
   useEffect(() => {
@@ -1174,6 +1186,36 @@ export function SettingsView({
                     Test sound
                   </button>
                 </div>
+                <div className="settings-subsection-title">Desktop notifications</div>
+                <div className="settings-subsection-subtitle">
+                  Notify when a turn finishes, fails, or waits on a permission prompt while the
+                  window is unfocused.
+                </div>
+                {projects.map((workspace) => {
+                  const enabled = !workspace.settings.notificationsMuted;
+                  return (
+                    <div key={workspace.id} className="settings-toggle-row">
+                      <div>
+                        <div className="settings-toggle-title">{workspace.name}</div>
+                        <div className="settings-toggle-subtitle">{workspace.path}</div>
+                      </div>
+                      <button
+                        type="button"
+                        className={`settings-toggle ${enabled ? "on" : ""}`}
+                        onClick={() =>
+                          void onToggleWorkspaceNotifications(workspace.id, enabled)
+                        }
+                        aria-pressed={enabled}
+                        aria-label={`Notifications for ${workspace.name}`}
+                      >
+                        <span className="settings-toggle-knob" />
+                      </button>
+                    </div>
+                  );
+                })}
+                {projects.length === 0 && (
+                  <div className="settings-empty">No projects yet.</div>
+                )}
               </section>
             )}
             {activeSection === "composer" && (
@@ -2150,6 +2192,26 @@ export function SettingsView({
                               await onUpdateWorkspaceClaudeBin(workspace.id, nextValue);
                             }}
                           />
+                          <input
+                            className="settings-input settings-input--compact"
+                            value={modelDrafts[workspace.id] ?? ""}
+                            placeholder="Default model"
+                            aria-label={`Model for ${workspace.name}`}
+                            onChange={(event) =>
+                              setModelDrafts((prev) => ({
+                                ...prev,
+                                [workspace.id]: event.target.value,
+                              }))
+                            }
+                            onBlur={async () => {
+                              const draft = modelDrafts[workspace.id] ?? "";
+                              const nextValue = draft.trim() || null;
+                              if (nextValue === (workspace.model ?? null)) {
+                                return;
+                              }
+                              await onUpdateWorkspaceModel(workspace.id, nextValue);
+                            }}
+                          />
                           <button
                             type="button"
                             className="ghost"
@@ -57,6 +57,7 @@ const defaultSettings: AppSettings = {
   composerListContinuation: false,
   composerCodeBlockCopyUseModifier: false,
   workspaceGroups: [],
+  workspaceDiscoveryRoots: [],
 };
 
 function normalizeAppSettings(settings: AppSettings): AppSettings {
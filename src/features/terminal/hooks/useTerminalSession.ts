@@ -5,7 +5,12 @@ import { FitAddon } from "@xterm/addon-fit";
 import "@xterm/xterm/css/xterm.css";
 import type { DebugEntry, TerminalStatus, WorkspaceInfo } from "../../../types";
 import { buildErrorDebugEntry } from "../../../utils/debugEntries";
-import { subscribeTerminalOutput, type TerminalOutputEvent } from "../../../services/events";
+import {
+  subscribeTerminalExit,
+  subscribeTerminalOutput,
+  type TerminalExitEvent,
+  type TerminalOutputEvent,
+} from "../../../services/events";
 import {
   openTerminalSession,
   resizeTerminalSession,
@@ -135,6 +140,35 @@ export function useTerminalSession({
     };
   }, [onDebug, writeToTerminal]);
 
+  useEffect(() => {
+    const unlisten = subscribeTerminalExit(
+      (payload: TerminalExitEvent) => {
+        const { workspaceId, terminalId, exitCode } = payload;
+        const key = `${workspaceId}:${terminalId}`;
+        const notice =
+          exitCode === null
+            ? "\r\n[Process exited]\r\n"
+            : `\r\n[Process exited with code ${exitCode}]\r\n`;
+        openedSessionsRef.current.delete(key);
+        outputBuffersRef.current.set(
+          key,
+          appendBuffer(outputBuffersRef.current.get(key), notice),
+        );
+        if (activeKeyRef.current === key) {
+          writeToTerminal(notice);
+        }
+      },
+      {
+        onError: (error) => {
+          onDebug?.(buildErrorDebugEntry("terminal listen error", error));
+        },
+      },
+    );
+    return () => {
+      unlisten();
+    };
+  }, [onDebug, writeToTerminal]);
+
   useEffect(() => {
     if (!isVisible) {
       inputDisposableRef.current?.dispose();
@@ -32,6 +32,8 @@ vi.mock("../../../services/tauri", () => ({
   resumeThread: vi.fn(),
   archiveThread: vi.fn(),
   interruptTurn: vi.fn(),
+  startTranscriptTail: vi.fn(() => Promise.resolve({})),
+  stopTranscriptTail: vi.fn(() => Promise.resolve({})),
 }));
 
 const workspace: WorkspaceInfo = {
@@ -5,7 +5,10 @@ import type {
   ConversationItem,
   CustomPromptOption,
   DebugEntry,
+  PermissionDecision,
   PermissionDenial,
+  PermissionRequest,
+  RateLimitWarning,
   RequestUserInputRequest,
   ThreadSummary,
   ThreadTokenUsage,
@@ -16,10 +19,13 @@ import type {
 } from "../../../types";
 import {
   type ApprovalRuleInfo,
+  getApprovalCommandInfo,
+  matchesCommandPrefix,
   normalizeCommandTokens,
 } from "../../../utils/approvalRules";
 import {
   rememberApprovalRule,
+  respondToPermissionRequest,
   sendUserMessage as sendUserMessageService,
   startReview as startReviewService,
   startThread as startThreadService,
@@ -27,6 +33,8 @@ import {
   resumeThread as resumeThreadService,
   archiveThread as archiveThreadService,
   interruptTurn as interruptTurnService,
+  startTranscriptTail,
+  stopTranscriptTail,
 } from "../../../services/tauri";
 import { useAppServerEvents } from "../../app/hooks/useAppServerEvents";
 import {
@@ -374,6 +382,9 @@ export function useThreads({
   const customNamesRef = useRef<CustomNamesMap>({});
   const approvalAllowlistRef = useRef<Record<string, string[][]>>({});
   const lastPromptByThreadRef = useRef<Record<string, LastPrompt>>({});
+  const refreshThreadRef = useRef<
+    ((workspaceId: string, threadId: string) => Promise<string | null>) | null
+  >(null);
 
   useEffect(() => {
     if (typeof window === "undefined") {
@@ -680,12 +691,51 @@ export function useThreads({
     }
   }, []);
 
+  const answerPermissionRequest = useCallback(
+    async (request: PermissionRequest, decision: PermissionDecision) => {
+      dispatch({ type: "removePermissionRequest", requestId: request.id });
+      try {
+        await respondToPermissionRequest(
+          request.workspace_id,
+          request.thread_id,
+          request.id,
+          decision,
+        );
+      } catch (error) {
+        onDebug?.({
+          id: `${Date.now()}-client-permission-response-error`,
+          timestamp: Date.now(),
+          source: "error",
+          label: "permission response error",
+          payload: error instanceof Error ? error.message : String(error),
+        });
+      }
+    },
+    [onDebug],
+  );
+
   const handlers = useMemo(
     () => ({
       onWorkspaceConnected,
       onRequestUserInput: (request: RequestUserInputRequest) => {
         dispatch({ type: "addUserInputRequest", request });
       },
+      onPermissionRequest: (request: PermissionRequest) => {
+        // Commands the user already allowed by prefix this session don't need a prompt
+        const commandInfo = getApprovalCommandInfo({
+          ...(request.tool_input ?? {}),
+          tool_name: request.tool_name,
+        });
+        const allowlist = approvalAllowlistRef.current[request.workspace_id] ?? [];
+        if (commandInfo && matchesCommandPrefix(commandInfo.tokens, allowlist)) {
+          void answerPermissionRequest(request, { behavior: "allow" });
+          return;
+        }
+        dispatch({ type: "addPermissionRequest", request });
+      },
+      onPermissionResolved: (_workspaceId: string, requestId: string) => {
+        dispatch({ type: "removePermissionRequest", requestId });
+      },
       onPermissionDenied: ({
         workspaceId,
         threadId,
@@ -930,6 +980,73 @@ export function useThreads({
           tokenUsage: normalizeTokenUsage(tokenUsage),
         });
       },
+      onSessionInitialized: (
+        workspaceId: string,
+        threadId: string,
+        session: { sessionId: string; model: string | null },
+      ) => {
+        if (!session.model) {
+          return;
+        }
+        dispatch({ type: "ensureThread", workspaceId, threadId });
+        dispatch({ type: "setThreadModel", threadId, model: session.model });
+      },
+      onSessionHibernated: (
+        workspaceId: string,
+        threadId: string,
+        idleMinutes: number,
+      ) => {
+        dispatch({ type: "ensureThread", workspaceId, threadId });
+        dispatch({
+          type: "addAssistantMessage",
+          threadId,
+          text: `Session hibernated after ${idleMinutes} minutes idle. Your next message resumes it.`,
+        });
+      },
+      onRateLimitWarning: (
+        workspaceId: string,
+        threadId: string,
+        warning: RateLimitWarning,
+      ) => {
+        dispatch({ type: "ensureThread", workspaceId, threadId });
+        const seconds = Math.ceil(warning.backoffMs / 1000);
+        // Quota warnings come with no backoff: the turn is still going through
+        const text =
+          seconds > 0
+            ? `${warning.message}. Cooling down for ${seconds}s before sending again.`
+            : `${warning.message}.`;
+        dispatch({ type: "addAssistantMessage", threadId, text });
+      },
+      onTurnRetrying: (
+        workspaceId: string,
+        threadId: string,
+        retry: { attempt: number; maxAttempts: number | null; delayMs: number; reason: string },
+      ) => {
+        dispatch({ type: "ensureThread", workspaceId, threadId });
+        const attempts = retry.maxAttempts
+          ? `attempt ${retry.attempt} of ${retry.maxAttempts}`
+          : `attempt ${retry.attempt}`;
+        const seconds = Math.ceil(retry.delayMs / 1000);
+        dispatch({
+          type: "addAssistantMessage",
+          threadId,
+          text: `${retry.reason}. Retrying in ${seconds}s (${attempts}).`,
+        });
+      },
+      onTranscriptAppended: (
+        workspaceId: string,
+        threadId: string,
+        update: { entries: Record<string, unknown>[]; external: boolean },
+      ) => {
+        // Turns the monitor runs already stream in live; only catch up on the rest
+        if (!update.external) {
+          return;
+        }
+        loadedThreads.current[threadId] = false;
+        if (threadId === activeThreadId) {
+          void refreshThreadRef.current?.(workspaceId, threadId);
+        }
+      },
       onTurnError: (
         workspaceId: string,
         threadId: string,
@@ -956,6 +1073,7 @@ export function useThreads({
     }),
     [
       activeThreadId,
+      answerPermissionRequest,
       getCustomName,
       onWorkspaceConnected,
       handleItemUpdate,
@@ -1146,6 +1264,22 @@ export function useThreads({
     [resumeThreadForWorkspace],
   );
 
+  useEffect(() => {
+    refreshThreadRef.current = refreshThread;
+  }, [refreshThread]);
+
+  useEffect(() => {
+    if (!activeWorkspaceId || !activeThreadId) {
+      return;
+    }
+    const workspaceId = activeWorkspaceId;
+    const threadId = activeThreadId;
+    startTranscriptTail(workspaceId, threadId).catch(() => {});
+    return () => {
+      stopTranscriptTail(workspaceId, threadId).catch(() => {});
+    };
+  }, [activeWorkspaceId, activeThreadId]);
+
   const resetWorkspaceThreads = useCallback(
     (workspaceId: string) => {
       const threadIds = new Set<string>();
@@ -1586,6 +1720,11 @@ export function useThreads({
           return;
         }
         const result = (response?.result ?? response) as Record<string, unknown>;
+        if (result?.queued) {
+          // The thread is still busy; the backend starts this turn once it finishes
+          // and reports it through the usual turn/started event.
+          return;
+        }
         const turn = (result?.turn ?? response?.turn ?? null) as
           | Record<string, unknown>
           | null;
@@ -1901,6 +2040,13 @@ export function useThreads({
     dispatch({ type: "removePermissionDenial", denialId: denial.id });
   }, []);
 
+  const handlePermissionDecision = useCallback(
+    (request: PermissionRequest, decision: PermissionDecision) => {
+      void answerPermissionRequest(request, decision);
+    },
+    [answerPermissionRequest],
+  );
+
   const setActiveThreadId = useCallback(
     (threadId: string | null, workspaceId?: string) => {
       const targetId = workspaceId ?? activeWorkspaceId;
@@ -1958,6 +2104,7 @@ export function useThreads({
     setActiveThreadId,
     activeItems,
     permissionDenials: state.permissionDenials,
+    permissionRequests: state.permissionRequests,
     userInputRequests: state.userInputRequests,
     threadsByWorkspace: state.threadsByWorkspace,
     threadParentById: state.threadParentById,
@@ -1967,6 +2114,7 @@ export function useThreads({
     threadListCursorByWorkspace: state.threadListCursorByWorkspace,
     activeTurnIdByThread: state.activeTurnIdByThread,
     tokenUsageByThread: state.tokenUsageByThread,
+    activeModelByThread: state.activeModelByThread,
     planByThread: state.planByThread,
     lastAgentMessageByThread: state.lastAgentMessageByThread,
     interruptTurn,
@@ -1988,6 +2136,7 @@ export function useThreads({
     handlePermissionRemember,
     handlePermissionRetry,
     handlePermissionDismiss,
+    handlePermissionDecision,
     handleUserInputSubmit,
   };
 }
@@ -1,6 +1,7 @@
 import type {
   ConversationItem,
   PermissionDenial,
+  PermissionRequest,
   RequestUserInputRequest,
   ThreadSummary,
   ThreadTokenUsage,
@@ -116,8 +117,10 @@ export type ThreadState = {
   threadListCursorByWorkspace: Record<string, string | null>;
   activeTurnIdByThread: Record<string, string | null>;
   permissionDenials: PermissionDenial[];
+  permissionRequests: PermissionRequest[];
   userInputRequests: RequestUserInputRequest[];
   tokenUsageByThread: Record<string, ThreadTokenUsage>;
+  activeModelByThread: Record<string, string>;
   planByThread: Record<string, TurnPlan | null>;
   lastAgentMessageByThread: Record<string, { text: string; timestamp: number }>;
 };
@@ -192,11 +195,14 @@ export type ThreadAction =
     }
   | { type: "addPermissionDenials"; denials: PermissionDenial[] }
   | { type: "removePermissionDenial"; denialId: string }
+  | { type: "addPermissionRequest"; request: PermissionRequest }
+  | { type: "removePermissionRequest"; requestId: string }
   | { type: "addUserInputRequest"; request: RequestUserInputRequest }
   | { type: "removeUserInputRequest"; requestId: number; workspaceId: string }
   | { type: "clearUserInputRequestsForThread"; threadId: string; workspaceId: string }
   | { type: "setThreadTokenUsage"; threadId: string; tokenUsage: ThreadTokenUsage }
   | { type: "setActiveTurnId"; threadId: string; turnId: string | null }
+  | { type: "setThreadModel"; threadId: string; model: string }
   | { type: "setThreadPlan"; threadId: string; plan: TurnPlan | null }
   | { type: "clearThreadPlan"; threadId: string }
   | {
@@ -219,8 +225,10 @@ export const initialState: ThreadState = {
   threadListCursorByWorkspace: {},
   activeTurnIdByThread: {},
   permissionDenials: [],
+  permissionRequests: [],
   userInputRequests: [],
   tokenUsageByThread: {},
+  activeModelByThread: {},
   planByThread: {},
   lastAgentMessageByThread: {},
 };
@@ -837,6 +845,27 @@ export function threadReducer(state: ThreadState, action: ThreadAction): ThreadS
         permissionDenials: filtered,
       };
     }
+    case "addPermissionRequest": {
+      if (state.permissionRequests.some((item) => item.id === action.request.id)) {
+        return state;
+      }
+      return {
+        ...state,
+        permissionRequests: [...state.permissionRequests, action.request],
+      };
+    }
+    case "removePermissionRequest": {
+      const filtered = state.permissionRequests.filter(
+        (item) => item.id !== action.requestId,
+      );
+      if (filtered.length === state.permissionRequests.length) {
+        return state;
+      }
+      return {
+        ...state,
+        permissionRequests: filtered,
+      };
+    }
     case "addUserInputRequest": {
       const exists = state.userInputRequests.some(
         (item) =>
@@ -915,6 +944,17 @@ export function threadReducer(state: ThreadState, action: ThreadAction): ThreadS
           [action.threadId]: action.tokenUsage,
         },
       };
+    case "setThreadModel":
+      if (state.activeModelByThread[action.threadId] === action.model) {
+        return state;
+      }
+      return {
+        ...state,
+        activeModelByThread: {
+          ...state.activeModelByThread,
+          [action.threadId]: action.model,
+        },
+      };
     case "setThreadPlan":
       return {
         ...state,
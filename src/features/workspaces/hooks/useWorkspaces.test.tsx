@@ -23,6 +23,7 @@ vi.mock("../../../services/tauri", () => ({
   removeWorkspace: vi.fn(),
   removeWorktree: vi.fn(),
   updateWorkspaceClaudeBin: vi.fn(),
+  updateWorkspaceModel: vi.fn(),
   updateWorkspaceSettings: vi.fn(),
 }));
 
@@ -21,6 +21,7 @@ import {
   renameWorktree as renameWorktreeService,
   renameWorktreeUpstream as renameWorktreeUpstreamService,
   updateWorkspaceClaudeBin as updateWorkspaceClaudeBinService,
+  updateWorkspaceModel as updateWorkspaceModelService,
   updateWorkspaceSettings as updateWorkspaceSettingsService,
 } from "../../../services/tauri";
 
@@ -464,6 +465,45 @@ export function useWorkspaces(options: UseWorkspacesOptions = {}) {
     }
   }
 
+  async function updateWorkspaceModel(workspaceId: string, model: string | null) {
+    onDebug?.({
+      id: `${Date.now()}-client-update-workspace-model`,
+      timestamp: Date.now(),
+      source: "client",
+      label: "workspace/model",
+      payload: { workspaceId, model },
+    });
+    const previous = workspaces.find((entry) => entry.id === workspaceId) ?? null;
+    if (previous) {
+      setWorkspaces((prev) =>
+        prev.map((entry) =>
+          entry.id === workspaceId ? { ...entry, model } : entry,
+        ),
+      );
+    }
+    try {
+      const updated = await updateWorkspaceModelService(workspaceId, model);
+      setWorkspaces((prev) =>
+        prev.map((entry) => (entry.id === workspaceId ? updated : entry)),
+      );
+      return updated;
+    } catch (error) {
+      if (previous) {
+        setWorkspaces((prev) =>
+          prev.map((entry) => (entry.id === workspaceId ? previous : entry)),
+        );
+      }
+      onDebug?.({
+        id: `${Date.now()}-client-update-workspace-model-error`,
+        timestamp: Date.now(),
+        source: "error",
+        label: "workspace/model error",
+        payload: error instanceof Error ? error.message : String(error),
+      });
+      throw error;
+    }
+  }
+
   const updateWorkspaceGroups = useCallback(
     async (nextGroups: WorkspaceGroup[]) => {
       if (!appSettings || !onUpdateAppSettings) {
@@ -831,6 +871,7 @@ export function useWorkspaces(options: UseWorkspacesOptions = {}) {
     markWorkspaceConnected,
     updateWorkspaceSettings,
     updateWorkspaceClaudeBin,
+    updateWorkspaceModel,
     createWorkspaceGroup,
     renameWorkspaceGroup,
     moveWorkspaceGroup,
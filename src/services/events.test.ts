@@ -4,9 +4,19 @@ import { listen } from "@tauri-apps/api/event";
 import type { AppServerEvent } from "../types";
 import {
   subscribeAppServerEvents,
+  subscribeBatchProgress,
+  subscribeCliUpdateProgress,
   subscribeMenuCycleModel,
   subscribeMenuNewAgent,
+  subscribeSessionDisconnected,
   subscribeTerminalOutput,
+  subscribeTurnQueueUpdated,
+} from "./events";
+import type {
+  BatchProgressEvent,
+  CliUpdateProgressEvent,
+  SessionDisconnectedEvent,
+  TurnQueueUpdatedEvent,
 } from "./events";
 
 vi.mock("@tauri-apps/api/event", () => ({
@@ -89,6 +99,122 @@ describe("events subscriptions", () => {
     cleanup();
   });
 
+  it("delivers session disconnect payloads", async () => {
+    let listener: EventCallback<SessionDisconnectedEvent> = () => {};
+    const unlisten = vi.fn();
+
+    vi.mocked(listen).mockImplementation((eventName, handler) => {
+      expect(eventName).toBe("session-disconnected");
+      listener = handler as EventCallback<SessionDisconnectedEvent>;
+      return Promise.resolve(unlisten);
+    });
+
+    const onEvent = vi.fn();
+    const cleanup = subscribeSessionDisconnected(onEvent);
+    const payload: SessionDisconnectedEvent = {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+      exitCode: 1,
+      reconnected: true,
+      error: null,
+    };
+    listener({ event: "session-disconnected", id: 1, payload });
+    expect(onEvent).toHaveBeenCalledWith(payload);
+
+    cleanup();
+  });
+
+  it("delivers turn queue updates", async () => {
+    let listener: EventCallback<TurnQueueUpdatedEvent> = () => {};
+    const unlisten = vi.fn();
+
+    vi.mocked(listen).mockImplementation((eventName, handler) => {
+      expect(eventName).toBe("turn-queue-updated");
+      listener = handler as EventCallback<TurnQueueUpdatedEvent>;
+      return Promise.resolve(unlisten);
+    });
+
+    const onEvent = vi.fn();
+    const cleanup = subscribeTurnQueueUpdated(onEvent);
+    const payload: TurnQueueUpdatedEvent = {
+      workspaceId: "ws-1",
+      queue: [
+        {
+          id: "prompt-1",
+          threadId: "thread-1",
+          preview: "Run the tests",
+          position: 1,
+          queuedAt: 1,
+        },
+      ],
+    };
+    listener({ event: "turn-queue-updated", id: 1, payload });
+    expect(onEvent).toHaveBeenCalledWith(payload);
+
+    cleanup();
+  });
+
+  it("delivers batch progress", async () => {
+    let listener: EventCallback<BatchProgressEvent> = () => {};
+    const unlisten = vi.fn();
+
+    vi.mocked(listen).mockImplementation((eventName, handler) => {
+      expect(eventName).toBe("batch-progress");
+      listener = handler as EventCallback<BatchProgressEvent>;
+      return Promise.resolve(unlisten);
+    });
+
+    const onEvent = vi.fn();
+    const cleanup = subscribeBatchProgress(onEvent);
+    const payload: BatchProgressEvent = {
+      workspaceId: "ws-2",
+      batch: {
+        id: "batch-1",
+        prompt: "Bump serde",
+        concurrency: 2,
+        status: "running",
+        createdAt: 1,
+        finishedAt: null,
+        total: 2,
+        completed: 0,
+        succeeded: 0,
+        failed: 0,
+        totalCostUsd: 0,
+        items: [],
+      },
+    };
+    listener({ event: "batch-progress", id: 1, payload });
+    expect(onEvent).toHaveBeenCalledWith(payload);
+
+    cleanup();
+  });
+
+  it("delivers CLI update progress", async () => {
+    let listener: EventCallback<CliUpdateProgressEvent> = () => {};
+    const unlisten = vi.fn();
+
+    vi.mocked(listen).mockImplementation((eventName, handler) => {
+      expect(eventName).toBe("cli-update-progress");
+      listener = handler as EventCallback<CliUpdateProgressEvent>;
+      return Promise.resolve(unlisten);
+    });
+
+    const onEvent = vi.fn();
+    const cleanup = subscribeCliUpdateProgress(onEvent);
+    const payload: CliUpdateProgressEvent = {
+      updateId: "update-1",
+      path: "/usr/local/bin/claude",
+      stage: "output",
+      line: "changed 3 packages in 4s",
+      version: null,
+      error: null,
+    };
+    listener({ event: "cli-update-progress", id: 1, payload });
+    expect(onEvent).toHaveBeenCalledWith(payload);
+
+    cleanup();
+  });
+
   it("reports listen errors through options", async () => {
     const error = new Error("nope");
     vi.mocked(listen).mockRejectedValueOnce(error);
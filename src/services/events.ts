@@ -1,5 +1,12 @@
 import { listen } from "@tauri-apps/api/event";
-import type { AppServerEvent, DictationEvent, DictationModelStatus } from "../types";
+import type {
+  ActivityEntry,
+  AppServerEvent,
+  BatchRun,
+  DictationEvent,
+  DictationModelStatus,
+  PipelineRun,
+} from "../types";
 
 export type Unsubscribe = () => void;
 
@@ -9,6 +16,52 @@ export type TerminalOutputEvent = {
   data: string;
 };
 
+export type TerminalExitEvent = {
+  workspaceId: string;
+  terminalId: string;
+  exitCode: number | null;
+};
+
+export type SessionDisconnectedEvent = {
+  workspaceId: string;
+  threadId: string;
+  exitCode: number | null;
+  reconnected: boolean;
+  error: string | null;
+};
+
+export type QueuedPrompt = {
+  id: string;
+  threadId: string;
+  preview: string;
+  position: number;
+  queuedAt: number;
+};
+
+export type TurnQueueUpdatedEvent = {
+  workspaceId: string;
+  queue: QueuedPrompt[];
+};
+
+export type BatchProgressEvent = {
+  workspaceId: string | null;
+  batch: BatchRun;
+};
+
+export type PipelineProgressEvent = {
+  workspaceId: string | null;
+  pipeline: PipelineRun;
+};
+
+export type CliUpdateProgressEvent = {
+  updateId: string;
+  path: string;
+  stage: "started" | "output" | "finished" | "failed";
+  line: string | null;
+  version: string | null;
+  error: string | null;
+};
+
 type SubscriptionOptions = {
   onError?: (error: unknown) => void;
 };
@@ -80,6 +133,21 @@ const appServerHub = createEventHub<AppServerEvent>("app-server-event");
 const dictationDownloadHub = createEventHub<DictationModelStatus>("dictation-download");
 const dictationEventHub = createEventHub<DictationEvent>("dictation-event");
 const terminalOutputHub = createEventHub<TerminalOutputEvent>("terminal-output");
+const terminalExitHub = createEventHub<TerminalExitEvent>("terminal-exit");
+const activityHub = createEventHub<ActivityEntry>("activity");
+const sessionDisconnectedHub = createEventHub<SessionDisconnectedEvent>(
+  "session-disconnected",
+);
+const turnQueueUpdatedHub = createEventHub<TurnQueueUpdatedEvent>(
+  "turn-queue-updated",
+);
+const batchProgressHub = createEventHub<BatchProgressEvent>("batch-progress");
+const pipelineProgressHub = createEventHub<PipelineProgressEvent>(
+  "pipeline-progress",
+);
+const cliUpdateProgressHub = createEventHub<CliUpdateProgressEvent>(
+  "cli-update-progress",
+);
 const updaterCheckHub = createEventHub<void>("updater-check");
 const menuNewAgentHub = createEventHub<void>("menu-new-agent");
 const menuNewWorktreeAgentHub = createEventHub<void>("menu-new-worktree-agent");
@@ -129,6 +197,55 @@ export function subscribeTerminalOutput(
   return terminalOutputHub.subscribe(onEvent, options);
 }
 
+export function subscribeTerminalExit(
+  onEvent: (event: TerminalExitEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return terminalExitHub.subscribe(onEvent, options);
+}
+
+export function subscribeActivity(
+  onEvent: (event: ActivityEntry) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return activityHub.subscribe(onEvent, options);
+}
+
+export function subscribeSessionDisconnected(
+  onEvent: (event: SessionDisconnectedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return sessionDisconnectedHub.subscribe(onEvent, options);
+}
+
+export function subscribeTurnQueueUpdated(
+  onEvent: (event: TurnQueueUpdatedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return turnQueueUpdatedHub.subscribe(onEvent, options);
+}
+
+export function subscribeBatchProgress(
+  onEvent: (event: BatchProgressEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return batchProgressHub.subscribe(onEvent, options);
+}
+
+export function subscribePipelineProgress(
+  onEvent: (event: PipelineProgressEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return pipelineProgressHub.subscribe(onEvent, options);
+}
+
+export function subscribeCliUpdateProgress(
+  onEvent: (event: CliUpdateProgressEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return cliUpdateProgressHub.subscribe(onEvent, options);
+}
+
 export function subscribeUpdaterCheck(
   onEvent: () => void,
   options?: SubscriptionOptions,
@@ -1,14 +1,105 @@
 import { beforeEach, describe, expect, it, vi } from "vitest";
 import { invoke } from "@tauri-apps/api/core";
 import {
+  addHook,
   addWorkspace,
+  addWorktree,
+  annotate,
+  applyRetentionPolicy,
+  archiveWorkspace,
+  aggregateWorkspaces,
+  archiveThread,
+  checkClaudeCliUpdates,
+  checkProviderConnectivity,
+  completePaths,
+  approvePlan,
+  attachExternalSession,
+  cancelOperation,
+  cancelPendingMessage,
+  createPipeline,
+  createPrFromTurn,
+  deleteSubagent,
+  discoverWorkspaces,
+  exportConfig,
+  exportSession,
+  exportUsage,
+  flushPendingMessages,
   getGitHubIssues,
   getGitLog,
+  getClaudeSettings,
+  getDiskUsage,
+  getEventStats,
   getGitStatus,
+  getMcpStatus,
+  getRateLimitStatus,
+  getSessionHealth,
+  getSessionLogs,
+  getActivity,
+  getSessionResources,
+  getTurnDiff,
+  getTurnEdits,
+  getTurnMetrics,
+  getUsageReport,
+  getTurnTimeline,
+  importConfig,
+  issueApiToken,
+  postIssueComment,
+  startIssueTurn,
+  killOrphanedSession,
+  killTerminalSession,
+  listApiAuditLog,
+  listBashDecisions,
+  listCompactions,
+  listMcpServers,
+  listPendingMessages,
+  listPendingInteractions,
+  listPendingPlans,
+  listSlashCommands,
+  listSubagents,
+  listThreadOwners,
+  listThreadSessions,
+  listWorkspaces,
+  listWorkspaceSessions,
+  listWorkspaceThreads,
+  focusWorkspace,
+  forkSession,
+  listSessionForks,
+  suspendWorkspaceSessions,
   stageGitAll,
+  takeOverThread,
+  startTranscriptTail,
+  testHook,
+  testProxyConnectivity,
+  unarchiveWorkspace,
+  updateClaudeCli,
+  respondToPermissionRequest,
+  revokeApiToken,
+  runDiskCleanup,
+  runDoctor,
+  runSlashCommand,
+  runSubagent,
+  savePromptTemplate,
+  overrideBudget,
+  rejectPlan,
+  replaySession,
+  revealRedactedSecret,
+  saveSchedule,
+  saveSubagent,
+  setPermissionPreset,
+  setStorageEncryption,
+  setWorkspacePinned,
+  setWorkspaceCredentials,
+  searchTranscripts,
+  searchAnnotations,
   respondToUserInputRequest,
+  runBatch,
   sendUserMessage,
+  saveContextPack,
+  estimateTokens,
   startReview,
+  updateClaudeSettings,
+  updateWorkspaceModel,
+  writeClaudeMd,
 } from "./tauri";
 
 vi.mock("@tauri-apps/api/core", () => ({
@@ -50,6 +141,1173 @@ describe("tauri invoke wrappers", () => {
     });
   });
 
+  it("maps workspace_id to workspaceId for workspace sessions", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await listWorkspaceSessions("ws-3");
+
+    expect(invokeMock).toHaveBeenCalledWith("list_workspace_sessions", {
+      workspaceId: "ws-3",
+    });
+  });
+
+  it("checks session health for one thread or the whole workspace", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await getSessionHealth("ws-5", "thread-5");
+    await getSessionHealth("ws-5");
+
+    expect(invokeMock).toHaveBeenCalledWith("session_health", {
+      workspaceId: "ws-5",
+      threadId: "thread-5",
+    });
+    expect(invokeMock).toHaveBeenCalledWith("session_health", {
+      workspaceId: "ws-5",
+      threadId: null,
+    });
+  });
+
+  it("archives workspaces and lists them on request", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce({});
+    invokeMock.mockResolvedValueOnce({});
+
+    await listWorkspaces();
+    await listWorkspaces(true);
+    await archiveWorkspace("ws-7");
+    await unarchiveWorkspace("ws-7");
+
+    expect(invokeMock).toHaveBeenCalledWith("list_workspaces", {
+      includeArchived: false,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("list_workspaces", {
+      includeArchived: true,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("archive_workspace", {
+      id: "ws-7",
+    });
+    expect(invokeMock).toHaveBeenCalledWith("unarchive_workspace", {
+      id: "ws-7",
+    });
+  });
+
+  it("pins a workspace for autostart", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+
+    await setWorkspacePinned("ws-7", true);
+
+    expect(invokeMock).toHaveBeenCalledWith("set_workspace_pinned", {
+      id: "ws-7",
+      pinned: true,
+    });
+  });
+
+  it("applies the retention policy", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+
+    await applyRetentionPolicy();
+
+    expect(invokeMock).toHaveBeenCalledWith("apply_retention_policy");
+  });
+
+  it("reports disk usage and runs cleanups", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+    invokeMock.mockResolvedValueOnce({});
+
+    await getDiskUsage(14);
+    await runDiskCleanup({ kind: "vacuumDatabase" });
+
+    expect(invokeMock).toHaveBeenCalledWith("get_disk_usage", {
+      olderThanDays: 14,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("run_disk_cleanup", {
+      action: { kind: "vacuumDatabase" },
+    });
+  });
+
+  it("exports and imports configuration bundles", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+    invokeMock.mockResolvedValueOnce({});
+    invokeMock.mockResolvedValueOnce({});
+
+    await exportConfig();
+    await exportConfig("/tmp/monitor.json");
+    await importConfig({ path: "/tmp/monitor.json" });
+
+    expect(invokeMock).toHaveBeenCalledWith("export_config", { path: null });
+    expect(invokeMock).toHaveBeenCalledWith("export_config", {
+      path: "/tmp/monitor.json",
+    });
+    expect(invokeMock).toHaveBeenCalledWith("import_config", {
+      bundle: null,
+      path: "/tmp/monitor.json",
+    });
+  });
+
+  it("lists compactions of a workspace or one thread", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await listCompactions("ws-1");
+    await listCompactions("ws-1", "thread-1");
+
+    expect(invokeMock).toHaveBeenCalledWith("list_compactions", {
+      workspaceId: "ws-1",
+      threadId: null,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("list_compactions", {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+    });
+  });
+
+  it("fetches session logs", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await getSessionLogs("ws-1");
+    await getSessionLogs("ws-1", 50);
+
+    expect(invokeMock).toHaveBeenCalledWith("get_session_logs", {
+      workspaceId: "ws-1",
+      lines: null,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("get_session_logs", {
+      workspaceId: "ws-1",
+      lines: 50,
+    });
+  });
+
+  it("fetches session resource usage", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await getSessionResources("ws-1");
+
+    expect(invokeMock).toHaveBeenCalledWith("get_session_resources", {
+      workspaceId: "ws-1",
+    });
+  });
+
+  it("completes @ paths with an optional limit", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([{ path: "src/", isDir: true }]);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await completePaths("ws-1", "src");
+    await completePaths("ws-1", "src/ma", 10);
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "complete_paths", {
+      workspaceId: "ws-1",
+      prefix: "src",
+      limit: null,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "complete_paths", {
+      workspaceId: "ws-1",
+      prefix: "src/ma",
+      limit: 10,
+    });
+  });
+
+  it("lists slash commands and runs one with arguments", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce({ result: {} });
+    invokeMock.mockResolvedValueOnce({ result: {} });
+
+    await listSlashCommands("ws-1");
+    await runSlashCommand("ws-1", "thread-1", "review", "src/lib.rs", {
+      accessMode: "read-only",
+    });
+    await runSlashCommand("ws-1", "thread-1", "standup");
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "list_slash_commands", {
+      workspaceId: "ws-1",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "run_slash_command", {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+      name: "review",
+      arguments: "src/lib.rs",
+      model: null,
+      accessMode: "read-only",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(3, "run_slash_command", {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+      name: "standup",
+      arguments: null,
+      model: null,
+      accessMode: null,
+    });
+  });
+
+  it("manages subagents and starts a turn for one", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce({ name: "reviewer" });
+    invokeMock.mockResolvedValueOnce({ deleted: true });
+    invokeMock.mockResolvedValueOnce({ result: {} });
+    const agent = {
+      name: "reviewer",
+      scope: "project" as const,
+      description: "Reviews code",
+      tools: ["Read", "Grep"],
+      prompt: "Review carefully.",
+    };
+
+    await listSubagents("ws-1");
+    await saveSubagent("ws-1", agent);
+    await deleteSubagent("ws-1", "user", "debugger");
+    await runSubagent("ws-1", "thread-1", "reviewer", "Check the diff");
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "list_subagents", {
+      workspaceId: "ws-1",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "save_subagent", {
+      workspaceId: "ws-1",
+      agent,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(3, "delete_subagent", {
+      workspaceId: "ws-1",
+      scope: "user",
+      name: "debugger",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(4, "run_subagent", {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+      name: "reviewer",
+      task: "Check the diff",
+      model: null,
+      accessMode: null,
+    });
+  });
+
+  it("lists, approves and rejects pending plans", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce(undefined);
+    invokeMock.mockResolvedValueOnce(undefined);
+
+    await listPendingPlans("ws-1");
+    await approvePlan("ws-1", "thread-1", "acceptEdits");
+    await rejectPlan("ws-1", "thread-2", "Keep the old API");
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "list_pending_plans", {
+      workspaceId: "ws-1",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "approve_plan", {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+      mode: "acceptEdits",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(3, "reject_plan", {
+      workspaceId: "ws-1",
+      threadId: "thread-2",
+      feedback: "Keep the old API",
+    });
+  });
+
+  it("lists pending interactions", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await listPendingInteractions("ws-1");
+
+    expect(invokeMock).toHaveBeenCalledWith("list_pending_interactions", {
+      workspaceId: "ws-1",
+    });
+  });
+
+  it("lists, cancels and flushes pending messages", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce(undefined);
+    invokeMock.mockResolvedValueOnce(undefined);
+
+    await listPendingMessages("ws-1");
+    await cancelPendingMessage("ws-1", "msg-1");
+    await flushPendingMessages("ws-1");
+
+    expect(invokeMock).toHaveBeenCalledWith("list_pending_messages", {
+      workspaceId: "ws-1",
+    });
+    expect(invokeMock).toHaveBeenCalledWith("cancel_pending_message", {
+      workspaceId: "ws-1",
+      messageId: "msg-1",
+    });
+    expect(invokeMock).toHaveBeenCalledWith("flush_pending_messages", {
+      workspaceId: "ws-1",
+    });
+  });
+
+  it("aggregates workspaces by tag or group", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await aggregateWorkspaces("tag", "client-a");
+    await aggregateWorkspaces("group");
+
+    expect(invokeMock).toHaveBeenCalledWith("aggregate_workspaces", {
+      by: "tag",
+      key: "client-a",
+    });
+    expect(invokeMock).toHaveBeenCalledWith("aggregate_workspaces", {
+      by: "group",
+      key: null,
+    });
+  });
+
+  it("lists the threads of a workspace", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ threads: [] });
+
+    await listWorkspaceThreads("ws-5");
+
+    expect(invokeMock).toHaveBeenCalledWith("list_workspace_threads", {
+      workspaceId: "ws-5",
+    });
+  });
+
+  it("suspends workspaces for focus mode", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({
+      suspended: [],
+      skipped: [],
+      resumed: [],
+      failed: {},
+    });
+    invokeMock.mockResolvedValueOnce({ workspaces: [] });
+
+    await suspendWorkspaceSessions("ws-5");
+    await focusWorkspace(null);
+
+    expect(invokeMock).toHaveBeenCalledWith("suspend_workspace_sessions", {
+      workspaceId: "ws-5",
+    });
+    expect(invokeMock).toHaveBeenCalledWith("focus_workspace", {
+      workspaceId: null,
+    });
+  });
+
+  it("forks a session at a message index", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ thread: {}, fork: {} });
+    invokeMock.mockResolvedValueOnce({ forks: [] });
+
+    await forkSession("ws-5", "session-1", 4);
+    await listSessionForks("ws-5");
+
+    expect(invokeMock).toHaveBeenCalledWith("fork_session", {
+      workspaceId: "ws-5",
+      sessionId: "session-1",
+      messageIndex: 4,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("list_session_forks", {
+      workspaceId: "ws-5",
+    });
+  });
+
+  it("reads and updates Claude settings by scope", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ effective: {}, scopes: [] });
+    invokeMock.mockResolvedValueOnce({});
+
+    await getClaudeSettings("ws-6");
+    await updateClaudeSettings("ws-6", "local", {
+      key: "model",
+      value: "claude-opus-4-5",
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("get_claude_settings", {
+      workspaceId: "ws-6",
+    });
+    expect(invokeMock).toHaveBeenCalledWith("update_claude_settings", {
+      workspaceId: "ws-6",
+      scope: "local",
+      update: { key: "model", value: "claude-opus-4-5" },
+    });
+  });
+
+  it("lists MCP servers and their session status", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await listMcpServers("ws-7");
+    await getMcpStatus("ws-7");
+
+    expect(invokeMock).toHaveBeenCalledWith("list_mcp_servers", {
+      workspaceId: "ws-7",
+    });
+    expect(invokeMock).toHaveBeenCalledWith("get_mcp_status", {
+      workspaceId: "ws-7",
+      threadId: null,
+    });
+  });
+
+  it("edits and dry-runs hooks", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ id: "PreToolUse/0/0" });
+    invokeMock.mockResolvedValueOnce({});
+    const hook = {
+      event: "PreToolUse" as const,
+      matcher: "Bash",
+      command: "./guard.sh",
+    };
+
+    await addHook("ws-8", "project", hook);
+    await testHook("ws-8", hook);
+
+    expect(invokeMock).toHaveBeenCalledWith("add_hook_definition", {
+      workspaceId: "ws-8",
+      scope: "project",
+      hook,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("test_hook", {
+      workspaceId: "ws-8",
+      hook,
+      payload: null,
+    });
+  });
+
+  it("writes CLAUDE.md with the modification time it was read at", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+    invokeMock.mockResolvedValueOnce({});
+
+    await writeClaudeMd("ws-9", "/repo/CLAUDE.md", "# Repo\n", 1700000000000);
+    await writeClaudeMd("ws-9", "/repo/CLAUDE.local.md", "notes");
+
+    expect(invokeMock).toHaveBeenCalledWith("write_claude_md", {
+      workspaceId: "ws-9",
+      path: "/repo/CLAUDE.md",
+      content: "# Repo\n",
+      expectedModifiedAt: 1700000000000,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("write_claude_md", {
+      workspaceId: "ws-9",
+      path: "/repo/CLAUDE.local.md",
+      content: "notes",
+      expectedModifiedAt: null,
+    });
+  });
+
+  it("starts transcript tails at the end of the file by default", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ path: "/t.jsonl", alreadyTailing: false });
+
+    await startTranscriptTail("ws-10", "thread-10");
+
+    expect(invokeMock).toHaveBeenCalledWith("start_transcript_tail", {
+      workspaceId: "ws-10",
+      threadId: "thread-10",
+      fromStart: false,
+    });
+  });
+
+  it("attaches external sessions by workspace and session id", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ path: "/s.jsonl", alreadyTailing: false });
+
+    await attachExternalSession("ws-11", "session-11");
+
+    expect(invokeMock).toHaveBeenCalledWith("attach_external_session", {
+      workspaceId: "ws-11",
+      sessionId: "session-11",
+    });
+  });
+
+  it("requests turn metrics with null filters by default", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ turns: [], workspaces: [] });
+
+    await getTurnMetrics();
+
+    expect(invokeMock).toHaveBeenCalledWith("get_turn_metrics", {
+      workspaceId: null,
+      limit: null,
+    });
+  });
+
+  it("requests a weekly usage report grouped by model", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ buckets: [], series: [] });
+
+    await getUsageReport({ since: 1000, interval: "week" }, "model");
+
+    expect(invokeMock).toHaveBeenCalledWith("get_usage_report", {
+      range: { since: 1000, interval: "week" },
+      groupBy: "model",
+      workspaceId: null,
+    });
+  });
+
+  it("fetches the tool-call timeline of a turn", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce(null);
+
+    await expect(getTurnTimeline("ws-12", "turn-3")).resolves.toBeNull();
+
+    expect(invokeMock).toHaveBeenCalledWith("get_turn_timeline", {
+      workspaceId: "ws-12",
+      turnId: "turn-3",
+    });
+  });
+
+  it("fetches the files a turn changed", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({
+      workspaceId: "ws-12",
+      threadId: "thread-1",
+      turnId: "turn-4",
+      createdAt: 1,
+      files: [{ path: "src/main.rs", status: "M", additions: 3, deletions: 1 }],
+      diff: "",
+      diffTruncated: false,
+    });
+
+    const diff = await getTurnDiff("ws-12", "turn-4");
+
+    expect(diff?.files[0].path).toBe("src/main.rs");
+    expect(invokeMock).toHaveBeenCalledWith("get_turn_diff", {
+      workspaceId: "ws-12",
+      turnId: "turn-4",
+    });
+  });
+
+  it("fetches a turn's file edits as hunks", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({
+      workspaceId: "ws-12",
+      threadId: "thread-1",
+      turnId: "turn-4",
+      createdAt: 1,
+      edits: [
+        {
+          toolUseId: "toolu_1",
+          path: "src/main.rs",
+          kind: "edit",
+          hunks: [
+            {
+              oldText: "run();",
+              newText: "run()?;",
+              replaceAll: false,
+              oldStart: 12,
+              oldLines: 1,
+              newStart: 12,
+              newLines: 1,
+            },
+          ],
+        },
+      ],
+    });
+
+    const edits = await getTurnEdits("ws-12", "turn-4");
+
+    expect(edits?.edits[0].hunks[0].oldStart).toBe(12);
+    expect(invokeMock).toHaveBeenCalledWith("get_turn_edits", {
+      workspaceId: "ws-12",
+      turnId: "turn-4",
+    });
+  });
+
+  it("lists a workspace's Bash policy decisions", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([
+      {
+        timestamp: 1,
+        workspaceId: "ws-3",
+        threadId: "thread-1",
+        turnId: "turn-1",
+        toolUseId: "toolu_1",
+        command: "rm -rf target",
+        action: "deny",
+        rule: "rm\\s+-rf",
+      },
+    ]);
+
+    const decisions = await listBashDecisions("ws-3", 20);
+
+    expect(decisions[0].action).toBe("deny");
+    expect(invokeMock).toHaveBeenCalledWith("list_bash_decisions", {
+      workspaceId: "ws-3",
+      limit: 20,
+    });
+  });
+
+  it("polls the activity feed after a cursor", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await getActivity({ since: 41, minSeverity: "warning" });
+
+    expect(invokeMock).toHaveBeenCalledWith("get_activity", {
+      since: 41,
+      workspaceId: null,
+      minSeverity: "warning",
+      limit: null,
+    });
+  });
+
+  it("opens a pull request from a turn", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({
+      url: "https://github.com/o/r/pull/7",
+      branch: "monitor/turn-1a2b3c4d",
+      title: "Fix the login redirect",
+      transcriptUrl: null,
+    });
+
+    await createPrFromTurn("ws-12", "thread-3", "turn-4", { draft: true });
+
+    expect(invokeMock).toHaveBeenCalledWith("create_pr_from_turn", {
+      workspaceId: "ws-12",
+      threadId: "thread-3",
+      turnId: "turn-4",
+      branch: null,
+      base: null,
+      draft: true,
+      shareTranscript: null,
+    });
+  });
+
+  it("starts a turn from an issue and posts back to it", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue({});
+
+    await startIssueTurn("acme/web#12", { template: "fix-issue" });
+    await postIssueComment("ws-12", "thread-3");
+
+    expect(invokeMock).toHaveBeenCalledWith("start_issue_turn", {
+      issue: "acme/web#12",
+      workspaceId: null,
+      template: "fix-issue",
+      model: null,
+      accessMode: null,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("post_issue_comment", {
+      workspaceId: "ws-12",
+      threadId: "thread-3",
+      body: null,
+    });
+  });
+
+  it("interrupts a terminal by default", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue(undefined);
+
+    await killTerminalSession("ws-12", "term-1");
+    await killTerminalSession("ws-12", "term-1", "kill");
+
+    expect(invokeMock).toHaveBeenCalledWith("terminal_kill", {
+      workspaceId: "ws-12",
+      terminalId: "term-1",
+      signal: undefined,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("terminal_kill", {
+      workspaceId: "ws-12",
+      terminalId: "term-1",
+      signal: "kill",
+    });
+  });
+
+  it("exports a session to a chosen path", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ path: "/tmp/session.md", bytes: 120 });
+
+    await exportSession("ws-12", "session-9", "markdown", "/tmp/session.md");
+
+    expect(invokeMock).toHaveBeenCalledWith("export_session", {
+      workspaceId: "ws-12",
+      sessionId: "session-9",
+      format: "markdown",
+      path: "/tmp/session.md",
+      operationId: null,
+    });
+  });
+
+  it("exports usage for a month as CSV", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ path: "/tmp/usage.csv", bytes: 512 });
+
+    await exportUsage("csv", { since: 1000, until: 2000, path: "/tmp/usage.csv" });
+
+    expect(invokeMock).toHaveBeenCalledWith("export_usage", {
+      since: 1000,
+      until: 2000,
+      format: "csv",
+      path: "/tmp/usage.csv",
+    });
+  });
+
+  it("searches transcripts with filters", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await searchTranscripts("race condition", {
+      workspaceIds: ["ws-12"],
+      roles: ["user"],
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("search_transcripts", {
+      query: "race condition",
+      workspaceIds: ["ws-12"],
+      since: null,
+      until: null,
+      roles: ["user"],
+      limit: null,
+      operationId: null,
+    });
+  });
+
+  it("cancels a search started with an operation id", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+    invokeMock.mockResolvedValueOnce(true);
+
+    await searchTranscripts("flaky test", {}, "op-1");
+    await cancelOperation("op-1");
+
+    expect(invokeMock).toHaveBeenCalledWith(
+      "search_transcripts",
+      expect.objectContaining({ query: "flaky test", operationId: "op-1" }),
+    );
+    expect(invokeMock).toHaveBeenCalledWith("cancel_operation", {
+      operationId: "op-1",
+    });
+  });
+
+  it("reads event batching stats", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ deltaBatchMs: 30, deltasMerged: 12 });
+
+    await expect(getEventStats()).resolves.toMatchObject({ deltasMerged: 12 });
+
+    expect(invokeMock).toHaveBeenCalledWith("get_event_stats");
+  });
+
+  it("annotates messages and searches annotations", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce(null);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await annotate("ws-12", "thread-1", "message", "msg-3", { rating: 4 });
+    await searchAnnotations("flaky", { bookmarked: true });
+
+    expect(invokeMock).toHaveBeenCalledWith("annotate", {
+      workspaceId: "ws-12",
+      threadId: "thread-1",
+      target: "message",
+      targetId: "msg-3",
+      note: null,
+      rating: 4,
+      bookmark: null,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("search_annotations", {
+      query: "flaky",
+      workspaceIds: null,
+      minRating: null,
+      bookmarked: true,
+      limit: null,
+    });
+  });
+
+  it("overrides the global budget when no workspace is given", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await overrideBudget();
+
+    expect(invokeMock).toHaveBeenCalledWith("override_budget", {
+      workspaceId: null,
+    });
+  });
+
+  it("replays a recording at real time by default", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ threadId: "replay-1" });
+
+    await replaySession("ws-12", "/tmp/thread-1-1700000000000.jsonl");
+
+    expect(invokeMock).toHaveBeenCalledWith("replay_session", {
+      workspaceId: "ws-12",
+      path: "/tmp/thread-1-1700000000000.jsonl",
+      speed: null,
+    });
+  });
+
+  it("kills an orphaned session by pid", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ killed: true });
+
+    await killOrphanedSession("ws-13", 4242);
+
+    expect(invokeMock).toHaveBeenCalledWith("kill_orphaned_session", {
+      workspaceId: "ws-13",
+      pid: 4242,
+    });
+  });
+
+  it("reveals a redacted value by its placeholder id", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ secret: "hunter22" });
+
+    await expect(revealRedactedSecret("3f9a0c1d2b7e4a55")).resolves.toEqual({
+      secret: "hunter22",
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("reveal_redacted_secret", {
+      id: "3f9a0c1d2b7e4a55",
+    });
+  });
+
+  it("turns on database encryption", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ enabled: true });
+
+    await expect(setStorageEncryption(true)).resolves.toEqual({ enabled: true });
+
+    expect(invokeMock).toHaveBeenCalledWith("set_storage_encryption", {
+      enabled: true,
+    });
+  });
+
+  it("stores workspace credentials and reads back only the provider", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ provider: "bedrock" });
+    const credentials = {
+      provider: "bedrock" as const,
+      accessKeyId: "AKIAEXAMPLE",
+      secretAccessKey: "secret",
+      region: "us-east-1",
+    };
+
+    await expect(setWorkspaceCredentials("ws-14", credentials)).resolves.toEqual({
+      provider: "bedrock",
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("set_workspace_credentials", {
+      workspaceId: "ws-14",
+      credentials,
+    });
+  });
+
+  it("checks a workspace's provider connectivity", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({
+      ok: true,
+      provider: "bedrock",
+      endpoint: "https://bedrock-runtime.us-west-2.amazonaws.com",
+      status: 403,
+      latencyMs: 120,
+      error: null,
+      hasCredentials: false,
+    });
+
+    const result = await checkProviderConnectivity("ws-15");
+
+    expect(result.provider).toBe("bedrock");
+    expect(invokeMock).toHaveBeenCalledWith("check_provider_connectivity", {
+      workspaceId: "ws-15",
+    });
+  });
+
+  it("tests connectivity through the app's proxy settings", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({
+      ok: false,
+      url: "https://api.anthropic.com",
+      proxy: "http://proxy.corp:3128",
+      status: null,
+      latencyMs: 30,
+      error: "error sending request: tunnel error",
+    });
+
+    const result = await testProxyConnectivity();
+
+    expect(result.proxy).toBe("http://proxy.corp:3128");
+    expect(invokeMock).toHaveBeenCalledWith("test_proxy_connectivity", {
+      workspaceId: null,
+      url: null,
+    });
+  });
+
+  it("checks for Claude CLI updates and starts one", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({
+      installations: [],
+      latestVersion: "1.0.60",
+      error: null,
+    });
+    invokeMock.mockResolvedValueOnce({ updateId: "update-1", source: "npm" });
+
+    const check = await checkClaudeCliUpdates();
+    const started = await updateClaudeCli("/usr/local/bin/claude");
+
+    expect(check.latestVersion).toBe("1.0.60");
+    expect(started.updateId).toBe("update-1");
+    expect(invokeMock).toHaveBeenCalledWith("check_claude_cli_updates");
+    expect(invokeMock).toHaveBeenCalledWith("update_claude_cli", {
+      path: "/usr/local/bin/claude",
+    });
+  });
+
+  it("runs the doctor for a workspace", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({
+      ok: false,
+      workspaceId: "ws-16",
+      checks: [
+        {
+          id: "git",
+          label: "Git",
+          status: "fail",
+          detail: "git: No such file or directory",
+          fix: "Install git.",
+        },
+      ],
+    });
+
+    const report = await runDoctor("ws-16");
+
+    expect(report.checks[0].status).toBe("fail");
+    expect(invokeMock).toHaveBeenCalledWith("run_doctor", {
+      workspaceId: "ws-16",
+    });
+  });
+
+  it("reads the rate limit status of a workspace", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ coolingDown: false, consecutive: 0 });
+
+    await getRateLimitStatus("ws-12");
+
+    expect(invokeMock).toHaveBeenCalledWith("get_rate_limit_status", {
+      workspaceId: "ws-12",
+    });
+  });
+
+  it("saves prompt templates with a null description by default", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+
+    await savePromptTemplate("ws-13", "global", {
+      name: "review",
+      body: "Review PR #{pr}",
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("prompt_template_save", {
+      workspaceId: "ws-13",
+      scope: "global",
+      name: "review",
+      description: null,
+      body: "Review PR #{pr}",
+    });
+  });
+
+  it("saves a cron schedule with nullable fields defaulted", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ id: "sched-1" });
+
+    await saveSchedule("ws-14", {
+      name: "Nightly TODOs",
+      prompt: "Summarize open TODOs",
+      schedule: { kind: "cron", expression: "0 2 * * *" },
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("schedule_save", {
+      workspaceId: "ws-14",
+      id: null,
+      name: "Nightly TODOs",
+      prompt: "Summarize open TODOs",
+      template: null,
+      values: null,
+      schedule: { kind: "cron", expression: "0 2 * * *" },
+      enabled: null,
+      model: null,
+      permissionMode: null,
+    });
+  });
+
+  it("starts a batch with default options", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ id: "batch-1" });
+
+    await runBatch("Bump serde to 1.0.210", ["ws-1", "ws-2"]);
+
+    expect(invokeMock).toHaveBeenCalledWith("batch_run", {
+      prompt: "Bump serde to 1.0.210",
+      workspaceIds: ["ws-1", "ws-2"],
+      concurrency: null,
+      model: null,
+      permissionMode: null,
+    });
+  });
+
+  it("creates a pipeline with step models defaulted", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ id: "pipe-1" });
+
+    await createPipeline("Plan then build", [
+      { workspaceId: "planner", prompt: "Plan the retry feature." },
+      {
+        workspaceId: "builder",
+        prompt: "Implement this plan:\n{previous}",
+        model: "opus",
+      },
+    ]);
+
+    expect(invokeMock).toHaveBeenCalledWith("create_pipeline", {
+      name: "Plan then build",
+      steps: [
+        { workspaceId: "planner", prompt: "Plan the retry feature.", model: null },
+        {
+          workspaceId: "builder",
+          prompt: "Implement this plan:\n{previous}",
+          model: "opus",
+        },
+      ],
+      permissionMode: null,
+    });
+  });
+
+  it("issues, revokes and audits API tokens", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue({});
+
+    await issueApiToken("portal", "observer");
+    await revokeApiToken("tok-1");
+    await listApiAuditLog("tok-1");
+
+    expect(invokeMock).toHaveBeenCalledWith("issue_api_token", {
+      name: "portal",
+      role: "observer",
+      expiresInDays: null,
+    });
+    expect(invokeMock).toHaveBeenCalledWith("revoke_api_token", { id: "tok-1" });
+    expect(invokeMock).toHaveBeenCalledWith("list_api_audit_log", {
+      tokenId: "tok-1",
+      limit: null,
+    });
+  });
+
+  it("lists thread owners and takes a thread over", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue([]);
+
+    await listThreadOwners("ws-1");
+    await takeOverThread("ws-1", "thread-1");
+
+    expect(invokeMock).toHaveBeenCalledWith("list_thread_owners", {
+      workspaceId: "ws-1",
+    });
+    expect(invokeMock).toHaveBeenCalledWith("take_over_thread", {
+      workspaceId: "ws-1",
+      threadId: "thread-1",
+    });
+  });
+
+  it("lists persisted thread sessions for a workspace", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await listThreadSessions("ws-4");
+
+    expect(invokeMock).toHaveBeenCalledWith("list_thread_sessions", {
+      workspaceId: "ws-4",
+    });
+  });
+
+  it("passes worktree base refs and archive cleanup flags", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+    invokeMock.mockResolvedValueOnce({});
+    invokeMock.mockResolvedValueOnce({ ok: true });
+
+    await addWorktree("ws-1", "feature/x");
+    await addWorktree("ws-1", "feature/y", "origin/main");
+    await archiveThread("ws-2", "thread-1", true);
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "add_worktree", {
+      parentId: "ws-1",
+      branch: "feature/x",
+      baseRef: null,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "add_worktree", {
+      parentId: "ws-1",
+      branch: "feature/y",
+      baseRef: "origin/main",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(3, "archive_thread", {
+      workspaceId: "ws-2",
+      threadId: "thread-1",
+      removeWorktree: true,
+    });
+  });
+
+  it("sends permission decisions with the request id", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce(undefined);
+
+    await respondToPermissionRequest("ws-5", "thread-5", "req-5", {
+      behavior: "deny",
+      interrupt: true,
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("respond_to_permission_request", {
+      workspaceId: "ws-5",
+      threadId: "thread-5",
+      requestId: "req-5",
+      decision: { behavior: "deny", interrupt: true },
+    });
+  });
+
+  it("switches a workspace's permission preset", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({
+      preset: "plan",
+      switchedThreads: ["thread-5"],
+      stoppedThreads: [],
+    });
+
+    await setPermissionPreset("ws-5", "plan");
+
+    expect(invokeMock).toHaveBeenCalledWith("set_permission_preset", {
+      workspaceId: "ws-5",
+      preset: "plan",
+    });
+  });
+
+  it("passes optional discovery roots as null", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce([]);
+
+    await discoverWorkspaces();
+    await discoverWorkspaces(["~/code"], 2);
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "discover_workspaces", {
+      roots: null,
+      maxDepth: null,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "discover_workspaces", {
+      roots: ["~/code"],
+      maxDepth: 2,
+    });
+  });
+
   it("maps workspace_id to workspaceId for GitHub issues", async () => {
     const invokeMock = vi.mocked(invoke);
     invokeMock.mockResolvedValueOnce({ total: 0, issues: [] });
@@ -109,10 +1367,63 @@ describe("tauri invoke wrappers", () => {
       effort: null,
       accessMode: "full-access",
       images: ["image.png"],
+      attachments: null,
+      contextPack: null,
       collaborationMode: null,
     });
   });
 
+  it("passes file and pasted attachments to sendUserMessage", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+    const attachments = [
+      { kind: "file" as const, path: "src/main.rs", embed: true },
+      { kind: "data" as const, dataUrl: "data:image/png;base64,iVBORw0KGgo=" },
+    ];
+
+    await sendUserMessage("ws-4", "thread-1", "look", { attachments });
+
+    expect(invokeMock).toHaveBeenCalledWith(
+      "send_user_message",
+      expect.objectContaining({ images: null, attachments }),
+    );
+  });
+
+  it("saves context packs with default budget and truncation", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+    const items = [{ kind: "file" as const, path: "src/lib.rs" }];
+
+    await saveContextPack("ws-4", { name: "core", items });
+
+    expect(invokeMock).toHaveBeenCalledWith("context_pack_save", {
+      workspaceId: "ws-4",
+      name: "core",
+      items,
+      maxTokens: null,
+      truncation: null,
+    });
+  });
+
+  it("estimates drafted messages for the selected models", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ totalTokens: 0, models: [] });
+
+    await estimateTokens("ws-4", "draft", {
+      contextPack: "core",
+      models: ["claude-sonnet-4-5-20250929"],
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("estimate_tokens", {
+      workspaceId: "ws-4",
+      text: "draft",
+      images: null,
+      attachments: null,
+      contextPack: "core",
+      models: ["claude-sonnet-4-5-20250929"],
+    });
+  });
+
   it("omits delivery when starting reviews without override", async () => {
     const invokeMock = vi.mocked(invoke);
     invokeMock.mockResolvedValueOnce({});
@@ -166,4 +1477,16 @@ describe("tauri invoke wrappers", () => {
       },
     });
   });
+
+  it("sends null to clear the workspace model", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ id: "ws-9" });
+
+    await updateWorkspaceModel("ws-9", null);
+
+    expect(invokeMock).toHaveBeenCalledWith("update_workspace_model", {
+      id: "ws-9",
+      model: null,
+    });
+  });
 });
@@ -1,15 +1,115 @@
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
 import type {
+  ActivityEntry,
+  ActivitySeverity,
+  Annotation,
+  AnnotationFields,
+  AnnotationSearchFilters,
+  AnnotationTarget,
+  ApiAuditEntry,
+  ApiToken,
+  ApiTokenRole,
   AppSettings,
+  BashDecision,
+  BatchRun,
+  BudgetStatus,
   ClaudeDoctorResult,
+  ClaudeMdFile,
+  ClaudeScopeSettings,
+  ClaudeSettingsScope,
+  ClaudeSettingsSnapshot,
+  ClaudeSettingsUpdate,
+  CliInstallation,
+  CliUpdateCheck,
+  CliUpdateStarted,
+  CleanupAction,
+  CleanupResult,
+  CompactionRecord,
+  ConfigBundle,
+  ConfigImportReport,
+  ContextItem,
+  ContextPack,
+  ContextTruncation,
+  CreatedPullRequest,
+  DoctorReport,
   ClaudeTasksResponse,
+  DailyUsage,
+  DiscoveredWorkspace,
+  DiskUsageReport,
+  EventStats,
+  ExpandedContextPack,
   DictationModelStatus,
   DictationSessionState,
+  ExternalSession,
+  HookEntry,
+  HookInput,
+  HookTestResult,
+  IssueLink,
+  IssuedApiToken,
   LocalUsageSnapshot,
+  McpServerConfig,
+  MessageAttachment,
+  OperationInfo,
+  OrphanedSession,
+  PathCompletion,
+  PendingMessage,
+  PendingInteraction,
+  PendingPlan,
+  PermissionDecision,
+  PermissionPreset,
+  PermissionPresetResult,
+  PipelineRun,
+  PipelineStepInput,
+  ProcessStats,
+  PromptTemplate,
+  PromptTemplateScope,
+  ProviderConnectivity,
+  ProxyConnectivity,
+  RateLimitStatus,
+  RetentionReport,
+  ScheduledPrompt,
+  ScheduleRun,
+  ScheduleSpec,
+  SessionExportFormat,
+  SessionExportResult,
+  SessionFork,
+  SessionHealth,
+  SessionLogLine,
+  SessionRecording,
+  SlashCommand,
+  StorageEncryptionStatus,
+  Subagent,
+  SubagentDraft,
+  SubagentScope,
+  SuspendOutcome,
+  ThreadMcpStatus,
+  ThreadOwner,
+  ThreadProcess,
+  TranscriptSearchFilters,
+  TranscriptSearchHit,
+  ThreadSessionRecord,
+  TokenEstimate,
+  TurnDiff,
+  TurnEdits,
+  TurnMetrics,
+  TurnTimeline,
+  TurnUsage,
+  UsageExportFormat,
+  UsageGroupBy,
+  UsageRange,
+  UsageReport,
+  WorkspaceAggregate,
+  WorkspaceAggregateBy,
+  WorkspaceCredentials,
+  WorkspaceCredentialsSummary,
+  WorkspaceFocusOutcome,
   WorkspaceInfo,
+  WorkspaceSessionSummary,
   WorkspaceSettings,
+  WorkspaceUsage,
 } from "../types";
+import type { QueuedPrompt } from "./events";
 import type {
   GitFileDiff,
   GitFileStatus,
@@ -46,8 +146,35 @@ export async function pickImageFiles(): Promise<string[]> {
   return Array.isArray(selection) ? selection : [selection];
 }
 
-export async function listWorkspaces(): Promise<WorkspaceInfo[]> {
-  return invoke<WorkspaceInfo[]>("list_workspaces");
+export async function listWorkspaces(
+  includeArchived = false,
+): Promise<WorkspaceInfo[]> {
+  return invoke<WorkspaceInfo[]>("list_workspaces", { includeArchived });
+}
+
+export async function archiveWorkspace(id: string): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("archive_workspace", { id });
+}
+
+export async function unarchiveWorkspace(id: string): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("unarchive_workspace", { id });
+}
+
+export async function setWorkspacePinned(
+  id: string,
+  pinned: boolean,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("set_workspace_pinned", { id, pinned });
+}
+
+export async function aggregateWorkspaces(
+  by: WorkspaceAggregateBy,
+  key?: string | null,
+): Promise<WorkspaceAggregate[]> {
+  return invoke<WorkspaceAggregate[]>("aggregate_workspaces", {
+    by,
+    key: key ?? null,
+  });
 }
 
 export async function addWorkspace(
@@ -61,6 +188,16 @@ export async function isWorkspacePathDir(path: string): Promise<boolean> {
   return invoke<boolean>("is_workspace_path_dir", { path });
 }
 
+export async function discoverWorkspaces(
+  roots?: string[],
+  maxDepth?: number,
+): Promise<DiscoveredWorkspace[]> {
+  return invoke<DiscoveredWorkspace[]>("discover_workspaces", {
+    roots: roots ?? null,
+    maxDepth: maxDepth ?? null,
+  });
+}
+
 export async function addClone(
   sourceWorkspaceId: string,
   copiesFolder: string,
@@ -76,8 +213,13 @@ export async function addClone(
 export async function addWorktree(
   parentId: string,
   branch: string,
+  baseRef?: string | null,
 ): Promise<WorkspaceInfo> {
-  return invoke<WorkspaceInfo>("add_worktree", { parentId, branch });
+  return invoke<WorkspaceInfo>("add_worktree", {
+    parentId,
+    branch,
+    baseRef: baseRef ?? null,
+  });
 }
 
 export async function updateWorkspaceSettings(
@@ -94,6 +236,13 @@ export async function updateWorkspaceClaudeBin(
   return invoke<WorkspaceInfo>("update_workspace_claude_bin", { id, claude_bin });
 }
 
+export async function updateWorkspaceModel(
+  id: string,
+  model: string | null,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("update_workspace_model", { id, model });
+}
+
 export async function removeWorkspace(id: string): Promise<void> {
   return invoke("remove_workspace", { id });
 }
@@ -142,6 +291,8 @@ export async function sendUserMessage(
     effort?: string | null;
     accessMode?: "read-only" | "current" | "full-access";
     images?: string[];
+    attachments?: MessageAttachment[];
+    contextPack?: string | null;
     collaborationMode?: Record<string, unknown> | null;
   },
 ) {
@@ -153,6 +304,8 @@ export async function sendUserMessage(
     effort: options?.effort ?? null,
     accessMode: options?.accessMode ?? null,
     images: options?.images ?? null,
+    attachments: options?.attachments ?? null,
+    contextPack: options?.contextPack ?? null,
     collaborationMode: options?.collaborationMode ?? null,
   });
 }
@@ -165,6 +318,50 @@ export async function interruptTurn(
   return invoke("turn_interrupt", { workspaceId, threadId, turnId });
 }
 
+export async function listThreadOwners(
+  workspaceId: string,
+): Promise<ThreadOwner[]> {
+  return invoke<ThreadOwner[]>("list_thread_owners", { workspaceId });
+}
+
+export async function takeOverThread(
+  workspaceId: string,
+  threadId: string,
+): Promise<ThreadOwner> {
+  return invoke<ThreadOwner>("take_over_thread", { workspaceId, threadId });
+}
+
+export async function listTurnQueue(workspaceId: string) {
+  return invoke<{ queue: QueuedPrompt[] }>("list_turn_queue", { workspaceId });
+}
+
+export async function cancelQueuedPrompt(workspaceId: string, promptId: string) {
+  return invoke("cancel_queued_prompt", { workspaceId, promptId });
+}
+
+export async function reorderQueuedPrompt(
+  workspaceId: string,
+  promptId: string,
+  position: number,
+) {
+  return invoke("reorder_queued_prompt", { workspaceId, promptId, position });
+}
+
+export async function listPendingMessages(workspaceId: string) {
+  return invoke<PendingMessage[]>("list_pending_messages", { workspaceId });
+}
+
+export async function cancelPendingMessage(
+  workspaceId: string,
+  messageId: string,
+) {
+  return invoke("cancel_pending_message", { workspaceId, messageId });
+}
+
+export async function flushPendingMessages(workspaceId: string) {
+  return invoke("flush_pending_messages", { workspaceId });
+}
+
 export async function startReview(
   workspaceId: string,
   threadId: string,
@@ -192,6 +389,64 @@ export async function respondToUserInputRequest(
   });
 }
 
+export async function respondToPermissionRequest(
+  workspaceId: string,
+  threadId: string,
+  requestId: string,
+  decision: PermissionDecision,
+) {
+  return invoke("respond_to_permission_request", {
+    workspaceId,
+    threadId,
+    requestId,
+    decision,
+  });
+}
+
+export async function listPendingInteractions(
+  workspaceId: string,
+): Promise<PendingInteraction[]> {
+  return invoke<PendingInteraction[]>("list_pending_interactions", {
+    workspaceId,
+  });
+}
+
+export async function listPendingPlans(
+  workspaceId: string,
+): Promise<PendingPlan[]> {
+  return invoke<PendingPlan[]>("list_pending_plans", { workspaceId });
+}
+
+export async function approvePlan(
+  workspaceId: string,
+  threadId: string,
+  mode?: "default" | "acceptEdits" | null,
+) {
+  return invoke("approve_plan", {
+    workspaceId,
+    threadId,
+    mode: mode ?? null,
+  });
+}
+
+export async function rejectPlan(
+  workspaceId: string,
+  threadId: string,
+  feedback: string,
+) {
+  return invoke("reject_plan", { workspaceId, threadId, feedback });
+}
+
+export async function setPermissionPreset(
+  workspaceId: string,
+  preset: PermissionPreset | null,
+) {
+  return invoke<PermissionPresetResult>("set_permission_preset", {
+    workspaceId,
+    preset,
+  });
+}
+
 export async function rememberApprovalRule(
   workspaceId: string,
   rule: string,
@@ -199,6 +454,108 @@ export async function rememberApprovalRule(
   return invoke("remember_approval_rule", { workspaceId, rule });
 }
 
+export async function getClaudeSettings(
+  workspaceId: string,
+): Promise<ClaudeSettingsSnapshot> {
+  return invoke<ClaudeSettingsSnapshot>("get_claude_settings", { workspaceId });
+}
+
+export async function updateClaudeSettings(
+  workspaceId: string,
+  scope: ClaudeSettingsScope,
+  update: ClaudeSettingsUpdate,
+): Promise<ClaudeScopeSettings> {
+  return invoke<ClaudeScopeSettings>("update_claude_settings", {
+    workspaceId,
+    scope,
+    update,
+  });
+}
+
+export async function listMcpServers(
+  workspaceId: string,
+): Promise<McpServerConfig[]> {
+  return invoke<McpServerConfig[]>("list_mcp_servers", { workspaceId });
+}
+
+export async function listClaudeMdFiles(
+  workspaceId: string,
+): Promise<ClaudeMdFile[]> {
+  return invoke<ClaudeMdFile[]>("list_claude_md_files", { workspaceId });
+}
+
+export async function readClaudeMd(
+  workspaceId: string,
+  path: string,
+): Promise<{ file: ClaudeMdFile; content: string }> {
+  return invoke("read_claude_md", { workspaceId, path });
+}
+
+export async function writeClaudeMd(
+  workspaceId: string,
+  path: string,
+  content: string,
+  expectedModifiedAt?: number | null,
+): Promise<ClaudeMdFile> {
+  return invoke<ClaudeMdFile>("write_claude_md", {
+    workspaceId,
+    path,
+    content,
+    expectedModifiedAt: expectedModifiedAt ?? null,
+  });
+}
+
+export async function listHooks(workspaceId: string): Promise<HookEntry[]> {
+  return invoke<HookEntry[]>("list_hooks", { workspaceId });
+}
+
+export async function addHook(
+  workspaceId: string,
+  scope: ClaudeSettingsScope,
+  hook: HookInput,
+): Promise<{ id: string }> {
+  return invoke("add_hook_definition", { workspaceId, scope, hook });
+}
+
+export async function updateHook(
+  workspaceId: string,
+  scope: ClaudeSettingsScope,
+  id: string,
+  hook: HookInput,
+): Promise<{ id: string }> {
+  return invoke("update_hook_definition", { workspaceId, scope, id, hook });
+}
+
+export async function removeHook(
+  workspaceId: string,
+  scope: ClaudeSettingsScope,
+  id: string,
+) {
+  return invoke("remove_hook_definition", { workspaceId, scope, id });
+}
+
+export async function testHook(
+  workspaceId: string,
+  hook: HookInput,
+  payload?: Record<string, unknown> | null,
+): Promise<HookTestResult> {
+  return invoke<HookTestResult>("test_hook", {
+    workspaceId,
+    hook,
+    payload: payload ?? null,
+  });
+}
+
+export async function getMcpStatus(
+  workspaceId: string,
+  threadId?: string | null,
+): Promise<ThreadMcpStatus[]> {
+  return invoke<ThreadMcpStatus[]>("get_mcp_status", {
+    workspaceId,
+    threadId: threadId ?? null,
+  });
+}
+
 export async function getGitStatus(workspace_id: string): Promise<{
   branchName: string;
   files: GitFileStatus[];
@@ -312,6 +669,62 @@ export async function getGitHubPullRequestComments(
   });
 }
 
+export async function createPrFromTurn(
+  workspaceId: string,
+  threadId: string,
+  turnId: string,
+  options: {
+    branch?: string | null;
+    base?: string | null;
+    draft?: boolean;
+    shareTranscript?: boolean;
+  } = {},
+): Promise<CreatedPullRequest> {
+  return invoke<CreatedPullRequest>("create_pr_from_turn", {
+    workspaceId,
+    threadId,
+    turnId,
+    branch: options.branch ?? null,
+    base: options.base ?? null,
+    draft: options.draft ?? null,
+    shareTranscript: options.shareTranscript ?? null,
+  });
+}
+
+export async function startIssueTurn(
+  issue: string,
+  options: {
+    workspaceId?: string | null;
+    template?: string | null;
+    model?: string | null;
+    accessMode?: string | null;
+  } = {},
+): Promise<IssueLink> {
+  return invoke<IssueLink>("start_issue_turn", {
+    issue,
+    workspaceId: options.workspaceId ?? null,
+    template: options.template ?? null,
+    model: options.model ?? null,
+    accessMode: options.accessMode ?? null,
+  });
+}
+
+export async function listIssueLinks(workspaceId: string): Promise<IssueLink[]> {
+  return invoke<IssueLink[]>("list_issue_links", { workspaceId });
+}
+
+export async function postIssueComment(
+  workspaceId: string,
+  threadId: string,
+  body?: string | null,
+): Promise<IssueLink> {
+  return invoke<IssueLink>("post_issue_comment", {
+    workspaceId,
+    threadId,
+    body: body ?? null,
+  });
+}
+
 export async function localUsageSnapshot(
   days?: number,
   workspacePath?: string | null,
@@ -323,136 +736,867 @@ export async function localUsageSnapshot(
   return invoke("local_usage_snapshot", payload);
 }
 
-export async function getModelList(workspaceId: string) {
-  return invoke<any>("model_list", { workspaceId });
-}
-
-export async function getCollaborationModes(workspaceId: string) {
-  return invoke<any>("collaboration_mode_list", { workspaceId });
+export async function getUsageTurns(options?: {
+  workspaceId?: string | null;
+  sessionId?: string | null;
+  limit?: number | null;
+}): Promise<TurnUsage[]> {
+  return invoke<TurnUsage[]>("usage_turns", {
+    workspaceId: options?.workspaceId ?? null,
+    sessionId: options?.sessionId ?? null,
+    limit: options?.limit ?? null,
+  });
 }
 
-export async function getGlobalRateLimits() {
-  return invoke<{ rateLimits: Record<string, unknown> | null }>(
-    "global_rate_limits",
-  );
+export async function getUsageDaily(
+  days?: number,
+  workspaceId?: string | null,
+): Promise<DailyUsage[]> {
+  return invoke<DailyUsage[]>("usage_daily", {
+    days: days ?? 30,
+    workspaceId: workspaceId ?? null,
+  });
 }
 
-export async function getSkillsList(workspaceId: string) {
-  return invoke<any>("skills_list", { workspaceId });
+export async function getUsageWorkspaces(
+  workspaceId?: string | null,
+): Promise<WorkspaceUsage[]> {
+  return invoke<WorkspaceUsage[]>("usage_workspaces", {
+    workspaceId: workspaceId ?? null,
+  });
 }
 
-export async function getPromptsList(workspaceId: string) {
-  return invoke<any>("prompts_list", { workspaceId });
+export async function getTurnMetrics(options?: {
+  workspaceId?: string | null;
+  limit?: number | null;
+}): Promise<TurnMetrics> {
+  return invoke<TurnMetrics>("get_turn_metrics", {
+    workspaceId: options?.workspaceId ?? null,
+    limit: options?.limit ?? null,
+  });
 }
 
-export async function getWorkspacePromptsDir(workspaceId: string) {
-  return invoke<string>("prompts_workspace_dir", { workspaceId });
+export async function getUsageReport(
+  range: UsageRange = {},
+  groupBy: UsageGroupBy | null = null,
+  workspaceId: string | null = null,
+): Promise<UsageReport> {
+  return invoke<UsageReport>("get_usage_report", {
+    range,
+    groupBy,
+    workspaceId,
+  });
 }
 
-export async function getGlobalPromptsDir() {
-  return invoke<string>("prompts_global_dir");
+export async function exportUsage(
+  format: UsageExportFormat,
+  options?: { since?: number | null; until?: number | null; path?: string | null },
+): Promise<SessionExportResult> {
+  return invoke<SessionExportResult>("export_usage", {
+    since: options?.since ?? null,
+    until: options?.until ?? null,
+    format,
+    path: options?.path ?? null,
+  });
 }
 
-export async function createPrompt(
+export async function getTurnTimeline(
   workspaceId: string,
-  data: {
-    scope: "workspace" | "global";
-    name: string;
-    description?: string | null;
-    argumentHint?: string | null;
-    content: string;
-  },
-) {
-  return invoke<any>("prompts_create", {
+  turnId: string,
+): Promise<TurnTimeline | null> {
+  return invoke<TurnTimeline | null>("get_turn_timeline", {
     workspaceId,
-    scope: data.scope,
-    name: data.name,
-    description: data.description ?? null,
-    argumentHint: data.argumentHint ?? null,
-    content: data.content,
+    turnId,
   });
 }
 
-export async function updatePrompt(
+export async function listCompactions(
   workspaceId: string,
-  data: {
-    path: string;
-    name: string;
-    description?: string | null;
-    argumentHint?: string | null;
-    content: string;
-  },
-) {
-  return invoke<any>("prompts_update", {
+  threadId?: string,
+): Promise<CompactionRecord[]> {
+  return invoke<CompactionRecord[]>("list_compactions", {
     workspaceId,
-    path: data.path,
-    name: data.name,
-    description: data.description ?? null,
-    argumentHint: data.argumentHint ?? null,
-    content: data.content,
+    threadId: threadId ?? null,
   });
 }
 
-export async function deletePrompt(workspaceId: string, path: string) {
-  return invoke<any>("prompts_delete", { workspaceId, path });
-}
-
-export async function movePrompt(
+export async function getSessionLogs(
   workspaceId: string,
-  data: { path: string; scope: "workspace" | "global" },
-) {
-  return invoke<any>("prompts_move", {
+  lines?: number,
+): Promise<SessionLogLine[]> {
+  return invoke<SessionLogLine[]>("get_session_logs", {
     workspaceId,
-    path: data.path,
-    scope: data.scope,
+    lines: lines ?? null,
   });
 }
 
-export async function getAppSettings(): Promise<AppSettings> {
-  return invoke<AppSettings>("get_app_settings");
+export async function getSessionResources(
+  workspaceId: string,
+): Promise<ProcessStats[]> {
+  return invoke<ProcessStats[]>("get_session_resources", { workspaceId });
 }
 
-export async function updateAppSettings(settings: AppSettings): Promise<AppSettings> {
-  return invoke<AppSettings>("update_app_settings", { settings });
+export async function getTurnDiff(
+  workspaceId: string,
+  turnId: string,
+): Promise<TurnDiff | null> {
+  return invoke<TurnDiff | null>("get_turn_diff", { workspaceId, turnId });
 }
 
-type MenuAcceleratorUpdate = {
-  id: string;
-  accelerator: string | null;
-};
-
-export async function setMenuAccelerators(
-  updates: MenuAcceleratorUpdate[],
-): Promise<void> {
-  return invoke("menu_set_accelerators", { updates });
+export async function getTurnEdits(
+  workspaceId: string,
+  turnId: string,
+): Promise<TurnEdits | null> {
+  return invoke<TurnEdits | null>("get_turn_edits", { workspaceId, turnId });
 }
 
-export async function runClaudeDoctor(
-  claudeBin: string | null,
-): Promise<ClaudeDoctorResult> {
-  return invoke<ClaudeDoctorResult>("claude_doctor", { claudeBin });
+export async function listBashDecisions(
+  workspaceId: string,
+  limit?: number | null,
+): Promise<BashDecision[]> {
+  return invoke<BashDecision[]>("list_bash_decisions", {
+    workspaceId,
+    limit: limit ?? null,
+  });
 }
 
-export async function getWorkspaceFiles(workspaceId: string) {
-  return invoke<string[]>("list_workspace_files", { workspaceId });
+export async function getActivity(options?: {
+  since?: number | null;
+  workspaceId?: string | null;
+  minSeverity?: ActivitySeverity | null;
+  limit?: number | null;
+}): Promise<ActivityEntry[]> {
+  return invoke<ActivityEntry[]>("get_activity", {
+    since: options?.since ?? null,
+    workspaceId: options?.workspaceId ?? null,
+    minSeverity: options?.minSeverity ?? null,
+    limit: options?.limit ?? null,
+  });
 }
 
-export async function readWorkspaceFile(
+export async function exportSession(
   workspaceId: string,
-  path: string,
-): Promise<{ content: string; truncated: boolean }> {
-  return invoke<{ content: string; truncated: boolean }>("read_workspace_file", {
+  sessionId: string,
+  format: SessionExportFormat,
+  path?: string | null,
+  operationId?: string | null,
+): Promise<SessionExportResult> {
+  return invoke<SessionExportResult>("export_session", {
     workspaceId,
-    path,
+    sessionId,
+    format,
+    path: path ?? null,
+    operationId: operationId ?? null,
   });
 }
 
-export async function listGitBranches(workspaceId: string) {
-  return invoke<any>("list_git_branches", { workspaceId });
+export async function searchTranscripts(
+  query: string,
+  filters: TranscriptSearchFilters = {},
+  operationId?: string | null,
+): Promise<TranscriptSearchHit[]> {
+  return invoke<TranscriptSearchHit[]>("search_transcripts", {
+    query,
+    workspaceIds: filters.workspaceIds ?? null,
+    since: filters.since ?? null,
+    until: filters.until ?? null,
+    roles: filters.roles ?? null,
+    limit: filters.limit ?? null,
+    operationId: operationId ?? null,
+  });
 }
 
-export async function checkoutGitBranch(workspaceId: string, name: string) {
-  return invoke("checkout_git_branch", { workspaceId, name });
+export async function cancelOperation(operationId: string): Promise<boolean> {
+  return invoke<boolean>("cancel_operation", { operationId });
+}
+
+export async function listOperations(): Promise<OperationInfo[]> {
+  return invoke<OperationInfo[]>("list_operations");
+}
+
+export async function getEventStats(): Promise<EventStats> {
+  return invoke<EventStats>("get_event_stats");
+}
+
+export async function annotate(
+  workspaceId: string,
+  threadId: string,
+  target: AnnotationTarget,
+  targetId: string,
+  fields: AnnotationFields,
+): Promise<Annotation | null> {
+  return invoke<Annotation | null>("annotate", {
+    workspaceId,
+    threadId,
+    target,
+    targetId,
+    note: fields.note ?? null,
+    rating: fields.rating ?? null,
+    bookmark: fields.bookmark ?? null,
+  });
+}
+
+export async function listAnnotations(
+  workspaceId: string,
+  threadId?: string | null,
+): Promise<Annotation[]> {
+  return invoke<Annotation[]>("list_annotations", {
+    workspaceId,
+    threadId: threadId ?? null,
+  });
+}
+
+export async function searchAnnotations(
+  query: string,
+  filters: AnnotationSearchFilters = {},
+): Promise<Annotation[]> {
+  return invoke<Annotation[]>("search_annotations", {
+    query,
+    workspaceIds: filters.workspaceIds ?? null,
+    minRating: filters.minRating ?? null,
+    bookmarked: filters.bookmarked ?? null,
+    limit: filters.limit ?? null,
+  });
+}
+
+export async function getBudgetStatus(
+  workspaceId?: string | null,
+): Promise<BudgetStatus[]> {
+  return invoke<BudgetStatus[]>("get_budget_status", {
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function overrideBudget(
+  workspaceId?: string | null,
+): Promise<BudgetStatus[]> {
+  return invoke<BudgetStatus[]>("override_budget", {
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function listRecordings(
+  workspaceId: string,
+): Promise<SessionRecording[]> {
+  return invoke<SessionRecording[]>("list_recordings", { workspaceId });
+}
+
+export async function replaySession(
+  workspaceId: string,
+  path: string,
+  speed?: number | null,
+): Promise<{ threadId: string }> {
+  return invoke<{ threadId: string }>("replay_session", {
+    workspaceId,
+    path,
+    speed: speed ?? null,
+  });
+}
+
+export async function listOrphanedSessions(): Promise<OrphanedSession[]> {
+  return invoke<OrphanedSession[]>("list_orphaned_sessions");
+}
+
+export async function adoptOrphanedSession(workspaceId: string, pid: number) {
+  return invoke("adopt_orphaned_session", { workspaceId, pid });
+}
+
+export async function killOrphanedSession(workspaceId: string, pid: number) {
+  return invoke<{ killed: boolean }>("kill_orphaned_session", { workspaceId, pid });
+}
+
+export async function revealRedactedSecret(id: string) {
+  return invoke<{ secret: string }>("reveal_redacted_secret", { id });
+}
+
+export async function getStorageEncryption(): Promise<StorageEncryptionStatus> {
+  return invoke<StorageEncryptionStatus>("get_storage_encryption");
+}
+
+export async function setStorageEncryption(
+  enabled: boolean,
+): Promise<StorageEncryptionStatus> {
+  return invoke<StorageEncryptionStatus>("set_storage_encryption", { enabled });
+}
+
+export async function getWorkspaceCredentials(
+  workspaceId: string,
+): Promise<WorkspaceCredentialsSummary> {
+  return invoke<WorkspaceCredentialsSummary>("get_workspace_credentials", {
+    workspaceId,
+  });
+}
+
+export async function setWorkspaceCredentials(
+  workspaceId: string,
+  credentials: WorkspaceCredentials | null,
+): Promise<WorkspaceCredentialsSummary> {
+  return invoke<WorkspaceCredentialsSummary>("set_workspace_credentials", {
+    workspaceId,
+    credentials,
+  });
+}
+
+export async function checkProviderConnectivity(
+  workspaceId: string,
+): Promise<ProviderConnectivity> {
+  return invoke<ProviderConnectivity>("check_provider_connectivity", {
+    workspaceId,
+  });
+}
+
+export async function testProxyConnectivity(
+  workspaceId?: string | null,
+  url?: string | null,
+): Promise<ProxyConnectivity> {
+  return invoke<ProxyConnectivity>("test_proxy_connectivity", {
+    workspaceId: workspaceId ?? null,
+    url: url ?? null,
+  });
+}
+
+export async function getRateLimitStatus(
+  workspaceId: string,
+): Promise<RateLimitStatus> {
+  return invoke<RateLimitStatus>("get_rate_limit_status", { workspaceId });
+}
+
+export async function listPromptTemplates(
+  workspaceId: string,
+): Promise<PromptTemplate[]> {
+  return invoke<PromptTemplate[]>("prompt_templates_list", { workspaceId });
+}
+
+export async function savePromptTemplate(
+  workspaceId: string,
+  scope: PromptTemplateScope,
+  template: { name: string; description?: string | null; body: string },
+): Promise<PromptTemplate> {
+  return invoke<PromptTemplate>("prompt_template_save", {
+    workspaceId,
+    scope,
+    name: template.name,
+    description: template.description ?? null,
+    body: template.body,
+  });
+}
+
+export async function deletePromptTemplate(
+  workspaceId: string,
+  scope: PromptTemplateScope,
+  name: string,
+) {
+  return invoke<{ deleted: boolean }>("prompt_template_delete", {
+    workspaceId,
+    scope,
+    name,
+  });
+}
+
+export async function renderPromptTemplate(
+  workspaceId: string,
+  name: string,
+  values: Record<string, string>,
+) {
+  return invoke<{ text: string }>("prompt_template_render", {
+    workspaceId,
+    name,
+    values,
+  });
+}
+
+export async function sendPromptTemplate(
+  workspaceId: string,
+  threadId: string,
+  name: string,
+  values: Record<string, string>,
+  options?: {
+    model?: string | null;
+    effort?: string | null;
+    accessMode?: "read-only" | "current" | "full-access";
+  },
+) {
+  return invoke("prompt_template_send", {
+    workspaceId,
+    threadId,
+    name,
+    values,
+    model: options?.model ?? null,
+    effort: options?.effort ?? null,
+    accessMode: options?.accessMode ?? null,
+  });
+}
+
+export async function listContextPacks(
+  workspaceId: string,
+): Promise<ContextPack[]> {
+  return invoke<ContextPack[]>("context_packs_list", { workspaceId });
+}
+
+export async function saveContextPack(
+  workspaceId: string,
+  pack: {
+    name: string;
+    items: ContextItem[];
+    maxTokens?: number | null;
+    truncation?: ContextTruncation | null;
+  },
+): Promise<ContextPack> {
+  return invoke<ContextPack>("context_pack_save", {
+    workspaceId,
+    name: pack.name,
+    items: pack.items,
+    maxTokens: pack.maxTokens ?? null,
+    truncation: pack.truncation ?? null,
+  });
+}
+
+export async function deleteContextPack(workspaceId: string, name: string) {
+  return invoke<{ deleted: boolean }>("context_pack_delete", {
+    workspaceId,
+    name,
+  });
+}
+
+export async function previewContextPack(
+  workspaceId: string,
+  name: string,
+): Promise<ExpandedContextPack> {
+  return invoke<ExpandedContextPack>("context_pack_preview", {
+    workspaceId,
+    name,
+  });
+}
+
+export async function estimateTokens(
+  workspaceId: string,
+  text: string,
+  options?: {
+    images?: string[];
+    attachments?: MessageAttachment[];
+    contextPack?: string | null;
+    models?: string[];
+  },
+): Promise<TokenEstimate> {
+  return invoke<TokenEstimate>("estimate_tokens", {
+    workspaceId,
+    text,
+    images: options?.images ?? null,
+    attachments: options?.attachments ?? null,
+    contextPack: options?.contextPack ?? null,
+    models: options?.models ?? null,
+  });
+}
+
+export async function listSlashCommands(
+  workspaceId: string,
+): Promise<SlashCommand[]> {
+  return invoke<SlashCommand[]>("list_slash_commands", { workspaceId });
+}
+
+export async function runSlashCommand(
+  workspaceId: string,
+  threadId: string,
+  name: string,
+  args?: string | null,
+  options?: {
+    model?: string | null;
+    accessMode?: "read-only" | "current" | "full-access";
+  },
+) {
+  return invoke("run_slash_command", {
+    workspaceId,
+    threadId,
+    name,
+    arguments: args ?? null,
+    model: options?.model ?? null,
+    accessMode: options?.accessMode ?? null,
+  });
+}
+
+export async function listSubagents(workspaceId: string): Promise<Subagent[]> {
+  return invoke<Subagent[]>("list_subagents", { workspaceId });
+}
+
+export async function saveSubagent(
+  workspaceId: string,
+  agent: SubagentDraft,
+): Promise<Subagent> {
+  return invoke<Subagent>("save_subagent", { workspaceId, agent });
+}
+
+export async function deleteSubagent(
+  workspaceId: string,
+  scope: SubagentScope,
+  name: string,
+) {
+  return invoke<{ deleted: boolean }>("delete_subagent", {
+    workspaceId,
+    scope,
+    name,
+  });
+}
+
+export async function runSubagent(
+  workspaceId: string,
+  threadId: string,
+  name: string,
+  task: string,
+  options?: {
+    model?: string | null;
+    accessMode?: "read-only" | "current" | "full-access";
+  },
+) {
+  return invoke("run_subagent", {
+    workspaceId,
+    threadId,
+    name,
+    task,
+    model: options?.model ?? null,
+    accessMode: options?.accessMode ?? null,
+  });
+}
+
+export async function listSchedules(
+  workspaceId?: string | null,
+): Promise<ScheduledPrompt[]> {
+  return invoke<ScheduledPrompt[]>("schedules_list", {
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function saveSchedule(
+  workspaceId: string,
+  schedule: {
+    id?: string | null;
+    name: string;
+    prompt?: string | null;
+    template?: string | null;
+    values?: Record<string, string> | null;
+    schedule: ScheduleSpec;
+    enabled?: boolean;
+    model?: string | null;
+    permissionMode?: string | null;
+  },
+): Promise<ScheduledPrompt> {
+  return invoke<ScheduledPrompt>("schedule_save", {
+    workspaceId,
+    id: schedule.id ?? null,
+    name: schedule.name,
+    prompt: schedule.prompt ?? null,
+    template: schedule.template ?? null,
+    values: schedule.values ?? null,
+    schedule: schedule.schedule,
+    enabled: schedule.enabled ?? null,
+    model: schedule.model ?? null,
+    permissionMode: schedule.permissionMode ?? null,
+  });
+}
+
+export async function deleteSchedule(scheduleId: string) {
+  return invoke<{ deleted: boolean }>("schedule_delete", { scheduleId });
+}
+
+export async function setScheduleEnabled(
+  scheduleId: string,
+  enabled: boolean,
+): Promise<ScheduledPrompt> {
+  return invoke<ScheduledPrompt>("schedule_set_enabled", {
+    scheduleId,
+    enabled,
+  });
+}
+
+export async function runScheduleNow(scheduleId: string) {
+  return invoke<{ started: boolean }>("schedule_run_now", { scheduleId });
+}
+
+export async function listScheduleRuns(
+  workspaceId: string,
+  scheduleId?: string | null,
+): Promise<ScheduleRun[]> {
+  return invoke<ScheduleRun[]>("schedule_runs_list", {
+    workspaceId,
+    scheduleId: scheduleId ?? null,
+  });
+}
+
+export async function runBatch(
+  prompt: string,
+  workspaceIds: string[],
+  options?: {
+    concurrency?: number | null;
+    model?: string | null;
+    permissionMode?: string | null;
+  },
+): Promise<BatchRun> {
+  return invoke<BatchRun>("batch_run", {
+    prompt,
+    workspaceIds,
+    concurrency: options?.concurrency ?? null,
+    model: options?.model ?? null,
+    permissionMode: options?.permissionMode ?? null,
+  });
+}
+
+export async function listBatches(): Promise<BatchRun[]> {
+  return invoke<BatchRun[]>("batches_list");
+}
+
+export async function cancelBatch(batchId: string): Promise<BatchRun> {
+  return invoke<BatchRun>("batch_cancel", { batchId });
+}
+
+export async function createPipeline(
+  name: string,
+  steps: PipelineStepInput[],
+  permissionMode?: string | null,
+): Promise<PipelineRun> {
+  return invoke<PipelineRun>("create_pipeline", {
+    name,
+    steps: steps.map((step) => ({
+      workspaceId: step.workspaceId,
+      prompt: step.prompt,
+      model: step.model ?? null,
+    })),
+    permissionMode: permissionMode ?? null,
+  });
+}
+
+export async function listPipelines(): Promise<PipelineRun[]> {
+  return invoke<PipelineRun[]>("list_pipelines");
+}
+
+export async function cancelPipeline(pipelineId: string): Promise<PipelineRun> {
+  return invoke<PipelineRun>("cancel_pipeline", { pipelineId });
+}
+
+export async function listApiTokens(): Promise<ApiToken[]> {
+  return invoke<ApiToken[]>("list_api_tokens");
+}
+
+export async function issueApiToken(
+  name: string,
+  role: ApiTokenRole,
+  expiresInDays?: number | null,
+): Promise<IssuedApiToken> {
+  return invoke<IssuedApiToken>("issue_api_token", {
+    name,
+    role,
+    expiresInDays: expiresInDays ?? null,
+  });
+}
+
+export async function revokeApiToken(id: string): Promise<ApiToken> {
+  return invoke<ApiToken>("revoke_api_token", { id });
+}
+
+export async function listApiAuditLog(
+  tokenId?: string | null,
+  limit?: number | null,
+): Promise<ApiAuditEntry[]> {
+  return invoke<ApiAuditEntry[]>("list_api_audit_log", {
+    tokenId: tokenId ?? null,
+    limit: limit ?? null,
+  });
+}
+
+export async function getModelList(workspaceId: string) {
+  return invoke<any>("model_list", { workspaceId });
+}
+
+export async function getCollaborationModes(workspaceId: string) {
+  return invoke<any>("collaboration_mode_list", { workspaceId });
+}
+
+export async function getGlobalRateLimits() {
+  return invoke<{ rateLimits: Record<string, unknown> | null }>(
+    "global_rate_limits",
+  );
+}
+
+export async function getSkillsList(workspaceId: string) {
+  return invoke<any>("skills_list", { workspaceId });
+}
+
+export async function getPromptsList(workspaceId: string) {
+  return invoke<any>("prompts_list", { workspaceId });
+}
+
+export async function getWorkspacePromptsDir(workspaceId: string) {
+  return invoke<string>("prompts_workspace_dir", { workspaceId });
+}
+
+export async function getGlobalPromptsDir() {
+  return invoke<string>("prompts_global_dir");
+}
+
+export async function createPrompt(
+  workspaceId: string,
+  data: {
+    scope: "workspace" | "global";
+    name: string;
+    description?: string | null;
+    argumentHint?: string | null;
+    content: string;
+  },
+) {
+  return invoke<any>("prompts_create", {
+    workspaceId,
+    scope: data.scope,
+    name: data.name,
+    description: data.description ?? null,
+    argumentHint: data.argumentHint ?? null,
+    content: data.content,
+  });
+}
+
+export async function updatePrompt(
+  workspaceId: string,
+  data: {
+    path: string;
+    name: string;
+    description?: string | null;
+    argumentHint?: string | null;
+    content: string;
+  },
+) {
+  return invoke<any>("prompts_update", {
+    workspaceId,
+    path: data.path,
+    name: data.name,
+    description: data.description ?? null,
+    argumentHint: data.argumentHint ?? null,
+    content: data.content,
+  });
+}
+
+export async function deletePrompt(workspaceId: string, path: string) {
+  return invoke<any>("prompts_delete", { workspaceId, path });
+}
+
+export async function movePrompt(
+  workspaceId: string,
+  data: { path: string; scope: "workspace" | "global" },
+) {
+  return invoke<any>("prompts_move", {
+    workspaceId,
+    path: data.path,
+    scope: data.scope,
+  });
+}
+
+export async function getAppSettings(): Promise<AppSettings> {
+  return invoke<AppSettings>("get_app_settings");
+}
+
+export async function updateAppSettings(settings: AppSettings): Promise<AppSettings> {
+  return invoke<AppSettings>("update_app_settings", { settings });
+}
+
+type MenuAcceleratorUpdate = {
+  id: string;
+  accelerator: string | null;
+};
+
+export async function setMenuAccelerators(
+  updates: MenuAcceleratorUpdate[],
+): Promise<void> {
+  return invoke("menu_set_accelerators", { updates });
+}
+
+export async function runClaudeDoctor(
+  claudeBin: string | null,
+): Promise<ClaudeDoctorResult> {
+  return invoke<ClaudeDoctorResult>("claude_doctor", { claudeBin });
+}
+
+export async function applyRetentionPolicy(): Promise<RetentionReport> {
+  return invoke<RetentionReport>("apply_retention_policy");
+}
+
+export async function getDiskUsage(
+  olderThanDays?: number,
+): Promise<DiskUsageReport> {
+  return invoke<DiskUsageReport>("get_disk_usage", {
+    olderThanDays: olderThanDays ?? null,
+  });
+}
+
+export async function runDiskCleanup(
+  action: CleanupAction,
+): Promise<CleanupResult> {
+  return invoke<CleanupResult>("run_disk_cleanup", { action });
+}
+
+export async function exportConfig(
+  path?: string | null,
+): Promise<ConfigBundle> {
+  return invoke<ConfigBundle>("export_config", { path: path ?? null });
+}
+
+export async function importConfig(
+  source: { bundle: ConfigBundle } | { path: string },
+): Promise<ConfigImportReport> {
+  return invoke<ConfigImportReport>("import_config", {
+    bundle: null,
+    path: null,
+    ...source,
+  });
+}
+
+export async function runDoctor(
+  workspaceId?: string | null,
+): Promise<DoctorReport> {
+  return invoke<DoctorReport>("run_doctor", {
+    workspaceId: workspaceId ?? null,
+  });
+}
+
+export async function listClaudeCliInstallations(): Promise<CliInstallation[]> {
+  return invoke<CliInstallation[]>("list_claude_cli_installations");
+}
+
+export async function checkClaudeCliUpdates(): Promise<CliUpdateCheck> {
+  return invoke<CliUpdateCheck>("check_claude_cli_updates");
+}
+
+export async function updateClaudeCli(path: string): Promise<CliUpdateStarted> {
+  return invoke<CliUpdateStarted>("update_claude_cli", { path });
+}
+
+export async function getWorkspaceFiles(workspaceId: string) {
+  return invoke<string[]>("list_workspace_files", { workspaceId });
+}
+
+export async function completePaths(
+  workspaceId: string,
+  prefix: string,
+  limit?: number,
+) {
+  return invoke<PathCompletion[]>("complete_paths", {
+    workspaceId,
+    prefix,
+    limit: limit ?? null,
+  });
+}
+
+export async function readWorkspaceFile(
+  workspaceId: string,
+  path: string,
+): Promise<{ content: string; truncated: boolean }> {
+  return invoke<{ content: string; truncated: boolean }>("read_workspace_file", {
+    workspaceId,
+    path,
+  });
+}
+
+export async function listGitBranches(workspaceId: string) {
+  return invoke<any>("list_git_branches", { workspaceId });
+}
+
+export async function checkoutGitBranch(workspaceId: string, name: string) {
+  return invoke("checkout_git_branch", { workspaceId, name });
 }
 
 export async function createGitBranch(workspaceId: string, name: string) {
@@ -539,6 +1683,14 @@ export async function resizeTerminalSession(
   return invoke("terminal_resize", { workspaceId, terminalId, cols, rows });
 }
 
+export async function killTerminalSession(
+  workspaceId: string,
+  terminalId: string,
+  signal?: "interrupt" | "terminate" | "kill",
+): Promise<void> {
+  return invoke("terminal_kill", { workspaceId, terminalId, signal });
+}
+
 export async function closeTerminalSession(
   workspaceId: string,
   terminalId: string,
@@ -554,12 +1706,126 @@ export async function listThreads(
   return invoke<any>("list_threads", { workspaceId, cursor, limit });
 }
 
+export async function listWorkspaceSessions(
+  workspaceId: string,
+): Promise<WorkspaceSessionSummary[]> {
+  return invoke<WorkspaceSessionSummary[]>("list_workspace_sessions", {
+    workspaceId,
+  });
+}
+
+export async function startTranscriptTail(
+  workspaceId: string,
+  threadId: string,
+  fromStart = false,
+): Promise<{ path: string; alreadyTailing: boolean }> {
+  return invoke("start_transcript_tail", { workspaceId, threadId, fromStart });
+}
+
+export async function stopTranscriptTail(workspaceId: string, threadId: string) {
+  return invoke("stop_transcript_tail", { workspaceId, threadId });
+}
+
+export async function listExternalSessions(): Promise<ExternalSession[]> {
+  return invoke<ExternalSession[]>("list_external_sessions");
+}
+
+export async function attachExternalSession(workspaceId: string, sessionId: string) {
+  return invoke("attach_external_session", { workspaceId, sessionId });
+}
+
+export async function detachExternalSession(workspaceId: string, sessionId: string) {
+  return invoke<{ detached: boolean }>("detach_external_session", {
+    workspaceId,
+    sessionId,
+  });
+}
+
+export async function getSessionHealth(
+  workspaceId: string,
+  threadId?: string | null,
+): Promise<SessionHealth[]> {
+  return invoke<SessionHealth[]>("session_health", {
+    workspaceId,
+    threadId: threadId ?? null,
+  });
+}
+
+export async function suspendWorkspaceSessions(
+  workspaceId: string,
+): Promise<SuspendOutcome> {
+  return invoke<SuspendOutcome>("suspend_workspace_sessions", { workspaceId });
+}
+
+export async function resumeWorkspaceSessions(
+  workspaceId: string,
+): Promise<SuspendOutcome> {
+  return invoke<SuspendOutcome>("resume_workspace_sessions", { workspaceId });
+}
+
+export async function focusWorkspace(workspaceId: string | null) {
+  return invoke<{ workspaces: WorkspaceFocusOutcome[] }>("focus_workspace", {
+    workspaceId,
+  });
+}
+
+export async function listWorkspaceThreads(workspaceId: string) {
+  return invoke<{ threads: ThreadProcess[] }>("list_workspace_threads", {
+    workspaceId,
+  });
+}
+
+export async function listThreadSessions(
+  workspaceId: string,
+): Promise<ThreadSessionRecord[]> {
+  return invoke<ThreadSessionRecord[]>("list_thread_sessions", { workspaceId });
+}
+
 export async function resumeThread(workspaceId: string, threadId: string) {
   return invoke<any>("resume_thread", { workspaceId, threadId });
 }
 
-export async function archiveThread(workspaceId: string, threadId: string) {
-  return invoke<any>("archive_thread", { workspaceId, threadId });
+export async function forkSession(
+  workspaceId: string,
+  sessionId: string,
+  messageIndex: number,
+): Promise<{ thread: any; fork: SessionFork }> {
+  return invoke<{ thread: any; fork: SessionFork }>("fork_session", {
+    workspaceId,
+    sessionId,
+    messageIndex,
+  });
+}
+
+export async function listSessionForks(workspaceId: string) {
+  return invoke<{ forks: SessionFork[] }>("list_session_forks", {
+    workspaceId,
+  });
+}
+
+export async function resumeSession(
+  workspaceId: string,
+  sessionId?: string | null,
+  options?: { model?: string | null; accessMode?: string | null },
+) {
+  return invoke<any>("resume_session", {
+    workspaceId,
+    sessionId: sessionId ?? null,
+    model: options?.model ?? null,
+    accessMode: options?.accessMode ?? null,
+  });
+}
+
+export async function archiveThread(
+  workspaceId: string,
+  threadId: string,
+  removeWorktree = false,
+) {
+  return invoke<any>("archive_thread", {
+    workspaceId,
+    threadId,
+    removeWorktree,
+  });
 }
 
 export async function getCommitMessagePrompt(
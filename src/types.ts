@@ -3,6 +3,98 @@ export type WorkspaceSettings = {
   sortOrder?: number | null;
   groupId?: string | null;
   gitRoot?: string | null;
+  interruptGraceMs?: number | null;
+  notificationsMuted?: boolean;
+  idleTimeoutMinutes?: number | null;
+  maxTurnMinutes?: number | null;
+  maxToolRunMinutes?: number | null;
+  interactionTimeouts?: InteractionTimeouts | null;
+  resourceLimits?: ResourceLimits | null;
+  retryPolicy?: RetryPolicy | null;
+  autoCommit?: AutoCommitPolicy | null;
+  verification?: VerificationPolicy | null;
+  readOnly?: boolean;
+  pathRules?: PathRules | null;
+  bashPolicy?: BashPolicy | null;
+  autoCompact?: AutoCompactPolicy | null;
+  costBudget?: CostBudget | null;
+  agent?: AgentKind | null;
+  agentBin?: string | null;
+  recordSessions?: boolean;
+  cliFlags?: ClaudeCliFlags | null;
+  permissionPreset?: PermissionPreset | null;
+  provider?: ProviderSettings | null;
+  proxy?: ProxySettings | null;
+  tags?: string[];
+};
+
+export type AgentKind = "claude" | "codex" | "mock";
+
+export type CostBudget = {
+  dailyUsd?: number | null;
+  monthlyUsd?: number | null;
+  warnRatio?: number | null;
+};
+
+export type AutoCommitPolicy = {
+  enabled: boolean;
+  branch?: string | null;
+};
+
+export type PathRules = {
+  allow?: string[];
+  deny?: string[];
+};
+
+export type BashPolicy = {
+  allow?: string[];
+  deny?: string[];
+};
+
+export type BashAction = "allow" | "deny" | "ask";
+
+export type BashDecision = {
+  timestamp: number;
+  workspaceId: string;
+  threadId: string;
+  turnId: string;
+  toolUseId: string | null;
+  command: string;
+  action: BashAction;
+  rule: string | null;
+};
+
+export type VerificationPolicy = {
+  commands: string[];
+  feedBackFailures?: boolean;
+  maxFixAttempts?: number | null;
+  timeoutSeconds?: number | null;
+};
+
+export type ResourceLimits = {
+  maxMemoryMb?: number | null;
+  autoRestart?: boolean;
+};
+
+export type InteractionTimeouts = {
+  staleAfterSeconds?: number | null;
+  autoAnswerAfterSeconds?: number | null;
+  autoAllowTools?: string[];
+  autoApprovePlans?: boolean;
+};
+
+export type AutoCompactPolicy = {
+  enabled: boolean;
+  thresholdPercent?: number;
+  instructions?: string | null;
+};
+
+export type RetryPolicy = {
+  enabled: boolean;
+  maxAttempts?: number;
+  baseDelayMs?: number;
+  maxDelayMs?: number;
+  jitter?: number;
 };
 
 export type WorkspaceGroup = {
@@ -18,18 +110,40 @@ export type WorktreeInfo = {
   branch: string;
 };
 
+export type WorkspaceAggregateBy = "tag" | "group";
+
+export type WorkspaceAggregate = {
+  key: string | null;
+  label: string;
+  workspaceIds: string[];
+  connected: number;
+  runningTurns: number;
+  queuedPrompts: number;
+  totals: UsageTotals;
+  lastActivityAt: number | null;
+};
+
 export type WorkspaceInfo = {
   id: string;
   name: string;
   path: string;
   connected: boolean;
   claude_bin?: string | null;
+  model?: string | null;
   kind?: WorkspaceKind;
   parentId?: string | null;
   worktree?: WorktreeInfo | null;
+  archivedAt?: number | null;
+  pinned?: boolean;
   settings: WorkspaceSettings;
 };
 
+export type DiscoveredWorkspace = {
+  entry: Omit<WorkspaceInfo, "connected">;
+  gitRepo: boolean;
+  claudeProject: boolean;
+};
+
 export type AppServerEvent = {
   workspace_id: string;
   message: Record<string, unknown>;
@@ -140,6 +254,121 @@ export type AppSettings = {
   composerListContinuation: boolean;
   composerCodeBlockCopyUseModifier: boolean;
   workspaceGroups: WorkspaceGroup[];
+  workspaceDiscoveryRoots: string[];
+  costBudget?: CostBudget | null;
+  webhooks?: WebhookConfig[];
+  shutdownDrainSeconds?: number | null;
+  deltaBatchMs?: number | null;
+  redaction?: RedactionSettings;
+  proxy?: ProxySettings | null;
+  retention?: RetentionPolicy;
+  logLevel?: string | null;
+};
+
+export type RedactionSettings = {
+  enabled: boolean;
+  patterns: string[];
+  keepOriginals: boolean;
+};
+
+export type RetentionPolicy = {
+  transcriptDays?: number | null;
+  usageDays?: number | null;
+};
+
+export type RetentionReport = {
+  transcriptsRemoved: number;
+  recordingsRemoved: number;
+  bytesFreed: number;
+  historyRowsRemoved: number;
+  usageRecordsRemoved: number;
+};
+
+export type DirUsage = {
+  bytes: number;
+  files: number;
+};
+
+export type CleanupAction =
+  | { kind: "deleteOldSessions"; olderThanDays: number }
+  | { kind: "vacuumDatabase" };
+
+export type CleanupSuggestion = {
+  action: CleanupAction;
+  bytes: number;
+  files: number;
+};
+
+export type WorkspaceDiskUsage = {
+  workspaceId: string;
+  name: string;
+  transcripts: DirUsage;
+  todos: DirUsage;
+  recordings: DirUsage;
+  sessionLogs: DirUsage;
+  staleSessions: DirUsage;
+  totalBytes: number;
+};
+
+export type DiskUsageReport = {
+  claudeHome: {
+    path: string | null;
+    projects: DirUsage;
+    todos: DirUsage;
+    shellSnapshots: DirUsage;
+    other: DirUsage;
+    total: DirUsage;
+  };
+  app: {
+    path: string;
+    database: DirUsage;
+    databaseFreeBytes: number;
+    recordings: DirUsage;
+    sessionLogs: DirUsage;
+    logs: DirUsage;
+    other: DirUsage;
+    total: DirUsage;
+  };
+  workspaces: WorkspaceDiskUsage[];
+  unassignedProjects: DirUsage;
+  suggestions: CleanupSuggestion[];
+};
+
+export type CleanupResult = {
+  bytesFreed: number;
+  filesRemoved: number;
+};
+
+export type ConfigBundle = {
+  version: number;
+  exportedAt: number;
+  settings: AppSettings;
+  workspaces: Omit<WorkspaceInfo, "connected">[];
+  templates: PromptTemplate[];
+  workspaceTemplates: Record<string, PromptTemplate[]>;
+  schedules: ScheduledPrompt[];
+};
+
+export type ConfigImportReport = {
+  workspacesAdded: number;
+  workspacesUpdated: number;
+  workspacesSkipped: string[];
+  templatesImported: number;
+  schedulesImported: number;
+};
+
+export type WebhookEventName =
+  | "turn.started"
+  | "turn.completed"
+  | "turn.failed"
+  | "permission.pending"
+  | "plan.pending";
+
+export type WebhookConfig = {
+  url: string;
+  secret?: string | null;
+  events?: WebhookEventName[];
+  enabled?: boolean;
 };
 
 export type ClaudeDoctorResult = {
@@ -149,6 +378,46 @@ export type ClaudeDoctorResult = {
   path: string | null;
 };
 
+export type DoctorCheckStatus = "pass" | "warn" | "fail" | "skip";
+
+export type DoctorCheck = {
+  id: string;
+  label: string;
+  status: DoctorCheckStatus;
+  detail: string;
+  fix: string | null;
+};
+
+export type DoctorReport = {
+  ok: boolean;
+  workspaceId: string | null;
+  checks: DoctorCheck[];
+};
+
+export type CliInstallSource = "npm" | "bun" | "homebrew" | "local" | "other";
+
+export type CliInstallation = {
+  path: string;
+  resolvedPath: string;
+  source: CliInstallSource;
+  version: string | null;
+  error: string | null;
+  isDefault: boolean;
+  workspaceIds: string[];
+  updateAvailable: boolean;
+};
+
+export type CliUpdateCheck = {
+  installations: CliInstallation[];
+  latestVersion: string | null;
+  error: string | null;
+};
+
+export type CliUpdateStarted = {
+  updateId: string;
+  source: CliInstallSource;
+};
+
 export type PermissionDenial = {
   id: string;
   workspace_id: string;
@@ -159,6 +428,26 @@ export type PermissionDenial = {
   tool_input?: Record<string, unknown> | null;
 };
 
+/** A tool call the CLI is holding until the user allows or denies it. */
+export type PermissionRequest = {
+  id: string;
+  workspace_id: string;
+  thread_id: string;
+  turn_id: string;
+  tool_name: string;
+  tool_use_id?: string | null;
+  tool_input?: Record<string, unknown> | null;
+  can_remember: boolean;
+};
+
+export type PermissionDecision =
+  | {
+      behavior: "allow";
+      always?: boolean;
+      updatedInput?: Record<string, unknown>;
+    }
+  | { behavior: "deny"; message?: string; interrupt?: boolean };
+
 export type RequestUserInputOption = {
   label: string;
   description: string;
@@ -169,6 +458,7 @@ export type RequestUserInputQuestion = {
   header: string;
   question: string;
   options?: RequestUserInputOption[];
+  multiSelect?: boolean;
 };
 
 export type RequestUserInputParams = {
@@ -257,6 +547,33 @@ export type GitHubPullRequest = {
   author: GitHubUser | null;
 };
 
+export type IssueProvider = "github" | "gitlab";
+
+export type IssueRef = {
+  provider: IssueProvider;
+  host: string;
+  repo: string;
+  number: number;
+  url: string;
+};
+
+export type IssueLink = {
+  workspaceId: string;
+  threadId: string;
+  turnId: string | null;
+  issue: IssueRef;
+  createdAt: number;
+  commentedAt: number | null;
+  commentUrl: string | null;
+};
+
+export type CreatedPullRequest = {
+  url: string;
+  branch: string;
+  title: string;
+  transcriptUrl: string | null;
+};
+
 export type GitHubPullRequestsResponse = {
   total: number;
   pullRequests: GitHubPullRequest[];
@@ -290,6 +607,100 @@ export type ThreadTokenUsage = {
   modelContextWindow: number | null;
 };
 
+export type UsageGaugeReading = {
+  outputTokens: number;
+  tokensPerSecond: number;
+  contextTokens: number;
+  contextWindow: number;
+  contextPercent: number;
+  done: boolean;
+};
+
+export type TurnTimeout = {
+  threadId: string;
+  turnId: string;
+  kind: "turn" | "toolRun";
+  limitSeconds: number;
+  elapsedSeconds: number;
+  toolName: string | null;
+  toolUseId: string | null;
+};
+
+export type ProcessStats = {
+  threadId: string;
+  pid: number;
+  processCount: number;
+  cpuPercent: number;
+  memoryBytes: number;
+  sampledAt: number;
+};
+
+export type ResourceWarning = {
+  threadId: string;
+  pid: number;
+  memoryBytes: number;
+  limitBytes: number;
+  restarting: boolean;
+};
+
+export type PathCompletion = {
+  path: string;
+  isDir: boolean;
+};
+
+export type MessageAttachment =
+  | { kind: "file"; path: string; embed?: boolean }
+  | { kind: "data"; name?: string | null; dataUrl: string };
+
+export type PendingInteraction = {
+  key: string;
+  kind: "permission" | "question" | "plan";
+  threadId: string;
+  turnId: string;
+  requestId: string | null;
+  toolName: string;
+  createdAt: number;
+  stale: boolean;
+};
+
+export type PendingPlan = {
+  threadId: string;
+  turnId: string;
+  requestId: string;
+  toolUseId: string | null;
+  plan: string;
+  proposedAt: number;
+};
+
+export type PendingMessage = {
+  id: string;
+  workspaceId: string;
+  threadId: string;
+  prompt: string;
+  images?: { mediaType: string; data: string }[];
+  model: string | null;
+  accessMode: string | null;
+  queuedAt: number;
+  attempts: number;
+  lastAttemptAt: number | null;
+  error: string | null;
+};
+
+export type CompactionTrigger = "threshold" | "manual" | "cli";
+
+export type CompactionRecord = {
+  id: string;
+  workspaceId: string;
+  threadId: string;
+  trigger: CompactionTrigger;
+  startedAt: number;
+  finishedAt: number | null;
+  tokensBefore: number | null;
+  tokensAfter: number | null;
+  tokensReclaimed: number | null;
+  error: string | null;
+};
+
 export type LocalUsageDay = {
   day: string;
   inputTokens: number;
@@ -309,6 +720,827 @@ export type LocalUsageTotals = {
   peakDayTokens: number;
 };
 
+export type TurnUsage = {
+  workspaceId: string;
+  sessionId: string;
+  turnId: string;
+  timestamp: number;
+  model: string | null;
+  inputTokens: number;
+  outputTokens: number;
+  cacheCreationInputTokens: number;
+  cacheReadInputTokens: number;
+  costUsd: number;
+  durationMs: number | null;
+  timings?: TurnTimings | null;
+  isError?: boolean;
+};
+
+export type TurnTimings = {
+  wallMs: number;
+  timeToFirstTokenMs: number | null;
+  toolMs: number;
+  modelMs: number;
+};
+
+export type ToolCallStatus = "running" | "succeeded" | "failed" | "unfinished";
+
+export type ToolCallRecord = {
+  id: string;
+  name: string;
+  inputSummary: string;
+  startedAt: number;
+  finishedAt: number | null;
+  durationMs: number | null;
+  status: ToolCallStatus;
+  output: string | null;
+  outputTruncated: boolean;
+};
+
+export type TurnTimeline = {
+  workspaceId: string;
+  threadId: string;
+  turnId: string;
+  startedAt: number;
+  finishedAt: number;
+  toolCalls: ToolCallRecord[];
+};
+
+export type TurnDiff = {
+  workspaceId: string;
+  threadId: string;
+  turnId: string;
+  createdAt: number;
+  files: GitFileStatus[];
+  diff: string;
+  diffTruncated: boolean;
+};
+
+export type EditHunk = {
+  oldText: string;
+  newText: string;
+  replaceAll: boolean;
+  oldStart: number | null;
+  oldLines: number;
+  newStart: number | null;
+  newLines: number;
+};
+
+export type FileEdit = {
+  toolUseId: string;
+  path: string;
+  kind: "write" | "edit";
+  hunks: EditHunk[];
+};
+
+export type TurnEdits = {
+  workspaceId: string;
+  threadId: string;
+  turnId: string;
+  createdAt: number;
+  edits: FileEdit[];
+};
+
+export type SessionExportFormat = "markdown" | "html" | "json";
+
+export type SessionExportResult = {
+  path?: string;
+  bytes?: number;
+  content?: string;
+};
+
+export type TranscriptRole = "user" | "assistant" | "tool";
+
+export type TranscriptSearchFilters = {
+  workspaceIds?: string[] | null;
+  since?: number | null;
+  until?: number | null;
+  roles?: TranscriptRole[] | null;
+  limit?: number | null;
+};
+
+export type TranscriptSearchHit = {
+  workspaceId: string;
+  sessionId: string;
+  role: TranscriptRole;
+  kind: "text" | "tool_input" | "tool_result";
+  timestamp: number;
+  snippet: { text: string; highlighted: boolean }[];
+};
+
+export type AnnotationTarget = "turn" | "message";
+
+export type Annotation = {
+  workspaceId: string;
+  threadId: string;
+  target: AnnotationTarget;
+  targetId: string;
+  note: string | null;
+  rating: number | null;
+  bookmark: string | null;
+  createdAt: number;
+  updatedAt: number;
+};
+
+export type AnnotationFields = {
+  note?: string | null;
+  rating?: number | null;
+  bookmark?: string | null;
+};
+
+export type AnnotationSearchFilters = {
+  workspaceIds?: string[] | null;
+  minRating?: number | null;
+  bookmarked?: boolean | null;
+  limit?: number | null;
+};
+
+export type BudgetStatus = {
+  workspaceId: string | null;
+  period: "daily" | "monthly";
+  limitUsd: number;
+  warnUsd: number;
+  spentUsd: number;
+  level: "ok" | "warning" | "exceeded";
+  resetsAt: number;
+  overridden: boolean;
+};
+
+export type SessionLogLine = {
+  timestamp: number;
+  threadId: string;
+  stream: "stdout" | "stderr";
+  text: string;
+  truncated?: boolean;
+};
+
+export type SessionRecording = {
+  path: string;
+  threadId: string;
+  agent: AgentKind;
+  startedAt: number;
+  sizeBytes: number;
+};
+
+export type PermissionPreset = "plan" | "acceptEdits" | "askEverything";
+
+export type PermissionPresetResult = {
+  preset: PermissionPreset | null;
+  switchedThreads: string[];
+  stoppedThreads: string[];
+};
+
+export type ClaudeCliFlags = {
+  allowedTools?: string[];
+  disallowedTools?: string[];
+  permissionMode?: string | null;
+  maxTurns?: number | null;
+  addDirs?: string[];
+};
+
+export type ModelProvider = "anthropic" | "bedrock" | "vertex";
+
+export type ProviderSettings = {
+  provider: ModelProvider;
+  region?: string | null;
+  profile?: string | null;
+  projectId?: string | null;
+};
+
+export type ProviderConnectivity = {
+  ok: boolean;
+  provider: ModelProvider;
+  endpoint: string;
+  status: number | null;
+  latencyMs: number;
+  error: string | null;
+  hasCredentials: boolean;
+};
+
+export type ProxySettings = {
+  httpProxy?: string | null;
+  httpsProxy?: string | null;
+  noProxy?: string | null;
+  caCertsPath?: string | null;
+};
+
+export type ProxyConnectivity = {
+  ok: boolean;
+  url: string;
+  proxy: string | null;
+  status: number | null;
+  latencyMs: number;
+  error: string | null;
+};
+
+export type WorkspaceCredentials =
+  | { provider: "anthropic"; apiKey: string }
+  | {
+      provider: "bedrock";
+      accessKeyId: string;
+      secretAccessKey: string;
+      sessionToken?: string | null;
+      region: string;
+    }
+  | {
+      provider: "vertex";
+      projectId: string;
+      region: string;
+      credentialsFile?: string | null;
+    };
+
+export type WorkspaceCredentialsSummary = {
+  provider: ModelProvider | null;
+};
+
+export type StorageEncryptionStatus = {
+  enabled: boolean;
+};
+
+export type OrphanedSession = {
+  pid: number;
+  workspaceId: string;
+  threadId: string;
+  startedAt: number;
+  attached: boolean;
+};
+
+export type WorkspaceTurnStats = {
+  workspaceId: string;
+  turns: number;
+  avgWallMs: number;
+  p50WallMs: number;
+  p90WallMs: number;
+  maxWallMs: number;
+  avgTimeToFirstTokenMs: number | null;
+  avgToolMs: number;
+  avgModelMs: number;
+};
+
+export type TurnMetrics = {
+  turns: TurnUsage[];
+  workspaces: WorkspaceTurnStats[];
+};
+
+export type UsageTotals = {
+  turns: number;
+  inputTokens: number;
+  outputTokens: number;
+  cacheCreationInputTokens: number;
+  cacheReadInputTokens: number;
+  costUsd: number;
+};
+
+export type UsageInterval = "day" | "week";
+
+export type UsageGroupBy = "workspace" | "model";
+
+export type UsageRange = {
+  since?: number | null;
+  until?: number | null;
+  interval?: UsageInterval;
+};
+
+export type UsageRollup = UsageTotals & {
+  failedTurns: number;
+  successRate: number | null;
+  avgDurationMs: number | null;
+};
+
+export type UsageBucket = UsageRollup & {
+  start: string;
+};
+
+export type UsageSeries = {
+  key: string | null;
+  buckets: UsageBucket[];
+  totals: UsageRollup;
+};
+
+export type UsageReport = {
+  since: number;
+  until: number;
+  interval: UsageInterval;
+  groupBy: UsageGroupBy | null;
+  buckets: UsageBucket[];
+  series: UsageSeries[];
+  totals: UsageRollup;
+};
+
+export type UsageExportFormat = "csv" | "json";
+
+export type DailyUsage = {
+  day: string;
+  totals: UsageTotals;
+};
+
+export type WorkspaceUsage = {
+  workspaceId: string;
+  totals: UsageTotals;
+  sessions: { sessionId: string; totals: UsageTotals }[];
+};
+
+export type WorkspaceSessionSummary = {
+  sessionId: string;
+  path: string;
+  createdAt: number | null;
+  updatedAt: number;
+  summary: string | null;
+  firstMessage: string | null;
+  messageCount: number;
+  gitBranch: string | null;
+};
+
+export type SessionHealthStatus = "healthy" | "degraded" | "dead" | "suspended";
+
+export type SessionHealth = {
+  threadId: string;
+  status: SessionHealthStatus;
+  pid: number | null;
+  latencyMs: number | null;
+  detail: string | null;
+  checkedAt: number;
+};
+
+export type ThreadProcess = {
+  threadId: string;
+  pid: number | null;
+  alive: boolean;
+  running: boolean;
+  queued: number;
+  model: string | null;
+  permissionMode: string | null;
+  idleMs: number | null;
+  suspended: boolean;
+  usage: UsageTotals;
+};
+
+export type SuspendOutcome = {
+  suspended: string[];
+  skipped: string[];
+  resumed: string[];
+  failed: Record<string, string>;
+};
+
+export type WorkspaceFocusOutcome = SuspendOutcome & {
+  workspaceId: string;
+};
+
+export type SessionFork = {
+  workspaceId: string;
+  sessionId: string;
+  parentSessionId: string;
+  messageIndex: number;
+  keptMessages: number;
+  createdAt: number;
+};
+
+export type ClaudeSettingsScope = "user" | "project" | "local";
+
+export type ClaudePermissionSettings = {
+  allow: string[];
+  deny: string[];
+  ask: string[];
+  defaultMode: string | null;
+};
+
+export type ClaudeSettings = {
+  permissions: ClaudePermissionSettings;
+  env: Record<string, string>;
+  hooks: Record<string, unknown> | null;
+  model: string | null;
+};
+
+export type ClaudeScopeSettings = {
+  scope: ClaudeSettingsScope;
+  path: string;
+  exists: boolean;
+  settings: ClaudeSettings;
+};
+
+export type ClaudeSettingsSnapshot = {
+  effective: ClaudeSettings;
+  scopes: ClaudeScopeSettings[];
+};
+
+export type ClaudeSettingsUpdate =
+  | { key: "permissions"; value: ClaudePermissionSettings }
+  | { key: "env"; value: Record<string, string> }
+  | { key: "hooks"; value: Record<string, unknown> | null }
+  | { key: "model"; value: string | null };
+
+export type McpServerSource =
+  | "local"
+  | "mcpJson"
+  | "user"
+  | "localSettings"
+  | "projectSettings"
+  | "userSettings";
+
+export type McpServerConfig = {
+  name: string;
+  source: McpServerSource;
+  path: string;
+  transport: string;
+  command: string | null;
+  args: string[];
+  url: string | null;
+  approval: "approved" | "pending" | "disabled";
+};
+
+export type McpServerState = {
+  name: string;
+  configured: boolean;
+  status: string | null;
+};
+
+export type ThreadMcpStatus = {
+  threadId: string;
+  servers: McpServerState[];
+};
+
+export type HookEvent =
+  | "PreToolUse"
+  | "PostToolUse"
+  | "Notification"
+  | "UserPromptSubmit"
+  | "Stop"
+  | "SubagentStop"
+  | "PreCompact"
+  | "SessionStart"
+  | "SessionEnd";
+
+export type HookInput = {
+  event: HookEvent;
+  matcher?: string | null;
+  command: string;
+  timeout?: number | null;
+};
+
+export type HookEntry = {
+  id: string;
+  scope: ClaudeSettingsScope;
+  path: string;
+  event: HookEvent;
+  matcher: string | null;
+  command: string;
+  timeout: number | null;
+};
+
+export type HookTestResult = {
+  exitCode: number | null;
+  stdout: string;
+  stderr: string;
+  durationMs: number;
+  timedOut: boolean;
+  blocking: boolean;
+  output: unknown;
+  payload: Record<string, unknown>;
+};
+
+export type ClaudeMdKind =
+  | "user"
+  | "project"
+  | "local"
+  | "ancestor"
+  | "nested"
+  | "worktreeParent";
+
+export type ClaudeMdFile = {
+  path: string;
+  kind: ClaudeMdKind;
+  relativePath: string | null;
+  exists: boolean;
+  sizeBytes: number;
+  lineCount: number;
+  modifiedAt: number | null;
+};
+
+export type ExternalSession = {
+  pid: number;
+  workspaceId: string;
+  cwd: string;
+  sessionId: string | null;
+  transcriptPath: string | null;
+  lastActivityAt: number | null;
+  attached: boolean;
+};
+
+export type RateLimitKind =
+  | "rateLimited"
+  | "overloaded"
+  | "usageLimit"
+  | "quotaWarning";
+
+export type QuotaEstimate = {
+  requestsRemaining: number | null;
+  requestsLimit: number | null;
+  tokensRemaining: number | null;
+  tokensLimit: number | null;
+  utilization: number | null;
+  resetsAt: number | null;
+};
+
+export type RateLimitWarning = {
+  kind: RateLimitKind;
+  message: string;
+  status: number | null;
+  retryAfterMs: number | null;
+  resetsAt: number | null;
+  quota: QuotaEstimate;
+  attempt: number;
+  backoffMs: number;
+  cooldownUntil: number;
+};
+
+export type RateLimitStatus = {
+  coolingDown: boolean;
+  cooldownUntil: number | null;
+  consecutive: number;
+  last: Omit<RateLimitWarning, "attempt" | "backoffMs" | "cooldownUntil"> | null;
+  quota: QuotaEstimate;
+};
+
+export type PromptTemplateScope = "workspace" | "global";
+
+export type PromptTemplate = {
+  name: string;
+  description: string | null;
+  body: string;
+  updatedAt: number;
+  scope: PromptTemplateScope;
+  placeholders: string[];
+};
+
+export type ContextItem =
+  | {
+      kind: "file";
+      path: string;
+      startLine?: number | null;
+      endLine?: number | null;
+    }
+  | { kind: "snippet"; label?: string | null; text: string };
+
+export type ContextTruncation = "sequential" | "proportional";
+
+export type ContextPack = {
+  name: string;
+  items: ContextItem[];
+  maxTokens: number | null;
+  truncation: ContextTruncation;
+  updatedAt: number;
+};
+
+export type ExpandedContextPack = {
+  text: string;
+  tokens: number;
+  maxTokens: number;
+  items: {
+    label: string;
+    tokens: number;
+    sentTokens: number;
+    truncated: boolean;
+    omitted: boolean;
+  }[];
+};
+
+export type TokenEstimate = {
+  textTokens: number;
+  attachmentTokens: number;
+  contextPackTokens: number;
+  imageTokens: number;
+  totalTokens: number;
+  models: { model: string; inputCostUsd: number | null }[];
+};
+
+export type SlashCommandScope = "project" | "user";
+
+export type SlashCommand = {
+  name: string;
+  scope: SlashCommandScope;
+  namespace: string | null;
+  path: string;
+  description: string | null;
+  argumentHint: string | null;
+  allowedTools: string[];
+  model: string | null;
+  shadowed: boolean;
+};
+
+export type SubagentScope = "project" | "user";
+
+export type Subagent = {
+  name: string;
+  scope: SubagentScope;
+  path: string;
+  description: string | null;
+  tools: string[] | null;
+  model: string | null;
+  color: string | null;
+  prompt: string;
+  shadowed: boolean;
+};
+
+export type SubagentDraft = {
+  name: string;
+  scope: SubagentScope;
+  description: string;
+  tools?: string[] | null;
+  model?: string | null;
+  color?: string | null;
+  prompt: string;
+  previousName?: string | null;
+};
+
+export type ScheduleSpec =
+  | { kind: "interval"; minutes: number }
+  | { kind: "cron"; expression: string };
+
+export type ScheduleRunStatus = "succeeded" | "failed";
+
+export type ScheduledPrompt = {
+  id: string;
+  workspaceId: string;
+  name: string;
+  prompt: string | null;
+  template: string | null;
+  values: Record<string, string>;
+  schedule: ScheduleSpec;
+  enabled: boolean;
+  model: string | null;
+  permissionMode: string | null;
+  createdAt: number;
+  lastRunAt: number | null;
+  lastStatus: ScheduleRunStatus | null;
+  nextRunAt: number | null;
+};
+
+export type ScheduleRun = {
+  id: string;
+  scheduleId: string;
+  scheduleName: string;
+  trigger: "schedule" | "manual";
+  status: ScheduleRunStatus;
+  startedAt: number;
+  finishedAt: number;
+  output: string | null;
+  error: string | null;
+};
+
+export type BatchItemStatus =
+  | "pending"
+  | "running"
+  | "succeeded"
+  | "failed"
+  | "cancelled";
+
+export type BatchItem = {
+  workspaceId: string;
+  workspaceName: string;
+  status: BatchItemStatus;
+  startedAt: number | null;
+  finishedAt: number | null;
+  output: string | null;
+  error: string | null;
+  costUsd: number | null;
+  durationMs: number | null;
+  inputTokens: number | null;
+  outputTokens: number | null;
+};
+
+export type BatchRun = {
+  id: string;
+  prompt: string;
+  concurrency: number;
+  status: "running" | "completed" | "cancelled";
+  createdAt: number;
+  finishedAt: number | null;
+  total: number;
+  completed: number;
+  succeeded: number;
+  failed: number;
+  totalCostUsd: number;
+  items: BatchItem[];
+};
+
+export type PipelineStepInput = {
+  workspaceId: string;
+  prompt: string;
+  model?: string | null;
+};
+
+export type PipelineStepStatus =
+  | "pending"
+  | "running"
+  | "succeeded"
+  | "failed"
+  | "skipped"
+  | "cancelled";
+
+export type PipelineStep = {
+  workspaceId: string;
+  workspaceName: string;
+  prompt: string;
+  model: string | null;
+  status: PipelineStepStatus;
+  input: string | null;
+  output: string | null;
+  error: string | null;
+  costUsd: number | null;
+  startedAt: number | null;
+  finishedAt: number | null;
+};
+
+export type PipelineRun = {
+  id: string;
+  name: string;
+  status: "running" | "completed" | "failed" | "cancelled";
+  createdAt: number;
+  finishedAt: number | null;
+  currentStep: number | null;
+  totalCostUsd: number;
+  steps: PipelineStep[];
+};
+
+export type ApiTokenRole = "observer" | "operator";
+
+export type ApiToken = {
+  id: string;
+  name: string;
+  role: ApiTokenRole;
+  createdAt: number;
+  expiresAt: number | null;
+  lastUsedAt: number | null;
+  revokedAt: number | null;
+};
+
+export type IssuedApiToken = {
+  token: ApiToken;
+  secret: string;
+};
+
+export type ActivitySeverity = "info" | "warning" | "error";
+
+export type ActivityKind = "turn" | "tool" | "permission" | "error";
+
+export type OperationInfo = {
+  id: string;
+  kind: string;
+  workspaceId: string | null;
+  startedAt: number;
+};
+
+export type EventStats = {
+  deltaBatchMs: number;
+  eventsEmitted: number;
+  deltasReceived: number;
+  deltasMerged: number;
+  batchesMerged: number;
+  largestBatch: number;
+  pendingDeltas: number;
+  emitErrors: number;
+  eventsDropped: number;
+};
+
+export type ActivityEntry = {
+  seq: number;
+  timestamp: number;
+  workspaceId: string;
+  threadId: string | null;
+  turnId: string | null;
+  kind: ActivityKind;
+  severity: ActivitySeverity;
+  summary: string;
+  method: string;
+};
+
+export type ApiAuditEntry = {
+  timestamp: number;
+  tokenId: string | null;
+  tokenName: string | null;
+  surface: "rest" | "websocket";
+  action: string;
+  allowed: boolean;
+  reason: string | null;
+};
+
+export type ThreadSessionStatus = "running" | "idle" | "interrupted";
+
+export type ThreadOwner = {
+  threadId: string;
+  user: string;
+  since: number;
+};
+
+export type ThreadSessionRecord = {
+  workspaceId: string;
+  threadId: string;
+  sessionId: string;
+  model: string | null;
+  status: ThreadSessionStatus;
+  updatedAt: number;
+};
+
 export type LocalUsageModel = {
   model: string;
   tokens: number;
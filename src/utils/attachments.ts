@@ -0,0 +1,25 @@
+const imageExtensions = [
+  ".png",
+  ".jpg",
+  ".jpeg",
+  ".gif",
+  ".webp",
+  ".bmp",
+  ".tiff",
+  ".tif",
+];
+
+export function isImagePath(path: string) {
+  const lower = path.toLowerCase();
+  return imageExtensions.some((ext) => lower.endsWith(ext));
+}
+
+// Composer attachments are file paths, pasted data URLs or image URLs.
+export function isImageAttachment(path: string) {
+  return (
+    path.startsWith("data:") ||
+    path.startsWith("http://") ||
+    path.startsWith("https://") ||
+    isImagePath(path)
+  );
+}